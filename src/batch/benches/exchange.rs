@@ -0,0 +1,175 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod utils;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use futures::StreamExt;
+use prost::Message;
+use risingwave_batch::executor::BoxedExecutor;
+use risingwave_common::array::{DataChunk, Row};
+use risingwave_common::types::DataType;
+use risingwave_common::util::hash_util::Crc32FastBuilder;
+use risingwave_common::util::value_encoding::{deserialize_datum, serialize_datum_ref};
+use risingwave_pb::data::DataChunk as ProstDataChunk;
+use tikv_jemallocator::Jemalloc;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use utils::create_input;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const SIZE: usize = 64 * 1024;
+const CHUNK_SIZES: [usize; 4] = [32, 128, 512, 1024];
+const CHANNEL_CAPACITIES: [usize; 3] = [1, 16, 256];
+/// How many times the slow receiver hashes every chunk it receives.
+const SLOW_RECEIVER_WORK: usize = 4;
+
+/// How chunks are passed through the local exchange channel.
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    /// Pass the chunks as they are, which is the baseline without serialization.
+    InMemory,
+    /// Encode the chunks to protobuf, as the exchange between compute nodes does.
+    Columnar,
+    /// Encode every row of the chunks with value encoding.
+    Row,
+}
+
+enum ExchangeMessage {
+    InMemory(DataChunk),
+    Columnar(Vec<u8>),
+    Row(Vec<Vec<u8>>),
+}
+
+fn encode(chunk: DataChunk, format: Format) -> ExchangeMessage {
+    match format {
+        Format::InMemory => ExchangeMessage::InMemory(chunk),
+        Format::Columnar => ExchangeMessage::Columnar(chunk.to_protobuf().encode_to_vec()),
+        Format::Row => ExchangeMessage::Row(
+            chunk
+                .rows()
+                .map(|row| {
+                    let mut buf = vec![];
+                    for datum in row.values() {
+                        serialize_datum_ref(&datum, &mut buf);
+                    }
+                    buf
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn decode(message: ExchangeMessage, data_types: &[DataType]) -> DataChunk {
+    match message {
+        ExchangeMessage::InMemory(chunk) => chunk,
+        ExchangeMessage::Columnar(bytes) => {
+            DataChunk::from_protobuf(&ProstDataChunk::decode(bytes.as_slice()).unwrap()).unwrap()
+        }
+        ExchangeMessage::Row(rows) => {
+            let rows = rows
+                .into_iter()
+                .map(|bytes| {
+                    let mut buf = bytes.as_slice();
+                    Row(data_types
+                        .iter()
+                        .map(|data_type| deserialize_datum(&mut buf, data_type).unwrap())
+                        .collect())
+                })
+                .collect::<Vec<_>>();
+            DataChunk::from_rows(&rows, data_types)
+        }
+    }
+}
+
+/// Executes `input` on a sender task, which passes its output chunks to the receiver through a
+/// bounded channel of `capacity`, simulating a local exchange without network. The receiver hashes
+/// every chunk `receiver_work` times, so that a non-zero value makes it slower than the sender and
+/// the sender is blocked by backpressure once the channel is full.
+async fn exchange(input: BoxedExecutor, format: Format, capacity: usize, receiver_work: usize) {
+    let data_types = input.schema().data_types();
+    let column_indices = (0..data_types.len()).collect::<Vec<_>>();
+    let (tx, mut rx) = mpsc::channel(capacity);
+
+    let sender = tokio::spawn(async move {
+        let mut stream = input.execute();
+        while let Some(chunk) = stream.next().await {
+            if tx.send(encode(chunk.unwrap(), format)).await.is_err() {
+                break;
+            }
+        }
+    });
+    while let Some(message) = rx.recv().await {
+        let chunk = decode(message, &data_types);
+        for _ in 0..receiver_work {
+            _ = black_box(chunk.get_hash_values(&column_indices, Crc32FastBuilder {}));
+        }
+        _ = black_box(chunk);
+    }
+    sender.await.unwrap();
+}
+
+fn create_exchange_input(chunk_size: usize) -> BoxedExecutor {
+    create_input(
+        &[DataType::Int64, DataType::Float64, DataType::Varchar],
+        chunk_size,
+        SIZE / chunk_size,
+    )
+}
+
+fn bench_exchange(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    for format in [Format::InMemory, Format::Columnar, Format::Row] {
+        for chunk_size in CHUNK_SIZES {
+            c.bench_with_input(
+                BenchmarkId::new(
+                    "ExchangeExecutor/throughput",
+                    format!("{}({:?})", chunk_size, format),
+                ),
+                &chunk_size,
+                |b, &chunk_size| {
+                    b.to_async(&rt).iter_batched(
+                        || create_exchange_input(chunk_size),
+                        |input| exchange(input, format, 16, 0),
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
+    }
+
+    let chunk_size = 1024;
+    for capacity in CHANNEL_CAPACITIES {
+        c.bench_with_input(
+            BenchmarkId::new(
+                "ExchangeExecutor/slow_receiver",
+                format!("{}(capacity: {})", chunk_size, capacity),
+            ),
+            &capacity,
+            |b, &capacity| {
+                b.to_async(&rt).iter_batched(
+                    || create_exchange_input(chunk_size),
+                    |input| exchange(input, Format::Columnar, capacity, SLOW_RECEIVER_WORK),
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_exchange);
+criterion_main!(benches);
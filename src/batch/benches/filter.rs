@@ -100,6 +100,7 @@ fn create_filter_executor(chunk_size: usize, chunk_num: usize) -> BoxedExecutor
         input,
         "FilterBenchmark".to_string(),
         CHUNK_SIZE,
+        false,
     ))
 }
 
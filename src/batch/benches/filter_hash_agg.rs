@@ -0,0 +1,152 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+pub mod utils;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use risingwave_batch::executor::{BoxedExecutor, FilterExecutor, HashAggExecutor};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::hash;
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_common::util::value_encoding::serialize_datum_to_bytes;
+use risingwave_expr::expr::{build_from_prost, AggKind};
+use risingwave_expr::vector_op::agg::AggStateFactory;
+use risingwave_pb::data::Datum as ProstDatum;
+use risingwave_pb::expr::agg_call::Arg;
+use risingwave_pb::expr::expr_node::RexNode;
+use risingwave_pb::expr::expr_node::Type::{
+    ConstantValue as TConstValue, InputRef, LessThan, Modulus,
+};
+use risingwave_pb::expr::{AggCall, ExprNode, FunctionCall, InputRefExpr};
+use tikv_jemallocator::Jemalloc;
+use tokio::runtime::Runtime;
+use utils::{create_input, execute_executor};
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const CHUNK_SIZE: usize = 1024;
+
+fn int64_constant(value: i64) -> ExprNode {
+    ExprNode {
+        expr_type: TConstValue as i32,
+        return_type: Some(DataType::Int64.to_protobuf()),
+        rex_node: Some(RexNode::Constant(ProstDatum {
+            body: serialize_datum_to_bytes(Some(ScalarImpl::Int64(value)).as_ref()),
+        })),
+    }
+}
+
+/// Creates `HashAgg(group by $0, sum($1)) <- Filter($0 % 100 < selectivity)`.
+fn create_filter_hash_agg_executor(
+    selectivity: i64,
+    enable_selection_vector: bool,
+    chunk_num: usize,
+) -> BoxedExecutor {
+    let input = create_input(&[DataType::Int64, DataType::Int64], CHUNK_SIZE, chunk_num);
+
+    // Expression: $0 % 100 < selectivity
+    let expr = {
+        let input_ref = ExprNode {
+            expr_type: InputRef as i32,
+            return_type: Some(DataType::Int64.to_protobuf()),
+            rex_node: Some(RexNode::InputRef(InputRefExpr { column_idx: 0 })),
+        };
+        let modulus = ExprNode {
+            expr_type: Modulus as i32,
+            return_type: Some(DataType::Int64.to_protobuf()),
+            rex_node: Some(RexNode::FuncCall(FunctionCall {
+                children: vec![input_ref, int64_constant(100)],
+            })),
+        };
+        ExprNode {
+            expr_type: LessThan as i32,
+            return_type: Some(DataType::Boolean.to_protobuf()),
+            rex_node: Some(RexNode::FuncCall(FunctionCall {
+                children: vec![modulus, int64_constant(selectivity)],
+            })),
+        }
+    };
+    let filter = Box::new(FilterExecutor::new(
+        build_from_prost(&expr).unwrap(),
+        input,
+        "FilterBenchmark".to_string(),
+        CHUNK_SIZE,
+        enable_selection_vector,
+    ));
+
+    let agg_call = AggCall {
+        r#type: AggKind::Sum.to_prost() as i32,
+        args: vec![Arg {
+            input: Some(InputRefExpr { column_idx: 1 }),
+            r#type: Some(DataType::Int64.to_protobuf()),
+        }],
+        return_type: Some(DataType::Int64.to_protobuf()),
+        distinct: false,
+        order_by_fields: vec![],
+        filter: None,
+        approx_relative_error: 0.0,
+        approx_percentile_fraction: 0.0,
+    };
+    let schema = Schema {
+        fields: vec![
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int64),
+        ],
+    };
+
+    Box::new(HashAggExecutor::<hash::Key64>::new(
+        vec![AggStateFactory::new(&agg_call).unwrap()],
+        vec![0],
+        vec![DataType::Int64],
+        schema,
+        filter,
+        "HashAggExecutor".to_string(),
+        CHUNK_SIZE,
+    ))
+}
+
+fn bench_filter_hash_agg(c: &mut Criterion) {
+    const SIZE: usize = 1024 * 1024;
+    let rt = Runtime::new().unwrap();
+    for selectivity in [1, 50] {
+        for enable_selection_vector in [false, true] {
+            c.bench_with_input(
+                BenchmarkId::new(
+                    format!(
+                        "FilterHashAgg(selection_vector: {})",
+                        enable_selection_vector
+                    ),
+                    format!("{}%", selectivity),
+                ),
+                &selectivity,
+                |b, &selectivity| {
+                    b.to_async(&rt).iter_batched(
+                        || {
+                            create_filter_hash_agg_executor(
+                                selectivity,
+                                enable_selection_vector,
+                                SIZE / CHUNK_SIZE,
+                            )
+                        },
+                        |e| execute_executor(e),
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
+    }
+}
+
+criterion_group!(benches, bench_filter_hash_agg);
+criterion_main!(benches);
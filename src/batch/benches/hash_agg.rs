@@ -51,6 +51,8 @@ fn create_agg_call(
         distinct: false,
         order_by_fields: vec![],
         filter: None,
+        approx_relative_error: 0.0,
+        approx_percentile_fraction: 0.0,
     }
 }
 
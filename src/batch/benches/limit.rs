@@ -14,8 +14,12 @@
 
 pub mod utils;
 
+use std::sync::atomic::Ordering;
+
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
-use risingwave_batch::executor::{BoxedExecutor, LimitExecutor};
+use futures::StreamExt;
+use risingwave_batch::executor::test_utils::CountingExecutor;
+use risingwave_batch::executor::{BoxedExecutor, Executor, LimitExecutor};
 use risingwave_common::types::DataType;
 use tikv_jemallocator::Jemalloc;
 use tokio::runtime::Runtime;
@@ -24,39 +28,99 @@ use utils::{create_input, execute_executor};
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+const SIZE: usize = 1024 * 1024;
+const CHUNK_SIZES: [usize; 3] = [32, 256, 1024];
+const LIMITS: [usize; 5] = [1, 10, 100, 1000, usize::MAX];
+
 fn create_limit_executor(
     chunk_size: usize,
     chunk_num: usize,
-    offset: usize,
     limit: usize,
+    offset: usize,
 ) -> BoxedExecutor {
     let input = create_input(&[DataType::Int64], chunk_size, chunk_num);
 
     Box::new(LimitExecutor::new(
         input,
-        offset,
         limit,
+        offset,
         "LimitExecutor".into(),
     ))
 }
 
+/// Checks that the executor stops pulling from its input once `limit` rows are returned, otherwise
+/// the numbers of the small limits are meaningless.
+async fn assert_early_exit(chunk_size: usize, limit: usize) {
+    let chunk_num = SIZE / chunk_size;
+    let input = CountingExecutor::new(create_input(&[DataType::Int64], chunk_size, chunk_num));
+    let pulled = input.pulled();
+    let executor = Box::new(LimitExecutor::new(
+        Box::new(input),
+        limit,
+        0,
+        "LimitExecutor".into(),
+    ));
+    execute_executor(executor).await;
+
+    let expected = (limit / chunk_size + usize::from(limit % chunk_size != 0)).min(chunk_num);
+    assert_eq!(
+        pulled.load(Ordering::Relaxed),
+        expected,
+        "LimitExecutor (limit: {}, chunk size: {}) pulled more chunks than needed",
+        limit,
+        chunk_size
+    );
+}
+
+fn limit_name(limit: usize) -> String {
+    if limit == usize::MAX {
+        "unlimited".to_string()
+    } else {
+        limit.to_string()
+    }
+}
+
 fn bench_limit(c: &mut Criterion) {
-    const SIZE: usize = 1024 * 1024;
     let rt = Runtime::new().unwrap();
 
-    for chunk_size in &[32, 128, 512, 1024, 2048, 4096] {
-        c.bench_with_input(
-            BenchmarkId::new("LimitExecutor", chunk_size),
-            chunk_size,
-            |b, &chunk_size| {
-                let chunk_num = SIZE / chunk_size;
-                b.to_async(&rt).iter_batched(
-                    || create_limit_executor(chunk_size, chunk_num, 128, 128),
-                    |e| execute_executor(e),
-                    BatchSize::SmallInput,
-                );
-            },
-        );
+    for chunk_size in CHUNK_SIZES {
+        let chunk_num = SIZE / chunk_size;
+        for limit in LIMITS {
+            rt.block_on(assert_early_exit(chunk_size, limit));
+
+            c.bench_with_input(
+                BenchmarkId::new(
+                    "LimitExecutor/first_row",
+                    format!("{}(limit: {})", chunk_size, limit_name(limit)),
+                ),
+                &(chunk_size, limit),
+                |b, &(chunk_size, limit)| {
+                    b.to_async(&rt).iter_batched(
+                        || create_limit_executor(chunk_size, chunk_num, limit, 0),
+                        |e| async move {
+                            let mut stream = e.execute();
+                            _ = criterion::black_box(stream.next().await.unwrap().unwrap());
+                        },
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+
+            c.bench_with_input(
+                BenchmarkId::new(
+                    "LimitExecutor/all_rows",
+                    format!("{}(limit: {})", chunk_size, limit_name(limit)),
+                ),
+                &(chunk_size, limit),
+                |b, &(chunk_size, limit)| {
+                    b.to_async(&rt).iter_batched(
+                        || create_limit_executor(chunk_size, chunk_num, limit, 0),
+                        |e| execute_executor(e),
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
     }
 }
 
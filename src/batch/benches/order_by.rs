@@ -14,8 +14,13 @@
 
 pub mod utils;
 
+use std::sync::Arc;
+
 use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use risingwave_batch::executor::test_utils::{gen_data, MockExecutor};
 use risingwave_batch::executor::{BoxedExecutor, OrderByExecutor};
+use risingwave_batch::task::MemoryContext;
+use risingwave_common::catalog::{Field, Schema};
 use risingwave_common::types::DataType;
 use risingwave_common::util::sort_util::{OrderPair, OrderType};
 use tikv_jemallocator::Jemalloc;
@@ -60,6 +65,36 @@ fn create_order_by_executor(
         order_pairs,
         "OrderByExecutor".into(),
         CHUNK_SIZE,
+        Arc::new(MemoryContext::new(usize::MAX)),
+    ))
+}
+
+/// Creates an order by executor over `input_size` bytes of `Int64` columns with the given memory
+/// budget. The input cycles through a small pool of generated chunks to keep the bench itself from
+/// holding the whole input in memory.
+fn create_spill_order_by_executor(input_size: usize, memory_budget: usize) -> BoxedExecutor {
+    const CHUNK_SIZE: usize = 1024;
+    const POOL_SIZE: usize = 64;
+    let data_types = vec![DataType::Int64; 4];
+    let chunk_num = input_size / (CHUNK_SIZE * 8 * data_types.len());
+
+    let pool = gen_data(CHUNK_SIZE, POOL_SIZE, &data_types);
+    let mut input = MockExecutor::new(Schema {
+        fields: data_types.iter().cloned().map(Field::unnamed).collect(),
+    });
+    for chunk in pool.into_iter().cycle().take(chunk_num) {
+        input.add(chunk);
+    }
+
+    Box::new(OrderByExecutor::new(
+        Box::new(input),
+        vec![
+            OrderPair::new(0, OrderType::Ascending),
+            OrderPair::new(1, OrderType::Descending),
+        ],
+        "OrderByExecutor".into(),
+        CHUNK_SIZE,
+        Arc::new(MemoryContext::new(memory_budget)),
     ))
 }
 
@@ -88,5 +123,22 @@ fn bench_order_by(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_order_by);
+fn bench_order_by_spill(c: &mut Criterion) {
+    const INPUT_SIZE: usize = 4 << 30;
+    const MEMORY_BUDGET: usize = 256 << 20;
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("OrderByExecutor(spill)");
+    group.sample_size(10);
+    group.bench_function("4GB input with 256MB budget", |b| {
+        b.to_async(&rt).iter_batched(
+            || create_spill_order_by_executor(INPUT_SIZE, MEMORY_BUDGET),
+            |e| execute_executor(e),
+            BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_order_by, bench_order_by_spill);
 criterion_main!(benches);
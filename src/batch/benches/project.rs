@@ -0,0 +1,238 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod utils;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use risingwave_batch::executor::{BoxedExecutor, ProjectExecutor};
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_common::util::value_encoding::serialize_datum_to_bytes;
+use risingwave_expr::expr::expr_binary_nonnull::new_binary_expr;
+use risingwave_expr::expr::{
+    build_from_prost, build_optimized_from_prost, make_input_ref, BoxedExpression,
+    InputRefExpression, LiteralExpression,
+};
+use risingwave_pb::data::data_type::TypeName;
+use risingwave_pb::data::{DataType as ProstDataType, Datum as ProstDatum};
+use risingwave_pb::expr::expr_node::{RexNode, Type};
+use risingwave_pb::expr::{ExprNode, FunctionCall};
+use tikv_jemallocator::Jemalloc;
+use tokio::runtime::Runtime;
+use utils::{create_input, execute_executor};
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+/// Input columns: `$0: Int64, $1: Int64, $2: Varchar, $3: Varchar`.
+const INPUT_TYPES: [DataType; 4] = [
+    DataType::Int64,
+    DataType::Int64,
+    DataType::Varchar,
+    DataType::Varchar,
+];
+
+#[derive(Clone, Copy, Debug)]
+enum ProjectCase {
+    /// `$0`
+    PassThrough,
+    /// `$0 + $1`
+    Arithmetic,
+    /// `$2 || $3`
+    Concat,
+    /// `(((($0 + $1) * $0) - $1) / 3) % 7`
+    Nested,
+}
+
+impl ProjectCase {
+    const ALL: [ProjectCase; 4] = [
+        ProjectCase::PassThrough,
+        ProjectCase::Arithmetic,
+        ProjectCase::Concat,
+        ProjectCase::Nested,
+    ];
+
+    /// Number of expression nodes, including input refs and literals, evaluated for each row.
+    fn expr_count(self) -> u64 {
+        match self {
+            ProjectCase::PassThrough => 1,
+            ProjectCase::Arithmetic | ProjectCase::Concat => 3,
+            ProjectCase::Nested => 11,
+        }
+    }
+
+    fn build_expr(self) -> BoxedExpression {
+        let input_ref = |idx: usize| -> BoxedExpression {
+            Box::new(InputRefExpression::new(INPUT_TYPES[idx].clone(), idx))
+        };
+        let int64_literal = |v: i64| -> BoxedExpression {
+            Box::new(LiteralExpression::new(DataType::Int64, Some(v.into())))
+        };
+        let binary = |kind: Type, ret: DataType, l: BoxedExpression, r: BoxedExpression| {
+            new_binary_expr(kind, ret, l, r).unwrap()
+        };
+
+        match self {
+            ProjectCase::PassThrough => input_ref(0),
+            ProjectCase::Arithmetic => {
+                binary(Type::Add, DataType::Int64, input_ref(0), input_ref(1))
+            }
+            ProjectCase::Concat => binary(
+                Type::ConcatOp,
+                DataType::Varchar,
+                input_ref(2),
+                input_ref(3),
+            ),
+            ProjectCase::Nested => {
+                let add = binary(Type::Add, DataType::Int64, input_ref(0), input_ref(1));
+                let mul = binary(Type::Multiply, DataType::Int64, add, input_ref(0));
+                let sub = binary(Type::Subtract, DataType::Int64, mul, input_ref(1));
+                let div = binary(Type::Divide, DataType::Int64, sub, int64_literal(3));
+                binary(Type::Modulus, DataType::Int64, div, int64_literal(7))
+            }
+        }
+    }
+}
+
+fn create_project_executor(
+    case: ProjectCase,
+    chunk_size: usize,
+    chunk_num: usize,
+) -> BoxedExecutor {
+    let input = create_input(&INPUT_TYPES, chunk_size, chunk_num);
+    Box::new(ProjectExecutor::new(
+        vec![case.build_expr()],
+        input,
+        "ProjectBenchmark".to_string(),
+    ))
+}
+
+/// Reports rows per second in the group `ProjectExecutor`, and expressions per second in the group
+/// `ProjectExecutorExpr`.
+fn bench_project(c: &mut Criterion) {
+    const TOTAL_SIZE: usize = 1024 * 1024usize;
+    let rt = Runtime::new().unwrap();
+    for (group_name, per_expr) in [("ProjectExecutor", false), ("ProjectExecutorExpr", true)] {
+        let mut group = c.benchmark_group(group_name);
+        for case in ProjectCase::ALL {
+            let elements = if per_expr {
+                TOTAL_SIZE as u64 * case.expr_count()
+            } else {
+                TOTAL_SIZE as u64
+            };
+            group.throughput(Throughput::Elements(elements));
+            for chunk_size in &[32, 256, 1024] {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{:?}", case), chunk_size),
+                    chunk_size,
+                    |b, &chunk_size| {
+                        let chunk_num = TOTAL_SIZE / chunk_size;
+                        b.to_async(&rt).iter_batched(
+                            || create_project_executor(case, chunk_size, chunk_num),
+                            |e| execute_executor(e),
+                            BatchSize::SmallInput,
+                        );
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+/// `(($0 + $1) * ($0 + $1)) + (($0 + $1) * (2 + 3)) - (($0 + $1) % (4 + 3))`, where `$0 + $1` is
+/// shared and `2 + 3` and `4 + 3` are constants.
+fn build_optimizable_expr_node() -> ExprNode {
+    let int64_type = || {
+        Some(ProstDataType {
+            type_name: TypeName::Int64 as i32,
+            ..Default::default()
+        })
+    };
+    let int64_literal = |v: i64| ExprNode {
+        expr_type: Type::ConstantValue as i32,
+        return_type: int64_type(),
+        rex_node: Some(RexNode::Constant(ProstDatum {
+            body: serialize_datum_to_bytes(Some(ScalarImpl::Int64(v)).as_ref()),
+        })),
+    };
+    let binary = |kind: Type, l: ExprNode, r: ExprNode| ExprNode {
+        expr_type: kind as i32,
+        return_type: int64_type(),
+        rex_node: Some(RexNode::FuncCall(FunctionCall {
+            children: vec![l, r],
+        })),
+    };
+
+    let sum = || {
+        binary(
+            Type::Add,
+            make_input_ref(0, TypeName::Int64),
+            make_input_ref(1, TypeName::Int64),
+        )
+    };
+    let square = binary(Type::Multiply, sum(), sum());
+    let scaled = binary(
+        Type::Multiply,
+        sum(),
+        binary(Type::Add, int64_literal(2), int64_literal(3)),
+    );
+    let modulus = binary(
+        Type::Modulus,
+        sum(),
+        binary(Type::Add, int64_literal(4), int64_literal(3)),
+    );
+    binary(Type::Subtract, binary(Type::Add, square, scaled), modulus)
+}
+
+/// Compares the projection built by `build_from_prost` and `build_optimized_from_prost`.
+fn bench_project_optimize(c: &mut Criterion) {
+    const TOTAL_SIZE: usize = 1024 * 1024usize;
+    let rt = Runtime::new().unwrap();
+    let expr_node = build_optimizable_expr_node();
+    let mut group = c.benchmark_group("ProjectExecutorOptimize");
+    group.throughput(Throughput::Elements(TOTAL_SIZE as u64));
+    for (name, optimized) in [("Unoptimized", false), ("Optimized", true)] {
+        for chunk_size in &[32, 256, 1024] {
+            group.bench_with_input(
+                BenchmarkId::new(name, chunk_size),
+                chunk_size,
+                |b, &chunk_size| {
+                    let chunk_num = TOTAL_SIZE / chunk_size;
+                    b.to_async(&rt).iter_batched(
+                        || {
+                            let expr = if optimized {
+                                build_optimized_from_prost(&expr_node)
+                            } else {
+                                build_from_prost(&expr_node)
+                            }
+                            .unwrap();
+                            let input = create_input(&INPUT_TYPES, chunk_size, chunk_num);
+                            Box::new(ProjectExecutor::new(
+                                vec![expr],
+                                input,
+                                "ProjectBenchmark".to_string(),
+                            )) as BoxedExecutor
+                        },
+                        |e| execute_executor(e),
+                        BatchSize::SmallInput,
+                    );
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_project, bench_project_optimize);
+criterion_main!(benches);
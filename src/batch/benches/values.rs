@@ -0,0 +1,119 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod utils;
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use risingwave_batch::executor::{BoxedExecutor, ValuesExecutor};
+use risingwave_common::array::{DataChunk, Row};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_expr::expr::{BoxedExpression, LiteralExpression};
+use tikv_jemallocator::Jemalloc;
+use tokio::runtime::Runtime;
+use utils::execute_executor;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const CHUNK_SIZE: usize = 1024;
+
+/// `(columns, rows)` of the values, from narrow and long to wide and short.
+const SHAPES: [(usize, usize); 3] = [(1, 1024 * 1024), (100, 10 * 1024), (1000, 1024)];
+
+/// The constant of the `col`-th column.
+fn constant(col: usize) -> ScalarImpl {
+    ScalarImpl::Int64(col as i64)
+}
+
+fn create_values_executor(columns: usize, rows: usize) -> BoxedExecutor {
+    let rows = (0..rows)
+        .map(|_| {
+            (0..columns)
+                .map(|col| {
+                    Box::new(LiteralExpression::new(DataType::Int64, Some(constant(col))))
+                        as BoxedExpression
+                })
+                .collect()
+        })
+        .collect();
+    let schema = Schema {
+        fields: vec![Field::unnamed(DataType::Int64); columns],
+    };
+    Box::new(ValuesExecutor::new(
+        rows,
+        schema,
+        "ValuesBenchmark".to_string(),
+        CHUNK_SIZE,
+    ))
+}
+
+/// The rows of the values already folded into datums, as the baseline of building the chunks
+/// with `DataChunk::from_rows` instead of evaluating one expression per cell.
+fn create_folded_rows(columns: usize, rows: usize) -> Vec<Row> {
+    let row = Row::new((0..columns).map(|col| Some(constant(col))).collect());
+    vec![row; rows]
+}
+
+fn build_chunks_from_rows(rows: Vec<Row>, data_types: &[DataType]) {
+    for rows in rows.chunks(CHUNK_SIZE) {
+        _ = black_box(DataChunk::from_rows(rows, data_types));
+    }
+}
+
+/// Reports cells per second of producing the chunks of constant values, by `ValuesExecutor` in
+/// the group `ValuesExecutor`, and by `DataChunk::from_rows` in the group `ValuesFromRows`.
+fn bench_values(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("ValuesExecutor");
+    for (columns, rows) in SHAPES {
+        group.throughput(Throughput::Elements((columns * rows) as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}x{}", columns, rows)),
+            &(columns, rows),
+            |b, &(columns, rows)| {
+                b.to_async(&rt).iter_batched(
+                    || create_values_executor(columns, rows),
+                    |e| execute_executor(e),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("ValuesFromRows");
+    for (columns, rows) in SHAPES {
+        let data_types = vec![DataType::Int64; columns];
+        group.throughput(Throughput::Elements((columns * rows) as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}x{}", columns, rows)),
+            &(columns, rows),
+            |b, &(columns, rows)| {
+                b.iter_batched(
+                    || create_folded_rows(columns, rows),
+                    |rows| build_chunks_from_rows(rows, &data_types),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_values);
+criterion_main!(benches);
@@ -16,8 +16,10 @@ use std::fmt::{Debug, Formatter};
 use std::future::Future;
 
 use futures::StreamExt;
+use prometheus::core::{AtomicU64, GenericCounter};
+use risingwave_common::array::checksum::ChunkChecksumExt;
 use risingwave_common::array::DataChunk;
-use risingwave_common::error::Result;
+use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::batch_plan::exchange_source::LocalExecutePlan::{self, Plan};
 use risingwave_pb::batch_plan::TaskOutputId;
 use risingwave_pb::task_service::{ExecuteRequest, GetDataResponse};
@@ -32,6 +34,9 @@ pub struct GrpcExchangeSource {
     stream: Streaming<GetDataResponse>,
 
     task_output_id: TaskOutputId,
+
+    /// Counts the received chunks whose checksums mismatch. None if the metrics are not recorded.
+    checksum_mismatch_count: Option<GenericCounter<AtomicU64>>,
 }
 
 impl GrpcExchangeSource {
@@ -58,9 +63,14 @@ impl GrpcExchangeSource {
         let source = Self {
             stream,
             task_output_id,
+            checksum_mismatch_count: None,
         };
         Ok(source)
     }
+
+    pub fn set_checksum_mismatch_count(&mut self, counter: GenericCounter<AtomicU64>) {
+        self.checksum_mismatch_count = Some(counter);
+    }
 }
 
 impl Debug for GrpcExchangeSource {
@@ -81,7 +91,19 @@ impl ExchangeSource for GrpcExchangeSource {
                 Some(r) => r,
             };
             let task_data = res?;
-            let data = DataChunk::from_protobuf(task_data.get_record_batch()?)?.compact();
+            let record_batch = task_data.get_record_batch()?;
+            if let Err(e) = record_batch.verify_checksum() {
+                if let Some(counter) = &self.checksum_mismatch_count {
+                    counter.inc();
+                }
+                return Err(ErrorCode::InternalError(format!(
+                    "GrpcExchangeSource received a corrupted chunk from task {:?}: {}",
+                    self.get_task_id(),
+                    e
+                ))
+                .into());
+            }
+            let data = DataChunk::from_protobuf(record_batch)?.compact();
             trace!(
                 "Receiver taskOutput = {:?}, data = {:?}",
                 self.task_output_id,
@@ -84,29 +84,53 @@ mod tests {
     use std::sync::Arc;
     use std::time::Duration;
 
+    use prometheus::core::GenericCounter;
+    use prost::Message;
+    use risingwave_common::array::checksum::ChunkChecksumExt;
+    use risingwave_common::array::DataChunkTestExt;
     use risingwave_pb::batch_plan::{TaskId, TaskOutputId};
     use risingwave_pb::data::DataChunk;
     use risingwave_pb::task_service::exchange_service_server::{
         ExchangeService, ExchangeServiceServer,
     };
     use risingwave_pb::task_service::{
-        GetDataRequest, GetDataResponse, GetStreamRequest, GetStreamResponse,
+        GetDataRequest, GetDataResponse, GetMultiplexedStreamRequest, GetMultiplexedStreamResponse,
+        GetStreamRequest, GetStreamResponse,
     };
     use risingwave_rpc_client::ComputeClient;
     use tokio::time::sleep;
     use tokio_stream::wrappers::ReceiverStream;
-    use tonic::{Request, Response, Status};
+    use tonic::{Request, Response, Status, Streaming};
 
     use crate::exchange_source::ExchangeSource;
     use crate::execution::grpc_exchange::GrpcExchangeSource;
 
     struct FakeExchangeService {
         rpc_called: Arc<AtomicBool>,
+        /// Whether to flip a byte of the chunks on the wire after attaching their checksums.
+        corrupt_chunk: bool,
+    }
+
+    /// A chunk with a checksum, whose value `0x0123456789abcdef` is corrupted on the wire.
+    fn corrupted_chunk() -> DataChunk {
+        let mut chunk = risingwave_common::array::DataChunk::from_pretty(
+            "I
+             81985529216486895",
+        )
+        .to_protobuf();
+        chunk.set_checksum();
+        let mut bytes = chunk.encode_to_vec();
+        let value = 0x0123456789abcdefi64.to_be_bytes();
+        let pos = bytes.windows(8).position(|window| window == value).unwrap();
+        bytes[pos] ^= 0xff;
+        DataChunk::decode(bytes.as_slice()).unwrap()
     }
 
     #[async_trait::async_trait]
     impl ExchangeService for FakeExchangeService {
         type GetDataStream = ReceiverStream<Result<GetDataResponse, Status>>;
+        type GetMultiplexedStreamStream =
+            ReceiverStream<std::result::Result<GetMultiplexedStreamResponse, Status>>;
         type GetStreamStream = ReceiverStream<std::result::Result<GetStreamResponse, Status>>;
 
         async fn get_data(
@@ -118,7 +142,11 @@ mod tests {
             for _ in 0..3 {
                 tx.send(Ok(GetDataResponse {
                     status: None,
-                    record_batch: Some(DataChunk::default()),
+                    record_batch: Some(if self.corrupt_chunk {
+                        corrupted_chunk()
+                    } else {
+                        DataChunk::default()
+                    }),
                 }))
                 .await
                 .unwrap();
@@ -132,6 +160,13 @@ mod tests {
         ) -> Result<Response<Self::GetStreamStream>, Status> {
             unimplemented!()
         }
+
+        async fn get_multiplexed_stream(
+            &self,
+            _request: Request<Streaming<GetMultiplexedStreamRequest>>,
+        ) -> Result<Response<Self::GetMultiplexedStreamStream>, Status> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test]
@@ -144,6 +179,7 @@ mod tests {
         let (shutdown_send, shutdown_recv) = tokio::sync::oneshot::channel();
         let exchange_svc = ExchangeServiceServer::new(FakeExchangeService {
             rpc_called: rpc_called.clone(),
+            corrupt_chunk: false,
         });
         let cp_server_run = server_run.clone();
         let join_handle = tokio::spawn(async move {
@@ -178,4 +214,45 @@ mod tests {
         shutdown_send.send(()).unwrap();
         join_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_exchange_client_checksum_mismatch() {
+        let addr: SocketAddr = "127.0.0.1:12346".parse().unwrap();
+        let (shutdown_send, shutdown_recv) = tokio::sync::oneshot::channel();
+        let exchange_svc = ExchangeServiceServer::new(FakeExchangeService {
+            rpc_called: Arc::new(AtomicBool::new(false)),
+            corrupt_chunk: true,
+        });
+        let join_handle = tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(exchange_svc)
+                .serve_with_shutdown(addr, async move {
+                    shutdown_recv.await.unwrap();
+                })
+                .await
+                .unwrap();
+        });
+        sleep(Duration::from_secs(1)).await;
+
+        let client = ComputeClient::new(addr.into()).await.unwrap();
+        let task_output_id = TaskOutputId {
+            task_id: Some(TaskId {
+                task_id: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut src = GrpcExchangeSource::create(client, task_output_id, None)
+            .await
+            .unwrap();
+        let counter = GenericCounter::new("checksum_mismatch_count", "test").unwrap();
+        src.set_checksum_mismatch_count(counter.clone());
+
+        let err = src.take_data().await.unwrap_err();
+        assert!(err.to_string().contains("task_id: 7"), "{}", err);
+        assert_eq!(counter.get(), 1);
+
+        shutdown_send.send(()).unwrap();
+        join_handle.await.unwrap();
+    }
 }
@@ -0,0 +1,158 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// A node of the operator-level plan tree of batch executors, returned by
+/// [`super::Executor::explain`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplainNode {
+    /// Name of the operator, which is the identity of the executor.
+    pub operator_name: String,
+    /// The number of rows the operator is estimated to output, if known.
+    pub estimated_rows: Option<u64>,
+    /// The number of rows the operator has actually output, if known.
+    pub actual_rows: Option<u64>,
+    /// Explain nodes of the inputs of the operator.
+    pub child_nodes: Vec<ExplainNode>,
+}
+
+impl ExplainNode {
+    pub fn new(operator_name: impl Into<String>) -> Self {
+        Self {
+            operator_name: operator_name.into(),
+            estimated_rows: None,
+            actual_rows: None,
+            child_nodes: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn with_estimated_rows(mut self, estimated_rows: u64) -> Self {
+        self.estimated_rows = Some(estimated_rows);
+        self
+    }
+
+    #[must_use]
+    pub fn with_actual_rows(mut self, actual_rows: u64) -> Self {
+        self.actual_rows = Some(actual_rows);
+        self
+    }
+
+    #[must_use]
+    pub fn with_child_nodes(mut self, child_nodes: Vec<ExplainNode>) -> Self {
+        self.child_nodes = child_nodes;
+        self
+    }
+
+    fn fmt_with_indent(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+        write!(f, "{}{}", "  ".repeat(level), self.operator_name)?;
+        match (self.estimated_rows, self.actual_rows) {
+            (None, None) => {}
+            (Some(estimated), None) => write!(f, " {{ estimated_rows: {} }}", estimated)?,
+            (None, Some(actual)) => write!(f, " {{ actual_rows: {} }}", actual)?,
+            (Some(estimated), Some(actual)) => write!(
+                f,
+                " {{ estimated_rows: {}, actual_rows: {} }}",
+                estimated, actual
+            )?,
+        }
+        writeln!(f)?;
+        for child in &self.child_nodes {
+            child.fmt_with_indent(f, level + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExplainNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_indent(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::DataChunk;
+    use risingwave_common::catalog::{Field, Schema};
+    use risingwave_common::hash::Key32;
+    use risingwave_common::test_prelude::DataChunkTestExt;
+    use risingwave_common::types::DataType;
+    use risingwave_expr::expr::LiteralExpression;
+
+    use super::*;
+    use crate::executor::join::JoinType;
+    use crate::executor::test_utils::MockExecutor;
+    use crate::executor::{BoxedExecutor, Executor, HashAggExecutor, NestedLoopJoinExecutor};
+
+    fn create_mock_executor(chunk: &str) -> BoxedExecutor {
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int32)],
+        };
+        Box::new(MockExecutor::with_chunk(
+            DataChunk::from_pretty(chunk),
+            schema,
+        ))
+    }
+
+    #[test]
+    fn test_explain_plan_tree() {
+        // select t1.v1 from t1, t2 group by t1.v1
+        let join = NestedLoopJoinExecutor::new(
+            Box::new(LiteralExpression::new(DataType::Boolean, Some(true.into()))),
+            JoinType::Inner,
+            vec![0],
+            create_mock_executor(
+                "i
+                 1
+                 2
+                 2",
+            ),
+            create_mock_executor(
+                "i
+                 3
+                 4",
+            ),
+            "NestedLoopJoinExecutor".to_string(),
+            1024,
+        );
+        let agg = HashAggExecutor::<Key32>::new(
+            vec![],
+            vec![0],
+            vec![DataType::Int32],
+            join.schema().clone(),
+            Box::new(join),
+            "HashAggExecutor".to_string(),
+            1024,
+        );
+
+        let explain = agg.explain();
+        let join = ExplainNode::new("NestedLoopJoinExecutor").with_child_nodes(vec![
+            ExplainNode::new("MockExecutor").with_estimated_rows(3),
+            ExplainNode::new("MockExecutor").with_estimated_rows(2),
+        ]);
+        assert_eq!(
+            explain,
+            ExplainNode::new("HashAggExecutor").with_child_nodes(vec![join])
+        );
+        assert_eq!(
+            explain.to_string(),
+            "HashAggExecutor
+  NestedLoopJoinExecutor
+    MockExecutor { estimated_rows: 3 }
+    MockExecutor { estimated_rows: 2 }
+"
+        );
+    }
+}
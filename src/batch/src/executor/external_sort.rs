@@ -0,0 +1,330 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Building blocks of the external sort used by [`super::OrderByExecutor`].
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use prost::Message;
+use risingwave_common::array::{DataChunk, RowRef};
+use risingwave_common::error::ErrorCode::InternalError;
+use risingwave_common::error::Result;
+use risingwave_common::types::DataType;
+use risingwave_common::util::chunk_coalesce::DataChunkBuilder;
+use risingwave_common::util::encoding_for_comparison::encode_chunk;
+use risingwave_common::util::sort_util::OrderPair;
+use risingwave_pb::data::DataChunk as ProstDataChunk;
+
+/// Sorts the rows in `chunks` by their memcomparable keys in `keys`, and rebuilds them into
+/// chunks of `chunk_size`.
+pub(super) fn sort_chunks(
+    chunks: &[DataChunk],
+    keys: Vec<Vec<Vec<u8>>>,
+    data_types: Vec<DataType>,
+    chunk_size: usize,
+) -> Vec<DataChunk> {
+    let mut encoded_rows = Vec::with_capacity(chunks.iter().map(|c| c.capacity()).sum());
+    for (chunk, encoded_chunk) in chunks.iter().zip(keys) {
+        encoded_rows.extend(
+            encoded_chunk
+                .into_iter()
+                .enumerate()
+                .map(|(row_id, row)| (chunk.row_at_unchecked_vis(row_id), row)),
+        );
+    }
+
+    encoded_rows.sort_unstable_by(|(_, a), (_, b)| a.cmp(b));
+
+    let mut chunk_builder = DataChunkBuilder::new(data_types, chunk_size);
+    let mut sorted = vec![];
+    for (row, _) in encoded_rows {
+        if let Some(spilled) = chunk_builder.append_one_row_ref(row) {
+            sorted.push(spilled);
+        }
+    }
+    if let Some(spilled) = chunk_builder.consume_all() {
+        sorted.push(spilled);
+    }
+    sorted
+}
+
+/// Writes a sorted run into an anonymous temporary file. Each chunk is written as its protobuf
+/// encoding prefixed with the encoded length.
+///
+/// The file is unlinked on creation, so it will be reclaimed by the OS as soon as the run is
+/// dropped, whether the query finishes or gets cancelled.
+pub(super) struct SpilledRunWriter {
+    writer: BufWriter<File>,
+}
+
+impl SpilledRunWriter {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(tempfile::tempfile()?),
+        })
+    }
+
+    pub fn write_chunk(&mut self, chunk: &DataChunk) -> Result<()> {
+        let encoded = chunk.to_protobuf().encode_to_vec();
+        self.writer
+            .write_u32::<LittleEndian>(encoded.len() as u32)?;
+        self.writer.write_all(&encoded)?;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<SortRun> {
+        let mut file = self.writer.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(SortRun::Spilled(BufReader::new(file)))
+    }
+}
+
+/// A sorted run to be merged, either kept in memory or spilled to disk.
+pub(super) enum SortRun {
+    InMemory(VecDeque<DataChunk>),
+    Spilled(BufReader<File>),
+}
+
+impl SortRun {
+    fn next_chunk(&mut self) -> Result<Option<DataChunk>> {
+        match self {
+            SortRun::InMemory(chunks) => Ok(chunks.pop_front()),
+            SortRun::Spilled(reader) => {
+                let len = match reader.read_u32::<LittleEndian>() {
+                    Ok(len) => len as usize,
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => return Err(e.into()),
+                };
+                let mut buf = vec![0; len];
+                reader.read_exact(&mut buf)?;
+                let proto = ProstDataChunk::decode(buf.as_slice())
+                    .map_err(|e| InternalError(format!("failed to decode spilled chunk: {}", e)))?;
+                Ok(Some(DataChunk::from_protobuf(&proto)?))
+            }
+        }
+    }
+}
+
+/// The read position of a [`SortRun`] during merging.
+struct RunCursor {
+    run: SortRun,
+    chunk: Option<DataChunk>,
+    keys: Vec<Vec<u8>>,
+    row_idx: usize,
+}
+
+impl RunCursor {
+    pub fn new(run: SortRun, order_pairs: &[OrderPair]) -> Result<Self> {
+        let mut cursor = Self {
+            run,
+            chunk: None,
+            keys: vec![],
+            row_idx: 0,
+        };
+        cursor.load_next_chunk(order_pairs)?;
+        Ok(cursor)
+    }
+
+    fn load_next_chunk(&mut self, order_pairs: &[OrderPair]) -> Result<()> {
+        self.row_idx = 0;
+        // Chunks in a run are built by `DataChunkBuilder`, so they're always compact.
+        self.chunk = self.run.next_chunk()?;
+        self.keys = match &self.chunk {
+            Some(chunk) => encode_chunk(chunk, order_pairs),
+            None => vec![],
+        };
+        Ok(())
+    }
+
+    /// Returns the sort key of the current row, or `None` if the run is exhausted.
+    pub fn key(&self) -> Option<&[u8]> {
+        self.keys.get(self.row_idx).map(Vec::as_slice)
+    }
+
+    pub fn row(&self) -> RowRef<'_> {
+        self.chunk
+            .as_ref()
+            .expect("run exhausted")
+            .row_at_unchecked_vis(self.row_idx)
+    }
+
+    pub fn advance(&mut self, order_pairs: &[OrderPair]) -> Result<()> {
+        self.row_idx += 1;
+        while self.chunk.is_some() && self.row_idx >= self.keys.len() {
+            self.load_next_chunk(order_pairs)?;
+        }
+        Ok(())
+    }
+}
+
+/// A loser tree for merging `k` sorted inputs with `O(log k)` comparisons per output row.
+///
+/// Leaves are the inputs, stored implicitly at `k..2k`. Each internal node `1..k` records the loser
+/// of the match between the winners of its two subtrees, and `tree[0]` records the overall winner.
+struct LoserTree {
+    tree: Vec<usize>,
+}
+
+impl LoserTree {
+    /// Builds the tree over `k` inputs. `beats(a, b)` returns whether the current row of input `a`
+    /// should be output before that of input `b`.
+    pub fn new(k: usize, beats: impl Fn(usize, usize) -> bool) -> Self {
+        assert!(k > 0);
+        let mut tree = Self { tree: vec![0; k] };
+        let winner = tree.build(1, &beats);
+        tree.tree[0] = winner;
+        tree
+    }
+
+    fn build(&mut self, node: usize, beats: &impl Fn(usize, usize) -> bool) -> usize {
+        let k = self.tree.len();
+        if node >= k {
+            return node - k;
+        }
+        let left = self.build(node * 2, beats);
+        let right = self.build(node * 2 + 1, beats);
+        if beats(left, right) {
+            self.tree[node] = right;
+            left
+        } else {
+            self.tree[node] = left;
+            right
+        }
+    }
+
+    /// The input whose current row should be output next.
+    pub fn winner(&self) -> usize {
+        self.tree[0]
+    }
+
+    /// Replays the matches from the leaf of the winner to the root, after the winner input has
+    /// advanced.
+    pub fn replay(&mut self, beats: impl Fn(usize, usize) -> bool) {
+        let k = self.tree.len();
+        let mut winner = self.tree[0];
+        let mut node = (winner + k) / 2;
+        while node > 0 {
+            if beats(self.tree[node], winner) {
+                std::mem::swap(&mut self.tree[node], &mut winner);
+            }
+            node /= 2;
+        }
+        self.tree[0] = winner;
+    }
+}
+
+/// Whether the current row of `cursors[a]` should be output before that of `cursors[b]`. An
+/// exhausted run never wins, and ties are broken by the run index to keep the merge stable.
+fn cursor_beats(cursors: &[RunCursor], a: usize, b: usize) -> bool {
+    match (cursors[a].key(), cursors[b].key()) {
+        (None, _) => false,
+        (Some(_), None) => true,
+        (Some(key_a), Some(key_b)) => (key_a, a) < (key_b, b),
+    }
+}
+
+/// Merges the sorted `runs` with a loser tree, producing the rows in chunks of `chunk_size`.
+pub(super) struct RunMerger {
+    cursors: Vec<RunCursor>,
+    tree: LoserTree,
+    order_pairs: Vec<OrderPair>,
+    chunk_builder: DataChunkBuilder,
+}
+
+impl RunMerger {
+    pub fn new(
+        runs: Vec<SortRun>,
+        order_pairs: Vec<OrderPair>,
+        data_types: Vec<DataType>,
+        chunk_size: usize,
+    ) -> Result<Self> {
+        let cursors = runs
+            .into_iter()
+            .map(|run| RunCursor::new(run, &order_pairs))
+            .collect::<Result<Vec<_>>>()?;
+        let tree = LoserTree::new(cursors.len(), |a, b| cursor_beats(&cursors, a, b));
+        Ok(Self {
+            cursors,
+            tree,
+            order_pairs,
+            chunk_builder: DataChunkBuilder::new(data_types, chunk_size),
+        })
+    }
+
+    /// Returns the next merged chunk, or `None` if all the runs are exhausted.
+    pub fn next_chunk(&mut self) -> Result<Option<DataChunk>> {
+        while self.cursors[self.tree.winner()].key().is_some() {
+            let winner = self.tree.winner();
+            let output = self
+                .chunk_builder
+                .append_one_row_ref(self.cursors[winner].row());
+            self.cursors[winner].advance(&self.order_pairs)?;
+            let cursors = &self.cursors;
+            self.tree.replay(|a, b| cursor_beats(cursors, a, b));
+            if output.is_some() {
+                return Ok(output);
+            }
+        }
+        Ok(self.chunk_builder.consume_all())
+    }
+
+    /// Merges all the runs into a single spilled run.
+    pub fn spill(mut self) -> Result<SortRun> {
+        let mut writer = SpilledRunWriter::new()?;
+        while let Some(chunk) = self.next_chunk()? {
+            writer.write_chunk(&chunk)?;
+        }
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loser_tree() {
+        let inputs: Vec<Vec<i32>> = vec![
+            vec![1, 4, 7, 10],
+            vec![],
+            vec![2, 2, 9],
+            vec![0, 3, 5, 6, 8],
+            vec![11],
+        ];
+        let mut positions = vec![0; inputs.len()];
+        let beats = |positions: &[usize], a: usize, b: usize| match (
+            inputs[a].get(positions[a]),
+            inputs[b].get(positions[b]),
+        ) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(x), Some(y)) => (x, a) < (y, b),
+        };
+
+        let mut tree = LoserTree::new(inputs.len(), |a, b| beats(&positions, a, b));
+        let mut output = vec![];
+        while let Some(v) = inputs[tree.winner()].get(positions[tree.winner()]) {
+            output.push(*v);
+            positions[tree.winner()] += 1;
+            tree.replay(|a, b| beats(&positions, a, b));
+        }
+
+        let mut expected = inputs.concat();
+        expected.sort();
+        assert_eq!(output, expected);
+    }
+}
@@ -15,11 +15,11 @@
 use anyhow::anyhow;
 use futures_async_stream::try_stream;
 use risingwave_common::array::ArrayImpl::Bool;
-use risingwave_common::array::{Array, DataChunk};
+use risingwave_common::array::{Array, DataChunk, Selection};
 use risingwave_common::catalog::Schema;
 use risingwave_common::error::{Result, RwError};
 use risingwave_common::util::chunk_coalesce::DataChunkBuilder;
-use risingwave_expr::expr::{build_from_prost, BoxedExpression};
+use risingwave_expr::expr::{build_optimized_from_prost, BoxedExpression};
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 
 use crate::error::BatchError;
@@ -33,6 +33,9 @@ pub struct FilterExecutor {
     child: BoxedExecutor,
     identity: String,
     chunk_size: usize,
+    /// Whether to select the rows passing the filter with a selection vector rather than
+    /// compacting them into new chunks.
+    enable_selection_vector: bool,
 }
 
 impl Executor for FilterExecutor {
@@ -45,7 +48,11 @@ impl Executor for FilterExecutor {
     }
 
     fn execute(self: Box<Self>) -> BoxedDataChunkStream {
-        self.do_execute()
+        if self.enable_selection_vector {
+            self.do_execute_with_selection()
+        } else {
+            self.do_execute()
+        }
     }
 }
 
@@ -78,6 +85,31 @@ impl FilterExecutor {
             yield chunk;
         }
     }
+
+    /// Yields the input chunks with the selection of the rows passing the filter, without copying
+    /// them. Falls back to visibility bitmaps for chunks too long to be represented by a selection.
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute_with_selection(self: Box<Self>) {
+        #[for_await]
+        for data_chunk in self.child.execute() {
+            let data_chunk = data_chunk?.compact();
+            let vis_array = self.expr.eval(&data_chunk)?;
+
+            if let Bool(vis) = vis_array.as_ref() {
+                let data_chunk = match Selection::from_bool_array(vis) {
+                    Some(selection) => data_chunk.with_selection(selection),
+                    None => data_chunk.with_visibility(vis.iter().collect()),
+                };
+                if data_chunk.cardinality() > 0 {
+                    yield data_chunk;
+                }
+            } else {
+                return Err(
+                    BatchError::Internal(anyhow!("Filter can only receive bool array")).into(),
+                );
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -94,12 +126,18 @@ impl BoxedExecutorBuilder for FilterExecutor {
         )?;
 
         let expr_node = filter_node.get_search_condition()?;
-        let expr = build_from_prost(expr_node)?;
+        let expr = build_optimized_from_prost(expr_node)?;
         Ok(Box::new(Self::new(
             expr,
             input,
             source.plan_node().get_identity().clone(),
             source.context.get_config().developer.batch_chunk_size,
+            source
+                .context
+                .get_config()
+                .developer
+                .batch_filter_enable_selection_vector
+                && source.accepts_selection(),
         )))
     }
 }
@@ -110,12 +148,14 @@ impl FilterExecutor {
         input: BoxedExecutor,
         identity: String,
         chunk_size: usize,
+        enable_selection_vector: bool,
     ) -> Self {
         Self {
             expr,
             child: input,
             identity,
             chunk_size,
+            enable_selection_vector,
         }
     }
 }
@@ -180,6 +220,7 @@ mod tests {
             child: Box::new(mock_executor),
             identity: "FilterExecutor".to_string(),
             chunk_size: CHUNK_SIZE,
+            enable_selection_vector: false,
         });
 
         let fields = &filter_executor.schema().fields;
@@ -287,6 +328,7 @@ mod tests {
             child: Box::new(mock_executor),
             identity: "FilterExecutor".to_string(),
             chunk_size: CHUNK_SIZE,
+            enable_selection_vector: false,
         });
         let fields = &filter_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Int32);
@@ -306,6 +348,51 @@ mod tests {
         assert_matches!(res, None);
     }
 
+    #[tokio::test]
+    async fn test_filter_executor_with_selection() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int32),
+                Field::unnamed(DataType::Int32),
+            ],
+        };
+        let mut mock_executor = MockExecutor::new(schema);
+        mock_executor.add(DataChunk::from_pretty(
+            "i i
+             2 1
+             2 2
+             4 1
+             3 3",
+        ));
+        mock_executor.add(DataChunk::from_pretty(
+            "i i
+             1 2",
+        ));
+        let expr = make_expression(Type::Equal);
+        let filter_executor = Box::new(FilterExecutor {
+            expr: build_from_prost(&expr).unwrap(),
+            child: Box::new(mock_executor),
+            identity: "FilterExecutor".to_string(),
+            chunk_size: CHUNK_SIZE,
+            enable_selection_vector: true,
+        });
+        let mut stream = filter_executor.execute();
+        let res = stream.next().await.unwrap().unwrap();
+        assert_eq!(res.capacity(), 4);
+        assert_eq!(res.cardinality(), 2);
+        assert_eq!(res.selection().unwrap().indices(), &[1, 3]);
+        assert_eq!(
+            res.compact(),
+            DataChunk::from_pretty(
+                "i i
+                 2 2
+                 3 3",
+            )
+        );
+        // Chunks without any rows passing the filter are skipped.
+        assert_matches!(stream.next().await, None);
+    }
+
     fn make_expression(kind: Type) -> ExprNode {
         let lhs = make_inputref(0);
         let rhs = make_inputref(1);
@@ -190,7 +190,7 @@ async fn data_chunk_stream(
 ) {
     // create the collector
     let source_id = source.get_task_id();
-    let counter = if let Some(ref metrics) = metrics {
+    let (counter, checksum_mismatch_count) = if let Some(ref metrics) = metrics {
         let mut labels = metrics.task_labels();
         let source_stage_id = source_id.stage_id.to_string();
         let source_task_id = source_id.stage_id.to_string();
@@ -201,16 +201,29 @@ async fn data_chunk_stream(
             source_task_id.as_str(),
         ]);
 
-        Some(
-            metrics
-                .metrics
-                .task_exchange_recv_row_number
-                .with_label_values(&labels[..]),
+        (
+            Some(
+                metrics
+                    .metrics
+                    .task_exchange_recv_row_number
+                    .with_label_values(&labels[..]),
+            ),
+            Some(
+                metrics
+                    .metrics
+                    .task_exchange_checksum_mismatch_count
+                    .with_label_values(&labels[..]),
+            ),
         )
     } else {
         // no metrics to collect, no counter
-        None
+        (None, None)
     };
+    if let ExchangeSourceImpl::Grpc(source) = &mut source {
+        if let Some(counter) = checksum_mismatch_count {
+            source.set_checksum_mismatch_count(counter);
+        }
+    }
 
     loop {
         if let Some(res) = source.take_data().await? {
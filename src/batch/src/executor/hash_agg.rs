@@ -28,6 +28,7 @@ use risingwave_pb::batch_plan::HashAggNode;
 
 use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
+    ExplainNode,
 };
 use crate::task::{BatchTaskContext, TaskId};
 
@@ -189,6 +190,10 @@ impl<K: HashKey + Send + Sync> Executor for HashAggExecutor<K> {
     fn execute(self: Box<Self>) -> BoxedDataChunkStream {
         self.do_execute()
     }
+
+    fn explain(&self) -> ExplainNode {
+        ExplainNode::new(self.identity()).with_child_nodes(vec![self.child.explain()])
+    }
 }
 
 impl<K: HashKey + Send + Sync> HashAggExecutor<K> {
@@ -200,9 +205,9 @@ impl<K: HashKey + Send + Sync> HashAggExecutor<K> {
         // consume all chunks to compute the agg result
         #[for_await]
         for chunk in self.child.execute() {
-            let chunk = chunk?.compact();
-            let keys = K::build(self.group_key_columns.as_slice(), &chunk)?;
-            for (row_id, key) in keys.into_iter().enumerate() {
+            let chunk = chunk?;
+            let keys = K::build_visible(self.group_key_columns.as_slice(), &chunk)?;
+            for (row_id, key) in keys {
                 let states: &mut Vec<BoxedAggState> = groups.entry(key).or_insert_with(|| {
                     self.agg_factories
                         .iter()
@@ -266,6 +271,7 @@ impl<K: HashKey + Send + Sync> HashAggExecutor<K> {
 
 #[cfg(test)]
 mod tests {
+    use risingwave_common::array::Selection;
     use risingwave_common::catalog::{Field, Schema};
     use risingwave_common::test_prelude::DataChunkTestExt;
     use risingwave_pb::data::data_type::TypeName;
@@ -320,6 +326,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let agg_prost = HashAggNode {
@@ -388,6 +396,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let agg_prost = HashAggNode {
@@ -416,4 +426,70 @@ mod tests {
         );
         diff_executor_output(actual_exec, Box::new(expect_exec)).await;
     }
+
+    #[tokio::test]
+    async fn execute_sum_with_selection() {
+        let t32 = DataType::Int32;
+        let chunk = DataChunk::from_pretty(
+            "i
+             1
+             2
+             4
+             8
+             16",
+        )
+        .with_selection(Selection::new(vec![1, 3, 4], 5));
+        let src_exec = MockExecutor::with_chunk(
+            chunk,
+            Schema {
+                fields: vec![Field::unnamed(t32.clone())],
+            },
+        );
+
+        let agg_call = AggCall {
+            r#type: Type::Sum as i32,
+            args: vec![Arg {
+                input: Some(InputRefExpr { column_idx: 0 }),
+                r#type: Some(ProstDataType {
+                    type_name: TypeName::Int32 as i32,
+                    ..Default::default()
+                }),
+            }],
+            return_type: Some(ProstDataType {
+                type_name: TypeName::Int64 as i32,
+                ..Default::default()
+            }),
+            distinct: false,
+            order_by_fields: vec![],
+            filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
+        };
+
+        let agg_prost = HashAggNode {
+            group_key: vec![],
+            agg_calls: vec![agg_call],
+        };
+
+        let actual_exec = HashAggExecutorBuilder::deserialize(
+            &agg_prost,
+            Box::new(src_exec),
+            TaskId::default(),
+            "HashAggExecutor".to_string(),
+            CHUNK_SIZE,
+        )
+        .unwrap();
+        let schema = Schema {
+            fields: vec![Field::unnamed(t32)],
+        };
+
+        let expect_exec = MockExecutor::with_chunk(
+            DataChunk::from_pretty(
+                "I
+                 26",
+            ),
+            schema,
+        );
+        diff_executor_output(actual_exec, Box::new(expect_exec)).await;
+    }
 }
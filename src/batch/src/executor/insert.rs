@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
+
 use anyhow::Context;
 use futures::future::try_join_all;
 use futures_async_stream::try_stream;
@@ -29,7 +31,19 @@ use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
 };
 use crate::task::BatchTaskContext;
+
+/// The maximum number of chunks written to the table source but not yet taken by the associated
+/// streaming source executor. The DML channel is unbounded, so the insert waits for the oldest
+/// in-flight chunk to be taken before writing more, instead of buffering the whole input there.
+const MAX_IN_FLIGHT_CHUNKS: usize = 16;
+
 /// [`InsertExecutor`] implements table insertion with values from its child executor.
+///
+/// The child is consumed chunk by chunk, so `INSERT INTO ... SELECT` never materializes the whole
+/// query result. The insertion is not atomic: chunks are committed with whichever epoch the
+/// streaming source executor takes them in. If the insert is cancelled or fails, the chunks taken
+/// before that are still materialized, while the rest are never written. Readers only observe the
+/// chunks of epochs committed by barriers, never a part of a chunk.
 pub struct InsertExecutor {
     /// Target table id.
     table_id: TableId,
@@ -80,7 +94,8 @@ impl InsertExecutor {
         let source = source_desc.source.as_table().expect("not table source");
         let row_id_index = source_desc.row_id_index;
 
-        let mut notifiers = Vec::new();
+        let mut notifiers = VecDeque::new();
+        let mut rows_inserted = 0;
 
         #[for_await]
         for data_chunk in self.child.execute() {
@@ -101,11 +116,20 @@ impl InsertExecutor {
             let chunk = StreamChunk::new(vec![Op::Insert; len], columns, None);
 
             let notifier = source.write_chunk(chunk)?;
-            notifiers.push(notifier);
+            notifiers.push_back(notifier);
+
+            // Apply backpressure to the child when the streaming source executor falls behind.
+            if notifiers.len() > MAX_IN_FLIGHT_CHUNKS {
+                rows_inserted += notifiers
+                    .pop_front()
+                    .unwrap()
+                    .await
+                    .context("failed to wait chunk to be written")?;
+            }
         }
 
         // Wait for all chunks to be taken / written.
-        let rows_inserted = try_join_all(notifiers)
+        rows_inserted += try_join_all(notifiers)
             .await
             .context("failed to wait chunks to be written")?
             .into_iter()
@@ -151,6 +175,7 @@ impl BoxedExecutorBuilder for InsertExecutor {
 #[cfg(test)]
 mod tests {
     use std::ops::Bound;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
     use futures::StreamExt;
@@ -299,4 +324,119 @@ mod tests {
 
         Ok(())
     }
+
+    /// Generates `chunk_num` chunks of `chunk_size` rows lazily, unlike `MockExecutor` which holds
+    /// all of its chunks in memory.
+    struct GenExecutor {
+        chunk_size: usize,
+        chunk_num: usize,
+        generated: Arc<AtomicUsize>,
+        schema: Schema,
+    }
+
+    impl Executor for GenExecutor {
+        fn schema(&self) -> &Schema {
+            &self.schema
+        }
+
+        fn identity(&self) -> &str {
+            "GenExecutor"
+        }
+
+        fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+            self.do_execute()
+        }
+    }
+
+    impl GenExecutor {
+        #[try_stream(boxed, ok = DataChunk, error = RwError)]
+        async fn do_execute(self: Box<Self>) {
+            for i in 0..self.chunk_num {
+                let mut builder = I64ArrayBuilder::new(self.chunk_size);
+                for j in 0..self.chunk_size {
+                    builder.append(Some((i * self.chunk_size + j) as i64));
+                }
+                self.generated.fetch_add(1, Ordering::SeqCst);
+                yield DataChunk::new(vec![Column::from(builder.finish())], self.chunk_size);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_executor_backpressure() -> Result<()> {
+        const CHUNK_SIZE: usize = 1000;
+        const CHUNK_NUM: usize = 10_000;
+
+        let source_manager: TableSourceManagerRef = Arc::new(TableSourceManager::default());
+        let table_id = TableId::new(0);
+
+        // The table has a row id column besides the inserted one.
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int64); 2],
+        };
+        let source_desc = create_table_source_desc_builder(
+            &schema,
+            table_id,
+            Some(1),
+            vec![1],
+            source_manager.clone(),
+        )
+        .build()
+        .await?;
+        let source = source_desc.source.as_table().unwrap();
+        let mut reader = source.stream_reader(vec![0.into()]).await?.into_stream();
+
+        let generated = Arc::new(AtomicUsize::new(0));
+        let child = Box::new(GenExecutor {
+            chunk_size: CHUNK_SIZE,
+            chunk_num: CHUNK_NUM,
+            generated: generated.clone(),
+            schema: Schema {
+                fields: vec![Field::unnamed(DataType::Int64)],
+            },
+        });
+        let insert_executor = Box::new(InsertExecutor::new(
+            table_id,
+            source_manager.clone(),
+            child,
+            "InsertExecutor".to_string(),
+        ));
+        let mut stream = insert_executor.execute();
+
+        // Without a reader taking the chunks, the insert stops pulling from its child once the
+        // in-flight chunks reach the limit, and waits for the oldest one to be taken.
+        assert!(futures::poll!(stream.next()).is_pending());
+        assert_eq!(generated.load(Ordering::SeqCst), MAX_IN_FLIGHT_CHUNKS + 1);
+
+        let read = async {
+            let mut taken = 0;
+            let mut rows = 0;
+            while rows < CHUNK_SIZE * CHUNK_NUM {
+                let chunk = reader.next().await.unwrap()?.chunk;
+                assert_eq!(
+                    chunk.columns()[0].array_ref().as_int64().value_at(0),
+                    Some(rows as i64)
+                );
+                rows += chunk.cardinality();
+                taken += 1;
+                assert!(generated.load(Ordering::SeqCst) <= taken + MAX_IN_FLIGHT_CHUNKS + 1);
+            }
+            Ok::<_, RwError>(())
+        };
+        let (result, read_result) = futures::join!(stream.next(), read);
+        read_result?;
+        assert_eq!(
+            result
+                .unwrap()?
+                .column_at(0)
+                .array()
+                .as_int64()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![Some((CHUNK_SIZE * CHUNK_NUM) as i64)]
+        );
+        assert_eq!(generated.load(Ordering::SeqCst), CHUNK_NUM);
+
+        Ok(())
+    }
 }
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use fixedbitset::FixedBitSet;
 use futures_async_stream::try_stream;
 use itertools::{repeat_n, Itertools};
-use risingwave_common::array::{Array, DataChunk, RowRef};
+use risingwave_common::array::{Array, DataChunk, RowRef, Selection};
 use risingwave_common::buffer::{Bitmap, BitmapBuilder};
 use risingwave_common::catalog::Schema;
 use risingwave_common::error::{Result, RwError};
@@ -70,6 +70,9 @@ pub struct HashJoinExecutor<K> {
     null_matched: Vec<bool>,
     identity: String,
     chunk_size: usize,
+    /// Whether to select the rows passing the non-equi condition with a selection vector rather
+    /// than a visibility bitmap.
+    enable_selection_vector: bool,
     _phantom: PhantomData<K>,
 }
 
@@ -156,6 +159,7 @@ pub struct EquiJoinParams<K> {
     hash_map: JoinHashMap<K>,
     next_build_row_with_same_key: ChunkedData<Option<RowId>>,
     chunk_size: usize,
+    enable_selection_vector: bool,
 }
 
 impl<K> EquiJoinParams<K> {
@@ -181,6 +185,7 @@ impl<K> EquiJoinParams<K> {
             hash_map,
             next_build_row_with_same_key,
             chunk_size,
+            enable_selection_vector: false,
         }
     }
 }
@@ -261,6 +266,7 @@ impl<K: HashKey> HashJoinExecutor<K> {
             hash_map,
             next_build_row_with_same_key,
             chunk_size: self.chunk_size,
+            enable_selection_vector: self.enable_selection_vector,
         };
 
         if let Some(cond) = self.cond.as_ref() {
@@ -334,8 +340,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 for build_row_id in
                     next_build_row_with_same_key.row_id_iter(hash_map.get(probe_key).copied())
                 {
@@ -362,11 +368,19 @@ impl<K: HashKey> HashJoinExecutor<K> {
         params: EquiJoinParams<K>,
         cond: &BoxedExpression,
     ) {
+        let enable_selection_vector = params.enable_selection_vector;
         #[for_await]
         for chunk in Self::do_inner_join(params) {
-            let mut chunk = chunk?;
-            chunk.set_visibility(cond.eval(&chunk)?.as_bool().iter().collect());
-            yield chunk
+            let chunk = chunk?;
+            let cond_result = cond.eval(&chunk)?;
+            let cond_result = cond_result.as_bool();
+            let selection = enable_selection_vector
+                .then(|| Selection::from_bool_array(cond_result))
+                .flatten();
+            yield match selection {
+                Some(selection) => chunk.with_selection(selection),
+                None => chunk.with_visibility(cond_result.iter().collect()),
+            }
         }
     }
 
@@ -388,8 +402,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 if let Some(first_matched_build_row_id) = hash_map.get(probe_key) {
                     for build_row_id in
                         next_build_row_with_same_key.row_id_iter(Some(*first_matched_build_row_id))
@@ -447,8 +461,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 non_equi_state.found_matched = false;
                 non_equi_state
                     .first_output_row_id
@@ -512,8 +526,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 if !ANTI_JOIN {
                     if hash_map.get(probe_key).is_some() {
                         if let Some(spilled) = Self::append_one_probe_row(
@@ -558,8 +572,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 non_equi_state
                     .first_output_row_id
                     .push(chunk_builder.buffered_count());
@@ -617,8 +631,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 non_equi_state.found_matched = false;
                 if let Some(first_matched_build_row_id) = hash_map.get(probe_key) {
                     non_equi_state
@@ -688,8 +702,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 for build_row_id in
                     next_build_row_with_same_key.row_id_iter(hash_map.get(probe_key).copied())
                 {
@@ -744,8 +758,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 for build_row_id in
                     next_build_row_with_same_key.row_id_iter(hash_map.get(probe_key).copied())
                 {
@@ -805,8 +819,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for probe_key in &probe_keys {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for (_, probe_key) in &probe_keys {
                 for build_row_id in
                     next_build_row_with_same_key.row_id_iter(hash_map.get(probe_key).copied())
                 {
@@ -851,8 +865,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 for build_row_id in
                     next_build_row_with_same_key.row_id_iter(hash_map.get(probe_key).copied())
                 {
@@ -913,8 +927,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 if let Some(first_matched_build_row_id) = hash_map.get(probe_key) {
                     for build_row_id in
                         next_build_row_with_same_key.row_id_iter(Some(*first_matched_build_row_id))
@@ -986,8 +1000,8 @@ impl<K: HashKey> HashJoinExecutor<K> {
         #[for_await]
         for probe_chunk in probe_side.execute() {
             let probe_chunk = probe_chunk?;
-            let probe_keys = K::build(&probe_key_idxs, &probe_chunk)?;
-            for (probe_row_id, probe_key) in probe_keys.iter().enumerate() {
+            let probe_keys = K::build_visible(&probe_key_idxs, &probe_chunk)?;
+            for &(probe_row_id, ref probe_key) in &probe_keys {
                 left_non_equi_state.found_matched = false;
                 if let Some(first_matched_build_row_id) = hash_map.get(probe_key) {
                     left_non_equi_state
@@ -1668,6 +1682,12 @@ impl BoxedExecutorBuilder for HashJoinExecutor<()> {
             identity: context.plan_node().get_identity().clone(),
             right_key_types,
             chunk_size: context.context.get_config().developer.batch_chunk_size,
+            enable_selection_vector: context
+                .context
+                .get_config()
+                .developer
+                .batch_hash_join_enable_selection_vector
+                && context.accepts_selection(),
         }
         .dispatch())
     }
@@ -1685,6 +1705,7 @@ struct HashJoinExecutorArgs {
     identity: String,
     right_key_types: Vec<DataType>,
     chunk_size: usize,
+    enable_selection_vector: bool,
 }
 
 impl HashKeyDispatcher for HashJoinExecutorArgs {
@@ -1702,6 +1723,7 @@ impl HashKeyDispatcher for HashJoinExecutorArgs {
             self.cond,
             self.identity,
             self.chunk_size,
+            self.enable_selection_vector,
         ))
     }
 
@@ -1723,6 +1745,7 @@ impl<K> HashJoinExecutor<K> {
         cond: Option<BoxedExpression>,
         identity: String,
         chunk_size: usize,
+        enable_selection_vector: bool,
     ) -> Self {
         assert_eq!(probe_key_idxs.len(), build_key_idxs.len());
         assert_eq!(probe_key_idxs.len(), null_matched.len());
@@ -1756,6 +1779,7 @@ impl<K> HashJoinExecutor<K> {
             cond,
             identity,
             chunk_size,
+            enable_selection_vector,
             _phantom: PhantomData,
         }
     }
@@ -1869,7 +1893,9 @@ mod tests {
             }
         }
 
-        fn create_left_executor(&self) -> BoxedExecutor {
+        /// The chunks have invisible holes, which are selected out by visibility bitmaps, or by
+        /// selection vectors with `with_selection`.
+        fn create_left_executor(&self, with_selection: bool) -> BoxedExecutor {
             let schema = Schema {
                 fields: vec![
                     Field::unnamed(DataType::Int32),
@@ -1878,23 +1904,32 @@ mod tests {
             };
             let mut executor = MockExecutor::new(schema);
 
-            executor.add(DataChunk::from_pretty(
-                "i f
-                 1 6.1
-                 2 .
-                 . 8.4
-                 3 3.9
-                 . .  ",
-            ));
-
-            executor.add(DataChunk::from_pretty(
-                "i f
-                 4 6.6
-                 3 .
-                 . 0.7
-                 5 .
-                 . 5.5",
-            ));
+            let chunks = [
+                DataChunk::from_pretty(
+                    "i f
+                     1 6.1
+                     2 .
+                     . 8.4
+                     3 3.9
+                     . .  ",
+                ),
+                DataChunk::from_pretty(
+                    "i f
+                     4 6.6
+                     3 .
+                     . 0.7
+                     5 .
+                     . 5.5",
+                ),
+            ];
+            for chunk in chunks {
+                let chunk = chunk.with_invisible_holes();
+                if with_selection {
+                    executor.add(chunk.into_selection_vis());
+                } else {
+                    executor.add(chunk);
+                }
+            }
 
             Box::new(executor)
         }
@@ -1970,10 +2005,15 @@ mod tests {
             .unwrap()
         }
 
-        fn create_join_executor(&self, has_non_equi_cond: bool, null_safe: bool) -> BoxedExecutor {
+        fn create_join_executor(
+            &self,
+            has_non_equi_cond: bool,
+            null_safe: bool,
+            with_selection: bool,
+        ) -> BoxedExecutor {
             let join_type = self.join_type;
 
-            let left_child = self.create_left_executor();
+            let left_child = self.create_left_executor(with_selection);
             let right_child = self.create_right_executor();
 
             let output_indices = (0..match join_type {
@@ -2000,11 +2040,27 @@ mod tests {
                 cond,
                 "HashJoinExecutor".to_string(),
                 CHUNK_SIZE,
+                with_selection,
             ))
         }
 
+        /// Checks the result with both visibility bitmaps and selection vectors.
         async fn do_test(&self, expected: DataChunk, has_non_equi_cond: bool, null_safe: bool) {
-            let join_executor = self.create_join_executor(has_non_equi_cond, null_safe);
+            for with_selection in [false, true] {
+                self.do_test_inner(&expected, has_non_equi_cond, null_safe, with_selection)
+                    .await;
+            }
+        }
+
+        async fn do_test_inner(
+            &self,
+            expected: &DataChunk,
+            has_non_equi_cond: bool,
+            null_safe: bool,
+            with_selection: bool,
+        ) {
+            let join_executor =
+                self.create_join_executor(has_non_equi_cond, null_safe, with_selection);
 
             let mut data_chunk_merger = DataChunkMerger::new(self.output_data_types()).unwrap();
 
@@ -2033,7 +2089,7 @@ mod tests {
 
             // TODO: Replace this with unsorted comparison
             // assert_eq!(expected, result_chunk);
-            assert!(is_data_chunk_eq(&expected, &result_chunk));
+            assert!(is_data_chunk_eq(expected, &result_chunk));
         }
     }
 
@@ -787,6 +787,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor".into(),
             CHUNK_SIZE,
+            usize::MAX,
         ))
     }
 
@@ -128,7 +128,7 @@ fn concatenate(left: &DataChunk, right: &DataChunk) -> Result<DataChunk> {
     let vis = match (left.vis(), right.vis()) {
         (Vis::Compact(_), _) => right.vis().clone(),
         (_, Vis::Compact(_)) => left.vis().clone(),
-        (Vis::Bitmap(_), Vis::Bitmap(_)) => {
+        _ => {
             return Err(BatchError::UnsupportedFunction(
                 "The concatenate behaviour of two chunk with visibility is undefined".to_string(),
             )
@@ -30,6 +30,7 @@ use risingwave_pb::batch_plan::plan_node::NodeBody;
 use crate::executor::join::{concatenate, convert_row_to_chunk, JoinType};
 use crate::executor::{
     BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder,
+    ExplainNode,
 };
 use crate::task::BatchTaskContext;
 
@@ -58,7 +59,8 @@ pub struct NestedLoopJoinExecutor {
     right_child: BoxedExecutor,
     /// Identity string of the executor
     identity: String,
-    /// The maximum size of the chunk produced by executor at a time.
+    /// The size of the chunks produced by executor. All the chunks except the last one have
+    /// exactly this many rows, regardless of the size of the input chunks.
     chunk_size: usize,
 }
 
@@ -74,6 +76,11 @@ impl Executor for NestedLoopJoinExecutor {
     fn execute(self: Box<Self>) -> BoxedDataChunkStream {
         self.do_execute()
     }
+
+    fn explain(&self) -> ExplainNode {
+        ExplainNode::new(self.identity())
+            .with_child_nodes(vec![self.left_child.explain(), self.right_child.explain()])
+    }
 }
 
 impl NestedLoopJoinExecutor {
@@ -207,6 +214,14 @@ impl NestedLoopJoinExecutor {
             chunk_size,
         }
     }
+
+    /// Overrides the size of the output chunks, which defaults to the `chunk_size` passed to
+    /// [`NestedLoopJoinExecutor::new`].
+    pub fn with_output_chunk_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "output chunk size must be positive");
+        self.chunk_size = size;
+        self
+    }
 }
 
 impl NestedLoopJoinExecutor {
@@ -469,6 +484,7 @@ impl NestedLoopJoinExecutor {
 }
 #[cfg(test)]
 mod tests {
+    use futures::TryStreamExt;
     use risingwave_common::array::*;
     use risingwave_common::catalog::{Field, Schema};
     use risingwave_common::types::DataType;
@@ -479,7 +495,7 @@ mod tests {
     use crate::executor::join::nested_loop_join::NestedLoopJoinExecutor;
     use crate::executor::join::JoinType;
     use crate::executor::test_utils::{diff_executor_output, MockExecutor};
-    use crate::executor::BoxedExecutor;
+    use crate::executor::{BoxedExecutor, Executor};
 
     const CHUNK_SIZE: usize = 1024;
 
@@ -578,6 +594,10 @@ mod tests {
         }
 
         fn create_join_executor(&self) -> BoxedExecutor {
+            Box::new(self.create_nested_loop_join_executor())
+        }
+
+        fn create_nested_loop_join_executor(&self) -> NestedLoopJoinExecutor {
             let join_type = self.join_type;
 
             let left_child = self.create_left_executor();
@@ -589,7 +609,7 @@ mod tests {
                 _ => vec![0, 1, 2, 3],
             };
 
-            Box::new(NestedLoopJoinExecutor::new(
+            NestedLoopJoinExecutor::new(
                 new_binary_expr(
                     Type::Equal,
                     DataType::Boolean,
@@ -603,7 +623,7 @@ mod tests {
                 right_child,
                 "NestedLoopJoinExecutor".into(),
                 CHUNK_SIZE,
-            ))
+            )
         }
 
         async fn do_test(&self, expected: DataChunk) {
@@ -767,4 +787,39 @@ mod tests {
 
         test_fixture.do_test(expected_chunk).await;
     }
+
+    #[tokio::test]
+    async fn test_output_chunk_size() {
+        for join_type in [JoinType::Inner, JoinType::LeftOuter, JoinType::FullOuter] {
+            let test_fixture = TestFixture::with_join_type(join_type);
+
+            let expected = test_fixture
+                .create_join_executor()
+                .execute()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            let expected_rows: usize = expected.iter().map(|chunk| chunk.cardinality()).sum();
+
+            let join_executor = test_fixture
+                .create_nested_loop_join_executor()
+                .with_output_chunk_size(1);
+            let chunks = Box::new(join_executor)
+                .execute()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(chunks.len(), expected_rows);
+            for chunk in &chunks {
+                assert_eq!(chunk.cardinality(), 1);
+            }
+            let rows = |chunks: &[DataChunk]| {
+                chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.rows().map(|row| row.to_owned_row()))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(rows(&chunks), rows(&expected));
+        }
+    }
 }
@@ -67,12 +67,14 @@ impl LimitExecutor {
         let mut skipped = 0;
         // the number of rows have been returned as execute result
         let mut returned = 0;
+        if self.limit == 0 {
+            return Ok(());
+        }
 
+        // Stop pulling from the child as soon as the limit is reached, so that a small limit only
+        // costs the chunks it needs.
         #[for_await]
         for data_chunk in self.child.execute() {
-            if returned == self.limit {
-                break;
-            }
             let data_chunk = data_chunk?;
             let cardinality = data_chunk.cardinality();
             if cardinality + skipped <= self.offset {
@@ -83,6 +85,9 @@ impl LimitExecutor {
             if skipped == self.offset && cardinality + returned <= self.limit {
                 returned += cardinality;
                 yield data_chunk;
+                if returned == self.limit {
+                    break;
+                }
                 continue;
             }
             // process chunk
@@ -111,6 +116,9 @@ impl LimitExecutor {
             yield data_chunk
                 .with_visibility(new_vis.into_iter().collect())
                 .compact();
+            if returned == self.limit {
+                break;
+            }
         }
     }
 }
@@ -143,6 +151,7 @@ impl LimitExecutor {
 #[cfg(test)]
 mod tests {
 
+    use std::sync::atomic::Ordering;
     use std::vec;
 
     use futures_async_stream::for_await;
@@ -152,7 +161,7 @@ mod tests {
     use risingwave_common::types::DataType;
 
     use super::*;
-    use crate::executor::test_utils::MockExecutor;
+    use crate::executor::test_utils::{CountingExecutor, MockExecutor};
 
     fn create_column(vec: &[Option<i32>]) -> Column {
         PrimitiveArray::from_slice(vec).into()
@@ -392,4 +401,36 @@ mod tests {
             test_limit_with_visibility(tot_row, 2, 2, 2, visibility).await;
         }
     }
+
+    #[tokio::test]
+    async fn test_limit_executor_early_exit() {
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int32)],
+        };
+        // 10 chunks of 3 rows, (limit, offset, expected chunks pulled).
+        for (limit, offset, expected) in [(0, 0, 0), (1, 0, 1), (3, 0, 1), (4, 0, 2), (4, 2, 2)] {
+            let mut mock = MockExecutor::new(schema.clone());
+            for i in 0..10 {
+                mock.add(DataChunk::new(
+                    vec![create_column(&[Some(i), Some(i), Some(i)])],
+                    3,
+                ));
+            }
+            let counting = CountingExecutor::new(Box::new(mock));
+            let pulled = counting.pulled();
+            let limit_executor = Box::new(LimitExecutor::new(
+                Box::new(counting),
+                limit,
+                offset,
+                "limit".to_string(),
+            ));
+            let mut rows = 0;
+            #[for_await]
+            for chunk in limit_executor.execute() {
+                rows += chunk.unwrap().cardinality();
+            }
+            assert_eq!(rows, limit);
+            assert_eq!(pulled.load(Ordering::Relaxed), expected);
+        }
+    }
 }
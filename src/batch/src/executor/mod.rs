@@ -14,6 +14,8 @@
 use anyhow::anyhow;
 mod delete;
 mod expand;
+mod explain;
+mod external_sort;
 mod filter;
 mod generic_exchange;
 mod group_top_n;
@@ -42,6 +44,7 @@ mod values;
 use async_recursion::async_recursion;
 pub use delete::*;
 pub use expand::*;
+pub use explain::*;
 pub use filter::*;
 use futures::stream::BoxStream;
 pub use generic_exchange::*;
@@ -96,6 +99,13 @@ pub trait Executor: Send + 'static {
     ///
     /// The implementation should guaranteed that each `DataChunk`'s cardinality is not zero.
     fn execute(self: Box<Self>) -> BoxedDataChunkStream;
+
+    /// Returns the operator-level plan tree rooted at this executor.
+    ///
+    /// By default, the executor is explained as a leaf named by its identity.
+    fn explain(&self) -> ExplainNode {
+        ExplainNode::new(self.identity())
+    }
 }
 
 impl std::fmt::Debug for BoxedExecutor {
@@ -119,6 +129,8 @@ pub struct ExecutorBuilder<'a, C> {
     pub task_id: &'a TaskId,
     context: C,
     epoch: u64,
+    /// Whether the parent executor can consume chunks whose visibility is a selection vector.
+    accepts_selection: bool,
 }
 
 macro_rules! build_executor {
@@ -140,6 +152,7 @@ impl<'a, C: Clone> ExecutorBuilder<'a, C> {
             task_id,
             context,
             epoch,
+            accepts_selection: false,
         }
     }
 
@@ -159,6 +172,25 @@ impl<'a, C: Clone> ExecutorBuilder<'a, C> {
     pub fn epoch(&self) -> u64 {
         self.epoch
     }
+
+    /// Returns whether the executor built here may yield chunks with selection vectors, i.e., its
+    /// parent iterates them without assuming the chunks are compact.
+    pub fn accepts_selection(&self) -> bool {
+        self.accepts_selection
+    }
+
+    /// Returns whether the `child_idx`-th child of this plan node may yield chunks with selection
+    /// vectors.
+    fn child_accepts_selection(&self, child_idx: usize) -> bool {
+        match self.plan_node.get_node_body() {
+            Ok(NodeBody::HashAgg(_)) => true,
+            // Only the probe side, as the build side is compacted anyway.
+            Ok(NodeBody::HashJoin(_)) => child_idx == 0,
+            // The project keeps the visibility of its input chunks as is.
+            Ok(NodeBody::Project(_)) => self.accepts_selection,
+            _ => false,
+        }
+    }
 }
 
 impl<'a, C: BatchTaskContext> ExecutorBuilder<'a, C> {
@@ -176,8 +208,10 @@ impl<'a, C: BatchTaskContext> ExecutorBuilder<'a, C> {
     #[async_recursion]
     async fn try_build(&self) -> Result<BoxedExecutor> {
         let mut inputs = Vec::with_capacity(self.plan_node.children.len());
-        for input_node in &self.plan_node.children {
-            let input = self.clone_for_plan(input_node).build().await?;
+        for (child_idx, input_node) in self.plan_node.children.iter().enumerate() {
+            let mut child_builder = self.clone_for_plan(input_node);
+            child_builder.accepts_selection = self.child_accepts_selection(child_idx);
+            let input = child_builder.build().await?;
             inputs.push(input);
         }
 
@@ -32,6 +32,7 @@ macro_rules! for_all_task_metrics {
             { task_scheduled_duration, GenericGaugeVec<AtomicF64> },
             { task_slow_poll_duration, GenericGaugeVec<AtomicF64> },
             { task_exchange_recv_row_number, GenericCounterVec<AtomicU64> },
+            { task_exchange_checksum_mismatch_count, GenericCounterVec<AtomicU64> },
             { task_row_seq_scan_next_duration, HistogramVec },
         }
     };
@@ -53,7 +54,7 @@ impl BatchTaskMetrics {
     /// The created [`BatchTaskMetrics`] is already registered to the `registry`.
     pub fn new(registry: Registry) -> Self {
         let task_labels = vec!["query_id", "stage_id", "task_id"];
-        let mut descs = Vec::with_capacity(8);
+        let mut descs = Vec::with_capacity(9);
 
         let task_first_poll_delay = GaugeVec::new(opts!(
             "batch_task_first_poll_delay",
@@ -128,6 +129,21 @@ impl BatchTaskMetrics {
         .unwrap();
         descs.extend(task_exchange_recv_row_number.desc().into_iter().cloned());
 
+        let task_exchange_checksum_mismatch_count = IntCounterVec::new(
+            opts!(
+                "batch_task_exchange_checksum_mismatch_count",
+                "Total number of chunks received from upstream source with mismatched checksum",
+            ),
+            &custom_labels,
+        )
+        .unwrap();
+        descs.extend(
+            task_exchange_checksum_mismatch_count
+                .desc()
+                .into_iter()
+                .cloned(),
+        );
+
         let mut custom_labels = task_labels.clone();
         custom_labels.extend_from_slice(&["executor_id"]);
         let task_row_seq_scan_next_duration = HistogramVec::new(
@@ -150,6 +166,7 @@ impl BatchTaskMetrics {
             task_scheduled_duration,
             task_slow_poll_duration,
             task_exchange_recv_row_number,
+            task_exchange_checksum_mismatch_count,
             task_row_seq_scan_next_duration,
         };
         registry.register(Box::new(metrics.clone())).unwrap();
@@ -168,7 +185,7 @@ impl Collector for BatchTaskMetrics {
     }
 
     fn collect(&self) -> Vec<proto::MetricFamily> {
-        let mut mfs = Vec::with_capacity(8);
+        let mut mfs = Vec::with_capacity(9);
 
         // The collected data will be cleared immediately to avoid unbounded memory usage.
         // Note that if data is inserted between `collect` and `reset`, it will be lost, though the
@@ -12,17 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
+
 use futures_async_stream::try_stream;
+use itertools::Itertools;
 use risingwave_common::array::DataChunk;
 use risingwave_common::catalog::Schema;
+use risingwave_common::collection::estimate_size::EstimateSize;
 use risingwave_common::error::{Result, RwError};
-use risingwave_common::util::chunk_coalesce::DataChunkBuilder;
 use risingwave_common::util::encoding_for_comparison::encode_chunk;
 use risingwave_common::util::sort_util::OrderPair;
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 
+use super::external_sort::{sort_chunks, RunMerger, SortRun, SpilledRunWriter};
 use super::{BoxedDataChunkStream, BoxedExecutor, BoxedExecutorBuilder, Executor, ExecutorBuilder};
-use crate::task::BatchTaskContext;
+use crate::task::{BatchTaskContext, MemoryContextRef, MemoryReservation};
 
 /// Order By Executor
 ///
@@ -31,14 +35,33 @@ use crate::task::BatchTaskContext;
 /// 2. Serialize each row into memcomparable format
 /// 3. Sort the serialized rows by quicksort
 /// 4. Build and yield data chunks according to the row order
+///
+/// The buffered data is reserved from the node's `memory_context`. Once it can't be reserved, the
+/// buffered rows are sorted and spilled to a temporary file as a sorted run. After all input is
+/// consumed, the runs are merged with a loser tree to produce the output.
+///
+/// Each spilled run keeps a temporary file open until it's merged. To bound the number of open
+/// files, a run has at least [`MIN_SPILL_RUN_ROWS`] rows, and every [`MAX_MERGE_FAN_IN`] runs of
+/// the same pass are merged into a larger one, so the runs are merged in multiple passes.
 pub struct OrderByExecutor {
     child: BoxedExecutor,
     order_pairs: Vec<OrderPair>,
     identity: String,
     schema: Schema,
     chunk_size: usize,
+    /// Memory context shared with other executors, from which the input rows are buffered.
+    memory_context: MemoryContextRef,
+    min_spill_run_rows: usize,
+    max_merge_fan_in: usize,
 }
 
+/// The minimum number of rows in a spilled run. The rows are buffered beyond the memory budget
+/// until then, so that a contended or zero budget doesn't spill every input chunk as a run.
+const MIN_SPILL_RUN_ROWS: usize = 8192;
+
+/// The maximum number of runs merged at once.
+const MAX_MERGE_FAN_IN: usize = 64;
+
 impl Executor for OrderByExecutor {
     fn schema(&self) -> &Schema {
         &self.schema
@@ -76,6 +99,7 @@ impl BoxedExecutorBuilder for OrderByExecutor {
             order_pairs,
             source.plan_node().get_identity().clone(),
             source.context.get_config().developer.batch_chunk_size,
+            source.context.memory_context(),
         )))
     }
 }
@@ -83,35 +107,92 @@ impl BoxedExecutorBuilder for OrderByExecutor {
 impl OrderByExecutor {
     #[try_stream(boxed, ok = DataChunk, error = RwError)]
     async fn do_execute(self: Box<Self>) {
-        let mut chunk_builder = DataChunkBuilder::new(self.schema.data_types(), self.chunk_size);
+        let data_types = self.schema.data_types();
         let mut chunks = Vec::new();
-        let mut encoded_rows = Vec::new();
+        let mut keys = Vec::new();
+        let mut buffered_rows = 0;
+        let mut reservation = MemoryReservation::new(self.memory_context.clone());
+        // The spilled runs in the input order, each with the number of merge passes it went
+        // through.
+        let mut runs: Vec<(usize, SortRun)> = Vec::new();
 
         #[for_await]
         for chunk in self.child.execute() {
-            chunks.push(chunk?.compact());
-        }
-
-        for chunk in &chunks {
-            let encoded_chunk = encode_chunk(chunk, &self.order_pairs);
-            encoded_rows.extend(
-                encoded_chunk
-                    .into_iter()
-                    .enumerate()
-                    .map(|(row_id, row)| (chunk.row_at_unchecked_vis(row_id), row)),
-            );
+            let chunk = chunk?.compact();
+            let encoded_chunk = encode_chunk(&chunk, &self.order_pairs);
+            let size = chunk.estimated_heap_size() + encoded_chunk.estimated_heap_size();
+            buffered_rows += chunk.cardinality();
+            chunks.push(chunk);
+            keys.push(encoded_chunk);
+
+            if !reservation.try_grow(size) && buffered_rows >= self.min_spill_run_rows {
+                let mut writer = SpilledRunWriter::new()?;
+                for sorted in sort_chunks(
+                    &chunks,
+                    std::mem::take(&mut keys),
+                    data_types.clone(),
+                    self.chunk_size,
+                ) {
+                    writer.write_chunk(&sorted)?;
+                }
+                runs.push((0, writer.finish()?));
+                chunks.clear();
+                buffered_rows = 0;
+                reservation.free();
+
+                // Merge the trailing runs of the same pass once there are enough of them. The
+                // merged runs are adjacent, so the merge stays stable.
+                while let Some((pass, _)) = runs.last() {
+                    let pass = *pass;
+                    let same_pass = runs.iter().rev().take_while(|(p, _)| *p == pass).count();
+                    if same_pass < self.max_merge_fan_in {
+                        break;
+                    }
+                    let same_pass_runs = runs
+                        .split_off(runs.len() - same_pass)
+                        .into_iter()
+                        .map(|(_, run)| run)
+                        .collect();
+                    let merged = RunMerger::new(
+                        same_pass_runs,
+                        self.order_pairs.clone(),
+                        data_types.clone(),
+                        self.chunk_size,
+                    )?
+                    .spill()?;
+                    runs.push((pass + 1, merged));
+                }
+            }
         }
 
-        encoded_rows.sort_unstable_by(|(_, a), (_, b)| a.cmp(b));
+        let sorted = sort_chunks(&chunks, keys, data_types.clone(), self.chunk_size);
+        drop(chunks);
 
-        for (row, _) in encoded_rows {
-            if let Some(spilled) = chunk_builder.append_one_row_ref(row) {
-                yield spilled
+        // Everything fits in memory.
+        if runs.is_empty() {
+            for chunk in sorted {
+                yield chunk;
             }
+            return Ok(());
+        }
+
+        let mut runs = runs.into_iter().map(|(_, run)| run).collect_vec();
+        runs.push(SortRun::InMemory(VecDeque::from(sorted)));
+        while runs.len() > self.max_merge_fan_in {
+            let merged = RunMerger::new(
+                runs.split_off(runs.len() - self.max_merge_fan_in),
+                self.order_pairs.clone(),
+                data_types.clone(),
+                self.chunk_size,
+            )?
+            .spill()?;
+            runs.push(merged);
         }
 
-        if let Some(spilled) = chunk_builder.consume_all() {
-            yield spilled
+        let mut merger =
+            RunMerger::new(runs, self.order_pairs.clone(), data_types, self.chunk_size)?;
+        while let Some(chunk) = merger.next_chunk()? {
+            yield chunk;
         }
     }
 }
@@ -122,6 +203,7 @@ impl OrderByExecutor {
         order_pairs: Vec<OrderPair>,
         identity: String,
         chunk_size: usize,
+        memory_context: MemoryContextRef,
     ) -> Self {
         let schema = child.schema().clone();
         Self {
@@ -130,8 +212,19 @@ impl OrderByExecutor {
             identity,
             schema,
             chunk_size,
+            memory_context,
+            min_spill_run_rows: MIN_SPILL_RUN_ROWS,
+            max_merge_fan_in: MAX_MERGE_FAN_IN,
         }
     }
+
+    #[cfg(test)]
+    fn with_spill_limits(mut self, min_spill_run_rows: usize, max_merge_fan_in: usize) -> Self {
+        assert!(max_merge_fan_in >= 2);
+        self.min_spill_run_rows = min_spill_run_rows;
+        self.max_merge_fan_in = max_merge_fan_in;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -149,9 +242,12 @@ mod tests {
     use risingwave_common::util::sort_util::OrderType;
 
     use super::*;
-    use crate::executor::test_utils::MockExecutor;
+    use crate::executor::test_utils::{gen_data, MockExecutor};
+    use crate::task::MemoryContext;
 
     const CHUNK_SIZE: usize = 1024;
+    const MEMORY_BUDGET: usize = usize::MAX;
+    const SPILL_LIMITS: (usize, usize) = (MIN_SPILL_RUN_ROWS, MAX_MERGE_FAN_IN);
 
     #[tokio::test]
     async fn test_simple_order_by_executor() {
@@ -184,6 +280,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor2".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Int32);
@@ -233,6 +330,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor2".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Float32);
@@ -282,6 +380,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor2".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
         let fields = &order_by_executor.schema().fields;
         assert_eq!(fields[0].data_type, DataType::Varchar);
@@ -356,6 +455,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
 
         let mut stream = order_by_executor.execute();
@@ -429,6 +529,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
 
         let mut stream = order_by_executor.execute();
@@ -522,6 +623,7 @@ mod tests {
             order_pairs,
             "OrderByExecutor".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
 
         let mut stream = order_by_executor.execute();
@@ -695,10 +797,149 @@ mod tests {
             order_pairs,
             "OrderByExecutor".to_string(),
             CHUNK_SIZE,
+            Arc::new(MemoryContext::new(MEMORY_BUDGET)),
         ));
 
         let mut stream = order_by_executor.execute();
         let res = stream.next().await;
         assert_eq!(res.unwrap().unwrap(), output_chunk)
     }
+
+    async fn collect_order_by_output(
+        input: Vec<DataChunk>,
+        schema: Schema,
+        order_pairs: Vec<OrderPair>,
+        memory_budget: usize,
+        (min_spill_run_rows, max_merge_fan_in): (usize, usize),
+    ) -> Vec<Row> {
+        let mut mock_executor = MockExecutor::new(schema);
+        for chunk in input {
+            mock_executor.add(chunk);
+        }
+        let memory_context = Arc::new(MemoryContext::new(memory_budget));
+        let order_by_executor = Box::new(
+            OrderByExecutor::new(
+                Box::new(mock_executor),
+                order_pairs,
+                "OrderByExecutor".to_string(),
+                CHUNK_SIZE,
+                memory_context.clone(),
+            )
+            .with_spill_limits(min_spill_run_rows, max_merge_fan_in),
+        );
+
+        let mut rows = vec![];
+        let mut stream = order_by_executor.execute();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.cardinality() <= CHUNK_SIZE);
+            rows.extend(chunk.rows().map(|row| row.to_owned_row()));
+        }
+        drop(stream);
+        // All the buffered memory is given back once the executor is done.
+        assert_eq!(memory_context.used(), 0);
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_order_by_executor_spill() {
+        let data_types = vec![DataType::Int64, DataType::Varchar, DataType::Float64];
+        let schema = Schema {
+            fields: data_types.iter().cloned().map(Field::unnamed).collect(),
+        };
+        // Random data with duplicated rows.
+        let input = gen_data(100, 30, &data_types);
+        let total_rows = input.iter().map(|c| c.cardinality()).sum::<usize>();
+
+        for order_pairs in [
+            vec![OrderPair::new(0, OrderType::Ascending)],
+            vec![
+                OrderPair::new(1, OrderType::Descending),
+                OrderPair::new(0, OrderType::Ascending),
+            ],
+            vec![
+                OrderPair::new(2, OrderType::Descending),
+                OrderPair::new(1, OrderType::Ascending),
+                OrderPair::new(0, OrderType::Descending),
+            ],
+        ] {
+            let expected = collect_order_by_output(
+                input.clone(),
+                schema.clone(),
+                order_pairs.clone(),
+                MEMORY_BUDGET,
+                SPILL_LIMITS,
+            )
+            .await;
+            assert_eq!(expected.len(), total_rows);
+
+            // A small budget to spill every few chunks, and a zero budget to spill every chunk.
+            // With tiny runs and fan-in, the runs are merged in multiple passes.
+            for memory_budget in [4096, 0] {
+                for spill_limits in [(1, 2), (1, 3), (100, 4), SPILL_LIMITS] {
+                    let actual = collect_order_by_output(
+                        input.clone(),
+                        schema.clone(),
+                        order_pairs.clone(),
+                        memory_budget,
+                        spill_limits,
+                    )
+                    .await;
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_by_executor_spill_with_nulls() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int32),
+                Field::unnamed(DataType::Int32),
+            ],
+        };
+        let input = vec![
+            DataChunk::from_pretty(
+                "i i
+                 3 .
+                 . 1
+                 1 2",
+            ),
+            DataChunk::from_pretty(
+                "i i
+                 . .
+                 2 2
+                 3 1",
+            ),
+            DataChunk::from_pretty(
+                "i i
+                 1 .
+                 . 3",
+            ),
+        ];
+        for order_pairs in [
+            vec![
+                OrderPair::new(0, OrderType::Ascending),
+                OrderPair::new(1, OrderType::Descending),
+            ],
+            vec![
+                OrderPair::new(0, OrderType::Descending),
+                OrderPair::new(1, OrderType::Ascending),
+            ],
+        ] {
+            let expected = collect_order_by_output(
+                input.clone(),
+                schema.clone(),
+                order_pairs.clone(),
+                MEMORY_BUDGET,
+                SPILL_LIMITS,
+            )
+            .await;
+            let actual =
+                collect_order_by_output(input.clone(), schema.clone(), order_pairs, 0, (1, 2))
+                    .await;
+            assert_eq!(actual, expected);
+        }
+    }
 }
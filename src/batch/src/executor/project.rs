@@ -18,7 +18,7 @@ use risingwave_common::array::column::Column;
 use risingwave_common::array::DataChunk;
 use risingwave_common::catalog::{Field, Schema};
 use risingwave_common::error::{Result, RwError};
-use risingwave_expr::expr::{build_from_prost, BoxedExpression};
+use risingwave_expr::expr::{build_optimized_from_prost, BoxedExpression};
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 
 use crate::executor::{
@@ -48,6 +48,19 @@ impl Executor for ProjectExecutor {
 }
 
 impl ProjectExecutor {
+    pub fn new(expr: Vec<BoxedExpression>, child: BoxedExecutor, identity: String) -> Self {
+        let fields = expr
+            .iter()
+            .map(|expr| Field::unnamed(expr.return_type()))
+            .collect::<Vec<Field>>();
+        Self {
+            expr,
+            child,
+            schema: Schema { fields },
+            identity,
+        }
+    }
+
     #[try_stream(boxed, ok = DataChunk, error = RwError)]
     async fn do_execute(mut self: Box<Self>) {
         #[for_await]
@@ -82,27 +95,21 @@ impl BoxedExecutorBuilder for ProjectExecutor {
         let project_exprs: Vec<_> = project_node
             .get_select_list()
             .iter()
-            .map(build_from_prost)
+            .map(build_optimized_from_prost)
             .try_collect()?;
 
-        let fields = project_exprs
-            .iter()
-            .map(|expr| Field::unnamed(expr.return_type()))
-            .collect::<Vec<Field>>();
-
-        Ok(Box::new(Self {
-            expr: project_exprs,
+        Ok(Box::new(Self::new(
+            project_exprs,
             child,
-            schema: Schema { fields },
-            identity: source.plan_node().get_identity().clone(),
-        }))
+            source.plan_node().get_identity().clone(),
+        )))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use futures::stream::StreamExt;
-    use risingwave_common::array::{Array, I32Array};
+    use risingwave_common::array::{Array, I32Array, Selection};
     use risingwave_common::catalog::{Field, Schema};
     use risingwave_common::test_prelude::*;
     use risingwave_common::types::DataType;
@@ -165,6 +172,40 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_project_with_selection() {
+        let chunk = DataChunk::from_pretty(
+            "i
+             1
+             2
+             3
+             4",
+        );
+        let selection = Selection::new(vec![1, 3], chunk.capacity());
+        let chunk = chunk.with_selection(selection.clone());
+
+        let mut mock_executor = MockExecutor::new(schema_unnamed!(DataType::Int32));
+        mock_executor.add(chunk);
+        let proj_executor = Box::new(ProjectExecutor {
+            expr: vec![Box::new(InputRefExpression::new(DataType::Int32, 0))],
+            child: Box::new(mock_executor),
+            schema: schema_unnamed!(DataType::Int32),
+            identity: "ProjectExecutor".to_string(),
+        });
+
+        let mut stream = proj_executor.execute();
+        let result_chunk = stream.next().await.unwrap().unwrap();
+        assert_eq!(result_chunk.selection(), Some(&selection));
+        assert_eq!(
+            result_chunk.compact(),
+            DataChunk::from_pretty(
+                "i
+                 2
+                 4"
+            )
+        );
+    }
+
     #[tokio::test]
     async fn test_project_dummy_chunk() {
         let literal = LiteralExpression::new(DataType::Int32, Some(1_i32.into()));
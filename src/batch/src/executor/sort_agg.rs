@@ -341,6 +341,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let count_star = AggStateFactory::new(&prost)?.create_agg_state();
@@ -435,6 +437,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let count_star = AggStateFactory::new(&prost)?.create_agg_state();
@@ -564,6 +568,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let sum_agg = AggStateFactory::new(&prost)?.create_agg_state();
@@ -649,6 +655,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let sum_agg = AggStateFactory::new(&prost)?.create_agg_state();
@@ -773,6 +781,8 @@ mod tests {
             distinct: false,
             order_by_fields: vec![],
             filter: None,
+            approx_relative_error: 0.0,
+            approx_percentile_fraction: 0.0,
         };
 
         let sum_agg = AggStateFactory::new(&prost)?.create_agg_state();
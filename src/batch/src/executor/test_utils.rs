@@ -14,6 +14,8 @@
 
 use std::collections::VecDeque;
 use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use assert_matches::assert_matches;
 use futures_async_stream::{for_await, try_stream};
@@ -29,7 +31,7 @@ use risingwave_pb::batch_plan::ExchangeSource as ProstExchangeSource;
 
 use crate::exchange_source::{ExchangeSource, ExchangeSourceImpl};
 use crate::executor::{
-    BoxedDataChunkStream, BoxedExecutor, CreateSource, Executor, LookupExecutorBuilder,
+    BoxedDataChunkStream, BoxedExecutor, CreateSource, Executor, ExplainNode, LookupExecutorBuilder,
 };
 use crate::task::{BatchTaskContext, TaskId};
 
@@ -160,6 +162,15 @@ impl Executor for MockExecutor {
     fn execute(self: Box<Self>) -> BoxedDataChunkStream {
         self.do_execute()
     }
+
+    fn explain(&self) -> ExplainNode {
+        let rows = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.cardinality() as u64)
+            .sum();
+        ExplainNode::new(self.identity()).with_estimated_rows(rows)
+    }
 }
 
 impl MockExecutor {
@@ -171,6 +182,52 @@ impl MockExecutor {
     }
 }
 
+/// Wraps an executor and counts the chunks pulled from it, so that tests and benchmarks can check
+/// how much input the parent executor actually consumes.
+pub struct CountingExecutor {
+    child: BoxedExecutor,
+    pulled: Arc<AtomicUsize>,
+}
+
+impl CountingExecutor {
+    pub fn new(child: BoxedExecutor) -> Self {
+        Self {
+            child,
+            pulled: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// The number of chunks pulled from the child, shared with the executor after it is boxed.
+    pub fn pulled(&self) -> Arc<AtomicUsize> {
+        self.pulled.clone()
+    }
+}
+
+impl Executor for CountingExecutor {
+    fn schema(&self) -> &Schema {
+        self.child.schema()
+    }
+
+    fn identity(&self) -> &str {
+        self.child.identity()
+    }
+
+    fn execute(self: Box<Self>) -> BoxedDataChunkStream {
+        self.do_execute()
+    }
+}
+
+impl CountingExecutor {
+    #[try_stream(boxed, ok = DataChunk, error = RwError)]
+    async fn do_execute(self: Box<Self>) {
+        #[for_await]
+        for data_chunk in self.child.execute() {
+            self.pulled.fetch_add(1, Ordering::Relaxed);
+            yield data_chunk?;
+        }
+    }
+}
+
 /// if the input from two child executor is same(considering order),
 /// it will also check the columns structure of chunks from child executor
 /// use for executor unit test.
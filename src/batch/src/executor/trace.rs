@@ -20,7 +20,7 @@ use risingwave_common::catalog::Schema;
 use risingwave_common::error::RwError;
 use tracing::event;
 
-use crate::executor::{BoxedDataChunkStream, BoxedExecutor, Executor};
+use crate::executor::{BoxedDataChunkStream, BoxedExecutor, Executor, ExplainNode};
 
 /// If tracing is enabled, we build a [`TraceExecutor`] on top of the underlying executor.
 /// So the duration of performance-critical operations will be traced, such as open/next/close.
@@ -48,6 +48,10 @@ impl Executor for TraceExecutor {
     fn execute(self: Box<Self>) -> BoxedDataChunkStream {
         self.do_execute()
     }
+
+    fn explain(&self) -> ExplainNode {
+        self.child.explain()
+    }
 }
 
 impl TraceExecutor {
@@ -36,7 +36,7 @@ pub struct ValuesExecutor {
 }
 
 impl ValuesExecutor {
-    pub(crate) fn new(
+    pub fn new(
         rows: Vec<Vec<BoxedExpression>>,
         schema: Schema,
         identity: String,
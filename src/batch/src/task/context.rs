@@ -22,7 +22,7 @@ use risingwave_storage::StateStoreImpl;
 
 use super::TaskId;
 use crate::executor::BatchTaskMetricsWithTaskLabels;
-use crate::task::{BatchEnvironment, TaskOutput, TaskOutputId};
+use crate::task::{BatchEnvironment, MemoryContextRef, TaskOutput, TaskOutputId};
 
 /// Context for batch task execution.
 ///
@@ -53,6 +53,9 @@ pub trait BatchTaskContext: Clone + Send + Sync + 'static {
 
     /// Get config for batch environment
     fn get_config(&self) -> &BatchConfig;
+
+    /// Get the memory context shared by all batch tasks on this node.
+    fn memory_context(&self) -> MemoryContextRef;
 }
 
 /// Batch task context on compute node.
@@ -97,6 +100,10 @@ impl BatchTaskContext for ComputeNodeContext {
     fn get_config(&self) -> &BatchConfig {
         self.env.config()
     }
+
+    fn memory_context(&self) -> MemoryContextRef {
+        self.env.memory_context()
+    }
 }
 
 impl ComputeNodeContext {
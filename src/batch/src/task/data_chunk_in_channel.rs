@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use risingwave_common::array::checksum::ChunkChecksumExt;
 use risingwave_common::array::DataChunk;
 use risingwave_pb::data::DataChunk as ProstDataChunk;
 use tokio::sync::OnceCell;
@@ -32,12 +33,18 @@ impl DataChunkInChannel {
         }
     }
 
-    pub async fn to_protobuf(&self) -> ProstDataChunk {
+    /// Encode the data chunk, with the checksum attached if `checksum` is set. The encoded chunk is
+    /// cached for the other outputs, which share the same `checksum` from the config.
+    pub async fn to_protobuf(&self, checksum: bool) -> ProstDataChunk {
         let prost_data_chunk = self
             .prost_data_chunk
             .get_or_init(|| async {
                 let res = self.data_chunk.clone().compact();
-                res.to_protobuf()
+                let mut prost = res.to_protobuf();
+                if checksum {
+                    prost.set_checksum();
+                }
+                prost
             })
             .await;
         prost_data_chunk.clone()
@@ -21,7 +21,7 @@ use risingwave_source::{TableSourceManager, TableSourceManagerRef};
 use risingwave_storage::StateStoreImpl;
 
 use crate::executor::BatchTaskMetrics;
-use crate::task::BatchManager;
+use crate::task::{BatchManager, MemoryContext, MemoryContextRef};
 
 pub(crate) type WorkerNodeId = u32;
 
@@ -52,6 +52,9 @@ pub struct BatchEnvironment {
 
     /// Compute client pool for grpc exchange.
     client_pool: ComputeClientPoolRef,
+
+    /// Memory shared by the buffering executors of all tasks on this node.
+    memory_context: MemoryContextRef,
 }
 
 impl BatchEnvironment {
@@ -66,6 +69,7 @@ impl BatchEnvironment {
         task_metrics: Arc<BatchTaskMetrics>,
         client_pool: ComputeClientPoolRef,
     ) -> Self {
+        let memory_context = Arc::new(MemoryContext::new(config.sort_memory_budget_mb << 20));
         BatchEnvironment {
             server_addr,
             task_manager,
@@ -75,6 +79,7 @@ impl BatchEnvironment {
             state_store,
             task_metrics,
             client_pool,
+            memory_context,
         }
     }
 
@@ -84,17 +89,20 @@ impl BatchEnvironment {
         use risingwave_rpc_client::ComputeClientPool;
         use risingwave_storage::monitor::StateStoreMetrics;
 
+        let config = BatchConfig::default();
+        let memory_context = Arc::new(MemoryContext::new(config.sort_memory_budget_mb << 20));
         BatchEnvironment {
             task_manager: Arc::new(BatchManager::new(None)),
             server_addr: "127.0.0.1:5688".parse().unwrap(),
             source_manager: std::sync::Arc::new(TableSourceManager::default()),
-            config: Arc::new(BatchConfig::default()),
+            config: Arc::new(config),
             worker_id: WorkerNodeId::default(),
             state_store: StateStoreImpl::shared_in_memory_store(Arc::new(
                 StateStoreMetrics::unused(),
             )),
             task_metrics: Arc::new(BatchTaskMetrics::for_test()),
             client_pool: Arc::new(ComputeClientPool::default()),
+            memory_context,
         }
     }
 
@@ -133,4 +141,8 @@ impl BatchEnvironment {
     pub fn client_pool(&self) -> ComputeClientPoolRef {
         self.client_pool.clone()
     }
+
+    pub fn memory_context(&self) -> MemoryContextRef {
+        self.memory_context.clone()
+    }
 }
@@ -0,0 +1,120 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+pub type MemoryContextRef = Arc<MemoryContext>;
+
+/// Tracks the memory buffered by batch executors on a node against a shared limit, so that
+/// concurrent queries cannot together exceed it.
+#[derive(Debug)]
+pub struct MemoryContext {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryContext {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Reserves `bytes` if it fits in the remaining budget. Returns `false` without reserving
+    /// anything otherwise.
+    fn try_reserve(&self, bytes: usize) -> bool {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|&used| used <= self.limit)
+            })
+            .is_ok()
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+/// The memory reserved by one executor from a [`MemoryContext`]. Everything it holds is released
+/// back to the context when it's dropped, including when the query is cancelled.
+pub struct MemoryReservation {
+    context: MemoryContextRef,
+    size: usize,
+}
+
+impl MemoryReservation {
+    pub fn new(context: MemoryContextRef) -> Self {
+        Self { context, size: 0 }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Grows the reservation by `bytes`. Returns `false` and leaves the reservation unchanged if
+    /// the context doesn't have enough memory left.
+    pub fn try_grow(&mut self, bytes: usize) -> bool {
+        let reserved = self.context.try_reserve(bytes);
+        if reserved {
+            self.size += bytes;
+        }
+        reserved
+    }
+
+    /// Releases everything held by the reservation, e.g. after the buffered data is spilled.
+    pub fn free(&mut self) {
+        self.context.release(std::mem::take(&mut self.size));
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_memory_context() {
+        let context = Arc::new(MemoryContext::new(100));
+        let mut a = MemoryReservation::new(context.clone());
+        let mut b = MemoryReservation::new(context.clone());
+
+        assert!(a.try_grow(60));
+        assert!(!b.try_grow(60));
+        assert_eq!(b.size(), 0);
+        assert!(b.try_grow(40));
+        assert_eq!(context.used(), 100);
+
+        a.free();
+        assert_eq!(context.used(), 40);
+        assert!(b.try_grow(60));
+
+        drop(b);
+        assert_eq!(context.used(), 0);
+    }
+}
@@ -109,6 +109,8 @@ pub struct TaskOutput {
     receiver: ChanReceiverImpl,
     output_id: TaskOutputId,
     failure: Arc<Mutex<Option<RwError>>>,
+    /// Whether to attach the checksums to the chunks, see `batch_exchange_checksum`.
+    checksum: bool,
 }
 
 impl TaskOutput {
@@ -134,7 +136,7 @@ impl TaskOutput {
                         self.output_id,
                         chunk.cardinality()
                     );
-                    let pb = chunk.to_protobuf().await;
+                    let pb = chunk.to_protobuf(self.checksum).await;
                     let resp = GetDataResponse {
                         status: Default::default(),
                         record_batch: Some(pb),
@@ -501,6 +503,7 @@ impl<C: BatchTaskContext> BatchTaskExecution<C> {
             receiver,
             output_id: output_id.try_into()?,
             failure: self.failure.clone(),
+            checksum: self.context.get_config().developer.batch_exchange_checksum,
         };
         Ok(task_output)
     }
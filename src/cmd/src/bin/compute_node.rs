@@ -25,10 +25,10 @@ fn main() {
 
     let opts = risingwave_compute::ComputeNodeOpts::parse();
 
-    risingwave_rt::init_risingwave_logger(risingwave_rt::LoggerSettings::new(
-        opts.enable_jaeger_tracing,
-        false,
-    ));
+    risingwave_rt::init_risingwave_logger(
+        risingwave_rt::LoggerSettings::new(opts.enable_jaeger_tracing, false)
+            .with_otlp_endpoint("compute", opts.otlp_endpoint.clone()),
+    );
 
     risingwave_rt::main_okk(risingwave_compute::start(opts))
 }
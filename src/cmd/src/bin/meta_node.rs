@@ -25,7 +25,10 @@ fn main() {
 
     let opts = risingwave_meta::MetaNodeOpts::parse();
 
-    risingwave_rt::init_risingwave_logger(risingwave_rt::LoggerSettings::new_default());
+    risingwave_rt::init_risingwave_logger(
+        risingwave_rt::LoggerSettings::new_default()
+            .with_otlp_endpoint("meta", opts.otlp_endpoint.clone()),
+    );
 
     risingwave_rt::main_okk(risingwave_meta::start(opts))
 }
@@ -42,10 +42,10 @@ fn main() -> Result<()> {
 
                 let opts = risingwave_compute::ComputeNodeOpts::parse_from(args);
 
-                risingwave_rt::init_risingwave_logger(risingwave_rt::LoggerSettings::new(
-                    opts.enable_jaeger_tracing,
-                    false,
-                ));
+                risingwave_rt::init_risingwave_logger(
+                    risingwave_rt::LoggerSettings::new(opts.enable_jaeger_tracing, false)
+                        .with_otlp_endpoint("compute", opts.otlp_endpoint.clone()),
+                );
 
                 risingwave_rt::main_okk(risingwave_compute::start(opts));
 
@@ -63,7 +63,10 @@ fn main() -> Result<()> {
 
                 let opts = risingwave_meta::MetaNodeOpts::parse_from(args);
 
-                risingwave_rt::init_risingwave_logger(risingwave_rt::LoggerSettings::new_default());
+                risingwave_rt::init_risingwave_logger(
+                    risingwave_rt::LoggerSettings::new_default()
+                        .with_otlp_endpoint("meta", opts.otlp_endpoint.clone()),
+                );
 
                 risingwave_rt::main_okk(risingwave_meta::start(opts));
 
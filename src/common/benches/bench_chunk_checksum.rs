@@ -0,0 +1,67 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use risingwave_common::array::checksum::ChunkChecksumExt;
+use risingwave_common::array::column::Column;
+use risingwave_common::array::{ArrayBuilder, I64ArrayBuilder, Op, StreamChunk, Utf8ArrayBuilder};
+
+fn build_chunk(size: usize) -> StreamChunk {
+    let mut ints = I64ArrayBuilder::new(size);
+    let mut strs = Utf8ArrayBuilder::new(size);
+    for i in 0..size {
+        ints.append(Some(i as i64));
+        strs.append(Some(format!("value-{}", i).as_str()));
+    }
+    let columns = vec![
+        Column::new(Arc::new(ints.finish().into())),
+        Column::new(Arc::new(strs.finish().into())),
+    ];
+    StreamChunk::new(vec![Op::Insert; size], columns, None)
+}
+
+/// Compares the cost of encoding a chunk for the exchange with and without the checksum, and of
+/// verifying the checksum on the receiver.
+fn bench_chunk_checksum(c: &mut Criterion) {
+    for size in [256, 1024, 4096] {
+        let chunk = build_chunk(size);
+        c.bench_with_input(BenchmarkId::new("to_protobuf", size), &chunk, |b, chunk| {
+            b.iter(|| chunk.to_protobuf())
+        });
+        c.bench_with_input(
+            BenchmarkId::new("to_protobuf_with_checksum", size),
+            &chunk,
+            |b, chunk| {
+                b.iter(|| {
+                    let mut prost = chunk.to_protobuf();
+                    prost.set_checksum();
+                    prost
+                })
+            },
+        );
+
+        let mut prost = chunk.to_protobuf();
+        prost.set_checksum();
+        c.bench_with_input(
+            BenchmarkId::new("verify_checksum", size),
+            &prost,
+            |b, prost| b.iter(|| prost.verify_checksum().unwrap()),
+        );
+    }
+}
+
+criterion_group!(benches, bench_chunk_checksum);
+criterion_main!(benches);
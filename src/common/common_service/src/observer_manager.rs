@@ -53,6 +53,8 @@ pub struct ObserverManager<T: NotificationClient, S: ObserverState> {
     rx: T::Channel,
     client: T,
     observer_states: S,
+    /// See [`ObserverManager::with_idle_timeout`].
+    idle_timeout: Option<Duration>,
 }
 
 pub trait ObserverState: Send + 'static {
@@ -62,6 +64,10 @@ pub trait ObserverState: Send + 'static {
 
     /// Initialize data from the meta. It will be called at start or resubscribe
     fn handle_initialization_notification(&mut self, resp: SubscribeResponse) -> Result<()>;
+
+    /// Called when the notification stream from the meta is broken, before resubscribing. The
+    /// data received so far is kept until the next `handle_initialization_notification`.
+    fn handle_connection_lost(&mut self) {}
 }
 
 impl<S: ObserverState> ObserverManager<RpcNotificationClient, S> {
@@ -85,13 +91,48 @@ where
             rx,
             client,
             observer_states,
+            idle_timeout: None,
+        }
+    }
+
+    /// Treat the notification stream as broken if nothing is received within `timeout`, and
+    /// resubscribe. The meta pushes notifications to the frontend on every barrier, so an idle
+    /// stream means the meta is likely unreachable even if the connection is not closed.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    async fn message(&mut self) -> std::result::Result<Option<SubscribeResponse>, Status> {
+        match self.idle_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, self.rx.message())
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Status::deadline_exceeded(format!(
+                        "no notification received in {:?}",
+                        timeout
+                    )))
+                }),
+            None => self.rx.message().await,
+        }
+    }
+
+    async fn subscribe(&self) -> Result<T::Channel> {
+        let subscribe = self.client.subscribe(S::SubscribeType::subscribe_type());
+        match self.idle_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, subscribe)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(ErrorCode::InternalError("subscribe timed out".to_string()).into())
+                }),
+            None => subscribe.await,
         }
     }
 
     /// `start` is used to spawn a new asynchronous task which receives meta's notification and
     /// call the `handle_initialization_notification` and `handle_notification` to update node data.
     pub async fn start(mut self) -> Result<JoinHandle<()>> {
-        let first_resp = self.rx.message().await?.ok_or_else(|| {
+        let first_resp = self.message().await?.ok_or_else(|| {
             ErrorCode::InternalError(
                 "ObserverManager start failed, Stream of notification terminated at the start."
                     .to_string(),
@@ -101,10 +142,11 @@ where
             .handle_initialization_notification(first_resp)?;
         let handle = tokio::spawn(async move {
             loop {
-                match self.rx.message().await {
+                match self.message().await {
                     Ok(resp) => {
                         if resp.is_none() {
                             tracing::error!("Stream of notification terminated.");
+                            self.observer_states.handle_connection_lost();
                             self.re_subscribe().await;
                             continue;
                         }
@@ -112,6 +154,7 @@ where
                     }
                     Err(e) => {
                         tracing::error!("Receives meta's notification err {:?}", e);
+                        self.observer_states.handle_connection_lost();
                         self.re_subscribe().await;
                     }
                 }
@@ -123,19 +166,26 @@ where
     /// `re_subscribe` is used to re-subscribe to the meta's notification.
     async fn re_subscribe(&mut self) {
         loop {
-            match self
-                .client
-                .subscribe(S::SubscribeType::subscribe_type())
-                .await
-            {
+            match self.subscribe().await {
                 Ok(rx) => {
                     tracing::debug!("re-subscribe success");
                     self.rx = rx;
-                    if let Ok(Some(snapshot_resp)) = self.rx.message().await {
-                        self.observer_states
+                    if let Ok(Some(snapshot_resp)) = self.message().await {
+                        match self
+                            .observer_states
                             .handle_initialization_notification(snapshot_resp)
-                            .expect("handle snapshot notification failed after re-subscribe");
-                        break;
+                        {
+                            Ok(()) => break,
+                            // The snapshot may be rejected, e.g. it's older than the local data.
+                            // Retry until the meta catches up.
+                            Err(e) => {
+                                tracing::warn!(
+                                    "failed to handle snapshot after re-subscribe: {}",
+                                    e
+                                );
+                                tokio::time::sleep(RE_SUBSCRIBE_RETRY_INTERVAL).await;
+                            }
+                        }
                     }
                 }
                 Err(_) => {
@@ -0,0 +1,157 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checksums of the chunks sent by the exchange, verified by the receiver to detect the corruption
+//! between nodes.
+
+use std::hash::Hasher;
+
+use prost::Message;
+use risingwave_pb::data::{
+    ChunkChecksum, Column as ProstColumn, DataChunk as ProstDataChunk,
+    StreamChunk as ProstStreamChunk,
+};
+use thiserror::Error;
+use twox_hash::XxHash64;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("chunk checksum mismatch: expected {expected:#018x}, actual {actual:#018x}")]
+pub struct ChunkChecksumMismatch {
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// The xxhash64 of the ops and the encoded columns of a chunk.
+fn xxhash64(ops: &[i32], columns: &[ProstColumn]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    for op in ops {
+        hasher.write_i32(*op);
+    }
+    let mut buf = Vec::new();
+    for column in columns {
+        buf.clear();
+        column
+            .encode(&mut buf)
+            .expect("encoding into a vec never fails");
+        hasher.write(&buf);
+    }
+    hasher.finish()
+}
+
+fn verify(
+    checksum: &Option<ChunkChecksum>,
+    actual: impl FnOnce() -> u64,
+) -> Result<(), ChunkChecksumMismatch> {
+    let Some(ChunkChecksum { xxhash64: expected }) = *checksum else {
+        return Ok(());
+    };
+    let actual = actual();
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(ChunkChecksumMismatch { expected, actual })
+    }
+}
+
+/// A chunk in protobuf that can carry a [`ChunkChecksum`].
+pub trait ChunkChecksumExt {
+    /// Attach the checksum of the chunk.
+    fn set_checksum(&mut self);
+
+    /// Verify the checksum of the chunk. A chunk without a checksum always passes, so that the
+    /// receiver costs nothing if the sender doesn't attach them.
+    fn verify_checksum(&self) -> Result<(), ChunkChecksumMismatch>;
+}
+
+impl ChunkChecksumExt for ProstStreamChunk {
+    fn set_checksum(&mut self) {
+        self.checksum = Some(ChunkChecksum {
+            xxhash64: xxhash64(&self.ops, &self.columns),
+        });
+    }
+
+    fn verify_checksum(&self) -> Result<(), ChunkChecksumMismatch> {
+        verify(&self.checksum, || xxhash64(&self.ops, &self.columns))
+    }
+}
+
+impl ChunkChecksumExt for ProstDataChunk {
+    fn set_checksum(&mut self) {
+        self.checksum = Some(ChunkChecksum {
+            xxhash64: xxhash64(&[], &self.columns),
+        });
+    }
+
+    fn verify_checksum(&self) -> Result<(), ChunkChecksumMismatch> {
+        verify(&self.checksum, || xxhash64(&[], &self.columns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{DataChunk, DataChunkTestExt, StreamChunk, StreamChunkTestExt};
+
+    /// Encode the chunk as if sent to another node, with the first byte of the given value buffer
+    /// flipped on the wire.
+    fn corrupt<M: Message + Default>(chunk: &M, value: &[u8]) -> M {
+        let mut bytes = chunk.encode_to_vec();
+        let pos = bytes
+            .windows(value.len())
+            .position(|window| window == value)
+            .unwrap();
+        bytes[pos] ^= 0xff;
+        M::decode(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_stream_chunk_checksum() {
+        let chunk = StreamChunk::from_pretty(
+            " I
+            + 1
+            - 81985529216486895",
+        );
+        let mut prost = chunk.to_protobuf();
+        assert!(prost.checksum.is_none());
+        prost.set_checksum();
+        prost.verify_checksum().unwrap();
+
+        // 81985529216486895 is 0x0123456789abcdef, encoded in big endian.
+        let value = 0x0123456789abcdefi64.to_be_bytes();
+        let corrupted = corrupt(&prost, &value);
+        let err = corrupted.verify_checksum().unwrap_err();
+        assert_eq!(err.expected, prost.checksum.unwrap().xxhash64);
+        assert_ne!(err.actual, err.expected);
+
+        // The corruption goes undetected without the checksum.
+        let corrupted = corrupt(&chunk.to_protobuf(), &value);
+        corrupted.verify_checksum().unwrap();
+        assert_ne!(StreamChunk::from_protobuf(&corrupted).unwrap(), chunk);
+    }
+
+    #[test]
+    fn test_data_chunk_checksum() {
+        let chunk = DataChunk::from_pretty(
+            "I
+             1
+             81985529216486895",
+        );
+        let mut prost = chunk.to_protobuf();
+        prost.set_checksum();
+        prost.verify_checksum().unwrap();
+
+        let corrupted = corrupt(&prost, &0x0123456789abcdefi64.to_be_bytes());
+        corrupted.verify_checksum().unwrap_err();
+    }
+}
@@ -19,6 +19,7 @@ use risingwave_pb::data::Column as ProstColumn;
 
 use super::{Array, ArrayError, ArrayResult, PrimitiveArray};
 use crate::array::{ArrayImpl, ArrayRef};
+use crate::collection::estimate_size::EstimateSize;
 
 /// Column is owned by `DataChunk`. It consists of logic data type and physical array
 /// implementation.
@@ -27,6 +28,12 @@ pub struct Column {
     array: ArrayRef,
 }
 
+impl EstimateSize for Column {
+    fn estimated_heap_size(&self) -> usize {
+        std::mem::size_of::<ArrayImpl>() + self.array.estimated_heap_size()
+    }
+}
+
 impl Column {
     pub fn new(array: ArrayRef) -> Column {
         Column { array }
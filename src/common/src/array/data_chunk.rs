@@ -23,8 +23,9 @@ use risingwave_pb::data::DataChunk as ProstDataChunk;
 use super::ArrayResult;
 use crate::array::column::Column;
 use crate::array::data_chunk_iter::{Row, RowRef};
-use crate::array::{ArrayBuilderImpl, StructValue};
+use crate::array::{ArrayBuilderImpl, Selection, StructValue};
 use crate::buffer::{Bitmap, BitmapBuilder};
+use crate::collection::estimate_size::EstimateSize;
 use crate::hash::HashCode;
 use crate::types::struct_type::StructType;
 use crate::types::{DataType, Datum, NaiveDateTimeWrapper, ToOwnedDatum};
@@ -40,11 +41,14 @@ pub struct DataChunk {
 }
 
 /// `Vis` is a visibility bitmap of rows. When all rows are visible, it is considered compact and
-/// is represented by a single cardinality number rather than that many of ones.
+/// is represented by a single cardinality number rather than that many of ones. The visible rows
+/// can also be represented by a [`Selection`] of their indices, which is cheaper to iterate at low
+/// selectivity.
 #[derive(Clone, PartialEq, Debug)]
 pub enum Vis {
     Bitmap(Bitmap),
     Compact(usize), // equivalent to all ones of this size
+    Selection(Selection),
 }
 
 impl From<Bitmap> for Vis {
@@ -59,11 +63,18 @@ impl From<usize> for Vis {
     }
 }
 
+impl From<Selection> for Vis {
+    fn from(s: Selection) -> Self {
+        Vis::Selection(s)
+    }
+}
+
 impl From<&Vis> for Vis {
     fn from(vis: &Vis) -> Self {
         match vis {
             Vis::Bitmap(b) => b.clone().into(),
             Vis::Compact(c) => (*c).into(),
+            Vis::Selection(s) => s.clone().into(),
         }
     }
 }
@@ -73,6 +84,7 @@ impl Vis {
         match self {
             Vis::Bitmap(b) => b.is_empty(),
             Vis::Compact(c) => *c == 0,
+            Vis::Selection(s) => s.capacity() == 0,
         }
     }
 
@@ -80,6 +92,7 @@ impl Vis {
         match self {
             Vis::Bitmap(b) => b.len(),
             Vis::Compact(c) => *c,
+            Vis::Selection(s) => s.capacity(),
         }
     }
 
@@ -92,6 +105,7 @@ impl Vis {
                 assert!(idx <= *c);
                 true
             }
+            Vis::Selection(s) => s.is_set(idx),
         }
     }
 
@@ -100,19 +114,45 @@ impl Vis {
         match self {
             Vis::Bitmap(b) => b.iter(),
             Vis::Compact(c) => iter::repeat(true).take(*c),
+            Vis::Selection(s) => s.bitmap().iter(),
+        }
+    }
+
+    /// Returns the indices of the visible rows in ascending order.
+    #[auto_enum(Iterator)]
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        match self {
+            Vis::Bitmap(b) => b.iter().positions(|v| v),
+            Vis::Compact(c) => 0..*c,
+            Vis::Selection(s) => s.iter(),
         }
     }
 }
 
+impl EstimateSize for Vis {
+    fn estimated_heap_size(&self) -> usize {
+        match self {
+            Vis::Bitmap(bitmap) => bitmap.estimated_heap_size(),
+            Vis::Compact(_) => 0,
+            Vis::Selection(selection) => selection.estimated_heap_size(),
+        }
+    }
+}
+
+/// The estimated size only counts the buffers of the chunk, which is much cheaper than encoding
+/// it and is precise enough for memory accounting.
+impl EstimateSize for DataChunk {
+    fn estimated_heap_size(&self) -> usize {
+        self.columns.estimated_heap_size() + self.vis2.estimated_heap_size()
+    }
+}
+
 impl DataChunk {
     /// Create a `DataChunk` with `columns` and visibility. The visibility can either be a `Bitmap`
     /// or a simple cardinality number.
     pub fn new<V: Into<Vis>>(columns: Vec<Column>, vis: V) -> Self {
         let vis = vis.into();
-        let capacity = match &vis {
-            Vis::Bitmap(b) => b.len(),
-            Vis::Compact(c) => *c,
-        };
+        let capacity = vis.len();
         for column in &columns {
             assert_eq!(capacity, column.array_ref().len());
         }
@@ -159,6 +199,7 @@ impl DataChunk {
                     None
                 }
             }
+            Vis::Selection(selection) => selection.next_set(row_idx),
         }
     }
 
@@ -175,15 +216,13 @@ impl DataChunk {
         match &self.vis2 {
             Vis::Bitmap(b) => b.num_high_bits(),
             Vis::Compact(len) => *len,
+            Vis::Selection(s) => s.len(),
         }
     }
 
     /// `capacity` returns physical length of any chunk column
     pub fn capacity(&self) -> usize {
-        match &self.vis2 {
-            Vis::Bitmap(b) => b.len(),
-            Vis::Compact(len) => *len,
-        }
+        self.vis2.len()
     }
 
     pub fn vis(&self) -> &Vis {
@@ -194,6 +233,39 @@ impl DataChunk {
         DataChunk::new(self.columns.clone(), visibility)
     }
 
+    pub fn with_selection(&self, selection: Selection) -> Self {
+        DataChunk::new(self.columns.clone(), selection)
+    }
+
+    /// Returns the selection of the visible rows if they are represented by one.
+    pub fn selection(&self) -> Option<&Selection> {
+        match &self.vis2 {
+            Vis::Selection(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Converts the selection of the visible rows to the equivalent visibility bitmap, for the
+    /// operators that don't understand selections, e.g. across exchanges.
+    pub fn into_bitmap_vis(self) -> Self {
+        match self.vis2 {
+            Vis::Selection(s) => DataChunk::new(self.columns, s.bitmap().clone()),
+            _ => self,
+        }
+    }
+
+    /// Converts the visibility bitmap to the equivalent selection of the visible rows if the chunk
+    /// is short enough to be represented by one.
+    pub fn into_selection_vis(self) -> Self {
+        match &self.vis2 {
+            Vis::Bitmap(b) => match Selection::from_bitmap(b) {
+                Some(selection) => DataChunk::new(self.columns, selection),
+                None => self,
+            },
+            _ => self,
+        }
+    }
+
     pub fn visibility(&self) -> Option<&Bitmap> {
         self.get_visibility_ref()
     }
@@ -202,6 +274,7 @@ impl DataChunk {
         match &self.vis2 {
             Vis::Bitmap(b) => Some(b),
             Vis::Compact(_) => None,
+            Vis::Selection(s) => Some(s.bitmap()),
         }
     }
 
@@ -228,6 +301,7 @@ impl DataChunk {
         let mut proto = ProstDataChunk {
             cardinality: self.cardinality() as u32,
             columns: Default::default(),
+            checksum: None,
         };
         let column_ref = &mut proto.columns;
         for arr in &self.columns {
@@ -254,6 +328,21 @@ impl DataChunk {
                     .collect::<Vec<_>>();
                 Self::new(columns, cardinality)
             }
+            Vis::Selection(selection) => {
+                let columns = self
+                    .columns
+                    .iter()
+                    .map(|col| {
+                        let array = col.array_ref();
+                        let mut builder = array.create_builder(selection.len());
+                        for idx in selection.iter() {
+                            builder.append_array_element(array, idx);
+                        }
+                        builder.finish().into()
+                    })
+                    .collect::<Vec<_>>();
+                Self::new(columns, selection.len())
+            }
         }
     }
 
@@ -376,11 +465,7 @@ impl DataChunk {
     /// * bool - whether this tuple is visible
     pub fn row_at(&self, pos: usize) -> (RowRef<'_>, bool) {
         let row = self.row_at_unchecked_vis(pos);
-        let vis = match &self.vis2 {
-            Vis::Bitmap(bitmap) => bitmap.is_set(pos),
-            Vis::Compact(_) => true,
-        };
-        (row, vis)
+        (row, self.vis2.is_set(pos))
     }
 
     /// Random access a tuple in a data chunk. Return in a row format.
@@ -458,6 +543,17 @@ impl DataChunk {
     /// vec<u8>
     pub fn serialize(&self) -> Vec<Vec<u8>> {
         match &self.vis2 {
+            Vis::Selection(selection) => {
+                let mut buffers = vec![vec![]; selection.capacity()];
+                for c in &self.columns {
+                    let c = c.array_ref();
+                    assert_eq!(c.len(), selection.capacity());
+                    for i in selection.iter() {
+                        serialize_datum_ref(&c.value_at(i), &mut buffers[i]);
+                    }
+                }
+                buffers
+            }
             Vis::Bitmap(vis) => {
                 let rows_num = vis.len();
                 let mut buffers = vec![vec![]; rows_num];
@@ -695,8 +791,10 @@ impl DataChunkTestExt for DataChunk {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
 
     use crate::array::*;
+    use crate::collection::estimate_size::EstimateSize;
     use crate::{column, column_nonnull};
 
     #[test]
@@ -779,6 +877,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_estimated_heap_size() {
+        let small = DataChunk::from_pretty(
+            "I T
+             1 a",
+        );
+        let large = DataChunk::from_pretty(
+            "I T
+             1 a
+             2 bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb
+             3 cccccccccccccccccccccccccccccccc",
+        );
+        assert!(small.estimated_heap_size() > 0);
+        assert!(large.estimated_heap_size() > small.estimated_heap_size() + 64);
+    }
+
     #[test]
     fn test_to_pretty_string() {
         let chunk = DataChunk::new(
@@ -839,4 +953,38 @@ mod tests {
         assert_eq!(chunk.clone().reorder_columns(&[0, 1, 2]), chunk);
         assert_eq!(chunk.reorder_columns(&[]).cardinality(), 3);
     }
+
+    #[test]
+    fn test_selection_vis() {
+        let chunk = DataChunk::from_pretty(
+            "I T
+             1 a
+             2 b D
+             3 c D
+             4 d
+             5 e D",
+        );
+        let selection_chunk = chunk.clone().into_selection_vis();
+        let selection = selection_chunk.selection().unwrap();
+        assert_eq!(selection.indices(), &[0, 3]);
+
+        // The selection behaves the same as the visibility bitmap.
+        assert_eq!(selection_chunk.cardinality(), chunk.cardinality());
+        assert_eq!(selection_chunk.capacity(), chunk.capacity());
+        assert_eq!(selection_chunk.visibility(), chunk.visibility());
+        assert_eq!(
+            selection_chunk.rows().map(|row| row.index()).collect_vec(),
+            vec![0, 3]
+        );
+        assert_eq!(selection_chunk.serialize(), chunk.serialize());
+        assert_eq!(
+            selection_chunk.clone().compact(),
+            DataChunk::from_pretty(
+                "I T
+                 1 a
+                 4 d",
+            )
+        );
+        assert_eq!(selection_chunk.into_bitmap_vis(), chunk);
+    }
 }
@@ -23,6 +23,7 @@ use risingwave_pb::data::{Array as ProstArray, ArrayType};
 use super::{Array, ArrayBuilder, ArrayIterator, NULL_VAL_FOR_HASH};
 use crate::array::{ArrayBuilderImpl, ArrayMeta};
 use crate::buffer::{Bitmap, BitmapBuilder};
+use crate::collection::estimate_size::EstimateSize;
 use crate::types::Decimal;
 
 #[derive(Debug, Clone)]
@@ -31,6 +32,12 @@ pub struct DecimalArray {
     data: Vec<Decimal>,
 }
 
+impl EstimateSize for DecimalArray {
+    fn estimated_heap_size(&self) -> usize {
+        self.bitmap.estimated_heap_size() + self.data.capacity() * size_of::<Decimal>()
+    }
+}
+
 impl DecimalArray {
     pub fn from_slice(data: &[Option<Decimal>]) -> Self {
         let mut builder = <Self as Array>::Builder::new(data.len());
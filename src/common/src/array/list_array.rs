@@ -28,6 +28,7 @@ use super::{
     RowRef, NULL_VAL_FOR_HASH,
 };
 use crate::buffer::{Bitmap, BitmapBuilder};
+use crate::collection::estimate_size::EstimateSize;
 use crate::types::{
     deserialize_datum_from, display_datum_ref, serialize_datum_ref_into, to_datum_ref, DataType,
     Datum, DatumRef, Scalar, ScalarRefImpl,
@@ -154,6 +155,15 @@ pub struct ListArray {
     len: usize,
 }
 
+impl EstimateSize for ListArray {
+    fn estimated_heap_size(&self) -> usize {
+        self.bitmap.estimated_heap_size()
+            + self.offsets.capacity() * std::mem::size_of::<usize>()
+            + std::mem::size_of::<ArrayImpl>()
+            + self.value.estimated_heap_size()
+    }
+}
+
 impl Array for ListArray {
     type Builder = ListArrayBuilder;
     type Iter<'a> = ArrayIterator<'a, Self>;
@@ -15,6 +15,7 @@
 //! `Array` defines all in-memory representations of vectorized execution framework.
 
 mod bool_array;
+pub mod checksum;
 mod chrono_array;
 pub mod column;
 mod column_proto_readers;
@@ -27,6 +28,7 @@ mod iterator;
 pub mod list_array;
 mod macros;
 mod primitive_array;
+mod selection;
 pub mod stream_chunk;
 mod stream_chunk_iter;
 pub mod struct_array;
@@ -52,12 +54,14 @@ pub use list_array::{ListArray, ListArrayBuilder, ListRef, ListValue};
 use paste::paste;
 pub use primitive_array::{PrimitiveArray, PrimitiveArrayBuilder, PrimitiveArrayItemType};
 use risingwave_pb::data::{Array as ProstArray, ArrayType as ProstArrayType};
-pub use stream_chunk::{Op, StreamChunk, StreamChunkTestExt};
+pub use selection::{Selection, MAX_SELECTION_CAPACITY};
+pub use stream_chunk::{HotKeyMark, Op, StreamChunk, StreamChunkTestExt};
 pub use struct_array::{StructArray, StructArrayBuilder, StructRef, StructValue};
 pub use utf8_array::*;
 
 pub use self::error::ArrayError;
 use crate::buffer::Bitmap;
+use crate::collection::estimate_size::EstimateSize;
 use crate::types::*;
 pub type ArrayResult<T> = std::result::Result<T, ArrayError>;
 
@@ -585,6 +589,14 @@ macro_rules! impl_array {
                 }
             }
         }
+
+        impl EstimateSize for ArrayImpl {
+            fn estimated_heap_size(&self) -> usize {
+                match self {
+                    $( Self::$variant_name(inner) => inner.estimated_heap_size(), )*
+                }
+            }
+        }
     }
 }
 
@@ -0,0 +1,177 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, OnceLock};
+
+use itertools::Itertools;
+
+use crate::array::{Array, BoolArray};
+use crate::buffer::{Bitmap, BitmapBuilder};
+use crate::collection::estimate_size::EstimateSize;
+
+/// The maximum capacity of a chunk whose visible rows can be represented by a [`Selection`].
+pub const MAX_SELECTION_CAPACITY: usize = u16::MAX as usize + 1;
+
+/// `Selection` is the indices of the visible rows of a chunk in ascending order. Iterating it costs
+/// the number of visible rows rather than the capacity of the chunk, so it's cheaper than a
+/// visibility bitmap at low selectivity. The equivalent bitmap is only built when asked for, by the
+/// operators that don't iterate the selection directly.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    indices: Arc<[u16]>,
+    capacity: usize,
+    bitmap: OnceLock<Bitmap>,
+}
+
+impl PartialEq for Selection {
+    fn eq(&self, other: &Self) -> bool {
+        self.capacity == other.capacity && self.indices == other.indices
+    }
+}
+
+impl EstimateSize for Selection {
+    fn estimated_heap_size(&self) -> usize {
+        self.indices.len() * std::mem::size_of::<u16>()
+            + self.bitmap.get().map_or(0, Bitmap::estimated_heap_size)
+    }
+}
+
+impl Selection {
+    /// # Panics
+    /// Panics if `capacity` exceeds [`MAX_SELECTION_CAPACITY`], or `indices` are not ascending
+    /// indices less than `capacity`.
+    pub fn new(indices: Vec<u16>, capacity: usize) -> Self {
+        assert!(capacity <= MAX_SELECTION_CAPACITY);
+        assert!(
+            indices.iter().tuple_windows().all(|(a, b)| a < b)
+                && indices
+                    .last()
+                    .map_or(true, |&idx| (idx as usize) < capacity),
+            "selection must be ascending indices less than {}",
+            capacity
+        );
+        Self {
+            indices: indices.into(),
+            capacity,
+            bitmap: OnceLock::new(),
+        }
+    }
+
+    /// Selects the rows whose values are true, where nulls are not selected. Returns `None` if the
+    /// array is too long to be represented by a selection.
+    pub fn from_bool_array(array: &BoolArray) -> Option<Self> {
+        if array.len() > MAX_SELECTION_CAPACITY {
+            return None;
+        }
+        let indices = array
+            .iter()
+            .positions(|value| value == Some(true))
+            .map(|idx| idx as u16)
+            .collect_vec();
+        Some(Self::new(indices, array.len()))
+    }
+
+    /// Selects the set positions of `bitmap`. Returns `None` if the bitmap is too long to be
+    /// represented by a selection.
+    pub fn from_bitmap(bitmap: &Bitmap) -> Option<Self> {
+        if bitmap.len() > MAX_SELECTION_CAPACITY {
+            return None;
+        }
+        let indices = bitmap
+            .iter()
+            .positions(|set| set)
+            .map(|idx| idx as u16)
+            .collect_vec();
+        Some(Self::new(indices, bitmap.len()))
+    }
+
+    pub fn indices(&self) -> &[u16] {
+        &self.indices
+    }
+
+    /// Returns the indices of the visible rows in ascending order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = usize> + '_ {
+        self.indices.iter().map(|&idx| idx as usize)
+    }
+
+    /// Returns the number of visible rows.
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the physical length of the chunk.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_set(&self, idx: usize) -> bool {
+        assert!(idx < self.capacity);
+        self.indices.binary_search(&(idx as u16)).is_ok()
+    }
+
+    /// Returns the first visible row index on or after `idx`.
+    pub fn next_set(&self, idx: usize) -> Option<usize> {
+        let pos = self.indices.partition_point(|&i| (i as usize) < idx);
+        self.indices.get(pos).map(|&i| i as usize)
+    }
+
+    /// Returns the equivalent visibility bitmap, which is built on the first call.
+    pub fn bitmap(&self) -> &Bitmap {
+        self.bitmap.get_or_init(|| {
+            let mut builder = BitmapBuilder::zeroed(self.capacity);
+            for idx in self.iter() {
+                builder.set(idx, true);
+            }
+            builder.finish()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection() {
+        let array = BoolArray::from_slice(&[Some(true), None, Some(false), Some(true), Some(true)]);
+        let selection = Selection::from_bool_array(&array).unwrap();
+        assert_eq!(selection.indices(), &[0, 3, 4]);
+        assert_eq!(selection.len(), 3);
+        assert_eq!(selection.capacity(), 5);
+        assert!(selection.is_set(3));
+        assert!(!selection.is_set(1));
+        assert_eq!(selection.next_set(1), Some(3));
+        assert_eq!(selection.next_set(5), None);
+
+        let bitmap = selection.bitmap();
+        assert_eq!(
+            bitmap.iter().collect_vec(),
+            vec![true, false, false, true, true]
+        );
+        assert_eq!(Selection::from_bitmap(bitmap).unwrap(), selection);
+
+        let bitmap = Bitmap::from_iter(std::iter::repeat(true).take(MAX_SELECTION_CAPACITY + 1));
+        assert!(Selection::from_bitmap(&bitmap).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_selection_not_ascending() {
+        Selection::new(vec![2, 1], 3);
+    }
+}
@@ -15,7 +15,9 @@
 use std::fmt;
 
 use itertools::Itertools;
-use risingwave_pb::data::{Op as ProstOp, StreamChunk as ProstStreamChunk};
+use risingwave_pb::data::{
+    HotKeyMark as ProstHotKeyMark, Op as ProstOp, StreamChunk as ProstStreamChunk,
+};
 
 use super::{ArrayResult, DataChunkTestExt};
 use crate::array::column::Column;
@@ -62,6 +64,53 @@ impl Op {
 
 pub type Ops<'a> = &'a [Op];
 
+/// `HotKeyMark` tells a hash join how to treat the rows of a `StreamChunk`. A hash dispatcher
+/// with a hot key mode routes the rows of hot keys to more than one actor, and marks the chunks
+/// with the role of each copy. Other chunks are `Normal`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HotKeyMark {
+    #[default]
+    Normal,
+    /// Build-side rows of hot keys, sent to the actor owning their vnode.
+    BroadcastOwner,
+    /// Copies of build-side rows of hot keys, broadcast to the other actors.
+    Replica,
+    /// Probe-side rows of hot keys, sent to the actor owning their vnode.
+    SpreadOwner,
+    /// Copies of probe-side rows of hot keys, spread among the other actors.
+    SpreadShare,
+}
+
+impl HotKeyMark {
+    pub fn to_protobuf(self) -> ProstHotKeyMark {
+        match self {
+            HotKeyMark::Normal => ProstHotKeyMark::Unspecified,
+            HotKeyMark::BroadcastOwner => ProstHotKeyMark::BroadcastOwner,
+            HotKeyMark::Replica => ProstHotKeyMark::Replica,
+            HotKeyMark::SpreadOwner => ProstHotKeyMark::SpreadOwner,
+            HotKeyMark::SpreadShare => ProstHotKeyMark::SpreadShare,
+        }
+    }
+
+    pub fn from_protobuf(prost: i32) -> ArrayResult<HotKeyMark> {
+        let mark = match ProstHotKeyMark::from_i32(prost) {
+            Some(ProstHotKeyMark::Unspecified) => HotKeyMark::Normal,
+            Some(ProstHotKeyMark::BroadcastOwner) => HotKeyMark::BroadcastOwner,
+            Some(ProstHotKeyMark::Replica) => HotKeyMark::Replica,
+            Some(ProstHotKeyMark::SpreadOwner) => HotKeyMark::SpreadOwner,
+            Some(ProstHotKeyMark::SpreadShare) => HotKeyMark::SpreadShare,
+            None => bail!("No such hot key mark"),
+        };
+        Ok(mark)
+    }
+
+    /// Whether the rows are persisted by the actor, i.e., they are not copies sent to the actors
+    /// not owning their vnode.
+    pub fn is_owner(self) -> bool {
+        !matches!(self, HotKeyMark::Replica | HotKeyMark::SpreadShare)
+    }
+}
+
 /// `StreamChunk` is used to pass data over the streaming pathway.
 #[derive(Clone, PartialEq)]
 pub struct StreamChunk {
@@ -69,6 +118,8 @@ pub struct StreamChunk {
     ops: Vec<Op>,
 
     pub(super) data: DataChunk,
+
+    hot_key_mark: HotKeyMark,
 }
 
 impl Default for StreamChunk {
@@ -79,6 +130,7 @@ impl Default for StreamChunk {
         Self {
             ops: Default::default(),
             data: DataChunk::new(vec![], 0),
+            hot_key_mark: HotKeyMark::Normal,
         }
     }
 }
@@ -94,7 +146,11 @@ impl StreamChunk {
             None => Vis::Compact(ops.len()),
         };
         let data = DataChunk::new(columns, vis);
-        StreamChunk { ops, data }
+        StreamChunk {
+            ops,
+            data,
+            hot_key_mark: HotKeyMark::Normal,
+        }
     }
 
     /// Build a `StreamChunk` from rows.
@@ -149,6 +205,7 @@ impl StreamChunk {
             return self;
         }
 
+        let hot_key_mark = self.hot_key_mark;
         let (ops, columns, visibility) = self.into_inner();
         let visibility = visibility.unwrap();
 
@@ -168,7 +225,7 @@ impl StreamChunk {
                 new_ops.push(op);
             }
         }
-        StreamChunk::new(new_ops, columns, None)
+        StreamChunk::new(new_ops, columns, None).with_hot_key_mark(hot_key_mark)
     }
 
     pub fn into_parts(self) -> (DataChunk, Vec<Op>) {
@@ -180,6 +237,7 @@ impl StreamChunk {
         let visibility = match vis {
             Vis::Bitmap(b) => Some(b),
             Vis::Compact(_) => None,
+            Vis::Selection(s) => Some(s.bitmap().clone()),
         };
         Self::new(ops, columns, visibility)
     }
@@ -189,6 +247,7 @@ impl StreamChunk {
         let visibility = match vis {
             Vis::Bitmap(b) => Some(b),
             Vis::Compact(_) => None,
+            Vis::Selection(s) => Some(s.bitmap().clone()),
         };
         (self.ops, columns, visibility)
     }
@@ -198,6 +257,8 @@ impl StreamChunk {
             cardinality: self.cardinality() as u32,
             ops: self.ops.iter().map(|op| op.to_protobuf() as i32).collect(),
             columns: self.columns().iter().map(|col| col.to_protobuf()).collect(),
+            checksum: None,
+            hot_key_mark: self.hot_key_mark.to_protobuf() as i32,
         }
     }
 
@@ -211,13 +272,24 @@ impl StreamChunk {
         for column in prost.get_columns() {
             columns.push(Column::from_protobuf(column, cardinality)?);
         }
-        Ok(StreamChunk::new(ops, columns, None))
+        let hot_key_mark = HotKeyMark::from_protobuf(prost.hot_key_mark)?;
+        Ok(StreamChunk::new(ops, columns, None).with_hot_key_mark(hot_key_mark))
     }
 
     pub fn ops(&self) -> &[Op] {
         &self.ops
     }
 
+    pub fn hot_key_mark(&self) -> HotKeyMark {
+        self.hot_key_mark
+    }
+
+    /// Set the [`HotKeyMark`] of the chunk.
+    pub fn with_hot_key_mark(mut self, hot_key_mark: HotKeyMark) -> Self {
+        self.hot_key_mark = hot_key_mark;
+        self
+    }
+
     pub fn visibility(&self) -> Option<&Bitmap> {
         self.data.visibility()
     }
@@ -267,6 +339,7 @@ impl StreamChunk {
             Self {
                 ops: self.ops,
                 data: self.data.reorder_columns(column_mapping),
+                hot_key_mark: self.hot_key_mark,
             }
         }
     }
@@ -347,6 +420,7 @@ impl StreamChunkTestExt for StreamChunk {
                 return StreamChunk {
                     ops: vec![],
                     data: DataChunk::from_pretty(s),
+                    hot_key_mark: HotKeyMark::Normal,
                 };
             }
         };
@@ -374,6 +448,7 @@ impl StreamChunkTestExt for StreamChunk {
         StreamChunk {
             ops,
             data: DataChunk::from_pretty(&chunk_str),
+            hot_key_mark: HotKeyMark::Normal,
         }
     }
 
@@ -402,7 +477,11 @@ impl StreamChunkTestExt for StreamChunk {
             .into_iter()
             .next()
             .unwrap();
-        StreamChunk { ops, data }
+        StreamChunk {
+            ops,
+            data,
+            hot_key_mark: HotKeyMark::Normal,
+        }
     }
 
     fn sort_rows(self) -> Self {
@@ -415,6 +494,7 @@ impl StreamChunkTestExt for StreamChunk {
         StreamChunk {
             ops: idx.iter().map(|&i| self.ops[i]).collect(),
             data: self.data.reorder_rows(&idx),
+            hot_key_mark: self.hot_key_mark,
         }
     }
 }
@@ -443,4 +523,25 @@ mod tests {
 +----+---+---+"
         );
     }
+
+    #[test]
+    fn test_hot_key_mark() {
+        let chunk = StreamChunk::from_pretty(
+            "  I I
+             + 1 6
+             - 2 . D
+             + 3 7",
+        )
+        .with_hot_key_mark(HotKeyMark::SpreadShare);
+
+        // The mark is kept by compaction and the protobuf encoding.
+        let chunk = chunk.compact();
+        assert_eq!(chunk.hot_key_mark(), HotKeyMark::SpreadShare);
+        let chunk = StreamChunk::from_protobuf(&chunk.to_protobuf()).unwrap();
+        assert_eq!(chunk.hot_key_mark(), HotKeyMark::SpreadShare);
+        assert_eq!(
+            StreamChunk::from_pretty("I\n + 1").hot_key_mark(),
+            HotKeyMark::Normal
+        );
+    }
 }
@@ -64,6 +64,25 @@ pub struct BatchConfig {
     #[serde(default)]
     pub worker_threads_num: Option<usize>,
 
+    /// The memory budget shared by all order by executors on a node. An executor spills its
+    /// buffered rows to a temporary file as a sorted run once the budget is used up.
+    #[serde(default = "default::sort_memory_budget_mb")]
+    pub sort_memory_budget_mb: usize,
+
+    /// The capacity of the query result cache in the frontend node, see
+    /// `RW_BATCH_ENABLE_RESULT_CACHE`.
+    #[serde(default = "default::result_cache_capacity_mb")]
+    pub result_cache_capacity_mb: usize,
+
+    /// The capacity of the query results cached for each session.
+    #[serde(default = "default::result_cache_session_capacity_mb")]
+    pub result_cache_session_capacity_mb: usize,
+
+    /// The target size of the data read by each task of a full scan on a table, by which the
+    /// frontend splits the scan into tasks. 0 to create a task for each parallel unit.
+    #[serde(default = "default::scan_split_target_mb")]
+    pub scan_split_target_mb: u64,
+
     #[serde(default)]
     pub developer: DeveloperConfig,
 }
@@ -94,6 +113,10 @@ pub struct StreamingConfig {
     #[serde(default = "default::checkpoint_frequency")]
     pub checkpoint_frequency: usize,
 
+    /// Trace every n-th barrier end to end across the nodes with OpenTelemetry. 0 for never.
+    #[serde(default)]
+    pub barrier_trace_sample_interval: usize,
+
     /// Whether to enable the minimal scheduling strategy, that is, only schedule the streaming
     /// fragment on one parallel unit per compute node.
     #[serde(default)]
@@ -243,6 +266,22 @@ pub struct DeveloperConfig {
     #[serde(default = "default::developer::batch_chunk_size")]
     pub batch_chunk_size: usize,
 
+    /// Set to true to let the batch filter executor select the rows passing the filter with a
+    /// selection vector, instead of compacting them into new chunks. It's cheaper at low
+    /// selectivity as long as the downstream executors iterate the selection directly.
+    #[serde(default = "default::developer::batch_filter_enable_selection_vector")]
+    pub batch_filter_enable_selection_vector: bool,
+
+    /// Set to true to let the batch hash join executor select the rows passing its non-equi
+    /// condition with a selection vector rather than a visibility bitmap.
+    #[serde(default = "default::developer::batch_hash_join_enable_selection_vector")]
+    pub batch_hash_join_enable_selection_vector: bool,
+
+    /// Set to true to attach an xxhash64 checksum to each chunk sent by the batch exchange, which
+    /// the receiver verifies to detect the corruption between nodes.
+    #[serde(default = "default::developer::batch_exchange_checksum")]
+    pub batch_exchange_checksum: bool,
+
     /// Set to true to enable per-executor row count metrics. This will produce a lot of timeseries
     /// and might affect the prometheus performance. If you only need actor input and output
     /// rows data, see `stream_actor_in_record_cnt` and `stream_actor_out_record_cnt` instead.
@@ -266,9 +305,34 @@ pub struct DeveloperConfig {
     #[serde(default = "default::developer::unsafe_stream_extreme_cache_size")]
     pub unsafe_stream_extreme_cache_size: usize,
 
+    /// Limit number of the cached entries (one per dedup key) in a dedup executor.
+    #[serde(default = "default::developer::unsafe_stream_dedup_cache_size")]
+    pub unsafe_stream_dedup_cache_size: usize,
+
+    /// Limit number of the cached partitions (one per partition key) in an over window executor.
+    #[serde(default = "default::developer::unsafe_stream_over_window_cache_size")]
+    pub unsafe_stream_over_window_cache_size: usize,
+
     /// The maximum size of the chunk produced by executor at a time.
     #[serde(default = "default::developer::stream_chunk_size")]
     pub stream_chunk_size: usize,
+
+    /// The byte budget of the state prefetched into the cache of a stateful executor when it is
+    /// built, e.g. after recovery or migration. The hottest keys within the budget are kept. 0 to
+    /// disable the prefetch.
+    #[serde(default = "default::developer::stream_state_prefetch_bytes")]
+    pub stream_state_prefetch_bytes: usize,
+
+    /// The maximum time a stateful executor spends prefetching its state in the background. The
+    /// first barrier is not held back by the prefetch, and the messages after it wait for it for
+    /// at most this long.
+    #[serde(default = "default::developer::stream_state_prefetch_deadline_ms")]
+    pub stream_state_prefetch_deadline_ms: u64,
+
+    /// Set to true to attach an xxhash64 checksum to each chunk sent to the remote downstream
+    /// actors, which the receiver verifies to detect the corruption between nodes.
+    #[serde(default = "default::developer::stream_exchange_checksum")]
+    pub stream_exchange_checksum: bool,
 }
 
 impl Default for DeveloperConfig {
@@ -340,6 +404,22 @@ mod default {
         "tempdisk".to_string()
     }
 
+    pub fn sort_memory_budget_mb() -> usize {
+        1024
+    }
+
+    pub fn result_cache_capacity_mb() -> usize {
+        256
+    }
+
+    pub fn result_cache_session_capacity_mb() -> usize {
+        16
+    }
+
+    pub fn scan_split_target_mb() -> u64 {
+        64
+    }
+
     pub fn barrier_interval_ms() -> u32 {
         250
     }
@@ -412,6 +492,18 @@ mod default {
             1024
         }
 
+        pub fn batch_filter_enable_selection_vector() -> bool {
+            false
+        }
+
+        pub fn batch_hash_join_enable_selection_vector() -> bool {
+            false
+        }
+
+        pub fn batch_exchange_checksum() -> bool {
+            false
+        }
+
         pub fn stream_enable_executor_row_count() -> bool {
             false
         }
@@ -432,9 +524,29 @@ mod default {
             1 << 10
         }
 
+        pub fn unsafe_stream_dedup_cache_size() -> usize {
+            1 << 16
+        }
+
+        pub fn unsafe_stream_over_window_cache_size() -> usize {
+            1 << 10
+        }
+
         pub fn stream_chunk_size() -> usize {
             1024
         }
+
+        pub fn stream_state_prefetch_bytes() -> usize {
+            0
+        }
+
+        pub fn stream_state_prefetch_deadline_ms() -> u64 {
+            1000
+        }
+
+        pub fn stream_exchange_checksum() -> bool {
+            false
+        }
     }
 }
 
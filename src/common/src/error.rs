@@ -131,6 +131,12 @@ pub enum ErrorCode {
 
     #[error("unrecognized configuration parameter \"{0}\"")]
     UnrecognizedConfigurationParameter(String),
+
+    #[error("Meta service unavailable: {0}")]
+    MetaUnavailable(String),
+
+    #[error("Subscription {0} has fallen behind its retention of {1} seconds")]
+    SubscriptionRetentionExceeded(String, u32),
 }
 
 pub fn internal_err(msg: impl Into<anyhow::Error>) -> RwError {
@@ -155,6 +161,7 @@ impl From<RwError> for tonic::Status {
             ErrorCode::ExprError(e) => tonic::Status::invalid_argument(e.to_string()),
             ErrorCode::PermissionDenied(e) => tonic::Status::permission_denied(e),
             ErrorCode::InternalError(e) => tonic::Status::internal(e),
+            ErrorCode::MetaUnavailable(e) => tonic::Status::unavailable(e),
             _ => tonic::Status::internal(err.to_string()),
         }
     }
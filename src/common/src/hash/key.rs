@@ -33,14 +33,14 @@ use itertools::Itertools;
 
 use crate::array::{
     Array, ArrayBuilder, ArrayBuilderImpl, ArrayError, ArrayImpl, ArrayResult, DataChunk, ListRef,
-    Row, StructRef,
+    Row, StructRef, Vis,
 };
 use crate::collection::estimate_size::EstimateSize;
 use crate::types::{
     DataType, Decimal, IntervalUnit, NaiveDateTimeWrapper, NaiveDateWrapper, NaiveTimeWrapper,
     OrderedF32, OrderedF64, ScalarRef, ToOwnedDatum, VirtualNode, VIRTUAL_NODE_COUNT,
 };
-use crate::util::hash_util::Crc32FastBuilder;
+use crate::util::hash_util::{finalize_hashers, Crc32FastBuilder};
 use crate::util::value_encoding::{deserialize_datum, serialize_datum};
 
 /// A wrapper for u64 hash result.
@@ -114,6 +114,48 @@ pub trait HashKey:
         ))
     }
 
+    /// Builds the keys of the visible rows of `data_chunk` only, each paired with its row index.
+    /// The keys are equal to the ones [`HashKey::build`] builds for the same rows.
+    fn build_visible(
+        column_idxes: &[usize],
+        data_chunk: &DataChunk,
+    ) -> ArrayResult<Vec<(usize, Self)>> {
+        if let Vis::Compact(_) = data_chunk.vis() {
+            return Ok(Self::build(column_idxes, data_chunk)?
+                .into_iter()
+                .enumerate()
+                .collect());
+        }
+
+        let row_ids = data_chunk.vis().iter_ones().collect_vec();
+        let mut hashers = row_ids
+            .iter()
+            .map(|_| Crc32FastBuilder.build_hasher())
+            .collect_vec();
+        for column_idx in column_idxes {
+            let array = data_chunk.column_at(*column_idx).array_ref();
+            for (row_id, hasher) in row_ids.iter().zip_eq(hashers.iter_mut()) {
+                array.hash_at(*row_id, hasher);
+            }
+        }
+        let mut serializers: Vec<Self::S> = finalize_hashers(&mut hashers[..])
+            .into_iter()
+            .map(|hash_code| Self::S::from_hash_code(hash_code.into()))
+            .collect();
+
+        for column_idx in column_idxes {
+            data_chunk
+                .column_at(*column_idx)
+                .array_ref()
+                .serialize_rows_to_hash_key(&row_ids, &mut serializers[..]);
+        }
+
+        Ok(row_ids
+            .into_iter()
+            .zip_eq(serializers.into_iter().map(Self::S::into_hash_key))
+            .collect())
+    }
+
     fn build_from_hash_code(
         column_idxes: &[usize],
         data_chunk: &DataChunk,
@@ -619,6 +661,20 @@ where
     }
 }
 
+fn serialize_array_rows_to_hash_key<'a, A, S>(
+    array: &'a A,
+    row_ids: &[usize],
+    serializers: &mut [S],
+) where
+    A: Array,
+    A::RefItem<'a>: HashKeySerDe<'a>,
+    S: HashKeySerializer,
+{
+    for (row_id, serializer) in row_ids.iter().zip_eq(serializers.iter_mut()) {
+        serializer.append(array.value_at(*row_id));
+    }
+}
+
 fn deserialize_array_element_from_hash_key<'a, A, S>(
     builder: &'a mut A,
     deserializer: &'a mut S,
@@ -643,6 +699,21 @@ impl ArrayImpl {
         }
         for_all_variants! { impl_all_serialize_to_hash_key }
     }
+
+    fn serialize_rows_to_hash_key<S: HashKeySerializer>(
+        &self,
+        row_ids: &[usize],
+        serializers: &mut [S],
+    ) {
+        macro_rules! impl_all_serialize_rows_to_hash_key {
+            ($({ $variant_name:ident, $suffix_name:ident, $array:ty, $builder:ty } ),*) => {
+                match self {
+                    $( Self::$variant_name(inner) => serialize_array_rows_to_hash_key(inner, row_ids, serializers), )*
+                }
+            };
+        }
+        for_all_variants! { impl_all_serialize_rows_to_hash_key }
+    }
 }
 
 impl ArrayBuilderImpl {
@@ -716,8 +787,9 @@ mod tests {
     use crate::array::{
         BoolArray, DataChunk, DataChunkTestExt, DecimalArray, F32Array, F64Array, I16Array,
         I32Array, I32ArrayBuilder, I64Array, NaiveDateArray, NaiveDateTimeArray, NaiveTimeArray,
-        Utf8Array,
+        Selection, Utf8Array,
     };
+    use crate::buffer::Bitmap;
     use crate::hash::{
         HashKey, Key128, Key16, Key256, Key32, Key64, KeySerialized, PrecomputedBuildHasher,
     };
@@ -945,4 +1017,31 @@ mod tests {
             .collect_vec();
         assert_eq!(i32_vec, vec![None, Some(2)]);
     }
+
+    fn do_test_build_visible<K: HashKey>(column_indexes: Vec<usize>) {
+        let (data, _) = generate_random_data_chunk();
+        let all_keys = K::build(&column_indexes, &data).unwrap();
+
+        let visibility: Bitmap = (0..data.capacity()).map(|i| i % 3 == 1).collect();
+        let selection = Selection::from_bitmap(&visibility).unwrap();
+        for chunk in [
+            data.with_visibility(visibility.clone()),
+            data.with_selection(selection),
+        ] {
+            let expected = all_keys
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(row_id, _)| visibility.is_set(*row_id))
+                .collect_vec();
+            assert_eq!(K::build_visible(&column_indexes, &chunk).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_build_visible() {
+        do_test_build_visible::<Key32>(vec![0, 1]);
+        do_test_build_visible::<Key256>(vec![3, 5, 6]);
+        do_test_build_visible::<KeySerialized>(vec![0, 7]);
+    }
 }
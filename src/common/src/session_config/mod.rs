@@ -14,6 +14,7 @@
 
 mod query_mode;
 mod search_path;
+mod timezone;
 mod transaction_isolation_level;
 
 use std::ops::Deref;
@@ -21,13 +22,14 @@ use std::ops::Deref;
 use itertools::Itertools;
 pub use query_mode::QueryMode;
 pub use search_path::{SearchPath, USER_NAME_WILD_CARD};
+pub use timezone::TimeZone;
 
 use crate::error::{ErrorCode, RwError};
 use crate::session_config::transaction_isolation_level::IsolationLevel;
 
 // This is a hack, &'static str is not allowed as a const generics argument.
 // TODO: refine this using the adt_const_params feature.
-const CONFIG_KEYS: [&str; 10] = [
+const CONFIG_KEYS: [&str; 19] = [
     "RW_IMPLICIT_FLUSH",
     "CREATE_COMPACTION_GROUP_FOR_MV",
     "QUERY_MODE",
@@ -38,6 +40,15 @@ const CONFIG_KEYS: [&str; 10] = [
     "MAX_SPLIT_RANGE_GAP",
     "SEARCH_PATH",
     "TRANSACTION ISOLATION LEVEL",
+    "RW_STREAMING_GRAPH_NOTICE",
+    "RW_OPTIMIZER_USE_STATISTICS",
+    "TIMEZONE",
+    "RW_BATCH_LOCAL_POINT_GET",
+    "RW_AUTO_WATERMARK",
+    "RW_AUTO_WATERMARK_ALLOWED_LATENESS_MS",
+    "RW_BATCH_ENABLE_RESULT_CACHE",
+    "RW_STREAMING_SEQUENTIAL_BACKFILL",
+    "RW_STREAMING_HOT_KEY_SKEW",
 ];
 
 // MUST HAVE 1v1 relationship to CONFIG_KEYS. e.g. CONFIG_KEYS[IMPLICIT_FLUSH] =
@@ -52,6 +63,15 @@ const BATCH_ENABLE_LOOKUP_JOIN: usize = 6;
 const MAX_SPLIT_RANGE_GAP: usize = 7;
 const SEARCH_PATH: usize = 8;
 const TRANSACTION_ISOLATION_LEVEL: usize = 9;
+const STREAMING_GRAPH_NOTICE: usize = 10;
+const OPTIMIZER_USE_STATISTICS: usize = 11;
+const TIMEZONE: usize = 12;
+const BATCH_LOCAL_POINT_GET: usize = 13;
+const AUTO_WATERMARK: usize = 14;
+const AUTO_WATERMARK_ALLOWED_LATENESS_MS: usize = 15;
+const BATCH_ENABLE_RESULT_CACHE: usize = 16;
+const STREAMING_SEQUENTIAL_BACKFILL: usize = 17;
+const STREAMING_HOT_KEY_SKEW: usize = 18;
 
 trait ConfigEntry: Default + for<'a> TryFrom<&'a [&'a str], Error = RwError> {
     fn entry_name() -> &'static str;
@@ -198,6 +218,14 @@ type ExtraFloatDigit = ConfigI32<EXTRA_FLOAT_DIGITS, 1>;
 type DateStyle = ConfigString<DATE_STYLE>;
 type BatchEnableLookupJoin = ConfigBool<BATCH_ENABLE_LOOKUP_JOIN, false>;
 type MaxSplitRangeGap = ConfigI32<MAX_SPLIT_RANGE_GAP, 8>;
+type StreamingGraphNotice = ConfigBool<STREAMING_GRAPH_NOTICE, false>;
+type OptimizerUseStatistics = ConfigBool<OPTIMIZER_USE_STATISTICS, true>;
+type BatchLocalPointGet = ConfigBool<BATCH_LOCAL_POINT_GET, true>;
+type AutoWatermark = ConfigBool<AUTO_WATERMARK, false>;
+type AutoWatermarkAllowedLatenessMs = ConfigI32<AUTO_WATERMARK_ALLOWED_LATENESS_MS, 5000>;
+type BatchEnableResultCache = ConfigBool<BATCH_ENABLE_RESULT_CACHE, false>;
+type StreamingSequentialBackfill = ConfigBool<STREAMING_SEQUENTIAL_BACKFILL, false>;
+type StreamingHotKeySkew = ConfigBool<STREAMING_HOT_KEY_SKEW, false>;
 
 #[derive(Default)]
 pub struct ConfigMap {
@@ -234,6 +262,40 @@ pub struct ConfigMap {
 
     /// see <https://www.postgresql.org/docs/current/transaction-iso.html>
     transaction_isolation_level: IsolationLevel,
+
+    /// If `RW_STREAMING_GRAPH_NOTICE` is on, a notice with the fragment graph created by meta will
+    /// be sent after `CREATE MATERIALIZED VIEW`.
+    streaming_graph_notice: StreamingGraphNotice,
+
+    /// If `RW_OPTIMIZER_USE_STATISTICS` is on, the optimizer consults the table statistics
+    /// collected by `ANALYZE` for join ordering and aggregation strategy.
+    optimizer_use_statistics: OptimizerUseStatistics,
+
+    /// see <https://www.postgresql.org/docs/current/runtime-config-client.html#GUC-TIMEZONE>
+    timezone: TimeZone,
+
+    /// If `RW_BATCH_LOCAL_POINT_GET` is on, point gets on the distribution key are executed in
+    /// local mode even if `QUERY_MODE` is distributed.
+    batch_local_point_get: BatchLocalPointGet,
+
+    /// If `RW_AUTO_WATERMARK` is on, a bounded-disorder watermark is derived on the time column of
+    /// an insert-only source that a `TUMBLE` or `HOP` window is applied on.
+    auto_watermark: AutoWatermark,
+
+    /// The allowed lateness of the watermarks derived by `RW_AUTO_WATERMARK`, in milliseconds.
+    auto_watermark_allowed_lateness_ms: AutoWatermarkAllowedLatenessMs,
+
+    /// If `RW_BATCH_ENABLE_RESULT_CACHE` is on, the results of `SELECT` statements are cached in
+    /// the session until new data is committed.
+    batch_enable_result_cache: BatchEnableResultCache,
+
+    /// If `RW_STREAMING_SEQUENTIAL_BACKFILL` is on, a materialized view on multiple upstream
+    /// tables backfills from one upstream table at a time, instead of all of them concurrently.
+    streaming_sequential_backfill: StreamingSequentialBackfill,
+
+    /// If `RW_STREAMING_HOT_KEY_SKEW` is on, the inner hash joins of a new materialized view
+    /// broadcast the build-side rows and spread the probe-side rows of their hot join keys.
+    streaming_hot_key_skew: StreamingHotKeySkew,
 }
 
 impl ConfigMap {
@@ -257,6 +319,24 @@ impl ConfigMap {
             self.max_split_range_gap = val.as_slice().try_into()?;
         } else if key.eq_ignore_ascii_case(SearchPath::entry_name()) {
             self.search_path = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(StreamingGraphNotice::entry_name()) {
+            self.streaming_graph_notice = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(OptimizerUseStatistics::entry_name()) {
+            self.optimizer_use_statistics = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(TimeZone::entry_name()) {
+            self.timezone = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(BatchLocalPointGet::entry_name()) {
+            self.batch_local_point_get = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(AutoWatermark::entry_name()) {
+            self.auto_watermark = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(AutoWatermarkAllowedLatenessMs::entry_name()) {
+            self.auto_watermark_allowed_lateness_ms = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(BatchEnableResultCache::entry_name()) {
+            self.batch_enable_result_cache = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(StreamingSequentialBackfill::entry_name()) {
+            self.streaming_sequential_backfill = val.as_slice().try_into()?;
+        } else if key.eq_ignore_ascii_case(StreamingHotKeySkew::entry_name()) {
+            self.streaming_hot_key_skew = val.as_slice().try_into()?;
         } else {
             return Err(ErrorCode::UnrecognizedConfigurationParameter(key.to_string()).into());
         }
@@ -285,6 +365,24 @@ impl ConfigMap {
             Ok(self.search_path.to_string())
         } else if key.eq_ignore_ascii_case(IsolationLevel::entry_name()) {
             Ok(self.transaction_isolation_level.to_string())
+        } else if key.eq_ignore_ascii_case(StreamingGraphNotice::entry_name()) {
+            Ok(self.streaming_graph_notice.to_string())
+        } else if key.eq_ignore_ascii_case(OptimizerUseStatistics::entry_name()) {
+            Ok(self.optimizer_use_statistics.to_string())
+        } else if key.eq_ignore_ascii_case(TimeZone::entry_name()) {
+            Ok(self.timezone.to_string())
+        } else if key.eq_ignore_ascii_case(BatchLocalPointGet::entry_name()) {
+            Ok(self.batch_local_point_get.to_string())
+        } else if key.eq_ignore_ascii_case(AutoWatermark::entry_name()) {
+            Ok(self.auto_watermark.to_string())
+        } else if key.eq_ignore_ascii_case(AutoWatermarkAllowedLatenessMs::entry_name()) {
+            Ok(self.auto_watermark_allowed_lateness_ms.to_string())
+        } else if key.eq_ignore_ascii_case(BatchEnableResultCache::entry_name()) {
+            Ok(self.batch_enable_result_cache.to_string())
+        } else if key.eq_ignore_ascii_case(StreamingSequentialBackfill::entry_name()) {
+            Ok(self.streaming_sequential_backfill.to_string())
+        } else if key.eq_ignore_ascii_case(StreamingHotKeySkew::entry_name()) {
+            Ok(self.streaming_hot_key_skew.to_string())
         } else {
             Err(ErrorCode::UnrecognizedConfigurationParameter(key.to_string()).into())
         }
@@ -336,6 +434,51 @@ impl ConfigMap {
                 name: SearchPath::entry_name().to_lowercase(),
                 setting : self.search_path.to_string(),
                 description : String::from("Sets the order in which schemas are searched when an object (table, data type, function, etc.) is referenced by a simple name with no schema specified")
+            },
+            VariableInfo {
+                name: StreamingGraphNotice::entry_name().to_lowercase(),
+                setting : self.streaming_graph_notice.to_string(),
+                description : String::from("If `RW_STREAMING_GRAPH_NOTICE` is on, a notice with the created fragment graph will be sent after creating a materialized view.")
+            },
+            VariableInfo {
+                name: OptimizerUseStatistics::entry_name().to_lowercase(),
+                setting : self.optimizer_use_statistics.to_string(),
+                description : String::from("If `RW_OPTIMIZER_USE_STATISTICS` is on, the optimizer uses the table statistics collected by `ANALYZE` for join ordering and aggregation strategy.")
+            },
+            VariableInfo {
+                name: TimeZone::entry_name().to_lowercase(),
+                setting : self.timezone.to_string(),
+                description : String::from("Sets the time zone for displaying and interpreting time stamps.")
+            },
+            VariableInfo {
+                name: BatchLocalPointGet::entry_name().to_lowercase(),
+                setting : self.batch_local_point_get.to_string(),
+                description : String::from("If `RW_BATCH_LOCAL_POINT_GET` is on, point gets on the distribution key are executed in local mode even if the query mode is distributed.")
+            },
+            VariableInfo {
+                name: AutoWatermark::entry_name().to_lowercase(),
+                setting : self.auto_watermark.to_string(),
+                description : String::from("If `RW_AUTO_WATERMARK` is on, a watermark is derived on the time column of an insert-only source that a `TUMBLE` or `HOP` window is applied on.")
+            },
+            VariableInfo {
+                name: AutoWatermarkAllowedLatenessMs::entry_name().to_lowercase(),
+                setting : self.auto_watermark_allowed_lateness_ms.to_string(),
+                description : String::from("The allowed lateness in milliseconds of the watermarks derived by `RW_AUTO_WATERMARK`.")
+            },
+            VariableInfo {
+                name: BatchEnableResultCache::entry_name().to_lowercase(),
+                setting : self.batch_enable_result_cache.to_string(),
+                description : String::from("If `RW_BATCH_ENABLE_RESULT_CACHE` is on, the results of `SELECT` statements are cached in the session until new data is committed.")
+            },
+            VariableInfo {
+                name: StreamingSequentialBackfill::entry_name().to_lowercase(),
+                setting : self.streaming_sequential_backfill.to_string(),
+                description : String::from("If `RW_STREAMING_SEQUENTIAL_BACKFILL` is on, a materialized view on multiple upstream tables backfills from one upstream table at a time.")
+            },
+            VariableInfo {
+                name: StreamingHotKeySkew::entry_name().to_lowercase(),
+                setting : self.streaming_hot_key_skew.to_string(),
+                description : String::from("If `RW_STREAMING_HOT_KEY_SKEW` is on, the inner hash joins of a new materialized view broadcast the build-side rows and spread the probe-side rows of their hot join keys.")
             }
         ]
     }
@@ -379,4 +522,44 @@ impl ConfigMap {
     pub fn get_search_path(&self) -> SearchPath {
         self.search_path.clone()
     }
+
+    pub fn get_streaming_graph_notice(&self) -> bool {
+        *self.streaming_graph_notice
+    }
+
+    pub fn get_optimizer_use_statistics(&self) -> bool {
+        *self.optimizer_use_statistics
+    }
+
+    pub fn get_timezone(&self) -> TimeZone {
+        self.timezone
+    }
+
+    pub fn get_batch_local_point_get(&self) -> bool {
+        *self.batch_local_point_get
+    }
+
+    pub fn get_auto_watermark(&self) -> bool {
+        *self.auto_watermark
+    }
+
+    pub fn get_auto_watermark_allowed_lateness_ms(&self) -> u64 {
+        if *self.auto_watermark_allowed_lateness_ms < 0 {
+            0
+        } else {
+            *self.auto_watermark_allowed_lateness_ms as u64
+        }
+    }
+
+    pub fn get_batch_enable_result_cache(&self) -> bool {
+        *self.batch_enable_result_cache
+    }
+
+    pub fn get_streaming_sequential_backfill(&self) -> bool {
+        *self.streaming_sequential_backfill
+    }
+
+    pub fn get_streaming_hot_key_skew(&self) -> bool {
+        *self.streaming_hot_key_skew
+    }
 }
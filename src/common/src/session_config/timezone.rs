@@ -0,0 +1,111 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Formatter;
+
+use chrono_tz::Tz;
+
+use super::{ConfigEntry, CONFIG_KEYS, TIMEZONE};
+use crate::error::ErrorCode::{self, InvalidConfigValue};
+use crate::error::RwError;
+
+/// see <https://www.postgresql.org/docs/current/runtime-config-client.html#GUC-TIMEZONE>
+///
+/// Only the names of the IANA time zone database are accepted. `LOCAL` and `DEFAULT` reset the
+/// time zone to `UTC`.
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone(Tz);
+
+impl TimeZone {
+    pub fn tz(&self) -> Tz {
+        self.0
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self(Tz::UTC)
+    }
+}
+
+impl ConfigEntry for TimeZone {
+    fn entry_name() -> &'static str {
+        CONFIG_KEYS[TIMEZONE]
+    }
+}
+
+impl TryFrom<&[&str]> for TimeZone {
+    type Error = RwError;
+
+    fn try_from(value: &[&str]) -> Result<Self, Self::Error> {
+        if value.len() != 1 {
+            return Err(ErrorCode::InternalError(format!(
+                "SET {} takes only one argument",
+                Self::entry_name()
+            ))
+            .into());
+        }
+
+        // String literals are passed with their quotes, e.g. `SET TIMEZONE TO 'Asia/Shanghai'`.
+        let s = value[0].trim_matches('\'');
+        if s.eq_ignore_ascii_case("local") || s.eq_ignore_ascii_case("default") {
+            return Ok(Self::default());
+        }
+        Tz::from_str_insensitive(s).map(Self).map_err(|_| {
+            InvalidConfigValue {
+                config_entry: Self::entry_name().to_string(),
+                config_value: s.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+impl std::fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_zone() {
+        assert_eq!(TimeZone::default().name(), "UTC");
+        assert_eq!(
+            TimeZone::try_from(["Asia/Shanghai"].as_slice())
+                .unwrap()
+                .name(),
+            "Asia/Shanghai"
+        );
+        assert_eq!(
+            TimeZone::try_from(["'us/pacific'"].as_slice())
+                .unwrap()
+                .name(),
+            "US/Pacific"
+        );
+        assert_eq!(
+            TimeZone::try_from(["DEFAULT"].as_slice()).unwrap(),
+            TimeZone::default()
+        );
+        assert!(TimeZone::try_from(["Mars/Olympus_Mons"].as_slice()).is_err());
+        assert!(TimeZone::try_from(["UTC", "UTC"].as_slice()).is_err());
+    }
+}
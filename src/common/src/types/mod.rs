@@ -67,7 +67,9 @@ pub type VnodeMapping = Vec<ParallelUnitId>;
 /// `VirtualNode` (a.k.a. VNode) is a minimal partition that a set of keys belong to. It is used for
 /// consistent hashing.
 pub type VirtualNode = u8;
-pub const VIRTUAL_NODE_SIZE: usize = std::mem::size_of::<VirtualNode>();
+/// The size of a [`VirtualNode`] encoded in a storage key, which is wider than the in-memory type
+/// so that the vnode count can grow without changing the key encoding again.
+pub const VIRTUAL_NODE_SIZE: usize = std::mem::size_of::<u16>();
 pub const VNODE_BITS: usize = 8;
 pub const VIRTUAL_NODE_COUNT: usize = 1 << VNODE_BITS;
 
@@ -36,6 +36,17 @@ pub enum AsyncStackTraceOption {
     Verbose,
 }
 
+/// The role of a compute node, which decides the kind of tasks scheduled to it.
+#[derive(Debug, Clone, Copy, ArgEnum)]
+pub enum Role {
+    /// Only runs streaming actors.
+    Streaming,
+    /// Only runs batch tasks, so that the ad-hoc queries are isolated from the streaming jobs.
+    Serving,
+    /// Runs both streaming actors and batch tasks.
+    Both,
+}
+
 /// Command-line arguments for compute-node.
 #[derive(Parser, Debug)]
 pub struct ComputeNodeOpts {
@@ -74,6 +85,11 @@ pub struct ComputeNodeOpts {
     #[clap(long, arg_enum, default_value_t = AsyncStackTraceOption::Off)]
     pub async_stack_trace: AsyncStackTraceOption,
 
+    /// Export traces to the OpenTelemetry collector at this endpoint with OTLP, e.g.
+    /// `http://127.0.0.1:4317`. Left empty to disable exporting.
+    #[clap(long)]
+    pub otlp_endpoint: Option<String>,
+
     /// Path to file cache data directory.
     /// Left empty to disable file cache.
     #[clap(long, default_value = "")]
@@ -82,6 +98,11 @@ pub struct ComputeNodeOpts {
     /// Enable managed lru cache, or use local lru cache.
     #[clap(long)]
     pub enable_managed_cache: bool,
+
+    /// The role of the compute node. Streaming actors are never scheduled to a `serving` node,
+    /// and batch scan tasks prefer `serving` nodes when there are any.
+    #[clap(long, arg_enum, default_value_t = Role::Both)]
+    pub role: Role,
 }
 
 use std::future::Future;
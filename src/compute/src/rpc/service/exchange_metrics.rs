@@ -13,13 +13,18 @@
 // limitations under the License.
 
 use prometheus::core::{AtomicU64, GenericCounterVec};
-use prometheus::{register_int_counter_vec_with_registry, Registry};
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_gauge_with_registry, IntGauge, Registry,
+};
 
 pub struct ExchangeServiceMetrics {
     pub registry: Registry,
     pub stream_exchange_bytes: GenericCounterVec<AtomicU64>,
     pub stream_fragment_exchange_bytes: GenericCounterVec<AtomicU64>,
     pub actor_sampled_serialize_duration_ns: GenericCounterVec<AtomicU64>,
+    pub stream_exchange_permit_blocking_duration_ns: GenericCounterVec<AtomicU64>,
+    pub stream_exchange_multiplexed_connections: IntGauge,
+    pub stream_exchange_multiplexed_channels: IntGauge,
 }
 
 impl ExchangeServiceMetrics {
@@ -48,11 +53,37 @@ impl ExchangeServiceMetrics {
         )
         .unwrap();
 
+        let stream_exchange_permit_blocking_duration_ns = register_int_counter_vec_with_registry!(
+            "stream_exchange_permit_blocking_duration_ns",
+            "Total blocking duration (ns) of the multiplexed exchange channel waiting for permits \
+             from downstream Actor",
+            &["up_actor_id", "down_actor_id"],
+            registry
+        )
+        .unwrap();
+
+        let stream_exchange_multiplexed_connections = register_int_gauge_with_registry!(
+            "stream_exchange_multiplexed_connections",
+            "Number of multiplexed exchange connections from other compute nodes",
+            registry
+        )
+        .unwrap();
+
+        let stream_exchange_multiplexed_channels = register_int_gauge_with_registry!(
+            "stream_exchange_multiplexed_channels",
+            "Number of exchange channels served on the multiplexed exchange connections",
+            registry
+        )
+        .unwrap();
+
         Self {
             registry,
             stream_exchange_bytes,
             stream_fragment_exchange_bytes,
             actor_sampled_serialize_duration_ns,
+            stream_exchange_permit_blocking_duration_ns,
+            stream_exchange_multiplexed_connections,
+            stream_exchange_multiplexed_channels,
         }
     }
 
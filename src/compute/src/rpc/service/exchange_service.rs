@@ -21,15 +21,17 @@ use futures_async_stream::try_stream;
 use risingwave_batch::task::BatchManager;
 use risingwave_pb::task_service::exchange_service_server::ExchangeService;
 use risingwave_pb::task_service::{
-    GetDataRequest, GetDataResponse, GetStreamRequest, GetStreamResponse,
+    GetDataRequest, GetDataResponse, GetMultiplexedStreamRequest, GetStreamRequest,
+    GetStreamResponse,
 };
 use risingwave_stream::executor::Message;
 use risingwave_stream::task::LocalStreamManager;
 use tokio::sync::mpsc::Receiver;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
 
 use crate::rpc::service::exchange_metrics::ExchangeServiceMetrics;
+use crate::rpc::service::multiplexed_exchange::{serve_multiplexed_stream, MultiplexedDataStream};
 
 /// Buffer size of the receiver of the remote channel.
 const BATCH_EXCHANGE_BUFFER_SIZE: usize = 1024;
@@ -39,6 +41,8 @@ pub struct ExchangeServiceImpl {
     batch_mgr: Arc<BatchManager>,
     stream_mgr: Arc<LocalStreamManager>,
     metrics: Arc<ExchangeServiceMetrics>,
+    /// Whether to attach the checksums to the chunks sent to the remote actors.
+    stream_exchange_checksum: bool,
 }
 
 type BatchDataStream = ReceiverStream<std::result::Result<GetDataResponse, Status>>;
@@ -47,6 +51,7 @@ type StreamDataStream = impl Stream<Item = std::result::Result<GetStreamResponse
 #[async_trait::async_trait]
 impl ExchangeService for ExchangeServiceImpl {
     type GetDataStream = BatchDataStream;
+    type GetMultiplexedStreamStream = MultiplexedDataStream;
     type GetStreamStream = StreamDataStream;
 
     #[cfg_attr(coverage, no_coverage)]
@@ -85,11 +90,30 @@ impl ExchangeService for ExchangeServiceImpl {
         Ok(Response::new(Self::get_stream_impl(
             self.metrics.clone(),
             peer_addr,
+            self.stream_exchange_checksum,
             receiver,
             up_down_actor_ids,
             up_down_fragment_ids,
         )))
     }
+
+    async fn get_multiplexed_stream(
+        &self,
+        request: Request<Streaming<GetMultiplexedStreamRequest>>,
+    ) -> std::result::Result<Response<Self::GetMultiplexedStreamStream>, Status> {
+        let peer_addr = request.remote_addr().ok_or_else(|| {
+            Status::unavailable("get_multiplexed_stream connection unestablished")
+        })?;
+        let stream_mgr = self.stream_mgr.clone();
+
+        Ok(Response::new(serve_multiplexed_stream(
+            self.metrics.clone(),
+            peer_addr,
+            self.stream_exchange_checksum,
+            request.into_inner(),
+            move |up_down_actor_ids| stream_mgr.take_receiver(up_down_actor_ids),
+        )))
+    }
 }
 
 impl ExchangeServiceImpl {
@@ -97,11 +121,13 @@ impl ExchangeServiceImpl {
         mgr: Arc<BatchManager>,
         stream_mgr: Arc<LocalStreamManager>,
         metrics: Arc<ExchangeServiceMetrics>,
+        stream_exchange_checksum: bool,
     ) -> Self {
         ExchangeServiceImpl {
             batch_mgr: mgr,
             stream_mgr,
             metrics,
+            stream_exchange_checksum,
         }
     }
 
@@ -109,6 +135,7 @@ impl ExchangeServiceImpl {
     async fn get_stream_impl(
         metrics: Arc<ExchangeServiceMetrics>,
         peer_addr: SocketAddr,
+        checksum: bool,
         mut receiver: Receiver<Message>,
         up_down_actor_ids: (u32, u32),
         up_down_fragment_ids: (u32, u32),
@@ -126,14 +153,14 @@ impl ExchangeServiceImpl {
             // add serialization duration metric with given sampling frequency
             let proto = if rr % SAMPLING_FREQUENCY == 0 {
                 let start_time = Instant::now();
-                let proto = msg.to_protobuf();
+                let proto = msg.to_protobuf_with_checksum(checksum);
                 metrics
                     .actor_sampled_serialize_duration_ns
                     .with_label_values(&[&up_actor_id])
                     .inc_by(start_time.elapsed().as_nanos() as u64);
                 proto
             } else {
-                msg.to_protobuf()
+                msg.to_protobuf_with_checksum(checksum)
             };
             rr += 1;
 
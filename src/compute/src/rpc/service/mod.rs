@@ -15,4 +15,5 @@
 pub mod exchange_metrics;
 pub mod exchange_service;
 pub mod monitor_service;
+pub mod multiplexed_exchange;
 pub mod stream_service;
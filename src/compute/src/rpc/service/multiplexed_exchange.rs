@@ -0,0 +1,565 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::{Stream, StreamExt};
+use risingwave_pb::task_service::get_multiplexed_stream_request::{
+    AddPermits, Subscribe, Unsubscribe, Value,
+};
+use risingwave_pb::task_service::get_multiplexed_stream_response::Payload;
+use risingwave_pb::task_service::{GetMultiplexedStreamRequest, GetMultiplexedStreamResponse};
+use risingwave_stream::error::StreamResult;
+use risingwave_stream::executor::Message;
+use risingwave_stream::task::{UpDownActorIds, UpDownFragmentIds};
+use tokio::sync::mpsc::{channel, unbounded_channel, Receiver, Sender};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+
+use crate::rpc::service::exchange_metrics::ExchangeServiceMetrics;
+
+/// Buffer size of the responses shared by all the channels on a multiplexed connection.
+const MULTIPLEXED_EXCHANGE_BUFFER_SIZE: usize = 1024;
+
+pub type MultiplexedDataStream =
+    ReceiverStream<std::result::Result<GetMultiplexedStreamResponse, Status>>;
+
+/// Serves a multiplexed exchange connection, which carries the messages of many channels from the
+/// local actors to the actors on the peer node. Each channel is forwarded by its own task, which
+/// only takes a message from the upstream after a permit is granted by the downstream, so that a
+/// slow downstream stops its own channel without blocking the others on the connection. The chunks
+/// carry their checksums if `checksum` is set.
+pub fn serve_multiplexed_stream(
+    metrics: Arc<ExchangeServiceMetrics>,
+    peer_addr: SocketAddr,
+    checksum: bool,
+    mut requests: impl Stream<Item = std::result::Result<GetMultiplexedStreamRequest, Status>>
+        + Send
+        + Unpin
+        + 'static,
+    take_receiver: impl Fn(UpDownActorIds) -> StreamResult<Receiver<Message>> + Send + 'static,
+) -> MultiplexedDataStream {
+    let (tx, rx) = channel(MULTIPLEXED_EXCHANGE_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        tracing::trace!(target: "events::compute::exchange", peer_addr = %peer_addr, "serve multiplexed stream exchange RPC");
+        metrics.stream_exchange_multiplexed_connections.inc();
+
+        // Each subscription is numbered, so that a channel finished by the upstream is removed
+        // only if it hasn't been subscribed again.
+        let mut channels: HashMap<UpDownActorIds, (u64, Arc<Semaphore>, JoinHandle<()>)> =
+            HashMap::new();
+        let mut next_seq = 0;
+        let (finished_tx, mut finished_rx) = unbounded_channel();
+        loop {
+            let request = tokio::select! {
+                request = requests.next() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+                Some((up_down_actor_ids, seq)) = finished_rx.recv() => {
+                    if let Entry::Occupied(entry) = channels.entry(up_down_actor_ids)
+                        && entry.get().0 == seq
+                    {
+                        entry.remove();
+                    }
+                    continue;
+                }
+            };
+            let value = match request {
+                Ok(GetMultiplexedStreamRequest { value: Some(value) }) => value,
+                Ok(request) => {
+                    tracing::warn!("invalid multiplexed stream request: {:?}", request);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("multiplexed stream from {} is broken: {}", peer_addr, e);
+                    break;
+                }
+            };
+
+            match value {
+                Value::Subscribe(Subscribe {
+                    channel: Some(channel),
+                    permits,
+                }) => {
+                    let up_down_actor_ids = (channel.up_actor_id, channel.down_actor_id);
+                    let up_down_fragment_ids = (channel.up_fragment_id, channel.down_fragment_id);
+                    let receiver = match take_receiver(up_down_actor_ids) {
+                        Ok(receiver) => receiver,
+                        Err(e) => {
+                            let response = GetMultiplexedStreamResponse {
+                                up_actor_id: up_down_actor_ids.0,
+                                down_actor_id: up_down_actor_ids.1,
+                                payload: Some(Payload::Error(e.to_string())),
+                            };
+                            if tx.send(Ok(response)).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                    };
+
+                    let seq = next_seq;
+                    next_seq += 1;
+                    let permits = Arc::new(Semaphore::new(permits as usize));
+                    let forward = forward_channel(
+                        metrics.clone(),
+                        receiver,
+                        permits.clone(),
+                        tx.clone(),
+                        up_down_actor_ids,
+                        up_down_fragment_ids,
+                        checksum,
+                    );
+                    let finished_tx = finished_tx.clone();
+                    let handle = tokio::spawn(async move {
+                        forward.await;
+                        let _ = finished_tx.send((up_down_actor_ids, seq));
+                    });
+                    if let Some((_, _, handle)) =
+                        channels.insert(up_down_actor_ids, (seq, permits, handle))
+                    {
+                        handle.abort();
+                    }
+                }
+                Value::AddPermits(AddPermits {
+                    up_actor_id,
+                    down_actor_id,
+                    permits,
+                }) => {
+                    if let Some((_, semaphore, _)) = channels.get(&(up_actor_id, down_actor_id)) {
+                        semaphore.add_permits(permits as usize);
+                    }
+                }
+                Value::Unsubscribe(Unsubscribe {
+                    up_actor_id,
+                    down_actor_id,
+                }) => {
+                    if let Some((_, _, handle)) = channels.remove(&(up_actor_id, down_actor_id)) {
+                        handle.abort();
+                    }
+                }
+                Value::Subscribe(Subscribe { channel: None, .. }) => {
+                    tracing::warn!("subscribe multiplexed stream without channel");
+                }
+            }
+        }
+
+        // The receivers are dropped along with the forwarding tasks, so that the upstream actors
+        // will find the channels closed, as with the dropped `GetStream` RPCs.
+        for (_, _, handle) in channels.into_values() {
+            handle.abort();
+        }
+        metrics.stream_exchange_multiplexed_connections.dec();
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// Decreases the channel count on drop, which happens when the forwarding task finishes or is
+/// aborted.
+struct ChannelGuard(Arc<ExchangeServiceMetrics>);
+
+impl Drop for ChannelGuard {
+    fn drop(&mut self) {
+        self.0.stream_exchange_multiplexed_channels.dec();
+    }
+}
+
+async fn forward_channel(
+    metrics: Arc<ExchangeServiceMetrics>,
+    mut receiver: Receiver<Message>,
+    permits: Arc<Semaphore>,
+    tx: Sender<std::result::Result<GetMultiplexedStreamResponse, Status>>,
+    up_down_actor_ids: UpDownActorIds,
+    up_down_fragment_ids: UpDownFragmentIds,
+    checksum: bool,
+) {
+    metrics.stream_exchange_multiplexed_channels.inc();
+    let _guard = ChannelGuard(metrics.clone());
+
+    let up_actor_id = up_down_actor_ids.0.to_string();
+    let down_actor_id = up_down_actor_ids.1.to_string();
+    let up_fragment_id = up_down_fragment_ids.0.to_string();
+    let down_fragment_id = up_down_fragment_ids.1.to_string();
+
+    loop {
+        // Do not take the message out of the upstream until the downstream grants a permit, so
+        // that the upstream actor gets back-pressured.
+        let start_time = Instant::now();
+        match permits.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => return,
+        }
+        metrics
+            .stream_exchange_permit_blocking_duration_ns
+            .with_label_values(&[&up_actor_id, &down_actor_id])
+            .inc_by(start_time.elapsed().as_nanos() as u64);
+
+        let Some(msg) = receiver.recv().await else {
+            break;
+        };
+        let response = GetMultiplexedStreamResponse {
+            up_actor_id: up_down_actor_ids.0,
+            down_actor_id: up_down_actor_ids.1,
+            payload: Some(Payload::Message(msg.to_protobuf_with_checksum(checksum))),
+        };
+        let bytes = Message::get_encoded_len(&response);
+
+        if tx.send(Ok(response)).await.is_err() {
+            return;
+        }
+
+        metrics
+            .stream_exchange_bytes
+            .with_label_values(&[&up_actor_id, &down_actor_id])
+            .inc_by(bytes as u64);
+        metrics
+            .stream_fragment_exchange_bytes
+            .with_label_values(&[&up_fragment_id, &down_fragment_id])
+            .inc_by(bytes as u64);
+    }
+
+    // Notify the downstream that the upstream is closed.
+    let _ = tx
+        .send(Ok(GetMultiplexedStreamResponse {
+            up_actor_id: up_down_actor_ids.0,
+            down_actor_id: up_down_actor_ids.1,
+            payload: None,
+        }))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use futures::future::try_join_all;
+    use risingwave_pb::task_service::exchange_service_server::{
+        ExchangeService, ExchangeServiceServer,
+    };
+    use risingwave_pb::task_service::{
+        GetDataRequest, GetDataResponse, GetStreamRequest, GetStreamResponse,
+    };
+    use risingwave_rpc_client::{ComputeClientPool, MultiplexedStream};
+    use risingwave_stream::executor::Barrier;
+    use tokio::sync::oneshot;
+    use tokio::time::sleep;
+    use tonic::{Request, Response, Streaming};
+
+    use super::*;
+
+    type Receivers = Arc<Mutex<HashMap<UpDownActorIds, Receiver<Message>>>>;
+
+    /// Serves the multiplexed exchange with the channels in `receivers`, as a compute node does
+    /// with the channels from its actors.
+    struct TestExchangeService {
+        metrics: Arc<ExchangeServiceMetrics>,
+        receivers: Receivers,
+    }
+
+    #[async_trait::async_trait]
+    impl ExchangeService for TestExchangeService {
+        type GetDataStream = ReceiverStream<std::result::Result<GetDataResponse, Status>>;
+        type GetMultiplexedStreamStream = MultiplexedDataStream;
+        type GetStreamStream = ReceiverStream<std::result::Result<GetStreamResponse, Status>>;
+
+        async fn get_data(
+            &self,
+            _: Request<GetDataRequest>,
+        ) -> std::result::Result<Response<Self::GetDataStream>, Status> {
+            unimplemented!()
+        }
+
+        async fn get_stream(
+            &self,
+            _: Request<GetStreamRequest>,
+        ) -> std::result::Result<Response<Self::GetStreamStream>, Status> {
+            unimplemented!()
+        }
+
+        async fn get_multiplexed_stream(
+            &self,
+            request: Request<Streaming<GetMultiplexedStreamRequest>>,
+        ) -> std::result::Result<Response<Self::GetMultiplexedStreamStream>, Status> {
+            let peer_addr = request.remote_addr().unwrap();
+            let receivers = self.receivers.clone();
+            Ok(Response::new(serve_multiplexed_stream(
+                self.metrics.clone(),
+                peer_addr,
+                false,
+                request.into_inner(),
+                move |ids| {
+                    receivers.lock().unwrap().remove(&ids).ok_or_else(|| {
+                        anyhow::anyhow!("channel from {} to {} does not exist", ids.0, ids.1).into()
+                    })
+                },
+            )))
+        }
+    }
+
+    struct TestNode {
+        addr: SocketAddr,
+        metrics: Arc<ExchangeServiceMetrics>,
+        receivers: Receivers,
+        client_pool: ComputeClientPool,
+        shutdown: oneshot::Sender<()>,
+        join_handle: JoinHandle<()>,
+    }
+
+    impl TestNode {
+        async fn start(addr: &str) -> Self {
+            let addr: SocketAddr = addr.parse().unwrap();
+            let metrics = Arc::new(ExchangeServiceMetrics::unused());
+            let receivers = Receivers::default();
+            let exchange_svc = ExchangeServiceServer::new(TestExchangeService {
+                metrics: metrics.clone(),
+                receivers: receivers.clone(),
+            });
+            let (shutdown, shutdown_recv) = oneshot::channel();
+            let join_handle = tokio::spawn(async move {
+                tonic::transport::Server::builder()
+                    .add_service(exchange_svc)
+                    .serve_with_shutdown(addr, async move {
+                        shutdown_recv.await.unwrap();
+                    })
+                    .await
+                    .unwrap();
+            });
+            sleep(Duration::from_secs(1)).await;
+
+            Self {
+                addr,
+                metrics,
+                receivers,
+                client_pool: ComputeClientPool::new(1),
+                shutdown,
+                join_handle,
+            }
+        }
+
+        /// Adds a channel from the actor on this node, of which the sender is returned.
+        fn add_channel(&self, ids: UpDownActorIds, buffer_size: usize) -> Sender<Message> {
+            let (tx, rx) = channel(buffer_size);
+            self.receivers.lock().unwrap().insert(ids, rx);
+            tx
+        }
+
+        /// Subscribes the channel from the actor on the `upstream` node.
+        async fn subscribe(
+            &self,
+            upstream: &TestNode,
+            ids: UpDownActorIds,
+            permits: u32,
+        ) -> MultiplexedStream {
+            self.client_pool
+                .get_by_addr(upstream.addr.into())
+                .await
+                .unwrap()
+                .get_multiplexed_stream(ids.0, ids.1, 0, 0, permits)
+                .await
+                .unwrap()
+        }
+
+        async fn shutdown(self) {
+            self.shutdown.send(()).unwrap();
+            self.join_handle.await.unwrap();
+        }
+    }
+
+    async fn recv_epoch(stream: &mut MultiplexedStream) -> u64 {
+        let message = stream.next().await.unwrap().unwrap();
+        Message::from_protobuf(&message)
+            .unwrap()
+            .as_barrier()
+            .unwrap()
+            .epoch
+            .curr
+    }
+
+    #[tokio::test]
+    async fn test_connection_count_with_many_channels() {
+        const FRAGMENT_COUNT: u32 = 8;
+        const ACTORS_PER_FRAGMENT: u32 = 4;
+        const CHANNELS_PER_DIRECTION: i64 = (FRAGMENT_COUNT * ACTORS_PER_FRAGMENT.pow(2)) as i64;
+
+        let node_a = TestNode::start("127.0.0.1:12351").await;
+        let node_b = TestNode::start("127.0.0.1:12352").await;
+
+        // Each fragment is hash-dispatched to the downstream fragment on the other node, and the
+        // actors of the fragments on both nodes are interleaved.
+        let mut senders = vec![];
+        let mut streams = vec![];
+        for fragment_id in 0..FRAGMENT_COUNT {
+            let (upstream, downstream, base) = if fragment_id % 2 == 0 {
+                (&node_a, &node_b, 0)
+            } else {
+                (&node_b, &node_a, 10000)
+            };
+            for up in 0..ACTORS_PER_FRAGMENT {
+                for down in 0..ACTORS_PER_FRAGMENT {
+                    let up_actor_id = base + fragment_id * 100 + up;
+                    let down_actor_id = base + 1000 + fragment_id * 100 + down;
+                    let ids = (up_actor_id, down_actor_id);
+                    senders.push(upstream.add_channel(ids, 16));
+                    streams.push(downstream.subscribe(upstream, ids, 16).await);
+                }
+            }
+        }
+        // Subscribing the fragments on the reverse direction as well.
+        for fragment_id in 0..FRAGMENT_COUNT {
+            let (upstream, downstream, base) = if fragment_id % 2 == 0 {
+                (&node_b, &node_a, 20000)
+            } else {
+                (&node_a, &node_b, 30000)
+            };
+            for up in 0..ACTORS_PER_FRAGMENT {
+                for down in 0..ACTORS_PER_FRAGMENT {
+                    let up_actor_id = base + fragment_id * 100 + up;
+                    let down_actor_id = base + 1000 + fragment_id * 100 + down;
+                    let ids = (up_actor_id, down_actor_id);
+                    senders.push(upstream.add_channel(ids, 16));
+                    streams.push(downstream.subscribe(upstream, ids, 16).await);
+                }
+            }
+        }
+
+        for epoch in 1..=3 {
+            try_join_all(
+                senders
+                    .iter()
+                    .map(|tx| tx.send(Message::Barrier(Barrier::new_test_barrier(epoch)))),
+            )
+            .await
+            .unwrap();
+            for stream in &mut streams {
+                assert_eq!(recv_epoch(stream).await, epoch);
+            }
+        }
+
+        // All the channels between the nodes are carried by a single connection per direction.
+        for node in [&node_a, &node_b] {
+            assert_eq!(
+                node.metrics.stream_exchange_multiplexed_connections.get(),
+                1
+            );
+            assert_eq!(
+                node.metrics.stream_exchange_multiplexed_channels.get(),
+                CHANNELS_PER_DIRECTION
+            );
+        }
+
+        // The channels are closed on drop, while the connections are kept.
+        drop(streams);
+        for node in [&node_a, &node_b] {
+            while node.metrics.stream_exchange_multiplexed_channels.get() > 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+            assert_eq!(
+                node.metrics.stream_exchange_multiplexed_connections.get(),
+                1
+            );
+        }
+        assert!(senders.iter().all(|tx| tx.is_closed()));
+
+        // Subscribing a channel that does not exist only fails the channel itself.
+        let mut stream = node_b.subscribe(&node_a, (42, 43), 16).await;
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+        let tx = node_a.add_channel((44, 45), 16);
+        let mut stream = node_b.subscribe(&node_a, (44, 45), 16).await;
+        tx.send(Message::Barrier(Barrier::new_test_barrier(4)))
+            .await
+            .unwrap();
+        assert_eq!(recv_epoch(&mut stream).await, 4);
+        assert_eq!(
+            node_a.metrics.stream_exchange_multiplexed_connections.get(),
+            1
+        );
+
+        node_a.shutdown().await;
+        node_b.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_isolation() {
+        const PERMITS: u32 = 4;
+        const BUFFER_SIZE: usize = 16;
+        const MESSAGE_COUNT: u64 = 100;
+
+        let node_a = TestNode::start("127.0.0.1:12353").await;
+        let node_b = TestNode::start("127.0.0.1:12354").await;
+
+        let fast_tx = node_a.add_channel((1, 2), BUFFER_SIZE);
+        let slow_tx = node_a.add_channel((3, 4), BUFFER_SIZE);
+        let mut fast = node_b.subscribe(&node_a, (1, 2), PERMITS).await;
+        let mut slow = node_b.subscribe(&node_a, (3, 4), PERMITS).await;
+
+        let send_all = |tx: Sender<Message>, sent: Arc<std::sync::atomic::AtomicU64>| async move {
+            for epoch in 1..=MESSAGE_COUNT {
+                tx.send(Message::Barrier(Barrier::new_test_barrier(epoch)))
+                    .await
+                    .unwrap();
+                sent.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        };
+        let slow_sent = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let slow_sender = tokio::spawn(send_all(slow_tx, slow_sent.clone()));
+        let fast_sender = tokio::spawn(send_all(fast_tx, Default::default()));
+
+        // The fast channel goes through, although the slow one on the same connection is never
+        // consumed.
+        for epoch in 1..=MESSAGE_COUNT {
+            assert_eq!(recv_epoch(&mut fast).await, epoch);
+        }
+        fast_sender.await.unwrap();
+
+        // The slow channel only forwards the messages within its window, after which its upstream
+        // is back-pressured by the full buffer.
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            slow_sent.load(std::sync::atomic::Ordering::SeqCst),
+            PERMITS as u64 + BUFFER_SIZE as u64
+        );
+        assert!(
+            node_a
+                .metrics
+                .stream_exchange_permit_blocking_duration_ns
+                .with_label_values(&["3", "4"])
+                .get()
+                > 0
+        );
+
+        // The slow channel catches up once it's consumed.
+        for epoch in 1..=MESSAGE_COUNT {
+            assert_eq!(recv_epoch(&mut slow).await, epoch);
+        }
+        slow_sender.await.unwrap();
+        assert_eq!(
+            node_a.metrics.stream_exchange_multiplexed_connections.get(),
+            1
+        );
+
+        node_a.shutdown().await;
+        node_b.shutdown().await;
+    }
+}
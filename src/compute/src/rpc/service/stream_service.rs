@@ -24,7 +24,9 @@ use risingwave_storage::dispatch_state_store;
 use risingwave_stream::error::StreamError;
 use risingwave_stream::executor::Barrier;
 use risingwave_stream::task::{LocalStreamManager, StreamEnvironment};
+use risingwave_tracing::TracingContext;
 use tonic::{Request, Response, Status};
+use tracing::Instrument;
 
 #[derive(Clone)]
 pub struct StreamServiceImpl {
@@ -133,8 +135,13 @@ impl StreamService for StreamServiceImpl {
         let barrier =
             Barrier::from_protobuf(req.get_barrier().unwrap()).map_err(StreamError::from)?;
 
-        self.mgr
-            .send_barrier(&barrier, req.actor_ids_to_send, req.actor_ids_to_collect)?;
+        barrier
+            .tracing_context
+            .child_span(|| tracing::info_span!("inject_barrier", epoch = barrier.epoch.curr))
+            .in_scope(|| {
+                self.mgr
+                    .send_barrier(&barrier, req.actor_ids_to_send, req.actor_ids_to_collect)
+            })?;
 
         Ok(Response::new(InjectBarrierResponse {
             request_id: req.request_id,
@@ -148,9 +155,12 @@ impl StreamService for StreamServiceImpl {
         request: Request<BarrierCompleteRequest>,
     ) -> Result<Response<BarrierCompleteResponse>, Status> {
         let req = request.into_inner();
+        let span = TracingContext::from_protobuf(&req.tracing_context)
+            .child_span(|| tracing::info_span!("barrier_complete", epoch = req.prev_epoch));
         let (collect_result, checkpoint) = self
             .mgr
             .collect_barrier(req.prev_epoch)
+            .instrument(span.clone())
             .stack_trace(format!("collect_barrier (epoch {})", req.prev_epoch))
             .await?;
         // Must finish syncing data written in the epoch before respond back to ensure persistence
@@ -158,6 +168,10 @@ impl StreamService for StreamServiceImpl {
         let synced_sstables = if checkpoint {
             self.mgr
                 .sync_epoch(req.prev_epoch)
+                .instrument(
+                    TracingContext::from_span(&span)
+                        .child_span(|| tracing::info_span!("sync_epoch", epoch = req.prev_epoch)),
+                )
                 .stack_trace(format!("sync_epoch (epoch {})", req.prev_epoch))
                 .await?
         } else {
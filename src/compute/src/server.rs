@@ -23,7 +23,7 @@ use risingwave_common::config::{load_config, MAX_CONNECTION_WINDOW_SIZE};
 use risingwave_common::monitor::process_linux::monitor_process;
 use risingwave_common::util::addr::HostAddr;
 use risingwave_common_service::metrics_manager::MetricsManager;
-use risingwave_pb::common::WorkerType;
+use risingwave_pb::common::{WorkerRole, WorkerType};
 use risingwave_pb::monitor_service::monitor_service_server::MonitorServiceServer;
 use risingwave_pb::stream_service::stream_service_server::StreamServiceServer;
 use risingwave_pb::task_service::exchange_service_server::ExchangeServiceServer;
@@ -53,7 +53,7 @@ use crate::rpc::service::monitor_service::{
     GrpcStackTraceManagerRef, MonitorServiceImpl, StackTraceMiddlewareLayer,
 };
 use crate::rpc::service::stream_service::StreamServiceImpl;
-use crate::{AsyncStackTraceOption, ComputeNodeConfig, ComputeNodeOpts};
+use crate::{AsyncStackTraceOption, ComputeNodeConfig, ComputeNodeOpts, Role};
 
 /// Bootstraps the compute-node.
 pub async fn compute_node_serve(
@@ -74,11 +74,17 @@ pub async fn compute_node_serve(
     let batch_config = Arc::new(config.batch.clone());
 
     // Register to the cluster. We're not ready to serve until activate is called.
-    let meta_client = MetaClient::register_new(
+    let role = match opts.role {
+        Role::Streaming => WorkerRole::Streaming,
+        Role::Serving => WorkerRole::Serving,
+        Role::Both => WorkerRole::Both,
+    };
+    let meta_client = MetaClient::register_new_with_role(
         &opts.meta_address,
         WorkerType::ComputeNode,
         &client_addr,
         config.streaming.worker_node_parallelism,
+        role,
     )
     .await
     .unwrap();
@@ -134,6 +140,9 @@ pub async fn compute_node_serve(
             // todo: set shutdown_sender in HummockStorage.
             let write_memory_limit =
                 storage_config.compactor_memory_limit_mb as u64 * 1024 * 1024 / 2;
+            let task_memory_limiter = Arc::new(MemoryLimiter::new(
+                storage_config.compactor_memory_limit_mb as u64 * 1024 * 1024,
+            ));
             let context = Arc::new(Context {
                 options: storage_config,
                 hummock_meta_client: hummock_meta_client.clone(),
@@ -146,6 +155,7 @@ pub async fn compute_node_serve(
                     .filter_key_extractor_manager()
                     .clone(),
                 read_memory_limiter,
+                task_memory_limiter,
                 sstable_id_manager: storage.sstable_id_manager(),
                 task_progress_manager: Default::default(),
             });
@@ -235,8 +245,12 @@ pub async fn compute_node_serve(
 
     // Boot the runtime gRPC services.
     let batch_srv = BatchServiceImpl::new(batch_mgr.clone(), batch_env);
-    let exchange_srv =
-        ExchangeServiceImpl::new(batch_mgr, stream_mgr.clone(), exchange_srv_metrics);
+    let exchange_srv = ExchangeServiceImpl::new(
+        batch_mgr,
+        stream_mgr.clone(),
+        exchange_srv_metrics,
+        config.streaming.developer.stream_exchange_checksum,
+    );
     let stream_srv = StreamServiceImpl::new(stream_mgr.clone(), stream_env.clone());
     let monitor_srv = MonitorServiceImpl::new(stream_mgr, grpc_stack_trace_mgr.clone());
 
@@ -15,6 +15,7 @@
 #![feature(generators)]
 #![feature(proc_macro_hygiene, stmt_expr_attributes)]
 
+use std::ops::Bound;
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -238,9 +239,7 @@ async fn test_table_materialize() -> StreamResult<()> {
     let message = materialize.next().await.unwrap()?;
     let mut col_row_ids = vec![];
     match message {
-        Message::Watermark(_) => {
-            todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-        }
+        Message::Watermark(_) => unreachable!(),
         Message::Chunk(c) => {
             let col_row_id = c.columns()[0].array_ref().as_int64();
             col_row_ids.push(col_row_id.value_at(0).unwrap());
@@ -313,9 +312,7 @@ async fn test_table_materialize() -> StreamResult<()> {
     // Poll `Materialize`, should output the same deletion stream chunk
     let message = materialize.next().await.unwrap()?;
     match message {
-        Message::Watermark(_) => {
-            todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-        }
+        Message::Watermark(_) => unreachable!(),
         Message::Chunk(c) => {
             let col_row_id = c.columns()[0].array_ref().as_int64();
             assert_eq!(col_row_id.value_at(0).unwrap(), col_row_ids[0]);
@@ -463,3 +460,97 @@ async fn test_row_seq_scan() -> Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn test_row_seq_scan_with_ranges() -> Result<()> {
+    // In this test we scan ranges on a descending pk column, which may contain NULL.
+    let memory_state_store = MemoryStateStore::new();
+
+    let column_descs = vec![
+        ColumnDesc::unnamed(ColumnId::from(0), DataType::Int32),
+        ColumnDesc::unnamed(ColumnId::from(1), DataType::Int32),
+        ColumnDesc::unnamed(ColumnId::from(2), DataType::Int64),
+    ];
+    let order_types = vec![OrderType::Ascending, OrderType::Descending];
+
+    let mut state = StateTable::new_without_distribution(
+        memory_state_store.clone(),
+        TableId::from(0x42),
+        column_descs.clone(),
+        order_types.clone(),
+        vec![0_usize, 1_usize],
+    );
+    let table = StorageTable::for_test(
+        memory_state_store.clone(),
+        TableId::from(0x42),
+        column_descs.clone(),
+        order_types,
+        vec![0, 1],
+    );
+
+    let epoch = EpochPair::new_test_epoch(1);
+    state.init_epoch(epoch);
+    epoch.inc();
+    state.insert(Row(vec![Some(1_i32.into()), None, Some(0_i64.into())]));
+    for i in 1..=9_i32 {
+        state.insert(Row(vec![
+            Some(1_i32.into()),
+            Some(i.into()),
+            Some((i as i64).into()),
+        ]));
+    }
+    state.insert(Row(vec![
+        Some(2_i32.into()),
+        Some(5_i32.into()),
+        Some(5_i64.into()),
+    ]));
+    state.commit_for_test(epoch.inc()).await.unwrap();
+
+    let scan_ranges = vec![
+        // 3 <= k2 < 6 on the descending column yields 5, 4, 3.
+        ScanRange {
+            pk_prefix: Row(vec![Some(1_i32.into())]),
+            next_col_bounds: (
+                Bound::Included(Some(3_i32.into())),
+                Bound::Excluded(Some(6_i32.into())),
+            ),
+        },
+        // k2 <= 2 yields 2, 1, but not NULL.
+        ScanRange {
+            pk_prefix: Row(vec![Some(1_i32.into())]),
+            next_col_bounds: (Bound::Unbounded, Bound::Included(Some(2_i32.into()))),
+        },
+        ScanRange {
+            pk_prefix: Row(vec![Some(2_i32.into())]),
+            next_col_bounds: (Bound::Unbounded, Bound::Unbounded),
+        },
+    ];
+    let executor = Box::new(RowSeqScanExecutor::new(
+        table,
+        scan_ranges,
+        u64::MAX,
+        1024,
+        "RowSeqScanExecutor2".to_string(),
+        None,
+    ));
+
+    let mut rows = vec![];
+    let mut stream = executor.execute();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        rows.extend(chunk.rows().map(|row| row.to_owned_row()));
+    }
+    rows.sort();
+    assert_eq!(
+        rows,
+        [(1, 1), (1, 2), (1, 3), (1, 4), (1, 5), (2, 5)]
+            .into_iter()
+            .map(|(k1, k2): (i32, i32)| Row(vec![
+                Some(k1.into()),
+                Some(k2.into()),
+                Some((k2 as i64).into())
+            ]))
+            .collect_vec()
+    );
+    Ok(())
+}
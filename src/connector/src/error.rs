@@ -23,6 +23,9 @@ pub enum ConnectorError {
     #[error("Invalid parameter {name}: {reason}")]
     InvalidParam { name: &'static str, reason: String },
 
+    #[error("Invalid option: {0}")]
+    InvalidOption(String),
+
     #[error("Kafka error: {0}")]
     Kafka(#[from] rdkafka::error::KafkaError),
 
@@ -0,0 +1,439 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Declarative schemas of the options accepted by connectors in the `WITH` clause, so that the
+//! options can be validated when the source or sink is created instead of failing at runtime.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::error::ConnectorError;
+use crate::sink::kafka::KAFKA_SINK_OPTIONS;
+use crate::source::datagen::DATAGEN_OPTIONS;
+use crate::source::filesystem::s3::S3_OPTIONS;
+use crate::source::kafka::KAFKA_OPTIONS;
+use crate::source::nexmark::NEXMARK_OPTIONS;
+
+/// Type of the value of an option. All values are strings in the `WITH` clause, and the type
+/// decides how they are parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionType {
+    String,
+    Integer,
+    UnsignedInteger,
+    Boolean,
+    /// One of the listed values.
+    OneOf {
+        values: &'static [&'static str],
+        ignore_case: bool,
+    },
+}
+
+impl OptionType {
+    fn check(&self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            OptionType::String => true,
+            OptionType::Integer => value.parse::<i64>().is_ok(),
+            OptionType::UnsignedInteger => value.parse::<u64>().is_ok(),
+            OptionType::Boolean => value.parse::<bool>().is_ok(),
+            OptionType::OneOf {
+                values,
+                ignore_case,
+            } => values.iter().any(|v| {
+                if *ignore_case {
+                    v.eq_ignore_ascii_case(value)
+                } else {
+                    *v == value
+                }
+            }),
+        };
+        if valid {
+            return Ok(());
+        }
+        Err(match self {
+            OptionType::String => unreachable!(),
+            OptionType::Integer => "expected an integer".to_string(),
+            OptionType::UnsignedInteger => "expected a non-negative integer".to_string(),
+            OptionType::Boolean => "expected true or false".to_string(),
+            OptionType::OneOf { values, .. } => format!("expected one of {}", values.join(", ")),
+        })
+    }
+}
+
+/// Description of an option accepted by a connector.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectorOptionDesc {
+    pub name: &'static str,
+    pub option_type: OptionType,
+    pub required: bool,
+    /// The value used by the connector if the option is not specified.
+    pub default: Option<&'static str>,
+    /// Former names of the option, which are still accepted but deprecated in favor of `name`.
+    pub aliases: &'static [&'static str],
+    /// If set, the option itself is deprecated, and this is shown to the user when it is used.
+    pub deprecation: Option<&'static str>,
+}
+
+impl ConnectorOptionDesc {
+    pub const fn required(name: &'static str, option_type: OptionType) -> Self {
+        Self {
+            name,
+            option_type,
+            required: true,
+            default: None,
+            aliases: &[],
+            deprecation: None,
+        }
+    }
+
+    pub const fn optional(name: &'static str, option_type: OptionType) -> Self {
+        Self {
+            required: false,
+            ..Self::required(name, option_type)
+        }
+    }
+
+    pub const fn with_default(self, default: &'static str) -> Self {
+        Self {
+            default: Some(default),
+            ..self
+        }
+    }
+
+    pub const fn with_aliases(self, aliases: &'static [&'static str]) -> Self {
+        Self { aliases, ..self }
+    }
+
+    pub const fn deprecated(self, deprecation: &'static str) -> Self {
+        Self {
+            deprecation: Some(deprecation),
+            ..self
+        }
+    }
+}
+
+/// The options accepted by a connector.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectorOptionsSchema {
+    pub connector: &'static str,
+    pub options: &'static [ConnectorOptionDesc],
+    /// Options with these prefixes are passed to the connector without validation, e.g. the
+    /// `fields.` options of datagen which are keyed by column names.
+    pub dynamic_prefixes: &'static [&'static str],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectorKind {
+    Source,
+    Sink,
+}
+
+const SOURCE_OPTIONS: &[ConnectorOptionsSchema] =
+    &[KAFKA_OPTIONS, NEXMARK_OPTIONS, DATAGEN_OPTIONS, S3_OPTIONS];
+
+const SINK_OPTIONS: &[ConnectorOptionsSchema] = &[KAFKA_SINK_OPTIONS];
+
+/// Returns the options schema of the connector, or `None` if the connector has not declared one
+/// and its options are not validated.
+pub fn get_options_schema(
+    kind: ConnectorKind,
+    connector: &str,
+) -> Option<&'static ConnectorOptionsSchema> {
+    let schemas = match kind {
+        ConnectorKind::Source => SOURCE_OPTIONS,
+        ConnectorKind::Sink => SINK_OPTIONS,
+    };
+    schemas
+        .iter()
+        .find(|schema| schema.connector.eq_ignore_ascii_case(connector))
+}
+
+impl ConnectorOptionsSchema {
+    /// Validates the options of the `WITH` clause. `common_keys` are accepted besides the options
+    /// of the connector, e.g. `connector` itself and the options of the row format.
+    ///
+    /// Returns the deprecation notices to be shown to the user.
+    pub fn validate(
+        &self,
+        options: &HashMap<String, String>,
+        common_keys: &[&str],
+    ) -> Result<Vec<String>, ConnectorError> {
+        let mut notices = vec![];
+        let mut specified: HashMap<&str, &str> = HashMap::new();
+
+        for (key, value) in options.iter().sorted() {
+            if common_keys.contains(&key.as_str())
+                || self
+                    .dynamic_prefixes
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            {
+                continue;
+            }
+            let Some(desc) = self
+                .options
+                .iter()
+                .find(|desc| desc.name == key || desc.aliases.contains(&key.as_str()))
+            else {
+                return Err(self.unknown_option_error(key, common_keys));
+            };
+
+            if let Some(other_key) = specified.insert(desc.name, key) {
+                return Err(ConnectorError::InvalidOption(format!(
+                    "option '{}' of {} is specified by both '{}' and '{}'",
+                    desc.name, self.connector, other_key, key
+                )));
+            }
+            if let Err(reason) = desc.option_type.check(value) {
+                return Err(ConnectorError::InvalidOption(format!(
+                    "invalid value '{}' of option '{}': {}",
+                    value, key, reason
+                )));
+            }
+            if key != desc.name {
+                notices.push(format!(
+                    "option '{}' is deprecated, please use '{}' instead",
+                    key, desc.name
+                ));
+            }
+            if let Some(deprecation) = desc.deprecation {
+                notices.push(format!(
+                    "option '{}' is deprecated: {}",
+                    desc.name, deprecation
+                ));
+            }
+        }
+
+        let missing = self
+            .options
+            .iter()
+            .filter(|desc| desc.required && !specified.contains_key(desc.name))
+            .map(|desc| format!("'{}'", desc.name))
+            .collect_vec();
+        if !missing.is_empty() {
+            return Err(ConnectorError::InvalidOption(format!(
+                "missing required option {} of {}",
+                missing.join(", "),
+                self.connector
+            )));
+        }
+
+        Ok(notices)
+    }
+
+    fn unknown_option_error(&self, key: &str, common_keys: &[&str]) -> ConnectorError {
+        let mut message = format!("unknown option '{}' of {}", key, self.connector);
+        let candidates = self
+            .options
+            .iter()
+            .flat_map(|desc| std::iter::once(desc.name).chain(desc.aliases.iter().copied()))
+            .chain(common_keys.iter().copied());
+        if let Some(suggestion) = suggest(key, candidates) {
+            write!(message, ", did you mean '{}'?", suggestion).unwrap();
+        }
+        ConnectorError::InvalidOption(message)
+    }
+}
+
+/// Returns the candidate nearest to `key` by edit distance, if it is close enough to be a typo.
+fn suggest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (key.chars().count() / 3).max(2);
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect_vec();
+    let mut distances = (0..=b.len()).collect_vec();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut last_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = last_diagonal + usize::from(a_char != *b_char);
+            last_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(distances[j + 1] + 1);
+        }
+    }
+    distances[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn validate_source(
+        connector: &str,
+        pairs: &[(&str, &str)],
+    ) -> Result<Vec<String>, ConnectorError> {
+        get_options_schema(ConnectorKind::Source, connector)
+            .unwrap()
+            .validate(&options(pairs), &["connector"])
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("nexmark.split.nums", "nexmark.split.num"), 1);
+        assert_eq!(edit_distance("topci", "topic"), 2);
+    }
+
+    #[test]
+    fn test_suggestion() {
+        let check = |connector: &str, key: &str, expected: Option<&str>| {
+            let err = validate_source(connector, &[(key, "1")]).unwrap_err();
+            let message = err.to_string();
+            assert!(message.contains(&format!("unknown option '{}'", key)));
+            match expected {
+                Some(expected) => assert!(
+                    message.ends_with(&format!("did you mean '{}'?", expected)),
+                    "{}",
+                    message
+                ),
+                None => assert!(!message.contains("did you mean"), "{}", message),
+            }
+        };
+
+        check("nexmark", "nexmark.split.nums", Some("nexmark.split.num"));
+        check("nexmark", "nexmark.event.number", Some("nexmark.event.num"));
+        check("nexmark", "nexmark.tabletype", Some("nexmark.table.type"));
+        check(
+            "kafka",
+            "properties.bootstrap.servers",
+            Some("properties.bootstrap.server"),
+        );
+        check("kafka", "topci", Some("topic"));
+        check("kafka", "conector", Some("connector"));
+        check(
+            "datagen",
+            "datagen.row.per.second",
+            Some("datagen.rows.per.second"),
+        );
+        check("s3", "s3.bucketname", Some("s3.bucket_name"));
+        // Too far from any valid option.
+        check("kafka", "foo", None);
+        check("nexmark", "kafka.topic", None);
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let err = validate_source("kafka", &[("topic", "t")]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("missing required option 'properties.bootstrap.server' of kafka"));
+
+        let err = validate_source("s3", &[("s3.region_name", "r")]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("missing required option 's3.bucket_name', 'sqs_queue_name' of s3"));
+
+        // Aliases also satisfy the requirement.
+        let notices = validate_source(
+            "kafka",
+            &[("kafka.brokers", "localhost:9092"), ("topic", "t")],
+        )
+        .unwrap();
+        assert_eq!(
+            notices,
+            vec![
+                "option 'kafka.brokers' is deprecated, please use 'properties.bootstrap.server' \
+                instead"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let err = validate_source(
+            "nexmark",
+            &[("nexmark.table.type", "Bid"), ("nexmark.split.num", "two")],
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid value 'two' of option 'nexmark.split.num': expected an integer"));
+
+        let err = validate_source("nexmark", &[("nexmark.table.type", "bid")]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected one of Person, Auction, Bid"));
+
+        let err = validate_source(
+            "kafka",
+            &[
+                ("properties.bootstrap.server", "localhost:9092"),
+                ("kafka.brokers", "localhost:9092"),
+                ("topic", "t"),
+            ],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(
+            "option 'properties.bootstrap.server' of kafka is specified by both \
+            'kafka.brokers' and 'properties.bootstrap.server'"
+        ));
+
+        // Keys with dynamic prefixes and common keys are not validated.
+        validate_source(
+            "datagen",
+            &[
+                ("connector", "datagen"),
+                ("fields.v1.kind", "sequence"),
+                ("datagen.rows.per.second", "10"),
+            ],
+        )
+        .unwrap();
+        validate_source(
+            "kafka",
+            &[
+                ("properties.bootstrap.server", "localhost:9092"),
+                ("topic", "t"),
+                ("scan.startup.mode", "EARLIEST"),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_deprecated_option() {
+        const SCHEMA: ConnectorOptionsSchema = ConnectorOptionsSchema {
+            connector: "test",
+            options: &[ConnectorOptionDesc::optional("old", OptionType::String)
+                .deprecated("it is ignored")],
+            dynamic_prefixes: &[],
+        };
+        let notices = SCHEMA.validate(&options(&[("old", "v")]), &[]).unwrap();
+        assert_eq!(notices, vec!["option 'old' is deprecated: it is ignored"]);
+    }
+
+    #[test]
+    fn test_unregistered_connector() {
+        assert!(get_options_schema(ConnectorKind::Source, "pulsar").is_none());
+        assert!(get_options_schema(ConnectorKind::Sink, "mysql").is_none());
+        assert!(get_options_schema(ConnectorKind::Source, "Kafka").is_some());
+    }
+}
@@ -31,10 +31,28 @@ use serde_json::{json, Map, Value};
 use tracing::warn;
 
 use super::{Sink, SinkError};
+use crate::options::{ConnectorOptionDesc, ConnectorOptionsSchema, OptionType};
 use crate::sink::Result;
 
 pub const KAFKA_SINK: &str = "kafka";
 
+/// The options of [`KafkaConfig`]. `identifier` is set by the sink executor.
+pub const KAFKA_SINK_OPTIONS: ConnectorOptionsSchema = ConnectorOptionsSchema {
+    connector: KAFKA_SINK,
+    options: &[
+        ConnectorOptionDesc::required("kafka.brokers", OptionType::String),
+        ConnectorOptionDesc::required("kafka.topic", OptionType::String),
+        ConnectorOptionDesc::required(
+            "format",
+            OptionType::OneOf {
+                values: &["append_only", "debezium"],
+                ignore_case: false,
+            },
+        ),
+    ],
+    dynamic_prefixes: &[],
+};
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct KafkaConfig {
     #[serde(rename = "kafka.brokers")]
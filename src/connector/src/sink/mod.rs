@@ -15,6 +15,7 @@
 pub mod kafka;
 pub mod mysql;
 pub mod redis;
+pub mod s3_parquet;
 
 use std::collections::HashMap;
 
@@ -23,6 +24,7 @@ use enum_as_inner::EnumAsInner;
 use risingwave_common::array::StreamChunk;
 use risingwave_common::catalog::Schema;
 use risingwave_common::error::{ErrorCode, RwError};
+use risingwave_object_store::object::ObjectError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 pub use tracing;
@@ -30,6 +32,7 @@ pub use tracing;
 use crate::sink::kafka::{KafkaConfig, KafkaSink, KAFKA_SINK};
 pub use crate::sink::mysql::{MySqlConfig, MySqlSink, MYSQL_SINK};
 use crate::sink::redis::{RedisConfig, RedisSink};
+use crate::sink::s3_parquet::{S3ParquetConfig, S3_PARQUET_SINK};
 
 #[async_trait]
 pub trait Sink {
@@ -52,6 +55,7 @@ pub enum SinkConfig {
     Mysql(MySqlConfig),
     Redis(RedisConfig),
     Kafka(KafkaConfig),
+    S3Parquet(S3ParquetConfig),
 }
 
 #[derive(Clone, Debug, EnumAsInner, Serialize, Deserialize)]
@@ -70,6 +74,9 @@ impl SinkConfig {
         match sink_type.to_lowercase().as_str() {
             KAFKA_SINK => Ok(SinkConfig::Kafka(KafkaConfig::from_hashmap(properties)?)),
             MYSQL_SINK => Ok(SinkConfig::Mysql(MySqlConfig::from_hashmap(properties)?)),
+            S3_PARQUET_SINK => Ok(SinkConfig::S3Parquet(S3ParquetConfig::from_hashmap(
+                properties,
+            )?)),
             _ => unimplemented!(),
         }
     }
@@ -79,6 +86,7 @@ impl SinkConfig {
             SinkConfig::Mysql(_) => "mysql",
             SinkConfig::Kafka(_) => "kafka",
             SinkConfig::Redis(_) => "redis",
+            SinkConfig::S3Parquet(_) => S3_PARQUET_SINK,
         }
     }
 }
@@ -96,6 +104,13 @@ impl SinkImpl {
             SinkConfig::Mysql(cfg) => SinkImpl::MySql(Box::new(MySqlSink::new(cfg).await?)),
             SinkConfig::Redis(cfg) => SinkImpl::Redis(Box::new(RedisSink::new(cfg)?)),
             SinkConfig::Kafka(cfg) => SinkImpl::Kafka(Box::new(KafkaSink::new(cfg).await?)),
+            // It exports the snapshots of the view in batch, see `s3_parquet::ParquetExporter`.
+            SinkConfig::S3Parquet(_) => {
+                return Err(SinkError::Config(format!(
+                    "{} sink is not a streaming sink",
+                    S3_PARQUET_SINK
+                )))
+            }
         })
     }
 
@@ -164,6 +179,12 @@ pub enum SinkError {
     JsonParse(String),
     #[error("config error: {0}")]
     Config(String),
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] ObjectError),
+    #[error("Scan error: {0}")]
+    Scan(RwError),
 }
 
 impl From<SinkError> for RwError {
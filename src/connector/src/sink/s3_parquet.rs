@@ -0,0 +1,864 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic snapshot export of a materialized view to Parquet files.
+//!
+//! Unlike the other sinks, this one does not consume the change log of the view. On each
+//! interval, the full view at a pinned epoch is scanned partition by partition, and each partition
+//! is written to one Parquet file under `{prefix}/epoch={epoch}/`. After all the files are written,
+//! a manifest listing them is published in the same directory. The readers should only consider
+//! the directories with a manifest, as the files of a failed export are removed on a best-effort
+//! basis.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use arrow::array::{
+    ArrayRef, BooleanBuilder, Date32Builder, Float32Builder, Float64Builder, Int16Builder,
+    Int32Builder, Int64Builder, StringBuilder, Time64MicrosecondBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow::datatypes::{
+    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, TimeUnit,
+};
+use arrow::record_batch::RecordBatch;
+use bytes::Bytes;
+use chrono::{Datelike, Timelike};
+use futures::future::try_join_all;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use itertools::Itertools;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use risingwave_common::array::{ArrayImpl, DataChunk};
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::{
+    DataType, NaiveDateTimeWrapper, NaiveDateWrapper, NaiveTimeWrapper, ScalarRefImpl,
+    UNIX_EPOCH_DAYS,
+};
+use risingwave_object_store::object::ObjectStoreRef;
+use serde::{Deserialize, Serialize};
+
+use crate::sink::{Result, SinkError};
+
+pub const S3_PARQUET_SINK: &str = "s3_parquet";
+
+/// The name of the manifest file in the directory of each exported snapshot.
+pub const MANIFEST_FILE_NAME: &str = "_manifest.json";
+
+/// The number of rows in each row group of the exported files, which is also about the number of
+/// rows buffered in memory for each partition being exported.
+const DEFAULT_ROW_GROUP_SIZE: usize = 64 * 1024;
+
+/// Whether the sink with `properties` is an `s3_parquet` sink, which exports the snapshots of the
+/// view in batch and has no streaming job.
+pub fn is_s3_parquet_sink(properties: &HashMap<String, String>) -> bool {
+    properties.get("connector").map_or(false, |connector| {
+        connector.eq_ignore_ascii_case(S3_PARQUET_SINK)
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct S3ParquetConfig {
+    /// Where the snapshots are exported to, e.g. `s3://bucket/path/to/mv`.
+    pub path: String,
+
+    /// How often the snapshot is exported, e.g. `1h`.
+    pub interval: Duration,
+}
+
+impl S3ParquetConfig {
+    pub fn from_hashmap(values: HashMap<String, String>) -> Result<Self> {
+        let path = values
+            .get("path")
+            .ok_or_else(|| SinkError::Config("path must be set".to_string()))?;
+        let interval = values
+            .get("interval")
+            .ok_or_else(|| SinkError::Config("interval must be set".to_string()))?;
+        let interval = humantime::parse_duration(interval)
+            .map_err(|e| SinkError::Config(format!("invalid interval \"{}\": {}", interval, e)))?;
+        if interval.is_zero() {
+            return Err(SinkError::Config("interval must be positive".to_string()));
+        }
+
+        Ok(Self {
+            path: path.trim_end_matches('/').to_string(),
+            interval,
+        })
+    }
+
+    /// The url of the object store to export to, and the prefix of the exported objects in it.
+    ///
+    /// For `s3://bucket/path`, the object store is the bucket. The other urls, e.g. `disk://dir` or
+    /// `memory`, are the object store as a whole.
+    pub fn object_store_url_and_prefix(&self) -> (String, String) {
+        if let Some(path) = self.path.strip_prefix("s3://") {
+            let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+            (format!("s3://{}", bucket), prefix.to_string())
+        } else {
+            (self.path.clone(), String::new())
+        }
+    }
+}
+
+/// Maps a column type to the Arrow type it is exported as.
+///
+/// `numeric` and `interval` are exported as their text representation, since Parquet has no
+/// lossless counterpart of them: `numeric` has a variable scale and can be `NaN` or infinity, and
+/// the Parquet interval can't hold a negative or sub-millisecond part.
+pub fn to_arrow_data_type(data_type: &DataType) -> Result<ArrowDataType> {
+    Ok(match data_type {
+        DataType::Boolean => ArrowDataType::Boolean,
+        DataType::Int16 => ArrowDataType::Int16,
+        DataType::Int32 => ArrowDataType::Int32,
+        DataType::Int64 => ArrowDataType::Int64,
+        DataType::Float32 => ArrowDataType::Float32,
+        DataType::Float64 => ArrowDataType::Float64,
+        DataType::Decimal => ArrowDataType::Utf8,
+        DataType::Date => ArrowDataType::Date32,
+        DataType::Varchar => ArrowDataType::Utf8,
+        DataType::Time => ArrowDataType::Time64(TimeUnit::Microsecond),
+        DataType::Timestamp => ArrowDataType::Timestamp(TimeUnit::Microsecond, None),
+        DataType::Timestampz => {
+            ArrowDataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string()))
+        }
+        DataType::Interval => ArrowDataType::Utf8,
+        DataType::Struct(_) | DataType::List { .. } => {
+            return Err(SinkError::Config(format!(
+                "{} is not supported by {} sink",
+                data_type, S3_PARQUET_SINK
+            )))
+        }
+    })
+}
+
+pub fn to_arrow_schema(schema: &Schema) -> Result<ArrowSchema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|f| {
+            Ok(ArrowField::new(
+                &f.name,
+                to_arrow_data_type(&f.data_type)?,
+                true,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ArrowSchema::new(fields))
+}
+
+/// Days since 1970-01-01.
+fn date_to_days(date: NaiveDateWrapper) -> i32 {
+    date.0.num_days_from_ce() - UNIX_EPOCH_DAYS
+}
+
+/// Microseconds since midnight.
+fn time_to_micros(time: NaiveTimeWrapper) -> i64 {
+    time.0.num_seconds_from_midnight() as i64 * 1_000_000 + (time.0.nanosecond() / 1_000) as i64
+}
+
+/// Microseconds since 1970-01-01 00:00:00.
+fn timestamp_to_micros(timestamp: NaiveDateTimeWrapper) -> i64 {
+    timestamp.0.timestamp() * 1_000_000 + timestamp.0.timestamp_subsec_micros() as i64
+}
+
+/// Converts a column to an Arrow array of the type given by [`to_arrow_data_type`].
+fn to_arrow_array(data_type: &DataType, array: &ArrayImpl) -> ArrayRef {
+    macro_rules! build {
+        ($builder:ty, $variant:ident, | $v:ident | $value:expr) => {{
+            let mut builder = <$builder>::new();
+            for datum in array.iter() {
+                match datum {
+                    Some(ScalarRefImpl::$variant($v)) => builder.append_value($value),
+                    None => builder.append_null(),
+                    Some(scalar) => unreachable!("unexpected {:?} for {}", scalar, data_type),
+                }
+            }
+            Arc::new(builder.finish()) as ArrayRef
+        }};
+    }
+
+    match data_type {
+        DataType::Boolean => build!(BooleanBuilder, Bool, |v| v),
+        DataType::Int16 => build!(Int16Builder, Int16, |v| v),
+        DataType::Int32 => build!(Int32Builder, Int32, |v| v),
+        DataType::Int64 => build!(Int64Builder, Int64, |v| v),
+        DataType::Float32 => build!(Float32Builder, Float32, |v| v.0),
+        DataType::Float64 => build!(Float64Builder, Float64, |v| v.0),
+        DataType::Decimal => build!(StringBuilder, Decimal, |v| v.to_string()),
+        DataType::Date => build!(Date32Builder, NaiveDate, |v| date_to_days(v)),
+        DataType::Varchar => build!(StringBuilder, Utf8, |v| v),
+        DataType::Time => build!(Time64MicrosecondBuilder, NaiveTime, |v| time_to_micros(v)),
+        DataType::Timestamp => {
+            build!(TimestampMicrosecondBuilder, NaiveDateTime, |v| {
+                timestamp_to_micros(v)
+            })
+        }
+        DataType::Timestampz => {
+            let mut builder = TimestampMicrosecondBuilder::new();
+            for datum in array.iter() {
+                match datum {
+                    Some(ScalarRefImpl::Int64(v)) => builder.append_value(v),
+                    None => builder.append_null(),
+                    Some(scalar) => unreachable!("unexpected {:?} for {}", scalar, data_type),
+                }
+            }
+            Arc::new(builder.finish().with_timezone("UTC".to_string()))
+        }
+        DataType::Interval => build!(StringBuilder, Interval, |v| v.to_string()),
+        DataType::Struct(_) | DataType::List { .. } => {
+            unreachable!("{} is rejected by `to_arrow_data_type`", data_type)
+        }
+    }
+}
+
+pub fn to_record_batch(schema: &Schema, arrow_schema: SchemaRef, chunk: DataChunk) -> RecordBatch {
+    let chunk = chunk.compact();
+    let columns = schema
+        .fields()
+        .iter()
+        .zip_eq(chunk.columns())
+        .map(|(field, column)| to_arrow_array(&field.data_type, column.array_ref()))
+        .collect();
+    RecordBatch::try_new(arrow_schema, columns).expect("arrays should match the schema")
+}
+
+/// The manifest of an exported snapshot. It's published after all the files are written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub epoch: u64,
+    pub columns: Vec<ExportColumn>,
+    pub files: Vec<ExportFile>,
+    pub total_rows: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub path: String,
+    pub rows: usize,
+}
+
+/// The buffer a Parquet file is written to, drained to the object store after each row group.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(&mut *self.0.lock().unwrap()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes the snapshots of a materialized view to an object store.
+pub struct ParquetExporter {
+    store: ObjectStoreRef,
+    prefix: String,
+    schema: Schema,
+    arrow_schema: SchemaRef,
+    row_group_size: usize,
+}
+
+impl ParquetExporter {
+    pub fn new(store: ObjectStoreRef, prefix: String, schema: Schema) -> Result<Self> {
+        let arrow_schema = Arc::new(to_arrow_schema(&schema)?);
+        Ok(Self {
+            store,
+            prefix,
+            schema,
+            arrow_schema,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+        })
+    }
+
+    pub fn with_row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size;
+        self
+    }
+
+    /// The directory of the snapshot exported at `epoch`.
+    pub fn snapshot_dir(&self, epoch: u64) -> String {
+        if self.prefix.is_empty() {
+            format!("epoch={}", epoch)
+        } else {
+            format!("{}/epoch={}", self.prefix, epoch)
+        }
+    }
+
+    /// Exports the snapshot at `epoch`, whose partitions are read by `partitions`. Each partition
+    /// is written to one file, and the manifest is published after all of them are written. If
+    /// any of them fails, the files written are removed. They're also removed in the background if
+    /// the export is cancelled by dropping the returned future.
+    pub async fn export(
+        &self,
+        epoch: u64,
+        partitions: Vec<BoxStream<'_, risingwave_common::error::Result<DataChunk>>>,
+    ) -> Result<ExportManifest> {
+        let dir = self.snapshot_dir(epoch);
+        let mut guard = CleanUpGuard {
+            store: self.store.clone(),
+            dir: Some(dir.clone()),
+        };
+        let result = self.export_inner(epoch, &dir, partitions).await;
+        guard.dir = None;
+        if let Err(e) = &result {
+            tracing::warn!("failed to export snapshot to {}: {}", dir, e);
+            if let Err(e) = clean_up(&self.store, &dir).await {
+                tracing::warn!("failed to clean up {}: {}", dir, e);
+            }
+        }
+        result
+    }
+
+    async fn export_inner(
+        &self,
+        epoch: u64,
+        dir: &str,
+        partitions: Vec<BoxStream<'_, risingwave_common::error::Result<DataChunk>>>,
+    ) -> Result<ExportManifest> {
+        let files = try_join_all(
+            partitions
+                .into_iter()
+                .enumerate()
+                .map(|(i, partition)| self.export_partition(dir, i, partition)),
+        )
+        .await?;
+
+        let manifest = ExportManifest {
+            epoch,
+            columns: self
+                .schema
+                .fields()
+                .iter()
+                .map(|f| ExportColumn {
+                    name: f.name.clone(),
+                    data_type: f.data_type.to_string(),
+                })
+                .collect(),
+            total_rows: files.iter().map(|f| f.rows).sum(),
+            files,
+        };
+        let manifest_path = format!("{}/{}", dir, MANIFEST_FILE_NAME);
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| SinkError::JsonParse(e.to_string()))?;
+        self.store
+            .upload(&manifest_path, Bytes::from(manifest_bytes))
+            .await?;
+
+        Ok(manifest)
+    }
+
+    /// Writes the partition to one file. The row groups are uploaded as soon as they're written,
+    /// so only about one row group of the partition is held in memory.
+    async fn export_partition(
+        &self,
+        dir: &str,
+        index: usize,
+        mut partition: BoxStream<'_, risingwave_common::error::Result<DataChunk>>,
+    ) -> Result<ExportFile> {
+        let path = format!("{}/part-{:05}.parquet", dir, index);
+        let mut uploader = self.store.streaming_upload(&path)?;
+        let buf = SharedBuffer::default();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+        let mut writer = ArrowWriter::try_new(buf.clone(), self.arrow_schema.clone(), Some(props))?;
+        let mut rows = 0;
+        while let Some(chunk) = partition.next().await {
+            let chunk = chunk.map_err(SinkError::Scan)?;
+            rows += chunk.cardinality();
+            writer.write(&to_record_batch(
+                &self.schema,
+                self.arrow_schema.clone(),
+                chunk,
+            ))?;
+            let written = buf.take();
+            if !written.is_empty() {
+                uploader.write_bytes(written).await?;
+            }
+        }
+        writer.close()?;
+        uploader.write_bytes(buf.take()).await?;
+        uploader.finish().await?;
+
+        Ok(ExportFile { path, rows })
+    }
+}
+
+/// Removes the files of the export to `dir` in the background when dropped, unless `dir` is taken
+/// after the export finishes.
+struct CleanUpGuard {
+    store: ObjectStoreRef,
+    dir: Option<String>,
+}
+
+impl Drop for CleanUpGuard {
+    fn drop(&mut self) {
+        if let Some(dir) = self.dir.take() {
+            tracing::warn!("export to {} is cancelled", dir);
+            let store = self.store.clone();
+            tokio::spawn(async move {
+                if let Err(e) = clean_up(&store, &dir).await {
+                    tracing::warn!("failed to clean up {}: {}", dir, e);
+                }
+            });
+        }
+    }
+}
+
+/// Removes all the objects in `dir`.
+async fn clean_up(store: &ObjectStoreRef, dir: &str) -> Result<()> {
+    let paths = store
+        .list(&format!("{}/", dir))
+        .await?
+        .into_iter()
+        .map(|metadata| metadata.key)
+        .collect_vec();
+    store.delete_objects(&paths).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use arrow::array::{Array, Int64Array, StringArray, TimestampMicrosecondArray};
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+    use futures::stream;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use risingwave_common::array::DataChunkTestExt;
+    use risingwave_common::catalog::Field;
+    use risingwave_common::error::{ErrorCode, RwError};
+    use risingwave_common::types::{Decimal, IntervalUnit, ScalarImpl};
+    use risingwave_object_store::object::object_metrics::ObjectStoreMetrics;
+    use risingwave_object_store::object::{InMemObjectStore, ObjectStore, ObjectStoreImpl};
+
+    use super::*;
+
+    fn scalar_types() -> Vec<(DataType, ArrowDataType, ScalarImpl)> {
+        vec![
+            (
+                DataType::Boolean,
+                ArrowDataType::Boolean,
+                ScalarImpl::Bool(true),
+            ),
+            (
+                DataType::Int16,
+                ArrowDataType::Int16,
+                ScalarImpl::Int16(-16),
+            ),
+            (DataType::Int32, ArrowDataType::Int32, ScalarImpl::Int32(32)),
+            (
+                DataType::Int64,
+                ArrowDataType::Int64,
+                ScalarImpl::Int64(-64),
+            ),
+            (
+                DataType::Float32,
+                ArrowDataType::Float32,
+                ScalarImpl::Float32(1.5.into()),
+            ),
+            (
+                DataType::Float64,
+                ArrowDataType::Float64,
+                ScalarImpl::Float64((-2.25).into()),
+            ),
+            (
+                DataType::Decimal,
+                ArrowDataType::Utf8,
+                ScalarImpl::Decimal(Decimal::from_str("-123.4500").unwrap()),
+            ),
+            (
+                DataType::Date,
+                ArrowDataType::Date32,
+                ScalarImpl::NaiveDate(NaiveDateWrapper(NaiveDate::from_ymd(1970, 1, 11))),
+            ),
+            (
+                DataType::Varchar,
+                ArrowDataType::Utf8,
+                ScalarImpl::Utf8("hello".to_string()),
+            ),
+            (
+                DataType::Time,
+                ArrowDataType::Time64(TimeUnit::Microsecond),
+                ScalarImpl::NaiveTime(NaiveTimeWrapper(NaiveTime::from_hms(0, 0, 1))),
+            ),
+            (
+                DataType::Timestamp,
+                ArrowDataType::Timestamp(TimeUnit::Microsecond, None),
+                ScalarImpl::NaiveDateTime(NaiveDateTimeWrapper(NaiveDateTime::from_timestamp(
+                    1, 0,
+                ))),
+            ),
+            (
+                DataType::Timestampz,
+                ArrowDataType::Timestamp(TimeUnit::Microsecond, Some("UTC".to_string())),
+                ScalarImpl::Int64(1_000_000),
+            ),
+            (
+                DataType::Interval,
+                ArrowDataType::Utf8,
+                ScalarImpl::Interval(IntervalUnit::new(1, 2, 3)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_type_mapping() {
+        for (data_type, arrow_data_type, _) in scalar_types() {
+            assert_eq!(
+                to_arrow_data_type(&data_type).unwrap(),
+                arrow_data_type,
+                "{}",
+                data_type
+            );
+        }
+        for data_type in [
+            DataType::List {
+                datatype: Box::new(DataType::Int32),
+            },
+            DataType::new_struct(vec![DataType::Int32], vec!["v".to_string()]),
+        ] {
+            assert!(matches!(
+                to_arrow_data_type(&data_type),
+                Err(SinkError::Config(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_to_record_batch() {
+        for (data_type, arrow_data_type, scalar) in scalar_types() {
+            let schema = Schema::new(vec![Field::with_name(data_type.clone(), "v")]);
+            let arrow_schema = Arc::new(to_arrow_schema(&schema).unwrap());
+            let mut builder = data_type.create_array_builder(2);
+            builder.append_datum(&Some(scalar.clone()));
+            builder.append_datum(&None);
+            let chunk = DataChunk::new(vec![builder.finish().into()], 2);
+
+            let batch = to_record_batch(&schema, arrow_schema, chunk);
+            let array = batch.column(0);
+            assert_eq!(array.data_type(), &arrow_data_type);
+            assert_eq!(array.len(), 2);
+            assert!(array.is_valid(0), "{}", data_type);
+            assert!(array.is_null(1), "{}", data_type);
+
+            match &data_type {
+                DataType::Decimal | DataType::Interval | DataType::Varchar => {
+                    let array = array.as_any().downcast_ref::<StringArray>().unwrap();
+                    assert_eq!(array.value(0), scalar.to_string());
+                }
+                DataType::Date => {
+                    let array = array
+                        .as_any()
+                        .downcast_ref::<arrow::array::Date32Array>()
+                        .unwrap();
+                    assert_eq!(array.value(0), 10);
+                }
+                DataType::Time => {
+                    let array = array
+                        .as_any()
+                        .downcast_ref::<arrow::array::Time64MicrosecondArray>()
+                        .unwrap();
+                    assert_eq!(array.value(0), 1_000_000);
+                }
+                DataType::Timestamp | DataType::Timestampz => {
+                    let array = array
+                        .as_any()
+                        .downcast_ref::<TimestampMicrosecondArray>()
+                        .unwrap();
+                    assert_eq!(array.value(0), 1_000_000);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let config = S3ParquetConfig::from_hashmap(HashMap::from([
+            ("path".to_string(), "s3://bucket/path/to/mv/".to_string()),
+            ("interval".to_string(), "1h".to_string()),
+        ]))
+        .unwrap();
+        assert_eq!(config.interval, Duration::from_secs(3600));
+        assert_eq!(
+            config.object_store_url_and_prefix(),
+            ("s3://bucket".to_string(), "path/to/mv".to_string())
+        );
+
+        assert!(S3ParquetConfig::from_hashmap(HashMap::from([(
+            "path".to_string(),
+            "s3://bucket".to_string()
+        )]))
+        .is_err());
+        assert!(S3ParquetConfig::from_hashmap(HashMap::from([
+            ("path".to_string(), "s3://bucket".to_string()),
+            ("interval".to_string(), "soon".to_string()),
+        ]))
+        .is_err());
+    }
+
+    fn in_mem_store() -> ObjectStoreRef {
+        Arc::new(ObjectStoreImpl::InMem(
+            InMemObjectStore::new().monitored(Arc::new(ObjectStoreMetrics::unused())),
+        ))
+    }
+
+    fn test_schema() -> Schema {
+        Schema::new(vec![
+            Field::with_name(DataType::Int64, "k"),
+            Field::with_name(DataType::Varchar, "v"),
+        ])
+    }
+
+    fn partition(
+        chunks: Vec<DataChunk>,
+    ) -> BoxStream<'static, risingwave_common::error::Result<DataChunk>> {
+        stream::iter(chunks.into_iter().map(Ok)).boxed()
+    }
+
+    async fn read_parquet(store: &ObjectStoreRef, path: &str) -> Vec<RecordBatch> {
+        let bytes = store.read(path, None).await.unwrap();
+        ParquetRecordBatchReaderBuilder::try_new(bytes)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let store = in_mem_store();
+        let exporter =
+            ParquetExporter::new(store.clone(), "path/to/mv".to_string(), test_schema()).unwrap();
+
+        let manifest = exporter
+            .export(
+                233,
+                vec![
+                    partition(vec![
+                        DataChunk::from_pretty(
+                            "I T
+                             1 a
+                             2 b",
+                        ),
+                        DataChunk::from_pretty(
+                            "I T
+                             3 .",
+                        ),
+                    ]),
+                    partition(vec![]),
+                    // The invisible rows are not exported.
+                    partition(vec![DataChunk::from_pretty(
+                        "I T
+                         4 d D
+                         5 e",
+                    )]),
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(manifest.epoch, 233);
+        assert_eq!(manifest.total_rows, 4);
+        assert_eq!(
+            manifest.columns,
+            vec![
+                ExportColumn {
+                    name: "k".to_string(),
+                    data_type: "bigint".to_string()
+                },
+                ExportColumn {
+                    name: "v".to_string(),
+                    data_type: "varchar".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            manifest.files,
+            vec![
+                ExportFile {
+                    path: "path/to/mv/epoch=233/part-00000.parquet".to_string(),
+                    rows: 3
+                },
+                ExportFile {
+                    path: "path/to/mv/epoch=233/part-00001.parquet".to_string(),
+                    rows: 0
+                },
+                ExportFile {
+                    path: "path/to/mv/epoch=233/part-00002.parquet".to_string(),
+                    rows: 1
+                },
+            ]
+        );
+
+        // The published manifest is the same as the returned one.
+        let published: ExportManifest = serde_json::from_slice(
+            &store
+                .read("path/to/mv/epoch=233/_manifest.json", None)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(published, manifest);
+
+        // The files contain the rows listed in the manifest.
+        for file in &manifest.files {
+            let batches = read_parquet(&store, &file.path).await;
+            let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+            assert_eq!(rows, file.rows);
+            for batch in batches {
+                assert_eq!(batch.schema().fields(), exporter.arrow_schema.fields());
+            }
+        }
+        let batches = read_parquet(&store, &manifest.files[0].path).await;
+        let v = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(v.value(0), "a");
+        assert!(v.is_null(2));
+    }
+
+    #[tokio::test]
+    async fn test_export_row_groups() {
+        let store = in_mem_store();
+        let exporter = ParquetExporter::new(store.clone(), "mv".to_string(), test_schema())
+            .unwrap()
+            .with_row_group_size(2);
+
+        let chunks = (0..5)
+            .map(|i| DataChunk::from_pretty(&format!("I T\n{} a", i)))
+            .collect();
+        let manifest = exporter.export(1, vec![partition(chunks)]).await.unwrap();
+        assert_eq!(manifest.total_rows, 5);
+
+        let bytes = store.read(&manifest.files[0].path, None).await.unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(bytes).unwrap();
+        let row_groups = reader.metadata().row_groups();
+        assert_eq!(
+            row_groups.iter().map(|rg| rg.num_rows()).collect_vec(),
+            vec![2, 2, 1]
+        );
+        let k = reader
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .flat_map(|batch| {
+                let k = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .clone();
+                k.iter().map(Option::unwrap).collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(k, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_export_failure_clean_up() {
+        let store = in_mem_store();
+        let exporter =
+            ParquetExporter::new(store.clone(), "mv".to_string(), test_schema()).unwrap();
+
+        let failed = stream::iter(vec![
+            Ok(DataChunk::from_pretty(
+                "I T
+                 1 a",
+            )),
+            Err(RwError::from(ErrorCode::InternalError(
+                "scan failed".to_string(),
+            ))),
+        ])
+        .boxed();
+        let result = exporter
+            .export(
+                1,
+                vec![
+                    partition(vec![DataChunk::from_pretty(
+                        "I T
+                         2 b",
+                    )]),
+                    failed,
+                ],
+            )
+            .await;
+        assert!(matches!(result, Err(SinkError::Scan(_))));
+
+        // Neither the file of the succeeded partition nor the manifest is left.
+        assert!(store.list("mv/").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_cancel_clean_up() {
+        let store = in_mem_store();
+        let exporter =
+            ParquetExporter::new(store.clone(), "mv".to_string(), test_schema()).unwrap();
+
+        // The first partition is written, while the second one never ends.
+        let pending = stream::iter(vec![Ok(DataChunk::from_pretty(
+            "I T
+             1 a",
+        ))])
+        .chain(stream::pending())
+        .boxed();
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            exporter.export(
+                1,
+                vec![
+                    partition(vec![DataChunk::from_pretty(
+                        "I T
+                         2 b",
+                    )]),
+                    pending,
+                ],
+            ),
+        )
+        .await;
+        assert!(result.is_err());
+
+        // The file of the first partition is removed in the background after the cancellation.
+        for _ in 0..100 {
+            if store.list("mv/").await.unwrap().is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("the files of the cancelled export are not removed");
+    }
+}
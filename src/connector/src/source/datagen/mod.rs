@@ -24,8 +24,22 @@ use serde_with::{serde_as, DisplayFromStr};
 pub use source::*;
 pub use split::*;
 
+use crate::options::{ConnectorOptionDesc, ConnectorOptionsSchema, OptionType};
+
 pub const DATAGEN_CONNECTOR: &str = "datagen";
 
+/// The options of [`DatagenProperties`].
+pub const DATAGEN_OPTIONS: ConnectorOptionsSchema = ConnectorOptionsSchema {
+    connector: DATAGEN_CONNECTOR,
+    options: &[
+        ConnectorOptionDesc::optional("datagen.split.num", OptionType::UnsignedInteger)
+            .with_default("1"),
+        ConnectorOptionDesc::optional("datagen.rows.per.second", OptionType::UnsignedInteger)
+            .with_default("10"),
+    ],
+    dynamic_prefixes: &["fields."],
+};
+
 #[serde_as]
 #[derive(Clone, Debug, Deserialize)]
 pub struct DatagenProperties {
@@ -17,8 +17,24 @@ mod source;
 
 use serde::Deserialize;
 
+use crate::options::{ConnectorOptionDesc, ConnectorOptionsSchema, OptionType};
+
 pub const S3_CONNECTOR: &str = "s3";
 
+/// The options of [`S3Properties`].
+pub const S3_OPTIONS: ConnectorOptionsSchema = ConnectorOptionsSchema {
+    connector: S3_CONNECTOR,
+    options: &[
+        ConnectorOptionDesc::required("s3.region_name", OptionType::String),
+        ConnectorOptionDesc::required("s3.bucket_name", OptionType::String),
+        ConnectorOptionDesc::required("sqs_queue_name", OptionType::String),
+        ConnectorOptionDesc::optional("match_pattern", OptionType::String),
+        ConnectorOptionDesc::optional("s3.credentials.access", OptionType::String),
+        ConnectorOptionDesc::optional("s3.credentials.secret", OptionType::String),
+    ],
+    dynamic_prefixes: &[],
+};
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct S3Properties {
     #[serde(rename = "s3.region_name")]
@@ -114,6 +114,12 @@ impl SplitEnumerator for KafkaSplitEnumerator {
     }
 }
 
+/// The start offset of a split is the last consumed offset, and the reader seeks to the one after
+/// it. So a split to read from `next_offset` starts at the offset before it.
+fn last_consumed_offset(next_offset: i64) -> i64 {
+    next_offset - 1
+}
+
 impl KafkaSplitEnumerator {
     async fn fetch_stop_offset(
         &self,
@@ -154,18 +160,21 @@ impl KafkaSplitEnumerator {
                         .client
                         .fetch_watermarks(self.topic.as_str(), *partition, KAFKA_SYNC_CALL_TIMEOUT)
                         .await?;
-                    let offset = match self.start_offset {
-                        KafkaEnumeratorOffset::Earliest => low_watermark - 1,
+                    let next_offset = match self.start_offset {
+                        KafkaEnumeratorOffset::Earliest => low_watermark,
                         KafkaEnumeratorOffset::Latest => high_watermark,
                         _ => unreachable!(),
                     };
-                    map.insert(*partition, Some(offset));
+                    map.insert(*partition, Some(last_consumed_offset(next_offset)));
                 }
                 Ok(map)
             }
-            KafkaEnumeratorOffset::Timestamp(time) => {
-                self.fetch_offset_for_time(partitions, time).await
-            }
+            KafkaEnumeratorOffset::Timestamp(time) => Ok(self
+                .fetch_offset_for_time(partitions, time)
+                .await?
+                .into_iter()
+                .map(|(partition, offset)| (partition, offset.map(last_consumed_offset)))
+                .collect()),
             KafkaEnumeratorOffset::None => partitions
                 .iter()
                 .map(|partition| Ok((*partition, None)))
@@ -236,3 +245,21 @@ impl KafkaSplitEnumerator {
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_consumed_offset() {
+        // A partition with the messages at offsets 3 to 6.
+        let (low_watermark, high_watermark) = (3, 7);
+        // The reader seeks to the offset after the start offset of the split.
+        let seek_offset = |next_offset| last_consumed_offset(next_offset) + 1;
+        // Reading from the earliest starts with the first message.
+        assert_eq!(seek_offset(low_watermark), 3);
+        // Reading from the latest starts with the next message to be produced, without skipping
+        // it.
+        assert_eq!(seek_offset(high_watermark), 7);
+    }
+}
@@ -17,6 +17,8 @@ use std::time::Duration;
 use rdkafka::ClientConfig;
 use serde::Deserialize;
 
+use crate::options::{ConnectorOptionDesc, ConnectorOptionsSchema, OptionType};
+
 pub mod enumerator;
 pub mod source;
 pub mod split;
@@ -27,6 +29,47 @@ pub use split::*;
 
 pub const KAFKA_CONNECTOR: &str = "kafka";
 
+/// The options of [`KafkaProperties`].
+pub const KAFKA_OPTIONS: ConnectorOptionsSchema = ConnectorOptionsSchema {
+    connector: KAFKA_CONNECTOR,
+    options: &[
+        ConnectorOptionDesc::required("properties.bootstrap.server", OptionType::String)
+            .with_aliases(&["kafka.brokers"]),
+        ConnectorOptionDesc::required("topic", OptionType::String).with_aliases(&["kafka.topic"]),
+        ConnectorOptionDesc::optional(
+            "scan.startup.mode",
+            OptionType::OneOf {
+                values: &["earliest", "latest"],
+                ignore_case: true,
+            },
+        )
+        .with_default("earliest")
+        .with_aliases(&["kafka.scan.startup.mode"]),
+        ConnectorOptionDesc::optional("scan.startup.timestamp_millis", OptionType::Integer)
+            .with_aliases(&["kafka.time.offset"]),
+        ConnectorOptionDesc::optional("properties.group.id", OptionType::String)
+            .with_aliases(&["kafka.consumer.group"]),
+        ConnectorOptionDesc::optional("properties.security.protocol", OptionType::String),
+        ConnectorOptionDesc::optional("properties.ssl.ca.location", OptionType::String),
+        ConnectorOptionDesc::optional("properties.ssl.certificate.location", OptionType::String),
+        ConnectorOptionDesc::optional("properties.ssl.key.location", OptionType::String),
+        ConnectorOptionDesc::optional("properties.ssl.key.password", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.mechanism", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.username", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.password", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.kerberos.service.name", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.kerberos.keytab", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.kerberos.principal", OptionType::String),
+        ConnectorOptionDesc::optional("properties.sasl.kerberos.kinit.cmd", OptionType::String),
+        ConnectorOptionDesc::optional(
+            "properties.sasl.kerberos.min.time.before.relogin",
+            OptionType::String,
+        ),
+        ConnectorOptionDesc::optional("properties.sasl.oauthbearer.config", OptionType::String),
+    ],
+    dynamic_prefixes: &[],
+};
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct KafkaProperties {
     #[serde(rename = "properties.bootstrap.server", alias = "kafka.brokers")]
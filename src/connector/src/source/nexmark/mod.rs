@@ -25,10 +25,75 @@ use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
 pub use split::*;
 
+use crate::options::{ConnectorOptionDesc, ConnectorOptionsSchema, OptionType};
+
 const NEXMARK_BASE_TIME: usize = 1_436_918_400_000;
 
 pub const NEXMARK_CONNECTOR: &str = "nexmark";
 
+/// The options of [`NexmarkPropertiesInner`].
+pub const NEXMARK_OPTIONS: ConnectorOptionsSchema = ConnectorOptionsSchema {
+    connector: NEXMARK_CONNECTOR,
+    options: &[
+        ConnectorOptionDesc::optional("nexmark.split.num", OptionType::Integer).with_default("1"),
+        ConnectorOptionDesc::optional("nexmark.event.num", OptionType::Integer).with_default("-1"),
+        ConnectorOptionDesc::required(
+            "nexmark.table.type",
+            OptionType::OneOf {
+                values: &["Person", "Auction", "Bid"],
+                ignore_case: false,
+            },
+        ),
+        ConnectorOptionDesc::optional("nexmark.max.chunk.size", OptionType::UnsignedInteger)
+            .with_default("1024"),
+        ConnectorOptionDesc::optional("nexmark.use.real.time", OptionType::Boolean)
+            .with_default("false"),
+        ConnectorOptionDesc::optional("nexmark.min.event.gap.in.ns", OptionType::UnsignedInteger)
+            .with_default("100000"),
+        ConnectorOptionDesc::optional("nexmark.active.people", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.in.flight.auctions", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional(
+            "nexmark.out.of.order.group.size",
+            OptionType::UnsignedInteger,
+        ),
+        ConnectorOptionDesc::optional("nexmark.avg.person.byte.size", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.avg.auction.byte.size", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.avg.bid.byte.size", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.seller.ratio", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.auction.ratio", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.bidder.ratio", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.channel.ratio", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.event.id", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.event.number", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.num.categories", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.auction.id.lead", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.seller.ratio.2", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.auction.ratio.2", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.hot.bidder.ratio.2", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.person.proportion", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.auction.proportion", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.bid.proportion", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.auction.id", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.person.id", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.category.id", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.person.id.lead", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.sine.approx.steps", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.base.time", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.us.states", OptionType::String),
+        ConnectorOptionDesc::optional("nexmark.us.cities", OptionType::String),
+        ConnectorOptionDesc::optional("nexmark.first.names", OptionType::String),
+        ConnectorOptionDesc::optional("nexmark.last.names", OptionType::String),
+        ConnectorOptionDesc::optional("nexmark.rate.shape", OptionType::String),
+        ConnectorOptionDesc::optional("nexmark.rate.period", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.first.event.rate", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.events.per.sec", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.next.event.rate", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.us.per.unit", OptionType::UnsignedInteger),
+        ConnectorOptionDesc::optional("nexmark.threads", OptionType::UnsignedInteger),
+    ],
+    dynamic_prefixes: &[],
+};
+
 const fn identity_i32<const V: i32>() -> i32 {
     V
 }
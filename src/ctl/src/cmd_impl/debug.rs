@@ -0,0 +1,64 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail};
+use risingwave_pb::meta::set_log_level_request::Target;
+
+use crate::common::MetaServiceOpts;
+
+/// Parses a duration like `30s`, `10m` or `1h` into seconds. A plain number is in seconds.
+fn parse_duration_secs(duration: &str) -> anyhow::Result<u64> {
+    let (value, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => duration.split_at(pos),
+        None => (duration, "s"),
+    };
+    let value: u64 = value
+        .parse()
+        .map_err(|_| anyhow!("invalid duration: {}", duration))?;
+    let secs = match unit {
+        "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => bail!("invalid duration: {}", duration),
+    };
+    Ok(secs)
+}
+
+pub async fn set_log_level(
+    fragment_id: Option<u32>,
+    actor_id: Option<u32>,
+    level: String,
+    duration: String,
+) -> anyhow::Result<()> {
+    let target = match (fragment_id, actor_id) {
+        (Some(fragment_id), None) => Target::FragmentId(fragment_id),
+        (None, Some(actor_id)) => Target::ActorId(actor_id),
+        _ => bail!("exactly one of --fragment and --actor should be specified"),
+    };
+    let duration_secs = parse_duration_secs(&duration)?;
+
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    let actor_ids = meta_client
+        .set_log_level(target, level.clone(), duration_secs)
+        .await?;
+
+    println!(
+        "Logging actors {:?} at level {} for {}",
+        actor_ids, level, duration
+    );
+
+    Ok(())
+}
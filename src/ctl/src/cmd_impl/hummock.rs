@@ -18,6 +18,8 @@ mod list_kv;
 pub use list_kv::*;
 mod sst_dump;
 pub use sst_dump::*;
+mod sst_inspect;
+pub use sst_inspect::*;
 mod compaction_group;
 mod disable_commit_epoch;
 mod list_version_deltas;
@@ -41,6 +41,24 @@ pub async fn update_compaction_config(
     Ok(())
 }
 
+pub async fn set_group_config(
+    id: CompactionGroupId,
+    max_concurrency: Option<u64>,
+    max_throughput: Option<u64>,
+) -> anyhow::Result<()> {
+    let mut configs = vec![];
+    if let Some(c) = max_concurrency {
+        configs.push(MutableConfig::MaxConcurrentTaskNumber(c));
+    }
+    if let Some(c) = max_throughput {
+        configs.push(MutableConfig::MaxCompactionThroughput(c));
+    }
+    if configs.is_empty() {
+        anyhow::bail!("at least one of --max-concurrency and --max-throughput is required");
+    }
+    update_compaction_config(vec![id], configs).await
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn build_compaction_config_vec(
     max_bytes_for_level_base: Option<u64>,
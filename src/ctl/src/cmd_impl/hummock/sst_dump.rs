@@ -20,7 +20,7 @@ use risingwave_common::array::RowDeserializer;
 use risingwave_common::types::{display_datum_ref, to_datum_ref};
 use risingwave_frontend::TableCatalog;
 use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
-use risingwave_hummock_sdk::key::{get_epoch, get_table_id, user_key};
+use risingwave_hummock_sdk::key::{get_epoch, get_table_id, user_key, KeyEncodingVersion};
 use risingwave_hummock_sdk::HummockSstableId;
 use risingwave_object_store::object::BlockLocation;
 use risingwave_rpc_client::MetaClient;
@@ -128,6 +128,7 @@ async fn print_blocks(
             block_data,
             table_data,
             block_meta.uncompressed_size as usize,
+            sstable_meta.key_encoding_version(),
         )?;
     }
 
@@ -139,12 +140,13 @@ fn print_kv_pairs(
     block_data: Bytes,
     table_data: &TableData,
     uncompressed_capacity: usize,
+    key_encoding: KeyEncodingVersion,
 ) -> anyhow::Result<()> {
     println!("\tKV-Pairs:");
 
     let block = Box::new(Block::decode(block_data, uncompressed_capacity).unwrap());
     let holder = BlockHolder::from_owned_block(block);
-    let mut block_iter = BlockIterator::new(holder);
+    let mut block_iter = BlockIterator::with_key_encoding(holder, key_encoding);
     block_iter.seek_to_first();
 
     while block_iter.is_valid() {
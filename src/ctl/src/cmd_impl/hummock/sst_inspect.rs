@@ -0,0 +1,60 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_hummock_sdk::HummockSstableId;
+use risingwave_storage::hummock::inspect::{
+    describe_sstable, dump_sstable, parse_user_key_range, DumpFilter,
+};
+
+use crate::common::HummockServiceOpts;
+
+pub async fn describe_sst(sst_id: HummockSstableId) -> anyhow::Result<()> {
+    let mut hummock_opts = HummockServiceOpts::from_env()?;
+    let (_, hummock) = hummock_opts.create_hummock_store().await?;
+    let sstable_store = hummock.sstable_store();
+
+    let description = describe_sstable(&sstable_store, sst_id).await?;
+    println!("{}", description);
+
+    hummock_opts.shutdown().await;
+    Ok(())
+}
+
+pub async fn dump_sst(
+    sst_id: HummockSstableId,
+    range: Option<String>,
+    table_id: Option<u32>,
+    limit: Option<usize>,
+) -> anyhow::Result<()> {
+    let user_key_range = match range {
+        Some(range) => parse_user_key_range(&range)?,
+        None => DumpFilter::default().user_key_range,
+    };
+    let filter = DumpFilter {
+        table_id,
+        user_key_range,
+        limit,
+    };
+
+    let mut hummock_opts = HummockServiceOpts::from_env()?;
+    let (_, hummock) = hummock_opts.create_hummock_store().await?;
+    let sstable_store = hummock.sstable_store();
+
+    for kv in dump_sstable(&sstable_store, sst_id, &filter).await? {
+        println!("{}", kv);
+    }
+
+    hummock_opts.shutdown().await;
+    Ok(())
+}
@@ -12,10 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod backup;
 mod cluster_info;
+mod fix_fragments;
 mod pause_resume;
 mod reschedule;
+mod system_params;
+mod update_sink_dispatch_type;
 
+pub use backup::*;
 pub use cluster_info::*;
+pub use fix_fragments::*;
 pub use pause_resume::*;
 pub use reschedule::*;
+pub use system_params::*;
+pub use update_sink_dispatch_type::*;
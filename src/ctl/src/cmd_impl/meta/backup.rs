@@ -0,0 +1,85 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Context;
+use comfy_table::{Row, Table};
+use risingwave_pb::meta::MetaBackupInfo;
+
+use crate::common::MetaServiceOpts;
+
+pub async fn create_backup() -> anyhow::Result<MetaBackupInfo> {
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    let backup = meta_client.create_meta_backup().await?;
+
+    println!(
+        "Created meta backup {} at epoch {}",
+        backup.id, backup.max_committed_epoch
+    );
+
+    Ok(backup)
+}
+
+pub async fn get_backups() -> anyhow::Result<Vec<MetaBackupInfo>> {
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    let backups = meta_client.list_meta_backups().await?;
+    Ok(backups)
+}
+
+pub async fn list_backups() -> anyhow::Result<()> {
+    let backups = get_backups().await?;
+
+    let mut table = Table::new();
+    table.set_header({
+        let mut row = Row::new();
+        row.add_cell("Id".into());
+        row.add_cell("Max Committed Epoch".into());
+        row.add_cell("SST Count".into());
+        row.add_cell("Create Time".into());
+        row
+    });
+    for backup in backups {
+        let mut row = Row::new();
+        row.add_cell(backup.id.into());
+        row.add_cell(backup.max_committed_epoch.into());
+        row.add_cell(backup.sst_ids.len().into());
+        row.add_cell(backup.create_time.into());
+        table.add_row(row);
+    }
+
+    println!("{table}");
+
+    Ok(())
+}
+
+pub async fn backup_table_fragments(table_id: u32, output: String) -> anyhow::Result<()> {
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    let backup = meta_client.backup_table_fragments(table_id).await?;
+    std::fs::write(&output, &backup)
+        .with_context(|| format!("failed to write the backup to {}", output))?;
+
+    println!(
+        "Backed up the table fragments of table {} to {} ({} bytes)",
+        table_id,
+        output,
+        backup.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_pb::meta::FixFragmentsRequest;
+
+use crate::common::MetaServiceOpts;
+
+/// Fix the selected kinds of inconsistent fragment metadata, or all kinds if none is selected.
+pub async fn fix_fragments(
+    dry_run: bool,
+    dangling_dispatchers: bool,
+    missing_actor_status: bool,
+    orphaned_actor_splits: bool,
+) -> anyhow::Result<Vec<String>> {
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    let select_all = !(dangling_dispatchers || missing_actor_status || orphaned_actor_splits);
+    let fixes = meta_client
+        .fix_fragments(FixFragmentsRequest {
+            dry_run,
+            fix_dangling_dispatchers: select_all || dangling_dispatchers,
+            fix_missing_actor_status: select_all || missing_actor_status,
+            fix_orphaned_actor_splits: select_all || orphaned_actor_splits,
+        })
+        .await?;
+
+    if fixes.is_empty() {
+        println!("No inconsistent fragment metadata found");
+    } else {
+        let action = if dry_run { "Proposed" } else { "Applied" };
+        for fix in &fixes {
+            println!("{} fix: {}", action, fix);
+        }
+    }
+
+    Ok(fixes)
+}
@@ -0,0 +1,46 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::bail;
+use risingwave_pb::stream_plan::DispatcherType;
+
+use crate::common::MetaServiceOpts;
+
+/// Update the dispatchers of the sink fragment of the materialized view to `dispatcher_type`,
+/// which is one of `simple`, `hash` and `broadcast`.
+pub async fn update_sink_dispatch_type(
+    table_id: u32,
+    dispatcher_type: String,
+) -> anyhow::Result<()> {
+    let dispatcher_type = match dispatcher_type.to_lowercase().as_str() {
+        "simple" => DispatcherType::Simple,
+        "hash" => DispatcherType::Hash,
+        "broadcast" => DispatcherType::Broadcast,
+        _ => bail!("unsupported dispatcher type: {}", dispatcher_type),
+    };
+
+    let meta_opts = MetaServiceOpts::from_env()?;
+    let meta_client = meta_opts.create_meta_client().await?;
+
+    meta_client
+        .update_sink_dispatch_type(table_id, dispatcher_type)
+        .await?;
+
+    println!(
+        "Updated the sink dispatchers of table {} to {:?}",
+        table_id, dispatcher_type
+    );
+
+    Ok(())
+}
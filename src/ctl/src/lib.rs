@@ -52,6 +52,9 @@ enum Commands {
     /// Commands for Benchmarks
     #[clap(subcommand)]
     Bench(BenchCommands),
+    /// Commands for debugging the streaming jobs
+    #[clap(subcommand)]
+    Debug(DebugCommands),
     /// Commands for tracing the compute nodes
     Trace,
     // TODO(yuhao): profile other nodes
@@ -86,6 +89,28 @@ enum HummockCommands {
         table_id: u32,
     },
     SstDump,
+    /// print the meta of an SST and the statistics of its keys
+    DescribeSst {
+        /// id of the SST to describe
+        sst_id: u64,
+    },
+    /// print the decoded key-value pairs of an SST
+    DumpSst {
+        /// id of the SST to dump
+        sst_id: u64,
+
+        /// range of the user keys without the table id prefix, in the form of `<start>..<end>`
+        /// with hex-encoded keys, where either side can be omitted
+        #[clap(short, long = "range")]
+        range: Option<String>,
+
+        #[clap(short, long = "table-id")]
+        table_id: Option<u32>,
+
+        /// the maximum number of key-value pairs to print
+        #[clap(short, long = "limit")]
+        limit: Option<usize>,
+    },
     /// trigger a targeted compaction through compaction_group_id
     TriggerManualCompaction {
         #[clap(short, long = "compaction-group-id", default_value_t = 2)]
@@ -132,6 +157,16 @@ enum HummockCommands {
         #[clap(long)]
         max_sub_compaction: Option<u32>,
     },
+    /// Update the scheduling limits of a compaction group. 0 means unlimited.
+    SetGroupConfig {
+        compaction_group_id: u64,
+        /// the maximum number of compaction tasks of the group running at the same time
+        #[clap(long)]
+        max_concurrency: Option<u64>,
+        /// the maximum compaction input bytes per second scheduled for the group
+        #[clap(long)]
+        max_throughput: Option<u64>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -181,6 +216,82 @@ enum MetaCommands {
         #[clap(long)]
         dry_run: bool,
     },
+    /// Commands for meta backups
+    #[clap(subcommand)]
+    Backup(BackupCommands),
+    /// Fix inconsistent fragment metadata left by bugs
+    ///
+    /// The stream graph is paused while the fixes are applied, and resumed afterwards. All kinds
+    /// of fixes are selected if none is specified.
+    FixFragments {
+        /// Show the fixes only, no actual operation
+        #[clap(long)]
+        dry_run: bool,
+        /// remove the dispatcher targets to actors that don't exist
+        #[clap(long)]
+        dangling_dispatchers: bool,
+        /// reconstruct the missing actor status from the vnode mappings of fragments
+        #[clap(long)]
+        missing_actor_status: bool,
+        /// remove the splits of actors that don't exist
+        #[clap(long)]
+        orphaned_actor_splits: bool,
+    },
+    /// Update the dispatchers of the sink fragment of a materialized view
+    ///
+    /// Only the updates that keep delivering the same rows to each downstream actor are allowed.
+    UpdateSinkDispatchType {
+        /// id of the materialized view
+        #[clap(long)]
+        table_id: u32,
+        /// the new dispatcher type: `simple`, `hash` or `broadcast`
+        #[clap(long)]
+        dispatcher_type: String,
+    },
+    /// get the system params of the cluster
+    SystemParams,
+    /// set a system param of the cluster, which takes effect at runtime
+    SetSystemParam {
+        /// name of the param, e.g. `slow_barrier_threshold_ms`
+        param: String,
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebugCommands {
+    /// log the events of the actors of a fragment, or a single actor, at a more verbose level
+    /// temporarily, without changing the log level of the whole compute node
+    SetLogLevel {
+        /// id of the fragment, whose running actors are all affected
+        #[clap(long)]
+        fragment: Option<u32>,
+        /// id of the actor
+        #[clap(long)]
+        actor: Option<u32>,
+        /// the level to log at, e.g. `trace` or `debug`
+        #[clap(long)]
+        level: String,
+        /// how long the level lasts, e.g. `30s`, `10m` or `1h`
+        #[clap(long, default_value = "10m")]
+        duration: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommands {
+    /// take a backup of the meta store
+    Create,
+    /// list all meta backups
+    List,
+    /// take a self-contained backup of the table fragments of a materialized view or sink, i.e.
+    /// all its actor plans, vnode mappings and split assignments
+    TableFragments {
+        /// id of the materialized view or sink
+        table_id: u32,
+        /// path of the file to write the backup to
+        output: String,
+    },
 }
 
 pub async fn start(opts: CliOpts) -> Result<()> {
@@ -201,6 +312,15 @@ pub async fn start(opts: CliOpts) -> Result<()> {
             cmd_impl::hummock::list_kv(epoch, table_id).await?;
         }
         Commands::Hummock(HummockCommands::SstDump) => cmd_impl::hummock::sst_dump().await.unwrap(),
+        Commands::Hummock(HummockCommands::DescribeSst { sst_id }) => {
+            cmd_impl::hummock::describe_sst(sst_id).await?
+        }
+        Commands::Hummock(HummockCommands::DumpSst {
+            sst_id,
+            range,
+            table_id,
+            limit,
+        }) => cmd_impl::hummock::dump_sst(sst_id, range, table_id, limit).await?,
         Commands::Hummock(HummockCommands::TriggerManualCompaction {
             compaction_group_id,
             table_id,
@@ -247,6 +367,18 @@ pub async fn start(opts: CliOpts) -> Result<()> {
             )
             .await?
         }
+        Commands::Hummock(HummockCommands::SetGroupConfig {
+            compaction_group_id,
+            max_concurrency,
+            max_throughput,
+        }) => {
+            cmd_impl::hummock::set_group_config(
+                compaction_group_id,
+                max_concurrency,
+                max_throughput,
+            )
+            .await?
+        }
         Commands::Table(TableCommands::Scan { mv_name }) => cmd_impl::table::scan(mv_name).await?,
         Commands::Table(TableCommands::ScanById { table_id }) => {
             cmd_impl::table::scan_id(table_id).await?
@@ -259,6 +391,44 @@ pub async fn start(opts: CliOpts) -> Result<()> {
         Commands::Meta(MetaCommands::Reschedule { plan, dry_run }) => {
             cmd_impl::meta::reschedule(plan, dry_run).await?
         }
+        Commands::Meta(MetaCommands::Backup(BackupCommands::Create)) => {
+            cmd_impl::meta::create_backup().await?;
+        }
+        Commands::Meta(MetaCommands::Backup(BackupCommands::List)) => {
+            cmd_impl::meta::list_backups().await?
+        }
+        Commands::Meta(MetaCommands::Backup(BackupCommands::TableFragments {
+            table_id,
+            output,
+        })) => cmd_impl::meta::backup_table_fragments(table_id, output).await?,
+        Commands::Meta(MetaCommands::FixFragments {
+            dry_run,
+            dangling_dispatchers,
+            missing_actor_status,
+            orphaned_actor_splits,
+        }) => {
+            cmd_impl::meta::fix_fragments(
+                dry_run,
+                dangling_dispatchers,
+                missing_actor_status,
+                orphaned_actor_splits,
+            )
+            .await?;
+        }
+        Commands::Meta(MetaCommands::UpdateSinkDispatchType {
+            table_id,
+            dispatcher_type,
+        }) => cmd_impl::meta::update_sink_dispatch_type(table_id, dispatcher_type).await?,
+        Commands::Meta(MetaCommands::SystemParams) => cmd_impl::meta::system_params().await?,
+        Commands::Meta(MetaCommands::SetSystemParam { param, value }) => {
+            cmd_impl::meta::set_system_param(param, value).await?
+        }
+        Commands::Debug(DebugCommands::SetLogLevel {
+            fragment,
+            actor,
+            level,
+            duration,
+        }) => cmd_impl::debug::set_log_level(fragment, actor, level, duration).await?,
         Commands::Trace => cmd_impl::trace::trace().await?,
         Commands::Profile { sleep } => cmd_impl::profile::profile(sleep).await?,
     }
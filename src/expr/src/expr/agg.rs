@@ -16,7 +16,9 @@ use std::convert::TryFrom;
 
 use parse_display::{Display, FromStr};
 use risingwave_common::bail;
+use risingwave_common::types::OrderedF64;
 use risingwave_pb::expr::agg_call::Type;
+use risingwave_pb::expr::AggCall;
 
 use crate::{ExprError, Result};
 
@@ -31,9 +33,21 @@ pub enum AggKind {
     Count,
     Avg,
     StringAgg,
-    ApproxCountDistinct,
+    /// The parameter is the desired relative error of the estimation.
+    #[display("approx_count_distinct")]
+    ApproxCountDistinct(#[from_str(default)] OrderedF64),
     ArrayAgg,
     FirstValue,
+    BitAnd,
+    BitOr,
+    BitXor,
+    /// The parameters are the fraction of the percentile and the desired relative error of the
+    /// estimation.
+    #[display("approx_percentile")]
+    ApproxPercentile(
+        #[from_str(default)] OrderedF64,
+        #[from_str(default)] OrderedF64,
+    ),
 }
 
 impl TryFrom<Type> for AggKind {
@@ -48,15 +62,77 @@ impl TryFrom<Type> for AggKind {
             Type::Avg => Ok(AggKind::Avg),
             Type::Count => Ok(AggKind::Count),
             Type::StringAgg => Ok(AggKind::StringAgg),
-            Type::ApproxCountDistinct => Ok(AggKind::ApproxCountDistinct),
+            Type::ApproxCountDistinct => Ok(AggKind::approx_count_distinct()),
             Type::ArrayAgg => Ok(AggKind::ArrayAgg),
             Type::FirstValue => Ok(AggKind::FirstValue),
+            Type::BitAnd => Ok(AggKind::BitAnd),
+            Type::BitOr => Ok(AggKind::BitOr),
+            Type::BitXor => Ok(AggKind::BitXor),
+            Type::ApproxPercentile => Ok(AggKind::approx_percentile(0.5)),
             Type::Unspecified => bail!("Unrecognized agg."),
         }
     }
 }
 
 impl AggKind {
+    /// The relative error of `approx_count_distinct` if not specified, which is the error of a
+    /// `HyperLogLog` sketch with 2^14 registers.
+    pub const DEFAULT_APPROX_COUNT_DISTINCT_ERROR: f64 = 1.04 / 128.0;
+    /// The relative error of `approx_percentile` if not specified.
+    pub const DEFAULT_APPROX_PERCENTILE_ERROR: f64 = 0.01;
+    /// The smallest relative error of `approx_count_distinct`, which is the error of a
+    /// `HyperLogLog` sketch with 2^16 registers, the most of both the batch and streaming sketches.
+    pub const MIN_APPROX_COUNT_DISTINCT_ERROR: f64 = 1.04 / 256.0;
+
+    /// `approx_count_distinct` with the default relative error.
+    pub fn approx_count_distinct() -> Self {
+        Self::ApproxCountDistinct(Self::DEFAULT_APPROX_COUNT_DISTINCT_ERROR.into())
+    }
+
+    /// `approx_percentile` of the fraction with the default relative error.
+    pub fn approx_percentile(fraction: f64) -> Self {
+        Self::ApproxPercentile(
+            fraction.into(),
+            Self::DEFAULT_APPROX_PERCENTILE_ERROR.into(),
+        )
+    }
+
+    /// Builds the kind of the agg call, including the parameters carried by the call.
+    pub fn from_protobuf(prost: &AggCall) -> Result<Self> {
+        let kind = Self::try_from(prost.get_type()?)?;
+        match kind {
+            Self::ApproxCountDistinct(_) if prost.approx_relative_error > 0.0 => Ok(
+                Self::ApproxCountDistinct(prost.approx_relative_error.into()),
+            ),
+            Self::ApproxPercentile(_, relative_error) => Ok(Self::ApproxPercentile(
+                prost.approx_percentile_fraction.into(),
+                if prost.approx_relative_error > 0.0 {
+                    prost.approx_relative_error.into()
+                } else {
+                    relative_error
+                },
+            )),
+            kind => Ok(kind),
+        }
+    }
+
+    /// Returns the relative error to be stored in the agg call, or 0 for other kinds.
+    pub fn approx_relative_error(&self) -> f64 {
+        match self {
+            Self::ApproxCountDistinct(relative_error)
+            | Self::ApproxPercentile(_, relative_error) => relative_error.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the fraction to be stored in the agg call, or 0 for other kinds.
+    pub fn approx_percentile_fraction(&self) -> f64 {
+        match self {
+            Self::ApproxPercentile(fraction, _) => fraction.0,
+            _ => 0.0,
+        }
+    }
+
     pub fn to_prost(self) -> Type {
         match self {
             Self::Min => Type::Min,
@@ -66,9 +142,13 @@ impl AggKind {
             Self::Avg => Type::Avg,
             Self::Count => Type::Count,
             Self::StringAgg => Type::StringAgg,
-            Self::ApproxCountDistinct => Type::ApproxCountDistinct,
+            Self::ApproxCountDistinct(_) => Type::ApproxCountDistinct,
             Self::ArrayAgg => Type::ArrayAgg,
             Self::FirstValue => Type::FirstValue,
+            Self::BitAnd => Type::BitAnd,
+            Self::BitOr => Type::BitOr,
+            Self::BitXor => Type::BitXor,
+            Self::ApproxPercentile(..) => Type::ApproxPercentile,
         }
     }
 }
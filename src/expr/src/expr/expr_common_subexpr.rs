@@ -0,0 +1,61 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::array::{ArrayRef, Column, DataChunk, Row};
+use risingwave_common::types::{DataType, Datum};
+
+use crate::expr::{BoxedExpression, Expression};
+use crate::Result;
+
+/// `CommonSubexprExpression` evaluates each of the sub-expressions shared by `root` once, then
+/// evaluates `root` on the input with the results of the sub-expressions prepended as columns.
+///
+/// Therefore, `root` refers to the `i`-th sub-expression with `InputRef(i)`, and to the `i`-th
+/// input column with `InputRef(subexprs.len() + i)`.
+#[derive(Debug)]
+pub struct CommonSubexprExpression {
+    subexprs: Vec<BoxedExpression>,
+    root: BoxedExpression,
+}
+
+impl CommonSubexprExpression {
+    pub fn new(subexprs: Vec<BoxedExpression>, root: BoxedExpression) -> Self {
+        Self { subexprs, root }
+    }
+}
+
+impl Expression for CommonSubexprExpression {
+    fn return_type(&self) -> DataType {
+        self.root.return_type()
+    }
+
+    fn eval(&self, input: &DataChunk) -> Result<ArrayRef> {
+        let mut columns = Vec::with_capacity(self.subexprs.len() + input.columns().len());
+        for subexpr in &self.subexprs {
+            columns.push(Column::new(subexpr.eval_checked(input)?));
+        }
+        columns.extend(input.columns().iter().cloned());
+        let input = DataChunk::new(columns, input.vis().clone());
+        self.root.eval(&input)
+    }
+
+    fn eval_row(&self, input: &Row) -> Result<Datum> {
+        let mut datums = Vec::with_capacity(self.subexprs.len() + input.0.len());
+        for subexpr in &self.subexprs {
+            datums.push(subexpr.eval_row(input)?);
+        }
+        datums.extend(input.0.iter().cloned());
+        self.root.eval_row(&Row(datums))
+    }
+}
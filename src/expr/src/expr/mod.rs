@@ -21,6 +21,7 @@ pub mod expr_binary_nonnull;
 pub mod expr_binary_nullable;
 mod expr_case;
 mod expr_coalesce;
+mod expr_common_subexpr;
 mod expr_concat_ws;
 mod expr_field;
 mod expr_in;
@@ -34,6 +35,7 @@ mod expr_ternary_bytes;
 mod expr_to_char_const_tmpl;
 pub mod expr_unary;
 mod expr_vnode;
+pub mod optimize;
 mod template;
 
 use std::convert::TryFrom;
@@ -51,12 +53,14 @@ use crate::expr::build_expr_from_prost::*;
 use crate::expr::expr_array_concat::ArrayConcatExpression;
 use crate::expr::expr_case::CaseExpression;
 use crate::expr::expr_coalesce::CoalesceExpression;
+use crate::expr::expr_common_subexpr::CommonSubexprExpression;
 use crate::expr::expr_concat_ws::ConcatWsExpression;
 use crate::expr::expr_field::FieldExpression;
 use crate::expr::expr_in::InExpression;
 use crate::expr::expr_nested_construct::NestedConstructExpression;
 use crate::expr::expr_regexp::RegexpMatchExpression;
 use crate::expr::expr_vnode::VnodeExpression;
+use crate::expr::optimize::{eliminate_common_subexprs, fold_constants};
 use crate::ExprError;
 
 pub type ExpressionRef = Arc<dyn Expression>;
@@ -150,5 +154,23 @@ pub fn build_from_prost(prost: &ExprNode) -> Result<BoxedExpression> {
     }
 }
 
+/// Builds the expression like [`build_from_prost`], after folding the constant sub-expressions
+/// and sharing the common sub-expressions so that each of them is evaluated only once. See
+/// [`optimize`] for details.
+pub fn build_optimized_from_prost(prost: &ExprNode) -> Result<BoxedExpression> {
+    let prost = fold_constants(prost);
+    match eliminate_common_subexprs(&prost) {
+        Some((subexprs, root)) => {
+            let subexprs = subexprs
+                .iter()
+                .map(build_from_prost)
+                .collect::<Result<Vec<_>>>()?;
+            let root = build_from_prost(&root)?;
+            Ok(CommonSubexprExpression::new(subexprs, root).boxed())
+        }
+        None => build_from_prost(&prost),
+    }
+}
+
 mod test_utils;
 pub use test_utils::*;
@@ -0,0 +1,305 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rewrites on the `ExprNode` tree applied before building the expression, see
+//! [`build_optimized_from_prost`](crate::expr::build_optimized_from_prost).
+
+use std::collections::HashMap;
+
+use prost::Message;
+use risingwave_common::array::Row;
+use risingwave_common::util::value_encoding::serialize_datum_to_bytes;
+use risingwave_pb::data::Datum as ProstDatum;
+use risingwave_pb::expr::expr_node::{RexNode, Type};
+use risingwave_pb::expr::{ExprNode, InputRefExpr};
+
+use crate::expr::build_from_prost;
+
+/// Returns whether the children of the function may be only evaluated on part of the rows, so that
+/// hoisting them out may raise errors that would not occur otherwise.
+fn is_conditional(expr_type: Type) -> bool {
+    matches!(
+        expr_type,
+        Type::Case | Type::Coalesce | Type::And | Type::Or
+    )
+}
+
+/// Folds the function calls whose arguments are all constants into constants.
+///
+/// A function call is folded only if it can be built and evaluated successfully. Otherwise it's
+/// kept as is, so that errors like division by zero are still raised at runtime, and only if the
+/// function is actually evaluated, e.g. not on an empty input.
+pub fn fold_constants(prost: &ExprNode) -> ExprNode {
+    let func_call = match &prost.rex_node {
+        Some(RexNode::FuncCall(func_call)) => func_call,
+        _ => return prost.clone(),
+    };
+
+    let mut folded = prost.clone();
+    let children = func_call.children.iter().map(fold_constants).collect();
+    if let Some(RexNode::FuncCall(func_call)) = &mut folded.rex_node {
+        func_call.children = children;
+    }
+
+    let foldable = matches!(&folded.rex_node, Some(RexNode::FuncCall(func_call))
+        if !func_call.children.is_empty()
+            && func_call
+                .children
+                .iter()
+                .all(|child| child.expr_type == Type::ConstantValue as i32));
+    if !foldable {
+        return folded;
+    }
+
+    match build_from_prost(&folded).and_then(|expr| expr.eval_row(&Row(vec![]))) {
+        Ok(datum) => ExprNode {
+            expr_type: Type::ConstantValue as i32,
+            return_type: folded.return_type,
+            rex_node: Some(RexNode::Constant(ProstDatum {
+                body: serialize_datum_to_bytes(datum.as_ref()),
+            })),
+        },
+        Err(_) => folded,
+    }
+}
+
+/// Finds the function calls that occur more than once in the expression, and rewrites them to be
+/// evaluated only once, by
+/// `CommonSubexprExpression`.
+///
+/// Returns the shared sub-expressions and the rewritten root, or `None` if there's nothing to
+/// share. Sub-expressions are not shared out of the children of conditional functions like `CASE`,
+/// since they may be only evaluated on part of the rows.
+pub fn eliminate_common_subexprs(prost: &ExprNode) -> Option<(Vec<ExprNode>, ExprNode)> {
+    let mut counts = HashMap::new();
+    count_subexprs(prost, &mut counts);
+
+    // Choose the outermost sub-expressions that occur more than once, and check how many times
+    // each of them is actually used, since an inner one may be only used inside an outer one.
+    let mut usages = HashMap::new();
+    let mut subexprs = vec![];
+    select_subexprs(prost, &counts, &mut usages, &mut subexprs);
+    let subexprs = subexprs
+        .into_iter()
+        .filter(|(key, _)| usages[key] > 1)
+        .collect::<Vec<_>>();
+    if subexprs.is_empty() {
+        return None;
+    }
+
+    let indices = subexprs
+        .iter()
+        .enumerate()
+        .map(|(idx, (key, _))| (key.clone(), idx))
+        .collect();
+    let root = rewrite_subexprs(prost, &indices, true);
+    Some((subexprs.into_iter().map(|(_, expr)| expr).collect(), root))
+}
+
+fn count_subexprs(prost: &ExprNode, counts: &mut HashMap<Vec<u8>, usize>) {
+    if let Some(RexNode::FuncCall(func_call)) = &prost.rex_node {
+        *counts.entry(prost.encode_to_vec()).or_default() += 1;
+        if !is_conditional(prost.get_expr_type().unwrap()) {
+            for child in &func_call.children {
+                count_subexprs(child, counts);
+            }
+        }
+    }
+}
+
+fn select_subexprs(
+    prost: &ExprNode,
+    counts: &HashMap<Vec<u8>, usize>,
+    usages: &mut HashMap<Vec<u8>, usize>,
+    subexprs: &mut Vec<(Vec<u8>, ExprNode)>,
+) {
+    if let Some(RexNode::FuncCall(func_call)) = &prost.rex_node {
+        let key = prost.encode_to_vec();
+        if counts[&key] > 1 {
+            let usage = usages.entry(key.clone()).or_default();
+            if *usage == 0 {
+                subexprs.push((key, prost.clone()));
+            }
+            *usage += 1;
+        } else if !is_conditional(prost.get_expr_type().unwrap()) {
+            for child in &func_call.children {
+                select_subexprs(child, counts, usages, subexprs);
+            }
+        }
+    }
+}
+
+/// Replaces the shared sub-expressions with references to their results, and shifts the
+/// references to the input columns accordingly.
+fn rewrite_subexprs(
+    prost: &ExprNode,
+    indices: &HashMap<Vec<u8>, usize>,
+    allow_share: bool,
+) -> ExprNode {
+    let make_input_ref = |column_idx: usize| ExprNode {
+        expr_type: Type::InputRef as i32,
+        return_type: prost.return_type.clone(),
+        rex_node: Some(RexNode::InputRef(InputRefExpr {
+            column_idx: column_idx as i32,
+        })),
+    };
+
+    match &prost.rex_node {
+        Some(RexNode::InputRef(input_ref)) => {
+            make_input_ref(indices.len() + input_ref.column_idx as usize)
+        }
+        Some(RexNode::FuncCall(func_call)) => {
+            if allow_share && let Some(&idx) = indices.get(&prost.encode_to_vec()) {
+                return make_input_ref(idx);
+            }
+            let allow_share = allow_share && !is_conditional(prost.get_expr_type().unwrap());
+            let mut rewritten = prost.clone();
+            if let Some(RexNode::FuncCall(rewritten_func_call)) = &mut rewritten.rex_node {
+                rewritten_func_call.children = func_call
+                    .children
+                    .iter()
+                    .map(|child| rewrite_subexprs(child, indices, allow_share))
+                    .collect();
+            }
+            rewritten
+        }
+        _ => prost.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::{DataChunk, DataChunkTestExt};
+    use risingwave_pb::data::data_type::TypeName;
+    use risingwave_pb::data::DataType as ProstDataType;
+    use risingwave_pb::expr::FunctionCall;
+
+    use super::*;
+    use crate::expr::{build_optimized_from_prost, make_i32_literal, make_input_ref};
+
+    fn make_function(kind: Type, ret: TypeName, children: Vec<ExprNode>) -> ExprNode {
+        ExprNode {
+            expr_type: kind as i32,
+            return_type: Some(ProstDataType {
+                type_name: ret as i32,
+                ..Default::default()
+            }),
+            rex_node: Some(RexNode::FuncCall(FunctionCall { children })),
+        }
+    }
+
+    fn make_i32_function(kind: Type, left: ExprNode, right: ExprNode) -> ExprNode {
+        make_function(kind, TypeName::Int32, vec![left, right])
+    }
+
+    fn input_chunk() -> DataChunk {
+        DataChunk::from_pretty(
+            "i i
+             1 2
+             3 .
+             5 6",
+        )
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        // $0 + (1 + 2)
+        let expr = make_i32_function(
+            Type::Add,
+            make_input_ref(0, TypeName::Int32),
+            make_i32_function(Type::Add, make_i32_literal(1), make_i32_literal(2)),
+        );
+        let expected = make_i32_function(
+            Type::Add,
+            make_input_ref(0, TypeName::Int32),
+            make_i32_literal(3),
+        );
+        assert_eq!(fold_constants(&expr), expected);
+    }
+
+    #[test]
+    fn test_fold_constants_preserve_error() {
+        // $0 + 1 / 0
+        let expr = make_i32_function(
+            Type::Add,
+            make_input_ref(0, TypeName::Int32),
+            make_i32_function(Type::Divide, make_i32_literal(1), make_i32_literal(0)),
+        );
+        assert_eq!(fold_constants(&expr), expr);
+
+        // The error is still raised on evaluation rather than on building.
+        let optimized = build_optimized_from_prost(&expr).unwrap();
+        assert!(optimized.eval(&input_chunk()).is_err());
+        let empty_chunk = DataChunk::from_pretty("i i");
+        assert_eq!(optimized.eval(&empty_chunk).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_eliminate_common_subexprs() {
+        // ($0 + $1) * ($0 + $1) + $1
+        let sum = make_i32_function(
+            Type::Add,
+            make_input_ref(0, TypeName::Int32),
+            make_input_ref(1, TypeName::Int32),
+        );
+        let expr = make_i32_function(
+            Type::Add,
+            make_i32_function(Type::Multiply, sum.clone(), sum.clone()),
+            make_input_ref(1, TypeName::Int32),
+        );
+
+        let (subexprs, root) = eliminate_common_subexprs(&expr).unwrap();
+        assert_eq!(subexprs, vec![sum]);
+        let expected_root = make_i32_function(
+            Type::Add,
+            make_i32_function(
+                Type::Multiply,
+                make_input_ref(0, TypeName::Int32),
+                make_input_ref(0, TypeName::Int32),
+            ),
+            make_input_ref(2, TypeName::Int32),
+        );
+        assert_eq!(root, expected_root);
+
+        let chunk = input_chunk();
+        let expected = build_from_prost(&expr).unwrap().eval(&chunk).unwrap();
+        let optimized = build_optimized_from_prost(&expr).unwrap();
+        assert_eq!(optimized.eval(&chunk).unwrap(), expected);
+        for (idx, row) in chunk.rows().enumerate() {
+            let row = row.to_owned_row();
+            assert_eq!(
+                optimized.eval_row(&row).unwrap(),
+                expected.datum_at(idx),
+                "row {}",
+                idx
+            );
+        }
+    }
+
+    #[test]
+    fn test_eliminate_common_subexprs_conditional() {
+        // coalesce($0 / $1, $0 / $1) + $0
+        let div = make_i32_function(
+            Type::Divide,
+            make_input_ref(0, TypeName::Int32),
+            make_input_ref(1, TypeName::Int32),
+        );
+        let expr = make_i32_function(
+            Type::Add,
+            make_function(Type::Coalesce, TypeName::Int32, vec![div.clone(), div]),
+            make_input_ref(0, TypeName::Int32),
+        );
+        assert!(eliminate_common_subexprs(&expr).is_none());
+    }
+}
@@ -24,7 +24,9 @@ use risingwave_pb::plan_common::OrderType as ProstOrderType;
 
 use crate::expr::{build_from_prost, AggKind};
 use crate::vector_op::agg::approx_count_distinct::ApproxCountDistinct;
+use crate::vector_op::agg::approx_percentile::ApproxPercentile;
 use crate::vector_op::agg::array_agg::create_array_agg_state;
+use crate::vector_op::agg::bit_agg::create_bit_agg_state;
 use crate::vector_op::agg::count_star::CountStar;
 use crate::vector_op::agg::filter::*;
 use crate::vector_op::agg::functions::*;
@@ -69,7 +71,7 @@ impl AggStateFactory {
         // NOTE: The function signature is checked by `AggCall::infer_return_type` in the frontend.
 
         let return_type = DataType::from(prost.get_return_type()?);
-        let agg_kind = AggKind::try_from(prost.get_type()?)?;
+        let agg_kind = AggKind::from_protobuf(prost)?;
         let distinct = prost.distinct;
         let mut order_pairs = vec![];
         let mut order_col_types = vec![];
@@ -86,9 +88,22 @@ impl AggStateFactory {
 
         let initial_agg_state: BoxedAggState = match (agg_kind, &prost.get_args()[..]) {
             (AggKind::Count, []) => Box::new(CountStar::new(return_type.clone())),
-            (AggKind::ApproxCountDistinct, [arg]) => {
+            (AggKind::ApproxCountDistinct(relative_error), [arg]) => {
                 let input_col_idx = arg.get_input()?.get_column_idx() as usize;
-                Box::new(ApproxCountDistinct::new(return_type.clone(), input_col_idx))
+                Box::new(ApproxCountDistinct::new(
+                    return_type.clone(),
+                    input_col_idx,
+                    relative_error.0,
+                )?)
+            }
+            (AggKind::ApproxPercentile(fraction, relative_error), [arg]) => {
+                let input_col_idx = arg.get_input()?.get_column_idx() as usize;
+                Box::new(ApproxPercentile::new(
+                    return_type.clone(),
+                    input_col_idx,
+                    fraction.0,
+                    relative_error.0,
+                ))
             }
             (AggKind::StringAgg, [agg_arg, delim_arg]) => {
                 assert_eq!(
@@ -107,6 +122,17 @@ impl AggStateFactory {
                 let agg_col_idx = arg.get_input()?.get_column_idx() as usize;
                 create_array_agg_state(return_type.clone(), agg_col_idx, order_pairs)?
             }
+            (AggKind::BitAnd | AggKind::BitOr | AggKind::BitXor, [arg]) => {
+                let input_type = DataType::from(arg.get_type()?);
+                let input_col_idx = arg.get_input()?.get_column_idx() as usize;
+                create_bit_agg_state(
+                    input_type,
+                    input_col_idx,
+                    agg_kind,
+                    return_type.clone(),
+                    distinct,
+                )?
+            }
             (agg_kind, [arg]) => {
                 // other unary agg call
                 let input_type = DataType::from(arg.get_type()?);
@@ -19,38 +19,162 @@ use risingwave_common::array::*;
 use risingwave_common::bail;
 use risingwave_common::types::*;
 
+use crate::expr::AggKind;
 use crate::vector_op::agg::aggregator::Aggregator;
-use crate::Result;
+use crate::{ExprError, Result};
+
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 16;
+/// The maximum value of a 4-bit register.
+const MAX_REGISTER_VALUE: u8 = 0xF;
+
+/// `HllAggState` is a `HyperLogLog` sketch with 4-bit registers, two of them packed in a byte.
+///
+/// A register can only hold a rank in `base..=base + 15`, where `base` is shared by all registers
+/// and increased once no register is at `base` any more. Ranks beyond the range are capped, which
+/// barely affects the estimation since such ranks are rare. See "Better with fewer bits:
+/// Improving the performance of cardinality estimation of large data streams" by Qingjun Xiao et
+/// al.
+#[derive(Clone)]
+pub struct HllAggState {
+    /// Number of low bits of the hash used as the index of the register.
+    precision: u8,
+    registers: Vec<u8>,
+    base: u8,
+    /// Number of registers holding `base`.
+    base_registers: usize,
+}
+
+impl HllAggState {
+    /// Creates a sketch whose standard error is at most `relative_error`, which is
+    /// 1.04/sqrt(num of registers). The number of registers is limited to `2^4..=2^16`, so the
+    /// error can't be smaller than [`AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR`].
+    pub fn new(relative_error: f64) -> Result<Self> {
+        let precision = (MIN_PRECISION..=MAX_PRECISION)
+            .find(|precision| 1.04 / ((1 << precision) as f64).sqrt() <= relative_error)
+            .ok_or_else(|| ExprError::InvalidParam {
+                name: "relative_error",
+                reason: format!(
+                    "must not be smaller than {}, but got {}",
+                    AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR,
+                    relative_error
+                ),
+            })?;
+        Ok(Self {
+            precision,
+            registers: vec![0; (1 << precision) / 2],
+            base: 0,
+            base_registers: 1 << precision,
+        })
+    }
+
+    fn num_of_registers(&self) -> usize {
+        1 << self.precision
+    }
+
+    fn get_register(&self, index: usize) -> u8 {
+        (self.registers[index / 2] >> ((index % 2) * 4)) & MAX_REGISTER_VALUE
+    }
+
+    fn set_register(&mut self, index: usize, value: u8) {
+        let shift = (index % 2) * 4;
+        let byte = &mut self.registers[index / 2];
+        *byte = (*byte & !(MAX_REGISTER_VALUE << shift)) | (value << shift);
+    }
+
+    /// Adds a 64-bit hash into the sketch. The low `precision` bits select the register, and the
+    /// rank is the number of trailing zeroes plus 1 in the remaining bits.
+    pub fn add_hash(&mut self, hash: u64) {
+        let index = (hash as usize) & (self.num_of_registers() - 1);
+        // To allow hash to terminate if the remaining bits are all 0s.
+        let rank =
+            (((hash >> self.precision) | (1 << (64 - self.precision))).trailing_zeros() + 1) as u8;
+        if rank <= self.base {
+            return;
+        }
+
+        let value = (rank - self.base).min(MAX_REGISTER_VALUE);
+        let old_value = self.get_register(index);
+        if value > old_value {
+            self.set_register(index, value);
+            if old_value == 0 {
+                self.base_registers -= 1;
+                if self.base_registers == 0 {
+                    self.rebase();
+                }
+            }
+        }
+    }
+
+    /// Increases `base` until some register holds it again.
+    fn rebase(&mut self) {
+        while self.base_registers == 0 {
+            self.base += 1;
+            for index in 0..self.num_of_registers() {
+                let value = self.get_register(index) - 1;
+                self.set_register(index, value);
+                if value == 0 {
+                    self.base_registers += 1;
+                }
+            }
+        }
+    }
+
+    /// Calculates the bias-corrected harmonic mean of the registers to get the approximate count.
+    pub fn estimate(&self) -> i64 {
+        let m = self.num_of_registers() as f64;
+        // See "HyperLogLog: the analysis of a near-optimal cardinality estimation algorithm" by
+        // Philippe Flajolet et al.
+        let bias_correction = match self.num_of_registers() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1. + 1.079 / m),
+        };
+
+        let mut mean = 0.0;
+        for index in 0..self.num_of_registers() {
+            let rank = self.base as i32 + self.get_register(index) as i32;
+            mean += 2f64.powi(-rank);
+        }
+        let raw_estimate = bias_correction * m * m / mean;
+
+        // If raw_estimate is not much bigger than m and some registers have value 0, set answer to
+        // m * log(m/V) where V is the number of registers with value 0
+        let answer = if raw_estimate <= 2.5 * m && self.base == 0 {
+            m * (m / self.base_registers as f64).ln()
+        } else {
+            raw_estimate
+        };
 
-const INDEX_BITS: u8 = 14; // number of bits used for finding the index of each 64-bit hash
-const NUM_OF_REGISTERS: usize = 1 << INDEX_BITS; // number of indices available
-const COUNT_BITS: u8 = 64 - INDEX_BITS; // number of non-index bits in each 64-bit hash
+        answer as i64
+    }
 
-// Approximation for bias correction for 16384 registers. See "HyperLogLog: the analysis of a
-// near-optimal cardinality estimation algorithm" by Philippe Flajolet et al.
-const BIAS_CORRECTION: f64 = 0.7213 / (1. + (1.079 / NUM_OF_REGISTERS as f64));
+    pub fn reset(&mut self) {
+        self.registers.fill(0);
+        self.base = 0;
+        self.base_registers = self.num_of_registers();
+    }
+}
 
-/// `ApproxCountDistinct` approximates the count of non-null rows using `HyperLogLog`. The
-/// estimation error for `HyperLogLog` is 1.04/sqrt(num of registers). With 2^14 registers this
-/// is ~1/128.
+/// `ApproxCountDistinct` approximates the count of non-null rows using `HyperLogLog`.
 #[derive(Clone)]
 pub struct ApproxCountDistinct {
     return_type: DataType,
     input_col_idx: usize,
-    registers: [u8; NUM_OF_REGISTERS],
+    state: HllAggState,
 }
 
 impl ApproxCountDistinct {
-    pub fn new(return_type: DataType, input_col_idx: usize) -> Self {
-        Self {
+    pub fn new(return_type: DataType, input_col_idx: usize, relative_error: f64) -> Result<Self> {
+        Ok(Self {
             return_type,
             input_col_idx,
-            registers: [0; NUM_OF_REGISTERS],
-        }
+            state: HllAggState::new(relative_error)?,
+        })
     }
 
-    /// Adds the count of the datum's hash into the register, if it is greater than the existing
-    /// count at the register
+    /// Adds the datum's hash into the sketch.
     fn add_datum(&mut self, datum_ref: DatumRef<'_>) {
         if datum_ref.is_none() {
             return;
@@ -58,13 +182,7 @@ impl ApproxCountDistinct {
 
         let scalar_impl = datum_ref.unwrap().into_scalar_impl();
         let hash = self.get_hash(scalar_impl);
-
-        let index = (hash as usize) & (NUM_OF_REGISTERS - 1); // Index is based on last few bits
-        let count = self.count_hash(hash);
-
-        if count > self.registers[index] {
-            self.registers[index] = count;
-        }
+        self.state.add_hash(hash);
     }
 
     /// Calculate the hash of the `scalar_impl` using Rust's default hasher
@@ -74,48 +192,6 @@ impl ApproxCountDistinct {
         scalar_impl.hash(&mut hasher);
         hasher.finish()
     }
-
-    /// Counts the number of trailing zeroes plus 1 in the non-index bits of the hash
-    fn count_hash(&self, mut hash: u64) -> u8 {
-        hash >>= INDEX_BITS; // Ignore bits used as index for the hash
-        hash |= 1 << COUNT_BITS; // To allow hash to terminate if it is all 0s
-
-        (hash.trailing_zeros() + 1) as u8
-    }
-
-    /// Calculates the bias-corrected harmonic mean of the registers to get the approximate count
-    fn calculate_result(&self) -> i64 {
-        let m = NUM_OF_REGISTERS as f64;
-        let mut mean = 0.0;
-
-        // Get harmonic mean of all the counts in results
-        for count in self.registers.iter() {
-            mean += 1.0 / ((1 << *count) as f64);
-        }
-
-        let raw_estimate = BIAS_CORRECTION * m * m / mean;
-
-        // If raw_estimate is not much bigger than m and some registers have value 0, set answer to
-        // m * log(m/V) where V is the number of registers with value 0
-        let answer = if raw_estimate <= 2.5 * m {
-            let mut zero_registers: f64 = 0.0;
-            for i in self.registers.iter() {
-                if *i == 0 {
-                    zero_registers += 1.0;
-                }
-            }
-
-            if zero_registers == 0.0 {
-                raw_estimate
-            } else {
-                m * (m.log2() - (zero_registers.log2()))
-            }
-        } else {
-            raw_estimate
-        };
-
-        answer as i64
-    }
 }
 
 impl Aggregator for ApproxCountDistinct {
@@ -143,8 +219,8 @@ impl Aggregator for ApproxCountDistinct {
     }
 
     fn output(&mut self, builder: &mut ArrayBuilderImpl) -> Result<()> {
-        let result = self.calculate_result();
-        self.registers = [0; NUM_OF_REGISTERS];
+        let result = self.state.estimate();
+        self.state.reset();
         match builder {
             ArrayBuilderImpl::Int64(b) => {
                 b.append(Some(result));
@@ -159,12 +235,13 @@ impl Aggregator for ApproxCountDistinct {
 mod tests {
 
     use risingwave_common::array::{
-        ArrayBuilder, ArrayBuilderImpl, DataChunk, I32Array, I64ArrayBuilder,
+        Array, ArrayBuilder, ArrayBuilderImpl, DataChunk, I32Array, I64ArrayBuilder,
     };
     use risingwave_common::types::DataType;
 
-    use crate::vector_op::agg::aggregator::Aggregator;
-    use crate::vector_op::agg::approx_count_distinct::ApproxCountDistinct;
+    use crate::expr::AggKind;
+    use crate::vector_op::agg::aggregator::{create_agg_state_unary, Aggregator};
+    use crate::vector_op::agg::approx_count_distinct::{ApproxCountDistinct, HllAggState};
 
     fn generate_data_chunk(size: usize, start: i32) -> DataChunk {
         let mut lhs = vec![];
@@ -176,12 +253,23 @@ mod tests {
         DataChunk::new(vec![col1], size)
     }
 
+    fn output_of(agg: &mut dyn Aggregator) -> i64 {
+        let mut builder = ArrayBuilderImpl::Int64(I64ArrayBuilder::new(1));
+        agg.output(&mut builder).unwrap();
+        builder.finish().as_int64().value_at(0).unwrap()
+    }
+
     #[test]
     fn test_update_single() {
         let inputs_size: [usize; 3] = [20000, 10000, 5000];
         let inputs_start: [i32; 3] = [0, 20000, 30000];
 
-        let mut agg = ApproxCountDistinct::new(DataType::Int64, 0);
+        let mut agg = ApproxCountDistinct::new(
+            DataType::Int64,
+            0,
+            AggKind::DEFAULT_APPROX_COUNT_DISTINCT_ERROR,
+        )
+        .unwrap();
         let mut builder = ArrayBuilderImpl::Int64(I64ArrayBuilder::new(3));
 
         for i in 0..3 {
@@ -201,7 +289,12 @@ mod tests {
         let inputs_size: [usize; 3] = [20000, 10000, 5000];
         let inputs_start: [i32; 3] = [0, 20000, 30000];
 
-        let mut agg = ApproxCountDistinct::new(DataType::Int64, 0);
+        let mut agg = ApproxCountDistinct::new(
+            DataType::Int64,
+            0,
+            AggKind::DEFAULT_APPROX_COUNT_DISTINCT_ERROR,
+        )
+        .unwrap();
         let mut builder = ArrayBuilderImpl::Int64(I64ArrayBuilder::new(3));
 
         for i in 0..3 {
@@ -214,4 +307,80 @@ mod tests {
         let array = builder.finish();
         assert_eq!(array.len(), 3);
     }
+
+    #[test]
+    fn test_hll_registers() {
+        let mut state = HllAggState::new(0.26).unwrap();
+        assert_eq!(state.num_of_registers(), 16);
+        assert_eq!(
+            HllAggState::new(AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR)
+                .unwrap()
+                .num_of_registers(),
+            1 << 16
+        );
+        assert!(HllAggState::new(AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR / 2.0).is_err());
+
+        // Rank 20 of register 3 is capped at 15.
+        state.add_hash((1 << (4 + 19)) | 3);
+        assert_eq!(state.get_register(3), 15);
+        assert_eq!(state.get_register(2), 0);
+
+        // Once all registers are set, the base is raised to the minimum rank.
+        for index in 0..16 {
+            state.add_hash((1 << (4 + 1)) | index);
+        }
+        assert_eq!(state.base, 2);
+        assert_eq!(state.get_register(3), 13);
+        assert_eq!(state.get_register(2), 0);
+
+        state.reset();
+        assert_eq!(state.base, 0);
+        assert_eq!(state.estimate(), 0);
+    }
+
+    /// Compares the estimation against the exact `count(distinct)` of 1M rows. The estimation
+    /// error is a standard error, so 3 times of it bounds the error with high probability.
+    #[test]
+    fn test_error_ratio() {
+        const ROW_COUNT: usize = 1_000_000;
+        const CHUNK_SIZE: usize = 10_000;
+
+        for relative_error in [0.01, 0.05] {
+            for ndv in [1000, 100_000, 1_000_000] {
+                let mut approx_agg =
+                    ApproxCountDistinct::new(DataType::Int64, 0, relative_error).unwrap();
+                let mut exact_agg = create_agg_state_unary(
+                    DataType::Int32,
+                    0,
+                    AggKind::Count,
+                    DataType::Int64,
+                    true,
+                )
+                .unwrap();
+
+                for chunk_start in (0..ROW_COUNT).step_by(CHUNK_SIZE) {
+                    // 7919 is a prime, so the rows have exactly `ndv` distinct values.
+                    let values = (chunk_start..chunk_start + CHUNK_SIZE)
+                        .map(|i| Some(((i * 7919) % ndv) as i32))
+                        .collect::<Vec<_>>();
+                    let data_chunk =
+                        DataChunk::new(vec![I32Array::from_slice(&values).into()], CHUNK_SIZE);
+                    approx_agg.update_multi(&data_chunk, 0, CHUNK_SIZE).unwrap();
+                    exact_agg.update_multi(&data_chunk, 0, CHUNK_SIZE).unwrap();
+                }
+
+                let exact = output_of(exact_agg.as_mut());
+                assert_eq!(exact, ndv as i64);
+                let estimation = output_of(&mut approx_agg);
+                let error_ratio = ((estimation - exact) as f64 / exact as f64).abs();
+                assert!(
+                    error_ratio < 3.0 * relative_error,
+                    "relative error: {}, exact: {}, estimation: {}",
+                    relative_error,
+                    exact,
+                    estimation
+                );
+            }
+        }
+    }
 }
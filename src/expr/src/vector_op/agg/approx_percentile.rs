@@ -0,0 +1,393 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use risingwave_common::array::*;
+use risingwave_common::bail;
+use risingwave_common::types::*;
+
+use crate::vector_op::agg::aggregator::Aggregator;
+use crate::Result;
+
+/// `PercentileSketch` is a `DDSketch` estimating the percentiles of float values. See "DDSketch: A
+/// Fast and Fully-Mergeable Quantile Sketch with Relative-Error Guarantees" by Charles Masson et
+/// al.
+///
+/// The magnitude of a value is counted in the bucket `ceil(log_gamma(magnitude))`, where `gamma =
+/// (1 + alpha) / (1 - alpha)` and `alpha` is the relative error. Every value of a bucket is within
+/// `alpha` of the estimation of the bucket, and the buckets are ordered as their values, so the
+/// estimated percentile is always within `alpha` of the exact one.
+///
+/// The counts of the buckets can be decreased as well, so values can be removed from the sketch
+/// without losing the guarantee, as long as they have been added before. The number of buckets is
+/// `ln(max / min) / ln(gamma)` for the values in `[min, max]` of each sign, e.g. about 700 for
+/// positive values in `[1, 1e6]` with 1% relative error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PercentileSketch {
+    gamma: f64,
+    ln_gamma: f64,
+    /// Counts of the positive values by the bucket.
+    positive: BTreeMap<i32, i64>,
+    /// Counts of the negative values by the bucket of the magnitude.
+    negative: BTreeMap<i32, i64>,
+    zero_count: i64,
+}
+
+impl PercentileSketch {
+    pub fn new(relative_error: f64) -> Self {
+        let gamma = (1.0 + relative_error) / (1.0 - relative_error);
+        Self {
+            gamma,
+            ln_gamma: gamma.ln(),
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero_count: 0,
+        }
+    }
+
+    /// Infinities are counted in the bucket `i32::MAX`, whose estimation is also infinite.
+    fn bucket_of(&self, magnitude: f64) -> i32 {
+        (magnitude.ln() / self.ln_gamma).ceil() as i32
+    }
+
+    fn estimate_of(&self, bucket: i32) -> f64 {
+        2.0 * self.gamma.powi(bucket) / (self.gamma + 1.0)
+    }
+
+    /// Adds `delta` to the count of the value. NaN is ignored.
+    fn update(&mut self, value: f64, delta: i64) {
+        if value == 0.0 {
+            self.zero_count += delta;
+            return;
+        }
+        if value.is_nan() {
+            return;
+        }
+        let bucket = self.bucket_of(value.abs());
+        let buckets = if value > 0.0 {
+            &mut self.positive
+        } else {
+            &mut self.negative
+        };
+        let count = buckets.entry(bucket).or_default();
+        *count += delta;
+        if *count == 0 {
+            buckets.remove(&bucket);
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.update(value, 1);
+    }
+
+    /// Removes a value added before.
+    pub fn remove(&mut self, value: f64) {
+        self.update(value, -1);
+    }
+
+    /// Number of values in the sketch.
+    pub fn count(&self) -> i64 {
+        self.negative.values().sum::<i64>() + self.zero_count + self.positive.values().sum::<i64>()
+    }
+
+    /// Number of non-empty buckets of the non-zero values, which is the size of the sketch.
+    pub fn num_buckets(&self) -> usize {
+        self.negative.len() + self.positive.len()
+    }
+
+    /// Estimates the value at `fraction` of the sorted values, which is the first value whose
+    /// position is at least `fraction` of the count, as `percentile_disc` does. Returns `None` if
+    /// the sketch is empty.
+    pub fn percentile(&self, fraction: f64) -> Option<f64> {
+        let count = self.count();
+        if count <= 0 {
+            return None;
+        }
+        let rank = ((fraction * count as f64).ceil() as i64 - 1).clamp(0, count - 1);
+
+        let mut seen = 0;
+        for (&bucket, &bucket_count) in self.negative.iter().rev() {
+            seen += bucket_count;
+            if seen > rank {
+                return Some(-self.estimate_of(bucket));
+            }
+        }
+        seen += self.zero_count;
+        if seen > rank {
+            return Some(0.0);
+        }
+        for (&bucket, &bucket_count) in &self.positive {
+            seen += bucket_count;
+            if seen > rank {
+                return Some(self.estimate_of(bucket));
+            }
+        }
+        unreachable!("the rank is less than the count of the sketch")
+    }
+
+    pub fn reset(&mut self) {
+        self.positive.clear();
+        self.negative.clear();
+        self.zero_count = 0;
+    }
+
+    /// Serializes the sketch as `[zero_count, num_negative_buckets, (bucket, count) of negative
+    /// values .., (bucket, count) of positive values ..]`.
+    pub fn serialize(&self) -> Vec<i64> {
+        let mut result = Vec::with_capacity(2 + 2 * (self.negative.len() + self.positive.len()));
+        result.push(self.zero_count);
+        result.push(self.negative.len() as i64);
+        for (&bucket, &count) in self.negative.iter().chain(&self.positive) {
+            result.push(bucket as i64);
+            result.push(count);
+        }
+        result
+    }
+
+    /// Deserializes the sketch serialized by [`PercentileSketch::serialize`] with the same relative
+    /// error.
+    pub fn deserialize(relative_error: f64, serialized: &[i64]) -> Self {
+        let mut sketch = Self::new(relative_error);
+        let [zero_count, num_negative_buckets, buckets @ ..] = serialized else {
+            panic!("invalid serialized percentile sketch: {:?}", serialized);
+        };
+        sketch.zero_count = *zero_count;
+        for (i, pair) in buckets.chunks_exact(2).enumerate() {
+            let buckets = if (i as i64) < *num_negative_buckets {
+                &mut sketch.negative
+            } else {
+                &mut sketch.positive
+            };
+            buckets.insert(pair[0] as i32, pair[1]);
+        }
+        sketch
+    }
+}
+
+/// `ApproxPercentile` estimates the percentile of non-null float values using
+/// [`PercentileSketch`].
+#[derive(Clone)]
+pub struct ApproxPercentile {
+    return_type: DataType,
+    input_col_idx: usize,
+    fraction: f64,
+    sketch: PercentileSketch,
+}
+
+impl ApproxPercentile {
+    pub fn new(
+        return_type: DataType,
+        input_col_idx: usize,
+        fraction: f64,
+        relative_error: f64,
+    ) -> Self {
+        Self {
+            return_type,
+            input_col_idx,
+            fraction,
+            sketch: PercentileSketch::new(relative_error),
+        }
+    }
+
+    fn add_rows(
+        &mut self,
+        input: &DataChunk,
+        start_row_id: usize,
+        end_row_id: usize,
+    ) -> Result<()> {
+        let array = match input.column_at(self.input_col_idx).array_ref() {
+            ArrayImpl::Float64(array) => array,
+            array => bail!(
+                "Unexpected input for approx_percentile: {}",
+                array.get_ident()
+            ),
+        };
+        for row_id in start_row_id..end_row_id {
+            if let Some(value) = array.value_at(row_id) {
+                self.sketch.add(value.0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Aggregator for ApproxPercentile {
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn update_single(&mut self, input: &DataChunk, row_id: usize) -> Result<()> {
+        self.add_rows(input, row_id, row_id + 1)
+    }
+
+    fn update_multi(
+        &mut self,
+        input: &DataChunk,
+        start_row_id: usize,
+        end_row_id: usize,
+    ) -> Result<()> {
+        self.add_rows(input, start_row_id, end_row_id)
+    }
+
+    fn output(&mut self, builder: &mut ArrayBuilderImpl) -> Result<()> {
+        let result = self.sketch.percentile(self.fraction);
+        self.sketch.reset();
+        match builder {
+            ArrayBuilderImpl::Float64(b) => {
+                b.append(result.map(OrderedF64::from));
+                Ok(())
+            }
+            _ => bail!("Unexpected builder for approx_percentile."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::{ArrayBuilder, ArrayBuilderImpl, DataChunk, F64ArrayBuilder};
+    use risingwave_common::types::DataType;
+
+    use super::*;
+
+    fn output_of(agg: &mut ApproxPercentile) -> Option<f64> {
+        let mut builder = ArrayBuilderImpl::Float64(F64ArrayBuilder::new(1));
+        agg.output(&mut builder).unwrap();
+        builder.finish().as_float64().value_at(0).map(|v| v.0)
+    }
+
+    /// The exact value of `percentile_disc(fraction)`.
+    fn exact_percentile(sorted: &[f64], fraction: f64) -> f64 {
+        let rank = ((fraction * sorted.len() as f64).ceil() as usize).max(1) - 1;
+        sorted[rank]
+    }
+
+    fn assert_within(estimation: f64, exact: f64, relative_error: f64) {
+        assert!(
+            (estimation - exact).abs() <= relative_error * exact.abs() * (1.0 + 1e-9),
+            "relative error: {}, exact: {}, estimation: {}",
+            relative_error,
+            exact,
+            estimation
+        );
+    }
+
+    /// Deterministic values of the given distribution, spanning several orders of magnitude.
+    fn generate_values(distribution: &str, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| {
+                // 7919 is a prime, so the values are shuffled.
+                let u = ((i * 7919) % n) as f64 / n as f64;
+                match distribution {
+                    "uniform" => u * 1000.0,
+                    "exponential" => -(1.0 - u).ln() * 100.0,
+                    "mixed" => (u - 0.3) * 1e6,
+                    _ => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    /// Compares the estimation of the batch aggregator against the exact percentiles.
+    #[test]
+    fn test_error_bound() {
+        const ROW_COUNT: usize = 100_000;
+        const CHUNK_SIZE: usize = 10_000;
+
+        for relative_error in [0.01, 0.05] {
+            for distribution in ["uniform", "exponential", "mixed"] {
+                let values = generate_values(distribution, ROW_COUNT);
+                let mut sorted = values.clone();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+                for fraction in [0.0, 0.01, 0.25, 0.5, 0.9, 0.99, 1.0] {
+                    let mut agg =
+                        ApproxPercentile::new(DataType::Float64, 0, fraction, relative_error);
+                    for chunk in values.chunks(CHUNK_SIZE) {
+                        let array = F64Array::from_slice(
+                            &chunk.iter().map(|v| Some((*v).into())).collect::<Vec<_>>(),
+                        );
+                        let data_chunk = DataChunk::new(vec![array.into()], chunk.len());
+                        agg.update_multi(&data_chunk, 0, chunk.len()).unwrap();
+                    }
+                    assert_within(
+                        output_of(&mut agg).unwrap(),
+                        exact_percentile(&sorted, fraction),
+                        relative_error,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_null_and_empty() {
+        let mut agg = ApproxPercentile::new(DataType::Float64, 0, 0.5, 0.01);
+        let array = F64Array::from_slice(&[None, Some(4.0.into()), None]);
+        let data_chunk = DataChunk::new(vec![array.into()], 3);
+        agg.update_multi(&data_chunk, 0, 3).unwrap();
+        assert_within(output_of(&mut agg).unwrap(), 4.0, 0.01);
+
+        // The sketch is reset after output.
+        agg.update_multi(&data_chunk, 0, 1).unwrap();
+        assert_eq!(output_of(&mut agg), None);
+    }
+
+    #[test]
+    fn test_sketch_remove() {
+        let mut sketch = PercentileSketch::new(0.01);
+        for v in 1..=100 {
+            sketch.add(v as f64);
+            sketch.add(-(v as f64));
+        }
+        sketch.add(0.0);
+        for v in 51..=100 {
+            sketch.remove(v as f64);
+            sketch.remove(-(v as f64));
+        }
+
+        // The same as the sketch of the remaining values.
+        let mut expected = PercentileSketch::new(0.01);
+        for v in 1..=50 {
+            expected.add(v as f64);
+            expected.add(-(v as f64));
+        }
+        expected.add(0.0);
+        assert_eq!(sketch, expected);
+        assert_eq!(sketch.count(), 101);
+        assert_eq!(sketch.percentile(0.5), Some(0.0));
+        assert_within(sketch.percentile(1.0).unwrap(), 50.0, 0.01);
+        assert_within(sketch.percentile(0.0).unwrap(), -50.0, 0.01);
+
+        sketch.add(f64::NAN);
+        sketch.add(f64::INFINITY);
+        assert_eq!(sketch.count(), 102);
+        assert_eq!(sketch.percentile(1.0), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_sketch_size() {
+        let mut sketch = PercentileSketch::new(0.01);
+        for i in 0..1_000_000 {
+            // Values in [1, 1e6).
+            sketch.add(1.0 + i as f64);
+        }
+        // ln(1e6) / ln(1.01 / 0.99) is about 691.
+        assert!(sketch.num_buckets() <= 692, "{}", sketch.num_buckets());
+
+        let serialized = sketch.serialize();
+        assert_eq!(serialized.len(), 2 + 2 * sketch.num_buckets());
+        assert_eq!(PercentileSketch::deserialize(0.01, &serialized), sketch);
+    }
+}
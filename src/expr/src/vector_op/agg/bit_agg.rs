@@ -0,0 +1,257 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use risingwave_common::array::*;
+use risingwave_common::bail;
+use risingwave_common::types::*;
+
+use crate::expr::AggKind;
+use crate::vector_op::agg::aggregator::{Aggregator, BoxedAggState};
+use crate::Result;
+
+/// Integer types that bitwise aggregations can be applied on.
+pub trait BitAggItem:
+    PrimitiveArrayItemType
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Copy
+    + Send
+    + 'static
+{
+}
+
+impl BitAggItem for i16 {}
+impl BitAggItem for i32 {}
+impl BitAggItem for i64 {}
+
+/// State of `bit_and`, `bit_or` and `bit_xor`. Null inputs are skipped, and the result is null if
+/// there's no non-null input.
+#[derive(Clone)]
+pub struct BitAggState<T: BitAggItem> {
+    return_type: DataType,
+    input_col_idx: usize,
+    kind: AggKind,
+    /// All bits set for `bit_and`, and all bits unset for `bit_or` and `bit_xor`.
+    init: T,
+    result: T,
+    has_input: bool,
+}
+
+impl<T: BitAggItem> BitAggState<T> {
+    pub fn new(return_type: DataType, input_col_idx: usize, kind: AggKind) -> Self {
+        let init = match kind {
+            AggKind::BitAnd => !T::default(),
+            AggKind::BitOr | AggKind::BitXor => T::default(),
+            _ => unreachable!("not a bitwise aggregation: {}", kind),
+        };
+        Self {
+            return_type,
+            input_col_idx,
+            kind,
+            init,
+            result: init,
+            has_input: false,
+        }
+    }
+
+    fn accumulate(&mut self, value: Option<T>) {
+        if let Some(value) = value {
+            self.result = match self.kind {
+                AggKind::BitAnd => self.result & value,
+                AggKind::BitOr => self.result | value,
+                AggKind::BitXor => self.result ^ value,
+                _ => unreachable!(),
+            };
+            self.has_input = true;
+        }
+    }
+
+    fn input_array<'a>(&self, input: &'a DataChunk) -> Result<&'a PrimitiveArray<T>> {
+        match T::try_into_array_ref(input.column_at(self.input_col_idx).array_ref()) {
+            Some(array) => Ok(array),
+            None => bail!("Input fail to match {}.", self.return_type),
+        }
+    }
+}
+
+impl<T: BitAggItem> Aggregator for BitAggState<T> {
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn update_single(&mut self, input: &DataChunk, row_id: usize) -> Result<()> {
+        let value = self.input_array(input)?.value_at(row_id);
+        self.accumulate(value);
+        Ok(())
+    }
+
+    fn update_multi(
+        &mut self,
+        input: &DataChunk,
+        start_row_id: usize,
+        end_row_id: usize,
+    ) -> Result<()> {
+        let array = self.input_array(input)?;
+        for row_id in start_row_id..end_row_id {
+            self.accumulate(array.value_at(row_id));
+        }
+        Ok(())
+    }
+
+    fn output(&mut self, builder: &mut ArrayBuilderImpl) -> Result<()> {
+        let result = std::mem::replace(&mut self.result, self.init);
+        let has_input = std::mem::replace(&mut self.has_input, false);
+        builder.append_datum(&has_input.then(|| result.to_scalar_value()));
+        Ok(())
+    }
+}
+
+pub fn create_bit_agg_state(
+    input_type: DataType,
+    input_col_idx: usize,
+    agg_kind: AggKind,
+    return_type: DataType,
+    distinct: bool,
+) -> Result<BoxedAggState> {
+    // `bit_and` and `bit_or` are idempotent, so `DISTINCT` makes no difference.
+    if distinct && agg_kind == AggKind::BitXor {
+        bail!("unsupported aggregator: type={:?} distinct=true", agg_kind);
+    }
+    let state: BoxedAggState = match (&input_type, &return_type) {
+        (DataType::Int16, DataType::Int16) => Box::new(BitAggState::<i16>::new(
+            return_type,
+            input_col_idx,
+            agg_kind,
+        )),
+        (DataType::Int32, DataType::Int32) => Box::new(BitAggState::<i32>::new(
+            return_type,
+            input_col_idx,
+            agg_kind,
+        )),
+        (DataType::Int64, DataType::Int64) => Box::new(BitAggState::<i64>::new(
+            return_type,
+            input_col_idx,
+            agg_kind,
+        )),
+        _ => bail!(
+            "unsupported aggregator: type={:?} input={:?} output={:?}",
+            agg_kind,
+            input_type,
+            return_type
+        ),
+    };
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::{DataChunk, DataChunkTestExt};
+
+    use super::*;
+
+    fn eval_bit_agg(input_type: DataType, kind: AggKind, input: &DataChunk) -> Result<ArrayImpl> {
+        let mut agg_state = create_bit_agg_state(input_type.clone(), 0, kind, input_type, false)?;
+        agg_state.update_multi(input, 0, input.capacity())?;
+        let mut builder = agg_state.return_type().create_array_builder(1);
+        agg_state.output(&mut builder)?;
+        Ok(builder.finish())
+    }
+
+    #[test]
+    fn test_bit_and() {
+        let input = DataChunk::from_pretty(
+            "i
+             7
+             .
+             14
+             .
+             6",
+        );
+        let result = eval_bit_agg(DataType::Int32, AggKind::BitAnd, &input).unwrap();
+        assert_eq!(result.as_int32().value_at(0), Some(0b0110));
+
+        let input = DataChunk::from_pretty(
+            "I
+             -1
+             .
+             -2",
+        );
+        let result = eval_bit_agg(DataType::Int64, AggKind::BitAnd, &input).unwrap();
+        assert_eq!(result.as_int64().value_at(0), Some(-2));
+    }
+
+    #[test]
+    fn test_bit_or() {
+        let input = DataChunk::from_pretty(
+            "i
+             .
+             1
+             .
+             4
+             8",
+        );
+        let result = eval_bit_agg(DataType::Int32, AggKind::BitOr, &input).unwrap();
+        assert_eq!(result.as_int32().value_at(0), Some(0b1101));
+    }
+
+    #[test]
+    fn test_bit_xor() {
+        let input = DataChunk::from_pretty(
+            "i
+             5
+             .
+             3
+             .
+             1",
+        );
+        let result = eval_bit_agg(DataType::Int32, AggKind::BitXor, &input).unwrap();
+        assert_eq!(result.as_int32().value_at(0), Some(0b0111));
+    }
+
+    #[test]
+    fn test_bit_agg_all_null() {
+        let input = DataChunk::from_pretty(
+            "i
+             .
+             .",
+        );
+        for kind in [AggKind::BitAnd, AggKind::BitOr, AggKind::BitXor] {
+            let result = eval_bit_agg(DataType::Int32, kind, &input).unwrap();
+            assert_eq!(result.as_int32().value_at(0), None, "{}", kind);
+        }
+    }
+
+    #[test]
+    fn test_bit_agg_reset_after_output() {
+        let mut agg_state =
+            create_bit_agg_state(DataType::Int32, 0, AggKind::BitAnd, DataType::Int32, false)
+                .unwrap();
+        let mut builder = DataType::Int32.create_array_builder(2);
+        agg_state
+            .update_multi(&DataChunk::from_pretty("i\n 3\n ."), 0, 2)
+            .unwrap();
+        agg_state.output(&mut builder).unwrap();
+        agg_state
+            .update_multi(&DataChunk::from_pretty("i\n 4"), 0, 1)
+            .unwrap();
+        agg_state.output(&mut builder).unwrap();
+        let result = builder.finish();
+        assert_eq!(result.as_int32().value_at(0), Some(3));
+        assert_eq!(result.as_int32().value_at(1), Some(4));
+    }
+}
@@ -14,7 +14,9 @@
 
 mod aggregator;
 mod approx_count_distinct;
+mod approx_percentile;
 mod array_agg;
+mod bit_agg;
 mod count_star;
 mod filter;
 mod functions;
@@ -24,4 +26,5 @@ mod general_sorted_grouper;
 mod string_agg;
 
 pub use aggregator::{AggStateFactory, BoxedAggState};
+pub use approx_percentile::PercentileSketch;
 pub use general_sorted_grouper::{create_sorted_grouper, BoxedSortedGrouper, EqGroups};
@@ -17,6 +17,7 @@ use std::str::FromStr;
 
 use bytes::{Bytes, BytesMut};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use num_traits::ToPrimitive;
 use postgres_types::ToSql;
 use risingwave_common::array::{Array, ListRef, ListValue};
@@ -109,9 +110,13 @@ pub fn str_to_timestampz(elem: &str) -> Result<i64> {
 
 #[inline(always)]
 pub fn timestampz_to_utc_string(elem: i64) -> String {
-    // Just a meaningful representation as placeholder. The real implementation depends on TimeZone
-    // from session. See #3552.
-    let instant = Utc.timestamp_nanos(elem * 1000);
+    timestampz_to_string(elem, Tz::UTC)
+}
+
+/// Renders the instant `elem` as the local time in `time_zone` with its UTC offset.
+#[inline(always)]
+pub fn timestampz_to_string(elem: i64, time_zone: Tz) -> String {
+    let instant = Utc.timestamp_nanos(elem * 1000).with_timezone(&time_zone);
     // PostgreSQL uses a space rather than `T` to separate the date and time.
     // https://www.postgresql.org/docs/current/datatype-datetime.html#DATATYPE-DATETIME-OUTPUT
     instant.format("%Y-%m-%d %H:%M:%S%.f%:z").to_string()
@@ -712,4 +717,23 @@ mod tests {
         assert!(str_to_list("{{1, 2, 3}, {4, 5, 6}", &DataType::Int32).is_err());
         assert!(str_to_list("{{1, 2, 3}, 4, 5, 6}}", &DataType::Int32).is_err());
     }
+
+    #[test]
+    fn test_timestampz_to_string() {
+        let usecs = str_to_timestampz("2022-03-13 09:30:00Z").unwrap();
+        assert_eq!(timestampz_to_utc_string(usecs), "2022-03-13 09:30:00+00:00");
+        assert_eq!(
+            timestampz_to_string(usecs, Tz::Asia__Shanghai),
+            "2022-03-13 17:30:00+08:00"
+        );
+        // Daylight saving time starts in US/Pacific at 2022-03-13 10:00:00 UTC.
+        assert_eq!(
+            timestampz_to_string(usecs, Tz::US__Pacific),
+            "2022-03-13 01:30:00-08:00"
+        );
+        assert_eq!(
+            timestampz_to_string(usecs + 3_600_000_000, Tz::US__Pacific),
+            "2022-03-13 03:30:00-07:00"
+        );
+    }
 }
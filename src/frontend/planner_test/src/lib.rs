@@ -106,6 +106,9 @@ pub struct TestCase {
     /// Error of `.gen_stream_plan()`
     pub stream_error: Option<String>,
 
+    /// Notices sent to the user by planner
+    pub planner_notice: Option<String>,
+
     /// Support using file content or file location to create source.
     pub create_source: Option<CreateSource>,
 
@@ -166,6 +169,9 @@ pub struct TestCaseResult {
     /// Error of `.gen_stream_plan()`
     pub stream_error: Option<String>,
 
+    /// Notices sent to the user by planner
+    pub planner_notice: Option<String>,
+
     /// The result of an `EXPLAIN` statement.
     ///
     /// This field is used when `sql` is an `EXPLAIN` statement.
@@ -204,6 +210,7 @@ impl TestCaseResult {
             batch_error: self.batch_error,
             batch_local_error: self.batch_local_error,
             stream_error: self.stream_error,
+            planner_notice: self.planner_notice,
             binder_error: self.binder_error,
             create_source: original_test_case.create_source.clone(),
             with_config_map: original_test_case.with_config_map.clone(),
@@ -426,6 +433,11 @@ impl TestCase {
             }
         };
 
+        // Only generate planner_notice if it is specified in test case
+        if self.planner_notice.is_some() {
+            ret.planner_notice = Some(context.take_notices().join("\n"));
+        }
+
         if self.optimized_logical_plan.is_some() || self.optimizer_error.is_some() {
             let optimized_logical_plan = match logical_plan.gen_optimized_logical_plan() {
                 Ok(optimized_logical_plan) => optimized_logical_plan,
@@ -569,6 +581,12 @@ fn check_result(expected: &TestCase, actual: &TestCaseResult) -> Result<()> {
         &actual.batch_plan_proto,
     )?;
 
+    check_option_plan_eq(
+        "planner_notice",
+        &expected.planner_notice,
+        &actual.planner_notice,
+    )?;
+
     check_option_plan_eq(
         "explain_output",
         &expected.explain_output,
@@ -15,8 +15,8 @@
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_sqlparser::ast::{Expr, ObjectName};
 
-use super::{Binder, BoundBaseTable, BoundTableSource};
-use crate::expr::ExprImpl;
+use super::{rewrite_expr_in_place, Binder, BoundBaseTable, BoundTableSource};
+use crate::expr::{ExprImpl, ExprRewriter};
 
 #[derive(Debug)]
 pub struct BoundDelete {
@@ -29,6 +29,14 @@ pub struct BoundDelete {
     pub selection: Option<ExprImpl>,
 }
 
+impl BoundDelete {
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        if let Some(selection) = &mut self.selection {
+            rewrite_expr_in_place(rewriter, selection);
+        }
+    }
+}
+
 impl Binder {
     pub(super) fn bind_delete(
         &mut self,
@@ -12,24 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
 use std::iter::once;
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use itertools::Itertools;
 use risingwave_common::array::ListValue;
 use risingwave_common::catalog::PG_CATALOG_SCHEMA_NAME;
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_common::session_config::USER_NAME_WILD_CARD;
-use risingwave_common::types::{DataType, Scalar};
+use risingwave_common::types::{DataType, Scalar, ScalarImpl};
 use risingwave_expr::expr::AggKind;
-use risingwave_sqlparser::ast::{Function, FunctionArg, FunctionArgExpr, WindowSpec};
+use risingwave_pb::user::grant_privilege::{Action as ProstAction, Object as ProstObject};
+use risingwave_sqlparser::ast::{
+    Function, FunctionArg, FunctionArgExpr, WindowFrame, WindowFrameBound, WindowFrameUnits,
+    WindowSpec,
+};
 
 use crate::binder::bind_context::Clause;
 use crate::binder::{Binder, BoundQuery, BoundSetExpr};
+use crate::catalog::root_catalog::SchemaPath;
+use crate::catalog::CatalogError;
 use crate::expr::{
-    AggCall, Expr, ExprImpl, ExprType, FunctionCall, Literal, OrderBy, Subquery, SubqueryKind,
-    TableFunction, TableFunctionType, WindowFunction, WindowFunctionType,
+    AggCall, Expr, ExprImpl, ExprType, Frame, FrameBound, FunctionCall, Literal, OrderBy, Subquery,
+    SubqueryKind, TableFunction, TableFunctionType, WindowFunction, WindowFunctionType,
 };
+use crate::user::user_privilege::has_privilege;
 use crate::utils::Condition;
 
 impl Binder {
@@ -60,11 +69,7 @@ impl Binder {
         // agg calls
         if let Ok(kind) = function_name.parse() {
             if f.over.is_some() {
-                return Err(ErrorCode::NotImplemented(
-                    format!("aggregate function as over window function: {}", kind),
-                    4978.into(),
-                )
-                .into());
+                return self.bind_aggregate_window_function(f, kind);
             }
             return self.bind_agg(f, kind);
         }
@@ -86,7 +91,8 @@ impl Binder {
 
         // window function
         if let Some(window_spec) = f.over {
-            return self.bind_window_function(window_spec, function_name, inputs);
+            let function_type = WindowFunctionType::from_str(&function_name)?;
+            return self.bind_window_function(window_spec, function_type, inputs);
         }
 
         // table function
@@ -127,6 +133,13 @@ impl Binder {
             "abs" => ExprType::Abs,
             // temporal/chrono
             "to_timestamp" => ExprType::ToTimestamp,
+            "now" if inputs.is_empty() => {
+                if !self.in_streaming {
+                    return Ok(self.bind_now_literal());
+                }
+                // Rewritten into a dynamic filter against the `now` stream by the planner.
+                ExprType::Now
+            }
             // string
             "substr" => ExprType::Substr,
             "length" => ExprType::Length,
@@ -258,6 +271,7 @@ impl Binder {
                 };
             }
             "pg_table_is_visible" => return Ok(ExprImpl::literal_bool(true)),
+            "has_table_privilege" => return self.bind_has_table_privilege(inputs),
             // internal
             "rw_vnode" => ExprType::Vnode,
             _ => {
@@ -271,6 +285,16 @@ impl Binder {
         Ok(FunctionCall::new(function_type, inputs)?.into())
     }
 
+    /// `now()` of a batch query is the time the query is bound.
+    fn bind_now_literal(&mut self) -> ExprImpl {
+        self.inlined_now = true;
+        let micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock set earlier than the UNIX epoch")
+            .as_micros() as i64;
+        Literal::new(Some(ScalarImpl::Int64(micros)), DataType::Timestampz).into()
+    }
+
     pub(super) fn bind_agg(&mut self, mut f: Function, kind: AggKind) -> Result<ExprImpl> {
         self.ensure_aggregate_allowed()?;
         let inputs: Vec<ExprImpl> = f
@@ -279,6 +303,11 @@ impl Binder {
             .map(|arg| self.bind_function_arg(arg))
             .flatten_ok()
             .try_collect()?;
+        let (kind, inputs) = match kind {
+            AggKind::ApproxCountDistinct(_) => Self::bind_approx_count_distinct(inputs)?,
+            AggKind::ApproxPercentile(..) => Self::bind_approx_percentile(inputs)?,
+            kind => (kind, inputs),
+        };
         if f.distinct {
             match &kind {
                 AggKind::Count if inputs.is_empty() => {
@@ -288,7 +317,7 @@ impl Binder {
                     )
                     .into());
                 }
-                AggKind::ApproxCountDistinct => {
+                AggKind::ApproxCountDistinct(_) => {
                     // approx_count_distinct(distinct ..) is disallowed because this defeats
                     // its purpose of trading accuracy for
                     // speed.
@@ -299,6 +328,12 @@ impl Binder {
                     )
                     .into());
                 }
+                AggKind::ApproxPercentile(..) => {
+                    return Err(ErrorCode::InvalidInputSyntax(
+                        "approx_percentile(distinct) is disallowed".into(),
+                    )
+                    .into());
+                }
                 AggKind::Max | AggKind::Min => {
                     // distinct max or min returns the same result as non-distinct max or min.
                     f.distinct = false;
@@ -366,6 +401,84 @@ impl Binder {
         )?)))
     }
 
+    /// Evaluates the constant parameter of an approximate agg call, e.g. the relative error.
+    fn eval_approx_param(param: ExprImpl, name: &str) -> Result<f64> {
+        if !param.is_const() {
+            return Err(
+                ErrorCode::InvalidInputSyntax(format!("the {} must be a constant", name)).into(),
+            );
+        }
+        match param.cast_implicit(DataType::Float64)?.eval_row_const()? {
+            Some(ScalarImpl::Float64(param)) => Ok(param.0),
+            _ => {
+                Err(ErrorCode::InvalidInputSyntax(format!("the {} must not be null", name)).into())
+            }
+        }
+    }
+
+    /// Binds the optional relative error, the second argument of `approx_count_distinct`, into
+    /// the agg kind.
+    fn bind_approx_count_distinct(mut inputs: Vec<ExprImpl>) -> Result<(AggKind, Vec<ExprImpl>)> {
+        if inputs.len() != 2 {
+            return Ok((AggKind::approx_count_distinct(), inputs));
+        }
+        let relative_error = Self::eval_approx_param(
+            inputs.pop().unwrap(),
+            "relative error of approx_count_distinct",
+        )?;
+        if !(AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR..1.0).contains(&relative_error) {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "the relative error of approx_count_distinct must be in [{}, 1), but got {}",
+                AggKind::MIN_APPROX_COUNT_DISTINCT_ERROR,
+                relative_error
+            ))
+            .into());
+        }
+        Ok((AggKind::ApproxCountDistinct(relative_error.into()), inputs))
+    }
+
+    /// Binds `approx_percentile(value, fraction [, relative_error])`, where the constant fraction
+    /// and relative error go into the agg kind, and the value is cast to float64.
+    fn bind_approx_percentile(mut inputs: Vec<ExprImpl>) -> Result<(AggKind, Vec<ExprImpl>)> {
+        if !(2..=3).contains(&inputs.len()) {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "approx_percentile expects 2 or 3 arguments, but got {}",
+                inputs.len()
+            ))
+            .into());
+        }
+        let relative_error = if inputs.len() == 3 {
+            let relative_error = Self::eval_approx_param(
+                inputs.pop().unwrap(),
+                "relative error of approx_percentile",
+            )?;
+            if !(relative_error > 0.0 && relative_error < 1.0) {
+                return Err(ErrorCode::InvalidInputSyntax(format!(
+                    "the relative error of approx_percentile must be in (0, 1), but got {}",
+                    relative_error
+                ))
+                .into());
+            }
+            relative_error
+        } else {
+            AggKind::DEFAULT_APPROX_PERCENTILE_ERROR
+        };
+        let fraction =
+            Self::eval_approx_param(inputs.pop().unwrap(), "fraction of approx_percentile")?;
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "the fraction of approx_percentile must be in [0, 1], but got {}",
+                fraction
+            ))
+            .into());
+        }
+        let value = inputs.pop().unwrap().cast_implicit(DataType::Float64)?;
+        Ok((
+            AggKind::ApproxPercentile(fraction.into(), relative_error.into()),
+            vec![value],
+        ))
+    }
+
     pub(super) fn bind_window_function(
         &mut self,
         WindowSpec {
@@ -373,18 +486,11 @@ impl Binder {
             order_by,
             window_frame,
         }: WindowSpec,
-        function_name: String,
+        function_type: WindowFunctionType,
         inputs: Vec<ExprImpl>,
     ) -> Result<ExprImpl> {
         self.ensure_window_function_allowed()?;
-        if let Some(window_frame) = window_frame {
-            return Err(ErrorCode::NotImplemented(
-                format!("window frame: {}", window_frame),
-                None.into(),
-            )
-            .into());
-        }
-        let window_function_type = WindowFunctionType::from_str(&function_name)?;
+        let frame = window_frame.map(Self::bind_window_frame).transpose()?;
         let partition_by = partition_by
             .into_iter()
             .map(|arg| self.bind_expr(arg))
@@ -396,7 +502,85 @@ impl Binder {
                 .map(|order_by_expr| self.bind_order_by_expr(order_by_expr))
                 .collect::<Result<_>>()?,
         );
-        Ok(WindowFunction::new(window_function_type, partition_by, order_by, inputs)?.into())
+        Ok(WindowFunction::new(function_type, partition_by, order_by, inputs, frame)?.into())
+    }
+
+    /// Binds `sum`, `count`, `min` and `max` with `OVER`, which aggregate the frame of each row.
+    fn bind_aggregate_window_function(&mut self, f: Function, kind: AggKind) -> Result<ExprImpl> {
+        if !matches!(
+            kind,
+            AggKind::Sum | AggKind::Count | AggKind::Min | AggKind::Max
+        ) {
+            return Err(ErrorCode::NotImplemented(
+                format!("aggregate function as over window function: {}", kind),
+                4978.into(),
+            )
+            .into());
+        }
+        if f.distinct || !f.order_by.is_empty() || f.filter.is_some() {
+            return Err(ErrorCode::NotImplemented(
+                format!("DISTINCT, ORDER BY or FILTER in window function: {}", kind),
+                None.into(),
+            )
+            .into());
+        }
+        let inputs = f
+            .args
+            .into_iter()
+            .map(|arg| self.bind_function_arg(arg))
+            .flatten_ok()
+            .try_collect()?;
+        self.bind_window_function(f.over.unwrap(), WindowFunctionType::Aggregate(kind), inputs)
+    }
+
+    /// Only bounded frames in `ROWS` mode are supported, e.g. `ROWS BETWEEN 10 PRECEDING AND
+    /// CURRENT ROW`.
+    fn bind_window_frame(window_frame: WindowFrame) -> Result<Frame> {
+        if window_frame.units != WindowFrameUnits::Rows {
+            return Err(ErrorCode::NotImplemented(
+                format!(
+                    "window frame in {} mode: {}",
+                    window_frame.units, window_frame
+                ),
+                None.into(),
+            )
+            .into());
+        }
+        // The offsets of the bounds relative to the current row must fit in `i64`.
+        let check_offset = |n: u64| {
+            i64::try_from(n).map(|_| n).map_err(|_| {
+                ErrorCode::InvalidInputSyntax(format!(
+                    "window frame offset {} is out of range: {}",
+                    n, window_frame
+                ))
+            })
+        };
+        let bind_bound = |bound: WindowFrameBound| match bound {
+            WindowFrameBound::Preceding(Some(n)) => check_offset(n).map(FrameBound::Preceding),
+            WindowFrameBound::CurrentRow => Ok(FrameBound::CurrentRow),
+            WindowFrameBound::Following(Some(n)) => check_offset(n).map(FrameBound::Following),
+            WindowFrameBound::Preceding(None) | WindowFrameBound::Following(None) => {
+                Err(ErrorCode::NotImplemented(
+                    format!("unbounded window frame: {}", window_frame),
+                    None.into(),
+                ))
+            }
+        };
+        let start = bind_bound(window_frame.start_bound.clone())?;
+        let end = bind_bound(
+            window_frame
+                .end_bound
+                .clone()
+                .unwrap_or(WindowFrameBound::CurrentRow),
+        )?;
+        if start.to_offset() > end.to_offset() {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "window frame starts after it ends: {}",
+                window_frame
+            ))
+            .into());
+        }
+        Ok(Frame { start, end })
     }
 
     fn rewrite_concat_to_concat_ws(inputs: Vec<ExprImpl>) -> Result<Vec<ExprImpl>> {
@@ -413,6 +597,173 @@ impl Binder {
         }
     }
 
+    /// Binds `has_table_privilege([user,] table, privilege)` with the privileges known by the
+    /// frontend. Like PostgreSQL, `privilege` may list several privileges separated by commas, each
+    /// optionally followed by `WITH GRANT OPTION`, and the result is true if any of them is held.
+    ///
+    /// A constant `table` is bound to a constant. Otherwise, e.g. for the names read from
+    /// `pg_class`, it's bound to a `CASE` over the relations of the database.
+    fn bind_has_table_privilege(&self, mut inputs: Vec<ExprImpl>) -> Result<ExprImpl> {
+        let (user_name, table, privilege) = match inputs.len() {
+            2 => {
+                let privilege = inputs.pop().unwrap();
+                let table = inputs.pop().unwrap();
+                (Some(self.auth_context.user_name.clone()), table, privilege)
+            }
+            3 => {
+                let privilege = inputs.pop().unwrap();
+                let table = inputs.pop().unwrap();
+                let user_name = Self::bind_const_varchar_arg(inputs.pop().unwrap())?;
+                (user_name, table, privilege)
+            }
+            _ => {
+                return Err(ErrorCode::ExprError(
+                    "Too many/few arguments for has_table_privilege()".into(),
+                )
+                .into())
+            }
+        };
+        let privilege = Self::bind_const_varchar_arg(privilege)?;
+        let (Some(user_name), Some(privilege)) = (user_name, privilege) else {
+            return Ok(ExprImpl::literal_null(DataType::Boolean));
+        };
+        let actions = parse_table_privileges(&privilege)?;
+
+        if table.is_const() {
+            return match Self::bind_const_varchar_arg(table)? {
+                Some(table_name) => Ok(ExprImpl::literal_bool(self.has_table_privilege(
+                    &user_name,
+                    &table_name,
+                    &actions,
+                )?)),
+                None => Ok(ExprImpl::literal_null(DataType::Boolean)),
+            };
+        }
+
+        let table = table.cast_implicit(DataType::Varchar)?;
+        if self
+            .user_info_reader
+            .read_guard()
+            .get_user_by_name(&user_name)
+            .is_none()
+        {
+            return Err(ErrorCode::CatalogError(
+                format!("role \"{}\" does not exist", user_name).into(),
+            )
+            .into());
+        }
+        let mut case_inputs = vec![];
+        for table_name in self.relation_names()? {
+            // The unqualified names of the relations out of the search path don't resolve.
+            let Ok(has_privilege) = self.has_table_privilege(&user_name, &table_name, &actions) else {
+                continue;
+            };
+            case_inputs.push(
+                FunctionCall::new(
+                    ExprType::Equal,
+                    vec![table.clone(), ExprImpl::literal_varchar(table_name)],
+                )?
+                .into(),
+            );
+            case_inputs.push(ExprImpl::literal_bool(has_privilege));
+        }
+        if case_inputs.is_empty() {
+            return Ok(ExprImpl::literal_null(DataType::Boolean));
+        }
+        case_inputs.push(ExprImpl::literal_null(DataType::Boolean));
+        Ok(FunctionCall::new(ExprType::Case, case_inputs)?.into())
+    }
+
+    /// Evaluates a constant argument of a function to a string.
+    fn bind_const_varchar_arg(arg: ExprImpl) -> Result<Option<String>> {
+        if !arg.is_const() {
+            return Err(ErrorCode::NotImplemented(
+                "Only constant user and privilege arguments are supported in \
+                 `has_table_privilege`."
+                    .to_string(),
+                None.into(),
+            )
+            .into());
+        }
+        match arg.cast_implicit(DataType::Varchar)?.eval_row_const()? {
+            Some(ScalarImpl::Utf8(s)) => Ok(Some(s)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The names of the tables, materialized views and sources of the current database, both
+    /// qualified by their schemas and not.
+    fn relation_names(&self) -> Result<BTreeSet<String>> {
+        let mut names = BTreeSet::new();
+        for schema in self.catalog.iter_schemas(&self.db_name)? {
+            let relations = schema
+                .iter_table()
+                .chain(schema.iter_mv())
+                .map(|table| table.name().to_string())
+                .chain(schema.iter_source().map(|source| source.name.clone()));
+            for name in relations {
+                names.insert(format!("{}.{}", schema.name(), name));
+                names.insert(name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Whether `user_name` holds any of `actions` on the relation `table_name`. The privileges to
+    /// query a table are granted on the table, while the ones to modify it are granted on its
+    /// associated source.
+    fn has_table_privilege(
+        &self,
+        user_name: &str,
+        table_name: &str,
+        actions: &[(ProstAction, bool)],
+    ) -> Result<bool> {
+        let (schema_name, table_name) = match table_name.split_once('.') {
+            Some((schema_name, table_name)) => (Some(schema_name), table_name),
+            None => (None, table_name),
+        };
+        let schema_path = match schema_name {
+            Some(schema_name) => SchemaPath::Name(schema_name),
+            None => SchemaPath::Path(&self.search_path, &self.auth_context.user_name),
+        };
+        let (owner, select_object, modify_object) =
+            match self
+                .catalog
+                .get_table_by_name(&self.db_name, schema_path, table_name)
+            {
+                Ok((table, _)) => (
+                    table.owner,
+                    ProstObject::TableId(table.id.table_id),
+                    table
+                        .associated_source_id()
+                        .map(|source_id| ProstObject::SourceId(source_id.table_id)),
+                ),
+                Err(_) => {
+                    let (source, _) = self
+                        .catalog
+                        .get_source_by_name(&self.db_name, schema_path, table_name)
+                        .map_err(|_| CatalogError::NotFound("relation", table_name.to_string()))?;
+                    let object = ProstObject::SourceId(source.id);
+                    (source.owner, object.clone(), Some(object))
+                }
+            };
+
+        let user_reader = self.user_info_reader.read_guard();
+        let user = user_reader.get_user_by_name(user_name).ok_or_else(|| {
+            ErrorCode::CatalogError(format!("role \"{}\" does not exist", user_name).into())
+        })?;
+        let has_any = actions.iter().any(|&(action, with_grant_option)| {
+            let object = match action {
+                ProstAction::Select => Some(&select_object),
+                _ => modify_object.as_ref(),
+            };
+            object.map_or(false, |object| {
+                has_privilege(user, owner, object, action, with_grant_option)
+            })
+        });
+        Ok(has_any)
+    }
+
     /// Make sure inputs only have 2 value and rewrite the arguments.
     /// Nullif(expr1,expr2) -> Case(Equal(expr1 = expr2),null,expr1).
     fn rewrite_nullif_to_case_when(inputs: Vec<ExprImpl>) -> Result<Vec<ExprImpl>> {
@@ -515,3 +866,32 @@ impl Binder {
         }
     }
 }
+
+/// Parses the `privilege` argument of `has_table_privilege` into the actions and whether the grant
+/// option is required for each.
+fn parse_table_privileges(privilege: &str) -> Result<Vec<(ProstAction, bool)>> {
+    privilege
+        .split(',')
+        .map(|privilege| {
+            let privilege = privilege.trim().to_lowercase();
+            let (action, with_grant_option) = match privilege.strip_suffix("with grant option") {
+                Some(action) => (action.trim_end(), true),
+                None => (privilege.as_str(), false),
+            };
+            let action = match action {
+                "select" => ProstAction::Select,
+                "insert" => ProstAction::Insert,
+                "update" => ProstAction::Update,
+                "delete" => ProstAction::Delete,
+                _ => {
+                    return Err(ErrorCode::InvalidInputSyntax(format!(
+                        "unrecognized privilege type: \"{}\"",
+                        privilege
+                    ))
+                    .into())
+                }
+            };
+            Ok((action, with_grant_option))
+        })
+        .collect()
+}
@@ -18,8 +18,8 @@ use risingwave_common::types::DataType;
 use risingwave_sqlparser::ast::{Ident, ObjectName, Query, SetExpr};
 
 use super::{BoundQuery, BoundSetExpr};
-use crate::binder::{Binder, BoundTableSource};
-use crate::expr::{ExprImpl, InputRef};
+use crate::binder::{rewrite_expr_in_place, Binder, BoundTableSource};
+use crate::expr::{ExprImpl, ExprRewriter, InputRef};
 
 #[derive(Debug)]
 pub struct BoundInsert {
@@ -33,6 +33,15 @@ pub struct BoundInsert {
     pub cast_exprs: Vec<ExprImpl>,
 }
 
+impl BoundInsert {
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        self.source.rewrite_exprs(rewriter);
+        for expr in &mut self.cast_exprs {
+            rewrite_expr_in_place(rewriter, expr);
+        }
+    }
+}
+
 impl Binder {
     pub(super) fn bind_insert(
         &mut self,
@@ -49,7 +49,9 @@ pub use update::BoundUpdate;
 pub use values::BoundValues;
 
 use crate::catalog::catalog_service::CatalogReadGuard;
+use crate::expr::{ExprImpl, ExprRewriter, SessionTimezone};
 use crate::session::{AuthContext, SessionImpl};
+use crate::user::user_service::UserInfoReader;
 
 /// `Binder` binds the identifiers in AST to columns in relations
 pub struct Binder {
@@ -58,6 +60,8 @@ pub struct Binder {
     db_name: String,
     context: BindContext,
     auth_context: Arc<AuthContext>,
+    /// Used to check the privileges of the users in functions like `has_table_privilege`.
+    user_info_reader: UserInfoReader,
     /// A stack holding contexts of outer queries when binding a subquery.
     /// It also holds all of the lateral contexts for each respective
     /// subquery.
@@ -77,27 +81,65 @@ pub struct Binder {
     cte_to_relation: HashMap<String, (BoundQuery, TableAlias)>,
 
     search_path: SearchPath,
+
+    /// Inlines the session time zone into the bound expressions that depend on it.
+    session_timezone: SessionTimezone,
+
+    /// Whether the statement is bound for a streaming job. `now()` is inlined as a constant in
+    /// batch queries, but keeps advancing in streaming ones.
+    in_streaming: bool,
+
+    /// Whether `now()` has been inlined as a constant, so that the result of the batch query
+    /// depends on when it's bound.
+    inlined_now: bool,
 }
 
 impl Binder {
-    pub fn new(session: &SessionImpl) -> Binder {
+    fn new_inner(session: &SessionImpl, in_streaming: bool) -> Binder {
         Binder {
             catalog: session.env().catalog_reader().read_guard(),
             db_name: session.database().to_string(),
             context: BindContext::new(),
             auth_context: session.auth_context(),
+            user_info_reader: session.env().user_info_reader().clone(),
             upper_subquery_contexts: vec![],
             lateral_contexts: vec![],
             next_subquery_id: 0,
             next_values_id: 0,
             cte_to_relation: HashMap::new(),
             search_path: session.config().get_search_path(),
+            session_timezone: SessionTimezone::new(session.config().get_timezone()),
+            in_streaming,
+            inlined_now: false,
         }
     }
 
+    pub fn new(session: &SessionImpl) -> Binder {
+        Self::new_inner(session, false)
+    }
+
+    /// Create a binder for the query of a streaming job, e.g. a materialized view.
+    pub fn new_for_stream(session: &SessionImpl) -> Binder {
+        Self::new_inner(session, true)
+    }
+
     /// Bind a [`Statement`].
     pub fn bind(&mut self, stmt: Statement) -> Result<BoundStatement> {
-        self.bind_statement(stmt)
+        let mut bound = self.bind_statement(stmt)?;
+        // Inlined in one pass over the whole statement, so that the casts added after binding the
+        // expressions, e.g. the ones aligning the types of `VALUES`, are covered as well.
+        bound.rewrite_exprs(&mut self.session_timezone);
+        Ok(bound)
+    }
+
+    /// Whether `now()` has been inlined as a constant into the bound statements.
+    pub fn has_inlined_now(&self) -> bool {
+        self.inlined_now
+    }
+
+    /// Whether the session time zone has been inlined into the bound statements.
+    pub fn has_inlined_session_timezone(&self) -> bool {
+        self.session_timezone.used()
     }
 
     fn push_context(&mut self) {
@@ -163,6 +205,12 @@ impl Binder {
     }
 }
 
+/// Rewrites `expr` in place with `rewriter`.
+fn rewrite_expr_in_place(rewriter: &mut impl ExprRewriter, expr: &mut ExprImpl) {
+    let owned = std::mem::replace(expr, ExprImpl::literal_bool(true));
+    *expr = rewriter.rewrite_expr(owned);
+}
+
 #[cfg(test)]
 pub mod test_utils {
     use super::Binder;
@@ -19,8 +19,8 @@ use risingwave_common::error::{ErrorCode, Result};
 use risingwave_common::types::DataType;
 use risingwave_sqlparser::ast::{Cte, Expr, Fetch, OrderByExpr, Query, Value, With};
 
-use crate::binder::{Binder, BoundSetExpr};
-use crate::expr::{CorrelatedId, Depth, ExprImpl};
+use crate::binder::{rewrite_expr_in_place, Binder, BoundSetExpr};
+use crate::expr::{CorrelatedId, Depth, ExprImpl, ExprRewriter};
 use crate::optimizer::property::{Direction, FieldOrder};
 
 /// A validated sql query, including order and union.
@@ -93,6 +93,15 @@ impl BoundQuery {
         self.body
             .collect_correlated_indices_by_depth_and_assign_id(depth, correlated_id)
     }
+
+    /// Rewrites the expressions of the query and its relations. Whether the subqueries in the
+    /// expressions are rewritten is up to `rewriter`.
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        self.body.rewrite_exprs(rewriter);
+        for expr in &mut self.extra_order_exprs {
+            rewrite_expr_in_place(rewriter, expr);
+        }
+    }
 }
 
 impl Binder {
@@ -21,8 +21,8 @@ use risingwave_common::error::{internal_error, ErrorCode, Result, RwError};
 use risingwave_sqlparser::ast::{FunctionArg, Ident, ObjectName, TableAlias, TableFactor};
 
 use super::bind_context::ColumnBinding;
-use crate::binder::{Binder, BoundSetExpr};
-use crate::expr::{Expr, ExprImpl, TableFunction, TableFunctionType};
+use crate::binder::{rewrite_expr_in_place, Binder, BoundSetExpr};
+use crate::expr::{Expr, ExprImpl, ExprRewriter, TableFunction, TableFunctionType};
 
 mod join;
 mod subquery;
@@ -108,6 +108,29 @@ impl Relation {
             _ => vec![],
         }
     }
+
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        match self {
+            Relation::Subquery(subquery) => subquery.query.rewrite_exprs(rewriter),
+            Relation::Join(join) => {
+                rewrite_expr_in_place(rewriter, &mut join.cond);
+                join.left.rewrite_exprs(rewriter);
+                join.right.rewrite_exprs(rewriter);
+            }
+            Relation::WindowTableFunction(window) => {
+                window.input.rewrite_exprs(rewriter);
+                for arg in &mut window.args {
+                    rewrite_expr_in_place(rewriter, arg);
+                }
+            }
+            Relation::TableFunction(table_function) => {
+                for arg in &mut table_function.args {
+                    rewrite_expr_in_place(rewriter, arg);
+                }
+            }
+            Relation::Source(_) | Relation::BaseTable(_) | Relation::SystemTable(_) => {}
+        }
+    }
 }
 
 impl Binder {
@@ -202,6 +225,11 @@ impl Binder {
         Self::resolve_schema_qualified_name(db_name, name, "sink name")
     }
 
+    /// return the `subscription_name`
+    pub fn resolve_subscription_name(name: ObjectName) -> Result<String> {
+        Self::resolve_single_name(name.0, "subscription name")
+    }
+
     /// return the `user_name`
     pub fn resolve_user_name(name: ObjectName) -> Result<String> {
         Self::resolve_single_name(name.0, "user name")
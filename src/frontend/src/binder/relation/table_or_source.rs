@@ -15,7 +15,7 @@
 use std::ops::Deref;
 use std::sync::Arc;
 
-use risingwave_common::catalog::{ColumnDesc, PG_CATALOG_SCHEMA_NAME};
+use risingwave_common::catalog::{is_system_schema, ColumnDesc, PG_CATALOG_SCHEMA_NAME};
 use risingwave_common::error::{ErrorCode, Result, RwError};
 use risingwave_common::session_config::USER_NAME_WILD_CARD;
 use risingwave_sqlparser::ast::TableAlias;
@@ -114,24 +114,25 @@ impl Binder {
             match schema_name {
                 Some(schema_name) => {
                     let schema_path = SchemaPath::Name(schema_name);
-                    if schema_name == PG_CATALOG_SCHEMA_NAME {
+                    if is_system_schema(schema_name) {
                         if let Ok(sys_table_catalog) =
-                            catalog.get_sys_table_by_name(db_name, table_name)
+                            catalog.get_sys_table_by_name(db_name, schema_name, table_name)
                         {
                             resolve_sys_table_relation(sys_table_catalog)
                         } else {
                             return Err(ErrorCode::NotImplemented(
                                 format!(
-                                    r###"pg_catalog.{} is not supported, please use `SHOW` commands for now.
+                                    r###"{}.{} is not supported, please use `SHOW` commands for now.
 `SHOW TABLES`,
 `SHOW MATERIALIZED VIEWS`,
 `DESCRIBE <table>`,
 `SHOW COLUMNS FROM [table]`
 "###,
-                                    table_name
+                                    schema_name, table_name
                                 ),
                                 1695.into(),
-                            ).into());
+                            )
+                            .into());
                         }
                     } else if let Ok((table_catalog, schema_name)) =
                         catalog.get_table_by_name(db_name, schema_path, table_name)
@@ -154,7 +155,7 @@ impl Binder {
                     for path in self.search_path.path() {
                         if path == PG_CATALOG_SCHEMA_NAME {
                             if let Ok(sys_table_catalog) =
-                                catalog.get_sys_table_by_name(db_name, table_name)
+                                catalog.get_sys_table_by_name(db_name, path, table_name)
                             {
                                 return Ok(resolve_sys_table_relation(sys_table_catalog));
                             }
@@ -22,13 +22,14 @@ use risingwave_sqlparser::ast::{Distinct, Expr, Select, SelectItem};
 
 use super::bind_context::{Clause, ColumnBinding};
 use super::UNNAMED_COLUMN;
-use crate::binder::{Binder, Relation};
+use crate::binder::{rewrite_expr_in_place, Binder, Relation};
 use crate::catalog::check_valid_column_name;
 use crate::catalog::pg_catalog::pg_user::{
     PG_USER_ID_INDEX, PG_USER_NAME_INDEX, PG_USER_TABLE_NAME,
 };
 use crate::expr::{
-    CorrelatedId, CorrelatedInputRef, Depth, Expr as _, ExprImpl, ExprType, FunctionCall, InputRef,
+    CorrelatedId, CorrelatedInputRef, Depth, Expr as _, ExprImpl, ExprRewriter, ExprType,
+    FunctionCall, InputRef,
 };
 
 #[derive(Debug, Clone)]
@@ -95,6 +96,27 @@ impl BoundSelect {
 
         correlated_indices
     }
+
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        let distinct_on: &mut [ExprImpl] = match &mut self.distinct {
+            BoundDistinct::DistinctOn(exprs) => exprs.as_mut_slice(),
+            _ => &mut [],
+        };
+        for expr in self
+            .select_items
+            .iter_mut()
+            .chain(self.where_clause.iter_mut())
+            .chain(self.group_by.iter_mut())
+            .chain(self.having.iter_mut())
+            .chain(distinct_on)
+        {
+            rewrite_expr_in_place(rewriter, expr);
+        }
+
+        if let Some(relation) = self.from.as_mut() {
+            relation.rewrite_exprs(rewriter);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -17,7 +17,7 @@ use risingwave_common::error::{ErrorCode, Result};
 use risingwave_sqlparser::ast::SetExpr;
 
 use crate::binder::{Binder, BoundSelect, BoundValues};
-use crate::expr::{CorrelatedId, Depth};
+use crate::expr::{CorrelatedId, Depth, ExprRewriter};
 
 /// Part of a validated query, without order or limit clause. It may be composed of smaller
 /// `BoundSetExpr`s via set operators (e.g. union).
@@ -58,6 +58,13 @@ impl BoundSetExpr {
             }
         }
     }
+
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        match self {
+            BoundSetExpr::Select(s) => s.rewrite_exprs(rewriter),
+            BoundSetExpr::Values(v) => v.rewrite_exprs(rewriter),
+        }
+    }
 }
 
 impl Binder {
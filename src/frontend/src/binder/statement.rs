@@ -18,6 +18,7 @@ use risingwave_sqlparser::ast::Statement;
 use super::delete::BoundDelete;
 use super::update::BoundUpdate;
 use crate::binder::{Binder, BoundInsert, BoundQuery};
+use crate::expr::ExprRewriter;
 
 #[derive(Debug)]
 pub enum BoundStatement {
@@ -27,6 +28,17 @@ pub enum BoundStatement {
     Query(Box<BoundQuery>),
 }
 
+impl BoundStatement {
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        match self {
+            BoundStatement::Insert(insert) => insert.rewrite_exprs(rewriter),
+            BoundStatement::Delete(delete) => delete.rewrite_exprs(rewriter),
+            BoundStatement::Update(update) => update.rewrite_exprs(rewriter),
+            BoundStatement::Query(query) => query.rewrite_exprs(rewriter),
+        }
+    }
+}
+
 impl Binder {
     pub(super) fn bind_statement(&mut self, stmt: Statement) -> Result<BoundStatement> {
         match stmt {
@@ -20,8 +20,8 @@ use risingwave_common::ensure;
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_sqlparser::ast::{Assignment, Expr, TableFactor, TableWithJoins};
 
-use super::{Binder, BoundTableSource, Relation};
-use crate::expr::{Expr as _, ExprImpl};
+use super::{rewrite_expr_in_place, Binder, BoundTableSource, Relation};
+use crate::expr::{Expr as _, ExprImpl, ExprRewriter};
 
 #[derive(Debug)]
 pub struct BoundUpdate {
@@ -38,6 +38,15 @@ pub struct BoundUpdate {
     pub exprs: Vec<ExprImpl>,
 }
 
+impl BoundUpdate {
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        self.table.rewrite_exprs(rewriter);
+        for expr in self.selection.iter_mut().chain(self.exprs.iter_mut()) {
+            rewrite_expr_in_place(rewriter, expr);
+        }
+    }
+}
+
 impl Binder {
     pub(super) fn bind_update(
         &mut self,
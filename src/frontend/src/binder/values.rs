@@ -19,8 +19,8 @@ use risingwave_common::types::DataType;
 use risingwave_sqlparser::ast::Values;
 
 use super::bind_context::Clause;
-use crate::binder::Binder;
-use crate::expr::{align_types, CorrelatedId, Depth, ExprImpl};
+use crate::binder::{rewrite_expr_in_place, Binder};
+use crate::expr::{align_types, CorrelatedId, Depth, ExprImpl, ExprRewriter};
 
 #[derive(Debug, Clone)]
 pub struct BoundValues {
@@ -58,6 +58,12 @@ impl BoundValues {
             })
             .collect()
     }
+
+    pub fn rewrite_exprs(&mut self, rewriter: &mut impl ExprRewriter) {
+        for expr in self.exprs_mut() {
+            rewrite_expr_in_place(rewriter, expr);
+        }
+    }
 }
 
 fn values_column_name(values_id: usize, col_id: usize) -> String {
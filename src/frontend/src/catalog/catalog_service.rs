@@ -21,11 +21,15 @@ use risingwave_common::error::ErrorCode::InternalError;
 use risingwave_common::error::{Result, RwError};
 use risingwave_pb::catalog::{
     Database as ProstDatabase, Index as ProstIndex, Schema as ProstSchema, Sink as ProstSink,
-    Source as ProstSource, Table as ProstTable,
+    Source as ProstSource, SubscriptionProgress as ProstSubscriptionProgress, Table as ProstTable,
+    TableStatistics as ProstTableStatistics,
 };
+use risingwave_pb::ddl_service::reset_source_offset_request::Position;
+use risingwave_pb::meta::TableFragments as ProstTableFragments;
 use risingwave_pb::stream_plan::StreamFragmentGraph;
 use risingwave_rpc_client::MetaClient;
 use tokio::sync::watch::Receiver;
+use uuid::Uuid;
 
 use super::root_catalog::Catalog;
 use super::DatabaseId;
@@ -60,11 +64,13 @@ pub trait CatalogWriter: Send + Sync {
         owner: UserId,
     ) -> Result<()>;
 
+    /// Returns the fragments of the created materialized view, with ids and actors assigned by
+    /// meta.
     async fn create_materialized_view(
         &self,
         table: ProstTable,
         graph: StreamFragmentGraph,
-    ) -> Result<()>;
+    ) -> Result<ProstTableFragments>;
 
     async fn create_materialized_source(
         &self,
@@ -82,6 +88,16 @@ pub trait CatalogWriter: Send + Sync {
 
     async fn create_source(&self, source: ProstSource) -> Result<()>;
 
+    /// Replaces the catalog of an existing source, e.g. with a newly added column.
+    async fn alter_source(&self, source: ProstSource) -> Result<()>;
+
+    async fn reset_source_offset(
+        &self,
+        source_id: u32,
+        position: Position,
+        timestamp_millis: i64,
+    ) -> Result<()>;
+
     async fn create_sink(&self, sink: ProstSink, graph: StreamFragmentGraph) -> Result<()>;
 
     async fn drop_materialized_source(&self, source_id: u32, table_id: TableId) -> Result<()>;
@@ -97,6 +113,28 @@ pub trait CatalogWriter: Send + Sync {
     async fn drop_schema(&self, schema_id: u32) -> Result<()>;
 
     async fn drop_index(&self, index_id: IndexId) -> Result<()>;
+
+    /// Persists the statistics of a table collected by `ANALYZE`.
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: ProstTableStatistics,
+    ) -> Result<()>;
+
+    /// Advances the progress of a subscription, if it's still `expected_progress`. The catalog
+    /// version is not bumped, so the progress in the local catalog is not updated.
+    async fn update_subscription_progress(
+        &self,
+        table_id: TableId,
+        expected_progress: ProstSubscriptionProgress,
+        progress: ProstSubscriptionProgress,
+    ) -> Result<()>;
+
+    /// Gets the latest progress of a subscription from the meta service.
+    async fn get_subscription_progress(
+        &self,
+        table_id: TableId,
+    ) -> Result<ProstSubscriptionProgress>;
 }
 
 #[derive(Clone)]
@@ -142,12 +180,16 @@ impl CatalogWriter for CatalogWriterImpl {
         &self,
         table: ProstTable,
         graph: StreamFragmentGraph,
-    ) -> Result<()> {
-        let (_, version) = self
+    ) -> Result<ProstTableFragments> {
+        // Generated for each statement, so that a retried request of it doesn't create another
+        // materialized view.
+        let idempotency_key = Uuid::new_v4().to_string();
+        let (_, version, table_fragments) = self
             .meta_client
-            .create_materialized_view(table, graph)
+            .create_materialized_view(table, graph, idempotency_key)
             .await?;
-        self.wait_version(version).await
+        self.wait_version(version).await?;
+        Ok(table_fragments)
     }
 
     async fn create_index(
@@ -178,6 +220,23 @@ impl CatalogWriter for CatalogWriterImpl {
         self.wait_version(version).await
     }
 
+    async fn alter_source(&self, source: ProstSource) -> Result<()> {
+        let version = self.meta_client.alter_source(source).await?;
+        self.wait_version(version).await
+    }
+
+    async fn reset_source_offset(
+        &self,
+        source_id: u32,
+        position: Position,
+        timestamp_millis: i64,
+    ) -> Result<()> {
+        self.meta_client
+            .reset_source_offset(source_id, position, timestamp_millis)
+            .await?;
+        Ok(())
+    }
+
     async fn create_sink(&self, sink: ProstSink, graph: StreamFragmentGraph) -> Result<()> {
         let (_id, version) = self.meta_client.create_sink(sink, graph).await?;
         self.wait_version(version).await
@@ -211,6 +270,38 @@ impl CatalogWriter for CatalogWriterImpl {
         self.wait_version(version).await
     }
 
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: ProstTableStatistics,
+    ) -> Result<()> {
+        let version = self
+            .meta_client
+            .update_table_statistics(table_id, statistics)
+            .await?;
+        self.wait_version(version).await
+    }
+
+    async fn update_subscription_progress(
+        &self,
+        table_id: TableId,
+        expected_progress: ProstSubscriptionProgress,
+        progress: ProstSubscriptionProgress,
+    ) -> Result<()> {
+        self.meta_client
+            .update_subscription_progress(table_id, expected_progress, progress)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_subscription_progress(
+        &self,
+        table_id: TableId,
+    ) -> Result<ProstSubscriptionProgress> {
+        let progress = self.meta_client.get_subscription_progress(table_id).await?;
+        Ok(progress)
+    }
+
     async fn drop_schema(&self, schema_id: u32) -> Result<()> {
         let version = self.meta_client.drop_schema(schema_id).await?;
         self.wait_version(version).await
@@ -15,7 +15,7 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use risingwave_common::catalog::PG_CATALOG_SCHEMA_NAME;
+use risingwave_common::catalog::is_system_schema;
 use risingwave_pb::catalog::{Database as ProstDatabase, Schema as ProstSchema};
 
 use crate::catalog::schema_catalog::SchemaCatalog;
@@ -83,7 +83,9 @@ impl DatabaseCatalog {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.schema_by_name.len() == 1 && self.schema_by_name.contains_key(PG_CATALOG_SCHEMA_NAME)
+        self.schema_by_name
+            .keys()
+            .all(|schema_name| is_system_schema(schema_name))
     }
 
     pub fn id(&self) -> DatabaseId {
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_common::catalog::{ColumnDesc, PG_CATALOG_SCHEMA_NAME};
+use risingwave_common::catalog::{is_system_schema, ColumnDesc};
 use risingwave_common::error::{ErrorCode, Result, RwError};
 use risingwave_common::types::DataType;
 use thiserror::Error;
@@ -23,6 +23,7 @@ pub(crate) mod database_catalog;
 pub(crate) mod index_catalog;
 pub(crate) mod pg_catalog;
 pub(crate) mod root_catalog;
+pub(crate) mod rw_catalog;
 pub(crate) mod schema_catalog;
 pub(crate) mod sink_catalog;
 pub(crate) mod source_catalog;
@@ -56,7 +57,7 @@ pub fn check_valid_column_name(column_name: &str) -> Result<()> {
 
 /// Check if modifications happen to system catalog.
 pub fn check_schema_writable(schema: &str) -> Result<()> {
-    if schema == PG_CATALOG_SCHEMA_NAME {
+    if is_system_schema(schema) {
         Err(ErrorCode::ProtocolError(format!(
             "permission denied to write on \"{}\", System catalog modifications are currently disallowed.",
             schema
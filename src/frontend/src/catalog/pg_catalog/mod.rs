@@ -29,6 +29,7 @@ use risingwave_common::array::Row;
 use risingwave_common::catalog::{ColumnDesc, SysCatalogReader, TableId, DEFAULT_SUPER_USER_ID};
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_pb::meta::get_backfill_progress_response::backfill_progress::Phase;
 use risingwave_pb::user::grant_privilege::{Action, Object};
 use risingwave_pb::user::UserInfo;
 use serde_json::json;
@@ -42,8 +43,12 @@ use crate::catalog::pg_catalog::pg_matviews_info::*;
 use crate::catalog::pg_catalog::pg_namespace::*;
 use crate::catalog::pg_catalog::pg_type::*;
 use crate::catalog::pg_catalog::pg_user::*;
+use crate::catalog::rw_catalog::rw_backfill_progress::RW_BACKFILL_PROGRESS_TABLE_NAME;
+use crate::catalog::rw_catalog::rw_meta_connection_status::RW_META_CONNECTION_STATUS_TABLE_NAME;
+use crate::catalog::rw_catalog::rw_table_write_stats::RW_TABLE_WRITE_STATS_TABLE_NAME;
 use crate::catalog::system_catalog::SystemCatalog;
 use crate::meta_client::FrontendMetaClient;
+use crate::observer::meta_connection_status::MetaConnectionStatusRef;
 use crate::scheduler::worker_node_manager::WorkerNodeManagerRef;
 use crate::session::AuthContext;
 use crate::user::user_privilege::available_prost_privilege;
@@ -60,6 +65,8 @@ pub struct SysCatalogReaderImpl {
     worker_node_manager: WorkerNodeManagerRef,
     // Read from meta.
     meta_client: Arc<dyn FrontendMetaClient>,
+    // Read the connection status to meta.
+    meta_connection_status: MetaConnectionStatusRef,
     auth_context: Arc<AuthContext>,
 }
 
@@ -69,6 +76,7 @@ impl SysCatalogReaderImpl {
         user_info_reader: UserInfoReader,
         worker_node_manager: WorkerNodeManagerRef,
         meta_client: Arc<dyn FrontendMetaClient>,
+        meta_connection_status: MetaConnectionStatusRef,
         auth_context: Arc<AuthContext>,
     ) -> Self {
         Self {
@@ -76,6 +84,7 @@ impl SysCatalogReaderImpl {
             user_info_reader,
             worker_node_manager,
             meta_client,
+            meta_connection_status,
             auth_context,
         }
     }
@@ -92,6 +101,9 @@ impl SysCatalogReader for SysCatalogReaderImpl {
             PG_USER_TABLE_NAME => self.read_user_info(),
             PG_CLASS_TABLE_NAME => self.read_class_info(),
             PG_INDEX_TABLE_NAME => self.read_index_info(),
+            RW_TABLE_WRITE_STATS_TABLE_NAME => self.read_table_write_stats().await,
+            RW_META_CONNECTION_STATUS_TABLE_NAME => Ok(self.read_meta_connection_status()),
+            RW_BACKFILL_PROGRESS_TABLE_NAME => self.read_backfill_progress().await,
             _ => {
                 Err(ErrorCode::ItemNotFound(format!("Invalid system table: {}", table_name)).into())
             }
@@ -348,10 +360,60 @@ impl SysCatalogReaderImpl {
 
         Ok(rows)
     }
+
+    async fn read_table_write_stats(&self) -> Result<Vec<Row>> {
+        let table_write_stats = self.meta_client.get_table_write_stats().await?;
+        Ok(table_write_stats
+            .into_iter()
+            .map(|table_write_stats| {
+                let stats = table_write_stats.stats.unwrap_or_default();
+                Row::new(vec![
+                    Some(ScalarImpl::Int32(table_write_stats.table_id as i32)),
+                    Some(ScalarImpl::Int32(table_write_stats.owner_table_id as i32)),
+                    Some(ScalarImpl::Int64(stats.total_bytes as i64)),
+                    Some(ScalarImpl::Int64(stats.total_key_count as i64)),
+                ])
+            })
+            .collect_vec())
+    }
+
+    async fn read_backfill_progress(&self) -> Result<Vec<Row>> {
+        let backfill_progress = self.meta_client.get_backfill_progress().await?;
+        Ok(backfill_progress
+            .into_iter()
+            .map(|progress| {
+                let phase = match progress.phase() {
+                    Phase::Pending => "PENDING",
+                    Phase::Backfilling => "BACKFILLING",
+                    Phase::Done => "DONE",
+                    Phase::ResettingOffset => "RESETTING_OFFSET",
+                };
+                Row::new(vec![
+                    Some(ScalarImpl::Int32(progress.table_id as i32)),
+                    Some(ScalarImpl::Int32(progress.upstream_table_id as i32)),
+                    Some(ScalarImpl::Utf8(phase.into())),
+                    Some(ScalarImpl::Int32(progress.done_actor_count as i32)),
+                    Some(ScalarImpl::Int32(progress.total_actor_count as i32)),
+                ])
+            })
+            .collect_vec())
+    }
+
+    fn read_meta_connection_status(&self) -> Vec<Row> {
+        let status = &self.meta_connection_status;
+        vec![Row::new(vec![
+            Some(ScalarImpl::Bool(status.is_connected())),
+            Some(ScalarImpl::Bool(status.read_only_standby())),
+            Some(ScalarImpl::Int64(
+                self.catalog_reader.read_guard().version() as i64,
+            )),
+            Some(ScalarImpl::Int64(status.staleness().as_millis() as i64)),
+        ])]
+    }
 }
 
 // TODO: support struct column and type name when necessary.
-type PgCatalogColumnsDef<'a> = (DataType, &'a str);
+pub(crate) type PgCatalogColumnsDef<'a> = (DataType, &'a str);
 
 /// `def_sys_catalog` defines a table with given id, name and columns.
 macro_rules! def_sys_catalog {
@@ -379,6 +441,8 @@ macro_rules! def_sys_catalog {
     };
 }
 
+pub(crate) use def_sys_catalog;
+
 /// `PG_CATALOG_MAP` includes all system catalogs. If you added a new system catalog, be
 /// sure to add a corresponding entry here.
 pub(crate) static PG_CATALOG_MAP: LazyLock<HashMap<String, SystemCatalog>> = LazyLock::new(|| {
@@ -17,7 +17,9 @@ use std::sync::Arc;
 
 use itertools::Itertools;
 use risingwave_common::bail;
-use risingwave_common::catalog::{CatalogVersion, IndexId, TableId, PG_CATALOG_SCHEMA_NAME};
+use risingwave_common::catalog::{
+    CatalogVersion, IndexId, TableId, PG_CATALOG_SCHEMA_NAME, RW_CATALOG_SCHEMA_NAME,
+};
 use risingwave_common::error::Result;
 use risingwave_common::session_config::{SearchPath, USER_NAME_WILD_CARD};
 use risingwave_pb::catalog::{
@@ -32,7 +34,7 @@ use crate::catalog::schema_catalog::SchemaCatalog;
 use crate::catalog::sink_catalog::SinkCatalog;
 use crate::catalog::system_catalog::SystemCatalog;
 use crate::catalog::table_catalog::TableCatalog;
-use crate::catalog::{pg_catalog, DatabaseId, IndexCatalog, SchemaId};
+use crate::catalog::{pg_catalog, rw_catalog, DatabaseId, IndexCatalog, SchemaId};
 
 #[derive(Copy, Clone)]
 pub enum SchemaPath<'a> {
@@ -98,17 +100,18 @@ impl Catalog {
             .unwrap()
             .create_schema(proto.clone());
 
-        if proto.name == PG_CATALOG_SCHEMA_NAME {
-            pg_catalog::get_all_pg_catalogs()
-                .into_iter()
-                .for_each(|sys_table| {
-                    self.get_database_mut(proto.database_id)
-                        .unwrap()
-                        .get_schema_mut(proto.id)
-                        .unwrap()
-                        .create_sys_table(sys_table);
-                });
-        }
+        let sys_tables = match proto.name.as_str() {
+            PG_CATALOG_SCHEMA_NAME => pg_catalog::get_all_pg_catalogs(),
+            RW_CATALOG_SCHEMA_NAME => rw_catalog::get_all_rw_catalogs(),
+            _ => vec![],
+        };
+        sys_tables.into_iter().for_each(|sys_table| {
+            self.get_database_mut(proto.database_id)
+                .unwrap()
+                .get_schema_mut(proto.id)
+                .unwrap()
+                .create_sys_table(sys_table);
+        });
     }
 
     pub fn create_table(&mut self, proto: &ProstTable) {
@@ -171,6 +174,14 @@ impl Catalog {
             .update_table(proto);
     }
 
+    pub fn update_source(&mut self, proto: &ProstSource) {
+        self.get_database_mut(proto.database_id)
+            .unwrap()
+            .get_schema_mut(proto.schema_id)
+            .unwrap()
+            .update_source(proto);
+    }
+
     pub fn drop_source(&mut self, db_id: DatabaseId, schema_id: SchemaId, source_id: SourceId) {
         self.get_database_mut(db_id)
             .unwrap()
@@ -324,9 +335,13 @@ impl Catalog {
         );
     }
 
-    pub fn get_sys_table_by_name(&self, db_name: &str, table_name: &str) -> Result<&SystemCatalog> {
-        self.get_schema_by_name(db_name, PG_CATALOG_SCHEMA_NAME)
-            .unwrap()
+    pub fn get_sys_table_by_name(
+        &self,
+        db_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<&SystemCatalog> {
+        self.get_schema_by_name(db_name, schema_name)?
             .get_system_table_by_name(table_name)
             .ok_or_else(|| CatalogError::NotFound("table", table_name.to_string()).into())
     }
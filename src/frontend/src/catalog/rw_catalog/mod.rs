@@ -0,0 +1,43 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod rw_backfill_progress;
+pub mod rw_meta_connection_status;
+pub mod rw_table_write_stats;
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use risingwave_common::catalog::{ColumnDesc, TableId, DEFAULT_SUPER_USER_ID};
+
+use crate::catalog::column_catalog::ColumnCatalog;
+use crate::catalog::pg_catalog::def_sys_catalog;
+use crate::catalog::rw_catalog::rw_backfill_progress::*;
+use crate::catalog::rw_catalog::rw_meta_connection_status::*;
+use crate::catalog::rw_catalog::rw_table_write_stats::*;
+use crate::catalog::system_catalog::SystemCatalog;
+
+/// `RW_CATALOG_MAP` includes all RisingWave specific system catalogs. If you added a new system
+/// catalog, be sure to add a corresponding entry here.
+pub(crate) static RW_CATALOG_MAP: LazyLock<HashMap<String, SystemCatalog>> = LazyLock::new(|| {
+    maplit::hashmap! {
+        RW_TABLE_WRITE_STATS_TABLE_NAME.to_string() => def_sys_catalog!(8, RW_TABLE_WRITE_STATS_TABLE_NAME, RW_TABLE_WRITE_STATS_COLUMNS),
+        RW_META_CONNECTION_STATUS_TABLE_NAME.to_string() => def_sys_catalog!(9, RW_META_CONNECTION_STATUS_TABLE_NAME, RW_META_CONNECTION_STATUS_COLUMNS),
+        RW_BACKFILL_PROGRESS_TABLE_NAME.to_string() => def_sys_catalog!(10, RW_BACKFILL_PROGRESS_TABLE_NAME, RW_BACKFILL_PROGRESS_COLUMNS),
+    }
+});
+
+pub fn get_all_rw_catalogs() -> Vec<SystemCatalog> {
+    RW_CATALOG_MAP.values().cloned().collect()
+}
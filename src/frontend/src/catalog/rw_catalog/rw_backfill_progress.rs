@@ -0,0 +1,31 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::types::DataType;
+
+use crate::catalog::pg_catalog::PgCatalogColumnsDef;
+
+/// The catalog `rw_backfill_progress` contains the backfill progress of each creating materialized
+/// view from each of its upstream tables. `phase` is one of `PENDING`, `BACKFILLING` and `DONE`,
+/// where an upstream table stays `PENDING` until the backfill from the previous ones is done in
+/// sequential backfill. A source whose offsets are being reset by `ALTER SOURCE ... RESET OFFSET`
+/// is listed as its own upstream table with the phase `RESETTING_OFFSET`.
+pub const RW_BACKFILL_PROGRESS_TABLE_NAME: &str = "rw_backfill_progress";
+pub const RW_BACKFILL_PROGRESS_COLUMNS: &[PgCatalogColumnsDef<'_>] = &[
+    (DataType::Int32, "table_id"),
+    (DataType::Int32, "upstream_table_id"),
+    (DataType::Varchar, "phase"),
+    (DataType::Int32, "done_actor_count"),
+    (DataType::Int32, "total_actor_count"),
+];
@@ -0,0 +1,28 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::types::DataType;
+
+use crate::catalog::pg_catalog::PgCatalogColumnsDef;
+
+/// The catalog `rw_meta_connection_status` contains a single row describing whether the frontend
+/// is connected to the meta service. `staleness_ms` is how long the cached catalog has not been
+/// updated since the connection was lost, and is zero if connected.
+pub const RW_META_CONNECTION_STATUS_TABLE_NAME: &str = "rw_meta_connection_status";
+pub const RW_META_CONNECTION_STATUS_COLUMNS: &[PgCatalogColumnsDef<'_>] = &[
+    (DataType::Boolean, "connected"),
+    (DataType::Boolean, "read_only_standby"),
+    (DataType::Int64, "catalog_version"),
+    (DataType::Int64, "staleness_ms"),
+];
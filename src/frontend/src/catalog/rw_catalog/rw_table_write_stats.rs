@@ -0,0 +1,28 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::types::DataType;
+
+use crate::catalog::pg_catalog::PgCatalogColumnsDef;
+
+/// The catalog `rw_table_write_stats` contains the bytes and keys written to each state table
+/// since the meta node starts. `owner_table_id` is the id of the materialized view, table or index
+/// that the state table belongs to.
+pub const RW_TABLE_WRITE_STATS_TABLE_NAME: &str = "rw_table_write_stats";
+pub const RW_TABLE_WRITE_STATS_COLUMNS: &[PgCatalogColumnsDef<'_>] = &[
+    (DataType::Int32, "table_id"),
+    (DataType::Int32, "owner_table_id"),
+    (DataType::Int64, "total_bytes"),
+    (DataType::Int64, "total_key_count"),
+];
@@ -16,7 +16,7 @@ use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use risingwave_common::catalog::{valid_table_name, IndexId, TableId, PG_CATALOG_SCHEMA_NAME};
+use risingwave_common::catalog::{is_system_schema, valid_table_name, IndexId, TableId};
 use risingwave_pb::catalog::{
     Index as ProstIndex, Schema as ProstSchema, Sink as ProstSink, Source as ProstSource,
     Table as ProstTable,
@@ -46,7 +46,7 @@ pub struct SchemaCatalog {
     index_by_id: HashMap<IndexId, Arc<IndexCatalog>>,
     indexes_by_table_id: HashMap<TableId, Vec<Arc<IndexCatalog>>>,
 
-    // This field only available when schema is "pg_catalog". Meanwhile, others will be empty.
+    // This field only available when schema is a system schema. Meanwhile, others will be empty.
     system_table_by_name: HashMap<String, SystemCatalog>,
     owner: u32,
 }
@@ -65,7 +65,7 @@ impl SchemaCatalog {
     }
 
     pub fn create_sys_table(&mut self, sys_table: SystemCatalog) {
-        assert_eq!(self.name, PG_CATALOG_SCHEMA_NAME);
+        assert!(is_system_schema(&self.name));
         self.system_table_by_name
             .try_insert(sys_table.name.clone(), sys_table)
             .unwrap();
@@ -140,6 +140,15 @@ impl SchemaCatalog {
         self.source_by_id.try_insert(id, source_ref).unwrap();
     }
 
+    pub fn update_source(&mut self, prost: &ProstSource) {
+        let name = prost.name.clone();
+        let id = prost.id;
+        let source_ref = Arc::new(SourceCatalog::from(prost));
+
+        self.source_by_name.insert(name, source_ref.clone());
+        self.source_by_id.insert(id, source_ref);
+    }
+
     pub fn drop_source(&mut self, id: SourceId) {
         let source_ref = self.source_by_id.remove(&id).unwrap();
         self.source_by_name.remove(&source_ref.name).unwrap();
@@ -179,11 +188,22 @@ impl SchemaCatalog {
         self.table_by_name
             .iter()
             .filter(|(_, v)| {
-                v.associated_source_id.is_none() && valid_table_name(&v.name) && !v.is_index
+                v.associated_source_id.is_none()
+                    && valid_table_name(&v.name)
+                    && !v.is_index
+                    && !v.is_subscription()
             })
             .map(|(_, v)| v)
     }
 
+    /// Iterate all the change logs of subscriptions
+    pub fn iter_subscription(&self) -> impl Iterator<Item = &Arc<TableCatalog>> {
+        self.table_by_name
+            .iter()
+            .filter(|(_, v)| v.is_subscription())
+            .map(|(_, v)| v)
+    }
+
     /// Iterate all indices
     pub fn iter_index(&self) -> impl Iterator<Item = &Arc<IndexCatalog>> {
         self.index_by_name.values()
@@ -16,6 +16,7 @@ use std::collections::HashMap;
 
 use risingwave_pb::catalog::source::Info;
 use risingwave_pb::catalog::{Source as ProstSource, StreamSourceInfo, TableSourceInfo};
+use risingwave_pb::plan_common::RowFormatType;
 
 use super::column_catalog::ColumnCatalog;
 use super::{ColumnId, SourceId};
@@ -52,6 +53,19 @@ impl SourceCatalog {
     pub fn is_stream(&self) -> bool {
         matches!(self.info, SourceCatalogInfo::StreamSource(_))
     }
+
+    /// Whether the source only produces inserts, i.e. it's declared `appendonly`, or it's a stream
+    /// source not in a CDC row format.
+    pub fn is_insert_only(&self) -> bool {
+        self.append_only
+            || match &self.info {
+                SourceCatalogInfo::StreamSource(info) => !matches!(
+                    info.row_format(),
+                    RowFormatType::DebeziumJson | RowFormatType::Maxwell
+                ),
+                SourceCatalogInfo::TableSource(_) => false,
+            }
+    }
 }
 
 impl From<&ProstSource> for SourceCatalog {
@@ -18,7 +18,11 @@ use itertools::Itertools;
 use risingwave_common::catalog::{TableDesc, TableId};
 use risingwave_common::config::constant::hummock::TABLE_OPTION_DUMMY_RETENTION_SECOND;
 use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
-use risingwave_pb::catalog::{ColumnIndex as ProstColumnIndex, Table as ProstTable};
+use risingwave_pb::catalog::{
+    ColumnIndex as ProstColumnIndex, SubscriptionProgress as ProstSubscriptionProgress,
+    Table as ProstTable, TableStatistics as ProstTableStatistics,
+    WatermarkDesc as ProstWatermarkDesc,
+};
 
 use super::column_catalog::ColumnCatalog;
 use super::{DatabaseId, FragmentId, SchemaId};
@@ -96,6 +100,20 @@ pub struct TableCatalog {
 
     /// Definition of the materialized view.
     pub definition: String,
+
+    /// Statistics collected by `ANALYZE`, if any.
+    pub statistics: Option<ProstTableStatistics>,
+
+    /// Watermarks derived on the upstream sources when the materialized view is created.
+    pub watermark_descs: Vec<ProstWatermarkDesc>,
+
+    /// The progress of the subscription when it's created, if the table is the change log of a
+    /// subscription. The latest progress is only kept by the meta service.
+    pub subscription_progress: Option<ProstSubscriptionProgress>,
+
+    /// The encoding version of the keys of the table, which is set by the meta service when the
+    /// table is created, and flipped once the legacy keys of the table are rewritten.
+    pub key_encoding_version: u32,
 }
 
 impl TableCatalog {
@@ -115,6 +133,11 @@ impl TableCatalog {
         self.associated_source_id
     }
 
+    /// Whether the table is the change log of a subscription.
+    pub fn is_subscription(&self) -> bool {
+        self.subscription_progress.is_some()
+    }
+
     /// Get a reference to the table catalog's columns.
     pub fn columns(&self) -> &[ColumnCatalog] {
         &self.columns
@@ -190,6 +213,10 @@ impl TableCatalog {
                 .map(|i| ProstColumnIndex { index: i as _ }),
             value_indices: self.value_indices.iter().map(|x| *x as _).collect(),
             definition: self.definition.clone(),
+            statistics: self.statistics.clone(),
+            watermark_descs: self.watermark_descs.clone(),
+            subscription_progress: self.subscription_progress.clone(),
+            key_encoding_version: self.key_encoding_version,
         }
     }
 }
@@ -236,6 +263,10 @@ impl From<ProstTable> for TableCatalog {
             vnode_col_idx: tb.vnode_col_idx.map(|x| x.index as usize),
             value_indices: tb.value_indices.iter().map(|x| *x as _).collect(),
             definition: tb.definition.clone(),
+            statistics: tb.statistics,
+            watermark_descs: tb.watermark_descs,
+            subscription_progress: tb.subscription_progress,
+            key_encoding_version: tb.key_encoding_version,
         }
     }
 }
@@ -320,6 +351,10 @@ mod tests {
             vnode_col_idx: None,
             value_indices: vec![0],
             definition: "".into(),
+            statistics: None,
+            watermark_descs: vec![],
+            subscription_progress: None,
+            key_encoding_version: 2,
         }
         .into();
 
@@ -377,6 +412,10 @@ mod tests {
                 vnode_col_idx: None,
                 value_indices: vec![0],
                 definition: "".into(),
+                statistics: None,
+                watermark_descs: vec![],
+                subscription_progress: None,
+                key_encoding_version: 2,
             }
         );
         assert_eq!(table, TableCatalog::from(table.to_prost(0, 0)));
@@ -96,8 +96,12 @@ impl AggCall {
             (AggKind::Sum0, _) => return invalid(),
 
             // ApproxCountDistinct
-            (AggKind::ApproxCountDistinct, [_]) => DataType::Int64,
-            (AggKind::ApproxCountDistinct, _) => return invalid(),
+            (AggKind::ApproxCountDistinct(_), [_]) => DataType::Int64,
+            (AggKind::ApproxCountDistinct(_), _) => return invalid(),
+
+            // ApproxPercentile, whose input is cast to float64 by the binder
+            (AggKind::ApproxPercentile(..), [DataType::Float64]) => DataType::Float64,
+            (AggKind::ApproxPercentile(..), _) => return invalid(),
 
             // Count
             (AggKind::Count, [] | [_]) => DataType::Int64,
@@ -112,6 +116,13 @@ impl AggCall {
                 datatype: Box::new(input.clone()),
             },
             (AggKind::ArrayAgg, _) => return invalid(),
+
+            // BitAnd, BitOr, BitXor
+            (
+                AggKind::BitAnd | AggKind::BitOr | AggKind::BitXor,
+                [input @ (DataType::Int16 | DataType::Int32 | DataType::Int64)],
+            ) => input.clone(),
+            (AggKind::BitAnd | AggKind::BitOr | AggKind::BitXor, _) => return invalid(),
         };
 
         Ok(return_type)
@@ -36,6 +36,7 @@ pub use order_by_expr::{OrderBy, OrderByExpr};
 mod expr_mutator;
 mod expr_rewriter;
 mod expr_visitor;
+mod session_timezone;
 mod type_inference;
 mod utils;
 
@@ -46,13 +47,14 @@ pub use input_ref::{input_ref_to_column_indices, InputRef, InputRefDisplay};
 pub use literal::Literal;
 pub use subquery::{Subquery, SubqueryKind};
 pub use table_function::{TableFunction, TableFunctionType};
-pub use window_function::{WindowFunction, WindowFunctionType};
+pub use window_function::{Frame, FrameBound, WindowFunction, WindowFunctionType};
 
 pub type ExprType = risingwave_pb::expr::expr_node::Type;
 
 pub use expr_mutator::ExprMutator;
 pub use expr_rewriter::ExprRewriter;
 pub use expr_visitor::ExprVisitor;
+pub use session_timezone::SessionTimezone;
 pub use type_inference::{
     agg_func_sigs, align_types, cast_map_array, cast_ok, cast_sigs, func_sigs, infer_type,
     least_restrictive, AggFuncSig, CastContext, CastSig, FuncSign,
@@ -397,6 +399,25 @@ impl ExprImpl {
         collector.correlated_indices
     }
 
+    /// Checks whether the expression has `now()`, which is only bound in streaming queries.
+    pub fn has_now(&self) -> bool {
+        struct Has {}
+
+        impl ExprVisitor<bool> for Has {
+            fn merge(a: bool, b: bool) -> bool {
+                a | b
+            }
+
+            fn visit_function_call(&mut self, func_call: &FunctionCall) -> bool {
+                func_call.get_expr_type() == ExprType::Now
+                    || func_call.inputs().iter().any(|expr| self.visit_expr(expr))
+            }
+        }
+
+        let mut visitor = Has {};
+        visitor.visit_expr(self)
+    }
+
     /// Checks whether this is a constant expr that can be evaluated over a dummy chunk.
     /// Equivalent to `!has_input_ref && !has_agg_call && !has_subquery &&
     /// !has_correlated_input_ref` but checks them in one pass.
@@ -410,6 +431,10 @@ impl ExprImpl {
             fn visit_expr(&mut self, expr: &ExprImpl) {
                 match expr {
                     ExprImpl::Literal(_inner) => {}
+                    // `now()` keeps advancing in streaming queries.
+                    ExprImpl::FunctionCall(inner) if inner.get_expr_type() == ExprType::Now => {
+                        self.has = true
+                    }
                     ExprImpl::FunctionCall(inner) => self.visit_function_call(inner),
                     _ => self.has = true,
                 }
@@ -0,0 +1,268 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::session_config::TimeZone;
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_expr::vector_op::cast::{str_to_timestamp, str_to_timestampz};
+
+use super::{Expr, ExprImpl, ExprRewriter, ExprType, FunctionCall, Literal, Subquery};
+
+/// Inlines the session time zone into the expressions whose results depend on it, by rewriting
+/// them to `AT TIME ZONE` with the zone as a constant:
+///
+/// * casts between `timestamp with time zone` and `timestamp`, `date` or `time`;
+/// * casts of string literals without a UTC offset to `timestamp with time zone`;
+/// * `extract` of any field other than `epoch` from a `timestamp with time zone`.
+///
+/// The binder applies it once to the whole bound statement, including the subqueries. As the zone
+/// is resolved at bind time, the plans, including those of materialized views, never depend on the
+/// time zone of the session that executes them.
+pub struct SessionTimezone {
+    timezone: TimeZone,
+    /// Whether any expression has been rewritten, i.e. whether the statement depends on the zone.
+    used: bool,
+}
+
+impl SessionTimezone {
+    pub fn new(timezone: TimeZone) -> Self {
+        Self {
+            timezone,
+            used: false,
+        }
+    }
+
+    pub fn used(&self) -> bool {
+        self.used
+    }
+
+    fn at_time_zone(&mut self, input: ExprImpl) -> ExprImpl {
+        self.used = true;
+        let time_zone = Literal::new(
+            Some(ScalarImpl::Utf8(self.timezone.name().to_string())),
+            DataType::Varchar,
+        );
+        FunctionCall::new(ExprType::AtTimeZone, vec![input, time_zone.into()])
+            .unwrap()
+            .into()
+    }
+
+    fn cast(input: ExprImpl, target: DataType) -> ExprImpl {
+        FunctionCall::new_unchecked(ExprType::Cast, vec![input], target).into()
+    }
+
+    fn rewrite_cast(&mut self, input: ExprImpl, target: DataType) -> ExprImpl {
+        match (input.return_type(), &target) {
+            (DataType::Timestamp, DataType::Timestampz) => self.at_time_zone(input),
+            (DataType::Date, DataType::Timestampz) => {
+                self.at_time_zone(Self::cast(input, DataType::Timestamp))
+            }
+            (DataType::Varchar, DataType::Timestampz) if is_local_timestamp_literal(&input) => {
+                self.at_time_zone(Self::cast(input, DataType::Timestamp))
+            }
+            (DataType::Timestampz, DataType::Timestamp) => self.at_time_zone(input),
+            (DataType::Timestampz, DataType::Date | DataType::Time) => {
+                let local = self.at_time_zone(input);
+                Self::cast(local, target)
+            }
+            _ => Self::cast(input, target),
+        }
+    }
+}
+
+/// Whether `expr` is a string literal of a timestamp without UTC offset, which is interpreted in
+/// the session time zone.
+fn is_local_timestamp_literal(expr: &ExprImpl) -> bool {
+    match expr {
+        ExprImpl::Literal(literal) => match literal.get_data() {
+            Some(ScalarImpl::Utf8(s)) => {
+                str_to_timestampz(s).is_err() && str_to_timestamp(s).is_ok()
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+impl ExprRewriter for SessionTimezone {
+    fn rewrite_subquery(&mut self, mut subquery: Subquery) -> ExprImpl {
+        subquery.query.rewrite_exprs(self);
+        subquery.into()
+    }
+
+    fn rewrite_function_call(&mut self, func_call: FunctionCall) -> ExprImpl {
+        let (func_type, inputs, ret) = func_call.decompose();
+        let mut inputs: Vec<ExprImpl> = inputs
+            .into_iter()
+            .map(|expr| self.rewrite_expr(expr))
+            .collect();
+        match func_type {
+            ExprType::Cast => {
+                let input = inputs.pop().unwrap();
+                self.rewrite_cast(input, ret)
+            }
+            ExprType::Extract
+                if inputs[1].return_type() == DataType::Timestampz
+                    && !matches!(
+                        inputs[0].as_literal().map(|field| field.get_data()),
+                        Some(Some(ScalarImpl::Utf8(field))) if field.eq_ignore_ascii_case("EPOCH")
+                    ) =>
+            {
+                let input = inputs.pop().unwrap();
+                inputs.push(self.at_time_zone(input));
+                FunctionCall::new(func_type, inputs).unwrap().into()
+            }
+            _ => FunctionCall::new_unchecked(func_type, inputs, ret).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::InputRef;
+    use crate::test_utils::LocalFrontend;
+
+    fn session_timezone(name: &str) -> SessionTimezone {
+        SessionTimezone::new(TimeZone::try_from([name].as_slice()).unwrap())
+    }
+
+    #[test]
+    fn test_rewrite_cast() {
+        let mut rewriter = session_timezone("Asia/Shanghai");
+        let input: ExprImpl = InputRef::new(0, DataType::Timestamp).into();
+        let cast = input.cast_implicit(DataType::Timestampz).unwrap();
+        let rewritten = rewriter.rewrite_expr(cast);
+        assert!(rewriter.used());
+        let (func_type, inputs, ret) = rewritten.into_function_call().unwrap().decompose();
+        assert_eq!(func_type, ExprType::AtTimeZone);
+        assert_eq!(ret, DataType::Timestampz);
+        assert_eq!(
+            inputs[1].as_literal().unwrap().get_data(),
+            &Some(ScalarImpl::Utf8("Asia/Shanghai".into()))
+        );
+
+        let input: ExprImpl = InputRef::new(0, DataType::Timestampz).into();
+        let cast = input.cast_assign(DataType::Date).unwrap();
+        let (func_type, inputs, ret) = rewriter
+            .rewrite_expr(cast)
+            .into_function_call()
+            .unwrap()
+            .decompose();
+        assert_eq!(func_type, ExprType::Cast);
+        assert_eq!(ret, DataType::Date);
+        assert_eq!(
+            inputs[0].as_function_call().unwrap().get_expr_type(),
+            ExprType::AtTimeZone
+        );
+    }
+
+    #[test]
+    fn test_rewrite_literal_cast() {
+        let mut rewriter = session_timezone("US/Pacific");
+        let with_offset: ExprImpl = Literal::new(
+            Some(ScalarImpl::Utf8("2022-10-01 12:00:00+08:00".into())),
+            DataType::Varchar,
+        )
+        .into();
+        let cast = with_offset.cast_explicit(DataType::Timestampz).unwrap();
+        let rewritten = rewriter.rewrite_expr(cast);
+        assert!(!rewriter.used());
+        assert_eq!(
+            rewritten.as_function_call().unwrap().get_expr_type(),
+            ExprType::Cast
+        );
+
+        let local: ExprImpl = Literal::new(
+            Some(ScalarImpl::Utf8("2022-10-01 12:00:00".into())),
+            DataType::Varchar,
+        )
+        .into();
+        let cast = local.cast_explicit(DataType::Timestampz).unwrap();
+        let rewritten = rewriter.rewrite_expr(cast);
+        assert!(rewriter.used());
+        assert_eq!(
+            rewritten.as_function_call().unwrap().get_expr_type(),
+            ExprType::AtTimeZone
+        );
+    }
+
+    #[test]
+    fn test_rewrite_extract() {
+        let mut rewriter = session_timezone("Europe/London");
+        let extract = |field: &str| -> ExprImpl {
+            FunctionCall::new(
+                ExprType::Extract,
+                vec![
+                    Literal::new(Some(ScalarImpl::Utf8(field.into())), DataType::Varchar).into(),
+                    InputRef::new(0, DataType::Timestampz).into(),
+                ],
+            )
+            .unwrap()
+            .into()
+        };
+
+        let (_, inputs, _) = rewriter
+            .rewrite_expr(extract("EPOCH"))
+            .into_function_call()
+            .unwrap()
+            .decompose();
+        assert_eq!(inputs[1].return_type(), DataType::Timestampz);
+        assert!(!rewriter.used());
+
+        let (_, inputs, _) = rewriter
+            .rewrite_expr(extract("HOUR"))
+            .into_function_call()
+            .unwrap()
+            .decompose();
+        assert_eq!(inputs[1].return_type(), DataType::Timestamp);
+        assert!(rewriter.used());
+    }
+
+    #[tokio::test]
+    async fn test_inline_whole_statement() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("SET TIMEZONE TO 'Asia/Shanghai'")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create table t (v timestamp)")
+            .await
+            .unwrap();
+
+        // The cast aligning the types of `VALUES` is added after the rows are bound.
+        let explain = frontend
+            .get_explain_output(
+                "explain values ('2022-10-01 12:00:00'::timestamp), \
+                 ('2022-10-01 12:00:00Z'::timestamp with time zone)",
+            )
+            .await;
+        assert!(explain.contains("Asia/Shanghai"), "{}", explain);
+
+        // So are the assignment casts of `INSERT`.
+        let explain = frontend
+            .get_explain_output("explain insert into t select now()")
+            .await;
+        assert!(explain.contains("Asia/Shanghai"), "{}", explain);
+
+        // And the subqueries are rewritten as well.
+        let explain = frontend
+            .get_explain_output(
+                "explain select * from t where exists \
+                 (select 1 from t as u where u.v::timestamp with time zone > now())",
+            )
+            .await;
+        assert!(explain.contains("Asia/Shanghai"), "{}", explain);
+    }
+}
@@ -75,7 +75,7 @@ static AGG_FUNC_SIG_MAP: LazyLock<AggFuncSigMap> = LazyLock::new(|| {
         A::Max,
         A::Count,
         A::Avg,
-        A::ApproxCountDistinct,
+        A::approx_count_distinct(),
     ] {
         for input in all_types {
             match AggCall::infer_return_type(&agg, &[DataType::from(input)]) {
@@ -293,6 +293,10 @@ fn infer_type_for_special(
             ensure_arity!("vnode", 1 <= | inputs |);
             Ok(Some(DataType::Int16))
         }
+        ExprType::Now => {
+            ensure_arity!("now", | inputs | == 0);
+            Ok(Some(DataType::Timestampz))
+        }
         _ => Ok(None),
     }
 }
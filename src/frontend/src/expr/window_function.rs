@@ -18,8 +18,9 @@ use itertools::Itertools;
 use parse_display::Display;
 use risingwave_common::error::ErrorCode;
 use risingwave_common::types::DataType;
+use risingwave_expr::expr::AggKind;
 
-use super::{Expr, ExprImpl, OrderBy, Result};
+use super::{AggCall, Expr, ExprImpl, OrderBy, Result};
 
 /// A window function performs a calculation across a set of table rows that are somehow related to
 /// the current row, according to the window spec `OVER (PARTITION BY .. ORDER BY ..)`.
@@ -34,6 +35,7 @@ pub struct WindowFunction {
     pub function_type: WindowFunctionType,
     pub partition_by: Vec<ExprImpl>,
     pub order_by: OrderBy,
+    pub frame: Option<Frame>,
 }
 
 #[derive(Debug, Display, Copy, Clone, PartialEq, Eq, Hash)]
@@ -42,6 +44,53 @@ pub enum WindowFunctionType {
     RowNumber,
     Rank,
     DenseRank,
+    Lag,
+    Lead,
+    #[display("{0}")]
+    Aggregate(AggKind),
+}
+
+/// The frame `ROWS BETWEEN start AND end` of a window function. Only bounded frames in `ROWS` mode
+/// are supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameBound {
+    Preceding(u64),
+    CurrentRow,
+    Following(u64),
+}
+
+impl FrameBound {
+    /// The offset of the bound relative to the current row. The binder only accepts the bounds
+    /// whose offsets fit in `i64`.
+    pub fn to_offset(self) -> i64 {
+        match self {
+            FrameBound::Preceding(n) => -(n as i64),
+            FrameBound::CurrentRow => 0,
+            FrameBound::Following(n) => n as i64,
+        }
+    }
+}
+
+impl std::fmt::Display for FrameBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameBound::Preceding(n) => write!(f, "{} PRECEDING", n),
+            FrameBound::CurrentRow => f.write_str("CURRENT ROW"),
+            FrameBound::Following(n) => write!(f, "{} FOLLOWING", n),
+        }
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ROWS BETWEEN {} AND {}", self.start, self.end)
+    }
 }
 
 impl WindowFunctionType {
@@ -63,6 +112,8 @@ impl FromStr for WindowFunctionType {
             "row_number" => Ok(WindowFunctionType::RowNumber),
             "rank" => Ok(WindowFunctionType::Rank),
             "dense_rank" => Ok(WindowFunctionType::DenseRank),
+            "lag" => Ok(WindowFunctionType::Lag),
+            "lead" => Ok(WindowFunctionType::Lead),
             _ => Err(ErrorCode::NotImplemented(
                 format!("unknown table function kind: {s}"),
                 None.into(),
@@ -74,25 +125,108 @@ impl FromStr for WindowFunctionType {
 impl WindowFunction {
     /// Create a `WindowFunction` expr with the return type inferred from `func_type` and types of
     /// `inputs`.
+    ///
+    /// The offset of `lag` and `lead` is turned into a frame of a single row, e.g. `lag(v, 2)` is
+    /// `lag(v)` over `ROWS BETWEEN 2 PRECEDING AND 2 PRECEDING`.
     pub fn new(
         function_type: WindowFunctionType,
         partition_by: Vec<ExprImpl>,
         order_by: OrderBy,
-        args: Vec<ExprImpl>,
+        mut args: Vec<ExprImpl>,
+        frame: Option<Frame>,
     ) -> Result<Self> {
-        if !args.is_empty() {
-            return Err(ErrorCode::BindError(format!(
-                "the length of args of {function_type} function should be 0"
-            ))
-            .into());
-        }
+        let (return_type, frame) = match function_type {
+            WindowFunctionType::RowNumber
+            | WindowFunctionType::Rank
+            | WindowFunctionType::DenseRank => {
+                if !args.is_empty() {
+                    return Err(ErrorCode::BindError(format!(
+                        "the length of args of {function_type} function should be 0"
+                    ))
+                    .into());
+                }
+                if frame.is_some() {
+                    return Err(ErrorCode::NotImplemented(
+                        format!("window frame for {function_type}"),
+                        None.into(),
+                    )
+                    .into());
+                }
+                (DataType::Int64, None)
+            }
+            WindowFunctionType::Lag | WindowFunctionType::Lead => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err(ErrorCode::BindError(format!(
+                        "the length of args of {function_type} function should be 1 or 2"
+                    ))
+                    .into());
+                }
+                if frame.is_some() {
+                    return Err(ErrorCode::NotImplemented(
+                        format!("window frame for {function_type}"),
+                        None.into(),
+                    )
+                    .into());
+                }
+                let offset = if args.len() == 2 {
+                    let offset = args.pop().unwrap();
+                    let invalid = || {
+                        ErrorCode::InvalidInputSyntax(format!(
+                            "the offset of {function_type} should be a non-negative integer constant, but got {:?}",
+                            offset
+                        ))
+                    };
+                    if !offset.is_const() {
+                        return Err(invalid().into());
+                    }
+                    let offset = offset
+                        .clone()
+                        .cast_implicit(DataType::Int64)
+                        .map_err(|_| invalid())?
+                        .eval_row_const()?;
+                    match offset {
+                        Some(offset) if *offset.as_int64() >= 0 => *offset.as_int64() as u64,
+                        _ => return Err(invalid().into()),
+                    }
+                } else {
+                    1
+                };
+                let bound = if function_type == WindowFunctionType::Lag {
+                    FrameBound::Preceding(offset)
+                } else {
+                    FrameBound::Following(offset)
+                };
+                (
+                    args[0].return_type(),
+                    Some(Frame {
+                        start: bound,
+                        end: bound,
+                    }),
+                )
+            }
+            WindowFunctionType::Aggregate(agg_kind) => {
+                let Some(frame) = frame else {
+                    return Err(ErrorCode::NotImplemented(
+                        format!("{agg_kind} as window function without a frame in ROWS mode"),
+                        None.into(),
+                    )
+                    .into());
+                };
+                let arg_types = args.iter().map(|arg| arg.return_type()).collect_vec();
+                (
+                    AggCall::infer_return_type(&agg_kind, &arg_types)?,
+                    Some(frame),
+                )
+            }
+        };
 
         Ok(Self {
             args,
-            return_type: DataType::Int64,
+            return_type,
             function_type,
             partition_by,
             order_by,
+            frame,
         })
     }
 }
@@ -106,9 +240,15 @@ impl std::fmt::Debug for WindowFunction {
                 .field("args", &self.args)
                 .field("partition_by", &self.partition_by)
                 .field("order_by", &format_args!("{}", self.order_by))
+                .field("frame", &self.frame)
                 .finish()
         } else {
-            write!(f, "{}() OVER(", self.function_type)?;
+            write!(
+                f,
+                "{}({:?}) OVER(",
+                self.function_type,
+                self.args.iter().format(", ")
+            )?;
 
             let mut delim = "";
             if !self.partition_by.is_empty() {
@@ -121,6 +261,10 @@ impl std::fmt::Debug for WindowFunction {
             }
             if !self.order_by.sort_exprs.is_empty() {
                 write!(f, "{delim}{}", self.order_by)?;
+                delim = " ";
+            }
+            if let Some(frame) = &self.frame {
+                write!(f, "{delim}{frame}")?;
             }
             f.write_str(")")?;
 
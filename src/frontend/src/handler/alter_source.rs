@@ -0,0 +1,319 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::error::ErrorCode::PermissionDenied;
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_expr::vector_op::cast::str_to_timestamp;
+use risingwave_pb::catalog::source::Info;
+use risingwave_pb::catalog::{ColumnIndex as ProstColumnIndex, Source as ProstSource};
+use risingwave_pb::ddl_service::reset_source_offset_request::Position;
+use risingwave_pb::plan_common::RowFormatType;
+use risingwave_sqlparser::ast::{AlterSourceOperation, ObjectName, SourceOffsetPosition};
+
+use super::create_table::bind_sql_columns;
+use super::privilege::check_super_user;
+use super::RwPgResponse;
+use crate::binder::Binder;
+use crate::catalog::column_catalog::ColumnCatalog;
+use crate::catalog::root_catalog::SchemaPath;
+use crate::catalog::source_catalog::SourceCatalogInfo;
+use crate::catalog::ColumnId;
+use crate::session::{OptimizerContext, SessionImpl};
+
+/// Handles `ALTER SOURCE <name> ADD COLUMN <column_def>` and
+/// `ALTER SOURCE <name> RESET OFFSET TO <position> FORCE`.
+///
+/// Only nullable columns can be added to non-materialized sources with a JSON-like row format,
+/// whose schema is defined by the catalog rather than by an external schema file.
+///
+/// Materialized views that already exist are not affected: each of them keeps the columns it
+/// selected at creation, including those defined with `SELECT *`. Only materialized views created
+/// after the `ALTER` will see the new column, which is `NULL` for records without that field.
+pub async fn handle_alter_source(
+    context: OptimizerContext,
+    name: ObjectName,
+    operation: AlterSourceOperation,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let db_name = session.database();
+    let (schema_name, source_name) = Binder::resolve_table_or_source_name(db_name, name)?;
+    let search_path = session.config().get_search_path();
+    let user_name = &session.auth_context().user_name;
+
+    let schema_path = match schema_name.as_deref() {
+        Some(schema_name) => SchemaPath::Name(schema_name),
+        None => SchemaPath::Path(&search_path, user_name),
+    };
+
+    let column_def = match operation {
+        AlterSourceOperation::AddColumn { column_def } => column_def,
+        AlterSourceOperation::ResetOffset { position, force } => {
+            return handle_reset_offset(
+                &session,
+                db_name,
+                schema_path,
+                &source_name,
+                position,
+                force,
+            )
+            .await;
+        }
+    };
+
+    let source = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (source, schema_name) =
+            catalog_reader.get_source_by_name(db_name, schema_path, &source_name)?;
+
+        let schema_catalog = catalog_reader.get_schema_by_name(db_name, schema_name)?;
+        if session.user_id() != source.owner
+            && session.user_id() != schema_catalog.owner()
+            && !check_super_user(&session)
+        {
+            return Err(PermissionDenied("Do not have the privilege".to_string()).into());
+        }
+
+        let info = match &source.info {
+            SourceCatalogInfo::StreamSource(info) => info.clone(),
+            SourceCatalogInfo::TableSource(_) => {
+                return Err(RwError::from(ErrorCode::InvalidInputSyntax(
+                    "Use `ALTER TABLE` to alter a table.".to_owned(),
+                )));
+            }
+        };
+        let row_format = info.row_format();
+        if !matches!(
+            row_format,
+            RowFormatType::Json | RowFormatType::DebeziumJson | RowFormatType::Maxwell
+        ) {
+            return Err(ErrorCode::NotImplemented(
+                format!(
+                    "alter source with row format {:?}, whose schema is defined by the schema file",
+                    row_format
+                ),
+                None.into(),
+            )
+            .into());
+        }
+
+        if catalog_reader
+            .get_table_by_name(db_name, SchemaPath::Name(schema_name), &source_name)
+            .is_ok()
+        {
+            return Err(ErrorCode::NotImplemented(
+                "alter materialized source".to_string(),
+                None.into(),
+            )
+            .into());
+        }
+
+        let (mut column_descs, pk_column_id) = bind_sql_columns(vec![column_def])?;
+        if pk_column_id.is_some() {
+            return Err(ErrorCode::InvalidInputSyntax(
+                "cannot add a primary key column to a source".to_owned(),
+            )
+            .into());
+        }
+        let mut column_desc = column_descs.pop().unwrap();
+        if source.columns.iter().any(|c| c.name() == column_desc.name) {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "column \"{}\" of source \"{}\" already exists",
+                column_desc.name, source_name
+            ))
+            .into());
+        }
+        let next_column_id = source
+            .columns
+            .iter()
+            .map(|c| c.column_id().get_id())
+            .max()
+            .map_or(0, |id| id + 1);
+        column_desc.column_id = ColumnId::new(next_column_id);
+
+        let mut columns = source
+            .columns
+            .iter()
+            .map(ColumnCatalog::to_protobuf)
+            .collect::<Vec<_>>();
+        columns.push(
+            ColumnCatalog {
+                column_desc,
+                is_hidden: false,
+            }
+            .to_protobuf(),
+        );
+
+        let database_id = catalog_reader.get_database_by_name(db_name)?.id();
+        ProstSource {
+            id: source.id,
+            schema_id: schema_catalog.id(),
+            database_id,
+            name: source.name.clone(),
+            row_id_index: source
+                .row_id_index
+                .map(|index| ProstColumnIndex { index: index as _ }),
+            columns,
+            pk_column_ids: source.pk_col_ids.iter().map(Into::into).collect(),
+            properties: source.properties.clone(),
+            info: Some(Info::StreamSource(info)),
+            owner: source.owner,
+        }
+    };
+
+    let catalog_writer = session.env().catalog_writer();
+    catalog_writer.alter_source(source).await?;
+
+    Ok(PgResponse::empty_result(StatementType::ALTER_SOURCE))
+}
+
+/// Resets the offsets of all splits of a source, so that the running streaming jobs read from the
+/// given position. Since the data between the old and the new offsets is skipped or read again,
+/// `FORCE` is required to acknowledge it.
+async fn handle_reset_offset(
+    session: &SessionImpl,
+    db_name: &str,
+    schema_path: SchemaPath<'_>,
+    source_name: &str,
+    position: SourceOffsetPosition,
+    force: bool,
+) -> Result<RwPgResponse> {
+    let source_id = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (source, schema_name) =
+            catalog_reader.get_source_by_name(db_name, schema_path, source_name)?;
+
+        let schema_catalog = catalog_reader.get_schema_by_name(db_name, schema_name)?;
+        if session.user_id() != source.owner
+            && session.user_id() != schema_catalog.owner()
+            && !check_super_user(session)
+        {
+            return Err(PermissionDenied("Do not have the privilege".to_string()).into());
+        }
+
+        if let SourceCatalogInfo::TableSource(_) = &source.info {
+            return Err(ErrorCode::InvalidInputSyntax(
+                "cannot reset the offsets of a table".to_owned(),
+            )
+            .into());
+        }
+        source.id
+    };
+
+    if !force {
+        return Err(ErrorCode::InvalidInputSyntax(
+            "resetting the offsets of a source skips or replays its data in all the streaming jobs \
+             reading from it, use `RESET OFFSET TO ... FORCE` to confirm"
+                .to_owned(),
+        )
+        .into());
+    }
+
+    let (position, timestamp_millis) = match position {
+        SourceOffsetPosition::Earliest => (Position::Earliest, 0),
+        SourceOffsetPosition::Latest => (Position::Latest, 0),
+        SourceOffsetPosition::Timestamp(timestamp) => {
+            let timestamp = str_to_timestamp(&timestamp)
+                .map_err(|e| ErrorCode::InvalidInputSyntax(e.to_string()))?;
+            (Position::Timestamp, timestamp.0.timestamp_millis())
+        }
+    };
+
+    let catalog_writer = session.env().catalog_writer();
+    catalog_writer
+        .reset_source_offset(source_id, position, timestamp_millis)
+        .await?;
+
+    Ok(PgResponse::empty_result(StatementType::ALTER_SOURCE))
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+    use risingwave_common::types::DataType;
+
+    use crate::catalog::root_catalog::SchemaPath;
+    use crate::test_utils::LocalFrontend;
+
+    #[tokio::test]
+    async fn test_alter_source_add_column() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("CREATE SOURCE s (v1 INT) ROW FORMAT JSON")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("ALTER SOURCE s ADD COLUMN v2 VARCHAR")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (source, _) = catalog_reader
+            .get_source_by_name(
+                DEFAULT_DATABASE_NAME,
+                SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                "s",
+            )
+            .unwrap();
+        let new_column = source.columns.last().unwrap();
+        assert_eq!(new_column.name(), "v2");
+        assert_eq!(new_column.data_type(), &DataType::Varchar);
+        assert!(source
+            .columns
+            .iter()
+            .take(source.columns.len() - 1)
+            .all(|c| c.column_id().get_id() < new_column.column_id().get_id()));
+    }
+
+    #[tokio::test]
+    async fn test_alter_source_reset_offset() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("CREATE SOURCE s (v1 INT) ROW FORMAT JSON")
+            .await
+            .unwrap();
+        for sql in [
+            "ALTER SOURCE s RESET OFFSET TO EARLIEST FORCE",
+            "ALTER SOURCE s RESET OFFSET TO LATEST FORCE",
+            "ALTER SOURCE s RESET OFFSET TO TIMESTAMP '2022-11-01 00:00:00' FORCE",
+        ] {
+            frontend.run_sql(sql).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alter_source_rejected() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("CREATE SOURCE s (v1 INT) ROW FORMAT JSON")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("CREATE MATERIALIZED SOURCE ms (v1 INT) ROW FORMAT JSON")
+            .await
+            .unwrap();
+
+        for sql in [
+            "ALTER SOURCE s ADD COLUMN v1 INT",
+            "ALTER SOURCE s ADD COLUMN v2 INT PRIMARY KEY",
+            "ALTER SOURCE s ADD COLUMN v2 INT NOT NULL",
+            "ALTER SOURCE ms ADD COLUMN v2 INT",
+            "ALTER SOURCE s RESET OFFSET TO EARLIEST",
+            "ALTER SOURCE s RESET OFFSET TO TIMESTAMP 'yesterday' FORCE",
+        ] {
+            assert!(frontend.run_sql(sql).await.is_err(), "{}", sql);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use itertools::Itertools;
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::array::Row;
+use risingwave_common::catalog::TableId;
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_common::session_config::QueryMode;
+use risingwave_common::types::{DataType, Datum};
+use risingwave_common::util::value_encoding::serialize_datum_to_bytes;
+use risingwave_pb::catalog::{
+    ColumnStatistics as ProstColumnStatistics, TableStatistics as ProstTableStatistics,
+};
+use risingwave_sqlparser::ast::{Ident, ObjectName};
+use risingwave_sqlparser::parser::Parser;
+
+use super::query::{distribute_execute, gen_batch_query_plan, local_execute};
+use super::RwPgResponse;
+use crate::binder::Binder;
+use crate::catalog::root_catalog::SchemaPath;
+use crate::catalog::table_catalog::TableCatalog;
+use crate::scheduler::BatchPlanFragmenter;
+use crate::session::{OptimizerContext, SessionImpl};
+
+/// The aggregations computed for a column of the analyzed table.
+struct ColumnAggs {
+    /// Index of `count(col)` in the output of the analyzing query.
+    non_null_count: usize,
+    /// Index of `approx_count_distinct(col)` in the output of the analyzing query, if collected.
+    distinct_count: Option<usize>,
+    /// Indices of `min(col)` and `max(col)` in the output of the analyzing query, if collected.
+    min_max: Option<(usize, usize)>,
+}
+
+fn supports_distinct_count(data_type: &DataType) -> bool {
+    !matches!(data_type, DataType::Struct(_) | DataType::List { .. })
+}
+
+fn supports_min_max(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Decimal
+            | DataType::Date
+            | DataType::Time
+            | DataType::Timestamp
+            | DataType::Timestampz
+            | DataType::Interval
+            | DataType::Varchar
+    )
+}
+
+/// Generates the query that computes the statistics of `table`. The first output column is the
+/// row count, followed by the aggregations described by the returned [`ColumnAggs`] of each
+/// column.
+fn gen_analyze_sql(schema_name: &str, table: &TableCatalog) -> (String, Vec<ColumnAggs>) {
+    let mut select_items = vec!["count(*)".to_string()];
+    let column_aggs = table
+        .columns()
+        .iter()
+        .map(|column| {
+            let column_name = Ident::with_quote('"', column.name());
+            let data_type = column.data_type();
+            let mut add_item = |item: String| {
+                select_items.push(item);
+                select_items.len() - 1
+            };
+
+            let non_null_count = add_item(format!("count({})", column_name));
+            let distinct_count = supports_distinct_count(data_type)
+                .then(|| add_item(format!("approx_count_distinct({})", column_name)));
+            let min_max = supports_min_max(data_type).then(|| {
+                (
+                    add_item(format!("min({})", column_name)),
+                    add_item(format!("max({})", column_name)),
+                )
+            });
+            ColumnAggs {
+                non_null_count,
+                distinct_count,
+                min_max,
+            }
+        })
+        .collect_vec();
+
+    let sql = format!(
+        "SELECT {} FROM {}.{}",
+        select_items.join(", "),
+        Ident::with_quote('"', schema_name),
+        Ident::with_quote('"', table.name()),
+    );
+    (sql, column_aggs)
+}
+
+fn datum_to_u64(datum: &Datum) -> u64 {
+    datum.as_ref().map_or(0, |scalar| *scalar.as_int64() as u64)
+}
+
+fn build_table_statistics(
+    epoch: u64,
+    row: &Row,
+    column_aggs: &[ColumnAggs],
+) -> ProstTableStatistics {
+    let row_count = datum_to_u64(&row[0]);
+    let columns = column_aggs
+        .iter()
+        .map(|aggs| {
+            let non_null_count = datum_to_u64(&row[aggs.non_null_count]);
+            let null_fraction = if row_count == 0 {
+                0.0
+            } else {
+                (row_count - non_null_count) as f64 / row_count as f64
+            };
+            let distinct_count = aggs
+                .distinct_count
+                .map_or(0, |idx| datum_to_u64(&row[idx]).min(non_null_count));
+            let (min, max) = match aggs.min_max {
+                Some((min_idx, max_idx)) if row[min_idx].is_some() => (
+                    serialize_datum_to_bytes(row[min_idx].as_ref()),
+                    serialize_datum_to_bytes(row[max_idx].as_ref()),
+                ),
+                _ => (vec![], vec![]),
+            };
+            ProstColumnStatistics {
+                null_fraction,
+                distinct_count,
+                min,
+                max,
+            }
+        })
+        .collect();
+
+    ProstTableStatistics {
+        epoch,
+        row_count,
+        columns,
+    }
+}
+
+/// Scans the whole table to compute its statistics, and returns the epoch that the statistics
+/// are collected at together with the output of the analyzing query.
+async fn collect_statistics(session: Arc<SessionImpl>, sql: String) -> Result<(u64, Row)> {
+    let stmt = Parser::parse_sql(&sql)
+        .map_err(|e| ErrorCode::InternalError(format!("failed to parse `{}`: {}", sql, e)))?
+        .swap_remove(0);
+    let context =
+        OptimizerContext::new(session.clone(), Arc::from(sql.as_str()), Default::default());
+
+    // Subblock to make sure PlanRef (an Rc) is dropped before `await` below.
+    let (query, query_mode) = {
+        let (plan, query_mode, _) = gen_batch_query_plan(&session, context.into(), stmt)?;
+        let plan_fragmenter = BatchPlanFragmenter::new(
+            session.env().worker_node_manager_ref(),
+            session.env().catalog_reader().clone(),
+        )
+        .with_scan_split_target_bytes(session.env().batch_config().scan_split_target_mb << 20);
+        (plan_fragmenter.split(plan)?, query_mode)
+    };
+
+    let hummock_snapshot_manager = session.env().hummock_snapshot_manager();
+    let query_id = query.query_id().clone();
+    let pinned_snapshot = hummock_snapshot_manager.acquire(&query_id).await?;
+    let epoch = pinned_snapshot.get_committed_epoch();
+
+    let mut chunk_stream = match query_mode {
+        QueryMode::Local => local_execute(session.clone(), query, pinned_snapshot)
+            .await?
+            .boxed(),
+        QueryMode::Distributed => distribute_execute(session.clone(), query, pinned_snapshot)
+            .await?
+            .boxed(),
+    };
+    let mut rows = vec![];
+    while let Some(chunk) = chunk_stream.next().await {
+        let chunk = chunk.map_err(|e| ErrorCode::InternalError(e.to_string()))?;
+        rows.extend(chunk.rows().map(|row| row.to_owned_row()));
+    }
+
+    match rows.into_iter().exactly_one() {
+        Ok(row) => Ok((epoch, row)),
+        Err(rows) => Err(RwError::from(ErrorCode::InternalError(format!(
+            "expect exactly one row when analyzing, got {}",
+            rows.count()
+        )))),
+    }
+}
+
+pub async fn handle_analyze(
+    context: OptimizerContext,
+    table_name: ObjectName,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let db_name = session.database();
+    let (schema_name, table_name) = Binder::resolve_table_or_source_name(db_name, table_name)?;
+    let search_path = session.config().get_search_path();
+    let user_name = &session.auth_context().user_name;
+    let schema_path = match schema_name.as_deref() {
+        Some(schema_name) => SchemaPath::Name(schema_name),
+        None => SchemaPath::Path(&search_path, user_name),
+    };
+
+    let (table_id, sql, column_aggs): (TableId, _, _) = {
+        let reader = session.env().catalog_reader().read_guard();
+        let (table, schema_name) = reader.get_table_by_name(db_name, schema_path, &table_name)?;
+        let (sql, column_aggs) = gen_analyze_sql(schema_name, table);
+        (table.id(), sql, column_aggs)
+    };
+
+    let (epoch, row) = collect_statistics(session.clone(), sql).await?;
+    let statistics = build_table_statistics(epoch, &row, &column_aggs);
+    session
+        .env()
+        .catalog_writer()
+        .update_table_statistics(table_id, statistics)
+        .await?;
+
+    Ok(PgResponse::empty_result(StatementType::ANALYZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+
+    use super::*;
+    use crate::test_utils::LocalFrontend;
+
+    async fn set_row_count(frontend: &LocalFrontend, table_name: &str, row_count: u64) {
+        let session = frontend.session_ref();
+        let table_id = session
+            .env()
+            .catalog_reader()
+            .read_guard()
+            .get_table_by_name(
+                DEFAULT_DATABASE_NAME,
+                SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                table_name,
+            )
+            .unwrap()
+            .0
+            .id();
+        let statistics = ProstTableStatistics {
+            epoch: 1,
+            row_count,
+            columns: vec![],
+        };
+        session
+            .env()
+            .catalog_writer()
+            .update_table_statistics(table_id, statistics)
+            .await
+            .unwrap();
+    }
+
+    /// Returns the tables in the order they are scanned in the explain output of `sql`.
+    async fn scan_order(frontend: &LocalFrontend, sql: &str) -> Vec<String> {
+        frontend
+            .get_explain_output(sql)
+            .await
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("BatchScan { table: "))
+            .map(|line| line.split(',').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_build_table_statistics() {
+        let column_aggs = vec![
+            ColumnAggs {
+                non_null_count: 1,
+                distinct_count: Some(2),
+                min_max: Some((3, 4)),
+            },
+            ColumnAggs {
+                non_null_count: 5,
+                distinct_count: None,
+                min_max: None,
+            },
+        ];
+        let row = Row(vec![
+            Some(100i64.into()),
+            Some(75i64.into()),
+            Some(80i64.into()),
+            Some(1i32.into()),
+            Some(9i32.into()),
+            Some(100i64.into()),
+        ]);
+        let statistics = build_table_statistics(42, &row, &column_aggs);
+        assert_eq!(statistics.epoch, 42);
+        assert_eq!(statistics.row_count, 100);
+        assert_eq!(statistics.columns[0].null_fraction, 0.25);
+        // The distinct count estimation can't be larger than the non-null count.
+        assert_eq!(statistics.columns[0].distinct_count, 75);
+        assert_eq!(
+            statistics.columns[0].min,
+            serialize_datum_to_bytes(Some(&1i32.into()))
+        );
+        assert_eq!(
+            statistics.columns[0].max,
+            serialize_datum_to_bytes(Some(&9i32.into()))
+        );
+        assert_eq!(statistics.columns[1].null_fraction, 0.0);
+        assert_eq!(statistics.columns[1].distinct_count, 0);
+        assert!(statistics.columns[1].min.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_join_order_flips_by_row_count() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table a (x int)").await.unwrap();
+        frontend.run_sql("create table b (x int)").await.unwrap();
+        let sql = "explain select * from a join b on a.x = b.x";
+
+        // Without statistics, the join order follows the query.
+        assert_eq!(scan_order(&frontend, sql).await, vec!["a", "b"]);
+
+        // The larger side is put on the left, so that the hash table is built on the tiny side.
+        set_row_count(&frontend, "a", 1).await;
+        set_row_count(&frontend, "b", 1_000_000).await;
+        assert_eq!(scan_order(&frontend, sql).await, vec!["b", "a"]);
+
+        frontend
+            .run_sql("set rw_optimizer_use_statistics = false")
+            .await
+            .unwrap();
+        assert_eq!(scan_order(&frontend, sql).await, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_join_order_by_row_count() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table a (x int)").await.unwrap();
+        frontend.run_sql("create table b (x int)").await.unwrap();
+        frontend.run_sql("create table c (x int)").await.unwrap();
+        let sql = "explain select * from b join c on b.x = c.x join a on a.x = b.x";
+        assert_eq!(scan_order(&frontend, sql).await, vec!["b", "c", "a"]);
+
+        set_row_count(&frontend, "a", 1_000_000).await;
+        set_row_count(&frontend, "b", 10).await;
+        set_row_count(&frontend, "c", 1000).await;
+        assert_eq!(scan_order(&frontend, sql).await, vec!["a", "b", "c"]);
+
+        frontend
+            .run_sql("set rw_optimizer_use_statistics = false")
+            .await
+            .unwrap();
+        assert_eq!(scan_order(&frontend, sql).await, vec!["b", "c", "a"]);
+    }
+}
@@ -76,6 +76,7 @@ pub(crate) fn gen_create_index_plan(
             table.owner,
             Action::Select,
             Object::TableId(table.id.table_id),
+            table.name(),
         )],
     )?;
 
@@ -163,6 +164,7 @@ pub(crate) fn gen_create_index_plan(
                     schema.owner(),
                     Action::Create,
                     Object::SchemaId(schema.id()),
+                    schema_name.as_str(),
                 )],
             )?;
         }
@@ -17,18 +17,20 @@ use risingwave_common::catalog::DEFAULT_SCHEMA_NAME;
 use risingwave_common::error::ErrorCode::InternalError;
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::catalog::Table as ProstTable;
+use risingwave_pb::stream_plan::BackfillOrder;
 use risingwave_pb::user::grant_privilege::{Action, Object};
-use risingwave_sqlparser::ast::{Ident, ObjectName, Query};
+use risingwave_sqlparser::ast::{Ident, ObjectName, Query, Statement};
 
 use super::privilege::{check_privileges, resolve_relation_privileges};
 use super::RwPgResponse;
-use crate::binder::{Binder, BoundSetExpr};
+use crate::binder::{Binder, BoundSetExpr, BoundStatement};
 use crate::catalog::check_schema_writable;
 use crate::handler::privilege::ObjectCheckItem;
 use crate::optimizer::PlanRef;
 use crate::planner::Planner;
 use crate::session::{OptimizerContext, OptimizerContextRef, SessionImpl};
-use crate::stream_fragmenter::build_graph;
+use crate::stream_fragmenter::{build_graph, mark_hot_key_skew};
+use crate::utils::explain_table_fragments;
 
 /// Generate create MV plan, return plan and mv table info.
 pub fn gen_create_mv_plan(
@@ -58,6 +60,7 @@ pub fn gen_create_mv_plan(
                     schema.owner(),
                     Action::Create,
                     Object::SchemaId(schema.id()),
+                    schema.name(),
                 )],
             )?;
         }
@@ -79,8 +82,18 @@ pub fn gen_create_mv_plan(
     };
 
     let bound = {
-        let mut binder = Binder::new(session);
-        binder.bind_query(query)?
+        let mut binder = Binder::new_for_stream(session);
+        let bound = match binder.bind(Statement::Query(Box::new(query)))? {
+            BoundStatement::Query(query) => *query,
+            _ => unreachable!(),
+        };
+        if binder.has_inlined_session_timezone() {
+            context.notice_to_user(format!(
+                "the session time zone \"{}\" is pinned into the materialized view",
+                session.config().get_timezone()
+            ));
+        }
+        bound
     };
 
     if let BoundSetExpr::Select(select) = &bound.body {
@@ -131,6 +144,7 @@ pub fn gen_create_mv_plan(
     table.owner = session.user_id();
 
     let ctx = plan.ctx();
+    table.watermark_descs = ctx.derived_watermarks();
     let explain_trace = ctx.is_explain_trace();
     if explain_trace {
         ctx.trace("Create Materialized View:");
@@ -148,7 +162,7 @@ pub async fn handle_create_mv(
 ) -> Result<RwPgResponse> {
     let session = context.session_ctx.clone();
 
-    let (table, graph) = {
+    let (table, graph, mut notices) = {
         {
             // Here is some duplicate code because we need to check name duplicated outside of
             // `gen_xxx_plan` to avoid `explain` reporting the error.
@@ -170,17 +184,34 @@ pub async fn handle_create_mv(
             catalog_reader.check_relation_name_duplicated(db_name, &schema_name, &table_name)?;
         }
 
-        let (plan, table) = gen_create_mv_plan(&session, context.into(), query, name, columns)?;
-        let graph = build_graph(plan);
+        let context: OptimizerContextRef = context.into();
+        let (plan, table) = gen_create_mv_plan(&session, context.clone(), query, name, columns)?;
+        let mut graph = build_graph(plan);
+        if session.config().get_streaming_sequential_backfill() {
+            graph.backfill_order = BackfillOrder::Sequential as i32;
+        }
+        if session.config().get_streaming_hot_key_skew() {
+            mark_hot_key_skew(&mut graph);
+        }
 
-        (table, graph)
+        (table, graph, context.take_notices())
     };
 
     let catalog_writer = session.env().catalog_writer();
-    catalog_writer
+    let table_fragments = catalog_writer
         .create_materialized_view(table, graph)
         .await?;
 
+    if session.config().get_streaming_graph_notice() {
+        notices.push(explain_table_fragments(&table_fragments)?);
+    }
+    if !notices.is_empty() {
+        return Ok(PgResponse::empty_result_with_notice(
+            StatementType::CREATE_MATERIALIZED_VIEW,
+            notices.join("\n"),
+        ));
+    }
+
     Ok(PgResponse::empty_result(
         StatementType::CREATE_MATERIALIZED_VIEW,
     ))
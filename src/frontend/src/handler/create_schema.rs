@@ -69,6 +69,7 @@ pub async fn handle_create_schema(
             db_owner,
             Action::Create,
             Object::DatabaseId(db_id),
+            database_name,
         )],
     )?;
 
@@ -16,8 +16,11 @@ use std::rc::Rc;
 
 use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_common::catalog::DEFAULT_SCHEMA_NAME;
-use risingwave_common::error::Result;
+use risingwave_common::error::{Result, RwError};
+use risingwave_connector::options::ConnectorKind;
+use risingwave_connector::sink::s3_parquet::{is_s3_parquet_sink, S3ParquetConfig};
 use risingwave_pb::catalog::Sink as ProstSink;
+use risingwave_pb::stream_plan::StreamFragmentGraph;
 use risingwave_pb::user::grant_privilege::{Action, Object};
 use risingwave_sqlparser::ast::CreateSinkStatement;
 
@@ -83,6 +86,7 @@ pub fn gen_sink_plan(
                     schema.owner(),
                     Action::Create,
                     Object::SchemaId(schema.id()),
+                    schema_name,
                 )],
             )?;
         }
@@ -134,6 +138,9 @@ pub async fn handle_create_sink(
     stmt: CreateSinkStatement,
 ) -> Result<RwPgResponse> {
     let session = context.session_ctx.clone();
+    let notices = context
+        .with_options
+        .validate_connector_options(ConnectorKind::Sink)?;
 
     let (sink, graph) = {
         // Here is some duplicate code because we need to check name duplicated outside of
@@ -156,20 +163,35 @@ pub async fn handle_create_sink(
             catalog_reader.check_relation_name_duplicated(db_name, schema_name, &sink_name)?;
         }
 
-        let (plan, sink) = gen_sink_plan(&session, context.into(), stmt)?;
-
-        (sink, build_graph(plan))
+        // A snapshot export has no streaming job, it's run by one of the frontends. See
+        // [`crate::scheduler::SnapshotExportManager`].
+        if is_s3_parquet_sink(context.with_options.inner()) {
+            S3ParquetConfig::from_hashmap(context.with_options.inner().clone())
+                .map_err(RwError::from)?;
+            let (_, sink) = gen_sink_plan(&session, context.into(), stmt)?;
+            (sink, StreamFragmentGraph::default())
+        } else {
+            let (plan, sink) = gen_sink_plan(&session, context.into(), stmt)?;
+            (sink, build_graph(plan))
+        }
     };
 
     let catalog_writer = session.env().catalog_writer();
     catalog_writer.create_sink(sink, graph).await?;
 
+    if !notices.is_empty() {
+        return Ok(PgResponse::empty_result_with_notice(
+            StatementType::CREATE_SINK,
+            notices.join("\n"),
+        ));
+    }
     Ok(PgResponse::empty_result(StatementType::CREATE_SINK))
 }
 
 #[cfg(test)]
 pub mod tests {
     use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+    use risingwave_pb::common::{WorkerNode, WorkerType};
 
     use crate::catalog::root_catalog::SchemaPath;
     use crate::test_utils::{create_proto_file, LocalFrontend, PROTO_FILE_DATA};
@@ -217,4 +239,60 @@ pub mod tests {
             .unwrap();
         assert_eq!(sink.name, "snk1");
     }
+
+    #[tokio::test]
+    async fn test_create_snapshot_export() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 varchar);")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t;")
+            .await
+            .unwrap();
+        frontend
+            .run_sql(
+                "create sink snk from mv with (connector = 's3_parquet', path = 'memory', \
+                 interval = '1h');",
+            )
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let env = session.env();
+        let sink_id = env
+            .catalog_reader()
+            .read_guard()
+            .get_sink_by_name(
+                DEFAULT_DATABASE_NAME,
+                SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                "snk",
+            )
+            .unwrap()
+            .0
+            .id;
+
+        // The export is run by the only frontend once it has learnt the frontend from meta.
+        let export_manager = env.snapshot_export_manager();
+        export_manager.reconcile(env).await;
+        assert!(export_manager.running_exports().is_empty());
+        env.worker_node_manager().add_worker_node(WorkerNode {
+            id: export_manager.worker_id(),
+            r#type: WorkerType::Frontend as i32,
+            ..Default::default()
+        });
+        export_manager.reconcile(env).await;
+        assert_eq!(export_manager.running_exports(), vec![sink_id]);
+
+        // The interval is required.
+        assert!(frontend
+            .run_sql("create sink snk2 from mv with (connector = 's3_parquet', path = 'memory');")
+            .await
+            .is_err());
+
+        frontend.run_sql("drop sink snk;").await.unwrap();
+        export_manager.reconcile(env).await;
+        assert!(export_manager.running_exports().is_empty());
+    }
 }
@@ -19,6 +19,7 @@ use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_common::catalog::DEFAULT_SCHEMA_NAME;
 use risingwave_common::error::ErrorCode::ProtocolError;
 use risingwave_common::error::{Result, RwError};
+use risingwave_connector::options::ConnectorKind;
 use risingwave_pb::catalog::source::Info;
 use risingwave_pb::catalog::{
     ColumnIndex as ProstColumnIndex, Source as ProstSource, StreamSourceInfo,
@@ -70,6 +71,7 @@ pub(crate) fn make_prost_source(
                     schema.owner(),
                     Action::Create,
                     Object::SchemaId(schema.id()),
+                    schema.name(),
                 )],
             )?;
         }
@@ -139,6 +141,9 @@ pub async fn handle_create_source(
     let (mut columns, pk_column_ids, row_id_index) =
         bind_sql_table_constraints(column_descs, pk_column_id_from_columns, stmt.constraints)?;
 
+    let notices = context
+        .with_options
+        .validate_connector_options(ConnectorKind::Source)?;
     let mut with_properties = context.with_options.inner().clone();
 
     let (columns, source_info) = match &stmt.source_schema {
@@ -269,6 +274,12 @@ pub async fn handle_create_source(
     } else {
         catalog_writer.create_source(source).await?;
     }
+    if !notices.is_empty() {
+        return Ok(PgResponse::empty_result_with_notice(
+            StatementType::CREATE_SOURCE,
+            notices.join("\n"),
+        ));
+    }
     Ok(PgResponse::empty_result(StatementType::CREATE_SOURCE))
 }
 
@@ -328,4 +339,31 @@ pub mod tests {
         };
         assert_eq!(columns, expected_columns);
     }
+
+    #[tokio::test]
+    async fn test_create_source_with_invalid_options() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+
+        let sql = "create source s (v int) with (connector = 'nexmark', \
+                   nexmark.table.type = 'Bid', nexmark.split.nums = '2') row format json";
+        let err = frontend.run_sql(sql).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "connector error: Invalid option: unknown option 'nexmark.split.nums' of nexmark, \
+             did you mean 'nexmark.split.num'?"
+        );
+
+        let sql = "create source s (v int) with (connector = 'kafka', topic = 'abc') \
+                   row format json";
+        let err = frontend.run_sql(sql).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "connector error: Invalid option: missing required option \
+             'properties.bootstrap.server' of kafka"
+        );
+
+        let sql = "create source s (v int) with (connector = 'kafka', topic = 'abc', \
+                   properties.bootstrap.server = 'localhost:9092') row format json";
+        frontend.run_sql(sql).await.unwrap();
+    }
 }
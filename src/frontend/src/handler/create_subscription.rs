@@ -0,0 +1,330 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::rc::Rc;
+use std::time::Duration;
+
+use fixedbitset::FixedBitSet;
+use itertools::Itertools;
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::catalog::hummock::PROPERTIES_RETENTION_SECOND_KEY;
+use risingwave_common::catalog::DEFAULT_SCHEMA_NAME;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_pb::catalog::{SubscriptionProgress, Table as ProstTable};
+use risingwave_pb::user::grant_privilege::{Action, Object};
+use risingwave_sqlparser::ast::CreateSubscriptionStatement;
+
+use super::privilege::check_privileges;
+use super::RwPgResponse;
+use crate::binder::Binder;
+use crate::catalog::check_schema_writable;
+use crate::catalog::root_catalog::SchemaPath;
+use crate::handler::privilege::ObjectCheckItem;
+use crate::optimizer::plan_node::{
+    LogicalScan, StreamChangeLog, StreamMaterialize, StreamTableScan, CHANGE_LOG_EPOCH_COLUMN_NAME,
+    CHANGE_LOG_OP_COLUMN_NAME, CHANGE_LOG_SEQ_COLUMN_NAME,
+};
+use crate::optimizer::property::{Order, RequiredDist};
+use crate::optimizer::PlanRef;
+use crate::session::{OptimizerContext, OptimizerContextRef, SessionImpl};
+use crate::stream_fragmenter::build_graph;
+use crate::WithOptions;
+
+/// The option of how long the changes are kept in the change log, e.g. `1h`.
+const RETENTION_OPTION: &str = "retention";
+const DEFAULT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parses the retention of the change log in seconds from the options.
+fn retention_seconds(properties: &WithOptions) -> Result<u32> {
+    let retention = match properties.get(RETENTION_OPTION) {
+        Some(retention) => humantime::parse_duration(retention).map_err(|e| {
+            ErrorCode::InvalidParameterValue(format!("invalid retention \"{}\": {}", retention, e))
+        })?,
+        None => DEFAULT_RETENTION,
+    };
+    match u32::try_from(retention.as_secs()) {
+        Ok(seconds) if seconds > 0 => Ok(seconds),
+        _ => Err(ErrorCode::InvalidParameterValue(format!(
+            "retention must be between 1 second and {} seconds",
+            u32::MAX
+        ))
+        .into()),
+    }
+}
+
+/// Generates the streaming job of a subscription, which appends the changes of the materialized
+/// view to its change log. The change log is a table named after the subscription, whose columns
+/// are the visible columns of the materialized view, followed by the name of the op of the change.
+/// The epoch of the change and its sequence number in the epoch are hidden columns, which are the
+/// primary key in which order the changes are fetched.
+pub fn gen_subscription_plan(
+    session: &SessionImpl,
+    context: OptimizerContextRef,
+    stmt: CreateSubscriptionStatement,
+) -> Result<(PlanRef, ProstTable)> {
+    let definition = format!("CREATE SUBSCRIPTION {}", stmt);
+    let db_name = session.database();
+    let (schema_name, associated_table_name) =
+        Binder::resolve_table_or_source_name(db_name, stmt.materialized_view.clone())?;
+    let search_path = session.config().get_search_path();
+    let user_name = &session.auth_context().user_name;
+    let schema_path = match schema_name.as_deref() {
+        Some(schema_name) => SchemaPath::Name(schema_name),
+        None => SchemaPath::Path(&search_path, user_name),
+    };
+
+    let (database_id, schema_id, associated_table) = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (associated_table, schema_name) =
+            catalog_reader.get_table_by_name(db_name, schema_path, &associated_table_name)?;
+        if associated_table.is_subscription() {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "cannot subscribe to subscription \"{}\"",
+                associated_table_name
+            ))
+            .into());
+        }
+
+        let schema = catalog_reader.get_schema_by_name(db_name, schema_name)?;
+
+        check_schema_writable(schema_name)?;
+        if schema_name != DEFAULT_SCHEMA_NAME {
+            check_privileges(
+                session,
+                &vec![ObjectCheckItem::new(
+                    schema.owner(),
+                    Action::Create,
+                    Object::SchemaId(schema.id()),
+                    schema_name,
+                )],
+            )?;
+        }
+
+        let db_id = catalog_reader.get_database_by_name(db_name)?.id();
+
+        (db_id, schema.id(), associated_table.clone())
+    };
+
+    let subscription_name = Binder::resolve_subscription_name(stmt.subscription_name)?;
+    let retention_seconds = retention_seconds(&context.inner().with_options)?;
+
+    let change_log_column_names = [
+        CHANGE_LOG_EPOCH_COLUMN_NAME,
+        CHANGE_LOG_SEQ_COLUMN_NAME,
+        CHANGE_LOG_OP_COLUMN_NAME,
+    ];
+    for column in associated_table.columns() {
+        let name = column.name();
+        if change_log_column_names.contains(&name) {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "column \"{}\" of \"{}\" conflicts with the columns of the change log",
+                name, associated_table_name
+            ))
+            .into());
+        }
+    }
+
+    let scan: PlanRef = StreamTableScan::new(LogicalScan::create(
+        associated_table_name,
+        false,
+        Rc::new(associated_table.table_desc()),
+        vec![],
+        context,
+    ))
+    .into();
+    // The sequence numbers are assigned in a single actor.
+    let scan = RequiredDist::single().enforce_if_not_satisfies(scan, &Order::any())?;
+    let change_log: PlanRef = StreamChangeLog::new(scan).into();
+
+    let visible_columns = associated_table
+        .columns()
+        .iter()
+        .positions(|column| !column.is_hidden)
+        .collect_vec();
+    let op_idx = associated_table.columns().len() + 2;
+    let mut out_fields = FixedBitSet::with_capacity(op_idx + 1);
+    visible_columns
+        .iter()
+        .for_each(|&idx| out_fields.insert(idx));
+    out_fields.insert(op_idx);
+    let out_names = visible_columns
+        .iter()
+        .map(|&idx| associated_table.columns()[idx].name().to_string())
+        .chain([CHANGE_LOG_OP_COLUMN_NAME.to_string()])
+        .collect();
+
+    let materialize = StreamMaterialize::create(
+        change_log,
+        subscription_name,
+        RequiredDist::Any,
+        Order::any(),
+        out_fields,
+        out_names,
+        false,
+        definition,
+    )?;
+    let mut table = materialize.table().to_prost(schema_id, database_id);
+    table.owner = session.user_id();
+    // The changes out of the retention are cleaned up by the TTL of the change log.
+    table.properties.insert(
+        PROPERTIES_RETENTION_SECOND_KEY.to_string(),
+        retention_seconds.to_string(),
+    );
+    let plan: PlanRef = materialize.into();
+
+    let ctx = plan.ctx();
+    let explain_trace = ctx.is_explain_trace();
+    if explain_trace {
+        ctx.trace("Create Subscription:");
+        ctx.trace(plan.explain_to_string().unwrap());
+    }
+
+    Ok((plan, table))
+}
+
+pub async fn handle_create_subscription(
+    context: OptimizerContext,
+    stmt: CreateSubscriptionStatement,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx.clone();
+
+    let (mut table, graph) = {
+        // Here is some duplicate code because we need to check name duplicated outside of
+        // `gen_xxx_plan` to avoid `explain` reporting the error.
+        let db_name = session.database();
+        let (schema_name, associated_table_name) =
+            Binder::resolve_table_or_source_name(db_name, stmt.materialized_view.clone())?;
+        let search_path = session.config().get_search_path();
+        let user_name = &session.auth_context().user_name;
+        let schema_path = match schema_name.as_deref() {
+            Some(schema_name) => SchemaPath::Name(schema_name),
+            None => SchemaPath::Path(&search_path, user_name),
+        };
+        let subscription_name = Binder::resolve_subscription_name(stmt.subscription_name.clone())?;
+
+        {
+            let catalog_reader = session.env().catalog_reader().read_guard();
+            let (_, schema_name) =
+                catalog_reader.get_table_by_name(db_name, schema_path, &associated_table_name)?;
+            if let Err(e) = catalog_reader.check_relation_name_duplicated(
+                db_name,
+                schema_name,
+                &subscription_name,
+            ) {
+                if stmt.if_not_exists {
+                    return Ok(PgResponse::empty_result_with_notice(
+                        StatementType::CREATE_SUBSCRIPTION,
+                        format!(
+                            "relation \"{}\" already exists, skipping",
+                            subscription_name
+                        ),
+                    ));
+                } else {
+                    return Err(e);
+                }
+            }
+        }
+
+        let (plan, table) = gen_subscription_plan(&session, context.into(), stmt)?;
+
+        (table, build_graph(plan))
+    };
+
+    // The changes are fetched from the latest snapshot on.
+    table.subscription_progress = Some(SubscriptionProgress {
+        epoch: session
+            .env()
+            .hummock_snapshot_manager()
+            .latest_committed_epoch(),
+        seq: i64::MAX,
+    });
+
+    let catalog_writer = session.env().catalog_writer();
+    catalog_writer
+        .create_materialized_view(table, graph)
+        .await?;
+
+    Ok(PgResponse::empty_result(StatementType::CREATE_SUBSCRIPTION))
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::hummock::PROPERTIES_RETENTION_SECOND_KEY;
+    use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+
+    use crate::catalog::root_catalog::SchemaPath;
+    use crate::test_utils::LocalFrontend;
+
+    #[tokio::test]
+    async fn test_create_subscription_handler() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("create table t (v1 int, v2 varchar);")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t;")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create subscription sub from mv with (retention = '1h');")
+            .await
+            .unwrap();
+        // Subscribing to a subscription is not allowed.
+        assert!(frontend
+            .run_sql("create subscription sub2 from sub;")
+            .await
+            .is_err());
+
+        let session = frontend.session_ref();
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (table, _) = catalog_reader
+            .get_table_by_name(
+                DEFAULT_DATABASE_NAME,
+                SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                "sub",
+            )
+            .unwrap();
+        assert!(table.is_subscription());
+        assert_eq!(
+            table
+                .columns()
+                .iter()
+                .filter(|column| !column.is_hidden)
+                .map(|column| column.name())
+                .collect::<Vec<_>>(),
+            vec!["v1", "v2", "changelog_op"]
+        );
+        assert_eq!(
+            table.properties.get(PROPERTIES_RETENTION_SECOND_KEY),
+            Some(&"3600".to_string())
+        );
+
+        // The materialized view is still listed as one, while the subscription is not.
+        let schema = catalog_reader
+            .get_schema_by_name(DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME)
+            .unwrap();
+        assert_eq!(
+            schema.iter_mv().map(|mv| mv.name()).collect::<Vec<_>>(),
+            vec!["mv"]
+        );
+        assert_eq!(
+            schema
+                .iter_subscription()
+                .map(|sub| sub.name())
+                .collect::<Vec<_>>(),
+            vec!["sub"]
+        );
+    }
+}
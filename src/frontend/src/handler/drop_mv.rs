@@ -87,6 +87,12 @@ pub async fn handle_drop_mv(
             )));
         }
 
+        if table.is_subscription() {
+            return Err(RwError::from(ErrorCode::InvalidInputSyntax(
+                "Use `DROP SUBSCRIPTION` to drop a subscription.".to_owned(),
+            )));
+        }
+
         // If the name is not valid, then it is actually an internal table.
         if !valid_table_name(&table_name) {
             return Err(RwError::from(ErrorCode::InvalidInputSyntax(
@@ -0,0 +1,132 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::error::ErrorCode::PermissionDenied;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_sqlparser::ast::ObjectName;
+
+use super::privilege::check_super_user;
+use super::RwPgResponse;
+use crate::binder::Binder;
+use crate::catalog::root_catalog::SchemaPath;
+use crate::session::OptimizerContext;
+
+pub async fn handle_drop_subscription(
+    context: OptimizerContext,
+    subscription_name: ObjectName,
+    if_exists: bool,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let db_name = session.database();
+    let (schema_name, subscription_name) =
+        Binder::resolve_table_or_source_name(db_name, subscription_name)?;
+    let search_path = session.config().get_search_path();
+    let user_name = &session.auth_context().user_name;
+    let schema_path = match schema_name.as_deref() {
+        Some(schema_name) => SchemaPath::Name(schema_name),
+        None => SchemaPath::Path(&search_path, user_name),
+    };
+
+    let table_id = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (table, schema_name) =
+            match catalog_reader.get_table_by_name(db_name, schema_path, &subscription_name) {
+                Ok((table, schema_name)) if table.is_subscription() => (table, schema_name),
+                Ok(_) => {
+                    return Err(ErrorCode::InvalidInputSyntax(format!(
+                        "\"{}\" is not a subscription",
+                        subscription_name
+                    ))
+                    .into())
+                }
+                Err(e) => {
+                    return if if_exists {
+                        Ok(RwPgResponse::empty_result_with_notice(
+                            StatementType::DROP_SUBSCRIPTION,
+                            format!(
+                                "subscription \"{}\" does not exist, skipping",
+                                subscription_name
+                            ),
+                        ))
+                    } else {
+                        Err(e)
+                    }
+                }
+            };
+
+        let schema_owner = catalog_reader
+            .get_schema_by_name(db_name, schema_name)
+            .unwrap()
+            .owner();
+        if table.owner != session.user_id()
+            && session.user_id() != schema_owner
+            && !check_super_user(&session)
+        {
+            return Err(PermissionDenied("Do not have the privilege".to_string()).into());
+        }
+
+        table.id()
+    };
+
+    // The change log is dropped together with the streaming job writing it.
+    let catalog_writer = session.env().catalog_writer();
+    catalog_writer.drop_materialized_view(table_id).await?;
+
+    Ok(PgResponse::empty_result(StatementType::DROP_SUBSCRIPTION))
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+
+    use crate::catalog::root_catalog::SchemaPath;
+    use crate::test_utils::LocalFrontend;
+
+    #[tokio::test]
+    async fn test_drop_subscription_handler() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int);").await.unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t;")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create subscription sub from mv;")
+            .await
+            .unwrap();
+
+        // Each of them must be dropped with its own statement.
+        assert!(frontend.run_sql("drop subscription mv;").await.is_err());
+        assert!(frontend
+            .run_sql("drop materialized view sub;")
+            .await
+            .is_err());
+        frontend.run_sql("drop subscription sub;").await.unwrap();
+        frontend
+            .run_sql("drop subscription if exists sub;")
+            .await
+            .unwrap();
+
+        let session = frontend.session_ref();
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        assert!(catalog_reader
+            .get_table_by_name(
+                DEFAULT_DATABASE_NAME,
+                SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                "sub"
+            )
+            .is_err());
+    }
+}
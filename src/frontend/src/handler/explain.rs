@@ -117,6 +117,9 @@ pub(super) fn handle_explain(
                 let plan_fragmenter = BatchPlanFragmenter::new(
                     session.env().worker_node_manager_ref(),
                     session.env().catalog_reader().clone(),
+                )
+                .with_scan_split_target_bytes(
+                    session.env().batch_config().scan_split_target_mb << 20,
                 );
                 let query = plan_fragmenter.split(plan)?;
                 let stage_graph_json = serde_json::to_string_pretty(&query.stage_graph).unwrap();
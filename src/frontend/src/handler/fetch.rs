@@ -0,0 +1,375 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use itertools::Itertools;
+use pgwire::pg_response::{PgResponse, StatementType};
+use risingwave_common::array::DataChunk;
+use risingwave_common::catalog::Schema;
+use risingwave_common::error::{ErrorCode, Result};
+use risingwave_common::session_config::QueryMode;
+use risingwave_common::util::epoch::Epoch;
+use risingwave_pb::catalog::SubscriptionProgress;
+use risingwave_sqlparser::ast::{Ident, ObjectName};
+use risingwave_sqlparser::parser::Parser;
+
+use super::query::{distribute_execute, gen_batch_query_plan, local_execute};
+use super::util::{to_pg_field, to_pg_rows};
+use super::RwPgResponse;
+use crate::binder::Binder;
+use crate::catalog::root_catalog::SchemaPath;
+use crate::optimizer::plan_node::{CHANGE_LOG_EPOCH_COLUMN_NAME, CHANGE_LOG_SEQ_COLUMN_NAME};
+use crate::scheduler::BatchPlanFragmenter;
+use crate::session::{OptimizerContext, SessionImpl};
+
+/// Generates the query that fetches at most `count` changes after `progress` from the change log
+/// of a subscription, in the order of the changes. The hidden epoch and sequence number of each
+/// change are appended to the output as the last two columns.
+fn gen_fetch_sql(
+    schema_name: &str,
+    subscription_name: &str,
+    progress: &SubscriptionProgress,
+    count: Option<u64>,
+) -> String {
+    let epoch = Ident::with_quote('"', CHANGE_LOG_EPOCH_COLUMN_NAME);
+    let seq = Ident::with_quote('"', CHANGE_LOG_SEQ_COLUMN_NAME);
+    // The epochs are stored as `bigint`, see `ChangeLogExecutor`.
+    let progress_epoch = progress.epoch as i64;
+    let mut sql = format!(
+        "SELECT *, {epoch}, {seq} FROM {}.{} WHERE {epoch} > {progress_epoch} OR ({epoch} = \
+         {progress_epoch} AND {seq} > {progress_seq}) ORDER BY {epoch}, {seq}",
+        Ident::with_quote('"', schema_name),
+        Ident::with_quote('"', subscription_name),
+        epoch = epoch,
+        seq = seq,
+        progress_epoch = progress_epoch,
+        progress_seq = progress.seq,
+    );
+    if let Some(count) = count {
+        sql.push_str(&format!(" LIMIT {}", count));
+    }
+    sql
+}
+
+/// Runs the fetching query, and returns the epoch it reads at together with its output.
+async fn fetch_changes(
+    session: Arc<SessionImpl>,
+    sql: String,
+) -> Result<(u64, Schema, Vec<DataChunk>)> {
+    let stmt = Parser::parse_sql(&sql)
+        .map_err(|e| ErrorCode::InternalError(format!("failed to parse `{}`: {}", sql, e)))?
+        .swap_remove(0);
+    let context =
+        OptimizerContext::new(session.clone(), Arc::from(sql.as_str()), Default::default());
+
+    // Subblock to make sure PlanRef (an Rc) is dropped before `await` below.
+    let (query, query_mode, schema) = {
+        let (plan, query_mode, schema) = gen_batch_query_plan(&session, context.into(), stmt)?;
+        let plan_fragmenter = BatchPlanFragmenter::new(
+            session.env().worker_node_manager_ref(),
+            session.env().catalog_reader().clone(),
+        )
+        .with_scan_split_target_bytes(session.env().batch_config().scan_split_target_mb << 20);
+        (plan_fragmenter.split(plan)?, query_mode, schema)
+    };
+
+    let hummock_snapshot_manager = session.env().hummock_snapshot_manager();
+    let query_id = query.query_id().clone();
+    let pinned_snapshot = hummock_snapshot_manager.acquire(&query_id).await?;
+    let epoch = pinned_snapshot.get_committed_epoch();
+
+    let mut chunk_stream = match query_mode {
+        QueryMode::Local => local_execute(session.clone(), query, pinned_snapshot)
+            .await?
+            .boxed(),
+        QueryMode::Distributed => distribute_execute(session.clone(), query, pinned_snapshot)
+            .await?
+            .boxed(),
+    };
+    let mut chunks = vec![];
+    while let Some(chunk) = chunk_stream.next().await {
+        chunks.push(chunk.map_err(|e| ErrorCode::InternalError(e.to_string()))?);
+    }
+    Ok((epoch, schema, chunks))
+}
+
+/// Returns the progress after the fetched changes, i.e. the last of them. If less than `count`
+/// changes are fetched, all the changes till the epoch read at are consumed.
+fn next_progress(
+    progress: &SubscriptionProgress,
+    schema: &Schema,
+    chunks: &[DataChunk],
+    count: Option<u64>,
+    read_epoch: u64,
+) -> SubscriptionProgress {
+    let fetched = chunks
+        .iter()
+        .map(|chunk| chunk.cardinality())
+        .sum::<usize>() as u64;
+    let next = if count.map_or(false, |count| fetched >= count) {
+        let epoch_idx = schema.len() - 2;
+        let seq_idx = schema.len() - 1;
+        let last_row = chunks
+            .iter()
+            .rev()
+            .find_map(|chunk| chunk.rows().last())
+            .expect("fetched changes should not be empty");
+        SubscriptionProgress {
+            epoch: last_row
+                .value_at(epoch_idx)
+                .map_or(0, |epoch| epoch.into_int64() as u64),
+            seq: last_row.value_at(seq_idx).map_or(0, |seq| seq.into_int64()),
+        }
+    } else {
+        SubscriptionProgress {
+            epoch: read_epoch,
+            seq: i64::MAX,
+        }
+    };
+    // The epoch read at may be older than the progress, if the progress is updated by another
+    // frontend whose snapshot is newer.
+    std::cmp::max_by_key(progress.clone(), next, |p| (p.epoch, p.seq))
+}
+
+pub async fn handle_fetch(
+    context: OptimizerContext,
+    subscription_name: ObjectName,
+    count: Option<u64>,
+    format: bool,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let db_name = session.database();
+    let (schema_name, subscription_name) =
+        Binder::resolve_table_or_source_name(db_name, subscription_name)?;
+    let search_path = session.config().get_search_path();
+    let user_name = &session.auth_context().user_name;
+    let schema_path = match schema_name.as_deref() {
+        Some(schema_name) => SchemaPath::Name(schema_name),
+        None => SchemaPath::Path(&search_path, user_name),
+    };
+
+    let (schema_name, table_id, retention_seconds) = {
+        let catalog_reader = session.env().catalog_reader().read_guard();
+        let (table, schema_name) =
+            catalog_reader.get_table_by_name(db_name, schema_path, &subscription_name)?;
+        if !table.is_subscription() {
+            return Err(ErrorCode::InvalidInputSyntax(format!(
+                "\"{}\" is not a subscription",
+                subscription_name
+            ))
+            .into());
+        }
+        (
+            schema_name.to_string(),
+            table.id(),
+            table.properties.retention_seconds(),
+        )
+    };
+    let progress = session
+        .env()
+        .catalog_writer()
+        .get_subscription_progress(table_id)
+        .await?;
+
+    // The changes after the progress may have been cleaned up, in which case the subscriber
+    // would silently miss them.
+    if let Some(retention_seconds) = retention_seconds {
+        let retention_ms = retention_seconds.get() as u64 * 1000;
+        if progress.epoch < Epoch::now().subtract_ms(retention_ms).0 {
+            return Err(ErrorCode::SubscriptionRetentionExceeded(
+                subscription_name,
+                retention_seconds.get(),
+            )
+            .into());
+        }
+    }
+
+    let sql = gen_fetch_sql(&schema_name, &subscription_name, &progress, count);
+    let (read_epoch, schema, chunks) = fetch_changes(session.clone(), sql).await?;
+    let next_progress = next_progress(&progress, &schema, &chunks, count, read_epoch);
+
+    // The epochs and the sequence numbers are not returned.
+    let output_indices = (0..schema.len() - 2).collect_vec();
+    let pg_descs = output_indices
+        .iter()
+        .map(|&idx| to_pg_field(&schema[idx]))
+        .collect_vec();
+    let column_types = output_indices
+        .iter()
+        .map(|&idx| schema[idx].data_type())
+        .collect_vec();
+    let time_zone = session.config().get_timezone();
+    let rows = chunks
+        .into_iter()
+        .flat_map(|chunk| {
+            to_pg_rows(
+                &column_types,
+                chunk.reorder_columns(&output_indices),
+                format,
+                time_zone,
+            )
+        })
+        .collect_vec();
+
+    let response = PgResponse::new_for_stream(StatementType::FETCH, None, rows.into(), pg_descs);
+    if next_progress == progress {
+        return Ok(response);
+    }
+    // The progress is only advanced after the changes are sent, so that they are fetched again
+    // rather than lost if the sending fails. The advance fails if the changes are fetched by
+    // another session concurrently, which only returns the changes more than once.
+    Ok(response.with_callback(Box::pin(async move {
+        session
+            .env()
+            .catalog_writer()
+            .update_subscription_progress(table_id, progress, next_progress)
+            .await
+            .map_err(Into::into)
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::{DataChunk, DataChunkTestExt};
+    use risingwave_common::catalog::{Field, Schema, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME};
+    use risingwave_common::types::DataType;
+    use risingwave_common::util::epoch::Epoch;
+    use risingwave_pb::catalog::SubscriptionProgress;
+
+    use super::{gen_fetch_sql, next_progress};
+    use crate::catalog::root_catalog::SchemaPath;
+    use crate::test_utils::LocalFrontend;
+
+    #[test]
+    fn test_gen_fetch_sql() {
+        let progress = SubscriptionProgress { epoch: 1, seq: 2 };
+        assert_eq!(
+            gen_fetch_sql("public", "sub", &progress, Some(10)),
+            "SELECT *, \"changelog_epoch\", \"changelog_seq\" FROM \"public\".\"sub\" \
+             WHERE \"changelog_epoch\" > 1 OR (\"changelog_epoch\" = 1 AND \"changelog_seq\" > 2) \
+             ORDER BY \"changelog_epoch\", \"changelog_seq\" LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_next_progress() {
+        let schema = Schema::new(vec![
+            Field::unnamed(DataType::Int32),
+            Field::unnamed(DataType::Varchar),
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int64),
+        ]);
+        let chunks = vec![DataChunk::from_pretty(
+            "i T      I I
+             1 insert 5 0
+             2 insert 5 1",
+        )];
+        let progress = SubscriptionProgress { epoch: 3, seq: 0 };
+
+        // All the requested changes are fetched, so there may be more after the last one.
+        assert_eq!(
+            next_progress(&progress, &schema, &chunks, Some(2), 7),
+            SubscriptionProgress { epoch: 5, seq: 1 }
+        );
+        // All the changes till the epoch read at are fetched.
+        assert_eq!(
+            next_progress(&progress, &schema, &chunks, Some(3), 7),
+            SubscriptionProgress {
+                epoch: 7,
+                seq: i64::MAX
+            }
+        );
+        assert_eq!(
+            next_progress(&progress, &schema, &chunks, None, 7),
+            SubscriptionProgress {
+                epoch: 7,
+                seq: i64::MAX
+            }
+        );
+        // The progress never goes back.
+        assert_eq!(next_progress(&progress, &schema, &[], None, 2), progress);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_non_subscription() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int);").await.unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t;")
+            .await
+            .unwrap();
+        let err = frontend.run_sql("fetch 1 from mv;").await.unwrap_err();
+        assert!(err.to_string().contains("is not a subscription"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_out_of_retention() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int);").await.unwrap();
+        frontend
+            .run_sql("create materialized view mv as select * from t;")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("create subscription sub from mv with (retention = '1h');")
+            .await
+            .unwrap();
+
+        // The subscription is created at epoch 0 in the mock cluster, which is out of retention.
+        let err = frontend.run_sql("fetch 1 from sub;").await.unwrap_err();
+        assert!(
+            err.to_string().contains("has fallen behind its retention"),
+            "{}",
+            err
+        );
+
+        let session = frontend.session_ref();
+        let table_id = {
+            let catalog_reader = session.env().catalog_reader().read_guard();
+            let (table, _) = catalog_reader
+                .get_table_by_name(
+                    DEFAULT_DATABASE_NAME,
+                    SchemaPath::Name(DEFAULT_SCHEMA_NAME),
+                    "sub",
+                )
+                .unwrap();
+            table.id()
+        };
+        let catalog_writer = session.env().catalog_writer();
+        let progress = catalog_writer
+            .get_subscription_progress(table_id)
+            .await
+            .unwrap();
+        catalog_writer
+            .update_subscription_progress(
+                table_id,
+                progress,
+                SubscriptionProgress {
+                    epoch: Epoch::now().0,
+                    seq: i64::MAX,
+                },
+            )
+            .await
+            .unwrap();
+        if let Err(err) = frontend.run_sql("fetch 1 from sub;").await {
+            assert!(
+                !err.to_string().contains("has fallen behind its retention"),
+                "{}",
+                err
+            );
+        }
+    }
+}
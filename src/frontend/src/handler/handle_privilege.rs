@@ -14,7 +14,9 @@
 
 use pgwire::pg_response::{PgResponse, StatementType};
 use risingwave_common::error::{ErrorCode, Result};
-use risingwave_pb::user::grant_privilege::{ActionWithGrantOption, Object as ProstObject};
+use risingwave_pb::user::grant_privilege::{
+    Action as ProstAction, ActionWithGrantOption, Object as ProstObject,
+};
 use risingwave_pb::user::GrantPrivilege as ProstPrivilege;
 use risingwave_sqlparser::ast::{GrantObjects, Privileges, Statement};
 
@@ -72,6 +74,28 @@ fn make_prost_privilege(
                 grant_objs.push(ProstObject::TableId(table.id().table_id));
             }
         }
+        GrantObjects::Tables(tables) => {
+            let db_name = session.database();
+            let search_path = session.config().get_search_path();
+            let user_name = &session.auth_context().user_name;
+
+            for name in tables {
+                let (schema_name, table_name) =
+                    Binder::resolve_table_or_source_name(db_name, name)?;
+                let schema_path = match schema_name.as_deref() {
+                    Some(schema_name) => SchemaPath::Name(schema_name),
+                    None => SchemaPath::Path(&search_path, user_name),
+                };
+
+                // A table is queried through its materialized view and modified through its
+                // associated source, so the privileges are granted on both of them.
+                let (table, _) = reader.get_table_by_name(db_name, schema_path, &table_name)?;
+                grant_objs.push(ProstObject::TableId(table.id().table_id));
+                if let Some(source_id) = table.associated_source_id() {
+                    grant_objs.push(ProstObject::SourceId(source_id.table_id));
+                }
+            }
+        }
         GrantObjects::Sources(sources) => {
             let db_name = session.database();
             let search_path = session.config().get_search_path();
@@ -124,8 +148,20 @@ fn make_prost_privilege(
 
     let mut prost_privileges = vec![];
     for objs in grant_objs {
+        // Materialized views can only be queried.
+        let action_with_opts = match objs {
+            ProstObject::TableId(_) => action_with_opts
+                .iter()
+                .filter(|opt| opt.action == ProstAction::Select as i32)
+                .cloned()
+                .collect(),
+            _ => action_with_opts.clone(),
+        };
+        if action_with_opts.is_empty() {
+            continue;
+        }
         prost_privileges.push(ProstPrivilege {
-            action_with_opts: action_with_opts.clone(),
+            action_with_opts,
             object: Some(objs),
         });
     }
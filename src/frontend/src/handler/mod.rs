@@ -23,20 +23,23 @@ use pgwire::pg_response::{PgResponse, RowSetResult};
 use pgwire::pg_server::BoxedError;
 use pgwire::types::Row;
 use risingwave_common::error::{ErrorCode, Result};
-use risingwave_sqlparser::ast::{DropStatement, ObjectType, Statement};
+use risingwave_sqlparser::ast::{DropStatement, ObjectType, ShowObject, Statement};
 
 use self::util::DataChunkToRowSetAdapter;
 use crate::scheduler::{DistributedQueryStream, LocalQueryStream};
 use crate::session::{OptimizerContext, SessionImpl};
 use crate::utils::WithOptions;
 
+mod alter_source;
 pub mod alter_user;
+mod analyze;
 mod create_database;
 pub mod create_index;
 pub mod create_mv;
 mod create_schema;
 pub mod create_sink;
 pub mod create_source;
+pub mod create_subscription;
 pub mod create_table;
 pub mod create_user;
 mod describe;
@@ -46,14 +49,16 @@ pub mod drop_mv;
 mod drop_schema;
 pub mod drop_sink;
 pub mod drop_source;
+mod drop_subscription;
 pub mod drop_table;
 pub mod drop_user;
 mod explain;
+mod fetch;
 mod flush;
 pub mod handle_privilege;
 pub mod privilege;
 pub mod query;
-mod show;
+pub mod show;
 pub mod util;
 pub mod variable;
 
@@ -84,12 +89,48 @@ impl From<Vec<Row>> for PgResponseStream {
     }
 }
 
+/// Whether the statement modifies the catalog or the data, which requires the meta service.
+fn requires_meta(stmt: &Statement) -> bool {
+    matches!(
+        stmt,
+        Statement::CreateSource { .. }
+            | Statement::CreateSink { .. }
+            | Statement::CreateSubscription { .. }
+            | Statement::CreateTable { .. }
+            | Statement::CreateDatabase { .. }
+            | Statement::CreateSchema { .. }
+            | Statement::CreateUser(_)
+            | Statement::AlterUser(_)
+            | Statement::AlterSource { .. }
+            | Statement::Grant { .. }
+            | Statement::Revoke { .. }
+            | Statement::Drop(_)
+            | Statement::Insert { .. }
+            | Statement::Delete { .. }
+            | Statement::Update { .. }
+            | Statement::CreateView { .. }
+            | Statement::Flush
+            | Statement::Analyze { .. }
+            | Statement::CreateIndex { .. }
+            | Statement::Fetch { .. }
+    )
+}
+
 pub async fn handle(
     session: Arc<SessionImpl>,
     stmt: Statement,
     sql: &str,
     format: bool,
 ) -> Result<RwPgResponse> {
+    if requires_meta(&stmt) {
+        session.env().meta_connection_status().check_writable()?;
+        // The session's own writes may be committed before the frontend is notified of the new
+        // epoch, so the results cached in the session are dropped for it to read its writes.
+        session
+            .env()
+            .query_result_cache()
+            .invalidate_session(session.session_id());
+    }
     let context = OptimizerContext::new(
         session.clone(),
         Arc::from(sql),
@@ -106,6 +147,13 @@ pub async fn handle(
             stmt,
         } => create_source::handle_create_source(context, is_materialized, stmt).await,
         Statement::CreateSink { stmt } => create_sink::handle_create_sink(context, stmt).await,
+        Statement::CreateSubscription { stmt } => {
+            create_subscription::handle_create_subscription(context, stmt).await
+        }
+        Statement::Fetch {
+            subscription_name,
+            count,
+        } => fetch::handle_fetch(context, subscription_name, count, format).await,
         Statement::CreateTable {
             name,
             columns,
@@ -154,9 +202,22 @@ pub async fn handle(
         } => create_schema::handle_create_schema(context, schema_name, if_not_exists).await,
         Statement::CreateUser(stmt) => create_user::handle_create_user(context, stmt).await,
         Statement::AlterUser(stmt) => alter_user::handle_alter_user(context, stmt).await,
+        Statement::AlterSource { name, operation } => {
+            alter_source::handle_alter_source(context, name, operation).await
+        }
         Statement::Grant { .. } => handle_privilege::handle_grant_privilege(context, stmt).await,
         Statement::Revoke { .. } => handle_privilege::handle_revoke_privilege(context, stmt).await,
         Statement::Describe { name } => describe::handle_describe(context, name),
+        Statement::Analyze { table_name } => analyze::handle_analyze(context, table_name).await,
+        Statement::ShowObjects(ShowObject::TableComplexity) => {
+            show::handle_show_table_complexity(context).await
+        }
+        Statement::ShowObjects(ShowObject::TableFragments { table_id }) => {
+            show::handle_show_table_fragments(context, table_id).await
+        }
+        Statement::ShowObjects(ShowObject::ClusterStatus) => {
+            show::handle_show_cluster_status(context).await
+        }
         Statement::ShowObjects(show_object) => show::handle_show_object(context, show_object),
         Statement::Drop(DropStatement {
             object_type,
@@ -177,6 +238,9 @@ pub async fn handle(
                 drop_source::handle_drop_source(context, object_name, if_exists).await
             }
             ObjectType::Sink => drop_sink::handle_drop_sink(context, object_name, if_exists).await,
+            ObjectType::Subscription => {
+                drop_subscription::handle_drop_subscription(context, object_name, if_exists).await
+            }
             ObjectType::Database => {
                 drop_database::handle_drop_database(
                     context,
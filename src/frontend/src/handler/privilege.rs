@@ -18,20 +18,31 @@ use risingwave_pb::user::grant_privilege::{Action as ProstAction, Object as Pros
 
 use crate::binder::{BoundStatement, Relation};
 use crate::session::SessionImpl;
+use crate::user::user_privilege::{has_privilege, object_kind};
 use crate::user::UserId;
 
 pub struct ObjectCheckItem {
     owner: UserId,
     action: ProstAction,
     object: ProstObject,
+    /// The kind and name of the object in the error message.
+    kind: &'static str,
+    name: String,
 }
 
 impl ObjectCheckItem {
-    pub fn new(owner: UserId, action: ProstAction, object: ProstObject) -> Self {
+    pub fn new(
+        owner: UserId,
+        action: ProstAction,
+        object: ProstObject,
+        name: impl Into<String>,
+    ) -> Self {
         Self {
             owner,
             action,
+            kind: object_kind(&object),
             object,
+            name: name.into(),
         }
     }
 }
@@ -48,6 +59,8 @@ pub(crate) fn resolve_relation_privileges(
                 owner: source.catalog.owner,
                 action,
                 object: ProstObject::SourceId(source.catalog.id),
+                kind: "source",
+                name: source.catalog.name.clone(),
             };
             objects.push(item);
         }
@@ -56,6 +69,8 @@ pub(crate) fn resolve_relation_privileges(
                 owner: table.table_catalog.owner,
                 action,
                 object: ProstObject::TableId(table.table_id.table_id),
+                kind: "table",
+                name: table.table_catalog.name.clone(),
             };
             objects.push(item);
         }
@@ -85,7 +100,10 @@ pub(crate) fn resolve_privileges(stmt: &BoundStatement) -> Vec<ObjectCheckItem>
             let object = ObjectCheckItem {
                 owner: insert.table_source.owner,
                 action: ProstAction::Insert,
-                object: ProstObject::TableId(insert.table_source.source_id.table_id),
+                // The table is modified through its associated source.
+                object: ProstObject::SourceId(insert.table_source.source_id.table_id),
+                kind: "table",
+                name: insert.table_source.name.clone(),
             };
             objects.push(object);
             if let crate::binder::BoundSetExpr::Select(select) = &insert.source.body {
@@ -98,7 +116,9 @@ pub(crate) fn resolve_privileges(stmt: &BoundStatement) -> Vec<ObjectCheckItem>
             let object = ObjectCheckItem {
                 owner: delete.table_source.owner,
                 action: ProstAction::Delete,
-                object: ProstObject::TableId(delete.table_source.source_id.table_id),
+                object: ProstObject::SourceId(delete.table_source.source_id.table_id),
+                kind: "table",
+                name: delete.table_source.name.clone(),
             };
             objects.push(object);
         }
@@ -106,7 +126,9 @@ pub(crate) fn resolve_privileges(stmt: &BoundStatement) -> Vec<ObjectCheckItem>
             let object = ObjectCheckItem {
                 owner: update.table_source.owner,
                 action: ProstAction::Update,
-                object: ProstObject::TableId(update.table_source.source_id.table_id),
+                object: ProstObject::SourceId(update.table_source.source_id.table_id),
+                kind: "table",
+                name: update.table_source.name.clone(),
             };
             objects.push(object);
         }
@@ -138,23 +160,13 @@ pub(crate) fn check_privileges(session: &SessionImpl, items: &Vec<ObjectCheckIte
     let reader = user_reader.read_guard();
 
     if let Some(info) = reader.get_user_by_name(session.user_name()) {
-        if info.is_super {
-            return Ok(());
-        }
         for item in items {
-            if item.owner == info.id {
-                continue;
-            }
-            let has_privilege = info.grant_privileges.iter().any(|privilege| {
-                privilege.object.is_some()
-                    && privilege.object.as_ref().unwrap() == &item.object
-                    && privilege
-                        .action_with_opts
-                        .iter()
-                        .any(|ao| ao.action == item.action as i32)
-            });
-            if !has_privilege {
-                return Err(PermissionDenied("Do not have the privilege".to_string()).into());
+            if !has_privilege(info, item.owner, &item.object, item.action, false) {
+                return Err(PermissionDenied(format!(
+                    "permission denied for {} {}",
+                    item.kind, item.name
+                ))
+                .into());
             }
         }
     } else {
@@ -166,10 +178,32 @@ pub(crate) fn check_privileges(session: &SessionImpl, items: &Vec<ObjectCheckIte
 #[cfg(test)]
 mod tests {
     use risingwave_common::catalog::{DEFAULT_DATABASE_NAME, DEFAULT_SUPER_USER_ID};
+    use risingwave_sqlparser::parser::Parser;
 
     use super::*;
+    use crate::binder::{Binder, BoundSetExpr};
+    use crate::expr::ExprImpl;
     use crate::test_utils::LocalFrontend;
 
+    fn bind(session: &SessionImpl, sql: &str) -> Result<BoundStatement> {
+        let stmt = Parser::parse_sql(sql).unwrap().into_iter().next().unwrap();
+        Binder::new(session).bind(stmt)
+    }
+
+    fn check_query(session: &SessionImpl, sql: &str) -> Result<()> {
+        check_privileges(session, &resolve_privileges(&bind(session, sql)?))
+    }
+
+    fn has_table_privilege(session: &SessionImpl, sql: &str) -> ExprImpl {
+        match bind(session, sql).unwrap() {
+            BoundStatement::Query(query) => match query.body {
+                BoundSetExpr::Select(select) => select.select_items[0].clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
     #[tokio::test]
     async fn test_check_privileges() {
         let frontend = LocalFrontend::new(Default::default()).await;
@@ -186,6 +220,7 @@ mod tests {
             DEFAULT_SUPER_USER_ID,
             ProstAction::Create,
             ProstObject::SchemaId(schema.id()),
+            "schema",
         )];
         assert!(check_privileges(&session, &check_items).is_ok());
 
@@ -206,7 +241,10 @@ mod tests {
                 .id
         };
         let session = frontend.session_user_ref(database, user_name, user_id);
-        assert!(check_privileges(&session, &check_items).is_err());
+        assert!(check_privileges(&session, &check_items)
+            .unwrap_err()
+            .to_string()
+            .contains("permission denied for schema schema"));
 
         frontend
             .run_sql("GRANT CREATE ON SCHEMA schema TO user")
@@ -214,4 +252,87 @@ mod tests {
             .unwrap();
         assert!(check_privileges(&session, &check_items).is_ok());
     }
+
+    #[tokio::test]
+    async fn test_check_query_privileges() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend
+            .run_sql("CREATE TABLE t (v1 int, v2 int)")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("CREATE MATERIALIZED VIEW mv1 AS SELECT v1 FROM t")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("CREATE MATERIALIZED VIEW mv2 AS SELECT v1 FROM mv1")
+            .await
+            .unwrap();
+        frontend
+            .run_sql("CREATE USER user1 WITH PASSWORD 'password1'")
+            .await
+            .unwrap();
+        let user_id = {
+            let user_reader = frontend.session_ref().env().user_info_reader();
+            let reader = user_reader.read_guard();
+            reader.get_user_by_name("user1").unwrap().id
+        };
+        let session = frontend.session_user_ref(
+            DEFAULT_DATABASE_NAME.to_string(),
+            "user1".to_string(),
+            user_id,
+        );
+
+        let err = check_query(&session, "SELECT * FROM mv2").unwrap_err();
+        assert!(err.to_string().contains("permission denied for table mv2"));
+        assert_eq!(
+            has_table_privilege(&session, "SELECT has_table_privilege('mv2', 'SELECT')"),
+            ExprImpl::literal_bool(false)
+        );
+
+        // Only the queried materialized view is checked, not the ones it's built on.
+        frontend
+            .run_sql("GRANT SELECT ON MATERIALIZED VIEW mv2 TO user1")
+            .await
+            .unwrap();
+        check_query(&session, "SELECT * FROM mv2").unwrap();
+        assert!(check_query(&session, "SELECT * FROM mv1").is_err());
+        assert_eq!(
+            has_table_privilege(
+                &frontend.session_ref(),
+                "SELECT has_table_privilege('user1', 'mv2', 'SELECT')"
+            ),
+            ExprImpl::literal_bool(true)
+        );
+        assert_eq!(
+            has_table_privilege(
+                &session,
+                "SELECT has_table_privilege('mv2', 'SELECT WITH GRANT OPTION')"
+            ),
+            ExprImpl::literal_bool(false)
+        );
+
+        // Modifying a table requires the privileges on it.
+        assert!(check_query(&session, "INSERT INTO t VALUES (1, 2)")
+            .unwrap_err()
+            .to_string()
+            .contains("permission denied for table t"));
+        frontend
+            .run_sql("GRANT INSERT ON TABLE t TO user1")
+            .await
+            .unwrap();
+        check_query(&session, "INSERT INTO t VALUES (1, 2)").unwrap();
+        assert!(check_query(&session, "DELETE FROM t").is_err());
+        assert_eq!(
+            has_table_privilege(&session, "SELECT has_table_privilege('t', 'INSERT')"),
+            ExprImpl::literal_bool(true)
+        );
+
+        // Revoking takes effect in the running session.
+        frontend
+            .run_sql("REVOKE SELECT ON MATERIALIZED VIEW mv2 FROM user1")
+            .await
+            .unwrap();
+        assert!(check_query(&session, "SELECT * FROM mv2").is_err());
+    }
 }
@@ -28,25 +28,42 @@ use super::{PgResponseStream, RwPgResponse};
 use crate::binder::{Binder, BoundSetExpr, BoundStatement};
 use crate::handler::privilege::{check_privileges, resolve_privileges};
 use crate::handler::util::{to_pg_field, DataChunkToRowSetAdapter};
+use crate::optimizer::is_point_get;
 use crate::planner::Planner;
 use crate::scheduler::plan_fragmenter::Query;
 use crate::scheduler::{
     BatchPlanFragmenter, DistributedQueryStream, ExecutionContext, ExecutionContextRef,
-    HummockSnapshotGuard, LocalQueryExecution, LocalQueryStream,
+    HummockSnapshotGuard, LocalQueryExecution, LocalQueryStream, QueryResultCacheKey,
+    QueryResultVersion,
 };
 use crate::session::{OptimizerContext, OptimizerContextRef, SessionImpl};
+use crate::utils::WithOptions;
 use crate::PlanRef;
 
 pub fn gen_batch_query_plan(
     session: &SessionImpl,
     context: OptimizerContextRef,
     stmt: Statement,
+) -> Result<(PlanRef, QueryMode, Schema)> {
+    let local_point_get = session.config().get_batch_local_point_get();
+    gen_batch_query_plan_inner(session, context, stmt, local_point_get)
+}
+
+fn gen_batch_query_plan_inner(
+    session: &SessionImpl,
+    context: OptimizerContextRef,
+    stmt: Statement,
+    local_point_get: bool,
 ) -> Result<(PlanRef, QueryMode, Schema)> {
     let stmt_type = to_statement_type(&stmt)?;
 
     let bound = {
         let mut binder = Binder::new(session);
-        binder.bind(stmt)?
+        let bound = binder.bind(stmt)?;
+        if binder.has_inlined_now() {
+            context.set_inlined_now();
+        }
+        bound
     };
 
     let check_items = resolve_privileges(&bound);
@@ -79,13 +96,37 @@ pub fn gen_batch_query_plan(
     let mut logical = planner.plan(bound)?;
     let schema = logical.schema().clone();
 
-    let physical = match query_mode {
-        QueryMode::Local => logical.gen_batch_local_plan()?,
-        QueryMode::Distributed => logical.gen_batch_distributed_plan()?,
+    let (physical, query_mode) = match query_mode {
+        QueryMode::Local => (logical.gen_batch_local_plan()?, QueryMode::Local),
+        QueryMode::Distributed => {
+            let plan = logical.gen_batch_distributed_plan()?;
+            if local_point_get && !must_dist && is_point_get(plan.clone()) {
+                (logical.gen_batch_local_plan()?, QueryMode::Local)
+            } else {
+                (plan, QueryMode::Distributed)
+            }
+        }
     };
     Ok((physical, query_mode, schema))
 }
 
+/// Plans the query again with `RW_BATCH_LOCAL_POINT_GET` off.
+fn gen_batch_query_without_local_point_get(
+    session: &Arc<SessionImpl>,
+    sql: Arc<str>,
+    with_options: WithOptions,
+    stmt: Statement,
+) -> Result<(Query, QueryMode)> {
+    let context = OptimizerContext::new(session.clone(), sql, with_options);
+    let (plan, query_mode, _) = gen_batch_query_plan_inner(session, context.into(), stmt, false)?;
+    let plan_fragmenter = BatchPlanFragmenter::new(
+        session.env().worker_node_manager_ref(),
+        session.env().catalog_reader().clone(),
+    )
+    .with_scan_split_target_bytes(session.env().batch_config().scan_split_target_mb << 20);
+    Ok((plan_fragmenter.split(plan)?, query_mode))
+}
+
 pub async fn handle_query(
     context: OptimizerContext,
     stmt: Statement,
@@ -95,9 +136,46 @@ pub async fn handle_query(
     let session = context.session_ctx.clone();
     let query_start_time = Instant::now();
 
+    // Only the results of `SELECT` are cached. The versions are read before planning, so that the
+    // result is invalidated by any change during planning.
+    let enable_result_cache = session.config().get_batch_enable_result_cache();
+    let result_cache_key = (stmt_type == StatementType::SELECT && enable_result_cache)
+        .then(|| result_cache_key(&session, &stmt, format));
+    let result_version = query_result_version(
+        &session,
+        session
+            .env()
+            .hummock_snapshot_manager()
+            .latest_committed_epoch(),
+    );
+    if let Some(key) = &result_cache_key
+        && let Some(result) = session.env().query_result_cache().get(key, &result_version)
+    {
+        return Ok(PgResponse::new_for_stream(
+            stmt_type,
+            None,
+            result.rows.to_vec().into(),
+            result.fields,
+        ));
+    }
+
+    // Kept to plan the query again if it's planned as a point get in local mode, but the vnode
+    // mappings it's partitioned by change before it's executed.
+    let point_get_fallback = (stmt_type == StatementType::SELECT
+        && session.config().get_query_mode() == QueryMode::Distributed
+        && session.config().get_batch_local_point_get())
+    .then(|| {
+        (
+            context.sql.clone(),
+            context.with_options.clone(),
+            stmt.clone(),
+        )
+    });
+
     // Subblock to make sure PlanRef (an Rc) is dropped before `await` below.
-    let (query, query_mode, output_schema) = {
-        let (plan, query_mode, schema) = gen_batch_query_plan(&session, context.into(), stmt)?;
+    let (mut query, mut query_mode, output_schema, mapping_version, inlined_now) = {
+        let context: OptimizerContextRef = context.into();
+        let (plan, query_mode, schema) = gen_batch_query_plan(&session, context.clone(), stmt)?;
 
         tracing::trace!(
             "Generated query plan: {:?}, query_mode:{:?}",
@@ -107,9 +185,22 @@ pub async fn handle_query(
         let plan_fragmenter = BatchPlanFragmenter::new(
             session.env().worker_node_manager_ref(),
             session.env().catalog_reader().clone(),
-        );
-        (plan_fragmenter.split(plan)?, query_mode, schema)
+        )
+        .with_scan_split_target_bytes(session.env().batch_config().scan_split_target_mb << 20);
+        let mapping_version = session
+            .env()
+            .worker_node_manager()
+            .fragment_mapping_version();
+        (
+            plan_fragmenter.split(plan)?,
+            query_mode,
+            schema,
+            mapping_version,
+            context.has_inlined_now(),
+        )
     };
+    // The result of a query reading `now()` is only valid when it's executed.
+    let result_cache_key = result_cache_key.filter(|_| !inlined_now);
     tracing::trace!("Generated query after plan fragmenter: {:?}", &query);
 
     let pg_descs = output_schema
@@ -122,19 +213,35 @@ pub async fn handle_query(
         .iter()
         .map(|f| f.data_type())
         .collect_vec();
+    let time_zone = session.config().get_timezone();
 
-    let mut row_stream = {
+    let (mut row_stream, committed_epoch) = {
         // Acquire hummock snapshot for execution.
         // TODO: if there's no table scan, we don't need to acquire snapshot.
         let hummock_snapshot_manager = session.env().hummock_snapshot_manager();
         let query_id = query.query_id().clone();
-        let pinned_snapshot = hummock_snapshot_manager.acquire(&query_id).await?;
+        let mut pinned_snapshot = hummock_snapshot_manager.acquire(&query_id).await?;
 
-        match query_mode {
+        // The point get is sent to the compute nodes owning its vnodes when it's planned. If the
+        // mappings have changed since, e.g. by a concurrent reschedule, the owners may be gone, so
+        // fall back to distributed mode which schedules the tasks with the latest mappings.
+        if query_mode == QueryMode::Local
+            && let Some((sql, with_options, stmt)) = point_get_fallback
+            && session.env().worker_node_manager().fragment_mapping_version() != mapping_version
+        {
+            tracing::debug!("vnode mappings changed, fall back to distributed mode: {}", sql);
+            (query, query_mode) =
+                gen_batch_query_without_local_point_get(&session, sql, with_options, stmt)?;
+            pinned_snapshot = hummock_snapshot_manager.acquire(query.query_id()).await?;
+        }
+
+        let committed_epoch = pinned_snapshot.get_committed_epoch();
+        let row_stream = match query_mode {
             QueryMode::Local => PgResponseStream::LocalQuery(DataChunkToRowSetAdapter::new(
                 local_execute(session.clone(), query, pinned_snapshot).await?,
                 column_types,
                 format,
+                time_zone,
             )),
             // Local mode do not support cancel tasks.
             QueryMode::Distributed => {
@@ -142,9 +249,11 @@ pub async fn handle_query(
                     distribute_execute(session.clone(), query, pinned_snapshot).await?,
                     column_types,
                     format,
+                    time_zone,
                 ))
             }
-        }
+        };
+        (row_stream, committed_epoch)
     };
 
     let rows_count = match stmt_type {
@@ -189,11 +298,46 @@ pub async fn handle_query(
             .inc();
     }
 
+    let row_stream = match result_cache_key {
+        Some(key) => session.env().query_result_cache().clone().cache_stream(
+            key,
+            QueryResultVersion {
+                committed_epoch,
+                ..result_version
+            },
+            pg_descs.clone(),
+            row_stream,
+        ),
+        None => row_stream,
+    };
+
     Ok(PgResponse::new_for_stream(
         stmt_type, rows_count, row_stream, pg_descs,
     ))
 }
 
+fn result_cache_key(session: &SessionImpl, stmt: &Statement, format: bool) -> QueryResultCacheKey {
+    let config = session.config();
+    QueryResultCacheKey {
+        session_id: session.session_id(),
+        query: stmt.to_string(),
+        format,
+        search_path: config.get_search_path().to_string(),
+        time_zone: config.get_timezone().name().to_string(),
+    }
+}
+
+/// The state of the cluster the results of queries at `committed_epoch` depend on.
+fn query_result_version(session: &SessionImpl, committed_epoch: u64) -> QueryResultVersion {
+    let env = session.env();
+    QueryResultVersion {
+        committed_epoch,
+        catalog_version: env.catalog_reader().read_guard().version(),
+        user_info_version: env.user_info_reader().read_guard().version(),
+        mapping_version: env.worker_node_manager().fragment_mapping_version(),
+    }
+}
+
 fn to_statement_type(stmt: &Statement) -> Result<StatementType> {
     use StatementType::*;
 
@@ -222,7 +366,7 @@ pub async fn distribute_execute(
 }
 
 #[expect(clippy::unused_async)]
-async fn local_execute(
+pub async fn local_execute(
     session: Arc<SessionImpl>,
     query: Query,
     pinned_snapshot: HummockSnapshotGuard,
@@ -255,3 +399,132 @@ async fn flush_for_write(session: &SessionImpl, stmt_type: StatementType) -> Res
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use pgwire::types::Row;
+    use risingwave_pb::hummock::HummockSnapshot;
+    use risingwave_sqlparser::parser::Parser;
+
+    use super::*;
+    use crate::test_utils::LocalFrontend;
+
+    fn query_mode_of(session: &Arc<SessionImpl>, sql: &str) -> QueryMode {
+        let stmt = Parser::parse_sql(sql).unwrap().into_iter().next().unwrap();
+        let context = OptimizerContext::new(session.clone(), Arc::from(sql), Default::default());
+        gen_batch_query_plan(session, context.into(), stmt)
+            .unwrap()
+            .1
+    }
+
+    #[tokio::test]
+    async fn test_local_point_get() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        let session = frontend.session_ref();
+        session
+            .run_statement("create table t (k int primary key, v int);", false)
+            .await
+            .unwrap();
+        session
+            .run_statement("set query_mode to distributed;", false)
+            .await
+            .unwrap();
+
+        for sql in [
+            "select v from t where k = 1",
+            "select v + 1 from t where k = 1 and v > 0",
+            "select v from t where k in (1, 2, 3)",
+        ] {
+            assert_eq!(query_mode_of(&session, sql), QueryMode::Local, "{}", sql);
+        }
+        for sql in [
+            "select v from t",
+            "select v from t where k > 1",
+            "select count(*) from t where k = 1",
+        ] {
+            assert_eq!(
+                query_mode_of(&session, sql),
+                QueryMode::Distributed,
+                "{}",
+                sql
+            );
+        }
+
+        session
+            .run_statement("set rw_batch_local_point_get to false;", false)
+            .await
+            .unwrap();
+        assert_eq!(
+            query_mode_of(&session, "select v from t where k = 1"),
+            QueryMode::Distributed
+        );
+    }
+    async fn run_select(session: &Arc<SessionImpl>, sql: &str) -> Vec<Row> {
+        let mut rsp = session.run_statement(sql, false).await.unwrap();
+        let mut rows = vec![];
+        while let Some(row_set) = rsp.values_stream().next().await {
+            rows.extend(row_set.unwrap());
+        }
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_invalidation() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        let session = frontend.session_ref();
+        for sql in [
+            "set query_mode to local;",
+            "set rw_batch_enable_result_cache to true;",
+        ] {
+            session.run_statement(sql, false).await.unwrap();
+        }
+        let metrics = session.env().frontend_metrics.clone();
+        let hit_count = || metrics.query_result_cache_hit_count.get();
+        let sql = "select 1 + 1;";
+
+        run_select(&session, sql).await;
+        assert_eq!(run_select(&session, sql).await.len(), 1);
+        assert_eq!(hit_count(), 1);
+
+        // A barrier is committed.
+        session
+            .env()
+            .hummock_snapshot_manager()
+            .update_epoch(HummockSnapshot {
+                committed_epoch: 1,
+                current_epoch: 1,
+            });
+        run_select(&session, sql).await;
+        assert_eq!(hit_count(), 1);
+        run_select(&session, sql).await;
+        assert_eq!(hit_count(), 2);
+
+        // A fragment is rescheduled.
+        session
+            .env()
+            .worker_node_manager()
+            .insert_fragment_mapping(1, vec![0]);
+        run_select(&session, sql).await;
+        assert_eq!(hit_count(), 2);
+        run_select(&session, sql).await;
+        assert_eq!(hit_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_skips_now() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        let session = frontend.session_ref();
+        for sql in [
+            "set query_mode to local;",
+            "set rw_batch_enable_result_cache to true;",
+        ] {
+            session.run_statement(sql, false).await.unwrap();
+        }
+        let metrics = session.env().frontend_metrics.clone();
+
+        for _ in 0..3 {
+            run_select(&session, "select now() > '2022-01-01'::timestamptz;").await;
+        }
+        assert_eq!(metrics.query_result_cache_hit_count.get(), 0);
+    }
+}
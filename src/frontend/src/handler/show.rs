@@ -16,7 +16,7 @@ use itertools::Itertools;
 use pgwire::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
 use pgwire::pg_response::{PgResponse, StatementType};
 use pgwire::types::Row;
-use risingwave_common::catalog::{ColumnDesc, DEFAULT_SCHEMA_NAME};
+use risingwave_common::catalog::{ColumnDesc, TableId, DEFAULT_SCHEMA_NAME};
 use risingwave_common::error::Result;
 use risingwave_sqlparser::ast::{Ident, ObjectName, ShowObject};
 
@@ -100,6 +100,11 @@ pub fn handle_show_object(context: OptimizerContext, command: ShowObject) -> Res
             .iter_sink()
             .map(|t| t.name.clone())
             .collect(),
+        ShowObject::Subscription { schema } => catalog_reader
+            .get_schema_by_name(session.database(), &schema_or_default(&schema))?
+            .iter_subscription()
+            .map(|t| t.name.clone())
+            .collect(),
         ShowObject::Columns { table } => {
             let columns = get_columns_from_table(&session, table)?;
             let rows = col_descs_to_rows(columns);
@@ -114,6 +119,15 @@ pub fn handle_show_object(context: OptimizerContext, command: ShowObject) -> Res
                 ],
             ));
         }
+        ShowObject::TableComplexity => {
+            unreachable!("`SHOW TABLE COMPLEXITY` is handled by `handle_show_table_complexity`")
+        }
+        ShowObject::TableFragments { .. } => {
+            unreachable!("`SHOW TABLE FRAGMENTS` is handled by `handle_show_table_fragments`")
+        }
+        ShowObject::ClusterStatus => {
+            unreachable!("`SHOW CLUSTER STATUS` is handled by `handle_show_cluster_status`")
+        }
     };
 
     let rows = names
@@ -129,6 +143,156 @@ pub fn handle_show_object(context: OptimizerContext, command: ShowObject) -> Res
     ))
 }
 
+pub fn table_complexity_fields() -> Vec<PgFieldDescriptor> {
+    vec![
+        PgFieldDescriptor::new("Name".to_owned(), TypeOid::Varchar),
+        PgFieldDescriptor::new("Fragments".to_owned(), TypeOid::Int),
+        PgFieldDescriptor::new("Actors".to_owned(), TypeOid::Int),
+    ]
+}
+
+/// Lists the streaming jobs with their numbers of fragments and actors, the most complex first.
+pub async fn handle_show_table_complexity(context: OptimizerContext) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let tables = session.env().meta_client().get_table_complexity().await?;
+
+    let catalog_reader = session.env().catalog_reader().read_guard();
+    let rows = tables
+        .into_iter()
+        .map(|table| {
+            // Sinks are not in the table catalog, so they are shown by id.
+            let name = catalog_reader
+                .get_table_name_by_id(TableId::new(table.table_id))
+                .unwrap_or_else(|_| table.table_id.to_string());
+            Row::new(vec![
+                Some(name.into()),
+                Some(table.fragment_count.to_string().into()),
+                Some(table.actor_count.to_string().into()),
+            ])
+        })
+        .collect_vec();
+
+    Ok(PgResponse::new_for_stream(
+        StatementType::SHOW_COMMAND,
+        Some(rows.len() as i32),
+        rows.into(),
+        table_complexity_fields(),
+    ))
+}
+
+pub fn table_fragments_fields() -> Vec<PgFieldDescriptor> {
+    vec![PgFieldDescriptor::new(
+        "Table Fragments".to_owned(),
+        TypeOid::Varchar,
+    )]
+}
+
+/// Shows the fragments and actors of a streaming job with their runtime state, one line per row.
+pub async fn handle_show_table_fragments(
+    context: OptimizerContext,
+    table_id: u32,
+) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let dump = session
+        .env()
+        .meta_client()
+        .dump_table_fragments(table_id)
+        .await?;
+
+    let rows = dump
+        .lines()
+        .map(|line| Row::new(vec![Some(line.to_string().into())]))
+        .collect_vec();
+
+    Ok(PgResponse::new_for_stream(
+        StatementType::SHOW_COMMAND,
+        Some(rows.len() as i32),
+        rows.into(),
+        table_fragments_fields(),
+    ))
+}
+
+pub fn cluster_status_fields() -> Vec<PgFieldDescriptor> {
+    vec![
+        PgFieldDescriptor::new("Category".to_owned(), TypeOid::Varchar),
+        PgFieldDescriptor::new("Name".to_owned(), TypeOid::Varchar),
+        PgFieldDescriptor::new("Value".to_owned(), TypeOid::Varchar),
+    ]
+}
+
+/// Shows the workers with their states, the numbers of streaming jobs by state, the last
+/// checkpoint, the barrier latency and the L0 SSTs of each compaction group, one fact per row.
+pub async fn handle_show_cluster_status(context: OptimizerContext) -> Result<RwPgResponse> {
+    let session = context.session_ctx;
+    let status = session.env().meta_client().get_cluster_status().await?;
+
+    let row = |category: &str, name: String, value: String| {
+        Row::new(vec![
+            Some(category.to_string().into()),
+            Some(name.into()),
+            Some(value.into()),
+        ])
+    };
+    let mut rows = status
+        .workers
+        .iter()
+        .map(|worker| {
+            let address = worker
+                .host
+                .as_ref()
+                .map_or_else(String::new, |host| format!("{}:{}", host.host, host.port));
+            row(
+                "worker",
+                address,
+                format!("{:?} {:?}", worker.r#type(), worker.state()),
+            )
+        })
+        .collect_vec();
+    rows.extend([
+        row(
+            "streaming_job",
+            "creating".to_string(),
+            status.creating_streaming_jobs.to_string(),
+        ),
+        row(
+            "streaming_job",
+            "created".to_string(),
+            status.created_streaming_jobs.to_string(),
+        ),
+        row(
+            "checkpoint",
+            "committed_epoch".to_string(),
+            status.committed_epoch.to_string(),
+        ),
+        row(
+            "checkpoint",
+            "committed_epoch_age_ms".to_string(),
+            status.committed_epoch_age_ms.to_string(),
+        ),
+        row(
+            "barrier",
+            "latency_p99_secs".to_string(),
+            format!("{:.3}", status.barrier_latency_p99_secs),
+        ),
+    ]);
+    rows.extend(
+        status
+            .l0_file_counts
+            .iter()
+            .sorted()
+            .map(|(group_id, count)| {
+                row("hummock_l0_files", group_id.to_string(), count.to_string())
+            }),
+    );
+
+    Ok(PgResponse::new_for_stream(
+        StatementType::SHOW_COMMAND,
+        Some(rows.len() as i32),
+        rows.into(),
+        cluster_status_fields(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -213,4 +377,48 @@ mod tests {
 
         assert_eq!(columns, expected_columns);
     }
+
+    #[tokio::test]
+    async fn test_show_table_complexity() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+        frontend.run_sql("create table t (v1 int)").await.unwrap();
+
+        // The mock meta client knows no fragments.
+        let rows = frontend
+            .query_formatted_result("SHOW TABLE COMPLEXITY")
+            .await;
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_show_table_fragments() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+
+        let rows = frontend
+            .query_formatted_result("SHOW TABLE FRAGMENTS 1001")
+            .await;
+        assert_eq!(
+            rows,
+            vec!["Row([Some(b\"TableFragments 1001 (Created)\")])".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_show_cluster_status() {
+        let frontend = LocalFrontend::new(Default::default()).await;
+
+        let rows = frontend.query_formatted_result("SHOW CLUSTER STATUS").await;
+        assert_eq!(
+            rows,
+            vec![
+                "Row([Some(b\"worker\"), Some(b\"127.0.0.1:5688\"), Some(b\"ComputeNode Running\")])",
+                "Row([Some(b\"streaming_job\"), Some(b\"creating\"), Some(b\"1\")])",
+                "Row([Some(b\"streaming_job\"), Some(b\"created\"), Some(b\"2\")])",
+                "Row([Some(b\"checkpoint\"), Some(b\"committed_epoch\"), Some(b\"65536\")])",
+                "Row([Some(b\"checkpoint\"), Some(b\"committed_epoch_age_ms\"), Some(b\"1000\")])",
+                "Row([Some(b\"barrier\"), Some(b\"latency_p99_secs\"), Some(b\"0.500\")])",
+                "Row([Some(b\"hummock_l0_files\"), Some(b\"2\"), Some(b\"3\")])",
+            ]
+        );
+    }
 }
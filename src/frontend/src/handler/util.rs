@@ -25,8 +25,9 @@ use pgwire::types::Row;
 use pin_project_lite::pin_project;
 use risingwave_common::array::DataChunk;
 use risingwave_common::catalog::{ColumnDesc, Field};
+use risingwave_common::session_config::TimeZone;
 use risingwave_common::types::{DataType, ScalarRefImpl};
-use risingwave_expr::vector_op::cast::{timestampz_to_utc_binary, timestampz_to_utc_string};
+use risingwave_expr::vector_op::cast::{timestampz_to_string, timestampz_to_utc_binary};
 
 pin_project! {
     /// Wrapper struct that converts a stream of DataChunk to a stream of RowSet based on formatting
@@ -43,17 +44,24 @@ pin_project! {
         chunk_stream: VS,
         column_types: Vec<DataType>,
         format: bool,
+        time_zone: TimeZone,
     }
 }
 impl<VS> DataChunkToRowSetAdapter<VS>
 where
     VS: Stream<Item = Result<DataChunk, BoxedError>>,
 {
-    pub fn new(chunk_stream: VS, column_types: Vec<DataType>, format: bool) -> Self {
+    pub fn new(
+        chunk_stream: VS,
+        column_types: Vec<DataType>,
+        format: bool,
+        time_zone: TimeZone,
+    ) -> Self {
         Self {
             chunk_stream,
             column_types,
             format,
+            time_zone,
         }
     }
 }
@@ -70,9 +78,12 @@ where
             Poll::Pending => Poll::Pending,
             Poll::Ready(chunk) => match chunk {
                 Some(chunk_result) => match chunk_result {
-                    Ok(chunk) => {
-                        Poll::Ready(Some(Ok(to_pg_rows(this.column_types, chunk, *this.format))))
-                    }
+                    Ok(chunk) => Poll::Ready(Some(Ok(to_pg_rows(
+                        this.column_types,
+                        chunk,
+                        *this.format,
+                        *this.time_zone,
+                    )))),
                     Err(err) => Poll::Ready(Some(Err(err))),
                 },
                 None => Poll::Ready(None),
@@ -81,14 +92,22 @@ where
     }
 }
 
-/// Format scalars according to postgres convention.
-fn pg_value_format(data_type: &DataType, d: ScalarRefImpl<'_>, format: bool) -> Bytes {
+/// Format scalars according to postgres convention. Timestamps with time zone are rendered in the
+/// session time zone in TEXT format.
+fn pg_value_format(
+    data_type: &DataType,
+    d: ScalarRefImpl<'_>,
+    format: bool,
+    time_zone: TimeZone,
+) -> Bytes {
     // format == false means TEXT format
     // format == true means BINARY format
     if !format {
         match (data_type, d) {
             (DataType::Boolean, ScalarRefImpl::Bool(b)) => if b { "t" } else { "f" }.into(),
-            (DataType::Timestampz, ScalarRefImpl::Int64(us)) => timestampz_to_utc_string(us).into(),
+            (DataType::Timestampz, ScalarRefImpl::Int64(us)) => {
+                timestampz_to_string(us, time_zone.tz()).into()
+            }
             _ => d.to_string().into(),
         }
     } else {
@@ -99,14 +118,19 @@ fn pg_value_format(data_type: &DataType, d: ScalarRefImpl<'_>, format: bool) ->
     }
 }
 
-fn to_pg_rows(column_types: &[DataType], chunk: DataChunk, format: bool) -> Vec<Row> {
+pub fn to_pg_rows(
+    column_types: &[DataType],
+    chunk: DataChunk,
+    format: bool,
+    time_zone: TimeZone,
+) -> Vec<Row> {
     chunk
         .rows()
         .map(|r| {
             Row::new(
                 r.values()
                     .zip_eq(column_types)
-                    .map(|(data, t)| data.map(|data| pg_value_format(t, data, format)))
+                    .map(|(data, t)| data.map(|data| pg_value_format(t, data, format, time_zone)))
                     .collect_vec(),
             )
         })
@@ -193,6 +217,7 @@ mod tests {
             ],
             chunk,
             false,
+            TimeZone::default(),
         );
         let expected: Vec<Vec<Option<Bytes>>> = vec![
             vec![
@@ -222,7 +247,9 @@ mod tests {
     fn test_value_format() {
         use {DataType as T, ScalarRefImpl as S};
 
-        let f = pg_value_format;
+        fn f(t: &DataType, d: ScalarRefImpl<'_>, format: bool) -> Bytes {
+            pg_value_format(t, d, format, TimeZone::default())
+        }
         assert_eq!(&f(&T::Float32, S::Float32(1_f32.into()), false), "1");
         assert_eq!(&f(&T::Float32, S::Float32(f32::NAN.into()), false), "NaN");
         assert_eq!(&f(&T::Float64, S::Float64(f64::NAN.into()), false), "NaN");
@@ -245,4 +272,39 @@ mod tests {
         assert_eq!(&f(&T::Boolean, S::Bool(true), false), "t");
         assert_eq!(&f(&T::Boolean, S::Bool(false), false), "f");
     }
+
+    #[test]
+    fn test_timestampz_value_format() {
+        use {DataType as T, ScalarRefImpl as S};
+
+        fn f(d: ScalarRefImpl<'_>, time_zone: &str) -> Bytes {
+            let time_zone = TimeZone::try_from([time_zone].as_slice()).unwrap();
+            pg_value_format(&DataType::Timestampz, d, false, time_zone)
+        }
+        // 2022-11-06 08:30:00 UTC, half an hour before daylight saving time ends in US/Pacific.
+        let usecs = 1_667_723_400_000_000;
+        assert_eq!(&f(S::Int64(usecs), "UTC"), "2022-11-06 08:30:00+00:00");
+        assert_eq!(
+            &f(S::Int64(usecs), "Asia/Shanghai"),
+            "2022-11-06 16:30:00+08:00"
+        );
+        assert_eq!(
+            &f(S::Int64(usecs), "US/Pacific"),
+            "2022-11-06 01:30:00-07:00"
+        );
+        assert_eq!(
+            &f(S::Int64(usecs + 3_600_000_000), "US/Pacific"),
+            "2022-11-06 01:30:00-08:00"
+        );
+        // The binary format is independent of the time zone.
+        assert_eq!(
+            pg_value_format(&T::Timestampz, S::Int64(usecs), true, TimeZone::default()),
+            pg_value_format(
+                &T::Timestampz,
+                S::Int64(usecs),
+                true,
+                TimeZone::try_from(["Asia/Shanghai"].as_slice()).unwrap()
+            )
+        );
+    }
 }
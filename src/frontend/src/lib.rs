@@ -92,6 +92,11 @@ pub struct FrontendOpts {
     /// >0 = open metrics
     #[clap(long, default_value = "0")]
     pub metrics_level: u32,
+
+    /// Keep serving read-only queries with the cached catalog when the connection to meta is
+    /// lost, and reject the statements that require meta until reconnected.
+    #[clap(long)]
+    pub read_only_standby: bool,
 }
 
 impl Default for FrontendOpts {
@@ -14,8 +14,12 @@
 
 use std::collections::HashMap;
 
-use risingwave_pb::hummock::HummockSnapshot;
+use risingwave_pb::hummock::get_table_write_stats_response::TableWriteStats;
+use risingwave_pb::hummock::{HummockSnapshot, HummockVersion};
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
+use risingwave_pb::meta::get_table_complexity_response::TableComplexity;
 use risingwave_pb::meta::list_table_fragments_response::TableFragmentInfo;
+use risingwave_pb::meta::GetClusterStatusResponse;
 use risingwave_rpc_client::error::Result;
 use risingwave_rpc_client::{HummockMetaClient, MetaClient};
 
@@ -40,6 +44,18 @@ pub trait FrontendMetaClient: Send + Sync {
     async fn unpin_snapshot(&self) -> Result<()>;
 
     async fn unpin_snapshot_before(&self, epoch: u64) -> Result<()>;
+
+    async fn get_table_write_stats(&self) -> Result<Vec<TableWriteStats>>;
+
+    async fn get_current_version(&self) -> Result<HummockVersion>;
+
+    async fn get_table_complexity(&self) -> Result<Vec<TableComplexity>>;
+
+    async fn get_backfill_progress(&self) -> Result<Vec<BackfillProgress>>;
+
+    async fn dump_table_fragments(&self, table_id: u32) -> Result<String>;
+
+    async fn get_cluster_status(&self) -> Result<GetClusterStatusResponse>;
 }
 
 pub struct FrontendMetaClientImpl(pub MetaClient);
@@ -72,4 +88,28 @@ impl FrontendMetaClient for FrontendMetaClientImpl {
     async fn unpin_snapshot_before(&self, epoch: u64) -> Result<()> {
         self.0.unpin_snapshot_before(epoch).await
     }
+
+    async fn get_table_write_stats(&self) -> Result<Vec<TableWriteStats>> {
+        self.0.get_table_write_stats().await
+    }
+
+    async fn get_current_version(&self) -> Result<HummockVersion> {
+        self.0.get_current_version().await
+    }
+
+    async fn get_table_complexity(&self) -> Result<Vec<TableComplexity>> {
+        self.0.get_table_complexity().await
+    }
+
+    async fn get_backfill_progress(&self) -> Result<Vec<BackfillProgress>> {
+        self.0.get_backfill_progress().await
+    }
+
+    async fn dump_table_fragments(&self, table_id: u32) -> Result<String> {
+        self.0.dump_table_fragments(table_id).await
+    }
+
+    async fn get_cluster_status(&self) -> Result<GetClusterStatusResponse> {
+        self.0.get_cluster_status().await
+    }
 }
@@ -14,13 +14,17 @@
 
 use prometheus::core::{AtomicU64, GenericCounter};
 use prometheus::{
-    register_histogram_with_registry, register_int_counter_with_registry, Histogram, Registry,
+    register_histogram_with_registry, register_int_counter_with_registry,
+    register_int_gauge_with_registry, Histogram, IntGauge, Registry,
 };
 
 pub struct FrontendMetrics {
     pub registry: Registry,
     pub query_counter_local_execution: GenericCounter<AtomicU64>,
     pub latency_local_execution: Histogram,
+    pub query_result_cache_hit_count: GenericCounter<AtomicU64>,
+    pub query_result_cache_miss_count: GenericCounter<AtomicU64>,
+    pub query_result_cache_memory_usage: IntGauge,
 }
 
 impl FrontendMetrics {
@@ -39,10 +43,34 @@ impl FrontendMetrics {
         )
         .unwrap();
 
+        let query_result_cache_hit_count = register_int_counter_with_registry!(
+            "frontend_query_result_cache_hit_count",
+            "Total number of queries served by the query result cache",
+            &registry
+        )
+        .unwrap();
+
+        let query_result_cache_miss_count = register_int_counter_with_registry!(
+            "frontend_query_result_cache_miss_count",
+            "Total number of queries missing the query result cache",
+            &registry
+        )
+        .unwrap();
+
+        let query_result_cache_memory_usage = register_int_gauge_with_registry!(
+            "frontend_query_result_cache_memory_usage",
+            "Total size in bytes of the results in the query result cache",
+            &registry
+        )
+        .unwrap();
+
         Self {
             registry,
             query_counter_local_execution,
             latency_local_execution,
+            query_result_cache_hit_count,
+            query_result_cache_miss_count,
+            query_result_cache_memory_usage,
         }
     }
 
@@ -0,0 +1,120 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use risingwave_common::error::{ErrorCode, Result};
+
+pub type MetaConnectionStatusRef = Arc<MetaConnectionStatus>;
+
+/// Tracks whether the frontend is receiving notifications from the meta service.
+///
+/// While the notification stream is broken, the catalog, worker nodes and fragment mappings are
+/// the ones received before, so they may be stale. In read-only standby mode, the frontend keeps
+/// serving batch queries with them and rejects the statements that require the meta service.
+#[derive(Debug)]
+pub struct MetaConnectionStatus {
+    read_only_standby: bool,
+
+    /// The time the notification stream was lost. `None` if connected.
+    disconnected_since: Mutex<Option<Instant>>,
+}
+
+impl MetaConnectionStatus {
+    pub fn new(read_only_standby: bool) -> Self {
+        Self {
+            read_only_standby,
+            disconnected_since: Mutex::new(None),
+        }
+    }
+
+    pub fn read_only_standby(&self) -> bool {
+        self.read_only_standby
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.disconnected_since.lock().is_none()
+    }
+
+    /// How long the local data has not been updated from the meta service. Zero if connected.
+    pub fn staleness(&self) -> Duration {
+        self.disconnected_since
+            .lock()
+            .map(|since| since.elapsed())
+            .unwrap_or_default()
+    }
+
+    pub fn set_connected(&self) {
+        if let Some(since) = self.disconnected_since.lock().take() {
+            tracing::info!(
+                "reconnected to meta after {:?}, local data caught up",
+                since.elapsed()
+            );
+        }
+    }
+
+    pub fn set_disconnected(&self) {
+        let mut disconnected_since = self.disconnected_since.lock();
+        if disconnected_since.is_none() {
+            if self.read_only_standby {
+                tracing::warn!("lost connection to meta, serving read-only queries from cache");
+            }
+            *disconnected_since = Some(Instant::now());
+        }
+    }
+
+    /// Fails fast with [`ErrorCode::MetaUnavailable`] if the frontend is a read-only standby and
+    /// the meta service is not connected, instead of waiting for the RPCs to time out.
+    pub fn check_writable(&self) -> Result<()> {
+        if self.read_only_standby && !self.is_connected() {
+            return Err(ErrorCode::MetaUnavailable(format!(
+                "the frontend is in read-only standby mode and has lost the connection to meta \
+                for {:?}, only read-only queries are allowed",
+                self.staleness()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_writable() {
+        let standby = MetaConnectionStatus::new(true);
+        let normal = MetaConnectionStatus::new(false);
+        for status in [&standby, &normal] {
+            assert!(status.is_connected());
+            assert_eq!(status.staleness(), Duration::ZERO);
+            status.check_writable().unwrap();
+            status.set_disconnected();
+            assert!(!status.is_connected());
+        }
+
+        assert!(matches!(
+            standby.check_writable().unwrap_err().inner(),
+            ErrorCode::MetaUnavailable(_)
+        ));
+        normal.check_writable().unwrap();
+
+        standby.set_connected();
+        standby.check_writable().unwrap();
+        assert_eq!(standby.staleness(), Duration::ZERO);
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::sync::Arc;
 
+use itertools::Itertools;
 use parking_lot::RwLock;
 use risingwave_common::catalog::CatalogVersion;
 use risingwave_common::error::{ErrorCode, Result};
@@ -25,6 +26,7 @@ use risingwave_pb::meta::SubscribeResponse;
 use tokio::sync::watch::Sender;
 
 use crate::catalog::root_catalog::Catalog;
+use crate::observer::meta_connection_status::MetaConnectionStatusRef;
 use crate::scheduler::worker_node_manager::WorkerNodeManagerRef;
 use crate::scheduler::HummockSnapshotManagerRef;
 use crate::user::user_manager::UserInfoManager;
@@ -37,6 +39,9 @@ pub(crate) struct FrontendObserverNode {
     user_info_manager: Arc<RwLock<UserInfoManager>>,
     user_info_updated_tx: Sender<UserInfoVersion>,
     hummock_snapshot_manager: HummockSnapshotManagerRef,
+    meta_connection_status: MetaConnectionStatusRef,
+    /// Whether the first snapshot has been received.
+    initialized: bool,
 }
 
 impl ObserverState for FrontendObserverNode {
@@ -62,7 +67,9 @@ impl ObserverState for FrontendObserverNode {
             Info::User(_) => {
                 self.handle_user_notification(resp);
             }
-            Info::ParallelUnitMapping(_) => self.handle_fragment_mapping_notification(resp),
+            Info::ParallelUnitMapping(_) | Info::BatchParallelUnitMapping(_) => {
+                self.handle_fragment_mapping_notification(resp)
+            }
             Info::Snapshot(_) => {
                 panic!(
                     "receiving a snapshot in the middle is unsupported now {:?}",
@@ -79,60 +86,78 @@ impl ObserverState for FrontendObserverNode {
     }
 
     fn handle_initialization_notification(&mut self, resp: SubscribeResponse) -> Result<()> {
+        let Some(Info::Snapshot(snapshot)) = resp.info else {
+            return Err(ErrorCode::InternalError(format!(
+                "the first notify should be frontend snapshot, but get {:?}",
+                resp
+            ))
+            .into());
+        };
+
         let mut catalog_guard = self.catalog.write();
         let mut user_guard = self.user_info_manager.write();
-        catalog_guard.clear();
-        user_guard.clear();
-        match resp.info {
-            Some(Info::Snapshot(snapshot)) => {
-                for db in snapshot.databases {
-                    catalog_guard.create_database(db)
-                }
-                for schema in snapshot.schemas {
-                    catalog_guard.create_schema(schema)
-                }
-                for table in snapshot.tables {
-                    catalog_guard.create_table(&table)
-                }
-                for source in snapshot.sources {
-                    catalog_guard.create_source(source)
-                }
-                for user in snapshot.users {
-                    user_guard.create_user(user)
-                }
-                for index in snapshot.indexes {
-                    catalog_guard.create_index(&index)
-                }
-                self.worker_node_manager.refresh(
-                    snapshot.nodes,
-                    snapshot
-                        .parallel_unit_mappings
-                        .iter()
-                        .map(|mapping| {
-                            (
-                                mapping.fragment_id,
-                                decompress_data(&mapping.original_indices, &mapping.data),
-                            )
-                        })
-                        .collect(),
-                );
-                self.hummock_snapshot_manager
-                    .update_epoch(snapshot.hummock_snapshot.unwrap());
+        // On resubscribe, the catalog is only rebuilt if the meta has moved on. A snapshot older
+        // than the local catalog is rejected so that the catalog never goes back.
+        if self.initialized && resp.version < catalog_guard.version() {
+            return Err(ErrorCode::InternalError(format!(
+                "snapshot version {} is older than the local catalog version {}",
+                resp.version,
+                catalog_guard.version()
+            ))
+            .into());
+        }
+        if !self.initialized || resp.version > catalog_guard.version() {
+            catalog_guard.clear();
+            user_guard.clear();
+            for db in snapshot.databases {
+                catalog_guard.create_database(db)
             }
-            _ => {
-                return Err(ErrorCode::InternalError(format!(
-                    "the first notify should be frontend snapshot, but get {:?}",
-                    resp
-                ))
-                .into())
+            for schema in snapshot.schemas {
+                catalog_guard.create_schema(schema)
+            }
+            for table in snapshot.tables {
+                catalog_guard.create_table(&table)
+            }
+            for source in snapshot.sources {
+                catalog_guard.create_source(source)
             }
+            for sink in snapshot.sinks {
+                catalog_guard.create_sink(sink)
+            }
+            for user in snapshot.users {
+                user_guard.create_user(user)
+            }
+            for index in snapshot.indexes {
+                catalog_guard.create_index(&index)
+            }
+            catalog_guard.set_version(resp.version);
+            self.catalog_updated_tx.send(resp.version).unwrap();
+            user_guard.set_version(resp.version);
+            self.user_info_updated_tx.send(resp.version).unwrap();
         }
-        catalog_guard.set_version(resp.version);
-        self.catalog_updated_tx.send(resp.version).unwrap();
-        user_guard.set_version(resp.version);
-        self.user_info_updated_tx.send(resp.version).unwrap();
+        self.worker_node_manager.refresh(
+            snapshot.nodes,
+            snapshot
+                .parallel_unit_mappings
+                .iter()
+                .map(|mapping| {
+                    (
+                        mapping.fragment_id,
+                        decompress_data(&mapping.original_indices, &mapping.data),
+                    )
+                })
+                .collect(),
+        );
+        self.hummock_snapshot_manager
+            .update_epoch(snapshot.hummock_snapshot.unwrap());
+        self.initialized = true;
+        self.meta_connection_status.set_connected();
         Ok(())
     }
+
+    fn handle_connection_lost(&mut self) {
+        self.meta_connection_status.set_disconnected();
+    }
 }
 
 impl FrontendObserverNode {
@@ -143,6 +168,7 @@ impl FrontendObserverNode {
         user_info_manager: Arc<RwLock<UserInfoManager>>,
         user_info_updated_tx: Sender<UserInfoVersion>,
         hummock_snapshot_manager: HummockSnapshotManagerRef,
+        meta_connection_status: MetaConnectionStatusRef,
     ) -> Self {
         Self {
             worker_node_manager,
@@ -151,6 +177,8 @@ impl FrontendObserverNode {
             user_info_manager,
             user_info_updated_tx,
             hummock_snapshot_manager,
+            meta_connection_status,
+            initialized: false,
         }
     }
 
@@ -184,6 +212,7 @@ impl FrontendObserverNode {
                 Operation::Delete => {
                     catalog_guard.drop_source(source.database_id, source.schema_id, source.id)
                 }
+                Operation::Update => catalog_guard.update_source(source),
                 _ => panic!("receive an unsupported notify {:?}", resp),
             },
             Info::Sink(sink) => match resp.operation() {
@@ -268,6 +297,38 @@ impl FrontendObserverNode {
                 }
                 _ => panic!("receive an unsupported notify {:?}", resp),
             },
+            Info::BatchParallelUnitMapping(batch) => {
+                let mappings = || {
+                    batch
+                        .mappings
+                        .iter()
+                        .map(|mapping| {
+                            (
+                                mapping.fragment_id,
+                                decompress_data(&mapping.original_indices, &mapping.data),
+                            )
+                        })
+                        .collect()
+                };
+                match resp.operation() {
+                    Operation::Add => self
+                        .worker_node_manager
+                        .insert_fragment_mapping_bulk(mappings()),
+                    Operation::Delete => {
+                        let fragment_ids = batch
+                            .mappings
+                            .iter()
+                            .map(|mapping| mapping.fragment_id)
+                            .collect_vec();
+                        self.worker_node_manager
+                            .remove_fragment_mapping_bulk(&fragment_ids);
+                    }
+                    Operation::Update => self
+                        .worker_node_manager
+                        .update_fragment_mapping_bulk(mappings()),
+                    _ => panic!("receive an unsupported notify {:?}", resp),
+                }
+            }
             _ => unreachable!(),
         }
     }
@@ -301,6 +362,7 @@ impl FrontendObserverNode {
         match operation {
             Operation::Add => self.worker_node_manager.add_worker_node(node),
             Operation::Delete => self.worker_node_manager.remove_worker_node(node),
+            Operation::Update => self.worker_node_manager.update_worker_node(node),
             _ => (),
         }
     }
@@ -20,6 +20,7 @@ pub mod property;
 mod delta_join_solver;
 mod heuristic;
 mod max_one_row_visitor;
+mod now_visitor;
 mod plan_correlated_id_finder;
 mod plan_rewriter;
 mod plan_visitor;
@@ -33,15 +34,51 @@ use risingwave_common::error::{ErrorCode, Result};
 
 use self::heuristic::{ApplyOrder, HeuristicOptimizer};
 use self::plan_node::{BatchProject, Convention, LogicalProject, StreamMaterialize};
-use self::plan_visitor::{has_batch_seq_scan, has_batch_seq_scan_where, has_logical_over_agg};
+use self::plan_visitor::{
+    has_batch_seq_scan, has_batch_seq_scan_where, has_logical_over_agg_where,
+};
 use self::property::RequiredDist;
 use self::rule::*;
 use crate::optimizer::max_one_row_visitor::HasMaxOneRowApply;
+use crate::optimizer::now_visitor::{streaming_now_error, HasNowOutsideFilter};
 use crate::optimizer::plan_node::{BatchExchange, PlanNodeType};
 use crate::optimizer::plan_visitor::{has_batch_exchange, has_logical_apply, PlanVisitor};
 use crate::optimizer::property::Distribution;
 use crate::utils::Condition;
 
+/// The max number of scan ranges of a point get, see [`is_point_get`].
+const POINT_GET_MAX_SCAN_RANGES: usize = 16;
+
+/// Returns `true` if the batch plan only reads a few rows located by the distribution key of a
+/// single table, e.g. `SELECT * FROM t WHERE pk = 1`. The vnodes to read are known from the scan
+/// ranges, so such a plan is cheap enough to run in local mode, which saves the overhead of
+/// scheduling the query, stages and tasks in distributed mode.
+pub fn is_point_get(plan: PlanRef) -> bool {
+    match plan.node_type() {
+        PlanNodeType::BatchExchange
+        | PlanNodeType::BatchProject
+        | PlanNodeType::BatchFilter
+        | PlanNodeType::BatchLimit => is_point_get(plan.inputs()[0].clone()),
+        PlanNodeType::BatchSeqScan => {
+            let scan = plan.as_batch_seq_scan().unwrap();
+            if scan.logical().is_sys_table() {
+                return false;
+            }
+            let table_desc = scan.logical().table_desc();
+            let pk_indices = table_desc.order_column_indices();
+            let scan_ranges = scan.scan_ranges();
+            !scan_ranges.is_empty()
+                && scan_ranges.len() <= POINT_GET_MAX_SCAN_RANGES
+                && scan_ranges.iter().all(|scan_range| {
+                    scan_range
+                        .try_compute_vnode(&table_desc.distribution_key, &pk_indices)
+                        .is_some()
+                })
+        }
+        _ => false,
+    }
+}
+
 /// `PlanRoot` is used to describe a plan. planner will construct a `PlanRoot` with `LogicalNode`.
 /// and required distribution and order. And `PlanRoot` can generate corresponding streaming or
 /// batch plan with optimization. the required Order and Distribution columns might be more than the
@@ -320,7 +357,11 @@ impl PlanRoot {
             ],
             ApplyOrder::TopDown,
         );
-        if has_logical_over_agg(plan.clone()) {
+        // Only the rank functions need to be transformed, while the others are supported by
+        // `StreamOverWindow`.
+        if has_logical_over_agg_where(plan.clone(), |over_agg| {
+            over_agg.window_function.function_type.is_rank_function()
+        }) {
             return Err(ErrorCode::InternalError(format!(
                 "OverAgg can not be transformed. Plan:\n{}",
                 plan.explain_to_string().unwrap()
@@ -439,6 +480,9 @@ impl PlanRoot {
         let mut plan = match self.plan.convention() {
             Convention::Logical => {
                 let plan = self.gen_optimized_logical_plan()?;
+                if HasNowOutsideFilter.visit(plan.clone()) {
+                    return Err(streaming_now_error(None));
+                }
                 let (plan, out_col_change) = plan.logical_rewrite_for_stream()?;
 
                 if explain_trace {
@@ -0,0 +1,71 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::error::{ErrorCode, RwError};
+
+use crate::optimizer::plan_node::{
+    LogicalAgg, LogicalJoin, LogicalProject, LogicalProjectSet, LogicalValues, PlanTreeNodeBinary,
+    PlanTreeNodeUnary,
+};
+use crate::optimizer::plan_visitor::PlanVisitor;
+
+/// The error of a streaming query using `now()` in a way that can't be planned into a dynamic
+/// filter. `expr` is the offending expression, if known.
+pub fn streaming_now_error(expr: Option<String>) -> RwError {
+    let mut msg = "`now()` in streaming queries is only supported as a lower bound of a column in \
+        WHERE or HAVING, e.g. `WHERE ts > now() - INTERVAL '1 hour'`"
+        .to_string();
+    if let Some(expr) = expr {
+        msg += &format!(", but got `{}`", expr);
+    }
+    ErrorCode::NotImplemented(msg, None.into()).into()
+}
+
+/// Returns true if the logical plan has `now()` outside of the filters, which are the only plan
+/// nodes that plan `now()` into dynamic filters when converted to stream.
+pub struct HasNowOutsideFilter;
+
+impl PlanVisitor<bool> for HasNowOutsideFilter {
+    fn merge(a: bool, b: bool) -> bool {
+        a | b
+    }
+
+    fn visit_logical_project(&mut self, plan: &LogicalProject) -> bool {
+        plan.exprs().iter().any(|expr| expr.has_now()) || self.visit(plan.input())
+    }
+
+    fn visit_logical_project_set(&mut self, plan: &LogicalProjectSet) -> bool {
+        plan.select_list().iter().any(|expr| expr.has_now()) || self.visit(plan.input())
+    }
+
+    fn visit_logical_agg(&mut self, plan: &LogicalAgg) -> bool {
+        plan.agg_calls().iter().any(|agg_call| {
+            agg_call
+                .filter
+                .conjunctions
+                .iter()
+                .any(|expr| expr.has_now())
+        }) || self.visit(plan.input())
+    }
+
+    fn visit_logical_join(&mut self, plan: &LogicalJoin) -> bool {
+        plan.on().conjunctions.iter().any(|expr| expr.has_now())
+            || self.visit(plan.left())
+            || self.visit(plan.right())
+    }
+
+    fn visit_logical_values(&mut self, plan: &LogicalValues) -> bool {
+        plan.rows().iter().flatten().any(|expr| expr.has_now())
+    }
+}
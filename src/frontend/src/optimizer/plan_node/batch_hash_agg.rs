@@ -14,15 +14,23 @@
 
 use std::fmt;
 
+use itertools::Itertools;
 use risingwave_common::error::Result;
 use risingwave_pb::batch_plan::plan_node::NodeBody;
 use risingwave_pb::batch_plan::HashAggNode;
 
 use super::generic::PlanAggCall;
-use super::{LogicalAgg, PlanBase, PlanRef, PlanTreeNodeUnary, ToBatchProst, ToDistributedBatch};
+use super::{
+    estimate_group_count, estimate_row_count, LogicalAgg, PlanBase, PlanRef, PlanTreeNodeUnary,
+    ToBatchProst, ToDistributedBatch,
+};
 use crate::optimizer::plan_node::ToLocalBatch;
 use crate::optimizer::property::{Distribution, Order, RequiredDist};
 
+/// Two-phase aggregation is used if the table statistics show that each group has at least this
+/// many rows on average, so that the partial aggregation shrinks the data to shuffle.
+const TWO_PHASE_AGG_MIN_ROWS_PER_GROUP: u64 = 8;
+
 #[derive(Debug, Clone)]
 pub struct BatchHashAgg {
     pub base: PlanBase,
@@ -51,6 +59,40 @@ impl BatchHashAgg {
     pub fn group_key(&self) -> &[usize] {
         self.logical.group_key()
     }
+
+    /// Whether the table statistics show that the groups are few compared with the input rows.
+    fn has_few_groups(&self) -> bool {
+        let input = self.input();
+        match (
+            estimate_row_count(&input),
+            estimate_group_count(&input, self.group_key()),
+        ) {
+            (Some(row_count), Some(group_count)) => {
+                row_count >= group_count.saturating_mul(TWO_PHASE_AGG_MIN_ROWS_PER_GROUP)
+            }
+            _ => false,
+        }
+    }
+
+    /// Partially aggregates the input in its own distribution, then shuffles the partial results
+    /// by the group key and aggregates them again.
+    fn to_two_phase_agg(&self, dist_input: PlanRef) -> Result<PlanRef> {
+        let partial_agg: PlanRef = self.clone_with_input(dist_input).into();
+        let n_group_key = self.group_key().len();
+        let total_group_key = (0..n_group_key).collect_vec();
+        let exchange = RequiredDist::shard_by_key(partial_agg.schema().len(), &total_group_key)
+            .enforce_if_not_satisfies(partial_agg, &Order::any())?;
+        let total_agg_calls = self
+            .agg_calls()
+            .iter()
+            .enumerate()
+            .map(|(partial_output_idx, agg_call)| {
+                agg_call.partial_to_total_agg_call(n_group_key + partial_output_idx)
+            })
+            .collect();
+        let total_agg_logical = LogicalAgg::new(total_agg_calls, total_group_key, exchange);
+        Ok(BatchHashAgg::new(total_agg_logical).into())
+    }
 }
 
 impl fmt::Display for BatchHashAgg {
@@ -71,6 +113,16 @@ impl PlanTreeNodeUnary for BatchHashAgg {
 impl_plan_tree_node_for_unary! { BatchHashAgg }
 impl ToDistributedBatch for BatchHashAgg {
     fn to_distributed(&self) -> Result<PlanRef> {
+        if self.logical.can_agg_two_phase() && self.has_few_groups() {
+            let dist_input = self.input().to_distributed()?;
+            let required_dist =
+                RequiredDist::shard_by_key(self.input().schema().len(), self.group_key());
+            if dist_input.distribution().satisfies(&RequiredDist::AnyShard)
+                && !dist_input.distribution().satisfies(&required_dist)
+            {
+                return self.to_two_phase_agg(dist_input);
+            }
+        }
         let new_input = self.input().to_distributed_with_required(
             &Order::any(),
             &RequiredDist::shard_by_key(self.input().schema().len(), self.group_key()),
@@ -284,6 +284,8 @@ impl PlanAggCall {
             return_type: Some(self.return_type.to_protobuf()),
             args: self.inputs.iter().map(InputRef::to_agg_arg_proto).collect(),
             distinct: self.distinct,
+            approx_relative_error: self.agg_kind.approx_relative_error(),
+            approx_percentile_fraction: self.agg_kind.approx_percentile_fraction(),
             order_by_fields: self
                 .order_by_fields
                 .iter()
@@ -298,8 +300,14 @@ impl PlanAggCall {
 
     pub fn partial_to_total_agg_call(&self, partial_output_idx: usize) -> PlanAggCall {
         let total_agg_kind = match &self.agg_kind {
-            AggKind::Min | AggKind::Max | AggKind::StringAgg | AggKind::FirstValue => self.agg_kind,
-            AggKind::Count | AggKind::ApproxCountDistinct | AggKind::Sum0 => AggKind::Sum0,
+            AggKind::Min
+            | AggKind::Max
+            | AggKind::StringAgg
+            | AggKind::FirstValue
+            | AggKind::BitAnd
+            | AggKind::BitOr
+            | AggKind::BitXor => self.agg_kind,
+            AggKind::Count | AggKind::ApproxCountDistinct(_) | AggKind::Sum0 => AggKind::Sum0,
             AggKind::Sum => AggKind::Sum,
             AggKind::Avg => {
                 panic!("Avg aggregation should have been rewritten to Sum+Count")
@@ -307,6 +315,9 @@ impl PlanAggCall {
             AggKind::ArrayAgg => {
                 panic!("2-phase ArrayAgg is not supported yet")
             }
+            AggKind::ApproxPercentile(..) => {
+                panic!("2-phase ApproxPercentile is not supported yet")
+            }
         };
         PlanAggCall {
             agg_kind: total_agg_kind,
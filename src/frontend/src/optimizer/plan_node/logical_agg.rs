@@ -27,7 +27,7 @@ use risingwave_pb::stream_plan::{agg_call_state, AggCallState as AggCallStatePro
 use super::generic::{self, GenericPlanRef, PlanAggCall, PlanAggCallDisplay, PlanAggOrderByField};
 use super::{
     BatchHashAgg, BatchSimpleAgg, ColPrunable, LogicalProjectBuilder, PlanBase, PlanRef,
-    PlanTreeNodeUnary, PredicatePushdown, StreamGlobalSimpleAgg, StreamHashAgg,
+    PlanTreeNodeUnary, PredicatePushdown, StreamDedup, StreamGlobalSimpleAgg, StreamHashAgg,
     StreamLocalSimpleAgg, StreamProject, ToBatch, ToStream,
 };
 use crate::catalog::table_catalog::TableCatalog;
@@ -204,10 +204,13 @@ impl LogicalAgg {
                 | AggKind::Max
                 | AggKind::StringAgg
                 | AggKind::ArrayAgg
-                | AggKind::FirstValue => {
+                | AggKind::FirstValue
+                | AggKind::BitAnd
+                | AggKind::BitOr
+                | AggKind::BitXor => {
                     panic!("State of AggKind enum {} is not `TableState`. It does not have registers in its state table.", agg_kind);
                 }
-                AggKind::ApproxCountDistinct => {
+                AggKind::ApproxCountDistinct(_) => {
                     internal_table_catalog_builder.add_column(&Field {
                         data_type: DataType::List {
                             datatype: Box::new(DataType::Int64),
@@ -217,6 +220,16 @@ impl LogicalAgg {
                         type_name: String::default(),
                     });
                 }
+                AggKind::ApproxPercentile(..) => {
+                    internal_table_catalog_builder.add_column(&Field {
+                        data_type: DataType::List {
+                            datatype: Box::new(DataType::Int64),
+                        },
+                        name: String::from("buckets"),
+                        sub_fields: vec![],
+                        type_name: String::default(),
+                    });
+                }
             }
 
             let mapping = ColIndexMapping::with_column_mapping(&column_mapping, in_fields.len());
@@ -278,7 +291,13 @@ impl LogicalAgg {
                 AggKind::Sum | AggKind::Sum0 | AggKind::Count | AggKind::Avg => {
                     AggCallState::ResultValue
                 }
-                AggKind::ApproxCountDistinct => {
+                AggKind::BitAnd | AggKind::BitOr | AggKind::BitXor => {
+                    unreachable!(
+                        "{} is not supported in streaming queries",
+                        agg_call.agg_kind
+                    )
+                }
+                AggKind::ApproxCountDistinct(_) => {
                     if !in_append_only {
                         // FIXME: now the approx count distinct on a non-append-only stream does not
                         // really has state and can handle failover or scale-out correctly
@@ -288,6 +307,12 @@ impl LogicalAgg {
                         AggCallState::Table(Box::new(state))
                     }
                 }
+                // The sketch supports retractions, so the table state works for both append-only
+                // and non-append-only inputs.
+                AggKind::ApproxPercentile(..) => {
+                    let state = get_table_state(agg_call.agg_kind);
+                    AggCallState::Table(Box::new(state))
+                }
             })
             .collect()
     }
@@ -386,6 +411,40 @@ impl LogicalAgg {
         }
     }
 
+    /// Plans the agg as a dedup if it only takes the first value of each group from an append-only
+    /// input, e.g. `SELECT DISTINCT ON`. Returns `None` if it's not the case.
+    fn try_gen_stream_dedup_plan(&self, stream_input: &PlanRef) -> Result<Option<PlanRef>> {
+        if !stream_input.append_only() || self.group_key().is_empty() {
+            return Ok(None);
+        }
+        let first_value_inputs: Option<Vec<_>> = self
+            .agg_calls()
+            .iter()
+            .map(|agg_call| {
+                (agg_call.agg_kind == AggKind::FirstValue
+                    && !agg_call.distinct
+                    && agg_call.order_by_fields.is_empty()
+                    && agg_call.filter.always_true())
+                .then(|| agg_call.inputs[0].index())
+            })
+            .collect();
+        let Some(first_value_inputs) = first_value_inputs else {
+            return Ok(None);
+        };
+
+        let input = RequiredDist::shard_by_key(stream_input.schema().len(), self.group_key())
+            .enforce_if_not_satisfies(stream_input.clone(), &Order::any())?;
+        let dedup = StreamDedup::new(input, self.group_key().to_vec());
+        let output_indices = self.group_key().iter().copied().chain(first_value_inputs);
+        Ok(Some(
+            StreamProject::new(LogicalProject::with_out_col_idx(
+                dedup.into(),
+                output_indices,
+            ))
+            .into(),
+        ))
+    }
+
     fn gen_dist_stream_agg_plan(&self, stream_input: PlanRef) -> Result<PlanRef> {
         // having group key, is not simple agg. we will just use shuffle agg
         // TODO(stonepage): in some situation the 2-phase agg is better. maybe some switch or
@@ -597,6 +656,7 @@ impl LogicalAggBuilder {
         let mut has_distinct = false;
         let mut has_order_by = false;
         let mut has_non_distinct_string_agg = false;
+        let mut has_non_distinct_approx_percentile = false;
         self.agg_calls.iter().for_each(|agg_call| {
             if agg_call.distinct {
                 has_distinct = true;
@@ -607,6 +667,9 @@ impl LogicalAggBuilder {
             if !agg_call.distinct && agg_call.agg_kind == AggKind::StringAgg {
                 has_non_distinct_string_agg = true;
             }
+            if !agg_call.distinct && matches!(agg_call.agg_kind, AggKind::ApproxPercentile(..)) {
+                has_non_distinct_approx_percentile = true;
+            }
         });
 
         // order by is disallowed occur with distinct because we can not diectly rewrite agg with
@@ -629,6 +692,16 @@ impl LogicalAggBuilder {
             .into());
         }
 
+        // the sketch of approx_percentile is not the output of the agg call, so it can't be
+        // rewritten as two-phase aggregates either.
+        if has_distinct && has_non_distinct_approx_percentile {
+            return Err(ErrorCode::NotImplemented(
+                "Non-distinct approx_percentile can't appear with distinct aggregates".into(),
+                TrackingIssue::none(),
+            )
+            .into());
+        }
+
         Ok(())
     }
 
@@ -650,7 +723,8 @@ impl LogicalAggBuilder {
             | AggKind::Sum
             | AggKind::Count
             | AggKind::Avg
-            | AggKind::ApproxCountDistinct => {
+            | AggKind::ApproxCountDistinct(_)
+            | AggKind::ApproxPercentile(..) => {
                 // this order by is unnecessary.
                 order_by = OrderBy::new(vec![]);
             }
@@ -1154,6 +1228,19 @@ impl ToBatch for LogicalAgg {
 
 impl ToStream for LogicalAgg {
     fn to_stream(&self) -> Result<PlanRef> {
+        if let Some(agg_call) = self.agg_calls().iter().find(|agg_call| {
+            matches!(
+                agg_call.agg_kind,
+                AggKind::BitAnd | AggKind::BitOr | AggKind::BitXor
+            )
+        }) {
+            return Err(ErrorCode::NotImplemented(
+                format!("{} in streaming queries", agg_call.agg_kind),
+                None.into(),
+            )
+            .into());
+        }
+
         // To rewrite StreamAgg, there are two things to do:
         // 1. insert a RowCount(Count with zero argument) at the beginning of agg_calls of
         // LogicalAgg.
@@ -1162,6 +1249,11 @@ impl ToStream for LogicalAgg {
         // LogicalAgg.
         // Please note that the index of group key need not be changed.
 
+        let stream_input = self.input().to_stream()?;
+        if let Some(plan) = self.try_gen_stream_dedup_plan(&stream_input)? {
+            return Ok(plan);
+        }
+
         let mut output_indices = (0..self.schema().len()).into_iter().collect_vec();
         output_indices
             .iter_mut()
@@ -1174,7 +1266,7 @@ impl ToStream for LogicalAgg {
             .collect_vec();
 
         let logical_agg = LogicalAgg::new(agg_calls, self.group_key().to_vec(), self.input());
-        let stream_agg = logical_agg.gen_dist_stream_agg_plan(self.input().to_stream()?)?;
+        let stream_agg = logical_agg.gen_dist_stream_agg_plan(stream_input)?;
 
         let stream_project = StreamProject::new(LogicalProject::with_out_col_idx(
             stream_agg,
@@ -17,13 +17,20 @@ use std::fmt;
 use fixedbitset::FixedBitSet;
 use itertools::Itertools;
 use risingwave_common::error::Result;
+use risingwave_common::types::DataType;
 
 use super::{
     generic, ColPrunable, CollectInputRef, LogicalProject, PlanBase, PlanRef, PlanTreeNodeUnary,
     PredicatePushdown, ToBatch, ToStream,
 };
-use crate::expr::{assert_input_ref, ExprImpl};
-use crate::optimizer::plan_node::{BatchFilter, StreamFilter};
+use crate::expr::{
+    assert_input_ref, ExprDisplay, ExprImpl, ExprRewriter, ExprType, FunctionCall, InputRef,
+};
+use crate::optimizer::now_visitor::streaming_now_error;
+use crate::optimizer::plan_node::{
+    BatchFilter, StreamDynamicFilter, StreamExchange, StreamFilter, StreamNow, StreamProject,
+};
+use crate::optimizer::property::Distribution;
 use crate::utils::{ColIndexMapping, Condition, ConditionDisplay};
 
 /// `LogicalFilter` iterates over its input and returns elements for which `predicate` evaluates to
@@ -181,8 +188,178 @@ impl ToBatch for LogicalFilter {
     }
 }
 
+/// Matches `left > now_expr` or `left >= now_expr` in either order, where `now_expr` never
+/// decreases as `now()` advances. Such a conjunction only gets stricter over time, so a row failing
+/// it never passes it again.
+fn as_now_lower_bound(expr: &ExprImpl) -> Option<(ExprImpl, ExprType, ExprImpl)> {
+    let ExprImpl::FunctionCall(function_call) = expr else {
+        return None;
+    };
+    let (comparator, left, right) = match function_call.get_expr_type() {
+        ty @ (ExprType::GreaterThan | ExprType::GreaterThanOrEqual) => {
+            let (_, left, right) = function_call.clone().decompose_as_binary();
+            (ty, left, right)
+        }
+        ExprType::LessThan => {
+            let (_, right, left) = function_call.clone().decompose_as_binary();
+            (ExprType::GreaterThan, left, right)
+        }
+        ExprType::LessThanOrEqual => {
+            let (_, right, left) = function_call.clone().decompose_as_binary();
+            (ExprType::GreaterThanOrEqual, left, right)
+        }
+        _ => return None,
+    };
+    if left.has_now() || left.is_const() || !is_non_decreasing_now(&right) {
+        return None;
+    }
+    Some((left, comparator, right))
+}
+
+/// Whether `expr` is `now()`, plus or minus constants.
+fn is_non_decreasing_now(expr: &ExprImpl) -> bool {
+    let ExprImpl::FunctionCall(function_call) = expr else {
+        return false;
+    };
+    match (function_call.get_expr_type(), function_call.inputs()) {
+        (ExprType::Now, []) => true,
+        (ExprType::Add, [a, b]) => {
+            (is_non_decreasing_now(a) && b.is_const()) || (a.is_const() && is_non_decreasing_now(b))
+        }
+        (ExprType::Subtract, [a, b]) => is_non_decreasing_now(a) && b.is_const(),
+        _ => false,
+    }
+}
+
+/// Replaces `now()` with the only column of [`StreamNow`].
+struct NowToInputRef;
+
+impl ExprRewriter for NowToInputRef {
+    fn rewrite_function_call(&mut self, func_call: FunctionCall) -> ExprImpl {
+        if func_call.get_expr_type() == ExprType::Now {
+            return InputRef::new(0, DataType::Timestampz).into();
+        }
+        let (func_type, inputs, ret) = func_call.decompose();
+        let inputs = inputs
+            .into_iter()
+            .map(|expr| self.rewrite_expr(expr))
+            .collect();
+        FunctionCall::new_unchecked(func_type, inputs, ret).into()
+    }
+}
+
+impl LogicalFilter {
+    /// Plans each conjunction with `now()` into a [`StreamDynamicFilter`] against a [`StreamNow`].
+    /// Only lower bounds of the form `left > now() - interval` are allowed, so that the rows
+    /// falling out of the bound can be cleaned from the state of the dynamic filter.
+    fn to_stream_with_now(&self) -> Result<PlanRef> {
+        let input = self.input();
+        let input_len = input.schema().len();
+        let (now_conds, other_conds): (Vec<_>, Vec<_>) = self
+            .predicate()
+            .conjunctions
+            .iter()
+            .cloned()
+            .partition(|expr| expr.has_now());
+        let now_conds: Vec<_> = now_conds
+            .iter()
+            .map(|expr| {
+                as_now_lower_bound(expr).ok_or_else(|| {
+                    streaming_now_error(Some(format!(
+                        "{}",
+                        ExprDisplay {
+                            expr,
+                            input_schema: input.schema()
+                        }
+                    )))
+                })
+            })
+            .try_collect()?;
+
+        let mut plan = input.to_stream()?;
+        if !other_conds.is_empty() {
+            let predicate = Condition {
+                conjunctions: other_conds,
+            };
+            plan = StreamFilter::new(LogicalFilter::new(plan, predicate)).into();
+        }
+
+        // The left sides that are not columns are appended to the input by a project, and removed
+        // after the dynamic filters.
+        let mut exprs = input
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| InputRef::new(idx, field.data_type()).into())
+            .collect_vec();
+        let mut filters = vec![];
+        for (left, comparator, now_expr) in now_conds {
+            let left_index = match left {
+                ExprImpl::InputRef(input_ref) => input_ref.index(),
+                left => {
+                    exprs.push(left);
+                    exprs.len() - 1
+                }
+            };
+            filters.push((left_index, comparator, now_expr));
+        }
+        let projected = exprs.len() > input_len;
+        if projected {
+            plan = StreamProject::new(LogicalProject::new(plan, exprs)).into();
+        }
+
+        for (left_index, comparator, now_expr) in filters {
+            let mut right: PlanRef = StreamNow::new(self.ctx()).into();
+            let is_now = matches!(
+                &now_expr,
+                ExprImpl::FunctionCall(call) if call.get_expr_type() == ExprType::Now
+            );
+            if !is_now {
+                let now_expr = NowToInputRef.rewrite_expr(now_expr);
+                right = StreamProject::new(LogicalProject::new(right, vec![now_expr])).into();
+            }
+            let right: PlanRef = StreamExchange::new(right, Distribution::Broadcast).into();
+
+            let left_len = plan.schema().len();
+            let predicate = Condition::with_expr(
+                FunctionCall::new(
+                    comparator,
+                    vec![
+                        InputRef::new(left_index, plan.schema().fields()[left_index].data_type())
+                            .into(),
+                        InputRef::new(left_len, right.schema().fields()[0].data_type()).into(),
+                    ],
+                )?
+                .into(),
+            );
+            plan = StreamDynamicFilter::new(left_index, predicate, plan, right, true).into();
+        }
+
+        if projected {
+            plan = StreamProject::new(LogicalProject::with_mapping(
+                plan.clone(),
+                ColIndexMapping::with_remaining_columns(
+                    &(0..input_len).collect_vec(),
+                    plan.schema().len(),
+                ),
+            ))
+            .into();
+        }
+        Ok(plan)
+    }
+}
+
 impl ToStream for LogicalFilter {
     fn to_stream(&self) -> Result<PlanRef> {
+        if self
+            .predicate()
+            .conjunctions
+            .iter()
+            .any(|expr| expr.has_now())
+        {
+            return self.to_stream_with_now();
+        }
         let new_input = self.input().to_stream()?;
         let new_logical = self.clone_with_input(new_input);
         Ok(StreamFilter::new(new_logical).into())
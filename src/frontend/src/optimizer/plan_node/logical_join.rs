@@ -1095,6 +1095,7 @@ impl ToStream for LogicalJoin {
                 predicate.other_cond().clone(),
                 left,
                 right,
+                false,
             )
             .into();
 
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
 use std::fmt;
 
 use itertools::Itertools;
@@ -20,8 +22,9 @@ use risingwave_common::error::{ErrorCode, Result, RwError};
 use risingwave_pb::plan_common::JoinType;
 
 use super::{
-    ColPrunable, LogicalFilter, LogicalJoin, LogicalProject, PlanBase, PlanNodeType, PlanRef,
-    PlanTreeNodeBinary, PlanTreeNodeUnary, PredicatePushdown, ToBatch, ToStream,
+    estimate_row_count, ColPrunable, LogicalFilter, LogicalJoin, LogicalProject, PlanBase,
+    PlanNodeType, PlanRef, PlanTreeNodeBinary, PlanTreeNodeUnary, PredicatePushdown, ToBatch,
+    ToStream,
 };
 use crate::expr::{ExprImpl, ExprRewriter};
 use crate::optimizer::plan_node::PlanTreeNode;
@@ -478,6 +481,53 @@ impl LogicalMultiJoin {
         Ok(join_ordering)
     }
 
+    /// Orders the inputs by their estimated row counts, which is only possible if the row counts of
+    /// all the inputs are known from the table statistics.
+    ///
+    /// Each connected component of the join graph starts from its largest remaining input, which
+    /// is the probe side of all the joins in the left-deep tree. Then the smallest input connected
+    /// to the inputs joined so far is repeatedly added as the build side of the next join.
+    /// Connected components are cross-joined in the order they are emitted.
+    pub(crate) fn statistics_ordering(&self) -> Option<Vec<usize>> {
+        let row_counts: Vec<u64> = self
+            .inputs
+            .iter()
+            .map(estimate_row_count)
+            .collect::<Option<_>>()?;
+
+        let (eq_join_conditions, _) = self.on.clone().split_by_input_col_nums(
+            &self.input_col_nums(),
+            // only_eq=
+            true,
+        );
+        let edges = eq_join_conditions.keys().cloned().collect_vec();
+        let connected = |joined: &[usize], input: usize| {
+            edges.iter().any(|&(a, b)| {
+                (a == input && joined.contains(&b)) || (b == input && joined.contains(&a))
+            })
+        };
+
+        let mut remaining: BTreeSet<usize> = (0..self.inputs.len()).collect();
+        let mut join_ordering = vec![];
+        while let Some(&base) = remaining
+            .iter()
+            .max_by_key(|&&i| (row_counts[i], Reverse(i)))
+        {
+            remaining.remove(&base);
+            let component_start = join_ordering.len();
+            join_ordering.push(base);
+            while let Some(&next) = remaining
+                .iter()
+                .filter(|&&i| connected(&join_ordering[component_start..], i))
+                .min_by_key(|&&i| (row_counts[i], i))
+            {
+                remaining.remove(&next);
+                join_ordering.push(next);
+            }
+        }
+        Some(join_ordering)
+    }
+
     pub(crate) fn input_col_nums(&self) -> Vec<usize> {
         self.inputs.iter().map(|i| i.schema().len()).collect()
     }
@@ -23,9 +23,12 @@ use risingwave_common::types::DataType;
 use super::generic::{PlanAggOrderByField, PlanAggOrderByFieldDisplay};
 use super::{
     gen_filter_and_pushdown, ColPrunable, LogicalProject, PlanBase, PlanRef, PlanTreeNodeUnary,
-    PredicatePushdown, ToBatch, ToStream,
+    PredicatePushdown, StreamOverWindow, ToBatch, ToStream,
 };
-use crate::expr::{Expr, ExprImpl, InputRef, InputRefDisplay, WindowFunction, WindowFunctionType};
+use crate::expr::{
+    Expr, ExprImpl, Frame, InputRef, InputRefDisplay, WindowFunction, WindowFunctionType,
+};
+use crate::optimizer::property::{Order, RequiredDist};
 use crate::utils::{ColIndexMapping, Condition};
 
 /// Rewritten version of [`WindowFunction`] which uses `InputRef` instead of `ExprImpl`.
@@ -33,13 +36,35 @@ use crate::utils::{ColIndexMapping, Condition};
 pub struct PlanWindowFunction {
     pub function_type: WindowFunctionType,
     pub return_type: DataType,
+    pub args: Vec<InputRef>,
     pub partition_by: Vec<InputRef>,
     /// TODO: rename & move `PlanAggOrderByField` so that it can be better shared like
     /// [`crate::expr::OrderByExpr`]
     pub order_by: Vec<PlanAggOrderByField>,
+    pub frame: Option<Frame>,
+}
+
+impl PlanWindowFunction {
+    fn rewrite_with_col_change(&self, col_change: &ColIndexMapping) -> Self {
+        let rewrite =
+            |input: &InputRef| InputRef::new(col_change.map(input.index), input.data_type.clone());
+        Self {
+            args: self.args.iter().map(rewrite).collect(),
+            partition_by: self.partition_by.iter().map(rewrite).collect(),
+            order_by: self
+                .order_by
+                .iter()
+                .map(|field| PlanAggOrderByField {
+                    input: rewrite(&field.input),
+                    ..field.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
 }
 
-struct PlanWindowFunctionDisplay<'a> {
+pub(super) struct PlanWindowFunctionDisplay<'a> {
     pub window_function: &'a PlanWindowFunction,
     pub input_schema: &'a Schema,
 }
@@ -51,11 +76,26 @@ impl<'a> std::fmt::Debug for PlanWindowFunctionDisplay<'a> {
             f.debug_struct("WindowFunction")
                 .field("function_type", &window_function.function_type)
                 .field("return_type", &window_function.return_type)
+                .field("args", &window_function.args)
                 .field("partition_by", &window_function.partition_by)
                 .field("order_by", &window_function.order_by)
+                .field("frame", &window_function.frame)
                 .finish()
         } else {
-            write!(f, "{}() OVER(", window_function.function_type)?;
+            write!(
+                f,
+                "{}({}) OVER(",
+                window_function.function_type,
+                window_function
+                    .args
+                    .iter()
+                    .format_with(", ", |input_ref, f| {
+                        f(&InputRefDisplay {
+                            input_ref,
+                            input_schema: self.input_schema,
+                        })
+                    })
+            )?;
 
             let mut delim = "";
             if !window_function.partition_by.is_empty() {
@@ -88,6 +128,10 @@ impl<'a> std::fmt::Debug for PlanWindowFunctionDisplay<'a> {
                         ))
                     })
                 )?;
+                delim = " ";
+            }
+            if let Some(frame) = &window_function.frame {
+                write!(f, "{delim}{frame}")?;
             }
             f.write_str(")")?;
 
@@ -185,9 +229,8 @@ impl LogicalOverAgg {
             function_type,
             partition_by,
             order_by,
+            frame,
         } = window_funcs.into_iter().next().unwrap();
-        assert!(args.is_empty());
-        assert!(return_type == DataType::Int64);
 
         // TODO: rewrite ORDER BY & PARTITION BY expr to InputRef like `LogicalAgg`
         let order_by = order_by
@@ -217,13 +260,26 @@ impl LogicalOverAgg {
                 .into()),
             })
             .collect::<Result<Vec<_>>>()?;
+        let args = args
+            .into_iter()
+            .map(|e| match e.as_input_ref() {
+                Some(i) => Ok(*i.clone()),
+                None => Err(ErrorCode::NotImplemented(
+                    "argument expression in window function".to_string(),
+                    None.into(),
+                )
+                .into()),
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         let over_agg = Self::new(
             PlanWindowFunction {
                 function_type,
                 return_type,
+                args,
                 partition_by,
                 order_by,
+                frame,
             },
             input,
         );
@@ -281,10 +337,46 @@ impl ToBatch for LogicalOverAgg {
 
 impl ToStream for LogicalOverAgg {
     fn to_stream(&self) -> Result<PlanRef> {
-        Err(ErrorCode::NotImplemented("OverAgg to stream".to_string(), 4847.into()).into())
+        if self.window_function.function_type.is_rank_function() {
+            return Err(
+                ErrorCode::NotImplemented("OverAgg to stream".to_string(), 4847.into()).into(),
+            );
+        }
+        let input = self.input().to_stream()?;
+        let partition_key = self
+            .window_function
+            .partition_by
+            .iter()
+            .map(|input_ref| input_ref.index)
+            .collect_vec();
+        let required_dist = if partition_key.is_empty() {
+            RequiredDist::single()
+        } else {
+            RequiredDist::hash_shard(&partition_key)
+        };
+        let input = required_dist.enforce_if_not_satisfies(input, &Order::any())?;
+        Ok(StreamOverWindow::new(self.clone_with_input(input)).into())
     }
 
-    fn logical_rewrite_for_stream(&self) -> Result<(PlanRef, crate::utils::ColIndexMapping)> {
-        Err(ErrorCode::NotImplemented("OverAgg to stream".to_string(), 4847.into()).into())
+    fn logical_rewrite_for_stream(&self) -> Result<(PlanRef, ColIndexMapping)> {
+        if self.window_function.function_type.is_rank_function() {
+            return Err(
+                ErrorCode::NotImplemented("OverAgg to stream".to_string(), 4847.into()).into(),
+            );
+        }
+        let (input, input_col_change) = self.input().logical_rewrite_for_stream()?;
+        let window_function = self
+            .window_function
+            .rewrite_with_col_change(&input_col_change);
+        // The window function stays the last column, after the columns of the new input.
+        let input_len = self.input().schema().len();
+        let out_col_change = ColIndexMapping::with_target_size(
+            (0..input_len)
+                .map(|idx| input_col_change.try_map(idx))
+                .chain(std::iter::once(Some(input.schema().len())))
+                .collect(),
+            input.schema().len() + 1,
+        );
+        Ok((Self::new(window_function, input).into(), out_col_change))
     }
 }
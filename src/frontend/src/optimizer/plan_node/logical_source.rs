@@ -16,8 +16,10 @@ use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
+use itertools::Itertools;
 use risingwave_common::catalog::Schema;
 use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_pb::catalog::WatermarkDesc as ProstWatermarkDesc;
 
 use super::{
     generic, ColPrunable, LogicalFilter, LogicalProject, PlanBase, PlanRef, PredicatePushdown,
@@ -34,17 +36,31 @@ use crate::TableCatalog;
 pub struct LogicalSource {
     pub base: PlanBase,
     pub core: generic::Source,
+    /// The indices of the source columns to output, in ascending order. The columns not required
+    /// by the downstream are pruned, so that the source executor won't parse them.
+    output_col_idx: Vec<usize>,
+    /// Watermarks generated on the output columns of the source.
+    watermark_descs: Vec<ProstWatermarkDesc>,
 }
 
 impl LogicalSource {
     pub fn new(source_catalog: Rc<SourceCatalog>, ctx: OptimizerContextRef) -> Self {
+        let output_col_idx = (0..source_catalog.columns.len()).collect();
+        Self::with_output_col_idx(source_catalog, output_col_idx, ctx)
+    }
+
+    fn with_output_col_idx(
+        source_catalog: Rc<SourceCatalog>,
+        output_col_idx: Vec<usize>,
+        ctx: OptimizerContextRef,
+    ) -> Self {
         let mut id_to_idx = HashMap::new();
 
-        let fields = source_catalog
-            .columns
+        let fields = output_col_idx
             .iter()
             .enumerate()
-            .map(|(idx, c)| {
+            .map(|(idx, &col_idx)| {
+                let c = &source_catalog.columns[col_idx];
                 id_to_idx.insert(c.column_id(), idx);
                 (&c.column_desc).into()
             })
@@ -66,9 +82,34 @@ impl LogicalSource {
         LogicalSource {
             base,
             core: generic::Source(source_catalog),
+            output_col_idx,
+            watermark_descs: vec![],
         }
     }
 
+    pub fn with_watermark_descs(mut self, watermark_descs: Vec<ProstWatermarkDesc>) -> Self {
+        self.watermark_descs = watermark_descs;
+        self
+    }
+
+    pub fn watermark_descs(&self) -> &[ProstWatermarkDesc] {
+        &self.watermark_descs
+    }
+
+    /// The watermark columns with their allowed lateness, for explaining.
+    pub(super) fn watermark_columns(&self) -> Vec<String> {
+        let fields = self.schema().fields();
+        self.watermark_descs
+            .iter()
+            .map(|desc| {
+                format!(
+                    "{} - {}ms",
+                    fields[desc.watermark_idx as usize].name, desc.allowed_lateness_ms
+                )
+            })
+            .collect()
+    }
+
     pub(super) fn column_names(&self) -> Vec<String> {
         self.schema()
             .fields()
@@ -81,6 +122,19 @@ impl LogicalSource {
         self.core.0.clone()
     }
 
+    /// The indices of the output columns in the source catalog.
+    pub fn output_col_idx(&self) -> &[usize] {
+        &self.output_col_idx
+    }
+
+    /// The index of the row id column in the output columns, if it's generated by the source.
+    pub fn row_id_index(&self) -> Option<usize> {
+        let row_id_index = self.source_catalog().row_id_index?;
+        self.output_col_idx
+            .iter()
+            .position(|&idx| idx == row_id_index)
+    }
+
     pub fn infer_internal_table_catalog(&self) -> TableCatalog {
         self.core.infer_internal_table_catalog(&self.base)
     }
@@ -92,17 +146,57 @@ impl fmt::Display for LogicalSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "LogicalSource {{ source: {}, columns: [{}] }}",
+            "LogicalSource {{ source: {}, columns: [{}]",
             self.source_catalog().name,
             self.column_names().join(", ")
-        )
+        )?;
+        if !self.watermark_descs.is_empty() {
+            write!(f, ", watermarks: [{}]", self.watermark_columns().join(", "))?;
+        }
+        write!(f, " }}")
     }
 }
 
 impl ColPrunable for LogicalSource {
     fn prune_col(&self, required_cols: &[usize]) -> PlanRef {
-        let mapping = ColIndexMapping::with_remaining_columns(required_cols, self.schema().len());
-        LogicalProject::with_mapping(self.clone().into(), mapping).into()
+        // The pk columns are kept as the stream key of the source.
+        let output_col_idx: Vec<usize> = required_cols
+            .iter()
+            .chain(self.logical_pk())
+            .map(|&i| self.output_col_idx[i])
+            .sorted()
+            .dedup()
+            .collect();
+        let watermark_descs = self
+            .watermark_descs
+            .iter()
+            .filter_map(|desc| {
+                let col_idx = self.output_col_idx[desc.watermark_idx as usize];
+                let watermark_idx = output_col_idx.iter().position(|&i| i == col_idx)?;
+                Some(ProstWatermarkDesc {
+                    watermark_idx: watermark_idx as u32,
+                    ..desc.clone()
+                })
+            })
+            .collect();
+        let source =
+            Self::with_output_col_idx(self.source_catalog(), output_col_idx.clone(), self.ctx())
+                .with_watermark_descs(watermark_descs);
+
+        let required_cols = required_cols
+            .iter()
+            .map(|&i| {
+                output_col_idx
+                    .iter()
+                    .position(|&idx| idx == self.output_col_idx[i])
+                    .unwrap()
+            })
+            .collect_vec();
+        if required_cols.iter().copied().eq(0..output_col_idx.len()) {
+            return source.into();
+        }
+        let mapping = ColIndexMapping::with_remaining_columns(&required_cols, output_col_idx.len());
+        LogicalProject::with_mapping(source.into(), mapping).into()
     }
 }
 
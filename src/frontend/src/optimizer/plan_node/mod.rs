@@ -242,6 +242,8 @@ mod to_prost;
 pub use to_prost::*;
 mod predicate_pushdown;
 pub use predicate_pushdown::*;
+mod statistics;
+pub use statistics::*;
 
 pub mod generic;
 pub mod stream;
@@ -291,6 +293,8 @@ mod logical_topn;
 mod logical_union;
 mod logical_update;
 mod logical_values;
+mod stream_change_log;
+mod stream_dedup;
 mod stream_delta_join;
 mod stream_dynamic_filter;
 mod stream_exchange;
@@ -304,6 +308,8 @@ mod stream_hop_window;
 mod stream_index_scan;
 mod stream_local_simple_agg;
 mod stream_materialize;
+mod stream_now;
+mod stream_over_window;
 mod stream_project;
 mod stream_project_set;
 mod stream_sink;
@@ -356,6 +362,11 @@ pub use logical_topn::LogicalTopN;
 pub use logical_union::LogicalUnion;
 pub use logical_update::LogicalUpdate;
 pub use logical_values::LogicalValues;
+pub use stream_change_log::{
+    StreamChangeLog, CHANGE_LOG_EPOCH_COLUMN_NAME, CHANGE_LOG_OP_COLUMN_NAME,
+    CHANGE_LOG_SEQ_COLUMN_NAME,
+};
+pub use stream_dedup::StreamDedup;
 pub use stream_delta_join::StreamDeltaJoin;
 pub use stream_dynamic_filter::StreamDynamicFilter;
 pub use stream_exchange::StreamExchange;
@@ -369,6 +380,8 @@ pub use stream_hop_window::StreamHopWindow;
 pub use stream_index_scan::StreamIndexScan;
 pub use stream_local_simple_agg::StreamLocalSimpleAgg;
 pub use stream_materialize::StreamMaterialize;
+pub use stream_now::StreamNow;
+pub use stream_over_window::StreamOverWindow;
 pub use stream_project::StreamProject;
 pub use stream_project_set::StreamProjectSet;
 pub use stream_sink::StreamSink;
@@ -458,6 +471,10 @@ macro_rules! for_all_plan_nodes {
             , { Stream, DynamicFilter }
             , { Stream, ProjectSet }
             , { Stream, GroupTopN }
+            , { Stream, Dedup }
+            , { Stream, ChangeLog }
+            , { Stream, Now }
+            , { Stream, OverWindow }
         }
     };
 }
@@ -549,6 +566,10 @@ macro_rules! for_stream_plan_nodes {
             , { Stream, DynamicFilter }
             , { Stream, ProjectSet }
             , { Stream, GroupTopN }
+            , { Stream, Dedup }
+            , { Stream, ChangeLog }
+            , { Stream, Now }
+            , { Stream, OverWindow }
         }
     };
 }
@@ -0,0 +1,85 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Naive cardinality estimation based on the table statistics collected by `ANALYZE`.
+//!
+//! Only scans, and the operators that keep the row count of their inputs unchanged or are assumed
+//! to do so (filters, projects and exchanges), are estimated. Predicates are not taken into
+//! account.
+
+use risingwave_pb::catalog::TableStatistics as ProstTableStatistics;
+
+use super::{LogicalScan, PlanNodeType, PlanRef};
+
+fn scan_statistics(scan: &LogicalScan) -> Option<ProstTableStatistics> {
+    scan.ctx().table_statistics(scan.table_desc().table_id)
+}
+
+fn as_scan(plan: &PlanRef) -> Option<&LogicalScan> {
+    match plan.node_type() {
+        PlanNodeType::LogicalScan => plan.as_logical_scan(),
+        PlanNodeType::BatchSeqScan => plan.as_batch_seq_scan().map(|scan| scan.logical()),
+        _ => None,
+    }
+}
+
+/// Estimates the number of rows produced by `plan`. Returns `None` if unknown.
+pub fn estimate_row_count(plan: &PlanRef) -> Option<u64> {
+    if let Some(scan) = as_scan(plan) {
+        return scan_statistics(scan).map(|statistics| statistics.row_count);
+    }
+    match plan.node_type() {
+        PlanNodeType::LogicalFilter
+        | PlanNodeType::BatchFilter
+        | PlanNodeType::LogicalProject
+        | PlanNodeType::BatchProject
+        | PlanNodeType::BatchExchange => estimate_row_count(&plan.inputs()[0]),
+        _ => None,
+    }
+}
+
+/// Estimates the number of distinct values of the column `col_idx` of `plan`. Returns `None` if
+/// unknown.
+pub fn estimate_distinct_count(plan: &PlanRef, col_idx: usize) -> Option<u64> {
+    if let Some(scan) = as_scan(plan) {
+        let table_col_idx = scan.output_col_idx()[col_idx];
+        return scan_statistics(scan)
+            .and_then(|statistics| statistics.columns.get(table_col_idx).cloned())
+            .map(|column| column.distinct_count)
+            // Zero means the distinct count is not collected for this column.
+            .filter(|distinct_count| *distinct_count > 0);
+    }
+    let exprs = match plan.node_type() {
+        PlanNodeType::LogicalFilter | PlanNodeType::BatchFilter | PlanNodeType::BatchExchange => {
+            return estimate_distinct_count(&plan.inputs()[0], col_idx);
+        }
+        PlanNodeType::LogicalProject => plan.as_logical_project().unwrap().exprs(),
+        PlanNodeType::BatchProject => plan.as_batch_project().unwrap().as_logical().exprs(),
+        _ => return None,
+    };
+    let input_ref = exprs[col_idx].as_input_ref()?;
+    estimate_distinct_count(&plan.inputs()[0], input_ref.index())
+}
+
+/// Estimates the number of distinct combinations of the columns `col_indices` of `plan`, assuming
+/// the columns are independent. Returns `None` if unknown.
+pub fn estimate_group_count(plan: &PlanRef, col_indices: &[usize]) -> Option<u64> {
+    let row_count = estimate_row_count(plan)?;
+    let mut group_count: u64 = 1;
+    for &col_idx in col_indices {
+        let distinct_count = estimate_distinct_count(plan, col_idx)?.max(1);
+        group_count = group_count.saturating_mul(distinct_count);
+    }
+    Some(group_count.min(row_count.max(1)))
+}
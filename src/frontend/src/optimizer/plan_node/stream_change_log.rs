@@ -0,0 +1,93 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::DataType;
+use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
+use risingwave_pb::stream_plan::ChangeLogNode;
+
+use super::{PlanBase, PlanRef, PlanTreeNodeUnary, StreamNode};
+use crate::optimizer::property::{Distribution, FunctionalDependencySet};
+use crate::stream_fragmenter::BuildFragmentGraphState;
+
+pub const CHANGE_LOG_EPOCH_COLUMN_NAME: &str = "changelog_epoch";
+pub const CHANGE_LOG_SEQ_COLUMN_NAME: &str = "changelog_seq";
+pub const CHANGE_LOG_OP_COLUMN_NAME: &str = "changelog_op";
+
+/// `StreamChangeLog` turns every change of its input into an appended row, followed by the epoch
+/// of the change, its sequence number in the epoch and the name of its op. The epoch and the
+/// sequence number are the stream key of the output.
+///
+/// The sequence numbers are only unique in an actor, so the input must be a singleton.
+#[derive(Debug, Clone)]
+pub struct StreamChangeLog {
+    pub base: PlanBase,
+    input: PlanRef,
+}
+
+impl StreamChangeLog {
+    pub fn new(input: PlanRef) -> Self {
+        assert_eq!(input.distribution(), &Distribution::Single);
+        let mut fields = input.schema().fields().to_vec();
+        let epoch_idx = fields.len();
+        fields.push(Field::with_name(
+            DataType::Int64,
+            CHANGE_LOG_EPOCH_COLUMN_NAME,
+        ));
+        fields.push(Field::with_name(
+            DataType::Int64,
+            CHANGE_LOG_SEQ_COLUMN_NAME,
+        ));
+        fields.push(Field::with_name(
+            DataType::Varchar,
+            CHANGE_LOG_OP_COLUMN_NAME,
+        ));
+        let pk_indices = vec![epoch_idx, epoch_idx + 1];
+        let functional_dependency = FunctionalDependencySet::with_key(fields.len(), &pk_indices);
+        let base = PlanBase::new_stream(
+            input.ctx(),
+            Schema::new(fields),
+            pk_indices,
+            functional_dependency,
+            Distribution::Single,
+            true,
+        );
+        StreamChangeLog { base, input }
+    }
+}
+
+impl fmt::Display for StreamChangeLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StreamChangeLog")
+    }
+}
+
+impl PlanTreeNodeUnary for StreamChangeLog {
+    fn input(&self) -> PlanRef {
+        self.input.clone()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(input)
+    }
+}
+impl_plan_tree_node_for_unary! { StreamChangeLog }
+
+impl StreamNode for StreamChangeLog {
+    fn to_stream_prost_body(&self, _state: &mut BuildFragmentGraphState) -> ProstStreamNode {
+        ProstStreamNode::ChangeLog(ChangeLogNode {})
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_common::util::sort_util::OrderType;
+use risingwave_pb::stream_plan::dedup_node::Retention;
+use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
+use risingwave_pb::stream_plan::DedupNode;
+
+use super::utils::{IndicesDisplay, TableCatalogBuilder};
+use super::{PlanBase, PlanRef, PlanTreeNodeUnary, StreamNode};
+use crate::catalog::TableCatalog;
+use crate::stream_fragmenter::BuildFragmentGraphState;
+
+/// `StreamDedup` drops the rows whose dedup key has been seen before, keeping the first row of each
+/// key. Both of its input and output are append-only.
+///
+/// If a column of the input carries watermarks, it's used as the retention column: the seen keys
+/// whose value of the column falls behind the watermark are evicted from the state.
+#[derive(Debug, Clone)]
+pub struct StreamDedup {
+    pub base: PlanBase,
+    input: PlanRef,
+    dedup_cols: Vec<usize>,
+    retention_col: Option<usize>,
+}
+
+impl StreamDedup {
+    pub fn new(input: PlanRef, dedup_cols: Vec<usize>) -> Self {
+        assert!(input.append_only());
+        assert!(!dedup_cols.is_empty());
+        // The dedup key is unique in the output.
+        let base = PlanBase::new_stream(
+            input.ctx(),
+            input.schema().clone(),
+            dedup_cols.clone(),
+            input.functional_dependency().clone(),
+            input.distribution().clone(),
+            true,
+        );
+        let retention_col = watermark_columns(&input).into_iter().next();
+        StreamDedup {
+            base,
+            input,
+            dedup_cols,
+            retention_col,
+        }
+    }
+
+    pub fn dedup_cols(&self) -> &[usize] {
+        &self.dedup_cols
+    }
+
+    pub fn retention_col(&self) -> Option<usize> {
+        self.retention_col
+    }
+
+    /// The state table stores the first row of each dedup key, ordered by the dedup key.
+    fn infer_internal_table_catalog(&self) -> TableCatalog {
+        let mut builder =
+            TableCatalogBuilder::new(self.ctx().inner().with_options.internal_table_subset());
+        for field in self.input.schema().fields() {
+            builder.add_column(field);
+        }
+        for &idx in &self.dedup_cols {
+            builder.add_order_column(idx, OrderType::Ascending);
+        }
+        builder.build(self.input.distribution().dist_column_indices().to_vec())
+    }
+}
+
+impl fmt::Display for StreamDedup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct("StreamDedup");
+        builder.field(
+            "dedup_cols",
+            &IndicesDisplay {
+                indices: &self.dedup_cols,
+                input_schema: self.input.schema(),
+            },
+        );
+        if let Some(retention_col) = self.retention_col {
+            builder.field(
+                "retention",
+                &format_args!("{}", self.input.schema().fields()[retention_col].name),
+            );
+        }
+        builder.finish()
+    }
+}
+
+/// Returns the columns of a stream plan that carry the watermarks generated by its sources, i.e.
+/// the watermark columns of the sources passed through by projections, filters and exchanges.
+fn watermark_columns(plan: &PlanRef) -> Vec<usize> {
+    if let Some(source) = plan.as_stream_source() {
+        source
+            .watermark_descs()
+            .iter()
+            .map(|desc| desc.watermark_idx as usize)
+            .collect()
+    } else if let Some(project) = plan.as_stream_project() {
+        let input_watermark_columns = watermark_columns(&project.input());
+        project
+            .as_logical()
+            .exprs()
+            .iter()
+            .enumerate()
+            .filter_map(|(output_idx, expr)| {
+                let input_ref = expr.as_input_ref()?;
+                input_watermark_columns
+                    .contains(&input_ref.index())
+                    .then_some(output_idx)
+            })
+            .collect()
+    } else if let Some(filter) = plan.as_stream_filter() {
+        watermark_columns(&filter.input())
+    } else if let Some(exchange) = plan.as_stream_exchange() {
+        watermark_columns(&exchange.input())
+    } else {
+        vec![]
+    }
+}
+
+impl PlanTreeNodeUnary for StreamDedup {
+    fn input(&self) -> PlanRef {
+        self.input.clone()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(input, self.dedup_cols.clone())
+    }
+}
+impl_plan_tree_node_for_unary! { StreamDedup }
+
+impl StreamNode for StreamDedup {
+    fn to_stream_prost_body(&self, state: &mut BuildFragmentGraphState) -> ProstStreamNode {
+        let table = self
+            .infer_internal_table_catalog()
+            .with_id(state.gen_table_id_wrapped());
+        ProstStreamNode::Dedup(DedupNode {
+            state_table: Some(table.to_internal_table_prost()),
+            dedup_column_indices: self.dedup_cols.iter().map(|idx| *idx as u32).collect(),
+            retention: self.retention_col.map(|idx| Retention {
+                column_index: idx as u32,
+            }),
+        })
+    }
+}
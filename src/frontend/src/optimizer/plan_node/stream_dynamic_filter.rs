@@ -37,10 +37,19 @@ pub struct StreamDynamicFilter {
     left_index: usize,
     left: PlanRef,
     right: PlanRef,
+    /// Whether the right side only makes the predicate stricter over time, e.g. `now()` for `>`.
+    /// If so, the left rows failing the predicate are cleaned from the state.
+    is_monotonic: bool,
 }
 
 impl StreamDynamicFilter {
-    pub fn new(left_index: usize, predicate: Condition, left: PlanRef, right: PlanRef) -> Self {
+    pub fn new(
+        left_index: usize,
+        predicate: Condition,
+        left: PlanRef,
+        right: PlanRef,
+        is_monotonic: bool,
+    ) -> Self {
         // TODO: derive from input
         let base = PlanBase::new_stream(
             left.ctx(),
@@ -57,6 +66,7 @@ impl StreamDynamicFilter {
             left_index,
             left,
             right,
+            is_monotonic,
         }
     }
 }
@@ -106,7 +116,13 @@ impl PlanTreeNodeBinary for StreamDynamicFilter {
     }
 
     fn clone_with_left_right(&self, left: PlanRef, right: PlanRef) -> Self {
-        Self::new(self.left_index, self.predicate.clone(), left, right)
+        Self::new(
+            self.left_index,
+            self.predicate.clone(),
+            left,
+            right,
+            self.is_monotonic,
+        )
     }
 }
 
@@ -127,6 +143,7 @@ impl StreamNode for StreamDynamicFilter {
             condition,
             left_table: Some(left_table.to_internal_table_prost()),
             right_table: Some(right_table.to_internal_table_prost()),
+            is_monotonic: self.is_monotonic,
         })
     }
 }
@@ -15,7 +15,7 @@
 use std::fmt;
 
 use risingwave_pb::stream_plan::stream_node::NodeBody;
-use risingwave_pb::stream_plan::{DispatchStrategy, DispatcherType, ExchangeNode};
+use risingwave_pb::stream_plan::{DispatchStrategy, DispatcherType, ExchangeNode, HotKeyMode};
 
 use super::{PlanBase, PlanRef, PlanTreeNodeUnary, StreamNode};
 use crate::optimizer::property::{Distribution, DistributionDisplay};
@@ -89,6 +89,8 @@ impl StreamNode for StreamExchange {
                     Distribution::HashShard(keys) => keys.iter().map(|num| *num as u32).collect(),
                     _ => vec![],
                 },
+                // Set by `mark_hot_key_skew` on the fragment graph if enabled.
+                hot_key_mode: HotKeyMode::Unspecified as i32,
             }),
         })
     }
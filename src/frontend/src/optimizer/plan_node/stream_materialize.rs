@@ -185,6 +185,10 @@ impl StreamMaterialize {
             vnode_col_idx: None,
             value_indices,
             definition,
+            statistics: None,
+            watermark_descs: vec![],
+            subscription_progress: None,
+            key_encoding_version: 0,
         };
 
         Ok(Self { base, input, table })
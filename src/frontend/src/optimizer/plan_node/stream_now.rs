@@ -0,0 +1,85 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::DataType;
+use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
+use risingwave_pb::stream_plan::NowNode;
+
+use super::utils::TableCatalogBuilder;
+use super::{PlanBase, StreamNode};
+use crate::catalog::TableCatalog;
+use crate::optimizer::property::{Distribution, FunctionalDependencySet};
+use crate::session::OptimizerContextRef;
+use crate::stream_fragmenter::BuildFragmentGraphState;
+
+/// `StreamNow` emits the value of `now()` in streaming queries, a single row of the timestamp of
+/// the latest barrier. On each barrier, the previous timestamp is deleted and the new one is
+/// inserted, and the timestamp never goes backwards.
+#[derive(Debug, Clone)]
+pub struct StreamNow {
+    pub base: PlanBase,
+}
+
+impl StreamNow {
+    pub fn new(ctx: OptimizerContextRef) -> Self {
+        let schema = Schema::new(vec![Field::with_name(DataType::Timestampz, "now")]);
+        let functional_dependency = FunctionalDependencySet::new(schema.len());
+        let base = PlanBase::new_stream(
+            ctx,
+            schema,
+            vec![],
+            functional_dependency,
+            Distribution::Single,
+            false,
+        );
+        Self { base }
+    }
+
+    /// The state table stores the last emitted timestamp.
+    fn infer_internal_table_catalog(&self) -> TableCatalog {
+        let mut builder =
+            TableCatalogBuilder::new(self.ctx().inner().with_options.internal_table_subset());
+        for field in self.schema().fields() {
+            builder.add_column(field);
+        }
+        builder.build(vec![])
+    }
+}
+
+impl_plan_tree_node_for_leaf! { StreamNow }
+
+impl fmt::Display for StreamNow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamNow")
+            .field(
+                "output",
+                &format_args!("[{}]", self.schema().fields()[0].name),
+            )
+            .finish()
+    }
+}
+
+impl StreamNode for StreamNow {
+    fn to_stream_prost_body(&self, state: &mut BuildFragmentGraphState) -> ProstStreamNode {
+        let table = self
+            .infer_internal_table_catalog()
+            .with_id(state.gen_table_id_wrapped());
+        ProstStreamNode::Now(NowNode {
+            state_table: Some(table.to_internal_table_prost()),
+        })
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use risingwave_common::util::sort_util::OrderType;
+use risingwave_pb::stream_plan::over_window_node::{
+    Aggregate, Frame as ProstFrame, ValueAt, WindowFunction as ProstWindowFunction,
+};
+use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
+use risingwave_pb::stream_plan::OverWindowNode;
+
+use super::generic::PlanAggCall;
+use super::logical_over_agg::PlanWindowFunctionDisplay;
+use super::utils::TableCatalogBuilder;
+use super::{LogicalOverAgg, PlanBase, PlanRef, PlanTreeNodeUnary, StreamNode};
+use crate::catalog::TableCatalog;
+use crate::expr::WindowFunctionType;
+use crate::stream_fragmenter::BuildFragmentGraphState;
+use crate::utils::Condition;
+
+/// `StreamOverWindow` computes a window function other than the rank functions over the rows of
+/// each partition, and appends the result to each row. Only bounded frames in `ROWS` mode are
+/// supported.
+#[derive(Debug, Clone)]
+pub struct StreamOverWindow {
+    pub base: PlanBase,
+    logical: LogicalOverAgg,
+}
+
+impl StreamOverWindow {
+    pub fn new(logical: LogicalOverAgg) -> Self {
+        assert!(!logical.window_function.function_type.is_rank_function());
+        let input = logical.input();
+        // The outputs of the rows are updated when their neighbors change.
+        let base = PlanBase::new_stream(
+            logical.ctx(),
+            logical.schema().clone(),
+            input.logical_pk().to_vec(),
+            logical.functional_dependency().clone(),
+            input.distribution().clone(),
+            false,
+        );
+        StreamOverWindow { base, logical }
+    }
+
+    /// The state table stores the input rows, ordered by the partition key, the order key and then
+    /// the stream key.
+    fn infer_internal_table_catalog(&self) -> TableCatalog {
+        let input = self.input();
+        let window_function = &self.logical.window_function;
+        let mut builder =
+            TableCatalogBuilder::new(self.ctx().inner().with_options.internal_table_subset());
+        for field in input.schema().fields() {
+            builder.add_column(field);
+        }
+
+        let mut order_cols = HashSet::new();
+        for input_ref in &window_function.partition_by {
+            if order_cols.insert(input_ref.index) {
+                builder.add_order_column(input_ref.index, OrderType::Ascending);
+            }
+        }
+        for field in &window_function.order_by {
+            if order_cols.insert(field.input.index) {
+                builder.add_order_column(field.input.index, OrderType::from(field.direction));
+            }
+        }
+        for &idx in input.logical_pk() {
+            if order_cols.insert(idx) {
+                builder.add_order_column(idx, OrderType::Ascending);
+            }
+        }
+        builder.build(input.distribution().dist_column_indices().to_vec())
+    }
+
+    fn window_function_to_protobuf(&self) -> ProstWindowFunction {
+        let window_function = &self.logical.window_function;
+        let frame = window_function
+            .frame
+            .expect("the frame of a window function to stream should be set");
+        match window_function.function_type {
+            WindowFunctionType::Aggregate(agg_kind) => {
+                let call = PlanAggCall {
+                    agg_kind,
+                    return_type: window_function.return_type.clone(),
+                    inputs: window_function.args.clone(),
+                    distinct: false,
+                    order_by_fields: vec![],
+                    filter: Condition::true_cond(),
+                };
+                ProstWindowFunction::Aggregate(Aggregate {
+                    call: Some(call.to_protobuf()),
+                    frame: Some(ProstFrame {
+                        start_offset: frame.start.to_offset(),
+                        end_offset: frame.end.to_offset(),
+                    }),
+                })
+            }
+            WindowFunctionType::Lag | WindowFunctionType::Lead => {
+                ProstWindowFunction::ValueAt(ValueAt {
+                    arg_index: window_function.args[0].index as u32,
+                    offset: frame.start.to_offset(),
+                })
+            }
+            WindowFunctionType::RowNumber
+            | WindowFunctionType::Rank
+            | WindowFunctionType::DenseRank => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Display for StreamOverWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamOverWindow")
+            .field(
+                "window_function",
+                &PlanWindowFunctionDisplay {
+                    window_function: &self.logical.window_function,
+                    input_schema: self.input().schema(),
+                },
+            )
+            .finish()
+    }
+}
+
+impl PlanTreeNodeUnary for StreamOverWindow {
+    fn input(&self) -> PlanRef {
+        self.logical.input()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(self.logical.clone_with_input(input))
+    }
+}
+impl_plan_tree_node_for_unary! { StreamOverWindow }
+
+impl StreamNode for StreamOverWindow {
+    fn to_stream_prost_body(&self, state: &mut BuildFragmentGraphState) -> ProstStreamNode {
+        let table = self
+            .infer_internal_table_catalog()
+            .with_id(state.gen_table_id_wrapped());
+        ProstStreamNode::OverWindow(OverWindowNode {
+            window_function: Some(self.window_function_to_protobuf()),
+            partition_by: self
+                .logical
+                .window_function
+                .partition_by
+                .iter()
+                .map(|input_ref| input_ref.index as u32)
+                .collect(),
+            state_table: Some(table.to_internal_table_prost()),
+        })
+    }
+}
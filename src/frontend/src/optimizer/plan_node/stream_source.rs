@@ -15,7 +15,7 @@
 use std::fmt;
 
 use itertools::Itertools;
-use risingwave_pb::catalog::ColumnIndex;
+use risingwave_pb::catalog::{ColumnIndex, WatermarkDesc as ProstWatermarkDesc};
 use risingwave_pb::stream_plan::source_node::Info;
 use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
 use risingwave_pb::stream_plan::SourceNode;
@@ -52,6 +52,10 @@ impl StreamSource {
             .map(|f| f.name.clone())
             .collect()
     }
+
+    pub fn watermark_descs(&self) -> &[ProstWatermarkDesc] {
+        self.logical.watermark_descs()
+    }
 }
 
 impl_plan_tree_node_for_leaf! { StreamSource }
@@ -64,8 +68,14 @@ impl fmt::Display for StreamSource {
             .field(
                 "columns",
                 &format_args!("[{}]", &self.column_names().join(", ")),
-            )
-            .finish()
+            );
+        if !self.logical.watermark_descs().is_empty() {
+            builder.field(
+                "watermarks",
+                &format_args!("[{}]", &self.logical.watermark_columns().join(", ")),
+            );
+        }
+        builder.finish()
     }
 }
 
@@ -84,13 +94,17 @@ impl StreamNode for StreamSource {
                 SourceCatalogInfo::StreamSource(info) => Info::StreamSource(info.to_owned()),
                 SourceCatalogInfo::TableSource(info) => Info::TableSource(info.to_owned()),
             }),
-            row_id_index: source_catalog
-                .row_id_index
+            row_id_index: self
+                .logical
+                .row_id_index()
                 .map(|index| ColumnIndex { index: index as _ }),
-            columns: source_catalog
-                .columns
+            // Only the output columns are passed to the executor, so that the pruned ones are
+            // never parsed.
+            columns: self
+                .logical
+                .output_col_idx()
                 .iter()
-                .map(|c| c.to_protobuf())
+                .map(|&idx| source_catalog.columns[idx].to_protobuf())
                 .collect_vec(),
             pk_column_ids: source_catalog
                 .pk_col_ids
@@ -98,6 +112,7 @@ impl StreamNode for StreamSource {
                 .map(Into::into)
                 .collect_vec(),
             properties: source_catalog.properties.clone(),
+            watermark_descs: self.logical.watermark_descs().to_vec(),
         })
     }
 }
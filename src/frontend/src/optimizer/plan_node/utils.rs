@@ -123,6 +123,10 @@ impl TableCatalogBuilder {
                 .value_indices
                 .unwrap_or_else(|| (0..self.columns.len()).collect_vec()),
             definition: "".into(),
+            statistics: None,
+            watermark_descs: vec![],
+            subscription_progress: None,
+            key_encoding_version: 0,
         }
     }
 
@@ -268,14 +268,20 @@ impl DistinctAggRule {
                     | AggKind::Avg
                     | AggKind::StringAgg
                     | AggKind::ArrayAgg
-                    | AggKind::FirstValue => (),
+                    | AggKind::FirstValue
+                    | AggKind::BitAnd
+                    | AggKind::BitOr
+                    | AggKind::BitXor => (),
                     AggKind::Count => {
                         agg_call.agg_kind = AggKind::Sum0;
                     }
                     // TODO: fix it as a real 2-phase plan of ApproxCountDistinct
-                    AggKind::ApproxCountDistinct => {
+                    AggKind::ApproxCountDistinct(_) => {
                         agg_call.agg_kind = AggKind::Sum0;
                     }
+                    AggKind::ApproxPercentile(..) => {
+                        unreachable!("approx_percentile can't appear with distinct aggregates")
+                    }
                 }
 
                 // the index of non-distinct aggs' subset in `column_subsets` is always 0 if it
@@ -17,7 +17,7 @@ use risingwave_pb::plan_common::JoinType;
 
 use super::{BoxedRule, Rule};
 use crate::expr::{Expr, ExprImpl, ExprRewriter, InputRef};
-use crate::optimizer::plan_node::LogicalJoin;
+use crate::optimizer::plan_node::{estimate_row_count, LogicalJoin, PlanTreeNodeBinary};
 use crate::optimizer::PlanRef;
 
 /// Convert right join type to left join type:
@@ -27,53 +27,59 @@ use crate::optimizer::PlanRef;
 /// `RightSemi` => `LeftSemi`
 ///
 /// `RightAnti` => `LeftAnti`
+///
+/// Also swaps the inputs of an inner join if the table statistics show that the right input, which
+/// is the build side of hash joins, is larger than the left one.
 pub struct JoinCommuteRule {}
 impl Rule for JoinCommuteRule {
     fn apply(&self, plan: PlanRef) -> Option<PlanRef> {
         let join: &LogicalJoin = plan.as_logical_join()?;
         let join_type = join.join_type();
-        match join_type {
-            JoinType::RightOuter | JoinType::RightSemi | JoinType::RightAnti => {
-                let (left, right, on, join_type, output_indices) = join.clone().decompose();
+        let should_commute = match join_type {
+            JoinType::RightOuter | JoinType::RightSemi | JoinType::RightAnti => true,
+            JoinType::Inner => Self::is_right_larger(join),
+            JoinType::LeftOuter
+            | JoinType::LeftSemi
+            | JoinType::LeftAnti
+            | JoinType::FullOuter
+            | JoinType::Unspecified => false,
+        };
+        if !should_commute {
+            return None;
+        }
 
-                let left_len = left.schema().len();
-                let right_len = right.schema().len();
+        let (left, right, on, join_type, output_indices) = join.clone().decompose();
 
-                let new_output_indices = output_indices
-                    .into_iter()
-                    .map(|i| {
-                        if i < left_len {
-                            i + right_len
-                        } else {
-                            i - left_len
-                        }
-                    })
-                    .collect_vec();
+        let left_len = left.schema().len();
+        let right_len = right.schema().len();
 
-                let mut condition_rewriter = Rewriter {
-                    join_left_len: left_len,
-                    join_left_offset: right_len as isize,
-                    join_right_offset: -(left_len as isize),
-                };
-                let new_on = on.rewrite_expr(&mut condition_rewriter);
+        let new_output_indices = output_indices
+            .into_iter()
+            .map(|i| {
+                if i < left_len {
+                    i + right_len
+                } else {
+                    i - left_len
+                }
+            })
+            .collect_vec();
 
-                let new_join = LogicalJoin::with_output_indices(
-                    right,
-                    left,
-                    Self::inverse_join_type(join_type),
-                    new_on,
-                    new_output_indices,
-                );
+        let mut condition_rewriter = Rewriter {
+            join_left_len: left_len,
+            join_left_offset: right_len as isize,
+            join_right_offset: -(left_len as isize),
+        };
+        let new_on = on.rewrite_expr(&mut condition_rewriter);
 
-                Some(new_join.into())
-            }
-            JoinType::Inner
-            | JoinType::LeftOuter
-            | JoinType::LeftSemi
-            | JoinType::LeftAnti
-            | JoinType::FullOuter
-            | JoinType::Unspecified => None,
-        }
+        let new_join = LogicalJoin::with_output_indices(
+            right,
+            left,
+            Self::inverse_join_type(join_type),
+            new_on,
+            new_output_indices,
+        );
+
+        Some(new_join.into())
     }
 }
 
@@ -105,6 +111,16 @@ impl JoinCommuteRule {
         Box::new(JoinCommuteRule {})
     }
 
+    fn is_right_larger(join: &LogicalJoin) -> bool {
+        match (
+            estimate_row_count(&join.left()),
+            estimate_row_count(&join.right()),
+        ) {
+            (Some(left), Some(right)) => right > left,
+            _ => false,
+        }
+    }
+
     fn inverse_join_type(join_type: JoinType) -> JoinType {
         match join_type {
             JoinType::Unspecified => JoinType::Unspecified,
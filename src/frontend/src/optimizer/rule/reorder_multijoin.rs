@@ -16,14 +16,18 @@ use super::super::plan_node::*;
 use super::Rule;
 use crate::optimizer::rule::BoxedRule;
 
-/// Reorders a multi join into a left deep join via the heuristic ordering
+/// Reorders a multi join into a left deep join via the ordering based on table statistics, or the
+/// heuristic ordering if the statistics of any input are unavailable.
 pub struct ReorderMultiJoinRule {}
 
 impl Rule for ReorderMultiJoinRule {
     fn apply(&self, plan: PlanRef) -> Option<PlanRef> {
         let join = plan.as_logical_multi_join()?;
         // check if join is inner and can be merged into multijoin
-        let join_ordering = join.heuristic_ordering().ok()?; // maybe panic here instead?
+        let join_ordering = match join.statistics_ordering() {
+            Some(join_ordering) => join_ordering,
+            None => join.heuristic_ordering().ok()?, // maybe panic here instead?
+        };
         let left_deep_join = join.as_reordered_left_deep_join(&join_ordering);
         Some(left_deep_join)
     }
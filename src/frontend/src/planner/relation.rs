@@ -17,6 +17,7 @@ use std::rc::Rc;
 use itertools::Itertools;
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_common::types::ScalarImpl;
+use risingwave_pb::catalog::WatermarkDesc;
 
 use crate::binder::{
     BoundBaseTable, BoundJoin, BoundSource, BoundSystemTable, BoundWindowTableFunction, Relation,
@@ -55,6 +56,10 @@ impl Planner {
     }
 
     pub(super) fn plan_base_table(&mut self, base_table: BoundBaseTable) -> Result<PlanRef> {
+        if let Some(statistics) = &base_table.table_catalog.statistics {
+            self.ctx()
+                .register_table_statistics(base_table.table_id, statistics.clone());
+        }
         Ok(LogicalScan::create(
             base_table.name,
             false,
@@ -73,6 +78,49 @@ impl Planner {
         Ok(LogicalSource::new(Rc::new(source.catalog), self.ctx()).into())
     }
 
+    /// Plans the input relation of a window table function. If it's an insert-only source, a
+    /// watermark is derived on the time column when `RW_AUTO_WATERMARK` is on. Otherwise the user
+    /// is noticed that the window states can never be cleaned.
+    fn plan_window_input(
+        &mut self,
+        input: Relation,
+        time_col_idx: usize,
+        kind: WindowTableFunctionKind,
+    ) -> Result<PlanRef> {
+        let source = match input {
+            Relation::Source(source) if source.catalog.is_insert_only() => source,
+            input => return self.plan_relation(input),
+        };
+
+        let ctx = self.ctx();
+        let config = ctx.inner().session_ctx.config();
+        let kind = format!("{:?}", kind).to_uppercase();
+        let column_name = source.catalog.columns[time_col_idx].name().to_string();
+        let source_name = source.catalog.name.clone();
+        let logical = LogicalSource::new(Rc::new(source.catalog), ctx.clone());
+        if !config.get_auto_watermark() {
+            ctx.notice_to_user(format!(
+                "{kind} is applied on column \"{column_name}\" of append-only source \
+                 \"{source_name}\" without a watermark, so the window states will never be \
+                 cleaned. Set `rw_auto_watermark` to derive one on the column."
+            ));
+            return Ok(logical.into());
+        }
+
+        let watermark = WatermarkDesc {
+            source_id: logical.source_catalog().id,
+            watermark_idx: time_col_idx as u32,
+            allowed_lateness_ms: config.get_auto_watermark_allowed_lateness_ms(),
+        };
+        ctx.notice_to_user(format!(
+            "derived a watermark on column \"{column_name}\" of source \"{source_name}\" for \
+             {kind}, with allowed lateness {}ms.",
+            watermark.allowed_lateness_ms
+        ));
+        ctx.register_derived_watermark(watermark.clone());
+        Ok(logical.with_watermark_descs(vec![watermark]).into())
+    }
+
     pub(super) fn plan_join(&mut self, join: BoundJoin) -> Result<PlanRef> {
         let left = self.plan_relation(join.left)?;
         let right = self.plan_relation(join.right)?;
@@ -111,6 +159,7 @@ impl Planner {
         args: Vec<ExprImpl>,
     ) -> Result<PlanRef> {
         let mut args = args.into_iter();
+        let time_col_idx = time_col.index();
 
         let col_data_types: Vec<_> = match &input {
             Relation::Source(s) => s
@@ -159,7 +208,8 @@ impl Planner {
                         .into();
                 exprs.push(window_start);
                 exprs.push(window_end);
-                let base = self.plan_relation(input)?;
+                let base =
+                    self.plan_window_input(input, time_col_idx, WindowTableFunctionKind::Tumble)?;
                 let project = LogicalProject::create(base, exprs);
                 Ok(project)
             }
@@ -176,7 +226,8 @@ impl Planner {
         time_col: InputRef,
         args: Vec<ExprImpl>,
     ) -> Result<PlanRef> {
-        let input = self.plan_relation(input)?;
+        let input =
+            self.plan_window_input(input, time_col.index(), WindowTableFunctionKind::Hop)?;
         let mut args = args.into_iter();
         let Some((ExprImpl::Literal(window_slide), ExprImpl::Literal(window_size))) = args.next_tuple() else {
             return Err(ErrorCode::BindError("Invalid arguments for HOP window function".to_string()).into());
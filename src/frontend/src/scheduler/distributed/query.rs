@@ -382,7 +382,7 @@ pub(crate) mod tests {
     use risingwave_common::catalog::{ColumnDesc, TableDesc};
     use risingwave_common::config::constant::hummock::TABLE_OPTION_DUMMY_RETENTION_SECOND;
     use risingwave_common::types::DataType;
-    use risingwave_pb::common::{HostAddress, ParallelUnit, WorkerNode, WorkerType};
+    use risingwave_pb::common::{HostAddress, ParallelUnit, WorkerNode, WorkerRole, WorkerType};
     use risingwave_pb::plan_common::JoinType;
     use risingwave_rpc_client::ComputeClientPool;
 
@@ -434,6 +434,13 @@ pub(crate) mod tests {
     }
 
     pub async fn create_query() -> Query {
+        create_query_with_scan_split(None).await
+    }
+
+    /// Creates the query of [`create_query`]. With `Some((table_bytes, target_bytes))`, the
+    /// scanned table has 256 vnodes distributed over all 24 parallel units and an estimated size
+    /// of `table_bytes`, and the scans are split by `target_bytes`.
+    pub async fn create_query_with_scan_split(scan_split: Option<(u64, u64)>) -> Query {
         // Construct a Hash Join with Exchange node.
         // Logical plan:
         //
@@ -557,6 +564,7 @@ pub(crate) mod tests {
             }),
             state: risingwave_pb::common::worker_node::State::Running as i32,
             parallel_units: generate_parallel_units(0, 0),
+            role: WorkerRole::Both as i32,
         };
         let worker2 = WorkerNode {
             id: 1,
@@ -567,6 +575,7 @@ pub(crate) mod tests {
             }),
             state: risingwave_pb::common::worker_node::State::Running as i32,
             parallel_units: generate_parallel_units(8, 1),
+            role: WorkerRole::Both as i32,
         };
         let worker3 = WorkerNode {
             id: 2,
@@ -577,15 +586,25 @@ pub(crate) mod tests {
             }),
             state: risingwave_pb::common::worker_node::State::Running as i32,
             parallel_units: generate_parallel_units(16, 2),
+            role: WorkerRole::Both as i32,
         };
         let workers = vec![worker1, worker2, worker3];
         let worker_node_manager = Arc::new(WorkerNodeManager::mock(workers));
-        worker_node_manager.insert_fragment_mapping(0, vec![]);
         let catalog = Arc::new(parking_lot::RwLock::new(Catalog::default()));
         catalog.write().insert_table_id_mapping(table_id, 0);
         let catalog_reader = CatalogReader::new(catalog);
         // Break the plan node into fragments.
-        let fragmenter = BatchPlanFragmenter::new(worker_node_manager, catalog_reader);
+        let mut fragmenter = BatchPlanFragmenter::new(worker_node_manager.clone(), catalog_reader);
+        match scan_split {
+            None => worker_node_manager.insert_fragment_mapping(0, vec![]),
+            Some((table_bytes, target_bytes)) => {
+                worker_node_manager
+                    .insert_fragment_mapping(0, (0..256).map(|vnode| vnode % 24).collect());
+                worker_node_manager
+                    .set_table_size_estimates(HashMap::from([(table_id.table_id, table_bytes)]));
+                fragmenter = fragmenter.with_scan_split_target_bytes(target_bytes);
+            }
+        }
         fragmenter.split(batch_exchange_node3.clone()).unwrap()
     }
 
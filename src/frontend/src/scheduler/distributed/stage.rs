@@ -304,17 +304,17 @@ impl StageRunner {
     ) -> SchedulerResult<()> {
         let mut futures = vec![];
 
-        if let Some(table_scan_info) = self.stage.table_scan_info.as_ref() && let Some(vnode_bitmaps) = table_scan_info.partitions() {
+        if let Some(table_scan_info) = self.stage.table_scan_info.as_ref() && let Some(partitions) = table_scan_info.partitions() {
             // If the stage has table scan nodes, we create tasks according to the data distribution
             // and partition of the table.
             // We let each task read one partition by setting the `vnode_ranges` of the scan node in
             // the task.
-            // We schedule the task to the worker node that owns the data partition.
-            let parallel_unit_ids = vnode_bitmaps.keys().cloned().collect_vec();
-            let workers = self.worker_node_manager.get_workers_by_parallel_unit_ids(&parallel_unit_ids)?;
+            // We schedule the task to the worker node that owns the data partition if it's alive.
+            let parallel_unit_ids = partitions.iter().map(|partition| partition.parallel_unit_id).collect_vec();
+            let workers = self.worker_node_manager.get_preferred_workers_by_parallel_unit_ids(&parallel_unit_ids)?;
 
-            for (i, (parallel_unit_id, worker)) in parallel_unit_ids
-                .into_iter()
+            for (i, (partition, worker)) in partitions
+                .iter()
                 .zip_eq(workers.into_iter())
                 .enumerate()
             {
@@ -323,8 +323,7 @@ impl StageRunner {
                     stage_id: self.stage.id,
                     task_id: i as u32,
                 };
-                let vnode_ranges = vnode_bitmaps[&parallel_unit_id].clone();
-                let plan_fragment = self.create_plan_fragment(i as u32, Some(vnode_ranges));
+                let plan_fragment = self.create_plan_fragment(i as u32, Some(partition.clone()));
                 futures.push(self.schedule_task(task_id, plan_fragment, Some(worker)));
             }
         } else {
@@ -0,0 +1,384 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic snapshot export of materialized views, i.e. the `s3_parquet` sinks.
+//!
+//! The exports are persisted in the catalog as sinks without streaming jobs, and each of them is
+//! run by one of the frontends.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use parking_lot::Mutex;
+use risingwave_batch::executor::{BoxedDataChunkStream, ExecutorBuilder};
+use risingwave_batch::task::TaskId;
+use risingwave_common::array::DataChunk;
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::error::{ErrorCode, Result, RwError};
+use risingwave_connector::sink::s3_parquet::{
+    is_s3_parquet_sink, ParquetExporter, S3ParquetConfig,
+};
+use risingwave_object_store::object::{parse_remote_object_store, ObjectStoreMetrics};
+use risingwave_pb::batch_plan::exchange_info::DistributionMode;
+use risingwave_pb::batch_plan::exchange_source::LocalExecutePlan::Plan;
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::batch_plan::{
+    ExchangeInfo, ExchangeNode, ExchangeSource, LocalExecutePlan, PlanFragment,
+    PlanNode as PlanNodeProst, RowSeqScanNode, TaskId as ProstTaskId, TaskOutputId,
+};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::plan_fragmenter::{vnode_mapping_to_owner_mapping, QueryId};
+use super::task_context::FrontendBatchTaskContext;
+use crate::catalog::sink_catalog::SinkCatalog;
+use crate::catalog::{CatalogError, SinkId, TableCatalog};
+use crate::session::{AuthContext, FrontendEnv};
+
+pub type SnapshotExportManagerRef = Arc<SnapshotExportManager>;
+
+/// How often the exports run by the frontend are reconciled with the catalog and the frontends in
+/// the cluster.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The frontend exporting the snapshots of the sink, among the frontends in ascending order of
+/// their ids. Every frontend picks the same one once it has learnt the same frontends from meta.
+pub fn snapshot_export_runner(sink_id: SinkId, frontend_ids: &[u32]) -> Option<u32> {
+    if frontend_ids.is_empty() {
+        return None;
+    }
+    Some(frontend_ids[sink_id as usize % frontend_ids.len()])
+}
+
+/// Runs the snapshot exports assigned to this frontend in the background, one task per export.
+///
+/// The exports are the `s3_parquet` sinks in the catalog, which have no streaming job. Each of
+/// them is run by one frontend, see [`snapshot_export_runner`], so that they're taken over by the
+/// other frontends when a frontend leaves the cluster. An export stops at the next reconciliation
+/// once its sink is dropped or it's assigned to another frontend.
+pub struct SnapshotExportManager {
+    /// The worker id of this frontend.
+    worker_id: u32,
+    exports: Mutex<HashMap<SinkId, JoinHandle<()>>>,
+}
+
+impl SnapshotExportManager {
+    pub fn new(worker_id: u32) -> Self {
+        Self {
+            worker_id,
+            exports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn worker_id(&self) -> u32 {
+        self.worker_id
+    }
+
+    /// The sinks whose snapshots are being exported by this frontend.
+    pub fn running_exports(&self) -> Vec<SinkId> {
+        self.exports.lock().keys().copied().sorted().collect()
+    }
+
+    /// Reconciles the exports every [`RECONCILE_INTERVAL`].
+    pub fn start(self: Arc<Self>, env: FrontendEnv) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(RECONCILE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                self.reconcile(&env).await;
+            }
+        })
+    }
+
+    /// Starts the exports assigned to this frontend, and stops the others.
+    pub async fn reconcile(&self, env: &FrontendEnv) {
+        let frontend_ids = env.worker_node_manager().list_frontend_ids();
+        let assigned: HashMap<SinkId, (String, Arc<SinkCatalog>)> = {
+            let catalog = env.catalog_reader().read_guard();
+            let mut assigned = HashMap::new();
+            for db_name in catalog.get_all_database_names() {
+                for schema in catalog.iter_schemas(&db_name).unwrap() {
+                    for sink in schema.iter_sink() {
+                        if is_s3_parquet_sink(sink.properties.inner())
+                            && snapshot_export_runner(sink.id, &frontend_ids)
+                                == Some(self.worker_id)
+                        {
+                            assigned.insert(sink.id, (db_name.clone(), sink.clone()));
+                        }
+                    }
+                }
+            }
+            assigned
+        };
+
+        let to_start = {
+            let mut exports = self.exports.lock();
+            exports.retain(|sink_id, handle| {
+                let keep = assigned.contains_key(sink_id);
+                if !keep {
+                    tracing::info!("stop exporting the snapshots of sink {}", sink_id);
+                    // The files of the snapshot being exported are removed by the exporter once
+                    // it's cancelled.
+                    handle.abort();
+                }
+                keep
+            });
+            assigned
+                .into_iter()
+                .filter(|(sink_id, _)| !exports.contains_key(sink_id))
+                .collect_vec()
+        };
+
+        for (sink_id, (db_name, sink)) in to_start {
+            match start_export(env, db_name, &sink).await {
+                Ok(handle) => {
+                    tracing::info!("start exporting the snapshots of sink {}", sink_id);
+                    self.exports.lock().insert(sink_id, handle);
+                }
+                Err(e) => tracing::warn!(
+                    "failed to start exporting the snapshots of sink {}: {}",
+                    sink_id,
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Starts to export the snapshots of the view of `sink` every `interval` of it.
+async fn start_export(
+    env: &FrontendEnv,
+    db_name: String,
+    sink: &SinkCatalog,
+) -> Result<JoinHandle<()>> {
+    let config =
+        S3ParquetConfig::from_hashmap(sink.properties.inner().clone()).map_err(RwError::from)?;
+    let table = env
+        .catalog_reader()
+        .read_guard()
+        .get_table_by_id(&sink.associated_table_id)?;
+    let owner_name = env
+        .user_info_reader()
+        .read_guard()
+        .get_user_name_by_id(sink.owner)
+        .ok_or_else(|| CatalogError::NotFound("user", sink.owner.to_string()))?;
+    let auth_context = Arc::new(AuthContext::new(db_name, owner_name, sink.owner));
+
+    let (url, prefix) = config.object_store_url_and_prefix();
+    let store = parse_remote_object_store(&url, Arc::new(ObjectStoreMetrics::unused())).await;
+    let export = SnapshotExport::new(
+        env.clone(),
+        auth_context,
+        &table,
+        ParquetExporter::new(Arc::new(store), prefix, snapshot_schema(&table))?,
+    );
+
+    let interval = config.interval;
+    Ok(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = export.run().await {
+                tracing::warn!("failed to export snapshot of {}: {}", export.table_name, e);
+            }
+        }
+    }))
+}
+
+/// The visible columns of `table`, which are the columns of the exported files.
+fn snapshot_schema(table: &TableCatalog) -> Schema {
+    Schema::new(
+        table
+            .columns()
+            .iter()
+            .filter(|c| !c.is_hidden())
+            .map(|c| Field::with_name(c.data_type().clone(), c.name()))
+            .collect(),
+    )
+}
+
+struct SnapshotExport {
+    env: FrontendEnv,
+    auth_context: Arc<AuthContext>,
+    table_name: String,
+    fragment_id: u32,
+    scan_node: RowSeqScanNode,
+    exporter: ParquetExporter,
+}
+
+impl SnapshotExport {
+    fn new(
+        env: FrontendEnv,
+        auth_context: Arc<AuthContext>,
+        table: &TableCatalog,
+        exporter: ParquetExporter,
+    ) -> Self {
+        let scan_node = RowSeqScanNode {
+            table_desc: Some(table.table_desc().to_protobuf()),
+            column_ids: table
+                .columns()
+                .iter()
+                .filter(|c| !c.is_hidden())
+                .map(|c| c.column_id().get_id())
+                .collect(),
+            scan_ranges: vec![],
+            vnode_bitmap: None,
+        };
+        Self {
+            env,
+            auth_context,
+            table_name: table.name().to_string(),
+            fragment_id: table.fragment_id,
+            scan_node,
+            exporter,
+        }
+    }
+
+    /// Exports the snapshot at the latest committed epoch, one file per partition of the table.
+    async fn run(&self) -> Result<()> {
+        let query_id = QueryId {
+            id: Uuid::new_v4().to_string(),
+        };
+        // The epoch is pinned until the export finishes.
+        let snapshot = self
+            .env
+            .hummock_snapshot_manager()
+            .acquire(&query_id)
+            .await?;
+        let epoch = snapshot.get_committed_epoch();
+
+        let worker_node_manager = self.env.worker_node_manager();
+        let vnode_mapping = worker_node_manager
+            .get_fragment_mapping(&self.fragment_id)
+            .ok_or_else(|| {
+                RwError::from(ErrorCode::InternalError(format!(
+                    "vnode mapping of {} not found",
+                    self.table_name
+                )))
+            })?;
+        let (parallel_unit_ids, vnode_bitmaps): (Vec<_>, Vec<_>) =
+            vnode_mapping_to_owner_mapping(vnode_mapping)
+                .into_iter()
+                .sorted_by_key(|(parallel_unit_id, _)| *parallel_unit_id)
+                .unzip();
+        let workers = worker_node_manager.get_workers_by_parallel_unit_ids(&parallel_unit_ids)?;
+
+        let partitions = workers
+            .into_iter()
+            .zip_eq(vnode_bitmaps)
+            .enumerate()
+            .map(|(idx, (worker, vnode_bitmap))| {
+                let scan_node = RowSeqScanNode {
+                    vnode_bitmap: Some(vnode_bitmap.to_protobuf()),
+                    ..self.scan_node.clone()
+                };
+                let source = ExchangeSource {
+                    task_output_id: Some(TaskOutputId {
+                        task_id: Some(ProstTaskId {
+                            query_id: query_id.id.clone(),
+                            stage_id: 1,
+                            task_id: idx as u32,
+                        }),
+                        output_id: 0,
+                    }),
+                    host: worker.host,
+                    local_execute_plan: Some(Plan(LocalExecutePlan {
+                        plan: Some(PlanFragment {
+                            root: Some(PlanNodeProst {
+                                children: vec![],
+                                identity: Uuid::new_v4().to_string(),
+                                node_body: Some(NodeBody::RowSeqScan(scan_node)),
+                            }),
+                            exchange_info: Some(ExchangeInfo {
+                                mode: DistributionMode::Single as i32,
+                                ..Default::default()
+                            }),
+                        }),
+                        epoch,
+                    })),
+                };
+                let exchange = PlanNodeProst {
+                    children: vec![],
+                    identity: Uuid::new_v4().to_string(),
+                    node_body: Some(NodeBody::Exchange(ExchangeNode {
+                        sources: vec![source],
+                        input_schema: vec![],
+                    })),
+                };
+                let task_id = TaskId {
+                    query_id: query_id.id.clone(),
+                    stage_id: 0,
+                    task_id: idx as u32,
+                };
+                let context =
+                    FrontendBatchTaskContext::new(self.env.clone(), self.auth_context.clone());
+                execute(exchange, task_id, context, epoch)
+            })
+            .collect();
+
+        let manifest = self.exporter.export(epoch, partitions).await?;
+        tracing::info!(
+            "exported {} rows of {} at epoch {}",
+            manifest.total_rows,
+            self.table_name,
+            epoch
+        );
+        Ok(())
+    }
+}
+
+fn execute(
+    plan: PlanNodeProst,
+    task_id: TaskId,
+    context: FrontendBatchTaskContext,
+    epoch: u64,
+) -> BoxedDataChunkStream {
+    #[try_stream(ok = DataChunk, error = RwError)]
+    async fn execute_inner(
+        plan: PlanNodeProst,
+        task_id: TaskId,
+        context: FrontendBatchTaskContext,
+        epoch: u64,
+    ) {
+        let executor = ExecutorBuilder::new(&plan, &task_id, context, epoch)
+            .build()
+            .await?;
+        #[for_await]
+        for chunk in executor.execute() {
+            yield chunk?;
+        }
+    }
+    Box::pin(execute_inner(plan, task_id, context, epoch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_export_runner() {
+        assert_eq!(snapshot_export_runner(1, &[]), None);
+        assert_eq!(snapshot_export_runner(1, &[3]), Some(3));
+        let frontend_ids = [2, 5, 7];
+        let runners = (0..6)
+            .map(|sink_id| snapshot_export_runner(sink_id, &frontend_ids).unwrap())
+            .collect_vec();
+        assert_eq!(runners, vec![2, 5, 7, 2, 5, 7]);
+    }
+}
@@ -29,6 +29,8 @@ use crate::meta_client::FrontendMetaClient;
 use crate::scheduler::plan_fragmenter::QueryId;
 use crate::scheduler::{SchedulerError, SchedulerResult};
 
+/// The interval to unpin the snapshots no longer used by queries. It also renews the lease of the
+/// snapshot pinned by this frontend, so it must be shorter than `snapshot_pin_lease_sec` of meta.
 const UNPIN_INTERVAL_SECS: u64 = 10;
 
 pub type HummockSnapshotManagerRef = Arc<HummockSnapshotManager>;
@@ -185,6 +187,11 @@ impl HummockSnapshotManager {
         })
     }
 
+    /// Returns the latest committed epoch known by the frontend, without pinning it.
+    pub fn latest_committed_epoch(&self) -> u64 {
+        self.latest_snapshot.load().committed_epoch
+    }
+
     pub fn update_epoch(&self, snapshot: HummockSnapshot) {
         // Note: currently the snapshot is not only updated from the observer, so we need to take
         // the `max` here instead of directly replace the snapshot.
@@ -313,7 +320,9 @@ impl HummockSnapshotManagerCore {
             last_unpin_snapshot
         );
 
-        if min_epoch <= last_unpin_snapshot {
+        // Still send the RPC if the min epoch hasn't advanced, which renews the lease of the pinned
+        // snapshot on meta.
+        if min_epoch < last_unpin_snapshot {
             return;
         }
 
@@ -324,8 +333,139 @@ impl HummockSnapshotManagerCore {
             tracing::info!("Unpin epoch {:?} with RPC", min_epoch);
             match meta_client.unpin_snapshot_before(min_epoch).await {
                 Ok(()) => last_unpin_epoch.store(min_epoch, Ordering::Release),
-                Err(e) => error!("Request meta to unpin snapshot failed {:?}!", e),
+                Err(e) => {
+                    error!("Request meta to unpin snapshot failed {:?}!", e);
+                    // Meta refuses to advance a pin released for its expired lease, so pin a
+                    // snapshot again for the following queries.
+                    if let Err(e) = meta_client.pin_snapshot().await {
+                        error!("Request meta to pin snapshot failed {:?}!", e);
+                    }
+                }
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use risingwave_pb::hummock::get_table_write_stats_response::TableWriteStats;
+    use risingwave_pb::hummock::HummockVersion;
+    use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
+    use risingwave_pb::meta::get_table_complexity_response::TableComplexity;
+    use risingwave_pb::meta::list_table_fragments_response::TableFragmentInfo;
+    use risingwave_pb::meta::GetClusterStatusResponse;
+    use risingwave_rpc_client::error::Result as RpcResult;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+    use crate::test_utils::MockFrontendMetaClient;
+
+    /// Records the epochs of `unpin_snapshot_before`.
+    struct UnpinRecordingMetaClient {
+        inner: MockFrontendMetaClient,
+        unpin_tx: UnboundedSender<u64>,
+    }
+
+    #[async_trait::async_trait]
+    impl FrontendMetaClient for UnpinRecordingMetaClient {
+        async fn pin_snapshot(&self) -> RpcResult<HummockSnapshot> {
+            self.inner.pin_snapshot().await
+        }
+
+        async fn get_epoch(&self) -> RpcResult<HummockSnapshot> {
+            self.inner.get_epoch().await
+        }
+
+        async fn flush(&self, checkpoint: bool) -> RpcResult<HummockSnapshot> {
+            self.inner.flush(checkpoint).await
+        }
+
+        async fn list_table_fragments(
+            &self,
+            table_ids: &[u32],
+        ) -> RpcResult<HashMap<u32, TableFragmentInfo>> {
+            self.inner.list_table_fragments(table_ids).await
+        }
+
+        async fn unpin_snapshot(&self) -> RpcResult<()> {
+            self.inner.unpin_snapshot().await
+        }
+
+        async fn unpin_snapshot_before(&self, epoch: u64) -> RpcResult<()> {
+            self.unpin_tx.send(epoch).unwrap();
+            Ok(())
+        }
+
+        async fn get_table_write_stats(&self) -> RpcResult<Vec<TableWriteStats>> {
+            self.inner.get_table_write_stats().await
+        }
+
+        async fn get_current_version(&self) -> RpcResult<HummockVersion> {
+            self.inner.get_current_version().await
+        }
+
+        async fn get_table_complexity(&self) -> RpcResult<Vec<TableComplexity>> {
+            self.inner.get_table_complexity().await
+        }
+
+        async fn get_backfill_progress(&self) -> RpcResult<Vec<BackfillProgress>> {
+            self.inner.get_backfill_progress().await
+        }
+
+        async fn dump_table_fragments(&self, table_id: u32) -> RpcResult<String> {
+            self.inner.dump_table_fragments(table_id).await
+        }
+
+        async fn get_cluster_status(&self) -> RpcResult<GetClusterStatusResponse> {
+            self.inner.get_cluster_status().await
+        }
+    }
+
+    fn snapshot(epoch: u64) -> HummockSnapshot {
+        HummockSnapshot {
+            committed_epoch: epoch,
+            current_epoch: epoch,
+        }
+    }
+
+    fn new_core(
+        latest_snapshot: SnapshotRef,
+    ) -> (HummockSnapshotManagerCore, UnboundedReceiver<u64>) {
+        let (unpin_tx, unpin_rx) = unbounded_channel();
+        let meta_client = Arc::new(UnpinRecordingMetaClient {
+            inner: MockFrontendMetaClient {},
+            unpin_tx,
+        });
+        (
+            HummockSnapshotManagerCore::new(meta_client, latest_snapshot),
+            unpin_rx,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_query_spanning_lease_renewal() {
+        let latest_snapshot = Arc::new(ArcSwap::from_pointee(snapshot(10)));
+        let (mut core, mut unpin_rx) = new_core(latest_snapshot.clone());
+
+        let query_id = QueryId {
+            id: "q1".to_string(),
+        };
+        let (tx, rx) = once_channel();
+        core.get_epoch_for_query_from_push(&mut vec![(query_id.clone(), tx)]);
+        assert_eq!(rx.await.unwrap().unwrap(), snapshot(10));
+
+        // The running query keeps the epoch pinned, while each unpin renews the lease on meta even
+        // though the min epoch doesn't advance.
+        latest_snapshot.store(Arc::new(snapshot(20)));
+        for _ in 0..3 {
+            core.unpin_snapshot_before(20);
+            assert_eq!(unpin_rx.recv().await.unwrap(), 10);
+        }
+
+        core.release_epoch(&mut vec![(query_id, 10)]);
+        core.unpin_snapshot_before(20);
+        assert_eq!(unpin_rx.recv().await.unwrap(), 20);
+    }
+}
@@ -220,16 +220,15 @@ impl LocalQueryExecution {
                 };
                 assert!(sources.is_empty());
 
-                if let Some(table_scan_info) = second_stage.table_scan_info.clone() && let Some(vnode_bitmaps) = table_scan_info.partitions() {
+                if let Some(table_scan_info) = second_stage.table_scan_info.clone() && let Some(partitions) = table_scan_info.partitions() {
                     // Similar to the distributed case (StageRunner::schedule_tasks).
                     // Set `vnode_ranges` of the scan node in `local_execute_plan` of each
                     // `exchange_source`.
-                    let (parallel_unit_ids, vnode_bitmaps): (Vec<_>, Vec<_>) =
-                        vnode_bitmaps.clone().into_iter().unzip();
-                    let workers = self.front_env.worker_node_manager().get_workers_by_parallel_unit_ids(&parallel_unit_ids)?;
+                    let parallel_unit_ids = partitions.iter().map(|partition| partition.parallel_unit_id).collect_vec();
+                    let workers = self.front_env.worker_node_manager().get_preferred_workers_by_parallel_unit_ids(&parallel_unit_ids)?;
 
                     for (idx, (worker_node, partition)) in
-                        (workers.into_iter().zip_eq(vnode_bitmaps.into_iter())).enumerate()
+                        (workers.into_iter().zip_eq(partitions.iter().cloned())).enumerate()
                     {
                         let second_stage_plan_node = self.convert_plan_node(
                             &second_stage.root,
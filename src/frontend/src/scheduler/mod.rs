@@ -24,12 +24,16 @@ use crate::session::SessionImpl;
 
 mod distributed;
 pub use distributed::*;
+mod export;
+pub use export::*;
 mod hummock_snapshot_manager;
 pub use hummock_snapshot_manager::*;
 pub mod plan_fragmenter;
 pub use plan_fragmenter::BatchPlanFragmenter;
 mod local;
 pub use local::*;
+mod result_cache;
+pub use result_cache::*;
 
 use crate::scheduler::task_context::FrontendBatchTaskContext;
 
@@ -114,6 +114,9 @@ pub struct BatchPlanFragmenter {
     next_stage_id: StageId,
     worker_node_manager: WorkerNodeManagerRef,
     catalog_reader: CatalogReader,
+    /// The target size of the data read by each task of a full table scan. `None` to read each
+    /// partition of the vnode mapping in a task.
+    scan_split_target_bytes: Option<u64>,
 }
 
 impl Default for QueryId {
@@ -132,8 +135,17 @@ impl BatchPlanFragmenter {
             next_stage_id: 0,
             worker_node_manager,
             catalog_reader,
+            scan_split_target_bytes: None,
         }
     }
+
+    /// Splits the full table scans by the estimated table sizes, so that each task reads about
+    /// `target_bytes`. The number of tasks may be less or more than the parallelism of the table.
+    /// A zero `target_bytes` disables it.
+    pub fn with_scan_split_target_bytes(mut self, target_bytes: u64) -> Self {
+        self.scan_split_target_bytes = (target_bytes > 0).then_some(target_bytes);
+        self
+    }
 }
 
 /// The fragmented query generated by [`BatchPlanFragmenter`].
@@ -199,12 +211,12 @@ pub struct TableScanInfo {
     /// full vnode bitmap, since we need to know where to schedule the singleton scan task.
     ///
     /// `None` iff the table is a system table.
-    partitions: Option<HashMap<ParallelUnitId, PartitionInfo>>,
+    partitions: Option<Vec<PartitionInfo>>,
 }
 
 impl TableScanInfo {
     /// For normal tables, `partitions` should always be `Some`.
-    pub fn new(name: String, partitions: HashMap<ParallelUnitId, PartitionInfo>) -> Self {
+    pub fn new(name: String, partitions: Vec<PartitionInfo>) -> Self {
         Self {
             name,
             partitions: Some(partitions),
@@ -223,13 +235,16 @@ impl TableScanInfo {
         self.name.as_ref()
     }
 
-    pub fn partitions(&self) -> Option<&HashMap<u32, PartitionInfo>> {
-        self.partitions.as_ref()
+    pub fn partitions(&self) -> Option<&[PartitionInfo]> {
+        self.partitions.as_deref()
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct PartitionInfo {
+    /// The parallel unit owning the vnodes of the partition, or most of them if the partition is
+    /// split by size. The task reading the partition prefers its worker for cache locality.
+    pub parallel_unit_id: ParallelUnitId,
     pub vnode_bitmap: Buffer,
     pub scan_ranges: Vec<ScanRangeProto>,
 }
@@ -447,14 +462,9 @@ impl BatchPlanFragmenter {
                                 partitions.len()
                             );
 
-                            *partitions = partitions
-                                .drain()
-                                .take(1)
-                                .update(|(_, info)| {
-                                    info.vnode_bitmap =
-                                        Bitmap::all_high_bits(VIRTUAL_NODE_COUNT).to_protobuf();
-                                })
-                                .collect();
+                            partitions.truncate(1);
+                            partitions[0].vnode_bitmap =
+                                Bitmap::all_high_bits(VIRTUAL_NODE_COUNT).to_protobuf();
                         }
                     } else {
                         // System table
@@ -560,8 +570,18 @@ impl BatchPlanFragmenter {
                             table_catalog.name()
                         )
                     })?;
-                let partitions =
-                    derive_partitions(scan_node.scan_ranges(), table_desc, &vnode_mapping);
+                let estimated_bytes = self
+                    .worker_node_manager
+                    .get_table_size_estimate(table_desc.table_id.table_id);
+                let partitions = match (self.scan_split_target_bytes, estimated_bytes) {
+                    (Some(target_bytes), Some(estimated_bytes))
+                        if scan_node.scan_ranges().is_empty() =>
+                    {
+                        let split_count = (estimated_bytes + target_bytes - 1) / target_bytes;
+                        split_vnodes(&vnode_mapping, split_count as usize)
+                    }
+                    _ => derive_partitions(scan_node.scan_ranges(), table_desc, &vnode_mapping),
+                };
                 TableScanInfo::new(name, partitions)
             };
             Ok(Some(info))
@@ -575,7 +595,9 @@ impl BatchPlanFragmenter {
 }
 
 // TODO: let frontend store owner_mapping directly?
-fn vnode_mapping_to_owner_mapping(vnode_mapping: VnodeMapping) -> HashMap<ParallelUnitId, Bitmap> {
+pub(crate) fn vnode_mapping_to_owner_mapping(
+    vnode_mapping: VnodeMapping,
+) -> HashMap<ParallelUnitId, Bitmap> {
     let mut m: HashMap<ParallelUnitId, BitmapBuilder> = HashMap::new();
     let num_vnodes = vnode_mapping.len();
     for (i, parallel_unit_id) in vnode_mapping.into_iter().enumerate() {
@@ -587,27 +609,62 @@ fn vnode_mapping_to_owner_mapping(vnode_mapping: VnodeMapping) -> HashMap<Parall
     m.into_iter().map(|(k, v)| (k, v.finish())).collect()
 }
 
+/// Splits all vnodes into `split_count` partitions of consecutive vnodes ordered by their owners,
+/// whose sizes differ by at most one. `split_count` is clamped to `[1, vnode count]`.
+fn split_vnodes(vnode_mapping: &VnodeMapping, split_count: usize) -> Vec<PartitionInfo> {
+    let num_vnodes = vnode_mapping.len();
+    if num_vnodes == 0 {
+        return vec![];
+    }
+    let split_count = split_count.clamp(1, num_vnodes);
+    let vnodes = (0..num_vnodes)
+        .sorted_by_key(|&vnode| (vnode_mapping[vnode], vnode))
+        .collect_vec();
+
+    let mut partitions = Vec::with_capacity(split_count);
+    let mut rest = vnodes.as_slice();
+    for i in 0..split_count {
+        let len = num_vnodes / split_count + usize::from(i < num_vnodes % split_count);
+        let (split, remaining) = rest.split_at(len);
+        rest = remaining;
+
+        let mut bitmap = BitmapBuilder::zeroed(num_vnodes);
+        let mut owner_counts: HashMap<ParallelUnitId, usize> = HashMap::new();
+        for &vnode in split {
+            bitmap.set(vnode, true);
+            *owner_counts.entry(vnode_mapping[vnode]).or_default() += 1;
+        }
+        let (parallel_unit_id, _) = owner_counts
+            .into_iter()
+            .max_by_key(|&(parallel_unit_id, count)| (count, std::cmp::Reverse(parallel_unit_id)))
+            .unwrap();
+        partitions.push(PartitionInfo {
+            parallel_unit_id,
+            vnode_bitmap: bitmap.finish().to_protobuf(),
+            scan_ranges: vec![],
+        });
+    }
+    partitions
+}
+
 /// Try to derive the partition to read from the scan range.
 /// It can be derived if the value of the distribution key is already known.
 fn derive_partitions(
     scan_ranges: &[ScanRange],
     table_desc: &TableDesc,
     vnode_mapping: &VnodeMapping,
-) -> HashMap<ParallelUnitId, PartitionInfo> {
+) -> Vec<PartitionInfo> {
     let num_vnodes = vnode_mapping.len();
     let mut partitions: HashMap<ParallelUnitId, (BitmapBuilder, Vec<_>)> = HashMap::new();
 
     if scan_ranges.is_empty() {
         return vnode_mapping_to_owner_mapping(vnode_mapping.clone())
             .into_iter()
-            .map(|(k, vnode_bitmap)| {
-                (
-                    k,
-                    PartitionInfo {
-                        vnode_bitmap: vnode_bitmap.to_protobuf(),
-                        scan_ranges: vec![],
-                    },
-                )
+            .sorted_by_key(|(parallel_unit_id, _)| *parallel_unit_id)
+            .map(|(parallel_unit_id, vnode_bitmap)| PartitionInfo {
+                parallel_unit_id,
+                vnode_bitmap: vnode_bitmap.to_protobuf(),
+                scan_ranges: vec![],
             })
             .collect();
     }
@@ -647,14 +704,11 @@ fn derive_partitions(
 
     partitions
         .into_iter()
-        .map(|(k, (bitmap, scan_ranges))| {
-            (
-                k,
-                PartitionInfo {
-                    vnode_bitmap: bitmap.finish().to_protobuf(),
-                    scan_ranges,
-                },
-            )
+        .sorted_by_key(|(parallel_unit_id, _)| *parallel_unit_id)
+        .map(|(parallel_unit_id, (bitmap, scan_ranges))| PartitionInfo {
+            parallel_unit_id,
+            vnode_bitmap: bitmap.finish().to_protobuf(),
+            scan_ranges,
         })
         .collect()
 }
@@ -663,10 +717,13 @@ fn derive_partitions(
 mod tests {
     use std::collections::{HashMap, HashSet};
 
-    use risingwave_common::types::ParallelUnitId;
+    use itertools::Itertools;
+    use risingwave_common::buffer::Bitmap;
+    use risingwave_common::types::{ParallelUnitId, VnodeMapping};
     use risingwave_pb::batch_plan::plan_node::NodeBody;
     use risingwave_pb::common::ParallelUnit;
 
+    use super::{split_vnodes, PartitionInfo, Query};
     use crate::optimizer::plan_node::PlanNodeType;
     use crate::scheduler::plan_fragmenter::StageId;
 
@@ -751,6 +808,116 @@ mod tests {
         assert!(scan_node2.has_table_scan());
     }
 
+    /// The vnodes of each partition, and the number of them owned by its parallel unit.
+    fn partition_vnodes(
+        partitions: &[PartitionInfo],
+        vnode_mapping: &VnodeMapping,
+    ) -> Vec<(Vec<usize>, usize)> {
+        partitions
+            .iter()
+            .map(|partition| {
+                let vnodes = Bitmap::from(&partition.vnode_bitmap)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, set)| *set)
+                    .map(|(vnode, _)| vnode)
+                    .collect_vec();
+                let owned = vnodes
+                    .iter()
+                    .filter(|&&vnode| vnode_mapping[vnode] == partition.parallel_unit_id)
+                    .count();
+                (vnodes, owned)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_vnodes() {
+        // 8 parallel units owning 32 consecutive vnodes each.
+        let vnode_mapping: VnodeMapping = (0..256).map(|vnode| vnode / 32).collect();
+
+        // A tiny table is read by a single task, preferring the parallel unit of the smallest id.
+        let partitions = split_vnodes(&vnode_mapping, 1);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions[0].parallel_unit_id, 0);
+        assert_eq!(
+            partition_vnodes(&partitions, &vnode_mapping)[0].0,
+            (0..256).collect_vec()
+        );
+
+        // A huge table is read by more tasks than the parallel units, each of which mostly reads
+        // the vnodes of a single parallel unit.
+        let partitions = split_vnodes(&vnode_mapping, 20);
+        assert_eq!(partitions.len(), 20);
+        let vnodes = partition_vnodes(&partitions, &vnode_mapping);
+        for (vnodes, owned) in &vnodes {
+            assert!(vnodes.len() == 12 || vnodes.len() == 13);
+            assert!(*owned * 2 >= vnodes.len());
+        }
+        assert_eq!(
+            vnodes
+                .iter()
+                .flat_map(|(vnodes, _)| vnodes)
+                .copied()
+                .sorted()
+                .collect_vec(),
+            (0..256).collect_vec()
+        );
+
+        // At most a task per vnode.
+        assert_eq!(split_vnodes(&vnode_mapping, 1000).len(), 256);
+        assert!(split_vnodes(&VnodeMapping::new(), 1).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fragmenter_scan_split() {
+        use crate::scheduler::distributed::tests::create_query_with_scan_split;
+
+        let scan_partitions = |query: &Query| {
+            let stage = &query.stage_graph.stages[&2];
+            let partitions = stage
+                .table_scan_info
+                .as_ref()
+                .unwrap()
+                .partitions()
+                .unwrap();
+            assert_eq!(stage.parallelism as usize, partitions.len());
+            partitions.to_vec()
+        };
+        let vnode_mapping: VnodeMapping = (0..256).map(|vnode| vnode % 24).collect();
+        const MB: u64 = 1 << 20;
+
+        // A tiny table.
+        let query = create_query_with_scan_split(Some((MB, 64 * MB))).await;
+        let partitions = scan_partitions(&query);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(
+            partition_vnodes(&partitions, &vnode_mapping)[0].0.len(),
+            256
+        );
+
+        // A huge table, split into more tasks than the 24 parallel units.
+        let query = create_query_with_scan_split(Some((100 * 64 * MB, 64 * MB))).await;
+        let partitions = scan_partitions(&query);
+        assert_eq!(partitions.len(), 100);
+        let vnode_count: usize = partition_vnodes(&partitions, &vnode_mapping)
+            .iter()
+            .map(|(vnodes, _)| vnodes.len())
+            .sum();
+        assert_eq!(vnode_count, 256);
+
+        // Without splitting, each parallel unit is read by a task.
+        let query = create_query_with_scan_split(Some((100 * 64 * MB, 0))).await;
+        let partitions = scan_partitions(&query);
+        assert_eq!(
+            partitions
+                .iter()
+                .map(|partition| partition.parallel_unit_id)
+                .collect_vec(),
+            (0..24).collect_vec()
+        );
+    }
+
     fn generate_parallel_units(
         start_id: ParallelUnitId,
         node_id: ParallelUnitId,
@@ -0,0 +1,408 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Caches the results of batch queries repeated in a session, e.g. by dashboards polling the same
+//! `SELECT`, until new data is committed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures_async_stream::try_stream;
+use parking_lot::Mutex;
+use pgwire::pg_field_descriptor::PgFieldDescriptor;
+use pgwire::pg_server::{BoxedError, SessionId};
+use pgwire::types::Row;
+
+use crate::monitor::FrontendMetrics;
+use crate::PgResponseStream;
+
+pub type QueryResultCacheRef = Arc<QueryResultCache>;
+
+/// Identifies the result of a query in a session.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QueryResultCacheKey {
+    pub session_id: SessionId,
+    /// The normalized query text, in which the parameters of a prepared statement are already
+    /// substituted.
+    pub query: String,
+    /// The session settings the query is bound and the rows are formatted by.
+    pub format: bool,
+    pub search_path: String,
+    pub time_zone: String,
+}
+
+/// The state of the cluster a query result is read at. The cached result is valid as long as none
+/// of them has changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryResultVersion {
+    pub committed_epoch: u64,
+    /// Changes if any relation is created, altered or dropped.
+    pub catalog_version: u64,
+    /// Changes if any privilege is granted or revoked.
+    pub user_info_version: u64,
+    /// Changes if any fragment is rescheduled.
+    pub mapping_version: u64,
+}
+
+impl QueryResultVersion {
+    fn is_valid(&self, current: &QueryResultVersion) -> bool {
+        current.committed_epoch <= self.committed_epoch
+            && current.catalog_version == self.catalog_version
+            && current.user_info_version == self.user_info_version
+            && current.mapping_version == self.mapping_version
+    }
+}
+
+/// A cached query result.
+#[derive(Clone, Debug)]
+pub struct QueryResult {
+    pub fields: Vec<PgFieldDescriptor>,
+    pub rows: Arc<Vec<Row>>,
+}
+
+struct CacheEntry {
+    version: QueryResultVersion,
+    result: QueryResult,
+    size: usize,
+    /// The key of the entry in [`CacheCore::lru`].
+    last_access: u64,
+}
+
+#[derive(Default)]
+struct CacheCore {
+    entries: HashMap<QueryResultCacheKey, CacheEntry>,
+    /// The keys of the entries ordered by their last access, from the least recent one.
+    lru: BTreeMap<u64, QueryResultCacheKey>,
+    session_usage: HashMap<SessionId, usize>,
+    usage: usize,
+    access_count: u64,
+}
+
+impl CacheCore {
+    fn touch(&mut self, key: &QueryResultCacheKey) {
+        self.access_count += 1;
+        let entry = self.entries.get_mut(key).unwrap();
+        let key = self.lru.remove(&entry.last_access).unwrap();
+        entry.last_access = self.access_count;
+        self.lru.insert(self.access_count, key);
+    }
+
+    fn remove(&mut self, key: &QueryResultCacheKey) -> Option<CacheEntry> {
+        let entry = self.entries.remove(key)?;
+        self.lru.remove(&entry.last_access);
+        self.usage -= entry.size;
+        let session_usage = self.session_usage.get_mut(&key.session_id).unwrap();
+        *session_usage -= entry.size;
+        if *session_usage == 0 {
+            self.session_usage.remove(&key.session_id);
+        }
+        Some(entry)
+    }
+
+    /// Evicts the least recently used entries, of `session_id` if specified, until `usage` of them
+    /// is no more than `capacity`.
+    fn evict(&mut self, session_id: Option<SessionId>, capacity: usize) {
+        loop {
+            let usage = match session_id {
+                Some(session_id) => self.session_usage.get(&session_id).copied().unwrap_or(0),
+                None => self.usage,
+            };
+            if usage <= capacity {
+                return;
+            }
+            let key = self
+                .lru
+                .values()
+                .find(|key| session_id.map_or(true, |session_id| key.session_id == session_id))
+                .cloned()
+                .unwrap();
+            self.remove(&key);
+        }
+    }
+}
+
+/// An LRU cache of query results, which is bounded both per session and globally.
+///
+/// A result is cached with the [`QueryResultVersion`] it's read at, and is invalidated once the
+/// committed epoch advances past it, or the catalog, the privileges or the fragment mappings
+/// change. The statements modifying the data or the catalog invalidate the results cached in their
+/// session, see [`QueryResultCache::invalidate_session`].
+pub struct QueryResultCache {
+    capacity: usize,
+    session_capacity: usize,
+    core: Mutex<CacheCore>,
+    metrics: Arc<FrontendMetrics>,
+}
+
+impl QueryResultCache {
+    pub fn new(capacity: usize, session_capacity: usize, metrics: Arc<FrontendMetrics>) -> Self {
+        Self {
+            capacity,
+            session_capacity,
+            core: Mutex::new(CacheCore::default()),
+            metrics,
+        }
+    }
+
+    /// Returns the cached result of `key` if it's still valid at the `current` version.
+    pub fn get(
+        &self,
+        key: &QueryResultCacheKey,
+        current: &QueryResultVersion,
+    ) -> Option<QueryResult> {
+        let mut core = self.core.lock();
+        let result = match core.entries.get(key) {
+            Some(entry) if entry.version.is_valid(current) => {
+                let result = entry.result.clone();
+                core.touch(key);
+                Some(result)
+            }
+            Some(_) => {
+                core.remove(key);
+                None
+            }
+            None => None,
+        };
+        self.report_usage(&core);
+        match result {
+            Some(_) => self.metrics.query_result_cache_hit_count.inc(),
+            None => self.metrics.query_result_cache_miss_count.inc(),
+        }
+        result
+    }
+
+    /// Caches the result of `key` read at `version`, evicting the least recently used results if
+    /// the capacity is exceeded. Returns false if the result is too large to be cached.
+    pub fn insert(
+        &self,
+        key: QueryResultCacheKey,
+        version: QueryResultVersion,
+        fields: Vec<PgFieldDescriptor>,
+        rows: Vec<Row>,
+    ) -> bool {
+        let size = entry_size(&key, &rows);
+        if size > self.session_capacity || size > self.capacity {
+            return false;
+        }
+
+        let mut core = self.core.lock();
+        core.remove(&key);
+        core.evict(Some(key.session_id), self.session_capacity - size);
+        core.evict(None, self.capacity - size);
+
+        core.access_count += 1;
+        let last_access = core.access_count;
+        core.lru.insert(last_access, key.clone());
+        *core.session_usage.entry(key.session_id).or_default() += size;
+        core.usage += size;
+        core.entries.insert(
+            key,
+            CacheEntry {
+                version,
+                result: QueryResult {
+                    fields,
+                    rows: Arc::new(rows),
+                },
+                size,
+                last_access,
+            },
+        );
+        self.report_usage(&core);
+        true
+    }
+
+    /// Removes the results cached in the session.
+    pub fn invalidate_session(&self, session_id: SessionId) {
+        let mut core = self.core.lock();
+        let keys = core
+            .entries
+            .keys()
+            .filter(|key| key.session_id == session_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            core.remove(&key);
+        }
+        self.report_usage(&core);
+    }
+
+    /// Forwards the rows of `stream`, and caches them once the stream is exhausted if they fit in
+    /// the session capacity.
+    pub fn cache_stream(
+        self: Arc<Self>,
+        key: QueryResultCacheKey,
+        version: QueryResultVersion,
+        fields: Vec<PgFieldDescriptor>,
+        stream: PgResponseStream,
+    ) -> PgResponseStream {
+        PgResponseStream::Rows(cache_stream_inner(self, key, version, fields, stream).boxed())
+    }
+
+    fn report_usage(&self, core: &CacheCore) {
+        self.metrics
+            .query_result_cache_memory_usage
+            .set(core.usage as i64);
+    }
+}
+
+#[try_stream(ok = Vec<Row>, error = BoxedError)]
+async fn cache_stream_inner(
+    cache: QueryResultCacheRef,
+    key: QueryResultCacheKey,
+    version: QueryResultVersion,
+    fields: Vec<PgFieldDescriptor>,
+    stream: PgResponseStream,
+) {
+    let mut rows = Some(vec![]);
+    let mut size = entry_size(&key, &[]);
+    #[for_await]
+    for row_set in stream {
+        let row_set = row_set?;
+        if let Some(cached_rows) = &mut rows {
+            size += row_set.iter().map(row_size).sum::<usize>();
+            if size > cache.session_capacity {
+                rows = None;
+            } else {
+                cached_rows.extend(row_set.iter().cloned());
+            }
+        }
+        yield row_set;
+    }
+    if let Some(rows) = rows {
+        cache.insert(key, version, fields, rows);
+    }
+}
+
+fn entry_size(key: &QueryResultCacheKey, rows: &[Row]) -> usize {
+    size_of::<CacheEntry>() + key.query.len() + rows.iter().map(row_size).sum::<usize>()
+}
+
+fn row_size(row: &Row) -> usize {
+    row.values()
+        .iter()
+        .map(|value| size_of::<Option<Bytes>>() + value.as_ref().map_or(0, Bytes::len))
+        .sum::<usize>()
+        + size_of::<Row>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(session_id: SessionId, query: &str) -> QueryResultCacheKey {
+        QueryResultCacheKey {
+            session_id,
+            query: query.to_string(),
+            format: false,
+            search_path: "public".to_string(),
+            time_zone: "UTC".to_string(),
+        }
+    }
+
+    fn version(committed_epoch: u64, mapping_version: u64) -> QueryResultVersion {
+        QueryResultVersion {
+            committed_epoch,
+            catalog_version: 1,
+            user_info_version: 1,
+            mapping_version,
+        }
+    }
+
+    fn rows(len: usize) -> Vec<Row> {
+        (0..len)
+            .map(|i| Row::new(vec![Some(Bytes::from(i.to_string()))]))
+            .collect()
+    }
+
+    #[test]
+    fn test_invalidated_by_new_epoch() {
+        let metrics = Arc::new(FrontendMetrics::for_test());
+        let cache = QueryResultCache::new(1 << 20, 1 << 20, metrics.clone());
+        let key = key((1, 1), "SELECT * FROM t");
+
+        assert!(cache.get(&key, &version(1, 1)).is_none());
+        assert!(cache.insert(key.clone(), version(1, 1), vec![], rows(3)));
+        for _ in 0..2 {
+            assert_eq!(cache.get(&key, &version(1, 1)).unwrap().rows.len(), 3);
+        }
+        assert_eq!(metrics.query_result_cache_hit_count.get(), 2);
+
+        // A barrier commits new data.
+        assert!(cache.get(&key, &version(2, 1)).is_none());
+        // The stale result is removed.
+        assert!(cache.get(&key, &version(1, 1)).is_none());
+        assert_eq!(metrics.query_result_cache_miss_count.get(), 3);
+        assert_eq!(metrics.query_result_cache_memory_usage.get(), 0);
+    }
+
+    #[test]
+    fn test_invalidated_by_reschedule() {
+        let cache = QueryResultCache::new(1 << 20, 1 << 20, Arc::new(FrontendMetrics::for_test()));
+        let key = key((1, 1), "SELECT * FROM t");
+
+        assert!(cache.insert(key.clone(), version(1, 1), vec![], rows(3)));
+        assert!(cache.get(&key, &version(1, 1)).is_some());
+        // A reschedule changes the fragment mappings.
+        assert!(cache.get(&key, &version(1, 2)).is_none());
+    }
+
+    #[test]
+    fn test_invalidate_session() {
+        let cache = QueryResultCache::new(1 << 20, 1 << 20, Arc::new(FrontendMetrics::for_test()));
+        let key1 = key((1, 1), "SELECT * FROM t");
+        let key2 = key((2, 2), "SELECT * FROM t");
+
+        assert!(cache.insert(key1.clone(), version(1, 1), vec![], rows(3)));
+        assert!(cache.insert(key2.clone(), version(1, 1), vec![], rows(3)));
+        cache.invalidate_session((1, 1));
+        assert!(cache.get(&key1, &version(1, 1)).is_none());
+        assert!(cache.get(&key2, &version(1, 1)).is_some());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let key1 = key((1, 1), "SELECT 1");
+        let key2 = key((1, 1), "SELECT 2");
+        let key3 = key((1, 1), "SELECT 3");
+        let key4 = key((2, 2), "SELECT 4");
+        let one_row = entry_size(&key1, &rows(1));
+        let two_rows = entry_size(&key1, &rows(2));
+        let cache = QueryResultCache::new(
+            one_row + two_rows,
+            one_row + two_rows,
+            Arc::new(FrontendMetrics::for_test()),
+        );
+
+        // Too large to be cached.
+        assert!(!cache.insert(key1.clone(), version(1, 1), vec![], rows(100)));
+        assert!(cache.insert(key1.clone(), version(1, 1), vec![], rows(1)));
+        assert!(cache.insert(key2.clone(), version(1, 1), vec![], rows(1)));
+        assert!(cache.get(&key1, &version(1, 1)).is_some());
+
+        // Exceeds the session capacity, and the least recently used `key2` is evicted.
+        assert!(cache.insert(key3.clone(), version(1, 1), vec![], rows(2)));
+        assert!(cache.get(&key1, &version(1, 1)).is_some());
+        assert!(cache.get(&key2, &version(1, 1)).is_none());
+
+        // Exceeds the global capacity, and the least recently used `key3` of the other session is
+        // evicted.
+        assert!(cache.insert(key4.clone(), version(1, 1), vec![], rows(2)));
+        assert!(cache.get(&key1, &version(1, 1)).is_some());
+        assert!(cache.get(&key3, &version(1, 1)).is_none());
+        assert!(cache.get(&key4, &version(1, 1)).is_some());
+    }
+}
@@ -15,7 +15,7 @@
 use std::sync::Arc;
 
 use risingwave_batch::executor::BatchTaskMetricsWithTaskLabels;
-use risingwave_batch::task::{BatchTaskContext, TaskOutput, TaskOutputId};
+use risingwave_batch::task::{BatchTaskContext, MemoryContextRef, TaskOutput, TaskOutputId};
 use risingwave_common::catalog::SysCatalogReaderRef;
 use risingwave_common::config::BatchConfig;
 use risingwave_common::error::Result;
@@ -50,6 +50,7 @@ impl BatchTaskContext for FrontendBatchTaskContext {
             self.env.user_info_reader().clone(),
             self.env.worker_node_manager_ref(),
             self.env.meta_client_ref(),
+            self.env.meta_connection_status().clone(),
             self.auth_context.clone(),
         ))
     }
@@ -77,4 +78,8 @@ impl BatchTaskContext for FrontendBatchTaskContext {
     fn get_config(&self) -> &BatchConfig {
         self.env.batch_config()
     }
+
+    fn memory_context(&self) -> MemoryContextRef {
+        self.env.batch_memory_context()
+    }
 }
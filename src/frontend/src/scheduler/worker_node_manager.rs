@@ -12,18 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{Arc, RwLock};
 
+use itertools::Itertools;
 use rand::seq::SliceRandom;
 use risingwave_common::bail;
 use risingwave_common::types::{ParallelUnitId, VnodeMapping};
 use risingwave_common::util::worker_util::get_pu_to_worker_mapping;
-use risingwave_pb::common::WorkerNode;
+use risingwave_pb::common::{WorkerNode, WorkerRole, WorkerType};
+use risingwave_pb::hummock::HummockVersion;
 
 use crate::catalog::FragmentId;
 use crate::scheduler::{SchedulerError, SchedulerResult};
 
+/// Estimates the size of each table by the SSTs containing it in the hummock `version`. The size
+/// of an SST containing multiple tables is split evenly among them.
+pub fn estimate_table_sizes(version: &HummockVersion) -> HashMap<u32, u64> {
+    let mut table_sizes = HashMap::new();
+    for levels in version.levels.values() {
+        let sub_levels = levels.l0.iter().flat_map(|l0| &l0.sub_levels);
+        for level in sub_levels.chain(&levels.levels) {
+            for sst in &level.table_infos {
+                let table_count = sst.table_ids.len() as u64;
+                for table_id in &sst.table_ids {
+                    *table_sizes.entry(*table_id).or_default() += sst.file_size / table_count;
+                }
+            }
+        }
+    }
+    table_sizes
+}
+
 /// `WorkerNodeManager` manages live worker nodes and table vnode mapping information.
 pub struct WorkerNodeManager {
     inner: RwLock<WorkerNodeManagerInner>,
@@ -31,9 +51,17 @@ pub struct WorkerNodeManager {
 
 #[derive(Default)]
 struct WorkerNodeManagerInner {
+    /// The compute nodes.
     worker_nodes: Vec<WorkerNode>,
+    /// The ids of the frontends, which share the snapshot exports.
+    frontend_ids: BTreeSet<u32>,
     /// fragment vnode mapping info.
     fragment_vnode_mapping: HashMap<FragmentId, VnodeMapping>,
+    /// Bumped on every change of `fragment_vnode_mapping`, so that a plan partitioned by the
+    /// mappings can tell whether they have changed since.
+    fragment_mapping_version: u64,
+    /// table id => estimated size in bytes, used to split the scans of large tables.
+    table_size_estimates: HashMap<u32, u64>,
 }
 
 pub type WorkerNodeManagerRef = Arc<WorkerNodeManager>;
@@ -55,7 +83,10 @@ impl WorkerNodeManager {
     pub fn mock(worker_nodes: Vec<WorkerNode>) -> Self {
         let inner = RwLock::new(WorkerNodeManagerInner {
             worker_nodes,
+            frontend_ids: BTreeSet::new(),
             fragment_vnode_mapping: HashMap::new(),
+            fragment_mapping_version: 0,
+            table_size_estimates: HashMap::new(),
         });
         Self { inner }
     }
@@ -64,22 +95,57 @@ impl WorkerNodeManager {
         self.inner.read().unwrap().worker_nodes.clone()
     }
 
+    /// The ids of the frontends in the cluster, in ascending order.
+    pub fn list_frontend_ids(&self) -> Vec<u32> {
+        self.inner
+            .read()
+            .unwrap()
+            .frontend_ids
+            .iter()
+            .copied()
+            .collect()
+    }
+
     pub fn add_worker_node(&self, node: WorkerNode) {
-        self.inner.write().unwrap().worker_nodes.push(node);
+        let mut write_guard = self.inner.write().unwrap();
+        if node.r#type() == WorkerType::Frontend {
+            write_guard.frontend_ids.insert(node.id);
+        } else {
+            write_guard.worker_nodes.push(node);
+        }
     }
 
     pub fn remove_worker_node(&self, node: WorkerNode) {
-        self.inner
-            .write()
-            .unwrap()
+        let mut write_guard = self.inner.write().unwrap();
+        if node.r#type() == WorkerType::Frontend {
+            write_guard.frontend_ids.remove(&node.id);
+        } else {
+            write_guard.worker_nodes.retain(|x| *x != node);
+        }
+    }
+
+    /// Replaces the worker node of the same id, e.g. after its role is changed.
+    pub fn update_worker_node(&self, node: WorkerNode) {
+        let mut write_guard = self.inner.write().unwrap();
+        match write_guard
             .worker_nodes
-            .retain(|x| *x != node);
+            .iter_mut()
+            .find(|worker| worker.id == node.id)
+        {
+            Some(worker) => *worker = node,
+            None => write_guard.worker_nodes.push(node),
+        }
     }
 
     pub fn refresh(&self, nodes: Vec<WorkerNode>, mapping: HashMap<FragmentId, VnodeMapping>) {
+        let (frontends, nodes): (Vec<_>, Vec<_>) = nodes
+            .into_iter()
+            .partition(|node| node.r#type() == WorkerType::Frontend);
         let mut write_guard = self.inner.write().unwrap();
         write_guard.worker_nodes = nodes;
+        write_guard.frontend_ids = frontends.iter().map(|node| node.id).collect();
         write_guard.fragment_vnode_mapping = mapping;
+        write_guard.fragment_mapping_version += 1;
     }
 
     /// Get a random worker node.
@@ -126,6 +192,69 @@ impl WorkerNodeManager {
         Ok(workers)
     }
 
+    /// Returns the worker to run the scan of each parallel unit.
+    ///
+    /// If there are serving-only workers, the scans are spread over them by parallel unit id, so
+    /// that they don't compete with the streaming actors. Otherwise, it's the worker owning the
+    /// parallel unit, or a random worker for the parallel units not found, e.g. the ones of a
+    /// worker that has gone away.
+    pub fn get_preferred_workers_by_parallel_unit_ids(
+        &self,
+        parallel_unit_ids: &[ParallelUnitId],
+    ) -> SchedulerResult<Vec<WorkerNode>> {
+        let inner = self.inner.read().unwrap();
+        if parallel_unit_ids.is_empty() || inner.worker_nodes.is_empty() {
+            return Err(SchedulerError::EmptyWorkerNodes);
+        }
+
+        let serving_workers = inner
+            .worker_nodes
+            .iter()
+            .filter(|worker| worker.role() == WorkerRole::Serving)
+            .collect_vec();
+        if !serving_workers.is_empty() {
+            return Ok(parallel_unit_ids
+                .iter()
+                .map(|parallel_unit_id| {
+                    serving_workers[*parallel_unit_id as usize % serving_workers.len()].clone()
+                })
+                .collect());
+        }
+
+        let pu_to_worker = get_pu_to_worker_mapping(&inner.worker_nodes);
+        Ok(parallel_unit_ids
+            .iter()
+            .map(
+                |parallel_unit_id| match pu_to_worker.get(parallel_unit_id) {
+                    Some(worker) => worker.clone(),
+                    None => inner
+                        .worker_nodes
+                        .choose(&mut rand::thread_rng())
+                        .unwrap()
+                        .clone(),
+                },
+            )
+            .collect())
+    }
+
+    pub fn get_table_size_estimate(&self, table_id: u32) -> Option<u64> {
+        self.inner
+            .read()
+            .unwrap()
+            .table_size_estimates
+            .get(&table_id)
+            .copied()
+    }
+
+    pub fn set_table_size_estimates(&self, table_size_estimates: HashMap<u32, u64>) {
+        self.inner.write().unwrap().table_size_estimates = table_size_estimates;
+    }
+
+    /// The version of the fragment vnode mappings, which changes whenever any of them changes.
+    pub fn fragment_mapping_version(&self) -> u64 {
+        self.inner.read().unwrap().fragment_mapping_version
+    }
+
     pub fn get_fragment_mapping(&self, fragment_id: &FragmentId) -> Option<VnodeMapping> {
         self.inner
             .read()
@@ -136,30 +265,63 @@ impl WorkerNodeManager {
     }
 
     pub fn insert_fragment_mapping(&self, fragment_id: FragmentId, vnode_mapping: VnodeMapping) {
-        self.inner
-            .write()
-            .unwrap()
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard
             .fragment_vnode_mapping
             .try_insert(fragment_id, vnode_mapping)
             .unwrap();
+        write_guard.fragment_mapping_version += 1;
     }
 
     pub fn update_fragment_mapping(&self, fragment_id: FragmentId, vnode_mapping: VnodeMapping) {
-        self.inner
-            .write()
-            .unwrap()
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard
             .fragment_vnode_mapping
             .insert(fragment_id, vnode_mapping)
             .unwrap();
+        write_guard.fragment_mapping_version += 1;
     }
 
     pub fn remove_fragment_mapping(&self, fragment_id: &FragmentId) {
-        self.inner
-            .write()
-            .unwrap()
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard
             .fragment_vnode_mapping
             .remove(fragment_id)
             .unwrap();
+        write_guard.fragment_mapping_version += 1;
+    }
+
+    /// Inserts the vnode mappings of multiple fragments at once, so that the scheduler never
+    /// observes a part of them. The same applies to the other `*_bulk` methods.
+    pub fn insert_fragment_mapping_bulk(&self, mappings: Vec<(FragmentId, VnodeMapping)>) {
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard.fragment_mapping_version += 1;
+        let fragment_vnode_mapping = &mut write_guard.fragment_vnode_mapping;
+        for (fragment_id, vnode_mapping) in mappings {
+            fragment_vnode_mapping
+                .try_insert(fragment_id, vnode_mapping)
+                .unwrap();
+        }
+    }
+
+    pub fn update_fragment_mapping_bulk(&self, mappings: Vec<(FragmentId, VnodeMapping)>) {
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard.fragment_mapping_version += 1;
+        let fragment_vnode_mapping = &mut write_guard.fragment_vnode_mapping;
+        for (fragment_id, vnode_mapping) in mappings {
+            fragment_vnode_mapping
+                .insert(fragment_id, vnode_mapping)
+                .unwrap();
+        }
+    }
+
+    pub fn remove_fragment_mapping_bulk(&self, fragment_ids: &[FragmentId]) {
+        let mut write_guard = self.inner.write().unwrap();
+        write_guard.fragment_mapping_version += 1;
+        let fragment_vnode_mapping = &mut write_guard.fragment_vnode_mapping;
+        for fragment_id in fragment_ids {
+            fragment_vnode_mapping.remove(fragment_id).unwrap();
+        }
     }
 }
 
@@ -167,7 +329,7 @@ impl WorkerNodeManager {
 mod tests {
 
     use risingwave_common::util::addr::HostAddr;
-    use risingwave_pb::common::{worker_node, WorkerType};
+    use risingwave_pb::common::{worker_node, WorkerRole, WorkerType};
 
     #[test]
     fn test_worker_node_manager() {
@@ -184,6 +346,7 @@ mod tests {
                 host: Some(HostAddr::try_from("127.0.0.1:1234").unwrap().to_protobuf()),
                 state: worker_node::State::Running as i32,
                 parallel_units: vec![],
+                role: WorkerRole::Both as i32,
             },
             WorkerNode {
                 id: 2,
@@ -191,6 +354,7 @@ mod tests {
                 host: Some(HostAddr::try_from("127.0.0.1:1235").unwrap().to_protobuf()),
                 state: worker_node::State::Running as i32,
                 parallel_units: vec![],
+                role: WorkerRole::Both as i32,
             },
         ];
         worker_nodes
@@ -205,5 +369,206 @@ mod tests {
             manager.list_worker_nodes(),
             worker_nodes.as_slice()[1..].to_vec()
         );
+
+        // The frontends are not scheduled on.
+        let frontend = WorkerNode {
+            id: 3,
+            r#type: WorkerType::Frontend as i32,
+            host: Some(HostAddr::try_from("127.0.0.1:4566").unwrap().to_protobuf()),
+            state: worker_node::State::Running as i32,
+            parallel_units: vec![],
+            role: WorkerRole::Both as i32,
+        };
+        manager.add_worker_node(frontend.clone());
+        assert_eq!(manager.worker_node_count(), 1);
+        assert_eq!(manager.list_frontend_ids(), vec![3]);
+        manager.refresh(
+            vec![frontend.clone(), worker_nodes[0].clone()],
+            HashMap::new(),
+        );
+        assert_eq!(manager.list_worker_nodes(), worker_nodes[..1].to_vec());
+        assert_eq!(manager.list_frontend_ids(), vec![3]);
+        manager.remove_worker_node(frontend);
+        assert!(manager.list_frontend_ids().is_empty());
+    }
+
+    #[test]
+    fn test_get_preferred_workers() {
+        use risingwave_pb::common::ParallelUnit;
+
+        use super::*;
+
+        let worker = WorkerNode {
+            id: 1,
+            r#type: WorkerType::ComputeNode as i32,
+            host: Some(HostAddr::try_from("127.0.0.1:1234").unwrap().to_protobuf()),
+            state: worker_node::State::Running as i32,
+            parallel_units: vec![ParallelUnit {
+                id: 7,
+                worker_node_id: 1,
+            }],
+            role: WorkerRole::Both as i32,
+        };
+        let manager = WorkerNodeManager::mock(vec![]);
+        assert!(manager
+            .get_preferred_workers_by_parallel_unit_ids(&[7])
+            .is_err());
+
+        manager.add_worker_node(worker.clone());
+        // The parallel unit 8 is gone, so any worker is chosen.
+        assert_eq!(
+            manager
+                .get_preferred_workers_by_parallel_unit_ids(&[7, 8])
+                .unwrap(),
+            vec![worker.clone(), worker]
+        );
+        assert!(manager.get_workers_by_parallel_unit_ids(&[8]).is_err());
+    }
+
+    #[test]
+    fn test_get_preferred_serving_workers() {
+        use risingwave_pb::common::ParallelUnit;
+
+        use super::*;
+
+        let worker = |id: u32, role: WorkerRole| WorkerNode {
+            id,
+            r#type: WorkerType::ComputeNode as i32,
+            host: Some(
+                HostAddr::try_from(format!("127.0.0.1:{}", 1234 + id).as_str())
+                    .unwrap()
+                    .to_protobuf(),
+            ),
+            state: worker_node::State::Running as i32,
+            parallel_units: vec![ParallelUnit {
+                id,
+                worker_node_id: id,
+            }],
+            role: role as i32,
+        };
+        let streaming = worker(1, WorkerRole::Streaming);
+        let both = worker(2, WorkerRole::Both);
+        let serving_1 = worker(3, WorkerRole::Serving);
+        let serving_2 = worker(4, WorkerRole::Serving);
+
+        // Falls back to the owners of the parallel units without serving workers.
+        let manager = WorkerNodeManager::mock(vec![streaming.clone(), both.clone()]);
+        assert_eq!(
+            manager
+                .get_preferred_workers_by_parallel_unit_ids(&[1, 2])
+                .unwrap(),
+            vec![streaming.clone(), both.clone()]
+        );
+
+        // The scans are spread over the serving workers.
+        manager.add_worker_node(serving_1.clone());
+        assert_eq!(
+            manager
+                .get_preferred_workers_by_parallel_unit_ids(&[1, 2])
+                .unwrap(),
+            vec![serving_1.clone(), serving_1.clone()]
+        );
+        manager.add_worker_node(serving_2.clone());
+        assert_eq!(
+            manager
+                .get_preferred_workers_by_parallel_unit_ids(&[1, 2])
+                .unwrap(),
+            vec![serving_2.clone(), serving_1.clone()]
+        );
+
+        // A worker switched to streaming is no longer preferred.
+        manager.update_worker_node(worker(3, WorkerRole::Streaming));
+        manager.update_worker_node(worker(4, WorkerRole::Streaming));
+        assert_eq!(manager.worker_node_count(), 4);
+        assert_eq!(
+            manager
+                .get_preferred_workers_by_parallel_unit_ids(&[1, 2])
+                .unwrap(),
+            vec![streaming, both]
+        );
+    }
+
+    #[test]
+    fn test_estimate_table_sizes() {
+        use risingwave_pb::hummock::hummock_version::Levels;
+        use risingwave_pb::hummock::{Level, OverlappingLevel, SstableInfo};
+
+        use super::*;
+
+        let sst = |table_ids: Vec<u32>, file_size| SstableInfo {
+            table_ids,
+            file_size,
+            ..Default::default()
+        };
+        let level = |table_infos| Level {
+            table_infos,
+            ..Default::default()
+        };
+        let version = HummockVersion {
+            levels: HashMap::from([
+                (
+                    2,
+                    Levels {
+                        levels: vec![level(vec![sst(vec![1], 100), sst(vec![1, 2], 60)])],
+                        l0: Some(OverlappingLevel {
+                            sub_levels: vec![level(vec![sst(vec![2], 10)])],
+                            ..Default::default()
+                        }),
+                    },
+                ),
+                (
+                    3,
+                    Levels {
+                        levels: vec![level(vec![sst(vec![3], 1000)])],
+                        l0: None,
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            estimate_table_sizes(&version),
+            HashMap::from([(1, 130), (2, 40), (3, 1000)])
+        );
+    }
+
+    #[test]
+    fn test_fragment_mapping_bulk() {
+        use super::*;
+
+        let manager = WorkerNodeManager::mock(vec![]);
+        manager.insert_fragment_mapping_bulk(vec![(1, vec![1, 2]), (2, vec![3])]);
+        assert_eq!(manager.get_fragment_mapping(&1), Some(vec![1, 2]));
+        assert_eq!(manager.get_fragment_mapping(&2), Some(vec![3]));
+
+        manager.update_fragment_mapping_bulk(vec![(1, vec![2, 1]), (2, vec![4])]);
+        assert_eq!(manager.get_fragment_mapping(&1), Some(vec![2, 1]));
+        assert_eq!(manager.get_fragment_mapping(&2), Some(vec![4]));
+
+        manager.remove_fragment_mapping_bulk(&[1, 2]);
+        assert_eq!(manager.get_fragment_mapping(&1), None);
+        assert_eq!(manager.get_fragment_mapping(&2), None);
+    }
+
+    #[test]
+    fn test_fragment_mapping_version() {
+        use super::*;
+
+        let manager = WorkerNodeManager::mock(vec![]);
+        let v0 = manager.fragment_mapping_version();
+        manager.insert_fragment_mapping(1, vec![1, 2]);
+        let v1 = manager.fragment_mapping_version();
+        assert!(v1 > v0);
+
+        // Reading the mappings doesn't change the version.
+        manager.get_fragment_mapping(&1);
+        assert_eq!(manager.fragment_mapping_version(), v1);
+
+        manager.update_fragment_mapping_bulk(vec![(1, vec![2, 1])]);
+        let v2 = manager.fragment_mapping_version();
+        assert!(v2 > v1);
+
+        manager.remove_fragment_mapping(&1);
+        assert!(manager.fragment_mapping_version() > v2);
     }
 }
@@ -26,6 +26,8 @@ use pgwire::pg_field_descriptor::{PgFieldDescriptor, TypeOid};
 use pgwire::pg_response::PgResponse;
 use pgwire::pg_server::{BoxedError, Session, SessionId, SessionManager, UserAuthenticator};
 use rand::RngCore;
+use risingwave_batch::task::{MemoryContext, MemoryContextRef};
+use risingwave_common::catalog::TableId;
 #[cfg(test)]
 use risingwave_common::catalog::{
     DEFAULT_DATABASE_NAME, DEFAULT_SUPER_USER, DEFAULT_SUPER_USER_ID,
@@ -37,6 +39,9 @@ use risingwave_common::session_config::ConfigMap;
 use risingwave_common::util::addr::HostAddr;
 use risingwave_common_service::observer_manager::ObserverManager;
 use risingwave_common_service::MetricsManager;
+use risingwave_pb::catalog::{
+    TableStatistics as ProstTableStatistics, WatermarkDesc as ProstWatermarkDesc,
+};
 use risingwave_pb::common::WorkerType;
 use risingwave_pb::user::auth_info::EncryptionType;
 use risingwave_pb::user::grant_privilege::{Action, Object};
@@ -52,14 +57,23 @@ use crate::catalog::catalog_service::{CatalogReader, CatalogWriter, CatalogWrite
 use crate::catalog::root_catalog::Catalog;
 use crate::expr::CorrelatedId;
 use crate::handler::handle;
+use crate::handler::show::{
+    cluster_status_fields, table_complexity_fields, table_fragments_fields,
+};
 use crate::handler::util::to_pg_field;
 use crate::meta_client::{FrontendMetaClient, FrontendMetaClientImpl};
 use crate::monitor::FrontendMetrics;
+use crate::observer::meta_connection_status::{MetaConnectionStatus, MetaConnectionStatusRef};
 use crate::observer::observer_manager::FrontendObserverNode;
 use crate::optimizer::plan_node::PlanNodeId;
 use crate::planner::Planner;
-use crate::scheduler::worker_node_manager::{WorkerNodeManager, WorkerNodeManagerRef};
-use crate::scheduler::{HummockSnapshotManager, HummockSnapshotManagerRef, QueryManager};
+use crate::scheduler::worker_node_manager::{
+    estimate_table_sizes, WorkerNodeManager, WorkerNodeManagerRef,
+};
+use crate::scheduler::{
+    HummockSnapshotManager, HummockSnapshotManagerRef, QueryManager, QueryResultCache,
+    QueryResultCacheRef, SnapshotExportManager, SnapshotExportManagerRef,
+};
 use crate::user::user_authentication::md5_hash_with_salt;
 use crate::user::user_manager::UserInfoManager;
 use crate::user::user_service::{UserInfoReader, UserInfoWriter, UserInfoWriterImpl};
@@ -84,6 +98,17 @@ pub struct OptimizerContext {
     pub next_correlated_id: AtomicU32,
     /// Store options or properties from the `with` clause
     pub with_options: WithOptions,
+    /// Statistics of the tables scanned by the query, registered by the planner so that the whole
+    /// query is optimized with the same version of them.
+    table_statistics: Mutex<HashMap<TableId, ProstTableStatistics>>,
+    /// Notices to be sent to the user along with the result of the statement.
+    notices: Mutex<Vec<String>>,
+    /// Watermarks derived on the sources by `RW_AUTO_WATERMARK`, recorded in the catalog of the
+    /// created materialized view.
+    derived_watermarks: Mutex<Vec<ProstWatermarkDesc>>,
+    /// Whether `now()` is inlined as a constant into the batch query, so that its result can't be
+    /// reused by later executions.
+    inlined_now: AtomicBool,
 }
 
 #[derive(Clone, Debug)]
@@ -137,6 +162,61 @@ impl OptimizerContextRef {
         let mut guard = self.inner.optimizer_trace.lock().unwrap();
         guard.drain(..).collect()
     }
+
+    pub fn notice_to_user(&self, notice: impl Into<String>) {
+        self.inner.notices.lock().unwrap().push(notice.into());
+    }
+
+    pub fn take_notices(&self) -> Vec<String> {
+        std::mem::take(&mut *self.inner.notices.lock().unwrap())
+    }
+
+    pub fn register_derived_watermark(&self, watermark: ProstWatermarkDesc) {
+        self.inner
+            .derived_watermarks
+            .lock()
+            .unwrap()
+            .push(watermark);
+    }
+
+    pub fn derived_watermarks(&self) -> Vec<ProstWatermarkDesc> {
+        self.inner.derived_watermarks.lock().unwrap().clone()
+    }
+
+    pub fn set_inlined_now(&self) {
+        self.inner.inlined_now.store(true, Ordering::Release);
+    }
+
+    pub fn has_inlined_now(&self) -> bool {
+        self.inner.inlined_now.load(Ordering::Acquire)
+    }
+
+    pub fn register_table_statistics(&self, table_id: TableId, statistics: ProstTableStatistics) {
+        self.inner
+            .table_statistics
+            .lock()
+            .unwrap()
+            .insert(table_id, statistics);
+    }
+
+    /// Returns the statistics of the table, or `None` if the table has not been analyzed or
+    /// `RW_OPTIMIZER_USE_STATISTICS` is off.
+    pub fn table_statistics(&self, table_id: TableId) -> Option<ProstTableStatistics> {
+        if !self
+            .inner
+            .session_ctx
+            .config()
+            .get_optimizer_use_statistics()
+        {
+            return None;
+        }
+        self.inner
+            .table_statistics
+            .lock()
+            .unwrap()
+            .get(&table_id)
+            .cloned()
+    }
 }
 
 impl OptimizerContext {
@@ -150,6 +230,10 @@ impl OptimizerContext {
             optimizer_trace: Arc::new(Mutex::new(vec![])),
             next_correlated_id: AtomicU32::new(1),
             with_options,
+            table_statistics: Mutex::new(HashMap::new()),
+            notices: Mutex::new(vec![]),
+            derived_watermarks: Mutex::new(vec![]),
+            inlined_now: AtomicBool::new(false),
         }
     }
 
@@ -166,6 +250,10 @@ impl OptimizerContext {
             optimizer_trace: Arc::new(Mutex::new(vec![])),
             next_correlated_id: AtomicU32::new(1),
             with_options: Default::default(),
+            table_statistics: Mutex::new(HashMap::new()),
+            notices: Mutex::new(vec![]),
+            derived_watermarks: Mutex::new(vec![]),
+            inlined_now: AtomicBool::new(false),
         }
         .into()
     }
@@ -181,6 +269,13 @@ impl std::fmt::Debug for OptimizerContext {
     }
 }
 
+/// A read-only standby frontend considers the meta unreachable if no notification is received
+/// within this time.
+const STANDBY_META_NOTIFICATION_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The interval to refresh the table size estimates, by which the scans of tables are split.
+const TABLE_SIZE_ESTIMATE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
 /// The global environment for the frontend server.
 #[derive(Clone)]
 pub struct FrontendEnv {
@@ -194,6 +289,8 @@ pub struct FrontendEnv {
     worker_node_manager: WorkerNodeManagerRef,
     query_manager: QueryManager,
     hummock_snapshot_manager: HummockSnapshotManagerRef,
+    snapshot_export_manager: SnapshotExportManagerRef,
+    query_result_cache: QueryResultCacheRef,
     server_addr: HostAddr,
     client_pool: ComputeClientPoolRef,
 
@@ -205,6 +302,11 @@ pub struct FrontendEnv {
     pub frontend_metrics: Arc<FrontendMetrics>,
 
     batch_config: BatchConfig,
+
+    /// Memory shared by the buffering executors of all local mode queries.
+    batch_memory_context: MemoryContextRef,
+
+    meta_connection_status: MetaConnectionStatusRef,
 }
 
 type SessionMapRef = Arc<Mutex<HashMap<(i32, i32), Arc<SessionImpl>>>>;
@@ -231,6 +333,9 @@ impl FrontendEnv {
         );
         let server_addr = HostAddr::try_from("127.0.0.1:4565").unwrap();
         let client_pool = Arc::new(ComputeClientPool::default());
+        let frontend_metrics = Arc::new(FrontendMetrics::for_test());
+        let batch_config = BatchConfig::default();
+        let query_result_cache = new_query_result_cache(&batch_config, frontend_metrics.clone());
         Self {
             meta_client,
             catalog_writer,
@@ -240,11 +345,15 @@ impl FrontendEnv {
             worker_node_manager,
             query_manager,
             hummock_snapshot_manager,
+            snapshot_export_manager: Arc::new(SnapshotExportManager::new(0)),
+            query_result_cache,
             server_addr,
             client_pool,
             sessions_map: Arc::new(Mutex::new(HashMap::new())),
-            frontend_metrics: Arc::new(FrontendMetrics::for_test()),
-            batch_config: BatchConfig::default(),
+            frontend_metrics,
+            batch_memory_context: new_batch_memory_context(&batch_config),
+            batch_config,
+            meta_connection_status: Arc::new(MetaConnectionStatus::new(false)),
         }
     }
 
@@ -316,6 +425,7 @@ impl FrontendEnv {
             user_info_updated_rx,
         ));
 
+        let meta_connection_status = Arc::new(MetaConnectionStatus::new(opts.read_only_standby));
         let frontend_observer_node = FrontendObserverNode::new(
             worker_node_manager.clone(),
             catalog,
@@ -323,14 +433,38 @@ impl FrontendEnv {
             user_info_manager,
             user_info_updated_tx,
             hummock_snapshot_manager.clone(),
+            meta_connection_status.clone(),
         );
-        let observer_manager =
+        let mut observer_manager =
             ObserverManager::new_with_meta_client(meta_client.clone(), frontend_observer_node)
                 .await;
+        if opts.read_only_standby {
+            observer_manager =
+                observer_manager.with_idle_timeout(STANDBY_META_NOTIFICATION_IDLE_TIMEOUT);
+        }
         let observer_join_handle = observer_manager.start().await?;
 
         meta_client.activate(&frontend_address).await?;
 
+        // The sizes of the tables are estimated by their SSTs in the current hummock version.
+        {
+            let frontend_meta_client = frontend_meta_client.clone();
+            let worker_node_manager = worker_node_manager.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(TABLE_SIZE_ESTIMATE_REFRESH_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    match frontend_meta_client.get_current_version().await {
+                        Ok(version) => worker_node_manager
+                            .set_table_size_estimates(estimate_table_sizes(&version)),
+                        Err(e) => {
+                            tracing::warn!("failed to refresh the table size estimates: {}", e)
+                        }
+                    }
+                }
+            });
+        }
+
         let client_pool = Arc::new(ComputeClientPool::new(
             frontend_config.server.connection_pool_size,
         ));
@@ -338,27 +472,35 @@ impl FrontendEnv {
         let registry = prometheus::Registry::new();
         monitor_process(&registry).unwrap();
         let frontend_metrics = Arc::new(FrontendMetrics::new(registry.clone()));
+        let query_result_cache = new_query_result_cache(&batch_config, frontend_metrics.clone());
 
         if opts.metrics_level > 0 {
             MetricsManager::boot_metrics_service(opts.prometheus_listener_addr.clone(), registry);
         }
 
+        let env = Self {
+            catalog_reader,
+            catalog_writer,
+            user_info_reader,
+            user_info_writer,
+            worker_node_manager,
+            meta_client: frontend_meta_client,
+            query_manager,
+            hummock_snapshot_manager,
+            snapshot_export_manager: Arc::new(SnapshotExportManager::new(meta_client.worker_id())),
+            query_result_cache,
+            server_addr: frontend_address,
+            client_pool,
+            frontend_metrics,
+            sessions_map: Arc::new(Mutex::new(HashMap::new())),
+            batch_memory_context: new_batch_memory_context(&batch_config),
+            batch_config,
+            meta_connection_status,
+        };
+        env.snapshot_export_manager.clone().start(env.clone());
+
         Ok((
-            Self {
-                catalog_reader,
-                catalog_writer,
-                user_info_reader,
-                user_info_writer,
-                worker_node_manager,
-                meta_client: frontend_meta_client,
-                query_manager,
-                hummock_snapshot_manager,
-                server_addr: frontend_address,
-                client_pool,
-                frontend_metrics,
-                sessions_map: Arc::new(Mutex::new(HashMap::new())),
-                batch_config,
-            },
+            env,
             observer_join_handle,
             heartbeat_join_handle,
             heartbeat_shutdown_sender,
@@ -409,6 +551,14 @@ impl FrontendEnv {
         &self.hummock_snapshot_manager
     }
 
+    pub fn snapshot_export_manager(&self) -> &SnapshotExportManagerRef {
+        &self.snapshot_export_manager
+    }
+
+    pub fn query_result_cache(&self) -> &QueryResultCacheRef {
+        &self.query_result_cache
+    }
+
     pub fn server_address(&self) -> &HostAddr {
         &self.server_addr
     }
@@ -420,6 +570,29 @@ impl FrontendEnv {
     pub fn batch_config(&self) -> &BatchConfig {
         &self.batch_config
     }
+
+    pub fn batch_memory_context(&self) -> MemoryContextRef {
+        self.batch_memory_context.clone()
+    }
+
+    pub fn meta_connection_status(&self) -> &MetaConnectionStatusRef {
+        &self.meta_connection_status
+    }
+}
+
+fn new_batch_memory_context(batch_config: &BatchConfig) -> MemoryContextRef {
+    Arc::new(MemoryContext::new(batch_config.sort_memory_budget_mb << 20))
+}
+
+fn new_query_result_cache(
+    batch_config: &BatchConfig,
+    frontend_metrics: Arc<FrontendMetrics>,
+) -> QueryResultCacheRef {
+    Arc::new(QueryResultCache::new(
+        batch_config.result_cache_capacity_mb << 20,
+        batch_config.result_cache_session_capacity_mb << 20,
+        frontend_metrics,
+    ))
 }
 
 pub struct AuthContext {
@@ -646,6 +819,7 @@ impl SessionManagerImpl {
     fn delete_session(&self, session_id: &SessionId) {
         let mut write_guard = self.env.sessions_map.lock().unwrap();
         write_guard.remove(session_id);
+        self.env.query_result_cache.invalidate_session(*session_id);
     }
 }
 
@@ -717,6 +891,9 @@ impl Session<PgResponseStream> for SessionImpl {
                         PgFieldDescriptor::new("Type".to_owned(), TypeOid::Varchar),
                     ]
                 }
+                ShowObject::TableComplexity => table_complexity_fields(),
+                ShowObject::TableFragments { .. } => table_fragments_fields(),
+                ShowObject::ClusterStatus => cluster_status_fields(),
                 _ => {
                     vec![PgFieldDescriptor::new("Name".to_owned(), TypeOid::Varchar)]
                 }
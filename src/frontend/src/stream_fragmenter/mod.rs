@@ -17,14 +17,15 @@ use graph::*;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
 mod rewrite;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use derivative::Derivative;
+use itertools::Itertools;
 use risingwave_common::catalog::TableId;
 use risingwave_common::error::Result;
 use risingwave_pb::plan_common::JoinType;
 use risingwave_pb::stream_plan::{
-    DispatchStrategy, DispatcherType, ExchangeNode, FragmentType,
+    DispatchStrategy, DispatcherType, ExchangeNode, FragmentType, HotKeyMode,
     StreamFragmentGraph as StreamFragmentGraphProto, StreamNode,
 };
 
@@ -93,6 +94,48 @@ pub fn build_graph(plan_node: PlanRef) -> StreamFragmentGraphProto {
     fragment_graph
 }
 
+/// Let the hash dispatchers feeding inner hash joins route the rows of hot join keys to more than
+/// one actor. The left input is the probe side whose hot rows are spread, and the right input is
+/// the build side whose hot rows are broadcast.
+pub fn mark_hot_key_skew(graph: &mut StreamFragmentGraphProto) {
+    fn visit(node: &StreamNode, modes: &mut HashMap<u64, HotKeyMode>) {
+        if let Some(NodeBody::HashJoin(hash_join)) = &node.node_body
+            && hash_join.join_type() == JoinType::Inner
+        {
+            for (input, mode) in node
+                .input
+                .iter()
+                .zip_eq([HotKeyMode::Probe, HotKeyMode::Build])
+            {
+                if let Some(NodeBody::Exchange(ExchangeNode {
+                    strategy: Some(strategy),
+                })) = &input.node_body
+                    && strategy.r#type() == DispatcherType::Hash
+                {
+                    modes.insert(input.operator_id, mode);
+                }
+            }
+        }
+        for input in &node.input {
+            visit(input, modes);
+        }
+    }
+
+    let mut modes = HashMap::new();
+    for fragment in graph.fragments.values() {
+        if let Some(node) = &fragment.node {
+            visit(node, &mut modes);
+        }
+    }
+    for edge in &mut graph.edges {
+        if let Some(mode) = modes.get(&edge.link_id)
+            && let Some(strategy) = &mut edge.dispatch_strategy
+        {
+            strategy.set_hot_key_mode(*mode);
+        }
+    }
+}
+
 #[expect(dead_code)]
 fn is_stateful_executor(stream_node: &StreamNode) -> bool {
     matches!(
@@ -125,6 +168,7 @@ fn rewrite_stream_node(
                 let strategy = DispatchStrategy {
                     r#type: DispatcherType::NoShuffle.into(),
                     column_indices: vec![], // TODO: use distribution key
+                    hot_key_mode: HotKeyMode::Unspecified as i32,
                 };
                 Ok(StreamNode {
                     stream_key: child_node.stream_key.clone(),
@@ -202,6 +246,12 @@ fn build_fragment(
     match stream_node.get_node_body()? {
         NodeBody::Source(_) => current_fragment.fragment_type = FragmentType::Source,
 
+        // The barriers are injected into `Now` like sources, and there's only one `now()`.
+        NodeBody::Now(_) => {
+            current_fragment.fragment_type = FragmentType::Source;
+            current_fragment.is_singleton = true;
+        }
+
         NodeBody::Materialize(_) => current_fragment.fragment_type = FragmentType::Sink,
 
         // TODO: Force singleton for TopN as a workaround. We should implement two phase TopN.
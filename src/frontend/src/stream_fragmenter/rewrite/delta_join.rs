@@ -21,8 +21,8 @@ use risingwave_pb::plan_common::{Field as ProstField, OrderType as ProstOrderTyp
 use risingwave_pb::stream_plan::lookup_node::ArrangementTableId;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
 use risingwave_pb::stream_plan::{
-    ArrangementInfo, DispatchStrategy, DispatcherType, ExchangeNode, LookupNode, LookupUnionNode,
-    StreamNode,
+    ArrangementInfo, DispatchStrategy, DispatcherType, ExchangeNode, HotKeyMode, LookupNode,
+    LookupUnionNode, StreamNode,
 };
 
 use super::super::{BuildFragmentGraphState, StreamFragment, StreamFragmentEdge};
@@ -53,6 +53,7 @@ fn dispatch_no_shuffle() -> DispatchStrategy {
     DispatchStrategy {
         r#type: DispatcherType::NoShuffle.into(),
         column_indices: vec![],
+        hot_key_mode: HotKeyMode::Unspecified as i32,
     }
 }
 
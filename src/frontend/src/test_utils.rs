@@ -24,16 +24,24 @@ use pgwire::pg_server::{BoxedError, Session, SessionId, SessionManager, UserAuth
 use pgwire::types::Row;
 use risingwave_common::catalog::{
     IndexId, TableId, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, DEFAULT_SUPER_USER,
-    DEFAULT_SUPER_USER_ID, NON_RESERVED_USER_ID, PG_CATALOG_SCHEMA_NAME,
+    DEFAULT_SUPER_USER_ID, NON_RESERVED_USER_ID, PG_CATALOG_SCHEMA_NAME, RW_CATALOG_SCHEMA_NAME,
 };
-use risingwave_common::error::Result;
+use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
 use risingwave_pb::catalog::{
     Database as ProstDatabase, Index as ProstIndex, Schema as ProstSchema, Sink as ProstSink,
-    Source as ProstSource, Table as ProstTable,
+    Source as ProstSource, SubscriptionProgress as ProstSubscriptionProgress, Table as ProstTable,
+    TableStatistics as ProstTableStatistics,
 };
-use risingwave_pb::hummock::HummockSnapshot;
+use risingwave_pb::common::worker_node::State as WorkerState;
+use risingwave_pb::common::{HostAddress, WorkerNode, WorkerRole, WorkerType};
+use risingwave_pb::ddl_service::reset_source_offset_request::Position;
+use risingwave_pb::hummock::get_table_write_stats_response::TableWriteStats;
+use risingwave_pb::hummock::{HummockSnapshot, HummockVersion};
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
+use risingwave_pb::meta::get_table_complexity_response::TableComplexity;
 use risingwave_pb::meta::list_table_fragments_response::TableFragmentInfo;
+use risingwave_pb::meta::{GetClusterStatusResponse, TableFragments as ProstTableFragments};
 use risingwave_pb::stream_plan::StreamFragmentGraph;
 use risingwave_pb::user::update_user_request::UpdateField;
 use risingwave_pb::user::{GrantPrivilege, UserInfo};
@@ -193,6 +201,8 @@ impl CatalogWriter for MockCatalogWriter {
             .await?;
         self.create_schema(database_id, PG_CATALOG_SCHEMA_NAME, owner)
             .await?;
+        self.create_schema(database_id, RW_CATALOG_SCHEMA_NAME, owner)
+            .await?;
         Ok(())
     }
 
@@ -217,11 +227,14 @@ impl CatalogWriter for MockCatalogWriter {
         &self,
         mut table: ProstTable,
         _graph: StreamFragmentGraph,
-    ) -> Result<()> {
+    ) -> Result<ProstTableFragments> {
         table.id = self.gen_id();
         self.catalog.write().create_table(&table);
         self.add_table_or_source_id(table.id, table.schema_id, table.database_id);
-        Ok(())
+        Ok(ProstTableFragments {
+            table_id: table.id,
+            ..Default::default()
+        })
     }
 
     async fn create_materialized_source(
@@ -241,6 +254,20 @@ impl CatalogWriter for MockCatalogWriter {
         self.create_source_inner(source).map(|_| ())
     }
 
+    async fn alter_source(&self, source: ProstSource) -> Result<()> {
+        self.catalog.write().update_source(&source);
+        Ok(())
+    }
+
+    async fn reset_source_offset(
+        &self,
+        _source_id: u32,
+        _position: Position,
+        _timestamp_millis: i64,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     async fn create_sink(&self, sink: ProstSink, graph: StreamFragmentGraph) -> Result<()> {
         self.create_sink_inner(sink, graph)
     }
@@ -352,6 +379,70 @@ impl CatalogWriter for MockCatalogWriter {
         self.catalog.write().drop_schema(database_id, schema_id);
         Ok(())
     }
+
+    async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: ProstTableStatistics,
+    ) -> Result<()> {
+        let &schema_id = self
+            .table_id_to_schema_id
+            .read()
+            .get(&table_id.table_id)
+            .unwrap();
+        let database_id = self.get_database_id_by_schema(schema_id);
+        let mut table = self
+            .catalog
+            .read()
+            .get_table_by_id(&table_id)?
+            .to_prost(schema_id, database_id);
+        table.statistics = Some(statistics);
+        self.catalog.write().update_table(&table);
+        Ok(())
+    }
+
+    async fn update_subscription_progress(
+        &self,
+        table_id: TableId,
+        expected_progress: ProstSubscriptionProgress,
+        progress: ProstSubscriptionProgress,
+    ) -> Result<()> {
+        let &schema_id = self
+            .table_id_to_schema_id
+            .read()
+            .get(&table_id.table_id)
+            .unwrap();
+        let database_id = self.get_database_id_by_schema(schema_id);
+        let mut table = self
+            .catalog
+            .read()
+            .get_table_by_id(&table_id)?
+            .to_prost(schema_id, database_id);
+        if table.subscription_progress != Some(expected_progress) {
+            return Err(ErrorCode::InternalError(format!(
+                "progress of subscription {} has been changed by a concurrent fetch",
+                table.name
+            ))
+            .into());
+        }
+        table.subscription_progress = Some(progress);
+        self.catalog.write().update_table(&table);
+        Ok(())
+    }
+
+    async fn get_subscription_progress(
+        &self,
+        table_id: TableId,
+    ) -> Result<ProstSubscriptionProgress> {
+        let progress = self
+            .catalog
+            .read()
+            .get_table_by_id(&table_id)?
+            .subscription_progress
+            .clone()
+            .unwrap_or_default();
+        Ok(progress)
+    }
 }
 
 impl MockCatalogWriter {
@@ -373,12 +464,19 @@ impl MockCatalogWriter {
             database_id: 0,
             owner: DEFAULT_SUPER_USER_ID,
         });
+        catalog.write().create_schema(ProstSchema {
+            id: 3,
+            name: RW_CATALOG_SCHEMA_NAME.to_string(),
+            database_id: 0,
+            owner: DEFAULT_SUPER_USER_ID,
+        });
         let mut map: HashMap<u32, DatabaseId> = HashMap::new();
         map.insert(1_u32, 0_u32);
         map.insert(2_u32, 0_u32);
+        map.insert(3_u32, 0_u32);
         Self {
             catalog,
-            id: AtomicU32::new(2),
+            id: AtomicU32::new(3),
             table_id_to_schema_id: Default::default(),
             schema_id_to_database_id: RwLock::new(map),
         }
@@ -643,6 +741,48 @@ impl FrontendMetaClient for MockFrontendMetaClient {
     async fn unpin_snapshot_before(&self, _epoch: u64) -> RpcResult<()> {
         Ok(())
     }
+
+    async fn get_table_write_stats(&self) -> RpcResult<Vec<TableWriteStats>> {
+        Ok(vec![])
+    }
+
+    async fn get_current_version(&self) -> RpcResult<HummockVersion> {
+        Ok(HummockVersion::default())
+    }
+
+    async fn get_table_complexity(&self) -> RpcResult<Vec<TableComplexity>> {
+        Ok(vec![])
+    }
+
+    async fn get_backfill_progress(&self) -> RpcResult<Vec<BackfillProgress>> {
+        Ok(vec![])
+    }
+
+    async fn dump_table_fragments(&self, table_id: u32) -> RpcResult<String> {
+        Ok(format!("TableFragments {} (Created)", table_id))
+    }
+
+    async fn get_cluster_status(&self) -> RpcResult<GetClusterStatusResponse> {
+        Ok(GetClusterStatusResponse {
+            workers: vec![WorkerNode {
+                id: 1,
+                r#type: WorkerType::ComputeNode as i32,
+                host: Some(HostAddress {
+                    host: "127.0.0.1".to_string(),
+                    port: 5688,
+                }),
+                state: WorkerState::Running as i32,
+                parallel_units: vec![],
+                role: WorkerRole::Both as i32,
+            }],
+            creating_streaming_jobs: 1,
+            created_streaming_jobs: 2,
+            committed_epoch: 65536,
+            committed_epoch_age_ms: 1000,
+            barrier_latency_p99_secs: 0.5,
+            l0_file_counts: HashMap::from([(2, 3)]),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -18,9 +18,11 @@ use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::user::grant_privilege::{
     Action as ProstAction, ActionWithGrantOption, Object as ProstObject,
 };
-use risingwave_pb::user::GrantPrivilege as ProstPrivilege;
+use risingwave_pb::user::{GrantPrivilege as ProstPrivilege, UserInfo};
 use risingwave_sqlparser::ast::{Action, GrantObjects, Privileges};
 
+use crate::user::UserId;
+
 // TODO: add user_privilege mod under user manager and move check and expand logic there, and bitmap
 // impl for privilege check.
 static AVAILABLE_ACTION_ON_DATABASE: &[Action] = &[Action::Connect, Action::Create];
@@ -44,7 +46,9 @@ pub fn check_privilege_type(privilege: &Privileges, objects: &GrantObjects) -> R
                 GrantObjects::Schemas(_) => actions
                     .iter()
                     .all(|action| AVAILABLE_ACTION_ON_SCHEMA.contains(action)),
-                GrantObjects::Sources(_) | GrantObjects::AllSourcesInSchema { .. } => actions
+                GrantObjects::Tables(_)
+                | GrantObjects::Sources(_)
+                | GrantObjects::AllSourcesInSchema { .. } => actions
                     .iter()
                     .all(|action| AVAILABLE_ACTION_ON_SOURCE.contains(action)),
                 GrantObjects::Mviews(_) | GrantObjects::AllMviewsInSchema { .. } => actions
@@ -68,9 +72,9 @@ pub fn available_privilege_actions(objects: &GrantObjects) -> Result<Vec<Action>
     match objects {
         GrantObjects::Databases(_) => Ok(AVAILABLE_ACTION_ON_DATABASE.to_vec()),
         GrantObjects::Schemas(_) => Ok(AVAILABLE_ACTION_ON_SCHEMA.to_vec()),
-        GrantObjects::Sources(_) | GrantObjects::AllSourcesInSchema { .. } => {
-            Ok(AVAILABLE_ACTION_ON_SOURCE.to_vec())
-        }
+        GrantObjects::Tables(_)
+        | GrantObjects::Sources(_)
+        | GrantObjects::AllSourcesInSchema { .. } => Ok(AVAILABLE_ACTION_ON_SOURCE.to_vec()),
         GrantObjects::Mviews(_) | GrantObjects::AllMviewsInSchema { .. } => {
             Ok(AVAILABLE_ACTION_ON_MVIEW.to_vec())
         }
@@ -117,3 +121,35 @@ pub fn available_prost_privilege(object: ProstObject) -> ProstPrivilege {
         object: Some(object),
     }
 }
+
+/// Whether `user` has the privilege of `action` on `object`, which is owned by `owner`. Like
+/// PostgreSQL, superusers and the owner of the object hold all the privileges on it, and are able
+/// to grant them to others.
+pub fn has_privilege(
+    user: &UserInfo,
+    owner: UserId,
+    object: &ProstObject,
+    action: ProstAction,
+    with_grant_option: bool,
+) -> bool {
+    if user.is_super || user.id == owner {
+        return true;
+    }
+    user.grant_privileges
+        .iter()
+        .filter(|privilege| privilege.object.as_ref() == Some(object))
+        .flat_map(|privilege| &privilege.action_with_opts)
+        .any(|ao| ao.action == action as i32 && (ao.with_grant_option || !with_grant_option))
+}
+
+/// The name of the kind of `object` in the error messages, e.g. "permission denied for table t".
+pub fn object_kind(object: &ProstObject) -> &'static str {
+    match object {
+        ProstObject::DatabaseId(_) => "database",
+        ProstObject::SchemaId(_)
+        | ProstObject::AllTablesSchemaId(_)
+        | ProstObject::AllSourcesSchemaId(_) => "schema",
+        ProstObject::TableId(_) => "table",
+        ProstObject::SourceId(_) => "source",
+    }
+}
@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{self, Debug, Display};
-use std::ops::Bound;
+use std::ops::{Bound, RangeBounds};
 use std::rc::Rc;
 use std::sync::LazyLock;
 
@@ -22,7 +23,8 @@ use fixedbitset::FixedBitSet;
 use itertools::Itertools;
 use risingwave_common::catalog::{Schema, TableDesc};
 use risingwave_common::error::Result;
-use risingwave_common::util::scan_range::{is_full_range, ScanRange};
+use risingwave_common::types::{Datum, ScalarImpl};
+use risingwave_common::util::scan_range::{full_range, is_full_range, ScanRange};
 
 use crate::expr::{
     factorization_expr, fold_boolean_constant, push_down_not, to_conjunctions,
@@ -238,7 +240,7 @@ impl Condition {
     }
 
     /// Generate range scans from each arm of `OR` clause and merge them.
-    /// Currently, only support equal type range scans.
+    /// Overlapping ranges on the same column are merged into a single one.
     /// Keep in mind that range scans can not overlap, otherwise duplicate rows will occur.
     fn disjunctions_to_scan_ranges(
         table_desc: Rc<TableDesc>,
@@ -258,6 +260,16 @@ impl Condition {
         // If any arm of `OR` clause fails, bail out.
         let disjunctions_result = disjunctions_result?;
 
+        // If any arm of `OR` clause can't be fully converted to scan ranges, bail out.
+        if !disjunctions_result
+            .iter()
+            .all(|(scan_ranges, other_condition)| {
+                other_condition.always_true() && !scan_ranges.is_empty()
+            })
+        {
+            return Ok(None);
+        }
+
         // If all arms of `OR` clause scan ranges are simply equal condition type, merge all
         // of them.
         let all_equal = disjunctions_result
@@ -299,7 +311,12 @@ impl Condition {
 
             Ok(Some((non_overlap_scan_ranges, Condition::true_cond())))
         } else {
-            Ok(None)
+            let scan_ranges = disjunctions_result
+                .into_iter()
+                .flat_map(|(scan_ranges, _)| scan_ranges)
+                .collect_vec();
+            Ok(merge_disjunctive_scan_ranges(scan_ranges)
+                .map(|scan_ranges| (scan_ranges, Condition::true_cond())))
         }
     }
 
@@ -378,7 +395,7 @@ impl Condition {
             let mut eq_conds = vec![];
 
             // analyze exprs in the group. scan_range is not updated
-            for expr in group.clone() {
+            for expr in group {
                 if let Some((input_ref, const_expr)) = expr.as_eq_const() &&
                     let Ok(const_expr) = const_expr.cast_implicit(input_ref.data_type) {
                     assert_eq!(input_ref.index, order_column_ids[i]);
@@ -434,16 +451,16 @@ impl Condition {
                     };
                     match op {
                         ExprType::LessThan => {
-                            ub.push((Bound::Excluded(value), expr));
+                            ub.push(Bound::Excluded(value));
                         }
                         ExprType::LessThanOrEqual => {
-                            ub.push((Bound::Included(value), expr));
+                            ub.push(Bound::Included(value));
                         }
                         ExprType::GreaterThan => {
-                            lb.push((Bound::Excluded(value), expr));
+                            lb.push(Bound::Excluded(value));
                         }
                         ExprType::GreaterThanOrEqual => {
-                            lb.push((Bound::Included(value), expr));
+                            lb.push(Bound::Included(value));
                         }
                         _ => unreachable!(),
                     }
@@ -452,29 +469,35 @@ impl Condition {
                 }
             }
 
+            // The bounds are merged into a single range, which is never true for NULL.
+            let range = (
+                lb.into_iter().fold(Bound::Unbounded, tighter_lower_bound),
+                ub.into_iter().fold(Bound::Unbounded, tighter_upper_bound),
+            );
+            if is_empty_range(&range) {
+                return Ok(false_cond());
+            }
+            if !is_full_range(&range) {
+                if eq_conds.is_empty() {
+                    // `a >= 1 AND a <= 1` is the same as `a = 1`.
+                    if let (Bound::Included(lower), Bound::Included(upper)) = &range && lower == upper {
+                        eq_conds = vec![Some(lower.clone())];
+                    }
+                } else {
+                    eq_conds.retain(|value| matches!(value, Some(value) if range.contains(value)));
+                    if eq_conds.is_empty() {
+                        return Ok(false_cond());
+                    }
+                }
+            }
+
             // update scan_range
             match eq_conds.len() {
                 1 => {
                     scan_range.eq_conds.extend(eq_conds.into_iter());
-                    // TODO: simplify bounds: it's either true or false according to whether lit is
-                    // included
-                    other_conds.extend(lb.into_iter().chain(ub.into_iter()).map(|(_, expr)| expr));
                 }
                 0 => {
-                    if lb.len() > 1 || ub.len() > 1 {
-                        // TODO: simplify bounds: it can be merged into a single lb & ub.
-                        other_conds
-                            .extend(lb.into_iter().chain(ub.into_iter()).map(|(_, expr)| expr));
-                    } else if !lb.is_empty() || !ub.is_empty() {
-                        scan_range.range = (
-                            lb.first()
-                                .map(|(bound, _)| (bound.clone()))
-                                .unwrap_or(Bound::Unbounded),
-                            ub.first()
-                                .map(|(bound, _)| (bound.clone()))
-                                .unwrap_or(Bound::Unbounded),
-                        )
-                    }
+                    scan_range.range = range;
                     other_conds.extend(groups[i + 1..].iter().flatten().cloned());
                     break;
                 }
@@ -485,7 +508,6 @@ impl Condition {
                     // a in (1,2) AND b = 1
                     // a in (1,2) AND b in (1,2)
                     // a in (1,2) AND b > 1
-                    other_conds.extend(lb.into_iter().chain(ub.into_iter()).map(|(_, expr)| expr));
                     other_conds.extend(groups[i + 1..].iter().flatten().cloned());
                     let scan_ranges = eq_conds
                         .into_iter()
@@ -660,6 +682,163 @@ impl fmt::Debug for ConditionDisplay<'_> {
     }
 }
 
+/// Returns the tighter one of two lower bounds.
+fn tighter_lower_bound(a: Bound<ScalarImpl>, b: Bound<ScalarImpl>) -> Bound<ScalarImpl> {
+    match (a, b) {
+        (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound,
+        (Bound::Included(x), Bound::Included(y)) => Bound::Included(x.max(y)),
+        (Bound::Excluded(x), Bound::Excluded(y)) => Bound::Excluded(x.max(y)),
+        (Bound::Included(x), Bound::Excluded(y)) | (Bound::Excluded(y), Bound::Included(x)) => {
+            if y >= x {
+                Bound::Excluded(y)
+            } else {
+                Bound::Included(x)
+            }
+        }
+    }
+}
+
+/// Returns the tighter one of two upper bounds.
+fn tighter_upper_bound(a: Bound<ScalarImpl>, b: Bound<ScalarImpl>) -> Bound<ScalarImpl> {
+    match (a, b) {
+        (Bound::Unbounded, bound) | (bound, Bound::Unbounded) => bound,
+        (Bound::Included(x), Bound::Included(y)) => Bound::Included(x.min(y)),
+        (Bound::Excluded(x), Bound::Excluded(y)) => Bound::Excluded(x.min(y)),
+        (Bound::Included(x), Bound::Excluded(y)) | (Bound::Excluded(y), Bound::Included(x)) => {
+            if y <= x {
+                Bound::Excluded(y)
+            } else {
+                Bound::Included(x)
+            }
+        }
+    }
+}
+
+/// Returns the looser one of two upper bounds.
+fn looser_upper_bound(a: Bound<ScalarImpl>, b: Bound<ScalarImpl>) -> Bound<ScalarImpl> {
+    match (a, b) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => Bound::Unbounded,
+        (a, b) => {
+            if tighter_upper_bound(a.clone(), b.clone()) == a {
+                b
+            } else {
+                a
+            }
+        }
+    }
+}
+
+fn is_empty_range(range: &(Bound<ScalarImpl>, Bound<ScalarImpl>)) -> bool {
+    match range {
+        (Bound::Included(lower), Bound::Included(upper)) => lower > upper,
+        (Bound::Included(lower) | Bound::Excluded(lower), Bound::Excluded(upper))
+        | (Bound::Excluded(lower), Bound::Included(upper)) => lower >= upper,
+        _ => false,
+    }
+}
+
+/// Orders lower bounds from the loosest to the tightest.
+fn cmp_lower_bound(a: &Bound<ScalarImpl>, b: &Bound<ScalarImpl>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(x), Bound::Excluded(y)) => x.cmp(y).then(Ordering::Less),
+        (Bound::Excluded(x), Bound::Included(y)) => x.cmp(y).then(Ordering::Greater),
+        (Bound::Included(x), Bound::Included(y)) | (Bound::Excluded(x), Bound::Excluded(y)) => {
+            x.cmp(y)
+        }
+    }
+}
+
+/// Returns whether there's a gap between a range ending at `upper` and a range starting at
+/// `lower`, i.e., they can't be merged into a single range.
+fn has_gap(upper: &Bound<ScalarImpl>, lower: &Bound<ScalarImpl>) -> bool {
+    match (upper, lower) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+        (Bound::Excluded(x), Bound::Excluded(y)) => x <= y,
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y))
+        | (Bound::Included(x), Bound::Excluded(y)) => x < y,
+    }
+}
+
+/// Merges the scan ranges from the arms of an `OR` clause into non-overlapping ones. Returns
+/// `None` if they can't be represented by scan ranges, e.g., they range over different columns.
+fn merge_disjunctive_scan_ranges(scan_ranges: Vec<ScanRange>) -> Option<Vec<ScanRange>> {
+    type Range = (Bound<ScalarImpl>, Bound<ScalarImpl>);
+
+    // Normalize each scan range into a prefix of `eq_conds` and a range on the next column, where
+    // a trailing non-NULL value in `eq_conds` is turned into a single-point range.
+    let mut ranges: BTreeMap<Vec<Datum>, Vec<Range>> = BTreeMap::new();
+    let mut null_prefixes: BTreeSet<Vec<Datum>> = BTreeSet::new();
+    for ScanRange {
+        mut eq_conds,
+        range,
+    } in scan_ranges
+    {
+        if !is_full_range(&range) {
+            ranges.entry(eq_conds).or_default().push(range);
+            continue;
+        }
+        match eq_conds.pop() {
+            Some(Some(value)) => ranges
+                .entry(eq_conds)
+                .or_default()
+                .push((Bound::Included(value.clone()), Bound::Included(value))),
+            Some(None) => {
+                null_prefixes.insert(eq_conds);
+            }
+            // Full table scan.
+            None => return None,
+        }
+    }
+    if !ranges
+        .keys()
+        .chain(null_prefixes.iter())
+        .map(Vec::len)
+        .all_equal()
+    {
+        return None;
+    }
+
+    let mut merged_scan_ranges = vec![];
+    for (prefix, mut ranges) in ranges {
+        ranges.sort_by(|a, b| cmp_lower_bound(&a.0, &b.0));
+        let mut merged: Vec<Range> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if !has_gap(&last.1, &range.0) => {
+                    last.1 = looser_upper_bound(last.1.clone(), range.1);
+                }
+                _ => merged.push(range),
+            }
+        }
+        for range in merged {
+            // A range without bounds still excludes NULL, which can't be represented.
+            if is_full_range(&range) {
+                return None;
+            }
+            let mut eq_conds = prefix.clone();
+            let range = match range {
+                (Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+                    eq_conds.push(Some(lower));
+                    full_range()
+                }
+                range => range,
+            };
+            merged_scan_ranges.push(ScanRange { eq_conds, range });
+        }
+    }
+    merged_scan_ranges.extend(null_prefixes.into_iter().map(|mut eq_conds| {
+        eq_conds.push(None);
+        ScanRange {
+            eq_conds,
+            range: full_range(),
+        }
+    }));
+    Some(merged_scan_ranges)
+}
+
 #[cfg(test)]
 mod tests {
     use rand::Rng;
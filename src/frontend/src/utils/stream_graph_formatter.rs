@@ -17,6 +17,7 @@ use std::collections::{BTreeMap, HashMap};
 use itertools::Itertools;
 use risingwave_common::error::{ErrorCode, Result};
 use risingwave_pb::catalog::Table;
+use risingwave_pb::meta::TableFragments;
 use risingwave_pb::stream_plan::agg_call_state::{AggTableState, MaterializedAggInputState};
 use risingwave_pb::stream_plan::stream_fragment_graph::{StreamFragment, StreamFragmentEdge};
 use risingwave_pb::stream_plan::{
@@ -33,6 +34,35 @@ pub fn explain_stream_graph(graph: &StreamFragmentGraph, is_verbose: bool) -> Re
     Ok(output)
 }
 
+/// Explains the fragments of a created streaming job, with the fragment ids, actors and state
+/// tables assigned by meta. Used for the notice of `create materialized view ...` when
+/// `RW_STREAMING_GRAPH_NOTICE` is on.
+pub fn explain_table_fragments(table_fragments: &TableFragments) -> Result<String> {
+    let mut output = String::new();
+    StreamGraphFormatter::new(false)
+        .explain_table_fragments(table_fragments, &mut output)
+        .map_err(|e| {
+            ErrorCode::InternalError(format!("failed to explain table fragments: {}", e))
+        })?;
+    Ok(output)
+}
+
+fn explain_dispatcher_type(
+    dispatcher_type: DispatcherType,
+    column_indices: Option<&[u32]>,
+) -> String {
+    match dispatcher_type {
+        DispatcherType::Unspecified => unreachable!(),
+        DispatcherType::Hash => match column_indices {
+            Some(column_indices) => format!("Hash({:?})", column_indices),
+            None => "Hash".to_string(),
+        },
+        DispatcherType::Broadcast => "Broadcast".to_string(),
+        DispatcherType::Simple => "Single".to_string(),
+        DispatcherType::NoShuffle => "NoShuffle".to_string(),
+    }
+}
+
 /// A formatter to display the final stream plan graph, used for `explain (distsql) create
 /// materialized view ...`
 struct StreamGraphFormatter {
@@ -77,6 +107,36 @@ impl StreamGraphFormatter {
         Ok(())
     }
 
+    fn explain_table_fragments(
+        &mut self,
+        table_fragments: &TableFragments,
+        f: &mut impl std::fmt::Write,
+    ) -> std::fmt::Result {
+        for (_, fragment) in table_fragments
+            .fragments
+            .iter()
+            .sorted_by_key(|(id, _)| **id)
+        {
+            writeln!(
+                f,
+                "Fragment {} (parallelism: {}, state tables: [{}])",
+                fragment.fragment_id,
+                fragment.actors.len(),
+                fragment.state_table_ids.iter().sorted().join(", ")
+            )?;
+            // All actors in a fragment share the same plan, so the first one is enough.
+            if let Some(node) = fragment.actors.first().and_then(|a| a.nodes.as_ref()) {
+                self.explain_node(1, node, f)?;
+            }
+            writeln!(f)?;
+        }
+        let tbs = self.tables.clone();
+        for tb in tbs.values() {
+            self.explain_table(tb, f)?;
+        }
+        Ok(())
+    }
+
     fn explain_table(&mut self, tb: &Table, f: &mut impl std::fmt::Write) -> std::fmt::Result {
         let tb = TableCatalog::from(tb.clone());
         writeln!(
@@ -127,16 +187,16 @@ impl StreamGraphFormatter {
                 let dist = edge.dispatch_strategy.as_ref().unwrap();
                 format!(
                     "StreamExchange {} from {}",
-                    match dist.r#type() {
-                        DispatcherType::Unspecified => unreachable!(),
-                        DispatcherType::Hash => format!("Hash({:?})", dist.column_indices),
-                        DispatcherType::Broadcast => "Broadcast".to_string(),
-                        DispatcherType::Simple => "Single".to_string(),
-                        DispatcherType::NoShuffle => "NoShuffle".to_string(),
-                    },
+                    explain_dispatcher_type(dist.r#type(), Some(&dist.column_indices)),
                     upstream_fragment_id
                 )
             }
+            // Exchanges have been replaced with merges in the fragments created by meta.
+            stream_node::NodeBody::Merge(merge) => format!(
+                "StreamMerge {} from {}",
+                explain_dispatcher_type(merge.upstream_dispatcher_type(), None),
+                merge.upstream_fragment_id
+            ),
             _ => node.identity.clone(),
         };
         writeln!(f, "{}{}", " ".repeat(level * 2), one_line_explain)?;
@@ -216,6 +276,18 @@ impl StreamGraphFormatter {
                     "state table: {}",
                     self.add_table(node.get_table().unwrap())
                 )),
+                stream_node::NodeBody::Dedup(node) => Some(format!(
+                    "state table: {}",
+                    self.add_table(node.get_state_table().unwrap())
+                )),
+                stream_node::NodeBody::Now(node) => Some(format!(
+                    "state table: {}",
+                    self.add_table(node.get_state_table().unwrap())
+                )),
+                stream_node::NodeBody::OverWindow(node) => Some(format!(
+                    "state table: {}",
+                    self.add_table(node.get_state_table().unwrap())
+                )),
                 _ => None,
             };
         if let Some(explain_table_oneline) = explain_table_oneline {
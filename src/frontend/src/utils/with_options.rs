@@ -18,8 +18,11 @@ use std::num::NonZeroU32;
 
 use itertools::Itertools;
 use risingwave_common::error::{ErrorCode, RwError};
+use risingwave_connector::aws_utils::{AWS_CUSTOM_CONFIG_KEY, AWS_DEFAULT_CONFIG};
+use risingwave_connector::options::{get_options_schema, ConnectorKind};
 use risingwave_sqlparser::ast::{
-    CreateSinkStatement, CreateSourceStatement, SqlOption, Statement, Value,
+    CreateSinkStatement, CreateSourceStatement, CreateSubscriptionStatement, SqlOption, Statement,
+    Value,
 };
 
 use crate::catalog::source_catalog::KAFKA_CONNECTOR;
@@ -30,6 +33,8 @@ mod options {
     pub const APPEND_ONLY: &str = "appendonly";
     pub const CONNECTOR: &str = "connector";
     pub const RETENTION_SECONDS: &str = PROPERTIES_RETENTION_SECOND_KEY;
+    /// The message name of the protobuf row format.
+    pub const PROTOBUF_MESSAGE: &str = "proto.message";
 }
 
 /// Options or properties extracted from the `WITH` clause of DDLs.
@@ -85,6 +90,31 @@ impl WithOptions {
         false
     }
 
+    /// Validate the options against the options schema of the connector, if the connector
+    /// declares one. Returns the notices about deprecated options.
+    pub fn validate_connector_options(&self, kind: ConnectorKind) -> Result<Vec<String>, RwError> {
+        let Some(schema) = self
+            .inner
+            .get(options::CONNECTOR)
+            .and_then(|connector| get_options_schema(kind, connector))
+        else {
+            return Ok(vec![]);
+        };
+        // Options that are not consumed by the connector, including the ones of the row format,
+        // whose schema may be located on S3.
+        let common_keys = [
+            options::CONNECTOR,
+            options::APPEND_ONLY,
+            options::RETENTION_SECONDS,
+            options::PROTOBUF_MESSAGE,
+        ]
+        .into_iter()
+        .chain(AWS_DEFAULT_CONFIG)
+        .chain(AWS_CUSTOM_CONFIG_KEY)
+        .collect_vec();
+        Ok(schema.validate(&self.inner, &common_keys)?)
+    }
+
     /// Get a subset of the options from the given keys.
     pub fn subset(&self, keys: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
         let inner = keys
@@ -155,6 +185,12 @@ impl TryFrom<&Statement> for WithOptions {
                     CreateSinkStatement {
                         with_properties, ..
                     },
+            }
+            | Statement::CreateSubscription {
+                stmt:
+                    CreateSubscriptionStatement {
+                        with_properties, ..
+                    },
             } => Self::try_from(with_properties.0.as_slice()),
 
             _ => Ok(Default::default()),
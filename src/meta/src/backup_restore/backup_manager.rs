@@ -0,0 +1,149 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
+use risingwave_pb::meta::meta_backup::{ColumnFamily, KeyValue};
+use risingwave_pb::meta::{MetaBackup, MetaBackupInfo};
+use tokio::sync::Mutex;
+
+use super::{
+    backup_column_families, hummock_version_in_backup, BackupStorage, META_BACKUP_FORMAT_VERSION,
+};
+use crate::hummock::HummockManagerRef;
+use crate::manager::MetaSrvEnv;
+use crate::storage::{MetaStore, Snapshot};
+use crate::MetaResult;
+
+pub type BackupManagerRef<S> = Arc<BackupManager<S>>;
+
+/// [`BackupManager`] takes backups of the meta store and writes them to the backup storage, which
+/// can be restored to an empty meta store with `--restore-from`.
+///
+/// The SSTs referenced by each backup are pinned in the hummock manager, so that they are never
+/// vacuumed and the backup stays restorable.
+pub struct BackupManager<S: MetaStore> {
+    env: MetaSrvEnv<S>,
+    hummock_manager: HummockManagerRef<S>,
+    /// `None` if `--backup-storage-url` is not specified, in which case backups are disabled.
+    backup_storage: Option<BackupStorage>,
+    /// Backups are taken one at a time, so that their ids don't conflict.
+    lock: Mutex<()>,
+}
+
+impl<S> BackupManager<S>
+where
+    S: MetaStore,
+{
+    pub async fn new(
+        env: MetaSrvEnv<S>,
+        hummock_manager: HummockManagerRef<S>,
+        backup_storage: Option<BackupStorage>,
+    ) -> MetaResult<Self> {
+        if let Some(backup_storage) = &backup_storage {
+            for info in backup_storage.list().await? {
+                hummock_manager.pin_backup_ssts(info.sst_ids).await;
+            }
+        }
+        Ok(Self {
+            env,
+            hummock_manager,
+            backup_storage,
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn backup_storage(&self) -> MetaResult<&BackupStorage> {
+        self.backup_storage.as_ref().ok_or_else(|| {
+            anyhow!("meta backup is disabled, as `--backup-storage-url` is not specified").into()
+        })
+    }
+
+    /// Takes a backup of all column families of the meta store at a consistent snapshot.
+    pub async fn create_backup(&self) -> MetaResult<MetaBackupInfo> {
+        let backup_storage = self.backup_storage()?;
+        let _guard = self.lock.lock().await;
+        let id = backup_storage
+            .list()
+            .await?
+            .last()
+            .map_or(1, |info| info.id + 1);
+
+        // The checkpoint cannot pass the safe point, so the SSTs of the hummock version in the
+        // snapshot are kept until they are pinned for the backup.
+        let safe_point = self.hummock_manager.register_safe_point().await;
+        let result = self.create_backup_impl(backup_storage, id).await;
+        self.hummock_manager.unregister_safe_point(safe_point).await;
+        let info = result?;
+
+        tracing::info!(
+            "meta backup {} created at epoch {}",
+            info.id,
+            info.max_committed_epoch
+        );
+        Ok(info)
+    }
+
+    async fn create_backup_impl(
+        &self,
+        backup_storage: &BackupStorage,
+        id: u64,
+    ) -> MetaResult<MetaBackupInfo> {
+        let mut column_families = vec![];
+        {
+            // The snapshot of the in-memory meta store blocks all writes, so drop it as soon as
+            // possible.
+            let snapshot = self.env.meta_store().snapshot().await;
+            for name in backup_column_families() {
+                let kvs = snapshot
+                    .list_cf_kv(&name)
+                    .await?
+                    .into_iter()
+                    .map(|(key, value)| KeyValue { key, value })
+                    .collect();
+                column_families.push(ColumnFamily { name, kvs });
+            }
+        }
+
+        let mut backup = MetaBackup {
+            info: None,
+            column_families,
+        };
+        let version = hummock_version_in_backup(&backup)?;
+        let info = MetaBackupInfo {
+            id,
+            format_version: META_BACKUP_FORMAT_VERSION,
+            max_committed_epoch: version.max_committed_epoch,
+            sst_ids: version.get_sst_ids(),
+            create_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs(),
+        };
+        backup.info = Some(info.clone());
+
+        self.hummock_manager
+            .pin_backup_ssts(info.sst_ids.iter().cloned())
+            .await;
+        backup_storage.write(&backup).await?;
+        Ok(info)
+    }
+
+    pub async fn list_backups(&self) -> MetaResult<Vec<MetaBackupInfo>> {
+        self.backup_storage()?.list().await
+    }
+}
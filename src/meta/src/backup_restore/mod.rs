@@ -0,0 +1,175 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod backup_manager;
+mod restore;
+
+use std::sync::Arc;
+
+use anyhow::Context;
+pub use backup_manager::*;
+use bytes::Bytes;
+use itertools::Itertools;
+use prost::Message;
+pub use restore::*;
+use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
+use risingwave_object_store::object::object_metrics::ObjectStoreMetrics;
+use risingwave_object_store::object::{parse_remote_object_store, ObjectStoreImpl};
+use risingwave_pb::catalog::{Database, Index, Schema, Sink, Source, Table};
+use risingwave_pb::hummock::{HummockVersion, HummockVersionDelta};
+use risingwave_pb::meta::{MetaBackup, MetaBackupInfo};
+use risingwave_pb::user::UserInfo;
+
+use crate::hummock::compaction_group::CompactionGroup;
+use crate::model::{MetadataModel, TableFragments, Worker};
+use crate::storage::DEFAULT_COLUMN_FAMILY;
+use crate::MetaResult;
+
+/// Bumped on incompatible changes to the content of [`MetaBackup`]. Backups of other format
+/// versions cannot be restored.
+pub const META_BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Column families included in meta backups.
+///
+/// The leader lease, pinned versions and snapshots of hummock, and compaction statuses and task
+/// assignments are excluded, as they are only valid for the workers and compaction tasks of the
+/// running cluster.
+fn backup_column_families() -> Vec<String> {
+    vec![
+        DEFAULT_COLUMN_FAMILY.to_string(),
+        Worker::cf_name(),
+        UserInfo::cf_name(),
+        Database::cf_name(),
+        Schema::cf_name(),
+        Table::cf_name(),
+        Source::cf_name(),
+        Sink::cf_name(),
+        Index::cf_name(),
+        TableFragments::cf_name(),
+        CompactionGroup::cf_name(),
+        HummockVersion::cf_name(),
+        HummockVersionDelta::cf_name(),
+    ]
+}
+
+/// Rebuilds the latest hummock version in `backup` from its checkpoint version and deltas, the
+/// same way as the hummock manager loads it from the meta store.
+fn hummock_version_in_backup(backup: &MetaBackup) -> MetaResult<HummockVersion> {
+    let decode_cf = |cf_name: String| {
+        backup
+            .column_families
+            .iter()
+            .find(|cf| cf.name == cf_name)
+            .into_iter()
+            .flat_map(|cf| cf.kvs.iter())
+            .map(|kv| kv.value.as_slice())
+            .collect_vec()
+    };
+    let mut version = match decode_cf(HummockVersion::cf_name()).first() {
+        Some(value) => {
+            HummockVersion::decode(*value).context("failed to decode the hummock version")?
+        }
+        None => return Ok(HummockVersion::default()),
+    };
+    let mut deltas = decode_cf(HummockVersionDelta::cf_name())
+        .into_iter()
+        .map(HummockVersionDelta::decode)
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to decode the hummock version deltas")?;
+    deltas.sort_by_key(|delta| delta.id);
+    for delta in &deltas {
+        if delta.prev_id == version.id {
+            version.apply_version_delta(delta);
+        }
+    }
+    Ok(version)
+}
+
+/// [`BackupStorage`] stores meta backups in an object store. Each backup is written to
+/// `<directory>/<id>.backup`, followed by its [`MetaBackupInfo`] to `<directory>/<id>.info`, so
+/// a backup is complete once its info is listed.
+#[derive(Clone)]
+pub struct BackupStorage {
+    store: Arc<ObjectStoreImpl>,
+    directory: String,
+}
+
+impl BackupStorage {
+    pub async fn new(url: &str, directory: String) -> Self {
+        let store = parse_remote_object_store(url, Arc::new(ObjectStoreMetrics::unused())).await;
+        Self {
+            store: Arc::new(store),
+            directory,
+        }
+    }
+
+    fn backup_path(&self, id: u64) -> String {
+        format!("{}/{}.backup", self.directory, id)
+    }
+
+    fn info_path(&self, id: u64) -> String {
+        format!("{}/{}.info", self.directory, id)
+    }
+
+    pub async fn write(&self, backup: &MetaBackup) -> MetaResult<()> {
+        let info = backup.info.as_ref().context("missing backup info")?;
+        self.store
+            .upload(
+                &self.backup_path(info.id),
+                Bytes::from(backup.encode_to_vec()),
+            )
+            .await
+            .context("failed to upload the meta backup")?;
+        self.store
+            .upload(&self.info_path(info.id), Bytes::from(info.encode_to_vec()))
+            .await
+            .context("failed to upload the meta backup info")?;
+        Ok(())
+    }
+
+    pub async fn read(&self, id: u64) -> MetaResult<MetaBackup> {
+        let bytes = self
+            .store
+            .read(&self.backup_path(id), None)
+            .await
+            .with_context(|| format!("failed to read meta backup {}", id))?;
+        let backup = MetaBackup::decode(bytes).context("failed to decode the meta backup")?;
+        Ok(backup)
+    }
+
+    /// Lists the complete backups ordered by id.
+    pub async fn list(&self) -> MetaResult<Vec<MetaBackupInfo>> {
+        let objects = self
+            .store
+            .list(&format!("{}/", self.directory))
+            .await
+            .context("failed to list meta backups")?;
+        let mut infos = vec![];
+        for object in objects {
+            if !object.key.ends_with(".info") {
+                continue;
+            }
+            let bytes = self
+                .store
+                .read(&object.key, None)
+                .await
+                .with_context(|| format!("failed to read meta backup info {}", object.key))?;
+            infos.push(
+                MetaBackupInfo::decode(bytes).context("failed to decode the meta backup info")?,
+            );
+        }
+        infos.sort_by_key(|info| info.id);
+        Ok(infos)
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::bail;
+use thiserror::Error;
+
+use super::{backup_column_families, BackupStorage, META_BACKUP_FORMAT_VERSION};
+use crate::manager::{advance_stored_id, HUMMOCK_SST_ID_CATEGORY};
+use crate::storage::MetaStore;
+use crate::MetaResult;
+
+/// The error of decoding a backup of a single object, e.g.
+/// [`TableFragments::deserialize_for_restore`](crate::model::TableFragments::deserialize_for_restore).
+#[derive(Error, Debug)]
+pub enum RestoreError {
+    #[error("backup is truncated: expect at least {expected} bytes, got {actual}")]
+    Truncated { expected: usize, actual: usize },
+
+    #[error("unsupported backup format version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("failed to decode the backup: {0}")]
+    Decode(#[from] prost::DecodeError),
+}
+
+/// SSTs uploaded after the backup was taken are not referenced by the restored hummock version,
+/// but may still be in object store with ids the restored id generator would allocate again. The
+/// SST id generator is advanced by this much on restore to avoid overwriting them, and they are
+/// removed by full GC later.
+const RESTORE_SST_ID_GAP: u64 = 1 << 32;
+
+/// Restores the meta store from the backup `backup_id` in `backup_storage`.
+///
+/// The meta store must be empty, and this must be done before any manager is created from it.
+/// All worker nodes must be restarted after the restore, as their states are newer than the
+/// backup.
+pub async fn restore_meta_store<S: MetaStore>(
+    meta_store: &S,
+    backup_storage: &BackupStorage,
+    backup_id: u64,
+) -> MetaResult<()> {
+    for cf in backup_column_families() {
+        if !meta_store.list_cf(&cf).await?.is_empty() {
+            bail!(
+                "cannot restore meta backup {} to a non-empty meta store, found data in {}",
+                backup_id,
+                cf
+            );
+        }
+    }
+
+    let backup = backup_storage.read(backup_id).await?;
+    let format_version = backup.info.as_ref().map_or(0, |info| info.format_version);
+    if format_version != META_BACKUP_FORMAT_VERSION {
+        bail!(
+            "cannot restore meta backup {} of format version {}, expect {}",
+            backup_id,
+            format_version,
+            META_BACKUP_FORMAT_VERSION
+        );
+    }
+
+    // The restore is not atomic. If it fails halfway, the meta store must be wiped before
+    // retrying.
+    for cf in backup.column_families {
+        for kv in cf.kvs {
+            meta_store.put_cf(&cf.name, kv.key, kv.value).await?;
+        }
+    }
+    advance_stored_id(meta_store, HUMMOCK_SST_ID_CATEGORY, RESTORE_SST_ID_GAP).await?;
+
+    tracing::info!("meta store restored from backup {}", backup_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
+
+    use super::*;
+    use crate::backup_restore::BackupManager;
+    use crate::hummock::test_utils::{add_test_tables, setup_compute_env};
+    use crate::storage::{MemStore, Snapshot, DEFAULT_COLUMN_FAMILY};
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let (env, hummock_manager, _cluster_manager, worker_node) = setup_compute_env(80).await;
+        add_test_tables(hummock_manager.as_ref(), worker_node.id).await;
+        let backup_storage = BackupStorage::new("memory", "backup".to_string()).await;
+        let backup_manager = BackupManager::new(
+            env.clone(),
+            hummock_manager.clone(),
+            Some(backup_storage.clone()),
+        )
+        .await
+        .unwrap();
+
+        let info = backup_manager.create_backup().await.unwrap();
+        assert_eq!(info.id, 1);
+        assert_eq!(info.format_version, META_BACKUP_FORMAT_VERSION);
+        let current_version = hummock_manager.get_current_version().await;
+        assert_eq!(
+            info.max_committed_epoch,
+            current_version.max_committed_epoch
+        );
+        assert_eq!(info.sst_ids, current_version.get_sst_ids());
+        assert_eq!(
+            backup_manager.list_backups().await.unwrap(),
+            vec![info.clone()]
+        );
+
+        // Restore to an empty meta store.
+        let meta_store = MemStore::new();
+        restore_meta_store(&meta_store, &backup_storage, info.id)
+            .await
+            .unwrap();
+        for cf in backup_column_families() {
+            let mut restored = meta_store.snapshot().await.list_cf_kv(&cf).await.unwrap();
+            let mut expected = env
+                .meta_store()
+                .snapshot()
+                .await
+                .list_cf_kv(&cf)
+                .await
+                .unwrap();
+            if cf == DEFAULT_COLUMN_FAMILY {
+                // The SST id generator is advanced on restore.
+                let sst_id_key = format!("{}_id_next_generator", HUMMOCK_SST_ID_CATEGORY);
+                let take_sst_id = |kvs: &mut Vec<(Vec<u8>, Vec<u8>)>| {
+                    let pos = kvs
+                        .iter()
+                        .position(|(key, _)| key == sst_id_key.as_bytes())
+                        .unwrap();
+                    let (_, value) = kvs.remove(pos);
+                    u64::from_be_bytes(value.try_into().unwrap())
+                };
+                assert_eq!(
+                    take_sst_id(&mut restored),
+                    take_sst_id(&mut expected) + RESTORE_SST_ID_GAP
+                );
+            }
+            assert_eq!(restored, expected);
+        }
+
+        // Restoring to a non-empty meta store is refused.
+        assert!(restore_meta_store(&meta_store, &backup_storage, info.id)
+            .await
+            .is_err());
+
+        let info = backup_manager.create_backup().await.unwrap();
+        assert_eq!(info.id, 2);
+        assert_eq!(backup_manager.list_backups().await.unwrap().len(), 2);
+    }
+}
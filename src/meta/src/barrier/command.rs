@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
 use futures::future::try_join_all;
@@ -25,8 +25,9 @@ use risingwave_pb::stream_plan::add_mutation::Dispatchers;
 use risingwave_pb::stream_plan::barrier::Mutation;
 use risingwave_pb::stream_plan::update_mutation::*;
 use risingwave_pb::stream_plan::{
-    ActorMapping, AddMutation, Dispatcher, PauseMutation, ResumeMutation,
-    SourceChangeSplitMutation, StopMutation, UpdateMutation,
+    ActorMapping, AddMutation, BackfillOrder, Dispatcher, PauseMutation, ResumeMutation,
+    SetLogLevelMutation, SourceChangeSplitMutation, SourceResetOffsetMutation,
+    StartBackfillMutation, StopMutation, UpdateMutation,
 };
 use risingwave_pb::stream_service::{DropActorsRequest, WaitEpochCommitRequest};
 use risingwave_rpc_client::StreamClientPoolRef;
@@ -35,7 +36,7 @@ use uuid::Uuid;
 use super::info::BarrierActorInfo;
 use super::snapshot::SnapshotManagerRef;
 use crate::barrier::CommandChanges;
-use crate::manager::{FragmentManagerRef, WorkerId};
+use crate::manager::{FragmentManagerRef, TopologyChange, WorkerId};
 use crate::model::{ActorId, DispatcherId, FragmentId, TableFragments};
 use crate::storage::MetaStore;
 use crate::stream::{build_actor_connector_splits, SourceManagerRef, SplitAssignment};
@@ -59,7 +60,7 @@ pub struct Reschedule {
     pub upstream_dispatcher_mapping: Option<ActorMapping>,
 
     /// The downstream fragments of this fragment.
-    pub downstream_fragment_id: Option<FragmentId>,
+    pub downstream_fragment_ids: Vec<FragmentId>,
 
     /// Reassigned splits for source actors
     pub actor_splits: HashMap<ActorId, Vec<SplitImpl>>,
@@ -94,11 +95,16 @@ pub enum Command {
     /// it adds the table fragments info to meta store. However, the creating progress will **last
     /// for a while** until the `finish` channel is signaled, then the state of `TableFragments`
     /// will be set to `Created`.
+    ///
+    /// In sequential backfill, only the chain actors of the first upstream table backfill on the
+    /// `Add` barrier. The others are started by `StartBackfill` barriers scheduled by the progress
+    /// tracker, one upstream table at a time.
     CreateMaterializedView {
         table_fragments: TableFragments,
         table_sink_map: HashMap<TableId, Vec<ActorId>>,
         dispatchers: HashMap<ActorId, Vec<Dispatcher>>,
         init_split_assignment: SplitAssignment,
+        backfill_order: BackfillOrder,
     },
 
     /// `Reschedule` command generates a `Update` barrier by the [`Reschedule`] of each fragment.
@@ -111,6 +117,10 @@ pub enum Command {
     /// `SourceSplitAssignment` generates Plain(Mutation::Splits) for pushing initialized splits or
     /// newly added splits.
     SourceSplitAssignment(SplitAssignment),
+
+    /// `SourceResetOffset` generates Plain(Mutation::SourceResetOffset) for overwriting the
+    /// offsets of the assigned splits, including the ones persisted in the source state tables.
+    SourceResetOffset(SplitAssignment),
 }
 
 impl Command {
@@ -126,6 +136,41 @@ impl Command {
         Self::Plain(Some(Mutation::Resume(ResumeMutation {})))
     }
 
+    pub fn start_backfill(actors: Vec<ActorId>) -> Self {
+        Self::Plain(Some(Mutation::StartBackfill(StartBackfillMutation {
+            actors,
+        })))
+    }
+
+    /// Updates the running dispatchers, e.g. to change their types.
+    pub fn update_dispatchers(dispatcher_update: Vec<DispatcherUpdate>) -> Self {
+        Self::Plain(Some(Mutation::Update(UpdateMutation {
+            dispatcher_update,
+            ..Default::default()
+        })))
+    }
+
+    pub fn set_log_level(actors: Vec<ActorId>, level: String, expire_at_ms: u64) -> Self {
+        Self::Plain(Some(Mutation::SetLogLevel(SetLogLevelMutation {
+            actors,
+            level,
+            expire_at_ms,
+        })))
+    }
+
+    /// The name of the kind of this command, for logging.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Command::Plain(None) => "Barrier",
+            Command::Plain(Some(_)) => "Plain",
+            Command::DropMaterializedViews(_) => "DropMaterializedViews",
+            Command::CreateMaterializedView { .. } => "CreateMaterializedView",
+            Command::RescheduleFragment(_) => "RescheduleFragment",
+            Command::SourceSplitAssignment(_) => "SourceSplitAssignment",
+            Command::SourceResetOffset(_) => "SourceResetOffset",
+        }
+    }
+
     /// Changes to the actors to be sent or collected after this command is committed.
     pub fn changes(&self) -> CommandChanges {
         match self {
@@ -147,7 +192,9 @@ impl Command {
                     .collect();
                 CommandChanges::Actor { to_add, to_remove }
             }
-            Command::SourceSplitAssignment(_) => CommandChanges::None,
+            Command::SourceSplitAssignment(_) | Command::SourceResetOffset(_) => {
+                CommandChanges::None
+            }
         }
     }
 
@@ -188,6 +235,10 @@ pub struct CommandContext<S: MetaStore> {
     pub checkpoint: bool,
 
     source_manager: SourceManagerRef<S>,
+
+    /// The root span of the distributed trace of this barrier. Disabled if the barrier is not
+    /// sampled for tracing.
+    pub span: tracing::Span,
 }
 
 impl<S: MetaStore> CommandContext<S> {
@@ -202,6 +253,7 @@ impl<S: MetaStore> CommandContext<S> {
         command: Command,
         checkpoint: bool,
         source_manager: SourceManagerRef<S>,
+        span: tracing::Span,
     ) -> Self {
         Self {
             fragment_manager,
@@ -213,6 +265,7 @@ impl<S: MetaStore> CommandContext<S> {
             command,
             checkpoint,
             source_manager,
+            span,
         }
     }
 }
@@ -238,6 +291,13 @@ where
                 }))
             }
 
+            Command::SourceResetOffset(change) => {
+                let actor_splits = change.values().flatten().map(|(a, s)| (*a, s.clone()));
+                Some(Mutation::SourceResetOffset(SourceResetOffsetMutation {
+                    actor_splits: build_actor_connector_splits(&actor_splits.collect()),
+                }))
+            }
+
             Command::DropMaterializedViews(table_ids) => {
                 let actors = self.fragment_manager.get_table_actor_ids(table_ids).await?;
                 Some(Mutation::Stop(StopMutation { actors }))
@@ -248,6 +308,15 @@ where
                 init_split_assignment: split_assignment,
                 ..
             } => {
+                let deferred_backfill_actors = if self.is_sequential_backfill() {
+                    self.actors_to_track()
+                        .into_values()
+                        .skip(1)
+                        .flatten()
+                        .collect()
+                } else {
+                    vec![]
+                };
                 let actor_dispatchers = dispatchers
                     .iter()
                     .map(|(&actor_id, dispatchers)| {
@@ -266,6 +335,7 @@ where
                 Some(Mutation::Add(AddMutation {
                     actor_dispatchers,
                     actor_splits,
+                    deferred_backfill_actors,
                 }))
             }
 
@@ -297,6 +367,7 @@ where
                                         removed_downstream_actor_id: reschedule
                                             .removed_actors
                                             .clone(),
+                                        ..Default::default()
                                     },
                                 )
                                 .unwrap();
@@ -307,7 +378,7 @@ where
 
                 let mut merge_update = HashMap::new();
                 for (&fragment_id, reschedule) in reschedules.iter() {
-                    if let Some(downstream_fragment_id) = reschedule.downstream_fragment_id {
+                    for &downstream_fragment_id in &reschedule.downstream_fragment_ids {
                         // Find the actors of the downstream fragment.
                         let downstream_actor_ids = self
                             .fragment_manager
@@ -397,20 +468,42 @@ where
         Ok(mutation)
     }
 
-    /// For `CreateMaterializedView`, returns the actors of the `Chain` nodes. For other commands,
-    /// returns an empty set.
-    pub fn actors_to_track(&self) -> HashSet<ActorId> {
+    /// For `CreateMaterializedView`, returns the actors of the `Chain` nodes grouped by the
+    /// upstream table they scan, in the order of backfill. For other commands, returns an empty
+    /// map.
+    pub fn actors_to_track(&self) -> BTreeMap<TableId, Vec<ActorId>> {
         match &self.command {
-            Command::CreateMaterializedView { dispatchers, .. } => dispatchers
-                .values()
-                .flatten()
-                .flat_map(|dispatcher| dispatcher.downstream_actor_id.iter().copied())
-                .collect(),
+            Command::CreateMaterializedView {
+                table_fragments, ..
+            } => table_fragments.chain_actor_ids_by_upstream_table(),
 
             _ => Default::default(),
         }
     }
 
+    /// The id of the table being created by `CreateMaterializedView`, if any.
+    pub fn creating_table_id(&self) -> Option<TableId> {
+        match &self.command {
+            Command::CreateMaterializedView {
+                table_fragments, ..
+            } => Some(table_fragments.table_id()),
+
+            _ => None,
+        }
+    }
+
+    /// Whether the chain actors of `CreateMaterializedView` backfill from one upstream table at a
+    /// time.
+    pub fn is_sequential_backfill(&self) -> bool {
+        matches!(
+            &self.command,
+            Command::CreateMaterializedView {
+                backfill_order: BackfillOrder::Sequential,
+                ..
+            }
+        )
+    }
+
     /// Do some stuffs after barriers are collected and the new storage version is committed, for
     /// the given command.
     pub async fn post_collect(&self) -> MetaResult<()> {
@@ -436,7 +529,8 @@ where
                 _ => {}
             },
 
-            Command::SourceSplitAssignment(split_assignment) => {
+            Command::SourceSplitAssignment(split_assignment)
+            | Command::SourceResetOffset(split_assignment) => {
                 self.fragment_manager
                     .update_actor_splits_by_split_assignment(split_assignment)
                     .await?;
@@ -466,7 +560,7 @@ where
 
                 // Drop fragment info in meta store.
                 self.fragment_manager
-                    .drop_table_fragments_vec(table_ids)
+                    .apply_topology_change(TopologyChange::DropTables(table_ids.clone()))
                     .await?;
             }
 
@@ -475,6 +569,7 @@ where
                 dispatchers,
                 table_sink_map,
                 init_split_assignment,
+                ..
             } => {
                 let mut dependent_table_actors = Vec::with_capacity(table_sink_map.len());
                 for (table_id, actors) in table_sink_map {
@@ -486,11 +581,11 @@ where
                     dependent_table_actors.push((*table_id, downstream_actors));
                 }
                 self.fragment_manager
-                    .post_create_table_fragments(
-                        &table_fragments.table_id(),
+                    .apply_topology_change(TopologyChange::PostCreateTable {
+                        table_id: table_fragments.table_id(),
                         dependent_table_actors,
-                        init_split_assignment.clone(),
-                    )
+                        split_assignment: init_split_assignment.clone(),
+                    })
                     .await?;
 
                 // For mview creation, the snapshot ingestion may last for several epochs. By
@@ -552,7 +647,7 @@ where
 
                 // Update fragment info after rescheduling in meta store.
                 self.fragment_manager
-                    .post_apply_reschedules(reschedules.clone())
+                    .apply_topology_change(TopologyChange::RescheduleFragments(reschedules.clone()))
                     .await?;
 
                 let mut stream_source_actor_splits = HashMap::new();
@@ -591,7 +686,9 @@ where
                 // Update the state of the table fragments from `Creating` to `Created`, so that the
                 // fragments can be scaled.
                 self.fragment_manager
-                    .mark_table_fragments_created(table_fragments.table_id())
+                    .apply_topology_change(TopologyChange::FinishCreateTable(
+                        table_fragments.table_id(),
+                    ))
                     .await?;
 
                 // Since the compute node reports that the chain actors have caught up with the
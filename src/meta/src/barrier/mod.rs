@@ -16,7 +16,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::mem::take;
 use std::ops::Deref;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use fail::fail_point;
 use futures::future::try_join_all;
@@ -28,28 +28,33 @@ use risingwave_common::util::epoch::INVALID_EPOCH;
 use risingwave_hummock_sdk::{HummockSstableId, LocalSstableInfo};
 use risingwave_pb::common::worker_node::State::Running;
 use risingwave_pb::common::WorkerType;
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
 use risingwave_pb::meta::table_fragments::actor_status::ActorState;
 use risingwave_pb::stream_plan::Barrier;
 use risingwave_pb::stream_service::{
     BarrierCompleteRequest, BarrierCompleteResponse, InjectBarrierRequest,
 };
+use risingwave_rpc_client::error::RpcError;
 use risingwave_rpc_client::StreamClientPoolRef;
+use risingwave_tracing::TracingContext;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot::{Receiver, Sender};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use self::command::CommandContext;
 use self::info::BarrierActorInfo;
 use self::notifier::Notifier;
 use self::progress::TrackingCommand;
+use self::slow_barrier::{is_slow_barrier, measure_latency, SlowBarrierRecord};
 use self::snapshot::SnapshotManagerRef;
 use crate::barrier::progress::CreateMviewProgressTracker;
 use crate::barrier::snapshot::SnapshotManager;
 use crate::barrier::BarrierEpochState::{Completed, InFlight};
 use crate::hummock::HummockManagerRef;
 use crate::manager::{
-    CatalogManagerRef, ClusterManagerRef, FragmentManagerRef, MetaSrvEnv, WorkerId,
+    CatalogManagerRef, ClusterManagerRef, FragmentManager, FragmentManagerRef, MetaSrvEnv, WorkerId,
 };
 use crate::model::{ActorId, BarrierManagerState};
 use crate::rpc::metrics::MetaMetrics;
@@ -63,6 +68,7 @@ mod notifier;
 mod progress;
 mod recovery;
 mod schedule;
+mod slow_barrier;
 mod snapshot;
 
 pub use self::command::{Command, Reschedule};
@@ -75,6 +81,11 @@ struct Scheduled {
     /// Choose a different barrier(checkpoint == true) according to it
     checkpoint: bool,
 }
+
+/// The responses of collecting a barrier from the compute nodes, along with the latency of
+/// collecting it from each of them.
+type CollectResult = MetaResult<Vec<(BarrierCompleteResponse, Duration)>>;
+
 /// Changes to the actors to be sent or collected after this command is committed.
 ///
 /// Since the checkpoints might be concurrent, the meta store of table fragments is only updated
@@ -119,6 +130,9 @@ pub struct GlobalBarrierManager<S: MetaStore> {
     /// The max barrier nums in flight
     in_flight_barrier_nums: usize,
 
+    /// Trace every n-th barrier. 0 for never.
+    barrier_trace_sample_interval: usize,
+
     cluster_manager: ClusterManagerRef<S>,
 
     pub(crate) catalog_manager: CatalogManagerRef<S>,
@@ -131,6 +145,9 @@ pub struct GlobalBarrierManager<S: MetaStore> {
 
     source_manager: SourceManagerRef<S>,
 
+    /// The backfill progress of the creating mviews, published by the progress tracker.
+    backfill_progress: parking_lot::RwLock<Vec<BackfillProgress>>,
+
     metrics: Arc<MetaMetrics>,
 
     pub(crate) env: MetaSrvEnv<S>,
@@ -257,6 +274,39 @@ where
         }
     }
 
+    /// Fences the command against the reschedules committed or in flight before it. A split
+    /// assignment derived from the actors of a fragment before a reschedule would assign splits
+    /// to the removed actors or miss the created ones, so it's rejected before being injected and
+    /// the scheduler may derive it again. The reschedules themselves reassign all the splits of
+    /// their fragments, which are committed after the assignments in flight.
+    async fn check_command(
+        &self,
+        command: &Command,
+        fragment_manager: &FragmentManager<S>,
+    ) -> MetaResult<()> {
+        match command {
+            Command::SourceSplitAssignment(assignment) | Command::SourceResetOffset(assignment) => {
+                let removing = assignment
+                    .values()
+                    .flat_map(|actor_splits| actor_splits.keys())
+                    .filter(|actor_id| self.removing_actors.contains(*actor_id))
+                    .copied()
+                    .sorted()
+                    .collect_vec();
+                if !removing.is_empty() {
+                    bail!(
+                        "actors {:?} in the split assignment are being removed by a reschedule",
+                        removing
+                    );
+                }
+                fragment_manager
+                    .check_split_assignment_actors(assignment)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Barrier can be sent to and collected from an actor if:
     /// 1. The actor is Running and not being dropped or removed in rescheduling.
     /// 2. The actor is Inactive and belongs to a creating MV or adding in rescheduling.
@@ -297,6 +347,9 @@ where
         self.command_ctx_queue.push_back(EpochNode {
             timer: Some(timer),
             wait_commit_timer: None,
+            inject_time: Instant::now(),
+            collect_latency: Duration::ZERO,
+            worker_collect_latencies: HashMap::new(),
             state: InFlight,
             command_ctx,
             notifiers,
@@ -308,7 +361,7 @@ where
     fn barrier_completed(
         &mut self,
         prev_epoch: u64,
-        result: Vec<BarrierCompleteResponse>,
+        result: Vec<(BarrierCompleteResponse, Duration)>,
     ) -> Vec<EpochNode<S>> {
         // change state to complete, and wait for nodes with the smaller epoch to commit
         let wait_commit_timer = self.metrics.barrier_wait_commit_latency.start_timer();
@@ -319,7 +372,12 @@ where
         {
             assert!(matches!(node.state, InFlight));
             node.wait_commit_timer = Some(wait_commit_timer);
-            node.state = Completed(result);
+            node.collect_latency = node.inject_time.elapsed();
+            node.worker_collect_latencies = result
+                .iter()
+                .map(|(resp, latency)| (resp.worker_id, *latency))
+                .collect();
+            node.state = Completed(result.into_iter().map(|(resp, _)| resp).collect());
         };
         // Find all continuous nodes with 'Complete' starting from first node
         let index = self
@@ -417,6 +475,12 @@ pub struct EpochNode<S: MetaStore> {
     timer: Option<HistogramTimer>,
     /// The timer of `barrier_wait_commit_latency`
     wait_commit_timer: Option<HistogramTimer>,
+    /// When this barrier is enqueued to be injected.
+    inject_time: Instant,
+    /// Latency from injecting this barrier to collecting it from all workers, set when completed.
+    collect_latency: Duration,
+    /// worker id => latency of collecting this barrier from the worker, set when completed.
+    worker_collect_latencies: HashMap<WorkerId, Duration>,
     /// Whether this barrier is in-flight or completed.
     state: BarrierEpochState,
     /// Context of this command to generate barrier and do some post jobs.
@@ -453,11 +517,13 @@ where
         let enable_recovery = env.opts.enable_recovery;
         let interval = env.opts.barrier_interval;
         let in_flight_barrier_nums = env.opts.in_flight_barrier_nums;
+        let barrier_trace_sample_interval = env.opts.barrier_trace_sample_interval;
         tracing::info!(
-            "Starting barrier manager with: interval={:?}, enable_recovery={}, in_flight_barrier_nums={}",
+            "Starting barrier manager with: interval={:?}, enable_recovery={}, in_flight_barrier_nums={}, barrier_trace_sample_interval={}",
             interval,
             enable_recovery,
             in_flight_barrier_nums,
+            barrier_trace_sample_interval,
         );
 
         let snapshot_manager = SnapshotManager::new(hummock_manager.clone()).into();
@@ -467,12 +533,14 @@ where
             enable_recovery,
             scheduled_barriers,
             in_flight_barrier_nums,
+            barrier_trace_sample_interval,
             cluster_manager,
             catalog_manager,
             fragment_manager,
             hummock_manager,
             snapshot_manager,
             source_manager,
+            backfill_progress: Default::default(),
             metrics,
             env,
         }
@@ -510,6 +578,7 @@ where
         let mut barrier_timer: Option<HistogramTimer> = None;
         let (barrier_complete_tx, mut barrier_complete_rx) = tokio::sync::mpsc::unbounded_channel();
         let mut checkpoint_control = CheckpointControl::new(self.metrics.clone());
+        let mut barrier_count = 0;
         loop {
             tokio::select! {
                 biased;
@@ -547,6 +616,16 @@ where
                 notifiers,
                 checkpoint,
             } = self.scheduled_barriers.pop_or_default().await;
+            if let Err(e) = checkpoint_control
+                .check_command(&command, &self.fragment_manager)
+                .await
+            {
+                tracing::warn!("rejected command {}: {}", command.kind_name(), e);
+                notifiers
+                    .into_iter()
+                    .for_each(|notifier| notifier.notify_collection_failed(e.clone()));
+                continue;
+            }
             let info = self
                 .resolve_actor_info(&mut checkpoint_control, &command)
                 .await;
@@ -583,7 +662,9 @@ where
                 command,
                 checkpoint,
                 self.source_manager.clone(),
+                self.new_barrier_span(barrier_count, new_epoch.0, checkpoint),
             ));
+            barrier_count += 1;
             let mut notifiers = notifiers;
             notifiers.iter_mut().for_each(Notifier::notify_to_send);
 
@@ -593,11 +674,28 @@ where
         }
     }
 
+    /// Create the root span of the distributed trace of the `barrier_count`-th barrier, or a
+    /// disabled span if the barrier is not sampled.
+    fn new_barrier_span(
+        &self,
+        barrier_count: usize,
+        epoch: u64,
+        checkpoint: bool,
+    ) -> tracing::Span {
+        if self.barrier_trace_sample_interval > 0
+            && barrier_count % self.barrier_trace_sample_interval == 0
+        {
+            tracing::info_span!(parent: None, "barrier", epoch, checkpoint)
+        } else {
+            tracing::Span::none()
+        }
+    }
+
     /// Inject a barrier to all CNs and spawn a task to collect it
     async fn inject_barrier(
         &self,
         command_context: Arc<CommandContext<S>>,
-        barrier_complete_tx: UnboundedSender<(u64, MetaResult<Vec<BarrierCompleteResponse>>)>,
+        barrier_complete_tx: UnboundedSender<(u64, CollectResult)>,
     ) {
         let prev_epoch = command_context.prev_epoch.0;
         let result = self.inject_barrier_inner(command_context.clone()).await;
@@ -622,6 +720,7 @@ where
         fail_point!("inject_barrier_err", |_| bail!("inject_barrier_err"));
         let mutation = command_context.to_mutation().await?;
         let info = command_context.info.clone();
+        let tracing_context = TracingContext::from_span(&command_context.span);
         let mut node_need_collect = HashMap::new();
         let inject_futures = info.node_map.iter().filter_map(|(node_id, node)| {
             let actor_ids_to_send = info.actor_ids_to_send(node_id).collect_vec();
@@ -641,8 +740,7 @@ where
                         prev: command_context.prev_epoch.0,
                     }),
                     mutation,
-                    // TODO(chi): add distributed tracing
-                    span: vec![],
+                    tracing_context: tracing_context.to_protobuf(),
                     checkpoint: command_context.checkpoint,
                     passed_actors: vec![],
                 };
@@ -663,6 +761,10 @@ where
                     // This RPC returns only if this worker node has injected this barrier.
                     client.inject_barrier(request).await
                 }
+                .instrument(
+                    tracing_context
+                        .child_span(|| tracing::info_span!("inject_barrier", worker_id = node_id)),
+                )
                 .into()
             }
         });
@@ -675,10 +777,11 @@ where
         node_need_collect: HashMap<WorkerId, bool>,
         client_pool_ref: StreamClientPoolRef,
         command_context: Arc<CommandContext<S>>,
-        barrier_complete_tx: UnboundedSender<(u64, MetaResult<Vec<BarrierCompleteResponse>>)>,
+        barrier_complete_tx: UnboundedSender<(u64, CollectResult)>,
     ) {
         let prev_epoch = command_context.prev_epoch.0;
         let info = command_context.info.clone();
+        let tracing_context = TracingContext::from_span(&command_context.span);
         let client_pool = client_pool_ref.deref();
         let collect_futures = info.node_map.iter().filter_map(|(node_id, node)| {
             if !*node_need_collect.get(node_id).unwrap() {
@@ -686,20 +789,28 @@ where
                 None
             } else {
                 let request_id = Uuid::new_v4().to_string();
+                let span = tracing_context
+                    .child_span(|| tracing::info_span!("collect_barrier", worker_id = node_id));
+                let request = BarrierCompleteRequest {
+                    request_id,
+                    prev_epoch,
+                    tracing_context: TracingContext::from_span(&span).to_protobuf(),
+                };
                 async move {
-                    let client = client_pool.get(node).await?;
-                    let request = BarrierCompleteRequest {
-                        request_id,
-                        prev_epoch,
-                    };
-                    tracing::trace!(
-                        target: "events::meta::barrier::barrier_complete",
-                        "barrier complete request: {:?}", request
-                    );
-
-                    // This RPC returns only if this worker node has collected this barrier.
-                    client.barrier_complete(request).await
+                    let (resp, latency) = measure_latency(async move {
+                        let client = client_pool.get(node).await?;
+                        tracing::trace!(
+                            target: "events::meta::barrier::barrier_complete",
+                            "barrier complete request: {:?}", request
+                        );
+
+                        // This RPC returns only if this worker node has collected this barrier.
+                        client.barrier_complete(request).await
+                    })
+                    .await;
+                    Ok::<_, RpcError>((resp?, latency))
                 }
+                .instrument(span)
                 .into()
             }
         });
@@ -715,7 +826,7 @@ where
     async fn barrier_complete_and_commit(
         &self,
         prev_epoch: u64,
-        result: MetaResult<Vec<BarrierCompleteResponse>>,
+        result: CollectResult,
         state: &mut BarrierManagerState,
         tracker: &mut CreateMviewProgressTracker<S>,
         checkpoint_control: &mut CheckpointControl<S>,
@@ -776,6 +887,7 @@ where
         if self.enable_recovery {
             // If failed, enter recovery mode.
             *tracker = CreateMviewProgressTracker::new();
+            self.backfill_progress.write().clear();
             let new_epoch = self.recovery(state.in_flight_prev_epoch).await;
             state.in_flight_prev_epoch = new_epoch;
             state
@@ -825,8 +937,11 @@ where
                         "no sstables should be produced in the first epoch"
                     );
                 } else if checkpoint {
+                    let span = TracingContext::from_span(&node.command_ctx.span)
+                        .child_span(|| tracing::info_span!("commit_epoch", epoch = prev_epoch));
                     self.hummock_manager
                         .commit_epoch(node.command_ctx.prev_epoch.0, synced_ssts, sst_to_worker)
+                        .instrument(span)
                         .await?;
                 } else {
                     self.hummock_manager.update_current_epoch(prev_epoch)?;
@@ -838,6 +953,8 @@ where
 
                 node.command_ctx.post_collect().await?;
 
+                self.log_slow_barrier(node).await;
+
                 // Notify about collected.
                 let mut notifiers = take(&mut node.notifiers);
                 notifiers.iter_mut().for_each(|notifier| {
@@ -861,6 +978,15 @@ where
                     commands
                 };
 
+                // Start the backfill from the next upstream tables of sequential backfill.
+                let actors_to_start_backfill = tracker.take_actors_to_start_backfill();
+                if !actors_to_start_backfill.is_empty() {
+                    self.scheduled_barriers
+                        .push_command(Command::start_backfill(actors_to_start_backfill))
+                        .await;
+                }
+                *self.backfill_progress.write() = tracker.backfill_progress();
+
                 for command in finished_commands {
                     // The command is ready to finish. We can now call `pre_finish`.
                     command.context.pre_finish().await?;
@@ -884,6 +1010,39 @@ where
         }
     }
 
+    /// Log the structured record of the barrier if it's slower than the system param
+    /// `slow_barrier_threshold_ms`.
+    async fn log_slow_barrier(&self, node: &EpochNode<S>) {
+        let threshold_ms = self
+            .env
+            .system_params_manager()
+            .get_params()
+            .await
+            .slow_barrier_threshold_ms;
+        if !is_slow_barrier(node.collect_latency, threshold_ms) {
+            return;
+        }
+        let guard = self.fragment_manager.get_fragment_read_guard().await;
+        let record = SlowBarrierRecord::new(
+            node.command_ctx.prev_epoch.0,
+            node.command_ctx.command.kind_name(),
+            node.command_ctx.checkpoint,
+            node.collect_latency,
+            &node.worker_collect_latencies,
+            &node.command_ctx.info.actor_map,
+            |actor_id| {
+                guard
+                    .get_fragment_by_actor(actor_id)
+                    .map(|(_, fragment_id)| fragment_id)
+            },
+        );
+        tracing::warn!(
+            target: "events::meta::barrier::slow",
+            "slow barrier: {}",
+            record.to_json()
+        );
+    }
+
     /// Resolve actor information from cluster, fragment manager and `ChangedTableId`.
     /// We use `changed_table_id` to modify the actors to be sent or collected. Because these actor
     /// will create or drop before this barrier flow through them.
@@ -911,4 +1070,193 @@ where
     }
 }
 
+impl<S> GlobalBarrierManager<S>
+where
+    S: MetaStore,
+{
+    /// Returns the backfill progress of the creating mviews from each of their upstream tables,
+    /// and the sources whose offsets are being reset.
+    pub fn get_backfill_progress(&self) -> Vec<BackfillProgress> {
+        let mut progress = self.backfill_progress.read().clone();
+        progress.extend(self.source_manager.get_offset_reset_progress());
+        progress
+    }
+}
+
 pub type BarrierManagerRef<S> = Arc<GlobalBarrierManager<S>>;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use risingwave_connector::source::datagen::DatagenSplit;
+    use risingwave_connector::source::{SplitId, SplitImpl, SplitMetaData};
+    use risingwave_pb::common::ParallelUnit;
+    use risingwave_pb::meta::table_fragments::{ActorStatus, Fragment};
+    use risingwave_pb::stream_plan::StreamActor;
+
+    use super::*;
+    use crate::model::{FragmentId, TableFragments};
+    use crate::storage::MemStore;
+    use crate::stream::{diff_splits, SplitAssignment};
+
+    const FRAGMENT_ID: FragmentId = 100;
+
+    fn split(split_index: i32) -> SplitImpl {
+        SplitImpl::Datagen(DatagenSplit::new(split_index, 3, None))
+    }
+
+    fn actor_status(actor_id: ActorId, state: ActorState) -> ActorStatus {
+        ActorStatus {
+            parallel_unit: Some(ParallelUnit {
+                id: actor_id,
+                worker_node_id: 1,
+            }),
+            state: state as i32,
+        }
+    }
+
+    /// Derives the assignment of the newly discovered splits from the running actors of the
+    /// fragment, the way the source manager does on each tick.
+    async fn derive_split_assignment(
+        fragment_manager: &FragmentManager<MemStore>,
+        discovered_splits: &BTreeMap<SplitId, SplitImpl>,
+    ) -> SplitAssignment {
+        let table_fragments = fragment_manager.list_table_fragments().await.unwrap();
+        let actor_splits = fragment_manager
+            .get_running_actors_of_fragment(FRAGMENT_ID)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|actor_id| {
+                let splits = table_fragments[0]
+                    .actor_splits
+                    .get(&actor_id)
+                    .cloned()
+                    .unwrap_or_default();
+                (actor_id, splits)
+            })
+            .collect();
+        let change = diff_splits(actor_splits, discovered_splits).unwrap();
+        HashMap::from([(FRAGMENT_ID, change)])
+    }
+
+    /// A split assignment derived before a reschedule of the source fragment, and scheduled while
+    /// the reschedule is in flight, is rejected. Derived again after the reschedule is committed,
+    /// it's accepted and leaves no splits to the removed actors.
+    #[tokio::test]
+    async fn test_reject_split_assignment_during_reschedule() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let mut table_fragments = TableFragments::new(
+            TableId::new(1),
+            BTreeMap::from([(
+                FRAGMENT_ID,
+                Fragment {
+                    fragment_id: FRAGMENT_ID,
+                    actors: [1, 2]
+                        .into_iter()
+                        .map(|actor_id| StreamActor {
+                            actor_id,
+                            fragment_id: FRAGMENT_ID,
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                },
+            )]),
+        );
+        table_fragments.set_actor_status(
+            [1, 2]
+                .into_iter()
+                .map(|actor_id| (actor_id, actor_status(actor_id, ActorState::Running)))
+                .collect(),
+        );
+        table_fragments.set_actor_splits_by_split_assignment(HashMap::from([(
+            FRAGMENT_ID,
+            HashMap::from([(1, vec![split(0)]), (2, vec![split(1)])]),
+        )]));
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+
+        // A new split is discovered while actor 2 is being replaced by actor 3.
+        let discovered_splits = (0..3)
+            .map(|split_index| (split(split_index).id(), split(split_index)))
+            .collect();
+        let stale_assignment = derive_split_assignment(&fragment_manager, &discovered_splits).await;
+
+        let reschedules = HashMap::from([(
+            FRAGMENT_ID,
+            Reschedule {
+                added_actors: vec![3],
+                removed_actors: vec![2],
+                vnode_bitmap_updates: HashMap::new(),
+                upstream_fragment_dispatcher_ids: vec![],
+                upstream_dispatcher_mapping: None,
+                downstream_fragment_ids: vec![],
+                actor_splits: HashMap::from([(1, vec![split(0)]), (3, vec![split(1)])]),
+            },
+        )]);
+        fragment_manager
+            .pre_apply_reschedules(HashMap::from([(
+                FRAGMENT_ID,
+                HashMap::from([(
+                    3,
+                    (
+                        StreamActor {
+                            actor_id: 3,
+                            fragment_id: FRAGMENT_ID,
+                            ..Default::default()
+                        },
+                        actor_status(3, ActorState::Inactive),
+                    ),
+                )]),
+            )]))
+            .await;
+        let reschedule = Command::RescheduleFragment(reschedules.clone());
+        let mut checkpoint_control = CheckpointControl::new(Arc::new(MetaMetrics::new()));
+        checkpoint_control.pre_resolve(&reschedule);
+        checkpoint_control.post_resolve(&reschedule);
+
+        let command = Command::SourceSplitAssignment(stale_assignment);
+        assert!(checkpoint_control
+            .check_command(&command, &fragment_manager)
+            .await
+            .is_err());
+
+        // The reschedule is committed once its barrier is collected, and the assignment is
+        // derived again on the retry.
+        fragment_manager.post_apply_reschedules(reschedules).await?;
+        checkpoint_control.remove_changes(reschedule.changes());
+        assert!(checkpoint_control
+            .check_command(&command, &fragment_manager)
+            .await
+            .is_err());
+
+        let assignment = derive_split_assignment(&fragment_manager, &discovered_splits).await;
+        let command = Command::SourceSplitAssignment(assignment.clone());
+        checkpoint_control
+            .check_command(&command, &fragment_manager)
+            .await?;
+        fragment_manager
+            .update_actor_splits_by_split_assignment(&assignment)
+            .await?;
+
+        let table_fragments = fragment_manager.list_table_fragments().await?;
+        let actor_splits = &table_fragments[0].actor_splits;
+        assert_eq!(
+            actor_splits.keys().copied().collect::<BTreeSet<_>>(),
+            BTreeSet::from([1, 3])
+        );
+        assert_eq!(
+            actor_splits
+                .values()
+                .flatten()
+                .map(|split| split.id())
+                .collect::<BTreeSet<_>>(),
+            discovered_splits.keys().cloned().collect()
+        );
+
+        Ok(())
+    }
+}
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+use risingwave_common::catalog::TableId;
 use risingwave_common::util::epoch::Epoch;
+use risingwave_pb::meta::get_backfill_progress_response::backfill_progress::Phase;
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
 use risingwave_pb::stream_service::barrier_complete_response::CreateMviewProgress;
 
 use super::command::CommandContext;
@@ -28,6 +31,8 @@ type CreateMviewEpoch = Epoch;
 
 #[derive(Clone, Copy)]
 enum ChainState {
+    /// Waiting for the backfill from the previous upstream tables, in sequential backfill.
+    Pending,
     ConsumingSnapshot,
     ConsumingUpstream(Epoch),
     Done,
@@ -35,23 +40,47 @@ enum ChainState {
 
 /// Progress of all actors containing chain nodes while creating mview.
 struct Progress {
+    /// The id of the creating mview.
+    table_id: TableId,
+
     states: HashMap<ActorId, ChainState>,
 
     done_count: usize,
+
+    /// The actors grouped by the upstream table they scan, in the order of backfill.
+    upstreams: Vec<(TableId, Vec<ActorId>)>,
+
+    /// The number of upstream tables in `upstreams` that the backfill has started from.
+    started_upstream_count: usize,
 }
 
 impl Progress {
-    /// Create a [`Progress`] for some creating mview, with all `actors` containing the chain nodes.
-    fn new(actors: impl IntoIterator<Item = ActorId>) -> Self {
-        let states = actors
-            .into_iter()
-            .map(|a| (a, ChainState::ConsumingSnapshot))
+    /// Create a [`Progress`] for some creating mview, with all `actors` containing the chain nodes
+    /// grouped by upstream table. In sequential backfill, only the actors of the first upstream
+    /// table start backfilling.
+    fn new(table_id: TableId, actors: BTreeMap<TableId, Vec<ActorId>>, sequential: bool) -> Self {
+        let upstreams = actors.into_iter().collect::<Vec<_>>();
+        let started_upstream_count = if sequential { 1 } else { upstreams.len() };
+        let states = upstreams
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, actors))| {
+                let state = if i < started_upstream_count {
+                    ChainState::ConsumingSnapshot
+                } else {
+                    ChainState::Pending
+                };
+                actors.iter().map(move |&a| (a, state))
+            })
             .collect::<HashMap<_, _>>();
         assert!(!states.is_empty());
 
         Self {
+            table_id,
             states,
             done_count: 0,
+            upstreams,
+            started_upstream_count,
         }
     }
 
@@ -64,10 +93,36 @@ impl Progress {
                 }
                 *state = new_state;
             }
+            ChainState::Pending => panic!("should not report before the backfill starts"),
             ChainState::Done => panic!("should not report done multiple times"),
         }
     }
 
+    /// If all actors of the upstream tables started are done, starts the backfill from the next
+    /// upstream table and returns its actors.
+    fn start_next_upstream(&mut self) -> Option<Vec<ActorId>> {
+        if self.started_upstream_count == self.upstreams.len()
+            || self.upstreams[..self.started_upstream_count]
+                .iter()
+                .flat_map(|(_, actors)| actors)
+                .any(|actor| !matches!(self.states[actor], ChainState::Done))
+        {
+            return None;
+        }
+
+        let (upstream_table_id, actors) = &self.upstreams[self.started_upstream_count];
+        tracing::debug!(
+            "start backfill from upstream table {} with actors {:?}",
+            upstream_table_id,
+            actors
+        );
+        for actor in actors {
+            self.states.insert(*actor, ChainState::ConsumingSnapshot);
+        }
+        self.started_upstream_count += 1;
+        Some(actors.clone())
+    }
+
     /// Returns whether all chains are done.
     fn is_done(&self) -> bool {
         self.done_count == self.states.len()
@@ -78,6 +133,33 @@ impl Progress {
     fn actors(&self) -> impl Iterator<Item = ActorId> + '_ {
         self.states.keys().cloned()
     }
+
+    /// Returns the backfill progress from each upstream table.
+    fn upstream_progress(&self) -> impl Iterator<Item = BackfillProgress> + '_ {
+        self.upstreams
+            .iter()
+            .enumerate()
+            .map(move |(i, (upstream_table_id, actors))| {
+                let done_actor_count = actors
+                    .iter()
+                    .filter(|actor| matches!(self.states[actor], ChainState::Done))
+                    .count();
+                let phase = if i >= self.started_upstream_count {
+                    Phase::Pending
+                } else if done_actor_count == actors.len() {
+                    Phase::Done
+                } else {
+                    Phase::Backfilling
+                };
+                BackfillProgress {
+                    table_id: self.table_id.table_id,
+                    upstream_table_id: upstream_table_id.table_id,
+                    phase: phase as i32,
+                    done_actor_count: done_actor_count as u32,
+                    total_actor_count: actors.len() as u32,
+                }
+            })
+    }
 }
 
 /// The command tracking by the [`CreateMviewProgressTracker`].
@@ -97,6 +179,10 @@ pub(super) struct CreateMviewProgressTracker<S: MetaStore> {
 
     /// Find the epoch of the create-mview DDL by the actor containing the chain node.
     actor_map: HashMap<ActorId, CreateMviewEpoch>,
+
+    /// The actors to start backfilling in sequential backfill, since the backfill from the
+    /// previous upstream tables is done.
+    actors_to_start_backfill: Vec<ActorId>,
 }
 
 impl<S: MetaStore> CreateMviewProgressTracker<S> {
@@ -104,6 +190,7 @@ impl<S: MetaStore> CreateMviewProgressTracker<S> {
         Self {
             progress_map: Default::default(),
             actor_map: Default::default(),
+            actors_to_start_backfill: Default::default(),
         }
     }
 
@@ -118,11 +205,15 @@ impl<S: MetaStore> CreateMviewProgressTracker<S> {
         }
 
         let ddl_epoch = command.context.curr_epoch;
-        for &actor in &actors {
+        for &actor in actors.values().flatten() {
             self.actor_map.insert(actor, ddl_epoch);
         }
 
-        let progress = Progress::new(actors);
+        let progress = Progress::new(
+            command.context.creating_table_id().unwrap(),
+            actors,
+            command.context.is_sequential_backfill(),
+        );
         let old = self.progress_map.insert(ddl_epoch, (progress, command));
         assert!(old.is_none());
         None
@@ -147,6 +238,9 @@ impl<S: MetaStore> CreateMviewProgressTracker<S> {
             Entry::Occupied(mut o) => {
                 let progress = &mut o.get_mut().0;
                 progress.update(actor, new_state);
+                if let Some(actors) = progress.start_next_upstream() {
+                    self.actors_to_start_backfill.extend(actors);
+                }
 
                 if progress.is_done() {
                     tracing::debug!("all actors done for creating mview with epoch {}!", epoch);
@@ -168,4 +262,17 @@ impl<S: MetaStore> CreateMviewProgressTracker<S> {
             }
         }
     }
+
+    /// Takes the actors to start backfilling, which should be sent with a `StartBackfill` barrier.
+    pub fn take_actors_to_start_backfill(&mut self) -> Vec<ActorId> {
+        std::mem::take(&mut self.actors_to_start_backfill)
+    }
+
+    /// Returns the backfill progress of all creating mviews from each of their upstream tables.
+    pub fn backfill_progress(&self) -> Vec<BackfillProgress> {
+        self.progress_map
+            .values()
+            .flat_map(|(progress, _)| progress.upstream_progress())
+            .collect()
+    }
 }
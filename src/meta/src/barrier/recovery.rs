@@ -27,13 +27,13 @@ use risingwave_pb::stream_service::{
     BroadcastActorInfoTableRequest, BuildActorsRequest, ForceStopActorsRequest, UpdateActorsRequest,
 };
 use tokio_retry::strategy::{jitter, ExponentialBackoff};
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 use uuid::Uuid;
 
 use crate::barrier::command::CommandContext;
 use crate::barrier::info::BarrierActorInfo;
 use crate::barrier::{CheckpointControl, Command, GlobalBarrierManager};
-use crate::manager::WorkerId;
+use crate::manager::{is_streaming_worker, TopologyChange, WorkerId};
 use crate::model::ActorId;
 use crate::storage::MetaStore;
 use crate::stream::build_actor_connector_splits;
@@ -81,7 +81,7 @@ where
 
         debug!("clean dirty table fragments: {:?}", to_drop_table_ids);
         self.fragment_manager
-            .drop_table_fragments_vec(&to_drop_table_ids)
+            .apply_topology_change(TopologyChange::DropTables(to_drop_table_ids))
             .await?;
 
         Ok(())
@@ -125,6 +125,7 @@ where
             let command = Command::Plain(Some(Mutation::Add(AddMutation {
                 actor_dispatchers: Default::default(),
                 actor_splits: build_actor_connector_splits(&source_split_assignments),
+                ..Default::default()
             })));
 
             let prev_epoch = new_epoch;
@@ -140,6 +141,7 @@ where
                 command,
                 true,
                 self.source_manager.clone(),
+                tracing::Span::none(),
             ));
 
             let (barrier_complete_tx, mut barrier_complete_rx) =
@@ -187,7 +189,9 @@ where
             let new_nodes = current_nodes
                 .into_iter()
                 .filter(|node| {
-                    !info.node_map.contains_key(&node.id) && !node_map.contains_key(&node.id)
+                    is_streaming_worker(node)
+                        && !info.node_map.contains_key(&node.id)
+                        && !node_map.contains_key(&node.id)
                 })
                 .collect_vec();
             for new_node in new_nodes {
@@ -230,9 +234,17 @@ where
         debug!("got expired workers {:#?}", expired_workers);
 
         let (migrate_map, node_map) = self.get_migrate_map_plan(info, &expired_workers).await;
+        let cost = self
+            .fragment_manager
+            .compute_migration_cost(&migrate_map, &self.hummock_manager.get_table_write_stats())
+            .await;
+        info!(?cost, "migrating actors of expired workers");
         // 2. migrate actors in fragments
         self.fragment_manager
-            .migrate_actors(&migrate_map, &node_map)
+            .apply_topology_change(TopologyChange::MigrateActors {
+                migrate_map,
+                node_map,
+            })
             .await?;
         debug!("migrate actors succeed.");
 
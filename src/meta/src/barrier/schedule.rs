@@ -235,6 +235,20 @@ impl ScheduledBarriers {
         scheduled
     }
 
+    /// Push a command scheduled by the barrier manager itself into the queue, which has no
+    /// notifiers to wait for it.
+    pub(super) async fn push_command(&self, command: Command) {
+        let mut queue = self.inner.queue.write().await;
+        queue.push_back(Scheduled {
+            checkpoint: command.need_checkpoint(),
+            command,
+            notifiers: Default::default(),
+        });
+        if queue.len() == 1 {
+            self.inner.changed_tx.send(()).ok();
+        }
+    }
+
     /// Wait for at least one scheduled barrier in the queue.
     pub(super) async fn wait_one(&self) {
         let queue = self.inner.queue.read().await;
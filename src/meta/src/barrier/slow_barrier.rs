@@ -0,0 +1,159 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::manager::WorkerId;
+use crate::model::{ActorId, FragmentId};
+
+/// Whether a barrier collected in `latency` is slow, where a zero `threshold_ms` disables the
+/// slow barrier log.
+pub fn is_slow_barrier(latency: Duration, threshold_ms: u64) -> bool {
+    threshold_ms > 0 && latency > Duration::from_millis(threshold_ms)
+}
+
+/// Awaits `future`, e.g. the RPC collecting a barrier from a worker, and returns its output with
+/// the time it takes.
+pub async fn measure_latency<F: Future>(future: F) -> (F::Output, Duration) {
+    let start_time = Instant::now();
+    let output = future.await;
+    (output, start_time.elapsed())
+}
+
+/// The structured record of a slow barrier, logged in JSON.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SlowBarrierRecord {
+    pub prev_epoch: u64,
+    pub command: &'static str,
+    pub checkpoint: bool,
+    /// Latency from injecting the barrier to collecting it from all workers.
+    pub latency_ms: u64,
+    /// worker id => latency of collecting the barrier from the worker.
+    pub worker_collect_latency_ms: BTreeMap<WorkerId, u64>,
+    pub slowest_worker: Option<WorkerId>,
+    /// The fragment with the most actors on the slowest worker, which most likely holds back the
+    /// barrier since the compute nodes don't report the latency of each actor.
+    pub slowest_fragment: Option<FragmentId>,
+}
+
+impl SlowBarrierRecord {
+    /// `actor_map` is the actors each worker collects the barrier from, and `fragment_of_actor`
+    /// maps the actors to their fragments.
+    pub fn new(
+        prev_epoch: u64,
+        command: &'static str,
+        checkpoint: bool,
+        latency: Duration,
+        worker_collect_latencies: &HashMap<WorkerId, Duration>,
+        actor_map: &HashMap<WorkerId, Vec<ActorId>>,
+        fragment_of_actor: impl Fn(ActorId) -> Option<FragmentId>,
+    ) -> Self {
+        // Ties are broken by the smaller worker id.
+        let slowest_worker = worker_collect_latencies
+            .iter()
+            .max_by_key(|(&worker_id, &latency)| (latency, std::cmp::Reverse(worker_id)))
+            .map(|(&worker_id, _)| worker_id);
+        let slowest_fragment = slowest_worker
+            .and_then(|worker_id| actor_map.get(&worker_id))
+            .and_then(|actor_ids| {
+                actor_ids
+                    .iter()
+                    .filter_map(|&actor_id| fragment_of_actor(actor_id))
+                    .counts()
+                    .into_iter()
+                    .max_by_key(|&(fragment_id, count)| (count, std::cmp::Reverse(fragment_id)))
+                    .map(|(fragment_id, _)| fragment_id)
+            });
+
+        Self {
+            prev_epoch,
+            command,
+            checkpoint,
+            latency_ms: latency.as_millis() as u64,
+            worker_collect_latency_ms: worker_collect_latencies
+                .iter()
+                .map(|(&worker_id, latency)| (worker_id, latency.as_millis() as u64))
+                .collect(),
+            slowest_worker,
+            slowest_fragment,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_barrier_record() {
+        // Collect the barrier from the workers, which take different times.
+        let inject_time = Instant::now();
+        let worker_collect_latencies: HashMap<_, _> = join_all([(1, 0), (2, 100), (3, 50)].map(
+            |(worker_id, ms)| async move {
+                let ((), latency) =
+                    measure_latency(tokio::time::sleep(Duration::from_millis(ms))).await;
+                (worker_id, latency)
+            },
+        ))
+        .await
+        .into_iter()
+        .collect();
+        let latency = inject_time.elapsed();
+        let actor_map = HashMap::from([(1, vec![1, 2]), (2, vec![3, 4, 5]), (3, vec![6])]);
+        let fragment_of_actor = |actor_id| match actor_id {
+            1 | 3 => Some(100),
+            2 | 4 | 5 => Some(101),
+            _ => None,
+        };
+
+        assert!(worker_collect_latencies[&2] >= Duration::from_millis(100));
+        assert!(worker_collect_latencies[&3] >= Duration::from_millis(50));
+        assert!(latency >= worker_collect_latencies[&2]);
+        assert!(!is_slow_barrier(latency, 0));
+        assert!(!is_slow_barrier(latency, 60_000));
+        assert!(is_slow_barrier(latency, 50));
+
+        let record = SlowBarrierRecord::new(
+            42,
+            "Barrier",
+            true,
+            latency,
+            &worker_collect_latencies,
+            &actor_map,
+            fragment_of_actor,
+        );
+        assert_eq!(record.latency_ms, latency.as_millis() as u64);
+        assert_eq!(record.worker_collect_latency_ms.len(), 3);
+        assert_eq!(record.slowest_worker, Some(2));
+        assert_eq!(record.slowest_fragment, Some(101));
+        let json: serde_json::Value = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(json["prev_epoch"], 42);
+        assert_eq!(
+            json["worker_collect_latency_ms"]["2"],
+            record.worker_collect_latency_ms[&2]
+        );
+        assert_eq!(json["slowest_fragment"], 101);
+    }
+}
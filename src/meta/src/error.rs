@@ -19,7 +19,7 @@ use risingwave_pb::ProstFieldNotFound;
 use risingwave_rpc_client::error::RpcError;
 
 use crate::hummock::error::Error as HummockError;
-use crate::manager::WorkerId;
+use crate::manager::{SplitAssignmentError, WorkerId};
 use crate::model::MetadataModelError;
 use crate::storage::MetaStoreError;
 
@@ -55,6 +55,9 @@ enum MetaErrorInner {
     #[error("{0} with name {1} exists")]
     Duplicated(&'static str, String),
 
+    #[error("Invalid source split assignment: {0}")]
+    InvalidSplitAssignment(#[source] SplitAssignmentError),
+
     #[error(transparent)]
     Internal(anyhow::Error),
 }
@@ -105,6 +108,14 @@ impl MetaError {
         std::matches!(self.inner.borrow(), &MetaErrorInner::InvalidWorker(_))
     }
 
+    /// The violation found in a rejected source split assignment, if this is the error.
+    pub fn split_assignment_error(&self) -> Option<&SplitAssignmentError> {
+        match &*self.inner {
+            MetaErrorInner::InvalidSplitAssignment(e) => Some(e),
+            _ => None,
+        }
+    }
+
     pub fn catalog_not_found<T: Into<String>>(relation: &'static str, name: T) -> Self {
         MetaErrorInner::CatalogNotFound(relation, name.into()).into()
     }
@@ -136,6 +147,12 @@ impl From<RpcError> for MetaError {
     }
 }
 
+impl From<SplitAssignmentError> for MetaError {
+    fn from(e: SplitAssignmentError) -> Self {
+        MetaErrorInner::InvalidSplitAssignment(e).into()
+    }
+}
+
 impl From<anyhow::Error> for MetaError {
     fn from(a: anyhow::Error) -> Self {
         MetaErrorInner::Internal(a).into()
@@ -152,6 +169,9 @@ impl From<MetaError> for tonic::Status {
                 tonic::Status::not_found(err.to_string())
             }
             MetaErrorInner::Duplicated(_, _) => tonic::Status::already_exists(err.to_string()),
+            MetaErrorInner::InvalidSplitAssignment(_) => {
+                tonic::Status::invalid_argument(err.to_string())
+            }
             _ => tonic::Status::internal(err.to_string()),
         }
     }
@@ -59,6 +59,8 @@ impl CompactionConfigBuilder {
                     | CompactionFilterFlag::TTL)
                     .into(),
                 max_sub_compaction: DEFAULT_MAX_SUB_COMPACTION,
+                max_concurrent_task_number: 0,
+                max_compaction_throughput: 0,
             },
         }
     }
@@ -103,4 +105,6 @@ builder_field! {
     compression_algorithm: Vec<String>,
     compaction_filter_mask: u32,
     max_sub_compaction: u32,
+    max_concurrent_task_number: u64,
+    max_compaction_throughput: u64,
 }
@@ -329,6 +329,7 @@ pub mod tests {
 
     use itertools::Itertools;
     use risingwave_common::config::constant::hummock::CompactionFilterFlag;
+    use risingwave_hummock_sdk::key::KeyEncodingVersion;
     use risingwave_pb::hummock::compaction_config::CompactionMode;
     use risingwave_pb::hummock::{KeyRange, Level, LevelType, OverlappingLevel, SstableInfo};
 
@@ -392,6 +393,8 @@ pub mod tests {
             stale_key_count: 0,
             total_key_count: 0,
             divide_version: 0,
+            table_stats: Default::default(),
+            key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
         }
     }
 
@@ -155,12 +155,16 @@ impl CompactStatus {
             task_status: TaskStatus::Pending as i32,
             compaction_group_id,
             existing_table_ids: vec![],
+            split_table_ids: vec![],
             compression_algorithm,
             target_file_size: ret.target_file_size,
             compaction_filter_mask: 0,
             table_options: HashMap::default(),
             current_epoch_time: 0,
             target_sub_level_id: ret.input.target_sub_level_id,
+            input_file_size: 0,
+            estimated_memory_usage: 0,
+            slow_task_threshold_ms: 0,
         };
         Some(compact_task)
     }
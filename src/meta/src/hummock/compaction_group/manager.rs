@@ -460,6 +460,12 @@ fn update_compaction_config(target: &mut CompactionConfig, items: &[MutableConfi
             MutableConfig::MaxSubCompaction(c) => {
                 target.max_sub_compaction = *c;
             }
+            MutableConfig::MaxConcurrentTaskNumber(c) => {
+                target.max_concurrent_task_number = *c;
+            }
+            MutableConfig::MaxCompactionThroughput(c) => {
+                target.max_compaction_throughput = *c;
+            }
         }
     }
 }
@@ -0,0 +1,284 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use risingwave_hummock_sdk::CompactionGroupId;
+use risingwave_pb::hummock::{CompactTask, CompactionConfig};
+
+/// Scheduling limits of a compaction group. 0 means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GroupScheduleLimit {
+    /// The maximum number of tasks of the group running at the same time.
+    pub max_concurrent_task_number: u64,
+    /// The maximum compaction input bytes per second scheduled for the group.
+    pub max_compaction_throughput: u64,
+}
+
+impl From<&CompactionConfig> for GroupScheduleLimit {
+    fn from(config: &CompactionConfig) -> Self {
+        Self {
+            max_concurrent_task_number: config.max_concurrent_task_number,
+            max_compaction_throughput: config.max_compaction_throughput,
+        }
+    }
+}
+
+#[derive(Default)]
+struct GroupState {
+    /// Current weight of the smooth weighted round robin.
+    current_weight: i64,
+    /// The group cannot be scheduled until this instant, because the input bytes of its
+    /// scheduled tasks have exceeded `max_compaction_throughput`.
+    throttled_until: Option<Instant>,
+}
+
+/// [`CompactionGroupLimiter`] decides which pending compaction group gets the next idle
+/// compactor.
+///
+/// A group is eligible if it has fewer running tasks than its `max_concurrent_task_number` and is
+/// not throttled by its `max_compaction_throughput`. Eligible groups are picked by smooth weighted
+/// round robin, weighted by their concurrency limits, so that the backlog of one group cannot
+/// monopolize all compactors.
+#[derive(Default)]
+pub struct CompactionGroupLimiter {
+    groups: HashMap<CompactionGroupId, GroupState>,
+}
+
+impl CompactionGroupLimiter {
+    /// Picks the next group to schedule from `pending`, or `None` if all of them have reached
+    /// their limits. Groups without a concurrency limit are weighted by `default_weight`.
+    pub fn pick_next(
+        &mut self,
+        pending: &BTreeSet<CompactionGroupId>,
+        limits: &HashMap<CompactionGroupId, GroupScheduleLimit>,
+        running_task_num: &HashMap<CompactionGroupId, u64>,
+        default_weight: u64,
+        now: Instant,
+    ) -> Option<CompactionGroupId> {
+        let mut eligible = vec![];
+        for group_id in pending {
+            let limit = limits.get(group_id).copied().unwrap_or_default();
+            if limit.max_concurrent_task_number != 0
+                && running_task_num.get(group_id).copied().unwrap_or(0)
+                    >= limit.max_concurrent_task_number
+            {
+                continue;
+            }
+            if let Some(state) = self.groups.get(group_id)
+                && let Some(throttled_until) = state.throttled_until
+                && throttled_until > now
+            {
+                continue;
+            }
+            let weight = match limit.max_concurrent_task_number {
+                0 => default_weight.max(1),
+                n => n,
+            };
+            eligible.push((*group_id, weight as i64));
+        }
+
+        let total_weight: i64 = eligible.iter().map(|(_, weight)| weight).sum();
+        let mut picked: Option<(CompactionGroupId, i64)> = None;
+        for (group_id, weight) in eligible {
+            let state = self.groups.entry(group_id).or_default();
+            state.current_weight += weight;
+            if picked.map_or(true, |(_, max_weight)| state.current_weight > max_weight) {
+                picked = Some((group_id, state.current_weight));
+            }
+        }
+        let (group_id, _) = picked?;
+        self.groups.get_mut(&group_id).unwrap().current_weight -= total_weight;
+        Some(group_id)
+    }
+
+    /// Accounts the input bytes of a task scheduled for `group_id` against its throughput limit.
+    pub fn on_task_scheduled(
+        &mut self,
+        group_id: CompactionGroupId,
+        limit: &GroupScheduleLimit,
+        input_bytes: u64,
+        now: Instant,
+    ) {
+        if limit.max_compaction_throughput == 0 {
+            return;
+        }
+        let state = self.groups.entry(group_id).or_default();
+        let start = state.throttled_until.map_or(now, |t| t.max(now));
+        state.throttled_until = Some(
+            start
+                + Duration::from_secs_f64(
+                    input_bytes as f64 / limit.max_compaction_throughput as f64,
+                ),
+        );
+    }
+
+    /// Removes the state of groups that no longer exist.
+    pub fn retain_groups(&mut self, group_ids: &[CompactionGroupId]) {
+        self.groups
+            .retain(|group_id, _| group_ids.contains(group_id));
+    }
+}
+
+/// Returns the total size of the input SSTs of `compact_task`.
+pub fn compact_task_input_bytes(compact_task: &CompactTask) -> u64 {
+    compact_task
+        .input_ssts
+        .iter()
+        .flat_map(|level| level.table_infos.iter())
+        .map(|sst| sst.file_size)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+    use std::time::{Duration, Instant};
+
+    use super::{CompactionGroupLimiter, GroupScheduleLimit};
+
+    const GROUP_A: u64 = 1;
+    const GROUP_B: u64 = 2;
+
+    fn concurrency_limit(n: u64) -> GroupScheduleLimit {
+        GroupScheduleLimit {
+            max_concurrent_task_number: n,
+            max_compaction_throughput: 0,
+        }
+    }
+
+    /// Simulates `steps` scheduling rounds with `capacity` compactor slots, where every task takes
+    /// `task_steps` rounds to finish. Returns the number of tasks assigned to each group.
+    fn simulate_concurrency(
+        limits: &HashMap<u64, GroupScheduleLimit>,
+        capacity: usize,
+        task_steps: usize,
+        steps: usize,
+    ) -> HashMap<u64, usize> {
+        let mut limiter = CompactionGroupLimiter::default();
+        let pending = BTreeSet::from([GROUP_A, GROUP_B]);
+        let now = Instant::now();
+        let mut running: Vec<(u64, usize)> = vec![];
+        let mut assigned = HashMap::new();
+        for step in 0..steps {
+            running.retain(|(_, finish_step)| *finish_step > step);
+            while running.len() < capacity {
+                let mut running_task_num = HashMap::new();
+                for (group_id, _) in &running {
+                    *running_task_num.entry(*group_id).or_insert(0) += 1;
+                }
+                let group_id = match limiter.pick_next(
+                    &pending,
+                    limits,
+                    &running_task_num,
+                    capacity as u64,
+                    now,
+                ) {
+                    Some(group_id) => group_id,
+                    None => break,
+                };
+                running.push((group_id, step + task_steps));
+                *assigned.entry(group_id).or_insert(0) += 1;
+            }
+        }
+        assigned
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let limits = HashMap::from([
+            (GROUP_A, concurrency_limit(3)),
+            (GROUP_B, concurrency_limit(1)),
+        ]);
+        let mut limiter = CompactionGroupLimiter::default();
+        let pending = BTreeSet::from([GROUP_A, GROUP_B]);
+        let now = Instant::now();
+        let running = HashMap::from([(GROUP_A, 3)]);
+        assert_eq!(
+            limiter.pick_next(&pending, &limits, &running, 4, now),
+            Some(GROUP_B)
+        );
+        let running = HashMap::from([(GROUP_A, 3), (GROUP_B, 1)]);
+        assert_eq!(limiter.pick_next(&pending, &limits, &running, 4, now), None);
+
+        // Enough capacity: each group runs up to its own limit.
+        let assigned = simulate_concurrency(&limits, 4, 2, 10);
+        assert_eq!(assigned[&GROUP_A], 15);
+        assert_eq!(assigned[&GROUP_B], 5);
+
+        // Insufficient capacity: the groups share compactors by their weights.
+        let assigned = simulate_concurrency(&limits, 2, 1, 12);
+        assert_eq!(assigned[&GROUP_A], 18);
+        assert_eq!(assigned[&GROUP_B], 6);
+    }
+
+    #[test]
+    fn test_default_limit_round_robin() {
+        let limits = HashMap::new();
+        let assigned = simulate_concurrency(&limits, 1, 1, 10);
+        assert_eq!(assigned[&GROUP_A], 5);
+        assert_eq!(assigned[&GROUP_B], 5);
+    }
+
+    #[test]
+    fn test_throughput_limit() {
+        let limits = HashMap::from([(
+            GROUP_A,
+            GroupScheduleLimit {
+                max_concurrent_task_number: 0,
+                max_compaction_throughput: 100,
+            },
+        )]);
+        let mut limiter = CompactionGroupLimiter::default();
+        let pending = BTreeSet::from([GROUP_A, GROUP_B]);
+        let start = Instant::now();
+        let mut assigned = HashMap::new();
+        // One compactor slot every 500ms, and each task reads 100 bytes.
+        for step in 0..10 {
+            let now = start + Duration::from_millis(500 * step);
+            let group_id = limiter
+                .pick_next(&pending, &limits, &HashMap::new(), 1, now)
+                .unwrap();
+            let limit = limits.get(&group_id).copied().unwrap_or_default();
+            limiter.on_task_scheduled(group_id, &limit, 100, now);
+            *assigned.entry(group_id).or_insert(0) += 1;
+        }
+        assert_eq!(assigned[&GROUP_A], 4);
+        assert_eq!(assigned[&GROUP_B], 6);
+
+        // Only the throttled group is pending.
+        let pending = BTreeSet::from([GROUP_A]);
+        let now = start + Duration::from_millis(6000);
+        assert_eq!(
+            limiter.pick_next(&pending, &limits, &HashMap::new(), 1, now),
+            Some(GROUP_A)
+        );
+        limiter.on_task_scheduled(GROUP_A, &limits[&GROUP_A], 100, now);
+        assert_eq!(
+            limiter.pick_next(&pending, &limits, &HashMap::new(), 1, now),
+            None
+        );
+        assert_eq!(
+            limiter.pick_next(
+                &pending,
+                &limits,
+                &HashMap::new(),
+                1,
+                now + Duration::from_secs(1)
+            ),
+            Some(GROUP_A)
+        );
+    }
+}
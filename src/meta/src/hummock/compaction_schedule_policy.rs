@@ -368,6 +368,7 @@ mod tests {
 
     use risingwave_common::try_match_expand;
     use risingwave_hummock_sdk::compaction_group::StaticCompactionGroupId;
+    use risingwave_hummock_sdk::key::KeyEncodingVersion;
     use risingwave_hummock_sdk::HummockContextId;
     use risingwave_pb::hummock::compact_task::TaskStatus;
     use risingwave_pb::hummock::subscribe_compact_tasks_response::Task;
@@ -421,6 +422,8 @@ mod tests {
                     stale_key_count: 0,
                     total_key_count: 0,
                     divide_version: 0,
+                    table_stats: Default::default(),
+                    key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
                 }],
             }],
             splits: vec![],
@@ -432,12 +435,16 @@ mod tests {
             task_status: TaskStatus::Pending as i32,
             compaction_group_id: StaticCompactionGroupId::StateDefault.into(),
             existing_table_ids: vec![],
+            split_table_ids: vec![],
             compression_algorithm: 0,
             target_file_size: 1,
             compaction_filter_mask: 0,
             table_options: HashMap::default(),
             current_epoch_time: 0,
             target_sub_level_id: 0,
+            input_file_size,
+            estimated_memory_usage: 0,
+            slow_task_threshold_ms: 0,
         }
     }
 
@@ -12,9 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
 use risingwave_hummock_sdk::compact::compact_task_to_string;
@@ -27,6 +27,9 @@ use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot::Receiver;
 use tokio::sync::Notify;
 
+use super::compaction_group_limiter::{
+    compact_task_input_bytes, CompactionGroupLimiter, GroupScheduleLimit,
+};
 use super::Compactor;
 use crate::hummock::error::Error;
 use crate::hummock::{CompactorManagerRef, HummockManagerRef};
@@ -36,6 +39,9 @@ use crate::storage::MetaStore;
 pub type CompactionSchedulerRef<S> = Arc<CompactionScheduler<S>>;
 pub type CompactionRequestChannelRef = Arc<CompactionRequestChannel>;
 
+/// How often pending compaction groups throttled by their scheduling limits are retried.
+const GROUP_THROTTLE_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 /// [`CompactionRequestChannel`] wrappers a mpsc channel and deduplicate requests from same
 /// compaction groups.
 pub struct CompactionRequestChannel {
@@ -93,6 +99,7 @@ where
     hummock_manager: HummockManagerRef<S>,
     compactor_manager: CompactorManagerRef,
     compaction_resume_notifier: Arc<Notify>,
+    group_limiter: Mutex<CompactionGroupLimiter>,
 }
 
 impl<S> CompactionScheduler<S>
@@ -109,6 +116,7 @@ where
             hummock_manager,
             compactor_manager,
             compaction_resume_notifier: Arc::new(Notify::new()),
+            group_limiter: Mutex::new(CompactionGroupLimiter::default()),
         }
     }
 
@@ -126,11 +134,19 @@ where
             self.env.opts.periodic_compaction_interval_sec,
         ));
         min_trigger_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut throttle_retry_interval = tokio::time::interval(GROUP_THROTTLE_RETRY_INTERVAL);
+        throttle_retry_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // Compaction groups that are requested but not yet picked. They stay scheduled in
+        // `sched_channel` until picked, so that requests are still deduplicated.
+        let mut pending_groups: BTreeSet<CompactionGroupId> = BTreeSet::new();
         loop {
-            let compaction_group: CompactionGroupId = tokio::select! {
+            let has_pending_groups = !pending_groups.is_empty();
+            tokio::select! {
                 compaction_group = sched_rx.recv() => {
                     match compaction_group {
-                        Some(compaction_group) => compaction_group,
+                        Some(compaction_group) => {
+                            pending_groups.insert(compaction_group);
+                        }
                         None => {
                             tracing::warn!("Compactor Scheduler: The Hummock manager has dropped the connection,
                                 it means it has either died or started a new session. Exiting.");
@@ -140,21 +156,27 @@ where
                 },
                 _ = min_trigger_interval.tick() => {
                     // Periodically trigger compaction for all compaction groups.
-                    for cg_id in self.hummock_manager.compaction_group_manager().compaction_group_ids().await {
-                        if let Err(e) = sched_channel.try_sched_compaction(cg_id) {
+                    let cg_ids = self.hummock_manager.compaction_group_manager().compaction_group_ids().await;
+                    for cg_id in &cg_ids {
+                        if let Err(e) = sched_channel.try_sched_compaction(*cg_id) {
                             tracing::warn!("Failed to schedule compaction for compaction group {}. {}", cg_id, e);
                         }
                     }
+                    self.group_limiter.lock().retain_groups(&cg_ids);
                     continue;
                 },
+                // Retry pending groups that were throttled by their scheduling limits.
+                _ = throttle_retry_interval.tick(), if has_pending_groups => {},
                 // Shutdown compactor scheduler
                 _ = &mut shutdown_rx => {
                     break;
                 }
             };
+            while let Ok(compaction_group) = sched_rx.try_recv() {
+                pending_groups.insert(compaction_group);
+            }
 
             sync_point::sync_point!("BEFORE_SCHEDULE_COMPACTION_TASK");
-            sched_channel.unschedule(compaction_group);
 
             // Wait for a compactor to become available.
             let compactor = loop {
@@ -171,12 +193,53 @@ where
                 }
             };
 
+            // Pick the next compaction group within its scheduling limits.
+            let compaction_group = match self.pick_next_group(&pending_groups).await {
+                Some(compaction_group) => compaction_group,
+                None => {
+                    tracing::debug!(
+                        "All pending compaction groups {:?} reach their scheduling limits.",
+                        pending_groups
+                    );
+                    continue;
+                }
+            };
+            pending_groups.remove(&compaction_group);
+            sched_channel.unschedule(compaction_group);
+
             // Pick a task and assign it to this compactor.
             self.pick_and_assign(compaction_group, compactor, sched_channel.clone())
                 .await;
         }
     }
 
+    async fn pick_next_group(
+        &self,
+        pending_groups: &BTreeSet<CompactionGroupId>,
+    ) -> Option<CompactionGroupId> {
+        let compaction_group_manager = self.hummock_manager.compaction_group_manager();
+        let mut limits = HashMap::with_capacity(pending_groups.len());
+        for group_id in pending_groups {
+            if let Some(group) = compaction_group_manager.compaction_group(*group_id).await {
+                limits.insert(
+                    *group_id,
+                    GroupScheduleLimit::from(&group.compaction_config()),
+                );
+            }
+        }
+        let running_task_num = self
+            .hummock_manager
+            .assigned_compact_task_num_by_group()
+            .await;
+        self.group_limiter.lock().pick_next(
+            pending_groups,
+            &limits,
+            &running_task_num,
+            self.compactor_manager.max_concurrent_task_number() as u64,
+            Instant::now(),
+        )
+    }
+
     /// Tries to pick a compaction task, schedule it to a compactor.
     ///
     /// Returns true if a task is successfully picked and sent.
@@ -230,10 +293,11 @@ where
         compactor: Arc<Compactor>,
         sched_channel: Arc<CompactionRequestChannel>,
     ) -> ScheduleStatus {
-        // 1. Pick a compaction task.
+        // 1. Pick a compaction task that fits in the memory of the compactor.
+        let memory_budget = self.compactor_manager.memory_budget(compactor.context_id());
         let compact_task = self
             .hummock_manager
-            .get_compact_task(compaction_group)
+            .get_compact_task_with_memory_budget(compaction_group, memory_budget)
             .await;
         let compact_task = match compact_task {
             Ok(Some(compact_task)) => compact_task,
@@ -280,10 +344,14 @@ where
         };
 
         // 3. Send the compaction task.
-        if let Err(e) = compactor
-            .send_task(Task::CompactTask(compact_task.clone()))
+        let mut task_to_send = compact_task.clone();
+        task_to_send.slow_task_threshold_ms = self
+            .env
+            .system_params_manager()
+            .get_params()
             .await
-        {
+            .slow_compaction_threshold_ms;
+        if let Err(e) = compactor.send_task(Task::CompactTask(task_to_send)).await {
             tracing::warn!(
                 "Failed to send task {} to {}. {:#?}",
                 compact_task.task_id,
@@ -294,6 +362,19 @@ where
                 .pause_compactor(compactor.context_id());
             return ScheduleStatus::SendFailure(compact_task);
         }
+        if let Some(group) = self
+            .hummock_manager
+            .compaction_group_manager()
+            .compaction_group(compaction_group)
+            .await
+        {
+            self.group_limiter.lock().on_task_scheduled(
+                compaction_group,
+                &GroupScheduleLimit::from(&group.compaction_config()),
+                compact_task_input_bytes(&compact_task),
+                Instant::now(),
+            );
+        }
 
         // Bypass reschedule if we want compaction scheduling in a deterministic way
         if self.env.opts.compaction_deterministic_test {
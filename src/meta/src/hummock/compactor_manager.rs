@@ -98,6 +98,19 @@ impl Compactor {
     }
 }
 
+/// Block size of SSTs assumed for the compactors not reporting theirs, which is the default
+/// `block_size_kb` of compactors.
+pub const DEFAULT_COMPACTOR_BLOCK_SIZE: u64 = 1 << 20;
+
+/// Memory available for compaction tasks on a compactor, reported when subscribing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactorMemoryBudget {
+    pub memory_limit: u64,
+    /// Block size of the SSTs read and built by the compactor, which the memory usage of the
+    /// tasks is estimated with.
+    pub block_size: u64,
+}
+
 /// `CompactorManager` maintains compactors which can process compact task.
 /// A compact task is tracked in `HummockManager::Compaction` via both `CompactStatus` and
 /// `CompactTaskAssignment`.
@@ -122,6 +135,9 @@ pub struct CompactorManager {
     // A map: { context_id -> { task_id -> heartbeat } }
     task_heartbeats:
         RwLock<HashMap<HummockContextId, HashMap<HummockCompactionTaskId, TaskHeartbeat>>>,
+
+    /// Memory available for compaction tasks on each compactor, reported when subscribing.
+    memory_budgets: RwLock<HashMap<HummockContextId, CompactorMemoryBudget>>,
 }
 
 impl CompactorManager {
@@ -137,6 +153,7 @@ impl CompactorManager {
             ))),
             task_expiry_seconds,
             task_heartbeats: Default::default(),
+            memory_budgets: Default::default(),
         };
         // Initialize heartbeat for existing tasks.
         task_assignment.into_iter().for_each(|assignment| {
@@ -152,6 +169,7 @@ impl CompactorManager {
             policy: RwLock::new(Box::new(RoundRobinPolicy::new())),
             task_expiry_seconds: 1,
             task_heartbeats: Default::default(),
+            memory_budgets: Default::default(),
         }
     }
 
@@ -161,6 +179,7 @@ impl CompactorManager {
             policy: RwLock::new(policy),
             task_expiry_seconds: 1,
             task_heartbeats: Default::default(),
+            memory_budgets: Default::default(),
         }
     }
 
@@ -206,12 +225,44 @@ impl CompactorManager {
     pub fn remove_compactor(&self, context_id: HummockContextId) {
         let mut policy = self.policy.write();
         policy.remove_compactor(context_id);
+        self.memory_budgets.write().remove(&context_id);
 
         // To remove the heartbeats, they need to be forcefully purged,
         // which is only safe when the context has been completely removed from meta.
         tracing::info!("Removed compactor session {}", context_id);
     }
 
+    /// Records the memory budget of the compactor. A zero budget means unknown, and a zero block
+    /// size means the compactor uses the default one.
+    pub fn set_memory_budget(
+        &self,
+        context_id: HummockContextId,
+        memory_limit: u64,
+        block_size: u64,
+    ) {
+        let mut memory_budgets = self.memory_budgets.write();
+        if memory_limit == 0 {
+            memory_budgets.remove(&context_id);
+        } else {
+            let block_size = if block_size == 0 {
+                DEFAULT_COMPACTOR_BLOCK_SIZE
+            } else {
+                block_size
+            };
+            memory_budgets.insert(
+                context_id,
+                CompactorMemoryBudget {
+                    memory_limit,
+                    block_size,
+                },
+            );
+        }
+    }
+
+    pub fn memory_budget(&self, context_id: HummockContextId) -> Option<CompactorMemoryBudget> {
+        self.memory_budgets.read().get(&context_id).copied()
+    }
+
     pub fn get_compactor(&self, context_id: HummockContextId) -> Option<Arc<Compactor>> {
         self.policy.read().get_compactor(context_id)
     }
@@ -37,6 +37,8 @@ pub enum Error {
     InvalidCompactionGroupMember(StateTableId),
     #[error("SST {0} is invalid")]
     InvalidSst(HummockSstableId),
+    #[error("snapshot pinned by hummock context {0} has been released for its expired lease")]
+    SnapshotPinExpired(HummockContextId),
     #[error(transparent)]
     Internal(anyhow::Error),
 }
@@ -16,13 +16,18 @@ use std::collections::BTreeMap;
 
 use function_name::named;
 use itertools::Itertools;
+use risingwave_hummock_sdk::compact::{
+    compact_task_input_size, estimate_compaction_memory, estimate_compaction_parallelism,
+    CompactionMemoryEstimateOptions,
+};
 use risingwave_hummock_sdk::{CompactionGroupId, HummockCompactionTaskId, HummockContextId};
-use risingwave_pb::hummock::{CompactTaskAssignment, CompactionConfig};
+use risingwave_pb::hummock::hummock_version::Levels;
+use risingwave_pb::hummock::{CompactTask, CompactTaskAssignment, CompactionConfig};
 
 use crate::hummock::compaction::CompactStatus;
 use crate::hummock::error::Result;
 use crate::hummock::manager::read_lock;
-use crate::hummock::HummockManager;
+use crate::hummock::{CompactorMemoryBudget, HummockManager};
 use crate::model::BTreeMapTransaction;
 use crate::storage::MetaStore;
 
@@ -88,6 +93,68 @@ impl Compaction {
     }
 }
 
+/// Estimates the peak memory usage of `compact_task` on a compactor with SST blocks of
+/// `block_size`, assuming the compactor splits it into sub-compactions of the target file size,
+/// but no more than `max_sub_compaction`.
+pub fn estimate_compact_task_memory(
+    compact_task: &CompactTask,
+    compaction_config: &CompactionConfig,
+    block_size: u64,
+) -> u64 {
+    let parallelism = estimate_compaction_parallelism(
+        compact_task_input_size(compact_task),
+        compact_task.target_file_size,
+        compaction_config.max_sub_compaction,
+    );
+    estimate_compaction_memory(
+        compact_task,
+        &CompactionMemoryEstimateOptions {
+            block_size,
+            max_target_file_size: u64::MAX,
+            parallelism,
+        },
+    )
+}
+
+/// Halves the max input size of `compaction_config` until the task picked from `compact_status`
+/// is estimated to fit in `memory_budget`, or the max input size reaches the target file size.
+/// `compact_status` is left untouched.
+pub fn limit_compaction_config_by_memory(
+    compact_status: &CompactStatus,
+    levels: &Levels,
+    task_id: HummockCompactionTaskId,
+    mut compaction_config: CompactionConfig,
+    memory_budget: CompactorMemoryBudget,
+) -> CompactionConfig {
+    loop {
+        let mut trial_status = compact_status.clone();
+        let task = match trial_status.get_compact_task(
+            levels,
+            task_id,
+            compact_status.compaction_group_id(),
+            None,
+            compaction_config.clone(),
+        ) {
+            Some(task) => task,
+            None => return compaction_config,
+        };
+        if estimate_compact_task_memory(&task, &compaction_config, memory_budget.block_size)
+            <= memory_budget.memory_limit
+            || compaction_config.max_compaction_bytes <= compaction_config.target_file_size_base
+        {
+            return compaction_config;
+        }
+        compaction_config.max_compaction_bytes = std::cmp::max(
+            compaction_config.max_compaction_bytes / 2,
+            compaction_config.target_file_size_base,
+        );
+        compaction_config.sub_level_max_compaction_bytes = std::cmp::min(
+            compaction_config.sub_level_max_compaction_bytes,
+            compaction_config.max_compaction_bytes,
+        );
+    }
+}
+
 impl<S> HummockManager<S>
 where
     S: MetaStore,
@@ -139,10 +206,19 @@ where
 #[cfg(test)]
 mod tests {
     use risingwave_hummock_sdk::CompactionGroupId;
-    use risingwave_pb::hummock::{CompactTask, CompactTaskAssignment, InputLevel, SstableInfo};
+    use risingwave_pb::hummock::hummock_version::Levels;
+    use risingwave_pb::hummock::{
+        CompactTask, CompactTaskAssignment, InputLevel, KeyRange, Level, LevelType,
+        OverlappingLevel, SstableInfo,
+    };
 
+    use crate::hummock::compaction::compaction_config::CompactionConfigBuilder;
     use crate::hummock::compaction::CompactStatus;
-    use crate::hummock::manager::compaction::Compaction;
+    use crate::hummock::manager::compaction::{
+        estimate_compact_task_memory, limit_compaction_config_by_memory, Compaction,
+    };
+    use crate::hummock::test_utils::iterator_test_key_of_epoch;
+    use crate::hummock::CompactorMemoryBudget;
 
     #[tokio::test]
     async fn test_cancel_assigned_tasks_for_context_ids() {
@@ -221,4 +297,87 @@ mod tests {
         );
         assert_eq!(compaction.compact_task_assignment.len(), 0);
     }
+
+    #[test]
+    fn test_limit_compaction_config_by_memory() {
+        let group_id = 0 as CompactionGroupId;
+        // Eight non-overlapping L0 sub-levels, each holding one SST of 1000 bytes.
+        let sub_levels = (1..9)
+            .map(|id| Level {
+                level_idx: 0,
+                level_type: LevelType::Nonoverlapping as i32,
+                table_infos: vec![SstableInfo {
+                    id,
+                    key_range: Some(KeyRange {
+                        left: iterator_test_key_of_epoch(1, 0, 1),
+                        right: iterator_test_key_of_epoch(1, 100, 1),
+                    }),
+                    file_size: 1000,
+                    ..Default::default()
+                }],
+                total_file_size: 1000,
+                sub_level_id: id,
+            })
+            .collect();
+        let levels = Levels {
+            l0: Some(OverlappingLevel {
+                sub_levels,
+                total_file_size: 8000,
+            }),
+            levels: vec![Level {
+                level_idx: 1,
+                level_type: LevelType::Nonoverlapping as i32,
+                ..Default::default()
+            }],
+        };
+        let config = CompactionConfigBuilder::new()
+            .max_level(1)
+            .level0_trigger_file_number(1)
+            .level0_tier_compact_file_number(1)
+            .target_file_size_base(100)
+            .max_compaction_bytes(100000)
+            .build();
+        let compact_status = CompactStatus::new(group_id, config.max_level);
+        let task = compact_status
+            .clone()
+            .get_compact_task(&levels, 1, group_id, None, config.clone())
+            .unwrap();
+        let block_size = 64;
+        let memory_usage = estimate_compact_task_memory(&task, &config, block_size);
+        // Larger blocks take more memory to read and build the SSTs.
+        assert!(estimate_compact_task_memory(&task, &config, 2 * block_size) > memory_usage);
+
+        // A task within the limit keeps the config as is.
+        let limited = limit_compaction_config_by_memory(
+            &compact_status,
+            &levels,
+            1,
+            config.clone(),
+            CompactorMemoryBudget {
+                memory_limit: memory_usage,
+                block_size,
+            },
+        );
+        assert_eq!(limited.max_compaction_bytes, config.max_compaction_bytes);
+
+        // An oversized task shrinks the max input size down to the target file size at most.
+        let limited = limit_compaction_config_by_memory(
+            &compact_status,
+            &levels,
+            1,
+            config.clone(),
+            CompactorMemoryBudget {
+                memory_limit: 0,
+                block_size,
+            },
+        );
+        assert_eq!(limited.max_compaction_bytes, config.target_file_size_base);
+        assert!(limited.sub_level_max_compaction_bytes <= limited.max_compaction_bytes);
+
+        // The trial picks don't leave pending tasks behind.
+        assert!(compact_status
+            .level_handlers
+            .iter()
+            .all(|handler| handler.get_pending_file_count() == 0));
+    }
 }
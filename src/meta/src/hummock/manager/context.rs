@@ -14,19 +14,33 @@
 
 use std::collections::HashSet;
 use std::ops::DerefMut;
+use std::time::{Duration, Instant};
 
 use fail::fail_point;
 use function_name::named;
+use itertools::Itertools;
 use risingwave_hummock_sdk::HummockContextId;
+use risingwave_pb::common::WorkerType;
 
 use crate::hummock::error::{Error, Result};
 use crate::hummock::manager::{
     commit_multi_var, read_lock, start_measure_real_process_timer, write_lock,
 };
+use crate::hummock::metrics_utils::trigger_pin_unpin_snapshot_state;
 use crate::hummock::HummockManager;
 use crate::model::{BTreeMapTransaction, ValTransaction};
 use crate::storage::{MetaStore, Transaction};
 
+/// The lease of the snapshot pinned by a frontend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum SnapshotPinLease {
+    /// Last renewed at the instant.
+    Renewed(Instant),
+    /// Not renewed in time, so the pinned snapshot has been released. The frontend can't advance
+    /// its pin with `unpin_snapshot_before` until it pins a snapshot again.
+    Expired,
+}
+
 impl<S> HummockManager<S>
 where
     S: MetaStore,
@@ -62,6 +76,12 @@ where
             pinned_versions.remove(*context_id);
             pinned_snapshots.remove(*context_id);
         }
+        {
+            let mut snapshot_pin_leases = self.snapshot_pin_leases.lock();
+            for context_id in context_ids.as_ref() {
+                snapshot_pin_leases.remove(context_id);
+            }
+        }
         commit_multi_var!(
             self,
             None,
@@ -119,4 +139,92 @@ where
 
         Ok(invalid_context_ids)
     }
+
+    /// Renews the lease of the snapshot pinned by `context_id`. Once renewed, the pin is released
+    /// by [`Self::release_expired_snapshot_pins`] unless renewed again in time.
+    pub fn renew_snapshot_pin_lease(&self, context_id: HummockContextId) {
+        self.snapshot_pin_leases
+            .lock()
+            .insert(context_id, SnapshotPinLease::Renewed(Instant::now()));
+    }
+
+    /// Starts the leases of the snapshots pinned by the frontends on restarting, as the leases are
+    /// only kept in memory. Otherwise the pin of a frontend that stopped renewing while meta was
+    /// down would never be released.
+    #[named]
+    pub(super) async fn init_snapshot_pin_leases(&self) {
+        let frontend_ids: HashSet<_> = self
+            .cluster_manager
+            .list_worker_node(WorkerType::Frontend, None)
+            .await
+            .into_iter()
+            .map(|worker| worker.id)
+            .collect();
+        let versioning_guard = read_lock!(self, versioning).await;
+        let now = Instant::now();
+        let mut snapshot_pin_leases = self.snapshot_pin_leases.lock();
+        for context_id in versioning_guard.pinned_snapshots.keys() {
+            if frontend_ids.contains(context_id) {
+                snapshot_pin_leases.insert(*context_id, SnapshotPinLease::Renewed(now));
+            }
+        }
+    }
+
+    /// Releases the snapshots pinned by the contexts which haven't renewed their lease for
+    /// `snapshot_pin_lease_sec` as of `now`, e.g. frontends that crashed or hung. Returns the
+    /// released contexts.
+    #[named]
+    pub async fn release_expired_snapshot_pins(
+        &self,
+        now: Instant,
+    ) -> Result<Vec<HummockContextId>> {
+        let lease = Duration::from_secs(self.env.opts.snapshot_pin_lease_sec);
+        if lease.is_zero() {
+            return Ok(vec![]);
+        }
+        let mut versioning_guard = write_lock!(self, versioning).await;
+        let expired_context_ids = {
+            let mut snapshot_pin_leases = self.snapshot_pin_leases.lock();
+            let expired_context_ids = snapshot_pin_leases
+                .iter()
+                .filter(|(_, lease_state)| match lease_state {
+                    SnapshotPinLease::Renewed(renewed_at) => {
+                        now.saturating_duration_since(*renewed_at) > lease
+                    }
+                    SnapshotPinLease::Expired => false,
+                })
+                .map(|(context_id, _)| *context_id)
+                .collect_vec();
+            for context_id in &expired_context_ids {
+                snapshot_pin_leases.insert(*context_id, SnapshotPinLease::Expired);
+            }
+            expired_context_ids
+        };
+        if expired_context_ids.is_empty() {
+            return Ok(expired_context_ids);
+        }
+
+        let versioning = versioning_guard.deref_mut();
+        let mut pinned_snapshots = BTreeMapTransaction::new(&mut versioning.pinned_snapshots);
+        for context_id in &expired_context_ids {
+            pinned_snapshots.remove(*context_id);
+        }
+        commit_multi_var!(self, None, pinned_snapshots)?;
+        trigger_pin_unpin_snapshot_state(&self.metrics, &versioning.pinned_snapshots);
+        self.metrics
+            .expired_snapshot_pin_count
+            .inc_by(expired_context_ids.len() as u64);
+        tracing::info!(
+            "Released the snapshots pinned by {:?} for the expired lease",
+            expired_context_ids
+        );
+
+        #[cfg(test)]
+        {
+            drop(versioning_guard);
+            self.check_state_consistency().await;
+        }
+
+        Ok(expired_context_ids)
+    }
 }
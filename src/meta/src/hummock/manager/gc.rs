@@ -19,7 +19,7 @@ use std::ops::DerefMut;
 use function_name::named;
 use itertools::Itertools;
 use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
-use risingwave_hummock_sdk::{HummockSstableId, HummockVersionId, INVALID_VERSION_ID};
+use risingwave_hummock_sdk::{HummockSstableId, INVALID_VERSION_ID};
 
 use crate::hummock::error::Result;
 use crate::hummock::manager::{commit_multi_var, read_lock, write_lock};
@@ -32,6 +32,8 @@ where
     S: MetaStore,
 {
     /// Gets SSTs that is safe to be deleted from object store.
+    ///
+    /// SSTs referenced by meta backups are never included, see `pin_backup_ssts`.
     #[named]
     pub async fn get_ssts_to_delete(&self) -> Vec<HummockSstableId> {
         read_lock!(self, versioning)
@@ -47,18 +49,9 @@ where
     /// Possibly extends deltas_to_delete.
     #[named]
     pub async fn ack_deleted_ssts(&self, sst_ids: &[HummockSstableId]) -> Result<()> {
-        let mut deltas_to_delete = HashSet::new();
-        let mut versioning_guard = write_lock!(self, versioning).await;
-        for sst_id in sst_ids {
-            if let Some(version_id) = versioning_guard.ssts_to_delete.remove(sst_id) && version_id != INVALID_VERSION_ID{
-                // Orphan SST is mapped to INVALID_VERSION_ID
-                deltas_to_delete.insert(version_id);
-            }
-        }
-        let remain_deltas: HashSet<HummockVersionId> =
-            HashSet::from_iter(versioning_guard.ssts_to_delete.values().cloned());
-        deltas_to_delete.retain(|id| !remain_deltas.contains(id));
-        versioning_guard.deltas_to_delete.extend(deltas_to_delete);
+        write_lock!(self, versioning)
+            .await
+            .untrack_ssts_to_delete(sst_ids);
         Ok(())
     }
 
@@ -102,6 +95,8 @@ where
             for delta in versioning_guard.hummock_version_deltas.values() {
                 tracked_sst_ids.extend(delta.get_gc_sst_ids());
             }
+            // SSTs referenced by meta backups are never deleted.
+            tracked_sst_ids.extend(versioning_guard.backup_pinned_ssts.iter().cloned());
             tracked_sst_ids
         };
         let to_delete = sst_ids
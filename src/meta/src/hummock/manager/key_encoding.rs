@@ -0,0 +1,183 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use function_name::named;
+use itertools::Itertools;
+use risingwave_hummock_sdk::key::KeyEncodingVersion;
+use risingwave_hummock_sdk::{CompactionGroupId, HummockSstableId};
+use tokio::sync::oneshot::Sender;
+use tokio::task::JoinHandle;
+
+use crate::hummock::compaction::ManualCompactionOption;
+use crate::hummock::manager::read_lock;
+use crate::hummock::HummockManager;
+use crate::manager::CatalogManagerRef;
+use crate::storage::MetaStore;
+use crate::MetaResult;
+
+/// The interval to schedule the rewrite of the SSTs in a legacy key encoding.
+const KEY_ENCODING_UPGRADE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The SSTs whose keys are in a legacy encoding in the current version.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LegacyKeyEncodingSsts {
+    /// The legacy SSTs in each level of each compaction group, where the level of the sub levels
+    /// of L0 is 0.
+    pub ssts: BTreeMap<(CompactionGroupId, usize), Vec<HummockSstableId>>,
+    /// The state tables with keys in the legacy SSTs.
+    pub table_ids: HashSet<u32>,
+}
+
+impl<S> HummockManager<S>
+where
+    S: MetaStore,
+{
+    #[named]
+    pub async fn get_legacy_key_encoding_ssts(&self) -> MetaResult<LegacyKeyEncodingSsts> {
+        let versioning_guard = read_lock!(self, versioning).await;
+        let mut legacy_ssts = LegacyKeyEncodingSsts::default();
+        for (&compaction_group_id, levels) in &versioning_guard.current_version.levels {
+            let l0 = levels
+                .l0
+                .iter()
+                .flat_map(|l0| l0.sub_levels.iter())
+                .map(|sub_level| (0, sub_level));
+            let levels = levels
+                .levels
+                .iter()
+                .map(|level| (level.level_idx as usize, level));
+            for (level_idx, level) in l0.chain(levels) {
+                for sst in &level.table_infos {
+                    let key_encoding = KeyEncodingVersion::from_u32(sst.key_encoding_version)
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "unknown key encoding version {} of SST {}",
+                                sst.key_encoding_version,
+                                sst.id
+                            )
+                        })?;
+                    if key_encoding == KeyEncodingVersion::LATEST {
+                        continue;
+                    }
+                    legacy_ssts
+                        .ssts
+                        .entry((compaction_group_id, level_idx))
+                        .or_default()
+                        .push(sst.id);
+                    legacy_ssts.table_ids.extend(sst.table_ids.iter().copied());
+                }
+            }
+        }
+        Ok(legacy_ssts)
+    }
+
+    /// Flips the key encoding version of the tables without keys in the legacy SSTs, and rewrites
+    /// the legacy SSTs in the lowest level of each compaction group through a manual compaction,
+    /// whose output SSTs are always in the latest encoding.
+    ///
+    /// A table never gets keys in a legacy encoding again once it has none, because the new SSTs,
+    /// either flushed or compacted, are all in the latest encoding. So the flip is never undone.
+    pub async fn upgrade_key_encoding(
+        &self,
+        catalog_manager: &CatalogManagerRef<S>,
+    ) -> MetaResult<()> {
+        let legacy_ssts = self.get_legacy_key_encoding_ssts().await?;
+
+        let upgraded_table_ids = catalog_manager
+            .list_legacy_key_encoding_tables()
+            .await?
+            .into_iter()
+            .filter(|table_id| !legacy_ssts.table_ids.contains(table_id))
+            .collect_vec();
+        if !upgraded_table_ids.is_empty() {
+            catalog_manager
+                .upgrade_table_key_encoding(&upgraded_table_ids)
+                .await?;
+            tracing::info!(
+                "Upgraded the key encoding of tables {:?}",
+                upgraded_table_ids
+            );
+        }
+
+        let mut scheduled_groups = HashSet::new();
+        for ((compaction_group_id, level), sst_ids) in legacy_ssts.ssts {
+            if !scheduled_groups.insert(compaction_group_id) {
+                continue;
+            }
+            let option = ManualCompactionOption {
+                sst_ids,
+                level,
+                ..Default::default()
+            };
+            // The SSTs are retried in the next round if they're being compacted, or if there's no
+            // idle compactor.
+            if let Err(err) = self
+                .trigger_manual_compaction(compaction_group_id, option)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to rewrite the legacy SSTs of compaction group {} in level {}. {:#?}",
+                    compaction_group_id,
+                    level,
+                    err
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts a task to periodically upgrade the key encoding, until no table is in a legacy
+    /// encoding. See [`Self::upgrade_key_encoding`].
+    pub async fn start_key_encoding_upgrader(
+        hummock_manager: Arc<Self>,
+        catalog_manager: CatalogManagerRef<S>,
+    ) -> (JoinHandle<()>, Sender<()>) {
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        let join_handle = tokio::spawn(async move {
+            let mut min_interval = tokio::time::interval(KEY_ENCODING_UPGRADE_INTERVAL);
+            min_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    // Wait for interval
+                    _ = min_interval.tick() => {},
+                    // Shutdown
+                    _ = &mut shutdown_rx => {
+                        tracing::info!("Key encoding upgrader is stopped");
+                        return;
+                    }
+                }
+                if let Err(err) = hummock_manager.upgrade_key_encoding(&catalog_manager).await {
+                    tracing::warn!("Failed to upgrade the key encoding. {:#?}", err);
+                    continue;
+                }
+                match catalog_manager.list_legacy_key_encoding_tables().await {
+                    Ok(table_ids) if table_ids.is_empty() => {
+                        tracing::info!("All tables are in the latest key encoding");
+                        return;
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        tracing::warn!("Failed to list the legacy key encoding tables. {:#?}", err)
+                    }
+                }
+            }
+        });
+        (join_handle, shutdown_tx)
+    }
+}
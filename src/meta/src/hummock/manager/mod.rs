@@ -13,11 +13,11 @@
 // limitations under the License.
 
 use std::borrow::{Borrow, BorrowMut};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::ops::Bound::{Excluded, Included};
 use std::ops::DerefMut;
 use std::sync::{Arc, LazyLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
 use fail::fail_point;
@@ -26,13 +26,15 @@ use itertools::Itertools;
 use prost::Message;
 use risingwave_common::monitor::rwlock::MonitoredRwLock;
 use risingwave_common::util::epoch::{Epoch, INVALID_EPOCH};
-use risingwave_hummock_sdk::compact::compact_task_to_string;
+use risingwave_hummock_sdk::compact::{compact_task_input_size, compact_task_to_string};
 use risingwave_hummock_sdk::compaction_group::hummock_version_ext::{
-    add_new_sub_level, HummockLevelsExt, HummockVersionExt,
+    add_new_sub_level, upgrade_legacy_key_range, HummockLevelsExt, HummockVersionDeltaExt,
+    HummockVersionExt,
 };
 use risingwave_hummock_sdk::{
-    CompactionGroupId, HummockCompactionTaskId, HummockContextId, HummockEpoch, HummockSstableId,
-    HummockVersionId, LocalSstableInfo, SstIdRange, FIRST_VERSION_ID, INVALID_VERSION_ID,
+    add_table_stats, CompactionGroupId, HummockCompactionTaskId, HummockContextId, HummockEpoch,
+    HummockSstableId, HummockVersionId, LocalSstableInfo, SstIdRange, FIRST_VERSION_ID,
+    INVALID_VERSION_ID,
 };
 use risingwave_pb::hummock::compact_task::TaskStatus;
 use risingwave_pb::hummock::group_delta::DeltaType;
@@ -41,7 +43,8 @@ use risingwave_pb::hummock::subscribe_compact_tasks_response::Task;
 use risingwave_pb::hummock::{
     pin_version_response, CompactTask, CompactTaskAssignment, GroupConstruct, GroupDelta,
     GroupDestroy, HummockPinnedSnapshot, HummockPinnedVersion, HummockSnapshot, HummockVersion,
-    HummockVersionDelta, HummockVersionDeltas, IntraLevelDelta, LevelType, ValidationTask,
+    HummockVersionDelta, HummockVersionDeltas, IntraLevelDelta, LevelType, TableStats,
+    ValidationTask,
 };
 use risingwave_pb::meta::subscribe_response::{Info, Operation};
 use risingwave_pb::meta::MetaLeaderInfo;
@@ -56,9 +59,9 @@ use crate::hummock::compaction_scheduler::CompactionRequestChannelRef;
 use crate::hummock::error::{Error, Result};
 use crate::hummock::metrics_utils::{
     trigger_pin_unpin_snapshot_state, trigger_pin_unpin_version_state, trigger_sst_stat,
-    trigger_version_stat,
+    trigger_table_write_stat, trigger_version_stat,
 };
-use crate::hummock::CompactorManagerRef;
+use crate::hummock::{CompactorManagerRef, CompactorMemoryBudget, DEFAULT_COMPACTOR_BLOCK_SIZE};
 use crate::manager::{ClusterManagerRef, IdCategory, LocalNotification, MetaSrvEnv, META_NODE_ID};
 use crate::model::{
     BTreeMapEntryTransaction, BTreeMapTransaction, MetadataModel, ValTransaction, VarTransaction,
@@ -68,7 +71,10 @@ use crate::rpc::{META_CF_NAME, META_LEADER_KEY};
 use crate::storage::{MetaStore, Transaction};
 
 mod context;
+use context::SnapshotPinLease;
 mod gc;
+mod key_encoding;
+pub use key_encoding::*;
 #[cfg(test)]
 mod tests;
 mod versioning;
@@ -101,6 +107,21 @@ pub struct HummockManager<S: MetaStore> {
     compaction_resume_notifier: parking_lot::RwLock<Option<Arc<Notify>>>,
 
     compactor_manager: CompactorManagerRef,
+
+    /// Cumulative write stats of each table since the meta node starts.
+    table_write_stats: parking_lot::RwLock<HashMap<u32, TableStats>>,
+
+    /// Bytes written to each table recently, which unlike the cumulative stats reflect how busy
+    /// the table is now.
+    recent_table_write_bytes: parking_lot::RwLock<RecentTableWriteBytes>,
+
+    /// Max input size of the tasks of each compaction group, lowered after compactors reject the
+    /// tasks for exceeding their memory budget, and raised back as the tasks succeed.
+    max_compaction_bytes_overrides: parking_lot::RwLock<HashMap<CompactionGroupId, u64>>,
+
+    /// The last time each frontend renewed the lease of its pinned snapshot. Contexts absent here
+    /// are not subject to the lease.
+    snapshot_pin_leases: parking_lot::Mutex<HashMap<HummockContextId, SnapshotPinLease>>,
 }
 
 pub type HummockManagerRef<S> = Arc<HummockManager<S>>;
@@ -189,6 +210,45 @@ static CANCEL_STATUS_SET: LazyLock<HashSet<TaskStatus>> = LazyLock::new(|| {
     .collect()
 });
 
+/// The window of [`RecentTableWriteBytes`].
+const TABLE_WRITE_BYTES_WINDOW: Duration = Duration::from_secs(600);
+
+/// The bytes written to each table in the epochs committed in the last
+/// [`TABLE_WRITE_BYTES_WINDOW`].
+#[derive(Default)]
+struct RecentTableWriteBytes {
+    epochs: VecDeque<(Instant, HashMap<u32, u64>)>,
+}
+
+impl RecentTableWriteBytes {
+    fn record(&mut self, now: Instant, table_write_stats: &HashMap<u32, TableStats>) {
+        self.expire(now);
+        let bytes = table_write_stats
+            .iter()
+            .map(|(table_id, stats)| (*table_id, stats.total_bytes))
+            .collect();
+        self.epochs.push_back((now, bytes));
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((committed_at, _)) = self.epochs.front()
+            && now.saturating_duration_since(*committed_at) > TABLE_WRITE_BYTES_WINDOW
+        {
+            self.epochs.pop_front();
+        }
+    }
+
+    fn get(&self, now: Instant, table_id: u32) -> u64 {
+        self.epochs
+            .iter()
+            .filter(|(committed_at, _)| {
+                now.saturating_duration_since(*committed_at) <= TABLE_WRITE_BYTES_WINDOW
+            })
+            .filter_map(|(_, bytes)| bytes.get(&table_id))
+            .sum()
+    }
+}
+
 #[derive(Debug)]
 pub enum CompactionResumeTrigger {
     /// The addition (re-subscription) of compactors
@@ -228,10 +288,15 @@ where
                 committed_epoch: INVALID_EPOCH,
                 current_epoch: INVALID_EPOCH,
             }),
+            table_write_stats: parking_lot::RwLock::new(HashMap::new()),
+            recent_table_write_bytes: parking_lot::RwLock::new(RecentTableWriteBytes::default()),
+            max_compaction_bytes_overrides: parking_lot::RwLock::new(HashMap::new()),
+            snapshot_pin_leases: parking_lot::Mutex::new(HashMap::new()),
         };
 
         instance.load_meta_store_state().await?;
         instance.release_invalid_contexts().await?;
+        instance.init_snapshot_pin_leases().await;
         instance.cancel_unassigned_compaction_task().await?;
         // Release snapshots pinned by meta on restarting.
         instance.release_contexts([META_NODE_ID]).await?;
@@ -305,11 +370,23 @@ where
         if !compaction_statuses.is_empty() {
             compaction_guard.compaction_statuses = compaction_statuses;
         }
+        // The key ranges of the SSTs added before the key encoding is versioned are upgraded on
+        // loading, so that all the key ranges compared by the meta node and served to the workers
+        // are in the latest encoding.
         compaction_guard.compact_task_assignment =
             CompactTaskAssignment::list(self.env.meta_store())
                 .await?
                 .into_iter()
-                .map(|assigned| (assigned.key().unwrap(), assigned))
+                .map(|mut assigned| {
+                    if let Some(compact_task) = assigned.compact_task.as_mut() {
+                        compact_task
+                            .input_ssts
+                            .iter_mut()
+                            .flat_map(|input_level| input_level.table_infos.iter_mut())
+                            .for_each(upgrade_legacy_key_range);
+                    }
+                    (assigned.key().unwrap(), assigned)
+                })
                 .collect();
 
         let versions = HummockVersion::list(self.env.meta_store()).await?;
@@ -318,7 +395,10 @@ where
             HummockVersionDelta::list(self.env.meta_store())
                 .await?
                 .into_iter()
-                .map(|version_delta| (version_delta.id, version_delta))
+                .map(|mut version_delta| {
+                    version_delta.upgrade_legacy_key_ranges();
+                    (version_delta.id, version_delta)
+                })
                 .collect();
 
         // Insert the initial version.
@@ -332,7 +412,9 @@ where
             init_version.insert(self.env.meta_store()).await?;
             init_version
         } else {
-            versions.first().unwrap().clone()
+            let mut checkpoint_version = versions.first().unwrap().clone();
+            checkpoint_version.upgrade_legacy_key_ranges();
+            checkpoint_version
         };
         versioning_guard.checkpoint_version = redo_state.clone();
 
@@ -522,6 +604,9 @@ where
             commit_multi_var!(self, Some(context_id), context_pinned_snapshot)?;
             trigger_pin_unpin_snapshot_state(&self.metrics, &guard.pinned_snapshots);
         }
+        if let Some(lease) = self.snapshot_pin_leases.lock().get_mut(&context_id) {
+            *lease = SnapshotPinLease::Renewed(Instant::now());
+        }
         Ok(HummockSnapshot::clone(&snapshot))
     }
 
@@ -536,6 +621,7 @@ where
         let _timer = start_measure_real_process_timer!(self);
         let mut pinned_snapshots = BTreeMapTransaction::new(&mut versioning_guard.pinned_snapshots);
         let release_snapshot = pinned_snapshots.remove(context_id);
+        self.snapshot_pin_leases.lock().remove(&context_id);
         if release_snapshot.is_some() {
             commit_multi_var!(self, Some(context_id), pinned_snapshots)?;
             trigger_pin_unpin_snapshot_state(&self.metrics, &versioning_guard.pinned_snapshots);
@@ -569,6 +655,11 @@ where
             assert!(snapshot_committed_epoch <= max_committed_epoch);
         }
         let last_read_epoch = std::cmp::min(snapshot_committed_epoch, max_committed_epoch);
+        // The epochs the frontend is still reading may have been vacuumed since its pin expired,
+        // so its pin must not be silently recreated here.
+        if self.snapshot_pin_leases.lock().get(&context_id) == Some(&SnapshotPinLease::Expired) {
+            return Err(Error::SnapshotPinExpired(context_id));
+        }
 
         let mut pinned_snapshots = BTreeMapTransaction::new(&mut versioning_guard.pinned_snapshots);
         let mut context_pinned_snapshot = pinned_snapshots.new_entry_txn_or_default(
@@ -598,11 +689,14 @@ where
         Ok(())
     }
 
+    /// Picks a compaction task of `compaction_group_id`. If `memory_budget` is given, the input of
+    /// the task is shrunk to fit in the memory of the compactor if possible.
     #[named]
     pub async fn get_compact_task_impl(
         &self,
         compaction_group_id: CompactionGroupId,
         manual_compaction_option: Option<ManualCompactionOption>,
+        memory_budget: Option<CompactorMemoryBudget>,
     ) -> Result<Option<CompactTask>> {
         let mut compaction_guard = write_lock!(self, compaction).await;
         let compaction = compaction_guard.deref_mut();
@@ -657,12 +751,36 @@ where
             return Ok(None);
         }
         let can_trivial_move = manual_compaction_option.is_none();
+        let levels = current_version.get_compaction_group_levels(compaction_group_id);
+        let mut compaction_config = group_config.compaction_config();
+        if manual_compaction_option.is_none() {
+            if let Some(&max_compaction_bytes) = self
+                .max_compaction_bytes_overrides
+                .read()
+                .get(&compaction_group_id)
+            {
+                compaction_config.max_compaction_bytes = max_compaction_bytes;
+                compaction_config.sub_level_max_compaction_bytes = std::cmp::min(
+                    compaction_config.sub_level_max_compaction_bytes,
+                    max_compaction_bytes,
+                );
+            }
+            if let Some(memory_budget) = memory_budget {
+                compaction_config = limit_compaction_config_by_memory(
+                    &compact_status,
+                    levels,
+                    task_id as HummockCompactionTaskId,
+                    compaction_config,
+                    memory_budget,
+                );
+            }
+        }
         let compact_task = compact_status.get_compact_task(
-            current_version.get_compaction_group_levels(compaction_group_id),
+            levels,
             task_id as HummockCompactionTaskId,
             compaction_group_id,
             manual_compaction_option,
-            group_config.compaction_config(),
+            compaction_config.clone(),
         );
         let mut compact_task = match compact_task {
             None => {
@@ -671,6 +789,12 @@ where
             Some(task) => task,
         };
         compact_task.watermark = watermark;
+        compact_task.input_file_size = compact_task_input_size(&compact_task);
+        compact_task.estimated_memory_usage = estimate_compact_task_memory(
+            &compact_task,
+            &compaction_config,
+            memory_budget.map_or(DEFAULT_COMPACTOR_BLOCK_SIZE, |budget| budget.block_size),
+        );
 
         if CompactStatus::is_trivial_move_task(&compact_task) && can_trivial_move {
             compact_task.sorted_output_ssts = compact_task.input_ssts[0].table_infos.clone();
@@ -706,6 +830,22 @@ where
                 }
             }
 
+            // Tables that alone have been written more than the base level size recently are
+            // likely to be moved to dedicated compaction groups, so keep them in separate SSTs.
+            compact_task.split_table_ids = {
+                let now = Instant::now();
+                let recent_table_write_bytes = self.recent_table_write_bytes.read();
+                compact_task
+                    .existing_table_ids
+                    .iter()
+                    .filter(|table_id| {
+                        recent_table_write_bytes.get(now, **table_id)
+                            >= group_config.compaction_config.max_bytes_for_level_base
+                    })
+                    .cloned()
+                    .collect()
+            };
+
             // build table_options
             compact_task.table_options = group_config
                 .table_id_to_options()
@@ -768,12 +908,22 @@ where
     pub async fn get_compact_task(
         &self,
         compaction_group_id: CompactionGroupId,
+    ) -> Result<Option<CompactTask>> {
+        self.get_compact_task_with_memory_budget(compaction_group_id, None)
+            .await
+    }
+
+    /// Picks a compaction task that is estimated to fit in `memory_budget` if possible.
+    pub async fn get_compact_task_with_memory_budget(
+        &self,
+        compaction_group_id: CompactionGroupId,
+        memory_budget: Option<CompactorMemoryBudget>,
     ) -> Result<Option<CompactTask>> {
         fail_point!("fp_get_compact_task", |_| Err(Error::MetaStore(
             anyhow::anyhow!("failpoint metastore error")
         )));
         while let Some(task) = self
-            .get_compact_task_impl(compaction_group_id, None)
+            .get_compact_task_impl(compaction_group_id, None, memory_budget)
             .await?
         {
             if let TaskStatus::Pending = task.task_status() {
@@ -789,7 +939,7 @@ where
         compaction_group_id: CompactionGroupId,
         manual_compaction_option: ManualCompactionOption,
     ) -> Result<Option<CompactTask>> {
-        self.get_compact_task_impl(compaction_group_id, Some(manual_compaction_option))
+        self.get_compact_task_impl(compaction_group_id, Some(manual_compaction_option), None)
             .await
     }
 
@@ -812,6 +962,23 @@ where
             .next_idle_compactor(&compactor_assigned_task_num)
     }
 
+    /// Returns the number of assigned compaction tasks of each compaction group.
+    #[named]
+    pub async fn assigned_compact_task_num_by_group(&self) -> HashMap<CompactionGroupId, u64> {
+        let compaction_guard = read_lock!(self, compaction).await;
+        let mut assigned_task_num = HashMap::new();
+        compaction_guard
+            .compact_task_assignment
+            .values()
+            .filter_map(|assignment| assignment.compact_task.as_ref())
+            .for_each(|task| {
+                *assigned_task_num
+                    .entry(task.compaction_group_id)
+                    .or_insert(0) += 1;
+            });
+        assigned_task_num
+    }
+
     /// Assign a compaction task to the compactor identified by `assignee_context_id`.
     #[named]
     pub async fn assign_compaction_task(
@@ -889,13 +1056,101 @@ where
         context_id: HummockContextId,
         compact_task: &mut CompactTask,
     ) -> Result<bool> {
+        if compact_task.task_status() == TaskStatus::MemoryLimitCanceled {
+            self.shrink_compaction_for_memory(context_id, compact_task)
+                .await;
+        }
         let ret = self
             .report_compact_task_impl(Some(context_id), compact_task, None)
             .await?;
+        if ret && compact_task.task_status() == TaskStatus::Success {
+            self.relax_compaction_for_memory(compact_task).await;
+        }
 
         Ok(ret)
     }
 
+    /// Lowers the max input size of the compaction group of `compact_task`, which is rejected by
+    /// the compactor for exceeding its memory budget, so that the next task is picked in
+    /// proportion to the budget.
+    async fn shrink_compaction_for_memory(
+        &self,
+        context_id: HummockContextId,
+        compact_task: &CompactTask,
+    ) {
+        let memory_budget = match self.compactor_manager.memory_budget(context_id) {
+            Some(memory_budget) => memory_budget.memory_limit,
+            None => return,
+        };
+        if compact_task.estimated_memory_usage == 0 {
+            return;
+        }
+        let compaction_group_id = compact_task.compaction_group_id;
+        let compaction_config = match self
+            .compaction_group_manager
+            .compaction_group(compaction_group_id)
+            .await
+        {
+            Some(group) => group.compaction_config(),
+            None => return,
+        };
+        let input_size = compact_task_input_size(compact_task);
+        let max_compaction_bytes = (input_size as u128 * memory_budget as u128
+            / compact_task.estimated_memory_usage as u128)
+            as u64;
+        let max_compaction_bytes = std::cmp::max(
+            max_compaction_bytes,
+            compaction_config.target_file_size_base,
+        );
+
+        let mut overrides = self.max_compaction_bytes_overrides.write();
+        let entry = overrides
+            .entry(compaction_group_id)
+            .or_insert(compaction_config.max_compaction_bytes);
+        *entry = std::cmp::min(*entry, max_compaction_bytes);
+        tracing::info!(
+            "Compaction task {} of {} bytes is rejected by compactor {} with memory budget {}. Max compaction bytes of compaction group {} is lowered to {}",
+            compact_task.task_id,
+            input_size,
+            context_id,
+            memory_budget,
+            compaction_group_id,
+            *entry
+        );
+    }
+
+    /// Doubles the max input size of the compaction group of `compact_task`, which has finished
+    /// successfully, if it has been lowered by [`Self::shrink_compaction_for_memory`]. The group
+    /// goes back to its configured max input size step by step, so that a compactor with a small
+    /// budget doesn't limit the tasks of the group forever.
+    async fn relax_compaction_for_memory(&self, compact_task: &CompactTask) {
+        let compaction_group_id = compact_task.compaction_group_id;
+        if !self
+            .max_compaction_bytes_overrides
+            .read()
+            .contains_key(&compaction_group_id)
+        {
+            return;
+        }
+        let max_compaction_bytes = self
+            .compaction_group_manager
+            .compaction_group(compaction_group_id)
+            .await
+            .map(|group| group.compaction_config().max_compaction_bytes);
+
+        let mut overrides = self.max_compaction_bytes_overrides.write();
+        let Some(entry) = overrides.get_mut(&compaction_group_id) else {
+            return;
+        };
+        *entry = entry.saturating_mul(2);
+        match max_compaction_bytes {
+            Some(max_compaction_bytes) if *entry < max_compaction_bytes => {}
+            _ => {
+                overrides.remove(&compaction_group_id);
+            }
+        }
+    }
+
     /// Finishes or cancels a compaction task, according to `task_status`.
     ///
     /// If `context_id` is not None, its validity will be checked when writing meta store.
@@ -1311,6 +1566,14 @@ where
             return Ok(());
         }
 
+        // Table write stats are only used for accounting, and are not kept in the version.
+        let mut table_write_stats: HashMap<u32, TableStats> = HashMap::new();
+        for (_, sst) in &mut sstables {
+            for (table_id, stats) in std::mem::take(&mut sst.table_stats) {
+                add_table_stats(table_write_stats.entry(table_id).or_default(), &stats);
+            }
+        }
+
         let (raw_compaction_groups, compaction_group_index) = self
             .compaction_group_manager
             .compaction_groups_and_index()
@@ -1508,6 +1771,20 @@ where
         assert!(prev_snapshot.committed_epoch < epoch);
         assert!(prev_snapshot.current_epoch < epoch);
 
+        trigger_table_write_stat(&self.metrics, &table_write_stats);
+        {
+            let mut cumulative_table_write_stats = self.table_write_stats.write();
+            for (table_id, stats) in &table_write_stats {
+                add_table_stats(
+                    cumulative_table_write_stats.entry(*table_id).or_default(),
+                    stats,
+                );
+            }
+        }
+        self.recent_table_write_bytes
+            .write()
+            .record(Instant::now(), &table_write_stats);
+
         trigger_version_stat(&self.metrics, &versioning.current_version);
         for compaction_group_id in &modified_compaction_groups {
             trigger_sst_stat(
@@ -1555,6 +1832,11 @@ where
         Ok(())
     }
 
+    /// Returns the cumulative write stats of each table since the meta node starts.
+    pub fn get_table_write_stats(&self) -> HashMap<u32, TableStats> {
+        self.table_write_stats.read().clone()
+    }
+
     /// We don't commit an epoch without checkpoint. We will only update the `max_current_epoch`.
     pub fn update_current_epoch(&self, max_current_epoch: HummockEpoch) -> Result<()> {
         // We only update `max_current_epoch`!
@@ -1960,6 +2242,10 @@ where
     pub fn cluster_manager(&self) -> &ClusterManagerRef<S> {
         &self.cluster_manager
     }
+
+    pub fn metrics(&self) -> &MetaMetrics {
+        &self.metrics
+    }
 }
 
 fn drop_sst(
@@ -14,27 +14,38 @@
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
+use risingwave_common::try_match_expand;
 use risingwave_common::util::epoch::INVALID_EPOCH;
 use risingwave_hummock_sdk::compact::compact_task_to_string;
-use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
+use risingwave_hummock_sdk::compaction_group::hummock_version_ext::{
+    HummockLevelsExt, HummockVersionExt,
+};
 use risingwave_hummock_sdk::compaction_group::StaticCompactionGroupId;
+use risingwave_hummock_sdk::key::{upgrade_full_key, KeyEncodingVersion};
 // use risingwave_hummock_sdk::key_range::KeyRange;
 use risingwave_hummock_sdk::{HummockContextId, HummockEpoch, HummockVersionId, FIRST_VERSION_ID};
-use risingwave_pb::common::{HostAddress, WorkerType};
+use risingwave_pb::catalog::Table;
+use risingwave_pb::common::{HostAddress, WorkerRole, WorkerType};
 use risingwave_pb::hummock::compact_task::TaskStatus;
 use risingwave_pb::hummock::pin_version_response::Payload;
+use risingwave_pb::hummock::subscribe_compact_tasks_response::Task;
 use risingwave_pb::hummock::{
-    HummockPinnedSnapshot, HummockPinnedVersion, HummockSnapshot, KeyRange,
+    HummockPinnedSnapshot, HummockPinnedVersion, HummockSnapshot, KeyRange, TableStats,
 };
 
+use super::{RecentTableWriteBytes, TABLE_WRITE_BYTES_WINDOW};
 use crate::hummock::compaction::ManualCompactionOption;
 use crate::hummock::error::Error;
 use crate::hummock::test_utils::*;
-use crate::hummock::{start_compaction_scheduler, CompactionScheduler, HummockManagerRef};
-use crate::manager::WorkerId;
+use crate::hummock::{
+    start_compaction_scheduler, CompactionScheduler, HummockManagerRef, LegacyKeyEncodingSsts,
+};
+use crate::manager::{CatalogManager, WorkerId, META_NODE_ID};
 use crate::model::MetadataModel;
 use crate::storage::MemStore;
 
@@ -49,6 +60,14 @@ fn pin_snapshots_epoch(pin_snapshots: &[HummockPinnedSnapshot]) -> Vec<u64> {
         .collect_vec()
 }
 
+fn pin_snapshots_context_id(pin_snapshots: &[HummockPinnedSnapshot]) -> Vec<HummockContextId> {
+    pin_snapshots
+        .iter()
+        .map(|p| p.context_id)
+        .sorted()
+        .collect_vec()
+}
+
 #[tokio::test]
 async fn test_unpin_snapshot_before() {
     let (env, hummock_manager, _cluster_manager, worker_node) = setup_compute_env(80).await;
@@ -103,6 +122,121 @@ async fn test_unpin_snapshot_before() {
     }
 }
 
+#[tokio::test]
+async fn test_snapshot_pin_lease() {
+    let (env, hummock_manager, _cluster_manager, worker_node) = setup_compute_env(80).await;
+    let context_id = worker_node.id;
+    let lease = Duration::from_secs(env.opts.snapshot_pin_lease_sec);
+
+    // The snapshot pinned by meta itself is not subject to the lease.
+    hummock_manager.pin_snapshot(META_NODE_ID).await.unwrap();
+    hummock_manager.pin_snapshot(context_id).await.unwrap();
+    hummock_manager.renew_snapshot_pin_lease(context_id);
+    let renewed_at = Instant::now();
+
+    assert!(hummock_manager
+        .release_expired_snapshot_pins(renewed_at + lease / 2)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![META_NODE_ID, context_id]
+    );
+
+    // The lease is renewed, so it hasn't expired by the time the previous one would have.
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    hummock_manager.renew_snapshot_pin_lease(context_id);
+    assert!(hummock_manager
+        .release_expired_snapshot_pins(renewed_at + lease + Duration::from_millis(5))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![META_NODE_ID, context_id]
+    );
+
+    // The worker stops renewing without being removed from the cluster, e.g. it hangs.
+    assert_eq!(
+        hummock_manager
+            .release_expired_snapshot_pins(Instant::now() + lease + Duration::from_secs(1))
+            .await
+            .unwrap(),
+        vec![context_id]
+    );
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![META_NODE_ID]
+    );
+
+    // The released pin is not recreated by advancing it.
+    let snapshot = hummock_manager.get_last_epoch().unwrap();
+    assert!(matches!(
+        hummock_manager
+            .unpin_snapshot_before(context_id, snapshot.clone())
+            .await,
+        Err(Error::SnapshotPinExpired(id)) if id == context_id
+    ));
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![META_NODE_ID]
+    );
+
+    // Pinning again starts a new lease, which expires as well if not renewed.
+    hummock_manager.pin_snapshot(context_id).await.unwrap();
+    hummock_manager
+        .unpin_snapshot_before(context_id, snapshot)
+        .await
+        .unwrap();
+    assert_eq!(
+        hummock_manager
+            .release_expired_snapshot_pins(Instant::now() + lease + Duration::from_secs(1))
+            .await
+            .unwrap(),
+        vec![context_id]
+    );
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![META_NODE_ID]
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_pin_lease_after_restart() {
+    let (env, hummock_manager, cluster_manager, worker_node) = setup_compute_env(80).await;
+    let lease = Duration::from_secs(env.opts.snapshot_pin_lease_sec);
+    let frontend = cluster_manager
+        .add_worker_node(
+            WorkerType::Frontend,
+            HostAddress {
+                host: "127.0.0.1".to_string(),
+                port: 2,
+            },
+            0,
+            WorkerRole::Both,
+        )
+        .await
+        .unwrap();
+    hummock_manager.pin_snapshot(worker_node.id).await.unwrap();
+    hummock_manager.pin_snapshot(frontend.id).await.unwrap();
+
+    // The leases are lost on restarting, and are started again for the frontends only.
+    hummock_manager.snapshot_pin_leases.lock().clear();
+    hummock_manager.init_snapshot_pin_leases().await;
+    assert_eq!(
+        hummock_manager
+            .release_expired_snapshot_pins(Instant::now() + lease + Duration::from_secs(1))
+            .await
+            .unwrap(),
+        vec![frontend.id]
+    );
+    assert_eq!(
+        pin_snapshots_context_id(&HummockPinnedSnapshot::list(env.meta_store()).await.unwrap()),
+        vec![worker_node.id]
+    );
+}
+
 #[tokio::test]
 async fn test_hummock_compaction_task() {
     let (_, hummock_manager, _, worker_node) = setup_compute_env(80).await;
@@ -195,6 +329,79 @@ async fn test_hummock_compaction_task() {
         .unwrap());
 }
 
+#[tokio::test]
+async fn test_max_compaction_bytes_override() {
+    let (_, hummock_manager, _, worker_node) = setup_compute_env(80).await;
+    let group_id = StaticCompactionGroupId::StateDefault.into();
+    let epoch: u64 = 1;
+    let original_tables = generate_test_tables(epoch, get_sst_ids(&hummock_manager, 2).await);
+    register_sstable_infos_to_compaction_group(
+        hummock_manager.compaction_group_manager(),
+        &original_tables,
+        group_id,
+    )
+    .await;
+    commit_from_meta_node(
+        hummock_manager.borrow(),
+        epoch,
+        to_local_sstable_info(&original_tables),
+    )
+    .await
+    .unwrap();
+
+    let compactor_manager = hummock_manager.compactor_manager_ref_for_test();
+    compactor_manager.add_compactor(worker_node.id, u64::MAX);
+    compactor_manager.set_memory_budget(worker_node.id, 1, 0);
+    let compaction_config = hummock_manager.get_compaction_config(group_id).await;
+    let max_compaction_bytes_override = || {
+        hummock_manager
+            .max_compaction_bytes_overrides
+            .read()
+            .get(&group_id)
+            .copied()
+    };
+
+    // A task rejected by the compactor lowers the max input size of the group.
+    let mut compact_task = hummock_manager
+        .get_compact_task(group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    hummock_manager
+        .assign_compaction_task(&compact_task, worker_node.id)
+        .await
+        .unwrap();
+    compact_task.set_task_status(TaskStatus::MemoryLimitCanceled);
+    assert!(hummock_manager
+        .report_compact_task(worker_node.id, &mut compact_task)
+        .await
+        .unwrap());
+    assert_eq!(
+        max_compaction_bytes_override(),
+        Some(compaction_config.target_file_size_base)
+    );
+
+    // A successful task raises it back towards the configured one.
+    let mut compact_task = hummock_manager
+        .get_compact_task(group_id)
+        .await
+        .unwrap()
+        .unwrap();
+    hummock_manager
+        .assign_compaction_task(&compact_task, worker_node.id)
+        .await
+        .unwrap();
+    compact_task.set_task_status(TaskStatus::Success);
+    assert!(hummock_manager
+        .report_compact_task(worker_node.id, &mut compact_task)
+        .await
+        .unwrap());
+    assert_eq!(
+        max_compaction_bytes_override(),
+        Some(2 * compaction_config.target_file_size_base)
+    );
+}
+
 #[tokio::test]
 async fn test_hummock_table() {
     let (_env, hummock_manager, _cluster_manager, _worker_node) = setup_compute_env(80).await;
@@ -340,6 +547,7 @@ async fn test_release_context_resource() {
             WorkerType::ComputeNode,
             fake_host_address_2,
             fake_parallelism,
+            WorkerRole::Both,
         )
         .await
         .unwrap();
@@ -474,6 +682,7 @@ async fn test_hummock_manager_basic() {
             WorkerType::ComputeNode,
             fake_host_address_2,
             fake_parallelism,
+            WorkerRole::Both,
         )
         .await
         .unwrap();
@@ -1195,3 +1404,251 @@ async fn test_extend_ssts_to_delete() {
         orphan_sst_num as usize + 3
     );
 }
+
+#[tokio::test]
+async fn test_version_safe_point_and_backup_pinned_ssts() {
+    let (_env, hummock_manager, _cluster_manager, worker_node) = setup_compute_env(80).await;
+    let context_id = worker_node.id;
+    let safe_point = hummock_manager.register_safe_point().await;
+    add_test_tables(hummock_manager.as_ref(), context_id).await;
+
+    // The checkpoint cannot pass the safe point.
+    assert_eq!(
+        hummock_manager.proceed_version_checkpoint().await.unwrap(),
+        0
+    );
+    assert!(hummock_manager.get_ssts_to_delete().await.is_empty());
+
+    hummock_manager.unregister_safe_point(safe_point).await;
+    assert_eq!(
+        hummock_manager.proceed_version_checkpoint().await.unwrap(),
+        4
+    );
+    let ssts_to_delete = hummock_manager.get_ssts_to_delete().await;
+    assert_eq!(ssts_to_delete.len(), 3);
+
+    // SSTs referenced by backups are never deleted.
+    hummock_manager
+        .pin_backup_ssts(ssts_to_delete[..2].iter().cloned())
+        .await;
+    assert_eq!(
+        hummock_manager.get_ssts_to_delete().await,
+        ssts_to_delete[2..].to_vec()
+    );
+
+    // The deltas are still deleted once the unpinned SSTs are, without waiting for the pinned
+    // ones.
+    hummock_manager
+        .ack_deleted_ssts(&ssts_to_delete[2..])
+        .await
+        .unwrap();
+    hummock_manager
+        .delete_version_deltas(usize::MAX)
+        .await
+        .unwrap();
+    assert!(hummock_manager
+        .list_version_deltas(0, u32::MAX)
+        .await
+        .unwrap()
+        .version_deltas
+        .iter()
+        .all(|delta| delta.get_gc_sst_ids().is_empty()));
+}
+
+#[test]
+fn test_recent_table_write_bytes() {
+    let stats = |bytes: &[(u32, u64)]| -> HashMap<u32, TableStats> {
+        bytes
+            .iter()
+            .map(|(table_id, total_bytes)| {
+                (
+                    *table_id,
+                    TableStats {
+                        total_bytes: *total_bytes,
+                        total_key_count: 1,
+                    },
+                )
+            })
+            .collect()
+    };
+    let start = Instant::now();
+    let mut recent = RecentTableWriteBytes::default();
+    recent.record(start, &stats(&[(1, 100), (2, 10)]));
+    recent.record(start + Duration::from_secs(1), &stats(&[(1, 50)]));
+    assert_eq!(recent.get(start + Duration::from_secs(1), 1), 150);
+    assert_eq!(recent.get(start + Duration::from_secs(1), 2), 10);
+    assert_eq!(recent.get(start + Duration::from_secs(1), 3), 0);
+
+    // The writes out of the window no longer count, however large they were in total.
+    let later = start + TABLE_WRITE_BYTES_WINDOW + Duration::from_millis(500);
+    assert_eq!(recent.get(later, 1), 50);
+    assert_eq!(recent.get(later, 2), 0);
+    recent.record(later + Duration::from_secs(1), &stats(&[(2, 20)]));
+    assert_eq!(recent.epochs.len(), 1);
+    assert_eq!(recent.get(later + Duration::from_secs(1), 1), 0);
+    assert_eq!(recent.get(later + Duration::from_secs(1), 2), 20);
+}
+
+#[tokio::test]
+async fn test_upgrade_key_encoding() {
+    let (env, hummock_manager, _, worker_node) = setup_compute_env(80).await;
+    let context_id = worker_node.id;
+    // The state tables 1, 2 and 3 have keys in the legacy SSTs, while 4 has none.
+    for table_id in 1..=4 {
+        Table {
+            id: table_id,
+            key_encoding_version: KeyEncodingVersion::V1.to_u32(),
+            ..Default::default()
+        }
+        .insert(env.meta_store())
+        .await
+        .unwrap();
+    }
+    let catalog_manager = Arc::new(CatalogManager::new(env.clone()).await.unwrap());
+    assert_eq!(
+        catalog_manager
+            .list_legacy_key_encoding_tables()
+            .await
+            .unwrap()
+            .into_iter()
+            .sorted()
+            .collect_vec(),
+        vec![1, 2, 3, 4]
+    );
+
+    // Commit SSTs written before the key encoding version is recorded.
+    let epoch = 1;
+    let mut legacy_ssts = generate_test_tables(epoch, get_sst_ids(&hummock_manager, 2).await);
+    for sst in &mut legacy_ssts {
+        sst.key_encoding_version = 0;
+    }
+    register_sstable_infos_to_compaction_group(
+        hummock_manager.compaction_group_manager(),
+        &legacy_ssts,
+        StaticCompactionGroupId::StateDefault.into(),
+    )
+    .await;
+    let ssts = to_local_sstable_info(&legacy_ssts);
+    let sst_to_worker = ssts.iter().map(|(_, sst)| (sst.id, context_id)).collect();
+    hummock_manager
+        .commit_epoch(epoch, ssts, sst_to_worker)
+        .await
+        .unwrap();
+
+    // The key ranges of the legacy SSTs are upgraded on loading, and the SSTs are marked legacy.
+    hummock_manager.load_meta_store_state().await.unwrap();
+    let version = hummock_manager.get_current_version().await;
+    let loaded_ssts = version
+        .get_compaction_group_levels(StaticCompactionGroupId::StateDefault.into())
+        .get_level0()
+        .sub_levels
+        .iter()
+        .flat_map(|sub_level| sub_level.table_infos.iter())
+        .sorted_by_key(|sst| sst.id)
+        .collect_vec();
+    assert_eq!(loaded_ssts.len(), legacy_ssts.len());
+    for (loaded_sst, legacy_sst) in loaded_ssts.into_iter().zip_eq(&legacy_ssts) {
+        let loaded_key_range = loaded_sst.key_range.as_ref().unwrap();
+        let legacy_key_range = legacy_sst.key_range.as_ref().unwrap();
+        assert_eq!(
+            loaded_key_range.left,
+            upgrade_full_key(&legacy_key_range.left)
+        );
+        assert_eq!(
+            loaded_key_range.right,
+            upgrade_full_key(&legacy_key_range.right)
+        );
+        assert_eq!(
+            loaded_sst.key_encoding_version,
+            KeyEncodingVersion::V1.to_u32()
+        );
+    }
+    let legacy_key_encoding_ssts = hummock_manager
+        .get_legacy_key_encoding_ssts()
+        .await
+        .unwrap();
+    assert_eq!(
+        legacy_key_encoding_ssts.ssts,
+        BTreeMap::from([(
+            (StaticCompactionGroupId::StateDefault.into(), 0),
+            get_sorted_sstable_ids(&legacy_ssts)
+        )])
+    );
+    assert_eq!(legacy_key_encoding_ssts.table_ids, HashSet::from([1, 2, 3]));
+
+    // Only the table without keys in the legacy SSTs is flipped, while the legacy SSTs are
+    // scheduled to be rewritten.
+    let compactor_manager_ref = hummock_manager.compactor_manager_ref_for_test();
+    let mut receiver = compactor_manager_ref.add_compactor(context_id, u64::MAX);
+    hummock_manager
+        .upgrade_key_encoding(&catalog_manager)
+        .await
+        .unwrap();
+    assert_eq!(
+        catalog_manager
+            .list_legacy_key_encoding_tables()
+            .await
+            .unwrap()
+            .into_iter()
+            .sorted()
+            .collect_vec(),
+        vec![1, 2, 3]
+    );
+    let task = receiver.recv().await.unwrap().unwrap().task.unwrap();
+    let mut compact_task = try_match_expand!(task, Task::CompactTask).unwrap();
+    assert_eq!(
+        compact_task
+            .input_ssts
+            .iter()
+            .flat_map(|level| level.table_infos.iter())
+            .map(|sst| sst.id)
+            .sorted()
+            .collect_vec(),
+        get_sorted_sstable_ids(&legacy_ssts)
+    );
+
+    // The tables with keys in the legacy SSTs are still legacy while the rewrite is running.
+    hummock_manager
+        .upgrade_key_encoding(&catalog_manager)
+        .await
+        .unwrap();
+    assert_eq!(
+        catalog_manager
+            .list_legacy_key_encoding_tables()
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
+    // The rewritten SSTs are in the latest encoding, after which all tables are flipped.
+    let rewritten_ssts = generate_test_tables(epoch, get_sst_ids(&hummock_manager, 2).await);
+    register_sstable_infos_to_compaction_group(
+        hummock_manager.compaction_group_manager(),
+        &rewritten_ssts,
+        StaticCompactionGroupId::StateDefault.into(),
+    )
+    .await;
+    compact_task.sorted_output_ssts = rewritten_ssts;
+    compact_task.set_task_status(TaskStatus::Success);
+    assert!(hummock_manager
+        .report_compact_task(context_id, &mut compact_task)
+        .await
+        .unwrap());
+    assert_eq!(
+        hummock_manager
+            .get_legacy_key_encoding_ssts()
+            .await
+            .unwrap(),
+        LegacyKeyEncodingSsts::default()
+    );
+    hummock_manager
+        .upgrade_key_encoding(&catalog_manager)
+        .await
+        .unwrap();
+    assert!(catalog_manager
+        .list_legacy_key_encoding_tables()
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -13,20 +13,20 @@
 // limitations under the License.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::RangeBounds;
 
 use function_name::named;
 use itertools::Itertools;
 use risingwave_hummock_sdk::{
-    CompactionGroupId, HummockContextId, HummockSstableId, HummockVersionId,
+    CompactionGroupId, HummockContextId, HummockSstableId, HummockVersionId, INVALID_VERSION_ID,
 };
 use risingwave_pb::common::WorkerNode;
 use risingwave_pb::hummock::{
     HummockPinnedSnapshot, HummockPinnedVersion, HummockVersion, HummockVersionDelta,
 };
 
-use crate::hummock::manager::read_lock;
+use crate::hummock::manager::{read_lock, write_lock};
 use crate::hummock::HummockManager;
 use crate::storage::MetaStore;
 
@@ -52,6 +52,11 @@ pub struct Versioning {
     /// SST which is referenced more than once
     pub branched_ssts:
         BTreeMap<HummockSstableId, HashMap<CompactionGroupId, /* divide version */ u64>>,
+    /// Versions that must not be passed by the checkpoint, like pinned versions. See
+    /// `register_safe_point`.
+    pub version_safe_points: Vec<HummockVersionId>,
+    /// SSTs referenced by meta backups, which are never deleted from object store.
+    pub backup_pinned_ssts: HashSet<HummockSstableId>,
 
     // Persistent states below
 
@@ -69,6 +74,9 @@ impl Versioning {
         for version_pin in self.pinned_versions.values() {
             min_pinned_version_id = cmp::min(version_pin.min_pinned_id, min_pinned_version_id);
         }
+        for safe_point in &self.version_safe_points {
+            min_pinned_version_id = cmp::min(*safe_point, min_pinned_version_id);
+        }
         min_pinned_version_id
     }
 
@@ -95,6 +103,35 @@ impl Versioning {
             // Otherwise, the delta is qualified for deletion after all its sst_to_delete is
             // deleted.
         }
+        self.untrack_backup_pinned_ssts();
+    }
+
+    /// Removes `sst_ids` from `ssts_to_delete`, and qualifies the deltas without any remaining
+    /// sst_to_delete for deletion.
+    pub fn untrack_ssts_to_delete(&mut self, sst_ids: &[HummockSstableId]) {
+        let mut deltas_to_delete = HashSet::new();
+        for sst_id in sst_ids {
+            if let Some(version_id) = self.ssts_to_delete.remove(sst_id) && version_id != INVALID_VERSION_ID {
+                // Orphan SST is mapped to INVALID_VERSION_ID
+                deltas_to_delete.insert(version_id);
+            }
+        }
+        let remain_deltas: HashSet<HummockVersionId> =
+            HashSet::from_iter(self.ssts_to_delete.values().cloned());
+        deltas_to_delete.retain(|id| !remain_deltas.contains(id));
+        self.deltas_to_delete.extend(deltas_to_delete);
+    }
+
+    /// SSTs referenced by meta backups are never deleted, so they're untracked as if deleted.
+    /// Otherwise, the deltas marking them stale would never be deleted.
+    fn untrack_backup_pinned_ssts(&mut self) {
+        let pinned_sst_ids = self
+            .ssts_to_delete
+            .keys()
+            .filter(|sst_id| self.backup_pinned_ssts.contains(sst_id))
+            .cloned()
+            .collect_vec();
+        self.untrack_ssts_to_delete(&pinned_sst_ids);
     }
 }
 
@@ -122,6 +159,37 @@ where
             .collect_vec()
     }
 
+    /// Registers the current version as a safe point, so that neither the version nor its SSTs
+    /// are garbage collected until [`Self::unregister_safe_point`] is called with the returned id.
+    #[named]
+    pub async fn register_safe_point(&self) -> HummockVersionId {
+        let mut versioning_guard = write_lock!(self, versioning).await;
+        let version_id = versioning_guard.current_version.id;
+        versioning_guard.version_safe_points.push(version_id);
+        version_id
+    }
+
+    #[named]
+    pub async fn unregister_safe_point(&self, safe_point: HummockVersionId) {
+        let mut versioning_guard = write_lock!(self, versioning).await;
+        if let Some(pos) = versioning_guard
+            .version_safe_points
+            .iter()
+            .position(|sp| *sp == safe_point)
+        {
+            versioning_guard.version_safe_points.remove(pos);
+        }
+    }
+
+    /// Prevents `sst_ids`, which are referenced by a meta backup, from being deleted from object
+    /// store.
+    #[named]
+    pub async fn pin_backup_ssts(&self, sst_ids: impl IntoIterator<Item = HummockSstableId>) {
+        let mut versioning_guard = write_lock!(self, versioning).await;
+        versioning_guard.backup_pinned_ssts.extend(sst_ids);
+        versioning_guard.untrack_backup_pinned_ssts();
+    }
+
     pub async fn list_workers(
         &self,
         context_ids: &[HummockContextId],
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::Ordering;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -20,7 +20,9 @@ use itertools::enumerate;
 use prost::Message;
 use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
 use risingwave_hummock_sdk::{CompactionGroupId, HummockContextId};
-use risingwave_pb::hummock::{HummockPinnedSnapshot, HummockPinnedVersion, HummockVersion};
+use risingwave_pb::hummock::{
+    HummockPinnedSnapshot, HummockPinnedVersion, HummockVersion, TableStats,
+};
 
 use crate::hummock::compaction::CompactStatus;
 use crate::rpc::metrics::MetaMetrics;
@@ -36,6 +38,20 @@ pub fn trigger_version_stat(metrics: &MetaMetrics, current_version: &HummockVers
     metrics.current_version_id.set(current_version.id as i64);
 }
 
+pub fn trigger_table_write_stat(metrics: &MetaMetrics, table_stats: &HashMap<u32, TableStats>) {
+    for (table_id, stats) in table_stats {
+        let table_id = table_id.to_string();
+        metrics
+            .table_write_bytes
+            .with_label_values(&[&table_id])
+            .inc_by(stats.total_bytes);
+        metrics
+            .table_write_key_count
+            .with_label_values(&[&table_id])
+            .inc_by(stats.total_key_count);
+    }
+}
+
 pub fn trigger_sst_stat(
     metrics: &MetaMetrics,
     compact_status: Option<&CompactStatus>,
@@ -142,6 +142,8 @@ impl HummockMetaClient for MockHummockMetaClient {
     async fn subscribe_compact_tasks(
         &self,
         _max_concurrent_task_number: u64,
+        _memory_budget: u64,
+        _block_size: u64,
     ) -> Result<Streaming<SubscribeCompactTasksResponse>> {
         unimplemented!()
     }
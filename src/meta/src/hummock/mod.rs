@@ -14,6 +14,7 @@
 
 pub mod compaction;
 pub mod compaction_group;
+mod compaction_group_limiter;
 mod compaction_schedule_policy;
 mod compaction_scheduler;
 pub mod compactor_manager;
@@ -32,7 +33,7 @@ mod utils;
 mod vacuum;
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub use compaction_scheduler::CompactionScheduler;
 pub use compactor_manager::*;
@@ -66,9 +67,19 @@ where
 {
     let mut workers = vec![
         start_compaction_scheduler(compaction_scheduler),
-        start_local_notification_receiver(hummock_manager, compactor_manager, notification_manager)
-            .await,
+        start_local_notification_receiver(
+            hummock_manager.clone(),
+            compactor_manager,
+            notification_manager,
+        )
+        .await,
     ];
+    if meta_opts.snapshot_pin_lease_sec > 0 {
+        workers.push(start_snapshot_pin_lease_checker(
+            hummock_manager,
+            Duration::from_secs(std::cmp::max(1, meta_opts.snapshot_pin_lease_sec / 4)),
+        ));
+    }
     // Start vacuum in non-deterministic compaction test
     if !meta_opts.compaction_deterministic_test {
         workers.push(start_vacuum_scheduler(
@@ -198,3 +209,36 @@ where
     });
     (join_handle, shutdown_tx)
 }
+
+/// Starts a task to periodically release the snapshots pinned by frontends whose lease has expired.
+pub fn start_snapshot_pin_lease_checker<S>(
+    hummock_manager: HummockManagerRef<S>,
+    interval: Duration,
+) -> (JoinHandle<()>, Sender<()>)
+where
+    S: MetaStore,
+{
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+    let join_handle = tokio::spawn(async move {
+        let mut check_interval = tokio::time::interval(interval);
+        check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                // Wait for interval
+                _ = check_interval.tick() => {},
+                // Shutdown checker
+                _ = &mut shutdown_rx => {
+                    tracing::info!("Snapshot pin lease checker is stopped");
+                    return;
+                }
+            }
+            if let Err(err) = hummock_manager
+                .release_expired_snapshot_pins(Instant::now())
+                .await
+            {
+                tracing::warn!("Failed to release expired snapshot pins {:#?}", err);
+            }
+        }
+    });
+    (join_handle, shutdown_tx)
+}
@@ -17,11 +17,11 @@ use std::time::Duration;
 
 use itertools::Itertools;
 use risingwave_hummock_sdk::compaction_group::StaticCompactionGroupId;
-use risingwave_hummock_sdk::key::key_with_epoch;
+use risingwave_hummock_sdk::key::{key_with_epoch, KeyEncodingVersion};
 use risingwave_hummock_sdk::{
     CompactionGroupId, HummockContextId, HummockEpoch, HummockSstableId, LocalSstableInfo,
 };
-use risingwave_pb::common::{HostAddress, WorkerNode, WorkerType};
+use risingwave_pb::common::{HostAddress, WorkerNode, WorkerRole, WorkerType};
 use risingwave_pb::hummock::compact_task::TaskStatus;
 use risingwave_pb::hummock::{CompactionConfig, HummockVersion, KeyRange, SstableInfo};
 
@@ -147,6 +147,8 @@ pub fn generate_test_tables(epoch: u64, sst_ids: Vec<HummockSstableId>) -> Vec<S
             stale_key_count: 0,
             total_key_count: 0,
             divide_version: 0,
+            table_stats: Default::default(),
+            key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
         });
     }
     sst_info
@@ -280,7 +282,12 @@ pub async fn setup_compute_env_with_config(
     };
     let fake_parallelism = 4;
     let worker_node = cluster_manager
-        .add_worker_node(WorkerType::ComputeNode, fake_host_address, fake_parallelism)
+        .add_worker_node(
+            WorkerType::ComputeNode,
+            fake_host_address,
+            fake_parallelism,
+            WorkerRole::Both,
+        )
         .await
         .unwrap();
     (env, hummock_manager, cluster_manager, worker_node)
@@ -37,6 +37,7 @@
 #![cfg_attr(coverage, feature(no_coverage))]
 #![test_runner(risingwave_test_runner::test_runner::run_failpont_tests)]
 
+mod backup_restore;
 mod barrier;
 #[cfg(not(madsim))] // no need in simulation test
 mod dashboard;
@@ -105,6 +106,11 @@ pub struct MetaNodeOpts {
     #[clap(long)]
     dashboard_ui_path: Option<String>,
 
+    /// Export traces to the OpenTelemetry collector at this endpoint with OTLP, e.g.
+    /// `http://127.0.0.1:4317`. Left empty to disable exporting.
+    #[clap(long)]
+    pub otlp_endpoint: Option<String>,
+
     /// No given `config_path` means to use default config.
     #[clap(long, default_value = "")]
     pub config_path: String,
@@ -150,6 +156,49 @@ pub struct MetaNodeOpts {
 
     #[clap(long, default_value = "10")]
     node_num_monitor_interval_sec: u64,
+
+    /// Table fragments staying in `Creating` state for longer than this are considered orphaned
+    /// by the meta store GC.
+    #[clap(long, default_value = "3600")]
+    creating_table_fragments_gc_threshold_sec: u64,
+
+    /// The max number of splits that a source actor can be assigned.
+    #[clap(long, default_value = "1024")]
+    max_splits_per_actor: usize,
+
+    /// The snapshot pinned by a frontend is released if the frontend doesn't renew it for this
+    /// long, e.g. after it crashes. 0 to disable the lease.
+    #[clap(long, default_value = "60")]
+    snapshot_pin_lease_sec: u64,
+
+    /// A DDL retried by the frontend with the same idempotency key within this long after it's
+    /// received returns the outcome of the original one instead of being executed again.
+    #[clap(long, default_value = "3600")]
+    ddl_idempotency_retention_sec: u64,
+
+    /// Barriers taking longer than this to be collected are logged. Only used to initialize the
+    /// system param of a new cluster, which can be changed at runtime with risectl.
+    #[clap(long, default_value = "10000")]
+    slow_barrier_threshold_ms: u64,
+
+    /// Compaction tasks taking longer than this are logged by the compactor. Only used to
+    /// initialize the system param of a new cluster, which can be changed at runtime with risectl.
+    #[clap(long, default_value = "300000")]
+    slow_compaction_threshold_ms: u64,
+
+    /// Remote object store url to write meta backups to, e.g. `s3://bucket`. Meta backup is
+    /// disabled if not specified.
+    #[clap(long)]
+    backup_storage_url: Option<String>,
+
+    /// Directory of meta backups in the backup storage.
+    #[clap(long, default_value = "backup")]
+    backup_storage_directory: String,
+
+    /// Restore the meta store from the backup with this id before serving. The meta store must be
+    /// empty, and all worker nodes must be restarted after the restore.
+    #[clap(long)]
+    restore_from: Option<u64>,
 }
 
 use std::future::Future;
@@ -215,6 +264,7 @@ pub fn start(opts: MetaNodeOpts) -> Pin<Box<dyn Future<Output = ()> + Send>> {
                 minimal_scheduling: meta_config.streaming.minimal_scheduling,
                 max_idle_ms,
                 checkpoint_frequency,
+                barrier_trace_sample_interval: meta_config.streaming.barrier_trace_sample_interval,
                 compaction_deterministic_test: opts.enable_compaction_deterministic,
                 vacuum_interval_sec: opts.vacuum_interval_sec,
                 min_sst_retention_time_sec: opts.min_sst_retention_time_sec,
@@ -222,6 +272,16 @@ pub fn start(opts: MetaNodeOpts) -> Pin<Box<dyn Future<Output = ()> + Send>> {
                 enable_committed_sst_sanity_check: opts.enable_committed_sst_sanity_check,
                 periodic_compaction_interval_sec: opts.periodic_compaction_interval_sec,
                 node_num_monitor_interval_sec: opts.node_num_monitor_interval_sec,
+                creating_table_fragments_gc_threshold_sec: opts
+                    .creating_table_fragments_gc_threshold_sec,
+                max_splits_per_actor: opts.max_splits_per_actor,
+                snapshot_pin_lease_sec: opts.snapshot_pin_lease_sec,
+                ddl_idempotency_retention_sec: opts.ddl_idempotency_retention_sec,
+                slow_barrier_threshold_ms: opts.slow_barrier_threshold_ms,
+                slow_compaction_threshold_ms: opts.slow_compaction_threshold_ms,
+                backup_storage_url: opts.backup_storage_url,
+                backup_storage_directory: opts.backup_storage_directory,
+                restore_from: opts.restore_from,
             },
         )
         .await
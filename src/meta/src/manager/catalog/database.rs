@@ -17,8 +17,9 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 
 use itertools::Itertools;
 use risingwave_pb::catalog::{Database, Index, Schema, Sink, Source, Table};
+use risingwave_pb::user::grant_privilege::Object;
 
-use super::{DatabaseId, RelationId, SchemaId, SinkId, SourceId};
+use super::{DatabaseId, RelationId, SchemaId, SinkId, SourceId, UserId};
 use crate::manager::{IndexId, MetaSrvEnv, TableId};
 use crate::model::MetadataModel;
 use crate::storage::MetaStore;
@@ -208,6 +209,18 @@ where
         }
     }
 
+    /// Returns the owner of the privilege object, or `None` if the object doesn't exist.
+    pub fn get_object_owner(&self, object: &Object) -> Option<UserId> {
+        match object {
+            Object::DatabaseId(id) => self.databases.get(id).map(|d| d.owner),
+            Object::SchemaId(id)
+            | Object::AllTablesSchemaId(id)
+            | Object::AllSourcesSchemaId(id) => self.schemas.get(id).map(|s| s.owner),
+            Object::TableId(id) => self.tables.get(id).map(|t| t.owner),
+            Object::SourceId(id) => self.sources.get(id).map(|s| s.owner),
+        }
+    }
+
     pub fn get_ref_count(&self, relation_id: RelationId) -> Option<usize> {
         self.relation_ref_count.get(&relation_id).cloned()
     }
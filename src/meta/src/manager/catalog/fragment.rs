@@ -12,39 +12,111 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context};
 use itertools::Itertools;
+use parking_lot::Mutex;
+use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::TableId;
-use risingwave_common::types::ParallelUnitId;
+use risingwave_common::types::{ParallelUnitId, VIRTUAL_NODE_COUNT};
+use risingwave_common::util::compress::decompress_data;
 use risingwave_common::{bail, try_match_expand};
-use risingwave_connector::source::SplitImpl;
-use risingwave_pb::common::{Buffer, ParallelUnit, ParallelUnitMapping, WorkerNode};
+use risingwave_connector::source::{SplitId, SplitImpl, SplitMetaData};
+use risingwave_hummock_sdk::key::{get_table_id, TABLE_KEY_PREFIX_LEN, TABLE_PREFIX_LEN};
+use risingwave_pb::common::{
+    BatchParallelUnitMapping, Buffer, ParallelUnit, ParallelUnitMapping, WorkerNode,
+};
+use risingwave_pb::hummock::{KeyRange, TableStats};
 use risingwave_pb::meta::subscribe_response::{Info, Operation};
 use risingwave_pb::meta::table_fragments::actor_status::ActorState;
-use risingwave_pb::meta::table_fragments::{ActorStatus, State};
+use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
+use risingwave_pb::meta::table_fragments::{ActorStatus, Fragment, State};
 use risingwave_pb::stream_plan::stream_node::NodeBody;
-use risingwave_pb::stream_plan::{Dispatcher, FragmentType, StreamActor, StreamNode};
-use tokio::sync::{RwLock, RwLockReadGuard};
+use risingwave_pb::stream_plan::update_mutation::DispatcherUpdate;
+use risingwave_pb::stream_plan::{
+    ActorMapping, Dispatcher, DispatcherType, FragmentType, StreamActor, StreamNode,
+};
+use tokio::sync::{broadcast, RwLock, RwLockReadGuard};
 
 use crate::barrier::Reschedule;
 use crate::manager::cluster::WorkerId;
-use crate::manager::{commit_meta, MetaSrvEnv};
+use crate::manager::{commit_meta, IdCategory, MetaSrvEnv, SourceId};
 use crate::model::{
-    ActorId, BTreeMapTransaction, FragmentId, MetadataModel, TableFragments, ValTransaction,
+    ActorId, BTreeMapTransaction, DispatcherId, FragmentId, MetadataModel, TableFragments,
+    ValTransaction,
 };
 use crate::storage::{MetaStore, Transaction};
-use crate::stream::{actor_mapping_to_parallel_unit_mapping, SplitAssignment};
+use crate::stream::{
+    actor_mapping_from_bitmaps, actor_mapping_to_parallel_unit_mapping, build_vnode_mapping,
+    vnode_mapping_to_bitmaps, SplitAssignment,
+};
 use crate::MetaResult;
 
 pub struct FragmentManagerCore {
     table_fragments: BTreeMap<TableId, TableFragments>,
+    /// When the table fragments in `Creating` state started to be created. The ones loaded from
+    /// the meta store are considered to be started when the meta node starts.
+    creating_since: HashMap<TableId, Instant>,
+    /// The table and the fragment that each actor in `table_fragments` belongs to. Must be
+    /// updated along with every mutation to `table_fragments`.
+    actor_to_location: HashMap<ActorId, (TableId, FragmentId)>,
+    /// Vnode mappings pinned by [`FragmentManager::pin_version_for_scan`] and not released yet.
+    scan_pins: Arc<Mutex<ScanPins>>,
+    /// The recent changes to the vnode mappings notified to the frontends, for incremental sync.
+    change_log: FragmentChangeLog,
 }
 
 impl FragmentManagerCore {
+    fn new(table_fragments: BTreeMap<TableId, TableFragments>) -> Self {
+        let now = Instant::now();
+        let creating_since = table_fragments
+            .values()
+            .filter(|tf| tf.state() == State::Creating)
+            .map(|tf| (tf.table_id(), now))
+            .collect();
+        let mut core = Self {
+            table_fragments,
+            creating_since,
+            actor_to_location: HashMap::new(),
+            scan_pins: Default::default(),
+            change_log: Default::default(),
+        };
+        core.reindex_actors(core.table_fragments.keys().copied().collect_vec());
+        core
+    }
+
+    /// Get the table and the fragment that the actor belongs to.
+    pub fn get_fragment_by_actor(&self, actor_id: ActorId) -> Option<(TableId, FragmentId)> {
+        self.actor_to_location.get(&actor_id).copied()
+    }
+
+    /// Rebuild the entries of `actor_to_location` for the given tables, whose table fragments have
+    /// just been inserted, updated or removed.
+    fn reindex_actors(&mut self, table_ids: impl IntoIterator<Item = TableId>) {
+        let table_ids: HashSet<TableId> = table_ids.into_iter().collect();
+        if table_ids.is_empty() {
+            return;
+        }
+        self.actor_to_location
+            .retain(|_, (table_id, _)| !table_ids.contains(table_id));
+        for table_id in table_ids {
+            let Some(table_fragments) = self.table_fragments.get(&table_id) else {
+                continue;
+            };
+            for fragment in table_fragments.fragments.values() {
+                for actor in &fragment.actors {
+                    self.actor_to_location
+                        .insert(actor.actor_id, (table_id, fragment.fragment_id));
+                }
+            }
+        }
+    }
+
     /// List all fragment vnode mapping info.
     pub fn all_fragment_mappings(&self) -> impl Iterator<Item = ParallelUnitMapping> + '_ {
         self.table_fragments.values().flat_map(|table_fragments| {
@@ -62,6 +134,70 @@ impl FragmentManagerCore {
         })
     }
 
+    /// List the fragments pinned by each active [`ScanVersion`], by pin id.
+    pub fn active_scan_pins(&self) -> BTreeMap<u64, Vec<FragmentId>> {
+        self.scan_pins.lock().pins.clone()
+    }
+
+    /// Returns the number of inactive actors of each table still being created, i.e. the actors
+    /// not yet started by the first barrier. Tables without such actors are omitted.
+    pub fn list_creating_actors_per_table(&self) -> HashMap<TableId, usize> {
+        self.table_fragments
+            .values()
+            .filter(|table_fragments| table_fragments.state() == State::Creating)
+            .map(|table_fragments| {
+                (
+                    table_fragments.table_id(),
+                    table_fragments.actor_count_in_state(ActorState::Inactive),
+                )
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Returns the maximum number of vnodes of a single stateful fragment that are assigned to
+    /// the parallel units on `worker_id`. Comparing it across workers tells whether the states are
+    /// skewed.
+    pub fn max_vnode_count_per_worker(&self, worker_id: WorkerId) -> usize {
+        self.table_fragments
+            .values()
+            .flat_map(|table_fragments| {
+                let parallel_unit_ids: HashSet<ParallelUnitId> = table_fragments
+                    .actor_status
+                    .values()
+                    .filter_map(|status| status.parallel_unit.as_ref())
+                    .filter(|parallel_unit| parallel_unit.worker_node_id == worker_id)
+                    .map(|parallel_unit| parallel_unit.id)
+                    .collect();
+                table_fragments
+                    .fragments
+                    .values()
+                    .filter(|fragment| !fragment.state_table_ids.is_empty())
+                    .filter_map(|fragment| fragment.vnode_mapping.as_ref())
+                    .map(move |vnode_mapping| {
+                        decompress_data(&vnode_mapping.original_indices, &vnode_mapping.data)
+                            .into_iter()
+                            .filter(|parallel_unit_id| parallel_unit_ids.contains(parallel_unit_id))
+                            .count()
+                    })
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of actors on the worker, including the inactive ones.
+    pub fn count_worker_actors(&self, worker_id: WorkerId) -> usize {
+        self.table_fragments
+            .values()
+            .filter_map(|table_fragments| {
+                table_fragments
+                    .worker_actor_ids()
+                    .get(&worker_id)
+                    .map(Vec::len)
+            })
+            .sum()
+    }
+
     pub fn all_internal_tables(&self) -> impl Iterator<Item = &u32> + '_ {
         self.table_fragments.values().flat_map(|table_fragments| {
             table_fragments
@@ -72,11 +208,386 @@ impl FragmentManagerCore {
     }
 }
 
+/// Actors not in `table_fragments` but still having entries in its `actor_splits`.
+fn orphaned_actor_splits(table_fragments: &TableFragments) -> HashSet<ActorId> {
+    let actor_ids: HashSet<ActorId> = table_fragments.actor_ids().into_iter().collect();
+    table_fragments
+        .actor_splits
+        .keys()
+        .filter(|actor_id| !actor_ids.contains(actor_id))
+        .copied()
+        .collect()
+}
+
+/// Upstream actor id => downstream actors not in `all_actor_ids`, but still referred by the
+/// dispatchers of the upstream actor in `table_fragments`.
+fn dangling_dispatcher_targets(
+    table_fragments: &TableFragments,
+    all_actor_ids: &HashSet<ActorId>,
+) -> HashMap<ActorId, HashSet<ActorId>> {
+    let mut dangling_targets = HashMap::new();
+    for actor in table_fragments.fragments.values().flat_map(|f| &f.actors) {
+        let targets: HashSet<ActorId> = actor
+            .dispatcher
+            .iter()
+            .flat_map(|d| &d.downstream_actor_id)
+            .filter(|actor_id| !all_actor_ids.contains(actor_id))
+            .copied()
+            .collect();
+        if !targets.is_empty() {
+            dangling_targets.insert(actor.actor_id, targets);
+        }
+    }
+    dangling_targets
+}
+
+/// Reconstructs the status of the actors in `fragment` without one, by looking up the parallel
+/// unit of their vnodes in the vnode mapping of the fragment. Actors whose parallel unit cannot be
+/// determined are skipped.
+fn reconstruct_missing_actor_status(
+    fragment: &Fragment,
+    actor_status: &BTreeMap<ActorId, ActorStatus>,
+    state: ActorState,
+    parallel_units: &HashMap<ParallelUnitId, ParallelUnit>,
+) -> BTreeMap<ActorId, ActorStatus> {
+    let Some(vnode_mapping) = &fragment.vnode_mapping else {
+        return BTreeMap::new();
+    };
+    let vnode_mapping = decompress_data(&vnode_mapping.original_indices, &vnode_mapping.data);
+    let singleton_parallel_unit = vnode_mapping.iter().dedup().exactly_one().ok();
+
+    fragment
+        .actors
+        .iter()
+        .filter(|actor| !actor_status.contains_key(&actor.actor_id))
+        .filter_map(|actor| {
+            let parallel_unit_id = match &actor.vnode_bitmap {
+                Some(buffer) => {
+                    let vnode = Bitmap::from(buffer).iter().position(|set| set)?;
+                    vnode_mapping.get(vnode)?
+                }
+                None => singleton_parallel_unit?,
+            };
+            let status = ActorStatus {
+                parallel_unit: Some(parallel_units.get(parallel_unit_id)?.clone()),
+                state: state as i32,
+            };
+            Some((actor.actor_id, status))
+        })
+        .collect()
+}
+
+/// Checks that actor ids are unique both inside each of the given `TableFragments` and across all
+/// of them.
+fn verify_actor_id_uniqueness<'a>(
+    all_table_fragments: impl IntoIterator<Item = &'a TableFragments>,
+) -> MetaResult<()> {
+    let mut actor_ids = vec![];
+    for table_fragments in all_table_fragments {
+        if let Err(duplicates) = table_fragments.verify_actor_id_uniqueness() {
+            bail!(
+                "duplicate actor ids found in table fragments {}: {:?}",
+                table_fragments.table_id(),
+                duplicates
+            );
+        }
+        actor_ids.extend(table_fragments.actor_ids());
+    }
+    let duplicates = actor_ids.into_iter().duplicates().sorted().collect_vec();
+    if !duplicates.is_empty() {
+        bail!("duplicate actor ids found across tables: {:?}", duplicates);
+    }
+    Ok(())
+}
+
+/// The problems that [`FragmentManager::validate_topology`] may find in a `TableFragments`.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum TopologyError {
+    #[error("actors form a cycle: {0:?}")]
+    Cycle(Vec<ActorId>),
+
+    #[error("actor {actor_id} has unknown upstream actor {upstream_actor_id}")]
+    UnknownUpstreamActor {
+        actor_id: ActorId,
+        upstream_actor_id: ActorId,
+    },
+
+    #[error("sink fragment {0} has no actors")]
+    EmptySinkFragment(FragmentId),
+
+    #[error(
+        "source actor {actor_id} in fragment {fragment_id} has upstream {upstream_actor_ids:?}"
+    )]
+    SourceWithUpstream {
+        fragment_id: FragmentId,
+        actor_id: ActorId,
+        upstream_actor_ids: Vec<ActorId>,
+    },
+}
+
+/// Checks the topology of the actors in `table_fragments`. See
+/// [`FragmentManager::validate_topology`].
+fn check_topology(table_fragments: &TableFragments) -> Result<(), TopologyError> {
+    let actors: BTreeMap<ActorId, &StreamActor> = table_fragments
+        .fragments
+        .values()
+        .flat_map(|fragment| &fragment.actors)
+        .map(|actor| (actor.actor_id, actor))
+        .collect();
+
+    for fragment in table_fragments.fragments.values() {
+        if fragment.fragment_type() == FragmentType::Sink && fragment.actors.is_empty() {
+            return Err(TopologyError::EmptySinkFragment(fragment.fragment_id));
+        }
+        if fragment.fragment_type() == FragmentType::Source {
+            if let Some(actor) = fragment
+                .actors
+                .iter()
+                .find(|actor| !actor.upstream_actor_id.is_empty())
+            {
+                return Err(TopologyError::SourceWithUpstream {
+                    fragment_id: fragment.fragment_id,
+                    actor_id: actor.actor_id,
+                    upstream_actor_ids: actor.upstream_actor_id.clone(),
+                });
+            }
+        }
+    }
+
+    // actor id => downstream actor ids, linked by both the upstream actor ids of the downstream
+    // and the dispatchers of the upstream. Dispatchers may point to the actors of other tables
+    // that are built on this one, which are out of the scope of this check.
+    let mut downstreams: BTreeMap<ActorId, BTreeSet<ActorId>> = BTreeMap::new();
+    for (&actor_id, actor) in &actors {
+        for &upstream_actor_id in &actor.upstream_actor_id {
+            if !actors.contains_key(&upstream_actor_id) {
+                return Err(TopologyError::UnknownUpstreamActor {
+                    actor_id,
+                    upstream_actor_id,
+                });
+            }
+            downstreams
+                .entry(upstream_actor_id)
+                .or_default()
+                .insert(actor_id);
+        }
+        downstreams.entry(actor_id).or_default().extend(
+            actor
+                .dispatcher
+                .iter()
+                .flat_map(|dispatcher| &dispatcher.downstream_actor_id)
+                .filter(|downstream_actor_id| actors.contains_key(downstream_actor_id)),
+        );
+    }
+
+    match find_cycle(&downstreams) {
+        Some(cycle) => Err(TopologyError::Cycle(cycle)),
+        None => Ok(()),
+    }
+}
+
+/// Finds a cycle in the graph by depth-first search, and returns the actors on it in order.
+fn find_cycle(downstreams: &BTreeMap<ActorId, BTreeSet<ActorId>>) -> Option<Vec<ActorId>> {
+    fn visit(
+        actor_id: ActorId,
+        downstreams: &BTreeMap<ActorId, BTreeSet<ActorId>>,
+        path: &mut Vec<ActorId>,
+        visited: &mut HashSet<ActorId>,
+    ) -> Option<Vec<ActorId>> {
+        if let Some(pos) = path.iter().position(|&id| id == actor_id) {
+            return Some(path[pos..].to_vec());
+        }
+        if !visited.insert(actor_id) {
+            return None;
+        }
+        path.push(actor_id);
+        for &downstream_actor_id in downstreams.get(&actor_id).into_iter().flatten() {
+            if let Some(cycle) = visit(downstream_actor_id, downstreams, path, visited) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+
+    let mut visited = HashSet::new();
+    downstreams
+        .keys()
+        .find_map(|&actor_id| visit(actor_id, downstreams, &mut vec![], &mut visited))
+}
+
+/// The problems that [`FragmentManager::apply_source_split_assignment_validated`] and
+/// [`FragmentManager::check_split_assignment_actors`] may find in a split assignment.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum SplitAssignmentError {
+    #[error("fragment {0} not found")]
+    FragmentNotFound(FragmentId),
+
+    #[error("actors {actor_ids:?} of fragment {fragment_id} are missing in the assignment")]
+    MissingActors {
+        fragment_id: FragmentId,
+        actor_ids: Vec<ActorId>,
+    },
+
+    #[error("actors {actor_ids:?} do not belong to fragment {fragment_id}")]
+    UnknownActors {
+        fragment_id: FragmentId,
+        actor_ids: Vec<ActorId>,
+    },
+
+    #[error(
+        "actor {actor_id} of fragment {fragment_id} is assigned {split_count} splits, more than \
+         {max_splits_per_actor}"
+    )]
+    TooManySplits {
+        fragment_id: FragmentId,
+        actor_id: ActorId,
+        split_count: usize,
+        max_splits_per_actor: usize,
+    },
+
+    #[error("splits {0:?} are assigned to more than one actor")]
+    DuplicateSplits(Vec<SplitId>),
+
+    #[error(
+        "splits of fragment {fragment_id} are changed by the assignment, missing: {missing:?}, \
+         unexpected: {unexpected:?}"
+    )]
+    SplitsMismatch {
+        fragment_id: FragmentId,
+        missing: Vec<SplitId>,
+        unexpected: Vec<SplitId>,
+    },
+
+    #[error(
+        "actors {actor_ids:?} of fragment {fragment_id} are not running, the fragment may have \
+         been rescheduled after the assignment was derived"
+    )]
+    StaleActors {
+        fragment_id: FragmentId,
+        actor_ids: Vec<ActorId>,
+    },
+}
+
+/// Checks a split assignment against the current one of the fragments. See
+/// [`FragmentManager::apply_source_split_assignment_validated`].
+fn check_split_assignment(
+    table_fragments: &BTreeMap<TableId, TableFragments>,
+    split_assignment: &SplitAssignment,
+    max_splits_per_actor: usize,
+) -> Result<(), SplitAssignmentError> {
+    // Check the fragments in order so that the error is deterministic.
+    for (&fragment_id, actor_splits) in split_assignment.iter().sorted_by_key(|(id, _)| **id) {
+        let (table_fragment, fragment) = table_fragments
+            .values()
+            .find_map(|t| t.fragments.get(&fragment_id).map(|f| (t, f)))
+            .ok_or(SplitAssignmentError::FragmentNotFound(fragment_id))?;
+
+        let actor_ids: BTreeSet<ActorId> = fragment.actors.iter().map(|a| a.actor_id).collect();
+        let missing = actor_ids
+            .iter()
+            .filter(|actor_id| !actor_splits.contains_key(actor_id))
+            .copied()
+            .collect_vec();
+        if !missing.is_empty() {
+            return Err(SplitAssignmentError::MissingActors {
+                fragment_id,
+                actor_ids: missing,
+            });
+        }
+        let unknown = actor_splits
+            .keys()
+            .filter(|actor_id| !actor_ids.contains(actor_id))
+            .copied()
+            .sorted()
+            .collect_vec();
+        if !unknown.is_empty() {
+            return Err(SplitAssignmentError::UnknownActors {
+                fragment_id,
+                actor_ids: unknown,
+            });
+        }
+
+        for (&actor_id, splits) in actor_splits.iter().sorted_by_key(|(id, _)| **id) {
+            if splits.len() > max_splits_per_actor {
+                return Err(SplitAssignmentError::TooManySplits {
+                    fragment_id,
+                    actor_id,
+                    split_count: splits.len(),
+                    max_splits_per_actor,
+                });
+            }
+        }
+
+        let assigned = actor_splits
+            .values()
+            .flatten()
+            .map(|s| s.id())
+            .collect_vec();
+        let duplicates = assigned.iter().duplicates().cloned().sorted().collect_vec();
+        if !duplicates.is_empty() {
+            return Err(SplitAssignmentError::DuplicateSplits(duplicates));
+        }
+        let assigned: BTreeSet<SplitId> = assigned.into_iter().collect();
+        let current: BTreeSet<SplitId> = actor_ids
+            .iter()
+            .filter_map(|actor_id| table_fragment.actor_splits.get(actor_id))
+            .flatten()
+            .map(|s| s.id())
+            .collect();
+        if assigned != current {
+            return Err(SplitAssignmentError::SplitsMismatch {
+                fragment_id,
+                missing: current.difference(&assigned).cloned().collect(),
+                unexpected: assigned.difference(&current).cloned().collect(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the actors in a split assignment are the running actors of their fragments. See
+/// [`FragmentManager::check_split_assignment_actors`].
+fn check_split_assignment_running_actors(
+    table_fragments: &BTreeMap<TableId, TableFragments>,
+    split_assignment: &SplitAssignment,
+) -> Result<(), SplitAssignmentError> {
+    for (&fragment_id, actor_splits) in split_assignment.iter().sorted_by_key(|(id, _)| **id) {
+        let (table_fragment, fragment) = table_fragments
+            .values()
+            .find_map(|t| t.fragments.get(&fragment_id).map(|f| (t, f)))
+            .ok_or(SplitAssignmentError::FragmentNotFound(fragment_id))?;
+
+        let stale = actor_splits
+            .keys()
+            .filter(|actor_id| {
+                !fragment.actors.iter().any(|a| a.actor_id == **actor_id)
+                    || table_fragment
+                        .actor_status
+                        .get(*actor_id)
+                        .map_or(true, |s| s.state != ActorState::Running as i32)
+            })
+            .copied()
+            .sorted()
+            .collect_vec();
+        if !stale.is_empty() {
+            return Err(SplitAssignmentError::StaleActors {
+                fragment_id,
+                actor_ids: stale,
+            });
+        }
+    }
+    Ok(())
+}
+
 /// `FragmentManager` stores definition and status of fragment as well as the actors inside.
 pub struct FragmentManager<S: MetaStore> {
     env: MetaSrvEnv<S>,
 
     core: RwLock<FragmentManagerCore>,
+
+    /// Sends the changes committed to the meta store to the subscribers.
+    change_tx: broadcast::Sender<FragmentChangeEvent>,
 }
 
 pub struct ActorInfos {
@@ -102,117 +613,847 @@ pub struct BuildGraphInfo {
 
 pub type FragmentManagerRef<S> = Arc<FragmentManager<S>>;
 
+/// The fixed part of [`MigrationCost::expected_downtime_ms`], for the recovery that rebuilds the
+/// actors on their new workers.
+const MIGRATION_BASE_DOWNTIME_MS: u64 = 1000;
+/// The conservative rate at which the migrated actors load their states, in bytes per millisecond.
+const MIGRATION_STATE_BYTES_PER_MS: u64 = 10 * 1024;
+
+/// The estimated cost of an actor migration, computed by
+/// [`FragmentManager::compute_migration_cost`] without performing it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationCost {
+    /// The size of the states owned by the migrated actors, in proportion to their vnodes.
+    pub estimated_bytes_to_transfer: u64,
+    pub actors_affected: usize,
+    pub fragments_affected: usize,
+    pub vnodes_reassigned: usize,
+    /// A conservative estimate from `estimated_bytes_to_transfer`, zero if nothing is migrated.
+    pub expected_downtime_ms: u64,
+}
+
+/// Orphaned data in the meta store collected by [`FragmentManager::collect_gc_candidates`]. The
+/// caller may remove the entries it doesn't want to delete before passing it to
+/// [`FragmentManager::apply_gc`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GcCandidates {
+    /// Table fragments that have been in `Creating` state for longer than
+    /// [`crate::manager::MetaOpts::creating_table_fragments_gc_threshold_sec`].
+    pub stale_creating_tables: HashSet<TableId>,
+    /// table id => actors not in the table but still having entries in its `actor_splits`.
+    pub orphaned_actor_splits: HashMap<TableId, HashSet<ActorId>>,
+    /// table id => upstream actor id => downstream actors not existing in any table, but still
+    /// referred by the dispatchers of the upstream actor.
+    pub dangling_dispatcher_targets: HashMap<TableId, HashMap<ActorId, HashSet<ActorId>>>,
+}
+
+impl GcCandidates {
+    pub fn is_empty(&self) -> bool {
+        self.stale_creating_tables.is_empty()
+            && self.orphaned_actor_splits.is_empty()
+            && self.dangling_dispatcher_targets.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct ScanPins {
+    next_pin_id: u64,
+    /// pin id => fragments whose vnode mappings are pinned.
+    pins: BTreeMap<u64, Vec<FragmentId>>,
+}
+
+/// A change to the vnode mapping of a fragment, recorded in [`FragmentChangeLog`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MappingChange {
+    Add(ParallelUnitMapping),
+    Update(ParallelUnitMapping),
+    Remove(FragmentId),
+}
+
+impl MappingChange {
+    fn from_notification(operation: Operation, mapping: &ParallelUnitMapping) -> Self {
+        match operation {
+            Operation::Add => Self::Add(mapping.clone()),
+            Operation::Delete => Self::Remove(mapping.fragment_id),
+            _ => Self::Update(mapping.clone()),
+        }
+    }
+}
+
+/// The recent changes to the vnode mappings of fragments, each entry of which is the changes
+/// notified to the frontends at once, with a sequence number increasing from 1.
+#[derive(Default)]
+struct FragmentChangeLog {
+    entries: VecDeque<(u64, Vec<MappingChange>)>,
+    /// The sequence number of the last entry, or 0 if none has been appended.
+    last_seq: u64,
+}
+
+impl FragmentChangeLog {
+    const MAX_ENTRIES: usize = 1000;
+
+    fn append(&mut self, changes: Vec<MappingChange>) {
+        self.last_seq += 1;
+        self.entries.push_back((self.last_seq, changes));
+        if self.entries.len() > Self::MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Returns the changes of the entries after `seq`, or `None` if some of them have been
+    /// evicted, `seq` is 0, or `seq` is later than the last entry, e.g. it's from the log of a
+    /// previous meta node, so that they can't be applied incrementally.
+    fn changes_since(&self, seq: u64) -> Option<Vec<MappingChange>> {
+        let first_seq = self
+            .entries
+            .front()
+            .map_or(self.last_seq + 1, |(seq, _)| *seq);
+        if seq == 0 || seq + 1 < first_seq || seq > self.last_seq {
+            return None;
+        }
+        Some(
+            self.entries
+                .iter()
+                .filter(|(entry_seq, _)| *entry_seq > seq)
+                .flat_map(|(_, changes)| changes.iter().cloned())
+                .collect(),
+        )
+    }
+}
+
+/// The capacity of the channel of [`FragmentManager::subscribe_fragment_changes`].
+const FRAGMENT_CHANGE_CHANNEL_CAPACITY: usize = 1000;
+
+/// How the fragments in a [`FragmentChangeEvent`] are changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentChangeOperation {
+    /// The fragments of a streaming job are added, which is in `Creating` state.
+    Add,
+    /// The fragments are updated, e.g. their actors are rescheduled, migrated, or have their
+    /// dispatchers or splits changed.
+    Update,
+    /// The fragments of a streaming job are deleted.
+    Delete,
+}
+
+/// A change to the fragments of a streaming job committed to the meta store, sent to the
+/// subscribers of [`FragmentManager::subscribe_fragment_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentChangeEvent {
+    pub table_id: TableId,
+    pub operation: FragmentChangeOperation,
+    /// The changed fragments, in ascending order.
+    pub fragment_ids: Vec<FragmentId>,
+}
+
+impl FragmentChangeEvent {
+    /// A change to all fragments of `table_fragments`.
+    fn all(table_fragments: &TableFragments, operation: FragmentChangeOperation) -> Self {
+        Self {
+            table_id: table_fragments.table_id(),
+            operation,
+            fragment_ids: table_fragments.fragment_ids().collect(),
+        }
+    }
+
+    fn update(table_id: TableId, fragment_ids: impl IntoIterator<Item = FragmentId>) -> Self {
+        Self {
+            table_id,
+            operation: FragmentChangeOperation::Update,
+            fragment_ids: fragment_ids.into_iter().sorted().dedup().collect(),
+        }
+    }
+}
+
+/// The vnode mappings of fragments captured from the same version of the fragment manager by
+/// [`FragmentManager::pin_version_for_scan`], so that a batch query scanning all of them routes
+/// consistently even if some are rescheduled in the meantime. The pin is tracked until
+/// [`ScanVersion::release`] is called or the version is dropped.
+pub struct ScanVersion {
+    pin_id: u64,
+    mappings: HashMap<FragmentId, ParallelUnitMapping>,
+    scan_pins: Arc<Mutex<ScanPins>>,
+}
+
+impl ScanVersion {
+    pub fn pin_id(&self) -> u64 {
+        self.pin_id
+    }
+
+    pub fn mapping(&self, fragment_id: FragmentId) -> Option<&ParallelUnitMapping> {
+        self.mappings.get(&fragment_id)
+    }
+
+    pub fn mappings(&self) -> &HashMap<FragmentId, ParallelUnitMapping> {
+        &self.mappings
+    }
+
+    /// Signal that the scan is complete and stop tracking the pin.
+    pub fn release(self) {}
+}
+
+impl Drop for ScanVersion {
+    fn drop(&mut self) {
+        self.scan_pins.lock().pins.remove(&self.pin_id);
+    }
+}
+
+/// A kind of fix in [`FragmentRepairs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FragmentRepairKind {
+    DanglingDispatchers,
+    MissingActorStatus,
+    OrphanedActorSplits,
+}
+
+/// Fixes of inconsistent fragment metadata found by [`FragmentManager::collect_fragment_repairs`],
+/// which can be applied by [`FragmentManager::apply_fragment_repairs`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FragmentRepairs {
+    /// table id => upstream actor id => downstream actors not existing in any table, to be removed
+    /// from the dispatchers of the upstream actor.
+    pub dangling_dispatcher_targets: HashMap<TableId, HashMap<ActorId, HashSet<ActorId>>>,
+    /// table id => actor id => status reconstructed from the vnode mapping of the fragment, for
+    /// actors without an entry in `actor_status`.
+    pub missing_actor_status: HashMap<TableId, BTreeMap<ActorId, ActorStatus>>,
+    /// table id => actors not in the table but still having entries in its `actor_splits`, to be
+    /// removed.
+    pub orphaned_actor_splits: HashMap<TableId, HashSet<ActorId>>,
+}
+
+impl FragmentRepairs {
+    pub fn is_empty(&self) -> bool {
+        self.dangling_dispatcher_targets.is_empty()
+            && self.missing_actor_status.is_empty()
+            && self.orphaned_actor_splits.is_empty()
+    }
+
+    /// Only keep the fixes of the given kinds.
+    pub fn retain_kinds(&mut self, kinds: &HashSet<FragmentRepairKind>) {
+        if !kinds.contains(&FragmentRepairKind::DanglingDispatchers) {
+            self.dangling_dispatcher_targets.clear();
+        }
+        if !kinds.contains(&FragmentRepairKind::MissingActorStatus) {
+            self.missing_actor_status.clear();
+        }
+        if !kinds.contains(&FragmentRepairKind::OrphanedActorSplits) {
+            self.orphaned_actor_splits.clear();
+        }
+    }
+
+    /// Human-readable descriptions of the fixes, ordered by table.
+    pub fn describe(&self) -> Vec<String> {
+        let mut fixes = vec![];
+        for (table_id, targets) in &self.dangling_dispatcher_targets {
+            for (actor_id, downstream_actor_ids) in targets {
+                fixes.push((
+                    table_id.table_id,
+                    format!(
+                        "table {}: remove dangling dispatcher targets {:?} of actor {}",
+                        table_id,
+                        downstream_actor_ids.iter().sorted().collect_vec(),
+                        actor_id
+                    ),
+                ));
+            }
+        }
+        for (table_id, actor_status) in &self.missing_actor_status {
+            for (actor_id, status) in actor_status {
+                let parallel_unit = status.parallel_unit.as_ref().unwrap();
+                fixes.push((
+                    table_id.table_id,
+                    format!(
+                        "table {}: restore status of actor {} on parallel unit {} of worker {}",
+                        table_id, actor_id, parallel_unit.id, parallel_unit.worker_node_id
+                    ),
+                ));
+            }
+        }
+        for (table_id, actor_ids) in &self.orphaned_actor_splits {
+            fixes.push((
+                table_id.table_id,
+                format!(
+                    "table {}: remove the splits of orphaned actors {:?}",
+                    table_id,
+                    actor_ids.iter().sorted().collect_vec()
+                ),
+            ));
+        }
+        fixes
+            .into_iter()
+            .sorted()
+            .map(|(_, description)| description)
+            .collect()
+    }
+}
+
+/// A change of the streaming topology applied by [`FragmentManager::apply_topology_change`].
+///
+/// The changes driven by a barrier are split into the steps before the barrier is injected and
+/// after it is collected. The steps after the collection are never rolled back, since the compute
+/// nodes have already applied the change, so they must be validated before the barrier.
+#[derive(Debug)]
+pub enum TopologyChange {
+    /// Add the fragments of a new streaming job, in `Creating` state, before the
+    /// `CreateMaterializedView` barrier is injected.
+    CreateTable(TableFragments),
+    /// Remove the fragments of a streaming job whose `CreateMaterializedView` barrier failed.
+    CancelCreateTable(TableId),
+    /// Start the actors of a new streaming job and add the dispatchers to them in their upstream,
+    /// after the `CreateMaterializedView` barrier is collected.
+    PostCreateTable {
+        table_id: TableId,
+        dependent_table_actors: Vec<(TableId, HashMap<ActorId, Vec<Dispatcher>>)>,
+        split_assignment: SplitAssignment,
+    },
+    /// Mark the fragments of a streaming job as `Created`, after the chain actors catch up.
+    FinishCreateTable(TableId),
+    /// Drop the fragments of the streaming jobs, and the dispatchers to them in their upstream.
+    DropTables(HashSet<TableId>),
+    /// Update the fragments after the `RescheduleFragment` barrier is collected. The new actors
+    /// must have been added with [`FragmentManager::pre_apply_reschedules`], and the fragments
+    /// checked with [`FragmentManager::check_reschedulable`].
+    RescheduleFragments(HashMap<FragmentId, Reschedule>),
+    /// Move the actors to the parallel units of other workers during recovery.
+    MigrateActors {
+        migrate_map: HashMap<ActorId, WorkerId>,
+        node_map: HashMap<WorkerId, WorkerNode>,
+    },
+}
+
+impl TopologyChange {
+    fn name(&self) -> &'static str {
+        match self {
+            TopologyChange::CreateTable(_) => "create table",
+            TopologyChange::CancelCreateTable(_) => "cancel create table",
+            TopologyChange::PostCreateTable { .. } => "post create table",
+            TopologyChange::FinishCreateTable(_) => "finish create table",
+            TopologyChange::DropTables(_) => "drop tables",
+            TopologyChange::RescheduleFragments(_) => "reschedule fragments",
+            TopologyChange::MigrateActors { .. } => "migrate actors",
+        }
+    }
+}
+
 impl<S: MetaStore> FragmentManager<S>
 where
     S: MetaStore,
 {
     pub async fn new(env: MetaSrvEnv<S>) -> MetaResult<Self> {
-        let table_fragments = try_match_expand!(
+        let mut table_fragments = try_match_expand!(
             TableFragments::list(env.meta_store()).await,
             Ok,
             "TableFragments::list fail"
         )?;
 
+        // Rewrite the table fragments persisted in the legacy format, which stores the whole plan
+        // of every actor.
+        for tf in table_fragments
+            .iter_mut()
+            .filter(|tf| tf.is_legacy_plan_format())
+        {
+            tf.insert(env.meta_store()).await?;
+            tf.mark_plan_format_migrated();
+        }
+
         let table_fragments = table_fragments
             .into_iter()
             .map(|tf| (tf.table_id(), tf))
             .collect();
 
+        let (change_tx, _) = broadcast::channel(FRAGMENT_CHANGE_CHANNEL_CAPACITY);
+
         Ok(Self {
             env,
-            core: RwLock::new(FragmentManagerCore { table_fragments }),
+            core: RwLock::new(FragmentManagerCore::new(table_fragments)),
+            change_tx,
         })
     }
 
-    pub async fn get_fragment_read_guard(&self) -> RwLockReadGuard<'_, FragmentManagerCore> {
-        self.core.read().await
+    /// Subscribes to the changes to the fragments, so that the callers can push the updates of
+    /// actors to the workers instead of polling. An event is sent after each change is committed
+    /// to the meta store, in the order of commits, and only the changes after the subscription
+    /// are received.
+    ///
+    /// The channel is bounded by [`FRAGMENT_CHANGE_CHANNEL_CAPACITY`]. A slow receiver may miss
+    /// events, in which case `recv` returns [`broadcast::error::RecvError::Lagged`], and the
+    /// receiver must fall back to re-fetch all fragments with [`Self::list_table_fragments`].
+    pub fn subscribe_fragment_changes(&self) -> broadcast::Receiver<FragmentChangeEvent> {
+        self.change_tx.subscribe()
     }
 
-    pub async fn list_table_fragments(&self) -> MetaResult<Vec<TableFragments>> {
-        let map = &self.core.read().await.table_fragments;
+    fn notify_fragment_changes(&self, events: impl IntoIterator<Item = FragmentChangeEvent>) {
+        for event in events {
+            if event.fragment_ids.is_empty() {
+                continue;
+            }
+            // Fails only if there's no subscriber.
+            let _ = self.change_tx.send(event);
+        }
+    }
 
-        Ok(map.values().cloned().collect())
+    pub async fn get_fragment_read_guard(&self) -> RwLockReadGuard<'_, FragmentManagerCore> {
+        self.core.read().await
     }
 
-    pub async fn batch_update_table_fragments(
+    /// Capture the vnode mappings of all the given fragments under a single read lock, so that they
+    /// are from the same version. Fails if any of the fragments is not found.
+    pub async fn pin_version_for_scan(
         &self,
-        table_fragments: &[TableFragments],
-    ) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
-        if table_fragments
-            .iter()
-            .any(|tf| !map.contains_key(&tf.table_id()))
-        {
-            bail!("update table fragments fail, table not found");
-        }
-
-        let mut table_fragments_txn = BTreeMapTransaction::new(map);
-        table_fragments.iter().for_each(|tf| {
-            table_fragments_txn.insert(tf.table_id(), tf.clone());
-        });
-        commit_meta!(self, table_fragments_txn)?;
+        fragment_ids: &[FragmentId],
+    ) -> MetaResult<ScanVersion> {
+        let core = self.core.read().await;
 
-        for table_fragment in table_fragments {
-            self.notify_fragment_mapping(table_fragment, Operation::Update)
-                .await;
+        let mut mappings = HashMap::with_capacity(fragment_ids.len());
+        for &fragment_id in fragment_ids {
+            let mapping = core
+                .table_fragments
+                .values()
+                .find_map(|table_fragments| table_fragments.fragments.get(&fragment_id))
+                .and_then(|fragment| fragment.vnode_mapping.clone())
+                .with_context(|| format!("vnode mapping of fragment not found: {}", fragment_id))?;
+            mappings.insert(fragment_id, mapping);
         }
 
-        Ok(())
+        let pin_id = {
+            let mut scan_pins = core.scan_pins.lock();
+            let pin_id = scan_pins.next_pin_id;
+            scan_pins.next_pin_id += 1;
+            scan_pins.pins.insert(pin_id, fragment_ids.to_vec());
+            pin_id
+        };
+
+        Ok(ScanVersion {
+            pin_id,
+            mappings,
+            scan_pins: core.scan_pins.clone(),
+        })
     }
 
-    async fn notify_fragment_mapping(&self, table_fragment: &TableFragments, operation: Operation) {
-        for fragment in table_fragment.fragments.values() {
-            if !fragment.state_table_ids.is_empty() {
-                let mapping = fragment
-                    .vnode_mapping
-                    .clone()
-                    .expect("no data distribution found");
-                self.env
-                    .notification_manager()
-                    .notify_frontend(operation, Info::ParallelUnitMapping(mapping))
-                    .await;
-            }
-        }
+    /// Returns the sequence number of the latest change to the vnode mappings, and the changes
+    /// after `seq`, for the frontends to sync the mappings incrementally instead of with full
+    /// snapshots. If `seq` is 0, too old to be retained in the change log, or later than the
+    /// latest change, all current mappings are returned as [`MappingChange::Add`], which should
+    /// replace the mappings of the caller.
+    pub async fn get_changes_since(&self, seq: u64) -> (u64, Vec<MappingChange>) {
+        let core = self.core.read().await;
+        let changes = core.change_log.changes_since(seq).unwrap_or_else(|| {
+            core.all_fragment_mappings()
+                .map(MappingChange::Add)
+                .collect()
+        });
+        (core.change_log.last_seq, changes)
     }
 
-    pub async fn select_table_fragments_by_table_id(
-        &self,
-        table_id: &TableId,
-    ) -> MetaResult<TableFragments> {
+    pub async fn list_table_fragments(&self) -> MetaResult<Vec<TableFragments>> {
         let map = &self.core.read().await.table_fragments;
-        Ok(map
+
+        Ok(map.values().cloned().collect())
+    }
+
+    /// Returns the total number of vnodes across all stateful fragments of each table, i.e. the
+    /// number of partitions that the states of the table are distributed into.
+    pub async fn get_total_vnode_count(&self) -> HashMap<TableId, usize> {
+        let map = &self.core.read().await.table_fragments;
+        map.values()
+            .map(|table_fragments| {
+                let vnode_count = table_fragments
+                    .fragments
+                    .values()
+                    .filter(|fragment| !fragment.state_table_ids.is_empty())
+                    .filter_map(|fragment| fragment.vnode_mapping.as_ref())
+                    .map(|vnode_mapping| {
+                        vnode_mapping
+                            .original_indices
+                            .last()
+                            .map_or(0, |&last_idx| last_idx as usize + 1)
+                    })
+                    .sum();
+                (table_fragments.table_id(), vnode_count)
+            })
+            .collect()
+    }
+
+    /// Returns all tables with their numbers of fragments, the most complex first. Tables with
+    /// the same number of fragments are ordered by id.
+    pub async fn list_tables_by_fragment_count(&self) -> Vec<(TableId, usize)> {
+        let map = &self.core.read().await.table_fragments;
+        Self::sort_by_count_desc(
+            map.values()
+                .map(|table_fragments| {
+                    (table_fragments.table_id(), table_fragments.fragments.len())
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns all tables with their total numbers of actors, the most complex first. Tables with
+    /// the same number of actors are ordered by id.
+    pub async fn list_tables_by_actor_count(&self) -> Vec<(TableId, usize)> {
+        let map = &self.core.read().await.table_fragments;
+        Self::sort_by_count_desc(
+            map.values()
+                .map(|table_fragments| {
+                    let actor_count = table_fragments
+                        .fragments
+                        .values()
+                        .map(|fragment| fragment.actors.len())
+                        .sum();
+                    (table_fragments.table_id(), actor_count)
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the number of running actors of each table. Tables without running actors are
+    /// omitted.
+    pub async fn list_running_actors_per_table(&self) -> HashMap<TableId, usize> {
+        let map = &self.core.read().await.table_fragments;
+        map.values()
+            .map(|table_fragments| {
+                (
+                    table_fragments.table_id(),
+                    table_fragments.actor_count_in_state(ActorState::Running),
+                )
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Returns the numbers of the streaming jobs still creating and already created.
+    pub async fn count_streaming_jobs_by_state(&self) -> (usize, usize) {
+        let map = &self.core.read().await.table_fragments;
+        map.values().fold(
+            (0, 0),
+            |(creating, created), table_fragments| match table_fragments.state() {
+                State::Creating => (creating + 1, created),
+                State::Created => (creating, created + 1),
+                State::Unspecified => (creating, created),
+            },
+        )
+    }
+
+    fn sort_by_count_desc(mut counts: Vec<(TableId, usize)>) -> Vec<(TableId, usize)> {
+        counts.sort_by_key(|&(table_id, count)| (Reverse(count), table_id.table_id));
+        counts
+    }
+
+    /// Returns the id of the streaming job that each state table belongs to, including the
+    /// mview table itself.
+    pub async fn get_state_table_owners(&self) -> HashMap<u32, TableId> {
+        let map = &self.core.read().await.table_fragments;
+        map.values()
+            .flat_map(|table_fragments| {
+                table_fragments
+                    .all_table_ids()
+                    .map(|table_id| (table_id, table_fragments.table_id()))
+            })
+            .collect()
+    }
+
+    pub async fn batch_update_table_fragments(
+        &self,
+        table_fragments: &[TableFragments],
+    ) -> MetaResult<()> {
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
+        if table_fragments
+            .iter()
+            .any(|tf| !map.contains_key(&tf.table_id()))
+        {
+            bail!("update table fragments fail, table not found");
+        }
+
+        let mut table_fragments_txn = BTreeMapTransaction::new(map);
+        table_fragments.iter().for_each(|tf| {
+            table_fragments_txn.insert(tf.table_id(), tf.clone());
+        });
+        commit_meta!(self, table_fragments_txn)?;
+        core.reindex_actors(table_fragments.iter().map(|tf| tf.table_id()));
+        self.notify_fragment_changes(
+            table_fragments
+                .iter()
+                .map(|tf| FragmentChangeEvent::all(tf, FragmentChangeOperation::Update)),
+        );
+
+        let mappings = table_fragments
+            .iter()
+            .flat_map(Self::fragment_mappings_to_notify)
+            .collect();
+        self.update_parallel_unit_mapping_bulk_in(core, mappings)
+            .await
+    }
+
+    /// Updates the vnode mappings of the fragments in `mappings` in one transaction, and notifies
+    /// the frontends of them with a single notification, so that the batch scheduler never routes
+    /// with a partially updated set of mappings.
+    pub async fn update_parallel_unit_mapping_bulk(
+        &self,
+        mappings: Vec<ParallelUnitMapping>,
+    ) -> MetaResult<()> {
+        let mut guard = self.core.write().await;
+        self.update_parallel_unit_mapping_bulk_in(&mut guard, mappings)
+            .await
+    }
+
+    /// The same as `update_parallel_unit_mapping_bulk`, for callers already holding the write guard
+    /// of `core`. The mappings that are already stored, e.g. committed along with the whole table
+    /// fragments, are only notified.
+    async fn update_parallel_unit_mapping_bulk_in(
+        &self,
+        core: &mut FragmentManagerCore,
+        mappings: Vec<ParallelUnitMapping>,
+    ) -> MetaResult<()> {
+        let map = &mut core.table_fragments;
+        let fragment_to_table: HashMap<_, _> = map
+            .values()
+            .flat_map(|table_fragments| {
+                table_fragments
+                    .fragment_ids()
+                    .map(|fragment_id| (fragment_id, table_fragments.table_id()))
+            })
+            .collect();
+
+        let mut table_fragments = BTreeMapTransaction::new(map);
+        let mut updated_fragments: BTreeMap<TableId, Vec<FragmentId>> = BTreeMap::new();
+        for mapping in &mappings {
+            let table_id = fragment_to_table
+                .get(&mapping.fragment_id)
+                .with_context(|| format!("fragment not exist: id={}", mapping.fragment_id))?;
+            let stored_mapping = &table_fragments.get(table_id).unwrap().fragments
+                [&mapping.fragment_id]
+                .vnode_mapping;
+            if stored_mapping.as_ref() == Some(mapping) {
+                continue;
+            }
+            let mut table_fragment = table_fragments.get_mut(*table_id).unwrap();
+            table_fragment
+                .fragments
+                .get_mut(&mapping.fragment_id)
+                .unwrap()
+                .vnode_mapping = Some(mapping.clone());
+            updated_fragments
+                .entry(*table_id)
+                .or_default()
+                .push(mapping.fragment_id);
+        }
+        if !updated_fragments.is_empty() {
+            commit_meta!(self, table_fragments)?;
+            self.notify_fragment_changes(updated_fragments.into_iter().map(
+                |(table_id, fragment_ids)| FragmentChangeEvent::update(table_id, fragment_ids),
+            ));
+        }
+
+        self.notify_parallel_unit_mapping_bulk(core, Operation::Update, mappings)
+            .await;
+
+        Ok(())
+    }
+
+    /// Returns the vnode mappings of the fragments with state tables in `table_fragment`, which
+    /// the frontends need for scheduling batch queries.
+    fn fragment_mappings_to_notify(
+        table_fragment: &TableFragments,
+    ) -> impl Iterator<Item = ParallelUnitMapping> + '_ {
+        table_fragment
+            .fragments
+            .values()
+            .filter(|fragment| !fragment.state_table_ids.is_empty())
+            .map(|fragment| {
+                fragment
+                    .vnode_mapping
+                    .clone()
+                    .expect("no data distribution found")
+            })
+    }
+
+    async fn notify_fragment_mapping(
+        &self,
+        core: &mut FragmentManagerCore,
+        table_fragment: &TableFragments,
+        operation: Operation,
+    ) {
+        let mappings = Self::fragment_mappings_to_notify(table_fragment).collect();
+        self.notify_parallel_unit_mapping_bulk(core, operation, mappings)
+            .await;
+    }
+
+    /// Notifies the frontends of all `mappings` with a single notification, and records them in
+    /// the change log of `core` as a single entry.
+    async fn notify_parallel_unit_mapping_bulk(
+        &self,
+        core: &mut FragmentManagerCore,
+        operation: Operation,
+        mappings: Vec<ParallelUnitMapping>,
+    ) {
+        if mappings.is_empty() {
+            return;
+        }
+        core.change_log.append(
+            mappings
+                .iter()
+                .map(|mapping| MappingChange::from_notification(operation, mapping))
+                .collect(),
+        );
+        self.env
+            .notification_manager()
+            .notify_frontend(
+                operation,
+                Info::BatchParallelUnitMapping(BatchParallelUnitMapping { mappings }),
+            )
+            .await;
+    }
+
+    pub async fn select_table_fragments_by_table_id(
+        &self,
+        table_id: &TableId,
+    ) -> MetaResult<TableFragments> {
+        let map = &self.core.read().await.table_fragments;
+        Ok(map
             .get(table_id)
             .cloned()
             .context(format!("table_fragment not exist: id={}", table_id))?)
     }
 
+    /// Checks that the actors in `table_fragments` form a valid streaming graph:
+    /// - the actors linked by upstream actor ids and dispatchers form a DAG;
+    /// - every upstream actor id refers to an actor in `table_fragments`;
+    /// - every sink fragment has at least one actor;
+    /// - the actors of source fragments have no upstream actors.
+    pub fn validate_topology(table_fragments: &TableFragments) -> MetaResult<()> {
+        check_topology(table_fragments).map_err(|e| {
+            anyhow!(e)
+                .context(format!(
+                    "invalid topology of table fragments {}",
+                    table_fragments.table_id()
+                ))
+                .into()
+        })
+    }
+
+    /// Applies a change of the streaming topology. This is the entry of the DDL operations, which
+    /// routes the change to the methods below in the right order.
+    ///
+    /// Each change is committed to the meta store in one transaction, so a failed change leaves
+    /// the fragments unchanged.
+    pub async fn apply_topology_change(&self, change: TopologyChange) -> MetaResult<()> {
+        let name = change.name();
+        let result = match change {
+            TopologyChange::CreateTable(table_fragments) => {
+                self.start_create_table_fragments(table_fragments).await
+            }
+            TopologyChange::CancelCreateTable(table_id) => {
+                self.cancel_create_table_fragments(&table_id).await
+            }
+            TopologyChange::PostCreateTable {
+                table_id,
+                dependent_table_actors,
+                split_assignment,
+            } => {
+                self.post_create_table_fragments(
+                    &table_id,
+                    dependent_table_actors,
+                    split_assignment,
+                )
+                .await
+            }
+            TopologyChange::FinishCreateTable(table_id) => {
+                self.mark_table_fragments_created(table_id).await
+            }
+            TopologyChange::DropTables(table_ids) => {
+                self.drop_table_fragments_vec(&table_ids).await
+            }
+            TopologyChange::RescheduleFragments(reschedules) => {
+                self.post_apply_reschedules(reschedules).await
+            }
+            TopologyChange::MigrateActors {
+                migrate_map,
+                node_map,
+            } => self.migrate_actors(&migrate_map, &node_map).await,
+        };
+
+        if let Err(e) = &result {
+            tracing::warn!("failed to apply topology change \"{}\": {}", name, e);
+        }
+        result
+    }
+
+    /// Checks that the fragments belong to the streaming jobs that have been created, which must
+    /// hold before the `RescheduleFragment` barrier is injected.
+    pub async fn check_reschedulable(
+        &self,
+        fragment_ids: impl Iterator<Item = &FragmentId>,
+    ) -> MetaResult<()> {
+        let map = &self.core.read().await.table_fragments;
+        for fragment_id in fragment_ids {
+            let table_fragments = map
+                .values()
+                .find(|table_fragments| table_fragments.fragments.contains_key(fragment_id))
+                .context(format!("fragment not exist: id={}", fragment_id))?;
+            if table_fragments.state() != State::Created {
+                bail!(
+                    "fragment {} of table {} is not created yet",
+                    fragment_id,
+                    table_fragments.table_id()
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Start create a new `TableFragments` and insert it into meta store, currently the actors'
     /// state is `ActorState::Inactive` and the table fragments' state is `State::Creating`.
     pub async fn start_create_table_fragments(
         &self,
         table_fragment: TableFragments,
     ) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
         let table_id = table_fragment.table_id();
         if map.contains_key(&table_id) {
             bail!("table_fragment already exist: id={}", table_id);
         }
 
+        Self::validate_topology(&table_fragment)?;
+        if cfg!(debug_assertions) {
+            verify_actor_id_uniqueness(map.values().chain([&table_fragment]))?;
+        }
+
+        let event = FragmentChangeEvent::all(&table_fragment, FragmentChangeOperation::Add);
         let mut table_fragments = BTreeMapTransaction::new(map);
         table_fragments.insert(table_id, table_fragment);
-        commit_meta!(self, table_fragments)
+        commit_meta!(self, table_fragments)?;
+        core.creating_since.insert(table_id, Instant::now());
+        core.reindex_actors([table_id]);
+        self.notify_fragment_changes([event]);
+        Ok(())
     }
 
     /// Cancel creation of a new `TableFragments` and delete it from meta store.
     pub async fn cancel_create_table_fragments(&self, table_id: &TableId) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
-        if !map.contains_key(table_id) {
-            tracing::warn!("table_fragment cleaned: id={}", table_id);
-        }
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
+        let event = match map.get(table_id) {
+            Some(table_fragments) => Some(FragmentChangeEvent::all(
+                table_fragments,
+                FragmentChangeOperation::Delete,
+            )),
+            None => {
+                tracing::warn!("table_fragment cleaned: id={}", table_id);
+                None
+            }
+        };
 
         let mut table_fragments = BTreeMapTransaction::new(map);
         table_fragments.remove(*table_id);
-        commit_meta!(self, table_fragments)
+        commit_meta!(self, table_fragments)?;
+        core.creating_since.remove(table_id);
+        core.reindex_actors([*table_id]);
+        self.notify_fragment_changes(event);
+        Ok(())
     }
 
     /// Called after the barrier collection of `CreateMaterializedView` command, which updates the
@@ -227,7 +1468,9 @@ where
         dependent_table_actors: Vec<(TableId, HashMap<ActorId, Vec<Dispatcher>>)>,
         split_assignment: SplitAssignment,
     ) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
 
         let mut table_fragments = BTreeMapTransaction::new(map);
         let mut table_fragment = table_fragments
@@ -238,6 +1481,10 @@ where
         table_fragment.update_actors_state(ActorState::Running);
         table_fragment.set_actor_splits_by_split_assignment(split_assignment);
         let table_fragment = table_fragment.clone();
+        let mut events = vec![FragmentChangeEvent::all(
+            &table_fragment,
+            FragmentChangeOperation::Update,
+        )];
 
         for (dependent_table_id, mut new_dispatchers) in dependent_table_actors {
             let mut dependent_table =
@@ -247,17 +1494,24 @@ where
                         "dependent table_fragment not exist: id={}",
                         dependent_table_id
                     ))?;
-            for fragment in dependent_table.fragments.values_mut() {
+            let mut updated_fragment_ids = vec![];
+            for (fragment_id, fragment) in &mut dependent_table.fragments {
                 for actor in &mut fragment.actors {
                     // Extend new dispatchers to table fragments.
                     if let Some(new_dispatchers) = new_dispatchers.remove(&actor.actor_id) {
                         actor.dispatcher.extend(new_dispatchers);
+                        updated_fragment_ids.push(*fragment_id);
                     }
                 }
             }
+            events.push(FragmentChangeEvent::update(
+                dependent_table_id,
+                updated_fragment_ids,
+            ));
         }
         commit_meta!(self, table_fragments)?;
-        self.notify_fragment_mapping(&table_fragment, Operation::Add)
+        self.notify_fragment_changes(events);
+        self.notify_fragment_mapping(core, &table_fragment, Operation::Add)
             .await;
 
         Ok(())
@@ -266,7 +1520,9 @@ where
     /// Called after the finish of `CreateMaterializedView` command, i.e., materialized view is
     /// completely created, which updates the state from `Creating` to `Created`.
     pub async fn mark_table_fragments_created(&self, table_id: TableId) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
 
         let mut table_fragments = BTreeMapTransaction::new(map);
         let mut table_fragment = table_fragments
@@ -275,59 +1531,397 @@ where
 
         assert_eq!(table_fragment.state(), State::Creating);
         table_fragment.set_state(State::Created);
-        commit_meta!(self, table_fragments)
+        let event = FragmentChangeEvent::all(&table_fragment, FragmentChangeOperation::Update);
+        commit_meta!(self, table_fragments)?;
+        core.creating_since.remove(&table_id);
+        self.notify_fragment_changes([event]);
+        Ok(())
     }
 
     /// Drop table fragments info and remove downstream actor infos in fragments from its dependent
     /// tables.
     pub async fn drop_table_fragments_vec(&self, table_ids: &HashSet<TableId>) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
         let to_delete_table_fragments = table_ids
             .iter()
             .filter_map(|table_id| map.get(table_id).cloned())
             .collect_vec();
 
         let mut table_fragments = BTreeMapTransaction::new(map);
+        let mut events = vec![];
+        let mut updated_fragments: BTreeMap<TableId, Vec<FragmentId>> = BTreeMap::new();
         for table_fragment in &to_delete_table_fragments {
             table_fragments.remove(table_fragment.table_id());
+            events.push(FragmentChangeEvent::all(
+                table_fragment,
+                FragmentChangeOperation::Delete,
+            ));
             let chain_actor_ids = table_fragment.chain_actor_ids();
-            let dependent_table_ids = table_fragment.dependent_table_ids();
-            for dependent_table_id in dependent_table_ids {
-                if table_ids.contains(&dependent_table_id) {
+            for upstream_table_id in table_fragment.upstream_table_ids() {
+                if table_ids.contains(&upstream_table_id) {
                     continue;
                 }
-                let mut dependent_table =
-                    table_fragments
-                        .get_mut(dependent_table_id)
-                        .context(format!(
-                            "dependent table_fragment not exist: id={}",
-                            dependent_table_id
-                        ))?;
+                let mut upstream_table =
+                    table_fragments.get_mut(upstream_table_id).context(format!(
+                        "upstream table_fragment not exist: id={}",
+                        upstream_table_id
+                    ))?;
 
-                dependent_table
+                let sink_fragments = upstream_table
                     .fragments
                     .values_mut()
-                    .filter(|f| f.fragment_type() == FragmentType::Sink)
-                    .flat_map(|f| &mut f.actors)
-                    .for_each(|a| {
+                    .filter(|f| f.fragment_type() == FragmentType::Sink);
+                for fragment in sink_fragments {
+                    fragment.actors.iter_mut().for_each(|a| {
                         a.dispatcher.retain_mut(|d| {
                             d.downstream_actor_id
                                 .retain(|x| !chain_actor_ids.contains(x));
                             !d.downstream_actor_id.is_empty()
                         })
                     });
+                    updated_fragments
+                        .entry(upstream_table_id)
+                        .or_default()
+                        .push(fragment.fragment_id);
+                }
+            }
+        }
+        commit_meta!(self, table_fragments)?;
+        core.creating_since
+            .retain(|table_id, _| !table_ids.contains(table_id));
+        core.reindex_actors(table_ids.iter().copied());
+        events.extend(
+            updated_fragments
+                .into_iter()
+                .map(|(table_id, fragment_ids)| {
+                    FragmentChangeEvent::update(table_id, fragment_ids)
+                }),
+        );
+        self.notify_fragment_changes(events);
+
+        for table_fragments in to_delete_table_fragments {
+            self.notify_fragment_mapping(core, &table_fragments, Operation::Delete)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Collect the orphaned data left in the meta store by aborted DDLs and crash recovery. Nothing
+    /// is deleted until the candidates are passed to [`Self::apply_gc`].
+    pub async fn collect_gc_candidates(&self) -> MetaResult<GcCandidates> {
+        let core = self.core.read().await;
+        let threshold =
+            Duration::from_secs(self.env.opts.creating_table_fragments_gc_threshold_sec);
+        let all_actor_ids: HashSet<ActorId> = core
+            .table_fragments
+            .values()
+            .flat_map(|table_fragments| table_fragments.actor_ids())
+            .collect();
+
+        let mut candidates = GcCandidates::default();
+        for (&table_id, table_fragments) in &core.table_fragments {
+            if table_fragments.state() == State::Creating
+                && core
+                    .creating_since
+                    .get(&table_id)
+                    .map_or(true, |since| since.elapsed() >= threshold)
+            {
+                candidates.stale_creating_tables.insert(table_id);
+            }
+
+            let orphaned_actor_ids = orphaned_actor_splits(table_fragments);
+            if !orphaned_actor_ids.is_empty() {
+                candidates
+                    .orphaned_actor_splits
+                    .insert(table_id, orphaned_actor_ids);
+            }
+
+            let dangling_targets = dangling_dispatcher_targets(table_fragments, &all_actor_ids);
+            if !dangling_targets.is_empty() {
+                candidates
+                    .dangling_dispatcher_targets
+                    .insert(table_id, dangling_targets);
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// Delete the orphaned data collected by [`Self::collect_gc_candidates`]. Candidates that are
+    /// no longer orphaned, e.g. the table has been created in the meantime, are skipped. The
+    /// dispatchers to the actors of the deleted table fragments are removed as well.
+    ///
+    /// Note that only the fragments are deleted here, the catalog of the stale creating tables
+    /// should be cleaned by the caller.
+    pub async fn apply_gc(&self, candidates: GcCandidates) -> MetaResult<()> {
+        if candidates.is_empty() {
+            return Ok(());
+        }
+
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
+        let to_delete_table_fragments = candidates
+            .stale_creating_tables
+            .iter()
+            .filter_map(|table_id| map.get(table_id))
+            .filter(|table_fragments| table_fragments.state() == State::Creating)
+            .cloned()
+            .collect_vec();
+        let deleted_table_ids: HashSet<TableId> = to_delete_table_fragments
+            .iter()
+            .map(|table_fragments| table_fragments.table_id())
+            .collect();
+        let deleted_actor_ids: HashSet<ActorId> = to_delete_table_fragments
+            .iter()
+            .flat_map(|table_fragments| table_fragments.actor_ids())
+            .collect();
+        let remaining_table_ids = map
+            .keys()
+            .filter(|table_id| !deleted_table_ids.contains(table_id))
+            .copied()
+            .collect_vec();
+        let existing_actor_ids: HashSet<ActorId> = remaining_table_ids
+            .iter()
+            .flat_map(|table_id| map[table_id].actor_ids())
+            .collect();
+
+        let mut table_fragments = BTreeMapTransaction::new(map);
+        for &table_id in &deleted_table_ids {
+            table_fragments.remove(table_id);
+        }
+        let mut events = to_delete_table_fragments
+            .iter()
+            .map(|table_fragments| {
+                FragmentChangeEvent::all(table_fragments, FragmentChangeOperation::Delete)
+            })
+            .collect_vec();
+        for table_id in remaining_table_ids {
+            let orphaned_actor_ids = candidates.orphaned_actor_splits.get(&table_id);
+            let dangling_targets = candidates.dangling_dispatcher_targets.get(&table_id);
+            let is_dangling = |upstream_actor_id: ActorId, downstream_actor_id: &ActorId| {
+                !existing_actor_ids.contains(downstream_actor_id)
+                    && (deleted_actor_ids.contains(downstream_actor_id)
+                        || dangling_targets
+                            .and_then(|targets| targets.get(&upstream_actor_id))
+                            .map_or(false, |targets| targets.contains(downstream_actor_id)))
+            };
+
+            let table_fragment = table_fragments.get(&table_id).unwrap();
+            let actor_ids: HashSet<ActorId> = table_fragment.actor_ids().into_iter().collect();
+            let is_orphaned = |actor_id: &ActorId| {
+                !actor_ids.contains(actor_id)
+                    && orphaned_actor_ids.map_or(false, |ids| ids.contains(actor_id))
+            };
+            let need_update = table_fragment.actor_splits.keys().any(is_orphaned)
+                || table_fragment
+                    .fragments
+                    .values()
+                    .flat_map(|f| &f.actors)
+                    .any(|a| {
+                        a.dispatcher
+                            .iter()
+                            .flat_map(|d| &d.downstream_actor_id)
+                            .any(|x| is_dangling(a.actor_id, x))
+                    });
+            if !need_update {
+                continue;
             }
+
+            let mut table_fragment = table_fragments.get_mut(table_id).unwrap();
+            table_fragment
+                .actor_splits
+                .retain(|actor_id, _| !is_orphaned(actor_id));
+            table_fragment
+                .fragments
+                .values_mut()
+                .flat_map(|f| &mut f.actors)
+                .for_each(|a| {
+                    let actor_id = a.actor_id;
+                    a.dispatcher.retain_mut(|d| {
+                        d.downstream_actor_id.retain(|x| !is_dangling(actor_id, x));
+                        !d.downstream_actor_id.is_empty()
+                    })
+                });
+            events.push(FragmentChangeEvent::all(
+                &table_fragment,
+                FragmentChangeOperation::Update,
+            ));
         }
         commit_meta!(self, table_fragments)?;
+        core.creating_since
+            .retain(|table_id, _| !deleted_table_ids.contains(table_id));
+        core.reindex_actors(deleted_table_ids);
+        self.notify_fragment_changes(events);
 
         for table_fragments in to_delete_table_fragments {
-            self.notify_fragment_mapping(&table_fragments, Operation::Delete)
+            tracing::info!(
+                "table fragments in creating state are garbage collected: id={}",
+                table_fragments.table_id()
+            );
+            self.notify_fragment_mapping(core, &table_fragments, Operation::Delete)
                 .await;
         }
 
         Ok(())
     }
 
+    /// Check the fragments for inconsistent metadata left by bugs, and collect the fixes. Missing
+    /// actor status is reconstructed with the given parallel units. Nothing is changed until the
+    /// fixes are passed to [`Self::apply_fragment_repairs`].
+    pub async fn collect_fragment_repairs(
+        &self,
+        parallel_units: &HashMap<ParallelUnitId, ParallelUnit>,
+    ) -> FragmentRepairs {
+        let core = self.core.read().await;
+        let all_actor_ids: HashSet<ActorId> = core
+            .table_fragments
+            .values()
+            .flat_map(|table_fragments| table_fragments.actor_ids())
+            .collect();
+
+        let mut repairs = FragmentRepairs::default();
+        for (&table_id, table_fragments) in &core.table_fragments {
+            let dangling_targets = dangling_dispatcher_targets(table_fragments, &all_actor_ids);
+            if !dangling_targets.is_empty() {
+                repairs
+                    .dangling_dispatcher_targets
+                    .insert(table_id, dangling_targets);
+            }
+
+            let state = if table_fragments.state() == State::Created {
+                ActorState::Running
+            } else {
+                ActorState::Inactive
+            };
+            let missing_actor_status: BTreeMap<_, _> = table_fragments
+                .fragments
+                .values()
+                .flat_map(|fragment| {
+                    reconstruct_missing_actor_status(
+                        fragment,
+                        &table_fragments.actor_status,
+                        state,
+                        parallel_units,
+                    )
+                })
+                .collect();
+            if !missing_actor_status.is_empty() {
+                repairs
+                    .missing_actor_status
+                    .insert(table_id, missing_actor_status);
+            }
+
+            let orphaned_actor_ids = orphaned_actor_splits(table_fragments);
+            if !orphaned_actor_ids.is_empty() {
+                repairs
+                    .orphaned_actor_splits
+                    .insert(table_id, orphaned_actor_ids);
+            }
+        }
+        repairs
+    }
+
+    /// Apply the fixes collected by [`Self::collect_fragment_repairs`] in one transaction. Fixes
+    /// that are no longer needed, e.g. the dangling actor has been created in the meantime, are
+    /// skipped.
+    ///
+    /// The fixes change the fragments under running actors, so the caller must pause the stream
+    /// graph before calling this.
+    ///
+    /// Dangling targets of hash dispatchers are refused, since the vnodes mapped to them have no
+    /// other owner to be remapped to. Nothing is changed in this case.
+    pub async fn apply_fragment_repairs(&self, repairs: FragmentRepairs) -> MetaResult<()> {
+        if repairs.is_empty() {
+            return Ok(());
+        }
+
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
+        let all_actor_ids: HashSet<ActorId> = map
+            .values()
+            .flat_map(|table_fragments| table_fragments.actor_ids())
+            .collect();
+        let table_ids: BTreeSet<TableId> = repairs
+            .dangling_dispatcher_targets
+            .keys()
+            .chain(repairs.missing_actor_status.keys())
+            .chain(repairs.orphaned_actor_splits.keys())
+            .copied()
+            .collect();
+
+        let mut table_fragments = BTreeMapTransaction::new(map);
+        let mut events = vec![];
+        for table_id in table_ids {
+            let Some(mut table_fragment) = table_fragments.get_mut(table_id) else {
+                continue;
+            };
+            events.push(FragmentChangeEvent::all(
+                &table_fragment,
+                FragmentChangeOperation::Update,
+            ));
+            if let Some(targets) = repairs.dangling_dispatcher_targets.get(&table_id) {
+                for actor in table_fragment
+                    .fragments
+                    .values_mut()
+                    .flat_map(|f| &mut f.actors)
+                {
+                    let Some(targets) = targets.get(&actor.actor_id) else {
+                        continue;
+                    };
+                    for dispatcher in &actor.dispatcher {
+                        if dispatcher.r#type() == DispatcherType::Hash
+                            && let Some(actor_id) = dispatcher.downstream_actor_id.iter().find(
+                                |actor_id| {
+                                    targets.contains(actor_id) && !all_actor_ids.contains(actor_id)
+                                },
+                            )
+                        {
+                            bail!(
+                                "cannot repair the dangling target {} of the hash dispatcher {} of actor {}",
+                                actor_id,
+                                dispatcher.dispatcher_id,
+                                actor.actor_id
+                            );
+                        }
+                    }
+                    actor.dispatcher.retain_mut(|d| {
+                        d.downstream_actor_id.retain(|actor_id| {
+                            !targets.contains(actor_id) || all_actor_ids.contains(actor_id)
+                        });
+                        !d.downstream_actor_id.is_empty()
+                    });
+                }
+            }
+            if let Some(actor_status) = repairs.missing_actor_status.get(&table_id) {
+                let actor_ids: HashSet<ActorId> = table_fragment.actor_ids().into_iter().collect();
+                for (actor_id, status) in actor_status {
+                    if actor_ids.contains(actor_id) {
+                        table_fragment
+                            .actor_status
+                            .entry(*actor_id)
+                            .or_insert_with(|| status.clone());
+                    }
+                }
+            }
+            if let Some(orphaned_actor_ids) = repairs.orphaned_actor_splits.get(&table_id) {
+                let actor_ids: HashSet<ActorId> = table_fragment.actor_ids().into_iter().collect();
+                table_fragment.actor_splits.retain(|actor_id, _| {
+                    !orphaned_actor_ids.contains(actor_id) || actor_ids.contains(actor_id)
+                });
+            }
+        }
+        commit_meta!(self, table_fragments)?;
+        self.notify_fragment_changes(events);
+
+        tracing::info!("fragment repairs applied: {:?}", repairs.describe());
+        Ok(())
+    }
+
     /// Used in [`crate::barrier::GlobalBarrierManager`], load all actor that need to be sent or
     /// collected
     pub async fn load_all_actors(
@@ -369,20 +1963,87 @@ where
         }
     }
 
-    /// Used in [`crate::barrier::GlobalBarrierManager`]
-    /// migrate actors and update fragments, generate migrate info
-    pub async fn migrate_actors(
+    /// Estimates the cost of [`Self::migrate_actors`] with `migrate_map`, without performing it.
+    /// `state_table_sizes` is the estimated size of each state table, e.g. the write stats of the
+    /// hummock manager. Actors already on their target workers are not counted.
+    pub async fn compute_migration_cost(
         &self,
         migrate_map: &HashMap<ActorId, WorkerId>,
-        node_map: &HashMap<WorkerId, WorkerNode>,
-    ) -> MetaResult<()> {
-        let mut parallel_unit_migrate_map = HashMap::new();
-        let mut pu_map: HashMap<WorkerId, Vec<&ParallelUnit>> = node_map
-            .iter()
-            .map(|(&worker_id, worker)| (worker_id, worker.parallel_units.iter().collect_vec()))
-            .collect();
-
-        // update actor status and generate pu to pu migrate info
+        state_table_sizes: &HashMap<u32, TableStats>,
+    ) -> MigrationCost {
+        let map = &self.core.read().await.table_fragments;
+        let mut cost = MigrationCost::default();
+        for table_fragments in map.values() {
+            for fragment in table_fragments.fragments.values() {
+                let migrated_parallel_units: HashSet<ParallelUnitId> = fragment
+                    .actors
+                    .iter()
+                    .filter_map(|actor| {
+                        let target = migrate_map.get(&actor.actor_id)?;
+                        let parallel_unit = table_fragments
+                            .actor_status
+                            .get(&actor.actor_id)?
+                            .parallel_unit
+                            .as_ref()?;
+                        (parallel_unit.worker_node_id != *target).then_some(parallel_unit.id)
+                    })
+                    .collect();
+                if migrated_parallel_units.is_empty() {
+                    continue;
+                }
+                cost.actors_affected += migrated_parallel_units.len();
+                cost.fragments_affected += 1;
+
+                // The share of the states of the fragment owned by the migrated actors.
+                let (migrated, total) = match &fragment.vnode_mapping {
+                    Some(vnode_mapping) => {
+                        let vnodes =
+                            decompress_data(&vnode_mapping.original_indices, &vnode_mapping.data);
+                        let migrated = vnodes
+                            .iter()
+                            .filter(|parallel_unit_id| {
+                                migrated_parallel_units.contains(parallel_unit_id)
+                            })
+                            .count();
+                        cost.vnodes_reassigned += migrated;
+                        (migrated, vnodes.len())
+                    }
+                    None => (migrated_parallel_units.len(), fragment.actors.len()),
+                };
+                let state_bytes: u64 = fragment
+                    .state_table_ids
+                    .iter()
+                    .filter_map(|table_id| state_table_sizes.get(table_id))
+                    .map(|stats| stats.total_bytes)
+                    .sum();
+                if total > 0 {
+                    cost.estimated_bytes_to_transfer +=
+                        (state_bytes as u128 * migrated as u128 / total as u128) as u64;
+                }
+            }
+        }
+        if cost.actors_affected > 0 {
+            cost.expected_downtime_ms = MIGRATION_BASE_DOWNTIME_MS
+                + (cost.estimated_bytes_to_transfer + MIGRATION_STATE_BYTES_PER_MS - 1)
+                    / MIGRATION_STATE_BYTES_PER_MS;
+        }
+        cost
+    }
+
+    /// Used in [`crate::barrier::GlobalBarrierManager`]
+    /// migrate actors and update fragments, generate migrate info
+    pub async fn migrate_actors(
+        &self,
+        migrate_map: &HashMap<ActorId, WorkerId>,
+        node_map: &HashMap<WorkerId, WorkerNode>,
+    ) -> MetaResult<()> {
+        let mut parallel_unit_migrate_map = HashMap::new();
+        let mut pu_map: HashMap<WorkerId, Vec<&ParallelUnit>> = node_map
+            .iter()
+            .map(|(&worker_id, worker)| (worker_id, worker.parallel_units.iter().collect_vec()))
+            .collect();
+
+        // update actor status and generate pu to pu migrate info
         let mut table_fragments = self.list_table_fragments().await?;
         let mut new_fragments = Vec::new();
         table_fragments.iter_mut().for_each(|fragment| {
@@ -453,6 +2114,41 @@ where
         split_assignment: &SplitAssignment,
     ) -> MetaResult<()> {
         let map = &mut self.core.write().await.table_fragments;
+        self.commit_split_assignment(map, split_assignment).await
+    }
+
+    /// Applies a split assignment that moves the current splits of the source fragments among
+    /// their actors. Before committing, it checks that:
+    /// - every actor of the affected fragments is in the assignment;
+    /// - no actor is assigned more than `max_splits_per_actor` splits;
+    /// - the splits assigned to each fragment are exactly its current splits.
+    pub async fn apply_source_split_assignment_validated(
+        &self,
+        split_assignment: SplitAssignment,
+    ) -> MetaResult<()> {
+        let map = &mut self.core.write().await.table_fragments;
+        check_split_assignment(map, &split_assignment, self.env.opts.max_splits_per_actor)?;
+        self.commit_split_assignment(map, &split_assignment).await
+    }
+
+    /// Checks that a split assignment only refers to the running actors of its fragments, so that
+    /// an assignment derived from the actors before a reschedule is rejected instead of
+    /// committing the splits of the removed actors or to the ones not created yet.
+    pub async fn check_split_assignment_actors(
+        &self,
+        split_assignment: &SplitAssignment,
+    ) -> MetaResult<()> {
+        let map = &self.core.read().await.table_fragments;
+        check_split_assignment_running_actors(map, split_assignment)
+            .map_err(|e| anyhow!(e).context("stale source split assignment"))?;
+        Ok(())
+    }
+
+    async fn commit_split_assignment(
+        &self,
+        map: &mut BTreeMap<TableId, TableFragments>,
+        split_assignment: &SplitAssignment,
+    ) -> MetaResult<()> {
         let to_update_table_fragments: HashMap<TableId, HashMap<ActorId, Vec<SplitImpl>>> = map
             .values()
             .filter(|t| t.fragment_ids().any(|f| split_assignment.contains_key(&f)))
@@ -467,12 +2163,26 @@ where
             })
             .collect();
 
+        let events = map
+            .values()
+            .filter(|t| to_update_table_fragments.contains_key(&t.table_id()))
+            .map(|t| {
+                FragmentChangeEvent::update(
+                    t.table_id(),
+                    t.fragment_ids()
+                        .filter(|f| split_assignment.contains_key(f)),
+                )
+            })
+            .collect_vec();
+
         let mut table_fragments = BTreeMapTransaction::new(map);
         for (table_id, actor_splits) in to_update_table_fragments {
             let mut table_fragment = table_fragments.get_mut(table_id).unwrap();
             table_fragment.actor_splits.extend(actor_splits);
         }
-        commit_meta!(self, table_fragments)
+        commit_meta!(self, table_fragments)?;
+        self.notify_fragment_changes(events);
+        Ok(())
     }
 
     /// Get the actor ids of the fragment with `fragment_id` with `Running` status.
@@ -497,14 +2207,245 @@ where
         bail!("fragment not found: {}", fragment_id)
     }
 
+    /// Get the worker that each split of the source fragment with `fragment_id` is assigned to, by
+    /// joining the splits assigned to the actors with the workers the actors are scheduled on.
+    pub async fn get_source_fragment_worker_mapping(
+        &self,
+        fragment_id: FragmentId,
+    ) -> MetaResult<HashMap<SplitId, WorkerId>> {
+        let map = &self.core.read().await.table_fragments;
+
+        for table_fragment in map.values() {
+            if let Some(fragment) = table_fragment.fragments.get(&fragment_id) {
+                if fragment.fragment_type != FragmentType::Source as i32 {
+                    bail!("fragment is not a source fragment: {}", fragment_id);
+                }
+
+                let mut split_to_worker = HashMap::new();
+                for actor in &fragment.actors {
+                    let Some(splits) = table_fragment.actor_splits.get(&actor.actor_id) else {
+                        continue;
+                    };
+                    let worker_id = table_fragment
+                        .actor_status
+                        .get(&actor.actor_id)
+                        .and_then(|status| status.parallel_unit.as_ref())
+                        .map(|parallel_unit| parallel_unit.worker_node_id as WorkerId)
+                        .context(format!(
+                            "parallel unit of actor not found: {}",
+                            actor.actor_id
+                        ))?;
+                    for split in splits {
+                        split_to_worker.insert(split.id(), worker_id);
+                    }
+                }
+                return Ok(split_to_worker);
+            }
+        }
+
+        bail!("fragment not found: {}", fragment_id)
+    }
+
+    /// Find the actor that `split_id` of source `source_id` is currently assigned to, along with
+    /// the worker the actor is scheduled on. Returns `None` if the split is not assigned to any
+    /// actor. Split ids are only unique within a source, so the lookup is limited to the source
+    /// fragments of `source_id`.
+    pub async fn find_actor_by_split(
+        &self,
+        source_id: SourceId,
+        split_id: &str,
+    ) -> MetaResult<Option<(ActorId, WorkerId)>> {
+        let map = &self.core.read().await.table_fragments;
+
+        for table_fragment in map.values() {
+            let Some(fragment_ids) = table_fragment.source_fragments().remove(&source_id) else {
+                continue;
+            };
+            let Some(actor_id) = fragment_ids
+                .iter()
+                .flat_map(|fragment_id| &table_fragment.fragments[fragment_id].actors)
+                .map(|actor| actor.actor_id)
+                .find(|actor_id| {
+                    table_fragment
+                        .actor_splits
+                        .get(actor_id)
+                        .into_iter()
+                        .flatten()
+                        .any(|split| &*split.id() == split_id)
+                })
+            else {
+                continue;
+            };
+            let worker_id = table_fragment
+                .actor_status
+                .get(&actor_id)
+                .and_then(|status| status.parallel_unit.as_ref())
+                .map(|parallel_unit| parallel_unit.worker_node_id as WorkerId)
+                .context(format!("parallel unit of actor not found: {}", actor_id))?;
+            return Ok(Some((actor_id, worker_id)));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the worker owning the most vnodes covered by `key_range` of the fragment, to which
+    /// a batch scan of the key range is preferably routed. Ties are broken by the smaller parallel
+    /// unit id.
+    ///
+    /// The bounds of `key_range` are state table keys in the format of `table_id | vnode | ...`,
+    /// where an empty bound or one without the vnode is unbounded, and a vnode beyond the vnode
+    /// count is clamped to the last vnode. Both bounds must be keys of the same state table of the
+    /// fragment. Returns `None` if the fragment has no vnode mapping or the key range covers no
+    /// vnode.
+    pub async fn get_preferred_worker_for_key_range(
+        &self,
+        fragment_id: FragmentId,
+        key_range: &KeyRange,
+    ) -> MetaResult<Option<WorkerId>> {
+        let map = &self.core.read().await.table_fragments;
+        let (table_fragments, fragment) = map
+            .values()
+            .find_map(|table_fragments| {
+                table_fragments
+                    .fragments
+                    .get(&fragment_id)
+                    .map(|fragment| (table_fragments, fragment))
+            })
+            .context(format!("fragment not found: {}", fragment_id))?;
+        let Some(vnode_mapping) = &fragment.vnode_mapping else {
+            return Ok(None);
+        };
+
+        // The table id of a bound, if it's bounded.
+        let table_id_of = |key: &[u8]| -> MetaResult<Option<u32>> {
+            if key.is_empty() {
+                return Ok(None);
+            }
+            if key.len() < TABLE_PREFIX_LEN {
+                bail!("key range bound without a table id: {:?}", key);
+            }
+            Ok(Some(get_table_id(key)))
+        };
+        let table_ids: HashSet<_> = [
+            table_id_of(&key_range.left)?,
+            table_id_of(&key_range.right)?,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if table_ids.len() > 1 {
+            bail!("key range spans tables: {:?}", table_ids);
+        }
+        if let Some(table_id) = table_ids.into_iter().next()
+            && !fragment.state_table_ids.contains(&table_id)
+        {
+            bail!(
+                "key range of table {} is not of fragment {}",
+                table_id,
+                fragment_id
+            );
+        }
+
+        // The vnode of a bound, if it has one. A vnode beyond the vnode count, e.g. the exclusive
+        // upper bound past the last vnode, is clamped to the last vnode.
+        let vnode_of = |key: &[u8]| {
+            key.get(TABLE_PREFIX_LEN..TABLE_KEY_PREFIX_LEN)
+                .map(|vnode| {
+                    let vnode = u16::from_be_bytes(vnode.try_into().unwrap()) as usize;
+                    vnode.min(VIRTUAL_NODE_COUNT - 1)
+                })
+        };
+        let start_vnode = vnode_of(&key_range.left).unwrap_or(0);
+        let end_vnode = vnode_of(&key_range.right).unwrap_or(VIRTUAL_NODE_COUNT - 1);
+        if start_vnode > end_vnode {
+            return Ok(None);
+        }
+
+        let vnode_mapping = decompress_data(&vnode_mapping.original_indices, &vnode_mapping.data);
+        let Some(covered_vnodes) = vnode_mapping.get(start_vnode..=end_vnode) else {
+            return Ok(None);
+        };
+        let Some((parallel_unit_id, _)) = covered_vnodes
+            .iter()
+            .copied()
+            .counts()
+            .into_iter()
+            .max_by_key(|&(parallel_unit_id, count)| (count, Reverse(parallel_unit_id)))
+        else {
+            return Ok(None);
+        };
+
+        let worker_id = fragment
+            .actors
+            .iter()
+            .filter_map(|actor| table_fragments.actor_status.get(&actor.actor_id))
+            .filter_map(|status| status.parallel_unit.as_ref())
+            .find(|parallel_unit| parallel_unit.id == parallel_unit_id)
+            .map(|parallel_unit| parallel_unit.worker_node_id as WorkerId)
+            .context(format!(
+                "no actor of fragment {} on parallel unit {}",
+                fragment_id, parallel_unit_id
+            ))?;
+        Ok(Some(worker_id))
+    }
+
+    /// Wait until all actors in `actor_ids` are in `Running` state, by polling the actor states
+    /// every 100ms. Returns an error with the pending actors if it's not satisfied within
+    /// `timeout`.
+    pub async fn wait_for_actor_running(
+        &self,
+        actor_ids: &HashSet<ActorId>,
+        timeout: Duration,
+    ) -> MetaResult<()> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+        let poll = async {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                if self.pending_running_actors(actor_ids).await.is_empty() {
+                    return;
+                }
+            }
+        };
+
+        if tokio::time::timeout(timeout, poll).await.is_err() {
+            let pending_actors = self.pending_running_actors(actor_ids).await;
+            bail!(
+                "timeout waiting for actors to be running: {:?}",
+                pending_actors
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the actors in `actor_ids` which are not in `Running` state or not found.
+    async fn pending_running_actors(&self, actor_ids: &HashSet<ActorId>) -> Vec<ActorId> {
+        let map = &self.core.read().await.table_fragments;
+
+        actor_ids
+            .iter()
+            .filter(|actor_id| {
+                map.values()
+                    .find_map(|table_fragment| table_fragment.actor_status.get(actor_id))
+                    .map_or(true, |status| status.state != ActorState::Running as i32)
+            })
+            .copied()
+            .sorted()
+            .collect()
+    }
+
     /// Add the newly added Actor to the `FragmentManager`
     pub async fn pre_apply_reschedules(
         &self,
         mut created_actors: HashMap<FragmentId, HashMap<ActorId, (StreamActor, ActorStatus)>>,
     ) -> HashMap<FragmentId, HashSet<ActorId>> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
 
         let mut applied_reschedules = HashMap::new();
+        let mut updated_table_ids = vec![];
 
         for table_fragments in map.values_mut() {
             let mut updated_actor_status = HashMap::new();
@@ -523,8 +2464,12 @@ where
                 }
             }
 
+            if !updated_actor_status.is_empty() {
+                updated_table_ids.push(table_fragments.table_id());
+            }
             table_fragments.actor_status.extend(updated_actor_status);
         }
+        core.reindex_actors(updated_table_ids);
 
         applied_reschedules
     }
@@ -534,10 +2479,15 @@ where
         &self,
         applied_reschedules: HashMap<FragmentId, HashSet<ActorId>>,
     ) {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
+        let mut updated_table_ids = vec![];
         for table_fragments in map.values_mut() {
+            let table_id = table_fragments.table_id();
             for (fragment_id, fragment) in &mut table_fragments.fragments {
                 if let Some(fragment_create_actors) = applied_reschedules.get(fragment_id) {
+                    updated_table_ids.push(table_id);
                     table_fragments
                         .actor_status
                         .drain_filter(|actor_id, _| fragment_create_actors.contains(actor_id));
@@ -547,6 +2497,7 @@ where
                 }
             }
         }
+        core.reindex_actors(updated_table_ids);
     }
 
     /// Apply `Reschedule`s to fragments.
@@ -554,7 +2505,9 @@ where
         &self,
         mut reschedules: HashMap<FragmentId, Reschedule>,
     ) -> MetaResult<()> {
-        let map = &mut self.core.write().await.table_fragments;
+        let mut guard = self.core.write().await;
+        let core = &mut *guard;
+        let map = &mut core.table_fragments;
 
         fn update_actors(
             actors: &mut Vec<ActorId>,
@@ -612,7 +2565,7 @@ where
         let mut table_fragments = BTreeMapTransaction::new(map);
         let mut fragment_mapping_to_notify = vec![];
 
-        for table_id in to_update_table_fragments {
+        for &table_id in &to_update_table_fragments {
             // Takes out the reschedules of the fragments in this table.
             let reschedules = reschedules
                 .drain_filter(|fragment_id, _| {
@@ -631,7 +2584,7 @@ where
                     vnode_bitmap_updates,
                     upstream_fragment_dispatcher_ids,
                     upstream_dispatcher_mapping,
-                    downstream_fragment_id,
+                    downstream_fragment_ids,
                     actor_splits,
                 } = reschedule;
 
@@ -726,8 +2679,8 @@ where
                     }
                 }
 
-                // Update the merge executor of the downstream fragment.
-                if let Some(downstream_fragment_id) = downstream_fragment_id {
+                // Update the merge executors of the downstream fragments.
+                for downstream_fragment_id in downstream_fragment_ids {
                     let downstream_fragment = table_fragment
                         .fragments
                         .get_mut(&downstream_fragment_id)
@@ -757,16 +2710,37 @@ where
         }
 
         assert!(reschedules.is_empty(), "all reschedules must be applied");
+        if cfg!(debug_assertions) {
+            verify_actor_id_uniqueness(
+                table_fragments
+                    .tree_ref()
+                    .keys()
+                    .filter_map(|table_id| table_fragments.get(table_id)),
+            )?;
+        }
         commit_meta!(self, table_fragments)?;
-
-        for mapping in fragment_mapping_to_notify {
-            self.env
-                .notification_manager()
-                .notify_frontend(Operation::Update, Info::ParallelUnitMapping(mapping))
-                .await;
+        for table_id in &to_update_table_fragments {
+            tracing::info!(
+                "table fragments rescheduled:\n{}",
+                core.table_fragments[table_id].debug_dump()
+            );
         }
+        self.notify_fragment_changes(to_update_table_fragments.iter().map(|table_id| {
+            FragmentChangeEvent::all(
+                &core.table_fragments[table_id],
+                FragmentChangeOperation::Update,
+            )
+        }));
+        core.reindex_actors(to_update_table_fragments);
 
-        Ok(())
+        self.update_parallel_unit_mapping_bulk_in(core, fragment_mapping_to_notify)
+            .await
+    }
+
+    /// Checks that actor ids are globally unique across all table fragments.
+    pub async fn verify_global_actor_id_uniqueness(&self) -> MetaResult<()> {
+        let map = &self.core.read().await.table_fragments;
+        verify_actor_id_uniqueness(map.values())
     }
 
     pub async fn table_node_actors(
@@ -878,6 +2852,195 @@ where
         Ok(info)
     }
 
+    /// Updates the dispatchers of the sink fragment of `table_id` to `new_dispatch_type`, e.g.
+    /// from `Simple` to `Hash` before the downstream fragment is scaled out. The updated `Hash`
+    /// dispatchers hash by the distribution key of the materialized view, which is the
+    /// distribution required by the sink, and their hash mappings are computed from the vnode
+    /// bitmaps of their downstream actors.
+    ///
+    /// Only the updates that keep delivering the same rows to each downstream actor are allowed.
+    /// Consumers of a `Broadcast` dispatcher expect all rows, so it can't become `Hash`, and a
+    /// `Hash` dispatcher can only become `Broadcast` with a single downstream actor. The
+    /// `NoShuffle` dispatchers to the chain actors of downstream materialized views are kept as
+    /// is, since the chain actors are aligned with the sink actors.
+    ///
+    /// The fragments are committed to the meta store, and the returned updates must be sent to the
+    /// running actors in an `Update` mutation. If the barrier fails, the actors are rebuilt from
+    /// the committed fragments in the recovery.
+    pub async fn update_sink_dispatch_type(
+        &self,
+        table_id: &TableId,
+        new_dispatch_type: DispatcherType,
+    ) -> MetaResult<HashMap<ActorId, Vec<DispatcherUpdate>>> {
+        if matches!(
+            new_dispatch_type,
+            DispatcherType::Unspecified | DispatcherType::NoShuffle
+        ) {
+            bail!(
+                "cannot update the dispatchers of a sink fragment to {:?}",
+                new_dispatch_type
+            );
+        }
+
+        let map = &mut self.core.write().await.table_fragments;
+        // The downstream actors may belong to other tables.
+        let actor_vnode_bitmaps: HashMap<ActorId, Option<Buffer>> = map
+            .values()
+            .flat_map(|table_fragments| table_fragments.fragments.values())
+            .flat_map(|fragment| &fragment.actors)
+            .map(|actor| (actor.actor_id, actor.vnode_bitmap.clone()))
+            .collect();
+
+        let mut table_fragments = BTreeMapTransaction::new(map);
+        let mut table_fragment = table_fragments
+            .get_mut(*table_id)
+            .context(format!("table_fragment not exist: id={}", table_id))?;
+        let sink_fragment = table_fragment
+            .fragments
+            .values_mut()
+            .find(|fragment| fragment.fragment_type == FragmentType::Sink as i32)
+            .context(format!("sink fragment not exist: table_id={}", table_id))?;
+        let fragment_id = sink_fragment.fragment_id;
+
+        let has_chain_downstreams = sink_fragment
+            .actors
+            .iter()
+            .flat_map(|actor| &actor.dispatcher)
+            .any(|dispatcher| dispatcher.r#type() == DispatcherType::NoShuffle);
+
+        let mut dispatcher_updates: HashMap<ActorId, Vec<DispatcherUpdate>> = HashMap::new();
+        for actor in &mut sink_fragment.actors {
+            let distribution_key = match actor
+                .nodes
+                .as_ref()
+                .and_then(|node| node.node_body.as_ref())
+            {
+                Some(NodeBody::Materialize(materialize)) => materialize
+                    .table
+                    .as_ref()
+                    .map(|table| {
+                        table
+                            .distribution_key
+                            .iter()
+                            .map(|&idx| idx as u32)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                _ => vec![],
+            };
+
+            for dispatcher in &mut actor.dispatcher {
+                let dispatch_type = dispatcher.r#type();
+                if dispatch_type == DispatcherType::NoShuffle || dispatch_type == new_dispatch_type
+                {
+                    continue;
+                }
+                match (dispatch_type, new_dispatch_type) {
+                    (DispatcherType::Broadcast, DispatcherType::Hash) => bail!(
+                        "cannot update broadcast dispatcher {} of actor {} to hash, as the \
+                         downstream actors expect all rows",
+                        dispatcher.dispatcher_id,
+                        actor.actor_id
+                    ),
+                    // The chain actors are scheduled by the vnodes of the sink actors, which the
+                    // actors fed by a broadcast no longer follow.
+                    (DispatcherType::Hash, DispatcherType::Broadcast) if has_chain_downstreams => {
+                        bail!(
+                            "cannot update hash dispatchers of fragment {} to broadcast, as there \
+                             are downstream chain actors",
+                            fragment_id
+                        )
+                    }
+                    (_, DispatcherType::Simple)
+                    | (DispatcherType::Hash, DispatcherType::Broadcast)
+                        if dispatcher.downstream_actor_id.len() != 1 =>
+                    {
+                        bail!(
+                            "cannot update dispatcher {} of actor {} to {:?}, as it has {} \
+                             downstream actors",
+                            dispatcher.dispatcher_id,
+                            actor.actor_id,
+                            new_dispatch_type,
+                            dispatcher.downstream_actor_id.len()
+                        );
+                    }
+                    _ => {}
+                }
+
+                match new_dispatch_type {
+                    DispatcherType::Simple | DispatcherType::Broadcast => {
+                        dispatcher.column_indices.clear();
+                        dispatcher.hash_mapping = None;
+                    }
+                    DispatcherType::Hash => {
+                        if distribution_key.is_empty() {
+                            bail!(
+                                "cannot update dispatcher {} of actor {} to hash, as the \
+                                 materialized view has no distribution key",
+                                dispatcher.dispatcher_id,
+                                actor.actor_id
+                            );
+                        }
+                        dispatcher.column_indices = distribution_key.clone();
+                        dispatcher.hash_mapping = Some(match dispatcher.downstream_actor_id[..] {
+                            [] => bail!(
+                                "dispatcher {} of actor {} has no downstream actor",
+                                dispatcher.dispatcher_id,
+                                actor.actor_id
+                            ),
+                            // Same as a simple dispatcher, see `GlobalStreamManager`.
+                            [downstream_actor_id] => ActorMapping {
+                                original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+                                data: vec![downstream_actor_id],
+                            },
+                            ref downstream_actor_ids => {
+                                let bitmaps = downstream_actor_ids
+                                    .iter()
+                                    .map(|actor_id| {
+                                        let bitmap = actor_vnode_bitmaps
+                                            .get(actor_id)
+                                            .and_then(|bitmap| bitmap.as_ref())
+                                            .context(format!(
+                                                "no vnode bitmap for downstream actor {}",
+                                                actor_id
+                                            ))?;
+                                        Ok((*actor_id, Bitmap::from(bitmap)))
+                                    })
+                                    .collect::<MetaResult<HashMap<_, _>>>()?;
+                                actor_mapping_from_bitmaps(&bitmaps)
+                            }
+                        });
+                    }
+                    DispatcherType::Unspecified | DispatcherType::NoShuffle => unreachable!(),
+                }
+                dispatcher.set_type(new_dispatch_type);
+
+                dispatcher_updates
+                    .entry(actor.actor_id)
+                    .or_default()
+                    .push(DispatcherUpdate {
+                        actor_id: actor.actor_id,
+                        dispatcher_id: dispatcher.dispatcher_id,
+                        hash_mapping: dispatcher.hash_mapping.clone(),
+                        dispatcher_type: new_dispatch_type as i32,
+                        column_indices: dispatcher.column_indices.clone(),
+                        ..Default::default()
+                    });
+            }
+        }
+
+        commit_meta!(self, table_fragments)?;
+        self.notify_fragment_changes([FragmentChangeEvent::update(*table_id, [fragment_id])]);
+        tracing::info!(
+            "dispatchers of sink fragment {} of table {} updated to {:?}",
+            fragment_id,
+            table_id,
+            new_dispatch_type
+        );
+
+        Ok(dispatcher_updates)
+    }
+
     pub async fn get_tables_worker_actors(
         &self,
         table_ids: &HashSet<TableId>,
@@ -896,4 +3059,2320 @@ where
 
         Ok(info)
     }
+
+    /// Plans the reschedules that scale the table and all the tables depending on it through chain
+    /// nodes, transitively, to `target_parallelism` parallel units of `worker_nodes`.
+    ///
+    /// A chain actor receives from the upstream actor on the same parallel unit through a
+    /// `NoShuffle` dispatcher, so all the hash-distributed fragments of these tables are assigned
+    /// the same vnode mapping. The parallel units currently used by the primary table are
+    /// preferred. The ids of the added actors are allocated from the id generator, and the added
+    /// actors are in the ascending order of their parallel units.
+    ///
+    /// Only the dispatchers within a table are listed in `upstream_fragment_dispatcher_ids` and
+    /// `downstream_fragment_ids`. The actors connected across tables by `NoShuffle` dispatchers are
+    /// paired by their vnodes instead, which are the same for the actors on the same parallel unit.
+    /// The splits of source actors are not reassigned.
+    pub async fn cross_table_reschedule_plan(
+        &self,
+        primary_table_id: TableId,
+        target_parallelism: usize,
+        worker_nodes: &[WorkerNode],
+    ) -> MetaResult<HashMap<TableId, HashMap<FragmentId, Reschedule>>> {
+        let guard = self.core.read().await;
+        let core = &*guard;
+        let map = &core.table_fragments;
+
+        if target_parallelism == 0 {
+            bail!("target parallelism must be positive");
+        }
+        let primary_table_fragments = map
+            .get(&primary_table_id)
+            .context(format!("table_fragment not exist: id={}", primary_table_id))?;
+
+        // Collect the tables depending on the primary table, transitively.
+        let mut table_ids = vec![primary_table_id];
+        let mut visited = HashSet::from([primary_table_id]);
+        let mut next = 0;
+        while next < table_ids.len() {
+            let upstream_table_id = table_ids[next];
+            next += 1;
+            for table_fragments in map.values() {
+                let table_id = table_fragments.table_id();
+                if !visited.contains(&table_id)
+                    && table_fragments
+                        .upstream_table_ids()
+                        .contains(&upstream_table_id)
+                {
+                    visited.insert(table_id);
+                    table_ids.push(table_id);
+                }
+            }
+        }
+        for table_id in &table_ids {
+            if map[table_id].state() != State::Created {
+                bail!("the materialized view {} is still creating", table_id);
+            }
+        }
+
+        // Pick the parallel units, interleaved across the workers, in favor of the ones currently
+        // used by the primary table.
+        let current_parallel_units: HashSet<ParallelUnitId> = primary_table_fragments
+            .actor_status
+            .values()
+            .filter_map(|status| status.parallel_unit.as_ref().map(|unit| unit.id))
+            .collect();
+        let max_parallel_units = worker_nodes
+            .iter()
+            .map(|worker| worker.parallel_units.len())
+            .max()
+            .unwrap_or(0);
+        let mut parallel_units = (0..max_parallel_units)
+            .flat_map(|i| {
+                worker_nodes
+                    .iter()
+                    .filter_map(move |worker| worker.parallel_units.get(i))
+            })
+            .sorted_by_key(|unit| !current_parallel_units.contains(&unit.id))
+            .cloned()
+            .collect_vec();
+        if parallel_units.len() < target_parallelism {
+            bail!(
+                "only {} parallel units available, less than the target parallelism {}",
+                parallel_units.len(),
+                target_parallelism
+            );
+        }
+        parallel_units.truncate(target_parallelism);
+        parallel_units.sort_by_key(|unit| unit.id);
+        let parallel_unit_bitmaps: BTreeMap<_, _> =
+            vnode_mapping_to_bitmaps(build_vnode_mapping(&parallel_units))
+                .into_iter()
+                .collect();
+
+        // Index the dispatchers to each actor.
+        let mut upstream_dispatchers: HashMap<ActorId, Vec<(ActorId, &Dispatcher)>> =
+            HashMap::new();
+        for actor in map
+            .values()
+            .flat_map(|table_fragments| table_fragments.fragments.values())
+            .flat_map(|fragment| &fragment.actors)
+        {
+            for dispatcher in &actor.dispatcher {
+                for downstream_actor_id in &dispatcher.downstream_actor_id {
+                    upstream_dispatchers
+                        .entry(*downstream_actor_id)
+                        .or_default()
+                        .push((actor.actor_id, dispatcher));
+                }
+            }
+        }
+
+        // The reschedule of a fragment, with the added actors identified by their parallel units
+        // until their ids are allocated.
+        struct FragmentPlan {
+            added_parallel_units: Vec<ParallelUnitId>,
+            removed_actors: Vec<ActorId>,
+            retained_actors: BTreeMap<ParallelUnitId, ActorId>,
+            vnodes_changed_actors: HashSet<ActorId>,
+            upstream_fragment_dispatcher_ids: Vec<(FragmentId, DispatcherId)>,
+            downstream_fragment_ids: Vec<FragmentId>,
+        }
+        let mut plans: BTreeMap<(TableId, FragmentId), FragmentPlan> = BTreeMap::new();
+        for table_id in &table_ids {
+            let table_fragments = &map[table_id];
+            for fragment in table_fragments.fragments.values() {
+                if fragment.distribution_type() != FragmentDistributionType::Hash {
+                    continue;
+                }
+
+                // Retain one actor on each chosen parallel unit, and remove the others.
+                let mut removed_actors = vec![];
+                let mut retained_actors = BTreeMap::new();
+                let mut vnodes_changed_actors = HashSet::new();
+                for actor in &fragment.actors {
+                    let parallel_unit_id = table_fragments
+                        .actor_status
+                        .get(&actor.actor_id)
+                        .and_then(|status| status.parallel_unit.as_ref())
+                        .with_context(|| {
+                            format!("could not found ParallelUnit for {}", actor.actor_id)
+                        })?
+                        .id;
+                    match parallel_unit_bitmaps.get(&parallel_unit_id) {
+                        Some(bitmap) if !retained_actors.contains_key(&parallel_unit_id) => {
+                            retained_actors.insert(parallel_unit_id, actor.actor_id);
+                            if actor
+                                .vnode_bitmap
+                                .as_ref()
+                                .map_or(true, |buffer| Bitmap::from(buffer) != *bitmap)
+                            {
+                                vnodes_changed_actors.insert(actor.actor_id);
+                            }
+                        }
+                        _ => removed_actors.push(actor.actor_id),
+                    }
+                }
+                let added_parallel_units = parallel_unit_bitmaps
+                    .keys()
+                    .filter(|id| !retained_actors.contains_key(*id))
+                    .copied()
+                    .collect_vec();
+                if added_parallel_units.is_empty()
+                    && removed_actors.is_empty()
+                    && vnodes_changed_actors.is_empty()
+                {
+                    continue;
+                }
+
+                // The dispatchers within the table are updated with the added and removed actors,
+                // while the `NoShuffle` ones across tables are rebuilt by pairing the actors.
+                let mut upstream_fragment_dispatcher_ids = BTreeSet::new();
+                for actor in &fragment.actors {
+                    for (upstream_actor_id, dispatcher) in upstream_dispatchers
+                        .get(&actor.actor_id)
+                        .into_iter()
+                        .flatten()
+                    {
+                        let (upstream_table_id, upstream_fragment_id) = core
+                            .get_fragment_by_actor(*upstream_actor_id)
+                            .with_context(|| {
+                                format!("upstream actor {} not found", upstream_actor_id)
+                            })?;
+                        if upstream_table_id == *table_id {
+                            upstream_fragment_dispatcher_ids
+                                .insert((upstream_fragment_id, dispatcher.dispatcher_id));
+                        } else if dispatcher.r#type() != DispatcherType::NoShuffle {
+                            bail!(
+                                "fragment {} receives from fragment {} of table {} by a {:?} \
+                                 dispatcher, which can't be rescheduled across tables",
+                                fragment.fragment_id,
+                                upstream_fragment_id,
+                                upstream_table_id,
+                                dispatcher.r#type()
+                            );
+                        }
+                    }
+                }
+                let mut downstream_fragment_ids = BTreeSet::new();
+                for dispatcher in fragment.actors.iter().flat_map(|actor| &actor.dispatcher) {
+                    for downstream_actor_id in &dispatcher.downstream_actor_id {
+                        match core.get_fragment_by_actor(*downstream_actor_id) {
+                            Some((downstream_table_id, downstream_fragment_id))
+                                if downstream_table_id == *table_id =>
+                            {
+                                downstream_fragment_ids.insert(downstream_fragment_id);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                plans.insert(
+                    (*table_id, fragment.fragment_id),
+                    FragmentPlan {
+                        added_parallel_units,
+                        removed_actors,
+                        retained_actors,
+                        vnodes_changed_actors,
+                        upstream_fragment_dispatcher_ids: upstream_fragment_dispatcher_ids
+                            .into_iter()
+                            .collect(),
+                        downstream_fragment_ids: downstream_fragment_ids.into_iter().collect(),
+                    },
+                );
+            }
+        }
+
+        // Verify that each chain fragment has the same vnodes on each parallel unit as its
+        // upstream fragments of other tables after the reschedule.
+        let vnode_counts = |table_id: &TableId, fragment_id: &FragmentId| {
+            if plans.contains_key(&(*table_id, *fragment_id)) {
+                parallel_unit_bitmaps
+                    .iter()
+                    .map(|(parallel_unit_id, bitmap)| (*parallel_unit_id, bitmap.num_high_bits()))
+                    .collect()
+            } else {
+                let table_fragments = &map[table_id];
+                fragment_current_vnode_counts(
+                    table_fragments,
+                    &table_fragments.fragments[fragment_id],
+                )
+            }
+        };
+        for table_id in &table_ids {
+            let table_fragments = &map[table_id];
+            for chain_fragment_id in table_fragments.chain_fragment_ids() {
+                let chain_fragment = &table_fragments.fragments[&chain_fragment_id];
+                let upstream_fragments = chain_fragment
+                    .actors
+                    .iter()
+                    .flat_map(|actor| {
+                        let dispatching_actor_ids = upstream_dispatchers
+                            .get(&actor.actor_id)
+                            .into_iter()
+                            .flatten()
+                            .map(|(actor_id, _)| actor_id);
+                        actor.upstream_actor_id.iter().chain(dispatching_actor_ids)
+                    })
+                    .filter_map(|actor_id| core.get_fragment_by_actor(*actor_id))
+                    .filter(|(upstream_table_id, _)| upstream_table_id != table_id)
+                    .collect::<BTreeSet<_>>();
+                let chain_vnode_counts = vnode_counts(table_id, &chain_fragment_id);
+                for (upstream_table_id, upstream_fragment_id) in upstream_fragments {
+                    if vnode_counts(&upstream_table_id, &upstream_fragment_id) != chain_vnode_counts
+                    {
+                        bail!(
+                            "vnodes of chain fragment {} mismatch its upstream fragment {} of table {}",
+                            chain_fragment_id,
+                            upstream_fragment_id,
+                            upstream_table_id
+                        );
+                    }
+                }
+            }
+        }
+        drop(guard);
+
+        let mut reschedules: HashMap<TableId, HashMap<FragmentId, Reschedule>> = HashMap::new();
+        for ((table_id, fragment_id), plan) in plans {
+            let mut actor_bitmaps = HashMap::new();
+            let mut vnode_bitmap_updates = HashMap::new();
+            for (parallel_unit_id, actor_id) in &plan.retained_actors {
+                let bitmap = &parallel_unit_bitmaps[parallel_unit_id];
+                if plan.vnodes_changed_actors.contains(actor_id) {
+                    vnode_bitmap_updates.insert(*actor_id, bitmap.clone());
+                }
+                actor_bitmaps.insert(*actor_id, bitmap.clone());
+            }
+            let mut added_actors = Vec::with_capacity(plan.added_parallel_units.len());
+            for parallel_unit_id in &plan.added_parallel_units {
+                let actor_id = self
+                    .env
+                    .id_gen_manager()
+                    .generate::<{ IdCategory::Actor }>()
+                    .await? as ActorId;
+                let bitmap = &parallel_unit_bitmaps[parallel_unit_id];
+                vnode_bitmap_updates.insert(actor_id, bitmap.clone());
+                actor_bitmaps.insert(actor_id, bitmap.clone());
+                added_actors.push(actor_id);
+            }
+
+            let upstream_dispatcher_mapping = if actor_bitmaps.len() == 1 {
+                ActorMapping {
+                    original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+                    data: actor_bitmaps.keys().copied().collect(),
+                }
+            } else {
+                actor_mapping_from_bitmaps(&actor_bitmaps)
+            };
+
+            reschedules.entry(table_id).or_default().insert(
+                fragment_id,
+                Reschedule {
+                    added_actors,
+                    removed_actors: plan.removed_actors,
+                    vnode_bitmap_updates,
+                    upstream_fragment_dispatcher_ids: plan.upstream_fragment_dispatcher_ids,
+                    upstream_dispatcher_mapping: Some(upstream_dispatcher_mapping),
+                    downstream_fragment_ids: plan.downstream_fragment_ids,
+                    actor_splits: HashMap::new(),
+                },
+            );
+        }
+
+        Ok(reschedules)
+    }
+}
+
+/// Returns the number of vnodes on each parallel unit of the fragment.
+fn fragment_current_vnode_counts(
+    table_fragments: &TableFragments,
+    fragment: &Fragment,
+) -> BTreeMap<ParallelUnitId, usize> {
+    let mut vnode_counts = BTreeMap::new();
+    for actor in &fragment.actors {
+        if let Some(parallel_unit) = table_fragments
+            .actor_status
+            .get(&actor.actor_id)
+            .and_then(|status| status.parallel_unit.as_ref())
+        {
+            *vnode_counts.entry(parallel_unit.id).or_default() += actor
+                .vnode_bitmap
+                .as_ref()
+                .map_or(VIRTUAL_NODE_COUNT, |buffer| {
+                    Bitmap::from(buffer).num_high_bits()
+                });
+        }
+    }
+    vnode_counts
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use risingwave_common::util::compress::compress_data;
+    use risingwave_connector::source::datagen::DatagenSplit;
+    use risingwave_hummock_sdk::key::encode_vnode;
+    use risingwave_pb::catalog::Table;
+    use risingwave_pb::meta::TableFragments as ProstTableFragments;
+    use risingwave_pb::stream_plan::source_node::Info as SourceInfo;
+    use risingwave_pb::stream_plan::{ChainNode, MaterializeNode, SourceNode};
+
+    use super::*;
+    use crate::manager::MetaOpts;
+    use crate::storage::MemStore;
+
+    fn table_fragments_with_actors(table_id: u32, fragments: &[&[ActorId]]) -> TableFragments {
+        let fragments = fragments
+            .iter()
+            .enumerate()
+            .map(|(fragment_id, actor_ids)| {
+                let fragment_id = table_id * 100 + fragment_id as FragmentId;
+                let fragment = Fragment {
+                    fragment_id,
+                    actors: actor_ids
+                        .iter()
+                        .map(|&actor_id| StreamActor {
+                            actor_id,
+                            fragment_id,
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                };
+                (fragment_id, fragment)
+            })
+            .collect();
+        TableFragments::new(TableId::new(table_id), fragments)
+    }
+
+    /// The status of an actor placed on the parallel unit with the same id as the actor.
+    fn actor_status(actor_id: ActorId, worker_node_id: WorkerId, state: ActorState) -> ActorStatus {
+        ActorStatus {
+            parallel_unit: Some(ParallelUnit {
+                id: actor_id,
+                worker_node_id,
+            }),
+            state: state as i32,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_actor_id_uniqueness() -> MetaResult<()> {
+        assert_eq!(
+            table_fragments_with_actors(1, &[&[1, 2], &[3, 4]]).verify_actor_id_uniqueness(),
+            Ok(())
+        );
+        assert_eq!(
+            table_fragments_with_actors(1, &[&[1, 2, 3], &[3, 4, 1]]).verify_actor_id_uniqueness(),
+            Err(vec![1, 3])
+        );
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(1, &[&[1, 2], &[3, 4]]))
+            .await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(2, &[&[5, 6]]))
+            .await?;
+        fragment_manager.verify_global_actor_id_uniqueness().await?;
+
+        // Duplicate actors inside a single table.
+        assert!(fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(3, &[&[7], &[7]]))
+            .await
+            .is_err());
+        // Duplicate actors across tables.
+        assert!(fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(3, &[&[4, 8]]))
+            .await
+            .is_err());
+        fragment_manager.verify_global_actor_id_uniqueness().await?;
+
+        Ok(())
+    }
+
+    fn actor_mut(table_fragments: &mut TableFragments, actor_id: ActorId) -> &mut StreamActor {
+        table_fragments
+            .fragments
+            .values_mut()
+            .flat_map(|fragment| &mut fragment.actors)
+            .find(|actor| actor.actor_id == actor_id)
+            .unwrap()
+    }
+
+    fn link(table_fragments: &mut TableFragments, upstream: ActorId, downstream: ActorId) {
+        actor_mut(table_fragments, upstream)
+            .dispatcher
+            .push(Dispatcher {
+                downstream_actor_id: vec![downstream],
+                ..Default::default()
+            });
+        actor_mut(table_fragments, downstream)
+            .upstream_actor_id
+            .push(upstream);
+    }
+
+    /// Source fragment with actors 1, 2 -> fragment with actor 3 -> sink fragment with actor 4.
+    fn valid_table_fragments() -> TableFragments {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2], &[3], &[4]]);
+        table_fragments
+            .fragments
+            .get_mut(&100)
+            .unwrap()
+            .fragment_type = FragmentType::Source as i32;
+        table_fragments
+            .fragments
+            .get_mut(&102)
+            .unwrap()
+            .fragment_type = FragmentType::Sink as i32;
+        link(&mut table_fragments, 1, 3);
+        link(&mut table_fragments, 2, 3);
+        link(&mut table_fragments, 3, 4);
+        table_fragments
+    }
+
+    #[tokio::test]
+    async fn test_validate_topology() -> MetaResult<()> {
+        assert_eq!(check_topology(&valid_table_fragments()), Ok(()));
+        FragmentManager::<MemStore>::validate_topology(&valid_table_fragments()).unwrap();
+
+        // Dispatching to the actors of other tables is allowed.
+        let mut table_fragments = valid_table_fragments();
+        actor_mut(&mut table_fragments, 4)
+            .dispatcher
+            .push(Dispatcher {
+                downstream_actor_id: vec![200],
+                ..Default::default()
+            });
+        assert_eq!(check_topology(&table_fragments), Ok(()));
+
+        let mut table_fragments = valid_table_fragments();
+        link(&mut table_fragments, 4, 3);
+        assert_eq!(
+            check_topology(&table_fragments),
+            Err(TopologyError::Cycle(vec![3, 4]))
+        );
+        assert!(FragmentManager::<MemStore>::validate_topology(&table_fragments).is_err());
+
+        // The cycle is formed by the dispatcher only.
+        let mut table_fragments = valid_table_fragments();
+        actor_mut(&mut table_fragments, 4)
+            .dispatcher
+            .push(Dispatcher {
+                downstream_actor_id: vec![3],
+                ..Default::default()
+            });
+        assert_eq!(
+            check_topology(&table_fragments),
+            Err(TopologyError::Cycle(vec![3, 4]))
+        );
+
+        let mut table_fragments = valid_table_fragments();
+        actor_mut(&mut table_fragments, 4)
+            .upstream_actor_id
+            .push(200);
+        assert_eq!(
+            check_topology(&table_fragments),
+            Err(TopologyError::UnknownUpstreamActor {
+                actor_id: 4,
+                upstream_actor_id: 200
+            })
+        );
+
+        let mut table_fragments = valid_table_fragments();
+        table_fragments
+            .fragments
+            .get_mut(&102)
+            .unwrap()
+            .actors
+            .clear();
+        actor_mut(&mut table_fragments, 3).dispatcher.clear();
+        assert_eq!(
+            check_topology(&table_fragments),
+            Err(TopologyError::EmptySinkFragment(102))
+        );
+
+        let mut table_fragments = valid_table_fragments();
+        link(&mut table_fragments, 3, 2);
+        assert_eq!(
+            check_topology(&table_fragments),
+            Err(TopologyError::SourceWithUpstream {
+                fragment_id: 100,
+                actor_id: 2,
+                upstream_actor_ids: vec![3]
+            })
+        );
+
+        // Invalid topology is rejected when creating the table fragments.
+        let mut table_fragments = valid_table_fragments();
+        link(&mut table_fragments, 4, 3);
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        assert!(fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await
+            .is_err());
+        fragment_manager
+            .start_create_table_fragments(valid_table_fragments())
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_source_fragment_worker_mapping() -> MetaResult<()> {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2], &[3]]);
+        let source_fragment_id = 100;
+        table_fragments
+            .fragments
+            .get_mut(&source_fragment_id)
+            .unwrap()
+            .fragment_type = FragmentType::Source as i32;
+        table_fragments.set_actor_status(
+            [(1, 1), (2, 2), (3, 1)]
+                .into_iter()
+                .map(|(actor_id, worker_node_id)| {
+                    (
+                        actor_id,
+                        actor_status(actor_id, worker_node_id, ActorState::Inactive),
+                    )
+                })
+                .collect(),
+        );
+        let split = |split_index| SplitImpl::Datagen(DatagenSplit::new(split_index, 3, None));
+        table_fragments.actor_splits =
+            HashMap::from([(1, vec![split(0)]), (2, vec![split(1), split(2)])]);
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+
+        let mapping = fragment_manager
+            .get_source_fragment_worker_mapping(source_fragment_id)
+            .await?;
+        let expected = HashMap::from([(split(0).id(), 1), (split(1).id(), 2), (split(2).id(), 2)]);
+        assert_eq!(mapping, expected);
+
+        // Not a source fragment.
+        assert!(fragment_manager
+            .get_source_fragment_worker_mapping(101)
+            .await
+            .is_err());
+        // Fragment not found.
+        assert!(fragment_manager
+            .get_source_fragment_worker_mapping(999)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_actor_by_split() -> MetaResult<()> {
+        // Two tables reading from different sources, whose split ids are the same.
+        let split = |split_index| SplitImpl::Datagen(DatagenSplit::new(split_index, 3, None));
+        let source_table_fragments =
+            |table_id: u32, source_id: SourceId, actor_ids: [ActorId; 3]| {
+                let mut table_fragments =
+                    table_fragments_with_actors(table_id, &[&actor_ids[..2], &actor_ids[2..]]);
+                for fragment in table_fragments.fragments.values_mut() {
+                    let is_source = fragment.fragment_id == table_id * 100;
+                    for actor in &mut fragment.actors {
+                        actor.nodes = Some(StreamNode {
+                            node_body: is_source.then(|| {
+                                NodeBody::Source(SourceNode {
+                                    source_id,
+                                    info: Some(SourceInfo::StreamSource(Default::default())),
+                                    ..Default::default()
+                                })
+                            }),
+                            ..Default::default()
+                        });
+                    }
+                }
+                table_fragments.set_actor_status(
+                    actor_ids
+                        .into_iter()
+                        .map(|actor_id| {
+                            (
+                                actor_id,
+                                actor_status(actor_id, actor_id % 2 + 1, ActorState::Inactive),
+                            )
+                        })
+                        .collect(),
+                );
+                table_fragments.actor_splits = HashMap::from([
+                    (actor_ids[0], vec![split(0)]),
+                    (actor_ids[1], vec![split(1), split(2)]),
+                ]);
+                table_fragments
+            };
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(source_table_fragments(1, 10, [1, 2, 3]))
+            .await?;
+        fragment_manager
+            .start_create_table_fragments(source_table_fragments(2, 20, [4, 5, 6]))
+            .await?;
+
+        for (source_id, split_index, expected) in [
+            (10, 0, (1, 2)),
+            (10, 1, (2, 1)),
+            (10, 2, (2, 1)),
+            (20, 0, (4, 1)),
+            (20, 1, (5, 2)),
+        ] {
+            assert_eq!(
+                fragment_manager
+                    .find_actor_by_split(source_id, &split(split_index).id())
+                    .await?,
+                Some(expected)
+            );
+        }
+        // Not assigned to any actor, or of an unknown source.
+        assert_eq!(
+            fragment_manager.find_actor_by_split(10, "unknown").await?,
+            None
+        );
+        assert_eq!(
+            fragment_manager
+                .find_actor_by_split(30, &split(0).id())
+                .await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_preferred_worker_for_key_range() -> MetaResult<()> {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2, 3], &[4]]);
+        let fragment_id = 100;
+        let fragment = table_fragments.fragments.get_mut(&fragment_id).unwrap();
+        fragment.state_table_ids = vec![1];
+        fragment.vnode_mapping = Some(ParallelUnitMapping {
+            fragment_id,
+            original_indices: vec![99, 199, 255],
+            data: vec![1, 2, 3],
+        });
+        table_fragments.set_actor_status(
+            (1..=4)
+                .map(|actor_id| {
+                    (
+                        actor_id,
+                        actor_status(actor_id, actor_id, ActorState::Inactive),
+                    )
+                })
+                .collect(),
+        );
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+
+        let key = |vnode: Option<u8>| {
+            let mut key = 1u32.to_be_bytes().to_vec();
+            key.extend(vnode.map(encode_vnode).into_iter().flatten());
+            key
+        };
+        let preferred_worker = |left: Option<u8>, right: Option<u8>| {
+            let key_range = KeyRange {
+                left: key(left),
+                right: key(right),
+            };
+            let fragment_manager = &fragment_manager;
+            async move {
+                fragment_manager
+                    .get_preferred_worker_for_key_range(fragment_id, &key_range)
+                    .await
+            }
+        };
+
+        // Parallel units 1 and 2 own 100 vnodes each, and the tie is broken by the smaller id.
+        assert_eq!(preferred_worker(None, None).await?, Some(1));
+        assert_eq!(preferred_worker(Some(50), Some(120)).await?, Some(1));
+        assert_eq!(preferred_worker(Some(150), None).await?, Some(3));
+        assert_eq!(preferred_worker(Some(180), Some(180)).await?, Some(2));
+        // Empty vnode range.
+        assert_eq!(preferred_worker(Some(120), Some(50)).await?, None);
+
+        let preferred_worker_of_raw = |left: Vec<u8>, right: Vec<u8>| {
+            let key_range = KeyRange { left, right };
+            let fragment_manager = &fragment_manager;
+            async move {
+                fragment_manager
+                    .get_preferred_worker_for_key_range(fragment_id, &key_range)
+                    .await
+            }
+        };
+        // The exclusive upper bound past the last vnode is clamped to the last vnode.
+        let past_last_vnode = [&1u32.to_be_bytes()[..], &[0x01, 0x00]].concat();
+        assert_eq!(
+            preferred_worker_of_raw(key(Some(150)), past_last_vnode.clone()).await?,
+            Some(3)
+        );
+        assert_eq!(
+            preferred_worker_of_raw(past_last_vnode, key(None)).await?,
+            Some(3)
+        );
+        // A bound with a partial vnode is unbounded.
+        assert_eq!(
+            preferred_worker_of_raw([&1u32.to_be_bytes()[..], &[0x00]].concat(), key(Some(50)))
+                .await?,
+            Some(1)
+        );
+        // Bounds without a table id, spanning tables, or of a table not of the fragment.
+        assert!(preferred_worker_of_raw(vec![0], key(None)).await.is_err());
+        assert!(
+            preferred_worker_of_raw(key(Some(50)), 2u32.to_be_bytes().to_vec())
+                .await
+                .is_err()
+        );
+        assert!(
+            preferred_worker_of_raw(2u32.to_be_bytes().to_vec(), 2u32.to_be_bytes().to_vec())
+                .await
+                .is_err()
+        );
+        // No vnode mapping.
+        assert_eq!(
+            fragment_manager
+                .get_preferred_worker_for_key_range(101, &KeyRange::default())
+                .await?,
+            None
+        );
+        // Fragment not found.
+        assert!(fragment_manager
+            .get_preferred_worker_for_key_range(999, &KeyRange::default())
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compute_migration_cost() -> MetaResult<()> {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2, 3], &[4]]);
+        let fragment = table_fragments.fragments.get_mut(&100).unwrap();
+        fragment.vnode_mapping = Some(ParallelUnitMapping {
+            fragment_id: 100,
+            original_indices: vec![99, 199, 255],
+            data: vec![1, 2, 3],
+        });
+        fragment.state_table_ids = vec![1];
+        table_fragments.set_actor_status(
+            (1..=4)
+                .map(|actor_id| {
+                    (
+                        actor_id,
+                        actor_status(actor_id, actor_id, ActorState::Running),
+                    )
+                })
+                .collect(),
+        );
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        let state_table_sizes = HashMap::from([(
+            1,
+            TableStats {
+                total_bytes: 2560,
+                total_key_count: 256,
+            },
+        )]);
+
+        assert_eq!(
+            fragment_manager
+                .compute_migration_cost(&HashMap::new(), &state_table_sizes)
+                .await,
+            MigrationCost::default()
+        );
+
+        // Actor 2 is already on worker 2, and the stateless actor 4 owns no vnodes.
+        let migrate_map = HashMap::from([(1, 5), (2, 2), (4, 5)]);
+        assert_eq!(
+            fragment_manager
+                .compute_migration_cost(&migrate_map, &state_table_sizes)
+                .await,
+            MigrationCost {
+                estimated_bytes_to_transfer: 1000,
+                actors_affected: 2,
+                fragments_affected: 2,
+                vnodes_reassigned: 100,
+                expected_downtime_ms: 1001,
+            }
+        );
+        // Nothing is migrated.
+        assert_eq!(
+            fragment_manager.list_table_fragments().await?[0].actor_status[&1]
+                .parallel_unit
+                .as_ref()
+                .unwrap()
+                .worker_node_id,
+            1
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_source_split_assignment_validated() -> MetaResult<()> {
+        let split = |split_index| SplitImpl::Datagen(DatagenSplit::new(split_index, 4, None));
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2, 3], &[4]]);
+        table_fragments.actor_splits = HashMap::from([
+            (1, vec![split(0)]),
+            (2, vec![split(1), split(2)]),
+            (3, vec![]),
+        ]);
+        let map = BTreeMap::from([(table_fragments.table_id(), table_fragments.clone())]);
+        let assignment = |actor_splits: Vec<(ActorId, Vec<SplitImpl>)>| -> SplitAssignment {
+            HashMap::from([(100, actor_splits.into_iter().collect())])
+        };
+        let check =
+            |split_assignment: SplitAssignment| check_split_assignment(&map, &split_assignment, 2);
+
+        let valid = assignment(vec![
+            (1, vec![split(0)]),
+            (2, vec![split(1)]),
+            (3, vec![split(2)]),
+        ]);
+        assert_eq!(check(valid.clone()), Ok(()));
+
+        assert_eq!(
+            check(HashMap::from([(999, HashMap::new())])),
+            Err(SplitAssignmentError::FragmentNotFound(999))
+        );
+        assert_eq!(
+            check(assignment(vec![
+                (1, vec![split(0), split(1)]),
+                (2, vec![split(2)])
+            ])),
+            Err(SplitAssignmentError::MissingActors {
+                fragment_id: 100,
+                actor_ids: vec![3]
+            })
+        );
+        assert_eq!(
+            check(assignment(vec![
+                (1, vec![split(0)]),
+                (2, vec![split(1)]),
+                (3, vec![split(2)]),
+                (4, vec![]),
+            ])),
+            Err(SplitAssignmentError::UnknownActors {
+                fragment_id: 100,
+                actor_ids: vec![4]
+            })
+        );
+        assert_eq!(
+            check(assignment(vec![
+                (1, vec![split(0), split(1), split(2)]),
+                (2, vec![]),
+                (3, vec![]),
+            ])),
+            Err(SplitAssignmentError::TooManySplits {
+                fragment_id: 100,
+                actor_id: 1,
+                split_count: 3,
+                max_splits_per_actor: 2
+            })
+        );
+        assert_eq!(
+            check(assignment(vec![
+                (1, vec![split(0), split(1)]),
+                (2, vec![split(0)]),
+                (3, vec![split(2)]),
+            ])),
+            Err(SplitAssignmentError::DuplicateSplits(vec![split(0).id()]))
+        );
+        assert_eq!(
+            check(assignment(vec![
+                (1, vec![split(0)]),
+                (2, vec![split(1)]),
+                (3, vec![split(3)]),
+            ])),
+            Err(SplitAssignmentError::SplitsMismatch {
+                fragment_id: 100,
+                missing: vec![split(2).id()],
+                unexpected: vec![split(3).id()],
+            })
+        );
+
+        let env = MetaSrvEnv::for_test_opts(Arc::new(MetaOpts {
+            max_splits_per_actor: 2,
+            ..Default::default()
+        }))
+        .await;
+        let fragment_manager = FragmentManager::new(env).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        let actor_splits = || async {
+            MetaResult::Ok(
+                fragment_manager
+                    .select_table_fragments_by_table_id(&TableId::new(1))
+                    .await?
+                    .actor_splits,
+            )
+        };
+
+        // An invalid assignment is not committed.
+        let err = fragment_manager
+            .apply_source_split_assignment_validated(assignment(vec![
+                (1, vec![split(0), split(1), split(2)]),
+                (2, vec![]),
+                (3, vec![]),
+            ]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.split_assignment_error(),
+            Some(&SplitAssignmentError::TooManySplits {
+                fragment_id: 100,
+                actor_id: 1,
+                split_count: 3,
+                max_splits_per_actor: 2
+            })
+        );
+        assert_eq!(actor_splits().await?[&3], vec![]);
+
+        fragment_manager
+            .apply_source_split_assignment_validated(valid)
+            .await?;
+        assert_eq!(actor_splits().await?[&2], vec![split(1)]);
+        assert_eq!(actor_splits().await?[&3], vec![split(2)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_split_assignment_actors() -> MetaResult<()> {
+        let split = |split_index| SplitImpl::Datagen(DatagenSplit::new(split_index, 2, None));
+        // Actor 3 is created by a reschedule that's not committed yet.
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2, 3]]);
+        table_fragments.set_actor_status(
+            (1..=3)
+                .map(|actor_id| {
+                    let state = if actor_id == 3 {
+                        ActorState::Inactive
+                    } else {
+                        ActorState::Running
+                    };
+                    (actor_id, actor_status(actor_id, 1, state))
+                })
+                .collect(),
+        );
+        let map = BTreeMap::from([(table_fragments.table_id(), table_fragments.clone())]);
+        let check = |actor_splits: Vec<(ActorId, Vec<SplitImpl>)>| {
+            let assignment = HashMap::from([(100, actor_splits.into_iter().collect())]);
+            check_split_assignment_running_actors(&map, &assignment)
+        };
+
+        assert_eq!(
+            check(vec![(1, vec![split(0)]), (2, vec![split(1)])]),
+            Ok(())
+        );
+        // The removed actor 4 and the created actor 3.
+        assert_eq!(
+            check(vec![(1, vec![split(0)]), (3, vec![]), (4, vec![split(1)])]),
+            Err(SplitAssignmentError::StaleActors {
+                fragment_id: 100,
+                actor_ids: vec![3, 4]
+            })
+        );
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        assert!(fragment_manager
+            .check_split_assignment_actors(&HashMap::from([(100, HashMap::from([(3, vec![])]))]))
+            .await
+            .is_err());
+        assert!(fragment_manager
+            .check_split_assignment_actors(&HashMap::from([(999, HashMap::new())]))
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_by_complexity() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        assert!(fragment_manager
+            .list_tables_by_fragment_count()
+            .await
+            .is_empty());
+
+        for table_fragments in [
+            table_fragments_with_actors(1, &[&[1, 2, 3, 4]]),
+            table_fragments_with_actors(2, &[&[5], &[6], &[7]]),
+            table_fragments_with_actors(3, &[&[8, 9], &[10, 11]]),
+            table_fragments_with_actors(4, &[&[12], &[13]]),
+        ] {
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+        }
+
+        let ranked = |counts: Vec<(TableId, usize)>| {
+            counts
+                .into_iter()
+                .map(|(table_id, count)| (table_id.table_id, count))
+                .collect_vec()
+        };
+        assert_eq!(
+            ranked(fragment_manager.list_tables_by_fragment_count().await),
+            vec![(2, 3), (3, 2), (4, 2), (1, 1)]
+        );
+        assert_eq!(
+            ranked(fragment_manager.list_tables_by_actor_count().await),
+            vec![(1, 4), (3, 4), (2, 3), (4, 2)]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_actor_running() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2]]);
+        table_fragments.set_actor_status(
+            [1, 2]
+                .into_iter()
+                .map(|actor_id| (actor_id, actor_status(actor_id, 1, ActorState::Inactive)))
+                .collect(),
+        );
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+
+        // The actors are not started, and actor 3 doesn't exist.
+        let err = fragment_manager
+            .wait_for_actor_running(&HashSet::from([1, 2, 3]), Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("[1, 2, 3]"), "{}", err);
+
+        // The actors are started by the first barrier while waiting.
+        let (waited, started) = tokio::join!(
+            fragment_manager.wait_for_actor_running(&HashSet::from([1, 2]), Duration::from_secs(5)),
+            async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                fragment_manager
+                    .post_create_table_fragments(&TableId::new(1), vec![], HashMap::new())
+                    .await
+            }
+        );
+        started?;
+        waited?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_actors_per_table() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let with_status = |table_id, actor_ids: &[ActorId], state: ActorState| {
+            let mut table_fragments = table_fragments_with_actors(table_id, &[actor_ids]);
+            table_fragments.set_actor_status(
+                actor_ids
+                    .iter()
+                    .map(|&actor_id| (actor_id, actor_status(actor_id, 1, state)))
+                    .collect(),
+            );
+            table_fragments
+        };
+        let creating = || async {
+            fragment_manager
+                .get_fragment_read_guard()
+                .await
+                .list_creating_actors_per_table()
+        };
+
+        // Both tables are creating, and the actors of table 2 have been started.
+        fragment_manager
+            .start_create_table_fragments(with_status(1, &[1, 2, 3], ActorState::Inactive))
+            .await?;
+        fragment_manager
+            .start_create_table_fragments(with_status(2, &[4, 5], ActorState::Running))
+            .await?;
+        assert_eq!(
+            fragment_manager.list_running_actors_per_table().await,
+            HashMap::from([(TableId::new(2), 2)])
+        );
+        assert_eq!(creating().await, HashMap::from([(TableId::new(1), 3)]));
+
+        // The actors of table 1 are started by the first barrier.
+        fragment_manager
+            .post_create_table_fragments(&TableId::new(1), vec![], HashMap::new())
+            .await?;
+        assert_eq!(
+            fragment_manager.list_running_actors_per_table().await,
+            HashMap::from([(TableId::new(1), 3), (TableId::new(2), 2)])
+        );
+        assert!(creating().await.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pin_version_for_scan() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let mapping = |fragment_id, data: Vec<ParallelUnitId>| ParallelUnitMapping {
+            fragment_id,
+            original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+            data,
+        };
+        for (table_id, parallel_unit_id) in [(1, 1), (2, 2)] {
+            let mut table_fragments = table_fragments_with_actors(table_id, &[&[table_id]]);
+            let fragment_id = table_id * 100;
+            table_fragments
+                .fragments
+                .get_mut(&fragment_id)
+                .unwrap()
+                .vnode_mapping = Some(mapping(fragment_id, vec![parallel_unit_id]));
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+        }
+
+        let version = fragment_manager.pin_version_for_scan(&[100, 200]).await?;
+        assert_eq!(version.mapping(100), Some(&mapping(100, vec![1])));
+        assert_eq!(version.mapping(200), Some(&mapping(200, vec![2])));
+        let other = fragment_manager.pin_version_for_scan(&[200]).await?;
+        assert_eq!(
+            fragment_manager
+                .get_fragment_read_guard()
+                .await
+                .active_scan_pins(),
+            BTreeMap::from([
+                (version.pin_id(), vec![100, 200]),
+                (other.pin_id(), vec![200])
+            ])
+        );
+
+        // The pinned mappings stay unchanged after the fragments are dropped.
+        fragment_manager
+            .drop_table_fragments_vec(&HashSet::from([TableId::new(2)]))
+            .await?;
+        assert_eq!(version.mapping(200), Some(&mapping(200, vec![2])));
+        assert!(fragment_manager
+            .pin_version_for_scan(&[100, 200])
+            .await
+            .is_err());
+
+        version.release();
+        drop(other);
+        assert!(fragment_manager
+            .get_fragment_read_guard()
+            .await
+            .active_scan_pins()
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fragment_change_log() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let mapping = |fragment_id, data: Vec<ParallelUnitId>| ParallelUnitMapping {
+            fragment_id,
+            original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+            data,
+        };
+        for table_id in [1, 2] {
+            let mut table_fragments = table_fragments_with_actors(table_id, &[&[table_id]]);
+            let fragment_id = table_id * 100;
+            let fragment = table_fragments.fragments.get_mut(&fragment_id).unwrap();
+            fragment.state_table_ids = vec![table_id];
+            fragment.vnode_mapping = Some(mapping(fragment_id, vec![1]));
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+            fragment_manager
+                .post_create_table_fragments(&TableId::new(table_id), vec![], HashMap::new())
+                .await?;
+        }
+        let (seq, changes) = fragment_manager.get_changes_since(0).await;
+        assert_eq!(seq, 2);
+        assert_eq!(
+            changes,
+            vec![
+                MappingChange::Add(mapping(100, vec![1])),
+                MappingChange::Add(mapping(200, vec![1])),
+            ]
+        );
+
+        fragment_manager
+            .update_parallel_unit_mapping_bulk(vec![mapping(100, vec![2])])
+            .await?;
+        fragment_manager
+            .drop_table_fragments_vec(&HashSet::from([TableId::new(2)]))
+            .await?;
+        assert_eq!(
+            fragment_manager.get_changes_since(1).await,
+            (
+                4,
+                vec![
+                    MappingChange::Add(mapping(200, vec![1])),
+                    MappingChange::Update(mapping(100, vec![2])),
+                    MappingChange::Remove(200),
+                ]
+            )
+        );
+        assert_eq!(fragment_manager.get_changes_since(4).await, (4, vec![]));
+
+        // Updating the whole table fragments sends its mappings through the bulk update as well,
+        // even if they are unchanged.
+        let table_fragments = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(1))
+            .await?;
+        fragment_manager
+            .batch_update_table_fragments(&[table_fragments])
+            .await?;
+        assert_eq!(
+            fragment_manager.get_changes_since(4).await,
+            (5, vec![MappingChange::Update(mapping(100, vec![2]))])
+        );
+
+        // The changes evicted from the log are replaced by a full snapshot.
+        let mut change_log = FragmentChangeLog::default();
+        for fragment_id in 0..FragmentChangeLog::MAX_ENTRIES as FragmentId + 2 {
+            change_log.append(vec![MappingChange::Remove(fragment_id)]);
+        }
+        assert_eq!(change_log.entries.len(), FragmentChangeLog::MAX_ENTRIES);
+        assert_eq!(change_log.changes_since(1), None);
+        assert_eq!(
+            change_log.changes_since(FragmentChangeLog::MAX_ENTRIES as u64),
+            Some(vec![
+                MappingChange::Remove(FragmentChangeLog::MAX_ENTRIES as FragmentId),
+                MappingChange::Remove(FragmentChangeLog::MAX_ENTRIES as FragmentId + 1),
+            ])
+        );
+        // So are the changes after a sequence number the log hasn't reached, e.g. one returned by
+        // a previous meta node.
+        assert_eq!(
+            change_log.changes_since(FragmentChangeLog::MAX_ENTRIES as u64 + 2),
+            Some(vec![])
+        );
+        assert_eq!(
+            change_log.changes_since(FragmentChangeLog::MAX_ENTRIES as u64 + 3),
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_fragment_changes() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let table_id = TableId::new(1);
+        let event = |operation, fragment_ids: &[FragmentId]| FragmentChangeEvent {
+            table_id,
+            operation,
+            fragment_ids: fragment_ids.to_vec(),
+        };
+
+        let mut rx = fragment_manager.subscribe_fragment_changes();
+        fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(1, &[&[1], &[2]]))
+            .await?;
+        fragment_manager
+            .post_create_table_fragments(&table_id, vec![], HashMap::new())
+            .await?;
+        fragment_manager
+            .mark_table_fragments_created(table_id)
+            .await?;
+
+        // Only the changes after the subscription are received.
+        let mut late_rx = fragment_manager.subscribe_fragment_changes();
+        fragment_manager
+            .update_parallel_unit_mapping_bulk(vec![ParallelUnitMapping {
+                fragment_id: 101,
+                original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+                data: vec![1],
+            }])
+            .await?;
+        fragment_manager
+            .drop_table_fragments_vec(&HashSet::from([table_id]))
+            .await?;
+
+        for expected in [
+            event(FragmentChangeOperation::Add, &[100, 101]),
+            event(FragmentChangeOperation::Update, &[100, 101]),
+            event(FragmentChangeOperation::Update, &[100, 101]),
+            event(FragmentChangeOperation::Update, &[101]),
+            event(FragmentChangeOperation::Delete, &[100, 101]),
+        ] {
+            assert_eq!(rx.try_recv().unwrap(), expected);
+        }
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            late_rx.try_recv().unwrap(),
+            event(FragmentChangeOperation::Update, &[101])
+        );
+        assert_eq!(
+            late_rx.try_recv().unwrap(),
+            event(FragmentChangeOperation::Delete, &[100, 101])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_topology_change() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let table_ids = HashSet::from([TableId::new(1)]);
+        let actor_ids = || {
+            let fragment_manager = &fragment_manager;
+            let table_ids = &table_ids;
+            async move {
+                let actor_ids = fragment_manager.get_table_actor_ids(table_ids).await?;
+                MetaResult::Ok(actor_ids.into_iter().sorted().collect_vec())
+            }
+        };
+
+        fragment_manager
+            .apply_topology_change(TopologyChange::CreateTable(table_fragments_with_actors(
+                1,
+                &[&[1, 2]],
+            )))
+            .await?;
+        assert!(fragment_manager
+            .apply_topology_change(TopologyChange::CreateTable(table_fragments_with_actors(
+                1,
+                &[&[3]]
+            ),))
+            .await
+            .is_err());
+        assert_eq!(actor_ids().await?, vec![1, 2]);
+
+        // The table is still being created, so the reschedule is rejected before its barrier.
+        assert!(fragment_manager
+            .check_reschedulable([100].iter())
+            .await
+            .is_err());
+        fragment_manager
+            .apply_topology_change(TopologyChange::FinishCreateTable(TableId::new(1)))
+            .await?;
+        fragment_manager.check_reschedulable([100].iter()).await?;
+        assert!(fragment_manager
+            .check_reschedulable([200].iter())
+            .await
+            .is_err());
+
+        fragment_manager
+            .apply_topology_change(TopologyChange::DropTables(table_ids.clone()))
+            .await?;
+        assert!(fragment_manager.list_table_fragments().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gc() -> MetaResult<()> {
+        let split = || SplitImpl::Datagen(DatagenSplit::new(0, 1, None));
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2]]);
+        table_fragments.fragments.get_mut(&100).unwrap().actors[0].dispatcher = vec![Dispatcher {
+            downstream_actor_id: vec![3, 99],
+            ..Default::default()
+        }];
+        table_fragments.actor_splits = HashMap::from([(1, vec![split()]), (42, vec![split()])]);
+
+        // Table fragments in creating state are not collected before the threshold.
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments.clone())
+            .await?;
+        assert_eq!(
+            fragment_manager
+                .collect_gc_candidates()
+                .await?
+                .stale_creating_tables,
+            HashSet::new()
+        );
+
+        let env = MetaSrvEnv::for_test_opts(Arc::new(MetaOpts {
+            creating_table_fragments_gc_threshold_sec: 0,
+            ..Default::default()
+        }))
+        .await;
+        let fragment_manager = FragmentManager::new(env).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        fragment_manager
+            .mark_table_fragments_created(TableId::new(1))
+            .await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments_with_actors(2, &[&[3]]))
+            .await?;
+
+        let mut candidates = fragment_manager.collect_gc_candidates().await?;
+        assert_eq!(
+            candidates,
+            GcCandidates {
+                stale_creating_tables: HashSet::from([TableId::new(2)]),
+                orphaned_actor_splits: HashMap::from([(TableId::new(1), HashSet::from([42]))]),
+                dangling_dispatcher_targets: HashMap::from([(
+                    TableId::new(1),
+                    HashMap::from([(1, HashSet::from([99]))])
+                )]),
+            }
+        );
+
+        // Keep the creating table.
+        candidates.stale_creating_tables.clear();
+        fragment_manager.apply_gc(candidates).await?;
+        let table_fragments = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(1))
+            .await?;
+        assert_eq!(
+            table_fragments.fragments[&100].actors[0].dispatcher[0].downstream_actor_id,
+            vec![3]
+        );
+        assert_eq!(
+            table_fragments.actor_splits.keys().copied().collect_vec(),
+            vec![1]
+        );
+
+        let candidates = fragment_manager.collect_gc_candidates().await?;
+        assert_eq!(
+            candidates,
+            GcCandidates {
+                stale_creating_tables: HashSet::from([TableId::new(2)]),
+                ..Default::default()
+            }
+        );
+        fragment_manager.apply_gc(candidates).await?;
+        let table_fragments = fragment_manager.list_table_fragments().await?;
+        assert_eq!(table_fragments.len(), 1);
+        assert!(table_fragments[0].fragments[&100].actors[0]
+            .dispatcher
+            .is_empty());
+        assert!(fragment_manager.collect_gc_candidates().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vnode_counts() -> MetaResult<()> {
+        // Fragment 100 is stateful with its vnodes evenly distributed on actors 1 to 4, and
+        // fragment 101 is stateless.
+        let mut table_1 = table_fragments_with_actors(1, &[&[1, 2, 3, 4], &[5]]);
+        schedule_on_parallel_units(&mut table_1);
+        table_1.fragments.get_mut(&100).unwrap().state_table_ids = vec![1];
+        table_1.set_actor_status(
+            [(1, 1), (2, 1), (3, 1), (4, 2), (5, 1)]
+                .into_iter()
+                .map(|(actor_id, worker_node_id)| {
+                    (
+                        actor_id,
+                        actor_status(actor_id, worker_node_id, ActorState::Running),
+                    )
+                })
+                .collect(),
+        );
+        let mut table_2 = table_fragments_with_actors(2, &[&[6, 7]]);
+        schedule_on_parallel_units(&mut table_2);
+        table_2.fragments.get_mut(&200).unwrap().state_table_ids = vec![2];
+        table_2.set_actor_status(
+            [6, 7]
+                .into_iter()
+                .map(|actor_id| (actor_id, actor_status(actor_id, 2, ActorState::Running)))
+                .collect(),
+        );
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        for table_fragments in [table_1, table_2] {
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+        }
+
+        assert_eq!(
+            fragment_manager.get_total_vnode_count().await,
+            HashMap::from([
+                (TableId::new(1), VIRTUAL_NODE_COUNT),
+                (TableId::new(2), VIRTUAL_NODE_COUNT)
+            ])
+        );
+        let guard = fragment_manager.get_fragment_read_guard().await;
+        // Actors 1 to 3 of table 1 take 3/4 of the vnodes, and the stateless actor 5 is ignored.
+        assert_eq!(
+            guard.max_vnode_count_per_worker(1),
+            VIRTUAL_NODE_COUNT / 4 * 3
+        );
+        // All vnodes of table 2 are on worker 2.
+        assert_eq!(guard.max_vnode_count_per_worker(2), VIRTUAL_NODE_COUNT);
+        assert_eq!(guard.max_vnode_count_per_worker(3), 0);
+
+        Ok(())
+    }
+
+    /// Schedule the actors of each fragment in `table_fragments` on the parallel units with the
+    /// same ids as the actors, with the vnodes distributed evenly.
+    fn schedule_on_parallel_units(table_fragments: &mut TableFragments) {
+        let mut statuses = BTreeMap::new();
+        for fragment in table_fragments.fragments.values_mut() {
+            let actor_ids = fragment.actors.iter().map(|a| a.actor_id).collect_vec();
+            let vnode_mapping = (0..VIRTUAL_NODE_COUNT)
+                .map(|vnode| actor_ids[vnode % actor_ids.len()])
+                .collect_vec();
+            for actor in &mut fragment.actors {
+                let bitmap: Bitmap = vnode_mapping
+                    .iter()
+                    .map(|&actor_id| actor_id == actor.actor_id)
+                    .collect();
+                actor.vnode_bitmap = Some(bitmap.to_protobuf());
+                statuses.insert(
+                    actor.actor_id,
+                    actor_status(actor.actor_id, 1, ActorState::Running),
+                );
+            }
+            let (original_indices, data) = compress_data(&vnode_mapping);
+            fragment.vnode_mapping = Some(ParallelUnitMapping {
+                fragment_id: fragment.fragment_id,
+                original_indices,
+                data,
+            });
+        }
+        table_fragments.set_actor_status(statuses);
+    }
+
+    #[tokio::test]
+    async fn test_fragment_repairs() -> MetaResult<()> {
+        let split = || SplitImpl::Datagen(DatagenSplit::new(0, 1, None));
+        let parallel_units: HashMap<_, _> = (1..=10)
+            .map(|id| {
+                let parallel_unit = ParallelUnit {
+                    id,
+                    worker_node_id: 1,
+                };
+                (id, parallel_unit)
+            })
+            .collect();
+
+        let mut healthy = table_fragments_with_actors(1, &[&[1, 2], &[3]]);
+        schedule_on_parallel_units(&mut healthy);
+        healthy.fragments.get_mut(&100).unwrap().actors[0].dispatcher = vec![Dispatcher {
+            downstream_actor_id: vec![4],
+            ..Default::default()
+        }];
+        healthy.actor_splits = HashMap::from([(1, vec![split()])]);
+
+        // A dangling dispatcher target 99 and an orphaned split of actor 42.
+        let mut corrupted = table_fragments_with_actors(2, &[&[4, 5], &[6]]);
+        schedule_on_parallel_units(&mut corrupted);
+        corrupted.fragments.get_mut(&200).unwrap().actors[1].dispatcher = vec![Dispatcher {
+            downstream_actor_id: vec![6, 99],
+            ..Default::default()
+        }];
+        corrupted.actor_splits = HashMap::from([(4, vec![split()]), (42, vec![split()])]);
+        // Missing actor status of actors 5 and 6.
+        let expected_status = corrupted.actor_status.clone();
+        corrupted.actor_status.retain(|actor_id, _| *actor_id == 4);
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        for table_fragments in [healthy.clone(), corrupted] {
+            let table_id = table_fragments.table_id();
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+            fragment_manager
+                .mark_table_fragments_created(table_id)
+                .await?;
+        }
+        let healthy = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(1))
+            .await?;
+
+        let repairs = fragment_manager
+            .collect_fragment_repairs(&parallel_units)
+            .await;
+        assert_eq!(
+            repairs,
+            FragmentRepairs {
+                dangling_dispatcher_targets: HashMap::from([(
+                    TableId::new(2),
+                    HashMap::from([(5, HashSet::from([99]))])
+                )]),
+                missing_actor_status: HashMap::from([(
+                    TableId::new(2),
+                    BTreeMap::from([
+                        (5, expected_status[&5].clone()),
+                        (6, expected_status[&6].clone())
+                    ])
+                )]),
+                orphaned_actor_splits: HashMap::from([(TableId::new(2), HashSet::from([42]))]),
+            }
+        );
+        assert_eq!(repairs.describe().len(), 4);
+
+        // Only fix the orphaned actor splits.
+        let mut selected = repairs.clone();
+        selected.retain_kinds(&HashSet::from([FragmentRepairKind::OrphanedActorSplits]));
+        fragment_manager.apply_fragment_repairs(selected).await?;
+        let mut remaining = repairs;
+        remaining.orphaned_actor_splits.clear();
+        assert_eq!(
+            fragment_manager
+                .collect_fragment_repairs(&parallel_units)
+                .await,
+            remaining
+        );
+
+        fragment_manager.apply_fragment_repairs(remaining).await?;
+        assert!(fragment_manager
+            .collect_fragment_repairs(&parallel_units)
+            .await
+            .is_empty());
+        let corrupted = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(2))
+            .await?;
+        assert_eq!(corrupted.actor_status, expected_status);
+        assert_eq!(
+            corrupted.fragments[&200].actors[1].dispatcher[0].downstream_actor_id,
+            vec![6]
+        );
+        assert_eq!(
+            fragment_manager
+                .select_table_fragments_by_table_id(&TableId::new(1))
+                .await?
+                .to_protobuf(),
+            healthy.to_protobuf()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fragment_repairs_hash_dispatcher() -> MetaResult<()> {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1], &[2]]);
+        schedule_on_parallel_units(&mut table_fragments);
+        table_fragments.fragments.get_mut(&100).unwrap().actors[0].dispatcher = vec![Dispatcher {
+            r#type: DispatcherType::Hash as i32,
+            downstream_actor_id: vec![2, 99],
+            ..Default::default()
+        }];
+
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        let table_fragments = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(1))
+            .await?;
+
+        // The dangling target is reported, but repairing it is refused without any change.
+        let repairs = fragment_manager
+            .collect_fragment_repairs(&HashMap::new())
+            .await;
+        assert_eq!(
+            repairs.dangling_dispatcher_targets,
+            HashMap::from([(TableId::new(1), HashMap::from([(1, HashSet::from([99]))]))])
+        );
+        assert!(fragment_manager
+            .apply_fragment_repairs(repairs.clone())
+            .await
+            .is_err());
+        assert_eq!(
+            fragment_manager
+                .collect_fragment_repairs(&HashMap::new())
+                .await,
+            repairs
+        );
+        assert_eq!(
+            fragment_manager
+                .select_table_fragments_by_table_id(&TableId::new(1))
+                .await?
+                .to_protobuf(),
+            table_fragments.to_protobuf()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_legacy_plan_format() -> MetaResult<()> {
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2, 3]]);
+        for actor in &mut table_fragments.fragments.get_mut(&100).unwrap().actors {
+            actor.nodes = Some(StreamNode {
+                identity: "MaterializeExecutor".to_string(),
+                ..Default::default()
+            });
+        }
+        let legacy_prost = table_fragments.to_protobuf();
+
+        let env = MetaSrvEnv::for_test().await;
+        env.meta_store()
+            .put_cf(
+                &TableFragments::cf_name(),
+                table_fragments.key()?.encode_to_vec(),
+                legacy_prost.encode_to_vec(),
+            )
+            .await?;
+
+        // The legacy entry is rewritten on load, while the actors in memory are unchanged.
+        let fragment_manager = FragmentManager::new(env.clone()).await?;
+        let prost = ProstTableFragments::decode(
+            env.meta_store()
+                .get_cf(
+                    &TableFragments::cf_name(),
+                    &table_fragments.key()?.encode_to_vec(),
+                )
+                .await?
+                .as_slice(),
+        )
+        .unwrap();
+        assert!(prost.fragments[&100].plan_template.is_some());
+        assert!(prost.fragments[&100]
+            .actors
+            .iter()
+            .all(|actor| actor.nodes.is_none()));
+
+        let loaded = fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(1))
+            .await?;
+        assert!(!loaded.is_legacy_plan_format());
+        assert_eq!(loaded.actors(), table_fragments.actors());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_actor_index_consistency() -> MetaResult<()> {
+        use rand::rngs::StdRng;
+        use rand::seq::IteratorRandom;
+        use rand::{Rng, SeedableRng};
+
+        async fn check_actor_index(fragment_manager: &FragmentManager<MemStore>) {
+            let core = fragment_manager.get_fragment_read_guard().await;
+            let mut expected = HashMap::new();
+            for (&table_id, table_fragments) in &core.table_fragments {
+                for (&fragment_id, fragment) in &table_fragments.fragments {
+                    for actor in &fragment.actors {
+                        expected.insert(actor.actor_id, (table_id, fragment_id));
+                    }
+                }
+            }
+            assert_eq!(core.actor_to_location, expected);
+            for (&actor_id, &location) in &expected {
+                assert_eq!(core.get_fragment_by_actor(actor_id), Some(location));
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+        let mut next_table_id = 1;
+        let mut next_actor_id = 1;
+        let mut pending_reschedules = vec![];
+
+        for _ in 0..200 {
+            let (table_ids, fragment_ids, actor_ids) = {
+                let core = fragment_manager.get_fragment_read_guard().await;
+                let table_ids = core.table_fragments.keys().copied().collect_vec();
+                let fragment_ids = core
+                    .table_fragments
+                    .values()
+                    .flat_map(|tf| tf.fragment_ids())
+                    .collect_vec();
+                let actor_ids = core.actor_to_location.keys().copied().collect_vec();
+                (table_ids, fragment_ids, actor_ids)
+            };
+
+            match rng.gen_range(0..5) {
+                // Create a table with random fragments and actors.
+                0 if next_table_id < 100 => {
+                    let fragments = (0..rng.gen_range(1..4))
+                        .map(|_| {
+                            (0..rng.gen_range(1..5))
+                                .map(|_| {
+                                    next_actor_id += 1;
+                                    next_actor_id
+                                })
+                                .collect_vec()
+                        })
+                        .collect_vec();
+                    let fragments = fragments.iter().map(|f| f.as_slice()).collect_vec();
+                    fragment_manager
+                        .start_create_table_fragments(table_fragments_with_actors(
+                            next_table_id,
+                            &fragments,
+                        ))
+                        .await?;
+                    next_table_id += 1;
+                }
+                // Drop or cancel a random table.
+                1 => {
+                    if let Some(&table_id) = table_ids.iter().choose(&mut rng) {
+                        if rng.gen_bool(0.5) {
+                            fragment_manager
+                                .drop_table_fragments_vec(&HashSet::from([table_id]))
+                                .await?;
+                        } else {
+                            fragment_manager
+                                .cancel_create_table_fragments(&table_id)
+                                .await?;
+                        }
+                    }
+                }
+                // Add an actor to a random fragment.
+                2 => {
+                    if let Some(&fragment_id) = fragment_ids.iter().choose(&mut rng) {
+                        next_actor_id += 1;
+                        let actor = StreamActor {
+                            actor_id: next_actor_id,
+                            fragment_id,
+                            ..Default::default()
+                        };
+                        let applied = fragment_manager
+                            .pre_apply_reschedules(HashMap::from([(
+                                fragment_id,
+                                HashMap::from([(next_actor_id, (actor, ActorStatus::default()))]),
+                            )]))
+                            .await;
+                        pending_reschedules.push(applied);
+                    }
+                }
+                // Undo a previous addition of actors.
+                3 => {
+                    if !pending_reschedules.is_empty() {
+                        let applied = pending_reschedules
+                            .swap_remove(rng.gen_range(0..pending_reschedules.len()));
+                        fragment_manager.cancel_apply_reschedules(applied).await;
+                    }
+                }
+                // Remove a random actor.
+                _ => {
+                    if let Some(&actor_id) = actor_ids.iter().choose(&mut rng) {
+                        let (_, fragment_id) = fragment_manager
+                            .get_fragment_read_guard()
+                            .await
+                            .get_fragment_by_actor(actor_id)
+                            .unwrap();
+                        let reschedule = Reschedule {
+                            added_actors: vec![],
+                            removed_actors: vec![actor_id],
+                            vnode_bitmap_updates: HashMap::new(),
+                            upstream_fragment_dispatcher_ids: vec![],
+                            upstream_dispatcher_mapping: None,
+                            downstream_fragment_ids: vec![],
+                            actor_splits: HashMap::new(),
+                        };
+                        fragment_manager
+                            .post_apply_reschedules(HashMap::from([(fragment_id, reschedule)]))
+                            .await?;
+                    }
+                }
+            }
+
+            check_actor_index(&fragment_manager).await;
+        }
+
+        // The index is rebuilt from the meta store on restart.
+        let restarted = FragmentManager::new(fragment_manager.env.clone()).await?;
+        check_actor_index(&restarted).await;
+
+        Ok(())
+    }
+
+    /// Distributes the actors of the fragment by hash, one actor on each of the given parallel
+    /// units in order.
+    fn hash_distributed(
+        table_fragments: &mut TableFragments,
+        fragment_id: FragmentId,
+        parallel_unit_ids: &[ParallelUnitId],
+    ) {
+        let parallel_units = parallel_unit_ids
+            .iter()
+            .map(|&id| ParallelUnit {
+                id,
+                worker_node_id: id / 2 + 1,
+            })
+            .collect_vec();
+        let bitmaps = vnode_mapping_to_bitmaps(build_vnode_mapping(&parallel_units));
+        let fragment = table_fragments.fragments.get_mut(&fragment_id).unwrap();
+        fragment.distribution_type = FragmentDistributionType::Hash as i32;
+        let mut statuses = vec![];
+        for (actor, parallel_unit) in fragment.actors.iter_mut().zip_eq(parallel_units) {
+            actor.vnode_bitmap = Some(bitmaps[&parallel_unit.id].to_protobuf());
+            statuses.push((
+                actor.actor_id,
+                ActorStatus {
+                    parallel_unit: Some(parallel_unit),
+                    state: ActorState::Running as i32,
+                },
+            ));
+        }
+        table_fragments.actor_status.extend(statuses);
+    }
+
+    /// Makes the actors of the fragment scan `upstream_table_id` with chain nodes, and gives the
+    /// other actors of the table empty stream nodes.
+    fn chain_on(
+        table_fragments: &mut TableFragments,
+        fragment_id: FragmentId,
+        upstream_table_id: u32,
+    ) {
+        for fragment in table_fragments.fragments.values_mut() {
+            let node_body = (fragment.fragment_id == fragment_id).then(|| {
+                NodeBody::Chain(ChainNode {
+                    table_id: upstream_table_id,
+                    ..Default::default()
+                })
+            });
+            for actor in &mut fragment.actors {
+                actor.nodes = Some(StreamNode {
+                    node_body: node_body.clone(),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    /// Connects the actors by a dispatcher of `dispatcher_type`.
+    fn dispatch(
+        table_fragments: &mut TableFragments,
+        upstream: ActorId,
+        downstreams: Vec<ActorId>,
+        dispatcher_id: DispatcherId,
+        dispatcher_type: DispatcherType,
+    ) {
+        actor_mut(table_fragments, upstream)
+            .dispatcher
+            .push(Dispatcher {
+                r#type: dispatcher_type as i32,
+                downstream_actor_id: downstreams,
+                dispatcher_id,
+                ..Default::default()
+            });
+    }
+
+    /// Returns the number of vnodes of each actor after the reschedule.
+    fn vnode_counts(reschedule: &Reschedule) -> BTreeMap<ActorId, usize> {
+        let mapping = reschedule.upstream_dispatcher_mapping.as_ref().unwrap();
+        decompress_data(&mapping.original_indices, &mapping.data)
+            .into_iter()
+            .counts()
+            .into_iter()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_cross_table_reschedule_plan() -> MetaResult<()> {
+        let fragment_manager = FragmentManager::new(MetaSrvEnv::for_test().await).await?;
+
+        // Table 1 with actors 1, 2 -> no shuffle to chain actors 3, 4 of table 2 -> hash to actors
+        // 5, 6 of table 2.
+        let mut mview = table_fragments_with_actors(1, &[&[1, 2]]);
+        for actor in mview
+            .fragments
+            .values_mut()
+            .flat_map(|fragment| &mut fragment.actors)
+        {
+            actor.nodes = Some(StreamNode::default());
+        }
+        dispatch(&mut mview, 1, vec![3], 100, DispatcherType::NoShuffle);
+        dispatch(&mut mview, 2, vec![4], 100, DispatcherType::NoShuffle);
+        // To the chain actor 7 of table 3, which will be created later.
+        dispatch(&mut mview, 1, vec![7], 101, DispatcherType::NoShuffle);
+        hash_distributed(&mut mview, 100, &[0, 2]);
+        let mut chain_mview = table_fragments_with_actors(2, &[&[3, 4], &[5, 6]]);
+        chain_on(&mut chain_mview, 200, 1);
+        for actor_id in [3, 4] {
+            dispatch(
+                &mut chain_mview,
+                actor_id,
+                vec![5, 6],
+                200,
+                DispatcherType::Hash,
+            );
+        }
+        hash_distributed(&mut chain_mview, 200, &[0, 2]);
+        hash_distributed(&mut chain_mview, 201, &[0, 2]);
+        for table_fragments in [mview, chain_mview] {
+            let table_id = table_fragments.table_id();
+            fragment_manager
+                .start_create_table_fragments(table_fragments)
+                .await?;
+            fragment_manager
+                .mark_table_fragments_created(table_id)
+                .await?;
+        }
+
+        let worker_nodes = (1..=2)
+            .map(|worker_id| WorkerNode {
+                id: worker_id,
+                parallel_units: (0..2)
+                    .map(|i| ParallelUnit {
+                        id: (worker_id - 1) * 2 + i,
+                        worker_node_id: worker_id,
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect_vec();
+
+        // Scale out to 3 parallel units, keeping the ones in use.
+        let plans = fragment_manager
+            .cross_table_reschedule_plan(TableId::new(1), 3, &worker_nodes)
+            .await?;
+        assert_eq!(plans.len(), 2);
+        let mview_plan = &plans[&TableId::new(1)][&100];
+        let chain_plan = &plans[&TableId::new(2)][&200];
+        let downstream_plan = &plans[&TableId::new(2)][&201];
+        let mut added_actors = HashSet::new();
+        for (plan, retained_actors) in [
+            (mview_plan, [1, 2]),
+            (chain_plan, [3, 4]),
+            (downstream_plan, [5, 6]),
+        ] {
+            assert_eq!(plan.added_actors.len(), 1);
+            assert!(plan.removed_actors.is_empty());
+            let added_actor = plan.added_actors[0];
+            assert!(!retained_actors.contains(&added_actor));
+            assert!(added_actors.insert(added_actor));
+            assert!(plan.vnode_bitmap_updates.contains_key(&added_actor));
+            let vnode_counts = vnode_counts(plan);
+            assert_eq!(
+                vnode_counts.keys().copied().collect_vec(),
+                retained_actors
+                    .into_iter()
+                    .chain([added_actor])
+                    .sorted()
+                    .collect_vec()
+            );
+            assert_eq!(vnode_counts.values().sum::<usize>(), VIRTUAL_NODE_COUNT);
+        }
+        // The actors on the same parallel unit are paired by their vnodes across tables.
+        for (mview_actor, chain_actor) in [
+            (1, 3),
+            (2, 4),
+            (mview_plan.added_actors[0], chain_plan.added_actors[0]),
+        ] {
+            assert_eq!(
+                vnode_counts(mview_plan)[&mview_actor],
+                vnode_counts(chain_plan)[&chain_actor]
+            );
+        }
+        assert_eq!(
+            mview_plan.vnode_bitmap_updates[&mview_plan.added_actors[0]],
+            chain_plan.vnode_bitmap_updates[&chain_plan.added_actors[0]]
+        );
+        // The chain fragment is paired with its upstream across tables, instead of updating the
+        // dispatchers of the upstream.
+        assert!(mview_plan.downstream_fragment_ids.is_empty());
+        assert!(chain_plan.upstream_fragment_dispatcher_ids.is_empty());
+        // The dispatchers within the table are resolved in the same table.
+        assert_eq!(chain_plan.downstream_fragment_ids, vec![201]);
+        assert_eq!(
+            downstream_plan.upstream_fragment_dispatcher_ids,
+            vec![(200, 200)]
+        );
+
+        // Scale in to a single parallel unit.
+        let plans = fragment_manager
+            .cross_table_reschedule_plan(TableId::new(1), 1, &worker_nodes)
+            .await?;
+        for (plan, retained_actor, removed_actor) in [
+            (&plans[&TableId::new(1)][&100], 1, 2),
+            (&plans[&TableId::new(2)][&200], 3, 4),
+            (&plans[&TableId::new(2)][&201], 5, 6),
+        ] {
+            assert!(plan.added_actors.is_empty());
+            assert_eq!(plan.removed_actors, vec![removed_actor]);
+            assert_eq!(
+                vnode_counts(plan),
+                BTreeMap::from([(retained_actor, VIRTUAL_NODE_COUNT)])
+            );
+        }
+
+        // Not enough parallel units.
+        assert!(fragment_manager
+            .cross_table_reschedule_plan(TableId::new(1), 5, &worker_nodes)
+            .await
+            .is_err());
+        assert!(fragment_manager
+            .cross_table_reschedule_plan(TableId::new(3), 2, &worker_nodes)
+            .await
+            .is_err());
+
+        // A singleton chain fragment can't follow the upstream to more parallel units.
+        let mut singleton_chain_mview = table_fragments_with_actors(3, &[&[7]]);
+        chain_on(&mut singleton_chain_mview, 300, 1);
+        singleton_chain_mview.set_actor_status(BTreeMap::from([(
+            7,
+            actor_status(0, 1, ActorState::Running),
+        )]));
+        fragment_manager
+            .start_create_table_fragments(singleton_chain_mview)
+            .await?;
+        fragment_manager
+            .mark_table_fragments_created(TableId::new(3))
+            .await?;
+        assert!(fragment_manager
+            .cross_table_reschedule_plan(TableId::new(1), 2, &worker_nodes)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_sink_dispatch_type() -> MetaResult<()> {
+        let env = MetaSrvEnv::for_test().await;
+        let fragment_manager = FragmentManager::new(env.clone()).await?;
+
+        // The downstream actors 3 and 4 own the lower and upper half of the vnodes.
+        let mut downstream = table_fragments_with_actors(2, &[&[3, 4]]);
+        for (actor_id, lower_half) in [(3, true), (4, false)] {
+            let bitmap: Bitmap = (0..VIRTUAL_NODE_COUNT)
+                .map(|vnode| (vnode < VIRTUAL_NODE_COUNT / 2) == lower_half)
+                .collect();
+            actor_mut(&mut downstream, actor_id).vnode_bitmap = Some(bitmap.to_protobuf());
+        }
+
+        // The sink actor 1 hashes to actors 3 and 4 by a key other than the distribution key, and
+        // the sink actor 2 dispatches to actor 3 only.
+        let half = VIRTUAL_NODE_COUNT as u64 / 2;
+        let hash_dispatcher = Dispatcher {
+            r#type: DispatcherType::Hash as i32,
+            column_indices: vec![0],
+            hash_mapping: Some(ActorMapping {
+                original_indices: vec![half - 1, 2 * half - 1],
+                data: vec![3, 4],
+            }),
+            dispatcher_id: 1,
+            downstream_actor_id: vec![3, 4],
+            ..Default::default()
+        };
+        let mut table_fragments = table_fragments_with_actors(1, &[&[1, 2]]);
+        let sink_fragment = table_fragments.fragments.get_mut(&100).unwrap();
+        sink_fragment.fragment_type = FragmentType::Sink as i32;
+        for actor in &mut sink_fragment.actors {
+            actor.nodes = Some(StreamNode {
+                node_body: Some(NodeBody::Materialize(MaterializeNode {
+                    table: Some(Table {
+                        distribution_key: vec![1],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            });
+        }
+        actor_mut(&mut table_fragments, 1)
+            .dispatcher
+            .push(hash_dispatcher.clone());
+        actor_mut(&mut table_fragments, 2)
+            .dispatcher
+            .push(Dispatcher {
+                r#type: DispatcherType::Simple as i32,
+                dispatcher_id: 2,
+                downstream_actor_id: vec![3],
+                ..Default::default()
+            });
+        fragment_manager
+            .start_create_table_fragments(downstream)
+            .await?;
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+
+        async fn sink_dispatchers(
+            fragment_manager: &FragmentManager<MemStore>,
+        ) -> Vec<Vec<Dispatcher>> {
+            let core = fragment_manager.core.read().await;
+            core.table_fragments[&TableId::new(1)].fragments[&100]
+                .actors
+                .iter()
+                .map(|actor| actor.dispatcher.clone())
+                .collect_vec()
+        }
+
+        assert!(fragment_manager
+            .update_sink_dispatch_type(&TableId::new(1), DispatcherType::NoShuffle)
+            .await
+            .is_err());
+        assert!(fragment_manager
+            .update_sink_dispatch_type(&TableId::new(2), DispatcherType::Hash)
+            .await
+            .is_err());
+
+        // Only the simple dispatcher is updated, which hashes by the distribution key.
+        let updates = fragment_manager
+            .update_sink_dispatch_type(&TableId::new(1), DispatcherType::Hash)
+            .await?;
+        let single_mapping = ActorMapping {
+            original_indices: vec![2 * half - 1],
+            data: vec![3],
+        };
+        assert_eq!(
+            updates,
+            HashMap::from([(
+                2,
+                vec![DispatcherUpdate {
+                    actor_id: 2,
+                    dispatcher_id: 2,
+                    hash_mapping: Some(single_mapping.clone()),
+                    dispatcher_type: DispatcherType::Hash as i32,
+                    column_indices: vec![1],
+                    ..Default::default()
+                }]
+            )])
+        );
+        let dispatchers = sink_dispatchers(&fragment_manager).await;
+        assert_eq!(
+            dispatchers,
+            vec![
+                vec![hash_dispatcher],
+                vec![Dispatcher {
+                    r#type: DispatcherType::Hash as i32,
+                    column_indices: vec![1],
+                    hash_mapping: Some(single_mapping),
+                    dispatcher_id: 2,
+                    downstream_actor_id: vec![3],
+                    ..Default::default()
+                }]
+            ]
+        );
+        // The update is persisted.
+        let reloaded = FragmentManager::new(env).await?;
+        assert_eq!(sink_dispatchers(&reloaded).await, dispatchers);
+
+        // Actor 1 has more than one downstream actor, each expecting its own partition.
+        for new_dispatch_type in [DispatcherType::Simple, DispatcherType::Broadcast] {
+            assert!(fragment_manager
+                .update_sink_dispatch_type(&TableId::new(1), new_dispatch_type)
+                .await
+                .is_err());
+            assert_eq!(sink_dispatchers(&fragment_manager).await, dispatchers);
+        }
+
+        // The consumers of a broadcast expect all rows.
+        {
+            let mut core = fragment_manager.core.write().await;
+            let table_fragments = core.table_fragments.get_mut(&TableId::new(1)).unwrap();
+            let dispatcher = &mut actor_mut(table_fragments, 2).dispatcher[0];
+            dispatcher.set_type(DispatcherType::Broadcast);
+            dispatcher.column_indices.clear();
+            dispatcher.hash_mapping = None;
+        }
+        let dispatchers = sink_dispatchers(&fragment_manager).await;
+        assert!(fragment_manager
+            .update_sink_dispatch_type(&TableId::new(1), DispatcherType::Hash)
+            .await
+            .is_err());
+        assert_eq!(sink_dispatchers(&fragment_manager).await, dispatchers);
+
+        Ok(())
+    }
 }
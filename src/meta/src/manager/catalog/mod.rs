@@ -27,11 +27,15 @@ use itertools::Itertools;
 use risingwave_common::catalog::{
     valid_table_name, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, DEFAULT_SUPER_USER,
     DEFAULT_SUPER_USER_FOR_PG, DEFAULT_SUPER_USER_FOR_PG_ID, DEFAULT_SUPER_USER_ID,
-    PG_CATALOG_SCHEMA_NAME,
+    PG_CATALOG_SCHEMA_NAME, RW_CATALOG_SCHEMA_NAME,
 };
 use risingwave_common::{bail, ensure};
+use risingwave_connector::sink::s3_parquet::is_s3_parquet_sink;
+use risingwave_hummock_sdk::key::KeyEncodingVersion;
 use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
-use risingwave_pb::catalog::{Database, Index, Schema, Sink, Source, Table};
+use risingwave_pb::catalog::{
+    Database, Index, Schema, Sink, Source, SubscriptionProgress, Table, TableStatistics,
+};
 use risingwave_pb::meta::subscribe_response::{Info, Operation};
 use risingwave_pb::user::grant_privilege::{ActionWithGrantOption, Object};
 use risingwave_pb::user::update_user_request::UpdateField;
@@ -54,6 +58,15 @@ pub type IndexId = u32;
 
 pub type UserId = u32;
 
+/// The tables are created in the latest key encoding, since they're only written by the latest
+/// state tables.
+fn with_latest_key_encoding(table: &Table) -> Table {
+    Table {
+        key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
+        ..table.clone()
+    }
+}
+
 /// `commit_meta` provides a wrapper for committing metadata changes to both in-memory and
 /// meta store.
 /// * $`manager`: metadata manager, which should contains an env field to access meta store.
@@ -168,7 +181,11 @@ where
         let mut schemas = BTreeMapTransaction::new(&mut database_core.schemas);
         databases.insert(database.id, database.clone());
         let mut schemas_added = vec![];
-        for schema_name in [DEFAULT_SCHEMA_NAME, PG_CATALOG_SCHEMA_NAME] {
+        for schema_name in [
+            DEFAULT_SCHEMA_NAME,
+            PG_CATALOG_SCHEMA_NAME,
+            RW_CATALOG_SCHEMA_NAME,
+        ] {
             let schema = Schema {
                 id: self
                     .env
@@ -308,6 +325,7 @@ where
             catalog_deleted_ids.extend(
                 sinks_to_drop
                     .into_iter()
+                    .filter(|sink| !is_s3_parquet_sink(&sink.properties))
                     .map(|sink| StreamingJobId::Sink(sink.id.into())),
             );
 
@@ -461,6 +479,11 @@ where
         internal_tables: Vec<Table>,
         table: &Table,
     ) -> MetaResult<NotificationVersion> {
+        let table = &with_latest_key_encoding(table);
+        let internal_tables = internal_tables
+            .iter()
+            .map(with_latest_key_encoding)
+            .collect_vec();
         let core = &mut self.core.lock().await.database;
         let mut tables = BTreeMapTransaction::new(&mut core.tables);
         let key = (table.database_id, table.schema_id, table.name.clone());
@@ -757,6 +780,150 @@ where
         }
     }
 
+    /// Replaces the catalog of an existing source. Only the columns are allowed to change, which is
+    /// checked by the frontend.
+    pub async fn alter_source(&self, source: &Source) -> MetaResult<NotificationVersion> {
+        let core = &mut self.core.lock().await.database;
+        let mut sources = BTreeMapTransaction::new(&mut core.sources);
+        if !sources.contains_key(&source.id) {
+            return Err(MetaError::catalog_not_found(
+                "source",
+                source.id.to_string(),
+            ));
+        }
+        sources.insert(source.id, source.clone());
+        commit_meta!(self, sources)?;
+
+        let version = self
+            .notify_frontend(Operation::Update, Info::Source(source.to_owned()))
+            .await;
+
+        Ok(version)
+    }
+
+    /// Persists the statistics of a table collected by `ANALYZE`. Statistics collected from an
+    /// older epoch than the persisted ones are rejected.
+    pub async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: TableStatistics,
+    ) -> MetaResult<NotificationVersion> {
+        let core = &mut self.core.lock().await.database;
+        let mut tables = BTreeMapTransaction::new(&mut core.tables);
+        let mut table = match tables.get(&table_id) {
+            Some(table) => table.clone(),
+            None => {
+                return Err(MetaError::catalog_not_found("table", table_id.to_string()));
+            }
+        };
+        if let Some(existing) = &table.statistics && existing.epoch > statistics.epoch {
+            bail!(
+                "statistics of table {} at epoch {} are older than the persisted ones at epoch {}",
+                table_id,
+                statistics.epoch,
+                existing.epoch
+            );
+        }
+        table.statistics = Some(statistics);
+        tables.insert(table_id, table.clone());
+        commit_meta!(self, tables)?;
+
+        let version = self
+            .notify_frontend(Operation::Update, Info::Table(table))
+            .await;
+
+        Ok(version)
+    }
+
+    /// Advances the progress of a subscription, only if the persisted progress is still
+    /// `expected_progress`. Otherwise the rows have been consumed by a concurrent fetch.
+    ///
+    /// The frontends are not notified, since they get the progress with
+    /// [`Self::get_subscription_progress`] on each fetch.
+    pub async fn update_subscription_progress(
+        &self,
+        table_id: TableId,
+        expected_progress: SubscriptionProgress,
+        progress: SubscriptionProgress,
+    ) -> MetaResult<()> {
+        let core = &mut self.core.lock().await.database;
+        let mut tables = BTreeMapTransaction::new(&mut core.tables);
+        let mut table = match tables.get(&table_id) {
+            Some(table) if table.subscription_progress.is_some() => table.clone(),
+            _ => {
+                return Err(MetaError::catalog_not_found(
+                    "subscription",
+                    table_id.to_string(),
+                ));
+            }
+        };
+        if table.subscription_progress.as_ref() != Some(&expected_progress) {
+            bail!(
+                "progress of subscription {} has been changed by a concurrent fetch",
+                table.name
+            );
+        }
+        table.subscription_progress = Some(progress);
+        tables.insert(table_id, table);
+        commit_meta!(self, tables)?;
+
+        Ok(())
+    }
+
+    pub async fn get_subscription_progress(
+        &self,
+        table_id: TableId,
+    ) -> MetaResult<SubscriptionProgress> {
+        let core = &self.core.lock().await.database;
+        core.tables
+            .get(&table_id)
+            .and_then(|table| table.subscription_progress.clone())
+            .ok_or_else(|| MetaError::catalog_not_found("subscription", table_id.to_string()))
+    }
+
+    /// Lists the tables whose keys may still be in a legacy encoding. Fails if any table is in an
+    /// unknown encoding.
+    pub async fn list_legacy_key_encoding_tables(&self) -> MetaResult<Vec<TableId>> {
+        let core = &self.core.lock().await.database;
+        let mut table_ids = vec![];
+        for table in core.tables.values() {
+            let key_encoding = KeyEncodingVersion::from_u32(table.key_encoding_version)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown key encoding version {} of table {}",
+                        table.key_encoding_version,
+                        table.id
+                    )
+                })?;
+            if key_encoding != KeyEncodingVersion::LATEST {
+                table_ids.push(table.id);
+            }
+        }
+        Ok(table_ids)
+    }
+
+    /// Flips the key encoding version of the tables to the latest, after all their keys in a
+    /// legacy encoding are rewritten. The dropped tables are skipped.
+    pub async fn upgrade_table_key_encoding(&self, table_ids: &[TableId]) -> MetaResult<()> {
+        let core = &mut self.core.lock().await.database;
+        let mut tables = BTreeMapTransaction::new(&mut core.tables);
+        let mut upgraded_tables = vec![];
+        for table_id in table_ids {
+            if let Some(table) = tables.get(table_id) {
+                let table = with_latest_key_encoding(table);
+                tables.insert(*table_id, table.clone());
+                upgraded_tables.push(table);
+            }
+        }
+        commit_meta!(self, tables)?;
+
+        for table in upgraded_tables {
+            self.notify_frontend(Operation::Update, Info::Table(table))
+                .await;
+        }
+        Ok(())
+    }
+
     pub async fn drop_source(&self, source_id: SourceId) -> MetaResult<NotificationVersion> {
         let core = &mut *self.core.lock().await;
         let database_core = &mut core.database;
@@ -833,6 +1000,11 @@ where
         mview: &Table,
         internal_tables: Vec<Table>,
     ) -> MetaResult<NotificationVersion> {
+        let mview = &with_latest_key_encoding(mview);
+        let internal_tables = internal_tables
+            .iter()
+            .map(with_latest_key_encoding)
+            .collect_vec();
         let core = &mut self.core.lock().await.database;
         let mut tables = BTreeMapTransaction::new(&mut core.tables);
         let mut sources = BTreeMapTransaction::new(&mut core.sources);
@@ -1090,6 +1262,7 @@ where
         index: &Index,
         table: &Table,
     ) -> MetaResult<NotificationVersion> {
+        let table = &with_latest_key_encoding(table);
         let core = &mut self.core.lock().await.database;
         let key = (table.database_id, table.schema_id, index.name.clone());
 
@@ -1182,7 +1355,12 @@ where
         }
     }
 
-    pub async fn drop_sink(&self, sink_id: SinkId) -> MetaResult<NotificationVersion> {
+    /// Drops the sink, and returns the streaming job to drop along with it, which the `s3_parquet`
+    /// sinks don't have.
+    pub async fn drop_sink(
+        &self,
+        sink_id: SinkId,
+    ) -> MetaResult<(NotificationVersion, Option<StreamingJobId>)> {
         let core = &mut self.core.lock().await.database;
         let mut sinks = BTreeMapTransaction::new(&mut core.sinks);
         let sink = sinks.remove(sink_id);
@@ -1193,11 +1371,13 @@ where
                 core.decrease_ref_count(dependent_relation_id);
             }
 
+            let stream_job = (!is_s3_parquet_sink(&sink.properties))
+                .then(|| StreamingJobId::Sink(sink.id.into()));
             let version = self
                 .notify_frontend(Operation::Delete, Info::Sink(sink))
                 .await;
 
-            Ok(version)
+            Ok((version, stream_job))
         } else {
             Err(MetaError::catalog_not_found("sink", sink_id.to_string()))
         }
@@ -1448,13 +1628,28 @@ where
         true
     }
 
+    #[inline(always)]
+    fn is_object_owner(
+        database_core: &DatabaseManager<S>,
+        privilege: &GrantPrivilege,
+        user_id: UserId,
+    ) -> bool {
+        privilege
+            .object
+            .as_ref()
+            .and_then(|object| database_core.get_object_owner(object))
+            == Some(user_id)
+    }
+
     pub async fn grant_privilege(
         &self,
         user_ids: &[UserId],
         new_grant_privileges: &[GrantPrivilege],
         grantor: UserId,
     ) -> MetaResult<NotificationVersion> {
-        let core = &mut self.core.lock().await.user;
+        let core = &mut *self.core.lock().await;
+        let database_core = &core.database;
+        let core = &mut core.user;
         let mut users = BTreeMapTransaction::new(&mut core.user_info);
         let mut user_updated = Vec::with_capacity(user_ids.len());
         let grantor_info = users
@@ -1479,6 +1674,10 @@ where
             }
             if !grantor_info.is_super {
                 for new_grant_privilege in new_grant_privileges {
+                    // The owner of an object holds all the privileges on it with grant option.
+                    if Self::is_object_owner(database_core, new_grant_privilege, grantor) {
+                        continue;
+                    }
                     if let Some(privilege) = grantor_info
                         .grant_privileges
                         .iter()
@@ -1571,7 +1770,9 @@ where
         revoke_grant_option: bool,
         cascade: bool,
     ) -> MetaResult<NotificationVersion> {
-        let core = &mut self.core.lock().await.user;
+        let core = &mut *self.core.lock().await;
+        let database_core = &core.database;
+        let core = &mut core.user;
         let mut users = BTreeMapTransaction::new(&mut core.user_info);
         let mut user_updated = HashMap::new();
         let mut users_info: VecDeque<UserInfo> = VecDeque::new();
@@ -1583,6 +1784,9 @@ where
         let same_user = granted_by == revoke_by.id;
         if !revoke_by.is_super {
             for privilege in revoke_grant_privileges {
+                if Self::is_object_owner(database_core, privilege, revoke_by.id) {
+                    continue;
+                }
                 if let Some(user_privilege) = revoke_by
                     .grant_privileges
                     .iter()
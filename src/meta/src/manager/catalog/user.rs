@@ -74,6 +74,7 @@ impl UserManager {
 #[cfg(test)]
 mod tests {
     use risingwave_common::catalog::{DEFAULT_SUPER_USER, DEFAULT_SUPER_USER_ID};
+    use risingwave_pb::catalog::Source;
     use risingwave_pb::user::grant_privilege::{Action, ActionWithGrantOption, Object};
     use risingwave_pb::user::GrantPrivilege;
 
@@ -354,4 +355,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_owner_grant_privilege() -> MetaResult<()> {
+        let catalog_manager = CatalogManager::new(MetaSrvEnv::for_test().await).await?;
+        let (owner_id, owner) = (10, "owner");
+        let (user_id, user) = (11, "user");
+        catalog_manager
+            .create_user(&make_test_user(owner_id, owner))
+            .await?;
+        catalog_manager
+            .create_user(&make_test_user(user_id, user))
+            .await?;
+
+        let (source_id, other_source_id) = (100, 101);
+        {
+            let sources = &mut catalog_manager.core.lock().await.database.sources;
+            sources.insert(
+                source_id,
+                Source {
+                    id: source_id,
+                    owner: owner_id,
+                    ..Default::default()
+                },
+            );
+            sources.insert(
+                other_source_id,
+                Source {
+                    id: other_source_id,
+                    owner: DEFAULT_SUPER_USER_ID,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // The owner can grant privileges without holding them.
+        catalog_manager
+            .grant_privilege(
+                &[user_id],
+                &[make_privilege(
+                    Object::SourceId(source_id),
+                    &[Action::Select, Action::Insert],
+                    false,
+                )],
+                owner_id,
+            )
+            .await?;
+        let user = catalog_manager.get_user(user_id).await?;
+        assert_eq!(user.grant_privileges.len(), 1);
+        assert_eq!(user.grant_privileges[0].action_with_opts.len(), 2);
+        // But not on objects owned by others.
+        let res = catalog_manager
+            .grant_privilege(
+                &[user_id],
+                &[make_privilege(
+                    Object::SourceId(other_source_id),
+                    &[Action::Select],
+                    false,
+                )],
+                owner_id,
+            )
+            .await;
+        assert!(res.is_err());
+
+        // The owner can revoke the privileges as well.
+        catalog_manager
+            .revoke_privilege(
+                &[user_id],
+                &[make_privilege(
+                    Object::SourceId(source_id),
+                    &[Action::Insert],
+                    false,
+                )],
+                owner_id,
+                owner_id,
+                false,
+                false,
+            )
+            .await?;
+        let user = catalog_manager.get_user(user_id).await?;
+        assert_eq!(user.grant_privileges.len(), 1);
+        assert_eq!(
+            user.grant_privileges[0].action_with_opts[0].action,
+            Action::Select as i32
+        );
+
+        Ok(())
+    }
 }
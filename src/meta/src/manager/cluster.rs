@@ -20,14 +20,14 @@ use std::time::{Duration, SystemTime};
 use itertools::Itertools;
 use risingwave_common::types::ParallelUnitId;
 use risingwave_pb::common::worker_node::State;
-use risingwave_pb::common::{HostAddress, ParallelUnit, WorkerNode, WorkerType};
+use risingwave_pb::common::{HostAddress, ParallelUnit, WorkerNode, WorkerRole, WorkerType};
 use risingwave_pb::meta::heartbeat_request;
 use risingwave_pb::meta::subscribe_response::{Info, Operation};
 use tokio::sync::oneshot::Sender;
 use tokio::sync::{RwLock, RwLockReadGuard};
 use tokio::task::JoinHandle;
 
-use crate::manager::{IdCategory, LocalNotification, MetaSrvEnv};
+use crate::manager::{FragmentManager, IdCategory, LocalNotification, MetaSrvEnv};
 use crate::model::{MetadataModel, Worker, INVALID_EXPIRE_AT};
 use crate::rpc::metrics::MetaMetrics;
 use crate::storage::MetaStore;
@@ -57,6 +57,11 @@ impl Hash for WorkerKey {
 /// The id preserved for the meta node. Note that there's no such entry in cluster manager.
 pub const META_NODE_ID: u32 = 0;
 
+/// Whether streaming actors can be scheduled to the compute node.
+pub fn is_streaming_worker(worker: &WorkerNode) -> bool {
+    worker.role() != WorkerRole::Serving
+}
+
 /// [`ClusterManager`] manager cluster/worker meta data in [`MetaStore`].
 pub struct ClusterManager<S: MetaStore> {
     env: MetaSrvEnv<S>,
@@ -129,6 +134,7 @@ where
         r#type: WorkerType,
         host_address: HostAddress,
         worker_node_parallelism: usize,
+        role: WorkerRole,
     ) -> MetaResult<WorkerNode> {
         let mut core = self.core.write().await;
         match core.get_worker_by_host(host_address.clone()) {
@@ -154,6 +160,7 @@ where
                     host: Some(host_address.clone()),
                     state: State::Starting as i32,
                     parallel_units,
+                    role: role as i32,
                 };
 
                 let worker = Worker::from_protobuf(worker_node.clone());
@@ -180,9 +187,10 @@ where
 
         core.update_worker_node(worker.clone());
 
-        // Notify frontends of new compute node.
+        // Notify frontends of new compute node, or new frontend which may take over the snapshot
+        // exports.
         let worker_type = worker.worker_type();
-        if worker_type == WorkerType::ComputeNode {
+        if worker_type == WorkerType::ComputeNode || worker_type == WorkerType::Frontend {
             self.env
                 .notification_manager()
                 .notify_frontend(Operation::Add, Info::Node(worker.worker_node))
@@ -192,6 +200,53 @@ where
         Ok(())
     }
 
+    /// Changes the role of a worker node. The caller must make sure that no actor is on the node
+    /// if it can't run streaming actors with the new role.
+    pub async fn update_worker_node_role(
+        &self,
+        host_address: HostAddress,
+        role: WorkerRole,
+        fragment_manager: &FragmentManager<S>,
+    ) -> MetaResult<WorkerNode> {
+        // Hold both locks, so that no actors can be scheduled or moved to the node between the
+        // check below and the update.
+        let fragment_guard = fragment_manager.get_fragment_read_guard().await;
+        let mut core = self.core.write().await;
+        let mut worker = core.get_worker_by_host_checked(host_address)?;
+        if worker.worker_node.role() == role {
+            return Ok(worker.to_protobuf());
+        }
+        worker.worker_node.role = role as i32;
+        if !is_streaming_worker(&worker.worker_node) {
+            // There's no drain command, so the actors must be rescheduled away beforehand.
+            let actor_count = fragment_guard.count_worker_actors(worker.worker_id());
+            if actor_count > 0 {
+                return Err(anyhow::anyhow!(
+                    "worker node {} can't become {:?} with {} actors on it, reschedule them away first",
+                    worker.worker_id(),
+                    role,
+                    actor_count
+                )
+                .into());
+            }
+        }
+        worker.insert(self.env.meta_store()).await?;
+
+        core.update_worker_node(worker.clone());
+
+        // Notify frontends, which prefer the serving nodes for batch scans.
+        if worker.worker_type() == WorkerType::ComputeNode
+            && worker.worker_node.state == State::Running as i32
+        {
+            self.env
+                .notification_manager()
+                .notify_frontend(Operation::Update, Info::Node(worker.to_protobuf()))
+                .await;
+        }
+
+        Ok(worker.to_protobuf())
+    }
+
     pub async fn delete_worker_node(&self, host_address: HostAddress) -> MetaResult<WorkerType> {
         let mut core = self.core.write().await;
         let worker = core.get_worker_by_host_checked(host_address.clone())?;
@@ -204,8 +259,8 @@ where
         // Update core.
         core.delete_worker_node(worker);
 
-        // Notify frontends to delete compute node.
-        if worker_type == WorkerType::ComputeNode {
+        // Notify frontends to delete compute node or frontend.
+        if worker_type == WorkerType::ComputeNode || worker_type == WorkerType::Frontend {
             self.env
                 .notification_manager()
                 .notify_frontend(Operation::Delete, Info::Node(worker_node.clone()))
@@ -334,6 +389,16 @@ where
         core.list_worker_node(worker_type, worker_state)
     }
 
+    /// Returns the workers of all types, ordered by id.
+    pub async fn list_all_worker_node(&self) -> Vec<WorkerNode> {
+        let core = self.core.read().await;
+        core.workers
+            .values()
+            .map(|worker| worker.to_protobuf())
+            .sorted_by_key(|worker| worker.id)
+            .collect_vec()
+    }
+
     pub async fn list_active_parallel_units(&self) -> Vec<ParallelUnit> {
         let core = self.core.read().await;
         core.list_active_parallel_units()
@@ -344,6 +409,19 @@ where
         core.get_active_parallel_unit_count()
     }
 
+    /// Returns the running compute nodes that streaming actors can be scheduled to, see
+    /// [`is_streaming_worker`].
+    pub async fn list_active_streaming_worker_node(&self) -> Vec<WorkerNode> {
+        let core = self.core.read().await;
+        core.list_active_streaming_worker_node()
+    }
+
+    /// Returns the parallel units of [`Self::list_active_streaming_worker_node`].
+    pub async fn list_active_streaming_parallel_units(&self) -> Vec<ParallelUnit> {
+        let core = self.core.read().await;
+        core.list_active_streaming_parallel_units()
+    }
+
     /// Generate `parallel_degree` parallel units.
     async fn generate_cn_parallel_units(
         &self,
@@ -467,6 +545,27 @@ impl ClusterManagerCore {
             .collect()
     }
 
+    fn list_active_streaming_worker_node(&self) -> Vec<WorkerNode> {
+        self.list_worker_node(WorkerType::ComputeNode, Some(State::Running))
+            .into_iter()
+            .filter(is_streaming_worker)
+            .collect()
+    }
+
+    fn list_active_streaming_parallel_units(&self) -> Vec<ParallelUnit> {
+        let streaming_workers: HashSet<_> = self
+            .list_active_streaming_worker_node()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+
+        self.parallel_units
+            .iter()
+            .filter(|p| streaming_workers.contains(&p.worker_node_id))
+            .cloned()
+            .collect()
+    }
+
     fn count_worker_node(&self) -> HashMap<WorkerType, u64> {
         const MONITORED_WORKER_TYPES: [WorkerType; 3] = [
             WorkerType::Compactor,
@@ -497,7 +596,15 @@ impl ClusterManagerCore {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
+    use risingwave_common::catalog::TableId;
+    use risingwave_pb::meta::table_fragments::actor_status::ActorState;
+    use risingwave_pb::meta::table_fragments::{ActorStatus, Fragment};
+    use risingwave_pb::stream_plan::StreamActor;
+
     use super::*;
+    use crate::model::TableFragments;
     use crate::storage::MemStore;
 
     #[tokio::test]
@@ -519,7 +626,12 @@ mod tests {
                 port: 5000 + i as i32,
             };
             let worker_node = cluster_manager
-                .add_worker_node(WorkerType::ComputeNode, fake_host_address, fake_parallelism)
+                .add_worker_node(
+                    WorkerType::ComputeNode,
+                    fake_host_address,
+                    fake_parallelism,
+                    WorkerRole::Both,
+                )
                 .await
                 .unwrap();
             worker_nodes.push(worker_node);
@@ -560,6 +672,125 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_worker_node_role() -> MetaResult<()> {
+        let env = MetaSrvEnv::for_test().await;
+        let cluster_manager = ClusterManager::new(env.clone(), Duration::new(0, 0)).await?;
+
+        let fake_parallelism = 4;
+        let mut hosts = vec![];
+        for (i, role) in [WorkerRole::Streaming, WorkerRole::Serving, WorkerRole::Both]
+            .into_iter()
+            .enumerate()
+        {
+            let host = HostAddress {
+                host: "localhost".to_string(),
+                port: 5000 + i as i32,
+            };
+            let worker_node = cluster_manager
+                .add_worker_node(
+                    WorkerType::ComputeNode,
+                    host.clone(),
+                    fake_parallelism,
+                    role,
+                )
+                .await?;
+            assert_eq!(worker_node.role(), role);
+            cluster_manager.activate_worker_node(host.clone()).await?;
+            hosts.push(host);
+        }
+
+        // The serving-only node is listed, but excluded from the streaming nodes.
+        let roles = |workers: Vec<WorkerNode>| {
+            workers
+                .iter()
+                .map(WorkerNode::role)
+                .sorted_by_key(|role| *role as i32)
+                .collect_vec()
+        };
+        assert_eq!(
+            roles(cluster_manager.list_all_worker_node().await),
+            vec![WorkerRole::Both, WorkerRole::Streaming, WorkerRole::Serving]
+        );
+        assert_eq!(
+            roles(cluster_manager.list_active_streaming_worker_node().await),
+            vec![WorkerRole::Both, WorkerRole::Streaming]
+        );
+        assert_eq!(
+            cluster_manager
+                .list_active_streaming_parallel_units()
+                .await
+                .len(),
+            2 * fake_parallelism
+        );
+
+        // The role is persisted and reflected in the streaming nodes.
+        let fragment_manager = FragmentManager::new(env.clone()).await?;
+        let worker_node = cluster_manager
+            .update_worker_node_role(hosts[1].clone(), WorkerRole::Both, &fragment_manager)
+            .await?;
+        assert_eq!(worker_node.role(), WorkerRole::Both);
+        assert_eq!(
+            cluster_manager
+                .list_active_streaming_parallel_units()
+                .await
+                .len(),
+            3 * fake_parallelism
+        );
+        let recovered = ClusterManager::new(env, Duration::new(0, 0)).await?;
+        assert_eq!(recovered.list_active_streaming_worker_node().await.len(), 3);
+
+        // A node with actors on it can't become serving-only.
+        let worker_id = cluster_manager
+            .list_all_worker_node()
+            .await
+            .into_iter()
+            .find(|worker| worker.host.as_ref() == Some(&hosts[2]))
+            .unwrap()
+            .id;
+        let mut table_fragments = TableFragments::new(
+            TableId::new(1),
+            BTreeMap::from([(
+                1,
+                Fragment {
+                    fragment_id: 1,
+                    actors: vec![StreamActor {
+                        actor_id: 1,
+                        fragment_id: 1,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+            )]),
+        );
+        table_fragments.set_actor_status(BTreeMap::from([(
+            1,
+            ActorStatus {
+                parallel_unit: Some(ParallelUnit {
+                    id: 0,
+                    worker_node_id: worker_id,
+                }),
+                state: ActorState::Inactive as i32,
+            },
+        )]));
+        fragment_manager
+            .start_create_table_fragments(table_fragments)
+            .await?;
+        cluster_manager
+            .update_worker_node_role(hosts[2].clone(), WorkerRole::Serving, &fragment_manager)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            cluster_manager
+                .list_active_streaming_worker_node()
+                .await
+                .len(),
+            3
+        );
+
+        Ok(())
+    }
+
     async fn assert_cluster_manager(
         cluster_manager: &ClusterManager<MemStore>,
         parallel_count: usize,
@@ -585,6 +816,7 @@ mod tests {
                 WorkerType::ComputeNode,
                 fake_host_address_2,
                 fake_parallelism,
+                WorkerRole::Both,
             )
             .await
             .unwrap();
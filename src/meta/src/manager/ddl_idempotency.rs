@@ -0,0 +1,255 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use itertools::Itertools;
+use parking_lot::Mutex;
+use risingwave_pb::ddl_service::ddl_idempotency_record::State;
+use risingwave_pb::ddl_service::DdlIdempotencyRecord;
+use tokio::sync::watch;
+
+use crate::model::MetadataModel;
+use crate::storage::MetaStore;
+use crate::MetaResult;
+
+pub type DdlIdempotencyManagerRef<S> = Arc<DdlIdempotencyManager<S>>;
+
+/// The result of [`DdlIdempotencyManager::begin`].
+pub enum DdlAttempt {
+    /// The DDL hasn't been executed, or its previous execution failed. The caller executes it and
+    /// reports the progress with the guard.
+    Execute(DdlGuard),
+    /// The DDL has been executed and created the table with the id.
+    Created(u32),
+}
+
+/// Held by the task executing the DDL with the key. The requests retrying the DDL with the key wait
+/// for it to be dropped.
+pub struct DdlGuard {
+    key: String,
+    received_at_ms: u64,
+    core: Arc<Mutex<DdlIdempotencyCore>>,
+    /// Closed on drop to wake up the waiting requests.
+    _finished: watch::Sender<()>,
+}
+
+impl Drop for DdlGuard {
+    fn drop(&mut self) {
+        self.core.lock().executing.remove(&self.key);
+    }
+}
+
+#[derive(Default)]
+struct DdlIdempotencyCore {
+    records: HashMap<String, DdlIdempotencyRecord>,
+    /// The DDLs being executed in this meta node.
+    executing: HashMap<String, watch::Receiver<()>>,
+}
+
+/// `DdlIdempotencyManager` deduplicates the DDLs retried by the frontends with the same
+/// idempotency key, e.g. after the RPC fails because of a meta failover. The progress of each DDL
+/// is persisted in the meta store, and kept for `retention` after the DDL is received.
+pub struct DdlIdempotencyManager<S: MetaStore> {
+    meta_store: Arc<S>,
+    retention: Duration,
+    core: Arc<Mutex<DdlIdempotencyCore>>,
+}
+
+impl<S> DdlIdempotencyManager<S>
+where
+    S: MetaStore,
+{
+    /// Loads the records persisted in the meta store. The DDLs still creating were interrupted by
+    /// the restart of meta: the ones whose tables are in `created_table_ids` have finished except
+    /// for the record, and the others are cleaned up by the recovery and can be executed again.
+    pub async fn new(
+        meta_store: Arc<S>,
+        retention: Duration,
+        created_table_ids: &HashSet<u32>,
+    ) -> MetaResult<Self> {
+        let mut records = HashMap::new();
+        for mut record in DdlIdempotencyRecord::list(&*meta_store).await? {
+            if record.state() != State::Created {
+                if !created_table_ids.contains(&record.table_id) {
+                    DdlIdempotencyRecord::delete(&*meta_store, &record.key).await?;
+                    continue;
+                }
+                record.set_state(State::Created);
+                record.insert(&*meta_store).await?;
+            }
+            records.insert(record.key.clone(), record);
+        }
+
+        Ok(Self {
+            meta_store,
+            retention,
+            core: Arc::new(Mutex::new(DdlIdempotencyCore {
+                records,
+                executing: HashMap::new(),
+            })),
+        })
+    }
+
+    /// Returns whether the DDL with `key` is to be executed, or the table it has created. If the
+    /// DDL is being executed, e.g. for a request cancelled by the frontend, waits for it to finish
+    /// first.
+    pub async fn begin(&self, key: &str) -> MetaResult<DdlAttempt> {
+        self.vacuum().await?;
+        loop {
+            let mut finished = {
+                let mut core = self.core.lock();
+                if let Some(finished) = core.executing.get(key) {
+                    finished.clone()
+                } else if let Some(record) = core.records.get(key)
+                    && record.state() == State::Created
+                {
+                    return Ok(DdlAttempt::Created(record.table_id));
+                } else {
+                    let (tx, rx) = watch::channel(());
+                    core.executing.insert(key.to_owned(), rx);
+                    return Ok(DdlAttempt::Execute(DdlGuard {
+                        key: key.to_owned(),
+                        received_at_ms: now_ms(),
+                        core: self.core.clone(),
+                        _finished: tx,
+                    }));
+                }
+            };
+            // Returns an error once the guard is dropped, which is what we're waiting for.
+            let _ = finished.changed().await;
+        }
+    }
+
+    /// Records that the DDL is creating the table, before the table is actually created.
+    pub async fn record_creating(&self, guard: &DdlGuard, table_id: u32) -> MetaResult<()> {
+        self.update(guard, table_id, State::Creating).await
+    }
+
+    /// Records that the DDL has created the table.
+    pub async fn finish(&self, guard: DdlGuard, table_id: u32) -> MetaResult<()> {
+        self.update(&guard, table_id, State::Created).await
+    }
+
+    /// Forgets the failed DDL, so that a retry executes it again.
+    pub async fn abort(&self, guard: DdlGuard) -> MetaResult<()> {
+        self.forget(&guard.key).await
+    }
+
+    /// Forgets the DDL with `key`, e.g. after the table it created is dropped.
+    pub async fn forget(&self, key: &str) -> MetaResult<()> {
+        DdlIdempotencyRecord::delete(&*self.meta_store, &key.to_owned()).await?;
+        self.core.lock().records.remove(key);
+        Ok(())
+    }
+
+    async fn update(&self, guard: &DdlGuard, table_id: u32, state: State) -> MetaResult<()> {
+        let record = DdlIdempotencyRecord {
+            key: guard.key.clone(),
+            table_id,
+            state: state as i32,
+            received_at_ms: guard.received_at_ms,
+        };
+        record.insert(&*self.meta_store).await?;
+        self.core.lock().records.insert(guard.key.clone(), record);
+        Ok(())
+    }
+
+    /// Deletes the records of the DDLs received longer than `retention` ago. A retry after that
+    /// executes the DDL again.
+    async fn vacuum(&self) -> MetaResult<()> {
+        let retention_ms = self.retention.as_millis() as u64;
+        let now = now_ms();
+        let expired = {
+            let core = self.core.lock();
+            core.records
+                .values()
+                .filter(|record| {
+                    record.received_at_ms + retention_ms <= now
+                        && !core.executing.contains_key(&record.key)
+                })
+                .map(|record| record.key.clone())
+                .collect_vec()
+        };
+        for key in expired {
+            self.forget(&key).await?;
+        }
+        Ok(())
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("Clock may have gone backwards")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemStore;
+
+    #[tokio::test]
+    async fn test_ddl_idempotency() -> MetaResult<()> {
+        let meta_store = Arc::new(MemStore::default());
+        let retention = Duration::from_secs(3600);
+        let manager = Arc::new(
+            DdlIdempotencyManager::new(meta_store.clone(), retention, &HashSet::new()).await?,
+        );
+
+        // The retry waits for the original request, and returns its outcome.
+        let DdlAttempt::Execute(guard) = manager.begin("a").await? else {
+            panic!("the DDL is new");
+        };
+        let retry = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.begin("a").await }
+        });
+        manager.record_creating(&guard, 1).await?;
+        manager.finish(guard, 1).await?;
+        assert!(matches!(retry.await.unwrap()?, DdlAttempt::Created(1)));
+
+        // The failed DDL is executed again.
+        let DdlAttempt::Execute(guard) = manager.begin("b").await? else {
+            panic!("the DDL is new");
+        };
+        manager.record_creating(&guard, 2).await?;
+        manager.abort(guard).await?;
+        let DdlAttempt::Execute(guard) = manager.begin("b").await? else {
+            panic!("the DDL has failed");
+        };
+
+        // After a restart, the interrupted DDL is done if its table has been created.
+        manager.record_creating(&guard, 3).await?;
+        let DdlAttempt::Execute(guard) = manager.begin("c").await? else {
+            panic!("the DDL is new");
+        };
+        manager.record_creating(&guard, 4).await?;
+        let manager =
+            DdlIdempotencyManager::new(meta_store.clone(), retention, &HashSet::from([3])).await?;
+        assert!(matches!(manager.begin("a").await?, DdlAttempt::Created(1)));
+        assert!(matches!(manager.begin("b").await?, DdlAttempt::Created(3)));
+        assert!(matches!(manager.begin("c").await?, DdlAttempt::Execute(_)));
+
+        // The records expire after the retention.
+        let manager =
+            DdlIdempotencyManager::new(meta_store, Duration::ZERO, &HashSet::new()).await?;
+        assert!(matches!(manager.begin("a").await?, DdlAttempt::Execute(_)));
+
+        Ok(())
+    }
+}
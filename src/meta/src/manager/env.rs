@@ -18,14 +18,14 @@ use std::time::Duration;
 
 #[cfg(any(test, feature = "test"))]
 use prost::Message;
-use risingwave_pb::meta::MetaLeaderInfo;
 #[cfg(any(test, feature = "test"))]
 use risingwave_pb::meta::MetaLeaseInfo;
+use risingwave_pb::meta::{MetaLeaderInfo, SystemParams};
 use risingwave_rpc_client::{StreamClientPool, StreamClientPoolRef};
 
 use crate::manager::{
     IdGeneratorManager, IdGeneratorManagerRef, IdleManager, IdleManagerRef, NotificationManager,
-    NotificationManagerRef,
+    NotificationManagerRef, SystemParamsManager, SystemParamsManagerRef,
 };
 #[cfg(any(test, feature = "test"))]
 use crate::rpc::{META_CF_NAME, META_LEADER_KEY, META_LEASE_KEY};
@@ -55,6 +55,9 @@ where
     /// idle status manager.
     idle_manager: IdleManagerRef,
 
+    /// system params manager.
+    system_params_manager: SystemParamsManagerRef<S>,
+
     info: MetaLeaderInfo,
 
     /// options read by all services
@@ -82,6 +85,8 @@ pub struct MetaOpts {
     pub compaction_deterministic_test: bool,
 
     pub checkpoint_frequency: usize,
+    /// Trace every n-th barrier. 0 for never.
+    pub barrier_trace_sample_interval: usize,
 
     /// Interval of GC metadata in meta store and stale SSTs in object store.
     pub vacuum_interval_sec: u64,
@@ -95,6 +100,27 @@ pub struct MetaOpts {
     pub periodic_compaction_interval_sec: u64,
     /// Interval of reporting the number of nodes in the cluster.
     pub node_num_monitor_interval_sec: u64,
+    /// Table fragments staying in `Creating` state for longer than this are considered orphaned
+    /// by the meta store GC.
+    pub creating_table_fragments_gc_threshold_sec: u64,
+    /// The max number of splits that a source actor can be assigned.
+    pub max_splits_per_actor: usize,
+    /// The snapshot pinned by a frontend is released if the frontend doesn't renew it for this
+    /// long. 0 to disable the lease.
+    pub snapshot_pin_lease_sec: u64,
+    /// A DDL retried with the same idempotency key within this long after it's received returns
+    /// the outcome of the original one.
+    pub ddl_idempotency_retention_sec: u64,
+    /// Initial value of the system param `slow_barrier_threshold_ms`.
+    pub slow_barrier_threshold_ms: u64,
+    /// Initial value of the system param `slow_compaction_threshold_ms`.
+    pub slow_compaction_threshold_ms: u64,
+    /// Remote object store url to write meta backups to. Meta backup is disabled if `None`.
+    pub backup_storage_url: Option<String>,
+    /// Directory of meta backups in the backup storage.
+    pub backup_storage_directory: String,
+    /// Restore the meta store from the backup with this id before serving.
+    pub restore_from: Option<u64>,
 }
 
 impl Default for MetaOpts {
@@ -106,6 +132,7 @@ impl Default for MetaOpts {
             minimal_scheduling: false,
             max_idle_ms: 0,
             checkpoint_frequency: 10,
+            barrier_trace_sample_interval: 0,
             compaction_deterministic_test: false,
             vacuum_interval_sec: 30,
             min_sst_retention_time_sec: 3600 * 24 * 7,
@@ -113,11 +140,28 @@ impl Default for MetaOpts {
             enable_committed_sst_sanity_check: false,
             periodic_compaction_interval_sec: 60,
             node_num_monitor_interval_sec: 10,
+            creating_table_fragments_gc_threshold_sec: 3600,
+            max_splits_per_actor: 1024,
+            snapshot_pin_lease_sec: 60,
+            ddl_idempotency_retention_sec: 3600,
+            slow_barrier_threshold_ms: 10000,
+            slow_compaction_threshold_ms: 300000,
+            backup_storage_url: None,
+            backup_storage_directory: "backup".to_string(),
+            restore_from: None,
         }
     }
 }
 
 impl MetaOpts {
+    /// The system params of a new cluster.
+    pub fn init_system_params(&self) -> SystemParams {
+        SystemParams {
+            slow_barrier_threshold_ms: self.slow_barrier_threshold_ms,
+            slow_compaction_threshold_ms: self.slow_compaction_threshold_ms,
+        }
+    }
+
     /// some test need `enable_recovery=true`
     #[cfg(test)]
     pub fn test(enable_recovery: bool) -> Self {
@@ -142,6 +186,11 @@ where
         let stream_client_pool = Arc::new(StreamClientPool::default());
         let notification_manager = Arc::new(NotificationManager::new(meta_store.clone()).await);
         let idle_manager = Arc::new(IdleManager::new(opts.max_idle_ms));
+        let system_params_manager = Arc::new(
+            SystemParamsManager::new(meta_store.clone(), opts.init_system_params())
+                .await
+                .unwrap(),
+        );
 
         Self {
             id_gen_manager,
@@ -149,6 +198,7 @@ where
             notification_manager,
             stream_client_pool,
             idle_manager,
+            system_params_manager,
             info,
             opts: opts.into(),
         }
@@ -186,6 +236,14 @@ where
         self.idle_manager.deref()
     }
 
+    pub fn system_params_manager_ref(&self) -> SystemParamsManagerRef<S> {
+        self.system_params_manager.clone()
+    }
+
+    pub fn system_params_manager(&self) -> &SystemParamsManager<S> {
+        self.system_params_manager.deref()
+    }
+
     pub fn stream_client_pool_ref(&self) -> StreamClientPoolRef {
         self.stream_client_pool.clone()
     }
@@ -238,6 +296,11 @@ impl MetaSrvEnv<MemStore> {
         let notification_manager = Arc::new(NotificationManager::new(meta_store.clone()).await);
         let stream_client_pool = Arc::new(StreamClientPool::default());
         let idle_manager = Arc::new(IdleManager::disabled());
+        let system_params_manager = Arc::new(
+            SystemParamsManager::new(meta_store.clone(), opts.init_system_params())
+                .await
+                .unwrap(),
+        );
 
         Self {
             id_gen_manager,
@@ -245,6 +308,7 @@ impl MetaSrvEnv<MemStore> {
             notification_manager,
             stream_client_pool,
             idle_manager,
+            system_params_manager,
             info: leader_info,
             opts,
         }
@@ -25,6 +25,8 @@ use crate::storage::{MetaStore, MetaStoreError, DEFAULT_COLUMN_FAMILY};
 
 pub const ID_PREALLOCATE_INTERVAL: u64 = 1000;
 
+pub const HUMMOCK_SST_ID_CATEGORY: &str = "hummock_ss_table_id";
+
 pub type Id = u64;
 
 // TODO: remove unnecessary async trait.
@@ -40,6 +42,36 @@ pub trait IdGenerator: Sync + Send + 'static {
     }
 }
 
+fn category_gen_key(category: &str) -> String {
+    format!("{}_id_next_generator", category)
+}
+
+/// Advances the next id of `category` stored in `meta_store` by `delta`. Must be called before any
+/// [`StoredIdGenerator`] of the category is created.
+pub async fn advance_stored_id<S: MetaStore>(
+    meta_store: &S,
+    category: &str,
+    delta: u64,
+) -> MetadataModelResult<()> {
+    let category_gen_key = category_gen_key(category);
+    let next_id = match meta_store
+        .get_cf(DEFAULT_COLUMN_FAMILY, category_gen_key.as_bytes())
+        .await
+    {
+        Ok(value) => u64::from_be_bytes(value.as_slice().try_into().unwrap()),
+        Err(MetaStoreError::ItemNotFound(_)) => 0,
+        Err(e) => return Err(e.into()),
+    };
+    meta_store
+        .put_cf(
+            DEFAULT_COLUMN_FAMILY,
+            category_gen_key.into_bytes(),
+            next_id.checked_add(delta).unwrap().to_be_bytes().to_vec(),
+        )
+        .await?;
+    Ok(())
+}
+
 /// [`StoredIdGenerator`] implements id generator using metastore.
 pub struct StoredIdGenerator<S> {
     meta_store: Arc<S>,
@@ -53,7 +85,7 @@ where
     S: MetaStore,
 {
     pub async fn new(meta_store: Arc<S>, category: &str, start: Option<Id>) -> Self {
-        let category_gen_key = format!("{}_id_next_generator", category);
+        let category_gen_key = category_gen_key(category);
         let res = meta_store
             .get_cf(DEFAULT_COLUMN_FAMILY, category_gen_key.as_bytes())
             .await;
@@ -201,7 +233,7 @@ where
                 StoredIdGenerator::new(meta_store.clone(), "hummock_snapshot", Some(1)).await,
             ),
             hummock_ss_table_id: Arc::new(
-                StoredIdGenerator::new(meta_store.clone(), "hummock_ss_table_id", Some(1)).await,
+                StoredIdGenerator::new(meta_store.clone(), HUMMOCK_SST_ID_CATEGORY, Some(1)).await,
             ),
             hummock_compaction_task: Arc::new(
                 StoredIdGenerator::new(meta_store.clone(), "hummock_compaction_task", Some(1))
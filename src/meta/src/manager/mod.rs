@@ -15,16 +15,20 @@
 mod background_deleter;
 mod catalog;
 mod cluster;
+mod ddl_idempotency;
 mod env;
 mod id;
 mod idle;
 mod notification;
 mod streaming_job;
+mod system_param;
 pub use background_deleter::*;
 pub use catalog::*;
 pub use cluster::*;
+pub use ddl_idempotency::*;
 pub use env::*;
 pub use id::*;
 pub use idle::*;
 pub use notification::*;
 pub use streaming_job::*;
+pub use system_param::*;
@@ -0,0 +1,129 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use prost::Message;
+use risingwave_common::bail;
+use risingwave_pb::meta::SystemParams;
+use tokio::sync::RwLock;
+
+use crate::storage::{MetaStore, MetaStoreError, DEFAULT_COLUMN_FAMILY};
+use crate::MetaResult;
+
+const SYSTEM_PARAMS_KEY: &[u8] = b"system_params";
+
+pub type SystemParamsManagerRef<S> = Arc<SystemParamsManager<S>>;
+
+/// `SystemParamsManager` keeps the [`SystemParams`] of the cluster, which can be changed at runtime
+/// and are persisted in the meta store.
+pub struct SystemParamsManager<S: MetaStore> {
+    meta_store: Arc<S>,
+    params: RwLock<SystemParams>,
+}
+
+impl<S> SystemParamsManager<S>
+where
+    S: MetaStore,
+{
+    /// Load the params persisted in the meta store, or persist `init_params` if there are none.
+    pub async fn new(meta_store: Arc<S>, init_params: SystemParams) -> MetaResult<Self> {
+        let params = match meta_store
+            .get_cf(DEFAULT_COLUMN_FAMILY, SYSTEM_PARAMS_KEY)
+            .await
+        {
+            Ok(bytes) => SystemParams::decode(bytes.as_slice())
+                .context("failed to decode the system params")?,
+            Err(MetaStoreError::ItemNotFound(_)) => {
+                meta_store
+                    .put_cf(
+                        DEFAULT_COLUMN_FAMILY,
+                        SYSTEM_PARAMS_KEY.to_vec(),
+                        init_params.encode_to_vec(),
+                    )
+                    .await?;
+                init_params
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            meta_store,
+            params: RwLock::new(params),
+        })
+    }
+
+    pub async fn get_params(&self) -> SystemParams {
+        self.params.read().await.clone()
+    }
+
+    /// Set the param named `name` to `value`, and return the updated params.
+    pub async fn set_param(&self, name: &str, value: &str) -> MetaResult<SystemParams> {
+        let mut params = self.params.write().await;
+        let mut new_params = params.clone();
+        let field = match name {
+            "slow_barrier_threshold_ms" => &mut new_params.slow_barrier_threshold_ms,
+            "slow_compaction_threshold_ms" => &mut new_params.slow_compaction_threshold_ms,
+            _ => bail!("unrecognized system param: {}", name),
+        };
+        *field = match value.parse() {
+            Ok(value) => value,
+            Err(e) => bail!("invalid value of system param {}: {}", name, e),
+        };
+
+        self.meta_store
+            .put_cf(
+                DEFAULT_COLUMN_FAMILY,
+                SYSTEM_PARAMS_KEY.to_vec(),
+                new_params.encode_to_vec(),
+            )
+            .await?;
+        *params = new_params.clone();
+        Ok(new_params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemStore;
+
+    #[tokio::test]
+    async fn test_system_params() -> MetaResult<()> {
+        let meta_store = Arc::new(MemStore::default());
+        let init_params = SystemParams {
+            slow_barrier_threshold_ms: 1000,
+            slow_compaction_threshold_ms: 60000,
+        };
+        let manager = SystemParamsManager::new(meta_store.clone(), init_params.clone()).await?;
+        assert_eq!(manager.get_params().await, init_params);
+
+        let params = manager
+            .set_param("slow_barrier_threshold_ms", "500")
+            .await?;
+        assert_eq!(params.slow_barrier_threshold_ms, 500);
+        assert!(manager
+            .set_param("slow_barrier_threshold_ms", "-1")
+            .await
+            .is_err());
+        assert!(manager.set_param("unknown", "1").await.is_err());
+        assert_eq!(manager.get_params().await, params);
+
+        // The persisted params take precedence over the initial ones.
+        let manager = SystemParamsManager::new(meta_store, init_params).await?;
+        assert_eq!(manager.get_params().await, params);
+
+        Ok(())
+    }
+}
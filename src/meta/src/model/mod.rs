@@ -15,6 +15,7 @@
 mod barrier;
 mod catalog;
 mod cluster;
+mod ddl;
 mod error;
 mod notification;
 mod stream;
@@ -101,7 +102,7 @@ pub trait MetadataModel: std::fmt::Debug + Sized {
             .put_cf(
                 &Self::cf_name(),
                 self.key()?.encode_to_vec(),
-                self.to_protobuf().encode_to_vec(),
+                self.to_protobuf_encoded_vec(),
             )
             .await
             .map_err(Into::into)
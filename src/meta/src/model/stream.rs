@@ -14,19 +14,24 @@
 
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use itertools::Itertools;
+use prost::Message;
+use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::TableId;
 use risingwave_common::types::ParallelUnitId;
 use risingwave_common::util::is_stream_source;
 use risingwave_connector::source::SplitImpl;
 use risingwave_pb::common::{Buffer, ParallelUnit, ParallelUnitMapping};
+use risingwave_pb::meta::table_fragments::actor_plan_delta::MergeUpstreams;
 use risingwave_pb::meta::table_fragments::actor_status::ActorState;
-use risingwave_pb::meta::table_fragments::{ActorStatus, Fragment, State};
+use risingwave_pb::meta::table_fragments::{ActorPlanDelta, ActorStatus, Fragment, State};
 use risingwave_pb::meta::TableFragments as ProstTableFragments;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
 use risingwave_pb::stream_plan::{FragmentType, SourceNode, StreamActor, StreamNode};
 
 use super::{ActorId, FragmentId};
+use crate::backup_restore::RestoreError;
 use crate::manager::{SourceId, WorkerId};
 use crate::model::{MetadataModel, MetadataModelResult};
 use crate::stream::{build_actor_connector_splits, build_actor_split_impls, SplitAssignment};
@@ -34,6 +39,10 @@ use crate::stream::{build_actor_connector_splits, build_actor_split_impls, Split
 /// Column family name for table fragments.
 const TABLE_FRAGMENTS_CF_NAME: &str = "cf/table_fragments";
 
+/// The format version of [`TableFragments::serialize_for_restore`]. Bumped on incompatible changes
+/// to the encoding, and the decoding of the older versions must be kept.
+const TABLE_FRAGMENTS_BACKUP_FORMAT_VERSION: u32 = 1;
+
 /// Fragments of a materialized view
 ///
 /// We store whole fragments in a single column family as follow:
@@ -54,6 +63,10 @@ pub struct TableFragments {
 
     /// The splits of actors
     pub(crate) actor_splits: HashMap<ActorId, Vec<SplitImpl>>,
+
+    /// Whether it's loaded from the legacy format which persists the whole plan of every actor,
+    /// and should be rewritten to the meta store.
+    legacy_plan_format: bool,
 }
 
 impl MetadataModel for TableFragments {
@@ -74,13 +87,27 @@ impl MetadataModel for TableFragments {
         }
     }
 
+    /// Encodes with [`TableFragments::to_compressed_protobuf`] for the meta store.
+    fn to_protobuf_encoded_vec(&self) -> Vec<u8> {
+        self.to_compressed_protobuf().encode_to_vec()
+    }
+
     fn from_protobuf(prost: Self::ProstType) -> Self {
+        let legacy_plan_format = prost.fragments.values().any(|fragment| {
+            fragment.plan_template.is_none()
+                && fragment.actors.iter().any(|actor| actor.nodes.is_some())
+        });
         Self {
             table_id: TableId::new(prost.table_id),
             state: prost.state(),
-            fragments: prost.fragments.into_iter().collect(),
+            fragments: prost
+                .fragments
+                .into_iter()
+                .map(|(fragment_id, fragment)| (fragment_id, decompress_fragment_plans(fragment)))
+                .collect(),
             actor_status: prost.actor_status.into_iter().collect(),
             actor_splits: build_actor_split_impls(&prost.actor_splits),
+            legacy_plan_format,
         }
     }
 
@@ -98,9 +125,61 @@ impl TableFragments {
             fragments,
             actor_status: BTreeMap::default(),
             actor_splits: HashMap::default(),
+            legacy_plan_format: false,
+        }
+    }
+
+    /// Returns the protobuf to persist, where the plan shared by the actors of a fragment is
+    /// stored only once. [`MetadataModel::from_protobuf`] accepts both this and the uncompressed
+    /// format.
+    pub fn to_compressed_protobuf(&self) -> ProstTableFragments {
+        let mut prost = self.to_protobuf();
+        for fragment in prost.fragments.values_mut() {
+            compress_fragment_plans(fragment);
+        }
+        prost
+    }
+
+    /// Returns a self-contained backup with the plans of all actors, the vnode mappings and the
+    /// split assignments, prefixed with the big-endian `u32` format version so that the backups
+    /// stay readable after the format changes. See [`TableFragments::deserialize_for_restore`].
+    pub fn serialize_for_restore(&self) -> Bytes {
+        let prost = self.to_protobuf();
+        let mut buf = BytesMut::with_capacity(4 + prost.encoded_len());
+        buf.put_u32(TABLE_FRAGMENTS_BACKUP_FORMAT_VERSION);
+        prost
+            .encode(&mut buf)
+            .expect("encoding into a buffer with enough capacity never fails");
+        buf.freeze()
+    }
+
+    /// Decodes a backup of any supported format version returned by
+    /// [`TableFragments::serialize_for_restore`].
+    pub fn deserialize_for_restore(bytes: &Bytes) -> Result<TableFragments, RestoreError> {
+        if bytes.len() < 4 {
+            return Err(RestoreError::Truncated {
+                expected: 4,
+                actual: bytes.len(),
+            });
+        }
+        let mut buf = bytes.clone();
+        match buf.get_u32() {
+            1 => Ok(Self::from_protobuf(ProstTableFragments::decode(buf)?)),
+            version => Err(RestoreError::UnsupportedVersion(version)),
         }
     }
 
+    /// Returns whether it's loaded from the legacy format which persists the whole plan of every
+    /// actor. See [`TableFragments::mark_plan_format_migrated`].
+    pub fn is_legacy_plan_format(&self) -> bool {
+        self.legacy_plan_format
+    }
+
+    /// Marks that it has been rewritten to the meta store in the current format.
+    pub fn mark_plan_format_migrated(&mut self) {
+        self.legacy_plan_format = false;
+    }
+
     pub fn fragment_ids(&self) -> impl Iterator<Item = FragmentId> + '_ {
         self.fragments.keys().cloned()
     }
@@ -146,10 +225,25 @@ impl TableFragments {
         }
     }
 
+    /// Returns the number of actors in the given state.
+    pub fn actor_count_in_state(&self, state: ActorState) -> usize {
+        self.actor_status
+            .values()
+            .filter(|actor_status| actor_status.state() == state)
+            .count()
+    }
+
     pub fn set_actor_splits_by_split_assignment(&mut self, split_assignment: SplitAssignment) {
         self.actor_splits = split_assignment.into_values().flatten().collect();
     }
 
+    /// Merges the splits of some actors into the current ones, e.g. after discovering new splits
+    /// for part of the source actors. The actors in `new_splits` have their splits replaced, and
+    /// the others keep their current splits.
+    pub fn merge_actor_splits(&mut self, new_splits: HashMap<ActorId, Vec<SplitImpl>>) {
+        self.actor_splits.extend(new_splits);
+    }
+
     /// Returns actor ids associated with this table.
     pub fn actor_ids(&self) -> Vec<ActorId> {
         self.fragments
@@ -158,6 +252,22 @@ impl TableFragments {
             .collect()
     }
 
+    /// Checks that no actor id appears more than once across the fragments of this table. Returns
+    /// the sorted duplicated actor ids on failure.
+    pub fn verify_actor_id_uniqueness(&self) -> Result<(), Vec<ActorId>> {
+        let duplicates = self
+            .actor_ids()
+            .into_iter()
+            .duplicates()
+            .sorted()
+            .collect_vec();
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(duplicates)
+        }
+    }
+
     /// Returns actors associated with this table.
     pub fn actors(&self) -> Vec<StreamActor> {
         self.fragments
@@ -271,28 +381,50 @@ impl TableFragments {
             .collect()
     }
 
-    /// Resolve dependent table
-    fn resolve_dependent_table(stream_node: &StreamNode, table_ids: &mut HashSet<TableId>) {
+    /// Resolve the upstream tables scanned by the chain nodes.
+    fn resolve_upstream_tables(stream_node: &StreamNode, table_ids: &mut HashSet<TableId>) {
         if let Some(NodeBody::Chain(chain)) = stream_node.node_body.as_ref() {
             table_ids.insert(TableId::new(chain.table_id));
         }
 
         for child in &stream_node.input {
-            Self::resolve_dependent_table(child, table_ids);
+            Self::resolve_upstream_tables(child, table_ids);
         }
     }
 
-    /// Returns dependent table ids.
-    pub fn dependent_table_ids(&self) -> HashSet<TableId> {
+    /// Returns the ids of the upstream tables that the chain actors scan, i.e. the tables whose
+    /// dispatchers send to the chain actors of this table.
+    pub fn upstream_table_ids(&self) -> HashSet<TableId> {
         let mut table_ids = HashSet::new();
-        self.fragments.values().for_each(|fragment| {
-            let actor = &fragment.actors[0];
-            Self::resolve_dependent_table(actor.nodes.as_ref().unwrap(), &mut table_ids);
-        });
-
+        for actor in self
+            .fragments
+            .values()
+            .flat_map(|fragment| &fragment.actors)
+        {
+            Self::resolve_upstream_tables(actor.nodes.as_ref().unwrap(), &mut table_ids);
+        }
         table_ids
     }
 
+    /// Returns the actors containing chain nodes grouped by the upstream table they scan, ordered
+    /// by the upstream table id. An actor scanning multiple upstream tables is grouped by the
+    /// first one.
+    pub fn chain_actor_ids_by_upstream_table(&self) -> BTreeMap<TableId, Vec<ActorId>> {
+        let mut actor_ids: BTreeMap<TableId, Vec<ActorId>> = BTreeMap::new();
+        for actor in self
+            .fragments
+            .values()
+            .flat_map(|fragment| &fragment.actors)
+        {
+            let mut table_ids = HashSet::new();
+            Self::resolve_upstream_tables(actor.nodes.as_ref().unwrap(), &mut table_ids);
+            if let Some(table_id) = table_ids.into_iter().min() {
+                actor_ids.entry(table_id).or_default().push(actor.actor_id);
+            }
+        }
+        actor_ids
+    }
+
     /// Returns states of actors group by worker id.
     pub fn worker_actor_states(&self) -> BTreeMap<WorkerId, Vec<(ActorId, ActorState)>> {
         let mut map = BTreeMap::default();
@@ -423,6 +555,29 @@ impl TableFragments {
             .collect()
     }
 
+    /// Returns the fragments in this table that receive the output of `fragment_id` through the
+    /// dispatchers of its actors. The fragments of other tables are not included.
+    pub fn downstream_fragment_ids(&self, fragment_id: FragmentId) -> HashSet<FragmentId> {
+        let Some(fragment) = self.fragments.get(&fragment_id) else {
+            return HashSet::new();
+        };
+        let downstream_actor_ids: HashSet<_> = fragment
+            .actors
+            .iter()
+            .flat_map(|actor| &actor.dispatcher)
+            .flat_map(|dispatcher| dispatcher.downstream_actor_id.iter().copied())
+            .collect();
+        self.fragments
+            .values()
+            .filter(|f| {
+                f.actors
+                    .iter()
+                    .any(|actor| downstream_actor_ids.contains(&actor.actor_id))
+            })
+            .map(|f| f.fragment_id)
+            .collect()
+    }
+
     /// Generate topological order of fragments. If `index(a) < index(b)` in vec, then a is the
     /// downstream of b.
     pub fn generate_topological_order(&self) -> Vec<FragmentId> {
@@ -519,4 +674,582 @@ impl TableFragments {
             .values()
             .flat_map(|f| f.state_table_ids.clone())
     }
+
+    /// Returns a human-readable tree of the fragments and actors with their runtime state, for
+    /// logs and admin commands. Fragments are marked with `[S]` for source, `[M]` for
+    /// materialize and `[-]` for others, and actors with `(R)` for running, `(I)` for inactive
+    /// and `(?)` for unknown.
+    ///
+    /// ```text
+    /// TableFragments 1 (Created)
+    /// +-- [S] Fragment 1: Source, Hash, 2 actors -> 2
+    /// |   +-- (R) Actor 10 @ parallel unit 0, vnodes 0-127
+    /// |   `-- (I) Actor 11 @ parallel unit 1, vnodes 128-255
+    /// `-- [M] Fragment 2: Sink, Single, 1 actor
+    ///     `-- (R) Actor 20 @ parallel unit 2
+    /// ```
+    pub fn debug_dump(&self) -> String {
+        let mut lines = vec![format!(
+            "TableFragments {} ({:?})",
+            self.table_id,
+            self.state()
+        )];
+        let fragment_count = self.fragments.len();
+        for (i, fragment) in self.fragments.values().enumerate() {
+            let last_fragment = i + 1 == fragment_count;
+            let (branch, indent) = if last_fragment {
+                ("`-- ", "    ")
+            } else {
+                ("+-- ", "|   ")
+            };
+
+            let icon = match fragment.fragment_type() {
+                FragmentType::Source => "[S]",
+                FragmentType::Sink => "[M]",
+                _ => "[-]",
+            };
+            let actor_count = fragment.actors.len();
+            let mut line = format!(
+                "{}{} Fragment {}: {:?}, {:?}, {} actor{}",
+                branch,
+                icon,
+                fragment.fragment_id,
+                fragment.fragment_type(),
+                fragment.distribution_type(),
+                actor_count,
+                if actor_count == 1 { "" } else { "s" }
+            );
+            let downstream_fragment_ids = self
+                .downstream_fragment_ids(fragment.fragment_id)
+                .into_iter()
+                .sorted()
+                .collect_vec();
+            if !downstream_fragment_ids.is_empty() {
+                line.push_str(&format!(
+                    " -> {}",
+                    downstream_fragment_ids.iter().join(", ")
+                ));
+            }
+            lines.push(line);
+
+            for (j, actor) in fragment.actors.iter().enumerate() {
+                let actor_branch = if j + 1 == actor_count { "`-- " } else { "+-- " };
+                let status = self.actor_status.get(&actor.actor_id);
+                let state = match status.map(|status| status.state()) {
+                    Some(ActorState::Running) => "(R)",
+                    Some(ActorState::Inactive) => "(I)",
+                    _ => "(?)",
+                };
+                let mut line = format!(
+                    "{}{}{} Actor {}",
+                    indent, actor_branch, state, actor.actor_id
+                );
+                if let Some(parallel_unit) = status.and_then(|status| status.parallel_unit.as_ref())
+                {
+                    line.push_str(&format!(" @ parallel unit {}", parallel_unit.id));
+                }
+                if let Some(vnode_bitmap) = &actor.vnode_bitmap {
+                    line.push_str(&format!(
+                        ", vnodes {}",
+                        format_vnode_ranges(&Bitmap::from(vnode_bitmap))
+                    ));
+                }
+                lines.push(line);
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Formats the set bits of a vnode bitmap as ranges, e.g. `0-63, 128-191`.
+fn format_vnode_ranges(bitmap: &Bitmap) -> String {
+    let vnodes = bitmap.iter().positions(|set| set).collect_vec();
+    if vnodes.is_empty() {
+        return "none".to_string();
+    }
+    let mut ranges = vec![];
+    let mut start = vnodes[0];
+    for (&prev, &vnode) in vnodes.iter().tuple_windows() {
+        if vnode != prev + 1 {
+            ranges.push((start, prev));
+            start = vnode;
+        }
+    }
+    ranges.push((start, *vnodes.last().unwrap()));
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .join(", ")
+}
+
+/// Clears the upstream actor ids of all merge nodes in `stream_node`, and collects them in
+/// pre-order.
+fn strip_merge_upstreams(stream_node: &mut StreamNode, merge_upstreams: &mut Vec<MergeUpstreams>) {
+    if let Some(NodeBody::Merge(merge)) = stream_node.node_body.as_mut() {
+        merge_upstreams.push(MergeUpstreams {
+            upstream_actor_id: std::mem::take(&mut merge.upstream_actor_id),
+        });
+    }
+    for input in &mut stream_node.input {
+        strip_merge_upstreams(input, merge_upstreams);
+    }
+}
+
+/// Fills the upstream actor ids of all merge nodes in `stream_node` in pre-order. The reverse of
+/// [`strip_merge_upstreams`].
+fn fill_merge_upstreams(
+    stream_node: &mut StreamNode,
+    merge_upstreams: &mut impl Iterator<Item = MergeUpstreams>,
+) {
+    if let Some(NodeBody::Merge(merge)) = stream_node.node_body.as_mut() {
+        merge.upstream_actor_id = merge_upstreams
+            .next()
+            .expect("missing upstreams of merge node")
+            .upstream_actor_id;
+    }
+    for input in &mut stream_node.input {
+        fill_merge_upstreams(input, merge_upstreams);
+    }
+}
+
+/// Compresses the fragment to persist, where the plan shared by the actors is stored only once in
+/// `plan_template`, and each actor only keeps the upstreams of its merge nodes in
+/// `actor_plan_deltas`. Actors whose plans differ from the template in other ways keep their whole
+/// plans.
+fn compress_fragment_plans(fragment: &mut Fragment) {
+    let mut plan_template = None;
+    let mut actor_plan_deltas = HashMap::new();
+    for actor in &mut fragment.actors {
+        let Some(mut nodes) = actor.nodes.clone() else {
+            continue;
+        };
+        let mut merge_upstreams = vec![];
+        strip_merge_upstreams(&mut nodes, &mut merge_upstreams);
+        if *plan_template.get_or_insert_with(|| nodes.clone()) == nodes {
+            actor.nodes = None;
+            actor_plan_deltas.insert(actor.actor_id, ActorPlanDelta { merge_upstreams });
+        }
+    }
+    fragment.plan_template = plan_template;
+    fragment.actor_plan_deltas = actor_plan_deltas;
+}
+
+/// Rebuilds the plans of actors in a fragment from the meta store. The reverse of
+/// [`compress_fragment_plans`]. Fragments in the legacy format are returned as is.
+fn decompress_fragment_plans(mut fragment: Fragment) -> Fragment {
+    let mut actor_plan_deltas = std::mem::take(&mut fragment.actor_plan_deltas);
+    if let Some(plan_template) = fragment.plan_template.take() {
+        for actor in &mut fragment.actors {
+            if let Some(delta) = actor_plan_deltas.remove(&actor.actor_id) {
+                let mut nodes = plan_template.clone();
+                fill_merge_upstreams(&mut nodes, &mut delta.merge_upstreams.into_iter());
+                actor.nodes = Some(nodes);
+            }
+        }
+    }
+    fragment
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use risingwave_connector::source::datagen::DatagenSplit;
+    use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
+    use risingwave_pb::stream_plan::{
+        ChainNode, Dispatcher, HashAggNode, MaterializeNode, MergeNode,
+    };
+
+    use super::*;
+
+    fn make_fragment(fragment_id: FragmentId, actors: &[(ActorId, &[ActorId])]) -> Fragment {
+        Fragment {
+            fragment_id,
+            actors: actors
+                .iter()
+                .map(|(actor_id, downstream_actor_ids)| StreamActor {
+                    actor_id: *actor_id,
+                    fragment_id,
+                    dispatcher: vec![Dispatcher {
+                        downstream_actor_id: downstream_actor_ids.to_vec(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// A backup of format version 1, which must stay readable.
+    const TABLE_FRAGMENTS_BACKUP_V1: &[u8] = &[
+        0x00, 0x00, 0x00, 0x01, // format version
+        0x08, 0x01, // table_id
+        0x10, 0x02, // state
+        0x1a, 0x1b, 0x08, 0x01, 0x12, 0x17, // fragments
+        0x08, 0x01, 0x18, 0x02, // fragment_id, distribution_type
+        0x22, 0x04, 0x08, 0x01, 0x10, 0x01, // actors
+        0x2a, 0x08, 0x08, 0x01, 0x12, 0x01, 0x01, 0x1a, 0x01, 0x03, // vnode_mapping
+        0x32, 0x01, 0x01, // state_table_ids
+        0x22, 0x0c, 0x08, 0x01, 0x12, 0x08, // actor_status
+        0x0a, 0x04, 0x08, 0x03, 0x10, 0x01, 0x10, 0x02, // parallel_unit, state
+    ];
+
+    fn make_backup_table_fragments() -> TableFragments {
+        let fragment = Fragment {
+            fragment_id: 1,
+            distribution_type: FragmentDistributionType::Hash as i32,
+            actors: vec![StreamActor {
+                actor_id: 1,
+                fragment_id: 1,
+                ..Default::default()
+            }],
+            vnode_mapping: Some(ParallelUnitMapping {
+                fragment_id: 1,
+                original_indices: vec![1],
+                data: vec![3],
+            }),
+            state_table_ids: vec![1],
+            ..Default::default()
+        };
+        let mut table_fragments =
+            TableFragments::new(TableId::new(1), BTreeMap::from([(1, fragment)]));
+        table_fragments.set_state(State::Created);
+        table_fragments.set_actor_status(BTreeMap::from([(
+            1,
+            ActorStatus {
+                parallel_unit: Some(ParallelUnit {
+                    id: 3,
+                    worker_node_id: 1,
+                }),
+                state: ActorState::Running as i32,
+            },
+        )]));
+        table_fragments
+    }
+
+    #[test]
+    fn test_set_and_merge_actor_splits() {
+        let split = |split_index| SplitImpl::Datagen(DatagenSplit::new(split_index, 3, None));
+        let mut table_fragments = TableFragments::new(TableId::new(1), BTreeMap::new());
+        table_fragments.set_actor_splits_by_split_assignment(HashMap::from([(
+            1,
+            HashMap::from([
+                (1, vec![split(0)]),
+                (2, vec![split(1)]),
+                (3, vec![split(2)]),
+            ]),
+        )]));
+
+        // Only actor 2 has new splits. Merging keeps the splits of the other actors.
+        let mut merged = table_fragments.clone();
+        merged.merge_actor_splits(HashMap::from([(2, vec![split(1), split(2)])]));
+        assert_eq!(
+            merged.actor_splits,
+            HashMap::from([
+                (1, vec![split(0)]),
+                (2, vec![split(1), split(2)]),
+                (3, vec![split(2)]),
+            ])
+        );
+
+        // Setting drops the splits of the actors absent from the assignment.
+        let mut set = table_fragments;
+        set.set_actor_splits_by_split_assignment(HashMap::from([(
+            1,
+            HashMap::from([(2, vec![split(1), split(2)])]),
+        )]));
+        assert_eq!(
+            set.actor_splits,
+            HashMap::from([(2, vec![split(1), split(2)])])
+        );
+    }
+
+    #[test]
+    fn test_serialize_for_restore() {
+        let table_fragments = make_backup_table_fragments();
+        let bytes = table_fragments.serialize_for_restore();
+        assert_eq!(&bytes[..], TABLE_FRAGMENTS_BACKUP_V1);
+
+        let restored = TableFragments::deserialize_for_restore(&bytes).unwrap();
+        assert_eq!(restored.to_protobuf(), table_fragments.to_protobuf());
+    }
+
+    #[test]
+    fn test_deserialize_for_restore_v1() {
+        let restored =
+            TableFragments::deserialize_for_restore(&Bytes::from_static(TABLE_FRAGMENTS_BACKUP_V1))
+                .unwrap();
+        assert_eq!(restored.table_id(), TableId::new(1));
+        assert_eq!(restored.state(), State::Created);
+        assert_eq!(restored.actor_ids(), vec![1]);
+        assert_eq!(restored.fragment_vnode_mapping(1).unwrap().data, vec![3]);
+        assert_eq!(restored.fragments()[0].state_table_ids, vec![1]);
+        assert_eq!(restored.fetch_parallel_unit_by_actor(&1).unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_deserialize_for_restore_error() {
+        assert_matches!(
+            TableFragments::deserialize_for_restore(&Bytes::from_static(&[0x00, 0x00, 0x01])),
+            Err(RestoreError::Truncated {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_matches!(
+            TableFragments::deserialize_for_restore(&Bytes::from_static(&[0x00, 0x00, 0x00, 0x02])),
+            Err(RestoreError::UnsupportedVersion(2))
+        );
+        assert_matches!(
+            TableFragments::deserialize_for_restore(&Bytes::from_static(&[
+                0x00, 0x00, 0x00, 0x01, 0x1a, 0x10
+            ])),
+            Err(RestoreError::Decode(_))
+        );
+    }
+
+    #[test]
+    fn test_downstream_fragment_ids() {
+        // Fragment 1 dispatches to both 2 and 3, which are merged into 4.
+        let table_fragments = TableFragments::new(
+            TableId::new(0),
+            BTreeMap::from([
+                (1, make_fragment(1, &[(10, &[20]), (11, &[21, 30])])),
+                (2, make_fragment(2, &[(20, &[40]), (21, &[40])])),
+                (3, make_fragment(3, &[(30, &[40])])),
+                (4, make_fragment(4, &[(40, &[])])),
+            ]),
+        );
+
+        assert_eq!(
+            table_fragments.downstream_fragment_ids(1),
+            HashSet::from([2, 3])
+        );
+        assert_eq!(
+            table_fragments.downstream_fragment_ids(2),
+            HashSet::from([4])
+        );
+        assert_eq!(
+            table_fragments.downstream_fragment_ids(3),
+            HashSet::from([4])
+        );
+        assert!(table_fragments.downstream_fragment_ids(4).is_empty());
+        assert!(table_fragments.downstream_fragment_ids(5).is_empty());
+    }
+
+    #[test]
+    fn test_upstream_table_ids() {
+        let chain_actor = |actor_id, upstream_table_ids: &[u32]| StreamActor {
+            actor_id,
+            nodes: Some(StreamNode {
+                node_body: Some(NodeBody::Materialize(MaterializeNode::default())),
+                input: upstream_table_ids
+                    .iter()
+                    .map(|&table_id| StreamNode {
+                        node_body: Some(NodeBody::Chain(ChainNode {
+                            table_id,
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let fragment = |fragment_id, actors| Fragment {
+            fragment_id,
+            actors,
+            ..Default::default()
+        };
+        let table_fragments = TableFragments::new(
+            TableId::new(3),
+            BTreeMap::from([
+                // The chains of a join on two tables.
+                (1, fragment(1, vec![chain_actor(10, &[1, 2])])),
+                (
+                    2,
+                    fragment(2, vec![chain_actor(20, &[]), chain_actor(21, &[4])]),
+                ),
+                (3, fragment(3, vec![])),
+            ]),
+        );
+        assert_eq!(
+            table_fragments.upstream_table_ids(),
+            HashSet::from([1, 2, 4].map(TableId::new))
+        );
+        // The actor scanning both table 1 and 2 is grouped by table 1.
+        assert_eq!(
+            table_fragments.chain_actor_ids_by_upstream_table(),
+            BTreeMap::from([(TableId::new(1), vec![10]), (TableId::new(4), vec![21])])
+        );
+        assert!(TableFragments::new(TableId::new(3), BTreeMap::new())
+            .upstream_table_ids()
+            .is_empty());
+    }
+
+    /// A fragment of `parallelism` actors running `Materialize <- HashAgg <- Merge`, where actor
+    /// `i` merges from upstream actors `1000 + i` and `2000 + i`.
+    fn make_high_parallelism_fragment(parallelism: u32) -> Fragment {
+        let actors = (0..parallelism)
+            .map(|i| {
+                let merge = StreamNode {
+                    node_body: Some(NodeBody::Merge(MergeNode {
+                        upstream_actor_id: vec![1000 + i, 2000 + i],
+                        upstream_fragment_id: 1,
+                        ..Default::default()
+                    })),
+                    identity: "MergeExecutor".to_string(),
+                    ..Default::default()
+                };
+                let agg = StreamNode {
+                    node_body: Some(NodeBody::HashAgg(HashAggNode {
+                        group_key: vec![0, 1],
+                        ..Default::default()
+                    })),
+                    input: vec![merge],
+                    identity: "HashAggExecutor".to_string(),
+                    ..Default::default()
+                };
+                StreamActor {
+                    actor_id: i + 1,
+                    fragment_id: 2,
+                    nodes: Some(StreamNode {
+                        node_body: Some(NodeBody::Materialize(MaterializeNode {
+                            table_id: 42,
+                            ..Default::default()
+                        })),
+                        input: vec![agg],
+                        identity: "MaterializeExecutor".to_string(),
+                        ..Default::default()
+                    }),
+                    upstream_actor_id: vec![1000 + i, 2000 + i],
+                    vnode_bitmap: Some(Buffer {
+                        body: vec![i as u8; 32],
+                        ..Default::default()
+                    }),
+                    mview_definition: "CREATE MATERIALIZED VIEW mv AS SELECT ...".to_string(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Fragment {
+            fragment_id: 2,
+            actors,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_compression() {
+        let mut table_fragments = TableFragments::new(
+            TableId::new(0),
+            BTreeMap::from([(2, make_high_parallelism_fragment(64))]),
+        );
+        // An actor whose plan differs from the others keeps its whole plan.
+        table_fragments.fragments.get_mut(&2).unwrap().actors[10]
+            .nodes
+            .as_mut()
+            .unwrap()
+            .identity = "MaterializeExecutor 10".to_string();
+
+        let prost = table_fragments.to_compressed_protobuf();
+        let legacy_prost = table_fragments.to_protobuf();
+
+        let fragment = &prost.fragments[&2];
+        assert!(fragment.plan_template.is_some());
+        assert_eq!(fragment.actor_plan_deltas.len(), 63);
+        assert!(fragment
+            .actors
+            .iter()
+            .enumerate()
+            .all(|(i, actor)| (i == 10) == actor.nodes.is_some()));
+
+        // The persisted size shrinks by about a plan for every actor sharing the template.
+        let plan_size = fragment.plan_template.as_ref().unwrap().encoded_len();
+        let size = prost.encoded_len();
+        let legacy_size = legacy_prost.encoded_len();
+        assert!(
+            size + 40 * plan_size < legacy_size,
+            "size {size}, legacy size {legacy_size}, plan size {plan_size}"
+        );
+
+        // The actors rebuilt from both formats are byte-identical to the original ones.
+        for prost in [prost, legacy_prost.clone()] {
+            let restored = TableFragments::from_protobuf(prost);
+            let expected = &table_fragments.fragments[&2];
+            let actual = &restored.fragments[&2];
+            assert_eq!(actual.actors.len(), expected.actors.len());
+            for (actual, expected) in actual.actors.iter().zip_eq(&expected.actors) {
+                assert_eq!(actual.encode_to_vec(), expected.encode_to_vec());
+            }
+            assert!(actual.plan_template.is_none());
+            assert!(actual.actor_plan_deltas.is_empty());
+        }
+
+        assert!(
+            !TableFragments::from_protobuf(table_fragments.to_compressed_protobuf())
+                .is_legacy_plan_format()
+        );
+        assert!(TableFragments::from_protobuf(legacy_prost).is_legacy_plan_format());
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let vnode_bitmap = |range: std::ops::Range<usize>| {
+            Bitmap::from_iter((0..256).map(|vnode| range.contains(&vnode))).to_protobuf()
+        };
+        let mut table_fragments = TableFragments::new(
+            TableId::new(1),
+            BTreeMap::from([
+                (1, make_fragment(1, &[(10, &[20]), (11, &[20])])),
+                (2, make_fragment(2, &[(20, &[])])),
+            ]),
+        );
+        {
+            let fragment = table_fragments.fragments.get_mut(&1).unwrap();
+            fragment.set_fragment_type(FragmentType::Source);
+            fragment.set_distribution_type(FragmentDistributionType::Hash);
+            fragment.actors[0].vnode_bitmap = Some(vnode_bitmap(0..128));
+            fragment.actors[1].vnode_bitmap = Some(vnode_bitmap(128..256));
+            let fragment = table_fragments.fragments.get_mut(&2).unwrap();
+            fragment.set_fragment_type(FragmentType::Sink);
+            fragment.set_distribution_type(FragmentDistributionType::Single);
+        }
+        let actor_status = |parallel_unit_id, state: ActorState| ActorStatus {
+            parallel_unit: Some(ParallelUnit {
+                id: parallel_unit_id,
+                ..Default::default()
+            }),
+            state: state as i32,
+        };
+        table_fragments.set_actor_status(BTreeMap::from([
+            (10, actor_status(0, ActorState::Running)),
+            (11, actor_status(1, ActorState::Inactive)),
+        ]));
+        table_fragments.set_state(State::Created);
+
+        assert_eq!(
+            table_fragments.debug_dump(),
+            "TableFragments 1 (Created)
++-- [S] Fragment 1: Source, Hash, 2 actors -> 2
+|   +-- (R) Actor 10 @ parallel unit 0, vnodes 0-127
+|   `-- (I) Actor 11 @ parallel unit 1, vnodes 128-255
+`-- [M] Fragment 2: Sink, Single, 1 actor
+    `-- (?) Actor 20"
+        );
+    }
+
+    #[test]
+    fn test_format_vnode_ranges() {
+        let bitmap = Bitmap::from_iter([true, true, false, true, false, true, true, true]);
+        assert_eq!(format_vnode_ranges(&bitmap), "0-1, 3, 5-7");
+        assert_eq!(format_vnode_ranges(&Bitmap::from_iter([false; 4])), "none");
+    }
 }
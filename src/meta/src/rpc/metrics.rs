@@ -17,8 +17,9 @@ use std::sync::atomic::AtomicU64;
 use prometheus::{
     exponential_buckets, histogram_opts, register_histogram_vec_with_registry,
     register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, Histogram,
-    HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Registry,
 };
 
 pub struct MetaMetrics {
@@ -52,6 +53,10 @@ pub struct MetaMetrics {
     pub level_compact_cnt: IntGaugeVec,
     /// The number of compact tasks
     pub compact_frequency: IntCounterVec,
+    /// Bytes written to each table on checkpoint
+    pub table_write_bytes: IntCounterVec,
+    /// Keys written to each table on checkpoint
+    pub table_write_key_count: IntCounterVec,
 
     pub level_file_size: IntGaugeVec,
     /// Hummock version size
@@ -62,6 +67,10 @@ pub struct MetaMetrics {
     pub checkpoint_version_id: IntGauge,
     /// The smallest version id that is being pinned.
     pub min_pinned_version_id: IntGauge,
+    /// The number of snapshot pin and unpin RPCs of each type.
+    pub snapshot_pin_rpc_count: IntCounterVec,
+    /// The number of snapshot pins released because their lease expired.
+    pub expired_snapshot_pin_count: IntCounter,
 
     /// Latency for hummock manager to acquire lock
     pub hummock_manager_lock_time: HistogramVec,
@@ -163,6 +172,37 @@ impl MetaMetrics {
         )
         .unwrap();
 
+        let snapshot_pin_rpc_count = register_int_counter_vec_with_registry!(
+            "storage_snapshot_pin_rpc_count",
+            "num of snapshot pin and unpin RPCs of each type",
+            &["type"],
+            registry
+        )
+        .unwrap();
+
+        let expired_snapshot_pin_count = register_int_counter_with_registry!(
+            "storage_expired_snapshot_pin_count",
+            "num of snapshot pins released because their lease expired",
+            registry
+        )
+        .unwrap();
+
+        let table_write_bytes = register_int_counter_vec_with_registry!(
+            "storage_table_write_bytes",
+            "bytes written to each table on checkpoint",
+            &["table_id"],
+            registry
+        )
+        .unwrap();
+
+        let table_write_key_count = register_int_counter_vec_with_registry!(
+            "storage_table_write_key_count",
+            "num of keys written to each table on checkpoint",
+            &["table_id"],
+            registry
+        )
+        .unwrap();
+
         let version_size =
             register_int_gauge_with_registry!("storage_version_size", "version size", registry)
                 .unwrap();
@@ -236,11 +276,15 @@ impl MetaMetrics {
             level_sst_num,
             level_compact_cnt,
             compact_frequency,
+            table_write_bytes,
+            table_write_key_count,
             level_file_size,
             version_size,
             current_version_id,
             checkpoint_version_id,
             min_pinned_version_id,
+            snapshot_pin_rpc_count,
+            expired_snapshot_pin_count,
             hummock_manager_lock_time,
             hummock_manager_real_process_time,
             time_after_last_observation: AtomicU64::new(0),
@@ -24,30 +24,35 @@ use risingwave_common_service::metrics_manager::MetricsManager;
 use risingwave_pb::ddl_service::ddl_service_server::DdlServiceServer;
 use risingwave_pb::health::health_server::HealthServer;
 use risingwave_pb::hummock::hummock_manager_service_server::HummockManagerServiceServer;
+use risingwave_pb::meta::backup_service_server::BackupServiceServer;
 use risingwave_pb::meta::cluster_service_server::ClusterServiceServer;
 use risingwave_pb::meta::heartbeat_service_server::HeartbeatServiceServer;
 use risingwave_pb::meta::notification_service_server::NotificationServiceServer;
 use risingwave_pb::meta::scale_service_server::ScaleServiceServer;
 use risingwave_pb::meta::stream_manager_service_server::StreamManagerServiceServer;
+use risingwave_pb::meta::system_params_service_server::SystemParamsServiceServer;
 use risingwave_pb::meta::{MetaLeaderInfo, MetaLeaseInfo};
 use risingwave_pb::user::user_service_server::UserServiceServer;
 use tokio::sync::oneshot::Sender;
 use tokio::task::JoinHandle;
 
 use super::intercept::MetricsMiddlewareLayer;
+use super::service::backup_service::BackupServiceImpl;
 use super::service::health_service::HealthServiceImpl;
 use super::service::notification_service::NotificationServiceImpl;
 use super::service::scale_service::ScaleServiceImpl;
+use super::service::system_params_service::SystemParamsServiceImpl;
 use super::DdlServiceImpl;
+use crate::backup_restore::{restore_meta_store, BackupManager, BackupStorage};
 use crate::barrier::{BarrierScheduler, GlobalBarrierManager};
 use crate::hummock::compaction_group::manager::CompactionGroupManager;
 use crate::hummock::{CompactionScheduler, HummockManager};
 use crate::manager::{
-    CatalogManager, ClusterManager, FragmentManager, IdleManager, MetaOpts, MetaSrvEnv,
-    StreamingJobBackgroundDeleter,
+    CatalogManager, ClusterManager, DdlIdempotencyManager, FragmentManager, IdleManager, MetaOpts,
+    MetaSrvEnv, StreamingJobBackgroundDeleter,
 };
 use crate::rpc::metrics::MetaMetrics;
-use crate::rpc::service::cluster_service::ClusterServiceImpl;
+use crate::rpc::service::cluster_service::{BarrierLatencyWindow, ClusterServiceImpl};
 use crate::rpc::service::heartbeat_service::HeartbeatServiceImpl;
 use crate::rpc::service::hummock_service::HummockServiceImpl;
 use crate::rpc::service::stream_service::StreamServiceImpl;
@@ -306,6 +311,19 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         lease_interval_secs,
     )
     .await?;
+    let backup_storage = match &opts.backup_storage_url {
+        Some(url) => Some(BackupStorage::new(url, opts.backup_storage_directory.clone()).await),
+        None => None,
+    };
+    if let Some(backup_id) = opts.restore_from {
+        let Some(backup_storage) = &backup_storage else {
+            bail!(
+                "cannot restore meta backup {}, as `--backup-storage-url` is not specified",
+                backup_id
+            );
+        };
+        restore_meta_store(meta_store.as_ref(), backup_storage, backup_id).await?;
+    }
     let env = MetaSrvEnv::<S>::new(opts, meta_store.clone(), info).await;
     let compaction_group_manager =
         Arc::new(CompactionGroupManager::new(env.clone()).await.unwrap());
@@ -336,6 +354,12 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         .unwrap(),
     );
 
+    let backup_manager = Arc::new(
+        BackupManager::new(env.clone(), hummock_manager.clone(), backup_storage)
+            .await
+            .unwrap(),
+    );
+
     #[cfg(not(madsim))]
     if let Some(dashboard_addr) = address_info.dashboard_addr.take() {
         let dashboard_service = crate::dashboard::DashboardService {
@@ -416,6 +440,17 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         compactor_manager.clone(),
     ));
 
+    // The creating streaming jobs have not been recovered yet, so only the created ones are listed.
+    let ddl_idempotency_manager = Arc::new(
+        DdlIdempotencyManager::new(
+            env.meta_store_ref(),
+            Duration::from_secs(env.opts.ddl_idempotency_retention_sec),
+            &catalog_manager.list_stream_job_ids().await.unwrap(),
+        )
+        .await
+        .unwrap(),
+    );
+
     let heartbeat_srv = HeartbeatServiceImpl::new(cluster_manager.clone());
     let ddl_srv = DdlServiceImpl::<S>::new(
         env.clone(),
@@ -425,6 +460,7 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         cluster_manager.clone(),
         fragment_manager.clone(),
         table_background_deleter,
+        ddl_idempotency_manager,
     );
 
     let user_srv = UserServiceImpl::<S>::new(env.clone(), catalog_manager.clone());
@@ -438,10 +474,19 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         stream_manager.clone(),
     );
 
-    let cluster_srv = ClusterServiceImpl::<S>::new(cluster_manager.clone());
+    let barrier_latency_window = Arc::new(BarrierLatencyWindow::new(
+        meta_metrics.barrier_latency.clone(),
+    ));
+    let cluster_srv = ClusterServiceImpl::<S>::new(
+        cluster_manager.clone(),
+        fragment_manager.clone(),
+        hummock_manager.clone(),
+        barrier_latency_window.clone(),
+    );
     let stream_srv = StreamServiceImpl::<S>::new(
         env.clone(),
         barrier_scheduler.clone(),
+        barrier_manager.clone(),
         fragment_manager.clone(),
     );
     let hummock_srv = HummockServiceImpl::new(
@@ -454,12 +499,14 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
     let notification_manager = env.notification_manager_ref();
     let notification_srv = NotificationServiceImpl::new(
         env.clone(),
-        catalog_manager,
+        catalog_manager.clone(),
         cluster_manager.clone(),
         hummock_manager.clone(),
         fragment_manager.clone(),
     );
     let health_srv = HealthServiceImpl::new();
+    let backup_srv = BackupServiceImpl::new(backup_manager, fragment_manager);
+    let system_params_srv = SystemParamsServiceImpl::new(env.system_params_manager_ref());
 
     if let Some(prometheus_addr) = address_info.prometheus_addr {
         MetricsManager::boot_metrics_service(
@@ -491,7 +538,11 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
         )
         .await,
     );
-    sub_tasks.push(HummockManager::start_compaction_heartbeat(hummock_manager).await);
+    sub_tasks.push(HummockManager::start_compaction_heartbeat(hummock_manager.clone()).await);
+    sub_tasks.push(
+        HummockManager::start_key_encoding_upgrader(hummock_manager, catalog_manager).await,
+    );
+    sub_tasks.push(BarrierLatencyWindow::start_snapshotter(barrier_latency_window).await);
     sub_tasks.push((lease_handle, lease_shutdown));
     sub_tasks.push((deleter_handle, deleter_shutdown));
     if cfg!(not(test)) {
@@ -532,6 +583,8 @@ pub async fn rpc_serve_with_store<S: MetaStore>(
             .add_service(UserServiceServer::new(user_srv))
             .add_service(ScaleServiceServer::new(scale_srv))
             .add_service(HealthServer::new(health_srv))
+            .add_service(BackupServiceServer::new(backup_srv))
+            .add_service(SystemParamsServiceServer::new(system_params_srv))
             .serve(address_info.listen_addr)
             .await
             .unwrap();
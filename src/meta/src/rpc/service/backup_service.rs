@@ -0,0 +1,83 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risingwave_common::catalog::TableId;
+use risingwave_pb::meta::backup_service_server::BackupService;
+use risingwave_pb::meta::{
+    BackupTableFragmentsRequest, BackupTableFragmentsResponse, CreateMetaBackupRequest,
+    CreateMetaBackupResponse, ListMetaBackupsRequest, ListMetaBackupsResponse,
+};
+use tonic::{Request, Response, Status};
+
+use crate::backup_restore::BackupManagerRef;
+use crate::manager::FragmentManagerRef;
+use crate::storage::MetaStore;
+
+pub struct BackupServiceImpl<S: MetaStore> {
+    backup_manager: BackupManagerRef<S>,
+    fragment_manager: FragmentManagerRef<S>,
+}
+
+impl<S> BackupServiceImpl<S>
+where
+    S: MetaStore,
+{
+    pub fn new(
+        backup_manager: BackupManagerRef<S>,
+        fragment_manager: FragmentManagerRef<S>,
+    ) -> Self {
+        Self {
+            backup_manager,
+            fragment_manager,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> BackupService for BackupServiceImpl<S>
+where
+    S: MetaStore,
+{
+    async fn create_meta_backup(
+        &self,
+        _request: Request<CreateMetaBackupRequest>,
+    ) -> Result<Response<CreateMetaBackupResponse>, Status> {
+        let backup = self.backup_manager.create_backup().await?;
+        Ok(Response::new(CreateMetaBackupResponse {
+            backup: Some(backup),
+        }))
+    }
+
+    async fn list_meta_backups(
+        &self,
+        _request: Request<ListMetaBackupsRequest>,
+    ) -> Result<Response<ListMetaBackupsResponse>, Status> {
+        let backups = self.backup_manager.list_backups().await?;
+        Ok(Response::new(ListMetaBackupsResponse { backups }))
+    }
+
+    async fn backup_table_fragments(
+        &self,
+        request: Request<BackupTableFragmentsRequest>,
+    ) -> Result<Response<BackupTableFragmentsResponse>, Status> {
+        let table_id = TableId::new(request.into_inner().table_id);
+        let table_fragments = self
+            .fragment_manager
+            .select_table_fragments_by_table_id(&table_id)
+            .await?;
+        Ok(Response::new(BackupTableFragmentsResponse {
+            table_fragments: table_fragments.serialize_for_restore().to_vec(),
+        }))
+    }
+}
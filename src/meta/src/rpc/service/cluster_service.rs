@@ -12,28 +12,122 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use prometheus::core::Metric;
+use prometheus::Histogram;
+use risingwave_common::monitor::my_stats::MyHistogram;
+use risingwave_common::util::epoch::{Epoch, INVALID_EPOCH};
 use risingwave_pb::meta::cluster_service_server::ClusterService;
 use risingwave_pb::meta::{
     ActivateWorkerNodeRequest, ActivateWorkerNodeResponse, AddWorkerNodeRequest,
-    AddWorkerNodeResponse, DeleteWorkerNodeRequest, DeleteWorkerNodeResponse, ListAllNodesRequest,
-    ListAllNodesResponse,
+    AddWorkerNodeResponse, DeleteWorkerNodeRequest, DeleteWorkerNodeResponse,
+    GetClusterStatusRequest, GetClusterStatusResponse, ListAllNodesRequest, ListAllNodesResponse,
+    UpdateWorkerNodeRoleRequest, UpdateWorkerNodeRoleResponse,
 };
+use tokio::sync::oneshot::Sender;
+use tokio::task::JoinHandle;
 use tonic::{Request, Response, Status};
 
-use crate::manager::ClusterManagerRef;
+use crate::hummock::HummockManagerRef;
+use crate::manager::{ClusterManagerRef, FragmentManagerRef};
 use crate::storage::MetaStore;
 
+/// How often [`BarrierLatencyWindow`] snapshots the barrier latency histogram.
+const BARRIER_LATENCY_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The number of snapshots kept by [`BarrierLatencyWindow`], so that its window covers the last
+/// minute.
+const BARRIER_LATENCY_SNAPSHOT_NUM: usize = 6;
+
+/// Reports the latency of the barriers collected in the last minute. The barrier latency
+/// histogram is cumulative since the meta node started, so it's periodically snapshotted and the
+/// oldest snapshot kept is subtracted from the current histogram.
+pub struct BarrierLatencyWindow {
+    barrier_latency: Histogram,
+    snapshots: Mutex<VecDeque<MyHistogram>>,
+}
+
+impl BarrierLatencyWindow {
+    pub fn new(barrier_latency: Histogram) -> Self {
+        Self {
+            barrier_latency,
+            snapshots: Mutex::new(VecDeque::with_capacity(BARRIER_LATENCY_SNAPSHOT_NUM)),
+        }
+    }
+
+    fn current(&self) -> MyHistogram {
+        MyHistogram::from_prom_hist(self.barrier_latency.metric().get_histogram())
+    }
+
+    fn snapshot(&self) {
+        let current = self.current();
+        let mut snapshots = self.snapshots.lock();
+        if snapshots.len() == BARRIER_LATENCY_SNAPSHOT_NUM {
+            snapshots.pop_front();
+        }
+        snapshots.push_back(current);
+    }
+
+    /// The p99 latency in seconds of the barriers collected since the oldest snapshot.
+    pub fn p99_secs(&self) -> f64 {
+        let current = self.current();
+        let snapshots = self.snapshots.lock();
+        match snapshots.front() {
+            Some(oldest) => MyHistogram::from_diff(oldest, &current),
+            None => current,
+        }
+        .get_percentile(99.0)
+    }
+
+    pub async fn start_snapshotter(window: Arc<Self>) -> (JoinHandle<()>, Sender<()>) {
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        let join_handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BARRIER_LATENCY_SNAPSHOT_INTERVAL);
+            loop {
+                tokio::select! {
+                    // Wait for interval
+                    _ = interval.tick() => {},
+                    // Shutdown
+                    _ = &mut shutdown_rx => {
+                        tracing::info!("Barrier latency snapshotter is stopped");
+                        return;
+                    }
+                }
+                window.snapshot();
+            }
+        });
+        (join_handle, shutdown_tx)
+    }
+}
+
 #[derive(Clone)]
 pub struct ClusterServiceImpl<S: MetaStore> {
     cluster_manager: ClusterManagerRef<S>,
+    fragment_manager: FragmentManagerRef<S>,
+    hummock_manager: HummockManagerRef<S>,
+    barrier_latency_window: Arc<BarrierLatencyWindow>,
 }
 
 impl<S> ClusterServiceImpl<S>
 where
     S: MetaStore,
 {
-    pub fn new(cluster_manager: ClusterManagerRef<S>) -> Self {
-        ClusterServiceImpl { cluster_manager }
+    pub fn new(
+        cluster_manager: ClusterManagerRef<S>,
+        fragment_manager: FragmentManagerRef<S>,
+        hummock_manager: HummockManagerRef<S>,
+        barrier_latency_window: Arc<BarrierLatencyWindow>,
+    ) -> Self {
+        ClusterServiceImpl {
+            cluster_manager,
+            fragment_manager,
+            hummock_manager,
+            barrier_latency_window,
+        }
     }
 }
 
@@ -50,9 +144,10 @@ where
         let worker_type = req.get_worker_type()?;
         let host = req.get_host()?.clone();
         let worker_node_parallelism = req.worker_node_parallelism as usize;
+        let worker_role = req.get_worker_role()?;
         let worker_node = self
             .cluster_manager
-            .add_worker_node(worker_type, host, worker_node_parallelism)
+            .add_worker_node(worker_type, host, worker_node_parallelism, worker_role)
             .await?;
         Ok(Response::new(AddWorkerNodeResponse {
             status: None,
@@ -80,6 +175,23 @@ where
         Ok(Response::new(DeleteWorkerNodeResponse { status: None }))
     }
 
+    async fn update_worker_node_role(
+        &self,
+        request: Request<UpdateWorkerNodeRoleRequest>,
+    ) -> Result<Response<UpdateWorkerNodeRoleResponse>, Status> {
+        let req = request.into_inner();
+        let host = req.get_host()?.clone();
+        let role = req.get_role()?;
+        let node = self
+            .cluster_manager
+            .update_worker_node_role(host, role, &self.fragment_manager)
+            .await?;
+        Ok(Response::new(UpdateWorkerNodeRoleResponse {
+            status: None,
+            node: Some(node),
+        }))
+    }
+
     async fn list_all_nodes(
         &self,
         request: Request<ListAllNodesRequest>,
@@ -100,4 +212,82 @@ where
             nodes: node_list,
         }))
     }
+
+    async fn get_cluster_status(
+        &self,
+        _request: Request<GetClusterStatusRequest>,
+    ) -> Result<Response<GetClusterStatusResponse>, Status> {
+        let workers = self.cluster_manager.list_all_worker_node().await;
+        let (creating_streaming_jobs, created_streaming_jobs) =
+            self.fragment_manager.count_streaming_jobs_by_state().await;
+
+        let committed_epoch = self.hummock_manager.get_last_epoch()?.committed_epoch;
+        let committed_epoch_age_ms = if committed_epoch == INVALID_EPOCH {
+            0
+        } else {
+            Epoch::physical_now().saturating_sub(Epoch(committed_epoch).physical_time())
+        };
+
+        let barrier_latency_p99_secs = self.barrier_latency_window.p99_secs();
+
+        let l0_file_counts = self
+            .hummock_manager
+            .get_read_guard()
+            .await
+            .current_version
+            .levels
+            .iter()
+            .map(|(group_id, levels)| {
+                let l0_file_count = levels.l0.as_ref().map_or(0, |l0| {
+                    l0.sub_levels
+                        .iter()
+                        .map(|level| level.table_infos.len())
+                        .sum::<usize>()
+                });
+                (*group_id, l0_file_count as u32)
+            })
+            .collect();
+
+        Ok(Response::new(GetClusterStatusResponse {
+            workers,
+            creating_streaming_jobs: creating_streaming_jobs as u32,
+            created_streaming_jobs: created_streaming_jobs as u32,
+            committed_epoch,
+            committed_epoch_age_ms,
+            barrier_latency_p99_secs,
+            l0_file_counts,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::metrics::MetaMetrics;
+
+    #[test]
+    fn test_barrier_latency_window() {
+        let barrier_latency = MetaMetrics::new().barrier_latency;
+        let window = BarrierLatencyWindow::new(barrier_latency.clone());
+        for _ in 0..100 {
+            barrier_latency.observe(100.0);
+        }
+        // Without any snapshot, all barriers since the start are reported.
+        assert!(window.p99_secs() > 50.0);
+
+        window.snapshot();
+        assert_eq!(window.p99_secs(), 0.0);
+        for _ in 0..100 {
+            barrier_latency.observe(100.0);
+        }
+        for _ in 1..BARRIER_LATENCY_SNAPSHOT_NUM {
+            window.snapshot();
+            assert!(window.p99_secs() > 50.0);
+        }
+
+        // The slow barriers are no longer reported once the snapshot before them is evicted.
+        window.snapshot();
+        barrier_latency.observe(0.05);
+        assert!(window.p99_secs() <= 0.1);
+    }
 }
@@ -14,11 +14,11 @@
 
 use std::collections::HashSet;
 
+use anyhow::anyhow;
 use risingwave_common::catalog::CatalogVersion;
+use risingwave_connector::sink::s3_parquet::is_s3_parquet_sink;
 use risingwave_pb::catalog::table::OptionalAssociatedSourceId;
 use risingwave_pb::catalog::*;
-use risingwave_pb::common::worker_node::State;
-use risingwave_pb::common::WorkerType;
 use risingwave_pb::ddl_service::ddl_service_server::DdlService;
 use risingwave_pb::ddl_service::*;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
@@ -26,16 +26,16 @@ use risingwave_pb::stream_plan::{StreamFragmentGraph, StreamNode};
 use tonic::{Request, Response, Status};
 
 use crate::manager::{
-    CatalogManagerRef, ClusterManagerRef, FragmentManagerRef, IdCategory, IdCategoryType,
-    MetaSrvEnv, NotificationVersion, SourceId, StreamingJob, StreamingJobBackgroundDeleterRef,
-    StreamingJobId, TableId,
+    CatalogManagerRef, ClusterManagerRef, DdlAttempt, DdlGuard, DdlIdempotencyManagerRef,
+    FragmentManagerRef, IdCategory, IdCategoryType, MetaSrvEnv, NotificationVersion, SourceId,
+    StreamingJob, StreamingJobBackgroundDeleterRef, StreamingJobId, TableId,
 };
-use crate::model::TableFragments;
+use crate::model::{MetadataModel, TableFragments};
 use crate::storage::MetaStore;
 use crate::stream::{
     ActorGraphBuilder, CreateMaterializedViewContext, GlobalStreamManagerRef, SourceManagerRef,
 };
-use crate::MetaResult;
+use crate::{MetaError, MetaResult};
 
 #[derive(Clone)]
 pub struct DdlServiceImpl<S: MetaStore> {
@@ -47,6 +47,7 @@ pub struct DdlServiceImpl<S: MetaStore> {
     cluster_manager: ClusterManagerRef<S>,
     fragment_manager: FragmentManagerRef<S>,
     table_background_deleter: StreamingJobBackgroundDeleterRef,
+    ddl_idempotency_manager: DdlIdempotencyManagerRef<S>,
 }
 
 impl<S> DdlServiceImpl<S>
@@ -62,6 +63,7 @@ where
         cluster_manager: ClusterManagerRef<S>,
         fragment_manager: FragmentManagerRef<S>,
         table_background_deleter: StreamingJobBackgroundDeleterRef,
+        ddl_idempotency_manager: DdlIdempotencyManagerRef<S>,
     ) -> Self {
         Self {
             env,
@@ -71,6 +73,7 @@ where
             cluster_manager,
             fragment_manager,
             table_background_deleter,
+            ddl_idempotency_manager,
         }
     }
 }
@@ -196,6 +199,49 @@ where
         }))
     }
 
+    async fn alter_source(
+        &self,
+        request: Request<AlterSourceRequest>,
+    ) -> Result<Response<AlterSourceResponse>, Status> {
+        self.env.idle_manager().record_activity();
+
+        let source = request.into_inner().get_source()?.clone();
+
+        // 1. Persist the new columns, so that streaming jobs created afterwards can see them.
+        let version = self.catalog_manager.alter_source(&source).await?;
+
+        // 2. Let the running source executors switch their parsers at a consistent epoch.
+        self.source_manager.alter_source_schema(&source).await?;
+
+        Ok(Response::new(AlterSourceResponse {
+            status: None,
+            version,
+        }))
+    }
+
+    async fn reset_source_offset(
+        &self,
+        request: Request<ResetSourceOffsetRequest>,
+    ) -> Result<Response<ResetSourceOffsetResponse>, Status> {
+        self.env.idle_manager().record_activity();
+
+        let req = request.into_inner();
+        let position = req.get_position()?;
+        let source = self
+            .catalog_manager
+            .list_sources()
+            .await
+            .into_iter()
+            .find(|source| source.id == req.source_id)
+            .ok_or_else(|| Status::not_found(format!("source {} not found", req.source_id)))?;
+
+        self.source_manager
+            .reset_source_offset(&source, position, req.timestamp_millis)
+            .await?;
+
+        Ok(Response::new(ResetSourceOffsetResponse { status: None }))
+    }
+
     async fn create_sink(
         &self,
         request: Request<CreateSinkRequest>,
@@ -206,9 +252,18 @@ where
         let sink = req.get_sink()?.clone();
         let fragment_graph = req.get_fragment_graph()?.clone();
 
+        if is_s3_parquet_sink(&sink.properties) {
+            let (sink_id, version) = self.create_snapshot_export_sink(sink).await?;
+            return Ok(Response::new(CreateSinkResponse {
+                status: None,
+                sink_id,
+                version,
+            }));
+        }
+
         let mut stream_job = StreamingJob::Sink(sink);
         let version = self
-            .create_stream_job(&mut stream_job, fragment_graph)
+            .create_stream_job(&mut stream_job, fragment_graph, None)
             .await?;
 
         Ok(Response::new(CreateSinkResponse {
@@ -225,11 +280,12 @@ where
         let sink_id = request.into_inner().sink_id;
 
         // 1. Drop sink in catalog.
-        let version = self.catalog_manager.drop_sink(sink_id).await?;
+        let (version, stream_job) = self.catalog_manager.drop_sink(sink_id).await?;
 
         // 2. drop sink in table background deleter asynchronously.
-        self.table_background_deleter
-            .delete(vec![StreamingJobId::Sink(sink_id.into())]);
+        if let Some(stream_job) = stream_job {
+            self.table_background_deleter.delete(vec![stream_job]);
+        }
 
         Ok(Response::new(DropSinkResponse {
             status: None,
@@ -248,15 +304,54 @@ where
         let fragment_graph = req.get_fragment_graph()?.clone();
 
         let mut stream_job = StreamingJob::MaterializedView(mview);
-        let version = self
-            .create_stream_job(&mut stream_job, fragment_graph)
-            .await?;
+        if req.idempotency_key.is_empty() {
+            let version = self
+                .create_stream_job(&mut stream_job, fragment_graph, None)
+                .await?;
+            return self
+                .create_materialized_view_response(stream_job.id(), version)
+                .await;
+        }
 
-        Ok(Response::new(CreateMaterializedViewResponse {
-            status: None,
-            table_id: stream_job.id(),
-            version,
-        }))
+        let ddl_guard = match self.begin_ddl(&req.idempotency_key).await? {
+            DdlAttempt::Execute(guard) => guard,
+            DdlAttempt::Created(table_id) => {
+                // The retried request has created the materialized view, any version after that
+                // is fine.
+                let version = self.env.notification_manager().current_version().await;
+                return self
+                    .create_materialized_view_response(table_id, version)
+                    .await;
+            }
+        };
+
+        // Executed in a separate task, so that the DDL goes on if this request is cancelled, e.g.
+        // because the frontend is disconnected. Its retry waits for the task in `begin_ddl`.
+        let this = self.clone();
+        let (table_id, version) = tokio::spawn(async move {
+            let result = this
+                .create_stream_job(&mut stream_job, fragment_graph, Some(&ddl_guard))
+                .await;
+            match result {
+                Ok(version) => {
+                    this.ddl_idempotency_manager
+                        .finish(ddl_guard, stream_job.id())
+                        .await?;
+                    Ok((stream_job.id(), version))
+                }
+                Err(err) => {
+                    if let Err(e) = this.ddl_idempotency_manager.abort(ddl_guard).await {
+                        tracing::warn!("failed to forget the failed DDL: {}", e);
+                    }
+                    Err(err)
+                }
+            }
+        })
+        .await
+        .map_err(|e| MetaError::from(anyhow!("failed to create materialized view: {}", e)))??;
+
+        self.create_materialized_view_response(table_id, version)
+            .await
     }
 
     async fn drop_materialized_view(
@@ -301,7 +396,7 @@ where
 
         let mut stream_job = StreamingJob::Index(index, index_table);
         let version = self
-            .create_stream_job(&mut stream_job, fragment_graph)
+            .create_stream_job(&mut stream_job, fragment_graph, None)
             .await?;
 
         Ok(Response::new(CreateIndexResponse {
@@ -336,6 +431,59 @@ where
         }))
     }
 
+    async fn update_table_statistics(
+        &self,
+        request: Request<UpdateTableStatisticsRequest>,
+    ) -> Result<Response<UpdateTableStatisticsResponse>, Status> {
+        self.env.idle_manager().record_activity();
+
+        let req = request.into_inner();
+        let statistics = req.get_statistics()?.clone();
+        let version = self
+            .catalog_manager
+            .update_table_statistics(req.table_id, statistics)
+            .await?;
+
+        Ok(Response::new(UpdateTableStatisticsResponse {
+            status: None,
+            version,
+        }))
+    }
+
+    async fn update_subscription_progress(
+        &self,
+        request: Request<UpdateSubscriptionProgressRequest>,
+    ) -> Result<Response<UpdateSubscriptionProgressResponse>, Status> {
+        self.env.idle_manager().record_activity();
+
+        let req = request.into_inner();
+        let expected_progress = req.get_expected_progress()?.clone();
+        let progress = req.get_progress()?.clone();
+        self.catalog_manager
+            .update_subscription_progress(req.table_id, expected_progress, progress)
+            .await?;
+
+        Ok(Response::new(UpdateSubscriptionProgressResponse {
+            status: None,
+        }))
+    }
+
+    async fn get_subscription_progress(
+        &self,
+        request: Request<GetSubscriptionProgressRequest>,
+    ) -> Result<Response<GetSubscriptionProgressResponse>, Status> {
+        let req = request.into_inner();
+        let progress = self
+            .catalog_manager
+            .get_subscription_progress(req.table_id)
+            .await?;
+
+        Ok(Response::new(GetSubscriptionProgressResponse {
+            status: None,
+            progress: Some(progress),
+        }))
+    }
+
     async fn create_materialized_source(
         &self,
         request: Request<CreateMaterializedSourceRequest>,
@@ -379,7 +527,6 @@ where
         &self,
         _request: Request<RisectlListStateTablesRequest>,
     ) -> Result<Response<RisectlListStateTablesResponse>, Status> {
-        use crate::model::MetadataModel;
         let tables = Table::list(self.env.meta_store()).await?;
         Ok(Response::new(RisectlListStateTablesResponse { tables }))
     }
@@ -389,19 +536,71 @@ impl<S> DdlServiceImpl<S>
 where
     S: MetaStore,
 {
-    /// `create_stream_job` creates a stream job and returns the version of the catalog.
+    /// Begins the DDL with the idempotency key. A DDL that created a table already dropped is
+    /// executed again.
+    async fn begin_ddl(&self, idempotency_key: &str) -> MetaResult<DdlAttempt> {
+        loop {
+            let attempt = self.ddl_idempotency_manager.begin(idempotency_key).await?;
+            if let DdlAttempt::Created(table_id) = attempt {
+                let dropped = self
+                    .catalog_manager
+                    .get_catalog_core_guard()
+                    .await
+                    .database
+                    .ensure_table_id(table_id)
+                    .is_err();
+                if dropped {
+                    self.ddl_idempotency_manager.forget(idempotency_key).await?;
+                    continue;
+                }
+            }
+            return Ok(attempt);
+        }
+    }
+
+    async fn create_materialized_view_response(
+        &self,
+        table_id: TableId,
+        version: NotificationVersion,
+    ) -> Result<Response<CreateMaterializedViewResponse>, Status> {
+        // The fragments have been updated by `post_create_table_fragments` once the creation
+        // barrier is collected, so the actors' info here is the one actually running.
+        let table_fragments = self
+            .fragment_manager
+            .select_table_fragments_by_table_id(&table_id.into())
+            .await?
+            .to_protobuf();
+
+        Ok(Response::new(CreateMaterializedViewResponse {
+            status: None,
+            table_id,
+            version,
+            table_fragments: Some(table_fragments),
+        }))
+    }
+
+    /// `create_stream_job` creates a stream job and returns the version of the catalog. If
+    /// `ddl_guard` is given, the id of the stream job is recorded with it before the stream job is
+    /// actually created.
     async fn create_stream_job(
         &self,
         stream_job: &mut StreamingJob,
         fragment_graph: StreamFragmentGraph,
+        ddl_guard: Option<&DdlGuard>,
     ) -> MetaResult<NotificationVersion> {
         let (mut ctx, table_fragments) =
             self.prepare_stream_job(stream_job, fragment_graph).await?;
-        match self
-            .stream_manager
-            .create_materialized_view(table_fragments, &mut ctx)
-            .await
-        {
+        let create = async {
+            if let Some(guard) = ddl_guard {
+                self.ddl_idempotency_manager
+                    .record_creating(guard, stream_job.id())
+                    .await?;
+            }
+            self.stream_manager
+                .create_materialized_view(table_fragments, &mut ctx)
+                .await
+        };
+        match create.await {
             Ok(_) => self.finish_stream_job(stream_job, &ctx).await,
             Err(err) => {
                 self.cancel_stream_job(stream_job, &ctx).await?;
@@ -447,6 +646,7 @@ where
             mview_name: stream_job.name(),
             mview_definition: stream_job.mview_definition(),
             table_properties: stream_job.properties(),
+            backfill_order: fragment_graph.backfill_order(),
             table_sink_map: self
                 .fragment_manager
                 .get_build_graph_info(&dependent_table_ids)
@@ -459,11 +659,14 @@ where
         let table_ids_cnt = fragment_graph.table_ids_cnt;
         let default_parallelism = if self.env.opts.minimal_scheduling {
             self.cluster_manager
-                .list_worker_node(WorkerType::ComputeNode, Some(State::Running))
+                .list_active_streaming_worker_node()
                 .await
                 .len()
         } else {
-            self.cluster_manager.get_active_parallel_unit_count().await
+            self.cluster_manager
+                .list_active_streaming_parallel_units()
+                .await
+                .len()
         };
         let mut actor_graph_builder = ActorGraphBuilder::new(
             self.env.id_gen_manager_ref(),
@@ -680,6 +883,32 @@ where
         Ok(version)
     }
 
+    /// Creates an `s3_parquet` sink, which is only a catalog. Its snapshots are exported by the
+    /// frontends.
+    async fn create_snapshot_export_sink(
+        &self,
+        mut sink: Sink,
+    ) -> MetaResult<(u32, NotificationVersion)> {
+        sink.id = self.gen_unique_id::<{ IdCategory::Table }>().await?;
+        sink.dependent_relations = vec![sink.associated_table_id];
+        self.catalog_manager
+            .start_create_sink_procedure(&sink)
+            .await?;
+        match self
+            .catalog_manager
+            .finish_create_sink_procedure(&sink)
+            .await
+        {
+            Ok(version) => Ok((sink.id, version)),
+            Err(err) => {
+                self.catalog_manager
+                    .cancel_create_sink_procedure(&sink)
+                    .await?;
+                Err(err)
+            }
+        }
+    }
+
     async fn gen_unique_id<const C: IdCategoryType>(&self) -> MetaResult<u32> {
         let id = self.env.id_gen_manager().generate::<C>().await? as u32;
         Ok(id)
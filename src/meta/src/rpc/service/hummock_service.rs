@@ -61,6 +61,14 @@ where
             fragment_manager,
         }
     }
+
+    fn report_snapshot_pin_rpc(&self, rpc_type: &str) {
+        self.hummock_manager
+            .metrics()
+            .snapshot_pin_rpc_count
+            .with_label_values(&[rpc_type])
+            .inc();
+    }
 }
 
 #[async_trait::async_trait]
@@ -178,6 +186,7 @@ where
         request: Request<PinSpecificSnapshotRequest>,
     ) -> Result<Response<PinSnapshotResponse>, Status> {
         let req = request.into_inner();
+        self.report_snapshot_pin_rpc("pin_specific_snapshot");
         let hummock_snapshot = self
             .hummock_manager
             .pin_specific_snapshot(req.context_id, req.epoch)
@@ -193,6 +202,7 @@ where
         request: Request<PinSnapshotRequest>,
     ) -> Result<Response<PinSnapshotResponse>, Status> {
         let req = request.into_inner();
+        self.report_snapshot_pin_rpc("pin_snapshot");
         let hummock_snapshot = self.hummock_manager.pin_snapshot(req.context_id).await?;
         Ok(Response::new(PinSnapshotResponse {
             status: None,
@@ -205,6 +215,7 @@ where
         request: Request<UnpinSnapshotRequest>,
     ) -> Result<Response<UnpinSnapshotResponse>, Status> {
         let req = request.into_inner();
+        self.report_snapshot_pin_rpc("unpin_snapshot");
         self.hummock_manager.unpin_snapshot(req.context_id).await?;
         Ok(Response::new(UnpinSnapshotResponse { status: None }))
     }
@@ -214,9 +225,14 @@ where
         request: Request<UnpinSnapshotBeforeRequest>,
     ) -> Result<Response<UnpinSnapshotBeforeResponse>, Status> {
         let req = request.into_inner();
+        self.report_snapshot_pin_rpc("unpin_snapshot_before");
         self.hummock_manager
             .unpin_snapshot_before(req.context_id, req.min_snapshot.unwrap())
             .await?;
+        // Frontends call it periodically even if their min epoch doesn't advance, which renews the
+        // lease of their pinned snapshot.
+        self.hummock_manager
+            .renew_snapshot_pin_lease(req.context_id);
         Ok(Response::new(UnpinSnapshotBeforeResponse { status: None }))
     }
 
@@ -252,6 +268,8 @@ where
         let rx = self
             .compactor_manager
             .add_compactor(context_id, req.max_concurrent_task_number);
+        self.compactor_manager
+            .set_memory_budget(context_id, req.memory_budget, req.block_size);
         // Trigger compaction on all compaction groups.
         for cg_id in self
             .hummock_manager
@@ -501,4 +519,29 @@ where
             status: None,
         }))
     }
+
+    async fn get_table_write_stats(
+        &self,
+        _request: Request<GetTableWriteStatsRequest>,
+    ) -> Result<Response<GetTableWriteStatsResponse>, Status> {
+        let state_table_owners = self.fragment_manager.get_state_table_owners().await;
+        // Stats of dropped tables are skipped.
+        let table_write_stats = self
+            .hummock_manager
+            .get_table_write_stats()
+            .into_iter()
+            .filter_map(|(table_id, stats)| {
+                state_table_owners.get(&table_id).map(|owner_table_id| {
+                    get_table_write_stats_response::TableWriteStats {
+                        table_id,
+                        owner_table_id: owner_table_id.table_id,
+                        stats: Some(stats),
+                    }
+                })
+            })
+            .collect();
+        Ok(Response::new(GetTableWriteStatsResponse {
+            table_write_stats,
+        }))
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod backup_service;
 pub mod cluster_service;
 pub mod ddl_service;
 pub mod health_service;
@@ -20,6 +21,7 @@ pub mod hummock_service;
 pub mod notification_service;
 pub mod scale_service;
 pub mod stream_service;
+pub mod system_params_service;
 pub mod user_service;
 
 use std::pin::Pin;
@@ -77,6 +77,7 @@ where
         let subscribe_type = req.get_subscribe_type()?;
         if subscribe_type == SubscribeType::Frontend {
             self.hummock_manager.pin_snapshot(req.worker_id).await?;
+            self.hummock_manager.renew_snapshot_pin_lease(req.worker_id);
         }
         let host_address = req.get_host()?.clone();
 
@@ -104,7 +105,9 @@ where
         let hummock_manager_guard = self.hummock_manager.get_read_guard().await;
 
         let cluster_guard = self.cluster_manager.get_cluster_core_guard().await;
-        let nodes = cluster_guard.list_worker_node(WorkerType::ComputeNode, Some(Running));
+        let mut nodes = cluster_guard.list_worker_node(WorkerType::ComputeNode, Some(Running));
+        // The frontends take over the snapshot exports of each other.
+        nodes.extend(cluster_guard.list_worker_node(WorkerType::Frontend, Some(Running)));
 
         match subscribe_type {
             SubscribeType::Compactor | SubscribeType::Hummock => {
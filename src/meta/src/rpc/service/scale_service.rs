@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 use risingwave_pb::catalog::source::Info::StreamSource;
@@ -20,14 +20,17 @@ use risingwave_pb::common::WorkerType;
 use risingwave_pb::meta::reschedule_request::Reschedule;
 use risingwave_pb::meta::scale_service_server::ScaleService;
 use risingwave_pb::meta::{
-    GetClusterInfoRequest, GetClusterInfoResponse, PauseRequest, PauseResponse, RescheduleRequest,
-    RescheduleResponse, ResumeRequest, ResumeResponse,
+    FixFragmentsRequest, FixFragmentsResponse, GetClusterInfoRequest, GetClusterInfoResponse,
+    PauseRequest, PauseResponse, RescheduleRequest, RescheduleResponse, ResumeRequest,
+    ResumeResponse, UpdateSinkDispatchTypeRequest, UpdateSinkDispatchTypeResponse,
 };
 use risingwave_pb::source::{ConnectorSplit, ConnectorSplits};
 use tonic::{Request, Response, Status};
 
 use crate::barrier::{BarrierScheduler, Command};
-use crate::manager::{CatalogManagerRef, ClusterManagerRef, FragmentManagerRef};
+use crate::manager::{
+    CatalogManagerRef, ClusterManagerRef, FragmentManagerRef, FragmentRepairKind,
+};
 use crate::model::MetadataModel;
 use crate::storage::MetaStore;
 use crate::stream::{GlobalStreamManagerRef, ParallelUnitReschedule, SourceManagerRef};
@@ -172,4 +175,68 @@ where
 
         Ok(Response::new(RescheduleResponse { success: true }))
     }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn fix_fragments(
+        &self,
+        request: Request<FixFragmentsRequest>,
+    ) -> Result<Response<FixFragmentsResponse>, Status> {
+        let req = request.into_inner();
+        let kinds: HashSet<_> = [
+            (
+                req.fix_dangling_dispatchers,
+                FragmentRepairKind::DanglingDispatchers,
+            ),
+            (
+                req.fix_missing_actor_status,
+                FragmentRepairKind::MissingActorStatus,
+            ),
+            (
+                req.fix_orphaned_actor_splits,
+                FragmentRepairKind::OrphanedActorSplits,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(selected, kind)| selected.then_some(kind))
+        .collect();
+
+        let parallel_units = self
+            .cluster_manager
+            .list_active_parallel_units()
+            .await
+            .into_iter()
+            .map(|parallel_unit| (parallel_unit.id, parallel_unit))
+            .collect();
+        let mut repairs = self
+            .fragment_manager
+            .collect_fragment_repairs(&parallel_units)
+            .await;
+        repairs.retain_kinds(&kinds);
+        let fixes = repairs.describe();
+
+        if !req.dry_run && !repairs.is_empty() {
+            self.barrier_scheduler.run_command(Command::pause()).await?;
+            // Resume even if the repairs fail, but report the failure of the repairs first.
+            let result = self.fragment_manager.apply_fragment_repairs(repairs).await;
+            let resumed = self.barrier_scheduler.run_command(Command::resume()).await;
+            result?;
+            resumed?;
+        }
+
+        Ok(Response::new(FixFragmentsResponse { fixes }))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn update_sink_dispatch_type(
+        &self,
+        request: Request<UpdateSinkDispatchTypeRequest>,
+    ) -> Result<Response<UpdateSinkDispatchTypeResponse>, Status> {
+        let req = request.into_inner();
+        let dispatcher_type = req.dispatcher_type();
+        self.stream_manager
+            .update_sink_dispatch_type(req.table_id.into(), dispatcher_type)
+            .await?;
+
+        Ok(Response::new(UpdateSinkDispatchTypeResponse {}))
+    }
 }
@@ -13,16 +13,21 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use itertools::Itertools;
+use risingwave_common::catalog::TableId;
+use risingwave_pb::meta::get_table_complexity_response::TableComplexity;
+use risingwave_pb::meta::get_table_concurrency_response::TableConcurrency;
 use risingwave_pb::meta::list_table_fragments_response::{
     ActorInfo, FragmentInfo, TableFragmentInfo,
 };
+use risingwave_pb::meta::set_log_level_request::Target;
 use risingwave_pb::meta::stream_manager_service_server::StreamManagerService;
 use risingwave_pb::meta::*;
 use tonic::{Request, Response, Status};
 
-use crate::barrier::BarrierScheduler;
+use crate::barrier::{BarrierManagerRef, BarrierScheduler, Command};
 use crate::manager::{FragmentManagerRef, MetaSrvEnv};
 use crate::storage::MetaStore;
 
@@ -35,6 +40,7 @@ where
 {
     env: MetaSrvEnv<S>,
     barrier_scheduler: BarrierScheduler<S>,
+    barrier_manager: BarrierManagerRef<S>,
     fragment_manager: FragmentManagerRef<S>,
 }
 
@@ -45,11 +51,13 @@ where
     pub fn new(
         env: MetaSrvEnv<S>,
         barrier_scheduler: BarrierScheduler<S>,
+        barrier_manager: BarrierManagerRef<S>,
         fragment_manager: FragmentManagerRef<S>,
     ) -> Self {
         StreamServiceImpl {
             env,
             barrier_scheduler,
+            barrier_manager,
             fragment_manager,
         }
     }
@@ -112,4 +120,132 @@ where
             table_fragments: info,
         }))
     }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn get_table_complexity(
+        &self,
+        _request: Request<GetTableComplexityRequest>,
+    ) -> Result<Response<GetTableComplexityResponse>, Status> {
+        let actor_counts: HashMap<_, _> = self
+            .fragment_manager
+            .list_tables_by_actor_count()
+            .await
+            .into_iter()
+            .collect();
+        let tables = self
+            .fragment_manager
+            .list_tables_by_fragment_count()
+            .await
+            .into_iter()
+            .map(|(table_id, fragment_count)| TableComplexity {
+                table_id: table_id.table_id,
+                fragment_count: fragment_count as u32,
+                actor_count: actor_counts.get(&table_id).copied().unwrap_or_default() as u32,
+            })
+            .collect();
+
+        Ok(Response::new(GetTableComplexityResponse { tables }))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn get_table_concurrency(
+        &self,
+        _request: Request<GetTableConcurrencyRequest>,
+    ) -> Result<Response<GetTableConcurrencyResponse>, Status> {
+        let running = self.fragment_manager.list_running_actors_per_table().await;
+        let creating = self
+            .fragment_manager
+            .get_fragment_read_guard()
+            .await
+            .list_creating_actors_per_table();
+        let tables = running
+            .keys()
+            .chain(creating.keys())
+            .unique()
+            .sorted_by_key(|table_id| table_id.table_id)
+            .map(|table_id| TableConcurrency {
+                table_id: table_id.table_id,
+                running_actor_count: running.get(table_id).copied().unwrap_or_default() as u32,
+                creating_actor_count: creating.get(table_id).copied().unwrap_or_default() as u32,
+            })
+            .collect();
+
+        Ok(Response::new(GetTableConcurrencyResponse { tables }))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn dump_table_fragments(
+        &self,
+        request: Request<DumpTableFragmentsRequest>,
+    ) -> Result<Response<DumpTableFragmentsResponse>, Status> {
+        let req = request.into_inner();
+        let table_fragments = self
+            .fragment_manager
+            .select_table_fragments_by_table_id(&TableId::new(req.table_id))
+            .await?;
+
+        Ok(Response::new(DumpTableFragmentsResponse {
+            dump: table_fragments.debug_dump(),
+        }))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn set_log_level(
+        &self,
+        request: Request<SetLogLevelRequest>,
+    ) -> Result<Response<SetLogLevelResponse>, Status> {
+        let req = request.into_inner();
+        let level: tracing::Level = req
+            .level
+            .parse()
+            .map_err(|_| Status::invalid_argument(format!("invalid log level: {}", req.level)))?;
+        let actor_ids = match req.target {
+            Some(Target::FragmentId(fragment_id)) => self
+                .fragment_manager
+                .get_running_actors_of_fragment(fragment_id)
+                .await?
+                .into_iter()
+                .sorted()
+                .collect_vec(),
+            Some(Target::ActorId(actor_id)) => {
+                let guard = self.fragment_manager.get_fragment_read_guard().await;
+                if guard.get_fragment_by_actor(actor_id).is_none() {
+                    return Err(Status::not_found(format!("actor not found: {}", actor_id)));
+                }
+                vec![actor_id]
+            }
+            None => return Err(Status::invalid_argument("no fragment or actor specified")),
+        };
+
+        let expire_at = SystemTime::now() + Duration::from_secs(req.duration_secs);
+        let expire_at_ms = expire_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        tracing::info!(
+            "set the log level of actors {:?} to {} for {}s",
+            actor_ids,
+            level,
+            req.duration_secs
+        );
+        self.barrier_scheduler
+            .run_command(Command::set_log_level(
+                actor_ids.clone(),
+                level.to_string(),
+                expire_at_ms,
+            ))
+            .await?;
+
+        Ok(Response::new(SetLogLevelResponse { actor_ids }))
+    }
+
+    #[cfg_attr(coverage, no_coverage)]
+    async fn get_backfill_progress(
+        &self,
+        _request: Request<GetBackfillProgressRequest>,
+    ) -> Result<Response<GetBackfillProgressResponse>, Status> {
+        Ok(Response::new(GetBackfillProgressResponse {
+            progress: self.barrier_manager.get_backfill_progress(),
+        }))
+    }
 }
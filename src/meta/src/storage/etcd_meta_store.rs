@@ -147,6 +147,41 @@ impl SnapshotViewer for ListViewer {
     }
 }
 
+struct ListKvViewer {
+    key: Vec<u8>,
+}
+
+impl SnapshotViewer for ListKvViewer {
+    type Output = Vec<(Key, Value)>;
+
+    type OutputFuture<'a> = impl Future<Output = MetaStoreResult<(i64, Self::Output)>> + 'a;
+
+    fn view(&self, mut client: KvClient, revision: i64) -> Self::OutputFuture<'_> {
+        async move {
+            // Etcd sorts the range by key by default.
+            let res = client
+                .get(
+                    self.key.clone(),
+                    Some(GetOptions::default().with_revision(revision).with_prefix()),
+                )
+                .await?;
+            let new_revision = if let Some(header) = res.header() {
+                header.revision()
+            } else {
+                return Err(MetaStoreError::Internal(anyhow::anyhow!(
+                    "Etcd response missing header"
+                )));
+            };
+            let value = res
+                .kvs()
+                .iter()
+                .map(|kv| (kv.key()[self.key.len()..].to_vec(), kv.value().to_vec()))
+                .collect();
+            Ok((new_revision, value))
+        }
+    }
+}
+
 #[async_trait]
 impl Snapshot for EtcdSnapshot {
     async fn list_cf(&self, cf: &str) -> MetaStoreResult<Vec<Vec<u8>>> {
@@ -156,6 +191,13 @@ impl Snapshot for EtcdSnapshot {
         self.view_inner(view).await
     }
 
+    async fn list_cf_kv(&self, cf: &str) -> MetaStoreResult<Vec<(Key, Value)>> {
+        let view = ListKvViewer {
+            key: encode_etcd_key(cf, &[]),
+        };
+        self.view_inner(view).await
+    }
+
     async fn get_cf(&self, cf: &str, key: &[u8]) -> MetaStoreResult<Vec<u8>> {
         let view = GetViewer {
             key: encode_etcd_key(cf, key),
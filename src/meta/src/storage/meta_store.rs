@@ -23,6 +23,8 @@ pub const DEFAULT_COLUMN_FAMILY: &str = "default";
 #[async_trait]
 pub trait Snapshot: Sync + Send + 'static {
     async fn list_cf(&self, cf: &str) -> MetaStoreResult<Vec<Vec<u8>>>;
+    /// Lists the keys along with the values in `cf`, ordered by key.
+    async fn list_cf_kv(&self, cf: &str) -> MetaStoreResult<Vec<(Key, Value)>>;
     async fn get_cf(&self, cf: &str, key: &[u8]) -> MetaStoreResult<Vec<u8>>;
 }
 
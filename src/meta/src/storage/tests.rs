@@ -97,6 +97,14 @@ async fn test_meta_store_basic<S: MetaStore>(store: &S) -> MetaStoreResult<()> {
         assert_eq!(vals.len(), 2);
         let vals = snapshot.list_cf(TEST_DEFAULT_CF).await?;
         assert_eq!(vals.len(), 3);
+        let kvs = snapshot.list_cf_kv("test_cf").await?;
+        assert_eq!(
+            kvs,
+            vec![
+                (b"key_1".to_vec(), b"value_1".to_vec()),
+                (b"key_2".to_vec(), b"value_2".to_vec()),
+            ]
+        );
     }
 
     assert!(store
@@ -318,10 +318,11 @@ where
             bail!("no available compute node in the cluster");
         }
 
-        // Associating ParallelUnit with Worker
+        // Associating ParallelUnit with Worker. Actors can't be rescheduled to the parallel units
+        // of the serving-only nodes, which are not found here.
         let parallel_unit_id_to_worker_id: BTreeMap<_, _> = self
             .cluster_manager
-            .list_active_parallel_units()
+            .list_active_streaming_parallel_units()
             .await
             .into_iter()
             .map(|parallel_unit| {
@@ -409,9 +410,11 @@ where
             }
             match fragment.get_fragment_type()? {
                 FragmentType::Source => {
+                    // The `Now` fragments are source fragments without source nodes.
                     let stream_node = fragment.actors.first().unwrap().get_nodes().unwrap();
-                    let source_node = TableFragments::find_source_node(stream_node).unwrap();
-                    if is_stream_source(source_node) {
+                    if let Some(source_node) = TableFragments::find_source_node(stream_node)
+                        && is_stream_source(source_node)
+                    {
                         stream_source_fragment_ids.insert(*fragment_id);
                     }
                 }
@@ -494,6 +497,12 @@ where
         revert_funcs: &mut Vec<BoxFuture<'_, ()>>,
         reschedules: HashMap<FragmentId, ParallelUnitReschedule>,
     ) -> MetaResult<()> {
+        // The fragments are updated after the barrier is collected, when the compute nodes have
+        // already applied the reschedule, so they must be checked before the barrier is injected.
+        self.fragment_manager
+            .check_reschedulable(reschedules.keys())
+            .await?;
+
         let ctx = self.build_reschedule_context(&reschedules).await?;
         // Index of actors to create/remove
         // Fragment Id => ( Actor Id => Parallel Unit Id )
@@ -782,14 +791,11 @@ where
                 }
             }
 
-            let downstream_fragment_id = if let Some(downstream_fragment_ids) =
-                ctx.downstream_fragment_id_map.get(&fragment_id)
-            {
-                let downstream_fragment_id = downstream_fragment_ids.iter().exactly_one().unwrap();
-                Some(*downstream_fragment_id as FragmentId)
-            } else {
-                None
-            };
+            let downstream_fragment_ids = ctx
+                .downstream_fragment_id_map
+                .get(&fragment_id)
+                .map(|ids| ids.iter().copied().sorted().collect())
+                .unwrap_or_default();
 
             let mut vnode_bitmap_updates = fragment_updated_bitmap.remove(&fragment_id).unwrap();
 
@@ -828,7 +834,7 @@ where
                     vnode_bitmap_updates,
                     upstream_fragment_dispatcher_ids,
                     upstream_dispatcher_mapping,
-                    downstream_fragment_id,
+                    downstream_fragment_ids,
                     actor_splits,
                 },
             );
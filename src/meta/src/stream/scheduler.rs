@@ -270,7 +270,7 @@ mod test {
     use risingwave_common::buffer::Bitmap;
     use risingwave_common::types::VIRTUAL_NODE_COUNT;
     use risingwave_pb::catalog::Table;
-    use risingwave_pb::common::{HostAddress, WorkerType};
+    use risingwave_pb::common::{HostAddress, WorkerRole, WorkerType};
     use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
     use risingwave_pb::stream_plan::stream_node::NodeBody;
     use risingwave_pb::stream_plan::{MaterializeNode, StreamActor, StreamNode, TopNNode};
@@ -292,7 +292,12 @@ mod test {
                 port: i as i32,
             };
             cluster_manager
-                .add_worker_node(WorkerType::ComputeNode, host.clone(), fake_parallelism)
+                .add_worker_node(
+                    WorkerType::ComputeNode,
+                    host.clone(),
+                    fake_parallelism,
+                    WorkerRole::Both,
+                )
                 .await?;
             cluster_manager.activate_worker_node(host).await?;
         }
@@ -405,4 +410,85 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_schedule_excludes_serving_nodes() -> MetaResult<()> {
+        let env = MetaSrvEnv::for_test().await;
+        let cluster_manager =
+            Arc::new(ClusterManager::new(env.clone(), Duration::from_secs(3600)).await?);
+
+        let fake_parallelism = 4;
+        let mut serving_worker_id = None;
+        for (port, role) in [WorkerRole::Streaming, WorkerRole::Serving, WorkerRole::Both]
+            .into_iter()
+            .enumerate()
+        {
+            let host = HostAddress {
+                host: "127.0.0.1".to_string(),
+                port: port as i32,
+            };
+            let worker = cluster_manager
+                .add_worker_node(
+                    WorkerType::ComputeNode,
+                    host.clone(),
+                    fake_parallelism,
+                    role,
+                )
+                .await?;
+            cluster_manager.activate_worker_node(host).await?;
+            if role == WorkerRole::Serving {
+                serving_worker_id = Some(worker.id);
+            }
+        }
+        let serving_worker_id = serving_worker_id.unwrap();
+
+        let parallel_units = cluster_manager.list_active_streaming_parallel_units().await;
+        assert_eq!(parallel_units.len(), 2 * fake_parallelism);
+        let scheduler = Scheduler::new(parallel_units);
+        let mut locations = ScheduledLocations::new();
+
+        let mut fragments = [
+            (1, FragmentDistributionType::Single, 1),
+            (
+                2,
+                FragmentDistributionType::Hash,
+                3 * fake_parallelism as u32,
+            ),
+        ]
+        .map(|(fragment_id, distribution_type, actor_count)| Fragment {
+            fragment_id,
+            fragment_type: 0,
+            distribution_type: distribution_type as i32,
+            actors: (0..actor_count)
+                .map(|i| StreamActor {
+                    actor_id: fragment_id * 100 + i,
+                    fragment_id,
+                    nodes: Some(StreamNode {
+                        node_body: Some(NodeBody::Materialize(MaterializeNode {
+                            table_id: fragment_id,
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    }),
+                    dispatcher: vec![],
+                    upstream_actor_id: vec![],
+                    same_worker_node_as_upstream: false,
+                    vnode_bitmap: None,
+                    mview_definition: "".to_owned(),
+                })
+                .collect(),
+            ..Default::default()
+        });
+        for fragment in &mut fragments {
+            scheduler.schedule(fragment, &mut locations).unwrap();
+        }
+
+        assert_eq!(locations.actor_locations.len(), 1 + 3 * fake_parallelism);
+        assert!(locations
+            .actor_locations
+            .values()
+            .all(|parallel_unit| parallel_unit.worker_node_id != serving_worker_id));
+
+        Ok(())
+    }
 }
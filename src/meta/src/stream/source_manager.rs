@@ -21,13 +21,21 @@ use std::time::Duration;
 
 use anyhow::anyhow;
 use itertools::Itertools;
+use risingwave_common::bail;
 use risingwave_common::catalog::TableId;
+use risingwave_connector::source::datagen::DATAGEN_CONNECTOR;
 use risingwave_connector::source::{
-    ConnectorProperties, SplitEnumeratorImpl, SplitId, SplitImpl, SplitMetaData,
+    ConnectorProperties, SplitEnumeratorImpl, SplitId, SplitImpl, SplitMetaData, KAFKA_CONNECTOR,
 };
 use risingwave_pb::catalog::source::Info::StreamSource;
 use risingwave_pb::catalog::Source;
+use risingwave_pb::ddl_service::reset_source_offset_request::Position;
+use risingwave_pb::meta::get_backfill_progress_response::backfill_progress::Phase;
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
 use risingwave_pb::source::{ConnectorSplit, ConnectorSplits};
+use risingwave_pb::stream_plan::barrier::Mutation;
+use risingwave_pb::stream_plan::source_change_schema_mutation::Columns;
+use risingwave_pb::stream_plan::SourceChangeSchemaMutation;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::{oneshot, Mutex};
 use tokio::task::JoinHandle;
@@ -48,6 +56,8 @@ pub struct SourceManager<S: MetaStore> {
     pub(crate) paused: Mutex<()>,
     barrier_scheduler: BarrierScheduler<S>,
     core: Mutex<SourceManagerCore<S>>,
+    /// Sources whose offsets are being reset, with the number of their source actors.
+    offset_resets: parking_lot::Mutex<BTreeMap<SourceId, usize>>,
 }
 
 struct SharedSplitMap {
@@ -291,23 +301,29 @@ impl<T: SplitMetaData + Clone> Eq for ActorSplitsAssignment<T> {}
 
 impl<T: SplitMetaData + Clone> PartialEq<Self> for ActorSplitsAssignment<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.splits.len() == other.splits.len()
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl<T: SplitMetaData + Clone> PartialOrd<Self> for ActorSplitsAssignment<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.splits.len().partial_cmp(&self.splits.len())
+        Some(self.cmp(other))
     }
 }
 
+/// The actor with the fewest splits is the greatest, and ties are broken by the smaller actor id,
+/// so that the assignment is deterministic.
 impl<T: SplitMetaData + Clone> Ord for ActorSplitsAssignment<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.splits.len().cmp(&self.splits.len())
+        other
+            .splits
+            .len()
+            .cmp(&self.splits.len())
+            .then_with(|| other.actor_id.cmp(&self.actor_id))
     }
 }
 
-fn diff_splits<T>(
+pub(crate) fn diff_splits<T>(
     actor_splits: HashMap<ActorId, Vec<T>>,
     discovered_splits: &BTreeMap<SplitId, T>,
 ) -> Option<HashMap<ActorId, Vec<T>>>
@@ -323,7 +339,7 @@ where
         .flat_map(|splits| splits.iter().map(SplitMetaData::id))
         .collect();
 
-    let new_discovered_splits: HashSet<_> = discovered_splits
+    let new_discovered_splits: BTreeSet<_> = discovered_splits
         .keys()
         .filter(|split_id| !prev_split_ids.contains(*split_id))
         .cloned()
@@ -338,6 +354,8 @@ where
         heap.push(ActorSplitsAssignment { actor_id, splits })
     }
 
+    // Each new split goes to the actor with the fewest splits, so no actor is left without any
+    // split unless there are fewer splits than actors.
     for split_id in new_discovered_splits {
         let mut peek_ref = heap.peek_mut().unwrap();
         peek_ref
@@ -345,6 +363,16 @@ where
             .push(discovered_splits.get(&split_id).cloned().unwrap());
     }
 
+    let idle_actors = heap.iter().filter(|a| a.splits.is_empty()).count();
+    if idle_actors > 0 {
+        tracing::info!(
+            "{} of {} source actors are assigned no split, as there are only {} splits",
+            idle_actors,
+            heap.len(),
+            discovered_splits.len()
+        );
+    }
+
     Some(
         heap.into_iter()
             .map(|ActorSplitsAssignment { actor_id, splits }| (actor_id, splits))
@@ -393,6 +421,7 @@ where
             barrier_scheduler,
             core,
             paused: Mutex::new(()),
+            offset_resets: Default::default(),
         })
     }
 
@@ -563,30 +592,181 @@ where
         Ok(())
     }
 
+    /// Pushes the altered columns of `source` down to the running source executors with a barrier,
+    /// so that all of them switch to the new schema at the same epoch.
+    pub async fn alter_source_schema(&self, source: &Source) -> MetaResult<()> {
+        let mutation = Mutation::SourceSchema(SourceChangeSchemaMutation {
+            source_columns: HashMap::from([(
+                source.id,
+                Columns {
+                    columns: source.columns.clone(),
+                },
+            )]),
+        });
+        self.barrier_scheduler
+            .run_command(Command::Plain(Some(mutation)))
+            .await
+    }
+
+    /// Resets the offsets of all splits of `source` to the given position, by overwriting the
+    /// splits assigned to its actors in a single barrier. The actors restart their readers at the
+    /// barrier, so that no data is read from the old offsets after it, while the other actors keep
+    /// running. Only one reset of a source runs at a time.
+    pub async fn reset_source_offset(
+        &self,
+        source: &Source,
+        position: Position,
+        timestamp_millis: i64,
+    ) -> MetaResult<()> {
+        if self.offset_resets.lock().contains_key(&source.id) {
+            bail!("the offsets of source {} are being reset", source.id);
+        }
+        let _pause_guard = self.paused.lock().await;
+
+        let assignment = {
+            let core = self.core.lock().await;
+            let Some(fragment_ids) = core.source_fragments.get(&source.id) else {
+                bail!("source {} is not used by any streaming job", source.id);
+            };
+            let reset_splits: HashMap<_, _> =
+                Self::list_reset_splits(source, position, timestamp_millis)
+                    .await?
+                    .into_iter()
+                    .map(|split| (split.id(), split))
+                    .collect();
+
+            // Only the running actors are assigned, not the ones created by a reschedule waiting
+            // for this reset to finish.
+            let mut assignment = SplitAssignment::new();
+            for &fragment_id in fragment_ids {
+                let actor_splits = core
+                    .fragment_manager
+                    .get_running_actors_of_fragment(fragment_id)
+                    .await?
+                    .into_iter()
+                    .map(|actor_id| {
+                        let splits = core
+                            .actor_splits
+                            .get(&actor_id)
+                            .into_iter()
+                            .flatten()
+                            .map(|split| {
+                                reset_splits
+                                    .get(&split.id())
+                                    .cloned()
+                                    .unwrap_or_else(|| split.clone())
+                            })
+                            .collect();
+                        (actor_id, splits)
+                    })
+                    .collect();
+                assignment.insert(fragment_id, actor_splits);
+            }
+            assignment
+        };
+
+        let actor_count = assignment.values().map(HashMap::len).sum();
+        if self
+            .offset_resets
+            .lock()
+            .insert(source.id, actor_count)
+            .is_some()
+        {
+            bail!("the offsets of source {} are being reset", source.id);
+        }
+        let result = self
+            .barrier_scheduler
+            .run_command(Command::SourceResetOffset(assignment))
+            .await;
+        self.offset_resets.lock().remove(&source.id);
+        result
+    }
+
+    /// Lists the splits of `source` with their start offsets at the given position.
+    async fn list_reset_splits(
+        source: &Source,
+        position: Position,
+        timestamp_millis: i64,
+    ) -> MetaResult<Vec<SplitImpl>> {
+        let mut properties = source.properties.clone();
+        match properties.get("connector").map(String::as_str) {
+            Some(KAFKA_CONNECTOR) => {
+                for key in [
+                    "scan.startup.mode",
+                    "kafka.scan.startup.mode",
+                    "scan.startup.timestamp_millis",
+                    "kafka.time.offset",
+                ] {
+                    properties.remove(key);
+                }
+                match position {
+                    Position::Earliest => {
+                        properties.insert("scan.startup.mode".into(), "earliest".into());
+                    }
+                    Position::Latest => {
+                        properties.insert("scan.startup.mode".into(), "latest".into());
+                    }
+                    Position::Timestamp => {
+                        properties.insert(
+                            "scan.startup.timestamp_millis".into(),
+                            timestamp_millis.to_string(),
+                        );
+                    }
+                }
+            }
+            // The datagen splits are listed from the beginning.
+            Some(DATAGEN_CONNECTOR) if position == Position::Earliest => {}
+            connector => bail!(
+                "resetting the offsets to {:?} is not supported for connector {}",
+                position,
+                connector.unwrap_or("unknown")
+            ),
+        }
+        let properties = ConnectorProperties::extract(properties)?;
+        let mut enumerator = SplitEnumeratorImpl::create(properties).await?;
+        Ok(enumerator.list_splits().await?)
+    }
+
+    /// Returns the progress of the running offset resets, shown as DDL progress.
+    pub fn get_offset_reset_progress(&self) -> Vec<BackfillProgress> {
+        self.offset_resets
+            .lock()
+            .iter()
+            .map(|(source_id, actor_count)| BackfillProgress {
+                table_id: *source_id,
+                upstream_table_id: *source_id,
+                phase: Phase::ResettingOffset as i32,
+                done_actor_count: 0,
+                total_actor_count: *actor_count as u32,
+            })
+            .collect()
+    }
+
     pub async fn list_assignments(&self) -> HashMap<ActorId, Vec<SplitImpl>> {
         let core = self.core.lock().await;
         core.actor_splits.clone()
     }
 
+    /// Pushes down the splits newly discovered. The assignment is derived again on each retry, as
+    /// the barrier manager rejects the one derived from the actors before a reschedule.
     async fn tick(&self) -> MetaResult<()> {
-        let diff = {
-            let core_guard = self.core.lock().await;
-            core_guard.diff().await?
-        };
+        tokio_retry::Retry::spawn(FixedInterval::new(Self::SOURCE_RETRY_INTERVAL), || async {
+            let diff = {
+                let core_guard = self.core.lock().await;
+                core_guard.diff().await?
+            };
+            if diff.is_empty() {
+                return Ok(());
+            }
 
-        if !diff.is_empty() {
             let command = Command::SourceSplitAssignment(diff);
             tracing::debug!("pushing down command {:#?}", command);
-
-            tokio_retry::Retry::spawn(FixedInterval::new(Self::SOURCE_RETRY_INTERVAL), || async {
-                let command = command.clone();
-                self.barrier_scheduler.run_command(command).await
-            })
-            .await
-            .expect("source manager barrier push down failed");
-        }
-
-        Ok(())
+            self.barrier_scheduler
+                .run_command(command)
+                .await
+                .inspect_err(|e| tracing::warn!("failed to push down source splits: {}", e))
+        })
+        .await
     }
 
     pub async fn run(&self) -> MetaResult<()> {
@@ -658,6 +838,7 @@ mod tests {
 
     use anyhow::anyhow;
     use bytes::Bytes;
+    use itertools::Itertools;
     use risingwave_connector::source::{SplitId, SplitMetaData};
     use serde::{Deserialize, Serialize};
 
@@ -765,4 +946,41 @@ mod tests {
 
         check_all_splits(&discovered_splits, &diff);
     }
+
+    #[test]
+    fn test_diff_splits_fewer_than_actors() {
+        let actor_splits = (0..5).map(|i| (i, vec![])).collect();
+        let discovered_splits: BTreeMap<SplitId, TestSplit> = (0..3)
+            .map(|i| {
+                let split = TestSplit { id: i };
+                (split.id(), split)
+            })
+            .collect();
+
+        let diff = diff_splits(actor_splits, &discovered_splits).unwrap();
+        check_all_splits(&discovered_splits, &diff);
+        // Only the actors exceeding the split count are left idle, and the assignment is stable.
+        let mut idle_actors = diff
+            .iter()
+            .filter(|(_, splits)| splits.is_empty())
+            .map(|(actor_id, _)| *actor_id)
+            .collect_vec();
+        idle_actors.sort();
+        assert_eq!(idle_actors, vec![3, 4]);
+        for actor_id in 0..3 {
+            assert_eq!(diff[&actor_id].len(), 1);
+        }
+
+        // New splits go to the idle actors first.
+        let discovered_splits: BTreeMap<SplitId, TestSplit> = (0..4)
+            .map(|i| {
+                let split = TestSplit { id: i };
+                (split.id(), split)
+            })
+            .collect();
+        let diff = diff_splits(diff, &discovered_splits).unwrap();
+        check_all_splits(&discovered_splits, &diff);
+        assert_eq!(diff[&3].len(), 1);
+        assert!(diff[&4].is_empty());
+    }
 }
@@ -306,6 +306,7 @@ impl StreamActorBuilder {
                     // will be filled later by stream manager
                     hash_mapping: None,
                     dispatcher_id: *dispatcher_id,
+                    hot_key_mode: dispatch_strategy.hot_key_mode,
                 },
             )
             .collect_vec();
@@ -619,6 +620,18 @@ impl StreamGraphBuilder {
                         }
                     }
 
+                    NodeBody::Dedup(node) => {
+                        update_table(node.state_table.as_mut().unwrap(), "DedupNode");
+                    }
+
+                    NodeBody::Now(node) => {
+                        update_table(node.state_table.as_mut().unwrap(), "NowNode");
+                    }
+
+                    NodeBody::OverWindow(node) => {
+                        update_table(node.state_table.as_mut().unwrap(), "OverWindowNode");
+                    }
+
                     NodeBody::GlobalSimpleAgg(node) => {
                         assert_eq!(node.agg_call_states.len(), node.agg_calls.len());
                         // In-place update the table id. Convert from local to global.
@@ -917,6 +930,9 @@ impl ActorGraphBuilder {
                             .keys()
                             .map(|id| id.as_global_id())
                             .collect(),
+                        // Only set when persisted.
+                        plan_template: None,
+                        actor_plan_deltas: Default::default(),
                     },
                 )
             })
@@ -1118,6 +1134,15 @@ impl ActorGraphBuilder {
             NodeBody::TopN(node) => {
                 vec![node.table.as_ref().unwrap().id]
             }
+            NodeBody::Dedup(node) => {
+                vec![node.state_table.as_ref().unwrap().id]
+            }
+            NodeBody::Now(node) => {
+                vec![node.state_table.as_ref().unwrap().id]
+            }
+            NodeBody::OverWindow(node) => {
+                vec![node.state_table.as_ref().unwrap().id]
+            }
             _ => {
                 vec![]
             }
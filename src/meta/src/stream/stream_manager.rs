@@ -26,7 +26,9 @@ use risingwave_pb::meta::table_fragments::actor_status::ActorState;
 use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
 use risingwave_pb::meta::table_fragments::ActorStatus;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
-use risingwave_pb::stream_plan::{ActorMapping, Dispatcher, DispatcherType, StreamNode};
+use risingwave_pb::stream_plan::{
+    ActorMapping, BackfillOrder, Dispatcher, DispatcherType, StreamNode,
+};
 use risingwave_pb::stream_service::{
     BroadcastActorInfoTableRequest, BuildActorsRequest, HangingChannel, UpdateActorsRequest,
 };
@@ -38,7 +40,7 @@ use crate::barrier::{BarrierScheduler, Command};
 use crate::hummock::compaction_group::manager::CompactionGroupManagerRef;
 use crate::manager::{
     ClusterManagerRef, DatabaseId, FragmentManagerRef, FragmentVNodeInfo, IdGeneratorManagerRef,
-    MetaSrvEnv, SchemaId, WorkerId,
+    MetaSrvEnv, SchemaId, TopologyChange, WorkerId,
 };
 use crate::model::{ActorId, FragmentId, TableFragments};
 use crate::storage::MetaStore;
@@ -77,6 +79,8 @@ pub struct CreateMaterializedViewContext {
     pub mview_definition: String,
 
     pub table_properties: HashMap<String, String>,
+    /// The order in which the chains backfill from their upstream tables.
+    pub backfill_order: BackfillOrder,
 }
 
 impl CreateMaterializedViewContext {
@@ -385,6 +389,7 @@ where
             dependent_table_ids,
             table_properties,
             chain_fragment_upstream_table_map,
+            backfill_order,
             ..
         }: &mut CreateMaterializedViewContext,
     ) -> MetaResult<()> {
@@ -406,7 +411,14 @@ where
             if workers.is_empty() {
                 bail!("no available compute node in the cluster");
             }
-            let parallel_units = self.cluster_manager.list_active_parallel_units().await;
+            // Actors are never scheduled to the serving-only nodes.
+            let parallel_units = self
+                .cluster_manager
+                .list_active_streaming_parallel_units()
+                .await;
+            if parallel_units.is_empty() {
+                bail!("no available streaming compute node in the cluster");
+            }
 
             // Create empty locations and the scheduler.
             let mut locations = ScheduledLocations::with_workers(workers);
@@ -669,7 +681,7 @@ where
 
         // Add table fragments to meta store with state: `State::Creating`.
         self.fragment_manager
-            .start_create_table_fragments(table_fragments.clone())
+            .apply_topology_change(TopologyChange::CreateTable(table_fragments.clone()))
             .await?;
 
         let table_id = table_fragments.table_id();
@@ -683,11 +695,12 @@ where
                 table_sink_map: table_sink_map.clone(),
                 dispatchers: dispatchers.clone(),
                 init_split_assignment: split_assignment,
+                backfill_order: *backfill_order,
             })
             .await
         {
             self.fragment_manager
-                .cancel_create_table_fragments(&table_id)
+                .apply_topology_change(TopologyChange::CancelCreateTable(table_id))
                 .await?;
             return Err(err);
         }
@@ -695,6 +708,31 @@ where
         Ok(())
     }
 
+    /// Updates the dispatchers of the sink fragment of the materialized view to
+    /// `new_dispatch_type`, and then the running dispatchers with an `Update` barrier. Check
+    /// [`crate::manager::FragmentManager::update_sink_dispatch_type`] for details.
+    pub async fn update_sink_dispatch_type(
+        &self,
+        table_id: TableId,
+        new_dispatch_type: DispatcherType,
+    ) -> MetaResult<()> {
+        let dispatcher_updates = self
+            .fragment_manager
+            .update_sink_dispatch_type(&table_id, new_dispatch_type)
+            .await?;
+        if dispatcher_updates.is_empty() {
+            return Ok(());
+        }
+
+        self.barrier_scheduler
+            .run_command(Command::update_dispatchers(
+                dispatcher_updates.into_values().flatten().collect(),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
     /// Dropping materialized view is done by barrier manager. Check
     /// [`Command::DropMaterializedViews`] for details.
     pub async fn drop_materialized_views(&self, table_ids: Vec<TableId>) -> MetaResult<()> {
@@ -759,7 +797,7 @@ mod tests {
     use std::time::Duration;
 
     use risingwave_common::catalog::TableId;
-    use risingwave_pb::common::{HostAddress, WorkerType};
+    use risingwave_pb::common::{HostAddress, WorkerRole, WorkerType};
     use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
     use risingwave_pb::meta::table_fragments::Fragment;
     use risingwave_pb::stream_plan::*;
@@ -927,7 +965,12 @@ mod tests {
             };
             let fake_parallelism = 4;
             cluster_manager
-                .add_worker_node(WorkerType::ComputeNode, host.clone(), fake_parallelism)
+                .add_worker_node(
+                    WorkerType::ComputeNode,
+                    host.clone(),
+                    fake_parallelism,
+                    WorkerRole::Both,
+                )
                 .await?;
             cluster_manager.activate_worker_node(host).await?;
 
@@ -1080,6 +1123,13 @@ mod tests {
         );
         let table_fragments = TableFragments::new(table_id, fragments);
         services.create_materialized_view(table_fragments).await?;
+        services
+            .fragment_manager
+            .wait_for_actor_running(
+                &actors.iter().map(|actor| actor.actor_id).collect(),
+                Duration::from_secs(5),
+            )
+            .await?;
 
         for actor in actors {
             let mut scheduled_actor = services
@@ -1170,6 +1220,12 @@ mod tests {
             .create_materialized_view(table_fragments)
             .await
             .unwrap();
+        let actor_ids: HashSet<_> = actors.iter().map(|actor| actor.actor_id).collect();
+        services
+            .fragment_manager
+            .wait_for_actor_running(&actor_ids, Duration::from_secs(5))
+            .await
+            .unwrap();
 
         for actor in actors {
             let mut scheduled_actor = services
@@ -1230,6 +1286,11 @@ mod tests {
             .unwrap();
         });
         notify1.notified().await;
+        services
+            .fragment_manager
+            .wait_for_actor_running(&actor_ids, Duration::from_secs(5))
+            .await
+            .unwrap();
 
         let table_fragments = services
             .global_stream_manager
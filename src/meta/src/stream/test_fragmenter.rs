@@ -67,6 +67,8 @@ fn make_sum_aggcall(idx: i32) -> AggCall {
         distinct: false,
         order_by_fields: vec![],
         filter: None,
+        approx_relative_error: 0.0,
+        approx_percentile_fraction: 0.0,
     }
 }
 
@@ -205,6 +207,7 @@ fn make_stream_fragments() -> Vec<StreamFragment> {
             strategy: Some(DispatchStrategy {
                 r#type: DispatcherType::Hash as i32,
                 column_indices: vec![0],
+                ..Default::default()
             }),
         })),
         fields: vec![
@@ -362,6 +365,7 @@ fn make_fragment_edges() -> Vec<StreamFragmentEdge> {
             dispatch_strategy: Some(DispatchStrategy {
                 r#type: DispatcherType::Simple as i32,
                 column_indices: vec![],
+                ..Default::default()
             }),
             same_worker_node: false,
             link_id: 4,
@@ -372,6 +376,7 @@ fn make_fragment_edges() -> Vec<StreamFragmentEdge> {
             dispatch_strategy: Some(DispatchStrategy {
                 r#type: DispatcherType::Hash as i32,
                 column_indices: vec![0],
+                ..Default::default()
             }),
             same_worker_node: false,
             link_id: 1,
@@ -65,6 +65,18 @@ impl StreamingUploader for InMemStreamingUploader {
     }
 }
 
+/// Sleep for `RW_SIM_OBJECT_STORE_READ_LATENCY_MS` milliseconds if set, so that reading the
+/// in-memory object store in the simulation is as slow as reading a remote one.
+#[cfg(madsim)]
+async fn simulate_read_latency() {
+    let latency_ms = std::env::var("RW_SIM_OBJECT_STORE_READ_LATENCY_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok());
+    if let Some(latency_ms) = latency_ms {
+        tokio::time::sleep(std::time::Duration::from_millis(latency_ms)).await;
+    }
+}
+
 /// In-memory object storage, useful for testing.
 #[derive(Default, Clone)]
 pub struct InMemObjectStore {
@@ -105,6 +117,8 @@ impl ObjectStore for InMemObjectStore {
         fail_point!("mem_read_err", |_| Err(ObjectError::internal(
             "mem read error"
         )));
+        #[cfg(madsim)]
+        simulate_read_latency().await;
         if let Some(loc) = block {
             self.get_object(path, |obj| find_block(obj, loc)).await?
         } else {
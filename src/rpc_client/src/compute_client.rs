@@ -29,11 +29,12 @@ use risingwave_pb::task_service::{
     AbortTaskRequest, AbortTaskResponse, CreateTaskRequest, ExecuteRequest, GetDataRequest,
     GetDataResponse, GetStreamRequest, GetStreamResponse, TaskInfoResponse,
 };
+use tokio::sync::Mutex;
 use tonic::transport::{Channel, Endpoint};
 use tonic::Streaming;
 
 use crate::error::Result;
-use crate::{RpcClient, RpcClientPool};
+use crate::{MultiplexedConnection, MultiplexedStream, RpcClient, RpcClientPool};
 
 #[derive(Clone)]
 pub struct ComputeClient {
@@ -41,6 +42,9 @@ pub struct ComputeClient {
     pub task_client: TaskServiceClient<Channel>,
     pub monitor_client: MonitorServiceClient<Channel>,
     pub addr: HostAddr,
+    /// Shared by all the streaming exchange channels from the actors on the node. Established on
+    /// first use, and re-established if broken.
+    multiplexed_connection: Arc<Mutex<Option<Arc<MultiplexedConnection>>>>,
 }
 
 impl ComputeClient {
@@ -63,6 +67,7 @@ impl ComputeClient {
             task_client,
             monitor_client,
             addr,
+            multiplexed_connection: Default::default(),
         }
     }
 
@@ -105,6 +110,51 @@ impl ComputeClient {
             .into_inner())
     }
 
+    /// Opens the channel from `up_actor_id` to `down_actor_id` on the multiplexed exchange
+    /// connection to the node. The upstream sends at most `permits` messages ahead of the
+    /// consumption of the returned stream.
+    pub async fn get_multiplexed_stream(
+        &self,
+        up_actor_id: u32,
+        down_actor_id: u32,
+        up_fragment_id: u32,
+        down_fragment_id: u32,
+        permits: u32,
+    ) -> Result<MultiplexedStream> {
+        let connection = {
+            let mut guard = self.multiplexed_connection.lock().await;
+            match guard.as_ref() {
+                Some(connection) if !connection.is_closed() => connection.clone(),
+                _ => {
+                    let connection = Arc::new(
+                        MultiplexedConnection::connect(self.exchange_client.clone()).await?,
+                    );
+                    *guard = Some(connection.clone());
+                    connection
+                }
+            }
+        };
+
+        connection
+            .subscribe(
+                GetStreamRequest {
+                    up_actor_id,
+                    down_actor_id,
+                    up_fragment_id,
+                    down_fragment_id,
+                },
+                permits,
+            )
+            .inspect_err(|_| {
+                tracing::error!(
+                    "failed to subscribe multiplexed stream from {} from actor {} to actor {}",
+                    self.addr,
+                    up_actor_id,
+                    down_actor_id
+                )
+            })
+    }
+
     pub async fn create_task(
         &self,
         task_id: TaskId,
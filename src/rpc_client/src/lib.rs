@@ -38,6 +38,8 @@ pub use compute_client::*;
 mod hummock_meta_client;
 pub use hummock_meta_client::HummockMetaClient;
 pub mod error;
+mod multiplexed_stream;
+pub use multiplexed_stream::*;
 mod stream_client;
 use rand::prelude::SliceRandom;
 use risingwave_common::util::addr::HostAddr;
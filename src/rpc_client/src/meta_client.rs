@@ -26,15 +26,22 @@ use risingwave_hummock_sdk::{
 };
 use risingwave_pb::catalog::{
     Database as ProstDatabase, Index as ProstIndex, Schema as ProstSchema, Sink as ProstSink,
-    Source as ProstSource, Table as ProstTable,
+    Source as ProstSource, SubscriptionProgress as ProstSubscriptionProgress, Table as ProstTable,
+    TableStatistics as ProstTableStatistics,
 };
-use risingwave_pb::common::WorkerType;
+use risingwave_pb::common::{WorkerNode, WorkerRole, WorkerType};
 use risingwave_pb::ddl_service::ddl_service_client::DdlServiceClient;
+use risingwave_pb::ddl_service::reset_source_offset_request::Position;
 use risingwave_pb::ddl_service::*;
+use risingwave_pb::hummock::get_table_write_stats_response::TableWriteStats;
 use risingwave_pb::hummock::hummock_manager_service_client::HummockManagerServiceClient;
 use risingwave_pb::hummock::rise_ctl_update_compaction_config_request::mutable_config::MutableConfig;
 use risingwave_pb::hummock::*;
+use risingwave_pb::meta::backup_service_client::BackupServiceClient;
 use risingwave_pb::meta::cluster_service_client::ClusterServiceClient;
+use risingwave_pb::meta::get_backfill_progress_response::BackfillProgress;
+use risingwave_pb::meta::get_table_complexity_response::TableComplexity;
+use risingwave_pb::meta::get_table_concurrency_response::TableConcurrency;
 use risingwave_pb::meta::heartbeat_request::{extra_info, ExtraInfo};
 use risingwave_pb::meta::heartbeat_service_client::HeartbeatServiceClient;
 use risingwave_pb::meta::list_table_fragments_response::TableFragmentInfo;
@@ -42,8 +49,9 @@ use risingwave_pb::meta::notification_service_client::NotificationServiceClient;
 use risingwave_pb::meta::reschedule_request::Reschedule as ProstReschedule;
 use risingwave_pb::meta::scale_service_client::ScaleServiceClient;
 use risingwave_pb::meta::stream_manager_service_client::StreamManagerServiceClient;
+use risingwave_pb::meta::system_params_service_client::SystemParamsServiceClient;
 use risingwave_pb::meta::*;
-use risingwave_pb::stream_plan::StreamFragmentGraph;
+use risingwave_pb::stream_plan::{DispatcherType, StreamFragmentGraph};
 use risingwave_pb::user::update_user_request::UpdateField;
 use risingwave_pb::user::user_service_client::UserServiceClient;
 use risingwave_pb::user::*;
@@ -53,7 +61,7 @@ use tokio_retry::strategy::{jitter, ExponentialBackoff};
 use tonic::transport::{Channel, Endpoint};
 use tonic::Streaming;
 
-use crate::error::Result;
+use crate::error::{Result, RpcError};
 use crate::hummock_meta_client::HummockMetaClient;
 use crate::{rpc_client_method_impl, ExtraInfoSourceRef};
 
@@ -69,7 +77,28 @@ pub struct MetaClient {
     pub inner: GrpcMetaClient,
 }
 
+/// Whether the RPC failed because of the connection to meta rather than meta itself, in which case
+/// meta may or may not have handled the request.
+fn is_connection_error(err: &RpcError) -> bool {
+    match err {
+        RpcError::TransportError(_) => true,
+        RpcError::GrpcStatus(status) => matches!(
+            status.code(),
+            tonic::Code::Unavailable
+                | tonic::Code::Unknown
+                | tonic::Code::Cancelled
+                | tonic::Code::DeadlineExceeded
+        ),
+        RpcError::Internal(_) => false,
+    }
+}
+
 impl MetaClient {
+    const DDL_RETRY_BASE_INTERVAL_MS: u64 = 100;
+    // The total backoff of the retries, long enough to outlast a meta failover.
+    const DDL_RETRY_MAX_BACKOFF_MS: u64 = 60000;
+    const DDL_RETRY_MAX_INTERVAL_MS: u64 = 5000;
+
     pub fn worker_id(&self) -> u32 {
         self.worker_id
     }
@@ -101,12 +130,32 @@ impl MetaClient {
         worker_type: WorkerType,
         addr: &HostAddr,
         worker_node_parallelism: usize,
+    ) -> Result<Self> {
+        Self::register_new_with_role(
+            meta_addr,
+            worker_type,
+            addr,
+            worker_node_parallelism,
+            WorkerRole::Both,
+        )
+        .await
+    }
+
+    /// Register the current node to the cluster with the given role, which decides whether
+    /// streaming actors can be scheduled to it.
+    pub async fn register_new_with_role(
+        meta_addr: &str,
+        worker_type: WorkerType,
+        addr: &HostAddr,
+        worker_node_parallelism: usize,
+        role: WorkerRole,
     ) -> Result<Self> {
         let grpc_meta_client = GrpcMetaClient::new(meta_addr).await?;
         let request = AddWorkerNodeRequest {
             worker_type: worker_type as i32,
             host: Some(addr.to_protobuf()),
             worker_node_parallelism: worker_node_parallelism as u64,
+            worker_role: role as i32,
         };
         let resp = grpc_meta_client.add_worker_node(request).await?;
         let worker_node = resp.node.expect("AddWorkerNodeResponse::node is empty");
@@ -162,18 +211,46 @@ impl MetaClient {
         Ok((resp.schema_id, resp.version))
     }
 
+    /// Create a materialized view. The request is retried with the same `idempotency_key` if it
+    /// fails because of the connection to meta, e.g. during a meta failover, so that the
+    /// materialized view is created at most once.
     pub async fn create_materialized_view(
         &self,
         table: ProstTable,
         graph: StreamFragmentGraph,
-    ) -> Result<(TableId, CatalogVersion)> {
+        idempotency_key: String,
+    ) -> Result<(TableId, CatalogVersion, TableFragments)> {
         let request = CreateMaterializedViewRequest {
             materialized_view: Some(table),
             fragment_graph: Some(graph),
+            idempotency_key,
         };
-        let resp = self.inner.create_materialized_view(request).await?;
+        let retry_strategy = ExponentialBackoff::from_millis(Self::DDL_RETRY_BASE_INTERVAL_MS)
+            .max_delay(Duration::from_millis(Self::DDL_RETRY_MAX_INTERVAL_MS))
+            .map(jitter)
+            .scan(Duration::ZERO, |backoff, interval| {
+                *backoff += interval;
+                (*backoff <= Duration::from_millis(Self::DDL_RETRY_MAX_BACKOFF_MS))
+                    .then_some(interval)
+            });
+        let resp = tokio_retry::RetryIf::spawn(
+            retry_strategy,
+            || self.inner.create_materialized_view(request.clone()),
+            |err: &RpcError| {
+                let retry = is_connection_error(err);
+                if retry {
+                    tracing::warn!("failed to create materialized view, retrying: {}", err);
+                }
+                retry
+            },
+        )
+        .await?;
         // TODO: handle error in `resp.status` here
-        Ok((resp.table_id.into(), resp.version))
+        Ok((
+            resp.table_id.into(),
+            resp.version,
+            resp.table_fragments.unwrap_or_default(),
+        ))
     }
 
     pub async fn drop_materialized_view(&self, table_id: TableId) -> Result<CatalogVersion> {
@@ -194,6 +271,31 @@ impl MetaClient {
         Ok((resp.source_id, resp.version))
     }
 
+    pub async fn alter_source(&self, source: ProstSource) -> Result<CatalogVersion> {
+        let request = AlterSourceRequest {
+            source: Some(source),
+        };
+
+        let resp = self.inner.alter_source(request).await?;
+        Ok(resp.version)
+    }
+
+    pub async fn reset_source_offset(
+        &self,
+        source_id: u32,
+        position: Position,
+        timestamp_millis: i64,
+    ) -> Result<()> {
+        let request = ResetSourceOffsetRequest {
+            source_id,
+            position: position as i32,
+            timestamp_millis,
+        };
+
+        self.inner.reset_source_offset(request).await?;
+        Ok(())
+    }
+
     pub async fn create_sink(
         &self,
         sink: ProstSink,
@@ -274,6 +376,45 @@ impl MetaClient {
         Ok(resp.version)
     }
 
+    pub async fn update_table_statistics(
+        &self,
+        table_id: TableId,
+        statistics: ProstTableStatistics,
+    ) -> Result<CatalogVersion> {
+        let request = UpdateTableStatisticsRequest {
+            table_id: table_id.table_id,
+            statistics: Some(statistics),
+        };
+        let resp = self.inner.update_table_statistics(request).await?;
+        Ok(resp.version)
+    }
+
+    pub async fn update_subscription_progress(
+        &self,
+        table_id: TableId,
+        expected_progress: ProstSubscriptionProgress,
+        progress: ProstSubscriptionProgress,
+    ) -> Result<()> {
+        let request = UpdateSubscriptionProgressRequest {
+            table_id: table_id.table_id,
+            expected_progress: Some(expected_progress),
+            progress: Some(progress),
+        };
+        self.inner.update_subscription_progress(request).await?;
+        Ok(())
+    }
+
+    pub async fn get_subscription_progress(
+        &self,
+        table_id: TableId,
+    ) -> Result<ProstSubscriptionProgress> {
+        let request = GetSubscriptionProgressRequest {
+            table_id: table_id.table_id,
+        };
+        let resp = self.inner.get_subscription_progress(request).await?;
+        Ok(resp.progress.unwrap_or_default())
+    }
+
     pub async fn drop_database(&self, database_id: u32) -> Result<CatalogVersion> {
         let request = DropDatabaseRequest { database_id };
         let resp = self.inner.drop_database(request).await?;
@@ -363,6 +504,23 @@ impl MetaClient {
         Ok(())
     }
 
+    /// Change the role of the compute node at `addr`. A node can only become serving-only when
+    /// no streaming actors are on it.
+    pub async fn update_worker_node_role(
+        &self,
+        addr: HostAddr,
+        role: WorkerRole,
+    ) -> Result<WorkerNode> {
+        let request = UpdateWorkerNodeRoleRequest {
+            host: Some(addr.to_protobuf()),
+            role: role as i32,
+        };
+        let resp = self.inner.update_worker_node_role(request).await?;
+        Ok(resp
+            .node
+            .expect("UpdateWorkerNodeRoleResponse::node is empty"))
+    }
+
     /// Starts a heartbeat worker.
     ///
     /// When sending heartbeat RPC, it also carries extra info from `extra_info_sources`.
@@ -436,6 +594,52 @@ impl MetaClient {
         Ok(resp.table_fragments)
     }
 
+    pub async fn get_table_complexity(&self) -> Result<Vec<TableComplexity>> {
+        let request = GetTableComplexityRequest {};
+        let resp = self.inner.get_table_complexity(request).await?;
+        Ok(resp.tables)
+    }
+
+    pub async fn get_table_concurrency(&self) -> Result<Vec<TableConcurrency>> {
+        let request = GetTableConcurrencyRequest {};
+        let resp = self.inner.get_table_concurrency(request).await?;
+        Ok(resp.tables)
+    }
+
+    pub async fn get_cluster_status(&self) -> Result<GetClusterStatusResponse> {
+        let request = GetClusterStatusRequest {};
+        let resp = self.inner.get_cluster_status(request).await?;
+        Ok(resp)
+    }
+
+    pub async fn get_backfill_progress(&self) -> Result<Vec<BackfillProgress>> {
+        let request = GetBackfillProgressRequest {};
+        let resp = self.inner.get_backfill_progress(request).await?;
+        Ok(resp.progress)
+    }
+
+    /// Logs the events of the actors at `level` for `duration_secs`. Returns the ids of the actors.
+    pub async fn set_log_level(
+        &self,
+        target: set_log_level_request::Target,
+        level: String,
+        duration_secs: u64,
+    ) -> Result<Vec<u32>> {
+        let request = SetLogLevelRequest {
+            target: Some(target),
+            level,
+            duration_secs,
+        };
+        let resp = self.inner.set_log_level(request).await?;
+        Ok(resp.actor_ids)
+    }
+
+    pub async fn dump_table_fragments(&self, table_id: u32) -> Result<String> {
+        let request = DumpTableFragmentsRequest { table_id };
+        let resp = self.inner.dump_table_fragments(request).await?;
+        Ok(resp.dump)
+    }
+
     pub async fn pause(&self) -> Result<()> {
         let request = PauseRequest {};
         let _resp = self.inner.pause(request).await?;
@@ -460,6 +664,56 @@ impl MetaClient {
         Ok(resp.success)
     }
 
+    /// Returns the descriptions of the fixes found, which are applied unless `dry_run`.
+    pub async fn fix_fragments(&self, request: FixFragmentsRequest) -> Result<Vec<String>> {
+        let resp = self.inner.fix_fragments(request).await?;
+        Ok(resp.fixes)
+    }
+
+    pub async fn update_sink_dispatch_type(
+        &self,
+        table_id: u32,
+        dispatcher_type: DispatcherType,
+    ) -> Result<()> {
+        let request = UpdateSinkDispatchTypeRequest {
+            table_id,
+            dispatcher_type: dispatcher_type as i32,
+        };
+        self.inner.update_sink_dispatch_type(request).await?;
+        Ok(())
+    }
+
+    pub async fn create_meta_backup(&self) -> Result<MetaBackupInfo> {
+        let request = CreateMetaBackupRequest {};
+        let resp = self.inner.create_meta_backup(request).await?;
+        Ok(resp.backup.unwrap())
+    }
+
+    pub async fn list_meta_backups(&self) -> Result<Vec<MetaBackupInfo>> {
+        let request = ListMetaBackupsRequest {};
+        let resp = self.inner.list_meta_backups(request).await?;
+        Ok(resp.backups)
+    }
+
+    /// Returns a self-contained backup of the table fragments of `table_id`.
+    pub async fn backup_table_fragments(&self, table_id: u32) -> Result<Vec<u8>> {
+        let request = BackupTableFragmentsRequest { table_id };
+        let resp = self.inner.backup_table_fragments(request).await?;
+        Ok(resp.table_fragments)
+    }
+
+    pub async fn get_system_params(&self) -> Result<SystemParams> {
+        let request = GetSystemParamsRequest {};
+        let resp = self.inner.get_system_params(request).await?;
+        Ok(resp.params.unwrap())
+    }
+
+    pub async fn set_system_param(&self, param: String, value: String) -> Result<SystemParams> {
+        let request = SetSystemParamRequest { param, value };
+        let resp = self.inner.set_system_param(request).await?;
+        Ok(resp.params.unwrap())
+    }
+
     pub async fn risectl_get_pinned_versions_summary(
         &self,
     ) -> Result<RiseCtlGetPinnedVersionsSummaryResponse> {
@@ -577,6 +831,12 @@ impl MetaClient {
         let _resp = self.inner.rise_ctl_update_compaction_config(req).await?;
         Ok(())
     }
+
+    pub async fn get_table_write_stats(&self) -> Result<Vec<TableWriteStats>> {
+        let req = GetTableWriteStatsRequest {};
+        let resp = self.inner.get_table_write_stats(req).await?;
+        Ok(resp.table_write_stats)
+    }
 }
 
 #[async_trait]
@@ -663,10 +923,14 @@ impl HummockMetaClient for MetaClient {
     async fn subscribe_compact_tasks(
         &self,
         max_concurrent_task_number: u64,
+        memory_budget: u64,
+        block_size: u64,
     ) -> Result<Streaming<SubscribeCompactTasksResponse>> {
         let req = SubscribeCompactTasksRequest {
             context_id: self.worker_id(),
             max_concurrent_task_number,
+            memory_budget,
+            block_size,
         };
         self.inner.subscribe_compact_tasks(req).await
     }
@@ -743,6 +1007,8 @@ pub struct GrpcMetaClient {
     pub stream_client: StreamManagerServiceClient<Channel>,
     pub user_client: UserServiceClient<Channel>,
     pub scale_client: ScaleServiceClient<Channel>,
+    pub backup_client: BackupServiceClient<Channel>,
+    pub system_params_client: SystemParamsServiceClient<Channel>,
 }
 
 impl GrpcMetaClient {
@@ -789,7 +1055,9 @@ impl GrpcMetaClient {
         let notification_client = NotificationServiceClient::new(channel.clone());
         let stream_client = StreamManagerServiceClient::new(channel.clone());
         let user_client = UserServiceClient::new(channel.clone());
-        let scale_client = ScaleServiceClient::new(channel);
+        let scale_client = ScaleServiceClient::new(channel.clone());
+        let backup_client = BackupServiceClient::new(channel.clone());
+        let system_params_client = SystemParamsServiceClient::new(channel);
         Ok(Self {
             cluster_client,
             heartbeat_client,
@@ -799,6 +1067,8 @@ impl GrpcMetaClient {
             stream_client,
             user_client,
             scale_client,
+            backup_client,
+            system_params_client,
         })
     }
 }
@@ -809,10 +1079,17 @@ macro_rules! for_all_meta_rpc {
              { cluster_client, add_worker_node, AddWorkerNodeRequest, AddWorkerNodeResponse }
             ,{ cluster_client, activate_worker_node, ActivateWorkerNodeRequest, ActivateWorkerNodeResponse }
             ,{ cluster_client, delete_worker_node, DeleteWorkerNodeRequest, DeleteWorkerNodeResponse }
+            ,{ cluster_client, update_worker_node_role, UpdateWorkerNodeRoleRequest, UpdateWorkerNodeRoleResponse }
             ,{ cluster_client, list_all_nodes, ListAllNodesRequest, ListAllNodesResponse }
+            ,{ cluster_client, get_cluster_status, GetClusterStatusRequest, GetClusterStatusResponse }
             ,{ heartbeat_client, heartbeat, HeartbeatRequest, HeartbeatResponse }
             ,{ stream_client, flush, FlushRequest, FlushResponse }
             ,{ stream_client, list_table_fragments, ListTableFragmentsRequest, ListTableFragmentsResponse }
+            ,{ stream_client, get_table_complexity, GetTableComplexityRequest, GetTableComplexityResponse }
+            ,{ stream_client, get_table_concurrency, GetTableConcurrencyRequest, GetTableConcurrencyResponse }
+            ,{ stream_client, dump_table_fragments, DumpTableFragmentsRequest, DumpTableFragmentsResponse }
+            ,{ stream_client, get_backfill_progress, GetBackfillProgressRequest, GetBackfillProgressResponse }
+            ,{ stream_client, set_log_level, SetLogLevelRequest, SetLogLevelResponse }
             ,{ ddl_client, create_materialized_source, CreateMaterializedSourceRequest, CreateMaterializedSourceResponse }
             ,{ ddl_client, create_materialized_view, CreateMaterializedViewRequest, CreateMaterializedViewResponse }
             ,{ ddl_client, create_source, CreateSourceRequest, CreateSourceResponse }
@@ -823,10 +1100,15 @@ macro_rules! for_all_meta_rpc {
             ,{ ddl_client, drop_materialized_source, DropMaterializedSourceRequest, DropMaterializedSourceResponse }
             ,{ ddl_client, drop_materialized_view, DropMaterializedViewRequest, DropMaterializedViewResponse }
             ,{ ddl_client, drop_source, DropSourceRequest, DropSourceResponse }
+            ,{ ddl_client, alter_source, AlterSourceRequest, AlterSourceResponse }
+            ,{ ddl_client, reset_source_offset, ResetSourceOffsetRequest, ResetSourceOffsetResponse }
             ,{ ddl_client, drop_sink, DropSinkRequest, DropSinkResponse }
             ,{ ddl_client, drop_database, DropDatabaseRequest, DropDatabaseResponse }
             ,{ ddl_client, drop_schema, DropSchemaRequest, DropSchemaResponse }
             ,{ ddl_client, drop_index, DropIndexRequest, DropIndexResponse }
+            ,{ ddl_client, update_table_statistics, UpdateTableStatisticsRequest, UpdateTableStatisticsResponse }
+            ,{ ddl_client, update_subscription_progress, UpdateSubscriptionProgressRequest, UpdateSubscriptionProgressResponse }
+            ,{ ddl_client, get_subscription_progress, GetSubscriptionProgressRequest, GetSubscriptionProgressResponse }
             ,{ ddl_client, risectl_list_state_tables, RisectlListStateTablesRequest, RisectlListStateTablesResponse }
             ,{ hummock_client, unpin_version_before, UnpinVersionBeforeRequest, UnpinVersionBeforeResponse }
             ,{ hummock_client, get_current_version, GetCurrentVersionRequest, GetCurrentVersionResponse }
@@ -854,6 +1136,7 @@ macro_rules! for_all_meta_rpc {
             ,{ hummock_client, rise_ctl_get_pinned_snapshots_summary, RiseCtlGetPinnedSnapshotsSummaryRequest, RiseCtlGetPinnedSnapshotsSummaryResponse }
             ,{ hummock_client, rise_ctl_list_compaction_group, RiseCtlListCompactionGroupRequest, RiseCtlListCompactionGroupResponse }
             ,{ hummock_client, rise_ctl_update_compaction_config, RiseCtlUpdateCompactionConfigRequest, RiseCtlUpdateCompactionConfigResponse }
+            ,{ hummock_client, get_table_write_stats, GetTableWriteStatsRequest, GetTableWriteStatsResponse }
             ,{ user_client, create_user, CreateUserRequest, CreateUserResponse }
             ,{ user_client, update_user, UpdateUserRequest, UpdateUserResponse }
             ,{ user_client, drop_user, DropUserRequest, DropUserResponse }
@@ -863,6 +1146,13 @@ macro_rules! for_all_meta_rpc {
             ,{ scale_client, resume, ResumeRequest, ResumeResponse }
             ,{ scale_client, get_cluster_info, GetClusterInfoRequest, GetClusterInfoResponse }
             ,{ scale_client, reschedule, RescheduleRequest, RescheduleResponse }
+            ,{ scale_client, fix_fragments, FixFragmentsRequest, FixFragmentsResponse }
+            ,{ scale_client, update_sink_dispatch_type, UpdateSinkDispatchTypeRequest, UpdateSinkDispatchTypeResponse }
+            ,{ backup_client, create_meta_backup, CreateMetaBackupRequest, CreateMetaBackupResponse }
+            ,{ backup_client, list_meta_backups, ListMetaBackupsRequest, ListMetaBackupsResponse }
+            ,{ backup_client, backup_table_fragments, BackupTableFragmentsRequest, BackupTableFragmentsResponse }
+            ,{ system_params_client, get_system_params, GetSystemParamsRequest, GetSystemParamsResponse }
+            ,{ system_params_client, set_system_param, SetSystemParamRequest, SetSystemParamResponse }
             ,{ notification_client, subscribe, SubscribeRequest, Streaming<SubscribeResponse> }
         }
     };
@@ -0,0 +1,224 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+
+use futures::{Stream, StreamExt};
+use risingwave_pb::stream_plan::StreamMessage;
+use risingwave_pb::task_service::exchange_service_client::ExchangeServiceClient;
+use risingwave_pb::task_service::get_multiplexed_stream_request::{
+    AddPermits, Subscribe, Unsubscribe, Value,
+};
+use risingwave_pb::task_service::get_multiplexed_stream_response::Payload;
+use risingwave_pb::task_service::{
+    GetMultiplexedStreamRequest, GetMultiplexedStreamResponse, GetStreamRequest,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::transport::Channel;
+use tonic::Streaming;
+
+use crate::error::{anyhow, Result};
+
+/// The upstream and downstream actor ids of a logical channel.
+type ChannelId = (u32, u32);
+
+#[derive(Default)]
+struct Channels {
+    /// Set once the connection is broken, after which no more channel can be opened on it.
+    closed: bool,
+    senders: HashMap<ChannelId, UnboundedSender<Result<StreamMessage>>>,
+}
+
+/// A `GetMultiplexedStream` RPC to a compute node, which carries the messages of all the logical
+/// channels from the actors on that node, instead of opening a gRPC stream for each of them.
+pub struct MultiplexedConnection {
+    request_tx: UnboundedSender<GetMultiplexedStreamRequest>,
+    channels: Arc<Mutex<Channels>>,
+}
+
+impl MultiplexedConnection {
+    pub async fn connect(mut client: ExchangeServiceClient<Channel>) -> Result<Self> {
+        let (request_tx, request_rx) = unbounded_channel();
+        let responses = client
+            .get_multiplexed_stream(UnboundedReceiverStream::new(request_rx))
+            .await?
+            .into_inner();
+        let channels = Arc::new(Mutex::new(Channels::default()));
+        tokio::spawn(Self::dispatch(responses, channels.clone()));
+
+        Ok(Self {
+            request_tx,
+            channels,
+        })
+    }
+
+    /// Routes the responses to the logical channels. All the channels fail if the connection is
+    /// broken.
+    async fn dispatch(
+        mut responses: Streaming<GetMultiplexedStreamResponse>,
+        channels: Arc<Mutex<Channels>>,
+    ) {
+        let error = loop {
+            let response = match responses.next().await {
+                Some(Ok(response)) => response,
+                Some(Err(status)) => break status.to_string(),
+                None => break "connection closed by peer".to_string(),
+            };
+            let id = (response.up_actor_id, response.down_actor_id);
+            let mut channels = channels.lock().unwrap();
+            match response.payload {
+                Some(Payload::Message(message)) => {
+                    if let Some(sender) = channels.senders.get(&id) {
+                        // The channel may be dropped by the downstream, in which case the upstream
+                        // will be unsubscribed soon.
+                        let _ = sender.send(Ok(message));
+                    }
+                }
+                Some(Payload::Error(error)) => {
+                    if let Some(sender) = channels.senders.remove(&id) {
+                        let _ = sender.send(Err(anyhow!(error).into()));
+                    }
+                }
+                None => {
+                    channels.senders.remove(&id);
+                }
+            }
+        };
+
+        tracing::warn!("multiplexed exchange connection is broken: {}", error);
+        let mut channels = channels.lock().unwrap();
+        channels.closed = true;
+        for (_, sender) in channels.senders.drain() {
+            let _ = sender.send(Err(anyhow!(
+                "multiplexed exchange connection is broken: {}",
+                error
+            )
+            .into()));
+        }
+    }
+
+    /// Opens the logical channel described by `request`. The upstream can send at most `permits`
+    /// messages before the downstream consumes them.
+    pub fn subscribe(&self, request: GetStreamRequest, permits: u32) -> Result<MultiplexedStream> {
+        let id = (request.up_actor_id, request.down_actor_id);
+        let (tx, rx) = unbounded_channel();
+        {
+            let mut channels = self.channels.lock().unwrap();
+            if channels.closed {
+                return Err(anyhow!("multiplexed exchange connection is broken").into());
+            }
+            match channels.senders.entry(id) {
+                Entry::Occupied(_) => {
+                    return Err(anyhow!(
+                        "channel from actor {} to actor {} is already subscribed",
+                        id.0,
+                        id.1
+                    )
+                    .into());
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(tx);
+                }
+            }
+        }
+
+        let subscribe = Value::Subscribe(Subscribe {
+            channel: Some(request),
+            permits,
+        });
+        let stream = MultiplexedStream {
+            id,
+            receiver: rx,
+            request_tx: self.request_tx.clone(),
+            channels: self.channels.clone(),
+            window: permits,
+            consumed: 0,
+        };
+        stream.send_request(subscribe)?;
+        Ok(stream)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.channels.lock().unwrap().closed
+    }
+
+    /// The number of the logical channels opened on this connection.
+    pub fn channel_count(&self) -> usize {
+        self.channels.lock().unwrap().senders.len()
+    }
+}
+
+/// A logical channel on a [`MultiplexedConnection`]. The consumed messages are given back to the
+/// upstream as permits once half of the window is consumed, so that a slow downstream only stops
+/// its own channel, instead of blocking the shared connection.
+pub struct MultiplexedStream {
+    id: ChannelId,
+    receiver: UnboundedReceiver<Result<StreamMessage>>,
+    request_tx: UnboundedSender<GetMultiplexedStreamRequest>,
+    channels: Arc<Mutex<Channels>>,
+    window: u32,
+    consumed: u32,
+}
+
+impl MultiplexedStream {
+    fn send_request(&self, value: Value) -> Result<()> {
+        self.request_tx
+            .send(GetMultiplexedStreamRequest { value: Some(value) })
+            .map_err(|_| anyhow!("multiplexed exchange connection is broken").into())
+    }
+}
+
+impl Stream for MultiplexedStream {
+    type Item = Result<StreamMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.receiver.poll_recv(cx));
+        if let Some(Ok(_)) = &item {
+            self.consumed += 1;
+            if self.consumed >= (self.window / 2).max(1) {
+                let add_permits = Value::AddPermits(AddPermits {
+                    up_actor_id: self.id.0,
+                    down_actor_id: self.id.1,
+                    permits: std::mem::take(&mut self.consumed),
+                });
+                // The error will be received from the channel on next poll.
+                let _ = self.send_request(add_permits);
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl Drop for MultiplexedStream {
+    fn drop(&mut self) {
+        if self
+            .channels
+            .lock()
+            .unwrap()
+            .senders
+            .remove(&self.id)
+            .is_some()
+        {
+            let _ = self.send_request(Value::Unsubscribe(Unsubscribe {
+                up_actor_id: self.id.0,
+                down_actor_id: self.id.1,
+            }));
+        }
+    }
+}
@@ -226,6 +226,12 @@ impl SourceDescBuilder {
         }
     }
 
+    /// Replaces the columns of the source, e.g. after `ALTER SOURCE ... ADD COLUMN`. Takes effect
+    /// on the next [`SourceDescBuilder::build`].
+    pub fn set_columns(&mut self, columns: Vec<ProstColumnCatalog>) {
+        self.columns = columns;
+    }
+
     pub async fn build(&self) -> Result<SourceDescRef> {
         match &self.info {
             ProstSourceInfo::TableSource(_) => self.build_table_source(),
@@ -258,15 +264,6 @@ impl SourceDescBuilder {
                 "protobuf file location not provided".to_string(),
             )));
         }
-        let source_parser_rs =
-            SourceParserImpl::create(&format, &self.properties, info.row_schema_location.as_str())
-                .await;
-        let parser = if let Ok(source_parser) = source_parser_rs {
-            source_parser
-        } else {
-            return Err(source_parser_rs.err().unwrap());
-        };
-
         let mut columns: Vec<_> = self
             .columns
             .iter()
@@ -276,6 +273,18 @@ impl SourceDescBuilder {
             columns[row_id_index.index as usize].skip_parse = true;
             row_id_index.index as usize
         });
+        let source_parser_rs = SourceParserImpl::create(
+            &format,
+            &self.properties,
+            info.row_schema_location.as_str(),
+            &columns,
+        )
+        .await;
+        let parser = if let Ok(source_parser) = source_parser_rs {
+            source_parser
+        } else {
+            return Err(source_parser_rs.err().unwrap());
+        };
         assert!(
             !self.pk_column_ids.is_empty(),
             "source should have at least one pk column"
@@ -32,7 +32,7 @@ use risingwave_connector::aws_utils::{default_conn_config, s3_client, AwsConfigV
 use risingwave_pb::plan_common::ColumnDesc;
 use url::Url;
 
-use crate::{SourceParser, SourceStreamChunkRowWriter, WriteGuard};
+use crate::{SourceColumnDesc, SourceParser, SourceStreamChunkRowWriter, WriteGuard};
 
 const AVRO_SCHEMA_LOCATION_S3_REGION: &str = "region";
 
@@ -82,6 +82,22 @@ impl AvroParser {
         }
     }
 
+    /// Drops the fields not read by `columns` from the reader schema, so that their values are
+    /// discarded while reading a record instead of being kept and converted. Avro's binary
+    /// encoding has no field tags, so the unused fields are still read to be skipped.
+    pub fn prune_fields(&mut self, columns: &[SourceColumnDesc]) {
+        if let Schema::Record { fields, lookup, .. } = &mut self.schema {
+            fields.retain(|field| columns.iter().any(|column| column.name == field.name));
+            for (position, field) in fields.iter_mut().enumerate() {
+                field.position = position;
+            }
+            *lookup = fields
+                .iter()
+                .map(|field| (field.name.clone(), field.position))
+                .collect();
+        }
+    }
+
     pub fn map_to_columns(&self) -> Result<Vec<ColumnDesc>> {
         // there must be a record at top level
         if let Schema::Record { fields, .. } = &self.schema {
@@ -417,6 +433,7 @@ mod test {
     use apache_avro::types::{Record, Value};
     use apache_avro::{Codec, Days, Duration, Millis, Months, Schema, Writer};
     use chrono::NaiveDate;
+    use itertools::Itertools;
     use risingwave_common::array::Op;
     use risingwave_common::catalog::ColumnId;
     use risingwave_common::error;
@@ -562,6 +579,44 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn test_avro_parser_pruned_fields() {
+        let mut avro_parser = new_avro_parser_from_local("simple-schema.avsc")
+            .await
+            .unwrap();
+        let record = build_avro_data(&avro_parser.schema);
+        let mut writer = Writer::with_codec(&avro_parser.schema, Vec::new(), Codec::Snappy);
+        writer.append(record).unwrap();
+        writer.flush().unwrap();
+        let input_data = writer.into_inner().unwrap();
+
+        let columns = build_rw_columns()
+            .into_iter()
+            .filter(|column| column.name == "id" || column.name == "name")
+            .collect_vec();
+        avro_parser.prune_fields(&columns);
+        let Schema::Record { fields, lookup, .. } = &avro_parser.schema else {
+            unreachable!()
+        };
+        assert_eq!(
+            fields.iter().map(|field| field.name.as_str()).collect_vec(),
+            ["id", "name"]
+        );
+        assert_eq!(lookup.len(), 2);
+
+        let mut builder = SourceStreamChunkBuilder::with_capacity(columns, 1);
+        {
+            let writer = builder.row_writer();
+            avro_parser.parse(&input_data[..], writer).unwrap();
+        }
+        let chunk = builder.finish();
+        let (op, row) = chunk.rows().next().unwrap();
+        assert_eq!(op, Op::Insert);
+        let row = row.to_owned_row();
+        assert_eq!(row[0], Some(ScalarImpl::Int32(32)));
+        assert_eq!(row[1], Some(ScalarImpl::Utf8("str_value".to_string())));
+    }
+
     fn build_rw_columns() -> Vec<SourceColumnDesc> {
         vec![
             SourceColumnDesc {
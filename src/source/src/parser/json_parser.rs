@@ -235,6 +235,53 @@ mod tests {
         assert_eq!(chunk.cardinality(), 2);
     }
 
+    #[test]
+    fn test_json_parser_pruned_columns() {
+        let parser = JsonParser;
+        // Only 3 of the 40 columns are required by the downstream.
+        let descs = vec![
+            SourceColumnDesc::simple("c0", DataType::Int32, 0.into()),
+            SourceColumnDesc::simple("c17", DataType::Int32, 17.into()),
+            SourceColumnDesc::simple("c39", DataType::Int32, 39.into()),
+        ];
+        let mut builder = SourceStreamChunkBuilder::with_capacity(descs, 2);
+
+        for row in 0..2 {
+            let fields = (0..40)
+                .map(|i| match i {
+                    0 | 17 | 39 => format!(r#""c{}": {}"#, i, row * 100 + i),
+                    // The pruned columns are never decoded, so their malformed values don't fail
+                    // the parser.
+                    _ => format!(r#""c{}": "not an int""#, i),
+                })
+                .join(", ");
+            let payload = format!("{{{}}}", fields);
+            let writer = builder.row_writer();
+            parser.parse(payload.as_bytes(), writer).unwrap();
+        }
+
+        let chunk = builder.finish();
+        assert_eq!(chunk.columns().len(), 3);
+        let rows = chunk
+            .rows()
+            .map(|(_, row)| {
+                (0..3)
+                    .map(|i| row.value_at(i).to_owned_datum())
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(
+            rows,
+            [[0, 17, 39], [100, 117, 139]]
+                .iter()
+                .map(|row| row
+                    .iter()
+                    .map(|&v| Some(ScalarImpl::Int32(v)))
+                    .collect_vec())
+                .collect_vec()
+        );
+    }
+
     #[test]
     fn test_json_parse_struct() {
         let parser = JsonParser;
@@ -288,10 +288,12 @@ impl SourceParserImpl {
         }
     }
 
+    /// Creates the parser of `format`, which only parses the fields read by `columns`.
     pub async fn create(
         format: &SourceFormat,
         properties: &HashMap<String, String>,
         schema_location: &str,
+        columns: &[SourceColumnDesc],
     ) -> Result<Arc<Self>> {
         const PROTOBUF_MESSAGE_KEY: &str = "proto.message";
         let parser = match format {
@@ -303,13 +305,16 @@ impl SourceParserImpl {
                         PROTOBUF_MESSAGE_KEY
                     )))
                 })?;
-                SourceParserImpl::Protobuf(
-                    ProtobufParser::new(schema_location, message_name, properties.clone()).await?,
-                )
+                let mut parser =
+                    ProtobufParser::new(schema_location, message_name, properties.clone()).await?;
+                parser.prune_fields(columns);
+                SourceParserImpl::Protobuf(parser)
             }
             SourceFormat::DebeziumJson => SourceParserImpl::DebeziumJson(DebeziumJsonParser),
             SourceFormat::Avro => {
-                SourceParserImpl::Avro(AvroParser::new(schema_location, properties.clone()).await?)
+                let mut parser = AvroParser::new(schema_location, properties.clone()).await?;
+                parser.prune_fields(columns);
+                SourceParserImpl::Avro(parser)
             }
             SourceFormat::Maxwell => SourceParserImpl::Maxwell(MaxwellParser),
             _ => {
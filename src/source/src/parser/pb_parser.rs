@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 use itertools::Itertools;
+use prost::encoding::{decode_key, skip_field, DecodeContext};
 use prost_reflect::{
     Cardinality, DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor,
     ReflectMessage, Value,
@@ -30,13 +32,15 @@ use risingwave_connector::aws_utils::{default_conn_config, s3_client, AwsConfigV
 use risingwave_pb::plan_common::ColumnDesc;
 use url::Url;
 
-use crate::{SourceParser, WriteGuard};
+use crate::{SourceColumnDesc, SourceParser, WriteGuard};
 
 const PB_SCHEMA_LOCATION_S3_REGION: &str = "region";
 
 #[derive(Debug, Clone)]
 pub struct ProtobufParser {
     pub message_descriptor: MessageDescriptor,
+    /// The numbers of the fields to decode, or `None` to decode all of them.
+    projected_fields: Option<HashSet<u32>>,
 }
 
 impl ProtobufParser {
@@ -80,7 +84,43 @@ impl ProtobufParser {
                 message_name, location, pool
             ))
         })?;
-        Ok(Self { message_descriptor })
+        Ok(Self {
+            message_descriptor,
+            projected_fields: None,
+        })
+    }
+
+    /// Only decodes the fields read by `columns`. The other fields are skipped on the wire
+    /// without being decoded.
+    pub fn prune_fields(&mut self, columns: &[SourceColumnDesc]) {
+        let projected_fields = columns
+            .iter()
+            .filter_map(|column| self.message_descriptor.get_field_by_name(&column.name))
+            .map(|field| field.number())
+            .collect();
+        self.projected_fields = Some(projected_fields);
+    }
+
+    /// Strips the fields that are not projected from the encoded message.
+    fn project<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        let Some(projected_fields) = &self.projected_fields else {
+            return Ok(Cow::Borrowed(payload));
+        };
+        let mut projected = Vec::with_capacity(payload.len());
+        let mut buf = payload;
+        while !buf.is_empty() {
+            let start = payload.len() - buf.len();
+            let tag = decode_key(&mut buf)
+                .and_then(|(tag, wire_type)| {
+                    skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+                    Ok(tag)
+                })
+                .map_err(|e| ProtocolError(format!("parse message failed: {}", e)))?;
+            if projected_fields.contains(&tag) {
+                projected.extend_from_slice(&payload[start..payload.len() - buf.len()]);
+            }
+        }
+        Ok(Cow::Owned(projected))
     }
 
     /// read binary schema from a local file
@@ -289,8 +329,9 @@ impl SourceParser for ProtobufParser {
         payload: &[u8],
         writer: crate::SourceStreamChunkRowWriter<'_>,
     ) -> Result<WriteGuard> {
-        let message = DynamicMessage::decode(self.message_descriptor.clone(), payload)
-            .map_err(|e| ProtocolError(format!("parse message failed: {}", e)))?;
+        let message =
+            DynamicMessage::decode(self.message_descriptor.clone(), &*self.project(payload)?)
+                .map_err(|e| ProtocolError(format!("parse message failed: {}", e)))?;
         writer.insert(|column_desc| {
             let field_desc = message
                 .descriptor()
@@ -321,6 +362,7 @@ mod test {
     use risingwave_pb::data::data_type::TypeName as ProstTypeName;
 
     use super::*;
+    use crate::SourceStreamChunkBuilder;
 
     fn schema_dir() -> String {
         let dir = PathBuf::from("src/test_data");
@@ -374,6 +416,33 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_pruned_fields() -> Result<()> {
+        let location = schema_dir() + "/simple-schema";
+        let mut parser = ProtobufParser::new(&location, "test.TestRecord", HashMap::new()).await?;
+        // Id: 123, Address: invalid UTF-8, Zipcode: 456
+        let payload = b"\x08\x7b\x12\x02\xff\xfe\x20\xc8\x03";
+        let columns = vec![
+            SourceColumnDesc::simple("id", DataType::Int32, 0.into()),
+            SourceColumnDesc::simple("zipcode", DataType::Int64, 1.into()),
+        ];
+
+        let mut builder = SourceStreamChunkBuilder::with_capacity(columns.clone(), 1);
+        assert!(parser.parse(payload, builder.row_writer()).is_err());
+
+        // The pruned address is skipped without being decoded, so its malformed value doesn't
+        // fail the parser.
+        parser.prune_fields(&columns);
+        let mut builder = SourceStreamChunkBuilder::with_capacity(columns, 1);
+        parser.parse(payload, builder.row_writer())?;
+        let chunk = builder.finish();
+        let row = chunk.rows().next().unwrap().1.to_owned_row();
+        assert_eq!(row[0], Some(ScalarImpl::Int32(123)));
+        assert_eq!(row[1], Some(ScalarImpl::Int64(456)));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_complex_schema() -> Result<()> {
         let location = schema_dir() + "/complex-schema";
@@ -20,7 +20,9 @@ use core::fmt;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::ast::{display_comma_separated, display_separated, DataType, Expr, Ident, ObjectName};
+use crate::ast::{
+    display_comma_separated, display_separated, value, DataType, Expr, Ident, ObjectName,
+};
 use crate::tokenizer::Token;
 
 /// An `ALTER TABLE` (`Statement::AlterTable`) operation
@@ -133,6 +135,62 @@ impl fmt::Display for AlterTableOperation {
     }
 }
 
+/// An `ALTER SOURCE` (`Statement::AlterSource`) operation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AlterSourceOperation {
+    /// `ADD [ COLUMN ] <column_def>`
+    AddColumn { column_def: ColumnDef },
+    /// `RESET OFFSET TO <position> [ FORCE ]`
+    ResetOffset {
+        position: SourceOffsetPosition,
+        force: bool,
+    },
+}
+
+impl fmt::Display for AlterSourceOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlterSourceOperation::AddColumn { column_def } => {
+                write!(f, "ADD COLUMN {}", column_def)
+            }
+            AlterSourceOperation::ResetOffset { position, force } => {
+                write!(f, "RESET OFFSET TO {}", position)?;
+                if *force {
+                    write!(f, " FORCE")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The position to reset the offsets of a source to, in `ALTER SOURCE ... RESET OFFSET`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SourceOffsetPosition {
+    Earliest,
+    Latest,
+    /// `TIMESTAMP '<timestamp>'`
+    Timestamp(String),
+}
+
+impl fmt::Display for SourceOffsetPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceOffsetPosition::Earliest => write!(f, "EARLIEST"),
+            SourceOffsetPosition::Latest => write!(f, "LATEST"),
+            SourceOffsetPosition::Timestamp(timestamp) => {
+                write!(
+                    f,
+                    "TIMESTAMP '{}'",
+                    value::escape_single_quote_string(timestamp)
+                )
+            }
+        }
+    }
+}
+
 /// An `ALTER COLUMN` (`Statement::AlterTable`) operation
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -32,8 +32,8 @@ use serde::{Deserialize, Serialize};
 
 pub use self::data_type::{DataType, StructField};
 pub use self::ddl::{
-    AlterColumnOperation, AlterTableOperation, ColumnDef, ColumnOption, ColumnOptionDef,
-    ReferentialAction, TableConstraint,
+    AlterColumnOperation, AlterSourceOperation, AlterTableOperation, ColumnDef, ColumnOption,
+    ColumnOptionDef, ReferentialAction, SourceOffsetPosition, TableConstraint,
 };
 pub use self::operator::{BinaryOperator, UnaryOperator};
 pub use self::query::{
@@ -704,8 +704,12 @@ pub enum ShowObject {
     MaterializedView { schema: Option<Ident> },
     Source { schema: Option<Ident> },
     Sink { schema: Option<Ident> },
+    Subscription { schema: Option<Ident> },
     MaterializedSource { schema: Option<Ident> },
     Columns { table: ObjectName },
+    TableComplexity,
+    TableFragments { table_id: u32 },
+    ClusterStatus,
 }
 
 impl fmt::Display for ShowObject {
@@ -732,7 +736,13 @@ impl fmt::Display for ShowObject {
                 write!(f, "MATERIALIZED SOURCES{}", fmt_schema(schema))
             }
             ShowObject::Sink { schema } => write!(f, "SINKS{}", fmt_schema(schema)),
+            ShowObject::Subscription { schema } => {
+                write!(f, "SUBSCRIPTIONS{}", fmt_schema(schema))
+            }
             ShowObject::Columns { table } => write!(f, "COLUMNS FROM {}", table),
+            ShowObject::TableComplexity => f.write_str("TABLE COMPLEXITY"),
+            ShowObject::TableFragments { table_id } => write!(f, "TABLE FRAGMENTS {}", table_id),
+            ShowObject::ClusterStatus => f.write_str("CLUSTER STATUS"),
         }
     }
 }
@@ -818,6 +828,12 @@ impl fmt::Display for ExplainOptions {
 pub enum Statement {
     /// Analyze (Hive)
     Analyze { table_name: ObjectName },
+    /// FETCH rows from a subscription
+    Fetch {
+        subscription_name: ObjectName,
+        /// The number of rows to fetch, or `None` for `ALL`
+        count: Option<u64>,
+    },
     /// Truncate (Hive)
     Truncate { table_name: ObjectName },
     /// SELECT
@@ -897,12 +913,20 @@ pub enum Statement {
     },
     /// CREATE SINK
     CreateSink { stmt: CreateSinkStatement },
+    /// CREATE SUBSCRIPTION
+    CreateSubscription { stmt: CreateSubscriptionStatement },
     /// ALTER TABLE
     AlterTable {
         /// Table name
         name: ObjectName,
         operation: AlterTableOperation,
     },
+    /// ALTER SOURCE
+    AlterSource {
+        /// Source name
+        name: ObjectName,
+        operation: AlterSourceOperation,
+    },
     /// DESCRIBE TABLE OR SOURCE
     Describe {
         /// Table or Source name
@@ -1041,6 +1065,17 @@ impl fmt::Display for Statement {
                 write!(f, "ANALYZE TABLE {}", table_name)?;
                 Ok(())
             }
+            Statement::Fetch {
+                subscription_name,
+                count,
+            } => {
+                write!(f, "FETCH ")?;
+                match count {
+                    Some(count) => write!(f, "{}", count)?,
+                    None => write!(f, "ALL")?,
+                }
+                write!(f, " FROM {}", subscription_name)
+            }
             Statement::Describe { name } => {
                 write!(f, "DESCRIBE {}", name)?;
                 Ok(())
@@ -1228,9 +1263,13 @@ impl fmt::Display for Statement {
                 }
             ),
             Statement::CreateSink { stmt } => write!(f, "CREATE SINK {}", stmt,),
+            Statement::CreateSubscription { stmt } => write!(f, "CREATE SUBSCRIPTION {}", stmt,),
             Statement::AlterTable { name, operation } => {
                 write!(f, "ALTER TABLE {} {}", name, operation)
             }
+            Statement::AlterSource { name, operation } => {
+                write!(f, "ALTER SOURCE {} {}", name, operation)
+            }
             Statement::Drop(stmt) => write!(f, "DROP {}", stmt),
             Statement::SetVariable {
                 local,
@@ -1711,6 +1750,7 @@ pub enum ObjectType {
     Source,
     MaterializedSource,
     Sink,
+    Subscription,
     Database,
     User,
 }
@@ -1726,6 +1766,7 @@ impl fmt::Display for ObjectType {
             ObjectType::Source => "SOURCE",
             ObjectType::MaterializedSource => "MATERIALIZED SOURCE",
             ObjectType::Sink => "SINK",
+            ObjectType::Subscription => "SUBSCRIPTION",
             ObjectType::Database => "DATABASE",
             ObjectType::User => "USER",
         })
@@ -1746,6 +1787,8 @@ impl ParseTo for ObjectType {
             ObjectType::Source
         } else if parser.parse_keyword(Keyword::SINK) {
             ObjectType::Sink
+        } else if parser.parse_keyword(Keyword::SUBSCRIPTION) {
+            ObjectType::Subscription
         } else if parser.parse_keyword(Keyword::INDEX) {
             ObjectType::Index
         } else if parser.parse_keyword(Keyword::SCHEMA) {
@@ -1756,7 +1799,7 @@ impl ParseTo for ObjectType {
             ObjectType::User
         } else {
             return parser.expected(
-                "TABLE, VIEW, INDEX, MATERIALIZED VIEW, SOURCE, MATERIALIZED SOURCE, SINK, SCHEMA, DATABASE or USER after DROP",
+                "TABLE, VIEW, INDEX, MATERIALIZED VIEW, SOURCE, MATERIALIZED SOURCE, SINK, SUBSCRIPTION, SCHEMA, DATABASE or USER after DROP",
                 parser.peek_token(),
             );
         };
@@ -193,6 +193,7 @@ define_keywords!(
     DROP,
     DYNAMIC,
     EACH,
+    EARLIEST,
     ELEMENT,
     ELSE,
     ENCRYPTED,
@@ -223,6 +224,7 @@ define_keywords!(
     FLUSH,
     FOLLOWING,
     FOR,
+    FORCE,
     FOREIGN,
     FORMAT,
     FRAME_ROW,
@@ -272,6 +274,7 @@ define_keywords!(
     LARGE,
     LAST,
     LATERAL,
+    LATEST,
     LEADING,
     LEFT,
     LEVEL,
@@ -392,6 +395,7 @@ define_keywords!(
     REPAIR,
     REPEATABLE,
     REPLACE,
+    RESET,
     RESTRICT,
     RESULT,
     RETURN,
@@ -450,6 +454,8 @@ define_keywords!(
     STRING,
     STRUCT,
     SUBMULTISET,
+    SUBSCRIPTION,
+    SUBSCRIPTIONS,
     SUBSTRING,
     SUBSTRING_REGEX,
     SUCCEEDS,
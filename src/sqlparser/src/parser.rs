@@ -168,6 +168,7 @@ impl Parser {
             Token::Word(w) => match w.keyword {
                 Keyword::EXPLAIN => Ok(self.parse_explain()?),
                 Keyword::ANALYZE => Ok(self.parse_analyze()?),
+                Keyword::FETCH => Ok(self.parse_fetch()?),
                 Keyword::SELECT | Keyword::WITH | Keyword::VALUES => {
                     self.prev_token();
                     Ok(Statement::Query(Box::new(self.parse_query()?)))
@@ -224,6 +225,26 @@ impl Parser {
         Ok(Statement::Analyze { table_name })
     }
 
+    /// Parse a `FETCH [ NEXT | ALL | count ] FROM subscription` statement.
+    pub fn parse_fetch(&mut self) -> Result<Statement, ParserError> {
+        let count = if self.parse_keyword(Keyword::ALL) {
+            None
+        } else if self.parse_keyword(Keyword::NEXT) {
+            Some(1)
+        } else if let Token::Number(_) = self.peek_token() {
+            Some(self.parse_literal_uint()?)
+        } else {
+            Some(1)
+        };
+        self.expect_keyword(Keyword::FROM)?;
+        let subscription_name = self.parse_object_name()?;
+
+        Ok(Statement::Fetch {
+            subscription_name,
+            count,
+        })
+    }
+
     /// Parse a new expression including wildcard & qualified wildcard
     pub fn parse_wildcard_expr(&mut self) -> Result<WildcardExpr, ParserError> {
         let index = self.index;
@@ -1481,6 +1502,8 @@ impl Parser {
             self.parse_create_source(true, or_replace)
         } else if self.parse_keyword(Keyword::SINK) {
             self.parse_create_sink(or_replace)
+        } else if self.parse_keyword(Keyword::SUBSCRIPTION) {
+            self.parse_create_subscription(or_replace)
         } else if or_replace {
             self.expected(
                 "[EXTERNAL] TABLE or [MATERIALIZED] VIEW after CREATE OR REPLACE",
@@ -1574,6 +1597,15 @@ impl Parser {
         })
     }
 
+    pub fn parse_create_subscription(
+        &mut self,
+        _or_replace: bool,
+    ) -> Result<Statement, ParserError> {
+        Ok(Statement::CreateSubscription {
+            stmt: CreateSubscriptionStatement::parse_to(self)?,
+        })
+    }
+
     // CREATE USER name [ [ WITH ] option [ ... ] ]
     // where option can be:
     //       SUPERUSER | NOSUPERUSER
@@ -1870,8 +1902,10 @@ impl Parser {
             self.parse_alter_table()
         } else if self.parse_keyword(Keyword::USER) {
             self.parse_alter_user()
+        } else if self.parse_keyword(Keyword::SOURCE) {
+            self.parse_alter_source()
         } else {
-            self.expected("TABLE or USER after ALTER", self.peek_token())
+            self.expected("TABLE, USER or SOURCE after ALTER", self.peek_token())
         }
     }
 
@@ -1966,6 +2000,41 @@ impl Parser {
         })
     }
 
+    pub fn parse_alter_source(&mut self) -> Result<Statement, ParserError> {
+        let source_name = self.parse_object_name()?;
+        let operation = if self.parse_keyword(Keyword::ADD) {
+            let _ = self.parse_keyword(Keyword::COLUMN);
+            let column_def = self.parse_column_def()?;
+            AlterSourceOperation::AddColumn { column_def }
+        } else if self.parse_keywords(&[Keyword::RESET, Keyword::OFFSET, Keyword::TO]) {
+            let position = match self.parse_one_of_keywords(&[
+                Keyword::EARLIEST,
+                Keyword::LATEST,
+                Keyword::TIMESTAMP,
+            ]) {
+                Some(Keyword::EARLIEST) => SourceOffsetPosition::Earliest,
+                Some(Keyword::LATEST) => SourceOffsetPosition::Latest,
+                Some(Keyword::TIMESTAMP) => {
+                    SourceOffsetPosition::Timestamp(self.parse_literal_string()?)
+                }
+                _ => {
+                    return self.expected(
+                        "EARLIEST, LATEST or TIMESTAMP after RESET OFFSET TO",
+                        self.peek_token(),
+                    )
+                }
+            };
+            let force = self.parse_keyword(Keyword::FORCE);
+            AlterSourceOperation::ResetOffset { position, force }
+        } else {
+            return self.expected("ADD or RESET after ALTER SOURCE", self.peek_token());
+        };
+        Ok(Statement::AlterSource {
+            name: source_name,
+            operation,
+        })
+    }
+
     /// Parse a copy statement
     pub fn parse_copy(&mut self) -> Result<Statement, ParserError> {
         let table_name = self.parse_object_name()?;
@@ -2714,6 +2783,20 @@ impl Parser {
     pub fn parse_set(&mut self) -> Result<Statement, ParserError> {
         let modifier = self.parse_one_of_keywords(&[Keyword::SESSION, Keyword::LOCAL]);
         let variable = self.parse_identifier()?;
+        if variable.value.eq_ignore_ascii_case("TIME") && self.parse_keyword(Keyword::ZONE) {
+            // `SET TIME ZONE <value>` is an alias of `SET timezone = <value>`.
+            let token = self.peek_token();
+            let value = match (self.parse_value(), token) {
+                (Ok(value), _) => SetVariableValue::Literal(value),
+                (Err(_), Token::Word(ident)) => SetVariableValue::Ident(ident.to_ident()),
+                (Err(_), unexpected) => self.expected("variable value", unexpected)?,
+            };
+            return Ok(Statement::SetVariable {
+                local: modifier == Some(Keyword::LOCAL),
+                variable: Ident::new("timezone"),
+                value: vec![value],
+            });
+        }
         if self.consume_token(&Token::Eq) || self.parse_keyword(Keyword::TO) {
             let mut values = vec![];
             loop {
@@ -2781,6 +2864,11 @@ impl Parser {
                         schema: self.parse_from_and_identifier()?,
                     }))
                 }
+                Keyword::SUBSCRIPTIONS => {
+                    return Ok(Statement::ShowObjects(ShowObject::Subscription {
+                        schema: self.parse_from_and_identifier()?,
+                    }))
+                }
                 Keyword::DATABASES => {
                     return Ok(Statement::ShowObjects(ShowObject::Database));
                 }
@@ -2801,6 +2889,37 @@ impl Parser {
                             .expected("VIEWS or SOURCES after MATERIALIZED", self.peek_token());
                     }
                 }
+                Keyword::TABLE => {
+                    if matches!(
+                        self.peek_token(),
+                        Token::Word(w) if w.value.eq_ignore_ascii_case("COMPLEXITY")
+                    ) {
+                        self.next_token();
+                        return Ok(Statement::ShowObjects(ShowObject::TableComplexity));
+                    }
+                    if matches!(
+                        self.peek_token(),
+                        Token::Word(w) if w.value.eq_ignore_ascii_case("FRAGMENTS")
+                    ) {
+                        self.next_token();
+                        let table_id = self.parse_literal_uint()?;
+                        let table_id = u32::try_from(table_id).map_err(|_| {
+                            ParserError::ParserError(format!("invalid table id: {}", table_id))
+                        })?;
+                        return Ok(Statement::ShowObjects(ShowObject::TableFragments {
+                            table_id,
+                        }));
+                    }
+                }
+                Keyword::CLUSTER => {
+                    if matches!(
+                        self.peek_token(),
+                        Token::Word(w) if w.value.eq_ignore_ascii_case("STATUS")
+                    ) {
+                        self.next_token();
+                        return Ok(Statement::ShowObjects(ShowObject::ClusterStatus));
+                    }
+                }
                 Keyword::COLUMNS => {
                     if self.parse_keyword(Keyword::FROM) {
                         return Ok(Statement::ShowObjects(ShowObject::Columns {
@@ -1560,6 +1560,54 @@ fn parse_alter_table() {
     }
 }
 
+#[test]
+fn parse_alter_source() {
+    let add_column = "ALTER SOURCE src ADD foo INT";
+    match one_statement_parses_to(add_column, "ALTER SOURCE src ADD COLUMN foo INT") {
+        Statement::AlterSource {
+            name,
+            operation: AlterSourceOperation::AddColumn { column_def },
+        } => {
+            assert_eq!("src", name.to_string());
+            assert_eq!("foo", column_def.name.to_string());
+            assert_eq!("INT", column_def.data_type.to_string());
+        }
+        _ => unreachable!(),
+    };
+
+    verified_stmt("ALTER SOURCE src RESET OFFSET TO EARLIEST FORCE");
+    verified_stmt("ALTER SOURCE src RESET OFFSET TO LATEST");
+    match verified_stmt("ALTER SOURCE src RESET OFFSET TO TIMESTAMP '2022-11-01 00:00:00' FORCE") {
+        Statement::AlterSource {
+            operation: AlterSourceOperation::ResetOffset { position, force },
+            ..
+        } => {
+            assert_eq!(
+                SourceOffsetPosition::Timestamp("2022-11-01 00:00:00".to_string()),
+                position
+            );
+            assert!(force);
+        }
+        _ => unreachable!(),
+    };
+
+    let res = parse_sql_statements("ALTER SOURCE src DROP COLUMN foo");
+    assert_eq!(
+        ParserError::ParserError(
+            "Expected ADD or RESET after ALTER SOURCE, found: DROP".to_string()
+        ),
+        res.unwrap_err()
+    );
+
+    let res = parse_sql_statements("ALTER SOURCE src RESET OFFSET TO 0");
+    assert_eq!(
+        ParserError::ParserError(
+            "Expected EARLIEST, LATEST or TIMESTAMP after RESET OFFSET TO, found: 0".to_string()
+        ),
+        res.unwrap_err()
+    );
+}
+
 #[test]
 fn parse_alter_table_constraints() {
     check_one("CONSTRAINT address_pkey PRIMARY KEY (address_id)");
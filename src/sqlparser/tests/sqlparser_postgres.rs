@@ -438,6 +438,11 @@ fn parse_set() {
 
     one_statement_parses_to("SET a TO b", "SET a = b");
     one_statement_parses_to("SET SESSION a = b", "SET a = b");
+    one_statement_parses_to(
+        "SET TIME ZONE 'Asia/Shanghai'",
+        "SET timezone = 'Asia/Shanghai'",
+    );
+    one_statement_parses_to("SET TIME ZONE LOCAL", "SET timezone = LOCAL");
 
     assert_eq!(
         parse_sql_statements("SET"),
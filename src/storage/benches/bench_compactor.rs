@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::ops::Range;
 use std::sync::Arc;
 
@@ -177,6 +178,8 @@ async fn compact<I: HummockIterator<Direction = Forward>>(iter: I, sstable_store
         cache_policy: CachePolicy::Disable,
         gc_delete_keys: false,
         watermark: 0,
+        split_table_ids: HashSet::new(),
+        table_split_size: None,
     };
     Compactor::compact_and_build_sst(
         &mut builder,
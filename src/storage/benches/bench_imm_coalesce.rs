@@ -0,0 +1,93 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+use std::ops::Bound;
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use risingwave_hummock_sdk::key::key_with_epoch;
+use risingwave_storage::hummock::shared_buffer::shared_buffer_batch::SharedBufferBatch;
+use risingwave_storage::hummock::store::version::StagingVersion;
+use risingwave_storage::hummock::value::HummockValue;
+
+const EPOCH: u64 = 2333;
+
+fn user_key_of(idx: usize) -> Vec<u8> {
+    format!("test_key_{:08}", idx).into_bytes()
+}
+
+/// Generates `imm_count` imms of the same epoch, each holding `batch_size` consecutive keys.
+fn gen_staging_version(batch_size: usize, imm_count: usize) -> StagingVersion {
+    let mut imm = VecDeque::new();
+    for i in 0..imm_count {
+        let batch_data = (0..batch_size)
+            .map(|j| {
+                (
+                    Bytes::from(key_with_epoch(user_key_of(i * batch_size + j), EPOCH)),
+                    HummockValue::put(Bytes::copy_from_slice("value".as_bytes())),
+                )
+            })
+            .collect();
+        imm.push_front(SharedBufferBatch::for_test(
+            batch_data,
+            EPOCH,
+            Default::default(),
+        ));
+    }
+    StagingVersion {
+        imm,
+        sst: VecDeque::new(),
+        epoch_fence: 0,
+    }
+}
+
+fn run_prune_overlap(staging: &StagingVersion, point_count: usize) {
+    for idx in 0..point_count {
+        let key = user_key_of(idx);
+        let key_range = (Bound::Included(key.clone()), Bound::Included(key));
+        let (imms, _) = staging.prune_overlap(EPOCH, Default::default(), &key_range);
+        assert_eq!(imms.count(), 1);
+    }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    const BATCH_SIZE: usize = 100;
+    const IMM_COUNT: usize = 100;
+
+    let staging = gen_staging_version(BATCH_SIZE, IMM_COUNT);
+    c.bench_with_input(
+        BenchmarkId::new("bench-prune-overlap", "100-imms"),
+        &staging,
+        |b, staging| {
+            b.iter(|| run_prune_overlap(staging, BATCH_SIZE * IMM_COUNT));
+        },
+    );
+
+    let mut staging = gen_staging_version(BATCH_SIZE, IMM_COUNT);
+    let imm_size = staging.imm[0].size();
+    let merge_count = staging.coalesce_small_imms(imm_size * 10, usize::MAX);
+    assert_eq!(merge_count, 10);
+    assert_eq!(staging.imm.len(), 10);
+    c.bench_with_input(
+        BenchmarkId::new("bench-prune-overlap", "10-coalesced-imms"),
+        &staging,
+        |b, staging| {
+            b.iter(|| run_prune_overlap(staging, BATCH_SIZE * IMM_COUNT));
+        },
+    );
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -114,6 +114,9 @@ pub async fn compactor_serve(
         hummock_meta_client.clone(),
         storage_config.sstable_id_remote_fetch_number,
     ));
+    let task_memory_limiter = Arc::new(MemoryLimiter::new(
+        (storage_config.compactor_memory_limit_mb as u64) << 20,
+    ));
     let context = Arc::new(Context {
         options: storage_config,
         hummock_meta_client: hummock_meta_client.clone(),
@@ -125,6 +128,7 @@ pub async fn compactor_serve(
         )),
         filter_key_extractor_manager: filter_key_extractor_manager.clone(),
         read_memory_limiter: memory_limiter,
+        task_memory_limiter,
         sstable_id_manager: sstable_id_manager.clone(),
         task_progress_manager: Default::default(),
     });
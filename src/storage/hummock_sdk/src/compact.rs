@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_pb::hummock::{CompactTask, SstableInfo};
+use risingwave_pb::hummock::{CompactTask, LevelType, SstableInfo};
 
 pub fn compact_task_to_string(compact_task: &CompactTask) -> String {
     use std::fmt::Write;
@@ -32,6 +32,13 @@ pub fn compact_task_to_string(compact_task: &CompactTask) -> String {
     )
     .unwrap();
     writeln!(s, "Compaction # splits: {:?} ", compact_task.splits.len()).unwrap();
+    writeln!(
+        s,
+        "Compaction input size: {}KB, estimated memory usage: {}KB",
+        compact_task.input_file_size / 1024,
+        compact_task.estimated_memory_usage / 1024
+    )
+    .unwrap();
     writeln!(s, "Compaction task status: {:?} ", compact_task.task_status).unwrap();
     s.push_str("Compaction Sstables structure: \n");
     for level_entry in &compact_task.input_ssts {
@@ -88,3 +95,145 @@ pub fn append_sstable_info_to_string(s: &mut String, sstable_info: &SstableInfo)
         .unwrap();
     }
 }
+
+/// The parameters of a compactor that determine the memory usage of a compaction task.
+#[derive(Clone, Copy, Debug)]
+pub struct CompactionMemoryEstimateOptions {
+    /// Size of a data block, the unit in which input SSTs are read and output SSTs are built.
+    pub block_size: u64,
+    /// Upper bound of the size of an output SST.
+    pub max_target_file_size: u64,
+    /// Number of sub-compactions running in parallel, i.e. the number of splits of the task.
+    pub parallelism: u64,
+}
+
+/// Total file size of the input SSTs of `compact_task`.
+pub fn compact_task_input_size(compact_task: &CompactTask) -> u64 {
+    compact_task
+        .input_ssts
+        .iter()
+        .flat_map(|level| level.table_infos.iter())
+        .map(|table| table.file_size)
+        .sum()
+}
+
+/// Estimates how many sub-compactions the compactor splits a task with `input_size` into, when
+/// each of them is expected to handle `sub_compaction_size` bytes.
+pub fn estimate_compaction_parallelism(
+    input_size: u64,
+    sub_compaction_size: u64,
+    max_sub_compaction: u32,
+) -> u64 {
+    if sub_compaction_size == 0 || input_size <= sub_compaction_size * 2 {
+        return 1;
+    }
+    std::cmp::min(input_size / sub_compaction_size, max_sub_compaction as u64).max(1)
+}
+
+/// Estimates the peak memory usage of running `compact_task`. Each sub-compaction holds
+/// - a block for each iterator on the input, i.e. one for each SST of an overlapping level and one
+///   for each non-overlapping level,
+/// - an output SST being built and its next block,
+/// - the key hashes of the output SST kept for deduplication and the bloom filter, assuming 4 bytes
+///   for every key no shorter than 64 bytes.
+pub fn estimate_compaction_memory(
+    compact_task: &CompactTask,
+    options: &CompactionMemoryEstimateOptions,
+) -> u64 {
+    let read_buffer_size: u64 = compact_task
+        .input_ssts
+        .iter()
+        .map(|level| {
+            let block_sizes = level
+                .table_infos
+                .iter()
+                .map(|table| std::cmp::min(table.file_size, options.block_size));
+            if level.level_type == LevelType::Nonoverlapping as i32 {
+                block_sizes.max().unwrap_or(0)
+            } else {
+                block_sizes.sum()
+            }
+        })
+        .sum();
+
+    let mut output_size = std::cmp::min(
+        options.max_target_file_size,
+        compact_task_input_size(compact_task),
+    );
+    if compact_task.target_file_size > 0 {
+        output_size = std::cmp::min(output_size, compact_task.target_file_size);
+    }
+    let dedup_buffer_size = output_size / 16;
+
+    let sub_compaction_size =
+        read_buffer_size + output_size + options.block_size + dedup_buffer_size;
+    sub_compaction_size * options.parallelism.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_pb::hummock::InputLevel;
+
+    use super::*;
+
+    const MB: u64 = 1 << 20;
+
+    fn input_level(level_idx: u32, level_type: LevelType, file_sizes: &[u64]) -> InputLevel {
+        InputLevel {
+            level_idx,
+            level_type: level_type as i32,
+            table_infos: file_sizes
+                .iter()
+                .map(|&file_size| SstableInfo {
+                    file_size,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_compaction_memory() {
+        let mut task = CompactTask {
+            input_ssts: vec![
+                input_level(0, LevelType::Overlapping, &[4 * MB, MB / 2, 2 * MB]),
+                input_level(1, LevelType::Nonoverlapping, &[8 * MB, 8 * MB]),
+            ],
+            target_file_size: 16 * MB,
+            ..Default::default()
+        };
+        assert_eq!(compact_task_input_size(&task), 22 * MB + MB / 2);
+
+        let options = CompactionMemoryEstimateOptions {
+            block_size: MB,
+            max_target_file_size: 32 * MB,
+            parallelism: 2,
+        };
+        // Blocks of the input: 1MB + 0.5MB + 1MB for L0, 1MB for L1.
+        // Output: 16MB SST + 1MB block + 1MB key hashes.
+        assert_eq!(
+            estimate_compaction_memory(&task, &options),
+            2 * (3 * MB + MB / 2 + 18 * MB)
+        );
+
+        // The output is bounded by the input.
+        task.input_ssts.pop();
+        task.target_file_size = 0;
+        let options = CompactionMemoryEstimateOptions {
+            parallelism: 1,
+            ..options
+        };
+        assert_eq!(
+            estimate_compaction_memory(&task, &options),
+            2 * MB + MB / 2 + (6 * MB + MB / 2) + MB + (6 * MB + MB / 2) / 16
+        );
+    }
+
+    #[test]
+    fn test_estimate_compaction_parallelism() {
+        assert_eq!(estimate_compaction_parallelism(64 * MB, 32 * MB, 4), 1);
+        assert_eq!(estimate_compaction_parallelism(100 * MB, 32 * MB, 4), 3);
+        assert_eq!(estimate_compaction_parallelism(1024 * MB, 32 * MB, 4), 4);
+        assert_eq!(estimate_compaction_parallelism(1024 * MB, 0, 4), 1);
+    }
+}
@@ -26,6 +26,7 @@ use risingwave_pb::hummock::{
 
 use super::StateTableId;
 use crate::compaction_group::StaticCompactionGroupId;
+use crate::key::{upgrade_full_key, KeyEncodingVersion};
 use crate::prost_key_range::KeyRangeExt;
 use crate::{can_concat, CompactionGroupId, HummockSstableId};
 
@@ -127,6 +128,9 @@ pub trait HummockVersionExt {
     fn build_branched_sst_info(
         &self,
     ) -> BTreeMap<HummockSstableId, HashMap<CompactionGroupId, u64>>;
+    /// Upgrades the key ranges of the SSTs added before the key encoding is versioned. See
+    /// [`upgrade_legacy_key_range`].
+    fn upgrade_legacy_key_ranges(&mut self);
 }
 
 impl HummockVersionExt for HummockVersion {
@@ -410,6 +414,18 @@ impl HummockVersionExt for HummockVersion {
         ret.retain(|_, v| v.len() != 1 || *v.values().next().unwrap() != 0);
         ret
     }
+
+    fn upgrade_legacy_key_ranges(&mut self) {
+        for levels in self.levels.values_mut() {
+            let l0 = levels.l0.iter_mut().flat_map(|l0| l0.sub_levels.iter_mut());
+            for level in l0.chain(levels.levels.iter_mut()) {
+                level
+                    .table_infos
+                    .iter_mut()
+                    .for_each(upgrade_legacy_key_range);
+            }
+        }
+    }
 }
 
 fn update_compaction_group_info(
@@ -424,6 +440,24 @@ fn update_compaction_group_info(
     }
 }
 
+/// Upgrades the key range of an SST added before the key encoding is versioned to the latest
+/// encoding, and marks the SST with the encoding of its keys, which is
+/// [`KeyEncodingVersion::V1`]. The SSTs already marked are kept as is, so the upgrade is
+/// idempotent.
+///
+/// The upgrade preserves the order of the keys, so the SSTs in a level stay sorted and
+/// non-overlapping after their key ranges are upgraded.
+pub fn upgrade_legacy_key_range(sst: &mut SstableInfo) {
+    if sst.key_encoding_version != 0 {
+        return;
+    }
+    if let Some(key_range) = sst.key_range.as_mut() {
+        key_range.left = upgrade_full_key(&key_range.left);
+        key_range.right = upgrade_full_key(&key_range.right);
+    }
+    sst.key_encoding_version = KeyEncodingVersion::V1.to_u32();
+}
+
 pub trait HummockLevelsExt {
     fn get_level0(&self) -> &OverlappingLevel;
     fn get_level(&self, idx: usize) -> &Level;
@@ -628,6 +662,9 @@ fn level_insert_ssts(operand: &mut Level, insert_table_infos: Vec<SstableInfo>)
 pub trait HummockVersionDeltaExt {
     fn get_removed_sst_ids(&self) -> Vec<HummockSstableId>;
     fn get_inserted_sst_ids(&self) -> Vec<HummockSstableId>;
+    /// Upgrades the key ranges of the inserted SSTs added before the key encoding is versioned.
+    /// See [`upgrade_legacy_key_range`].
+    fn upgrade_legacy_key_ranges(&mut self);
 }
 
 impl HummockVersionDeltaExt for HummockVersionDelta {
@@ -658,6 +695,19 @@ impl HummockVersionDeltaExt for HummockVersionDelta {
         }
         ret
     }
+
+    fn upgrade_legacy_key_ranges(&mut self) {
+        for group_deltas in self.group_deltas.values_mut() {
+            for group_delta in &mut group_deltas.group_deltas {
+                if let Some(DeltaType::IntraLevel(intra_level)) = group_delta.delta_type.as_mut() {
+                    intra_level
+                        .inserted_table_infos
+                        .iter_mut()
+                        .for_each(upgrade_legacy_key_range);
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -669,11 +719,13 @@ mod tests {
     use risingwave_pb::hummock::hummock_version_delta::GroupDeltas;
     use risingwave_pb::hummock::{
         CompactionConfig, GroupConstruct, GroupDelta, GroupDestroy, HummockVersion,
-        HummockVersionDelta, IntraLevelDelta, Level, LevelType, OverlappingLevel, SstableInfo,
+        HummockVersionDelta, IntraLevelDelta, KeyRange, Level, LevelType, OverlappingLevel,
+        SstableInfo,
     };
 
-    use super::HummockLevelsExt;
+    use super::{upgrade_legacy_key_range, HummockLevelsExt};
     use crate::compaction_group::hummock_version_ext::HummockVersionExt;
+    use crate::key::{key_with_epoch, upgrade_full_key, KeyEncodingVersion};
 
     #[test]
     fn test_get_sst_ids() {
@@ -822,4 +874,36 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_upgrade_legacy_key_range() {
+        let legacy_key_range = KeyRange {
+            left: key_with_epoch(b"\0\0\0\x01\x02aaa".to_vec(), 1),
+            right: key_with_epoch(b"\0\0\0\x01\x03bbb".to_vec(), 1),
+        };
+        let mut sst = SstableInfo {
+            id: 1,
+            key_range: Some(legacy_key_range.clone()),
+            ..Default::default()
+        };
+        upgrade_legacy_key_range(&mut sst);
+        let upgraded_key_range = KeyRange {
+            left: upgrade_full_key(&legacy_key_range.left),
+            right: upgrade_full_key(&legacy_key_range.right),
+        };
+        assert_eq!(sst.key_range.as_ref(), Some(&upgraded_key_range));
+        assert_eq!(sst.key_encoding_version, KeyEncodingVersion::V1.to_u32());
+
+        // The SSTs already marked are kept as is.
+        upgrade_legacy_key_range(&mut sst);
+        assert_eq!(sst.key_range.as_ref(), Some(&upgraded_key_range));
+        let mut latest_sst = SstableInfo {
+            id: 2,
+            key_range: Some(upgraded_key_range.clone()),
+            key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
+            ..Default::default()
+        };
+        upgrade_legacy_key_range(&mut latest_sst);
+        assert_eq!(latest_sst.key_range, Some(upgraded_key_range));
+    }
 }
@@ -19,13 +19,12 @@ use std::time::Duration;
 
 use parking_lot::RwLock;
 use risingwave_common::catalog::ColumnDesc;
-use risingwave_common::types::VIRTUAL_NODE_SIZE;
 use risingwave_common::util::ordered::OrderedRowSerde;
 use risingwave_common::util::sort_util::OrderType;
 use risingwave_pb::catalog::Table;
 use tokio::sync::Notify;
 
-use crate::key::{get_table_id, TABLE_PREFIX_LEN};
+use crate::key::{get_table_id, split_table_key, TABLE_KEY_PREFIX_LEN};
 
 const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -144,12 +143,9 @@ pub struct SchemaFilterKeyExtractor {
 
 impl FilterKeyExtractor for SchemaFilterKeyExtractor {
     fn extract<'a>(&self, full_key: &'a [u8]) -> &'a [u8] {
-        if full_key.len() < TABLE_PREFIX_LEN + VIRTUAL_NODE_SIZE {
+        let Some((_table_id, _vnode, pk)) = split_table_key(full_key) else {
             return full_key;
-        }
-
-        let (_table_prefix, key) = full_key.split_at(TABLE_PREFIX_LEN);
-        let (_vnode_prefix, pk) = key.split_at(VIRTUAL_NODE_SIZE);
+        };
 
         // if the key with table_id deserializer fail from schema, that should panic here for early
         // detection
@@ -158,7 +154,7 @@ impl FilterKeyExtractor for SchemaFilterKeyExtractor {
             .deserialize_prefix_len_with_column_indices(pk, 0..self.read_pattern_prefix_column)
             .unwrap();
 
-        let prefix_len = TABLE_PREFIX_LEN + VIRTUAL_NODE_SIZE + pk_prefix_len;
+        let prefix_len = TABLE_KEY_PREFIX_LEN + pk_prefix_len;
         &full_key[0..prefix_len]
     }
 }
@@ -224,7 +220,7 @@ impl Debug for MultiFilterKeyExtractor {
 
 impl FilterKeyExtractor for MultiFilterKeyExtractor {
     fn extract<'a>(&self, full_key: &'a [u8]) -> &'a [u8] {
-        if full_key.len() < TABLE_PREFIX_LEN + VIRTUAL_NODE_SIZE {
+        if full_key.len() < TABLE_KEY_PREFIX_LEN {
             return full_key;
         }
 
@@ -375,7 +371,7 @@ mod tests {
         FilterKeyExtractorImpl, FilterKeyExtractorManager, FullKeyFilterKeyExtractor,
         MultiFilterKeyExtractor,
     };
-    use crate::key::TABLE_PREFIX_LEN;
+    use crate::key::{encode_vnode, TABLE_PREFIX_LEN};
 
     #[test]
     fn test_default_filter_key_extractor() {
@@ -476,6 +472,10 @@ mod tests {
             vnode_col_idx: None,
             value_indices: vec![0],
             definition: "".into(),
+            statistics: None,
+            watermark_descs: vec![],
+            subscription_progress: None,
+            key_encoding_version: 0,
         }
     }
 
@@ -500,7 +500,7 @@ mod tests {
             buf.to_vec()
         };
 
-        let vnode_prefix = "v".as_bytes();
+        let vnode_prefix = &encode_vnode(1)[..];
         assert_eq!(VIRTUAL_NODE_SIZE, vnode_prefix.len());
 
         let full_key = [&table_prefix, vnode_prefix, &row_bytes].concat();
@@ -538,7 +538,7 @@ mod tests {
                 buf.to_vec()
             };
 
-            let vnode_prefix = "v".as_bytes();
+            let vnode_prefix = &encode_vnode(1)[..];
             assert_eq!(VIRTUAL_NODE_SIZE, vnode_prefix.len());
 
             let full_key = [&table_prefix, vnode_prefix, &row_bytes].concat();
@@ -581,7 +581,7 @@ mod tests {
                 buf.to_vec()
             };
 
-            let vnode_prefix = "v".as_bytes();
+            let vnode_prefix = &encode_vnode(1)[..];
             assert_eq!(VIRTUAL_NODE_SIZE, vnode_prefix.len());
 
             let full_key = [&table_prefix, vnode_prefix, &row_bytes].concat();
@@ -616,7 +616,7 @@ mod tests {
                 buf.to_vec()
             };
 
-            let vnode_prefix = "v".as_bytes();
+            let vnode_prefix = &encode_vnode(1)[..];
             assert_eq!(VIRTUAL_NODE_SIZE, vnode_prefix.len());
 
             let row_bytes = "full_key".as_bytes();
@@ -18,12 +18,71 @@ use std::ops::{Bound, RangeBounds};
 use std::{ptr, u64};
 
 use bytes::{Buf, BufMut, BytesMut};
+use risingwave_common::types::{VirtualNode, VIRTUAL_NODE_SIZE};
 
 use super::version_cmp::VersionedComparator;
 use crate::HummockEpoch;
 
 pub const EPOCH_LEN: usize = std::mem::size_of::<HummockEpoch>();
 pub const TABLE_PREFIX_LEN: usize = std::mem::size_of::<u32>();
+/// The length of `table_id | vnode` at the beginning of a state table key in the latest encoding.
+/// See [`split_table_key`].
+pub const TABLE_KEY_PREFIX_LEN: usize = TABLE_PREFIX_LEN + VIRTUAL_NODE_SIZE;
+
+/// The encoding of the keys of the state tables.
+///
+/// The keys of an SST are all in the same encoding, which is decided by the format version of its
+/// meta. The keys of the legacy SSTs are upgraded to the latest encoding when they are read, so
+/// the readers, the iterators and the key ranges only see keys in the latest encoding, and never
+/// compare keys of different encodings. The compaction rewrites the legacy SSTs in the latest
+/// encoding, after which the encoding version of the tables in the catalog is flipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyEncodingVersion {
+    /// `table_id (4B) | vnode (1B) | key`.
+    V1 = 1,
+    /// `table_id (4B) | vnode (2B) | key`.
+    V2 = 2,
+}
+
+impl KeyEncodingVersion {
+    pub const LATEST: Self = Self::V2;
+
+    /// Decodes the encoding version kept in the catalog or in an `SstableInfo`, where 0 means the
+    /// field is written before the versioning, whose keys are in [`KeyEncodingVersion::V1`].
+    /// Returns `None` for an unknown version, e.g. one written by a newer release.
+    pub fn from_u32(version: u32) -> Option<Self> {
+        match version {
+            0 | 1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Encodes the vnode of a state table key in big-endian.
+pub fn encode_vnode(vnode: VirtualNode) -> [u8; VIRTUAL_NODE_SIZE] {
+    (vnode as u16).to_be_bytes()
+}
+
+/// Decodes the vnode at the beginning of `buf`, which is encoded by [`encode_vnode`].
+///
+/// Panics if the vnode is out of range, so it's only for keys known to be written by a state
+/// table. Use [`try_decode_vnode`] for arbitrary key bytes.
+pub fn decode_vnode(buf: &[u8]) -> VirtualNode {
+    let vnode = u16::from_be_bytes(buf[..VIRTUAL_NODE_SIZE].try_into().unwrap());
+    VirtualNode::try_from(vnode).unwrap_or_else(|_| panic!("invalid vnode {}", vnode))
+}
+
+/// Decodes the vnode at the beginning of `buf` like [`decode_vnode`]. Returns `None` if `buf` is
+/// too short or the vnode is out of range.
+pub fn try_decode_vnode(buf: &[u8]) -> Option<VirtualNode> {
+    let vnode = u16::from_be_bytes(buf.get(..VIRTUAL_NODE_SIZE)?.try_into().unwrap());
+    VirtualNode::try_from(vnode).ok()
+}
 
 /// Converts user key to full key by appending `epoch` to the user key.
 pub fn key_with_epoch(mut user_key: Vec<u8>, epoch: HummockEpoch) -> Vec<u8> {
@@ -328,6 +387,84 @@ pub fn table_prefix(table_id: u32) -> Vec<u8> {
     buf.to_vec()
 }
 
+/// Splits a state table key `table_id | vnode | key` in the latest encoding into the table id, the
+/// vnode and the rest, where the table id and the vnode are in big-endian. Returns `None` if the
+/// key is too short or its vnode is out of range.
+///
+/// All the state tables write keys in this encoding, through the [`table_prefix`] of the keyspace
+/// and the vnode prefix of the serialized pk, so that the keys of a table, and the keys of a vnode
+/// in a table, are contiguous in the storage.
+pub fn split_table_key(table_key: &[u8]) -> Option<(u32, VirtualNode, &[u8])> {
+    if table_key.len() < TABLE_KEY_PREFIX_LEN {
+        return None;
+    }
+    let (mut prefix, key) = table_key.split_at(TABLE_KEY_PREFIX_LEN);
+    let table_id = prefix.get_u32();
+    Some((table_id, try_decode_vnode(prefix)?, key))
+}
+
+/// Upgrades a user key in [`KeyEncodingVersion::V1`] to [`KeyEncodingVersion::V2`], by widening
+/// its 1-byte vnode to 2 bytes. Keys too short to have a vnode, like the table prefix or the empty
+/// bound of a key range, are kept as is.
+///
+/// The upgrade preserves the order of the keys, so an SST or a block stays sorted after its keys
+/// are upgraded.
+pub fn upgrade_user_key(user_key: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(user_key.len() + 1);
+    upgrade_user_key_into(user_key, &mut buf);
+    buf
+}
+
+fn upgrade_user_key_into(user_key: &[u8], buf: &mut Vec<u8>) {
+    if user_key.len() <= TABLE_PREFIX_LEN {
+        buf.extend_from_slice(user_key);
+        return;
+    }
+    let (table_prefix, vnode_and_key) = user_key.split_at(TABLE_PREFIX_LEN);
+    buf.extend_from_slice(table_prefix);
+    buf.extend_from_slice(&encode_vnode(vnode_and_key[0]));
+    buf.extend_from_slice(&vnode_and_key[1..]);
+}
+
+/// Upgrades a full key in [`KeyEncodingVersion::V1`] to [`KeyEncodingVersion::V2`] into `buf`,
+/// keeping the epoch. See [`upgrade_user_key`].
+pub fn upgrade_full_key_into(full_key: &[u8], buf: &mut Vec<u8>) {
+    buf.clear();
+    if full_key.len() < EPOCH_LEN {
+        buf.extend_from_slice(full_key);
+        return;
+    }
+    let (user_key, epoch) = split_key_epoch(full_key);
+    upgrade_user_key_into(user_key, buf);
+    buf.extend_from_slice(epoch);
+}
+
+/// Upgrades a full key in [`KeyEncodingVersion::V1`] to [`KeyEncodingVersion::V2`], keeping the
+/// epoch. See [`upgrade_user_key`].
+pub fn upgrade_full_key(full_key: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(full_key.len() + 1);
+    upgrade_full_key_into(full_key, &mut buf);
+    buf
+}
+
+/// The inverse of [`upgrade_user_key`]. Returns `None` if the key is not an upgraded one, which
+/// means no key in [`KeyEncodingVersion::V1`] equals it.
+pub fn downgrade_user_key(user_key: &[u8]) -> Option<Vec<u8>> {
+    if user_key.len() <= TABLE_PREFIX_LEN {
+        return Some(user_key.to_vec());
+    }
+    if user_key.len() < TABLE_KEY_PREFIX_LEN || user_key[TABLE_PREFIX_LEN] != 0 {
+        return None;
+    }
+    Some(
+        [
+            &user_key[..TABLE_PREFIX_LEN],
+            &user_key[TABLE_PREFIX_LEN + 1..],
+        ]
+        .concat(),
+    )
+}
+
 /// [`FullKey`] can be created on either a `Vec<u8>` or a `&[u8]`.
 ///
 /// Its format is (`user_key`, `epoch`).
@@ -382,8 +519,162 @@ impl<T: Eq + AsRef<[u8]>> PartialOrd for FullKey<T> {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
     use super::*;
 
+    fn table_key(table_id: u32, vnode: VirtualNode, key: &[u8]) -> Vec<u8> {
+        [&table_prefix(table_id)[..], &encode_vnode(vnode), key].concat()
+    }
+
+    fn legacy_table_key(table_id: u32, vnode: VirtualNode, key: &[u8]) -> Vec<u8> {
+        [&table_prefix(table_id)[..], &[vnode], key].concat()
+    }
+
+    #[test]
+    fn test_table_key_round_trip() {
+        let table_ids = [0, 1, 233, u32::MAX - 1, u32::MAX];
+        let keys: [&[u8]; 4] = [b"", b"\x00", b"key", b"\xff\xff"];
+        for table_id in table_ids {
+            for vnode in 0..=VirtualNode::MAX {
+                for key in keys {
+                    let encoded = table_key(table_id, vnode, key);
+                    assert_eq!(encoded.len(), TABLE_KEY_PREFIX_LEN + key.len());
+                    assert_eq!(get_table_id(&encoded), table_id);
+                    assert_eq!(split_table_key(&encoded), Some((table_id, vnode, key)));
+                }
+            }
+        }
+        assert_eq!(split_table_key(&table_prefix(233)), None);
+    }
+
+    #[test]
+    fn test_out_of_range_vnode() {
+        let out_of_range = 0x0100u16.to_be_bytes();
+        assert_eq!(try_decode_vnode(&out_of_range), None);
+        assert_eq!(try_decode_vnode(&out_of_range[..1]), None);
+        assert_eq!(
+            try_decode_vnode(&encode_vnode(VirtualNode::MAX)),
+            Some(VirtualNode::MAX)
+        );
+
+        let key = [&table_prefix(233)[..], &out_of_range, b"key"].concat();
+        assert_eq!(split_table_key(&key), None);
+    }
+
+    #[test]
+    fn test_vnode_round_trip() {
+        for vnode in 0..=VirtualNode::MAX {
+            let encoded = encode_vnode(vnode);
+            assert_eq!(encoded.len(), VIRTUAL_NODE_SIZE);
+            assert_eq!(decode_vnode(&encoded), vnode);
+        }
+    }
+
+    #[test]
+    fn test_key_encoding_version() {
+        assert_eq!(
+            KeyEncodingVersion::from_u32(0),
+            Some(KeyEncodingVersion::V1)
+        );
+        for version in [KeyEncodingVersion::V1, KeyEncodingVersion::V2] {
+            assert_eq!(
+                KeyEncodingVersion::from_u32(version.to_u32()),
+                Some(version)
+            );
+        }
+        assert_eq!(KeyEncodingVersion::from_u32(3), None);
+        assert_eq!(KeyEncodingVersion::LATEST, KeyEncodingVersion::V2);
+    }
+
+    #[test]
+    fn test_upgrade_key_round_trip() {
+        let table_ids = [0, 1, 233, u32::MAX - 1, u32::MAX];
+        let keys: [&[u8]; 4] = [b"", b"\x00", b"key", b"\xff\xff"];
+        for table_id in table_ids {
+            for vnode in 0..=VirtualNode::MAX {
+                for key in keys {
+                    let legacy = legacy_table_key(table_id, vnode, key);
+                    let upgraded = upgrade_user_key(&legacy);
+                    assert_eq!(upgraded, table_key(table_id, vnode, key));
+                    assert_eq!(downgrade_user_key(&upgraded), Some(legacy.clone()));
+
+                    let legacy_full_key = key_with_epoch(legacy, 233);
+                    let upgraded_full_key = upgrade_full_key(&legacy_full_key);
+                    assert_eq!(get_epoch(&upgraded_full_key), 233);
+                    assert_eq!(user_key(&upgraded_full_key), upgraded);
+                }
+            }
+        }
+
+        // The keys without a vnode are kept as is.
+        for key in [&b""[..], b"\x00", &table_prefix(233)[..]] {
+            assert_eq!(upgrade_user_key(key), key);
+            assert_eq!(downgrade_user_key(key).as_deref(), Some(key));
+        }
+        assert_eq!(upgrade_full_key(b"short"), b"short");
+        assert_eq!(
+            upgrade_full_key(&key_with_epoch(table_prefix(233), 1)),
+            key_with_epoch(table_prefix(233), 1)
+        );
+
+        // The keys not upgraded from any legacy key.
+        assert_eq!(
+            downgrade_user_key(&[&table_prefix(233)[..], b"\x00"].concat()),
+            None
+        );
+        assert_eq!(
+            downgrade_user_key(&[&table_prefix(233)[..], b"\x01\x00"].concat()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_upgrade_key_order() {
+        // The upgrade preserves the order of the full keys, including the ones without a vnode.
+        let mut legacy = vec![];
+        for table_id in [0, 1, 255, 256, u32::MAX] {
+            legacy.push(key_with_epoch(table_prefix(table_id), 1));
+            for vnode in [0, 1, 127, 128, VirtualNode::MAX] {
+                for key in [&b""[..], b"\x00", b"\x00\x00", b"\x01", b"\xff"] {
+                    for epoch in [0, 1, u64::MAX] {
+                        legacy.push(key_with_epoch(
+                            legacy_table_key(table_id, vnode, key),
+                            epoch,
+                        ));
+                    }
+                }
+            }
+        }
+        legacy.sort_by(|a, b| VersionedComparator::compare_key(a, b));
+        let upgraded = legacy.iter().map(|key| upgrade_full_key(key)).collect_vec();
+        for (a, b) in upgraded.iter().tuple_windows() {
+            assert_eq!(VersionedComparator::compare_key(a, b), Ordering::Less);
+        }
+    }
+
+    #[test]
+    fn test_table_key_order() {
+        // The encoded keys are ordered by table id, then vnode, then key.
+        let mut decoded = vec![];
+        for table_id in [0, 1, 255, 256, u32::MAX] {
+            for vnode in [0, 1, 127, 128, VirtualNode::MAX] {
+                for key in [&b""[..], b"\x00", b"\x00\x00", b"\x01", b"\xff"] {
+                    decoded.push((table_id, vnode, key));
+                }
+            }
+        }
+        let mut encoded = decoded
+            .iter()
+            .map(|(table_id, vnode, key)| table_key(*table_id, *vnode, key))
+            .collect::<Vec<_>>();
+        decoded.sort();
+        encoded.sort();
+        for (encoded, decoded) in encoded.iter().zip_eq(decoded) {
+            assert_eq!(split_table_key(encoded), Some(decoded));
+        }
+    }
+
     #[test]
     fn test_key_epoch() {
         let full_key = key_with_epoch(b"aaa".to_vec(), 233);
@@ -26,7 +26,7 @@ extern crate num_derive;
 use std::cmp::Ordering;
 use std::ops::Deref;
 
-use risingwave_pb::hummock::SstableInfo;
+use risingwave_pb::hummock::{SstableInfo, TableStats};
 pub use version_cmp::*;
 
 use crate::key::user_key;
@@ -112,6 +112,12 @@ impl SstIdRange {
     }
 }
 
+/// Adds the table write stats of `other` into `stats`.
+pub fn add_table_stats(stats: &mut TableStats, other: &TableStats) {
+    stats.total_bytes += other.total_bytes;
+    stats.total_key_count += other.total_key_count;
+}
+
 pub fn can_concat(ssts: &[impl Deref<Target = SstableInfo>]) -> bool {
     let len = ssts.len();
     for i in 0..len - 1 {
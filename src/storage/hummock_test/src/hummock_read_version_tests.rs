@@ -12,20 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::ops::Bound;
 
+use bytes::Bytes;
 use itertools::Itertools;
 use risingwave_common::catalog::TableId;
-use risingwave_hummock_sdk::key::key_with_epoch;
+use risingwave_hummock_sdk::key::{key_with_epoch, KeyEncodingVersion};
+use risingwave_hummock_sdk::HummockEpoch;
 use risingwave_meta::hummock::test_utils::setup_compute_env;
 use risingwave_pb::hummock::{KeyRange, SstableInfo};
+use risingwave_storage::hummock::event_handler::hummock_event_handler::imbalanced_epoch;
 use risingwave_storage::hummock::iterator::test_utils::iterator_test_key_of_epoch;
+use risingwave_storage::hummock::local_version::pinned_version::PinnedVersion;
 use risingwave_storage::hummock::shared_buffer::shared_buffer_batch::SharedBufferBatch;
 use risingwave_storage::hummock::store::memtable::ImmutableMemtable;
 use risingwave_storage::hummock::store::version::{
-    HummockReadVersion, StagingData, StagingSstableInfo, VersionUpdate,
+    HummockReadVersion, StagingData, StagingSstableInfo, StagingVersion, VersionUpdate,
 };
 use risingwave_storage::hummock::test_utils::gen_dummy_batch;
+use risingwave_storage::hummock::value::HummockValue;
+use risingwave_storage::storage_value::StorageValue;
+use tokio::sync::mpsc::unbounded_channel;
 
 use crate::test_utils::prepare_first_valid_version;
 
@@ -52,7 +60,9 @@ async fn test_read_version_basic() {
         )
         .await;
 
-        read_version.update(VersionUpdate::Staging(StagingData::ImmMem(imm)));
+        read_version
+            .update(VersionUpdate::Staging(StagingData::ImmMem(imm)))
+            .unwrap();
 
         let key = iterator_test_key_of_epoch(0, epoch);
         let key_range = (Bound::Included(key.to_vec()), Bound::Included(key.to_vec()));
@@ -85,7 +95,9 @@ async fn test_read_version_basic() {
             )
             .await;
 
-            read_version.update(VersionUpdate::Staging(StagingData::ImmMem(imm)));
+            read_version
+                .update(VersionUpdate::Staging(StagingData::ImmMem(imm)))
+                .unwrap();
         }
 
         let key = iterator_test_key_of_epoch(0, epoch);
@@ -141,6 +153,8 @@ async fn test_read_version_basic() {
                     stale_key_count: 1,
                     total_key_count: 1,
                     divide_version: 0,
+                    table_stats: Default::default(),
+                    key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
                 },
                 SstableInfo {
                     id: 2,
@@ -154,6 +168,8 @@ async fn test_read_version_basic() {
                     stale_key_count: 1,
                     total_key_count: 1,
                     divide_version: 0,
+                    table_stats: Default::default(),
+                    key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
                 },
             ],
             epoch_id_vec_for_clear,
@@ -161,7 +177,9 @@ async fn test_read_version_basic() {
         );
 
         {
-            read_version.update(VersionUpdate::Staging(StagingData::Sst(dummy_sst)));
+            read_version
+                .update(VersionUpdate::Staging(StagingData::Sst(dummy_sst)))
+                .unwrap();
         }
     }
 
@@ -230,3 +248,563 @@ async fn test_read_version_basic() {
         assert_eq!(2, staging_ssts[0].id);
     }
 }
+
+#[tokio::test]
+async fn test_epoch_histogram() {
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+
+    let (pinned_version, _, _) =
+        prepare_first_valid_version(env, hummock_manager_ref, worker_node).await;
+
+    let mut read_version = HummockReadVersion::new(pinned_version);
+    assert!(read_version.staging().epoch_histogram().is_empty());
+
+    async fn add_imm(read_version: &mut HummockReadVersion, epoch: u64) {
+        let imm = SharedBufferBatch::build_shared_buffer_batch(
+            epoch,
+            gen_dummy_batch(epoch),
+            TableId::default(),
+            None,
+        )
+        .await;
+        read_version
+            .update(VersionUpdate::Staging(StagingData::ImmMem(imm)))
+            .unwrap();
+    }
+
+    // All the writes are at a single epoch.
+    for _ in 0..3 {
+        add_imm(&mut read_version, 1).await;
+    }
+    {
+        let histogram = read_version.staging().epoch_histogram();
+        assert_eq!(histogram, BTreeMap::from([(1, 3)]));
+        assert_eq!(imbalanced_epoch(&histogram), None);
+    }
+
+    // Bursty writes across epochs.
+    for epoch in [2, 3, 3, 3, 3, 4] {
+        add_imm(&mut read_version, epoch).await;
+    }
+    let staging = read_version.staging();
+    let histogram = staging.epoch_histogram();
+    assert_eq!(histogram, BTreeMap::from([(1, 3), (2, 1), (3, 4), (4, 1)]));
+    assert_eq!(histogram.values().sum::<usize>(), staging.imm.len());
+    assert_eq!(
+        histogram.keys().copied().collect::<HashSet<_>>(),
+        staging.imm.iter().map(|imm| imm.epoch()).collect()
+    );
+    // Epoch 3 holds 4 of the 9 imms, which is not the majority.
+    assert_eq!(imbalanced_epoch(&histogram), None);
+    assert_eq!(
+        imbalanced_epoch(&BTreeMap::from([(1, 3), (2, 1), (3, 5)])),
+        Some(3)
+    );
+}
+
+#[tokio::test]
+async fn test_read_epoch_visibility() {
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+
+    let (pinned_version, _, _) =
+        prepare_first_valid_version(env, hummock_manager_ref, worker_node).await;
+
+    let pin_version = |max_committed_epoch: HummockEpoch, id_offset: u64| {
+        let mut version = pinned_version.version();
+        version.id += id_offset;
+        version.max_committed_epoch = max_committed_epoch;
+        PinnedVersion::new(version, unbounded_channel().0)
+    };
+
+    let mut read_version = HummockReadVersion::new(pin_version(10, 0));
+    assert_eq!(read_version.read_epoch(), 10);
+    for epoch in [0, 9, 10] {
+        assert!(read_version.is_epoch_visible(epoch));
+    }
+    for epoch in [11, HummockEpoch::MAX] {
+        assert!(!read_version.is_epoch_visible(epoch));
+    }
+
+    // Staging data do not advance the read epoch.
+    let imm = SharedBufferBatch::build_shared_buffer_batch(
+        11,
+        gen_dummy_batch(11),
+        TableId::default(),
+        None,
+    )
+    .await;
+    read_version
+        .update(VersionUpdate::Staging(StagingData::ImmMem(imm)))
+        .unwrap();
+    assert_eq!(read_version.read_epoch(), 10);
+    assert!(!read_version.is_epoch_visible(11));
+
+    // The horizon moves forward along with the committed version.
+    read_version
+        .update(VersionUpdate::CommittedSnapshot(pin_version(11, 1)))
+        .unwrap();
+    assert_eq!(read_version.read_epoch(), 11);
+    assert!(read_version.is_epoch_visible(11));
+    assert!(!read_version.is_epoch_visible(12));
+    assert!(read_version.staging().imm.is_empty());
+
+    // The writes of the committed epochs are fenced afterwards.
+    assert_eq!(read_version.staging().epoch_fence(), 12);
+    let imm = SharedBufferBatch::build_shared_buffer_batch(
+        11,
+        gen_dummy_batch(11),
+        TableId::default(),
+        None,
+    )
+    .await;
+    assert!(read_version
+        .update(VersionUpdate::Staging(StagingData::ImmMem(imm)))
+        .is_err());
+    assert!(read_version.staging().imm.is_empty());
+
+    // The very first epoch is only visible when nothing has been committed.
+    let read_version = HummockReadVersion::new(pin_version(0, 0));
+    assert_eq!(read_version.read_epoch(), 0);
+    assert!(read_version.is_epoch_visible(0));
+    assert!(!read_version.is_epoch_visible(1));
+}
+
+#[tokio::test]
+async fn test_epoch_fence() {
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+
+    let (pinned_version, _, _) =
+        prepare_first_valid_version(env, hummock_manager_ref, worker_node).await;
+
+    let pin_version = |max_committed_epoch: HummockEpoch, id_offset: u64| {
+        let mut version = pinned_version.version();
+        version.id += id_offset;
+        version.max_committed_epoch = max_committed_epoch;
+        PinnedVersion::new(version, unbounded_channel().0)
+    };
+    let build_imm = |epoch: HummockEpoch| {
+        SharedBufferBatch::build_shared_buffer_batch(
+            epoch,
+            gen_dummy_batch(epoch),
+            TableId::default(),
+            None,
+        )
+    };
+
+    // The fence never moves backwards.
+    let mut staging = StagingVersion {
+        imm: VecDeque::new(),
+        sst: VecDeque::new(),
+        epoch_fence: 0,
+    };
+    staging.set_epoch_fence(3);
+    staging.set_epoch_fence(2);
+    assert_eq!(staging.epoch_fence(), 3);
+
+    // Without a fence, writes of any epoch are accepted.
+    let mut read_version = HummockReadVersion::new(pin_version(0, 0));
+    assert_eq!(read_version.staging().epoch_fence(), 0);
+    read_version
+        .update(VersionUpdate::Staging(StagingData::ImmMem(
+            build_imm(1).await,
+        )))
+        .unwrap();
+
+    // Committing epoch 2 fences the writes below epoch 3.
+    read_version
+        .update(VersionUpdate::CommittedSnapshot(pin_version(2, 1)))
+        .unwrap();
+    assert_eq!(read_version.staging().epoch_fence(), 3);
+    assert!(read_version.staging().imm.is_empty());
+
+    // Writes below the fence are rejected without being applied.
+    let err = read_version
+        .update(VersionUpdate::Staging(StagingData::ImmMem(
+            build_imm(2).await,
+        )))
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Write epoch 2 is below the epoch fence 3"),
+        "{}",
+        err
+    );
+    assert!(read_version.staging().imm.is_empty());
+
+    // Writes of the fence epoch and above are accepted.
+    for epoch in [3, 4] {
+        read_version
+            .update(VersionUpdate::Staging(StagingData::ImmMem(
+                build_imm(epoch).await,
+            )))
+            .unwrap();
+    }
+    assert_eq!(
+        read_version
+            .staging()
+            .imm
+            .iter()
+            .map(|imm| imm.epoch())
+            .collect_vec(),
+        vec![4, 3]
+    );
+}
+
+#[tokio::test]
+async fn test_build_delete_range_tombstones() {
+    async fn build_imm(epoch: HummockEpoch, keys: &[&'static [u8]]) -> ImmutableMemtable {
+        let kv_pairs = keys
+            .iter()
+            .map(|key| {
+                (
+                    Bytes::from_static(key),
+                    StorageValue::new_put(format!("value{}", epoch)),
+                )
+            })
+            .collect();
+        SharedBufferBatch::build_shared_buffer_batch(epoch, kv_pairs, TableId::default(), None)
+            .await
+    }
+
+    // newer data comes first
+    let mut staging = StagingVersion {
+        imm: VecDeque::from([
+            build_imm(3, &[b"aa", b"cc"]).await,
+            build_imm(2, &[b"bb", b"dd"]).await,
+            build_imm(1, &[b"aa", b"bb", b"cc"]).await,
+        ]),
+        sst: VecDeque::new(),
+        epoch_fence: 0,
+    };
+
+    // Delete [bb, dd) at epoch 3, which doesn't cover the writes of epoch 3.
+    let tombstones = staging.build_delete_range_tombstones(
+        &(
+            Bound::Included(Bytes::from_static(b"bb")),
+            Bound::Excluded(Bytes::from_static(b"dd")),
+        ),
+        3,
+    );
+    // cc is written at epoch 3, so only bb is deleted.
+    assert_eq!(tombstones.len(), 1);
+    assert_eq!(tombstones[0].get_payload().len(), 1);
+    // Added the same way as the read version adds an imm.
+    staging.imm.push_front(tombstones[0].clone());
+    // The tombstone imm is the newest one.
+    assert_eq!(staging.imm[0].batch_id(), tombstones[0].batch_id());
+    let batch_ids = staging.imm.iter().map(|imm| imm.batch_id()).collect_vec();
+    assert_eq!(
+        batch_ids,
+        batch_ids.iter().cloned().sorted().rev().collect_vec()
+    );
+    assert_eq!(
+        staging.imm.iter().map(|imm| imm.epoch()).collect_vec(),
+        vec![3, 3, 2, 1]
+    );
+
+    // Returns the value of the first imm that contains `key`, i.e. the newest one.
+    let read = |staging: &StagingVersion, key: &'static [u8], epoch: HummockEpoch| {
+        let key_range = (Bound::Included(key.to_vec()), Bound::Included(key.to_vec()));
+        let (mut imms, _) = staging.prune_overlap(epoch, TableId::default(), &key_range);
+        imms.find_map(|imm| imm.get(key))
+    };
+
+    // Tombstoned keys.
+    assert_eq!(read(&staging, b"bb", 3), Some(HummockValue::Delete));
+    assert_eq!(
+        read(&staging, b"bb", 2),
+        Some(HummockValue::Put("value2".into()))
+    );
+    assert_eq!(
+        read(&staging, b"cc", 2),
+        Some(HummockValue::Put("value1".into()))
+    );
+    // Written at the tombstone epoch.
+    assert_eq!(
+        read(&staging, b"cc", 3),
+        Some(HummockValue::Put("value3".into()))
+    );
+    // Out of the range.
+    assert_eq!(
+        read(&staging, b"aa", 3),
+        Some(HummockValue::Put("value3".into()))
+    );
+    assert_eq!(
+        read(&staging, b"dd", 3),
+        Some(HummockValue::Put("value2".into()))
+    );
+
+    // Nothing to delete below epoch 1.
+    let tombstones =
+        staging.build_delete_range_tombstones(&(Bound::Unbounded, Bound::Unbounded), 1);
+    assert!(tombstones.is_empty());
+}
+
+#[tokio::test]
+async fn test_staging_data_diff() {
+    async fn build_imm(epoch: HummockEpoch) -> ImmutableMemtable {
+        SharedBufferBatch::build_shared_buffer_batch(
+            epoch,
+            gen_dummy_batch(epoch),
+            TableId::default(),
+            None,
+        )
+        .await
+    }
+
+    fn build_sst(id: u64, epoch: HummockEpoch) -> StagingSstableInfo {
+        StagingSstableInfo::new(
+            vec![gen_staging_sst_info(id, b"a", b"b", epoch)],
+            vec![epoch],
+            vec![],
+        )
+    }
+
+    fn staging_of(imms: &[&ImmutableMemtable]) -> StagingVersion {
+        StagingVersion {
+            imm: imms.iter().map(|&imm| imm.clone()).collect(),
+            sst: VecDeque::new(),
+            epoch_fence: 0,
+        }
+    }
+
+    fn with_ssts(mut staging: StagingVersion, ssts: &[&StagingSstableInfo]) -> StagingVersion {
+        staging.sst = ssts.iter().map(|&sst| sst.clone()).collect();
+        staging
+    }
+
+    fn batch_ids(staging: &StagingVersion) -> Vec<u64> {
+        staging.imm.iter().map(|imm| imm.batch_id()).collect()
+    }
+
+    fn sst_ids(staging: &StagingVersion) -> Vec<Vec<u64>> {
+        staging.sst.iter().map(|sst| sst.sst_ids()).collect()
+    }
+
+    // Checks the diff from `baseline` to `staging`, and that applying it catches up.
+    fn check_diff(
+        staging: &StagingVersion,
+        mut baseline: StagingVersion,
+        added: &[&ImmutableMemtable],
+        removed: &[&ImmutableMemtable],
+    ) {
+        let diff = staging.diff(&baseline);
+        assert_eq!(
+            diff.added_imms
+                .iter()
+                .map(|imm| imm.batch_id())
+                .collect_vec(),
+            added.iter().map(|imm| imm.batch_id()).collect_vec()
+        );
+        assert_eq!(
+            diff.removed_batch_ids,
+            removed.iter().map(|imm| imm.batch_id()).collect_vec()
+        );
+        baseline.apply_diff(diff);
+        assert_eq!(batch_ids(&baseline), batch_ids(staging));
+        assert_eq!(sst_ids(&baseline), sst_ids(staging));
+    }
+
+    let imm1 = build_imm(1).await;
+    let imm2 = build_imm(2).await;
+    let imm3 = build_imm(3).await;
+
+    // newer data comes first
+    let staging = staging_of(&[&imm2, &imm1]);
+
+    // Neither added nor removed.
+    check_diff(&staging, staging_of(&[&imm2, &imm1]), &[], &[]);
+    // Added only.
+    check_diff(
+        &staging_of(&[&imm3, &imm2, &imm1]),
+        staging_of(&[&imm2, &imm1]),
+        &[&imm3],
+        &[],
+    );
+    // Removed only, e.g. the oldest imm is flushed.
+    check_diff(
+        &staging_of(&[&imm2]),
+        staging_of(&[&imm2, &imm1]),
+        &[],
+        &[&imm1],
+    );
+    // Both added and removed.
+    check_diff(&staging_of(&[&imm3, &imm2]), staging, &[&imm3], &[&imm1]);
+    // An imm with a smaller batch id is added after a newer one, and placed after it.
+    check_diff(
+        &staging_of(&[&imm3, &imm2, &imm1]),
+        staging_of(&[&imm3, &imm1]),
+        &[&imm2],
+        &[],
+    );
+
+    // The imms 1 and 2 are uploaded as staging SSTs.
+    let sst1 = build_sst(1, 1);
+    let sst2 = build_sst(2, 2);
+    let staging = with_ssts(staging_of(&[&imm3]), &[&sst2, &sst1]);
+    let diff = staging.diff(&with_ssts(staging_of(&[&imm3, &imm2]), &[&sst1]));
+    assert_eq!(
+        diff.added_ssts
+            .iter()
+            .map(|sst| sst.sst_ids())
+            .collect_vec(),
+        vec![vec![2]]
+    );
+    assert!(diff.removed_sst_ids.is_empty());
+    assert_eq!(diff.removed_batch_ids, vec![imm2.batch_id()]);
+    let mut baseline = with_ssts(staging_of(&[&imm3, &imm2]), &[&sst1]);
+    baseline.apply_diff(diff);
+    assert_eq!(batch_ids(&baseline), batch_ids(&staging));
+    assert_eq!(sst_ids(&baseline), sst_ids(&staging));
+
+    // A staging SST of an older epoch is added after a newer one, and the oldest is removed.
+    let sst3 = build_sst(3, 3);
+    let mut baseline = with_ssts(staging_of(&[]), &[&sst3, &sst1]);
+    let staging = with_ssts(staging_of(&[]), &[&sst3, &sst2]);
+    let diff = staging.diff(&baseline);
+    assert_eq!(diff.removed_sst_ids, vec![vec![1]]);
+    baseline.apply_diff(diff);
+    assert_eq!(sst_ids(&baseline), vec![vec![3], vec![2]]);
+}
+
+fn gen_staging_sst_info(id: u64, left: &[u8], right: &[u8], epoch: HummockEpoch) -> SstableInfo {
+    SstableInfo {
+        id,
+        key_range: Some(KeyRange {
+            left: key_with_epoch(left.to_vec(), epoch),
+            right: key_with_epoch(right.to_vec(), epoch),
+        }),
+        file_size: 1,
+        table_ids: vec![0],
+        meta_offset: 1,
+        stale_key_count: 1,
+        total_key_count: 1,
+        divide_version: 0,
+        table_stats: Default::default(),
+        key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
+    }
+}
+
+#[tokio::test]
+async fn test_staging_data_serde() {
+    let imm = SharedBufferBatch::build_shared_buffer_batch(
+        1,
+        vec![
+            (Bytes::from_static(b"aa"), StorageValue::new_put("value1")),
+            (Bytes::from_static(b"bb"), StorageValue::new_delete()),
+        ],
+        TableId::default(),
+        None,
+    )
+    .await;
+    let bytes = StagingData::ImmMem(imm.clone()).serialize();
+    let StagingData::ImmMem(decoded) = StagingData::deserialize(&bytes).unwrap() else {
+        panic!("expect imm");
+    };
+    assert_eq!(decoded, imm);
+    assert_eq!(decoded.batch_id(), imm.batch_id());
+    assert_eq!(decoded.size(), imm.size());
+    assert_eq!(decoded.get(b"bb"), Some(HummockValue::Delete));
+
+    let staging_sst = StagingSstableInfo::new(
+        vec![gen_staging_sst_info(1, b"aa", b"bb", 1)],
+        vec![2, 1],
+        vec![imm.batch_id()],
+    );
+    let bytes = StagingData::Sst(staging_sst.clone()).serialize();
+    let StagingData::Sst(decoded) = StagingData::deserialize(&bytes).unwrap() else {
+        panic!("expect staging sst");
+    };
+    assert_eq!(decoded.sstable_infos(), staging_sst.sstable_infos());
+    assert_eq!(StagingData::Sst(decoded).serialize(), bytes);
+
+    // Truncated or corrupted data.
+    assert!(StagingData::deserialize(&Bytes::new()).is_err());
+    assert!(StagingData::deserialize(&bytes.slice(..bytes.len() - 1)).is_err());
+    assert!(StagingData::deserialize(&Bytes::from_static(&[2])).is_err());
+}
+
+#[tokio::test]
+async fn test_staging_data_serde_prune_overlap() {
+    // newer data comes first
+    let mut staging = StagingVersion {
+        imm: VecDeque::new(),
+        sst: VecDeque::new(),
+        epoch_fence: 0,
+    };
+    for i in 0..100 {
+        let epoch = (i / 10 + 1) as HummockEpoch;
+        let kv_pairs = (i..i + 5)
+            .map(|j| {
+                let value = if j % 7 == 0 {
+                    StorageValue::new_delete()
+                } else {
+                    StorageValue::new_put(format!("value{}", i))
+                };
+                (Bytes::from(format!("key{:03}", j)), value)
+            })
+            .collect();
+        let imm =
+            SharedBufferBatch::build_shared_buffer_batch(epoch, kv_pairs, TableId::default(), None)
+                .await;
+        staging.imm.push_front(imm);
+    }
+    staging.sst.push_front(StagingSstableInfo::new(
+        vec![
+            gen_staging_sst_info(1, b"key000", b"key030", 1),
+            gen_staging_sst_info(2, b"key040", b"key060", 1),
+        ],
+        vec![1],
+        vec![],
+    ));
+
+    let entries = staging
+        .sst
+        .iter()
+        .map(|staging_sst| StagingData::Sst(staging_sst.clone()))
+        .chain(
+            staging
+                .imm
+                .iter()
+                .map(|imm| StagingData::ImmMem(imm.clone())),
+        )
+        .map(|staging_data| staging_data.serialize())
+        .collect_vec();
+    let mut decoded = StagingVersion {
+        imm: VecDeque::new(),
+        sst: VecDeque::new(),
+        epoch_fence: 0,
+    };
+    for entry in &entries {
+        match StagingData::deserialize(entry).unwrap() {
+            StagingData::ImmMem(imm) => decoded.imm.push_back(imm),
+            StagingData::Sst(staging_sst) => decoded.sst.push_back(staging_sst),
+        }
+    }
+
+    let key_ranges = [
+        (Bound::Unbounded, Bound::Unbounded),
+        (
+            Bound::Included(b"key010".to_vec()),
+            Bound::Excluded(b"key020".to_vec()),
+        ),
+        (Bound::Included(b"key035".to_vec()), Bound::Unbounded),
+        (Bound::Unbounded, Bound::Included(b"key003".to_vec())),
+        (
+            Bound::Included(b"key200".to_vec()),
+            Bound::Included(b"key300".to_vec()),
+        ),
+    ];
+    for epoch in [0, 1, 5, 10, HummockEpoch::MAX] {
+        for key_range in &key_ranges {
+            let (imms, ssts) = staging.prune_overlap(epoch, TableId::default(), key_range);
+            let (decoded_imms, decoded_ssts) =
+                decoded.prune_overlap(epoch, TableId::default(), key_range);
+            assert!(imms.eq(decoded_imms));
+            assert!(ssts.eq(decoded_ssts));
+        }
+    }
+}
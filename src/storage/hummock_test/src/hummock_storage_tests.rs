@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ops::Bound::{Included, Unbounded};
+use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -90,7 +90,7 @@ pub async fn prepare_hummock_event_handler(
     (hummock_event_handler, event_tx)
 }
 
-async fn try_wait_epoch_for_test(
+pub(crate) async fn try_wait_epoch_for_test(
     wait_epoch: u64,
     version_update_notifier_tx: &tokio::sync::watch::Sender<HummockEpoch>,
     event_tx: &UnboundedSender<HummockEvent>,
@@ -101,7 +101,10 @@ async fn try_wait_epoch_for_test(
     assert_eq!(*version_update_notifier_tx.borrow(), wait_epoch);
 }
 
-async fn sync_epoch(event_tx: &UnboundedSender<HummockEvent>, epoch: HummockEpoch) -> SyncResult {
+pub(crate) async fn sync_epoch(
+    event_tx: &UnboundedSender<HummockEvent>,
+    epoch: HummockEpoch,
+) -> SyncResult {
     event_tx
         .send(HummockEvent::SealEpoch {
             epoch,
@@ -813,6 +816,238 @@ async fn test_delete_get() {
         .is_none());
 }
 
+#[tokio::test]
+async fn test_delete_range_flush() {
+    let sstable_store = mock_sstable_store();
+    let hummock_options = Arc::new(default_config_for_test());
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+    let hummock_meta_client = Arc::new(MockHummockMetaClient::new(
+        hummock_manager_ref.clone(),
+        worker_node.id,
+    ));
+
+    let (hummock_event_handler, event_tx) = prepare_hummock_event_handler(
+        hummock_options.clone(),
+        env,
+        hummock_manager_ref,
+        worker_node,
+        sstable_store.clone(),
+    )
+    .await;
+
+    let read_version = hummock_event_handler.read_version();
+    let version_update_notifier_tx = hummock_event_handler.version_update_notifier_tx();
+
+    tokio::spawn(hummock_event_handler.start_hummock_event_handler_worker());
+
+    let initial_epoch = read_version.read().committed().max_committed_epoch();
+
+    let hummock_storage = HummockStorage::for_test(
+        hummock_options,
+        sstable_store,
+        hummock_meta_client.clone(),
+        read_version,
+        event_tx.clone(),
+    )
+    .unwrap();
+
+    let read_options = || ReadOptions {
+        prefix_hint: None,
+        check_bloom_filter: true,
+        table_id: Default::default(),
+        retention_seconds: None,
+    };
+
+    let epoch1 = initial_epoch + 1;
+    let batch1 = vec![
+        (
+            prefixed_key(Bytes::from("aa")),
+            StorageValue::new_put("111"),
+        ),
+        (
+            prefixed_key(Bytes::from("bb")),
+            StorageValue::new_put("222"),
+        ),
+    ];
+    hummock_storage
+        .ingest_batch(
+            batch1,
+            WriteOptions {
+                epoch: epoch1,
+                table_id: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+    // Delete [bb, cc) at epoch2.
+    let epoch2 = initial_epoch + 2;
+    let deleted = hummock_storage
+        .delete_range(
+            (
+                Included(prefixed_key(Bytes::from("bb"))),
+                Excluded(prefixed_key(Bytes::from("cc"))),
+            ),
+            epoch2,
+        )
+        .unwrap();
+    assert_eq!(deleted, 1);
+    {
+        let read_version = hummock_storage.read_version();
+        assert_eq!(
+            vec![epoch2, epoch1],
+            read_version
+                .read()
+                .staging()
+                .imm
+                .iter()
+                .map(|imm| imm.epoch())
+                .collect::<Vec<_>>()
+        );
+    }
+    assert!(hummock_storage
+        .get(&prefixed_key("bb".as_bytes()), epoch2, read_options())
+        .await
+        .unwrap()
+        .is_none());
+
+    // The tombstones are flushed with the other imms and cleared from the staging data.
+    let ssts = sync_epoch(&event_tx, epoch2).await.uncommitted_ssts;
+    hummock_meta_client
+        .commit_epoch(epoch2, ssts)
+        .await
+        .unwrap();
+    try_wait_epoch_for_test(epoch2, &version_update_notifier_tx, &event_tx).await;
+    {
+        let read_version = hummock_storage.read_version();
+        assert!(read_version.read().staging().imm.is_empty());
+        assert!(read_version.read().staging().sst.is_empty());
+    }
+
+    assert_eq!(
+        hummock_storage
+            .get(&prefixed_key("aa".as_bytes()), epoch2, read_options())
+            .await
+            .unwrap(),
+        Some(Bytes::from("111"))
+    );
+    assert!(hummock_storage
+        .get(&prefixed_key("bb".as_bytes()), epoch2, read_options())
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        hummock_storage
+            .get(&prefixed_key("bb".as_bytes()), epoch1, read_options())
+            .await
+            .unwrap(),
+        Some(Bytes::from("222"))
+    );
+}
+
+#[tokio::test]
+async fn test_read_uncommitted_epoch() {
+    let sstable_store = mock_sstable_store();
+    let hummock_options = Arc::new(default_config_for_test());
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+    let hummock_meta_client = Arc::new(MockHummockMetaClient::new(
+        hummock_manager_ref.clone(),
+        worker_node.id,
+    ));
+
+    let (hummock_event_handler, event_tx) = prepare_hummock_event_handler(
+        hummock_options.clone(),
+        env,
+        hummock_manager_ref,
+        worker_node,
+        sstable_store.clone(),
+    )
+    .await;
+
+    let read_version = hummock_event_handler.read_version();
+    tokio::spawn(hummock_event_handler.start_hummock_event_handler_worker());
+
+    let initial_epoch = read_version.read().committed().max_committed_epoch();
+
+    let hummock_storage = HummockStorage::for_test(
+        hummock_options,
+        sstable_store,
+        hummock_meta_client.clone(),
+        read_version.clone(),
+        event_tx.clone(),
+    )
+    .unwrap();
+
+    let epoch1 = initial_epoch + 1;
+    let batch1 = vec![(
+        prefixed_key(Bytes::from("aa")),
+        StorageValue::new_put("111"),
+    )];
+    hummock_storage
+        .ingest_batch(
+            batch1,
+            WriteOptions {
+                epoch: epoch1,
+                table_id: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+    let read_options = || ReadOptions {
+        prefix_hint: None,
+        check_bloom_filter: true,
+        table_id: Default::default(),
+        retention_seconds: None,
+    };
+
+    // The staging data of epoch1 is visible.
+    assert_eq!(
+        hummock_storage
+            .get(&prefixed_key("aa".as_bytes()), epoch1, read_options())
+            .await
+            .unwrap()
+            .unwrap(),
+        "111".as_bytes()
+    );
+
+    // Nothing has been written to epoch2 yet, but a streaming read of the uncommitted epoch
+    // still sees the staging data of epoch1.
+    let epoch2 = initial_epoch + 2;
+    assert_eq!(
+        hummock_storage
+            .get(&prefixed_key("aa".as_bytes()), epoch2, read_options())
+            .await
+            .unwrap()
+            .unwrap(),
+        "111".as_bytes()
+    );
+    let mut iter = hummock_storage
+        .iter(
+            (Unbounded, Included(prefixed_key(b"ee").to_vec())),
+            epoch2,
+            read_options(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        iter.next().await.unwrap().unwrap().1,
+        Bytes::copy_from_slice(&b"111"[..])
+    );
+    assert_eq!(None, iter.next().await.unwrap());
+
+    // A snapshot read of a committed epoch must be served by the committed version.
+    let read_version = read_version.read();
+    read_version.validate_snapshot_epoch(initial_epoch).unwrap();
+    let err = read_version.validate_snapshot_epoch(epoch1).unwrap_err();
+    assert!(err.to_string().contains(&format!(
+        "Read epoch {} is beyond the visible horizon {}",
+        epoch1, initial_epoch
+    )));
+}
+
 #[tokio::test]
 async fn test_multiple_epoch_sync() {
     let sstable_store = mock_sstable_store();
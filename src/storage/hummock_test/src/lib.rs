@@ -36,5 +36,7 @@ mod hummock_read_version_tests;
 
 #[cfg(test)]
 mod hummock_storage_tests;
+#[cfg(all(test, feature = "differential_test"))]
+mod read_path_differential_tests;
 #[cfg(all(test, feature = "sync_point"))]
 mod sync_point_tests;
@@ -0,0 +1,394 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Differential tests of the read path of [`HummockStorage`].
+//!
+//! Random workloads of puts and deletes across epochs are applied both to a model, which is a
+//! `BTreeMap` snapshot per epoch, and to the real storage, where the data of the oldest epochs is
+//! committed, the data of the following epochs is synced to staging SSTs, and the data of the
+//! newest epochs stays in staging imms. Full-range scans, sub-range scans and point gets at every
+//! epoch are then compared against the model. A failing workload is shrunk before reporting.
+//!
+//! The seed and the number of workloads can be overridden with the environment variables
+//! `RW_HUMMOCK_DIFF_TEST_SEED` and `RW_HUMMOCK_DIFF_TEST_CASES`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::{Rng, SeedableRng};
+use risingwave_common::config::StorageConfig;
+use risingwave_hummock_sdk::HummockEpoch;
+use risingwave_meta::hummock::test_utils::setup_compute_env;
+use risingwave_meta::hummock::MockHummockMetaClient;
+use risingwave_rpc_client::HummockMetaClient;
+use risingwave_storage::hummock::iterator::test_utils::mock_sstable_store;
+use risingwave_storage::hummock::store::state_store::HummockStorage;
+use risingwave_storage::hummock::store::{ReadOptions, StateStore};
+use risingwave_storage::hummock::test_utils::default_config_for_test;
+use risingwave_storage::storage_value::StorageValue;
+use risingwave_storage::store::WriteOptions;
+
+use crate::hummock_storage_tests::{
+    prepare_hummock_event_handler, sync_epoch, try_wait_epoch_for_test,
+};
+use crate::test_utils::prefixed_key;
+
+const DEFAULT_SEED: u64 = 0x5eed_1195;
+const DEFAULT_CASES: usize = 32;
+/// Workloads failing after this many shrinking attempts are reported as is.
+const MAX_SHRINK_ATTEMPTS: usize = 256;
+
+const KEY_COUNT: usize = 64;
+const MAX_EPOCHS: usize = 6;
+const MAX_BATCHES_PER_EPOCH: usize = 3;
+const MAX_OPS_PER_BATCH: usize = 24;
+
+/// Sub-ranges of key indices scanned besides the full range.
+const SCAN_RANGES: &[(Bound<usize>, Bound<usize>)] = &[
+    (Included(0), Excluded(16)),
+    (Included(10), Included(40)),
+    (Included(33), Unbounded),
+    (Unbounded, Included(7)),
+];
+
+fn key_of(key_idx: usize) -> Bytes {
+    prefixed_key(format!("key_{:03}", key_idx))
+}
+
+/// Values are long enough so that the SSTs are split into many blocks.
+fn value_of(key_idx: usize, version: usize) -> Bytes {
+    Bytes::from(format!("value_{:03}_{:03}_", key_idx, version).repeat(8))
+}
+
+fn to_key_range(range: &(Bound<usize>, Bound<usize>)) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    (
+        range.0.map(|key_idx| key_of(key_idx).to_vec()),
+        range.1.map(|key_idx| key_of(key_idx).to_vec()),
+    )
+}
+
+/// A put with the version of the value, or a delete.
+type Op = (usize, Option<usize>);
+
+#[derive(Clone, Debug)]
+struct Workload {
+    /// The batches written in each epoch. The keys of the batches of an epoch are disjoint.
+    epochs: Vec<Vec<Vec<Op>>>,
+    /// Epochs `[0, committed)` are synced and committed.
+    committed: usize,
+    /// Epochs `[committed, synced)` are synced but not committed, so they are read from the
+    /// staging SSTs. The rest are read from the staging imms.
+    synced: usize,
+}
+
+impl Workload {
+    fn generate(rng: &mut StdRng) -> Self {
+        let epoch_count = rng.gen_range(1..=MAX_EPOCHS);
+        let mut version = 0;
+        let epochs = (0..epoch_count)
+            .map(|_| {
+                let batch_count = rng.gen_range(1..=MAX_BATCHES_PER_EPOCH);
+                let mut free_keys: BTreeSet<usize> = (0..KEY_COUNT).collect();
+                (0..batch_count)
+                    .map(|_| {
+                        let op_count = rng.gen_range(1..=MAX_OPS_PER_BATCH).min(free_keys.len());
+                        let keys = free_keys.iter().copied().choose_multiple(rng, op_count);
+                        keys.into_iter()
+                            .map(|key_idx| {
+                                free_keys.remove(&key_idx);
+                                version += 1;
+                                (key_idx, rng.gen_bool(0.8).then_some(version))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    // All the keys may have been taken by the previous batches of the epoch.
+                    .filter(|batch| !batch.is_empty())
+                    .collect()
+            })
+            .collect();
+        let committed = rng.gen_range(0..=epoch_count);
+        let synced = rng.gen_range(committed..=epoch_count);
+        Self {
+            epochs,
+            committed,
+            synced,
+        }
+    }
+
+    /// The snapshot of the key-value pairs after each epoch.
+    fn model(&self) -> Vec<BTreeMap<Bytes, Bytes>> {
+        let mut snapshot = BTreeMap::new();
+        self.epochs
+            .iter()
+            .map(|batches| {
+                for &(key_idx, version) in batches.iter().flatten() {
+                    match version {
+                        Some(version) => {
+                            snapshot.insert(key_of(key_idx), value_of(key_idx, version))
+                        }
+                        None => snapshot.remove(&key_of(key_idx)),
+                    };
+                }
+                snapshot.clone()
+            })
+            .collect()
+    }
+
+    /// Simpler workloads derived from this one, simplest first.
+    fn shrink_candidates(&self) -> Vec<Workload> {
+        let mut candidates = vec![];
+        for epoch_idx in 0..self.epochs.len() {
+            let mut candidate = self.clone();
+            candidate.epochs.remove(epoch_idx);
+            candidate.committed -= (epoch_idx < self.committed) as usize;
+            candidate.synced -= (epoch_idx < self.synced) as usize;
+            candidates.push(candidate);
+        }
+        if self.committed > 0 {
+            let mut candidate = self.clone();
+            candidate.committed -= 1;
+            candidates.push(candidate);
+        }
+        if self.synced > self.committed {
+            let mut candidate = self.clone();
+            candidate.synced -= 1;
+            candidates.push(candidate);
+        }
+        for (epoch_idx, batches) in self.epochs.iter().enumerate() {
+            for (batch_idx, batch) in batches.iter().enumerate() {
+                if batches.len() > 1 {
+                    let mut candidate = self.clone();
+                    candidate.epochs[epoch_idx].remove(batch_idx);
+                    candidates.push(candidate);
+                }
+                if batch.len() > 1 {
+                    for op_idx in 0..batch.len() {
+                        let mut candidate = self.clone();
+                        candidate.epochs[epoch_idx][batch_idx].remove(op_idx);
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+        candidates
+    }
+}
+
+fn read_options() -> ReadOptions {
+    ReadOptions {
+        table_id: Default::default(),
+        retention_seconds: None,
+        check_bloom_filter: true,
+        prefix_hint: None,
+    }
+}
+
+fn storage_config() -> Arc<StorageConfig> {
+    Arc::new(StorageConfig {
+        // Use the smallest blocks so that scans cross block boundaries.
+        block_size_kb: 1,
+        sstable_size_mb: 1,
+        ..default_config_for_test()
+    })
+}
+
+/// Applies `workload` to a fresh storage and compares the reads against the model. Returns the
+/// first discrepancy found.
+async fn run_workload(workload: &Workload) -> Result<(), String> {
+    let sstable_store = mock_sstable_store();
+    let hummock_options = storage_config();
+    let (env, hummock_manager_ref, _cluster_manager_ref, worker_node) =
+        setup_compute_env(8080).await;
+    let hummock_meta_client = Arc::new(MockHummockMetaClient::new(
+        hummock_manager_ref.clone(),
+        worker_node.id,
+    ));
+    let (hummock_event_handler, event_tx) = prepare_hummock_event_handler(
+        hummock_options.clone(),
+        env,
+        hummock_manager_ref,
+        worker_node,
+        sstable_store.clone(),
+    )
+    .await;
+    let read_version = hummock_event_handler.read_version();
+    let version_update_notifier_tx = hummock_event_handler.version_update_notifier_tx();
+    tokio::spawn(hummock_event_handler.start_hummock_event_handler_worker());
+    let initial_epoch = read_version.read().committed().max_committed_epoch();
+    let hummock_storage = HummockStorage::for_test(
+        hummock_options,
+        sstable_store,
+        hummock_meta_client.clone(),
+        read_version,
+        event_tx.clone(),
+    )
+    .unwrap();
+
+    let epoch_of = |epoch_idx: usize| -> HummockEpoch { initial_epoch + epoch_idx as u64 + 1 };
+
+    let mut synced_ssts = vec![];
+    for (epoch_idx, batches) in workload.epochs.iter().enumerate() {
+        for batch in batches {
+            let mut kv_pairs = batch
+                .iter()
+                .map(|&(key_idx, version)| {
+                    let value = match version {
+                        Some(version) => StorageValue::new_put(value_of(key_idx, version)),
+                        None => StorageValue::new_delete(),
+                    };
+                    (key_of(key_idx), value)
+                })
+                .collect::<Vec<_>>();
+            kv_pairs.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            hummock_storage
+                .ingest_batch(
+                    kv_pairs,
+                    WriteOptions {
+                        epoch: epoch_of(epoch_idx),
+                        table_id: Default::default(),
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        if epoch_idx < workload.synced {
+            let sync_result = sync_epoch(&event_tx, epoch_of(epoch_idx)).await;
+            synced_ssts.push(sync_result.uncommitted_ssts);
+        }
+    }
+    for (epoch_idx, ssts) in synced_ssts.into_iter().take(workload.committed).enumerate() {
+        hummock_meta_client
+            .commit_epoch(epoch_of(epoch_idx), ssts)
+            .await
+            .unwrap();
+    }
+    if workload.committed > 0 {
+        try_wait_epoch_for_test(
+            epoch_of(workload.committed - 1),
+            &version_update_notifier_tx,
+            &event_tx,
+        )
+        .await;
+    }
+
+    for (epoch_idx, snapshot) in workload.model().iter().enumerate() {
+        let epoch = epoch_of(epoch_idx);
+
+        for range in [(Unbounded, Unbounded)].iter().chain(SCAN_RANGES) {
+            let key_range = to_key_range(range);
+            let expected = snapshot
+                .range::<[u8], _>((
+                    key_range.0.as_ref().map(Vec::as_slice),
+                    key_range.1.as_ref().map(Vec::as_slice),
+                ))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Vec<_>>();
+            let actual = hummock_storage
+                .iter(key_range, epoch, read_options())
+                .await
+                .map_err(|e| format!("failed to iter at epoch {}: {}", epoch_idx, e))?
+                .collect(None)
+                .await
+                .map_err(|e| format!("failed to iter at epoch {}: {}", epoch_idx, e))?;
+            if actual != expected {
+                return Err(format!(
+                    "scan of {:?} at epoch {} mismatches\nexpected: {:?}\nactual: {:?}",
+                    range, epoch_idx, expected, actual
+                ));
+            }
+        }
+
+        for key_idx in 0..KEY_COUNT {
+            let key = key_of(key_idx);
+            let actual = hummock_storage
+                .get(&key, epoch, read_options())
+                .await
+                .map_err(|e| format!("failed to get at epoch {}: {}", epoch_idx, e))?;
+            let expected = snapshot.get(&key).cloned();
+            if actual != expected {
+                return Err(format!(
+                    "get of key {} at epoch {} mismatches\nexpected: {:?}\nactual: {:?}",
+                    key_idx, epoch_idx, expected, actual
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Greedily shrinks the failing `workload` to a local minimum that still fails.
+async fn shrink(mut workload: Workload, mut error: String) -> (Workload, String) {
+    let mut attempts = 0;
+    'outer: while attempts < MAX_SHRINK_ATTEMPTS {
+        for candidate in workload.shrink_candidates() {
+            attempts += 1;
+            if let Err(candidate_error) = run_workload(&candidate).await {
+                workload = candidate;
+                error = candidate_error;
+                continue 'outer;
+            }
+            if attempts >= MAX_SHRINK_ATTEMPTS {
+                break 'outer;
+            }
+        }
+        break;
+    }
+    (workload, error)
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+#[tokio::test]
+async fn test_read_path_differential() {
+    let seed = env_or("RW_HUMMOCK_DIFF_TEST_SEED", DEFAULT_SEED);
+    let cases = env_or("RW_HUMMOCK_DIFF_TEST_CASES", DEFAULT_CASES);
+    let mut rng = StdRng::seed_from_u64(seed);
+    for case in 0..cases {
+        let workload = Workload::generate(&mut rng);
+        if let Err(error) = run_workload(&workload).await {
+            let (workload, error) = shrink(workload, error).await;
+            panic!(
+                "case {} of seed {} fails: {}\nshrunk workload: {:#?}",
+                case, seed, error, workload
+            );
+        }
+    }
+}
+
+/// A key is updated in a staging imm, while its older version is in a committed SST whose scan
+/// crosses block boundaries.
+#[tokio::test]
+async fn test_read_path_staging_imm_over_committed_sst() {
+    let epoch0 = (0..KEY_COUNT).map(|key_idx| (key_idx, Some(0))).collect();
+    let epoch1 = (0..KEY_COUNT)
+        .step_by(3)
+        .map(|key_idx| (key_idx, if key_idx % 2 == 0 { Some(1) } else { None }))
+        .collect();
+    let workload = Workload {
+        epochs: vec![vec![epoch0], vec![epoch1]],
+        committed: 1,
+        synced: 1,
+    };
+    run_workload(&workload).await.unwrap();
+}
@@ -31,6 +31,9 @@ pub enum StorageError {
 
     #[error("Deserialize row error {0}.")]
     DeserializeRow(ValueEncodingError),
+
+    #[error("Async flush error: {0}")]
+    AsyncFlush(String),
 }
 
 pub type StorageResult<T> = std::result::Result<T, StorageError>;
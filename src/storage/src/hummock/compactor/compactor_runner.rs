@@ -61,7 +61,10 @@ impl CompactorRunner {
             left: Bytes::copy_from_slice(task.splits[split_index].get_left()),
             right: Bytes::copy_from_slice(task.splits[split_index].get_right()),
         };
-        let compactor = Compactor::new(
+        // Prefer cutting the output at table boundaries once an SST is half full, so that most
+        // tables do not share SSTs with others and can be reclaimed or moved without rewriting.
+        let table_split_size = options.capacity / 2;
+        let mut compactor = Compactor::new(
             context.context.clone(),
             options,
             key_range,
@@ -69,6 +72,8 @@ impl CompactorRunner {
             task.gc_delete_keys,
             task.watermark,
         );
+        compactor.task_config.split_table_ids = task.split_table_ids.iter().copied().collect();
+        compactor.task_config.table_split_size = Some(table_split_size);
 
         Self {
             compactor,
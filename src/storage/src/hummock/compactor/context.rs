@@ -48,6 +48,9 @@ pub struct Context {
 
     pub read_memory_limiter: Arc<MemoryLimiter>,
 
+    /// Bounds the estimated memory usage of the compaction tasks running concurrently.
+    pub task_memory_limiter: Arc<MemoryLimiter>,
+
     pub sstable_id_manager: SstableIdManagerRef,
 
     pub task_progress_manager: TaskProgressManagerRef,
@@ -80,6 +83,7 @@ impl Context {
             compaction_executor,
             filter_key_extractor_manager,
             read_memory_limiter: memory_limiter,
+            task_memory_limiter: MemoryLimiter::unlimit(),
             sstable_id_manager,
             task_progress_manager: Default::default(),
         }
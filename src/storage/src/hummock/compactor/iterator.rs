@@ -18,6 +18,7 @@ use std::sync::atomic::AtomicU64;
 use std::sync::{atomic, Arc};
 use std::time::Instant;
 
+use risingwave_hummock_sdk::key::KeyEncodingVersion;
 use risingwave_hummock_sdk::key_range::KeyRange;
 use risingwave_hummock_sdk::VersionedComparator;
 use risingwave_pb::hummock::SstableInfo;
@@ -40,6 +41,9 @@ struct SstableStreamIterator {
     /// The maximum number of remaining blocks that iterator will download and read.
     remaining_blocks: usize,
 
+    /// The encoding of the keys in the SST.
+    key_encoding: KeyEncodingVersion,
+
     /// Counts the time used for IO.
     stats_ptr: Arc<AtomicU64>,
 }
@@ -58,16 +62,19 @@ impl SstableStreamIterator {
     // returns the first value.
 
     /// Initialises a new [`SstableStreamIterator`] which iterates over the given [`BlockStream`].
-    /// The iterator reads at most `max_block_count` from the stream.
+    /// The iterator reads at most `max_block_count` from the stream, whose keys are in
+    /// `key_encoding`.
     pub fn new(
         block_stream: BlockStream,
         max_block_count: usize,
+        key_encoding: KeyEncodingVersion,
         stats: &StoreLocalStatistic,
     ) -> Self {
         Self {
             block_stream,
             block_iter: None,
             remaining_blocks: max_block_count,
+            key_encoding,
             stats_ptr: stats.remote_io_time.clone(),
         }
     }
@@ -107,7 +114,7 @@ impl SstableStreamIterator {
     async fn next_block(&mut self) -> HummockResult<()> {
         // Check if we want and if we can load the next block.
         if self.remaining_blocks > 0 && let Some(block) = self.download_next_block().await? {
-            let mut block_iter = BlockIterator::new(block);
+            let mut block_iter = BlockIterator::with_key_encoding(block, self.key_encoding);
             block_iter.seek_to_first();
 
             self.remaining_blocks -= 1;
@@ -262,8 +269,12 @@ impl ConcatSstableIterator {
             let add = (now.elapsed().as_secs_f64() * 1000.0).ceil();
             stats_ptr.fetch_add(add as u64, atomic::Ordering::Relaxed);
 
-            let mut sstable_iter =
-                SstableStreamIterator::new(block_stream, end_index - start_index, &self.stats);
+            let mut sstable_iter = SstableStreamIterator::new(
+                block_stream,
+                end_index - start_index,
+                table.value().meta.key_encoding_version(),
+                &self.stats,
+            );
             sstable_iter.seek(seek_key).await?;
 
             self.sstable_iter = Some(sstable_iter);
@@ -18,6 +18,7 @@ mod compactor_runner;
 mod context;
 mod iterator;
 mod shared_buffer_compact;
+mod slow_task;
 mod sstable_store;
 pub(super) mod task_progress;
 
@@ -38,7 +39,9 @@ use futures::{stream, StreamExt, TryFutureExt};
 pub use iterator::ConcatSstableIterator;
 use itertools::Itertools;
 use risingwave_common::config::constant::hummock::CompactionFilterFlag;
-use risingwave_hummock_sdk::compact::compact_task_to_string;
+use risingwave_hummock_sdk::compact::{
+    compact_task_to_string, estimate_compaction_memory, CompactionMemoryEstimateOptions,
+};
 use risingwave_hummock_sdk::filter_key_extractor::FilterKeyExtractorImpl;
 use risingwave_hummock_sdk::key::{get_epoch, user_key, FullKey};
 use risingwave_hummock_sdk::key_range::KeyRange;
@@ -47,7 +50,7 @@ use risingwave_hummock_sdk::VersionedComparator;
 use risingwave_pb::hummock::compact_task::TaskStatus;
 use risingwave_pb::hummock::subscribe_compact_tasks_response::Task;
 use risingwave_pb::hummock::{
-    CompactTask, CompactTaskProgress, KeyRange as KeyRange_vec, LevelType, SstableInfo,
+    CompactTask, CompactTaskProgress, KeyRange as KeyRange_vec, SstableInfo,
     SubscribeCompactTasksResponse,
 };
 use risingwave_rpc_client::HummockMetaClient;
@@ -62,6 +65,7 @@ use self::task_progress::TaskProgress;
 use super::multi_builder::CapacitySplitTableBuilder;
 use super::{HummockResult, SstableBuilderOptions, SstableWriterOptions};
 use crate::hummock::compactor::compactor_runner::CompactorRunner;
+use crate::hummock::compactor::slow_task::SlowCompactionRecord;
 use crate::hummock::compactor::task_progress::TaskProgressGuard;
 use crate::hummock::iterator::{Forward, HummockIterator};
 use crate::hummock::multi_builder::{SplitTableOutput, TableBuilderFactory};
@@ -122,6 +126,10 @@ pub struct TaskConfig {
     pub cache_policy: CachePolicy,
     pub gc_delete_keys: bool,
     pub watermark: u64,
+    /// Tables whose boundaries always cut the output SSTs, hinted by meta.
+    pub split_table_ids: HashSet<u32>,
+    /// Output SSTs larger than this are cut at the next table boundary.
+    pub table_split_size: Option<usize>,
 }
 
 #[derive(Clone)]
@@ -208,13 +216,7 @@ impl Compactor {
             .compact_task_duration
             .with_label_values(&[compact_task.input_ssts[0].level_idx.to_string().as_str()])
             .start_timer();
-
-        let need_quota = estimate_memory_use_for_compaction(&compact_task);
-        tracing::info!(
-            "Ready to handle compaction task: {} need memory: {}",
-            compact_task.task_id,
-            need_quota
-        );
+        let start_time = Instant::now();
 
         let multi_filter = build_multi_compaction_filter(&compact_task);
 
@@ -228,6 +230,58 @@ impl Compactor {
         // Number of splits (key ranges) is equal to number of compaction tasks
         let parallelism = compact_task.splits.len();
         assert_ne!(parallelism, 0, "splits cannot be empty");
+
+        let need_quota = estimate_compaction_memory(
+            &compact_task,
+            &CompactionMemoryEstimateOptions {
+                block_size: (context.options.block_size_kb as u64) << 10,
+                max_target_file_size: (context.options.sstable_size_mb as u64) << 20,
+                parallelism: parallelism as u64,
+            },
+        );
+        tracing::info!(
+            "Ready to handle compaction task: {} need memory: {}",
+            compact_task.task_id,
+            need_quota
+        );
+        // Reject the task if it can never fit in the memory, so that meta can pick a smaller one
+        // for this compactor or try another one. Otherwise wait for the running tasks to release
+        // enough memory.
+        let memory_budget = context.task_memory_limiter.quota();
+        if need_quota > memory_budget {
+            tracing::warn!(
+                "Compaction task {} needs {} bytes of memory, exceeding the budget {}",
+                compact_task.task_id,
+                need_quota,
+                memory_budget
+            );
+            compact_task.estimated_memory_usage = need_quota;
+            Self::compact_done(
+                &mut compact_task,
+                context.clone(),
+                vec![],
+                TaskStatus::MemoryLimitCanceled,
+            )
+            .await;
+            return TaskStatus::MemoryLimitCanceled;
+        }
+        let _memory_tracker = tokio::select! {
+            tracker = context.task_memory_limiter.require_memory(need_quota) => tracker,
+            _ = &mut shutdown_rx => {
+                tracing::warn!(
+                    "Compaction task {} cancelled externally while waiting for memory",
+                    compact_task.task_id
+                );
+                Self::compact_done(
+                    &mut compact_task,
+                    context.clone(),
+                    vec![],
+                    TaskStatus::ManualCanceled,
+                )
+                .await;
+                return TaskStatus::ManualCanceled;
+            }
+        };
         context.stats.compact_task_pending_num.inc();
         let mut task_status = TaskStatus::Success;
         let mut output_ssts = Vec::with_capacity(parallelism);
@@ -291,6 +345,13 @@ impl Compactor {
 
         // Sort by split/key range index.
         output_ssts.sort_by_key(|(split_index, _)| *split_index);
+        let split_output_bytes = output_ssts
+            .iter()
+            .map(|(split_index, ssts)| {
+                let output_bytes = ssts.iter().map(|sst| sst.file_size).sum::<u64>();
+                (*split_index, output_bytes)
+            })
+            .collect_vec();
 
         sync_point::sync_point!("BEFORE_COMPACT_REPORT");
         // After a compaction is done, mutate the compaction task.
@@ -302,6 +363,15 @@ impl Compactor {
             cost_time,
             compact_task_to_string(&compact_task)
         );
+        if let Some(record) =
+            SlowCompactionRecord::new_if_slow(&compact_task, &split_output_bytes, start_time)
+        {
+            tracing::warn!(
+                target: "events::compaction::slow_task",
+                "slow compaction task: {}",
+                record.to_json()
+            );
+        }
         context.stats.compact_task_pending_num.dec();
         for level in &compact_task.input_ssts {
             for table in &level.table_infos {
@@ -324,7 +394,9 @@ impl Compactor {
             .reserve(compact_task.splits.len());
         let mut compaction_write_bytes = 0;
         for (_, ssts) in output_ssts {
-            for sst_info in ssts {
+            for mut sst_info in ssts {
+                // Table write stats are only reported for data newly flushed from shared buffer.
+                sst_info.table_stats.clear();
                 compaction_write_bytes += sst_info.file_size;
                 compact_task.sorted_output_ssts.push(sst_info);
             }
@@ -386,7 +458,11 @@ impl Compactor {
                 }
 
                 let mut stream = match hummock_meta_client
-                    .subscribe_compact_tasks(max_concurrent_task_number)
+                    .subscribe_compact_tasks(
+                        max_concurrent_task_number,
+                        compactor_context.context.task_memory_limiter.quota(),
+                        (compactor_context.context.options.block_size_kb as u64) << 10,
+                    )
                     .await
                 {
                     Ok(stream) => {
@@ -616,6 +692,8 @@ impl Compactor {
                 cache_policy,
                 gc_delete_keys,
                 watermark,
+                split_table_ids: HashSet::new(),
+                table_split_size: None,
             },
         }
     }
@@ -735,6 +813,8 @@ impl Compactor {
             builder_factory,
             self.context.stats.clone(),
             task_progress,
+            self.task_config.split_table_ids.clone(),
+            self.task_config.table_split_size,
         );
         Compactor::compact_and_build_sst(
             &mut sst_builder,
@@ -748,22 +828,6 @@ impl Compactor {
     }
 }
 
-pub fn estimate_memory_use_for_compaction(task: &CompactTask) -> u64 {
-    let mut total_memory_size = 0;
-    for level in &task.input_ssts {
-        if level.level_type == LevelType::Nonoverlapping as i32 {
-            if let Some(table) = level.table_infos.first() {
-                total_memory_size += table.file_size * task.splits.len() as u64;
-            }
-        } else {
-            for table in &level.table_infos {
-                total_memory_size += table.file_size;
-            }
-        }
-    }
-    total_memory_size
-}
-
 fn build_multi_compaction_filter(compact_task: &CompactTask) -> MultiCompactionFilter {
     use risingwave_common::catalog::TableOption;
     let mut multi_filter = MultiCompactionFilter::default();
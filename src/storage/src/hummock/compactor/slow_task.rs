@@ -0,0 +1,164 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, Instant};
+
+use risingwave_pb::hummock::CompactTask;
+use serde::Serialize;
+
+/// The structured record of a compaction task slower than its `slow_task_threshold_ms`, logged in
+/// JSON.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SlowCompactionRecord {
+    pub task_id: u64,
+    pub compaction_group_id: u64,
+    pub input_level: u32,
+    pub target_level: u32,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub latency_ms: u64,
+    /// The key range of the split with the most output bytes, in hex.
+    pub widest_key_range: Option<(String, String)>,
+}
+
+impl SlowCompactionRecord {
+    /// Returns the record if the task started at `start_time` and finished now is slow, where a
+    /// zero threshold disables the slow task log. `split_output_bytes` is the output bytes of each
+    /// split.
+    pub fn new_if_slow(
+        compact_task: &CompactTask,
+        split_output_bytes: &[(usize, u64)],
+        start_time: Instant,
+    ) -> Option<Self> {
+        let latency = start_time.elapsed();
+        let threshold_ms = compact_task.slow_task_threshold_ms;
+        if threshold_ms == 0 || latency <= Duration::from_millis(threshold_ms) {
+            return None;
+        }
+
+        let widest_key_range = split_output_bytes
+            .iter()
+            .max_by_key(|&&(split_index, output_bytes)| {
+                (output_bytes, std::cmp::Reverse(split_index))
+            })
+            .and_then(|(split_index, _)| compact_task.splits.get(*split_index))
+            .map(|key_range| (hex::encode(&key_range.left), hex::encode(&key_range.right)));
+        Some(Self {
+            task_id: compact_task.task_id,
+            compaction_group_id: compact_task.compaction_group_id,
+            input_level: compact_task
+                .input_ssts
+                .first()
+                .map_or(0, |level| level.level_idx),
+            target_level: compact_task.target_level,
+            input_bytes: compact_task
+                .input_ssts
+                .iter()
+                .flat_map(|level| level.table_infos.iter())
+                .map(|table| table.file_size)
+                .sum(),
+            output_bytes: split_output_bytes.iter().map(|(_, bytes)| bytes).sum(),
+            latency_ms: latency.as_millis() as u64,
+            widest_key_range,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_pb::hummock::{InputLevel, KeyRange, SstableInfo};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_compaction_record() {
+        let sst = |file_size| SstableInfo {
+            file_size,
+            ..Default::default()
+        };
+        let key_range = |left: &[u8], right: &[u8]| KeyRange {
+            left: left.to_vec(),
+            right: right.to_vec(),
+            inf: false,
+        };
+        let compact_task = CompactTask {
+            input_ssts: vec![
+                InputLevel {
+                    level_idx: 0,
+                    table_infos: vec![sst(100), sst(200)],
+                    ..Default::default()
+                },
+                InputLevel {
+                    level_idx: 1,
+                    table_infos: vec![sst(300)],
+                    ..Default::default()
+                },
+            ],
+            splits: vec![key_range(b"", b"\x01"), key_range(b"\x01", b"")],
+            task_id: 7,
+            target_level: 1,
+            compaction_group_id: 2,
+            slow_task_threshold_ms: 50,
+            ..Default::default()
+        };
+        let split_output_bytes = [(0, 150), (1, 400)];
+
+        let start_time = Instant::now();
+        assert!(SlowCompactionRecord::new_if_slow(
+            &CompactTask {
+                slow_task_threshold_ms: 60_000,
+                ..compact_task.clone()
+            },
+            &split_output_bytes,
+            start_time
+        )
+        .is_none());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(SlowCompactionRecord::new_if_slow(
+            &CompactTask {
+                slow_task_threshold_ms: 0,
+                ..compact_task.clone()
+            },
+            &split_output_bytes,
+            start_time
+        )
+        .is_none());
+
+        let record =
+            SlowCompactionRecord::new_if_slow(&compact_task, &split_output_bytes, start_time)
+                .unwrap();
+        assert!(record.latency_ms >= 100);
+        assert_eq!(
+            record,
+            SlowCompactionRecord {
+                task_id: 7,
+                compaction_group_id: 2,
+                input_level: 0,
+                target_level: 1,
+                input_bytes: 600,
+                output_bytes: 550,
+                latency_ms: record.latency_ms,
+                widest_key_range: Some(("01".to_string(), "".to_string())),
+            }
+        );
+        let json: serde_json::Value = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(json["task_id"], 7);
+        assert_eq!(json["input_bytes"], 600);
+        assert_eq!(json["widest_key_range"][0], "01");
+    }
+}
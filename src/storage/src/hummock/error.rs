@@ -46,6 +46,10 @@ enum HummockErrorInner {
     WaitEpoch(String),
     #[error("Expired Epoch: watermark {safe_epoch}, epoch {epoch}.")]
     ExpiredEpoch { safe_epoch: u64, epoch: u64 },
+    #[error("Write epoch {write_epoch} is below the epoch fence {fence}.")]
+    WriteEpochBelowFence { write_epoch: u64, fence: u64 },
+    #[error("Read epoch {epoch} is beyond the visible horizon {horizon}.")]
+    ReadEpochBeyondHorizon { epoch: u64, horizon: u64 },
     #[error("CompactionExecutor error {0}.")]
     CompactionExecutor(String),
     #[error("TieredCache error {0}.")]
@@ -117,6 +121,14 @@ impl HummockError {
         HummockErrorInner::ExpiredEpoch { safe_epoch, epoch }.into()
     }
 
+    pub fn write_epoch_below_fence(write_epoch: u64, fence: u64) -> HummockError {
+        HummockErrorInner::WriteEpochBelowFence { write_epoch, fence }.into()
+    }
+
+    pub fn read_epoch_beyond_horizon(epoch: u64, horizon: u64) -> HummockError {
+        HummockErrorInner::ReadEpochBeyondHorizon { epoch, horizon }.into()
+    }
+
     pub fn compaction_executor(error: impl ToString) -> HummockError {
         HummockErrorInner::CompactionExecutor(error.to_string()).into()
     }
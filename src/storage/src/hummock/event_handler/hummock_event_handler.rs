@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 use std::iter::once;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -79,6 +80,23 @@ impl BufferTracker {
     }
 }
 
+/// The writes are considered imbalanced if a single epoch holds more than this fraction of the
+/// staging imms.
+const IMBALANCED_EPOCH_RATIO: f64 = 0.5;
+
+/// Returns the epoch with the most imms in `epoch_histogram` if the writes span multiple epochs and
+/// are imbalanced, i.e., the epoch holds more than [`IMBALANCED_EPOCH_RATIO`] of the imms.
+pub fn imbalanced_epoch(epoch_histogram: &BTreeMap<HummockEpoch, usize>) -> Option<HummockEpoch> {
+    if epoch_histogram.len() < 2 {
+        return None;
+    }
+    let total: usize = epoch_histogram.values().sum();
+    let (epoch, count) = epoch_histogram
+        .iter()
+        .max_by_key(|(epoch, count)| (**count, Reverse(**epoch)))?;
+    (*count as f64 > total as f64 * IMBALANCED_EPOCH_RATIO).then_some(*epoch)
+}
+
 pub struct HummockEventHandler {
     buffer_tracker: BufferTracker,
     sstable_id_manager: SstableIdManagerRef,
@@ -143,11 +161,16 @@ impl HummockEventHandler {
     }
 
     fn try_flush_shared_buffer(&mut self) {
+        // Flush the epoch holding most of the staging imms first, so that its memory is released
+        // before it grows further.
+        let target_epoch = imbalanced_epoch(&self.read_version.read().staging().epoch_histogram());
         // Keep issuing new flush task until flush is not needed or we can issue
         // no more task
         while self.buffer_tracker.need_more_flush() {
-            if let Some((epoch, join_handle)) =
-                self.local_version_manager.clone().flush_shared_buffer()
+            if let Some((epoch, join_handle)) = self
+                .local_version_manager
+                .clone()
+                .flush_shared_buffer(target_epoch)
             {
                 self.upload_handle_manager
                     .add_epoch_handle(epoch, once(join_handle));
@@ -351,7 +374,8 @@ impl HummockEventHandler {
             .write()
             .update(VersionUpdate::CommittedSnapshot(
                 self.pinned_version.clone(),
-            ));
+            ))
+            .expect("committed snapshot is not fenced");
 
         let max_committed_epoch = self.pinned_version.max_committed_epoch();
 
@@ -115,9 +115,11 @@ impl HummockMetaClient for MonitoredHummockMetaClient {
     async fn subscribe_compact_tasks(
         &self,
         max_concurrent_task_number: u64,
+        memory_budget: u64,
+        block_size: u64,
     ) -> Result<Streaming<SubscribeCompactTasksResponse>> {
         self.meta_client
-            .subscribe_compact_tasks(max_concurrent_task_number)
+            .subscribe_compact_tasks(max_concurrent_task_number, memory_budget, block_size)
             .await
     }
 
@@ -17,6 +17,7 @@ use std::ops::RangeBounds;
 use std::sync::Arc;
 
 use bytes::Bytes;
+use itertools::Itertools;
 use parking_lot::{RwLock, RwLockWriteGuard};
 use risingwave_common::catalog::TableId;
 use risingwave_hummock_sdk::compaction_group::hummock_version_ext::HummockVersionExt;
@@ -183,13 +184,18 @@ impl LocalVersionManager {
     }
 
     /// Issue a concurrent upload task to flush some local shared buffer batch to object store.
+    /// The shared buffer of `target_epoch` is flushed first if given, and otherwise the one of the
+    /// smallest epoch.
     ///
     /// This method should only be called in the buffer tracker worker.
     ///
     /// Return:
     ///   - Some(task join handle) when there is new upload task
     ///   - None when there is no new task
-    pub fn flush_shared_buffer(self: Arc<Self>) -> Option<(HummockEpoch, JoinHandle<()>)> {
+    pub fn flush_shared_buffer(
+        self: Arc<Self>,
+        target_epoch: Option<HummockEpoch>,
+    ) -> Option<(HummockEpoch, JoinHandle<()>)> {
         let (epoch, (order_index, payload, task_write_batch_size), compaction_group_index) = {
             let mut local_version_guard = self.local_version.write();
 
@@ -198,7 +204,10 @@ impl LocalVersionManager {
             let mut task = None;
             let compaction_group_index =
                 local_version_guard.pinned_version.compaction_group_index();
-            for (epoch, shared_buffer) in local_version_guard.iter_mut_unsynced_shared_buffer() {
+            for (epoch, shared_buffer) in local_version_guard
+                .iter_mut_unsynced_shared_buffer()
+                .sorted_by_key(|(epoch, _)| Some(**epoch) != target_epoch)
+            {
                 if let Some(upload_task) = shared_buffer.new_upload_task() {
                     task = Some((*epoch, upload_task, compaction_group_index));
                     break;
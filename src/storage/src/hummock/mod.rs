@@ -93,6 +93,7 @@ use crate::hummock::sstable_store::{SstableStoreRef, TableHolder};
 use crate::hummock::store::state_store::HummockStorageIterator;
 use crate::hummock::store::version::HummockReadVersion;
 use crate::monitor::StoreLocalStatistic;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 
 struct HummockStorageShutdownGuard {
     shutdown_sender: UnboundedSender<HummockEvent>,
@@ -135,6 +136,8 @@ pub struct HummockStorage {
     version_update_notifier_tx: Arc<tokio::sync::watch::Sender<HummockEpoch>>,
 
     seal_epoch: Arc<AtomicU64>,
+
+    flush_tracker: Arc<FlushTracker>,
 }
 
 impl HummockStorage {
@@ -235,6 +238,7 @@ impl HummockStorage {
             version_update_notifier_tx: hummock_event_handler.version_update_notifier_tx(),
             seal_epoch: hummock_event_handler.sealed_epoch(),
             hummock_event_sender: event_tx,
+            flush_tracker: Arc::default(),
         };
 
         tokio::spawn(hummock_event_handler.start_hummock_event_handler_worker());
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
@@ -20,16 +21,16 @@ use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::{Arc, LazyLock};
 
-use bytes::Bytes;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use risingwave_common::catalog::TableId;
-use risingwave_hummock_sdk::key::FullKey;
+use risingwave_hummock_sdk::key::{table_prefix, FullKey, EPOCH_LEN};
 
 use crate::hummock::iterator::{
     Backward, DirectionEnum, Forward, HummockIterator, HummockIteratorDirection,
 };
 use crate::hummock::utils::MemoryTracker;
 use crate::hummock::value::HummockValue;
-use crate::hummock::{key, HummockEpoch, HummockResult, MemoryLimiter};
+use crate::hummock::{key, HummockEpoch, HummockError, HummockResult, MemoryLimiter};
 use crate::storage_value::StorageValue;
 
 pub(crate) type SharedBufferItem = (Bytes, HummockValue<Bytes>);
@@ -40,6 +41,8 @@ pub(crate) struct SharedBufferBatchInner {
     size: usize,
     _tracker: Option<MemoryTracker>,
     batch_id: SharedBufferBatchId,
+    /// Ids of the batches that this batch is merged from. Empty if it's not a merged batch.
+    merged_batch_ids: Vec<SharedBufferBatchId>,
 }
 
 impl Deref for SharedBufferBatchInner {
@@ -94,6 +97,7 @@ impl SharedBufferBatch {
                 size,
                 _tracker: None,
                 batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                merged_batch_ids: vec![],
             }),
             epoch,
             table_id,
@@ -124,6 +128,134 @@ impl SharedBufferBatch {
                 size,
                 _tracker: tracker,
                 batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                merged_batch_ids: vec![],
+            }),
+            epoch,
+            table_id,
+        }
+    }
+
+    /// Merges `batches` of the same epoch and table into a single batch. `batches` should be
+    /// ordered from newer to older, and for a key written in several batches, only the value in
+    /// the newest one is kept.
+    ///
+    /// The merged batch doesn't hold a memory tracker, since the memory of the source batches is
+    /// still tracked until they are flushed.
+    pub fn merge(batches: &[SharedBufferBatch]) -> Self {
+        assert!(!batches.is_empty());
+        let epoch = batches[0].epoch;
+        let table_id = batches[0].table_id;
+        assert!(batches
+            .iter()
+            .all(|batch| batch.epoch == epoch && batch.table_id == table_id));
+
+        let mut payload = batches
+            .iter()
+            .flat_map(|batch| batch.inner.iter().cloned())
+            .collect::<Vec<_>>();
+        // The sort is stable, so for duplicated keys the one from the newer batch comes first and
+        // is kept by `dedup_by`.
+        payload.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        payload.dedup_by(|(k1, _), (k2, _)| k1 == k2);
+        let size = Self::measure_batch_size(&payload);
+        let merged_batch_ids = batches
+            .iter()
+            .flat_map(|batch| batch.covered_batch_ids())
+            .collect();
+
+        Self {
+            inner: Arc::new(SharedBufferBatchInner {
+                payload,
+                size,
+                _tracker: None,
+                batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                merged_batch_ids,
+            }),
+            epoch,
+            table_id,
+        }
+    }
+
+    /// Splits the batch into the pairs with user keys in `[start, split_key)` and the ones in
+    /// `[split_key, end]`. All versions of a user key go to the same half, and either half may be
+    /// empty.
+    ///
+    /// If the batch isn't shared, its memory tracker is divided between the halves. Otherwise the
+    /// halves don't hold a tracker, since the memory is still tracked by the other references.
+    pub fn split_at_key(self, split_key: &[u8]) -> (SharedBufferBatch, SharedBufferBatch) {
+        let covered_batch_ids = self.covered_batch_ids();
+        let (mut payload, tracker) = match Arc::try_unwrap(self.inner) {
+            Ok(inner) => (inner.payload, inner._tracker),
+            Err(inner) => (inner.payload.clone(), None),
+        };
+        let pos = payload.partition_point(|(k, _)| key::user_key(k) < split_key);
+        let right_payload = payload.split_off(pos);
+        let left_payload = payload;
+
+        let left_size = Self::measure_batch_size(&left_payload);
+        let right_size = Self::measure_batch_size(&right_payload);
+        let (left_tracker, right_tracker) = match tracker {
+            Some(mut tracker) => (Some(tracker.split(left_size as u64)), Some(tracker)),
+            None => (None, None),
+        };
+        let build =
+            |payload: Vec<SharedBufferItem>, size: usize, tracker: Option<MemoryTracker>| Self {
+                inner: Arc::new(SharedBufferBatchInner {
+                    payload,
+                    size,
+                    _tracker: tracker,
+                    batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                    merged_batch_ids: covered_batch_ids.clone(),
+                }),
+                epoch: self.epoch,
+                table_id: self.table_id,
+            };
+        (
+            build(left_payload, left_size, left_tracker),
+            build(right_payload, right_size, right_tracker),
+        )
+    }
+
+    /// Splits the batch at each of `sorted_split_keys` with [`Self::split_at_key`], e.g. to flush
+    /// the parts to SSTs in parallel. Returns `sorted_split_keys.len() + 1` batches ordered by key.
+    pub fn split_at_keys(self, sorted_split_keys: &[&[u8]]) -> Vec<SharedBufferBatch> {
+        let mut batches = Vec::with_capacity(sorted_split_keys.len() + 1);
+        let mut rest = self;
+        for split_key in sorted_split_keys {
+            let (left, right) = rest.split_at_key(split_key);
+            batches.push(left);
+            rest = right;
+        }
+        batches.push(rest);
+        batches
+    }
+
+    /// Builds a batch of `table_id` that deletes `sorted_user_keys` at `epoch`.
+    ///
+    /// Like a merged batch, the batch doesn't hold a memory tracker.
+    pub fn build_tombstones(
+        sorted_user_keys: Vec<Bytes>,
+        epoch: HummockEpoch,
+        table_id: TableId,
+    ) -> Self {
+        let kv_pairs = sorted_user_keys
+            .into_iter()
+            .map(|user_key| (user_key, StorageValue::new_delete()))
+            .collect();
+        let payload = Self::build_shared_buffer_item_batches(kv_pairs, epoch);
+        let size = Self::measure_batch_size(&payload);
+        #[cfg(debug_assertions)]
+        {
+            Self::check_table_prefix(table_id, &payload)
+        }
+
+        Self {
+            inner: Arc::new(SharedBufferBatchInner {
+                payload,
+                size,
+                _tracker: None,
+                batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                merged_batch_ids: vec![],
             }),
             epoch,
             table_id,
@@ -146,15 +278,19 @@ impl SharedBufferBatch {
     }
 
     pub fn get(&self, user_key: &[u8]) -> Option<HummockValue<Bytes>> {
+        self.point_get(user_key).cloned()
+    }
+
+    /// Looks up `user_key` in O(log N) without copying the value. Returns
+    /// `Some(HummockValue::Delete)` if the key is deleted in the batch, and `None` if the batch
+    /// doesn't contain the key.
+    pub fn point_get(&self, user_key: &[u8]) -> Option<&HummockValue<Bytes>> {
         // Perform binary search on user key because the items in SharedBufferBatch is ordered by
         // user key.
-        match self
-            .inner
+        self.inner
             .binary_search_by(|m| key::user_key(&m.0).cmp(user_key))
-        {
-            Ok(i) => Some(self.inner[i].1.clone()),
-            Err(_) => None,
-        }
+            .ok()
+            .map(|i| &self.inner[i].1)
     }
 
     pub fn into_directed_iter<D: HummockIteratorDirection>(self) -> SharedBufferBatchIterator<D> {
@@ -189,18 +325,42 @@ impl SharedBufferBatch {
         key::user_key(&self.inner.last().unwrap().0)
     }
 
+    /// Returns the maximum epoch of the key-value pairs in the batch, which is the epoch the batch
+    /// is written in. A batch merged from several batches may contain pairs of smaller epochs, see
+    /// [`Self::min_epoch`] and [`Self::epoch_count`]. An empty batch still has this epoch.
     pub fn epoch(&self) -> u64 {
         self.epoch
     }
 
+    /// Returns the minimum epoch of the key-value pairs in the batch, which is [`Self::epoch`] if
+    /// the batch is empty.
+    pub fn min_epoch(&self) -> HummockEpoch {
+        self.inner
+            .iter()
+            .map(|(k, _)| key::get_epoch(k))
+            .min()
+            .unwrap_or(self.epoch)
+    }
+
+    /// Returns the number of distinct epochs in the batch. A batch with `epoch_count() > 1` is
+    /// merged from several batches, and readers must be aware that a user key may have multiple
+    /// versions in it. An empty batch counts as one epoch, [`Self::epoch`], consistent with
+    /// [`Self::min_epoch`].
+    pub fn epoch_count(&self) -> usize {
+        self.inner
+            .iter()
+            .map(|(k, _)| key::get_epoch(k))
+            .collect::<HashSet<_>>()
+            .len()
+            .max(1)
+    }
+
     pub fn size(&self) -> usize {
         self.inner.size
     }
 
     #[cfg(debug_assertions)]
     fn check_table_prefix(check_table_id: TableId, sorted_items: &Vec<SharedBufferItem>) {
-        use risingwave_hummock_sdk::key::table_prefix;
-
         if check_table_id.table_id() == 0 {
             // for unit-test
             return;
@@ -217,6 +377,125 @@ impl SharedBufferBatch {
         self.inner.batch_id
     }
 
+    /// Returns the ids of the batches whose data are included in this batch, i.e. the source
+    /// batches for a merged batch, or the batch itself otherwise.
+    pub fn covered_batch_ids(&self) -> Vec<SharedBufferBatchId> {
+        if self.inner.merged_batch_ids.is_empty() {
+            vec![self.inner.batch_id]
+        } else {
+            self.inner.merged_batch_ids.clone()
+        }
+    }
+
+    /// Returns the ids of the source batches if this batch is merged from them, or an empty slice
+    /// otherwise.
+    pub(crate) fn merged_batch_ids(&self) -> &[SharedBufferBatchId] {
+        &self.inner.merged_batch_ids
+    }
+
+    /// Restores the ids of a batch just reconstructed by [`Self::from_checkpoint_bytes`], so that
+    /// the staging SSTs that refer to the original batch still match it. The batches built
+    /// afterwards don't reuse the restored ids.
+    pub(crate) fn with_batch_ids(
+        mut self,
+        batch_id: SharedBufferBatchId,
+        merged_batch_ids: Vec<SharedBufferBatchId>,
+    ) -> Self {
+        let max_batch_id = merged_batch_ids.iter().copied().fold(batch_id, u64::max);
+        SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_max(max_batch_id + 1, Relaxed);
+        let inner = Arc::get_mut(&mut self.inner).expect("the reconstructed batch is not shared");
+        inner.batch_id = batch_id;
+        inner.merged_batch_ids = merged_batch_ids;
+        self
+    }
+
+    /// Encodes the batch for checkpointing, which can be decoded by
+    /// [`Self::from_checkpoint_bytes`].
+    ///
+    /// The epoch of the batch comes first, followed by the number of the key-value pairs and the
+    /// pairs in order. Each pair is encoded as the length-prefixed full key and the
+    /// length-prefixed value in the format of [`HummockValue::encode`], which is also used by the
+    /// SST blocks that the batch is flushed to. All integers are little-endian.
+    pub fn to_checkpoint_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(12 + self.size() + self.inner.len() * 9);
+        buf.put_u64_le(self.epoch);
+        buf.put_u32_le(self.inner.len() as u32);
+        for (key, value) in self.inner.iter() {
+            buf.put_u32_le(key.len() as u32);
+            buf.put_slice(key);
+            buf.put_u32_le(value.encoded_len() as u32);
+            value.encode(&mut buf);
+        }
+        buf.freeze()
+    }
+
+    /// Reconstructs a batch of `table_id` from the bytes encoded by [`Self::to_checkpoint_bytes`].
+    /// The keys and values share the memory of `bytes`.
+    ///
+    /// The batch gets a new batch id, and doesn't hold a memory tracker.
+    pub fn from_checkpoint_bytes(mut bytes: Bytes, table_id: TableId) -> HummockResult<Self> {
+        fn split_to(bytes: &mut Bytes, len: usize) -> HummockResult<Bytes> {
+            if bytes.len() < len {
+                return Err(HummockError::decode_error(format!(
+                    "checkpoint truncated: expect {} more bytes, but only {} left",
+                    len,
+                    bytes.len()
+                )));
+            }
+            Ok(bytes.split_to(len))
+        }
+
+        // Table id 0 is only used in unit tests, whose keys have no table prefix.
+        let prefix = (table_id.table_id() != 0).then(|| table_prefix(table_id.table_id()));
+        let epoch = split_to(&mut bytes, 8)?.get_u64_le();
+        let len = split_to(&mut bytes, 4)?.get_u32_le() as usize;
+        let mut payload = Vec::with_capacity(len.min(bytes.len()));
+        for _ in 0..len {
+            let key_len = split_to(&mut bytes, 4)?.get_u32_le() as usize;
+            let key = split_to(&mut bytes, key_len)?;
+            if key.len() < EPOCH_LEN {
+                return Err(HummockError::decode_error(format!(
+                    "checkpoint key of {} bytes has no epoch",
+                    key.len()
+                )));
+            }
+            if let Some(prefix) = &prefix && !key.starts_with(prefix) {
+                return Err(HummockError::decode_error(format!(
+                    "checkpoint key not in table {}",
+                    table_id
+                )));
+            }
+            let value_len = split_to(&mut bytes, 4)?.get_u32_le() as usize;
+            let value = split_to(&mut bytes, value_len)?;
+            let value = match HummockValue::from_slice(&value)? {
+                HummockValue::Put(_) => HummockValue::Put(value.slice(1..)),
+                HummockValue::Delete => HummockValue::Delete,
+            };
+            if let Some((last_key, _)) = payload.last() && *last_key >= key {
+                return Err(HummockError::decode_error("checkpoint keys not sorted"));
+            }
+            payload.push((key, value));
+        }
+        if !bytes.is_empty() {
+            return Err(HummockError::decode_error(format!(
+                "checkpoint has {} trailing bytes",
+                bytes.len()
+            )));
+        }
+
+        Ok(Self {
+            inner: Arc::new(SharedBufferBatchInner {
+                size: Self::measure_batch_size(&payload),
+                payload,
+                _tracker: None,
+                batch_id: SHARED_BUFFER_BATCH_ID_GENERATOR.fetch_add(1, Relaxed),
+                merged_batch_ids: vec![],
+            }),
+            epoch,
+            table_id,
+        })
+    }
+
     pub fn build_shared_buffer_item_batches(
         kv_pairs: Vec<(Bytes, StorageValue)>,
         epoch: HummockEpoch,
@@ -360,6 +639,7 @@ mod tests {
 
     use super::*;
     use crate::hummock::iterator::test_utils::{iterator_test_key_of, iterator_test_key_of_epoch};
+    use crate::hummock::store::memtable::ImmutableMemtable;
 
     fn transform_shared_buffer(
         batches: Vec<(Vec<u8>, HummockValue<Bytes>)>,
@@ -446,6 +726,300 @@ mod tests {
         assert_eq!(output, shared_buffer_items);
     }
 
+    #[test]
+    fn test_shared_buffer_batch_point_get() {
+        let epoch = 1;
+        let shared_buffer_items = vec![
+            (
+                iterator_test_key_of_epoch(1, epoch),
+                HummockValue::put(Bytes::from("value1")),
+            ),
+            (iterator_test_key_of_epoch(2, epoch), HummockValue::delete()),
+            (
+                iterator_test_key_of_epoch(4, epoch),
+                HummockValue::put(Bytes::from("value4")),
+            ),
+        ];
+        let imm: ImmutableMemtable = SharedBufferBatch::for_test(
+            transform_shared_buffer(shared_buffer_items),
+            epoch,
+            Default::default(),
+        );
+
+        // Present keys.
+        assert_eq!(
+            imm.point_get(user_key(&iterator_test_key_of(1))),
+            Some(&HummockValue::put(Bytes::from("value1")))
+        );
+        assert_eq!(
+            imm.point_get(user_key(&iterator_test_key_of(4))),
+            Some(&HummockValue::put(Bytes::from("value4")))
+        );
+        // Deleted key.
+        assert_eq!(
+            imm.point_get(user_key(&iterator_test_key_of(2))),
+            Some(&HummockValue::Delete)
+        );
+        // Absent keys, before, between and after the keys in the batch.
+        for idx in [0, 3, 5] {
+            assert_eq!(imm.point_get(user_key(&iterator_test_key_of(idx))), None);
+        }
+    }
+
+    #[test]
+    fn test_shared_buffer_batch_epoch_count() {
+        let epoch = 3;
+        let shared_buffer_items = vec![
+            (
+                iterator_test_key_of_epoch(1, epoch),
+                HummockValue::put(Bytes::from("value1")),
+            ),
+            (
+                iterator_test_key_of_epoch(2, epoch),
+                HummockValue::put(Bytes::from("value2")),
+            ),
+        ];
+        let shared_buffer_batch = SharedBufferBatch::for_test(
+            transform_shared_buffer(shared_buffer_items),
+            epoch,
+            Default::default(),
+        );
+        assert_eq!(shared_buffer_batch.epoch_count(), 1);
+        assert_eq!(shared_buffer_batch.min_epoch(), epoch);
+        assert_eq!(shared_buffer_batch.epoch(), epoch);
+
+        // A merged batch holding multiple versions of the same user key.
+        let shared_buffer_items = vec![
+            (
+                iterator_test_key_of_epoch(1, epoch),
+                HummockValue::put(Bytes::from("value1")),
+            ),
+            (
+                iterator_test_key_of_epoch(1, epoch - 1),
+                HummockValue::put(Bytes::from("value0")),
+            ),
+            (
+                iterator_test_key_of_epoch(2, epoch - 2),
+                HummockValue::Delete,
+            ),
+        ];
+        let shared_buffer_batch = SharedBufferBatch::for_test(
+            transform_shared_buffer(shared_buffer_items),
+            epoch,
+            Default::default(),
+        );
+        assert_eq!(shared_buffer_batch.epoch_count(), 3);
+        assert_eq!(shared_buffer_batch.min_epoch(), epoch - 2);
+        assert_eq!(shared_buffer_batch.epoch(), epoch);
+
+        // An empty batch only has the epoch it's written in.
+        let shared_buffer_batch =
+            SharedBufferBatch::for_test(transform_shared_buffer(vec![]), epoch, Default::default());
+        assert_eq!(shared_buffer_batch.epoch_count(), 1);
+        assert_eq!(shared_buffer_batch.min_epoch(), epoch);
+        assert_eq!(shared_buffer_batch.epoch(), epoch);
+    }
+
+    #[test]
+    fn test_shared_buffer_batch_merge() {
+        let epoch = 1;
+        let older = SharedBufferBatch::for_test(
+            transform_shared_buffer(vec![
+                (
+                    iterator_test_key_of_epoch(1, epoch),
+                    HummockValue::put(Bytes::from("old_value1")),
+                ),
+                (
+                    iterator_test_key_of_epoch(3, epoch),
+                    HummockValue::put(Bytes::from("value3")),
+                ),
+            ]),
+            epoch,
+            Default::default(),
+        );
+        let newer = SharedBufferBatch::for_test(
+            transform_shared_buffer(vec![
+                (
+                    iterator_test_key_of_epoch(1, epoch),
+                    HummockValue::put(Bytes::from("value1")),
+                ),
+                (iterator_test_key_of_epoch(2, epoch), HummockValue::Delete),
+            ]),
+            epoch,
+            Default::default(),
+        );
+
+        let merged = SharedBufferBatch::merge(&[newer.clone(), older.clone()]);
+        assert_eq!(merged.epoch(), epoch);
+        assert_eq!(
+            merged.covered_batch_ids(),
+            vec![newer.batch_id(), older.batch_id()]
+        );
+        assert_eq!(
+            merged
+                .get_payload()
+                .iter()
+                .map(|(k, _)| k.clone())
+                .collect_vec(),
+            vec![
+                iterator_test_key_of_epoch(1, epoch),
+                iterator_test_key_of_epoch(2, epoch),
+                iterator_test_key_of_epoch(3, epoch),
+            ]
+        );
+        assert_eq!(
+            merged.get(user_key(&iterator_test_key_of_epoch(1, epoch))),
+            Some(HummockValue::put(Bytes::from("value1")))
+        );
+        assert_eq!(
+            merged.size(),
+            SharedBufferBatch::measure_batch_size(merged.get_payload())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_buffer_batch_split_at_key() {
+        let epoch = 3;
+        // A merged batch holding multiple versions of the user key 2.
+        let items = transform_shared_buffer(vec![
+            (
+                iterator_test_key_of_epoch(1, epoch),
+                HummockValue::put(Bytes::from("value1")),
+            ),
+            (
+                iterator_test_key_of_epoch(2, epoch),
+                HummockValue::put(Bytes::from("value2")),
+            ),
+            (
+                iterator_test_key_of_epoch(2, epoch - 1),
+                HummockValue::put(Bytes::from("value2_old")),
+            ),
+            (
+                iterator_test_key_of_epoch(3, epoch - 2),
+                HummockValue::Delete,
+            ),
+        ]);
+        let limiter = MemoryLimiter::new(1 << 20);
+        let batch =
+            SharedBufferBatch::build(items.clone(), epoch, Some(&limiter), TableId::default())
+                .await;
+        assert_eq!(limiter.get_memory_usage(), batch.size() as u64);
+
+        // The versions of a user key are never separated.
+        let (left, right) = batch
+            .clone()
+            .split_at_key(user_key(&iterator_test_key_of_epoch(2, epoch)));
+        assert_eq!(left.get_payload(), &items[..1]);
+        assert_eq!(right.get_payload(), &items[1..]);
+        assert_eq!(left.covered_batch_ids(), vec![batch.batch_id()]);
+        assert_eq!(right.epoch(), epoch);
+        assert_eq!(right.min_epoch(), epoch - 2);
+        assert_eq!(SharedBufferBatch::merge(&[left, right]), batch);
+
+        // The memory is tracked by the halves once the batch is no longer shared.
+        let (left, right) = batch.split_at_key(user_key(&iterator_test_key_of_epoch(3, epoch)));
+        assert_eq!(
+            limiter.get_memory_usage(),
+            (left.size() + right.size()) as u64
+        );
+        let merged = SharedBufferBatch::merge(&[left.clone(), right.clone()]);
+        assert_eq!(merged.get_payload(), &items[..]);
+        drop(left);
+        assert_eq!(limiter.get_memory_usage(), right.size() as u64);
+        drop(right);
+        assert_eq!(limiter.get_memory_usage(), 0);
+
+        // N-way split, including empty parts at both ends.
+        let batch = SharedBufferBatch::for_test(items.clone(), epoch, Default::default());
+        let split_keys = [0, 2, 3, 4].map(|i| iterator_test_key_of_epoch(i, epoch));
+        let split_keys = split_keys.iter().map(|k| user_key(k)).collect_vec();
+        let parts = batch.clone().split_at_keys(&split_keys);
+        assert_eq!(
+            parts
+                .iter()
+                .map(|part| part.get_payload().len())
+                .collect_vec(),
+            vec![0, 1, 2, 1, 0]
+        );
+        assert_eq!(SharedBufferBatch::merge(&parts), batch);
+    }
+
+    #[test]
+    fn test_shared_buffer_batch_checkpoint_bytes() {
+        let epoch = 3;
+        // A merged batch with values of multiple epochs and a delete.
+        let batch = SharedBufferBatch::for_test(
+            transform_shared_buffer(vec![
+                (
+                    iterator_test_key_of_epoch(1, epoch),
+                    HummockValue::put(Bytes::from("value1")),
+                ),
+                (
+                    iterator_test_key_of_epoch(1, epoch - 1),
+                    HummockValue::put(Bytes::new()),
+                ),
+                (
+                    iterator_test_key_of_epoch(2, epoch - 2),
+                    HummockValue::Delete,
+                ),
+            ]),
+            epoch,
+            Default::default(),
+        );
+        let bytes = batch.to_checkpoint_bytes();
+        let recovered =
+            SharedBufferBatch::from_checkpoint_bytes(bytes.clone(), Default::default()).unwrap();
+        assert_eq!(recovered, batch);
+        assert_eq!(recovered.epoch(), epoch);
+        assert_eq!(recovered.min_epoch(), epoch - 2);
+        assert_eq!(recovered.size(), batch.size());
+        assert_ne!(recovered.batch_id(), batch.batch_id());
+        assert_eq!(recovered.to_checkpoint_bytes(), bytes);
+
+        let empty = SharedBufferBatch::for_test(vec![], epoch, Default::default());
+        let recovered = SharedBufferBatch::from_checkpoint_bytes(
+            empty.to_checkpoint_bytes(),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(recovered.get_payload().is_empty());
+        assert_eq!(recovered.epoch(), epoch);
+
+        // Truncated or corrupted checkpoints are rejected.
+        for len in [0, 4, 12, bytes.len() - 1] {
+            SharedBufferBatch::from_checkpoint_bytes(bytes.slice(..len), Default::default())
+                .unwrap_err();
+        }
+        let mut trailing = BytesMut::from(&bytes[..]);
+        trailing.put_u8(0);
+        SharedBufferBatch::from_checkpoint_bytes(trailing.freeze(), Default::default())
+            .unwrap_err();
+        let unsorted = SharedBufferBatch::for_test(
+            transform_shared_buffer(vec![
+                (iterator_test_key_of_epoch(2, epoch), HummockValue::Delete),
+                (iterator_test_key_of_epoch(1, epoch), HummockValue::Delete),
+            ]),
+            epoch,
+            Default::default(),
+        );
+        SharedBufferBatch::from_checkpoint_bytes(
+            unsorted.to_checkpoint_bytes(),
+            Default::default(),
+        )
+        .unwrap_err();
+        // Keys without an epoch, or outside the table, are rejected.
+        let mut short_key = BytesMut::new();
+        short_key.put_u64_le(epoch);
+        short_key.put_u32_le(1);
+        short_key.put_u32_le(2);
+        short_key.put_slice(b"ab");
+        short_key.put_u32_le(1);
+        HummockValue::<&[u8]>::Delete.encode(&mut short_key);
+        SharedBufferBatch::from_checkpoint_bytes(short_key.freeze(), Default::default())
+            .unwrap_err();
+        SharedBufferBatch::from_checkpoint_bytes(bytes, TableId::new(1)).unwrap_err();
+    }
+
     #[tokio::test]
     async fn test_shared_buffer_batch_seek() {
         let epoch = 1;
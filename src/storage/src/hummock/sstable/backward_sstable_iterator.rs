@@ -67,7 +67,10 @@ impl BackwardSstableIterator {
                     &mut self.stats,
                 )
                 .await?;
-            let mut block_iter = BlockIterator::new(block);
+            let mut block_iter = BlockIterator::with_key_encoding(
+                block,
+                self.sst.value().meta.key_encoding_version(),
+            );
             if let Some(key) = seek_key {
                 block_iter.seek_le(key);
             } else {
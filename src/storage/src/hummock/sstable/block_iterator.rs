@@ -16,6 +16,7 @@ use std::cmp::Ordering;
 use std::ops::Range;
 
 use bytes::BytesMut;
+use risingwave_hummock_sdk::key::{upgrade_full_key, upgrade_full_key_into, KeyEncodingVersion};
 use risingwave_hummock_sdk::VersionedComparator;
 
 use super::KeyPrefix;
@@ -35,10 +36,21 @@ pub struct BlockIterator {
     value_range: Range<usize>,
     /// Current entry len.
     entry_len: usize,
+    /// The encoding of the keys in the block.
+    key_encoding: KeyEncodingVersion,
+    /// Current key upgraded to the latest encoding, if the block is in a legacy encoding.
+    upgraded_key: Vec<u8>,
 }
 
 impl BlockIterator {
     pub fn new(block: BlockHolder) -> Self {
+        Self::with_key_encoding(block, KeyEncodingVersion::LATEST)
+    }
+
+    /// Creates an iterator on a block whose keys are in `key_encoding`. The keys in a legacy
+    /// encoding are upgraded to the latest one, so the iterator always yields, and is always
+    /// seeked by, the keys in the latest encoding.
+    pub fn with_key_encoding(block: BlockHolder, key_encoding: KeyEncodingVersion) -> Self {
         Self {
             block,
             offset: usize::MAX,
@@ -46,6 +58,8 @@ impl BlockIterator {
             key: BytesMut::default(),
             value_range: 0..0,
             entry_len: 0,
+            key_encoding,
+            upgraded_key: vec![],
         }
     }
 
@@ -61,7 +75,7 @@ impl BlockIterator {
 
     pub fn key(&self) -> &[u8] {
         assert!(self.is_valid());
-        &self.key[..]
+        self.current_key()
     }
 
     pub fn value(&self) -> &[u8] {
@@ -98,11 +112,32 @@ impl BlockIterator {
 }
 
 impl BlockIterator {
+    fn is_legacy(&self) -> bool {
+        self.key_encoding != KeyEncodingVersion::LATEST
+    }
+
+    /// Current key in the latest encoding.
+    fn current_key(&self) -> &[u8] {
+        if self.is_legacy() {
+            &self.upgraded_key[..]
+        } else {
+            &self.key[..]
+        }
+    }
+
+    /// Upgrades the current key after it is decoded, if the block is in a legacy encoding.
+    fn upgrade_current_key(&mut self) {
+        if self.is_legacy() {
+            upgrade_full_key_into(&self.key, &mut self.upgraded_key);
+        }
+    }
+
     /// Invalidates current state after reaching a invalid state.
     fn invalidate(&mut self) {
         self.offset = self.block.len();
         self.restart_point_index = self.block.restart_point_len();
         self.key.clear();
+        self.upgraded_key.clear();
         self.value_range = 0..0;
         self.entry_len = 0;
     }
@@ -120,6 +155,7 @@ impl BlockIterator {
         self.key.truncate(prefix.overlap_len());
         self.key
             .extend_from_slice(&self.block.data()[prefix.diff_key_range()]);
+        self.upgrade_current_key();
         self.value_range = prefix.value_range();
         self.offset = offset;
         self.entry_len = prefix.entry_len();
@@ -133,7 +169,7 @@ impl BlockIterator {
     /// Moves forward until reaching the first that equals or larger than the given `key`.
     fn next_until_key(&mut self, key: &[u8]) {
         while self.is_valid()
-            && VersionedComparator::compare_key(&self.key[..], key) == Ordering::Less
+            && VersionedComparator::compare_key(self.current_key(), key) == Ordering::Less
         {
             self.next_inner();
         }
@@ -142,7 +178,7 @@ impl BlockIterator {
     /// Moves backward until reaching the first key that equals or smaller than the given `key`.
     fn prev_until_key(&mut self, key: &[u8]) {
         while self.is_valid()
-            && VersionedComparator::compare_key(&self.key[..], key) == Ordering::Greater
+            && VersionedComparator::compare_key(self.current_key(), key) == Ordering::Greater
         {
             self.prev_inner();
         }
@@ -184,7 +220,12 @@ impl BlockIterator {
             .search_restart_partition_point(|&probe| {
                 let prefix = self.decode_prefix_at(probe as usize);
                 let probe_key = &self.block.data()[prefix.diff_key_range()];
-                match VersionedComparator::compare_key(probe_key, key) {
+                let ordering = if self.is_legacy() {
+                    VersionedComparator::compare_key(&upgrade_full_key(probe_key), key)
+                } else {
+                    VersionedComparator::compare_key(probe_key, key)
+                };
+                match ordering {
                     Ordering::Less | Ordering::Equal => true,
                     Ordering::Greater => false,
                 }
@@ -203,6 +244,7 @@ impl BlockIterator {
         let offset = self.block.restart_point(index) as usize;
         let prefix = self.decode_prefix_at(offset);
         self.key = BytesMut::from(&self.block.data()[prefix.diff_key_range()]);
+        self.upgrade_current_key();
         self.value_range = prefix.value_range();
         self.offset = offset;
         self.entry_len = prefix.entry_len();
@@ -213,6 +255,8 @@ impl BlockIterator {
 #[cfg(test)]
 mod tests {
     use bytes::{BufMut, Bytes};
+    use itertools::Itertools;
+    use risingwave_hummock_sdk::key::encode_vnode;
 
     use super::*;
     use crate::hummock::{Block, BlockBuilder, BlockBuilderOptions};
@@ -356,6 +400,66 @@ mod tests {
         assert_eq!(&full_key(format!("k{:02}", 4).as_bytes(), 4)[..], it.key());
     }
 
+    #[test]
+    fn test_legacy_key_encoding() {
+        let table_key = |vnode: u8, key: &[u8], legacy: bool| {
+            let mut table_key = 1u32.to_be_bytes().to_vec();
+            if legacy {
+                table_key.push(vnode);
+            } else {
+                table_key.extend(encode_vnode(vnode));
+            }
+            table_key.extend_from_slice(key);
+            table_key
+        };
+        let entries = [0, 1, 128, 255]
+            .into_iter()
+            .flat_map(|vnode| [(vnode, &b"a"[..]), (vnode, b"b")])
+            .collect_vec();
+
+        let mut builder = BlockBuilder::new(BlockBuilderOptions {
+            restart_interval: 3,
+            ..Default::default()
+        });
+        for (vnode, key) in &entries {
+            builder.add(&full_key(&table_key(*vnode, key, true), 1), key);
+        }
+        let capacity = builder.uncompressed_block_size();
+        let buf = builder.build().to_vec();
+        let block = Box::new(Block::decode(buf.into(), capacity).unwrap());
+        let mut it = BlockIterator::with_key_encoding(
+            BlockHolder::from_owned_block(block),
+            KeyEncodingVersion::V1,
+        );
+
+        // The keys are yielded in the latest encoding.
+        it.seek_to_first();
+        for (vnode, key) in &entries {
+            assert!(it.is_valid());
+            assert_eq!(&full_key(&table_key(*vnode, key, false), 1)[..], it.key());
+            assert_eq!(*key, it.value());
+            it.next();
+        }
+        assert!(!it.is_valid());
+
+        it.seek_to_last();
+        for (vnode, key) in entries.iter().rev() {
+            assert_eq!(&full_key(&table_key(*vnode, key, false), 1)[..], it.key());
+            it.prev();
+        }
+        assert!(!it.is_valid());
+
+        // The iterator is seeked by the keys in the latest encoding.
+        it.seek(&full_key(&table_key(128, b"b", false), 1));
+        assert_eq!(&full_key(&table_key(128, b"b", false), 1)[..], it.key());
+        it.seek(&full_key(&table_key(128, b"c", false), 1));
+        assert_eq!(&full_key(&table_key(255, b"a", false), 1)[..], it.key());
+        it.seek_le(&full_key(&table_key(128, b"c", false), 1));
+        assert_eq!(&full_key(&table_key(128, b"b", false), 1)[..], it.key());
+        it.seek(&full_key(&table_key(255, b"c", false), 1));
+        assert!(!it.is_valid());
+    }
+
     pub fn full_key(user_key: &[u8], epoch: u64) -> Bytes {
         let mut buf = BytesMut::with_capacity(user_key.len() + 8);
         buf.put_slice(user_key);
@@ -12,16 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 use bytes::BytesMut;
 use risingwave_common::config::StorageConfig;
+use risingwave_hummock_sdk::add_table_stats;
 use risingwave_hummock_sdk::filter_key_extractor::{
     FilterKeyExtractorImpl, FullKeyFilterKeyExtractor,
 };
-use risingwave_hummock_sdk::key::{get_table_id, user_key};
-use risingwave_pb::hummock::SstableInfo;
+use risingwave_hummock_sdk::key::{get_table_id, user_key, KeyEncodingVersion};
+use risingwave_pb::hummock::{SstableInfo, TableStats};
 
 use super::bloom::Bloom;
 use super::utils::CompressionAlgorithm;
@@ -93,6 +94,10 @@ pub struct SstableBuilder<W: SstableWriter> {
     /// `table_id` of added keys.
     table_ids: BTreeSet<u32>,
     last_table_id: u32,
+    /// Write stats of each table, excluding those of `last_table_id`.
+    table_stats: HashMap<u32, TableStats>,
+    /// Write stats of `last_table_id`.
+    last_table_stats: TableStats,
     /// Hashes of user keys.
     user_key_hashes: Vec<u32>,
     last_full_key: Vec<u8>,
@@ -138,6 +143,8 @@ impl<W: SstableWriter> SstableBuilder<W> {
             table_ids: BTreeSet::new(),
             user_key_hashes: Vec::with_capacity(options.capacity / DEFAULT_ENTRY_SIZE + 1),
             last_table_id: 0,
+            table_stats: HashMap::new(),
+            last_table_stats: TableStats::default(),
             raw_value: BytesMut::new(),
             last_full_key: vec![],
             key_count: 0,
@@ -173,8 +180,11 @@ impl<W: SstableWriter> SstableBuilder<W> {
         if is_new_user_key {
             let mut extract_key = user_key(full_key);
             let table_id = get_table_id(full_key);
-            if self.last_table_id != table_id {
+            // `table_ids` is empty for the first key, whose table id may equal the initial
+            // `last_table_id`.
+            if self.last_table_id != table_id || self.table_ids.is_empty() {
                 self.table_ids.insert(table_id);
+                self.finish_last_table_stats();
                 self.last_table_id = table_id;
             }
             extract_key = self.filter_key_extractor.extract(extract_key);
@@ -195,7 +205,11 @@ impl<W: SstableWriter> SstableBuilder<W> {
         }
         self.total_key_count += 1;
 
+        let block_len = self.block_builder.approximate_len();
         self.block_builder.add(full_key, self.raw_value.as_ref());
+        self.last_table_stats.total_bytes +=
+            (self.block_builder.approximate_len() - block_len) as u64;
+        self.last_table_stats.total_key_count += 1;
         self.total_key_size += full_key.len();
         self.total_value_size += self.raw_value.len();
         self.raw_value.clear();
@@ -228,6 +242,7 @@ impl<W: SstableWriter> SstableBuilder<W> {
         let largest_key = self.last_full_key.clone();
 
         self.build_block().await?;
+        self.finish_last_table_stats();
         let meta_offset = self.writer.data_len() as u64;
         assert!(!smallest_key.is_empty());
 
@@ -262,6 +277,8 @@ impl<W: SstableWriter> SstableBuilder<W> {
             stale_key_count: self.stale_key_count,
             total_key_count: self.total_key_count,
             divide_version: 0,
+            table_stats: self.table_stats,
+            key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
         };
         tracing::trace!(
             "meta_size {} bloom_filter_size {}  add_key_counts {} ",
@@ -283,6 +300,17 @@ impl<W: SstableWriter> SstableBuilder<W> {
         })
     }
 
+    fn finish_last_table_stats(&mut self) {
+        if self.last_table_stats.total_key_count == 0 {
+            return;
+        }
+        let last_table_stats = std::mem::take(&mut self.last_table_stats);
+        add_table_stats(
+            self.table_stats.entry(self.last_table_id).or_default(),
+            &last_table_stats,
+        );
+    }
+
     pub fn approximate_len(&self) -> usize {
         self.writer.data_len()
             + self.block_builder.approximate_len()
@@ -320,6 +348,8 @@ impl<W: SstableWriter> SstableBuilder<W> {
 
 #[cfg(test)]
 pub(super) mod tests {
+    use risingwave_hummock_sdk::key::{key_with_epoch, table_prefix};
+
     use super::*;
     use crate::hummock::iterator::test_utils::mock_sstable_store;
     use crate::hummock::test_utils::{
@@ -369,6 +399,45 @@ pub(super) mod tests {
         assert_eq!(meta2, meta);
     }
 
+    #[tokio::test]
+    async fn test_table_stats() {
+        let opt = default_builder_opt_for_test();
+        let mut b = SstableBuilder::for_test(0, mock_sst_writer(&opt), opt);
+
+        let table_ids = [1, 2, 3];
+        let key_count_per_table = 1000;
+        for table_id in table_ids {
+            for i in 0..key_count_per_table {
+                let mut user_key = table_prefix(table_id);
+                user_key.extend_from_slice(format!("key_test_{:05}", i).as_bytes());
+                // Write one new version and one stale version for each user key.
+                for (epoch, is_new_user_key) in [(2, true), (1, false)] {
+                    b.add(
+                        &key_with_epoch(user_key.clone(), epoch),
+                        HummockValue::put(&test_value_of(i)),
+                        is_new_user_key,
+                    )
+                    .await
+                    .unwrap();
+                }
+            }
+        }
+
+        let info = b.finish().await.unwrap().sst_info;
+        assert_eq!(info.table_stats.len(), table_ids.len());
+        for table_id in table_ids {
+            assert_eq!(
+                info.table_stats[&table_id].total_key_count,
+                key_count_per_table as u64 * 2
+            );
+        }
+
+        // The per-table bytes only exclude block and sstable meta, which should be small.
+        let total_bytes: u64 = info.table_stats.values().map(|s| s.total_bytes).sum();
+        assert!(total_bytes <= info.file_size);
+        assert!(total_bytes as f64 >= info.file_size as f64 * 0.9);
+    }
+
     async fn test_with_bloom_filter(with_blooms: bool) {
         let key_count = 1000;
 
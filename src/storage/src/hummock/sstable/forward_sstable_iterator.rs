@@ -88,7 +88,10 @@ impl SstableIterator {
                     &mut self.stats,
                 )
                 .await?;
-            let mut block_iter = BlockIterator::new(block);
+            let mut block_iter = BlockIterator::with_key_encoding(
+                block,
+                self.sst.value().meta.key_encoding_version(),
+            );
             if let Some(key) = seek_key {
                 block_iter.seek(key);
             } else {
@@ -189,15 +192,19 @@ impl SstableIteratorType for SstableIterator {
 mod tests {
     use itertools::Itertools;
     use rand::prelude::*;
-    use risingwave_hummock_sdk::key::key_with_epoch;
+    use risingwave_hummock_sdk::key::{encode_vnode, key_with_epoch, user_key, KeyEncodingVersion};
 
     use super::*;
     use crate::assert_bytes_eq;
     use crate::hummock::iterator::test_utils::mock_sstable_store;
+    use crate::hummock::iterator::UnorderedMergeIteratorInner;
+    use crate::hummock::sstable::LEGACY_VERSION;
     use crate::hummock::test_utils::{
-        create_small_table_cache, default_builder_opt_for_test, gen_default_test_sstable,
-        gen_test_sstable, prefixed_key, test_key_of, test_value_of, TEST_KEYS_COUNT,
+        create_small_table_cache, default_builder_opt_for_test, default_writer_opt_for_test,
+        gen_default_test_sstable, gen_test_sstable, gen_test_sstable_data, prefixed_key, put_sst,
+        test_key_of, test_value_of, TEST_KEYS_COUNT,
     };
+    use crate::hummock::SstableBuilderOptions;
 
     async fn inner_test_forward_iterator(sstable_store: SstableStoreRef, handle: TableHolder) {
         // We should have at least 10 blocks, so that sstable iterator test could cover more code
@@ -352,4 +359,192 @@ mod tests {
         }
         assert_eq!(cnt, TEST_KEYS_COUNT);
     }
+
+    /// `table_id (4B) | vnode | user key`, where the vnode is 1 byte in the legacy encoding.
+    fn migration_test_key(vnode: u8, idx: usize, epoch: u64, legacy: bool) -> Vec<u8> {
+        let mut table_key = 1u32.to_be_bytes().to_vec();
+        if legacy {
+            table_key.push(vnode);
+        } else {
+            table_key.extend(encode_vnode(vnode));
+        }
+        table_key.extend_from_slice(format!("key_test_{:05}", idx).as_bytes());
+        key_with_epoch(table_key, epoch)
+    }
+
+    fn migration_test_kvs(vnodes: &[u8], epoch: u64, legacy: bool) -> Vec<(Vec<u8>, Vec<u8>)> {
+        vnodes
+            .iter()
+            .flat_map(|&vnode| {
+                (0..100).map(move |idx| {
+                    (
+                        migration_test_key(vnode, idx, epoch, legacy),
+                        format!("value_{}_{}_{}", vnode, idx, epoch).into_bytes(),
+                    )
+                })
+            })
+            .collect_vec()
+    }
+
+    fn into_put_kvs(
+        kvs: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> impl Iterator<Item = (Vec<u8>, HummockValue<Vec<u8>>)> {
+        kvs.into_iter()
+            .map(|(key, value)| (key, HummockValue::put(value)))
+    }
+
+    async fn collect_kvs(
+        iter: &mut impl HummockIterator<Direction = Forward>,
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut kvs = vec![];
+        iter.rewind().await.unwrap();
+        while iter.is_valid() {
+            kvs.push((
+                iter.key().to_vec(),
+                iter.value().into_user_value().unwrap().to_vec(),
+            ));
+            iter.next().await.unwrap();
+        }
+        kvs
+    }
+
+    #[tokio::test]
+    async fn test_key_encoding_migration() {
+        let sstable_store = mock_sstable_store();
+        let read_options = Arc::new(SstableIteratorReadOptions::default());
+        let opts = SstableBuilderOptions {
+            block_capacity: 1024,
+            restart_interval: 4,
+            ..default_builder_opt_for_test()
+        };
+        let vnodes = [0, 1, 128, 255];
+        let mut stats = StoreLocalStatistic::default();
+
+        // An SST written in the legacy encoding.
+        let (data, mut meta) = gen_test_sstable_data(
+            opts.clone(),
+            into_put_kvs(migration_test_kvs(&vnodes, 1, true)),
+        )
+        .await;
+        meta.version = LEGACY_VERSION;
+        let legacy_sst_info = put_sst(
+            1,
+            data,
+            meta,
+            sstable_store.clone(),
+            default_writer_opt_for_test(),
+        )
+        .await
+        .unwrap();
+        let legacy_sst = sstable_store
+            .sstable(&legacy_sst_info, &mut stats)
+            .await
+            .unwrap();
+        assert_eq!(
+            legacy_sst.value().meta.key_encoding_version(),
+            KeyEncodingVersion::V1
+        );
+        assert!(legacy_sst.value().meta.block_metas.len() > 1);
+
+        // Before the migration, the legacy SST is read in the latest encoding.
+        let expected_legacy_kvs = migration_test_kvs(&vnodes, 1, false);
+        let mut legacy_iter =
+            SstableIterator::create(legacy_sst, sstable_store.clone(), read_options.clone());
+        assert_eq!(collect_kvs(&mut legacy_iter).await, expected_legacy_kvs);
+        for (key, value) in &expected_legacy_kvs {
+            legacy_iter.seek(key).await.unwrap();
+            assert_eq!(legacy_iter.key(), &key[..]);
+            assert_eq!(legacy_iter.value().into_user_value().unwrap(), &value[..]);
+            assert!(!legacy_iter
+                .sst
+                .value()
+                .surely_not_have_user_key(user_key(key)));
+        }
+
+        // During the migration, the legacy SST is read together with an SST in the latest
+        // encoding, which overwrites some of its keys.
+        let latest_kvs = migration_test_kvs(&vnodes[1..3], 2, false);
+        let latest_sst = gen_test_sstable(
+            opts.clone(),
+            2,
+            into_put_kvs(latest_kvs.clone()),
+            sstable_store.clone(),
+        )
+        .await;
+        assert_eq!(
+            latest_sst.meta.key_encoding_version(),
+            KeyEncodingVersion::LATEST
+        );
+        let latest_sst_info = latest_sst.get_sstable_info();
+        let mut expected_mixed_kvs = expected_legacy_kvs
+            .iter()
+            .cloned()
+            .chain(latest_kvs)
+            .collect_vec();
+        expected_mixed_kvs.sort_by(|(a, _), (b, _)| VersionedComparator::compare_key(a, b));
+        let mut mixed_iter = UnorderedMergeIteratorInner::new([
+            SstableIterator::create(
+                sstable_store
+                    .sstable(&legacy_sst_info, &mut stats)
+                    .await
+                    .unwrap(),
+                sstable_store.clone(),
+                read_options.clone(),
+            ),
+            SstableIterator::create(
+                sstable_store
+                    .sstable(&latest_sst_info, &mut stats)
+                    .await
+                    .unwrap(),
+                sstable_store.clone(),
+                read_options.clone(),
+            ),
+        ]);
+        assert_eq!(collect_kvs(&mut mixed_iter).await, expected_mixed_kvs);
+
+        // The legacy SST is rewritten by a compaction, whose output is in the latest encoding and
+        // yields the same keys.
+        let rewritten_sst = gen_test_sstable(
+            opts,
+            3,
+            into_put_kvs(expected_legacy_kvs.clone()),
+            sstable_store.clone(),
+        )
+        .await;
+        assert_eq!(
+            rewritten_sst.meta.key_encoding_version(),
+            KeyEncodingVersion::LATEST
+        );
+        let rewritten_sst_info = rewritten_sst.get_sstable_info();
+        let mut rewritten_iter = SstableIterator::create(
+            sstable_store
+                .sstable(&rewritten_sst_info, &mut stats)
+                .await
+                .unwrap(),
+            sstable_store.clone(),
+            read_options.clone(),
+        );
+        assert_eq!(collect_kvs(&mut rewritten_iter).await, expected_legacy_kvs);
+
+        // After the migration, the reads are the same as during it.
+        let mut migrated_iter = UnorderedMergeIteratorInner::new([
+            SstableIterator::create(
+                sstable_store
+                    .sstable(&rewritten_sst_info, &mut stats)
+                    .await
+                    .unwrap(),
+                sstable_store.clone(),
+                read_options.clone(),
+            ),
+            SstableIterator::create(
+                sstable_store
+                    .sstable(&latest_sst_info, &mut stats)
+                    .await
+                    .unwrap(),
+                sstable_store.clone(),
+                read_options,
+            ),
+        ]);
+        assert_eq!(collect_kvs(&mut migrated_iter).await, expected_mixed_kvs);
+    }
 }
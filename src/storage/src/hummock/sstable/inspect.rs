@@ -0,0 +1,479 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decodes the content of SSTs for debugging, which is shared by `risectl hummock describe-sst`
+//! and `risectl hummock dump-sst`.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Bound, RangeBounds};
+
+use bytes::Buf;
+use risingwave_hummock_sdk::key::{get_epoch, get_table_id, user_key, TABLE_PREFIX_LEN};
+use risingwave_hummock_sdk::{HummockEpoch, HummockSstableId};
+use risingwave_object_store::object::BlockLocation;
+
+use super::{BlockIterator, Sstable, SstableMeta};
+use crate::hummock::sstable_store::SstableStore;
+use crate::hummock::value::HummockValue;
+use crate::hummock::{CachePolicy, HummockError, HummockResult};
+use crate::monitor::StoreLocalStatistic;
+
+/// Length of the trailing fields of an SST: meta offset (8B), checksum (8B), version (4B) and
+/// magic (4B). See [`SstableMeta::encode_to`].
+const FOOTER_LEN: usize = 8 + 8 + 4 + 4;
+
+/// Summary of an SST, including the fields stored in [`SstableMeta`] and the statistics collected
+/// by iterating all its keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SstableDescription {
+    pub id: HummockSstableId,
+    pub file_size: u64,
+    pub meta_offset: u64,
+    pub version: u32,
+    pub block_count: usize,
+    pub smallest_key: Vec<u8>,
+    pub largest_key: Vec<u8>,
+    pub bloom_filter_size: usize,
+    pub table_ids: Vec<u32>,
+    pub total_key_count: u64,
+    /// Number of keys that are older versions of their previous keys.
+    pub stale_key_count: u64,
+    pub delete_key_count: u64,
+}
+
+impl Display for SstableDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "SST id: {}", self.id)?;
+        writeln!(f, "File Size: {}", self.file_size)?;
+        writeln!(f, "Meta Offset: {}", self.meta_offset)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Block Count: {}", self.block_count)?;
+        writeln!(f, "Key Range:")?;
+        writeln!(f, "\tleft:\t{}", FullKeyDisplay(&self.smallest_key))?;
+        writeln!(f, "\tright:\t{}", FullKeyDisplay(&self.largest_key))?;
+        writeln!(f, "Table Ids: {:?}", self.table_ids)?;
+        writeln!(f, "Bloom Filter Size: {}", self.bloom_filter_size)?;
+        writeln!(f, "Total Key Count: {}", self.total_key_count)?;
+        writeln!(f, "Stale Key Count: {}", self.stale_key_count)?;
+        write!(f, "Delete Key Count: {}", self.delete_key_count)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Put,
+    Delete,
+}
+
+/// A key-value pair of an SST with its full key decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedKeyValue {
+    pub table_id: u32,
+    /// The user key without the table id prefix.
+    pub user_key: Vec<u8>,
+    pub epoch: HummockEpoch,
+    pub value_type: ValueType,
+    /// Empty for `Delete`.
+    pub value: Vec<u8>,
+}
+
+impl DecodedKeyValue {
+    pub fn decode(full_key: &[u8], raw_value: &[u8]) -> HummockResult<Self> {
+        let (value_type, value) = match HummockValue::from_slice(raw_value)? {
+            HummockValue::Put(value) => (ValueType::Put, value.to_vec()),
+            HummockValue::Delete => (ValueType::Delete, vec![]),
+        };
+        Ok(Self {
+            table_id: get_table_id(full_key),
+            user_key: user_key(full_key)[TABLE_PREFIX_LEN..].to_vec(),
+            epoch: get_epoch(full_key),
+            value_type,
+            value,
+        })
+    }
+}
+
+impl Display for DecodedKeyValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "table: {}, key: {}, epoch: {}, type: {:?}",
+            self.table_id,
+            BytesDisplay(&self.user_key),
+            self.epoch,
+            self.value_type
+        )?;
+        if self.value_type == ValueType::Put {
+            write!(f, ", value: {}", BytesDisplay(&self.value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays bytes in hex, followed by the best-effort UTF-8 string with non-printable bytes
+/// escaped.
+pub struct BytesDisplay<'a>(pub &'a [u8]);
+
+impl Display for BytesDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, " ({})", self.0.escape_ascii())
+    }
+}
+
+/// Displays a full key as its table id, user key and epoch.
+struct FullKeyDisplay<'a>(&'a [u8]);
+
+impl Display for FullKeyDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.len() < TABLE_PREFIX_LEN + std::mem::size_of::<HummockEpoch>() {
+            return write!(f, "{}", BytesDisplay(self.0));
+        }
+        write!(
+            f,
+            "table: {}, key: {}, epoch: {}",
+            get_table_id(self.0),
+            BytesDisplay(&user_key(self.0)[TABLE_PREFIX_LEN..]),
+            get_epoch(self.0)
+        )
+    }
+}
+
+/// Filters the key-value pairs to dump.
+#[derive(Debug, Clone)]
+pub struct DumpFilter {
+    pub table_id: Option<u32>,
+    /// Range of the user keys without the table id prefix.
+    pub user_key_range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+    /// The maximum number of key-value pairs to return.
+    pub limit: Option<usize>,
+}
+
+impl Default for DumpFilter {
+    fn default() -> Self {
+        Self {
+            table_id: None,
+            user_key_range: (Bound::Unbounded, Bound::Unbounded),
+            limit: None,
+        }
+    }
+}
+
+impl DumpFilter {
+    fn matches(&self, kv: &DecodedKeyValue) -> bool {
+        self.table_id
+            .map_or(true, |table_id| table_id == kv.table_id)
+            && self.user_key_range.contains(&kv.user_key)
+    }
+}
+
+/// Parses a user key range in the form of `<start>..<end>`, where `start` (inclusive) and `end`
+/// (exclusive) are hex strings and can be omitted for unbounded.
+pub fn parse_user_key_range(range: &str) -> HummockResult<(Bound<Vec<u8>>, Bound<Vec<u8>>)> {
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| HummockError::other(format!("invalid key range: {}", range)))?;
+    let parse_bound = |hex: &str, bound: fn(Vec<u8>) -> Bound<Vec<u8>>| {
+        if hex.is_empty() {
+            Ok(Bound::Unbounded)
+        } else {
+            decode_hex(hex).map(bound)
+        }
+    };
+    Ok((
+        parse_bound(start, Bound::Included)?,
+        parse_bound(end, Bound::Excluded)?,
+    ))
+}
+
+fn decode_hex(hex: &str) -> HummockResult<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return Err(HummockError::other(format!("invalid hex string: {}", hex)));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| HummockError::other(format!("invalid hex string: {}", hex)))
+        })
+        .collect()
+}
+
+/// Loads the meta of the SST with `sst_id` directly from the object store, without consulting the
+/// version for its `SstableInfo`. Returns the SST and the size of its object.
+pub async fn load_sstable(
+    sstable_store: &SstableStore,
+    sst_id: HummockSstableId,
+) -> HummockResult<(Sstable, u64)> {
+    let path = sstable_store.get_sst_data_path(sst_id);
+    let store = sstable_store.store();
+    let file_size = store
+        .metadata(&path)
+        .await
+        .map_err(HummockError::object_io_error)?
+        .total_size;
+    if file_size < FOOTER_LEN {
+        return Err(HummockError::decode_error(format!(
+            "object of SST {} is too small: {} bytes",
+            sst_id, file_size
+        )));
+    }
+
+    let footer = store
+        .read(
+            &path,
+            Some(BlockLocation {
+                offset: file_size - FOOTER_LEN,
+                size: 8,
+            }),
+        )
+        .await
+        .map_err(HummockError::object_io_error)?;
+    let meta_offset = (&footer[..]).get_u64_le() as usize;
+    if meta_offset >= file_size - FOOTER_LEN {
+        return Err(HummockError::decode_error(format!(
+            "invalid meta offset of SST {}: {}",
+            sst_id, meta_offset
+        )));
+    }
+
+    let buf = store
+        .read(
+            &path,
+            Some(BlockLocation {
+                offset: meta_offset,
+                size: file_size - meta_offset,
+            }),
+        )
+        .await
+        .map_err(HummockError::object_io_error)?;
+    let meta = SstableMeta::decode(&mut &buf[..])?;
+    Ok((Sstable::new(sst_id, meta), file_size as u64))
+}
+
+/// Iterates all the key-value pairs of the SST in order, until `f` returns `false`.
+async fn for_each_key_value(
+    sstable_store: &SstableStore,
+    sst: &Sstable,
+    mut f: impl FnMut(&[u8], &[u8]) -> HummockResult<bool>,
+) -> HummockResult<()> {
+    let mut stats = StoreLocalStatistic::default();
+    for block_index in 0..sst.meta.block_metas.len() {
+        let block = sstable_store
+            .get(sst, block_index as u64, CachePolicy::Disable, &mut stats)
+            .await?;
+        let mut block_iter =
+            BlockIterator::with_key_encoding(block, sst.meta.key_encoding_version());
+        block_iter.seek_to_first();
+        while block_iter.is_valid() {
+            if !f(block_iter.key(), block_iter.value())? {
+                return Ok(());
+            }
+            block_iter.next();
+        }
+    }
+    Ok(())
+}
+
+/// Describes the SST with `sst_id`, by loading its meta and iterating all its keys.
+pub async fn describe_sstable(
+    sstable_store: &SstableStore,
+    sst_id: HummockSstableId,
+) -> HummockResult<SstableDescription> {
+    let (sst, file_size) = load_sstable(sstable_store, sst_id).await?;
+
+    let mut table_ids = BTreeSet::new();
+    let mut total_key_count = 0;
+    let mut stale_key_count = 0;
+    let mut delete_key_count = 0;
+    let mut last_user_key: Option<Vec<u8>> = None;
+    for_each_key_value(sstable_store, &sst, |full_key, raw_value| {
+        let current_user_key = user_key(full_key);
+        if last_user_key.as_deref() == Some(current_user_key) {
+            stale_key_count += 1;
+        } else {
+            last_user_key = Some(current_user_key.to_vec());
+        }
+        table_ids.insert(get_table_id(full_key));
+        if matches!(HummockValue::from_slice(raw_value)?, HummockValue::Delete) {
+            delete_key_count += 1;
+        }
+        total_key_count += 1;
+        Ok(true)
+    })
+    .await?;
+
+    let meta = &sst.meta;
+    Ok(SstableDescription {
+        id: sst_id,
+        file_size,
+        meta_offset: meta.meta_offset,
+        version: meta.version,
+        block_count: meta.block_metas.len(),
+        smallest_key: meta.smallest_key.clone(),
+        largest_key: meta.largest_key.clone(),
+        bloom_filter_size: meta.bloom_filter.len(),
+        table_ids: table_ids.into_iter().collect(),
+        total_key_count,
+        stale_key_count,
+        delete_key_count,
+    })
+}
+
+/// Decodes the key-value pairs of the SST with `sst_id` that pass the `filter`, in order.
+pub async fn dump_sstable(
+    sstable_store: &SstableStore,
+    sst_id: HummockSstableId,
+    filter: &DumpFilter,
+) -> HummockResult<Vec<DecodedKeyValue>> {
+    let (sst, _) = load_sstable(sstable_store, sst_id).await?;
+
+    let limit = filter.limit.unwrap_or(usize::MAX);
+    let mut kvs = vec![];
+    if limit > 0 {
+        for_each_key_value(sstable_store, &sst, |full_key, raw_value| {
+            let kv = DecodedKeyValue::decode(full_key, raw_value)?;
+            if filter.matches(&kv) {
+                kvs.push(kv);
+            }
+            Ok(kvs.len() < limit)
+        })
+        .await?;
+    }
+    Ok(kvs)
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_hummock_sdk::key::{key_with_epoch, table_prefix};
+
+    use super::*;
+    use crate::hummock::iterator::test_utils::mock_sstable_store;
+    use crate::hummock::test_utils::{default_builder_opt_for_test, gen_test_sstable};
+    use crate::hummock::SstableStoreRef;
+
+    fn full_key(table_id: u32, key: &[u8], epoch: HummockEpoch) -> Vec<u8> {
+        key_with_epoch([table_prefix(table_id).as_slice(), key].concat(), epoch)
+    }
+
+    async fn gen_sstable_for_inspect(sstable_store: SstableStoreRef) -> HummockSstableId {
+        let sst_id = 1;
+        let kvs = vec![
+            (full_key(1, b"aaa", 2), HummockValue::put(b"v2".to_vec())),
+            (full_key(1, b"aaa", 1), HummockValue::delete()),
+            (full_key(1, b"bbb", 1), HummockValue::put(b"v1".to_vec())),
+            (
+                full_key(2, b"\x00\xff", 1),
+                HummockValue::put(b"v3".to_vec()),
+            ),
+        ];
+        gen_test_sstable(
+            default_builder_opt_for_test(),
+            sst_id,
+            kvs.into_iter(),
+            sstable_store,
+        )
+        .await;
+        sst_id
+    }
+
+    #[tokio::test]
+    async fn test_describe_sstable() {
+        let sstable_store = mock_sstable_store();
+        let sst_id = gen_sstable_for_inspect(sstable_store.clone()).await;
+
+        let description = describe_sstable(&sstable_store, sst_id).await.unwrap();
+        assert_eq!(description.id, sst_id);
+        assert_eq!(description.block_count, 1);
+        assert_eq!(description.smallest_key, full_key(1, b"aaa", 2));
+        assert_eq!(description.largest_key, full_key(2, b"\x00\xff", 1));
+        assert!(description.bloom_filter_size > 0);
+        assert_eq!(description.table_ids, vec![1, 2]);
+        assert_eq!(description.total_key_count, 4);
+        assert_eq!(description.stale_key_count, 1);
+        assert_eq!(description.delete_key_count, 1);
+
+        assert!(describe_sstable(&sstable_store, sst_id + 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dump_sstable() {
+        let sstable_store = mock_sstable_store();
+        let sst_id = gen_sstable_for_inspect(sstable_store.clone()).await;
+
+        let kvs = dump_sstable(&sstable_store, sst_id, &DumpFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(kvs.len(), 4);
+        assert_eq!(
+            kvs[1],
+            DecodedKeyValue {
+                table_id: 1,
+                user_key: b"aaa".to_vec(),
+                epoch: 1,
+                value_type: ValueType::Delete,
+                value: vec![],
+            }
+        );
+        assert_eq!(
+            kvs[3].to_string(),
+            "table: 2, key: 00ff (\\x00\\xff), epoch: 1, type: Put, value: 7633 (v3)"
+        );
+
+        let filter = DumpFilter {
+            table_id: Some(1),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let kvs = dump_sstable(&sstable_store, sst_id, &filter).await.unwrap();
+        assert_eq!(kvs.len(), 2);
+        assert!(kvs.iter().all(|kv| kv.user_key == b"aaa"));
+
+        let filter = DumpFilter {
+            table_id: Some(1),
+            user_key_range: parse_user_key_range("626262..").unwrap(),
+            ..Default::default()
+        };
+        let kvs = dump_sstable(&sstable_store, sst_id, &filter).await.unwrap();
+        assert_eq!(kvs.len(), 1);
+        assert_eq!(kvs[0].user_key, b"bbb");
+        assert_eq!(kvs[0].value, b"v1");
+    }
+
+    #[test]
+    fn test_parse_user_key_range() {
+        assert_eq!(
+            parse_user_key_range("..").unwrap(),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
+        assert_eq!(
+            parse_user_key_range("00ff..0100").unwrap(),
+            (
+                Bound::Included(vec![0x00, 0xff]),
+                Bound::Excluded(vec![0x01, 0x00])
+            )
+        );
+        assert_eq!(
+            parse_user_key_range("..Ab").unwrap(),
+            (Bound::Unbounded, Bound::Excluded(vec![0xab]))
+        );
+        assert!(parse_user_key_range("00ff").is_err());
+        assert!(parse_user_key_range("0..").is_err());
+        assert!(parse_user_key_range("zz..").is_err());
+    }
+}
@@ -29,12 +29,14 @@ pub use builder::*;
 pub mod writer;
 pub use writer::*;
 mod forward_sstable_iterator;
+pub mod inspect;
 pub mod multi_builder;
 use bytes::{Buf, BufMut};
 use fail::fail_point;
 pub use forward_sstable_iterator::*;
 mod backward_sstable_iterator;
 pub use backward_sstable_iterator::*;
+use risingwave_hummock_sdk::key::{downgrade_user_key, upgrade_full_key, KeyEncodingVersion};
 use risingwave_hummock_sdk::HummockSstableId;
 #[cfg(test)]
 use risingwave_pb::hummock::{KeyRange, SstableInfo};
@@ -42,15 +44,20 @@ use risingwave_pb::hummock::{KeyRange, SstableInfo};
 mod sstable_id_manager;
 mod utils;
 pub use sstable_id_manager::*;
+use utils::get_length_prefixed_slice;
 pub use utils::CompressionAlgorithm;
-use utils::{get_length_prefixed_slice, put_length_prefixed_slice};
+pub(crate) use utils::{
+    put_length_prefixed_slice, try_get_length_prefixed_bytes, try_get_u32_le, try_get_u64_le,
+};
 
 use self::utils::{xxhash64_checksum, xxhash64_verify};
 use super::{HummockError, HummockResult};
 
 const DEFAULT_META_BUFFER_CAPACITY: usize = 4096;
 const MAGIC: u32 = 0x5785ab73;
-const VERSION: u32 = 1;
+const VERSION: u32 = 2;
+/// The format version of the SSTs whose keys are in [`KeyEncodingVersion::V1`].
+const LEGACY_VERSION: u32 = 1;
 
 /// [`Sstable`] is a handle for accessing SST.
 #[derive(Clone)]
@@ -83,7 +90,14 @@ impl Sstable {
             true
         };
         if enable_bloom_filter() && self.has_bloom_filter() {
-            let hash = farmhash::fingerprint32(user_key);
+            let hash = match self.meta.key_encoding_version() {
+                KeyEncodingVersion::V2 => farmhash::fingerprint32(user_key),
+                // The bloom filter of a legacy SST is built on the keys before the upgrade.
+                KeyEncodingVersion::V1 => match downgrade_user_key(user_key) {
+                    Some(user_key) => farmhash::fingerprint32(&user_key),
+                    None => return true,
+                },
+            };
             let bloom = Bloom::new(&self.meta.bloom_filter);
             bloom.surely_not_have_hash(hash)
         } else {
@@ -114,6 +128,8 @@ impl Sstable {
             stale_key_count: 0,
             total_key_count: self.meta.key_count as u64,
             divide_version: 0,
+            table_stats: Default::default(),
+            key_encoding_version: self.meta.key_encoding_version().to_u32(),
         }
     }
 }
@@ -203,7 +219,7 @@ impl SstableMeta {
         buf.put_u64_le(self.meta_offset);
         let checksum = xxhash64_checksum(&buf[start_offset..]);
         buf.put_u64_le(checksum);
-        buf.put_u32_le(VERSION);
+        buf.put_u32_le(self.version);
         buf.put_u32_le(MAGIC);
     }
 
@@ -218,7 +234,7 @@ impl SstableMeta {
 
         cursor -= 4;
         let version = (&buf[cursor..cursor + 4]).get_u32_le();
-        if version != VERSION {
+        if version != VERSION && version != LEGACY_VERSION {
             return Err(HummockError::invalid_format_version(version));
         }
 
@@ -239,7 +255,7 @@ impl SstableMeta {
         let largest_key = get_length_prefixed_slice(buf);
         let meta_offset = buf.get_u64_le();
 
-        Ok(Self {
+        let mut meta = Self {
             block_metas,
             bloom_filter,
             estimated_size,
@@ -248,7 +264,34 @@ impl SstableMeta {
             largest_key,
             meta_offset,
             version,
-        })
+        };
+        if version == LEGACY_VERSION {
+            meta.upgrade_keys();
+        }
+        Ok(meta)
+    }
+
+    /// The encoding of the keys in the blocks. The keys of the meta itself are always in the
+    /// latest encoding, as the ones of a legacy SST are upgraded on decoding.
+    pub fn key_encoding_version(&self) -> KeyEncodingVersion {
+        if self.version == LEGACY_VERSION {
+            KeyEncodingVersion::V1
+        } else {
+            KeyEncodingVersion::LATEST
+        }
+    }
+
+    /// Upgrades the keys of the meta of a legacy SST, so that the SST can be located and searched
+    /// by the keys in the latest encoding. The blocks are upgraded by [`BlockIterator`] instead.
+    ///
+    /// The meta is kept in the legacy format version to tell the encoding of its blocks, and must
+    /// not be encoded again.
+    fn upgrade_keys(&mut self) {
+        for block_meta in &mut self.block_metas {
+            block_meta.smallest_key = upgrade_full_key(&block_meta.smallest_key);
+        }
+        self.smallest_key = upgrade_full_key(&self.smallest_key);
+        self.largest_key = upgrade_full_key(&self.largest_key);
     }
 
     #[inline]
@@ -281,6 +324,9 @@ pub struct SstableIteratorReadOptions {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use risingwave_hummock_sdk::key::key_with_epoch;
+
     use super::*;
 
     #[test]
@@ -314,4 +360,61 @@ mod tests {
         let decoded_meta = SstableMeta::decode(&mut &buf[..]).unwrap();
         assert_eq!(decoded_meta, meta);
     }
+
+    #[test]
+    pub fn test_legacy_sstable_meta_dec() {
+        // `table_id (4B) | vnode (1B) | user key` in the legacy encoding.
+        let legacy_key = |vnode: u8, user_key: &[u8]| {
+            let mut table_key = 1u32.to_be_bytes().to_vec();
+            table_key.push(vnode);
+            table_key.extend_from_slice(user_key);
+            key_with_epoch(table_key, 233)
+        };
+        let meta = SstableMeta {
+            block_metas: vec![
+                BlockMeta {
+                    smallest_key: legacy_key(0, b"smallest-key"),
+                    offset: 0,
+                    len: 100,
+                    uncompressed_size: 0,
+                },
+                BlockMeta {
+                    smallest_key: legacy_key(5, b"some-key"),
+                    offset: 100,
+                    len: 100,
+                    uncompressed_size: 0,
+                },
+            ],
+            bloom_filter: b"0123456789".to_vec(),
+            estimated_size: 123,
+            key_count: 123,
+            smallest_key: legacy_key(0, b"smallest-key"),
+            largest_key: legacy_key(9, b"largest-key"),
+            meta_offset: 123,
+            version: LEGACY_VERSION,
+        };
+        let buf = meta.encode_to_bytes();
+        let decoded_meta = SstableMeta::decode(&mut &buf[..]).unwrap();
+        assert_eq!(decoded_meta.key_encoding_version(), KeyEncodingVersion::V1);
+        assert_eq!(decoded_meta.version, LEGACY_VERSION);
+        for (decoded_block_meta, block_meta) in decoded_meta
+            .block_metas
+            .iter()
+            .zip_eq(meta.block_metas.iter())
+        {
+            assert_eq!(
+                decoded_block_meta.smallest_key,
+                upgrade_full_key(&block_meta.smallest_key)
+            );
+            assert_eq!(decoded_block_meta.offset, block_meta.offset);
+        }
+        assert_eq!(
+            decoded_meta.smallest_key,
+            upgrade_full_key(&meta.smallest_key)
+        );
+        assert_eq!(
+            decoded_meta.largest_key,
+            upgrade_full_key(&meta.largest_key)
+        );
+    }
 }
@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::Arc;
 
-use risingwave_hummock_sdk::key::FullKey;
+use risingwave_hummock_sdk::key::{get_table_id, FullKey};
 use risingwave_hummock_sdk::HummockEpoch;
 use risingwave_pb::hummock::SstableInfo;
 use tokio::task::JoinHandle;
@@ -46,6 +47,12 @@ pub struct SplitTableOutput {
 /// A wrapper for [`SstableBuilder`] which automatically split key-value pairs into multiple tables,
 /// based on their target capacity set in options.
 ///
+/// Besides the capacity, which is a hard cap checked before each new user key, the output can also
+/// be cut at table boundaries, i.e. where the table id prefix of the keys changes:
+/// - the boundaries of `split_table_ids` are always cut, so that these tables never share an SST
+///   with others.
+/// - other boundaries are cut once the current SST reaches `table_split_size`.
+///
 /// When building is finished, one may call `finish` to get the results of zero, one or more tables.
 pub struct CapacitySplitTableBuilder<F>
 where
@@ -63,6 +70,16 @@ where
 
     /// Update the number of sealed Sstables.
     task_progress: Option<Arc<TaskProgress>>,
+
+    /// Tables that are likely to be moved to other compaction groups, whose boundaries are always
+    /// cut.
+    split_table_ids: HashSet<u32>,
+
+    /// Soft size threshold, beyond which the current SST is sealed at the next table boundary.
+    table_split_size: Option<usize>,
+
+    /// Table id of the last added key.
+    last_table_id: Option<u32>,
 }
 
 impl<F> CapacitySplitTableBuilder<F>
@@ -74,6 +91,8 @@ where
         builder_factory: F,
         stats: Arc<StateStoreMetrics>,
         task_progress: Option<Arc<TaskProgress>>,
+        split_table_ids: HashSet<u32>,
+        table_split_size: Option<usize>,
     ) -> Self {
         Self {
             builder_factory,
@@ -81,6 +100,9 @@ where
             current_builder: None,
             stats,
             task_progress,
+            split_table_ids,
+            table_split_size,
+            last_table_id: None,
         }
     }
 
@@ -91,6 +113,9 @@ where
             current_builder: None,
             stats: Arc::new(StateStoreMetrics::unused()),
             task_progress: None,
+            split_table_ids: HashSet::new(),
+            table_split_size: None,
+            last_table_id: None,
         }
     }
 
@@ -123,8 +148,9 @@ where
 
     /// Adds a key-value pair to the underlying builders.
     ///
-    /// If `allow_split` and the current builder reaches its capacity, this function will create a
-    /// new one with the configuration generated by the closure provided earlier.
+    /// If `allow_split` and the current builder reaches its capacity, or a table boundary that
+    /// should be cut is met, this function will create a new one with the configuration generated
+    /// by the closure provided earlier.
     ///
     /// Note that in some cases like compaction of the same user key, automatic splitting is not
     /// allowed, where `allow_split` should be `false`.
@@ -134,11 +160,15 @@ where
         value: HummockValue<&[u8]>,
         is_new_user_key: bool,
     ) -> HummockResult<()> {
+        let table_id = get_table_id(full_key);
         if let Some(builder) = self.current_builder.as_ref() {
-            if is_new_user_key && builder.reach_capacity() {
+            if is_new_user_key
+                && (builder.reach_capacity() || self.should_split_table(builder, table_id))
+            {
                 self.seal_current().await?;
             }
         }
+        self.last_table_id = Some(table_id);
 
         if self.current_builder.is_none() {
             let builder = self.builder_factory.open_builder().await?;
@@ -150,6 +180,20 @@ where
         Ok(())
     }
 
+    /// Returns true if the current SST should be cut before the keys of `table_id`.
+    fn should_split_table(&self, builder: &SstableBuilder<F::Writer>, table_id: u32) -> bool {
+        match self.last_table_id {
+            Some(last_table_id) if last_table_id != table_id => {
+                self.split_table_ids.contains(&last_table_id)
+                    || self.split_table_ids.contains(&table_id)
+                    || self
+                        .table_split_size
+                        .map_or(false, |size| builder.approximate_len() >= size)
+            }
+            _ => false,
+        }
+    }
+
     /// Marks the current builder as sealed. Next call of `add` will always create a new table.
     ///
     /// If there's no builder created, or current one is already sealed before, then this function
@@ -254,6 +298,9 @@ impl TableBuilderFactory for LocalTableBuilderFactory {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use risingwave_hummock_sdk::key::{key_with_epoch, table_prefix};
+
     use super::*;
     use crate::hummock::iterator::test_utils::mock_sstable_store;
     use crate::hummock::sstable::utils::CompressionAlgorithm;
@@ -366,4 +413,112 @@ mod tests {
             .await
             .unwrap();
     }
+
+    /// Adds `key_count_per_table` keys with 100-byte values to each of `table_ids` in order.
+    async fn add_multi_table_keys<F: TableBuilderFactory>(
+        builder: &mut CapacitySplitTableBuilder<F>,
+        table_ids: &[u32],
+        key_count_per_table: usize,
+    ) {
+        for &table_id in table_ids {
+            for i in 0..key_count_per_table {
+                let mut user_key = table_prefix(table_id);
+                user_key.extend_from_slice(format!("key_test_{:05}", i).as_bytes());
+                builder
+                    .add_full_key(
+                        &key_with_epoch(user_key, 233),
+                        HummockValue::put(&[b'v'; 100]),
+                        true,
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    fn output_table_ids(outputs: &[SplitTableOutput]) -> Vec<Vec<u32>> {
+        outputs
+            .iter()
+            .map(|output| output.sst_info.table_ids.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_split_table_ids() {
+        let mut builder = CapacitySplitTableBuilder::new(
+            LocalTableBuilderFactory::new(
+                1001,
+                mock_sstable_store(),
+                default_builder_opt_for_test(),
+            ),
+            Arc::new(StateStoreMetrics::unused()),
+            None,
+            HashSet::from([2]),
+            None,
+        );
+        add_multi_table_keys(&mut builder, &[1, 2, 3, 4], 20).await;
+
+        let results = builder.finish().await.unwrap();
+        assert_eq!(
+            output_table_ids(&results),
+            vec![vec![1], vec![2], vec![3, 4]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_split_size() {
+        // Each table takes about 3KB, so the boundary after every two tables is cut.
+        let mut builder = CapacitySplitTableBuilder::new(
+            LocalTableBuilderFactory::new(
+                1001,
+                mock_sstable_store(),
+                default_builder_opt_for_test(),
+            ),
+            Arc::new(StateStoreMetrics::unused()),
+            None,
+            HashSet::new(),
+            Some(4 << 10),
+        );
+        add_multi_table_keys(&mut builder, &[1, 2, 3, 4], 20).await;
+
+        let results = builder.finish().await.unwrap();
+        assert_eq!(output_table_ids(&results), vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[tokio::test]
+    async fn test_table_split_with_capacity() {
+        let block_size = 1 << 10;
+        let table_capacity = 8 * block_size;
+        let opts = SstableBuilderOptions {
+            capacity: table_capacity,
+            block_capacity: block_size,
+            restart_interval: DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::None,
+        };
+        let mut builder = CapacitySplitTableBuilder::new(
+            LocalTableBuilderFactory::new(1001, mock_sstable_store(), opts),
+            Arc::new(StateStoreMetrics::unused()),
+            None,
+            HashSet::from([1, 2, 3]),
+            None,
+        );
+        // Each table takes about 27KB, which exceeds the capacity.
+        let table_ids = [1, 2, 3];
+        add_multi_table_keys(&mut builder, &table_ids, 200).await;
+
+        let results = builder.finish().await.unwrap();
+        assert!(results.len() > table_ids.len());
+        let output_table_ids = output_table_ids(&results);
+        // No SST spans multiple tables, and all tables are covered in order.
+        assert!(output_table_ids.iter().all(|ids| ids.len() == 1));
+        assert_eq!(
+            output_table_ids.into_iter().flatten().dedup().collect_vec(),
+            table_ids
+        );
+        // The data size never exceeds the capacity by more than the last added entry.
+        for output in &results {
+            assert!(output.sst_info.meta_offset as usize <= table_capacity + 256);
+        }
+    }
 }
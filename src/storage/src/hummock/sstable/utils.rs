@@ -71,9 +71,9 @@ pub fn xxhash64_verify(data: &[u8], checksum: u64) -> HummockResult<()> {
     Ok(())
 }
 
-use bytes::{Buf, BufMut};
+use bytes::{Buf, BufMut, Bytes};
 
-pub fn put_length_prefixed_slice(buf: &mut Vec<u8>, slice: &[u8]) {
+pub fn put_length_prefixed_slice(buf: &mut impl BufMut, slice: &[u8]) {
     let len = slice.len() as u32;
     buf.put_u32_le(len);
     buf.put_slice(slice);
@@ -86,6 +86,34 @@ pub fn get_length_prefixed_slice(buf: &mut &[u8]) -> Vec<u8> {
     v
 }
 
+/// Like [`get_length_prefixed_slice`], but slices `buf` without copying, and returns an error
+/// rather than panicking on truncated data.
+pub fn try_get_length_prefixed_bytes(buf: &mut Bytes) -> HummockResult<Bytes> {
+    let len = try_get_u32_le(buf)? as usize;
+    if buf.remaining() < len {
+        return Err(HummockError::decode_error(format!(
+            "expect {} bytes, but only {} remaining",
+            len,
+            buf.remaining()
+        )));
+    }
+    Ok(buf.split_to(len))
+}
+
+pub fn try_get_u32_le(buf: &mut impl Buf) -> HummockResult<u32> {
+    if buf.remaining() < 4 {
+        return Err(HummockError::decode_error("unexpected end of u32"));
+    }
+    Ok(buf.get_u32_le())
+}
+
+pub fn try_get_u64_le(buf: &mut impl Buf) -> HummockResult<u64> {
+    if buf.remaining() < 8 {
+        return Err(HummockError::decode_error("unexpected end of u64"));
+    }
+    Ok(buf.get_u64_le())
+}
+
 #[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CompressionAlgorithm {
     None,
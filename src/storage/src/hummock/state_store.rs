@@ -56,6 +56,7 @@ use crate::hummock::{HummockEpoch, HummockError, HummockResult};
 use crate::monitor::{StateStoreMetrics, StoreLocalStatistic};
 use crate::storage_value::StorageValue;
 use crate::store::*;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 use crate::{define_state_store_associated_type, StateStore, StateStoreIter};
 
 pub(crate) trait HummockIteratorType: 'static {
@@ -241,6 +242,16 @@ impl HummockStorage {
         Ok(None)
     }
 
+    /// Checks that a snapshot read of the committed `epoch` is served by the local read version,
+    /// once the epoch is committed.
+    fn validate_snapshot_epoch(&self, epoch: HummockEpoch) -> StorageResult<()> {
+        self.storage_core
+            .read_version()
+            .read()
+            .validate_snapshot_epoch(epoch)?;
+        Ok(())
+    }
+
     fn read_filter<R, B>(
         &self,
         read_options: &ReadOptions,
@@ -605,7 +616,7 @@ impl StateStore for HummockStorage {
             // avoid unnecessary check in the loop if the value does not change
             let max_committed_epoch = *receiver.borrow_and_update();
             if max_committed_epoch >= wait_epoch {
-                return Ok(());
+                return self.validate_snapshot_epoch(wait_epoch);
             }
             loop {
                 match tokio::time::timeout(Duration::from_secs(30), receiver.changed()).await {
@@ -634,7 +645,7 @@ impl StateStore for HummockStorage {
                     Ok(Ok(_)) => {
                         let max_committed_epoch = *receiver.borrow();
                         if max_committed_epoch >= wait_epoch {
-                            return Ok(());
+                            return self.validate_snapshot_epoch(wait_epoch);
                         }
                     }
                 }
@@ -675,6 +686,10 @@ impl StateStore for HummockStorage {
             .expect("should send success");
     }
 
+    fn flush_tracker(&self) -> &Arc<FlushTracker> {
+        &self.flush_tracker
+    }
+
     fn clear_shared_buffer(&self) -> Self::ClearSharedBufferFuture<'_> {
         async move {
             let (tx, rx) = oneshot::channel();
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::future::Future;
 use std::iter::once;
 use std::ops::Bound::{Excluded, Included};
@@ -25,8 +25,8 @@ use minitrace::future::FutureExt;
 use minitrace::Span;
 use parking_lot::RwLock;
 use risingwave_common::config::StorageConfig;
-use risingwave_hummock_sdk::can_concat;
 use risingwave_hummock_sdk::key::{key_with_epoch, user_key};
+use risingwave_hummock_sdk::{can_concat, HummockEpoch};
 use risingwave_pb::hummock::LevelType;
 use risingwave_rpc_client::HummockMetaClient;
 use tokio::sync::mpsc;
@@ -142,7 +142,7 @@ impl HummockStorageCore {
     }
 
     /// See `HummockReadVersion::update` for more details.
-    pub fn update(&self, info: VersionUpdate) {
+    pub fn update(&self, info: VersionUpdate) -> HummockResult<()> {
         self.read_version.write().update(info)
     }
 
@@ -166,9 +166,14 @@ impl HummockStorageCore {
                     .staging()
                     .prune_overlap(epoch, read_options.table_id, &key_range);
 
-            let staging_imm = staging_imm_iter
+            let mut staging_imm = staging_imm_iter
                 .cloned()
                 .collect::<Vec<ImmutableMemtable>>();
+            // The first imm containing the key is taken as the result, so the imms must be checked
+            // from the newest epoch to the oldest. The staging imms are not guaranteed to be in
+            // epoch order since imms may be added out of order. The sort is stable so that the
+            // later added imm still comes first among those of the same epoch.
+            staging_imm.sort_by_key(|imm| Reverse(imm.epoch()));
 
             let staging_sst = staging_sst_iter.cloned().collect_vec();
             let committed_version = read_version.committed().clone();
@@ -518,7 +523,7 @@ impl StateStore for HummockStorage {
             .await;
             let imm_size = imm.size();
             self.core
-                .update(VersionUpdate::Staging(StagingData::ImmMem(imm.clone())));
+                .update(VersionUpdate::Staging(StagingData::ImmMem(imm.clone())))?;
 
             // insert imm to uploader
             self.core
@@ -582,13 +587,46 @@ impl HummockStorage {
     }
 
     /// See `HummockReadVersion::update` for more details.
-    pub fn update(&self, info: VersionUpdate) {
+    pub fn update(&self, info: VersionUpdate) -> HummockResult<()> {
         self.core.update(info)
     }
 
     pub fn read_version(&self) -> Arc<RwLock<HummockReadVersion>> {
         self.core.read_version.clone()
     }
+
+    /// Deletes the staging keys within `range` written below `tombstone_epoch`. The tombstone
+    /// imms are built and added to the read version under a single write guard, so that no write
+    /// lands in between, and are handed to the uploader the same way as `ingest_batch` only after
+    /// all of them are added. Returns the number of deleted keys.
+    pub fn delete_range(
+        &self,
+        range: (Bound<Bytes>, Bound<Bytes>),
+        tombstone_epoch: HummockEpoch,
+    ) -> HummockResult<usize> {
+        let tombstones = {
+            let mut read_version = self.core.read_version.write();
+            let tombstones = read_version
+                .staging()
+                .build_delete_range_tombstones(&range, tombstone_epoch);
+            // All the tombstones are of `tombstone_epoch`, so either the epoch fence rejects the
+            // first one and none is added, or all of them are added.
+            for imm in &tombstones {
+                read_version.update(VersionUpdate::Staging(StagingData::ImmMem(imm.clone())))?;
+            }
+            tombstones
+        };
+
+        let mut deleted_key_count = 0;
+        for imm in tombstones {
+            deleted_key_count += imm.get_payload().len();
+            self.core
+                .event_sender
+                .send(HummockEvent::ImmToUploader(imm))
+                .unwrap();
+        }
+        Ok(deleted_key_count)
+    }
 }
 
 type StagingDataIterator = OrderedMergeIteratorInner<
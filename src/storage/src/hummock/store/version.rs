@@ -12,17 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashSet, VecDeque};
-use std::ops::Bound;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::ops::{Bound, RangeBounds};
 
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use itertools::Itertools;
+use prost::Message;
 use risingwave_common::catalog::TableId;
-use risingwave_hummock_sdk::HummockEpoch;
+use risingwave_hummock_sdk::{key, HummockEpoch, HummockSstableId};
 use risingwave_pb::hummock::{HummockVersionDelta, SstableInfo};
 
 use super::memtable::{ImmId, ImmutableMemtable};
 use crate::hummock::local_version::pinned_version::PinnedVersion;
+use crate::hummock::shared_buffer::shared_buffer_batch::SharedBufferBatch;
+use crate::hummock::sstable::{
+    put_length_prefixed_slice, try_get_length_prefixed_bytes, try_get_u32_le, try_get_u64_le,
+};
 use crate::hummock::utils::{check_subset_preserve_order, filter_single_sst, range_overlap};
+use crate::hummock::{HummockError, HummockResult};
 
 // TODO: use a custom data structure to allow in-place update instead of proto
 // pub type CommittedVersion = HummockVersion;
@@ -63,6 +70,47 @@ impl StagingSstableInfo {
     pub fn sstable_infos(&self) -> &Vec<SstableInfo> {
         &self.sstable_infos
     }
+
+    /// The ids of the SSTs, which identify the staging SST.
+    pub fn sst_ids(&self) -> Vec<HummockSstableId> {
+        self.sstable_infos.iter().map(|sst| sst.id).collect()
+    }
+
+    fn encode(&self, buf: &mut impl BufMut) {
+        buf.put_u32_le(self.sstable_infos.len() as u32);
+        for sstable_info in &self.sstable_infos {
+            put_length_prefixed_slice(buf, &sstable_info.encode_to_vec());
+        }
+        buf.put_u32_le(self.epochs.len() as u32);
+        for epoch in &self.epochs {
+            buf.put_u64_le(*epoch);
+        }
+        buf.put_u32_le(self.imm_ids.len() as u32);
+        for imm_id in &self.imm_ids {
+            buf.put_u64_le(*imm_id);
+        }
+    }
+
+    fn decode(buf: &mut Bytes) -> HummockResult<Self> {
+        let sstable_infos = (0..try_get_u32_le(buf)?)
+            .map(|_| -> HummockResult<SstableInfo> {
+                Ok(SstableInfo::decode(try_get_length_prefixed_bytes(buf)?)?)
+            })
+            .collect::<HummockResult<Vec<_>>>()?;
+        let epochs = (0..try_get_u32_le(buf)?)
+            .map(|_| try_get_u64_le(buf))
+            .collect::<HummockResult<Vec<_>>>()?;
+        if epochs.is_empty() || !epochs.is_sorted_by(|epoch1, epoch2| epoch2.partial_cmp(epoch1)) {
+            return Err(HummockError::decode_error(format!(
+                "invalid epochs of staging sst: {:?}",
+                epochs
+            )));
+        }
+        let imm_ids = (0..try_get_u32_le(buf)?)
+            .map(|_| try_get_u64_le(buf))
+            .collect::<HummockResult<Vec<_>>>()?;
+        Ok(Self::new(sstable_infos, epochs, imm_ids))
+    }
 }
 
 #[derive(Clone)]
@@ -72,6 +120,73 @@ pub enum StagingData {
     Sst(StagingSstableInfo),
 }
 
+const STAGING_DATA_IMM_MEM: u8 = 0;
+const STAGING_DATA_SST: u8 = 1;
+
+impl StagingData {
+    /// Serializes the staging data into a flag of its type followed by the encoded imm or staging
+    /// sst. An imm is encoded as its table id and batch ids, followed by the length-prefixed
+    /// checkpoint of [`SharedBufferBatch::to_checkpoint_bytes`], where keys and values are
+    /// length-prefixed and the keys are full keys as in SSTs.
+    pub fn serialize(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        match self {
+            StagingData::ImmMem(imm) => {
+                buf.put_u8(STAGING_DATA_IMM_MEM);
+                buf.put_u32_le(imm.table_id.table_id());
+                buf.put_u64_le(imm.batch_id());
+                buf.put_u32_le(imm.merged_batch_ids().len() as u32);
+                for batch_id in imm.merged_batch_ids() {
+                    buf.put_u64_le(*batch_id);
+                }
+                put_length_prefixed_slice(&mut buf, &imm.to_checkpoint_bytes());
+            }
+            StagingData::Sst(staging_sst) => {
+                buf.put_u8(STAGING_DATA_SST);
+                staging_sst.encode(&mut buf);
+            }
+        }
+        buf.freeze()
+    }
+
+    /// Deserializes the staging data serialized by [`Self::serialize`]. The keys and values of an
+    /// imm refer to `bytes` without copying.
+    pub fn deserialize(bytes: &Bytes) -> HummockResult<Self> {
+        let mut buf = bytes.clone();
+        if !buf.has_remaining() {
+            return Err(HummockError::decode_error("empty staging data"));
+        }
+        let staging = match buf.get_u8() {
+            STAGING_DATA_IMM_MEM => {
+                let table_id = TableId::new(try_get_u32_le(&mut buf)?);
+                let batch_id = try_get_u64_le(&mut buf)?;
+                let merged_batch_ids = (0..try_get_u32_le(&mut buf)?)
+                    .map(|_| try_get_u64_le(&mut buf))
+                    .collect::<HummockResult<Vec<_>>>()?;
+                let checkpoint = try_get_length_prefixed_bytes(&mut buf)?;
+                StagingData::ImmMem(
+                    SharedBufferBatch::from_checkpoint_bytes(checkpoint, table_id)?
+                        .with_batch_ids(batch_id, merged_batch_ids),
+                )
+            }
+            STAGING_DATA_SST => StagingData::Sst(StagingSstableInfo::decode(&mut buf)?),
+            flag => {
+                return Err(HummockError::decode_error(format!(
+                    "unknown staging data flag {}",
+                    flag
+                )))
+            }
+        };
+        if buf.has_remaining() {
+            return Err(HummockError::decode_error(format!(
+                "{} trailing bytes after staging data",
+                buf.remaining()
+            )));
+        }
+        Ok(staging)
+    }
+}
+
 pub enum VersionUpdate {
     /// a new staging data entry will be added.
     Staging(StagingData),
@@ -86,9 +201,21 @@ pub struct StagingVersion {
     pub imm: VecDeque<ImmutableMemtable>,
     // newer data comes first
     pub sst: VecDeque<StagingSstableInfo>,
+    // imms of epochs below the fence are rejected, see `set_epoch_fence`
+    pub epoch_fence: HummockEpoch,
 }
 
 impl StagingVersion {
+    /// Rejects the imms of epochs below `watermark` added afterwards. It's raised above the max
+    /// committed epoch on each committed snapshot, and never moves backwards.
+    pub fn set_epoch_fence(&mut self, watermark: HummockEpoch) {
+        self.epoch_fence = self.epoch_fence.max(watermark);
+    }
+
+    pub fn epoch_fence(&self) -> HummockEpoch {
+        self.epoch_fence
+    }
+
     pub fn prune_overlap<'a>(
         &'a self,
         epoch: HummockEpoch,
@@ -119,6 +246,199 @@ impl StagingVersion {
             });
         (overlapped_imms, overlapped_ssts)
     }
+
+    /// Builds the tombstone imms of a range tombstone at `tombstone_epoch`, one per table: every
+    /// key within `range` written by an imm of an epoch below `tombstone_epoch` is deleted, unless
+    /// it is also written at `tombstone_epoch` or later. The tombstone imms get fresh batch ids and
+    /// thus come before all the staging imms, so the keys written at newer or the same epoch are
+    /// left out to not be shadowed by them.
+    pub fn build_delete_range_tombstones(
+        &self,
+        range: &(Bound<Bytes>, Bound<Bytes>),
+        tombstone_epoch: HummockEpoch,
+    ) -> Vec<ImmutableMemtable> {
+        let mut deleted_keys: BTreeMap<TableId, BTreeSet<Bytes>> = BTreeMap::new();
+        let mut kept_keys: HashSet<(TableId, &[u8])> = HashSet::new();
+        for imm in &self.imm {
+            for (full_key, _) in imm.get_payload() {
+                let user_key = key::user_key(full_key);
+                if !range.contains::<[u8]>(user_key) {
+                    continue;
+                }
+                if imm.epoch() < tombstone_epoch {
+                    deleted_keys
+                        .entry(imm.table_id)
+                        .or_default()
+                        .insert(Bytes::copy_from_slice(user_key));
+                } else {
+                    kept_keys.insert((imm.table_id, user_key));
+                }
+            }
+        }
+
+        deleted_keys
+            .into_iter()
+            .filter_map(|(table_id, user_keys)| {
+                let user_keys = user_keys
+                    .into_iter()
+                    .filter(|user_key| !kept_keys.contains(&(table_id, user_key.as_ref())))
+                    .collect_vec();
+                (!user_keys.is_empty()).then(|| {
+                    SharedBufferBatch::build_tombstones(user_keys, tombstone_epoch, table_id)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the number of imms of each epoch. All the writes are at a single epoch if there's
+    /// only one entry, while bursty writes across epochs produce multiple entries.
+    pub fn epoch_histogram(&self) -> BTreeMap<HummockEpoch, usize> {
+        self.imm
+            .iter()
+            .map(|imm| imm.epoch())
+            .counts()
+            .into_iter()
+            .collect()
+    }
+
+    /// Greedily merges consecutive imms of the same epoch and table whose combined size is no
+    /// larger than `max_imm_size_bytes`, so that fewer imms have to be checked and iterated on
+    /// read. At most `max_merge_count` merges are performed, each of which replaces a run of
+    /// imms with a single one. Returns the number of merges performed.
+    pub fn coalesce_small_imms(
+        &mut self,
+        max_imm_size_bytes: usize,
+        max_merge_count: usize,
+    ) -> usize {
+        let mut merge_count = 0;
+        let mut coalesced = VecDeque::with_capacity(self.imm.len());
+        // imms of the run being coalesced, newer first.
+        let mut run: Vec<ImmutableMemtable> = vec![];
+        let mut run_size = 0;
+
+        let flush_run = |run: &mut Vec<ImmutableMemtable>,
+                         coalesced: &mut VecDeque<ImmutableMemtable>,
+                         merge_count: &mut usize| {
+            match run.len() {
+                0 => {}
+                1 => coalesced.push_back(run.pop().unwrap()),
+                _ => {
+                    coalesced.push_back(SharedBufferBatch::merge(run));
+                    run.clear();
+                    *merge_count += 1;
+                }
+            }
+        };
+
+        for imm in self.imm.drain(..) {
+            let can_extend = match run.last() {
+                Some(last) => {
+                    merge_count < max_merge_count
+                        && last.epoch() == imm.epoch()
+                        && last.table_id == imm.table_id
+                        && run_size + imm.size() <= max_imm_size_bytes
+                }
+                None => false,
+            };
+            if !can_extend {
+                flush_run(&mut run, &mut coalesced, &mut merge_count);
+                run_size = 0;
+            }
+            run_size += imm.size();
+            run.push(imm);
+        }
+        flush_run(&mut run, &mut coalesced, &mut merge_count);
+
+        self.imm = coalesced;
+        merge_count
+    }
+
+    /// Returns the imms and staging SSTs added and removed since `baseline`, so that a replica
+    /// holding `baseline` only needs the delta to catch up with `self`. The imms are identified by
+    /// their batch ids, and the staging SSTs by the ids of their SSTs.
+    pub fn diff(&self, baseline: &StagingVersion) -> StagingDataDiff {
+        let batch_ids: HashSet<ImmId> = self.imm.iter().map(|imm| imm.batch_id()).collect();
+        let baseline_batch_ids: HashSet<ImmId> =
+            baseline.imm.iter().map(|imm| imm.batch_id()).collect();
+        let sst_ids: HashSet<Vec<HummockSstableId>> =
+            self.sst.iter().map(StagingSstableInfo::sst_ids).collect();
+        let baseline_sst_ids: HashSet<Vec<HummockSstableId>> = baseline
+            .sst
+            .iter()
+            .map(StagingSstableInfo::sst_ids)
+            .collect();
+        StagingDataDiff {
+            added_imms: self
+                .imm
+                .iter()
+                .filter(|imm| !baseline_batch_ids.contains(&imm.batch_id()))
+                .cloned()
+                .collect(),
+            removed_batch_ids: baseline
+                .imm
+                .iter()
+                .map(|imm| imm.batch_id())
+                .filter(|batch_id| !batch_ids.contains(batch_id))
+                .collect(),
+            added_ssts: self
+                .sst
+                .iter()
+                .filter(|sst| !baseline_sst_ids.contains(&sst.sst_ids()))
+                .cloned()
+                .collect(),
+            removed_sst_ids: baseline
+                .sst
+                .iter()
+                .map(StagingSstableInfo::sst_ids)
+                .filter(|ids| !sst_ids.contains(ids))
+                .collect(),
+        }
+    }
+
+    /// Applies a diff returned by [`Self::diff`] on the baseline. The added imms are placed by
+    /// their batch ids, and the added staging SSTs by their newest epochs, so that newer data
+    /// still comes first.
+    pub fn apply_diff(&mut self, diff: StagingDataDiff) {
+        let removed_batch_ids: HashSet<ImmId> = diff.removed_batch_ids.into_iter().collect();
+        self.imm
+            .retain(|imm| !removed_batch_ids.contains(&imm.batch_id()));
+        for imm in diff.added_imms.into_iter().rev() {
+            let index = self
+                .imm
+                .iter()
+                .position(|kept| kept.batch_id() < imm.batch_id())
+                .unwrap_or(self.imm.len());
+            self.imm.insert(index, imm);
+        }
+
+        let removed_sst_ids: HashSet<Vec<HummockSstableId>> =
+            diff.removed_sst_ids.into_iter().collect();
+        self.sst
+            .retain(|sst| !removed_sst_ids.contains(&sst.sst_ids()));
+        // An SST uploaded later comes before the kept ones of the same newest epoch.
+        for sst in diff.added_ssts.into_iter().rev() {
+            let index = self
+                .sst
+                .iter()
+                .position(|kept| kept.epochs[0] <= sst.epochs[0])
+                .unwrap_or(self.sst.len());
+            self.sst.insert(index, sst);
+        }
+    }
+}
+
+/// The imms and staging SSTs added and removed between two [`StagingVersion`]s, see
+/// [`StagingVersion::diff`].
+#[derive(Clone, Default)]
+pub struct StagingDataDiff {
+    /// The imms present in the new version but not in the baseline, newer first.
+    pub added_imms: Vec<ImmutableMemtable>,
+    /// The batch ids of the imms present in the baseline but not in the new version.
+    pub removed_batch_ids: Vec<ImmId>,
+    /// The staging SSTs present in the new version but not in the baseline, newer first.
+    pub added_ssts: Vec<StagingSstableInfo>,
+    /// The SST ids of the staging SSTs present in the baseline but not in the new version.
+    pub removed_sst_ids: Vec<Vec<HummockSstableId>>,
 }
 
 /// A container of information required for reading from hummock.
@@ -140,6 +460,7 @@ impl HummockReadVersion {
             staging: StagingVersion {
                 imm: VecDeque::default(),
                 sst: VecDeque::default(),
+                epoch_fence: 0,
             },
 
             committed: committed_version,
@@ -147,54 +468,21 @@ impl HummockReadVersion {
     }
 
     /// Updates the read version with `VersionUpdate`.
-    /// A `OrderIdx` that can uniquely identify the newly added entry will be returned.
-    pub fn update(&mut self, info: VersionUpdate) {
+    ///
+    /// Returns an error without applying the update if an imm is below the epoch fence of the
+    /// staging version, see [`StagingVersion::set_epoch_fence`].
+    pub fn update(&mut self, info: VersionUpdate) -> HummockResult<()> {
         match info {
-            VersionUpdate::Staging(staging) => match staging {
-                // TODO: add a check to ensure that the added batch id of added imm is greater than
-                // the batch id of imm at the front
-                StagingData::ImmMem(imm) => self.staging.imm.push_front(imm),
-                StagingData::Sst(staging_sst) => {
-                    // TODO: enable this stricter check after each streaming table owns a read
-                    // version. assert!(self.staging.imm.len() >=
-                    // staging_sst.imm_ids.len()); assert!(staging_sst
-                    //     .imm_ids
-                    //     .is_sorted_by(|batch_id1, batch_id2| batch_id2.partial_cmp(batch_id1)));
-                    // assert!(
-                    //     check_subset_preserve_order(
-                    //         staging_sst.imm_ids.iter().cloned(),
-                    //         self.staging.imm.iter().map(|imm| imm.batch_id()),
-                    //     ),
-                    //     "the imm id of staging sstable info not preserve the imm order. staging
-                    // sst imm ids: {:?}, current imm ids: {:?}",
-                    //     staging_sst.imm_ids.iter().collect_vec(),
-                    //     self.staging.imm.iter().map(|imm| imm.batch_id()).collect_vec()
-                    // );
-                    // for clear_imm_id in staging_sst.imm_ids.iter().rev() {
-                    //     let item = self.staging.imm.back().unwrap();
-                    //     assert_eq!(*clear_imm_id, item.batch_id());
-                    //     self.staging.imm.pop_back();
-                    // }
-
-                    debug_assert!(
-                        check_subset_preserve_order(
-                            staging_sst.imm_ids.iter().cloned().sorted(),
-                            self.staging.imm.iter().map(|imm| imm.batch_id()).sorted()
-                        ),
-                        "the set of imm ids in the staging_sst {:?} is not a subset of current staging imms {:?}",
-                        staging_sst.imm_ids.iter().cloned().sorted().collect_vec(),
-                        self.staging.imm.iter().map(|imm| imm.batch_id()).sorted().collect_vec(),
-                    );
-
-                    let imm_id_set: HashSet<ImmId> =
-                        HashSet::from_iter(staging_sst.imm_ids.iter().cloned());
-                    self.staging
-                        .imm
-                        .retain(|imm| !imm_id_set.contains(&imm.batch_id()));
-
-                    self.staging.sst.push_front(staging_sst);
+            VersionUpdate::Staging(staging) => {
+                let epoch_fence = self.staging.epoch_fence();
+                if let StagingData::ImmMem(imm) = &staging && imm.epoch() < epoch_fence {
+                    return Err(HummockError::write_epoch_below_fence(
+                        imm.epoch(),
+                        epoch_fence,
+                    ));
                 }
-            },
+                self.update_staging(staging);
+            }
 
             VersionUpdate::CommittedDelta(_) => {
                 unimplemented!()
@@ -203,6 +491,9 @@ impl HummockReadVersion {
             VersionUpdate::CommittedSnapshot(committed_version) => {
                 let max_committed_epoch = committed_version.max_committed_epoch();
                 self.committed = committed_version;
+                // The writes of the committed epochs are all synced before the commit, so any
+                // write of them added afterwards is stale.
+                self.staging.set_epoch_fence(max_committed_epoch + 1);
 
                 {
                     // TODO: remove it when support update staging local_sst
@@ -220,6 +511,59 @@ impl HummockReadVersion {
                 }
             }
         }
+        Ok(())
+    }
+
+    fn update_staging(&mut self, staging: StagingData) {
+        match staging {
+            // TODO: add a check to ensure that the added batch id of added imm is greater than
+            // the batch id of imm at the front
+            StagingData::ImmMem(imm) => self.staging.imm.push_front(imm),
+            StagingData::Sst(staging_sst) => {
+                // TODO: enable this stricter check after each streaming table owns a read
+                // version. assert!(self.staging.imm.len() >=
+                // staging_sst.imm_ids.len()); assert!(staging_sst
+                //     .imm_ids
+                //     .is_sorted_by(|batch_id1, batch_id2| batch_id2.partial_cmp(batch_id1)));
+                // assert!(
+                //     check_subset_preserve_order(
+                //         staging_sst.imm_ids.iter().cloned(),
+                //         self.staging.imm.iter().map(|imm| imm.batch_id()),
+                //     ),
+                //     "the imm id of staging sstable info not preserve the imm order. staging
+                // sst imm ids: {:?}, current imm ids: {:?}",
+                //     staging_sst.imm_ids.iter().collect_vec(),
+                //     self.staging.imm.iter().map(|imm| imm.batch_id()).collect_vec()
+                // );
+                // for clear_imm_id in staging_sst.imm_ids.iter().rev() {
+                //     let item = self.staging.imm.back().unwrap();
+                //     assert_eq!(*clear_imm_id, item.batch_id());
+                //     self.staging.imm.pop_back();
+                // }
+
+                debug_assert!(
+                    check_subset_preserve_order(
+                        staging_sst.imm_ids.iter().cloned().sorted(),
+                        self.staging.imm.iter().flat_map(|imm| imm.covered_batch_ids()).sorted()
+                    ),
+                    "the set of imm ids in the staging_sst {:?} is not a subset of current staging imms {:?}",
+                    staging_sst.imm_ids.iter().cloned().sorted().collect_vec(),
+                    self.staging.imm.iter().flat_map(|imm| imm.covered_batch_ids()).sorted().collect_vec(),
+                );
+
+                let imm_id_set: HashSet<ImmId> =
+                    HashSet::from_iter(staging_sst.imm_ids.iter().cloned());
+                // A coalesced imm can only be removed when all the imms it's merged from
+                // have been flushed.
+                self.staging.imm.retain(|imm| {
+                    !imm.covered_batch_ids()
+                        .iter()
+                        .all(|batch_id| imm_id_set.contains(batch_id))
+                });
+
+                self.staging.sst.push_front(staging_sst);
+            }
+        }
     }
 
     pub fn staging(&self) -> &StagingVersion {
@@ -230,6 +574,32 @@ impl HummockReadVersion {
         &self.committed
     }
 
+    /// The max committed epoch of the pinned version, i.e. the newest epoch whose data is fully
+    /// visible through the committed version.
+    pub fn read_epoch(&self) -> HummockEpoch {
+        self.committed.max_committed_epoch()
+    }
+
+    /// Whether the data of `epoch` is visible through the committed version alone. Reads beyond
+    /// this horizon must consult the staging data as well.
+    pub fn is_epoch_visible(&self, epoch: HummockEpoch) -> bool {
+        epoch <= self.read_epoch()
+    }
+
+    /// Rejects a snapshot read of `epoch` that is not visible through the committed version.
+    ///
+    /// Only snapshot reads of committed epochs are checked. Streaming reads of the current
+    /// uncommitted epoch are served by the staging data, which may hold nothing of that epoch yet.
+    pub fn validate_snapshot_epoch(&self, epoch: HummockEpoch) -> HummockResult<()> {
+        if !self.is_epoch_visible(epoch) {
+            return Err(HummockError::read_epoch_beyond_horizon(
+                epoch,
+                self.read_epoch(),
+            ));
+        }
+        Ok(())
+    }
+
     pub fn clear_uncommitted(&mut self) {
         self.staging.imm.clear();
         self.staging.sst.clear();
@@ -17,7 +17,7 @@ use std::sync::Arc;
 use bytes::{BufMut, Bytes};
 use itertools::Itertools;
 use risingwave_common::config::StorageConfig;
-use risingwave_hummock_sdk::key::key_with_epoch;
+use risingwave_hummock_sdk::key::{key_with_epoch, KeyEncodingVersion};
 use risingwave_hummock_sdk::HummockSstableId;
 use risingwave_pb::hummock::{KeyRange, SstableInfo};
 
@@ -103,6 +103,8 @@ pub fn gen_dummy_sst_info(id: HummockSstableId, batches: Vec<SharedBufferBatch>)
         stale_key_count: 0,
         total_key_count: 0,
         divide_version: 0,
+        table_stats: Default::default(),
+        key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
     }
 }
 
@@ -174,6 +176,8 @@ pub async fn put_sst(
         stale_key_count: 0,
         total_key_count: 0,
         divide_version: 0,
+        table_stats: Default::default(),
+        key_encoding_version: KeyEncodingVersion::LATEST.to_u32(),
     };
     let writer_output = writer.finish(meta).await?;
     writer_output.await.unwrap()?;
@@ -254,9 +254,24 @@ impl MemoryLimiter {
     pub fn get_memory_usage(&self) -> u64 {
         self.inner.total_size.load(AtomicOrdering::Acquire)
     }
+
+    pub fn quota(&self) -> u64 {
+        self.inner.quota
+    }
 }
 
 impl MemoryTracker {
+    /// Splits at most `quota` off the tracker into a new one, which releases it on drop
+    /// independently.
+    pub fn split(&mut self, quota: u64) -> MemoryTracker {
+        let quota = std::cmp::min(quota, self.quota);
+        self.quota -= quota;
+        MemoryTracker {
+            limiter: self.limiter.clone(),
+            quota,
+        }
+    }
+
     pub fn try_increase_memory(&mut self, target: u64) -> bool {
         if self.quota >= target {
             return true;
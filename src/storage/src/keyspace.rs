@@ -14,8 +14,10 @@
 
 use std::future::Future;
 use std::ops::RangeBounds;
+use std::sync::Arc;
 
 use bytes::Bytes;
+use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::TableId;
 use risingwave_hummock_sdk::key::{prefixed_range, table_prefix};
 
@@ -33,6 +35,10 @@ pub struct Keyspace<S: StateStore> {
     prefix: Vec<u8>,
 
     table_id: TableId,
+
+    /// The vnodes owned by the actor writing this keyspace, if any. The keys written are checked
+    /// to be prefixed by one of them in debug builds.
+    vnodes: Option<Arc<Bitmap>>,
 }
 
 // TODO: remove storage interface from keyspace, and and call it directly in storage_table
@@ -50,9 +56,20 @@ impl<S: StateStore> Keyspace<S> {
             store,
             prefix,
             table_id: *id,
+            vnodes: None,
         }
     }
 
+    /// Sets the vnodes owned by the actor writing this keyspace.
+    pub fn set_vnodes(&mut self, vnodes: Arc<Bitmap>) {
+        self.vnodes = Some(vnodes);
+    }
+
+    /// Returns the vnodes owned by the actor writing this keyspace, if any.
+    pub fn vnodes(&self) -> Option<&Arc<Bitmap>> {
+        self.vnodes.as_ref()
+    }
+
     /// Treats the keyspace as a single key, and returns the key.
     pub fn key(&self) -> &[u8] {
         &self.prefix
@@ -27,6 +27,7 @@ use risingwave_hummock_sdk::HummockReadEpoch;
 use crate::error::StorageResult;
 use crate::storage_value::StorageValue;
 use crate::store::*;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 use crate::{define_state_store_associated_type, StateStore, StateStoreIter};
 
 mod batched_iter {
@@ -169,6 +170,8 @@ type KeyWithEpoch = (Bytes, Reverse<u64>);
 pub struct MemoryStateStore {
     /// Stores (key, epoch) -> user value.
     inner: Arc<RwLock<BTreeMap<KeyWithEpoch, Option<Bytes>>>>,
+
+    flush_tracker: Arc<FlushTracker>,
 }
 
 fn to_bytes_range<R, B>(range: R) -> (Bound<KeyWithEpoch>, Bound<KeyWithEpoch>)
@@ -324,6 +327,10 @@ impl StateStore for MemoryStateStore {
 
     fn seal_epoch(&self, _epoch: u64, _is_checkpoint: bool) {}
 
+    fn flush_tracker(&self) -> &Arc<FlushTracker> {
+        &self.flush_tracker
+    }
+
     fn clear_shared_buffer(&self) -> Self::ClearSharedBufferFuture<'_> {
         async move { Ok(()) }
     }
@@ -27,6 +27,7 @@ use crate::hummock::sstable_store::SstableStoreRef;
 use crate::hummock::{HummockStorage, SstableIdManagerRef};
 use crate::storage_value::StorageValue;
 use crate::store::*;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 use crate::{define_state_store_associated_type, StateStore, StateStoreIter};
 
 /// A state store wrapper for monitoring metrics.
@@ -35,11 +36,19 @@ pub struct MonitoredStateStore<S> {
     inner: S,
 
     stats: Arc<StateStoreMetrics>,
+
+    /// Owned by the wrapper instead of the inner store, which may be shared by the compute nodes
+    /// in the same process, e.g. [`crate::memory::MemoryStateStore::shared`].
+    flush_tracker: Arc<FlushTracker>,
 }
 
 impl<S> MonitoredStateStore<S> {
     pub fn new(inner: S, stats: Arc<StateStoreMetrics>) -> Self {
-        Self { inner, stats }
+        Self {
+            inner,
+            stats,
+            flush_tracker: Arc::default(),
+        }
     }
 }
 
@@ -248,6 +257,10 @@ where
         self.inner.seal_epoch(epoch, is_checkpoint);
     }
 
+    fn flush_tracker(&self) -> &Arc<FlushTracker> {
+        &self.flush_tracker
+    }
+
     fn monitored(self, _stats: Arc<StateStoreMetrics>) -> MonitoredStateStore<Self> {
         panic!("the state store is already monitored")
     }
@@ -14,12 +14,14 @@
 
 use std::future::Future;
 use std::ops::Bound;
+use std::sync::Arc;
 
 use bytes::Bytes;
 use risingwave_hummock_sdk::HummockReadEpoch;
 
 use crate::storage_value::StorageValue;
 use crate::store::*;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 use crate::{define_state_store_associated_type, StateStore, StateStoreIter};
 
 /// A panic state store. If a workload is fully in-memory, we can use this state store to
@@ -113,6 +115,10 @@ impl StateStore for PanicStateStore {
         panic!("should not update current epoch from the panic state store!");
     }
 
+    fn flush_tracker(&self) -> &Arc<FlushTracker> {
+        panic!("should not flush to the panic state store!");
+    }
+
     fn clear_shared_buffer(&self) -> Self::ClearSharedBufferFuture<'_> {
         async move {
             panic!("should not clear shared buffer from the panic state store!");
@@ -16,6 +16,7 @@ use risingwave_common::array::Row;
 use risingwave_common::error::Result;
 use risingwave_common::types::{VirtualNode, VIRTUAL_NODE_SIZE};
 use risingwave_common::util::ordered::OrderedRowSerde;
+use risingwave_hummock_sdk::key::{decode_vnode, encode_vnode};
 
 pub fn serialize_pk(pk: &Row, serializer: &OrderedRowSerde) -> Vec<u8> {
     let mut result = vec![];
@@ -29,7 +30,7 @@ pub fn serialize_pk_with_vnode(
     vnode: VirtualNode,
 ) -> Vec<u8> {
     let pk_bytes = serialize_pk(pk, serializer);
-    [&vnode.to_be_bytes(), pk_bytes.as_slice()].concat()
+    [&encode_vnode(vnode), pk_bytes.as_slice()].concat()
 }
 
 // NOTE: Only for debug purpose now
@@ -37,13 +38,13 @@ pub fn deserialize_pk_with_vnode(
     key: &[u8],
     deserializer: &OrderedRowSerde,
 ) -> Result<(VirtualNode, Row)> {
-    let vnode = VirtualNode::from_be_bytes(key[0..VIRTUAL_NODE_SIZE].try_into().unwrap());
+    let vnode = decode_vnode(key);
     let pk = deserializer.deserialize(&key[VIRTUAL_NODE_SIZE..])?;
     Ok((vnode, pk))
 }
 
 pub fn parse_raw_key_to_vnode_and_key(raw_key: &[u8]) -> (VirtualNode, &[u8]) {
     let (vnode_bytes, key_bytes) = raw_key.split_at(VIRTUAL_NODE_SIZE);
-    let vnode = VirtualNode::from_be_bytes(vnode_bytes.try_into().unwrap());
+    let vnode = decode_vnode(vnode_bytes);
     (vnode, key_bytes)
 }
@@ -22,6 +22,7 @@ use risingwave_hummock_sdk::{HummockReadEpoch, LocalSstableInfo};
 use crate::error::StorageResult;
 use crate::monitor::{MonitoredStateStore, StateStoreMetrics};
 use crate::storage_value::StorageValue;
+use crate::table::streaming_table::flush_tracker::FlushTracker;
 use crate::write_batch::WriteBatch;
 
 #[derive(Default, Debug)]
@@ -162,6 +163,9 @@ pub trait StateStore: Send + Sync + 'static + Clone {
     /// update max current epoch in storage.
     fn seal_epoch(&self, epoch: u64, is_checkpoint: bool);
 
+    /// Returns the tracker of the state table flushes to this state store.
+    fn flush_tracker(&self) -> &Arc<FlushTracker>;
+
     /// Creates a [`MonitoredStateStore`] from this state store, with given `stats`.
     fn monitored(self, stats: Arc<StateStoreMetrics>) -> MonitoredStateStore<Self> {
         MonitoredStateStore::new(self, stats)
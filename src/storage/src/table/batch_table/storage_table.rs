@@ -28,7 +28,7 @@ use risingwave_common::catalog::{ColumnDesc, ColumnId, Schema, TableId, TableOpt
 use risingwave_common::types::{Datum, VirtualNode};
 use risingwave_common::util::ordered::*;
 use risingwave_common::util::sort_util::OrderType;
-use risingwave_hummock_sdk::key::{end_bound_of_prefix, next_key, prefixed_range};
+use risingwave_hummock_sdk::key::{encode_vnode, end_bound_of_prefix, next_key, prefixed_range};
 use risingwave_hummock_sdk::HummockReadEpoch;
 use tracing::trace;
 
@@ -331,10 +331,10 @@ impl<S: StateStore> StorageTable<S> {
         // TODO: if there're some vnodes continuously in the range and we don't care about order, we
         // can use a single iterator.
         let iterators: Vec<_> = try_join_all(vnodes.map(|vnode| {
-            let raw_key_range = prefixed_range(encoded_key_range.clone(), &vnode.to_be_bytes());
+            let raw_key_range = prefixed_range(encoded_key_range.clone(), &encode_vnode(vnode));
             let prefix_hint = prefix_hint
                 .clone()
-                .map(|prefix_hint| [&vnode.to_be_bytes(), prefix_hint.as_slice()].concat());
+                .map(|prefix_hint| [&encode_vnode(vnode), prefix_hint.as_slice()].concat());
             let wait_epoch = wait_epoch.clone();
             async move {
                 let read_options = self.get_read_option(wait_epoch.get_epoch());
@@ -427,18 +427,30 @@ impl<S: StateStore> StorageTable<S> {
             }
         }
 
-        let start_key = serialize_pk_bound(
-            &self.pk_serializer,
-            pk_prefix,
-            next_col_bounds.start_bound(),
-            true,
-        );
-        let end_key = serialize_pk_bound(
-            &self.pk_serializer,
-            pk_prefix,
-            next_col_bounds.end_bound(),
-            false,
-        );
+        let (mut start_bound, mut end_bound) =
+            (next_col_bounds.start_bound(), next_col_bounds.end_bound());
+        let is_full_range = matches!((start_bound, end_bound), (Unbounded, Unbounded));
+        let is_descending = !is_full_range
+            && self.pk_serializer.get_order_types()[pk_prefix.size()] == OrderType::Descending;
+        // The serialized keys of a descending column are in the reverse order of the values, so
+        // the lower bound of the values becomes the end key.
+        if is_descending {
+            std::mem::swap(&mut start_bound, &mut end_bound);
+        }
+        // A bounded range is derived from comparisons, which are never true for NULL. NULL is the
+        // smallest serialized value of the next column, or the largest if it's descending, so we
+        // exclude it from the unbounded side.
+        const NULL_BOUND: Bound<&Datum> = Excluded(&None);
+        if !is_full_range {
+            match (is_descending, start_bound, end_bound) {
+                (false, Unbounded, _) => start_bound = NULL_BOUND,
+                (true, _, Unbounded) => end_bound = NULL_BOUND,
+                _ => {}
+            }
+        }
+
+        let start_key = serialize_pk_bound(&self.pk_serializer, pk_prefix, start_bound, true);
+        let end_key = serialize_pk_bound(&self.pk_serializer, pk_prefix, end_bound, false);
 
         assert!(pk_prefix.size() <= self.pk_indices.len());
         let pk_prefix_indices = (0..pk_prefix.size())
@@ -144,6 +144,30 @@ fn compute_chunk_vnode(chunk: &DataChunk, indices: &[usize], vnodes: &Bitmap) ->
     }
 }
 
+/// Get vnode values from the vnode column at `vnode_col_idx` on the given `chunk`, which are
+/// computed by the upstream with the same distribution as the table.
+fn compute_chunk_vnode_by_column(
+    chunk: &DataChunk,
+    vnode_col_idx: usize,
+    vnodes: &Bitmap,
+) -> Vec<VirtualNode> {
+    chunk
+        .column_at(vnode_col_idx)
+        .array_ref()
+        .as_int16()
+        .iter()
+        .zip_eq(chunk.vis().iter())
+        .map(|(vnode, vis)| {
+            let vnode = vnode.map_or(DEFAULT_VNODE, |vnode| vnode as VirtualNode);
+            // Ignore the invisible rows.
+            if vis {
+                check_vnode_is_set(vnode, vnodes);
+            }
+            vnode
+        })
+        .collect()
+}
+
 /// Check whether the given `vnode` is set in the `vnodes` of this table.
 fn check_vnode_is_set(vnode: VirtualNode, vnodes: &Bitmap) {
     let is_set = vnodes.is_set(vnode as usize);
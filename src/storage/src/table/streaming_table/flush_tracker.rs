@@ -0,0 +1,147 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+use crate::error::{StorageError, StorageResult};
+
+/// State tables seal their mem-tables on barriers and flush them to the state store in the
+/// background. `FlushTracker` tracks the flushes in progress of each epoch, so that an epoch is
+/// only reported as collected after all the mem-tables sealed in it are ingested.
+///
+/// Each state store owns a tracker (see `StateStore::flush_tracker`), so that the compute nodes
+/// sharing a process don't wait for or reset the flushes of each other.
+#[derive(Default)]
+pub struct FlushTracker {
+    inner: Mutex<FlushTrackerInner>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct FlushTrackerInner {
+    /// The number of flushes in progress of each epoch.
+    pending: BTreeMap<u64, usize>,
+    /// The errors of the failed flushes of each epoch, kept until the epoch is collected by
+    /// [`FlushTracker::collect_epoch`].
+    failed: BTreeMap<u64, Vec<String>>,
+    /// Bumped by [`FlushTracker::reset`], so that the flushes registered before are ignored.
+    generation: u64,
+}
+
+impl FlushTrackerInner {
+    fn error_before(&self, epoch: u64) -> Option<StorageError> {
+        let errors = self.failed.range(..=epoch).flat_map(|(_, e)| e).join("; ");
+        (!errors.is_empty()).then(|| StorageError::AsyncFlush(errors))
+    }
+}
+
+impl FlushTracker {
+    /// Registers a flush of `epoch`, which is in progress until the returned guard is finished or
+    /// dropped.
+    pub fn register(self: &Arc<Self>, epoch: u64) -> FlushGuard {
+        let mut inner = self.inner.lock();
+        *inner.pending.entry(epoch).or_default() += 1;
+        FlushGuard {
+            tracker: self.clone(),
+            epoch,
+            generation: inner.generation,
+            error: Some("flush aborted".to_string()),
+        }
+    }
+
+    /// Waits until the flushes of `epoch` and the epochs before are done. Returns an error if any
+    /// of them failed. The errors are kept for the other waiters until the epoch is collected.
+    pub async fn wait_epoch(&self, epoch: u64) -> StorageResult<()> {
+        loop {
+            let notified = self.notify.notified();
+            {
+                let inner = self.inner.lock();
+                if inner.pending.range(..=epoch).next().is_none() {
+                    return inner.error_before(epoch).map_or(Ok(()), Err);
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// Waits like [`FlushTracker::wait_epoch`] for the barrier of `epoch` to be collected, and
+    /// then forgets the errors of `epoch` and the epochs before.
+    pub async fn collect_epoch(&self, epoch: u64) -> StorageResult<()> {
+        let result = self.wait_epoch(epoch).await;
+        let mut inner = self.inner.lock();
+        let remaining = inner.failed.split_off(&(epoch + 1));
+        inner.failed = remaining;
+        result
+    }
+
+    /// Forgets all the flushes, e.g. when the actors are dropped for recovery, which aborts their
+    /// flushes.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock();
+        inner.pending.clear();
+        inner.failed.clear();
+        inner.generation += 1;
+        drop(inner);
+        self.notify.notify_waiters();
+    }
+
+    /// Returns the number of flushes in progress of `epoch`.
+    pub fn pending_count(&self, epoch: u64) -> usize {
+        self.inner
+            .lock()
+            .pending
+            .get(&epoch)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// A flush registered in the [`FlushTracker`].
+pub struct FlushGuard {
+    tracker: Arc<FlushTracker>,
+    epoch: u64,
+    generation: u64,
+    error: Option<String>,
+}
+
+impl FlushGuard {
+    /// Marks the flush as done with `result`.
+    pub fn finish(mut self, result: &StorageResult<()>) {
+        self.error = result.as_ref().err().map(|e| e.to_string());
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        let mut inner = self.tracker.inner.lock();
+        if inner.generation != self.generation {
+            return;
+        }
+        if let Some(error) = self.error.take() {
+            inner.failed.entry(self.epoch).or_default().push(error);
+        }
+        let count = inner.pending.get_mut(&self.epoch).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            inner.pending.remove(&self.epoch);
+        }
+        drop(inner);
+        self.tracker.notify.notify_waiters();
+    }
+}
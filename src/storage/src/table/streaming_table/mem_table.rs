@@ -62,6 +62,11 @@ impl MemTable {
         !self.buffer.is_empty()
     }
 
+    /// Returns the number of buffered row operations.
+    pub fn row_count(&self) -> usize {
+        self.buffer.len()
+    }
+
     /// read methods
     pub fn get_row_op(&self, pk: &[u8]) -> Option<&RowOp> {
         self.buffer.get(pk)
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod flush_tracker;
 pub mod mem_table;
 pub mod state_table;
 
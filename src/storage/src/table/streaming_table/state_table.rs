@@ -14,7 +14,7 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::marker::PhantomData;
 use std::ops::Bound;
 use std::ops::Bound::*;
@@ -23,7 +23,7 @@ use std::sync::Arc;
 use async_stack_trace::StackTrace;
 use futures::{pin_mut, Stream, StreamExt};
 use futures_async_stream::try_stream;
-use itertools::{izip, Itertools};
+use itertools::{izip, Either, Itertools};
 use risingwave_common::array::{Op, Row, RowDeserializer, StreamChunk, Vis};
 use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::{ColumnDesc, TableId, TableOption};
@@ -32,12 +32,16 @@ use risingwave_common::util::epoch::EpochPair;
 use risingwave_common::util::ordered::OrderedRowSerde;
 use risingwave_common::util::sort_util::OrderType;
 use risingwave_hummock_sdk::key::{
-    end_bound_of_prefix, prefixed_range, range_of_prefix, start_bound_of_excluded_prefix,
+    encode_vnode, end_bound_of_prefix, prefixed_range, range_of_prefix,
+    start_bound_of_excluded_prefix,
 };
 use risingwave_pb::catalog::Table;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tracing::trace;
 
-use super::mem_table::{MemTable, MemTableIter, RowOp};
+use super::flush_tracker::FlushTracker;
+use super::mem_table::{MemTable, RowOp};
 use crate::error::{StorageError, StorageResult};
 use crate::keyspace::StripPrefixIterator;
 use crate::row_serde::row_serde_util::{
@@ -46,7 +50,10 @@ use crate::row_serde::row_serde_util::{
 use crate::storage_value::StorageValue;
 use crate::store::{ReadOptions, WriteOptions};
 use crate::table::streaming_table::mem_table::MemTableError;
-use crate::table::{compute_chunk_vnode, compute_vnode, Distribution};
+use crate::table::{
+    check_vnode_is_set, compute_chunk_vnode, compute_chunk_vnode_by_column, compute_vnode,
+    Distribution,
+};
 use crate::{Keyspace, StateStore, StateStoreIter};
 
 /// `StateTable` is the interface accessing relational data in KV(`StateStore`) with
@@ -100,6 +107,74 @@ pub struct StateTable<S: StateStore> {
 
     /// the epoch flush to the state store last time
     epoch: Option<EpochPair>,
+
+    /// Mem-tables sealed on barriers in epoch order, which may not have been ingested into the
+    /// state store yet. Point reads consult them after `mem_table`.
+    sealed_mem_tables: VecDeque<(u64, Arc<BTreeMap<Vec<u8>, RowOp>>)>,
+
+    /// Progress of flushing the sealed mem-tables in the background.
+    flush_progress: watch::Receiver<FlushProgress>,
+    flush_progress_tx: Arc<watch::Sender<FlushProgress>>,
+
+    /// The background flushes of the sealed mem-tables, aborted when the table is dropped.
+    flush_tasks: FlushTasks,
+}
+
+/// The handles of the background flushes spawned by a state table. They are aborted on drop, e.g.
+/// when the actor is dropped for recovery, so that no stale mem-tables are ingested after it. The
+/// clones of a state table, like the one moved into a flush, don't own the flushes.
+#[derive(Default)]
+struct FlushTasks(VecDeque<(u64, JoinHandle<()>)>);
+
+impl Clone for FlushTasks {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Drop for FlushTasks {
+    fn drop(&mut self) {
+        for (_, handle) in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+/// Progress of flushing the sealed mem-tables of a state table, which are flushed one after
+/// another in epoch order.
+#[derive(Default)]
+struct FlushProgress {
+    /// All the mem-tables sealed in this epoch and before have been ingested.
+    flushed_epoch: u64,
+    /// The error of the first failed flush. No more mem-tables are ingested after it.
+    error: Option<String>,
+}
+
+impl FlushProgress {
+    /// Returns whether the mem-tables sealed in `epoch` and before have been ingested.
+    fn is_flushed(&self, epoch: u64) -> StorageResult<bool> {
+        match &self.error {
+            Some(error) => Err(StorageError::AsyncFlush(error.clone())),
+            None => Ok(self.flushed_epoch >= epoch),
+        }
+    }
+}
+
+/// Waits until the mem-tables sealed in `epoch` and before have been ingested.
+async fn wait_flushed(
+    mut flush_progress: watch::Receiver<FlushProgress>,
+    epoch: u64,
+) -> StorageResult<()> {
+    loop {
+        let flushed = flush_progress.borrow().is_flushed(epoch)?;
+        if flushed {
+            return Ok(());
+        }
+        flush_progress
+            .changed()
+            .await
+            .map_err(|_| StorageError::AsyncFlush("flush progress closed".to_string()))?;
+    }
 }
 
 // initialize
@@ -152,8 +227,6 @@ impl<S: StateStore> StateTable<S> {
             })
             .collect_vec();
 
-        let keyspace = Keyspace::table_root(store, &table_id);
-
         let pk_data_types = pk_indices
             .iter()
             .map(|i| table_columns[*i].data_type.clone())
@@ -171,6 +244,9 @@ impl<S: StateStore> StateTable<S> {
             None => Distribution::fallback(),
         };
 
+        let mut keyspace = Keyspace::table_root(store, &table_id);
+        keyspace.set_vnodes(vnodes.clone());
+
         let vnode_col_idx_in_pk = table_catalog
             .vnode_col_idx
             .as_ref()
@@ -198,6 +274,7 @@ impl<S: StateStore> StateTable<S> {
             true => None,
             false => Some(input_value_indices),
         };
+        let (flush_progress_tx, flush_progress) = watch::channel(FlushProgress::default());
         Self {
             mem_table: MemTable::new(),
             keyspace,
@@ -212,6 +289,10 @@ impl<S: StateStore> StateTable<S> {
             vnode_col_idx_in_pk,
             value_indices,
             epoch: None,
+            sealed_mem_tables: VecDeque::new(),
+            flush_progress,
+            flush_progress_tx: Arc::new(flush_progress_tx),
+            flush_tasks: FlushTasks::default(),
         }
     }
 
@@ -269,7 +350,8 @@ impl<S: StateStore> StateTable<S> {
         }: Distribution,
         value_indices: Vec<usize>,
     ) -> Self {
-        let keyspace = Keyspace::table_root(store, &table_id);
+        let mut keyspace = Keyspace::table_root(store, &table_id);
+        keyspace.set_vnodes(vnodes.clone());
 
         let pk_data_types = pk_indices
             .iter()
@@ -295,6 +377,7 @@ impl<S: StateStore> StateTable<S> {
                     })
             })
             .collect_vec();
+        let (flush_progress_tx, flush_progress) = watch::channel(FlushProgress::default());
         Self {
             mem_table: MemTable::new(),
             keyspace,
@@ -309,6 +392,10 @@ impl<S: StateStore> StateTable<S> {
             vnode_col_idx_in_pk: None,
             value_indices: Some(value_indices),
             epoch: None,
+            sealed_mem_tables: VecDeque::new(),
+            flush_progress,
+            flush_progress_tx: Arc::new(flush_progress_tx),
+            flush_tasks: FlushTasks::default(),
         }
     }
 
@@ -354,7 +441,9 @@ impl<S: StateStore> StateTable<S> {
         let prefix_len = pk_prefix.0.len();
         if let Some(vnode_col_idx_in_pk) = self.vnode_col_idx_in_pk {
             let vnode = pk_prefix.0.get(vnode_col_idx_in_pk).unwrap();
-            vnode.clone().unwrap().into_int16() as _
+            let vnode = vnode.clone().unwrap().into_int16() as _;
+            check_vnode_is_set(vnode, &self.vnodes);
+            vnode
         } else {
             // For streaming, the given prefix must be enough to calculate the vnode
             assert!(self.dist_key_in_pk_indices.iter().all(|&d| d < prefix_len));
@@ -371,10 +460,20 @@ impl<S: StateStore> StateTable<S> {
         &self.pk_serde
     }
 
+    /// Get the vnodes owned by the state table.
+    pub fn vnode_bitmap(&self) -> &Arc<Bitmap> {
+        &self.vnodes
+    }
+
     pub fn is_dirty(&self) -> bool {
         self.mem_table.is_dirty()
     }
 
+    /// Get the tracker of the flushes in the background, owned by the state store.
+    pub fn flush_tracker(&self) -> &Arc<FlushTracker> {
+        self.keyspace.state_store().flush_tracker()
+    }
+
     fn get_read_option(&self, epoch: u64) -> ReadOptions {
         ReadOptions {
             epoch,
@@ -386,12 +485,20 @@ impl<S: StateStore> StateTable<S> {
 
 const ENABLE_SANITY_CHECK: bool = cfg!(debug_assertions);
 
+/// Mem-tables with at least this many rows are flushed in the background on barriers.
+pub const ASYNC_FLUSH_MIN_ROWS: usize = 4096;
+
 // point get
 impl<S: StateStore> StateTable<S> {
     /// Get a single row from state table.
     pub async fn get_row<'a>(&'a self, pk: &'a Row) -> StorageResult<Option<Row>> {
         let serialized_pk = serialize_pk_with_vnode(pk, &self.pk_serde, self.compute_vnode(pk));
-        let mem_table_res = self.mem_table.get_row_op(&serialized_pk);
+        let mem_table_res = self.mem_table.get_row_op(&serialized_pk).or_else(|| {
+            self.sealed_mem_tables
+                .iter()
+                .rev()
+                .find_map(|(_, mem_table)| mem_table.get(&serialized_pk))
+        });
 
         let read_options = self.get_read_option(self.epoch());
         match mem_table_res {
@@ -445,6 +552,7 @@ impl<S: StateStore> StateTable<S> {
         }
         assert_eq!(self.vnodes.len(), new_vnodes.len());
 
+        self.keyspace.set_vnodes(new_vnodes.clone());
         std::mem::replace(&mut self.vnodes, new_vnodes)
     }
 }
@@ -516,10 +624,17 @@ impl<S: StateStore> StateTable<S> {
 
         let mut vnode_and_pks = vec![vec![]; chunk.capacity()];
 
-        compute_chunk_vnode(&chunk, &self.dist_key_indices, &self.vnodes)
-            .into_iter()
-            .zip_eq(vnode_and_pks.iter_mut())
-            .for_each(|(vnode, vnode_and_pk)| vnode_and_pk.extend(vnode.to_be_bytes()));
+        match self.vnode_col_idx_in_pk {
+            Some(vnode_col_idx_in_pk) => compute_chunk_vnode_by_column(
+                &chunk,
+                self.pk_indices[vnode_col_idx_in_pk],
+                &self.vnodes,
+            ),
+            None => compute_chunk_vnode(&chunk, &self.dist_key_indices, &self.vnodes),
+        }
+        .into_iter()
+        .zip_eq(vnode_and_pks.iter_mut())
+        .for_each(|(vnode, vnode_and_pk)| vnode_and_pk.extend(encode_vnode(vnode)));
 
         let value_chunk = if let Some(ref value_indices) = self.value_indices {
             chunk.clone().reorder_columns(value_indices)
@@ -540,7 +655,7 @@ impl<S: StateStore> StateTable<S> {
 
         let (_, vis) = key_chunk.into_parts();
         match vis {
-            Vis::Bitmap(vis) => {
+            Vis::Bitmap(_) | Vis::Selection(_) => {
                 for ((op, key, value), vis) in izip!(op, vnode_and_pks, values).zip_eq(vis.iter()) {
                     if vis {
                         match op {
@@ -574,22 +689,88 @@ impl<S: StateStore> StateTable<S> {
         self.epoch = Some(new_epoch);
     }
 
+    /// Seals the mem-table of the previous epoch and flushes it to the state store. Large
+    /// mem-tables are flushed in the background, so that the barrier can be forwarded without
+    /// waiting for the flush. The epoch is only collected after the flush is done, see
+    /// [`FlushTracker`].
     pub async fn commit(&mut self, new_epoch: EpochPair) -> StorageResult<()> {
         assert_eq!(self.epoch(), new_epoch.prev);
-        let mem_table = std::mem::take(&mut self.mem_table).into_parts();
-        self.batch_write_rows(mem_table, new_epoch.prev).await?;
+        self.seal_and_flush(new_epoch.prev).await?;
         self.update_epoch(new_epoch);
         Ok(())
     }
 
     /// used for unit test, and do not need to assert epoch.
     pub async fn commit_for_test(&mut self, new_epoch: EpochPair) -> StorageResult<()> {
+        self.wait_for_flush().await?;
         let mem_table = std::mem::take(&mut self.mem_table).into_parts();
-        self.batch_write_rows(mem_table, new_epoch.prev).await?;
+        self.batch_write_rows(&mem_table, new_epoch.prev).await?;
         self.update_epoch(new_epoch);
         Ok(())
     }
 
+    async fn seal_and_flush(&mut self, epoch: u64) -> StorageResult<()> {
+        // Surface the error of the previous flushes, and drop the mem-tables already ingested.
+        let flushed_epoch = {
+            let flush_progress = self.flush_progress.borrow();
+            if let Some(error) = &flush_progress.error {
+                return Err(StorageError::AsyncFlush(error.clone()));
+            }
+            flush_progress.flushed_epoch
+        };
+        self.sealed_mem_tables
+            .retain(|(sealed_epoch, _)| *sealed_epoch > flushed_epoch);
+        self.flush_tasks
+            .0
+            .retain(|(sealed_epoch, _)| *sealed_epoch > flushed_epoch);
+        if !self.is_dirty() {
+            return Ok(());
+        }
+        // Small mem-tables are cheap to flush in place, unless they have to wait for the flushes
+        // in the background to keep the order.
+        if self.sealed_mem_tables.is_empty() && self.mem_table.row_count() < ASYNC_FLUSH_MIN_ROWS {
+            let mem_table = std::mem::take(&mut self.mem_table).into_parts();
+            return self.batch_write_rows(&mem_table, epoch).await;
+        }
+        let prev_sealed_epoch = self.sealed_mem_tables.back().map(|(epoch, _)| *epoch);
+
+        let mem_table = Arc::new(std::mem::take(&mut self.mem_table).into_parts());
+        self.sealed_mem_tables.push_back((epoch, mem_table.clone()));
+
+        let guard = self.flush_tracker().register(epoch);
+        let mut this = self.clone();
+        let handle = tokio::spawn(async move {
+            let result = async {
+                if let Some(prev_sealed_epoch) = prev_sealed_epoch {
+                    wait_flushed(this.flush_progress.clone(), prev_sealed_epoch).await?;
+                }
+                this.batch_write_rows(&mem_table, epoch).await
+            }
+            .await;
+            if let Err(e) = &result {
+                tracing::error!(table_id = ?this.table_id(), epoch, "failed to flush: {}", e);
+            }
+            guard.finish(&result);
+            this.flush_progress_tx
+                .send_modify(|flush_progress| match result {
+                    Ok(()) => flush_progress.flushed_epoch = epoch,
+                    Err(e) => {
+                        flush_progress.error.get_or_insert(e.to_string());
+                    }
+                });
+        });
+        self.flush_tasks.0.push_back((epoch, handle));
+        Ok(())
+    }
+
+    /// Waits until all the sealed mem-tables have been ingested into the state store.
+    async fn wait_for_flush(&self) -> StorageResult<()> {
+        match self.sealed_mem_tables.back() {
+            Some((epoch, _)) => wait_flushed(self.flush_progress.clone(), *epoch).await,
+            None => Ok(()),
+        }
+    }
+
     // TODO(st1page): maybe we should extract a pub struct to do it
     /// just specially used by those state table read-only and after the call the data
     /// in the epoch will be visible
@@ -601,7 +782,7 @@ impl<S: StateStore> StateTable<S> {
     /// Write to state store.
     async fn batch_write_rows(
         &mut self,
-        buffer: BTreeMap<Vec<u8>, RowOp>,
+        buffer: &BTreeMap<Vec<u8>, RowOp>,
         epoch: u64,
     ) -> StorageResult<()> {
         let mut write_batch = self.keyspace.start_write_batch(WriteOptions {
@@ -615,22 +796,22 @@ impl<S: StateStore> StateTable<S> {
                 // state table.
                 RowOp::Insert(row) => {
                     if ENABLE_SANITY_CHECK && !self.disable_sanity_check {
-                        self.do_insert_sanity_check(&pk, &row, epoch).await?;
+                        self.do_insert_sanity_check(pk, row, epoch).await?;
                     }
-                    write_batch.put(pk, StorageValue::new_put(row));
+                    write_batch.put(pk, StorageValue::new_put(row.clone()));
                 }
                 RowOp::Delete(row) => {
                     if ENABLE_SANITY_CHECK && !self.disable_sanity_check {
-                        self.do_delete_sanity_check(&pk, &row, epoch).await?;
+                        self.do_delete_sanity_check(pk, row, epoch).await?;
                     }
                     write_batch.delete(pk);
                 }
                 RowOp::Update((old_row, new_row)) => {
                     if ENABLE_SANITY_CHECK && !self.disable_sanity_check {
-                        self.do_update_sanity_check(&pk, &old_row, &new_row, epoch)
+                        self.do_update_sanity_check(pk, old_row, new_row, epoch)
                             .await?;
                     }
-                    write_batch.put(pk, StorageValue::new_put(new_row));
+                    write_batch.put(pk, StorageValue::new_put(new_row.clone()));
                 }
             }
         }
@@ -793,7 +974,8 @@ impl<S: StateStore> StateTable<S> {
             to_memcomparable_bound(&pk_range.1, true),
         );
 
-        let memcomparable_range_with_vnode = prefixed_range(memcomparable_range, &[vnode]);
+        let memcomparable_range_with_vnode =
+            prefixed_range(memcomparable_range, &encode_vnode(vnode));
 
         // TODO: provide a trace of useful params.
 
@@ -847,11 +1029,29 @@ impl<S: StateStore> StateTable<S> {
         )
     }
 
+    /// This function scans all rows of the relational table under the given `vnode`, return both
+    /// key and value. The returned keys are prefixed with the vnode.
+    pub async fn iter_key_and_val_with_vnode(
+        &self,
+        vnode: VirtualNode,
+    ) -> StorageResult<RowStreamWithPk<'_, S>> {
+        let encoded_key_range = range_of_prefix(&encode_vnode(vnode));
+        let (mem_table_iter, storage_iter_stream) = self
+            .iter_inner(encoded_key_range, None, self.epoch())
+            .await?;
+        let storage_iter = storage_iter_stream.into_stream();
+
+        Ok(
+            StateTableRowIter::new(mem_table_iter, storage_iter, self.row_deserializer.clone())
+                .into_stream(),
+        )
+    }
+
     async fn iter_with_pk_prefix_inner<'a>(
         &'a self,
         pk_prefix: &'a Row,
         epoch: u64,
-    ) -> StorageResult<(MemTableIter<'_>, StorageIterInner<S>)> {
+    ) -> StorageResult<(MemTableOpIter<'_>, StorageIterInner<S>)> {
         let prefix_serializer = self.pk_serde.prefix(pk_prefix.size());
         let encoded_prefix = serialize_pk(pk_prefix, &prefix_serializer);
         let encoded_key_range = range_of_prefix(&encoded_prefix);
@@ -859,7 +1059,7 @@ impl<S: StateStore> StateTable<S> {
         // We assume that all usages of iterating the state table only access a single vnode.
         // If this assertion fails, then something must be wrong with the operator implementation or
         // the distribution derivation from the optimizer.
-        let vnode = self.compute_vnode(pk_prefix).to_be_bytes();
+        let vnode = encode_vnode(self.compute_vnode(pk_prefix));
         let encoded_key_range_with_vnode = prefixed_range(encoded_key_range, &vnode);
 
         // Construct prefix hint for prefix bloom filter.
@@ -888,9 +1088,21 @@ impl<S: StateStore> StateTable<S> {
         key_range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
         prefix_hint: Option<Vec<u8>>,
         epoch: u64,
-    ) -> StorageResult<(MemTableIter<'_>, StorageIterInner<S>)> {
-        // Mem table iterator.
-        let mem_table_iter = self.mem_table.iter(key_range.clone());
+    ) -> StorageResult<(MemTableOpIter<'_>, StorageIterInner<S>)> {
+        // Mem table iterator, over the sealed mem-tables not flushed yet as well.
+        let mem_table_iter = if self.sealed_mem_tables.is_empty() {
+            Either::Left(
+                self.mem_table
+                    .iter(key_range.clone())
+                    .map(|(pk, row_op)| (Cow::Borrowed(pk), Cow::Borrowed(row_op))),
+            )
+        } else {
+            Either::Right(
+                self.latest_mem_table_ops(&key_range)
+                    .into_iter()
+                    .map(|(pk, row_op)| (Cow::Owned(pk), Cow::Owned(row_op))),
+            )
+        };
 
         // Storage iterator.
         let storage_iter = StorageIterInner::<S>::new(
@@ -904,8 +1116,35 @@ impl<S: StateStore> StateTable<S> {
 
         Ok((mem_table_iter, storage_iter))
     }
+
+    /// Returns the latest operations on the keys in `key_range` in the sealed mem-tables and
+    /// `mem_table`. Whether a sealed mem-table has been ingested by the state store is unknown to
+    /// the iterators, so the operations are turned into inserts of the latest rows or deletes,
+    /// which are correct either way.
+    fn latest_mem_table_ops(
+        &self,
+        key_range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ) -> Vec<(Vec<u8>, RowOp)> {
+        let mut latest_ops = BTreeMap::new();
+        for (_, mem_table) in &self.sealed_mem_tables {
+            latest_ops.extend(mem_table.range(key_range.clone()));
+        }
+        latest_ops.extend(self.mem_table.iter(key_range.clone()));
+        latest_ops
+            .into_iter()
+            .map(|(pk, row_op)| {
+                let row_op = match row_op {
+                    RowOp::Insert(row) | RowOp::Update((_, row)) => RowOp::Insert(row.clone()),
+                    RowOp::Delete(row) => RowOp::Delete(row.clone()),
+                };
+                (pk.clone(), row_op)
+            })
+            .collect()
+    }
 }
 
+type MemTableOpIter<'a> = impl Iterator<Item = (Cow<'a, Vec<u8>>, Cow<'a, RowOp>)>;
+
 pub type RowStream<'a, S: StateStore> = impl Stream<Item = StorageResult<Cow<'a, Row>>>;
 pub type RowStreamWithPk<'a, S: StateStore> =
     impl Stream<Item = StorageResult<(Cow<'a, Vec<u8>>, Cow<'a, Row>)>>;
@@ -921,7 +1160,7 @@ struct StateTableRowIter<'a, M, C> {
 
 impl<'a, M, C> StateTableRowIter<'a, M, C>
 where
-    M: Iterator<Item = (&'a Vec<u8>, &'a RowOp)>,
+    M: Iterator<Item = (Cow<'a, Vec<u8>>, Cow<'a, RowOp>)>,
     C: Stream<Item = StorageResult<(Vec<u8>, Row)>>,
 {
     fn new(mem_table_iter: M, storage_iter: C, deserializer: RowDeserializer) -> Self {
@@ -954,17 +1193,17 @@ where
                 // The stream side has come to an end, return data from the mem table.
                 (None, Some(_)) => {
                     let (pk, row_op) = mem_table_iter.next().unwrap();
-                    match row_op {
+                    match &*row_op {
                         RowOp::Insert(row_bytes) | RowOp::Update((_, row_bytes)) => {
                             let row = self.deserializer.deserialize(row_bytes.as_ref())?;
 
-                            yield (Cow::Borrowed(pk), Cow::Owned(row))
+                            yield (pk, Cow::Owned(row))
                         }
                         _ => {}
                     }
                 }
                 (Some(Ok((storage_pk, _))), Some((mem_table_pk, _))) => {
-                    match storage_pk.cmp(mem_table_pk) {
+                    match storage_pk.cmp(&**mem_table_pk) {
                         Ordering::Less => {
                             // yield data from storage
                             let (pk, row) = storage_iter.next().await.unwrap()?;
@@ -976,11 +1215,11 @@ where
 
                             let (pk, row_op) = mem_table_iter.next().unwrap();
                             let (_, old_row_in_storage) = storage_iter.next().await.unwrap()?;
-                            match row_op {
+                            match &*row_op {
                                 RowOp::Insert(row_bytes) => {
                                     let row = self.deserializer.deserialize(row_bytes.as_ref())?;
 
-                                    yield (Cow::Borrowed(pk), Cow::Owned(row));
+                                    yield (pk, Cow::Owned(row));
                                 }
                                 RowOp::Delete(_) => {}
                                 RowOp::Update((old_row_bytes, new_row_bytes)) => {
@@ -991,7 +1230,7 @@ where
 
                                     debug_assert!(old_row == old_row_in_storage);
 
-                                    yield (Cow::Borrowed(pk), Cow::Owned(new_row));
+                                    yield (pk, Cow::Owned(new_row));
                                 }
                             }
                         }
@@ -999,11 +1238,11 @@ where
                             // yield data from mem table
                             let (pk, row_op) = mem_table_iter.next().unwrap();
 
-                            match row_op {
+                            match &*row_op {
                                 RowOp::Insert(row_bytes) => {
                                     let row = self.deserializer.deserialize(row_bytes.as_ref())?;
 
-                                    yield (Cow::Borrowed(pk), Cow::Owned(row));
+                                    yield (pk, Cow::Owned(row));
                                 }
                                 RowOp::Delete(_) => {}
                                 RowOp::Update(_) => unreachable!(
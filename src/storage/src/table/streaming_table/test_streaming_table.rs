@@ -12,18 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use futures::{pin_mut, StreamExt};
+use std::ops::Bound::{Excluded, Included};
+
+use bytes::Bytes;
+use futures::{pin_mut, StreamExt, TryStreamExt};
+use itertools::Itertools;
 use risingwave_common::array::{Op, Row, StreamChunk};
 use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::{ColumnDesc, ColumnId, TableId};
-use risingwave_common::types::DataType;
+use risingwave_common::types::{DataType, VIRTUAL_NODE_COUNT};
 use risingwave_common::util::epoch::EpochPair;
+use risingwave_common::util::hash_util::Crc32FastBuilder;
 use risingwave_common::util::sort_util::OrderType;
+use risingwave_hummock_sdk::key::{key_with_epoch, table_prefix};
+use risingwave_hummock_sdk::key_range::{KeyRange, KeyRangeCommon};
+use risingwave_pb::catalog::{ColumnIndex, Table};
+use risingwave_pb::plan_common::{ColumnCatalog, ColumnOrder};
 
 use crate::error::StorageResult;
+use crate::hummock::iterator::test_utils::mock_sstable_store;
+use crate::hummock::test_utils::{default_builder_opt_for_test, gen_test_sstable};
+use crate::hummock::value::HummockValue;
 use crate::memory::MemoryStateStore;
-use crate::table::streaming_table::state_table::StateTable;
+use crate::store::ReadOptions;
+use crate::table::streaming_table::state_table::{StateTable, ASYNC_FLUSH_MIN_ROWS};
 use crate::table::DEFAULT_VNODE;
+use crate::StateStore;
 
 // test state table
 #[tokio::test]
@@ -1518,3 +1532,353 @@ async fn test_state_table_write_chunk_value_indices() {
         &Row::new(vec![Some(false.into()), Some(4888i64.into()),])
     );
 }
+
+#[tokio::test]
+async fn test_state_table_async_flush() -> StorageResult<()> {
+    const EPOCH: u64 = 0x10000;
+
+    let state_store = MemoryStateStore::new();
+    let flush_tracker = state_store.flush_tracker().clone();
+    let new_state_table = |table_id: u32, epoch| {
+        let mut state_table = StateTable::new_without_distribution(
+            state_store.clone(),
+            TableId::from(table_id),
+            vec![
+                ColumnDesc::unnamed(ColumnId::from(0), DataType::Int32),
+                ColumnDesc::unnamed(ColumnId::from(1), DataType::Int32),
+            ],
+            vec![OrderType::Ascending],
+            vec![0],
+        );
+        state_table.init_epoch(EpochPair::new_test_epoch(epoch));
+        state_table
+    };
+    let row = |k: usize, v: usize| Row(vec![Some((k as i32).into()), Some((v as i32).into())]);
+    let pk = |k: usize| Row(vec![Some((k as i32).into())]);
+
+    let row_count = ASYNC_FLUSH_MIN_ROWS * 4;
+
+    let mut state_table = new_state_table(0x42, EPOCH);
+
+    // The barrier is not blocked by flushing a large mem-table: the test runtime is
+    // single-threaded, so the flush can't make any progress until the test yields.
+    for k in 0..row_count {
+        state_table.insert(row(k, k));
+    }
+    state_table
+        .commit(EpochPair::new_test_epoch(EPOCH + 1))
+        .await?;
+    assert_eq!(flush_tracker.pending_count(EPOCH), 1);
+    // The flushes are tracked per state store, and don't block the other stores in the process.
+    assert_eq!(
+        MemoryStateStore::new().flush_tracker().pending_count(EPOCH),
+        0
+    );
+    // The commit returns before the mem-table is ingested.
+    assert_eq!(new_state_table(0x42, EPOCH).get_row(&pk(0)).await?, None);
+
+    // Seal a small mem-table right after. It's flushed in the background as well to keep the
+    // order of epochs.
+    state_table.update(row(0, 0), row(0, 100));
+    state_table.delete(row(1, 1));
+    state_table
+        .commit(EpochPair::new_test_epoch(EPOCH + 2))
+        .await?;
+    assert_eq!(flush_tracker.pending_count(EPOCH), 1);
+    assert_eq!(flush_tracker.pending_count(EPOCH + 1), 1);
+
+    // Reads see the latest writes across the seal boundaries before they are flushed.
+    assert_eq!(state_table.get_row(&pk(0)).await?, Some(row(0, 100)));
+    assert_eq!(state_table.get_row(&pk(1)).await?, None);
+    assert_eq!(state_table.get_row(&pk(2)).await?, Some(row(2, 2)));
+    // So do the iterators, without waiting for the flushes.
+    let rows: Vec<_> = state_table.iter().await?.try_collect().await?;
+    assert_eq!(rows.len(), row_count - 1);
+    assert_eq!(&*rows[0], &row(0, 100));
+    assert_eq!(flush_tracker.pending_count(EPOCH), 1);
+
+    // Both epochs are ingested in order after the flushes are done.
+    flush_tracker.wait_epoch(EPOCH + 1).await?;
+    assert_eq!(flush_tracker.pending_count(EPOCH), 0);
+    assert_eq!(flush_tracker.pending_count(EPOCH + 1), 0);
+
+    let state_table = new_state_table(0x42, EPOCH);
+    assert_eq!(state_table.get_row(&pk(0)).await?, Some(row(0, 0)));
+    assert_eq!(state_table.get_row(&pk(1)).await?, Some(row(1, 1)));
+    let rows = state_table.iter().await?.count().await;
+    assert_eq!(rows, row_count);
+
+    let state_table = new_state_table(0x42, EPOCH + 1);
+    assert_eq!(state_table.get_row(&pk(0)).await?, Some(row(0, 100)));
+    assert_eq!(state_table.get_row(&pk(1)).await?, None);
+    let rows = state_table.iter().await?.count().await;
+    assert_eq!(rows, row_count - 1);
+
+    Ok(())
+}
+
+/// The state table of each simulated actor owns half of the vnodes.
+const ACTOR_VNODE_SPLIT: usize = VIRTUAL_NODE_COUNT / 2;
+
+/// The shape of the state table of an executor, with columns `(a int, b int, vnode smallint)`.
+struct StateTableShape {
+    pk_indices: Vec<usize>,
+    dist_key_indices: Vec<usize>,
+    vnode_col_idx: Option<usize>,
+    /// The columns the vnode of a row is computed by, which are the distribution key of the
+    /// upstream if the vnode column is specified.
+    vnode_key_indices: Vec<usize>,
+}
+
+fn state_table_catalog(table_id: u32, shape: &StateTableShape) -> Table {
+    Table {
+        id: table_id,
+        columns: [DataType::Int32, DataType::Int32, DataType::Int16]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, data_type)| ColumnCatalog {
+                column_desc: Some(
+                    ColumnDesc::unnamed(ColumnId::from(idx as i32), data_type).to_protobuf(),
+                ),
+                is_hidden: false,
+            })
+            .collect(),
+        pk: shape
+            .pk_indices
+            .iter()
+            .map(|&index| ColumnOrder {
+                order_type: OrderType::Ascending.to_prost() as i32,
+                index: index as u32,
+            })
+            .collect(),
+        distribution_key: shape
+            .dist_key_indices
+            .iter()
+            .map(|&index| index as i32)
+            .collect(),
+        vnode_col_idx: shape.vnode_col_idx.map(|index| ColumnIndex {
+            index: index as u64,
+        }),
+        value_indices: vec![0, 1, 2],
+        ..Default::default()
+    }
+}
+
+/// The vnodes owned by the simulated actor `actor_idx`. If `split_vnodes` is false, every actor
+/// owns all the vnodes, as if the rows were dispatched to the actors regardless of their vnodes.
+fn actor_vnodes(actor_idx: usize, split_vnodes: bool) -> Bitmap {
+    (0..VIRTUAL_NODE_COUNT)
+        .map(|vnode| !split_vnodes || (vnode >= ACTOR_VNODE_SPLIT) == (actor_idx == 1))
+        .collect()
+}
+
+/// Writes rows from two simulated actors of a fragment, each with a state store of its own, and
+/// returns the keys written by each actor. If `split_vnodes`, the rows are dispatched to the
+/// actor owning their vnodes, otherwise they are dispatched round-robin.
+async fn write_from_actors(
+    shape: &StateTableShape,
+    split_vnodes: bool,
+    write_chunk: bool,
+) -> Vec<Vec<Bytes>> {
+    let data_types = [DataType::Int32, DataType::Int32, DataType::Int16];
+    let table_id = 0x42;
+    let catalog = state_table_catalog(table_id, shape);
+    let epoch = EpochPair::new_test_epoch(1);
+    let state_stores = [MemoryStateStore::new(), MemoryStateStore::new()];
+    let mut actors = (0..2)
+        .map(|actor_idx| {
+            let mut state_table = StateTable::from_table_catalog(
+                &catalog,
+                state_stores[actor_idx].clone(),
+                Some(actor_vnodes(actor_idx, split_vnodes).into()),
+            );
+            state_table.init_epoch(epoch);
+            state_table
+        })
+        .collect_vec();
+
+    for i in 0..100_i32 {
+        let mut row = Row::new(vec![Some(i.into()), Some((i % 23).into()), None]);
+        let vnode = row
+            .hash_by_indices(&shape.vnode_key_indices, &Crc32FastBuilder {})
+            .to_vnode();
+        row.0[2] = Some((vnode as i16).into());
+        let actor_idx = if split_vnodes {
+            (vnode as usize >= ACTOR_VNODE_SPLIT) as usize
+        } else {
+            i as usize % 2
+        };
+        let actor = &mut actors[actor_idx];
+        if write_chunk {
+            actor.write_chunk(StreamChunk::from_rows(&[(Op::Insert, row)], &data_types));
+        } else {
+            actor.insert(row);
+        }
+    }
+
+    for actor in &mut actors {
+        actor.commit_for_test(epoch.inc()).await.unwrap();
+    }
+
+    let mut keys = vec![];
+    for state_store in state_stores {
+        let actor_keys = state_store
+            .scan(
+                None,
+                (
+                    Included(table_prefix(table_id)),
+                    Excluded(table_prefix(table_id + 1)),
+                ),
+                None,
+                ReadOptions {
+                    epoch: epoch.curr,
+                    table_id: TableId::new(table_id),
+                    retention_seconds: None,
+                },
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect_vec();
+        keys.push(actor_keys);
+    }
+    assert_eq!(keys.iter().map(Vec::len).sum::<usize>(), 100);
+    keys
+}
+
+/// Checks that the key ranges written by the actors of a fragment are disjoint.
+async fn check_actor_key_ranges_disjoint(shape: StateTableShape) {
+    for write_chunk in [false, true] {
+        let keys = write_from_actors(&shape, true, write_chunk).await;
+        // The keys are sorted by the scan, and the keys of the first actor all come before the ones
+        // of the second actor.
+        assert!(keys[0].last().unwrap() < keys[1].first().unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_materialize_key_ranges_disjoint() {
+    check_actor_key_ranges_disjoint(StateTableShape {
+        pk_indices: vec![0],
+        dist_key_indices: vec![0],
+        vnode_col_idx: None,
+        vnode_key_indices: vec![0],
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_hash_agg_key_ranges_disjoint() {
+    // The group key is `b, a`.
+    check_actor_key_ranges_disjoint(StateTableShape {
+        pk_indices: vec![1, 0],
+        dist_key_indices: vec![1, 0],
+        vnode_col_idx: None,
+        vnode_key_indices: vec![1, 0],
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_local_agg_key_ranges_disjoint() {
+    // The group key of the local phase of a two-phase agg is `b, vnode`, where the vnode column is
+    // computed by the upstream distribution key `a`, and is after the other group keys.
+    check_actor_key_ranges_disjoint(StateTableShape {
+        pk_indices: vec![1, 2],
+        dist_key_indices: vec![],
+        vnode_col_idx: Some(2),
+        vnode_key_indices: vec![0],
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_hash_join_key_ranges_disjoint() {
+    // The join key is `b`, and the stream key is `a`.
+    check_actor_key_ranges_disjoint(StateTableShape {
+        pk_indices: vec![1, 0],
+        dist_key_indices: vec![1],
+        vnode_col_idx: None,
+        vnode_key_indices: vec![1],
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_local_top_n_key_ranges_disjoint() {
+    // The local phase of a two-phase top-n is grouped by the vnode column, and ordered by `b`.
+    check_actor_key_ranges_disjoint(StateTableShape {
+        pk_indices: vec![2, 1, 0],
+        dist_key_indices: vec![0],
+        vnode_col_idx: Some(2),
+        vnode_key_indices: vec![0],
+    })
+    .await;
+}
+
+/// Builds an L0 SST of the keys written by each simulated actor, as if it's flushed by the compute
+/// node of the actor, and returns whether the SSTs overlap, in which case compacting either of
+/// them takes the other as input as well.
+async fn actor_ssts_overlap(keys: Vec<Vec<Bytes>>) -> bool {
+    let sstable_store = mock_sstable_store();
+    let epoch = EpochPair::new_test_epoch(1).curr;
+    let mut key_ranges = vec![];
+    for (actor_idx, keys) in keys.into_iter().enumerate() {
+        let sst = gen_test_sstable(
+            default_builder_opt_for_test(),
+            actor_idx as u64 + 1,
+            keys.into_iter().map(|key| {
+                (
+                    key_with_epoch(key.to_vec(), epoch),
+                    HummockValue::put(vec![]),
+                )
+            }),
+            sstable_store.clone(),
+        )
+        .await;
+        key_ranges.push(KeyRange::new(
+            sst.meta.smallest_key.into(),
+            sst.meta.largest_key.into(),
+        ));
+    }
+    key_ranges[0].full_key_overlap(&key_ranges[1])
+}
+
+#[tokio::test]
+async fn test_actor_ssts_not_overlapping() {
+    // The group key of a hash agg is `b, a`.
+    let shape = StateTableShape {
+        pk_indices: vec![1, 0],
+        dist_key_indices: vec![1, 0],
+        vnode_col_idx: None,
+        vnode_key_indices: vec![1, 0],
+    };
+    // The SSTs of the actors overlap if each actor writes the rows of all the vnodes...
+    assert!(actor_ssts_overlap(write_from_actors(&shape, false, false).await).await);
+    // ...and don't if each actor only writes the rows of its own vnodes.
+    assert!(!actor_ssts_overlap(write_from_actors(&shape, true, false).await).await);
+}
+
+#[test]
+#[should_panic]
+fn test_write_vnode_not_owned() {
+    let shape = StateTableShape {
+        pk_indices: vec![1, 2],
+        dist_key_indices: vec![],
+        vnode_col_idx: Some(2),
+        vnode_key_indices: vec![0],
+    };
+    let catalog = state_table_catalog(0x42, &shape);
+    let mut state_table = StateTable::from_table_catalog(
+        &catalog,
+        MemoryStateStore::new(),
+        Some(actor_vnodes(0, true).into()),
+    );
+    state_table.init_epoch(EpochPair::new_test_epoch(1));
+    state_table.insert(Row::new(vec![
+        Some(1_i32.into()),
+        Some(1_i32.into()),
+        Some((ACTOR_VNODE_SPLIT as i16).into()),
+    ]));
+}
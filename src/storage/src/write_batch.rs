@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use bytes::Bytes;
+use risingwave_hummock_sdk::key::decode_vnode;
 
 use crate::error::StorageResult;
 use crate::hummock::HummockError;
@@ -116,6 +117,17 @@ impl<'a, S: StateStore> KeySpaceWriteBatch<'a, S> {
     /// If `key` is valid, it will be prefixed with `keyspace` key.
     /// Otherwise, only `keyspace` key is pushed.
     fn do_push(&mut self, key: &[u8], value: StorageValue) {
+        // The keys are prefixed by the vnode, so that the actors of a fragment, which own disjoint
+        // vnodes, write disjoint key ranges and their SSTs don't overlap.
+        if cfg!(debug_assertions) && let Some(vnodes) = self.keyspace.vnodes() {
+            let vnode = decode_vnode(key);
+            assert!(
+                vnodes.is_set(vnode as usize),
+                "key of vnode {} is written to table {} by an actor not owning it",
+                vnode,
+                self.keyspace.table_id().table_id
+            );
+        }
         let key = self.keyspace.prefixed_key(key).into();
         self.global.batch.push((key, value));
     }
@@ -15,6 +15,7 @@
 pub use builder::*;
 pub use column_mapping::*;
 pub use infallible_expr::*;
+pub use state_prefetch::*;
 use risingwave_common::array::Row;
 use risingwave_storage::table::streaming_table::state_table::{RowStream, StateTable};
 use risingwave_storage::StateStore;
@@ -24,6 +25,7 @@ use crate::executor::StreamExecutorResult;
 mod builder;
 mod column_mapping;
 mod infallible_expr;
+mod state_prefetch;
 
 pub async fn iter_state_table<'a, S: StateStore>(
     state_table: &'a StateTable<S>,
@@ -0,0 +1,322 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use futures::{pin_mut, FutureExt, StreamExt};
+use risingwave_common::array::Row;
+use risingwave_common::collection::estimate_size::EstimateSize;
+use risingwave_common::types::{VirtualNode, VIRTUAL_NODE_SIZE};
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+use risingwave_storage::StateStore;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::executor::monitor::StreamingMetrics;
+use crate::executor::StreamExecutorResult;
+use crate::task::ActorId;
+
+/// Limits of prefetching the state of an executor into its cache when the executor is built, so
+/// that the first barriers after recovery or migration do not run against a cold cache.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StatePrefetchConfig {
+    /// Keep at most this many bytes of the prefetched state. 0 disables the prefetch.
+    pub max_bytes: usize,
+    /// Stop prefetching after this long, so that the executor does not wait for the prefetch
+    /// for longer than this.
+    pub deadline: Duration,
+}
+
+impl StatePrefetchConfig {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes > 0 && !self.deadline.is_zero()
+    }
+}
+
+/// The rows of the keys sharing a prefix of the primary key, e.g. an agg group or a join key,
+/// along with their deserialized primary keys.
+pub type PrefetchedGroup = Vec<(Row, Row)>;
+
+/// A prefetch running in the background, so that the executor keeps processing barriers while
+/// its state is read. The prefetch is aborted if the handle is dropped.
+pub struct StatePrefetchHandle<T>(JoinHandle<StreamExecutorResult<T>>);
+
+impl<T: Send + 'static> StatePrefetchHandle<T> {
+    pub fn spawn(prefetch: impl Future<Output = StreamExecutorResult<T>> + Send + 'static) -> Self {
+        Self(tokio::spawn(prefetch))
+    }
+
+    /// Take the result of the prefetch in `slot` if it has finished. If `wait` is set, wait for
+    /// it to finish, which takes at most the deadline after the prefetch is spawned.
+    pub async fn take_finished(
+        slot: &mut Option<Self>,
+        wait: bool,
+    ) -> Option<StreamExecutorResult<T>> {
+        let handle = &mut slot.as_mut()?.0;
+        let result = if wait {
+            Some(handle.await)
+        } else {
+            handle.now_or_never()
+        }?;
+        *slot = None;
+        Some(match result {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::Error::new(e)
+                .context("state prefetch task failed")
+                .into()),
+        })
+    }
+}
+
+impl<T> Drop for StatePrefetchHandle<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Reads the rows of the vnodes owned by `state_table` until `config.deadline`, and returns the
+/// hottest groups that fit in `config.max_bytes`, hottest first. The rows whose primary keys
+/// share the first `prefix_len` columns form a group, and a group is only returned if all its
+/// rows are read.
+///
+/// The state store does not record when a key was last written, so `hotness` ranks the groups
+/// by what the state itself records instead, e.g. the row count of an agg group.
+pub async fn prefetch_state_table<S: StateStore>(
+    state_table: &StateTable<S>,
+    prefix_len: usize,
+    hotness: impl Fn(&PrefetchedGroup) -> u64,
+    config: &StatePrefetchConfig,
+    metrics: &StreamingMetrics,
+    actor_id: ActorId,
+) -> StreamExecutorResult<Vec<PrefetchedGroup>> {
+    let start = Instant::now();
+    let deadline = start + config.deadline;
+    let pk_serde = state_table.pk_serde();
+
+    let mut hottest = HottestGroups::new(config.max_bytes, hotness);
+    'vnodes: for (vnode, owned) in state_table.vnode_bitmap().iter().enumerate() {
+        if !owned {
+            continue;
+        }
+        let iter = match timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            state_table.iter_key_and_val_with_vnode(vnode as VirtualNode),
+        )
+        .await
+        {
+            Ok(iter) => iter?,
+            Err(_) => break,
+        };
+        pin_mut!(iter);
+
+        let mut group: PrefetchedGroup = vec![];
+        let mut group_bytes = 0;
+        loop {
+            match timeout(
+                deadline.saturating_duration_since(Instant::now()),
+                iter.next(),
+            )
+            .await
+            {
+                Ok(Some(item)) => {
+                    let (pk_bytes, row) = item?;
+                    let pk = pk_serde
+                        .deserialize(&pk_bytes[VIRTUAL_NODE_SIZE..])
+                        .context("failed to deserialize the prefetched key")?;
+                    if let Some((first_pk, _)) = group.first()
+                        && first_pk.0[..prefix_len] != pk.0[..prefix_len]
+                    {
+                        hottest.push(std::mem::take(&mut group), group_bytes);
+                        group_bytes = 0;
+                    }
+                    group_bytes += pk_bytes.len() + row.estimated_size();
+                    group.push((pk, row.into_owned()));
+                }
+                Ok(None) => {
+                    hottest.push(group, group_bytes);
+                    break;
+                }
+                // The group being read may be incomplete, so it is dropped.
+                Err(_) => break 'vnodes,
+            }
+        }
+    }
+
+    let actor_id_str = actor_id.to_string();
+    metrics
+        .state_prefetch_bytes
+        .with_label_values(&[&actor_id_str])
+        .inc_by(hottest.bytes as u64);
+    metrics
+        .state_prefetch_duration
+        .with_label_values(&[&actor_id_str])
+        .observe(start.elapsed().as_secs_f64());
+
+    Ok(hottest.into_hottest_first())
+}
+
+struct HotGroup {
+    hotness: u64,
+    seq: usize,
+    bytes: usize,
+    rows: PrefetchedGroup,
+}
+
+impl PartialEq for HotGroup {
+    fn eq(&self, other: &Self) -> bool {
+        (self.hotness, self.seq) == (other.hotness, other.seq)
+    }
+}
+
+impl Eq for HotGroup {}
+
+impl PartialOrd for HotGroup {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HotGroup {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.hotness, self.seq).cmp(&(other.hotness, other.seq))
+    }
+}
+
+/// Keeps the hottest groups within a byte budget. At least one group is kept, even if it alone
+/// exceeds the budget.
+struct HottestGroups<F> {
+    /// A min-heap, so that the coldest group is evicted first.
+    heap: BinaryHeap<Reverse<HotGroup>>,
+    bytes: usize,
+    max_bytes: usize,
+    hotness: F,
+    next_seq: usize,
+}
+
+impl<F: Fn(&PrefetchedGroup) -> u64> HottestGroups<F> {
+    fn new(max_bytes: usize, hotness: F) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            bytes: 0,
+            max_bytes,
+            hotness,
+            next_seq: 0,
+        }
+    }
+
+    fn push(&mut self, rows: PrefetchedGroup, bytes: usize) {
+        if rows.is_empty() {
+            return;
+        }
+        self.heap.push(Reverse(HotGroup {
+            hotness: (self.hotness)(&rows),
+            seq: self.next_seq,
+            bytes,
+            rows,
+        }));
+        self.next_seq += 1;
+        self.bytes += bytes;
+        while self.bytes > self.max_bytes && self.heap.len() > 1 {
+            let Reverse(coldest) = self.heap.pop().unwrap();
+            self.bytes -= coldest.bytes;
+        }
+    }
+
+    fn into_hottest_first(self) -> Vec<PrefetchedGroup> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(group)| group.rows)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use risingwave_common::catalog::{ColumnDesc, ColumnId, TableId};
+    use risingwave_common::types::DataType;
+    use risingwave_common::util::epoch::EpochPair;
+    use risingwave_common::util::sort_util::OrderType;
+    use risingwave_storage::memory::MemoryStateStore;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_prefetch_hottest_groups() {
+        let column_descs = vec![
+            ColumnDesc::unnamed(ColumnId::new(0), DataType::Int64),
+            ColumnDesc::unnamed(ColumnId::new(1), DataType::Int64),
+        ];
+        let mut state_table = StateTable::new_without_distribution(
+            MemoryStateStore::new(),
+            TableId::new(1),
+            column_descs,
+            vec![OrderType::Ascending, OrderType::Ascending],
+            vec![0, 1],
+        );
+        state_table.init_epoch(EpochPair::new_test_epoch(1));
+        // Key `k` has `[1, 3, 2][k]` rows.
+        for (k, n) in [1i64, 3, 2].into_iter().enumerate() {
+            for v in 0..n {
+                state_table.insert(Row(vec![Some((k as i64).into()), Some(v.into())]));
+            }
+        }
+        state_table
+            .commit(EpochPair::new_test_epoch(2))
+            .await
+            .unwrap();
+
+        let prefetch = |max_bytes| {
+            let state_table = &state_table;
+            async move {
+                let config = StatePrefetchConfig {
+                    max_bytes,
+                    deadline: Duration::from_secs(60),
+                };
+                let groups = prefetch_state_table(
+                    state_table,
+                    1,
+                    |group| group.len() as u64,
+                    &config,
+                    &StreamingMetrics::unused(),
+                    0,
+                )
+                .await
+                .unwrap();
+                groups
+                    .into_iter()
+                    .map(|group| {
+                        let keys = group.iter().map(|(pk, _)| pk.0[0].clone()).dedup();
+                        assert_eq!(keys.count(), 1);
+                        (group[0].0 .0[0].clone().unwrap().into_int64(), group.len())
+                    })
+                    .collect_vec()
+            }
+        };
+
+        // All groups fit in the budget, hottest first.
+        assert_eq!(prefetch(1 << 20).await, vec![(1, 3), (2, 2), (0, 1)]);
+        // Only the hottest group is kept, with all its rows.
+        assert_eq!(prefetch(1).await, vec![(1, 3)]);
+    }
+}
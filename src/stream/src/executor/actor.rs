@@ -22,17 +22,19 @@ use minitrace::prelude::*;
 use parking_lot::Mutex;
 use risingwave_common::util::epoch::EpochPair;
 use risingwave_expr::ExprError;
+use risingwave_tracing::ACTOR_LOG_LEVELS;
 use tokio_stream::StreamExt;
 
 use super::monitor::StreamingMetrics;
 use super::subtask::SubtaskHandle;
-use super::StreamConsumer;
+use super::{Mutation, StreamConsumer};
 use crate::error::StreamResult;
-use crate::task::{ActorId, SharedContext};
+use crate::task::{ActorId, FragmentId, SharedContext};
 
 /// Shared by all operators of an actor.
 pub struct ActorContext {
     pub id: ActorId,
+    pub fragment_id: FragmentId,
 
     // TODO: report errors and prompt the user.
     pub errors: Mutex<HashMap<String, Vec<ExprError>>>,
@@ -42,8 +44,13 @@ pub type ActorContextRef = Arc<ActorContext>;
 
 impl ActorContext {
     pub fn create(id: ActorId) -> ActorContextRef {
+        Self::create_with_fragment(id, 0)
+    }
+
+    pub fn create_with_fragment(id: ActorId, fragment_id: FragmentId) -> ActorContextRef {
         Arc::new(Self {
             id,
+            fragment_id,
             errors: Default::default(),
         })
     }
@@ -131,12 +138,25 @@ where
             last_epoch = Some(barrier.epoch);
 
             // Collect barriers to local barrier manager
-            self.context.lock_barrier_manager().collect(id, &barrier)?;
+            barrier
+                .actor_span("collect_barrier", id, self.actor_context.fragment_id)
+                .in_scope(|| self.context.lock_barrier_manager().collect(id, &barrier))?;
+
+            if let Some(Mutation::SetLogLevel {
+                actors,
+                level,
+                expire_at,
+            }) = barrier.mutation.as_deref()
+                && actors.contains(&id)
+            {
+                ACTOR_LOG_LEVELS.set(id, *level, *expire_at);
+            }
 
             // Then stop this actor if asked
             let to_stop = barrier.is_stop_or_update_drop_actor(id);
             if to_stop {
                 tracing::trace!(actor_id = id, "actor exit");
+                ACTOR_LOG_LEVELS.remove(id);
                 return Ok(());
             }
 
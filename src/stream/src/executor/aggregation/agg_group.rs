@@ -56,6 +56,13 @@ impl<S: StateStore> Debug for AggGroup<S> {
 /// We assume the first state of aggregation is always `StreamingRowCountAgg`.
 const ROW_COUNT_COLUMN: usize = 0;
 
+/// Get the row count of a group from its agg result in the result table.
+pub fn result_row_count(result: &Row) -> usize {
+    result.0[ROW_COUNT_COLUMN]
+        .as_ref()
+        .map_or(0, |x| *x.as_int64() as usize)
+}
+
 /// Information about the changes built by `AggState::build_changes`.
 pub struct AggChangesInfo {
     /// The number of rows and corresponding ops in the changes.
@@ -81,6 +88,29 @@ impl<S: StateStore> AggGroup<S> {
         let prev_result: Option<Row> = result_table
             .get_row(group_key.as_ref().unwrap_or_else(Row::empty))
             .await?;
+        Self::create_with_prev_result(
+            group_key,
+            prev_result,
+            agg_calls,
+            storages,
+            pk_indices,
+            extreme_cache_size,
+            input_schema,
+        )
+        .await
+    }
+
+    /// Create [`AggGroup`] for the given [`AggCall`]s and `group_key`, with the previous agg
+    /// result already read from the result table, e.g. by prefetching.
+    pub async fn create_with_prev_result(
+        group_key: Option<Row>,
+        prev_result: Option<Row>,
+        agg_calls: &[AggCall],
+        storages: &[AggStateStorage<S>],
+        pk_indices: &PkIndices,
+        extreme_cache_size: usize,
+        input_schema: &Schema,
+    ) -> StreamExecutorResult<AggGroup<S>> {
         let prev_outputs: Option<Vec<_>> = prev_result.map(|row| row.0);
         if let Some(prev_outputs) = prev_outputs.as_ref() {
             assert_eq!(prev_outputs.len(), agg_calls.len());
@@ -0,0 +1,278 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::{pin_mut, StreamExt};
+use itertools::Itertools;
+use risingwave_common::array::stream_chunk::Ops;
+use risingwave_common::array::*;
+use risingwave_common::buffer::Bitmap;
+use risingwave_common::types::{Datum, OrderedF64, ScalarImpl};
+use risingwave_common::{bail, must_match};
+use risingwave_expr::vector_op::agg::PercentileSketch;
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+use risingwave_storage::StateStore;
+
+use crate::common::iter_state_table;
+use crate::executor::aggregation::table_state::AggTable;
+use crate::executor::StreamExecutorResult;
+
+/// `StreamingApproxPercentile` estimates the percentile of the float input with a
+/// [`PercentileSketch`], whose buckets are stored in a single row of the state table. Unlike
+/// `HyperLogLog`, the sketch handles deletions exactly, so the input doesn't need to be
+/// append-only and the error bound holds for the current values.
+pub struct StreamingApproxPercentile {
+    fraction: f64,
+    relative_error: f64,
+    sketch: PercentileSketch,
+}
+
+impl StreamingApproxPercentile {
+    pub fn new(fraction: f64, relative_error: f64) -> Self {
+        Self {
+            fraction,
+            relative_error,
+            sketch: PercentileSketch::new(relative_error),
+        }
+    }
+
+    fn apply_row(&mut self, op: Op, value: Option<OrderedF64>) {
+        if let Some(value) = value {
+            match op {
+                Op::Insert | Op::UpdateInsert => self.sketch.add(value.0),
+                Op::Delete | Op::UpdateDelete => self.sketch.remove(value.0),
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: StateStore> AggTable<S> for StreamingApproxPercentile {
+    fn apply_batch(
+        &mut self,
+        ops: Ops<'_>,
+        visibility: Option<&Bitmap>,
+        data: &[&ArrayImpl],
+    ) -> StreamExecutorResult<()> {
+        let array = match data[0] {
+            ArrayImpl::Float64(array) => array,
+            array => bail!(
+                "Unexpected input for approx_percentile: {}",
+                array.get_ident()
+            ),
+        };
+        match visibility {
+            None => {
+                for (op, value) in ops.iter().zip_eq(array.iter()) {
+                    self.apply_row(*op, value);
+                }
+            }
+            Some(visibility) => {
+                for ((visible, op), value) in
+                    visibility.iter().zip_eq(ops.iter()).zip_eq(array.iter())
+                {
+                    if visible {
+                        self.apply_row(*op, value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_output(&mut self) -> StreamExecutorResult<Datum> {
+        Ok(self
+            .sketch
+            .percentile(self.fraction)
+            .map(|value| ScalarImpl::Float64(value.into())))
+    }
+
+    async fn update_from_state_table(
+        &mut self,
+        state_table: &StateTable<S>,
+        group_key: Option<&Row>,
+    ) -> StreamExecutorResult<()> {
+        let data_iter = iter_state_table(state_table, group_key).await?;
+        pin_mut!(data_iter);
+        if let Some(state_row) = data_iter.next().await {
+            let state_row = state_row?;
+            let buckets = must_match!(
+                &state_row[group_key.map(|row| row.size()).unwrap_or_default()],
+                Some(ScalarImpl::List(list)) => list
+            );
+            let serialized = buckets
+                .values()
+                .iter()
+                .map(|datum| must_match!(datum, Some(ScalarImpl::Int64(v)) => *v))
+                .collect_vec();
+            self.sketch = PercentileSketch::deserialize(self.relative_error, &serialized);
+        }
+        Ok(())
+    }
+
+    async fn commit_state(
+        &self,
+        state_table: &mut StateTable<S>,
+        group_key: Option<&Row>,
+    ) -> StreamExecutorResult<()> {
+        let mut current_row = group_key
+            .map(|row| row.values().cloned().collect_vec())
+            .unwrap_or_default();
+        current_row.push(Some(ScalarImpl::List(ListValue::new(
+            self.sketch
+                .serialize()
+                .into_iter()
+                .map(|v| Some(ScalarImpl::Int64(v)))
+                .collect_vec(),
+        ))));
+        let current_row = Row::new(current_row);
+
+        let state_row = {
+            let data_iter = iter_state_table(state_table, group_key).await?;
+            pin_mut!(data_iter);
+            if let Some(state_row) = data_iter.next().await {
+                Some(state_row?)
+            } else {
+                None
+            }
+        };
+        match state_row {
+            Some(state_row) => {
+                state_table.update(state_row.into_owned(), current_row);
+            }
+            None => {
+                state_table.insert(current_row);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{ColumnDesc, ColumnId, TableId};
+    use risingwave_common::types::DataType;
+    use risingwave_common::util::epoch::EpochPair;
+    use risingwave_common::util::sort_util::OrderType;
+    use risingwave_storage::memory::MemoryStateStore;
+
+    use super::*;
+
+    fn apply<S: StateStore>(
+        state: &mut dyn AggTable<S>,
+        ops: &[Op],
+        values: &[Option<f64>],
+    ) -> StreamExecutorResult<()> {
+        let array =
+            F64Array::from_slice(&values.iter().map(|v| v.map(OrderedF64::from)).collect_vec());
+        state.apply_batch(ops, None, &[&array.into()])
+    }
+
+    fn output<S: StateStore>(state: &mut dyn AggTable<S>) -> Option<f64> {
+        state
+            .get_output()
+            .unwrap()
+            .map(|datum| datum.into_float64().0)
+    }
+
+    /// Applies insertions and deletions of shuffled values, and compares the estimation against
+    /// the exact percentiles of the remaining values.
+    #[test]
+    fn test_retraction() {
+        const N: usize = 10_000;
+        const RELATIVE_ERROR: f64 = 0.01;
+
+        let mut state = StreamingApproxPercentile::new(0.9, RELATIVE_ERROR);
+        let state: &mut dyn AggTable<MemoryStateStore> = &mut state;
+        // 7919 is a prime, so the values are shuffled.
+        let values = (0..N)
+            .map(|i| Some(((i * 7919) % N) as f64 - 1000.0))
+            .collect_vec();
+        apply(state, &[Op::Insert; N], &values).unwrap();
+        let estimation = output(state).unwrap();
+        // The exact 90th percentile of [-1000, 9000) is 7999.
+        assert!(
+            (estimation - 7999.0).abs() <= 7999.0 * RELATIVE_ERROR,
+            "{}",
+            estimation
+        );
+
+        // Delete the values greater than or equal to 4000, leaving [-1000, 4000).
+        let deleted = values
+            .iter()
+            .copied()
+            .filter(|v| v.unwrap() >= 4000.0)
+            .collect_vec();
+        apply(state, &vec![Op::Delete; deleted.len()], &deleted).unwrap();
+        let estimation = output(state).unwrap();
+        // The exact 90th percentile of [-1000, 4000) is 3499.
+        assert!(
+            (estimation - 3499.0).abs() <= 3499.0 * RELATIVE_ERROR,
+            "{}",
+            estimation
+        );
+
+        // Nulls are ignored, and the output of an empty sketch is null.
+        let remaining = values
+            .iter()
+            .copied()
+            .filter(|v| v.unwrap() < 4000.0)
+            .chain([None])
+            .collect_vec();
+        apply(state, &vec![Op::Delete; remaining.len()], &remaining).unwrap();
+        assert_eq!(output(state), None);
+    }
+
+    #[tokio::test]
+    async fn test_recovery() {
+        let store = MemoryStateStore::new();
+        let mut state_table = StateTable::new_without_distribution(
+            store,
+            TableId::new(0),
+            vec![
+                ColumnDesc::unnamed(ColumnId::new(0), DataType::Int64),
+                ColumnDesc::unnamed(
+                    ColumnId::new(1),
+                    DataType::List {
+                        datatype: Box::new(DataType::Int64),
+                    },
+                ),
+            ],
+            vec![OrderType::Ascending],
+            vec![0],
+        );
+        state_table.init_epoch(EpochPair::new_test_epoch(1));
+        let group_key = Row::new(vec![Some(ScalarImpl::Int64(1))]);
+
+        let mut state = StreamingApproxPercentile::new(0.5, 0.01);
+        apply::<MemoryStateStore>(
+            &mut state,
+            &[Op::Insert, Op::Insert, Op::Insert, Op::Delete],
+            &[Some(-3.0), Some(0.0), Some(1000.0), Some(-3.0)],
+        )
+        .unwrap();
+        state
+            .commit_state(&mut state_table, Some(&group_key))
+            .await
+            .unwrap();
+
+        let mut recovered = StreamingApproxPercentile::new(0.5, 0.01);
+        recovered
+            .update_from_state_table(&state_table, Some(&group_key))
+            .await
+            .unwrap();
+        assert_eq!(recovered.sketch, state.sketch);
+        assert_eq!(output::<MemoryStateStore>(&mut recovered), Some(0.0));
+    }
+}
@@ -20,6 +20,7 @@ use std::any::Any;
 pub use approx_count_distinct::*;
 pub use approx_distinct_append::AppendOnlyStreamingApproxCountDistinct;
 use approx_distinct_utils::StreamingApproxCountDistinct;
+pub use approx_percentile::StreamingApproxPercentile;
 use dyn_clone::DynClone;
 pub use foldable::*;
 use risingwave_common::array::stream_chunk::Ops;
@@ -39,6 +40,7 @@ use crate::executor::{StreamExecutorError, StreamExecutorResult};
 mod approx_count_distinct;
 mod approx_distinct_append;
 mod approx_distinct_utils;
+mod approx_percentile;
 mod foldable;
 mod row_count;
 
@@ -132,10 +134,10 @@ pub fn create_streaming_agg_impl(
                         Box::new(<$state_impl>::new())
                     }
                 )*
-                (AggKind::ApproxCountDistinct, _, DataType::Int64, Some(datum)) => {
+                (AggKind::ApproxCountDistinct(_), _, DataType::Int64, Some(datum)) => {
                     Box::new(UpdatableStreamingApproxCountDistinct::<{approx_count_distinct::DENSE_BITS_DEFAULT}>::with_datum(datum))
                 }
-                (AggKind::ApproxCountDistinct, _, DataType::Int64, None) => {
+                (AggKind::ApproxCountDistinct(_), _, DataType::Int64, None) => {
                     Box::new(UpdatableStreamingApproxCountDistinct::<{approx_count_distinct::DENSE_BITS_DEFAULT}>::with_no_initial())
                 }
                 (other_agg, other_input, other_return, _) => panic!(
@@ -21,7 +21,7 @@ use risingwave_expr::expr::AggKind;
 use risingwave_storage::table::streaming_table::state_table::StateTable;
 use risingwave_storage::StateStore;
 
-use super::agg_impl::AppendOnlyStreamingApproxCountDistinct;
+use super::agg_impl::{AppendOnlyStreamingApproxCountDistinct, StreamingApproxPercentile};
 use super::AggCall;
 use crate::executor::StreamExecutorResult;
 
@@ -88,9 +88,14 @@ impl<S: StateStore> TableState<S> {
         Self {
             arg_indices: agg_call.args.val_indices().to_vec(),
             inner: match agg_call.kind {
-                AggKind::ApproxCountDistinct => {
+                // The streaming sketch has a fixed number of buckets regardless of the
+                // requested relative error.
+                AggKind::ApproxCountDistinct(_) => {
                     Box::new(AppendOnlyStreamingApproxCountDistinct::new())
                 }
+                AggKind::ApproxPercentile(fraction, relative_error) => {
+                    Box::new(StreamingApproxPercentile::new(fraction.0, relative_error.0))
+                }
                 _ => panic!(
                     "Agg kind `{}` is not expected to have table state",
                     agg_call.kind
@@ -76,16 +76,39 @@ impl ChainExecutor {
 
         // 1. Poll the upstream to get the first barrier.
         let barrier = expect_first_barrier(&mut upstream).await?;
-        let prev_epoch = barrier.epoch.prev;
+        let mut prev_epoch = barrier.epoch.prev;
 
         // If the barrier is a conf change of creating this mview, init snapshot from its epoch
         // and begin to consume the snapshot.
         // Otherwise, it means we've recovered and the snapshot is already consumed.
         let to_consume_snapshot = barrier.is_add_dispatcher(self.actor_id);
+        let backfill_deferred = to_consume_snapshot && barrier.is_backfill_deferred(self.actor_id);
 
         // The first barrier message should be propagated.
         yield Message::Barrier(barrier);
 
+        // If the backfill is deferred, init snapshot from the epoch of the barrier starting it
+        // instead. The upstream chunks before that barrier are discarded, as they're included in
+        // the snapshot.
+        if backfill_deferred {
+            loop {
+                let msg = upstream
+                    .next()
+                    .await
+                    .ok_or_else(|| StreamExecutorError::channel_closed("upstream"))??;
+                if let Message::Barrier(barrier) = msg {
+                    let to_start = barrier.is_start_backfill(self.actor_id);
+                    if to_start {
+                        prev_epoch = barrier.epoch.prev;
+                    }
+                    yield Message::Barrier(barrier);
+                    if to_start {
+                        break;
+                    }
+                }
+            }
+        }
+
         // 2. Consume the snapshot if needed. Note that the snapshot is already projected, so
         // there's no mapping required.
         if to_consume_snapshot {
@@ -103,9 +126,8 @@ impl ChainExecutor {
         #[for_await]
         for msg in upstream {
             match msg? {
-                Message::Watermark(_) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                // The upstream materialized views don't emit watermarks.
+                Message::Watermark(_) => {}
                 Message::Chunk(chunk) => {
                     yield Message::Chunk(mapping(&self.upstream_indices, chunk));
                 }
@@ -185,6 +207,7 @@ mod test {
                         }],
                     },
                     splits: Default::default(),
+                    deferred_backfills: Default::default(),
                 })),
                 Message::Chunk(StreamChunk::from_pretty("I\n + 3")),
                 Message::Chunk(StreamChunk::from_pretty("I\n + 4")),
@@ -203,4 +226,65 @@ mod test {
         }
         assert_eq!(count, 4);
     }
+
+    #[tokio::test]
+    async fn test_deferred_backfill() {
+        let barrier_manager = LocalBarrierManager::for_test();
+        let progress =
+            CreateMviewProgress::for_test(Arc::new(parking_lot::Mutex::new(barrier_manager)));
+        let actor_id = progress.actor_id();
+
+        let schema = Schema::new(vec![Field::unnamed(DataType::Int64)]);
+        let first = Box::new(
+            MockSource::with_chunks(
+                schema.clone(),
+                PkIndices::new(),
+                vec![
+                    StreamChunk::from_pretty("I\n + 1"),
+                    StreamChunk::from_pretty("I\n + 2"),
+                ],
+            )
+            .stop_on_finish(false),
+        );
+
+        let second = Box::new(MockSource::with_messages(
+            schema.clone(),
+            PkIndices::new(),
+            vec![
+                Message::Barrier(Barrier::new_test_barrier(1).with_mutation(Mutation::Add {
+                    adds: maplit::hashmap! {
+                        0 => vec![Dispatcher {
+                            downstream_actor_id: vec![actor_id],
+                            ..Default::default()
+                        }],
+                    },
+                    splits: Default::default(),
+                    deferred_backfills: maplit::hashset! { actor_id },
+                })),
+                // The chunks before the backfill starts are included in the snapshot.
+                Message::Chunk(StreamChunk::from_pretty("I\n + 1")),
+                Message::Barrier(Barrier::new_test_barrier(2)),
+                Message::Chunk(StreamChunk::from_pretty("I\n + 2")),
+                Message::Barrier(
+                    Barrier::new_test_barrier(3)
+                        .with_mutation(Mutation::StartBackfill(maplit::hashset! { actor_id })),
+                ),
+                Message::Chunk(StreamChunk::from_pretty("I\n + 3")),
+            ],
+        ));
+
+        let chain = ChainExecutor::new(first, second, vec![0], progress, schema);
+        let mut chain = Box::new(chain).execute();
+
+        for epoch in 1..=3 {
+            let barrier = chain.next().await.unwrap().unwrap().into_barrier().unwrap();
+            assert_eq!(barrier.epoch.curr, epoch);
+        }
+        let mut count = 0;
+        while let Some(Message::Chunk(ck)) = chain.next().await.transpose().unwrap() {
+            count += 1;
+            assert_eq!(ck, StreamChunk::from_pretty(&format!("I\n + {count}")));
+        }
+        assert_eq!(count, 3);
+    }
 }
@@ -0,0 +1,235 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use risingwave_common::array::column::Column;
+use risingwave_common::array::{I64Array, Op, StreamChunk, Utf8Array};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::DataType;
+
+use super::error::StreamExecutorError;
+use super::*;
+
+/// Returns the name of `op` in the `changelog_op` column.
+pub fn change_log_op_name(op: Op) -> &'static str {
+    match op {
+        Op::Insert => "insert",
+        Op::Delete => "delete",
+        Op::UpdateDelete => "update_delete",
+        Op::UpdateInsert => "update_insert",
+    }
+}
+
+/// [`ChangeLogExecutor`] turns every change of its input into an appended row, which is the
+/// changed row followed by the epoch of the change, its sequence number in the epoch and the name
+/// of its op. The output is append-only, with the epoch and the sequence number as its stream key.
+///
+/// The sequence numbers are only unique within the actor, so the input must be a singleton.
+pub struct ChangeLogExecutor {
+    input: BoxedExecutor,
+    schema: Schema,
+    pk_indices: PkIndices,
+    identity: String,
+}
+
+impl ChangeLogExecutor {
+    pub fn new(input: BoxedExecutor, executor_id: u64) -> Self {
+        let mut fields = input.schema().clone().into_fields();
+        let epoch_idx = fields.len();
+        fields.push(Field::with_name(DataType::Int64, "changelog_epoch"));
+        fields.push(Field::with_name(DataType::Int64, "changelog_seq"));
+        fields.push(Field::with_name(DataType::Varchar, "changelog_op"));
+        Self {
+            input,
+            schema: Schema::new(fields),
+            pk_indices: vec![epoch_idx, epoch_idx + 1],
+            identity: format!("ChangeLogExecutor {:X}", executor_id),
+        }
+    }
+
+    #[try_stream(ok = Message, error = StreamExecutorError)]
+    async fn execute_inner(self) {
+        let mut input = self.input.execute();
+        let barrier = expect_first_barrier(&mut input).await?;
+        let mut epoch = barrier.epoch.curr;
+        let mut seq = 0;
+        yield Message::Barrier(barrier);
+
+        #[for_await]
+        for msg in input {
+            match msg? {
+                Message::Chunk(chunk) => {
+                    let (data_chunk, ops) = chunk.compact().into_parts();
+                    let cardinality = data_chunk.cardinality();
+                    if cardinality == 0 {
+                        continue;
+                    }
+                    let (mut columns, _) = data_chunk.into_parts();
+
+                    let epochs = vec![Some(epoch as i64); cardinality];
+                    let seqs = (seq..seq + cardinality as i64).map(Some).collect_vec();
+                    let op_names = ops
+                        .iter()
+                        .map(|op| Some(change_log_op_name(*op)))
+                        .collect_vec();
+                    seq += cardinality as i64;
+                    columns.push(Column::new(Arc::new(I64Array::from_slice(&epochs).into())));
+                    columns.push(Column::new(Arc::new(I64Array::from_slice(&seqs).into())));
+                    columns.push(Column::new(Arc::new(
+                        Utf8Array::from_slice(&op_names).into(),
+                    )));
+
+                    yield Message::Chunk(StreamChunk::new(
+                        vec![Op::Insert; cardinality],
+                        columns,
+                        None,
+                    ));
+                }
+                Message::Barrier(barrier) => {
+                    epoch = barrier.epoch.curr;
+                    seq = 0;
+                    yield Message::Barrier(barrier);
+                }
+                // The watermarks of the changed rows don't hold for the epochs.
+                Message::Watermark(_) => {}
+            }
+        }
+    }
+}
+
+impl Debug for ChangeLogExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeLogExecutor").finish()
+    }
+}
+
+impl Executor for ChangeLogExecutor {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn pk_indices(&self) -> PkIndicesRef<'_> {
+        &self.pk_indices
+    }
+
+    fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    fn execute(self: Box<Self>) -> BoxedMessageStream {
+        self.execute_inner().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use risingwave_common::array::{StreamChunk, StreamChunkTestExt};
+    use risingwave_common::catalog::{Field, Schema};
+    use risingwave_common::types::DataType;
+
+    use super::ChangeLogExecutor;
+    use crate::executor::test_utils::MockSource;
+    use crate::executor::{Executor, Message};
+
+    #[tokio::test]
+    async fn test_change_log() {
+        let schema = Schema::new(vec![
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int64),
+        ]);
+        let (mut tx, source) = MockSource::channel(schema, vec![0]);
+        tx.push_barrier(1, false);
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 10
+            + 2 20 D
+            + 3 30",
+        ));
+        tx.push_chunk(StreamChunk::from_pretty(
+            "  I I
+            U- 1 10
+            U+ 1 11",
+        ));
+        tx.push_barrier(2, false);
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            - 3 30",
+        ));
+
+        let change_log = ChangeLogExecutor::new(Box::new(source), 1);
+        assert_eq!(change_log.pk_indices(), &[2, 3]);
+        let mut change_log = Box::new(change_log).execute();
+
+        assert!(matches!(
+            change_log.next().await.unwrap().unwrap(),
+            Message::Barrier(_)
+        ));
+        let chunk = change_log
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_chunk()
+            .unwrap();
+        assert_eq!(
+            chunk,
+            StreamChunk::from_pretty(
+                " I I  I I T
+                + 1 10 1 0 insert
+                + 3 30 1 1 insert"
+            )
+        );
+        let chunk = change_log
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_chunk()
+            .unwrap();
+        assert_eq!(
+            chunk,
+            StreamChunk::from_pretty(
+                " I I  I I T
+                + 1 10 1 2 update_delete
+                + 1 11 1 3 update_insert"
+            )
+        );
+
+        // The sequence numbers start over in each epoch.
+        assert!(matches!(
+            change_log.next().await.unwrap().unwrap(),
+            Message::Barrier(_)
+        ));
+        let chunk = change_log
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_chunk()
+            .unwrap();
+        assert_eq!(
+            chunk,
+            StreamChunk::from_pretty(
+                " I I  I I T
+                + 3 30 2 0 delete"
+            )
+        );
+    }
+}
@@ -0,0 +1,432 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use futures::{pin_mut, StreamExt};
+use futures_async_stream::try_stream;
+use risingwave_common::array::{Op, Row, StreamChunk};
+use risingwave_common::buffer::BitmapBuilder;
+use risingwave_common::catalog::Schema;
+use risingwave_common::types::Datum;
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+use risingwave_storage::StateStore;
+
+use super::error::StreamExecutorError;
+use super::{
+    expect_first_barrier, ActorContextRef, BoxedExecutor, BoxedMessageStream, Executor,
+    ExecutorInfo, Message, PkIndicesRef,
+};
+use crate::cache::{cache_may_stale, EvictableHashMap, ExecutorCache, LruManagerRef};
+
+/// [`DedupExecutor`] drops the rows whose dedup key has been seen before, i.e. the first row of
+/// each key wins. The input must be append-only, and so is the output.
+///
+/// The seen keys are kept in the state table forever, unless a retention column is given. In that
+/// case, the keys whose value of the retention column falls behind the watermark of the column
+/// are evicted, and a later row with the same key will be accepted again.
+pub struct DedupExecutor<S: StateStore> {
+    ctx: ActorContextRef,
+
+    input: Option<BoxedExecutor>,
+
+    info: ExecutorInfo,
+
+    /// Stores the first row of each dedup key, with the dedup key as the primary key.
+    state_table: StateTable<S>,
+
+    dedup_col_indices: Vec<usize>,
+
+    retention_col_idx: Option<usize>,
+
+    /// The dedup keys known to be seen.
+    cache: ExecutorCache<Row, ()>,
+}
+
+impl<S: StateStore> DedupExecutor<S> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ctx: ActorContextRef,
+        input: BoxedExecutor,
+        state_table: StateTable<S>,
+        dedup_col_indices: Vec<usize>,
+        retention_col_idx: Option<usize>,
+        executor_id: u64,
+        cache_size: usize,
+        lru_manager: Option<LruManagerRef>,
+    ) -> Self {
+        let info = ExecutorInfo {
+            schema: input.schema().clone(),
+            pk_indices: dedup_col_indices.clone(),
+            identity: format!("DedupExecutor {:X}", executor_id),
+        };
+        let cache = if let Some(lru_manager) = lru_manager {
+            ExecutorCache::Managed(lru_manager.create_cache())
+        } else {
+            ExecutorCache::Local(EvictableHashMap::new(cache_size))
+        };
+        Self {
+            ctx,
+            input: Some(input),
+            info,
+            state_table,
+            dedup_col_indices,
+            retention_col_idx,
+            cache,
+        }
+    }
+
+    /// Returns the chunk with the duplicate rows made invisible.
+    async fn dedup_chunk(
+        &mut self,
+        chunk: StreamChunk,
+    ) -> Result<Option<StreamChunk>, StreamExecutorError> {
+        let mut vis = BitmapBuilder::with_capacity(chunk.capacity());
+        let mut has_visible = false;
+        for row in chunk.data_chunk().rows_with_holes() {
+            let Some(row) = row else {
+                vis.append(false);
+                continue;
+            };
+            let key = row.row_by_indices(&self.dedup_col_indices);
+            let seen = if self.cache.contains(&key) {
+                true
+            } else if self.state_table.get_row(&key).await?.is_some() {
+                self.cache.put(key, ());
+                true
+            } else {
+                self.state_table.insert(row.to_owned_row());
+                self.cache.put(key, ());
+                false
+            };
+            vis.append(!seen);
+            has_visible |= !seen;
+        }
+
+        if !has_visible {
+            return Ok(None);
+        }
+        let (ops, columns, _) = chunk.into_inner();
+        debug_assert!(ops.iter().all(|op| *op == Op::Insert));
+        Ok(Some(StreamChunk::new(ops, columns, Some(vis.finish()))))
+    }
+
+    /// Evicts the keys whose value of the retention column is below the watermark.
+    async fn clean_state(&mut self, watermark: &Datum) -> Result<(), StreamExecutorError> {
+        let Some(retention_col_idx) = self.retention_col_idx else {
+            return Ok(());
+        };
+        if watermark.is_none() {
+            return Ok(());
+        }
+
+        let mut expired = vec![];
+        {
+            let iter = self.state_table.iter().await?;
+            pin_mut!(iter);
+            while let Some(row) = iter.next().await.transpose()? {
+                if let Some(value) = &row[retention_col_idx]
+                    && Some(value) < watermark.as_ref()
+                {
+                    expired.push(row.into_owned());
+                }
+            }
+        }
+        if !expired.is_empty() {
+            for row in expired {
+                self.state_table.delete(row);
+            }
+            self.cache.clear();
+        }
+        Ok(())
+    }
+
+    #[try_stream(ok = Message, error = StreamExecutorError)]
+    async fn execute_inner(mut self) {
+        let mut input = self.input.take().unwrap().execute();
+
+        let barrier = expect_first_barrier(&mut input).await?;
+        self.state_table.init_epoch(barrier.epoch);
+        self.cache.update_epoch(barrier.epoch.curr);
+        yield Message::Barrier(barrier);
+
+        #[for_await]
+        for msg in input {
+            match msg? {
+                Message::Chunk(chunk) => {
+                    if let Some(chunk) = self.dedup_chunk(chunk).await? {
+                        yield Message::Chunk(chunk);
+                    }
+                }
+                Message::Watermark(watermark) => {
+                    if Some(watermark.col_idx) == self.retention_col_idx {
+                        self.clean_state(&watermark.val).await?;
+                    }
+                    yield Message::Watermark(watermark);
+                }
+                Message::Barrier(barrier) => {
+                    self.state_table.commit(barrier.epoch).await?;
+                    self.cache.evict();
+
+                    if let Some(vnode_bitmap) = barrier.as_update_vnode_bitmap(self.ctx.id) {
+                        let previous_vnode_bitmap =
+                            self.state_table.update_vnode_bitmap(vnode_bitmap.clone());
+                        if cache_may_stale(&previous_vnode_bitmap, &vnode_bitmap) {
+                            self.cache.clear();
+                        }
+                    }
+
+                    self.cache.update_epoch(barrier.epoch.curr);
+                    yield Message::Barrier(barrier);
+                }
+            }
+        }
+    }
+}
+
+impl<S: StateStore> Executor for DedupExecutor<S> {
+    fn execute(self: Box<Self>) -> BoxedMessageStream {
+        self.execute_inner().boxed()
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.info.schema
+    }
+
+    fn pk_indices(&self) -> PkIndicesRef<'_> {
+        &self.info.pk_indices
+    }
+
+    fn identity(&self) -> &str {
+        &self.info.identity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::catalog::{ColumnDesc, ColumnId, Field, TableId};
+    use risingwave_common::types::{DataType, ScalarImpl};
+    use risingwave_common::util::sort_util::OrderType;
+    use risingwave_storage::memory::MemoryStateStore;
+
+    use super::*;
+    use crate::executor::test_utils::{MessageSender, MockSource};
+    use crate::executor::{ActorContext, Watermark};
+
+    fn create_state_table(store: MemoryStateStore) -> StateTable<MemoryStateStore> {
+        let column_descs = vec![
+            ColumnDesc::unnamed(ColumnId::new(0), DataType::Int64),
+            ColumnDesc::unnamed(ColumnId::new(1), DataType::Int64),
+        ];
+        StateTable::new_without_distribution(
+            store,
+            TableId::new(1),
+            column_descs,
+            vec![OrderType::Ascending],
+            vec![0],
+        )
+    }
+
+    fn create_executor(
+        state_table: StateTable<MemoryStateStore>,
+        retention_col_idx: Option<usize>,
+    ) -> (MessageSender, BoxedMessageStream) {
+        let schema = Schema::new(vec![
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int64),
+        ]);
+        let (tx, source) = MockSource::channel(schema, vec![0]);
+        let executor = DedupExecutor::new(
+            ActorContext::create(123),
+            Box::new(source),
+            state_table,
+            vec![0],
+            retention_col_idx,
+            1,
+            1024,
+            None,
+        );
+        (tx, Box::new(executor).execute())
+    }
+
+    async fn expect_chunk(dedup: &mut BoxedMessageStream, expected: &str) {
+        let msg = dedup.next().await.unwrap().unwrap();
+        assert_eq!(
+            msg.into_chunk().unwrap().compact(),
+            StreamChunk::from_pretty(expected)
+        );
+    }
+
+    async fn expect_barrier(dedup: &mut BoxedMessageStream) {
+        dedup.next().await.unwrap().unwrap().into_barrier().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dedup_across_barriers() {
+        let state_table = create_state_table(MemoryStateStore::new());
+        let (mut tx, mut dedup) = create_executor(state_table, None);
+
+        tx.push_barrier(1, false);
+        expect_barrier(&mut dedup).await;
+
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 1
+            + 2 2
+            + 1 3",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 1 1
+            + 2 2",
+        )
+        .await;
+
+        tx.push_barrier(2, false);
+        expect_barrier(&mut dedup).await;
+
+        // The duplicates straddling the barrier are dropped, and a chunk of duplicates only is
+        // dropped entirely.
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 2 4
+            + 1 5",
+        ));
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 3 6
+            + 2 7",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 3 6",
+        )
+        .await;
+
+        tx.push_barrier(3, false);
+        expect_barrier(&mut dedup).await;
+    }
+
+    #[tokio::test]
+    async fn test_dedup_recovery() {
+        let store = MemoryStateStore::new();
+        let (mut tx, mut dedup) = create_executor(create_state_table(store.clone()), None);
+
+        tx.push_barrier(1, false);
+        expect_barrier(&mut dedup).await;
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 1
+            + 2 2",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 1 1
+            + 2 2",
+        )
+        .await;
+        tx.push_barrier(2, false);
+        expect_barrier(&mut dedup).await;
+
+        // The rows after the last barrier are lost, and will be replayed after recovery.
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 3 3",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 3 3",
+        )
+        .await;
+        drop(dedup);
+
+        // Recover from the state of epoch 2.
+        let (mut tx, mut dedup) = create_executor(create_state_table(store), None);
+        tx.push_barrier(2, false);
+        expect_barrier(&mut dedup).await;
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 4
+            + 3 3
+            + 2 5",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 3 3",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_dedup_retention() {
+        let state_table = create_state_table(MemoryStateStore::new());
+        let (mut tx, mut dedup) = create_executor(state_table, Some(1));
+
+        tx.push_barrier(1, false);
+        expect_barrier(&mut dedup).await;
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 1
+            + 2 8",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 1 1
+            + 2 8",
+        )
+        .await;
+        tx.push_barrier(2, false);
+        expect_barrier(&mut dedup).await;
+
+        // Key 1 falls out of the retention window, while key 2 doesn't.
+        let watermark = Watermark::new(1, DataType::Int64, Some(ScalarImpl::Int64(5)));
+        tx.push_watermark(watermark.clone());
+        assert_eq!(
+            dedup.next().await.unwrap().unwrap(),
+            Message::Watermark(watermark)
+        );
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 9
+            + 2 10",
+        ));
+        expect_chunk(
+            &mut dedup,
+            " I I
+            + 1 9",
+        )
+        .await;
+
+        // Watermarks of other columns don't evict anything.
+        let watermark = Watermark::new(0, DataType::Int64, Some(ScalarImpl::Int64(100)));
+        tx.push_watermark(watermark.clone());
+        assert_eq!(
+            dedup.next().await.unwrap().unwrap(),
+            Message::Watermark(watermark)
+        );
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I
+            + 1 11
+            + 2 12",
+        ));
+        // The chunk is dropped entirely.
+        tx.push_barrier(3, false);
+        expect_barrier(&mut dedup).await;
+    }
+}
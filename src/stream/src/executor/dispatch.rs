@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::future::Future;
 use std::iter::repeat_with;
@@ -22,20 +22,21 @@ use async_stack_trace::StackTrace;
 use futures::Stream;
 use futures_async_stream::try_stream;
 use itertools::Itertools;
-use risingwave_common::array::{Op, StreamChunk};
-use risingwave_common::buffer::BitmapBuilder;
+use risingwave_common::array::{HotKeyMark, Op, StreamChunk};
+use risingwave_common::buffer::{Bitmap, BitmapBuilder};
+use risingwave_common::hash::HashCode;
 use risingwave_common::util::compress::decompress_data;
 use risingwave_common::util::hash_util::Crc32FastBuilder;
 use risingwave_pb::stream_plan::update_mutation::DispatcherUpdate as ProstDispatcherUpdate;
-use risingwave_pb::stream_plan::Dispatcher as ProstDispatcher;
+use risingwave_pb::stream_plan::{Dispatcher as ProstDispatcher, DispatcherType, HotKeyMode};
 use smallvec::{smallvec, SmallVec};
-use tracing::event;
+use tracing::{event, Instrument};
 
 use super::exchange::output::{new_output, BoxedOutput};
 use crate::error::StreamResult;
 use crate::executor::monitor::StreamingMetrics;
-use crate::executor::{Barrier, BoxedExecutor, Message, Mutation, StreamConsumer};
-use crate::task::{ActorId, DispatcherId, SharedContext};
+use crate::executor::{Barrier, BoxedExecutor, Message, Mutation, StreamConsumer, Watermark};
+use crate::task::{ActorId, DispatcherId, FragmentId, SharedContext};
 
 /// [`DispatchExecutor`] consumes messages and send them into downstream actors. Usually,
 /// data chunks will be dispatched with some specified policy, while control message
@@ -49,6 +50,7 @@ struct DispatchExecutorInner {
     dispatchers: Vec<DispatcherImpl>,
     actor_id: u32,
     actor_id_str: String,
+    fragment_id: FragmentId,
     context: Arc<SharedContext>,
     metrics: Arc<StreamingMetrics>,
 }
@@ -65,10 +67,11 @@ impl DispatchExecutorInner {
 
     async fn dispatch(&mut self, msg: Message) -> StreamResult<()> {
         match msg {
-            Message::Watermark(_) => {
-                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
+            Message::Watermark(watermark) => {
+                for dispatcher in &mut self.dispatchers {
+                    dispatcher.dispatch_watermark(watermark.clone()).await?;
+                }
             }
-
             Message::Chunk(chunk) => {
                 self.metrics
                     .actor_out_record_cnt
@@ -152,14 +155,41 @@ impl DispatchExecutorInner {
     }
 
     /// Update the dispatcher AFTER we dispatch this barrier. We'll remove some outputs and finally
-    /// update the hash mapping.
+    /// update the hash mapping, or rebuild the dispatcher with the outputs if its type is changed.
     fn post_update_dispatcher(&mut self, update: &ProstDispatcherUpdate) -> StreamResult<()> {
         let ids = update.removed_downstream_actor_id.iter().copied().collect();
 
-        let dispatcher = self.find_dispatcher(update.dispatcher_id);
-        dispatcher.remove_outputs(&ids);
+        self.find_dispatcher(update.dispatcher_id)
+            .remove_outputs(&ids);
+
+        if update.dispatcher_type() != DispatcherType::Unspecified {
+            let index = self
+                .dispatchers
+                .iter()
+                .position(|d| d.dispatcher_id() == update.dispatcher_id)
+                .unwrap();
+            let old_dispatcher = self.dispatchers.remove(index);
+            let hot_key_mode = match &old_dispatcher {
+                DispatcherImpl::Hash(dispatcher) => dispatcher.hot_key_mode,
+                _ => HotKeyMode::Unspecified,
+            };
+            let outputs = old_dispatcher.into_outputs();
+            let new_dispatcher = DispatcherImpl::with_outputs(
+                outputs,
+                &ProstDispatcher {
+                    r#type: update.dispatcher_type,
+                    column_indices: update.column_indices.clone(),
+                    hash_mapping: update.hash_mapping.clone(),
+                    dispatcher_id: update.dispatcher_id,
+                    downstream_actor_id: vec![],
+                    hot_key_mode: hot_key_mode as i32,
+                },
+            )?;
+            self.dispatchers.insert(index, new_dispatcher);
+            return Ok(());
+        }
 
-        match dispatcher {
+        match self.find_dispatcher(update.dispatcher_id) {
             // The hash mapping is only used by the hash dispatcher.
             DispatcherImpl::Hash(dispatcher) => {
                 dispatcher.hash_mapping = {
@@ -168,7 +198,10 @@ impl DispatchExecutorInner {
                         &compressed_mapping.original_indices,
                         &compressed_mapping.data,
                     )
-                }
+                };
+                // The downstream hash joins drop their copies of the rows of hot keys on this
+                // barrier, so the hot keys are detected again with the new outputs.
+                dispatcher.hot_key_detector.reset();
             }
             _ => assert!(update.hash_mapping.is_none()),
         }
@@ -240,6 +273,7 @@ impl DispatchExecutor {
         input: BoxedExecutor,
         dispatchers: Vec<DispatcherImpl>,
         actor_id: u32,
+        fragment_id: FragmentId,
         context: Arc<SharedContext>,
         metrics: Arc<StreamingMetrics>,
     ) -> Self {
@@ -249,6 +283,7 @@ impl DispatchExecutor {
                 dispatchers,
                 actor_id,
                 actor_id_str: actor_id.to_string(),
+                fragment_id,
                 context,
                 metrics,
             },
@@ -268,8 +303,18 @@ impl StreamConsumer for DispatchExecutor {
             for msg in input {
                 let msg: Message = msg?;
                 let barrier = msg.as_barrier().cloned();
+                let span = barrier
+                    .as_ref()
+                    .map_or_else(tracing::Span::none, |barrier| {
+                        barrier.actor_span(
+                            "dispatch_barrier",
+                            self.inner.actor_id,
+                            self.inner.fragment_id,
+                        )
+                    });
                 self.inner
                     .dispatch(msg)
+                    .instrument(span)
                     .verbose_stack_trace(if barrier.is_some() {
                         "dispatch_barrier"
                     } else {
@@ -304,6 +349,12 @@ impl DispatcherImpl {
             .map(|&down_id| new_output(context, actor_id, down_id))
             .collect::<StreamResult<Vec<_>>>()?;
 
+        Self::with_outputs(outputs, dispatcher)
+    }
+
+    /// Build the dispatcher with existing `outputs`, ignoring the downstream actors in
+    /// `dispatcher`.
+    fn with_outputs(outputs: Vec<BoxedOutput>, dispatcher: &ProstDispatcher) -> StreamResult<Self> {
         use risingwave_pb::stream_plan::DispatcherType::*;
         let dispatcher_impl = match dispatcher.get_type()? {
             Hash => {
@@ -322,12 +373,15 @@ impl DispatcherImpl {
                     )
                 };
 
-                DispatcherImpl::Hash(HashDataDispatcher::new(
-                    outputs,
-                    column_indices,
-                    hash_mapping,
-                    dispatcher.dispatcher_id,
-                ))
+                DispatcherImpl::Hash(
+                    HashDataDispatcher::new(
+                        outputs,
+                        column_indices,
+                        hash_mapping,
+                        dispatcher.dispatcher_id,
+                    )
+                    .with_hot_key_mode(dispatcher.get_hot_key_mode()?),
+                )
             }
             Broadcast => DispatcherImpl::Broadcast(BroadcastDispatcher::new(
                 outputs,
@@ -359,6 +413,12 @@ macro_rules! impl_dispatcher {
                 }
             }
 
+            pub async fn dispatch_watermark(&mut self, watermark: Watermark) -> StreamResult<()> {
+                match self {
+                    $( Self::$variant_name(inner) => inner.dispatch_watermark(watermark).await, )*
+                }
+            }
+
             pub fn add_outputs(&mut self, outputs: impl IntoIterator<Item = BoxedOutput>) {
                 match self {
                     $(Self::$variant_name(inner) => inner.add_outputs(outputs), )*
@@ -371,6 +431,12 @@ macro_rules! impl_dispatcher {
                 }
             }
 
+            pub fn into_outputs(self) -> Vec<BoxedOutput> {
+                match self {
+                    $(Self::$variant_name(inner) => inner.into_outputs(), )*
+                }
+            }
+
             pub fn dispatcher_id(&self) -> DispatcherId {
                 match self {
                     $(Self::$variant_name(inner) => inner.dispatcher_id(), )*
@@ -403,6 +469,7 @@ macro_rules! define_dispatcher_associated_types {
     () => {
         type DataFuture<'a> = impl DispatchFuture<'a>;
         type BarrierFuture<'a> = impl DispatchFuture<'a>;
+        type WatermarkFuture<'a> = impl DispatchFuture<'a>;
     };
 }
 
@@ -411,16 +478,21 @@ pub trait DispatchFuture<'a> = Future<Output = StreamResult<()>> + Send;
 pub trait Dispatcher: Debug + 'static {
     type DataFuture<'a>: DispatchFuture<'a>;
     type BarrierFuture<'a>: DispatchFuture<'a>;
+    type WatermarkFuture<'a>: DispatchFuture<'a>;
 
     /// Dispatch a data chunk to downstream actors.
     fn dispatch_data(&mut self, chunk: StreamChunk) -> Self::DataFuture<'_>;
     /// Dispatch a barrier to downstream actors, generally by broadcasting it.
     fn dispatch_barrier(&mut self, barrier: Barrier) -> Self::BarrierFuture<'_>;
+    /// Dispatch a watermark to downstream actors, generally by broadcasting it.
+    fn dispatch_watermark(&mut self, watermark: Watermark) -> Self::WatermarkFuture<'_>;
 
     /// Add new outputs to the dispatcher.
     fn add_outputs(&mut self, outputs: impl IntoIterator<Item = BoxedOutput>);
     /// Remove outputs to `actor_ids` from the dispatcher.
     fn remove_outputs(&mut self, actor_ids: &HashSet<ActorId>);
+    /// Take all outputs of the dispatcher, e.g. to rebuild it with another type.
+    fn into_outputs(self) -> Vec<BoxedOutput>;
 
     /// The ID of the dispatcher. A [`DispatchExecutor`] may have multiple dispatchers with
     /// different IDs.
@@ -470,6 +542,16 @@ impl Dispatcher for RoundRobinDataDispatcher {
         }
     }
 
+    fn dispatch_watermark(&mut self, watermark: Watermark) -> Self::WatermarkFuture<'_> {
+        async move {
+            // always broadcast watermark
+            for output in &mut self.outputs {
+                output.send(Message::Watermark(watermark.clone())).await?;
+            }
+            Ok(())
+        }
+    }
+
     fn add_outputs(&mut self, outputs: impl IntoIterator<Item = BoxedOutput>) {
         self.outputs.extend(outputs.into_iter());
     }
@@ -481,6 +563,10 @@ impl Dispatcher for RoundRobinDataDispatcher {
         self.cur = self.cur.min(self.outputs.len() - 1);
     }
 
+    fn into_outputs(self) -> Vec<BoxedOutput> {
+        self.outputs
+    }
+
     fn dispatcher_id(&self) -> DispatcherId {
         self.dispatcher_id
     }
@@ -490,6 +576,103 @@ impl Dispatcher for RoundRobinDataDispatcher {
     }
 }
 
+/// [`HotKeyDetector`] samples one in every this many rows.
+const HOT_KEY_SAMPLE_INTERVAL: usize = 16;
+/// Barrier intervals with fewer sampled rows are skipped, as the estimation would be too noisy.
+const HOT_KEY_MIN_SAMPLED_ROWS: usize = 64;
+/// A key is hot if it takes more than this many times the fair share of rows of one output.
+const HOT_KEY_FAIR_SHARE_FACTOR: usize = 2;
+/// The maximum number of hot keys, which bounds the rows of hot keys the downstream actors keep
+/// in memory.
+const HOT_KEY_MAX_COUNT: usize = 16;
+/// The maximum number of rows of a hot key inserted as hot key rows, which bounds the rows of each
+/// hot key the downstream actors keep in memory.
+const HOT_KEY_MAX_ROWS: usize = 4096;
+
+/// [`HotKeyDetector`] estimates the frequencies of the dispatch keys in each barrier interval by
+/// sampling, to find hot keys that cap the throughput of the downstream fragment as all of their
+/// rows land on the same actor.
+///
+/// A key stays hot once found, until [`HotKeyDetector::reset`]. The downstream hash joins match
+/// the rows of a hot key by where they were routed when inserted, so their retractions must be
+/// routed in the same way.
+///
+/// Once [`HOT_KEY_MAX_ROWS`] rows of a hot key are inserted as hot key rows, its further inserts
+/// are routed as normal rows and joined by the owner, so that the copies the downstream actors
+/// keep in memory stop growing. Its retractions are still routed as hot key rows, which the
+/// downstream actors not holding the retracted rows ignore.
+#[derive(Debug, Default)]
+pub struct HotKeyDetector {
+    /// The number of visible rows seen, used to pick the rows to sample.
+    rows_seen: usize,
+    /// Hash code of the key => the number of sampled rows in the current barrier interval.
+    sampled: HashMap<u64, usize>,
+    sampled_rows: usize,
+    /// Hash codes of the hot keys.
+    hot_keys: BTreeSet<u64>,
+    /// Hash code of a hot key => the number of its rows inserted as hot key rows.
+    hot_rows: HashMap<u64, usize>,
+}
+
+impl HotKeyDetector {
+    fn sample(&mut self, hash_codes: &[HashCode], visibility: Option<&Bitmap>) {
+        for (i, hash_code) in hash_codes.iter().enumerate() {
+            if visibility.map_or(false, |visibility| !visibility.is_set(i)) {
+                continue;
+            }
+            if self.rows_seen % HOT_KEY_SAMPLE_INTERVAL == 0 {
+                *self.sampled.entry(hash_code.hash_code()).or_default() += 1;
+                self.sampled_rows += 1;
+            }
+            self.rows_seen += 1;
+        }
+    }
+
+    /// Add the hot keys found in the samples of the barrier interval that just ended. Returns
+    /// whether the hot keys are changed.
+    fn finish_interval(&mut self, num_outputs: usize) -> bool {
+        let sampled = std::mem::take(&mut self.sampled);
+        let sampled_rows = std::mem::take(&mut self.sampled_rows);
+        if num_outputs < 2 || sampled_rows < HOT_KEY_MIN_SAMPLED_ROWS {
+            return false;
+        }
+        let new_hot_keys = sampled
+            .into_iter()
+            .filter(|(hash_code, count)| {
+                !self.hot_keys.contains(hash_code)
+                    && count * num_outputs > HOT_KEY_FAIR_SHARE_FACTOR * sampled_rows
+            })
+            .sorted_by_key(|(_, count)| std::cmp::Reverse(*count))
+            .map(|(hash_code, _)| hash_code)
+            .take(HOT_KEY_MAX_COUNT - self.hot_keys.len())
+            .collect_vec();
+        self.hot_keys.extend(&new_hot_keys);
+        !new_hot_keys.is_empty()
+    }
+
+    /// Count an insert of a hot key routed as a hot key row. Returns false without counting if the
+    /// key already has [`HOT_KEY_MAX_ROWS`] such rows, so that the insert is routed as a normal
+    /// row instead.
+    fn try_insert_hot_row(&mut self, hash_code: u64) -> bool {
+        let hot_rows = self.hot_rows.entry(hash_code).or_default();
+        if *hot_rows >= HOT_KEY_MAX_ROWS {
+            return false;
+        }
+        *hot_rows += 1;
+        true
+    }
+
+    /// Forget the hot keys and the samples.
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Hash codes of the hot keys.
+    pub fn hot_keys(&self) -> &BTreeSet<u64> {
+        &self.hot_keys
+    }
+}
+
 pub struct HashDataDispatcher {
     outputs: Vec<BoxedOutput>,
     keys: Vec<usize>,
@@ -497,6 +680,9 @@ pub struct HashDataDispatcher {
     /// different downstream actors.
     hash_mapping: Vec<ActorId>,
     dispatcher_id: DispatcherId,
+    /// How the rows of hot keys are routed. If unspecified, the hot keys are not detected.
+    hot_key_mode: HotKeyMode,
+    hot_key_detector: HotKeyDetector,
 }
 
 impl Debug for HashDataDispatcher {
@@ -505,6 +691,8 @@ impl Debug for HashDataDispatcher {
             .field("outputs", &self.outputs)
             .field("keys", &self.keys)
             .field("dispatcher_id", &self.dispatcher_id)
+            .field("hot_key_mode", &self.hot_key_mode)
+            .field("hot_keys", self.hot_key_detector.hot_keys())
             .finish_non_exhaustive()
     }
 }
@@ -521,7 +709,153 @@ impl HashDataDispatcher {
             keys,
             hash_mapping,
             dispatcher_id,
+            hot_key_mode: HotKeyMode::Unspecified,
+            hot_key_detector: HotKeyDetector::default(),
+        }
+    }
+
+    /// Route the rows of hot keys to more than one output, with the given mode.
+    pub fn with_hot_key_mode(mut self, hot_key_mode: HotKeyMode) -> Self {
+        self.hot_key_mode = hot_key_mode;
+        self
+    }
+
+    pub fn hot_key_detector(&self) -> &HotKeyDetector {
+        &self.hot_key_detector
+    }
+
+    /// Dispatch a chunk containing rows of hot keys.
+    ///
+    /// Rows of other keys, and the inserts of a hot key beyond [`HOT_KEY_MAX_ROWS`], are sent to
+    /// the output owning their vnode as usual. For the rows of hot
+    /// keys, the output owning their vnode gets them in a chunk marked `BroadcastOwner` or
+    /// `SpreadOwner` and persists them. In `Build` mode, all other outputs get a copy marked
+    /// `Replica`. In `Probe` mode, one other output picked by the hash of the whole row gets a
+    /// copy marked `SpreadShare`, so that the retraction of a row reaches the same output as the
+    /// row. See `HotKeyState` of the hash join for how the copies are joined.
+    async fn dispatch_hot_data(
+        &mut self,
+        chunk: StreamChunk,
+        hash_values: Vec<HashCode>,
+    ) -> StreamResult<()> {
+        let (owner_mark, copy_mark) = match self.hot_key_mode {
+            HotKeyMode::Build => (HotKeyMark::BroadcastOwner, HotKeyMark::Replica),
+            HotKeyMode::Probe => (HotKeyMark::SpreadOwner, HotKeyMark::SpreadShare),
+            HotKeyMode::Unspecified => unreachable!(),
+        };
+        let num_outputs = self.outputs.len();
+        let output_indices: HashMap<ActorId, usize> = self
+            .outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| (output.actor_id(), i))
+            .collect();
+        let row_hash_values = match self.hot_key_mode {
+            HotKeyMode::Probe => {
+                let all_columns = (0..chunk.columns().len()).collect_vec();
+                chunk
+                    .data_chunk()
+                    .get_hash_values(&all_columns, Crc32FastBuilder {})
+            }
+            _ => vec![],
+        };
+
+        let new_vis_maps = || {
+            repeat_with(|| BitmapBuilder::with_capacity(chunk.capacity()))
+                .take(num_outputs)
+                .collect_vec()
+        };
+        let mut normal_vis_maps = new_vis_maps();
+        let mut owner_vis_maps = new_vis_maps();
+        let mut copy_vis_maps = new_vis_maps();
+        let mut last_update_delete = None;
+        let mut new_ops: Vec<Op> = Vec::with_capacity(chunk.capacity());
+
+        let (ops, columns, visibility) = chunk.into_inner();
+
+        for (i, (hash_value, op)) in hash_values.iter().zip_eq(ops).enumerate() {
+            let visible = visibility
+                .as_ref()
+                .map_or(true, |visibility| visibility.is_set(i));
+            let vnode = hash_value.to_vnode();
+            let owner = output_indices.get(&self.hash_mapping[vnode as usize]);
+            let hot = visible
+                && owner.is_some()
+                && self
+                    .hot_key_detector
+                    .hot_keys
+                    .contains(&hash_value.hash_code())
+                && (matches!(op, Op::Delete | Op::UpdateDelete)
+                    || self
+                        .hot_key_detector
+                        .try_insert_hot_row(hash_value.hash_code()));
+            let spread_to = match self.hot_key_mode {
+                HotKeyMode::Probe if hot => {
+                    // Pick one of the outputs other than the owner.
+                    let owner = *owner.unwrap();
+                    let picked =
+                        (row_hash_values[i].hash_code() % (num_outputs as u64 - 1)) as usize;
+                    Some(if picked < owner { picked } else { picked + 1 })
+                }
+                _ => None,
+            };
+            for j in 0..num_outputs {
+                let is_owner = owner == Some(&j);
+                normal_vis_maps[j].append(visible && !hot && is_owner);
+                owner_vis_maps[j].append(hot && is_owner);
+                copy_vis_maps[j]
+                    .append(hot && !is_owner && spread_to.map_or(true, |spread_to| spread_to == j));
+            }
+
+            // The 'update' message, noted by an UpdateDelete and a successive UpdateInsert, need
+            // to be rewritten to common Delete and Insert if they were dispatched to different
+            // actors, or to more than one actor.
+            if !visible {
+                new_ops.push(op);
+            } else if op == Op::UpdateDelete {
+                last_update_delete = Some((vnode, hot));
+            } else if op == Op::UpdateInsert {
+                if last_update_delete == Some((vnode, false)) && !hot {
+                    new_ops.push(Op::UpdateDelete);
+                    new_ops.push(Op::UpdateInsert);
+                } else {
+                    new_ops.push(Op::Delete);
+                    new_ops.push(Op::Insert);
+                }
+            } else {
+                new_ops.push(op);
+            }
+        }
+
+        let ops = new_ops;
+
+        for (((normal_vis_map, owner_vis_map), copy_vis_map), output) in normal_vis_maps
+            .into_iter()
+            .zip_eq(owner_vis_maps)
+            .zip_eq(copy_vis_maps)
+            .zip_eq(self.outputs.iter_mut())
+        {
+            for (vis_map, hot_key_mark) in [
+                (normal_vis_map, HotKeyMark::Normal),
+                (owner_vis_map, owner_mark),
+                (copy_vis_map, copy_mark),
+            ] {
+                let new_stream_chunk =
+                    StreamChunk::new(ops.clone(), columns.clone(), Some(vis_map.finish()))
+                        .with_hot_key_mark(hot_key_mark);
+                if new_stream_chunk.cardinality() > 0 {
+                    event!(
+                        tracing::Level::TRACE,
+                        msg = "chunk",
+                        downstream = output.actor_id(),
+                        "send = \n{:#?}",
+                        new_stream_chunk
+                    );
+                    output.send(Message::Chunk(new_stream_chunk)).await?;
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -534,6 +868,19 @@ impl Dispatcher for HashDataDispatcher {
 
     fn dispatch_barrier(&mut self, barrier: Barrier) -> Self::BarrierFuture<'_> {
         async move {
+            // Hot keys are only changed at barriers, so that the downstream actors see the rows
+            // of a key routed in the same way within an epoch.
+            if self.hot_key_mode != HotKeyMode::Unspecified
+                && self.hot_key_detector.finish_interval(self.outputs.len())
+            {
+                tracing::info!(
+                    dispatcher_id = self.dispatcher_id,
+                    hot_key_mode = ?self.hot_key_mode,
+                    hot_keys = ?self.hot_key_detector.hot_keys(),
+                    "hot keys of hash dispatcher changed"
+                );
+            }
+
             // always broadcast barrier
             for output in &mut self.outputs {
                 output.send(Message::Barrier(barrier.clone())).await?;
@@ -542,6 +889,16 @@ impl Dispatcher for HashDataDispatcher {
         }
     }
 
+    fn dispatch_watermark(&mut self, watermark: Watermark) -> Self::WatermarkFuture<'_> {
+        async move {
+            // always broadcast watermark
+            for output in &mut self.outputs {
+                output.send(Message::Watermark(watermark.clone())).await?;
+            }
+            Ok(())
+        }
+    }
+
     fn dispatch_data(&mut self, chunk: StreamChunk) -> Self::DataFuture<'_> {
         async move {
             // A chunk can be shuffled into multiple output chunks that to be sent to downstreams.
@@ -551,9 +908,15 @@ impl Dispatcher for HashDataDispatcher {
 
             // get hash value of every line by its key
             let hash_builder = Crc32FastBuilder {};
-            let vnodes = chunk
-                .data_chunk()
-                .get_hash_values(&self.keys, hash_builder)
+            let hash_values = chunk.data_chunk().get_hash_values(&self.keys, hash_builder);
+            if self.hot_key_mode != HotKeyMode::Unspecified {
+                self.hot_key_detector
+                    .sample(&hash_values, chunk.visibility());
+                if !self.hot_key_detector.hot_keys().is_empty() && num_outputs > 1 {
+                    return self.dispatch_hot_data(chunk, hash_values).await;
+                }
+            }
+            let vnodes = hash_values
                 .into_iter()
                 .map(|hash| hash.to_vnode())
                 .collect_vec();
@@ -657,6 +1020,10 @@ impl Dispatcher for HashDataDispatcher {
             .count();
     }
 
+    fn into_outputs(self) -> Vec<BoxedOutput> {
+        self.outputs
+    }
+
     fn dispatcher_id(&self) -> DispatcherId {
         self.dispatcher_id
     }
@@ -714,6 +1081,15 @@ impl Dispatcher for BroadcastDispatcher {
         }
     }
 
+    fn dispatch_watermark(&mut self, watermark: Watermark) -> Self::WatermarkFuture<'_> {
+        async move {
+            for output in self.outputs.values_mut() {
+                output.send(Message::Watermark(watermark.clone())).await?;
+            }
+            Ok(())
+        }
+    }
+
     fn add_outputs(&mut self, outputs: impl IntoIterator<Item = BoxedOutput>) {
         self.outputs.extend(Self::into_pairs(outputs));
     }
@@ -724,6 +1100,10 @@ impl Dispatcher for BroadcastDispatcher {
             .count();
     }
 
+    fn into_outputs(self) -> Vec<BoxedOutput> {
+        self.outputs.into_values().collect()
+    }
+
     fn dispatcher_id(&self) -> DispatcherId {
         self.dispatcher_id
     }
@@ -792,11 +1172,27 @@ impl Dispatcher for SimpleDispatcher {
         }
     }
 
+    fn dispatch_watermark(&mut self, watermark: Watermark) -> Self::WatermarkFuture<'_> {
+        async move {
+            let output = self
+                .output
+                .iter_mut()
+                .exactly_one()
+                .expect("expect exactly one output");
+
+            output.send(Message::Watermark(watermark)).await
+        }
+    }
+
     fn remove_outputs(&mut self, actor_ids: &HashSet<ActorId>) {
         self.output
             .retain(|output| !actor_ids.contains(&output.actor_id()));
     }
 
+    fn into_outputs(self) -> Vec<BoxedOutput> {
+        self.output.into_vec()
+    }
+
     fn dispatcher_id(&self) -> DispatcherId {
         self.dispatcher_id
     }
@@ -815,10 +1211,10 @@ mod tests {
     use futures::{pin_mut, StreamExt};
     use itertools::Itertools;
     use risingwave_common::array::stream_chunk::StreamChunkTestExt;
-    use risingwave_common::array::{Array, ArrayBuilder, I32ArrayBuilder, Op};
+    use risingwave_common::array::{Array, ArrayBuilder, I32ArrayBuilder, Op, Row};
     use risingwave_common::catalog::Schema;
     use risingwave_common::types::VIRTUAL_NODE_COUNT;
-    use risingwave_pb::stream_plan::DispatcherType;
+    use risingwave_pb::stream_plan::ActorMapping;
     use static_assertions::const_assert_eq;
     use tokio::sync::mpsc::channel;
 
@@ -986,6 +1382,7 @@ mod tests {
             input,
             vec![broadcast_dispatcher, simple_dispatcher],
             actor_id,
+            0,
             ctx.clone(),
             metrics,
         ))
@@ -1016,6 +1413,7 @@ mod tests {
                 added_downstream_actor_id: vec![new],
                 removed_downstream_actor_id: vec![old],
                 hash_mapping: Default::default(),
+                ..Default::default()
             }]
         };
         let b1 = Barrier::new_test_barrier(1).with_mutation(Mutation::Update {
@@ -1067,6 +1465,7 @@ mod tests {
                 added_downstream_actor_id: vec![new_simple],
                 removed_downstream_actor_id: vec![old_simple],
                 hash_mapping: Default::default(),
+                ..Default::default()
             }]
         };
         let b3 = Barrier::new_test_barrier(3).with_mutation(Mutation::Update {
@@ -1096,6 +1495,138 @@ mod tests {
         try_recv!(new_simple).unwrap().as_barrier().unwrap();
     }
 
+    #[tokio::test]
+    async fn test_dispatcher_type_change() {
+        let (tx, rx) = channel(16);
+        let actor_id = 233;
+        let (first, second) = (234, 235);
+        let input = Box::new(ReceiverExecutor::for_test(rx));
+        let ctx = Arc::new(SharedContext::for_test());
+        let metrics = Arc::new(StreamingMetrics::unused());
+
+        {
+            let mut actor_infos = ctx.actor_infos.write();
+            for local_actor_id in [actor_id, first, second] {
+                actor_infos.insert(local_actor_id, helper_make_local_actor(local_actor_id));
+            }
+        }
+        add_local_channels(ctx.clone(), vec![(actor_id, first), (actor_id, second)]);
+
+        let dispatcher_id = 666;
+        let simple_dispatcher = DispatcherImpl::new(
+            &ctx,
+            actor_id,
+            &ProstDispatcher {
+                r#type: DispatcherType::Simple as _,
+                dispatcher_id,
+                downstream_actor_id: vec![first],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let executor = Box::new(DispatchExecutor::new(
+            input,
+            vec![simple_dispatcher],
+            actor_id,
+            0,
+            ctx.clone(),
+            metrics,
+        ))
+        .execute();
+        pin_mut!(executor);
+
+        let mut rxs = [first, second]
+            .into_iter()
+            .map(|id| (id, ctx.take_receiver(&(actor_id, id)).unwrap()))
+            .collect::<HashMap<_, _>>();
+        macro_rules! try_recv {
+            ($down_id:expr) => {
+                rxs.get_mut(&$down_id).unwrap().try_recv()
+            };
+        }
+        let update_barrier = |epoch, update| {
+            Barrier::new_test_barrier(epoch).with_mutation(Mutation::Update {
+                dispatchers: maplit::hashmap! { actor_id => vec![update] },
+                merges: Default::default(),
+                vnode_bitmaps: Default::default(),
+                dropped_actors: Default::default(),
+                actor_splits: Default::default(),
+            })
+        };
+        let chunk = || {
+            StreamChunk::from_pretty(
+                " I
+                + 1
+                + 2",
+            )
+        };
+
+        // The simple dispatcher becomes a broadcast to both actors after the barrier.
+        tx.send(Message::Barrier(update_barrier(
+            1,
+            ProstDispatcherUpdate {
+                actor_id,
+                dispatcher_id,
+                added_downstream_actor_id: vec![second],
+                dispatcher_type: DispatcherType::Broadcast as _,
+                ..Default::default()
+            },
+        )))
+        .await
+        .unwrap();
+        executor.next().await.unwrap().unwrap();
+        tx.send(Message::Chunk(chunk())).await.unwrap();
+        tx.send(Message::Barrier(Barrier::new_test_barrier(2)))
+            .await
+            .unwrap();
+        executor.next().await.unwrap().unwrap();
+        for down_id in [first, second] {
+            try_recv!(down_id).unwrap().as_barrier().unwrap();
+            assert_eq!(
+                try_recv!(down_id)
+                    .unwrap()
+                    .as_chunk()
+                    .unwrap()
+                    .cardinality(),
+                2
+            );
+            try_recv!(down_id).unwrap().as_barrier().unwrap();
+        }
+
+        // The broadcast becomes a hash dispatcher sending all rows to the first actor.
+        tx.send(Message::Barrier(update_barrier(
+            3,
+            ProstDispatcherUpdate {
+                actor_id,
+                dispatcher_id,
+                hash_mapping: Some(ActorMapping {
+                    original_indices: vec![VIRTUAL_NODE_COUNT as u64 - 1],
+                    data: vec![first],
+                }),
+                dispatcher_type: DispatcherType::Hash as _,
+                column_indices: vec![0],
+                ..Default::default()
+            },
+        )))
+        .await
+        .unwrap();
+        executor.next().await.unwrap().unwrap();
+        tx.send(Message::Chunk(chunk())).await.unwrap();
+        tx.send(Message::Barrier(Barrier::new_test_barrier(4)))
+            .await
+            .unwrap();
+        executor.next().await.unwrap().unwrap();
+        try_recv!(first).unwrap().as_barrier().unwrap();
+        assert_eq!(
+            try_recv!(first).unwrap().as_chunk().unwrap().cardinality(),
+            2
+        );
+        try_recv!(first).unwrap().as_barrier().unwrap();
+        // The second actor receives no rows.
+        try_recv!(second).unwrap().as_barrier().unwrap();
+        try_recv!(second).unwrap().as_barrier().unwrap();
+    }
+
     #[tokio::test]
     async fn test_hash_dispatcher() {
         let num_outputs = 5; // actor id ranges from 1 to 5
@@ -1200,4 +1731,294 @@ mod tests {
             }
         }
     }
+
+    /// Collect the visible rows sent to each output, with the marks of their chunks.
+    fn collect_marked_rows(
+        output_data_vecs: &[Arc<Mutex<Vec<Message>>>],
+    ) -> Vec<Vec<(HotKeyMark, Op, Row)>> {
+        output_data_vecs
+            .iter()
+            .map(|data| {
+                std::mem::take(&mut *data.lock().unwrap())
+                    .into_iter()
+                    .filter_map(|message| message.into_chunk().ok())
+                    .flat_map(|chunk| {
+                        chunk
+                            .rows()
+                            .map(|(op, row)| (chunk.hot_key_mark(), op, row.to_owned_row()))
+                            .collect_vec()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_hash_dispatcher_hot_keys() {
+        let num_outputs = 4;
+        let output_data_vecs = (0..num_outputs)
+            .map(|_| Arc::new(Mutex::new(Vec::new())))
+            .collect_vec();
+        let make_dispatcher = |hot_key_mode| {
+            let outputs = output_data_vecs
+                .iter()
+                .enumerate()
+                .map(|(actor_id, data)| {
+                    Box::new(MockOutput::new(1 + actor_id as u32, data.clone())) as BoxedOutput
+                })
+                .collect_vec();
+            let hash_mapping = (0..VIRTUAL_NODE_COUNT)
+                .map(|vnode| (vnode % num_outputs) as ActorId + 1)
+                .collect_vec();
+            HashDataDispatcher::new(outputs, vec![0], hash_mapping, 0)
+                .with_hot_key_mode(hot_key_mode)
+        };
+
+        // Rows of (key, id), where 90% of the rows have the key 1.
+        let make_chunk = |ops: Vec<Op>, ids: std::ops::Range<i32>| {
+            let mut keys = I32ArrayBuilder::new(ids.len());
+            let mut values = I32ArrayBuilder::new(ids.len());
+            for id in ids {
+                keys.append(Some(if id % 10 == 0 { id } else { 1 }));
+                values.append(Some(id));
+            }
+            StreamChunk::new(
+                ops,
+                vec![keys.finish().into(), values.finish().into()],
+                None,
+            )
+        };
+        let hot_key = |row: &Row| row[0] == Some(1.into());
+
+        let mut hash_dispatcher = make_dispatcher(HotKeyMode::Probe);
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Insert; 2048], 0..2048))
+            .await
+            .unwrap();
+        // Hot keys are only updated at barriers, so the rows are still dispatched by the hash
+        // mapping.
+        assert!(hash_dispatcher.hot_key_detector().hot_keys().is_empty());
+        let rows = collect_marked_rows(&output_data_vecs);
+        assert!(rows
+            .iter()
+            .flatten()
+            .all(|(mark, ..)| *mark == HotKeyMark::Normal));
+        assert!(rows.iter().any(|rows| rows.len() >= 2048 * 9 / 10));
+        let owner = rows
+            .iter()
+            .position(|rows| rows.iter().any(|(_, _, row)| hot_key(row)))
+            .unwrap();
+
+        hash_dispatcher
+            .dispatch_barrier(Barrier::new_test_barrier(1))
+            .await
+            .unwrap();
+        assert_eq!(hash_dispatcher.hot_key_detector().hot_keys().len(), 1);
+
+        // In the probe mode, the owner persists all rows of the hot key, and the copies are spread
+        // among the other outputs evenly.
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Insert; 2048], 2048..4096))
+            .await
+            .unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        let num_hot_rows = (2048..4096).filter(|id| id % 10 != 0).count();
+        for (i, rows) in rows.iter().enumerate() {
+            let (hot, normal): (Vec<_>, Vec<_>) = rows.iter().partition(|(_, _, row)| hot_key(row));
+            assert!(normal.iter().all(|(mark, ..)| *mark == HotKeyMark::Normal));
+            if i == owner {
+                assert_eq!(hot.len(), num_hot_rows);
+                assert!(hot
+                    .iter()
+                    .all(|(mark, ..)| *mark == HotKeyMark::SpreadOwner));
+            } else {
+                assert!(hot
+                    .iter()
+                    .all(|(mark, ..)| *mark == HotKeyMark::SpreadShare));
+                assert!(hot.len() > num_hot_rows / (num_outputs - 1) / 2);
+            }
+        }
+        let share_targets: HashMap<Row, usize> = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, rows)| {
+                rows.iter()
+                    .filter(|(mark, ..)| *mark == HotKeyMark::SpreadShare)
+                    .map(move |(_, _, row)| (row.clone(), i))
+            })
+            .collect();
+        assert_eq!(share_targets.len(), num_hot_rows);
+
+        // The hot key stays hot even if it's no longer skewed, and the retraction of a row reaches
+        // the same outputs as the row.
+        hash_dispatcher
+            .dispatch_barrier(Barrier::new_test_barrier(2))
+            .await
+            .unwrap();
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Delete; 2048], 2048..4096))
+            .await
+            .unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        for (i, rows) in rows.iter().enumerate() {
+            for (mark, op, row) in rows {
+                assert_eq!(*op, Op::Delete);
+                match mark {
+                    HotKeyMark::SpreadOwner => assert_eq!(i, owner),
+                    HotKeyMark::SpreadShare => assert_eq!(share_targets[row], i),
+                    _ => assert!(!hot_key(row)),
+                }
+            }
+        }
+
+        // Updates of the hot key are split into deletes and inserts, as they are sent to more
+        // than one output.
+        let update_chunk = StreamChunk::from_pretty(
+            "  i i
+            U- 1 1
+            U+ 1 2",
+        );
+        hash_dispatcher.dispatch_data(update_chunk).await.unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        assert_eq!(
+            rows[owner].iter().map(|(_, op, _)| *op).collect_vec(),
+            vec![Op::Delete, Op::Insert]
+        );
+
+        // In the build mode, the rows of the hot key are broadcast.
+        let mut hash_dispatcher = make_dispatcher(HotKeyMode::Build);
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Insert; 2048], 0..2048))
+            .await
+            .unwrap();
+        hash_dispatcher
+            .dispatch_barrier(Barrier::new_test_barrier(1))
+            .await
+            .unwrap();
+        collect_marked_rows(&output_data_vecs);
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Insert; 2048], 2048..4096))
+            .await
+            .unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        for (i, rows) in rows.iter().enumerate() {
+            let hot = rows.iter().filter(|(_, _, row)| hot_key(row)).collect_vec();
+            assert_eq!(hot.len(), num_hot_rows);
+            let expected_mark = if i == owner {
+                HotKeyMark::BroadcastOwner
+            } else {
+                HotKeyMark::Replica
+            };
+            assert!(hot.iter().all(|(mark, ..)| *mark == expected_mark));
+        }
+
+        // Without a hot key mode, the hot keys are not detected.
+        let mut hash_dispatcher = make_dispatcher(HotKeyMode::Unspecified);
+        hash_dispatcher
+            .dispatch_data(make_chunk(vec![Op::Insert; 2048], 0..2048))
+            .await
+            .unwrap();
+        hash_dispatcher
+            .dispatch_barrier(Barrier::new_test_barrier(1))
+            .await
+            .unwrap();
+        assert!(hash_dispatcher.hot_key_detector().hot_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hash_dispatcher_hot_key_max_rows() {
+        let num_outputs = 4;
+        let output_data_vecs = (0..num_outputs)
+            .map(|_| Arc::new(Mutex::new(Vec::new())))
+            .collect_vec();
+        let outputs = output_data_vecs
+            .iter()
+            .enumerate()
+            .map(|(actor_id, data)| {
+                Box::new(MockOutput::new(1 + actor_id as u32, data.clone())) as BoxedOutput
+            })
+            .collect_vec();
+        let hash_mapping = (0..VIRTUAL_NODE_COUNT)
+            .map(|vnode| (vnode % num_outputs) as ActorId + 1)
+            .collect_vec();
+        let mut hash_dispatcher = HashDataDispatcher::new(outputs, vec![0], hash_mapping, 0)
+            .with_hot_key_mode(HotKeyMode::Build);
+
+        // Rows of (key, id), all of the key 1.
+        let make_chunk = |op: Op, ids: std::ops::Range<i32>| {
+            let mut keys = I32ArrayBuilder::new(ids.len());
+            let mut values = I32ArrayBuilder::new(ids.len());
+            for id in ids.clone() {
+                keys.append(Some(1));
+                values.append(Some(id));
+            }
+            StreamChunk::new(
+                vec![op; ids.len()],
+                vec![keys.finish().into(), values.finish().into()],
+                None,
+            )
+        };
+
+        hash_dispatcher
+            .dispatch_data(make_chunk(Op::Insert, 0..2048))
+            .await
+            .unwrap();
+        hash_dispatcher
+            .dispatch_barrier(Barrier::new_test_barrier(1))
+            .await
+            .unwrap();
+        assert_eq!(hash_dispatcher.hot_key_detector().hot_keys().len(), 1);
+        let owner = collect_marked_rows(&output_data_vecs)
+            .iter()
+            .position(|rows| !rows.is_empty())
+            .unwrap();
+
+        // The inserts beyond the limit are only sent to the owner as normal rows, so the copies
+        // held by the other outputs are bounded.
+        let max_rows = HOT_KEY_MAX_ROWS as i32;
+        hash_dispatcher
+            .dispatch_data(make_chunk(Op::Insert, 0..max_rows / 2))
+            .await
+            .unwrap();
+        hash_dispatcher
+            .dispatch_data(make_chunk(Op::Insert, max_rows / 2..max_rows + 100))
+            .await
+            .unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        for (i, rows) in rows.iter().enumerate() {
+            let (hot, normal): (Vec<_>, Vec<_>) = rows
+                .iter()
+                .partition(|(mark, ..)| *mark != HotKeyMark::Normal);
+            assert_eq!(hot.len(), HOT_KEY_MAX_ROWS);
+            if i == owner {
+                assert!(hot
+                    .iter()
+                    .all(|(mark, ..)| *mark == HotKeyMark::BroadcastOwner));
+                assert_eq!(normal.len(), 100);
+                assert!(normal
+                    .iter()
+                    .all(|(_, _, row)| *row[1].as_ref().unwrap().as_int32() >= max_rows));
+            } else {
+                assert!(hot.iter().all(|(mark, ..)| *mark == HotKeyMark::Replica));
+                assert!(normal.is_empty());
+            }
+        }
+
+        // The retractions are still routed as hot key rows, and the outputs ignore the ones of
+        // the rows they don't hold.
+        hash_dispatcher
+            .dispatch_data(make_chunk(Op::Delete, 0..max_rows + 100))
+            .await
+            .unwrap();
+        let rows = collect_marked_rows(&output_data_vecs);
+        for (i, rows) in rows.iter().enumerate() {
+            assert_eq!(rows.len(), HOT_KEY_MAX_ROWS + 100);
+            let expected_mark = if i == owner {
+                HotKeyMark::BroadcastOwner
+            } else {
+                HotKeyMark::Replica
+            };
+            assert!(rows.iter().all(|(mark, ..)| *mark == expected_mark));
+        }
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::borrow::Cow;
 use std::ops::Bound::{self, *};
 use std::sync::Arc;
 
@@ -51,6 +52,9 @@ pub struct DynamicFilterExecutor<S: StateStore> {
     range_cache: RangeCache<S>,
     right_table: StateTable<S>,
     is_right_table_writer: bool,
+    /// Whether the right side only makes the predicate stricter over time. If so, the left rows
+    /// failing the predicate never pass it again, and are cleaned from the state.
+    is_monotonic: bool,
     schema: Schema,
     metrics: Arc<StreamingMetrics>,
     /// The maximum size of the chunk produced by executor at a time.
@@ -70,6 +74,7 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
         mut state_table_l: StateTable<S>,
         mut state_table_r: StateTable<S>,
         is_right_table_writer: bool,
+        is_monotonic: bool,
         metrics: Arc<StreamingMetrics>,
         chunk_size: usize,
     ) -> Self {
@@ -89,6 +94,7 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
             range_cache: RangeCache::new(state_table_l, usize::MAX),
             right_table: state_table_r,
             is_right_table_writer,
+            is_monotonic,
             metrics,
             schema,
             chunk_size,
@@ -106,6 +112,9 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
         let mut new_visibility = BitmapBuilder::with_capacity(ops.len());
         let mut last_res = false;
 
+        // The rows failing a monotonic predicate never pass it again, so they need not be stored.
+        // The predicate is `None` for a NULL right value, which is not monotonic.
+        let skip_failed_rows = self.is_monotonic && condition.is_some();
         let eval_results = condition.map(|cond| {
             cond.eval_infallible(data_chunk, |err| {
                 self.ctx.on_compute_error(err, self.identity())
@@ -169,6 +178,9 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
             // Store the rows without a null left key
             // null key in left side of predicate should never be stored
             // (it will never satisfy the filter condition)
+            if skip_failed_rows && !res {
+                continue;
+            }
             if let Some(val) = left_val {
                 match *op {
                     Op::Insert | Op::UpdateInsert => {
@@ -233,6 +245,17 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
         }
     }
 
+    /// Returns the range of the left keys failing the predicate given the right value `curr`.
+    fn get_failed_range(&self, curr: ScalarImpl) -> (Bound<ScalarImpl>, Bound<ScalarImpl>) {
+        match self.comparator {
+            GreaterThan => (Unbounded, Included(curr)),
+            GreaterThanOrEqual => (Unbounded, Excluded(curr)),
+            LessThan => (Included(curr), Unbounded),
+            LessThanOrEqual => (Excluded(curr), Unbounded),
+            _ => unreachable!(),
+        }
+    }
+
     #[try_stream(ok = Message, error = StreamExecutorError)]
     async fn into_stream(mut self) {
         let input_l = self.source_l.take().unwrap();
@@ -268,7 +291,19 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
 
         let barrier = expect_first_barrier_from_aligned_stream(&mut aligned_stream).await?;
         self.right_table.init_epoch(barrier.epoch);
-        self.range_cache.init(barrier.epoch);
+        self.range_cache.init(barrier.epoch).await?;
+
+        // Recover the right value of the last epoch, so that the deletes of it from the right side
+        // are consistent after recovery.
+        let recovered_row = {
+            let iter = self.right_table.iter().await?;
+            pin_mut!(iter);
+            iter.next().await.transpose()?.map(Cow::into_owned)
+        };
+        if let Some(row) = recovered_row {
+            prev_epoch_value = Some(row[0].clone());
+            current_epoch_value = Some(row[0].clone());
+        }
 
         // The first barrier message should be propagated.
         yield Message::Barrier(barrier);
@@ -357,6 +392,12 @@ impl<S: StateStore> DynamicFilterExecutor<S> {
                         if let Some(chunk) = stream_chunk_builder.take()? {
                             yield Message::Chunk(chunk);
                         }
+
+                        if self.is_monotonic && let Some(curr) = curr.clone() {
+                            let failed_range = self.get_failed_range(curr);
+                            self.range_cache
+                                .delete_range(failed_range, &row_deserializer)?;
+                        }
                     }
 
                     if self.is_right_table_writer {
@@ -411,6 +452,7 @@ mod tests {
     use risingwave_common::array::stream_chunk::StreamChunkTestExt;
     use risingwave_common::array::*;
     use risingwave_common::catalog::{ColumnDesc, ColumnId, Field, Schema, TableId};
+    use risingwave_common::util::epoch::EpochPair;
     use risingwave_common::util::sort_util::OrderType;
     use risingwave_storage::memory::MemoryStateStore;
 
@@ -440,8 +482,50 @@ mod tests {
         (state_table_l, state_table_r)
     }
 
+    /// The state tables of a dynamic filter against a now stream on `mem_state`, which can be
+    /// reopened for recovery. The right table has no primary key, just like the one planned by
+    /// the frontend for `now()`.
+    fn create_monotonic_state_table(
+        mem_state: MemoryStateStore,
+    ) -> (StateTable<MemoryStateStore>, StateTable<MemoryStateStore>) {
+        let column_descs = ColumnDesc::unnamed(ColumnId::new(0), DataType::Int64);
+        let state_table_l = StateTable::new_without_distribution(
+            mem_state.clone(),
+            TableId::new(0),
+            vec![column_descs.clone()],
+            vec![OrderType::Ascending],
+            vec![0],
+        );
+        let state_table_r = StateTable::new_without_distribution(
+            mem_state,
+            TableId::new(1),
+            vec![column_descs],
+            vec![],
+            vec![],
+        );
+        (state_table_l, state_table_r)
+    }
+
     fn create_executor(
         comparator: ExprNodeType,
+    ) -> (MessageSender, MessageSender, BoxedMessageStream) {
+        let (mem_state_l, mem_state_r) = create_in_memory_state_table();
+        create_executor_with_state_tables(comparator, mem_state_l, mem_state_r, false)
+    }
+
+    fn create_monotonic_executor(
+        comparator: ExprNodeType,
+        mem_state: MemoryStateStore,
+    ) -> (MessageSender, MessageSender, BoxedMessageStream) {
+        let (mem_state_l, mem_state_r) = create_monotonic_state_table(mem_state);
+        create_executor_with_state_tables(comparator, mem_state_l, mem_state_r, true)
+    }
+
+    fn create_executor_with_state_tables(
+        comparator: ExprNodeType,
+        mem_state_l: StateTable<MemoryStateStore>,
+        mem_state_r: StateTable<MemoryStateStore>,
+        is_monotonic: bool,
     ) -> (MessageSender, MessageSender, BoxedMessageStream) {
         let schema = Schema {
             fields: vec![Field::unnamed(DataType::Int64)],
@@ -449,7 +533,6 @@ mod tests {
         let (tx_l, source_l) = MockSource::channel(schema.clone(), vec![0]);
         let (tx_r, source_r) = MockSource::channel(schema, vec![]);
 
-        let (mem_state_l, mem_state_r) = create_in_memory_state_table();
         let executor = DynamicFilterExecutor::<MemoryStateStore>::new(
             ActorContext::create(123),
             Box::new(source_l),
@@ -461,6 +544,7 @@ mod tests {
             mem_state_l,
             mem_state_r,
             true,
+            is_monotonic,
             Arc::new(StreamingMetrics::unused()),
             1024,
         );
@@ -880,4 +964,117 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn test_dynamic_filter_monotonic() {
+        let mem_state = MemoryStateStore::new();
+        let (mut tx_l, mut tx_r, mut dynamic_filter) =
+            create_monotonic_executor(ExprNodeType::GreaterThan, mem_state.clone());
+
+        // push the init barrier for left and right
+        tx_l.push_barrier(1, false);
+        tx_r.push_barrier(1, false);
+        dynamic_filter.next().await.unwrap().unwrap();
+
+        tx_l.push_chunk(StreamChunk::from_pretty(
+            "  I
+             + 1
+             + 2
+             + 3
+             + 4",
+        ));
+        tx_r.push_chunk(StreamChunk::from_pretty(
+            "  I
+             + 2",
+        ));
+        tx_l.push_barrier(2, false);
+        tx_r.push_barrier(2, false);
+
+        let chunk = dynamic_filter.next().await.unwrap().unwrap();
+        assert_eq!(
+            chunk.into_chunk().unwrap(),
+            StreamChunk::from_pretty(
+                " I
+                + 3
+                + 4"
+            )
+        );
+        dynamic_filter.next().await.unwrap().unwrap();
+
+        // The rows failing the predicate are not stored, so deleting the expired row 1 and
+        // inserting the row 2 are both no-ops.
+        tx_l.push_chunk(StreamChunk::from_pretty(
+            "  I
+             - 1
+             + 2
+             + 5",
+        ));
+        let chunk = dynamic_filter.next().await.unwrap().unwrap();
+        assert_eq!(
+            chunk.into_chunk().unwrap().compact(),
+            StreamChunk::from_pretty(
+                " I
+                + 5"
+            )
+        );
+
+        tx_r.push_chunk(StreamChunk::from_pretty(
+            "  I
+             - 2
+             + 3",
+        ));
+        tx_l.push_barrier(3, false);
+        tx_r.push_barrier(3, false);
+
+        let chunk = dynamic_filter.next().await.unwrap().unwrap();
+        assert_eq!(
+            chunk.into_chunk().unwrap(),
+            StreamChunk::from_pretty(
+                " I
+                - 3"
+            )
+        );
+        dynamic_filter.next().await.unwrap().unwrap();
+
+        // Only the rows passing the predicate are left in the state.
+        let (mut state_table_l, _) = create_monotonic_state_table(mem_state.clone());
+        state_table_l.init_epoch(EpochPair::new_test_epoch(4));
+        let rows: Vec<_> = {
+            let iter = state_table_l.iter().await.unwrap();
+            pin_mut!(iter);
+            iter.map(|row| row.unwrap().into_owned()).collect().await
+        };
+        assert_eq!(
+            rows,
+            vec![
+                Row::new(vec![Some(4i64.into())]),
+                Row::new(vec![Some(5i64.into())]),
+            ]
+        );
+
+        // Recover from the state, where the right value 3 is restored and the delete of it is
+        // consistent.
+        let (mut tx_l, mut tx_r, mut dynamic_filter) =
+            create_monotonic_executor(ExprNodeType::GreaterThan, mem_state);
+        tx_l.push_barrier(4, false);
+        tx_r.push_barrier(4, false);
+        dynamic_filter.next().await.unwrap().unwrap();
+
+        tx_r.push_chunk(StreamChunk::from_pretty(
+            "  I
+             - 3
+             + 4",
+        ));
+        tx_l.push_barrier(5, false);
+        tx_r.push_barrier(5, false);
+
+        let chunk = dynamic_filter.next().await.unwrap().unwrap();
+        assert_eq!(
+            chunk.into_chunk().unwrap(),
+            StreamChunk::from_pretty(
+                " I
+                - 4"
+            )
+        );
+    }
 }
@@ -17,11 +17,15 @@ use std::task::{Context, Poll};
 use std::time::Instant;
 
 use async_stack_trace::{SpanValue, StackTrace};
-use futures::{pin_mut, Stream};
+use futures::{pin_mut, Stream, TryStreamExt};
 use futures_async_stream::try_stream;
 use pin_project::pin_project;
+use risingwave_common::array::checksum::{ChunkChecksumExt, ChunkChecksumMismatch};
 use risingwave_common::bail;
 use risingwave_common::util::addr::{is_local_address, HostAddr};
+use risingwave_pb::stream_plan::stream_message::StreamMessage;
+use risingwave_pb::stream_plan::StreamMessage as ProstStreamMessage;
+use risingwave_rpc_client::error::RpcError;
 use risingwave_rpc_client::ComputeClientPool;
 use tokio::sync::mpsc::Receiver;
 
@@ -29,7 +33,9 @@ use crate::error::StreamResult;
 use crate::executor::error::StreamExecutorError;
 use crate::executor::monitor::StreamingMetrics;
 use crate::executor::*;
-use crate::task::{FragmentId, SharedContext, UpDownActorIds, UpDownFragmentIds};
+use crate::task::{
+    FragmentId, SharedContext, UpDownActorIds, UpDownFragmentIds, LOCAL_OUTPUT_CHANNEL_SIZE,
+};
 
 /// `Input` provides an interface for [`MergeExecutor`] and [`ReceiverExecutor`] to receive data
 /// from upstream actors.
@@ -66,7 +72,7 @@ pub struct LocalInput {
 type LocalInputStreamInner = impl MessageStream;
 
 impl LocalInput {
-    fn new(channel: Receiver<Message>, actor_id: ActorId) -> Self {
+    pub fn new(channel: Receiver<Message>, actor_id: ActorId) -> Self {
         Self {
             inner: Self::run(channel, actor_id),
             actor_id,
@@ -146,9 +152,37 @@ impl RemoteInput {
         metrics: Arc<StreamingMetrics>,
     ) {
         let client = client_pool.get_by_addr(upstream_addr).await?;
-        let stream = client
-            .get_stream(up_down_ids.0, up_down_ids.1, up_down_frag.0, up_down_frag.1)
-            .await?;
+        // The channel is multiplexed on the connection to the upstream node with the others,
+        // and has the same buffer size as the local channels. Fall back to a dedicated `GetStream`
+        // RPC if the multiplexed connection can't be established, e.g. the upstream node doesn't
+        // serve it.
+        let stream = match client
+            .get_multiplexed_stream(
+                up_down_ids.0,
+                up_down_ids.1,
+                up_down_frag.0,
+                up_down_frag.1,
+                LOCAL_OUTPUT_CHANNEL_SIZE as u32,
+            )
+            .await
+        {
+            Ok(stream) => stream.boxed(),
+            Err(e) => {
+                tracing::warn!(
+                    "failed to subscribe multiplexed stream from actor {} to actor {}, fall back \
+                     to GetStream: {}",
+                    up_down_ids.0,
+                    up_down_ids.1,
+                    e
+                );
+                client
+                    .get_stream(up_down_ids.0, up_down_ids.1, up_down_frag.0, up_down_frag.1)
+                    .await?
+                    .map_ok(|response| response.message.expect("no message"))
+                    .map_err(RpcError::from)
+                    .boxed()
+            }
+        };
 
         let up_actor_id = up_down_ids.0.to_string();
         let down_actor_id = up_down_ids.1.to_string();
@@ -163,9 +197,8 @@ impl RemoteInput {
         pin_mut!(stream);
         while let Some(data_res) = stream.next().verbose_stack_trace(span.clone()).await {
             match data_res {
-                Ok(stream_msg) => {
-                    let bytes = Message::get_encoded_len(&stream_msg);
-                    let msg = stream_msg.get_message().expect("no message");
+                Ok(msg) => {
+                    let bytes = Message::get_encoded_len(&msg);
 
                     metrics
                         .exchange_recv_size
@@ -177,17 +210,29 @@ impl RemoteInput {
                         .with_label_values(&[&up_fragment_id, &down_fragment_id])
                         .inc_by(bytes as u64);
 
+                    if let Err(e) = verify_checksum(&msg) {
+                        metrics
+                            .exchange_checksum_mismatch_count
+                            .with_label_values(&[&up_actor_id, &down_actor_id])
+                            .inc();
+                        bail!(
+                            "RemoteInput received a corrupted chunk from upstream actor {}: {}",
+                            up_actor_id,
+                            e
+                        );
+                    }
+
                     // add deserialization duration metric with given sampling frequency
                     let msg_res = if rr % SAMPLING_FREQUENCY == 0 {
                         let start_time = Instant::now();
-                        let msg_res = Message::from_protobuf(msg);
+                        let msg_res = Message::from_protobuf(&msg);
                         metrics
                             .actor_sampled_deserialize_duration_ns
                             .with_label_values(&[&down_actor_id])
                             .inc_by(start_time.elapsed().as_nanos() as u64);
                         msg_res
                     } else {
-                        Message::from_protobuf(msg)
+                        Message::from_protobuf(&msg)
                     };
                     rr += 1;
 
@@ -207,6 +252,14 @@ impl RemoteInput {
     }
 }
 
+/// Verify the checksum of the chunk in `msg`, if any.
+fn verify_checksum(msg: &ProstStreamMessage) -> Result<(), ChunkChecksumMismatch> {
+    match &msg.stream_message {
+        Some(StreamMessage::StreamChunk(chunk)) => chunk.verify_checksum(),
+        _ => Ok(()),
+    }
+}
+
 impl Stream for RemoteInput {
     type Item = MessageStreamItem;
 
@@ -22,7 +22,7 @@ use risingwave_expr::expr::BoxedExpression;
 
 use super::{
     ActorContextRef, Executor, ExecutorInfo, PkIndicesRef, SimpleExecutor, SimpleExecutorWrapper,
-    StreamExecutorResult,
+    StreamExecutorResult, Watermark,
 };
 use crate::common::InfallibleExpression;
 
@@ -109,6 +109,7 @@ impl SimpleExecutor for SimpleFilterExecutor {
         assert!(match vis {
             Vis::Compact(c) => c == n,
             Vis::Bitmap(ref m) => m.len() == n,
+            Vis::Selection(ref s) => s.capacity() == n,
         });
 
         if let ArrayImpl::Bool(bool_array) = &*pred_output {
@@ -169,6 +170,10 @@ impl SimpleExecutor for SimpleFilterExecutor {
         })
     }
 
+    fn handle_watermark(&self, watermark: Watermark) -> Option<Watermark> {
+        Some(watermark)
+    }
+
     fn schema(&self) -> &Schema {
         &self.info.schema
     }
@@ -22,7 +22,7 @@ use futures_async_stream::try_stream;
 use iter_chunks::IterChunks;
 use itertools::Itertools;
 use risingwave_common::array::column::Column;
-use risingwave_common::array::{Row, StreamChunk};
+use risingwave_common::array::{DataChunk, Row, StreamChunk};
 use risingwave_common::buffer::Bitmap;
 use risingwave_common::catalog::Schema;
 use risingwave_common::hash::{HashCode, HashKey, PrecomputedBuildHasher};
@@ -34,8 +34,13 @@ use risingwave_storage::StateStore;
 use super::aggregation::{agg_call_filter_res, iter_table_storage, AggStateStorage};
 use super::{expect_first_barrier, ActorContextRef, Executor, PkIndicesRef, StreamExecutorResult};
 use crate::cache::{cache_may_stale, EvictableHashMap, ExecutorCache, LruManagerRef};
+use crate::common::{
+    prefetch_state_table, PrefetchedGroup, StatePrefetchConfig, StatePrefetchHandle,
+};
 use crate::error::StreamResult;
-use crate::executor::aggregation::{generate_agg_schema, AggCall, AggChangesInfo, AggGroup};
+use crate::executor::aggregation::{
+    generate_agg_schema, result_row_count, AggCall, AggChangesInfo, AggGroup,
+};
 use crate::executor::error::StreamExecutorError;
 use crate::executor::monitor::StreamingMetrics;
 use crate::executor::{BoxedMessageStream, Message, PkIndices};
@@ -117,6 +122,9 @@ struct HashAggExecutorExtra<K: HashKey, S: StateStore> {
 
     /// The maximum size of the chunk produced by executor at a time.
     chunk_size: usize,
+
+    /// Limits of prefetching the groups into the cache when the executor is built.
+    state_prefetch: StatePrefetchConfig,
 }
 
 impl<K: HashKey, S: StateStore> Executor for HashAggExecutor<K, S> {
@@ -153,6 +161,7 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
         lru_manager: Option<LruManagerRef>,
         metrics: Arc<StreamingMetrics>,
         chunk_size: usize,
+        state_prefetch: StatePrefetchConfig,
     ) -> StreamResult<Self> {
         let input_info = input.info();
         let schema = generate_agg_schema(input.as_ref(), &agg_calls, Some(&group_key_indices));
@@ -178,6 +187,7 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
                 total_lookup_count: AtomicU64::new(0),
                 metrics,
                 chunk_size,
+                state_prefetch,
             },
             _phantom: PhantomData,
         })
@@ -233,6 +243,7 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
         Ok(result)
     }
 
+    #[cfg_attr(not(feature = "telemetry"), allow(unused_variables))]
     async fn apply_chunk(
         HashAggExecutorExtra::<K, S> {
             ref ctx,
@@ -248,11 +259,15 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
             ref schema,
             lookup_miss_count,
             total_lookup_count,
+            ref metrics,
             ..
         }: &mut HashAggExecutorExtra<K, S>,
         agg_groups: &mut AggGroupMap<K, S>,
         chunk: StreamChunk,
     ) -> StreamExecutorResult<()> {
+        #[cfg(feature = "telemetry")]
+        let hash_start_time = std::time::Instant::now();
+
         // Compute hash code here before serializing keys to avoid duplicate hash code computation.
         let hash_codes = chunk
             .data_chunk()
@@ -264,6 +279,15 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
         // TODO: this might be inefficient if there are not too many duplicated keys in one batch.
         let unique_keys = Self::get_unique_keys(keys, hash_codes, chunk.visibility())?;
 
+        #[cfg(feature = "telemetry")]
+        let state_update_start_time = {
+            metrics
+                .agg_chunk_hash_duration
+                .with_label_values(&[&ctx.id.to_string()])
+                .observe(hash_start_time.elapsed().as_secs_f64());
+            std::time::Instant::now()
+        };
+
         let group_key_types = &schema.data_types()[..group_key_indices.len()];
         let mut futures = vec![];
         for (key, _hash_code, _) in &unique_keys {
@@ -365,6 +389,109 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
             agg_group.apply_chunk(storages, &ops, &columns, visibilities)?;
         }
 
+        #[cfg(feature = "telemetry")]
+        metrics
+            .agg_chunk_state_update_duration
+            .with_label_values(&[&ctx.id.to_string()])
+            .observe(state_update_start_time.elapsed().as_secs_f64());
+
+        Ok(())
+    }
+
+    /// Prefetch the hottest groups in the result table in the background, so that the first
+    /// chunks after recovery or migration do not miss the cache for every group. The groups
+    /// with the most rows are taken as the hottest.
+    fn spawn_prefetch_groups(
+        HashAggExecutorExtra::<K, S> {
+            ref ctx,
+            ref group_key_indices,
+            ref result_table,
+            ref metrics,
+            ref state_prefetch,
+            ..
+        }: &HashAggExecutorExtra<K, S>,
+    ) -> StatePrefetchHandle<Vec<PrefetchedGroup>> {
+        let result_table = result_table.clone();
+        let group_key_len = group_key_indices.len();
+        let state_prefetch = *state_prefetch;
+        let metrics = metrics.clone();
+        let actor_id = ctx.id;
+        StatePrefetchHandle::spawn(async move {
+            prefetch_state_table(
+                &result_table,
+                group_key_len,
+                |group| {
+                    group
+                        .iter()
+                        .map(|(_, result)| result_row_count(result) as u64)
+                        .sum()
+                },
+                &state_prefetch,
+                &metrics,
+                actor_id,
+            )
+            .await
+        })
+    }
+
+    /// Put the prefetched groups into the cache. This must be called before any chunk is applied,
+    /// as the prefetched groups are read from the state at the first barrier.
+    async fn put_prefetched_groups(
+        HashAggExecutorExtra::<K, S> {
+            ref group_key_indices,
+            ref agg_calls,
+            ref storages,
+            ref input_schema,
+            ref input_pk_indices,
+            ref extreme_cache_size,
+            ref schema,
+            ..
+        }: &HashAggExecutorExtra<K, S>,
+        agg_groups: &mut AggGroupMap<K, S>,
+        groups: Vec<PrefetchedGroup>,
+    ) -> StreamExecutorResult<()> {
+        if groups.is_empty() {
+            return Ok(());
+        }
+
+        // Each group has a single result row. Put the coldest groups first, so that the hottest
+        // ones are evicted last.
+        let (group_keys, prev_results): (Vec<_>, Vec<_>) =
+            groups.into_iter().rev().flatten().unzip();
+
+        // Build the hash keys in the same way as `apply_chunk`, so that they hit the cache.
+        let group_key_types = &schema.data_types()[..group_key_indices.len()];
+        let group_key_chunk = DataChunk::from_rows(&group_keys, group_key_types);
+        let key_indices = (0..group_key_indices.len()).collect_vec();
+        let hash_codes = group_key_chunk.get_hash_values(&key_indices, Crc32FastBuilder);
+        let keys = K::build_from_hash_code(&key_indices, &group_key_chunk, hash_codes);
+
+        let futures = keys
+            .into_iter()
+            .zip_eq(group_keys.into_iter().zip_eq(prev_results))
+            .map(|(key, (group_key, prev_result))| async move {
+                let agg_group = AggGroup::create_with_prev_result(
+                    Some(group_key),
+                    Some(prev_result),
+                    agg_calls,
+                    storages,
+                    input_pk_indices,
+                    *extreme_cache_size,
+                    input_schema,
+                )
+                .await?;
+                Ok::<_, StreamExecutorError>((key, Box::new(agg_group)))
+            });
+        let mut buffered = stream::iter(futures).buffered(10).fuse();
+        while let Some(result) = buffered.next().await {
+            let (key, agg_group) = result?;
+            agg_groups.put(key, Some(agg_group));
+        }
+        drop(buffered);
+
+        // Evict cache to target capacity, in case the prefetch budget exceeds it.
+        agg_groups.evict();
+
         Ok(())
     }
 
@@ -520,15 +647,27 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
         extra.result_table.init_epoch(barrier.epoch);
         agg_states.update_epoch(barrier.epoch.curr);
 
+        // Prefetch the state in the background, without holding back the barriers.
+        let mut state_prefetch = extra
+            .state_prefetch
+            .is_enabled()
+            .then(|| Self::spawn_prefetch_groups(&extra));
+
         yield Message::Barrier(barrier);
 
         #[for_await]
         for msg in input {
             let msg = msg?;
+            // Put the prefetched groups into the cache once they are read. Chunks access the
+            // cache, so they wait for the prefetch.
+            let wait = matches!(msg, Message::Chunk(_));
+            if let Some(groups) =
+                StatePrefetchHandle::take_finished(&mut state_prefetch, wait).await
+            {
+                Self::put_prefetched_groups(&extra, &mut agg_states, groups?).await?;
+            }
             match msg {
-                Message::Watermark(_) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Message::Watermark(_) => {}
 
                 Message::Chunk(chunk) => {
                     Self::apply_chunk(&mut extra, &mut agg_states, chunk).await?;
@@ -551,6 +690,8 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
                         if cache_may_stale(&previous_vnode_bitmap, &vnode_bitmap) {
                             agg_states.clear();
                         }
+                        // The groups being prefetched may be of the vnodes no longer owned.
+                        state_prefetch = None;
                     }
 
                     // Update the current epoch.
@@ -566,6 +707,7 @@ impl<K: HashKey, S: StateStore> HashAggExecutor<K, S> {
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use assert_matches::assert_matches;
     use futures::StreamExt;
@@ -580,6 +722,7 @@ mod tests {
     use risingwave_storage::memory::MemoryStateStore;
     use risingwave_storage::StateStore;
 
+    use crate::common::StatePrefetchConfig;
     use crate::executor::aggregation::{AggArgs, AggCall};
     use crate::executor::monitor::StreamingMetrics;
     use crate::executor::test_utils::agg_executor::{create_agg_state_table, create_result_table};
@@ -596,6 +739,8 @@ mod tests {
         group_by_cache_size: usize,
         extreme_cache_size: usize,
         executor_id: u64,
+        state_prefetch: StatePrefetchConfig,
+        metrics: Arc<StreamingMetrics>,
     ) -> Box<dyn Executor> {
         let agg_state_tables = agg_calls
             .iter()
@@ -631,8 +776,9 @@ mod tests {
             group_by_cache_size,
             extreme_cache_size,
             None,
-            Arc::new(StreamingMetrics::unused()),
+            metrics,
             1024,
+            state_prefetch,
         )
         .unwrap()
         .boxed()
@@ -660,6 +806,94 @@ mod tests {
         test_local_hash_aggregation_min_append_only(MemoryStateStore::new()).await
     }
 
+    #[tokio::test]
+    async fn test_hash_agg_state_prefetch_in_memory() {
+        // Without prefetch, every group misses the cache after recovery.
+        let lookup_miss_count =
+            test_hash_agg_state_prefetch(MemoryStateStore::new(), StatePrefetchConfig::disabled())
+                .await;
+        assert_eq!(lookup_miss_count, 100);
+
+        // With prefetch, every group is already in the cache.
+        let state_prefetch = StatePrefetchConfig {
+            max_bytes: 1 << 20,
+            deadline: Duration::from_secs(60),
+        };
+        let lookup_miss_count =
+            test_hash_agg_state_prefetch(MemoryStateStore::new(), state_prefetch).await;
+        assert_eq!(lookup_miss_count, 0);
+
+        // Only the hottest group is kept within the byte budget.
+        let state_prefetch = StatePrefetchConfig {
+            max_bytes: 1,
+            deadline: Duration::from_secs(60),
+        };
+        let lookup_miss_count =
+            test_hash_agg_state_prefetch(MemoryStateStore::new(), state_prefetch).await;
+        assert_eq!(lookup_miss_count, 99);
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[tokio::test]
+    async fn test_hash_agg_chunk_telemetry() {
+        const NUM_CHUNKS: u64 = 1000;
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int64)],
+        };
+        let agg_calls = vec![AggCall {
+            kind: AggKind::Count,
+            args: AggArgs::None,
+            return_type: DataType::Int64,
+            order_pairs: vec![],
+            append_only: false,
+            filter: None,
+        }];
+
+        let (mut tx, source) = MockSource::channel(schema, PkIndices::new());
+        tx.push_barrier(1, false);
+        for i in 0..NUM_CHUNKS as i64 {
+            let rows = (0..4)
+                .map(|j| (Op::Insert, Row(vec![Some((i * 4 + j).into())])))
+                .collect_vec();
+            tx.push_chunk(StreamChunk::from_rows(&rows, &[DataType::Int64]));
+        }
+        tx.push_barrier(2, false);
+
+        let metrics = Arc::new(StreamingMetrics::unused());
+        let hash_agg = new_boxed_hash_agg_executor(
+            MemoryStateStore::new(),
+            Box::new(source),
+            agg_calls,
+            vec![0],
+            vec![],
+            1 << 16,
+            1 << 10,
+            1,
+            StatePrefetchConfig::disabled(),
+            metrics.clone(),
+        );
+        let mut hash_agg = hash_agg.execute();
+        // Consume the init barrier, then the output until the second barrier.
+        hash_agg.next().await.unwrap().unwrap();
+        while hash_agg
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .as_barrier()
+            .is_none()
+        {}
+
+        for histogram in [
+            &metrics.agg_chunk_hash_duration,
+            &metrics.agg_chunk_state_update_duration,
+        ] {
+            let histogram = histogram.with_label_values(&["123"]);
+            assert_eq!(histogram.get_sample_count(), NUM_CHUNKS);
+            assert!(histogram.get_sample_sum() > 0.0);
+        }
+    }
+
     async fn test_local_hash_aggregation_count<S: StateStore>(store: S) {
         let schema = Schema {
             fields: vec![Field::unnamed(DataType::Int64)],
@@ -720,6 +954,8 @@ mod tests {
             1 << 16,
             1 << 10,
             1,
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
         );
         let mut hash_agg = hash_agg.execute();
 
@@ -822,6 +1058,8 @@ mod tests {
             1 << 16,
             1 << 10,
             1,
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
         );
         let mut hash_agg = hash_agg.execute();
 
@@ -916,6 +1154,8 @@ mod tests {
             1 << 16,
             1 << 10,
             1,
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
         );
         let mut hash_agg = hash_agg.execute();
 
@@ -1015,6 +1255,8 @@ mod tests {
             1 << 16,
             1 << 10,
             1,
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
         );
         let mut hash_agg = hash_agg.execute();
 
@@ -1052,6 +1294,100 @@ mod tests {
         );
     }
 
+    /// Aggregates 100 groups, then rebuilds the executor on the same store with `state_prefetch`,
+    /// as if it were recovered on another node, and updates all the groups again. Returns the
+    /// number of cache misses of the rebuilt executor.
+    async fn test_hash_agg_state_prefetch<S: StateStore>(
+        store: S,
+        state_prefetch: StatePrefetchConfig,
+    ) -> u64 {
+        const NUM_GROUPS: i64 = 100;
+        let schema = Schema {
+            fields: vec![Field::unnamed(DataType::Int64)],
+        };
+        let chunk = || {
+            let rows = (0..NUM_GROUPS)
+                .map(|i| (Op::Insert, Row(vec![Some(i.into())])))
+                .collect_vec();
+            StreamChunk::from_rows(&rows, &[DataType::Int64])
+        };
+        let agg_calls = vec![AggCall {
+            kind: AggKind::Count,
+            args: AggArgs::None,
+            return_type: DataType::Int64,
+            order_pairs: vec![],
+            append_only: false,
+            filter: None,
+        }];
+
+        let (mut tx, source) = MockSource::channel(schema.clone(), PkIndices::new());
+        tx.push_barrier(1, false);
+        tx.push_chunk(chunk());
+        tx.push_barrier(2, false);
+        let hash_agg = new_boxed_hash_agg_executor(
+            store.clone(),
+            Box::new(source),
+            agg_calls.clone(),
+            vec![0],
+            vec![],
+            1 << 16,
+            1 << 10,
+            1,
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
+        );
+        let mut hash_agg = hash_agg.execute();
+        // Consume the init barrier, the stream chunk and the barrier.
+        for _ in 0..3 {
+            hash_agg.next().await.unwrap().unwrap();
+        }
+
+        let (mut tx, source) = MockSource::channel(schema, PkIndices::new());
+        tx.push_barrier(3, false);
+        tx.push_chunk(chunk());
+        tx.push_barrier(4, false);
+        let metrics = Arc::new(StreamingMetrics::unused());
+        let hash_agg = new_boxed_hash_agg_executor(
+            store,
+            Box::new(source),
+            agg_calls,
+            vec![0],
+            vec![],
+            1 << 16,
+            1 << 10,
+            1,
+            state_prefetch,
+            metrics.clone(),
+        );
+        let mut hash_agg = hash_agg.execute();
+
+        // Consume the init barrier
+        hash_agg.next().await.unwrap().unwrap();
+        // Every group is updated from the recovered state.
+        let msg = hash_agg.next().await.unwrap().unwrap();
+        let rows = msg.into_chunk().unwrap().sorted_rows();
+        assert_eq!(rows.len(), 2 * NUM_GROUPS as usize);
+        assert_eq!(
+            rows[0],
+            (
+                Op::UpdateDelete,
+                Row(vec![Some(0i64.into()), Some(1i64.into())])
+            )
+        );
+        assert_eq!(
+            rows[NUM_GROUPS as usize],
+            (
+                Op::UpdateInsert,
+                Row(vec![Some(0i64.into()), Some(2i64.into())])
+            )
+        );
+
+        metrics
+            .agg_lookup_miss_count
+            .with_label_values(&["123"])
+            .get()
+    }
+
     trait SortedRows {
         fn sorted_rows(self) -> Vec<(Op, Row)>;
     }
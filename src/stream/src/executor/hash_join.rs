@@ -20,7 +20,7 @@ use fixedbitset::FixedBitSet;
 use futures::{pin_mut, StreamExt};
 use futures_async_stream::try_stream;
 use itertools::Itertools;
-use risingwave_common::array::{Op, Row, RowRef, StreamChunk};
+use risingwave_common::array::{HotKeyMark, Op, Row, RowRef, StreamChunk};
 use risingwave_common::bail;
 use risingwave_common::catalog::Schema;
 use risingwave_common::hash::HashKey;
@@ -39,7 +39,9 @@ use super::{
     ActorContextRef, BoxedExecutor, BoxedMessageStream, Executor, Message, PkIndices, PkIndicesRef,
 };
 use crate::cache::LruManagerRef;
-use crate::common::{InfallibleExpression, StreamChunkBuilder};
+use crate::common::{
+    InfallibleExpression, StatePrefetchConfig, StatePrefetchHandle, StreamChunkBuilder,
+};
 use crate::executor::expect_first_barrier_from_aligned_stream;
 use crate::executor::JoinType::LeftAnti;
 
@@ -176,6 +178,8 @@ struct JoinSide<K: HashKey, S: StateStore> {
     start_pos: usize,
     /// The mapping from input indices of a side to output columes.
     i2o_mapping: Vec<(usize, usize)>,
+    /// The rows of hot join keys kept in memory.
+    hot: HotKeyState<K>,
 }
 
 impl<K: HashKey, S: StateStore> std::fmt::Debug for JoinSide<K, S> {
@@ -247,6 +251,8 @@ pub struct HashJoinExecutor<K: HashKey, S: StateStore, const T: JoinTypePrimitiv
     metrics: Arc<StreamingMetrics>,
     /// The maximum size of the chunk produced by executor at a time
     chunk_size: usize,
+    /// Limits of prefetching the join keys into the caches when the executor is built.
+    state_prefetch: StatePrefetchConfig,
 }
 
 impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> std::fmt::Debug
@@ -442,6 +448,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
         is_append_only: bool,
         metrics: Arc<StreamingMetrics>,
         chunk_size: usize,
+        state_prefetch: StatePrefetchConfig,
     ) -> Self {
         let side_l_column_n = input_l.schema().len();
 
@@ -580,6 +587,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                 pk_indices: state_pk_indices_l,
                 start_pos: 0,
                 i2o_mapping: left_to_output,
+                hot: HotKeyState::default(),
             },
             side_r: JoinSide {
                 ht: JoinHashMap::new(
@@ -603,6 +611,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                 pk_indices: state_pk_indices_r,
                 start_pos: side_l_column_n,
                 i2o_mapping: right_to_output,
+                hot: HotKeyState::default(),
             },
             pk_indices,
             cond,
@@ -611,6 +620,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
             append_only_optimize,
             metrics,
             chunk_size,
+            state_prefetch,
         }
     }
 
@@ -630,6 +640,15 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
         self.side_l.init(barrier.epoch);
         self.side_r.init(barrier.epoch);
 
+        // Prefetch the state of both sides in the background, without holding back the barriers.
+        let [mut state_prefetch_l, mut state_prefetch_r] =
+            [&self.side_l, &self.side_r].map(|side| {
+                self.state_prefetch.is_enabled().then(|| {
+                    side.ht
+                        .spawn_prefetch(&self.state_prefetch, self.metrics.clone(), self.ctx.id)
+                })
+            });
+
         // The first barrier message should be propagated.
         yield Message::Barrier(barrier);
         let actor_id_str = self.ctx.id.to_string();
@@ -644,7 +663,21 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                 .join_actor_input_waiting_duration_ns
                 .with_label_values(&[&actor_id_str])
                 .inc_by(start_time.elapsed().as_nanos() as u64);
-            match msg? {
+            let msg = msg?;
+            // Put the prefetched entries into the caches once they are read. Chunks access the
+            // caches, so they wait for the prefetch.
+            let wait = !matches!(msg, AlignedMessage::Barrier(_));
+            for (side, state_prefetch) in [
+                (&mut self.side_l, &mut state_prefetch_l),
+                (&mut self.side_r, &mut state_prefetch_r),
+            ] {
+                if let Some(entries) =
+                    StatePrefetchHandle::take_finished(state_prefetch, wait).await
+                {
+                    side.ht.put_prefetched(entries?)?;
+                }
+            }
+            match msg {
                 AlignedMessage::Left(chunk) => {
                     #[for_await]
                     for chunk in Self::eq_join_oneside::<{ SideType::Left }>(
@@ -659,9 +692,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                         self.chunk_size,
                     ) {
                         yield chunk.map(|v| match v {
-                            Message::Watermark(_) => {
-                                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                            }
+                            watermark @ Message::Watermark(_) => watermark,
                             Message::Chunk(chunk) => Message::Chunk(chunk),
                             barrier @ Message::Barrier(_) => barrier,
                         })?;
@@ -681,9 +712,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                         self.chunk_size,
                     ) {
                         yield chunk.map(|v| match v {
-                            Message::Watermark(_) => {
-                                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                            }
+                            watermark @ Message::Watermark(_) => watermark,
                             Message::Chunk(chunk) => Message::Chunk(chunk),
                             barrier @ Message::Barrier(_) => barrier,
                         })?;
@@ -696,6 +725,16 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                     if let Some(vnode_bitmap) = barrier.as_update_vnode_bitmap(self.ctx.id) {
                         self.side_l.ht.update_vnode_bitmap(vnode_bitmap.clone());
                         self.side_r.ht.update_vnode_bitmap(vnode_bitmap);
+                        // The entries being prefetched may be of the vnodes no longer owned.
+                        state_prefetch_l = None;
+                        state_prefetch_r = None;
+                    }
+
+                    // The hash dispatchers may route the rows of hot keys to other actors from
+                    // now on, so drop the copies and let the owners join all the existing rows.
+                    if barrier.is_update() {
+                        self.side_l.hot.clear();
+                        self.side_r.hot.clear();
                     }
 
                     // Update epoch for managed cache.
@@ -785,6 +824,7 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
         append_only_optimize: bool,
         chunk_size: usize,
     ) {
+        let hot_key_mark = chunk.hot_key_mark();
         let chunk = chunk.compact();
 
         let (side_update, side_match) = if SIDE == SideType::Left {
@@ -824,9 +864,89 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                 Ok(cond_match)
             };
 
+        if hot_key_mark != HotKeyMark::Normal && T != JoinType::Inner {
+            bail!("hot key marks are only supported by inner hash joins");
+        }
+
         let keys = K::build(&side_update.join_key_indices, chunk.data_chunk())?;
+
+        // The copies of the rows of hot keys owned by other actors are only joined with the
+        // copies in memory.
+        if !hot_key_mark.is_owner() {
+            for ((op, row), key) in chunk.rows().zip_eq(keys.iter()) {
+                if !key.null_bitmap().is_subset(side_match.ht.null_matched()) {
+                    continue;
+                }
+                let value = row.to_owned_row();
+                match op {
+                    Op::Insert | Op::UpdateInsert => {
+                        for (matched_mark, matched_row) in side_match.hot.copies(key) {
+                            if is_copy_pair(hot_key_mark, *matched_mark)
+                                && check_join_condition(&row, matched_row)?
+                            {
+                                if let Some(chunk) = hashjoin_chunk_builder.with_match_on_insert(
+                                    &row,
+                                    &JoinRow::new(matched_row.clone(), 0),
+                                )? {
+                                    yield Message::Chunk(chunk);
+                                }
+                            }
+                        }
+                        side_update.hot.insert_copy(key, hot_key_mark, value);
+                    }
+                    Op::Delete | Op::UpdateDelete => {
+                        if !side_update.hot.delete_copy(key, &value) {
+                            continue;
+                        }
+                        for (matched_mark, matched_row) in side_match.hot.copies(key) {
+                            if is_copy_pair(hot_key_mark, *matched_mark)
+                                && check_join_condition(&row, matched_row)?
+                            {
+                                if let Some(chunk) = hashjoin_chunk_builder.with_match_on_delete(
+                                    &row,
+                                    &JoinRow::new(matched_row.clone(), 0),
+                                )? {
+                                    yield Message::Chunk(chunk);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(chunk) = hashjoin_chunk_builder.take()? {
+                yield Message::Chunk(chunk);
+            }
+            return Ok(());
+        }
+
         for ((op, row), key) in chunk.rows().zip_eq(keys.iter()) {
             let value = row.to_owned_row();
+            // The mark the row was inserted with. Pairs of rows inserted as the hot key rows of
+            // both sides are joined by the actors holding their copies.
+            let row_mark = match op {
+                Op::Insert | Op::UpdateInsert => {
+                    if hot_key_mark != HotKeyMark::Normal {
+                        side_update.hot.insert_owner_mark(
+                            value.by_indices(&side_update.pk_indices),
+                            hot_key_mark,
+                        );
+                    }
+                    hot_key_mark
+                }
+                Op::Delete | Op::UpdateDelete if side_update.hot.has_owner_marks() => side_update
+                    .hot
+                    .remove_owner_mark(&value.by_indices(&side_update.pk_indices)),
+                Op::Delete | Op::UpdateDelete => HotKeyMark::Normal,
+            };
+            let joined_elsewhere = |matched_row: &Row| {
+                row_mark != HotKeyMark::Normal
+                    && joined_by_copies(
+                        row_mark,
+                        side_match
+                            .hot
+                            .owner_mark(&matched_row.by_indices(&side_match.pk_indices)),
+                    )
+            };
             let matched_rows: Option<HashValueType> =
                 Self::hash_eq_match(key, &mut side_match.ht).await?;
             match op {
@@ -838,6 +958,9 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                             matched_rows.values_mut(&side_match.all_data_types)
                         {
                             let mut matched_row = matched_row?;
+                            if joined_elsewhere(&matched_row.row) {
+                                continue;
+                            }
                             if check_join_condition(&row, &matched_row.row)? {
                                 degree += 1;
                                 if !forward_exactly_once(T, SIDE) {
@@ -896,6 +1019,9 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
                             matched_rows.values_mut(&side_match.all_data_types)
                         {
                             let mut matched_row = matched_row?;
+                            if joined_elsewhere(&matched_row.row) {
+                                continue;
+                            }
                             if check_join_condition(&row, &matched_row.row)? {
                                 degree += 1;
                                 if need_update_side_matched_degree(T, SIDE) {
@@ -945,6 +1071,8 @@ impl<K: HashKey, S: StateStore, const T: JoinTypePrimitive> HashJoinExecutor<K,
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use risingwave_common::array::stream_chunk::StreamChunkTestExt;
     use risingwave_common::array::*;
     use risingwave_common::catalog::{ColumnDesc, ColumnId, Field, Schema, TableId};
@@ -1016,6 +1144,22 @@ mod tests {
     fn create_executor<const T: JoinTypePrimitive>(
         with_condition: bool,
         null_safe: bool,
+    ) -> (MessageSender, MessageSender, BoxedMessageStream) {
+        create_executor_on_store::<T>(
+            with_condition,
+            null_safe,
+            MemoryStateStore::new(),
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
+        )
+    }
+
+    fn create_executor_on_store<const T: JoinTypePrimitive>(
+        with_condition: bool,
+        null_safe: bool,
+        mem_state: MemoryStateStore,
+        state_prefetch: StatePrefetchConfig,
+        metrics: Arc<StreamingMetrics>,
     ) -> (MessageSender, MessageSender, BoxedMessageStream) {
         let schema = Schema {
             fields: vec![
@@ -1029,8 +1173,6 @@ mod tests {
         let params_r = JoinParams::new(vec![0], vec![]);
         let cond = with_condition.then(create_cond);
 
-        let mem_state = MemoryStateStore::new();
-
         let (state_l, degree_state_l) = create_in_memory_state_table(
             mem_state.clone(),
             &[DataType::Int64, DataType::Int64],
@@ -1070,8 +1212,9 @@ mod tests {
             degree_state_r,
             None,
             false,
-            Arc::new(StreamingMetrics::unused()),
+            metrics,
             1024,
+            state_prefetch,
         );
         (tx_l, tx_r, Box::new(executor).execute())
     }
@@ -1143,6 +1286,7 @@ mod tests {
             true,
             Arc::new(StreamingMetrics::unused()),
             1024,
+            StatePrefetchConfig::disabled(),
         );
         (tx_l, tx_r, Box::new(executor).execute())
     }
@@ -2321,6 +2465,118 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_streaming_hash_join_state_prefetch() {
+        // Without prefetch, every probed key misses the cache after recovery.
+        let (chunks, lookup_miss_count_l, lookup_miss_count_r) =
+            test_hash_join_state_prefetch(StatePrefetchConfig::disabled()).await;
+        assert_eq!((lookup_miss_count_l, lookup_miss_count_r), (3, 2));
+
+        // With prefetch, every probed key is already in the cache, and the join results, which
+        // depend on the degrees of the left side, are the same.
+        let state_prefetch = StatePrefetchConfig {
+            max_bytes: 1 << 20,
+            deadline: Duration::from_secs(60),
+        };
+        let (prefetched_chunks, lookup_miss_count_l, lookup_miss_count_r) =
+            test_hash_join_state_prefetch(state_prefetch).await;
+        assert_eq!((lookup_miss_count_l, lookup_miss_count_r), (0, 0));
+        assert_eq!(prefetched_chunks, chunks);
+    }
+
+    /// Runs a left outer join, then rebuilds the executor on the same store with
+    /// `state_prefetch`, as if it were recovered on another node, and probes both sides. Returns
+    /// the output chunks and the number of cache misses of each side of the rebuilt executor.
+    async fn test_hash_join_state_prefetch(
+        state_prefetch: StatePrefetchConfig,
+    ) -> (Vec<StreamChunk>, u64, u64) {
+        let mem_state = MemoryStateStore::new();
+        let (mut tx_l, mut tx_r, mut hash_join) = create_executor_on_store::<{ JoinType::LeftOuter }>(
+            false,
+            false,
+            mem_state.clone(),
+            StatePrefetchConfig::disabled(),
+            Arc::new(StreamingMetrics::unused()),
+        );
+        tx_l.push_barrier(1, false);
+        tx_r.push_barrier(1, false);
+        tx_l.push_chunk(StreamChunk::from_pretty(
+            "  I I
+             + 1 4
+             + 2 5
+             + 3 6",
+        ));
+        tx_r.push_chunk(StreamChunk::from_pretty(
+            "  I I
+             + 2 7
+             + 4 8",
+        ));
+        tx_l.push_barrier(2, false);
+        tx_r.push_barrier(2, false);
+        while let Some(msg) = hash_join.next().await {
+            if let Message::Barrier(barrier) = msg.unwrap() && barrier.epoch.curr == 2 {
+                break;
+            }
+        }
+
+        let metrics = Arc::new(StreamingMetrics::unused());
+        let (mut tx_l, mut tx_r, mut hash_join) = create_executor_on_store::<{ JoinType::LeftOuter }>(
+            false,
+            false,
+            mem_state,
+            state_prefetch,
+            metrics.clone(),
+        );
+        tx_l.push_barrier(3, false);
+        tx_r.push_barrier(3, false);
+        hash_join.next().await.unwrap().unwrap();
+
+        // Probe the left side, including a key that was not matched before.
+        tx_r.push_chunk(StreamChunk::from_pretty(
+            "  I  I
+             + 1  9
+             + 2 10
+             + 3 11",
+        ));
+        let mut chunks = vec![hash_join
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_chunk()
+            .unwrap()];
+        // Probe the right side.
+        tx_l.push_chunk(StreamChunk::from_pretty(
+            "  I  I
+             + 2 12
+             + 4 13",
+        ));
+        chunks.push(
+            hash_join
+                .next()
+                .await
+                .unwrap()
+                .unwrap()
+                .into_chunk()
+                .unwrap(),
+        );
+        tx_l.push_barrier(4, false);
+        tx_r.push_barrier(4, false);
+        hash_join.next().await.unwrap().unwrap();
+
+        let lookup_miss_count = |side| {
+            metrics
+                .join_lookup_miss_count
+                .with_label_values(&["123", side])
+                .get()
+        };
+        (
+            chunks,
+            lookup_miss_count("left"),
+            lookup_miss_count("right"),
+        )
+    }
+
     #[tokio::test]
     async fn test_streaming_hash_left_join() {
         let chunk_l1 = StreamChunk::from_pretty(
@@ -2967,4 +3223,85 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn test_streaming_hash_inner_join_hot_key() {
+        // The owner of the key 1 and another actor, fed as a hash dispatcher with hot key modes
+        // would after the key becomes hot, and a plain join as the reference.
+        let (mut tx_l_owner, mut tx_r_owner, mut owner) =
+            create_executor::<{ JoinType::Inner }>(false, false);
+        let (mut tx_l_other, mut tx_r_other, mut other) =
+            create_executor::<{ JoinType::Inner }>(false, false);
+        let (mut tx_l, mut tx_r, mut reference) =
+            create_executor::<{ JoinType::Inner }>(false, false);
+
+        async fn next_rows(hash_join: &mut BoxedMessageStream) -> Vec<(Op, Row)> {
+            let chunk = hash_join
+                .next()
+                .await
+                .unwrap()
+                .unwrap()
+                .into_chunk()
+                .unwrap();
+            chunk
+                .rows()
+                .map(|(op, row)| (op, row.to_owned_row()))
+                .collect()
+        }
+
+        for tx in [
+            &mut tx_l_owner,
+            &mut tx_r_owner,
+            &mut tx_l_other,
+            &mut tx_r_other,
+            &mut tx_l,
+            &mut tx_r,
+        ] {
+            tx.push_barrier(1, false);
+        }
+        for hash_join in [&mut owner, &mut other, &mut reference] {
+            hash_join.next().await.unwrap().unwrap();
+        }
+
+        // Rows inserted before the key becomes hot are only sent to the owner.
+        tx_l_owner.push_chunk(StreamChunk::from_pretty(" I I\n + 1 1"));
+        tx_l.push_chunk(StreamChunk::from_pretty(" I I\n + 1 1"));
+        assert_eq!(next_rows(&mut owner).await, next_rows(&mut reference).await);
+        tx_r_owner.push_chunk(StreamChunk::from_pretty(" I I\n + 1 10"));
+        tx_r.push_chunk(StreamChunk::from_pretty(" I I\n + 1 10"));
+        assert_eq!(next_rows(&mut owner).await, next_rows(&mut reference).await);
+
+        // Then the right side broadcasts the rows of the key, and the left side spreads them.
+        let steps = [
+            (false, " I I\n + 1 11"),
+            (true, " I I\n + 1 2"),
+            (true, " I I\n + 1 3"),
+            (true, " I I\n - 1 1"),
+            (false, " I I\n - 1 11"),
+            (true, " I I\n - 1 2"),
+        ];
+        let mut rows_of_other = 0;
+        for (is_left, chunk) in steps {
+            let chunk = StreamChunk::from_pretty(chunk);
+            if is_left {
+                tx_l_owner.push_chunk(chunk.clone().with_hot_key_mark(HotKeyMark::SpreadOwner));
+                tx_l_other.push_chunk(chunk.clone().with_hot_key_mark(HotKeyMark::SpreadShare));
+                tx_l.push_chunk(chunk);
+            } else {
+                tx_r_owner.push_chunk(chunk.clone().with_hot_key_mark(HotKeyMark::BroadcastOwner));
+                tx_r_other.push_chunk(chunk.clone().with_hot_key_mark(HotKeyMark::Replica));
+                tx_r.push_chunk(chunk);
+            }
+            let rows_from_other = next_rows(&mut other).await;
+            rows_of_other += rows_from_other.len();
+            let mut rows = next_rows(&mut owner).await;
+            rows.extend(rows_from_other);
+            rows.sort();
+            let mut expected = next_rows(&mut reference).await;
+            expected.sort();
+            assert_eq!(rows, expected);
+        }
+        // The pairs of rows inserted after the key became hot are joined by the other actor.
+        assert_eq!(rows_of_other, 4);
+    }
 }
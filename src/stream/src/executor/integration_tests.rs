@@ -121,6 +121,7 @@ async fn test_merger_sum_aggr() {
             inputs, 0,
         ))],
         0,
+        0,
         ctx,
         metrics,
     );
@@ -246,9 +247,7 @@ impl StreamConsumer for MockConsumer {
         async move {
             while let Some(item) = input.next().await {
                 match item? {
-                    Message::Watermark(_) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
+                    Message::Watermark(_) => {}
                     Message::Chunk(chunk) => data.lock().unwrap().push(chunk),
                     Message::Barrier(barrier) => yield barrier,
                 }
@@ -327,7 +327,6 @@ impl<S: StateStore> LookupExecutor<S> {
     }
 
     /// Store the barrier.
-    #[expect(clippy::unused_async)]
     async fn process_barrier(&mut self, barrier: Barrier) -> StreamExecutorResult<()> {
         if self.last_barrier.is_none() {
             assert_ne!(barrier.epoch.prev, 0, "lookup requires prev epoch != 0");
@@ -361,6 +360,14 @@ impl<S: StateStore> LookupExecutor<S> {
             // the lookup executor
             // TODO(st1page): maybe we should not use state table here.
 
+            // The arrangement is flushed to the state store in the background after the barrier
+            // is forwarded, so wait for it before looking up the data of the epoch.
+            self.arrangement
+                .state_table
+                .flush_tracker()
+                .wait_epoch(barrier.epoch.prev)
+                .await?;
+
             self.arrangement
                 .state_table
                 .commit_no_data_expected(barrier.epoch);
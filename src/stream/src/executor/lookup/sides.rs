@@ -97,9 +97,7 @@ pub async fn poll_until_barrier(stream: impl MessageStream, expected_barrier: Ba
     #[for_await]
     for item in stream {
         match item? {
-            Message::Watermark(_) => {
-                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-            }
+            Message::Watermark(_) => {}
             c @ Message::Chunk(_) => yield c,
             Message::Barrier(b) => {
                 if b.epoch != expected_barrier.epoch {
@@ -152,12 +150,8 @@ pub async fn align_barrier(left: impl MessageStream, right: impl MessageStream)
                     yield Either::Right(Message::Barrier(b.clone()));
                     break 'inner (SideStatus::RightBarrier, b);
                 }
-                Some(Either::Right(Ok(Message::Watermark(_)))) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
-                Some(Either::Left(Ok(Message::Watermark(_)))) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Some(Either::Right(Ok(Message::Watermark(_)))) => {}
+                Some(Either::Left(Ok(Message::Watermark(_)))) => {}
                 Some(Either::Left(Err(e))) | Some(Either::Right(Err(e))) => return Err(e),
                 None => {
                     break 'outer;
@@ -234,12 +228,8 @@ pub async fn stream_lookup_arrange_prev_epoch(
                         break;
                     }
                 }
-                Either::Left(Message::Watermark(_)) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
-                Either::Right(Message::Watermark(_)) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Either::Left(Message::Watermark(_)) => {}
+                Either::Right(Message::Watermark(_)) => {}
             }
         }
 
@@ -249,9 +239,7 @@ pub async fn stream_lookup_arrange_prev_epoch(
                 .await
                 .expect("unexpected close of barrier aligner")?
             {
-                Either::Left(Message::Watermark(_)) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Either::Left(Message::Watermark(_)) => {}
                 Either::Left(Message::Chunk(msg)) => yield ArrangeMessage::Stream(msg),
                 Either::Left(Message::Barrier(b)) => {
                     yield ArrangeMessage::Barrier(b);
@@ -318,12 +306,8 @@ pub async fn stream_lookup_arrange_this_epoch(
                     }
                     break 'inner Status::ArrangeReady;
                 }
-                Either::Left(Message::Watermark(_)) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
-                Either::Right(Message::Watermark(_)) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Either::Left(Message::Watermark(_)) => {}
+                Either::Right(Message::Watermark(_)) => {}
             }
         };
         match status {
@@ -340,12 +324,8 @@ pub async fn stream_lookup_arrange_this_epoch(
                         yield ArrangeMessage::Barrier(b);
                         break;
                     }
-                    Either::Left(Message::Watermark(_)) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
-                    Either::Right(Message::Watermark(_)) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
+                    Either::Left(Message::Watermark(_)) => {}
+                    Either::Right(Message::Watermark(_)) => {}
                     Either::Right(_) => unreachable!(),
                 }
             },
@@ -372,9 +352,7 @@ pub async fn stream_lookup_arrange_this_epoch(
                         yield ArrangeMessage::Barrier(stream_barrier);
                         break;
                     }
-                    Either::Right(Message::Watermark(_)) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
+                    Either::Right(Message::Watermark(_)) => {}
                 }
             },
         }
@@ -112,9 +112,7 @@ impl LookupUnionExecutor {
                     };
                     end = false;
                     match msg {
-                        Message::Watermark(_) => {
-                            todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                        }
+                        Message::Watermark(_) => {}
 
                         msg @ Message::Chunk(_) => yield msg,
                         Message::Barrier(barrier) => {
@@ -18,7 +18,9 @@ use std::ops::Bound::{self, *};
 use std::ops::RangeBounds;
 
 use anyhow::anyhow;
-use risingwave_common::array::Row;
+use futures::{pin_mut, StreamExt};
+use itertools::Itertools;
+use risingwave_common::array::{Row, RowDeserializer};
 use risingwave_common::row::CompactedRow;
 use risingwave_common::types::ScalarImpl;
 use risingwave_common::util::epoch::EpochPair;
@@ -65,8 +67,22 @@ impl<S: StateStore> RangeCache<S> {
         }
     }
 
-    pub fn init(&mut self, epoch: EpochPair) {
+    /// Initialize the epoch of the `StateTable`, and load the rows stored in it into the cache,
+    /// keyed by the first column of the primary key.
+    pub async fn init(&mut self, epoch: EpochPair) -> StreamExecutorResult<()> {
         self.state_table.init_epoch(epoch);
+        let key_idx = self.state_table.pk_indices()[0];
+        let iter = self.state_table.iter().await?;
+        pin_mut!(iter);
+        while let Some(row) = iter.next().await.transpose()? {
+            if let Some(k) = row[key_idx].clone() {
+                self.cache
+                    .entry(k)
+                    .or_insert_with(HashSet::new)
+                    .insert(row.as_ref().into());
+            }
+        }
+        Ok(())
     }
 
     /// Insert a row and corresponding scalar value key into cache (if within range) and
@@ -144,6 +160,27 @@ impl<S: StateStore> RangeCache<S> {
         self.cache.range(range)
     }
 
+    /// Delete the rows of the keys in `range` from cache and `StateTable`, which are the rows that
+    /// will never satisfy the predicate again.
+    pub fn delete_range(
+        &mut self,
+        range: (Bound<ScalarImpl>, Bound<ScalarImpl>),
+        row_deserializer: &RowDeserializer,
+    ) -> StreamExecutorResult<()> {
+        let keys = self
+            .cache
+            .range(range)
+            .map(|(k, _)| k.clone())
+            .collect_vec();
+        for k in keys {
+            for row in self.cache.remove(&k).unwrap() {
+                self.state_table
+                    .delete(row_deserializer.deserialize(row.row.as_ref())?);
+            }
+        }
+        Ok(())
+    }
+
     /// Flush writes to the `StateTable` from the in-memory buffer.
     pub async fn flush(&mut self, epoch: EpochPair) -> StreamExecutorResult<()> {
         // self.metrics.flush();
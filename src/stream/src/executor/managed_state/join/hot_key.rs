@@ -0,0 +1,133 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use risingwave_common::array::{HotKeyMark, Row};
+use risingwave_common::hash::HashKey;
+
+/// The rows of hot join keys that one side of a hash join keeps in memory, besides its state
+/// table.
+///
+/// A hash dispatcher with a hot key mode sends the rows of a hot key to the actor owning their
+/// vnode, and copies them to other actors: the build side broadcasts the copies, and the probe
+/// side spreads them. A pair of rows of a hot key is joined
+/// - by the actor holding both copies, if both rows were inserted as hot key rows, i.e. marked
+///   `BroadcastOwner` and `SpreadOwner` respectively at the owner.
+/// - by the owner otherwise, as the other actors don't have the rows inserted before the key became
+///   hot.
+///
+/// Retractions are joined by where the rows were inserted, so the owner looks up the marks of the
+/// deleted rows, and other actors ignore the retractions of the copies they don't hold.
+///
+/// The marks and the copies are only in memory, and are dropped on recovery and on
+/// [`HotKeyState::clear`], after which all pairs are joined by the owner with its state table.
+/// They are bounded per key, as each upstream hash dispatcher routes at most `HOT_KEY_MAX_ROWS`
+/// inserts of a hot key as hot key rows, and the further ones as normal rows joined by the owner.
+pub struct HotKeyState<K: HashKey> {
+    /// The marks of the persisted rows inserted as hot key rows, by their primary keys.
+    owner_marks: HashMap<Row, HotKeyMark>,
+    /// The copies of the rows of hot keys owned by other actors.
+    copies: HashMap<K, Vec<(HotKeyMark, Row)>>,
+}
+
+impl<K: HashKey> Default for HotKeyState<K> {
+    fn default() -> Self {
+        Self {
+            owner_marks: HashMap::new(),
+            copies: HashMap::new(),
+        }
+    }
+}
+
+impl<K: HashKey> HotKeyState<K> {
+    /// The mark a persisted row was inserted with.
+    pub fn owner_mark(&self, pk: &Row) -> HotKeyMark {
+        if self.owner_marks.is_empty() {
+            return HotKeyMark::Normal;
+        }
+        self.owner_marks
+            .get(pk)
+            .copied()
+            .unwrap_or(HotKeyMark::Normal)
+    }
+
+    pub fn insert_owner_mark(&mut self, pk: Row, mark: HotKeyMark) {
+        if mark != HotKeyMark::Normal {
+            self.owner_marks.insert(pk, mark);
+        }
+    }
+
+    /// Remove the mark of a deleted row, and return the mark it was inserted with.
+    pub fn remove_owner_mark(&mut self, pk: &Row) -> HotKeyMark {
+        if self.owner_marks.is_empty() {
+            return HotKeyMark::Normal;
+        }
+        self.owner_marks.remove(pk).unwrap_or(HotKeyMark::Normal)
+    }
+
+    pub fn has_owner_marks(&self) -> bool {
+        !self.owner_marks.is_empty()
+    }
+
+    pub fn copies(&self, key: &K) -> &[(HotKeyMark, Row)] {
+        self.copies.get(key).map_or(&[], |copies| copies.as_slice())
+    }
+
+    pub fn insert_copy(&mut self, key: &K, mark: HotKeyMark, row: Row) {
+        self.copies
+            .entry(key.clone())
+            .or_default()
+            .push((mark, row));
+    }
+
+    /// Remove a copy of a deleted row. Returns whether the copy is held by this actor.
+    pub fn delete_copy(&mut self, key: &K, row: &Row) -> bool {
+        let Some(copies) = self.copies.get_mut(key) else {
+            return false;
+        };
+        let Some(pos) = copies.iter().position(|(_, copy)| copy == row) else {
+            return false;
+        };
+        copies.swap_remove(pos);
+        if copies.is_empty() {
+            self.copies.remove(key);
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.owner_marks.clear();
+        self.copies.clear();
+    }
+}
+
+/// Whether a pair of persisted rows is joined by an actor holding their copies, instead of the
+/// owner.
+pub fn joined_by_copies(mark: HotKeyMark, matched_mark: HotKeyMark) -> bool {
+    matches!(
+        (mark, matched_mark),
+        (HotKeyMark::BroadcastOwner, HotKeyMark::SpreadOwner)
+            | (HotKeyMark::SpreadOwner, HotKeyMark::BroadcastOwner)
+    )
+}
+
+/// Whether a pair of copies is joined by the actor holding them.
+pub fn is_copy_pair(mark: HotKeyMark, matched_mark: HotKeyMark) -> bool {
+    matches!(
+        (mark, matched_mark),
+        (HotKeyMark::Replica, HotKeyMark::SpreadShare)
+            | (HotKeyMark::SpreadShare, HotKeyMark::Replica)
+    )
+}
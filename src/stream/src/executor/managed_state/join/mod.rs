@@ -12,20 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod hot_key;
 mod iter_utils;
 mod join_entry_state;
 
 use std::alloc::Global;
 use std::ops::{Deref, DerefMut, Index};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::Context;
 use fixedbitset::FixedBitSet;
 use futures::future::try_join;
 use futures_async_stream::for_await;
+pub use hot_key::{is_copy_pair, joined_by_copies, HotKeyState};
+use itertools::Itertools;
 pub(super) use join_entry_state::JoinEntryState;
 use local_stats_alloc::{SharedStatsAlloc, StatsAlloc};
-use risingwave_common::array::{Row, RowDeserializer};
+use risingwave_common::array::{DataChunk, Row, RowDeserializer};
 use risingwave_common::bail;
 use risingwave_common::buffer::Bitmap;
 use risingwave_common::collection::estimate_size::EstimateSize;
@@ -37,11 +41,13 @@ use risingwave_common::util::ordered::OrderedRowSerde;
 use risingwave_common::util::sort_util::OrderType;
 use risingwave_storage::table::streaming_table::state_table::StateTable;
 use risingwave_storage::StateStore;
+use tokio::time::timeout;
 
 use self::iter_utils::zip_by_order_key;
 use crate::cache::{
     cache_may_stale, EvictableHashMap, ExecutorCache, LruManagerRef, ManagedLruCache,
 };
+use crate::common::{prefetch_state_table, StatePrefetchConfig, StatePrefetchHandle};
 use crate::executor::error::StreamExecutorResult;
 use crate::executor::monitor::StreamingMetrics;
 use crate::task::ActorId;
@@ -248,6 +254,7 @@ pub struct JoinHashMap<K: HashKey, S: StateStore> {
     metrics: JoinHashMapMetrics,
 }
 
+#[derive(Clone)]
 struct TableInner<S: StateStore> {
     pk_indices: Vec<usize>,
     // This should be identical to the pk in state table.
@@ -408,71 +415,101 @@ impl<K: HashKey, S: StateStore> JoinHashMap<K, S> {
     /// Will return a empty `JoinEntryState` even when state does not exist in remote.
     async fn fetch_cached_state(&self, key: &K) -> StreamExecutorResult<JoinEntryState> {
         let key = key.clone().deserialize(&self.join_key_data_types)?;
-
-        let table_iter_fut = self.state.table.iter_key_and_val(&key);
-
-        let mut entry_state = JoinEntryState::default();
-
-        if self.need_degree_table {
-            let degree_table_iter_fut = self.degree_state.table.iter_key_and_val(&key);
-
-            let (table_iter, degree_table_iter) =
-                try_join(table_iter_fut, degree_table_iter_fut).await?;
-
-            // TODO(chi): fix this after Rust compiler bug is resolved
-            // https://github.com/risingwavelabs/risingwave/issues/5977
-            // Given that matched keys are generally small, we can safely fetch it all instead of
-            // making it a stream.
-
-            let mut table_data = vec![];
-            let mut degree_table_data = vec![];
-
-            #[for_await]
-            for x in table_iter {
-                table_data.push(x?);
+        fetch_entry_state(
+            &self.state,
+            &self.degree_state,
+            self.need_degree_table,
+            &self.pk_serializer,
+            &key,
+        )
+        .await
+    }
+
+    /// Prefetch the hottest join keys in the state table in the background, so that the first
+    /// chunks after recovery or migration do not miss the cache for every key. The join keys with
+    /// the most rows are taken as the hottest.
+    pub fn spawn_prefetch(
+        &self,
+        config: &StatePrefetchConfig,
+        metrics: Arc<StreamingMetrics>,
+        actor_id: ActorId,
+    ) -> StatePrefetchHandle<Vec<(Row, JoinEntryState)>> {
+        let state = self.state.clone();
+        let degree_state = self.degree_state.clone();
+        let need_degree_table = self.need_degree_table;
+        let pk_serializer = self.pk_serializer.clone();
+        let join_key_len = self.join_key_data_types.len();
+        let config = *config;
+        StatePrefetchHandle::spawn(async move {
+            let deadline = Instant::now() + config.deadline;
+            let groups = prefetch_state_table(
+                &state.table,
+                join_key_len,
+                |group| group.len() as u64,
+                &config,
+                &metrics,
+                actor_id,
+            )
+            .await?;
+
+            let mut entries = Vec::with_capacity(groups.len());
+            for group in groups {
+                let join_key = Row(group[0].0 .0[..join_key_len].to_vec());
+                let entry_state = if need_degree_table {
+                    // The degrees are in the degree table, so read the entry as on a cache miss.
+                    match timeout(
+                        deadline.saturating_duration_since(Instant::now()),
+                        fetch_entry_state(
+                            &state,
+                            &degree_state,
+                            need_degree_table,
+                            &pk_serializer,
+                            &join_key,
+                        ),
+                    )
+                    .await
+                    {
+                        Ok(entry_state) => entry_state?,
+                        Err(_) => break,
+                    }
+                } else {
+                    let mut entry_state = JoinEntryState::default();
+                    for (_, row) in group {
+                        let pk =
+                            row.extract_memcomparable_by_indices(&pk_serializer, &state.pk_indices);
+                        entry_state.insert(pk, JoinRow::new(row, 0).encode());
+                    }
+                    entry_state
+                };
+                entries.push((join_key, entry_state));
             }
+            Ok(entries)
+        })
+    }
 
-            #[for_await]
-            for x in degree_table_iter {
-                degree_table_data.push(x?);
-            }
+    /// Put the prefetched join entries into the cache. This must be called before the cache is
+    /// accessed, as the prefetched entries are read from the state at the first barrier.
+    pub fn put_prefetched(
+        &mut self,
+        entries: Vec<(Row, JoinEntryState)>,
+    ) -> StreamExecutorResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
 
-            // We need this because ttl may remove some entries from table but leave the entries
-            // with the same stream key in degree table.
-            let zipped_iter = zip_by_order_key(
-                futures::stream::iter(table_data.into_iter()),
-                futures::stream::iter(degree_table_data.into_iter()),
-            );
+        // Put the coldest entries first, so that the hottest ones are evicted last.
+        let (join_keys, entry_states): (Vec<_>, Vec<_>) = entries.into_iter().rev().unzip();
+        let join_key_chunk = DataChunk::from_rows(&join_keys, &self.join_key_data_types);
+        let key_indices = (0..self.join_key_data_types.len()).collect_vec();
+        let keys = K::build(&key_indices, &join_key_chunk)?;
+        for (key, entry_state) in keys.into_iter().zip_eq(entry_states) {
+            self.inner.put(key, entry_state);
+        }
 
-            #[for_await]
-            for row_and_degree in zipped_iter {
-                let (row, degree) = row_and_degree?;
-                let pk = row
-                    .extract_memcomparable_by_indices(&self.pk_serializer, &self.state.pk_indices);
-                let degree_i64 = degree
-                    .0
-                    .last()
-                    .cloned()
-                    .context("Empty row")?
-                    .context("Fail to fetch a degree")?;
-                entry_state.insert(
-                    pk,
-                    JoinRow::new(row.into_owned(), *degree_i64.as_int64() as u64).encode(),
-                );
-            }
-        } else {
-            let table_iter = table_iter_fut.await?;
-
-            #[for_await]
-            for row in table_iter {
-                let row = row?.1;
-                let pk = row
-                    .extract_memcomparable_by_indices(&self.pk_serializer, &self.state.pk_indices);
-                entry_state.insert(pk, JoinRow::new(row.into_owned(), 0).encode());
-            }
-        };
+        // Evict cache to target capacity, in case the prefetch budget exceeds it.
+        self.inner.evict();
 
-        Ok(entry_state)
+        Ok(())
     }
 
     pub async fn flush(&mut self, epoch: EpochPair) -> StreamExecutorResult<()> {
@@ -589,6 +626,78 @@ impl<K: HashKey, S: StateStore> JoinHashMap<K, S> {
     }
 }
 
+/// Read the entry of the join `key` from the state table and, if needed, the degree table.
+async fn fetch_entry_state<S: StateStore>(
+    state: &TableInner<S>,
+    degree_state: &TableInner<S>,
+    need_degree_table: bool,
+    pk_serializer: &OrderedRowSerde,
+    key: &Row,
+) -> StreamExecutorResult<JoinEntryState> {
+    let table_iter_fut = state.table.iter_key_and_val(key);
+
+    let mut entry_state = JoinEntryState::default();
+
+    if need_degree_table {
+        let degree_table_iter_fut = degree_state.table.iter_key_and_val(key);
+
+        let (table_iter, degree_table_iter) =
+            try_join(table_iter_fut, degree_table_iter_fut).await?;
+
+        // TODO(chi): fix this after Rust compiler bug is resolved
+        // https://github.com/risingwavelabs/risingwave/issues/5977
+        // Given that matched keys are generally small, we can safely fetch it all instead of
+        // making it a stream.
+
+        let mut table_data = vec![];
+        let mut degree_table_data = vec![];
+
+        #[for_await]
+        for x in table_iter {
+            table_data.push(x?);
+        }
+
+        #[for_await]
+        for x in degree_table_iter {
+            degree_table_data.push(x?);
+        }
+
+        // We need this because ttl may remove some entries from table but leave the entries
+        // with the same stream key in degree table.
+        let zipped_iter = zip_by_order_key(
+            futures::stream::iter(table_data.into_iter()),
+            futures::stream::iter(degree_table_data.into_iter()),
+        );
+
+        #[for_await]
+        for row_and_degree in zipped_iter {
+            let (row, degree) = row_and_degree?;
+            let pk = row.extract_memcomparable_by_indices(pk_serializer, &state.pk_indices);
+            let degree_i64 = degree
+                .0
+                .last()
+                .cloned()
+                .context("Empty row")?
+                .context("Fail to fetch a degree")?;
+            entry_state.insert(
+                pk,
+                JoinRow::new(row.into_owned(), *degree_i64.as_int64() as u64).encode(),
+            );
+        }
+    } else {
+        let table_iter = table_iter_fut.await?;
+
+        #[for_await]
+        for row in table_iter {
+            let row = row?.1;
+            let pk = row.extract_memcomparable_by_indices(pk_serializer, &state.pk_indices);
+            entry_state.insert(pk, JoinRow::new(row.into_owned(), 0).encode());
+        }
+    };
+
+    Ok(entry_state)
+}
+
 impl<K: HashKey, S: StateStore> Deref for JoinHashMap<K, S> {
     type Target = JoinHashMapInner<K>;
 
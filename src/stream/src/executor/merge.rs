@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -83,7 +84,7 @@ impl MergeExecutor {
 
     #[cfg(test)]
     pub fn for_test(inputs: Vec<tokio::sync::mpsc::Receiver<Message>>) -> Self {
-        use super::exchange::input::LocalInput;
+        use super::exchange::input::{Input, LocalInput};
 
         Self::new(
             Schema::default(),
@@ -92,7 +93,12 @@ impl MergeExecutor {
             514,
             1919,
             1024,
-            inputs.into_iter().map(LocalInput::for_test).collect(),
+            // The upstreams are distinguished by their actor ids for aligning the watermarks.
+            inputs
+                .into_iter()
+                .enumerate()
+                .map(|(i, input)| LocalInput::new(input, i as ActorId).boxed_input())
+                .collect(),
             SharedContext::for_test().into(),
             810,
             StreamingMetrics::unused().into(),
@@ -118,9 +124,8 @@ impl MergeExecutor {
             let mut msg: Message = msg?;
 
             match &mut msg {
-                Message::Watermark(_) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                // The watermarks are already aligned among the upstreams by `SelectReceivers`.
+                Message::Watermark(_) => {}
                 Message::Chunk(chunk) => {
                     self.metrics
                         .actor_in_record_cnt
@@ -206,6 +211,10 @@ pub struct SelectReceivers {
     barrier: Option<Barrier>,
     last_base: usize,
     actor_id: u32,
+    /// The latest watermarks from each upstream, by the watermark column.
+    upstream_watermarks: BTreeMap<usize, HashMap<ActorId, Watermark>>,
+    /// The watermarks last yielded, by the watermark column.
+    current_watermarks: BTreeMap<usize, Watermark>,
 }
 
 impl SelectReceivers {
@@ -218,6 +227,32 @@ impl SelectReceivers {
             last_base: 0,
             actor_id,
             barrier: None,
+            upstream_watermarks: BTreeMap::new(),
+            current_watermarks: BTreeMap::new(),
+        }
+    }
+
+    /// Records the watermark from the upstream. Returns the min watermark of the column among all
+    /// upstreams if it's advanced, which is the watermark of the merged stream.
+    fn handle_watermark(&mut self, upstream: ActorId, watermark: Watermark) -> Option<Watermark> {
+        let col_idx = watermark.col_idx();
+        let upstream_watermarks = self.upstream_watermarks.entry(col_idx).or_default();
+        upstream_watermarks.insert(upstream, watermark);
+        if upstream_watermarks.len() < self.upstreams.len() + self.blocks.len() {
+            return None;
+        }
+        let min_watermark = upstream_watermarks
+            .values()
+            .min_by(|a, b| a.val().cmp(b.val()))
+            .unwrap()
+            .clone();
+        match self.current_watermarks.get(&col_idx) {
+            Some(current) if current.val() >= min_watermark.val() => None,
+            _ => {
+                self.current_watermarks
+                    .insert(col_idx, min_watermark.clone());
+                Some(min_watermark)
+            }
         }
     }
 
@@ -237,6 +272,9 @@ impl SelectReceivers {
 
         self.upstreams
             .retain(|u| !upstream_actor_ids.contains(&u.actor_id()));
+        for upstream_watermarks in self.upstream_watermarks.values_mut() {
+            upstream_watermarks.retain(|actor_id, _| !upstream_actor_ids.contains(actor_id));
+        }
         self.last_base = 0;
     }
 }
@@ -276,8 +314,12 @@ impl Stream for SelectReceivers {
                         self.last_base = (idx + 1) % self.upstreams.len();
                         return Poll::Ready(Some(Ok(message)));
                     }
-                    Some(Ok(Message::Watermark(_))) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
+                    Some(Ok(Message::Watermark(watermark))) => {
+                        let upstream = self.upstreams[idx].actor_id();
+                        if let Some(watermark) = self.handle_watermark(upstream, watermark) {
+                            self.last_base = (idx + 1) % self.upstreams.len();
+                            return Poll::Ready(Some(Ok(Message::Watermark(watermark))));
+                        }
                     }
                 },
             }
@@ -310,18 +352,25 @@ mod tests {
     use assert_matches::assert_matches;
     use futures::FutureExt;
     use itertools::Itertools;
-    use risingwave_common::array::{Op, StreamChunk};
+    use prost::Message as _;
+    use risingwave_common::array::checksum::ChunkChecksumExt;
+    use risingwave_common::array::{Op, StreamChunk, StreamChunkTestExt};
+    use risingwave_common::types::{DataType, ScalarImpl};
+    use risingwave_pb::data::StreamChunk as ProstStreamChunk;
     use risingwave_pb::stream_plan::StreamMessage;
     use risingwave_pb::task_service::exchange_service_server::{
         ExchangeService, ExchangeServiceServer,
     };
+    use risingwave_pb::task_service::get_multiplexed_stream_request::Value;
+    use risingwave_pb::task_service::get_multiplexed_stream_response::Payload;
     use risingwave_pb::task_service::{
-        GetDataRequest, GetDataResponse, GetStreamRequest, GetStreamResponse,
+        GetDataRequest, GetDataResponse, GetMultiplexedStreamRequest, GetMultiplexedStreamResponse,
+        GetStreamRequest, GetStreamResponse,
     };
     use risingwave_rpc_client::ComputeClientPool;
     use tokio::time::sleep;
     use tokio_stream::wrappers::ReceiverStream;
-    use tonic::{Request, Response, Status};
+    use tonic::{Request, Response, Status, Streaming};
 
     use super::*;
     use crate::executor::exchange::input::RemoteInput;
@@ -397,6 +446,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_merge_watermarks() {
+        let (tx1, rx1) = tokio::sync::mpsc::channel(16);
+        let (tx2, rx2) = tokio::sync::mpsc::channel(16);
+        let mut merger = MergeExecutor::for_test(vec![rx1, rx2]).boxed().execute();
+        let watermark = |val| Watermark::new(0, DataType::Int64, Some(ScalarImpl::Int64(val)));
+
+        // The watermark of the merged stream is the min of the upstreams, once every upstream
+        // has reported one.
+        tx1.send(Message::Watermark(watermark(10))).await.unwrap();
+        tx2.send(Message::Watermark(watermark(5))).await.unwrap();
+        assert_eq!(
+            merger.next().await.unwrap().unwrap(),
+            Message::Watermark(watermark(5))
+        );
+
+        // It's only emitted when advanced.
+        tx1.send(Message::Watermark(watermark(12))).await.unwrap();
+        tx2.send(Message::Watermark(watermark(8))).await.unwrap();
+        assert_eq!(
+            merger.next().await.unwrap().unwrap(),
+            Message::Watermark(watermark(8))
+        );
+
+        tx1.send(Message::Barrier(Barrier::new_test_barrier(1)))
+            .await
+            .unwrap();
+        tx2.send(Message::Barrier(Barrier::new_test_barrier(1)))
+            .await
+            .unwrap();
+        assert_matches!(merger.next().await.unwrap().unwrap(), Message::Barrier(_));
+    }
+
     #[tokio::test]
     async fn test_configuration_change() {
         let schema = Schema { fields: vec![] };
@@ -509,11 +591,30 @@ mod tests {
 
     struct FakeExchangeService {
         rpc_called: Arc<AtomicBool>,
+        /// Whether to flip a byte of the chunk on the wire after attaching its checksum.
+        corrupt_chunk: bool,
+    }
+
+    /// A chunk with a checksum, whose value `0x0123456789abcdef` is corrupted on the wire.
+    fn corrupted_chunk() -> ProstStreamChunk {
+        let mut chunk = StreamChunk::from_pretty(
+            " I
+            + 81985529216486895",
+        )
+        .to_protobuf();
+        chunk.set_checksum();
+        let mut bytes = chunk.encode_to_vec();
+        let value = 0x0123456789abcdefi64.to_be_bytes();
+        let pos = bytes.windows(8).position(|window| window == value).unwrap();
+        bytes[pos] ^= 0xff;
+        ProstStreamChunk::decode(bytes.as_slice()).unwrap()
     }
 
     #[async_trait::async_trait]
     impl ExchangeService for FakeExchangeService {
         type GetDataStream = ReceiverStream<std::result::Result<GetDataResponse, Status>>;
+        type GetMultiplexedStreamStream =
+            ReceiverStream<std::result::Result<GetMultiplexedStreamResponse, Status>>;
         type GetStreamStream = ReceiverStream<std::result::Result<GetStreamResponse, Status>>;
 
         async fn get_data(
@@ -527,34 +628,56 @@ mod tests {
             &self,
             _request: Request<GetStreamRequest>,
         ) -> std::result::Result<Response<Self::GetStreamStream>, Status> {
+            unimplemented!()
+        }
+
+        async fn get_multiplexed_stream(
+            &self,
+            request: Request<Streaming<GetMultiplexedStreamRequest>>,
+        ) -> std::result::Result<Response<Self::GetMultiplexedStreamStream>, Status> {
             let (tx, rx) = tokio::sync::mpsc::channel(10);
             self.rpc_called.store(true, Ordering::SeqCst);
-            // send stream_chunk
-            let stream_chunk = StreamChunk::default().to_protobuf();
-            tx.send(Ok(GetStreamResponse {
-                message: Some(StreamMessage {
-                    stream_message: Some(
-                        risingwave_pb::stream_plan::stream_message::StreamMessage::StreamChunk(
-                            stream_chunk,
-                        ),
+            let mut requests = request.into_inner();
+            let corrupt_chunk = self.corrupt_chunk;
+            tokio::spawn(async move {
+                // Wait for the channel to be subscribed.
+                let request = requests.next().await.unwrap().unwrap();
+                let Some(Value::Subscribe(subscribe)) = request.value else {
+                    unreachable!()
+                };
+                let channel = subscribe.channel.unwrap();
+                let response = |message| GetMultiplexedStreamResponse {
+                    up_actor_id: channel.up_actor_id,
+                    down_actor_id: channel.down_actor_id,
+                    payload: Some(Payload::Message(StreamMessage {
+                        stream_message: Some(message),
+                    })),
+                };
+                // send stream_chunk
+                let stream_chunk = if corrupt_chunk {
+                    corrupted_chunk()
+                } else {
+                    StreamChunk::default().to_protobuf()
+                };
+                tx.send(Ok(response(
+                    risingwave_pb::stream_plan::stream_message::StreamMessage::StreamChunk(
+                        stream_chunk,
                     ),
-                }),
-            }))
-            .await
-            .unwrap();
-            // send barrier
-            let barrier = Barrier::new_test_barrier(12345);
-            tx.send(Ok(GetStreamResponse {
-                message: Some(StreamMessage {
-                    stream_message: Some(
-                        risingwave_pb::stream_plan::stream_message::StreamMessage::Barrier(
-                            barrier.to_protobuf(),
-                        ),
+                )))
+                .await
+                .unwrap();
+                // send barrier
+                let barrier = Barrier::new_test_barrier(12345);
+                tx.send(Ok(response(
+                    risingwave_pb::stream_plan::stream_message::StreamMessage::Barrier(
+                        barrier.to_protobuf(),
                     ),
-                }),
-            }))
-            .await
-            .unwrap();
+                )))
+                .await
+                .unwrap();
+                // Keep the connection until the client is gone.
+                while requests.next().await.is_some() {}
+            });
             Ok(Response::new(ReceiverStream::new(rx)))
         }
     }
@@ -569,6 +692,7 @@ mod tests {
         let (shutdown_send, shutdown_recv) = tokio::sync::oneshot::channel();
         let exchange_svc = ExchangeServiceServer::new(FakeExchangeService {
             rpc_called: rpc_called.clone(),
+            corrupt_chunk: false,
         });
         let cp_server_run = server_run.clone();
         let join_handle = tokio::spawn(async move {
@@ -612,4 +736,47 @@ mod tests {
         shutdown_send.send(()).unwrap();
         join_handle.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_stream_exchange_checksum_mismatch() {
+        let addr = "127.0.0.1:12349".parse().unwrap();
+        let (shutdown_send, shutdown_recv) = tokio::sync::oneshot::channel();
+        let exchange_svc = ExchangeServiceServer::new(FakeExchangeService {
+            rpc_called: Arc::new(AtomicBool::new(false)),
+            corrupt_chunk: true,
+        });
+        let join_handle = tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(exchange_svc)
+                .serve_with_shutdown(addr, async move {
+                    shutdown_recv.await.unwrap();
+                })
+                .await
+                .unwrap();
+        });
+        sleep(Duration::from_secs(1)).await;
+
+        let metrics = Arc::new(StreamingMetrics::unused());
+        let remote_input = RemoteInput::new(
+            ComputeClientPool::default(),
+            addr.into(),
+            (7, 8),
+            (0, 0),
+            metrics.clone(),
+        );
+        pin_mut!(remote_input);
+
+        let err = remote_input.next().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("upstream actor 7"), "{}", err);
+        assert_eq!(
+            metrics
+                .exchange_checksum_mismatch_count
+                .with_label_values(&["7", "8"])
+                .get(),
+            1
+        );
+
+        shutdown_send.send(()).unwrap();
+        join_handle.await.unwrap();
+    }
 }
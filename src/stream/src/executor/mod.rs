@@ -15,6 +15,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_stack_trace::StackTrace;
 use enum_as_inner::EnumAsInner;
@@ -22,24 +23,29 @@ use futures::stream::BoxStream;
 use futures::{Stream, StreamExt};
 use itertools::Itertools;
 use minitrace::prelude::*;
+use risingwave_common::array::checksum::ChunkChecksumExt;
 use risingwave_common::array::column::Column;
 use risingwave_common::array::StreamChunk;
 use risingwave_common::buffer::Bitmap;
-use risingwave_common::catalog::Schema;
+use risingwave_common::catalog::{Schema, TableId};
 use risingwave_common::types::{DataType, Datum};
 use risingwave_common::util::epoch::EpochPair;
 use risingwave_common::util::value_encoding::{deserialize_datum, serialize_datum_to_bytes};
 use risingwave_connector::source::SplitImpl;
 use risingwave_pb::data::{Datum as ProstDatum, Epoch as ProstEpoch};
+use risingwave_pb::plan_common::ColumnCatalog as ProstColumnCatalog;
 use risingwave_pb::stream_plan::add_mutation::Dispatchers;
 use risingwave_pb::stream_plan::barrier::Mutation as ProstMutation;
+use risingwave_pb::stream_plan::source_change_schema_mutation::Columns as ProstSourceColumns;
 use risingwave_pb::stream_plan::stream_message::StreamMessage;
 use risingwave_pb::stream_plan::update_mutation::{DispatcherUpdate, MergeUpdate};
 use risingwave_pb::stream_plan::{
     AddMutation, Barrier as ProstBarrier, Dispatcher as ProstDispatcher, PauseMutation,
-    ResumeMutation, SourceChangeSplitMutation, StopMutation, StreamMessage as ProstStreamMessage,
-    UpdateMutation, Watermark as ProstWatermark,
+    ResumeMutation, SetLogLevelMutation, SourceChangeSchemaMutation, SourceChangeSplitMutation,
+    SourceResetOffsetMutation, StartBackfillMutation, StopMutation,
+    StreamMessage as ProstStreamMessage, UpdateMutation, Watermark as ProstWatermark,
 };
+use risingwave_tracing::TracingContext;
 use smallvec::SmallVec;
 
 use crate::error::StreamResult;
@@ -53,6 +59,8 @@ pub mod monitor;
 pub mod aggregation;
 mod batch_query;
 mod chain;
+mod change_log;
+mod dedup;
 mod dispatch;
 mod dynamic_filter;
 mod error;
@@ -68,6 +76,8 @@ mod lookup_union;
 mod managed_state;
 mod merge;
 mod mview;
+mod now;
+mod over_window;
 mod project;
 mod project_set;
 mod rearranged_chain;
@@ -89,6 +99,8 @@ pub use actor::{Actor, ActorContext, ActorContextRef};
 use anyhow::Context;
 pub use batch_query::BatchQueryExecutor;
 pub use chain::ChainExecutor;
+pub use change_log::{change_log_op_name, ChangeLogExecutor};
+pub use dedup::DedupExecutor;
 pub use dispatch::{DispatchExecutor, DispatcherImpl};
 pub use dynamic_filter::DynamicFilterExecutor;
 pub use error::{StreamExecutorError, StreamExecutorResult};
@@ -104,6 +116,8 @@ pub use lookup_union::LookupUnionExecutor;
 pub use managed_state::join::JoinManagedCache;
 pub use merge::MergeExecutor;
 pub use mview::*;
+pub use now::NowExecutor;
+pub use over_window::{OverWindowExecutor, WindowFunction};
 pub use project::ProjectExecutor;
 pub use project_set::*;
 pub use rearranged_chain::RearrangedChainExecutor;
@@ -201,8 +215,25 @@ pub enum Mutation {
         adds: HashMap<ActorId, Vec<ProstDispatcher>>,
         // TODO: remove this and use `SourceChangesSplit` after we support multiple mutations.
         splits: HashMap<ActorId, Vec<SplitImpl>>,
+        /// The chain actors that don't backfill until a [`Mutation::StartBackfill`] including
+        /// them.
+        deferred_backfills: HashSet<ActorId>,
     },
     SourceChangeSplit(HashMap<ActorId, Vec<SplitImpl>>),
+    /// The new full column catalogs of the altered sources, keyed by source id.
+    SourceChangeSchema(HashMap<TableId, Vec<ProstColumnCatalog>>),
+    /// Start the backfill of the chain actors deferred by [`Mutation::Add`].
+    StartBackfill(HashSet<ActorId>),
+    /// The splits with the reset offsets of each source actor, which override the persisted
+    /// states of the splits.
+    SourceResetOffset(HashMap<ActorId, Vec<SplitImpl>>),
+    /// Log the events of the actors at `level` until `expire_at`, regardless of the log level of
+    /// the compute node.
+    SetLogLevel {
+        actors: HashSet<ActorId>,
+        level: tracing::Level,
+        expire_at: SystemTime,
+    },
     Pause,
     Resume,
 }
@@ -215,6 +246,9 @@ pub struct Barrier {
 
     /// The actors that this barrier has passed locally. Used for debugging only.
     pub passed_actors: Vec<ActorId>,
+
+    /// The context of the distributed trace of this barrier, injected by the meta service.
+    pub tracing_context: TracingContext,
 }
 
 impl Barrier {
@@ -225,6 +259,7 @@ impl Barrier {
             checkpoint: true,
             mutation: Default::default(),
             passed_actors: Default::default(),
+            tracing_context: TracingContext::none(),
         }
     }
 
@@ -241,6 +276,25 @@ impl Barrier {
         self.with_mutation(Mutation::Stop(HashSet::default()))
     }
 
+    /// Create a span named `name` for handling this barrier in the actor, as a child of the
+    /// distributed trace of this barrier. Returns a disabled span if this barrier is not traced.
+    pub fn actor_span(
+        &self,
+        name: &'static str,
+        actor_id: ActorId,
+        fragment_id: FragmentId,
+    ) -> tracing::Span {
+        self.tracing_context.child_span(|| {
+            tracing::info_span!(
+                "actor_barrier",
+                otel.name = name,
+                actor_id,
+                fragment_id,
+                epoch = self.epoch.curr
+            )
+        })
+    }
+
     /// Whether this barrier carries stop mutation.
     pub fn is_with_stop_mutation(&self) -> bool {
         matches!(self.mutation.as_deref(), Some(Mutation::Stop(_)))
@@ -272,6 +326,23 @@ impl Barrier {
         )
     }
 
+    /// Whether this barrier is to add the chain actor with `actor_id` with its backfill deferred,
+    /// i.e., it should not backfill until [`Barrier::is_start_backfill`].
+    pub fn is_backfill_deferred(&self, actor_id: ActorId) -> bool {
+        matches!(
+            self.mutation.as_deref(),
+            Some(Mutation::Add { deferred_backfills, .. }) if deferred_backfills.contains(&actor_id)
+        )
+    }
+
+    /// Whether this barrier is to start the deferred backfill of the chain actor with `actor_id`.
+    pub fn is_start_backfill(&self, actor_id: ActorId) -> bool {
+        matches!(
+            self.mutation.as_deref(),
+            Some(Mutation::StartBackfill(actors)) if actors.contains(&actor_id)
+        )
+    }
+
     /// Whether this barrier is for configuration change. Used for source executor initialization.
     pub fn is_update(&self) -> bool {
         matches!(self.mutation.as_deref(), Some(Mutation::Update { .. }))
@@ -353,7 +424,11 @@ impl Mutation {
                     })
                     .collect(),
             }),
-            Mutation::Add { adds, .. } => ProstMutation::Add(AddMutation {
+            Mutation::Add {
+                adds,
+                deferred_backfills,
+                ..
+            } => ProstMutation::Add(AddMutation {
                 actor_dispatchers: adds
                     .iter()
                     .map(|(&actor_id, dispatchers)| {
@@ -365,6 +440,7 @@ impl Mutation {
                         )
                     })
                     .collect(),
+                deferred_backfill_actors: deferred_backfills.iter().copied().collect(),
                 ..Default::default()
             }),
             Mutation::SourceChangeSplit(changes) => {
@@ -386,6 +462,53 @@ impl Mutation {
                         .collect(),
                 })
             }
+            Mutation::SourceChangeSchema(changes) => {
+                ProstMutation::SourceSchema(SourceChangeSchemaMutation {
+                    source_columns: changes
+                        .iter()
+                        .map(|(source_id, columns)| {
+                            (
+                                source_id.table_id,
+                                ProstSourceColumns {
+                                    columns: columns.clone(),
+                                },
+                            )
+                        })
+                        .collect(),
+                })
+            }
+            Mutation::StartBackfill(actors) => {
+                ProstMutation::StartBackfill(StartBackfillMutation {
+                    actors: actors.iter().copied().collect(),
+                })
+            }
+            Mutation::SourceResetOffset(changes) => {
+                ProstMutation::SourceResetOffset(SourceResetOffsetMutation {
+                    actor_splits: changes
+                        .iter()
+                        .map(|(&actor_id, splits)| {
+                            (
+                                actor_id,
+                                ConnectorSplits {
+                                    splits: splits.iter().map(ConnectorSplit::from).collect(),
+                                },
+                            )
+                        })
+                        .collect(),
+                })
+            }
+            Mutation::SetLogLevel {
+                actors,
+                level,
+                expire_at,
+            } => ProstMutation::SetLogLevel(SetLogLevelMutation {
+                actors: actors.iter().copied().collect(),
+                level: level.to_string(),
+                expire_at_ms: expire_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            }),
             Mutation::Pause => ProstMutation::Pause(PauseMutation {}),
             Mutation::Resume => ProstMutation::Resume(ResumeMutation {}),
         }
@@ -452,6 +575,7 @@ impl Mutation {
                         )
                     })
                     .collect(),
+                deferred_backfills: add.deferred_backfill_actors.iter().copied().collect(),
             },
 
             ProstMutation::Splits(s) => {
@@ -471,6 +595,39 @@ impl Mutation {
                 }
                 Mutation::SourceChangeSplit(change_splits.into_iter().collect())
             }
+            ProstMutation::SourceSchema(s) => Mutation::SourceChangeSchema(
+                s.source_columns
+                    .iter()
+                    .map(|(&source_id, columns)| (TableId::new(source_id), columns.columns.clone()))
+                    .collect(),
+            ),
+            ProstMutation::StartBackfill(start) => {
+                Mutation::StartBackfill(start.actors.iter().copied().collect())
+            }
+            ProstMutation::SourceResetOffset(reset) => Mutation::SourceResetOffset(
+                reset
+                    .actor_splits
+                    .iter()
+                    .map(|(&actor_id, splits)| {
+                        Ok((
+                            actor_id,
+                            splits
+                                .splits
+                                .iter()
+                                .map(SplitImpl::try_from)
+                                .try_collect()?,
+                        ))
+                    })
+                    .collect::<StreamExecutorResult<_>>()?,
+            ),
+            ProstMutation::SetLogLevel(set) => Mutation::SetLogLevel {
+                actors: set.actors.iter().copied().collect(),
+                level: set
+                    .level
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid log level: {}", set.level))?,
+                expire_at: UNIX_EPOCH + Duration::from_millis(set.expire_at_ms),
+            },
             ProstMutation::Pause(_) => Mutation::Pause,
             ProstMutation::Resume(_) => Mutation::Resume,
         };
@@ -485,7 +642,7 @@ impl Barrier {
             mutation,
             checkpoint,
             passed_actors,
-            ..
+            tracing_context,
         }: Barrier = self.clone();
         ProstBarrier {
             epoch: Some(ProstEpoch {
@@ -493,7 +650,7 @@ impl Barrier {
                 prev: epoch.prev,
             }),
             mutation: mutation.map(|mutation| mutation.to_protobuf()),
-            span: vec![],
+            tracing_context: tracing_context.to_protobuf(),
             checkpoint,
             passed_actors,
         }
@@ -512,6 +669,7 @@ impl Barrier {
             epoch: EpochPair::new(epoch.curr, epoch.prev),
             mutation,
             passed_actors: prost.get_passed_actors().clone(),
+            tracing_context: TracingContext::from_protobuf(&prost.tracing_context),
         })
     }
 }
@@ -519,26 +677,49 @@ impl Barrier {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Watermark {
     col_idx: usize,
+    data_type: DataType,
     val: Datum,
 }
 
 impl Watermark {
+    pub fn new(col_idx: usize, data_type: DataType, val: Datum) -> Self {
+        Self {
+            col_idx,
+            data_type,
+            val,
+        }
+    }
+
+    pub fn col_idx(&self) -> usize {
+        self.col_idx
+    }
+
+    pub fn val(&self) -> &Datum {
+        &self.val
+    }
+
+    /// The same watermark on the column at `col_idx`, e.g. after the column is projected.
+    pub fn with_idx(self, col_idx: usize) -> Self {
+        Self { col_idx, ..self }
+    }
+
     pub fn to_protobuf(&self) -> ProstWatermark {
         ProstWatermark {
             col_idx: self.col_idx as _,
             val: Some(ProstDatum {
                 body: serialize_datum_to_bytes(self.val.as_ref()),
             }),
+            data_type: Some(self.data_type.to_protobuf()),
         }
     }
 
-    pub fn from_protobuf(
-        prost: &ProstWatermark,
-        data_type: &DataType,
-    ) -> StreamExecutorResult<Self> {
+    pub fn from_protobuf(prost: &ProstWatermark) -> StreamExecutorResult<Self> {
+        let data_type = DataType::from(prost.get_data_type()?);
+        let val = deserialize_datum(&*prost.get_val()?.body, &data_type)?;
         Ok(Watermark {
             col_idx: prost.col_idx as _,
-            val: deserialize_datum(&*prost.get_val()?.body, data_type)?,
+            data_type,
+            val,
         })
     }
 }
@@ -579,13 +760,22 @@ impl Message {
     }
 
     pub fn to_protobuf(&self) -> ProstStreamMessage {
+        self.to_protobuf_with_checksum(false)
+    }
+
+    /// Same as [`Message::to_protobuf`], with the checksum attached to the chunk if `checksum` is
+    /// set.
+    pub fn to_protobuf_with_checksum(&self, checksum: bool) -> ProstStreamMessage {
         let prost = match self {
             Self::Chunk(stream_chunk) => {
-                let prost_stream_chunk = stream_chunk.to_protobuf();
+                let mut prost_stream_chunk = stream_chunk.to_protobuf();
+                if checksum {
+                    prost_stream_chunk.set_checksum();
+                }
                 StreamMessage::StreamChunk(prost_stream_chunk)
             }
             Self::Barrier(barrier) => StreamMessage::Barrier(barrier.clone().to_protobuf()),
-            Self::Watermark(_) => todo!("https://github.com/risingwavelabs/risingwave/issues/6042"),
+            Self::Watermark(watermark) => StreamMessage::Watermark(watermark.to_protobuf()),
         };
         ProstStreamMessage {
             stream_message: Some(prost),
@@ -600,6 +790,9 @@ impl Message {
             StreamMessage::Barrier(ref barrier) => {
                 Message::Barrier(Barrier::from_protobuf(barrier)?)
             }
+            StreamMessage::Watermark(ref watermark) => {
+                Message::Watermark(Watermark::from_protobuf(watermark)?)
+            }
         };
         Ok(res)
     }
@@ -40,8 +40,10 @@ pub struct StreamingMetrics {
     pub actor_out_record_cnt: GenericCounterVec<AtomicU64>,
     pub actor_sampled_deserialize_duration_ns: GenericCounterVec<AtomicU64>,
     pub source_output_row_count: GenericCounterVec<AtomicU64>,
+    pub source_parked_actors: GenericGaugeVec<AtomicI64>,
     pub exchange_recv_size: GenericCounterVec<AtomicU64>,
     pub exchange_frag_recv_size: GenericCounterVec<AtomicU64>,
+    pub exchange_checksum_mismatch_count: GenericCounterVec<AtomicU64>,
 
     // Streaming Join
     pub join_lookup_miss_count: GenericCounterVec<AtomicU64>,
@@ -56,6 +58,14 @@ pub struct StreamingMetrics {
     pub agg_lookup_miss_count: GenericCounterVec<AtomicU64>,
     pub agg_total_lookup_count: GenericCounterVec<AtomicU64>,
     pub agg_cached_keys: GenericGaugeVec<AtomicI64>,
+    /// Per-chunk durations of the hashing and state-update phases of hash aggregation, only
+    /// observed with the `telemetry` feature.
+    pub agg_chunk_hash_duration: HistogramVec,
+    pub agg_chunk_state_update_duration: HistogramVec,
+
+    // State prefetch
+    pub state_prefetch_bytes: GenericCounterVec<AtomicU64>,
+    pub state_prefetch_duration: HistogramVec,
 
     /// The duration from receipt of barrier to all actors collection.
     /// And the max of all node `barrier_inflight_latency` is the latency for a barrier
@@ -85,6 +95,14 @@ impl StreamingMetrics {
         )
         .unwrap();
 
+        let source_parked_actors = register_int_gauge_vec_with_registry!(
+            "stream_source_parked_actors",
+            "Number of source actors parked for having no split assigned",
+            &["source_id"],
+            registry
+        )
+        .unwrap();
+
         let actor_execution_time = register_gauge_vec_with_registry!(
             "stream_actor_actor_execution_time",
             "Total execution time (s) of an actor",
@@ -125,6 +143,14 @@ impl StreamingMetrics {
         )
         .unwrap();
 
+        let exchange_checksum_mismatch_count = register_int_counter_vec_with_registry!(
+            "stream_exchange_checksum_mismatch_count",
+            "Total number of chunks received from upstream actor with mismatched checksum",
+            &["up_actor_id", "down_actor_id"],
+            registry
+        )
+        .unwrap();
+
         let actor_fast_poll_duration = register_gauge_vec_with_registry!(
             "stream_actor_fast_poll_duration",
             "tokio's metrics",
@@ -310,6 +336,38 @@ impl StreamingMetrics {
         )
         .unwrap();
 
+        let opts = histogram_opts!(
+            "stream_agg_chunk_hash_duration_seconds",
+            "Duration of hashing the group keys of a chunk in hash aggregation",
+            exponential_buckets(0.000001, 10.0, 6).unwrap() // max 100ms
+        );
+        let agg_chunk_hash_duration =
+            register_histogram_vec_with_registry!(opts, &["actor_id"], registry).unwrap();
+
+        let opts = histogram_opts!(
+            "stream_agg_chunk_state_update_duration_seconds",
+            "Duration of updating the group states with a chunk in hash aggregation",
+            exponential_buckets(0.000001, 10.0, 6).unwrap() // max 100ms
+        );
+        let agg_chunk_state_update_duration =
+            register_histogram_vec_with_registry!(opts, &["actor_id"], registry).unwrap();
+
+        let state_prefetch_bytes = register_int_counter_vec_with_registry!(
+            "stream_state_prefetch_bytes",
+            "Total bytes of state prefetched into the executor caches",
+            &["actor_id"],
+            registry
+        )
+        .unwrap();
+
+        let opts = histogram_opts!(
+            "stream_state_prefetch_duration_seconds",
+            "Duration of prefetching state into the executor caches",
+            exponential_buckets(0.001, 2.0, 16).unwrap() // max 32s
+        );
+        let state_prefetch_duration =
+            register_histogram_vec_with_registry!(opts, &["actor_id"], registry).unwrap();
+
         let opts = histogram_opts!(
             "stream_barrier_inflight_duration_seconds",
             "barrier_inflight_latency",
@@ -351,8 +409,10 @@ impl StreamingMetrics {
             actor_out_record_cnt,
             actor_sampled_deserialize_duration_ns,
             source_output_row_count,
+            source_parked_actors,
             exchange_recv_size,
             exchange_frag_recv_size,
+            exchange_checksum_mismatch_count,
             join_lookup_miss_count,
             join_total_lookup_count,
             join_actor_input_waiting_duration_ns,
@@ -363,6 +423,10 @@ impl StreamingMetrics {
             agg_lookup_miss_count,
             agg_total_lookup_count,
             agg_cached_keys,
+            agg_chunk_hash_duration,
+            agg_chunk_state_update_duration,
+            state_prefetch_bytes,
+            state_prefetch_duration,
             barrier_inflight_latency,
             barrier_sync_latency,
             sink_commit_duration,
@@ -133,9 +133,8 @@ impl<S: StateStore> MaterializeExecutor<S> {
         for msg in input {
             let msg = msg?;
             yield match msg {
-                Message::Watermark(_) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                // The watermarks are not propagated to the downstream materialized views.
+                Message::Watermark(_) => continue,
                 Message::Chunk(chunk) => {
                     self.state_table.write_chunk(chunk.clone());
                     Message::Chunk(chunk)
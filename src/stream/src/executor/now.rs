@@ -0,0 +1,227 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::UNIX_EPOCH;
+
+use futures::{pin_mut, StreamExt};
+use futures_async_stream::try_stream;
+use risingwave_common::array::{Op, Row, StreamChunk};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::{DataType, ScalarImpl};
+use risingwave_common::util::epoch::Epoch;
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+use risingwave_storage::StateStore;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use super::error::StreamExecutorError;
+use super::{
+    Barrier, BoxedMessageStream, Executor, ExecutorInfo, Message, PkIndicesRef,
+    StreamExecutorResult,
+};
+
+/// [`NowExecutor`] emits the value of `now()` in streaming queries. It holds a single row of the
+/// timestamp of the latest barrier, and on each barrier, deletes the previous timestamp and inserts
+/// the new one. The timestamp never goes backwards, even across recovery.
+pub struct NowExecutor<S: StateStore> {
+    info: ExecutorInfo,
+
+    /// Receives the barriers injected by the meta service, as a source does.
+    barrier_receiver: Option<UnboundedReceiver<Barrier>>,
+
+    /// Stores the last emitted timestamp.
+    state_table: StateTable<S>,
+}
+
+impl<S: StateStore> NowExecutor<S> {
+    pub fn new(
+        barrier_receiver: UnboundedReceiver<Barrier>,
+        executor_id: u64,
+        state_table: StateTable<S>,
+    ) -> Self {
+        let info = ExecutorInfo {
+            schema: Schema::new(vec![Field::with_name(DataType::Timestampz, "now")]),
+            pk_indices: vec![],
+            identity: format!("NowExecutor {:X}", executor_id),
+        };
+        Self {
+            info,
+            barrier_receiver: Some(barrier_receiver),
+            state_table,
+        }
+    }
+
+    /// Returns the timestamp of the barrier in microseconds since the UNIX epoch.
+    fn timestamp_of(barrier: &Barrier) -> i64 {
+        Epoch(barrier.epoch.curr)
+            .as_system_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as i64
+    }
+
+    async fn recover_last_timestamp(&self) -> StreamExecutorResult<Option<i64>> {
+        let iter = self.state_table.iter().await?;
+        pin_mut!(iter);
+        let last_timestamp = iter
+            .next()
+            .await
+            .transpose()?
+            .and_then(|row| row[0].as_ref().map(|scalar| *scalar.as_int64()));
+        Ok(last_timestamp)
+    }
+
+    #[try_stream(ok = Message, error = StreamExecutorError)]
+    async fn into_stream(mut self) {
+        let mut barrier_receiver = self.barrier_receiver.take().unwrap();
+        let mut last_timestamp = None;
+        let mut is_first_barrier = true;
+
+        while let Some(barrier) = barrier_receiver.recv().await {
+            if is_first_barrier {
+                self.state_table.init_epoch(barrier.epoch);
+                last_timestamp = self.recover_last_timestamp().await?;
+                is_first_barrier = false;
+            } else {
+                self.state_table.commit(barrier.epoch).await?;
+            }
+
+            let timestamp = Self::timestamp_of(&barrier).max(last_timestamp.unwrap_or(i64::MIN));
+            yield Message::Barrier(barrier);
+
+            if last_timestamp == Some(timestamp) {
+                continue;
+            }
+            let mut rows = vec![];
+            if let Some(last_timestamp) = last_timestamp {
+                let row = Row::new(vec![Some(ScalarImpl::Int64(last_timestamp))]);
+                self.state_table.delete(row.clone());
+                rows.push((Op::Delete, row));
+            }
+            let row = Row::new(vec![Some(ScalarImpl::Int64(timestamp))]);
+            self.state_table.insert(row.clone());
+            rows.push((Op::Insert, row));
+            last_timestamp = Some(timestamp);
+
+            yield Message::Chunk(StreamChunk::from_rows(
+                &rows,
+                &self.info.schema.data_types(),
+            ));
+        }
+    }
+}
+
+impl<S: StateStore> Executor for NowExecutor<S> {
+    fn execute(self: Box<Self>) -> BoxedMessageStream {
+        self.into_stream().boxed()
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.info.schema
+    }
+
+    fn pk_indices(&self) -> PkIndicesRef<'_> {
+        &self.info.pk_indices
+    }
+
+    fn identity(&self) -> &str {
+        &self.info.identity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::catalog::{ColumnDesc, ColumnId, TableId};
+    use risingwave_common::util::epoch::EpochPair;
+    use risingwave_storage::memory::MemoryStateStore;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+    use super::*;
+
+    fn create_executor(store: MemoryStateStore) -> (UnboundedSender<Barrier>, BoxedMessageStream) {
+        let state_table = StateTable::new_without_distribution(
+            store,
+            TableId::new(0),
+            vec![ColumnDesc::unnamed(ColumnId::new(0), DataType::Timestampz)],
+            vec![],
+            vec![],
+        );
+        let (tx, rx) = unbounded_channel();
+        let executor = NowExecutor::new(rx, 1, state_table);
+        (tx, Box::new(executor).execute())
+    }
+
+    /// The epoch of the given physical time in milliseconds.
+    fn epoch(ms: u64) -> u64 {
+        Epoch::from_physical_time(ms).0
+    }
+
+    fn barrier(curr: u64, prev: u64) -> Barrier {
+        Barrier {
+            epoch: EpochPair::new(curr, prev),
+            ..Barrier::new_test_barrier(curr)
+        }
+    }
+
+    /// The chunk of the given ops on the timestamps of the given physical times.
+    fn chunk(rows: &[(Op, u64)]) -> StreamChunk {
+        let rows = rows
+            .iter()
+            .map(|&(op, ms)| {
+                let timestamp = NowExecutor::<MemoryStateStore>::timestamp_of(
+                    &Barrier::new_test_barrier(epoch(ms)),
+                );
+                (op, Row::new(vec![Some(ScalarImpl::Int64(timestamp))]))
+            })
+            .collect::<Vec<_>>();
+        StreamChunk::from_rows(&rows, &[DataType::Timestampz])
+    }
+
+    async fn expect_barrier(now: &mut BoxedMessageStream) {
+        now.next().await.unwrap().unwrap().as_barrier().unwrap();
+    }
+
+    async fn expect_chunk(now: &mut BoxedMessageStream, expected: StreamChunk) {
+        let message = now.next().await.unwrap().unwrap();
+        assert_eq!(message.into_chunk().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_now() {
+        let store = MemoryStateStore::new();
+        let (tx, mut now) = create_executor(store.clone());
+
+        tx.send(barrier(epoch(1000), epoch(500))).unwrap();
+        expect_barrier(&mut now).await;
+        expect_chunk(&mut now, chunk(&[(Op::Insert, 1000)])).await;
+
+        tx.send(barrier(epoch(2000), epoch(1000))).unwrap();
+        expect_barrier(&mut now).await;
+        expect_chunk(&mut now, chunk(&[(Op::Delete, 1000), (Op::Insert, 2000)])).await;
+
+        tx.send(barrier(epoch(3000), epoch(2000))).unwrap();
+        expect_barrier(&mut now).await;
+        expect_chunk(&mut now, chunk(&[(Op::Delete, 2000), (Op::Insert, 3000)])).await;
+
+        // Recover from the state committed in epoch 2000, where the timestamp of the recovery
+        // barrier is no later than the last one, so nothing is emitted.
+        drop(now);
+        let (tx, mut now) = create_executor(store);
+        tx.send(barrier(epoch(2000) + 1, epoch(2000))).unwrap();
+        expect_barrier(&mut now).await;
+
+        tx.send(barrier(epoch(4000), epoch(2000) + 1)).unwrap();
+        expect_barrier(&mut now).await;
+        expect_chunk(&mut now, chunk(&[(Op::Delete, 2000), (Op::Insert, 4000)])).await;
+    }
+}
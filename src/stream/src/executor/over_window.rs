@@ -0,0 +1,636 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::ops::Range;
+
+use futures::{pin_mut, StreamExt};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use risingwave_common::array::{DataChunk, Op, Row, StreamChunk};
+use risingwave_common::catalog::{Field, Schema};
+use risingwave_common::types::{DataType, Datum};
+use risingwave_expr::vector_op::agg::AggStateFactory;
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+use risingwave_storage::StateStore;
+
+use super::error::StreamExecutorError;
+use super::{
+    expect_first_barrier, ActorContextRef, BoxedExecutor, BoxedMessageStream, Executor,
+    ExecutorInfo, Message, PkIndicesRef, StreamExecutorResult,
+};
+use crate::cache::{cache_may_stale, EvictableHashMap, ExecutorCache, LruManagerRef};
+
+/// The window function computed by [`OverWindowExecutor`] for each row.
+pub enum WindowFunction {
+    /// Aggregates the rows in the frame `ROWS BETWEEN .. AND ..`, whose bounds are offsets
+    /// relative to the current row.
+    Aggregate {
+        call: AggStateFactory,
+        start_offset: i64,
+        end_offset: i64,
+    },
+    /// Takes the argument of the row at the offset relative to the current row, i.e. `lag` with a
+    /// negative offset and `lead` with a positive one.
+    ValueAt { arg_idx: usize, offset: i64 },
+}
+
+impl WindowFunction {
+    pub fn return_type(&self, input_schema: &Schema) -> DataType {
+        match self {
+            Self::Aggregate { call, .. } => call.get_return_type(),
+            Self::ValueAt { arg_idx, .. } => input_schema[*arg_idx].data_type(),
+        }
+    }
+
+    /// The output of a row only depends on the rows within the radius of it.
+    fn radius(&self) -> usize {
+        match self {
+            Self::Aggregate {
+                start_offset,
+                end_offset,
+                ..
+            } => start_offset.unsigned_abs().max(end_offset.unsigned_abs()) as usize,
+            Self::ValueAt { offset, .. } => offset.unsigned_abs() as usize,
+        }
+    }
+
+    /// Computes the output of the row at `pos` of the partition, whose rows are in order.
+    fn evaluate(&self, partition: &DataChunk, pos: usize) -> StreamExecutorResult<Datum> {
+        let frame = |start_offset: i64, end_offset: i64| -> Range<usize> {
+            let len = partition.capacity() as i64;
+            // The offsets may be as large as `i64::MAX`, so the bounds saturate instead of
+            // overflowing before being clamped into the partition.
+            let start = (pos as i64).saturating_add(start_offset).clamp(0, len) as usize;
+            let end = (pos as i64)
+                .saturating_add(end_offset)
+                .saturating_add(1)
+                .clamp(0, len) as usize;
+            start..end.max(start)
+        };
+        match self {
+            Self::Aggregate {
+                call,
+                start_offset,
+                end_offset,
+            } => {
+                let frame = frame(*start_offset, *end_offset);
+                let mut state = call.create_agg_state();
+                if !frame.is_empty() {
+                    state.update_multi(partition, frame.start, frame.end)?;
+                }
+                let mut builder = call.get_return_type().create_array_builder(1);
+                state.output(&mut builder)?;
+                Ok(builder.finish().datum_at(0))
+            }
+            Self::ValueAt { arg_idx, offset } => {
+                let frame = frame(*offset, *offset);
+                Ok(if frame.is_empty() {
+                    None
+                } else {
+                    partition
+                        .column_at(*arg_idx)
+                        .array_ref()
+                        .datum_at(frame.start)
+                })
+            }
+        }
+    }
+}
+
+/// The rows of a partition, keyed and ordered by the memcomparable primary key of the state table.
+type Partition = BTreeMap<Vec<u8>, Row>;
+
+/// [`OverWindowExecutor`] computes a window function over the rows of each partition, ordered by
+/// the order key of the window and then the stream key, and appends the result to each row.
+///
+/// The input rows are stored in the state table, with the partition key, the order key and the
+/// stream key as the primary key. The partitions touched by the chunks are cached, and only the
+/// rows within the radius of the window function around the changed rows are recomputed, each over
+/// the rows within the radius around it, since the others have the same frames as before. The
+/// changes of their outputs are emitted.
+pub struct OverWindowExecutor<S: StateStore> {
+    ctx: ActorContextRef,
+
+    input: Option<BoxedExecutor>,
+
+    info: ExecutorInfo,
+
+    state_table: StateTable<S>,
+
+    partition_key_indices: Vec<usize>,
+
+    window_function: WindowFunction,
+
+    /// The cached partitions, keyed by the partition key.
+    cache: ExecutorCache<Row, Partition>,
+}
+
+impl<S: StateStore> OverWindowExecutor<S> {
+    pub fn new(
+        ctx: ActorContextRef,
+        input: BoxedExecutor,
+        state_table: StateTable<S>,
+        partition_key_indices: Vec<usize>,
+        window_function: WindowFunction,
+        executor_id: u64,
+        cache_size: usize,
+        lru_manager: Option<LruManagerRef>,
+    ) -> Self {
+        let mut schema = input.schema().clone();
+        schema
+            .fields
+            .push(Field::unnamed(window_function.return_type(input.schema())));
+        let info = ExecutorInfo {
+            schema,
+            pk_indices: input.pk_indices().to_vec(),
+            identity: format!("OverWindowExecutor {:X}", executor_id),
+        };
+        let cache = if let Some(lru_manager) = lru_manager {
+            ExecutorCache::Managed(lru_manager.create_cache())
+        } else {
+            ExecutorCache::Local(EvictableHashMap::new(cache_size))
+        };
+        Self {
+            ctx,
+            input: Some(input),
+            info,
+            state_table,
+            partition_key_indices,
+            window_function,
+            cache,
+        }
+    }
+
+    fn serialize_pk(&self, row: &Row) -> Vec<u8> {
+        row.extract_memcomparable_by_indices(
+            self.state_table.pk_serde(),
+            self.state_table.pk_indices(),
+        )
+    }
+
+    async fn load_partition(&self, partition_key: &Row) -> StreamExecutorResult<Partition> {
+        let mut partition = Partition::new();
+        let iter = self.state_table.iter_with_pk_prefix(partition_key).await?;
+        pin_mut!(iter);
+        while let Some(row) = iter.next().await.transpose()? {
+            let row = row.into_owned();
+            partition.insert(self.serialize_pk(&row), row);
+        }
+        Ok(partition)
+    }
+
+    /// Computes the output rows of the given keys present in the partition. Each of them is
+    /// evaluated over the rows within the radius around it only.
+    fn compute_outputs(
+        &self,
+        partition: &Partition,
+        keys: &BTreeSet<Vec<u8>>,
+    ) -> StreamExecutorResult<HashMap<Vec<u8>, Row>> {
+        let radius = self.window_function.radius();
+        let data_types = self.input_data_types();
+
+        let mut outputs = HashMap::new();
+        for key in keys {
+            let Some(row) = partition.get(key) else {
+                continue;
+            };
+            let mut window = partition
+                .range(..key.clone())
+                .rev()
+                .take(radius)
+                .map(|(_, row)| row.clone())
+                .collect_vec();
+            window.reverse();
+            let pos = window.len();
+            window.push(row.clone());
+            window.extend(
+                partition
+                    .range((Excluded(key.clone()), Unbounded))
+                    .take(radius)
+                    .map(|(_, row)| row.clone()),
+            );
+            let chunk = DataChunk::from_rows(&window, &data_types);
+            let output = self.window_function.evaluate(&chunk, pos)?;
+            outputs.insert(key.clone(), row.concat([output]));
+        }
+        Ok(outputs)
+    }
+
+    fn input_data_types(&self) -> Vec<DataType> {
+        let mut data_types = self.info.schema.data_types();
+        data_types.pop();
+        data_types
+    }
+
+    async fn apply_chunk(
+        &mut self,
+        chunk: StreamChunk,
+    ) -> StreamExecutorResult<Option<StreamChunk>> {
+        // Group the changes by partition, in the order of their first appearance.
+        let mut partition_indices = HashMap::new();
+        let mut changes: Vec<(Row, Vec<(Op, Row)>)> = vec![];
+        for (op, row) in chunk.rows() {
+            let row = row.to_owned_row();
+            let partition_key = row.by_indices(&self.partition_key_indices);
+            let idx = *partition_indices
+                .entry(partition_key.clone())
+                .or_insert_with(|| {
+                    changes.push((partition_key, vec![]));
+                    changes.len() - 1
+                });
+            changes[idx].1.push((op, row));
+        }
+
+        let radius = self.window_function.radius();
+        let mut output = vec![];
+        for (partition_key, changes) in changes {
+            let mut partition = match self.cache.pop(&partition_key) {
+                Some(partition) => partition,
+                None => self.load_partition(&partition_key).await?,
+            };
+            let changed_keys = changes
+                .iter()
+                .map(|(_, row)| self.serialize_pk(row))
+                .collect::<BTreeSet<_>>();
+
+            // A row kept by the changes and within the radius of a changed row afterwards is also
+            // within the radius of a changed row, or of the position of an inserted one, before
+            // the changes. So the affected rows can be found before applying the changes.
+            let mut affected_keys = neighbor_keys(&partition, &changed_keys, radius);
+            affected_keys.extend(changed_keys.iter().cloned());
+            let mut old_outputs = self.compute_outputs(&partition, &affected_keys)?;
+
+            for (op, row) in changes {
+                let key = self.serialize_pk(&row);
+                match op {
+                    Op::Insert | Op::UpdateInsert => {
+                        partition.insert(key, row.clone());
+                        self.state_table.insert(row);
+                    }
+                    Op::Delete | Op::UpdateDelete => {
+                        partition.remove(&key);
+                        self.state_table.delete(row);
+                    }
+                }
+            }
+            let mut new_outputs = self.compute_outputs(&partition, &affected_keys)?;
+            self.cache.put(partition_key, partition);
+
+            for key in &affected_keys {
+                match (old_outputs.remove(key), new_outputs.remove(key)) {
+                    (Some(old), Some(new)) => {
+                        if old != new {
+                            output.push((Op::UpdateDelete, old));
+                            output.push((Op::UpdateInsert, new));
+                        }
+                    }
+                    (Some(old), None) => output.push((Op::Delete, old)),
+                    (None, Some(new)) => output.push((Op::Insert, new)),
+                    (None, None) => {}
+                }
+            }
+        }
+
+        if output.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(StreamChunk::from_rows(
+            &output,
+            &self.info.schema.data_types(),
+        )))
+    }
+
+    #[try_stream(ok = Message, error = StreamExecutorError)]
+    async fn execute_inner(mut self) {
+        let mut input = self.input.take().unwrap().execute();
+
+        let barrier = expect_first_barrier(&mut input).await?;
+        self.state_table.init_epoch(barrier.epoch);
+        self.cache.update_epoch(barrier.epoch.curr);
+        yield Message::Barrier(barrier);
+
+        #[for_await]
+        for msg in input {
+            match msg? {
+                Message::Chunk(chunk) => {
+                    if let Some(chunk) = self.apply_chunk(chunk).await? {
+                        yield Message::Chunk(chunk);
+                    }
+                }
+                // The outputs of the rows behind the watermark may still change with the rows
+                // after them, so the watermarks are not propagated.
+                Message::Watermark(_) => {}
+                Message::Barrier(barrier) => {
+                    self.state_table.commit(barrier.epoch).await?;
+                    self.cache.evict();
+
+                    if let Some(vnode_bitmap) = barrier.as_update_vnode_bitmap(self.ctx.id) {
+                        let previous_vnode_bitmap =
+                            self.state_table.update_vnode_bitmap(vnode_bitmap.clone());
+                        if cache_may_stale(&previous_vnode_bitmap, &vnode_bitmap) {
+                            self.cache.clear();
+                        }
+                    }
+
+                    self.cache.update_epoch(barrier.epoch.curr);
+                    yield Message::Barrier(barrier);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the keys of the rows within `radius` of any of the changed keys in the partition,
+/// including the changed keys themselves.
+fn neighbor_keys(
+    partition: &Partition,
+    changed_keys: &BTreeSet<Vec<u8>>,
+    radius: usize,
+) -> BTreeSet<Vec<u8>> {
+    let mut keys = BTreeSet::new();
+    for key in changed_keys {
+        keys.extend(
+            partition
+                .range(..key.clone())
+                .rev()
+                .take(radius)
+                .map(|(key, _)| key.clone()),
+        );
+        keys.extend(
+            partition
+                .range(key.clone()..)
+                .take(radius + 1)
+                .map(|(key, _)| key.clone()),
+        );
+    }
+    keys
+}
+
+impl<S: StateStore> Executor for OverWindowExecutor<S> {
+    fn execute(self: Box<Self>) -> BoxedMessageStream {
+        self.execute_inner().boxed()
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.info.schema
+    }
+
+    fn pk_indices(&self) -> PkIndicesRef<'_> {
+        &self.info.pk_indices
+    }
+
+    fn identity(&self) -> &str {
+        &self.info.identity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+    use risingwave_common::catalog::{ColumnDesc, ColumnId, TableId};
+    use risingwave_common::util::sort_util::OrderType;
+    use risingwave_pb::data::data_type::TypeName;
+    use risingwave_pb::data::DataType as ProstDataType;
+    use risingwave_pb::expr::agg_call::{Arg, Type};
+    use risingwave_pb::expr::{AggCall, InputRefExpr};
+    use risingwave_storage::memory::MemoryStateStore;
+
+    use super::*;
+    use crate::executor::test_utils::{MessageSender, MockSource};
+    use crate::executor::ActorContext;
+
+    /// The input is `(partition, ts, v, id)` with `id` as the stream key, ordered by `ts`.
+    fn create_executor(
+        window_function: WindowFunction,
+        cache_size: usize,
+    ) -> (MessageSender, BoxedMessageStream) {
+        let data_types = [
+            DataType::Int64,
+            DataType::Int64,
+            DataType::Int32,
+            DataType::Int64,
+        ];
+        let schema = Schema::new(data_types.iter().cloned().map(Field::unnamed).collect());
+        let (tx, source) = MockSource::channel(schema, vec![3]);
+        let state_table = StateTable::new_without_distribution(
+            MemoryStateStore::new(),
+            TableId::new(1),
+            data_types
+                .into_iter()
+                .enumerate()
+                .map(|(id, data_type)| ColumnDesc::unnamed(ColumnId::new(id as i32), data_type))
+                .collect(),
+            vec![OrderType::Ascending; 3],
+            vec![0, 1, 3],
+        );
+        let executor = OverWindowExecutor::new(
+            ActorContext::create(123),
+            Box::new(source),
+            state_table,
+            vec![0],
+            window_function,
+            1,
+            cache_size,
+            None,
+        );
+        (tx, Box::new(executor).execute())
+    }
+
+    /// `sum(v) OVER (PARTITION BY partition ORDER BY ts ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING)`
+    fn sum_over_neighbors() -> WindowFunction {
+        let call = AggCall {
+            r#type: Type::Sum as i32,
+            args: vec![Arg {
+                input: Some(InputRefExpr { column_idx: 2 }),
+                r#type: Some(ProstDataType {
+                    type_name: TypeName::Int32 as i32,
+                    ..Default::default()
+                }),
+            }],
+            return_type: Some(ProstDataType {
+                type_name: TypeName::Int64 as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        WindowFunction::Aggregate {
+            call: AggStateFactory::new(&call).unwrap(),
+            start_offset: -1,
+            end_offset: 1,
+        }
+    }
+
+    async fn expect_chunk(over_window: &mut BoxedMessageStream, expected: &str) {
+        let msg = over_window.next().await.unwrap().unwrap();
+        assert_eq!(
+            msg.into_chunk().unwrap().compact(),
+            StreamChunk::from_pretty(expected)
+        );
+    }
+
+    async fn expect_barrier(over_window: &mut BoxedMessageStream) {
+        over_window
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_barrier()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sum_with_out_of_order_changes() {
+        check_sum_with_out_of_order_changes(1024).await;
+    }
+
+    /// The partitions are evicted on each barrier and reloaded from the state table.
+    #[tokio::test]
+    async fn test_sum_with_evicted_partitions() {
+        check_sum_with_out_of_order_changes(0).await;
+    }
+
+    async fn check_sum_with_out_of_order_changes(cache_size: usize) {
+        let (mut tx, mut over_window) = create_executor(sum_over_neighbors(), cache_size);
+        tx.push_barrier(1, false);
+        expect_barrier(&mut over_window).await;
+
+        // The rows arrive out of order, and the partitions are independent.
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            + 1 30 3 1
+            + 1 10 1 2
+            + 2 10 7 3
+            + 1 20 2 4",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I I
+            + 1 10 1 2 3
+            + 1 20 2 4 6
+            + 1 30 3 1 5
+            + 2 10 7 3 7",
+        )
+        .await;
+        tx.push_barrier(2, false);
+        expect_barrier(&mut over_window).await;
+
+        // Inserting into the middle of the frames changes the neighbors only.
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            + 1 25 10 5
+            + 1 40 4 6",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I I
+            U- 1 20 2 4 6
+            U+ 1 20 2 4 13
+            + 1 25 10 5 15
+            U- 1 30 3 1 5
+            U+ 1 30 3 1 17
+            + 1 40 4 6 7",
+        )
+        .await;
+
+        // Deleting a row and updating another inside the frames.
+        tx.push_chunk(StreamChunk::from_pretty(
+            "  I I i I
+            -  1 25 10 5
+            U- 1 40 4 6
+            U+ 1 40 5 6",
+        ));
+        expect_chunk(
+            &mut over_window,
+            "  I I i I I
+            U- 1 20 2 4 13
+            U+ 1 20 2 4 6
+            -  1 25 10 5 15
+            U- 1 30 3 1 17
+            U+ 1 30 3 1 10
+            U- 1 40 4 6 7
+            U+ 1 40 5 6 8",
+        )
+        .await;
+
+        // The changes are visible to the next epoch.
+        tx.push_barrier(3, false);
+        expect_barrier(&mut over_window).await;
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            - 1 10 1 2",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I I
+            - 1 10 1 2 3
+            U- 1 20 2 4 6
+            U+ 1 20 2 4 5",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_lag() {
+        let (mut tx, mut over_window) = create_executor(
+            WindowFunction::ValueAt {
+                arg_idx: 2,
+                offset: -1,
+            },
+            1024,
+        );
+        tx.push_barrier(1, false);
+        expect_barrier(&mut over_window).await;
+
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            + 1 30 3 1
+            + 1 10 1 2",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I i
+            + 1 10 1 2 .
+            + 1 30 3 1 1",
+        )
+        .await;
+
+        // The row after the inserted or deleted one takes the new previous value.
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            + 1 20 2 3",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I i
+            + 1 20 2 3 1
+            U- 1 30 3 1 1
+            U+ 1 30 3 1 2",
+        )
+        .await;
+        tx.push_chunk(StreamChunk::from_pretty(
+            " I I i I
+            - 1 10 1 2",
+        ));
+        expect_chunk(
+            &mut over_window,
+            " I I i I i
+            - 1 10 1 2 .
+            U- 1 20 2 3 1
+            U+ 1 20 2 3 .",
+        )
+        .await;
+    }
+}
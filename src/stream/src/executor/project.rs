@@ -22,7 +22,7 @@ use risingwave_expr::expr::BoxedExpression;
 
 use super::{
     ActorContextRef, Executor, ExecutorInfo, PkIndices, PkIndicesRef, SimpleExecutor,
-    SimpleExecutorWrapper, StreamExecutorResult,
+    SimpleExecutorWrapper, StreamExecutorResult, Watermark,
 };
 use crate::common::InfallibleExpression;
 
@@ -46,6 +46,16 @@ impl ProjectExecutor {
             inner: SimpleProjectExecutor::new(ctx, info, exprs, execuotr_id),
         }
     }
+
+    /// Sets the output columns which are the input columns as is, as pairs of the input and output
+    /// column indices. The watermarks on these input columns are forwarded to the output columns.
+    pub fn with_watermark_derivations(
+        mut self,
+        watermark_derivations: Vec<(usize, usize)>,
+    ) -> Self {
+        self.inner.watermark_derivations = watermark_derivations;
+        self
+    }
 }
 
 /// `ProjectExecutor` project data with the `expr`. The `expr` takes a chunk of data,
@@ -57,6 +67,9 @@ pub struct SimpleProjectExecutor {
 
     /// Expressions of the current projection.
     exprs: Vec<BoxedExpression>,
+
+    /// See [`ProjectExecutor::with_watermark_derivations`].
+    watermark_derivations: Vec<(usize, usize)>,
 }
 
 impl SimpleProjectExecutor {
@@ -80,6 +93,7 @@ impl SimpleProjectExecutor {
                 identity: format!("ProjectExecutor {:X}", executor_id),
             },
             exprs,
+            watermark_derivations: vec![],
         }
     }
 }
@@ -115,6 +129,13 @@ impl SimpleExecutor for SimpleProjectExecutor {
         Ok(Some(new_chunk))
     }
 
+    fn handle_watermark(&self, watermark: Watermark) -> Option<Watermark> {
+        self.watermark_derivations
+            .iter()
+            .find(|(input_idx, _)| *input_idx == watermark.col_idx())
+            .map(|(_, output_idx)| watermark.with_idx(*output_idx))
+    }
+
     fn schema(&self) -> &Schema {
         &self.info.schema
     }
@@ -134,9 +155,9 @@ mod tests {
     use risingwave_common::array::stream_chunk::StreamChunkTestExt;
     use risingwave_common::array::StreamChunk;
     use risingwave_common::catalog::{Field, Schema};
-    use risingwave_common::types::DataType;
+    use risingwave_common::types::{DataType, ScalarImpl};
     use risingwave_expr::expr::expr_binary_nonnull::new_binary_expr;
-    use risingwave_expr::expr::InputRefExpression;
+    use risingwave_expr::expr::{InputRefExpression, LiteralExpression};
     use risingwave_pb::expr::expr_node::Type;
 
     use super::super::test_utils::MockSource;
@@ -206,4 +227,49 @@ mod tests {
 
         assert!(project.next().await.unwrap().unwrap().is_stop());
     }
+
+    #[tokio::test]
+    async fn test_project_watermark() {
+        let schema = Schema {
+            fields: vec![
+                Field::unnamed(DataType::Int64),
+                Field::unnamed(DataType::Int64),
+            ],
+        };
+        let (mut tx, source) = MockSource::channel(schema, PkIndices::new());
+        let watermark =
+            |col_idx, val| Watermark::new(col_idx, DataType::Int64, Some(ScalarImpl::Int64(val)));
+
+        // Project `$1 + 1, $1`, so only the watermarks on `$1` are forwarded, to the second column.
+        let add_expr = new_binary_expr(
+            Type::Add,
+            DataType::Int64,
+            Box::new(InputRefExpression::new(DataType::Int64, 1)),
+            Box::new(LiteralExpression::new(DataType::Int64, Some(1_i64.into()))),
+        )
+        .unwrap();
+        let project = Box::new(
+            ProjectExecutor::new(
+                ActorContext::create(123),
+                Box::new(source),
+                vec![],
+                vec![
+                    add_expr,
+                    Box::new(InputRefExpression::new(DataType::Int64, 1)),
+                ],
+                1,
+            )
+            .with_watermark_derivations(vec![(1, 1)]),
+        );
+        let mut project = project.execute();
+
+        tx.push_watermark(watermark(0, 10));
+        tx.push_watermark(watermark(1, 20));
+        tx.push_barrier(1, true);
+        assert_eq!(
+            project.next().await.unwrap().unwrap(),
+            Message::Watermark(watermark(1, 20))
+        );
+        assert!(project.next().await.unwrap().unwrap().is_stop());
+    }
 }
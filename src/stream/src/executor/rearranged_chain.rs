@@ -50,10 +50,6 @@ pub struct RearrangedChainExecutor {
 
 fn mapping(upstream_indices: &[usize], msg: Message) -> Message {
     match msg {
-        Message::Watermark(_) => {
-            todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-        }
-
         Message::Chunk(chunk) => {
             let (ops, columns, visibility) = chunk.into_inner();
             let mapped_columns = upstream_indices
@@ -86,10 +82,7 @@ impl RearrangedMessage {
 impl RearrangedMessage {
     fn rearranged_from(msg: Message) -> Self {
         match msg {
-            Message::Watermark(_) => {
-                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-            }
-
+            Message::Watermark(_) => unreachable!("the snapshot has no watermarks"),
             Message::Chunk(chunk) => RearrangedMessage::Chunk(chunk),
             Message::Barrier(barrier) => RearrangedMessage::RearrangedBarrier(barrier),
         }
@@ -97,10 +90,7 @@ impl RearrangedMessage {
 
     fn phantom_from(msg: Message) -> Self {
         match msg {
-            Message::Watermark(_) => {
-                todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-            }
-
+            Message::Watermark(_) => unreachable!("the watermarks are not rearranged"),
             Message::Chunk(chunk) => RearrangedMessage::Chunk(chunk),
             Message::Barrier(barrier) => RearrangedMessage::PhantomBarrier(barrier),
         }
@@ -139,17 +129,39 @@ impl RearrangedChainExecutor {
             .map(move |result| result.map(|msg| mapping(&upstream_indices, msg)));
 
         // 1. Poll the upstream to get the first barrier.
-        let first_barrier = expect_first_barrier(&mut upstream).await?;
-        let create_epoch = first_barrier.epoch;
+        let mut first_barrier = expect_first_barrier(&mut upstream).await?;
 
         // If the barrier is a conf change of creating this mview, init snapshot from its epoch
         // and begin to consume the snapshot.
         // Otherwise, it means we've recovered and the snapshot is already consumed.
         let to_consume_snapshot = first_barrier.is_add_dispatcher(self.actor_id);
+        let backfill_deferred =
+            to_consume_snapshot && first_barrier.is_backfill_deferred(self.actor_id);
 
         // The first barrier message should be propagated.
         yield Message::Barrier(first_barrier.clone());
 
+        // If the backfill is deferred, the barrier starting it takes the place of the first
+        // barrier. The upstream chunks before that barrier are discarded, as they're included in
+        // the snapshot.
+        if backfill_deferred {
+            loop {
+                let msg = upstream
+                    .next()
+                    .await
+                    .ok_or_else(|| StreamExecutorError::channel_closed("upstream"))??;
+                if let Message::Barrier(barrier) = msg {
+                    let to_start = barrier.is_start_backfill(self.actor_id);
+                    yield Message::Barrier(barrier.clone());
+                    if to_start {
+                        first_barrier = barrier;
+                        break;
+                    }
+                }
+            }
+        }
+        let create_epoch = first_barrier.epoch;
+
         if to_consume_snapshot {
             // If we need to consume the snapshot ...
             // We will spawn a background task to poll the upstream actively, in order to get the
@@ -291,6 +303,10 @@ impl RearrangedChainExecutor {
 
                 Either::Right((Some(msg), _)) => {
                     let msg = msg?;
+                    // The upstream materialized views don't emit watermarks.
+                    if matches!(msg, Message::Watermark(_)) {
+                        continue;
+                    }
 
                     // If we polled a barrier, rearrange it by yielding and leave a phantom barrier
                     // with `RearrangedMessage::phantom_from` in-place.
@@ -127,9 +127,8 @@ impl Executor for ReceiverExecutor {
                 let mut msg: Message = msg?;
 
                 match &mut msg {
-                    Message::Watermark(_) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
+                    // The watermarks of the only upstream are forwarded as is.
+                    Message::Watermark(_) => {}
                     Message::Chunk(chunk) => {
                         self.metrics
                             .actor_in_record_cnt
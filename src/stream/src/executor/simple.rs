@@ -17,7 +17,9 @@ use futures_async_stream::try_stream;
 use risingwave_common::catalog::Schema;
 
 use super::error::{StreamExecutorError, StreamExecutorResult};
-use super::{BoxedExecutor, BoxedMessageStream, Executor, Message, PkIndicesRef, StreamChunk};
+use super::{
+    BoxedExecutor, BoxedMessageStream, Executor, Message, PkIndicesRef, StreamChunk, Watermark,
+};
 
 /// Executor which can handle [`StreamChunk`]s one by one.
 pub trait SimpleExecutor: Send + 'static {
@@ -25,6 +27,10 @@ pub trait SimpleExecutor: Send + 'static {
     fn map_filter_chunk(&mut self, chunk: StreamChunk)
         -> StreamExecutorResult<Option<StreamChunk>>;
 
+    /// Convert a watermark of the input to the one of the output, or `None` if the watermark
+    /// column is not in the output.
+    fn handle_watermark(&self, watermark: Watermark) -> Option<Watermark>;
+
     /// See [`super::Executor::schema`].
     fn schema(&self) -> &Schema;
 
@@ -74,9 +80,10 @@ where
         for msg in input {
             let msg = msg?;
             match msg {
-                Message::Watermark(_) => {
-                    todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                }
+                Message::Watermark(watermark) => match inner.handle_watermark(watermark) {
+                    Some(new_watermark) => yield Message::Watermark(new_watermark),
+                    None => continue,
+                },
                 Message::Chunk(chunk) => match inner.map_filter_chunk(chunk)? {
                     Some(new_chunk) => yield Message::Chunk(new_chunk),
                     None => continue,
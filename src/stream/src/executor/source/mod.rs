@@ -19,3 +19,6 @@ mod reader;
 pub mod state_table_handler;
 
 pub use state_table_handler::*;
+
+mod watermark;
+pub use watermark::BoundedDisorderWatermarkGenerator;
@@ -18,12 +18,15 @@ use std::sync::Arc;
 use either::Either;
 use futures::StreamExt;
 use futures_async_stream::try_stream;
+use prometheus::core::{AtomicI64, GenericGauge};
 use risingwave_common::array::column::Column;
 use risingwave_common::array::stream_chunk::Ops;
 use risingwave_common::array::{ArrayBuilder, I64ArrayBuilder, Op, StreamChunk};
 use risingwave_common::catalog::{ColumnId, Schema, TableId};
 use risingwave_common::util::epoch::UNIX_SINGULARITY_DATE_EPOCH;
 use risingwave_connector::source::{ConnectorState, SplitId, SplitImpl, SplitMetaData};
+use risingwave_pb::catalog::WatermarkDesc;
+use risingwave_pb::plan_common::ColumnCatalog as ProstColumnCatalog;
 use risingwave_source::connector_source::SourceContext;
 use risingwave_source::row_id::RowIdGenerator;
 use risingwave_source::*;
@@ -31,6 +34,7 @@ use risingwave_storage::StateStore;
 use tokio::sync::mpsc::UnboundedReceiver;
 
 use super::reader::SourceReaderStream;
+use super::BoundedDisorderWatermarkGenerator;
 use crate::error::StreamResult;
 use crate::executor::error::StreamExecutorError;
 use crate::executor::monitor::StreamingMetrics;
@@ -70,6 +74,12 @@ pub struct SourceExecutor<S: StateStore> {
 
     state_cache: HashMap<SplitId, SplitImpl>,
 
+    /// Generators of the watermarks on the time columns, which filter out the late rows.
+    watermark_generators: Vec<BoundedDisorderWatermarkGenerator>,
+
+    /// Set if the executor is parked for having no split assigned. See [`ParkedGuard`].
+    parked: Option<ParkedGuard>,
+
     #[expect(dead_code)]
     /// Expected barrier latency
     expected_barrier_latency_ms: u64,
@@ -113,10 +123,23 @@ impl<S: StateStore> SourceExecutor<S> {
             source_identify: "Table_".to_string() + &source_id.table_id().to_string(),
             split_state_store: state_table,
             state_cache: HashMap::new(),
+            watermark_generators: vec![],
+            parked: None,
             expected_barrier_latency_ms,
         })
     }
 
+    pub fn with_watermark_descs(mut self, watermark_descs: &[WatermarkDesc]) -> Self {
+        self.watermark_generators = watermark_descs
+            .iter()
+            .map(|desc| {
+                let data_type = self.schema.fields[desc.watermark_idx as usize].data_type();
+                BoundedDisorderWatermarkGenerator::new(desc, data_type)
+            })
+            .collect();
+        self
+    }
+
     /// Generate a row ID column.
     async fn gen_row_id_column(&mut self, len: usize) -> Column {
         let mut builder = I64ArrayBuilder::new(len);
@@ -168,14 +191,37 @@ impl<S: StateStore> SourceExecutor<S> {
     }
 }
 
+/// A connector source executor with no split assigned is parked: its connector reader is dropped
+/// and only barriers are processed, until some splits are assigned to it again. This happens when
+/// the parallelism of the source fragment exceeds the number of splits.
+///
+/// The guard counts the executor in the parked actors metric while it's alive.
+struct ParkedGuard(GenericGauge<AtomicI64>);
+
+impl ParkedGuard {
+    fn new(gauge: GenericGauge<AtomicI64>) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for ParkedGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
 impl<S: StateStore> SourceExecutor<S> {
     // Note: get_diff will modify the state_cache
     async fn get_diff(&mut self, rhs: ConnectorState) -> StreamExecutorResult<ConnectorState> {
-        // rhs can not be None because we do not support split number reduction
-
+        // rhs can not be None, while it can be empty if all splits are moved to other actors.
         let split_change = rhs.unwrap();
         let mut target_state: Vec<SplitImpl> = Vec::with_capacity(split_change.len());
-        let mut no_change_flag = true;
+        // Some splits are moved away.
+        let mut no_change_flag = self
+            .stream_source_splits
+            .iter()
+            .all(|split| split_change.iter().any(|sc| sc.id() == split.id()));
         for sc in &split_change {
             if let Some(s) = self.state_cache.get(&sc.id()) {
                 target_state.push(s.clone())
@@ -213,12 +259,42 @@ impl<S: StateStore> SourceExecutor<S> {
         if !cache.is_empty() {
             self.split_state_store.take_snapshot(cache).await?
         }
+        for generator in &mut self.watermark_generators {
+            if let Some(max_seen_us) = generator.take_updated_max_seen_us() {
+                for split in &self.stream_source_splits {
+                    self.split_state_store
+                        .set_watermark_max_seen(&split.id(), generator.col_idx(), max_seen_us)
+                        .await?;
+                }
+            }
+        }
         // commit anyway, even if no message saved
         self.split_state_store.state_store.commit(epoch).await?;
 
         Ok(())
     }
 
+    /// Recovers the watermarks with the min of the max values seen by the readers of the assigned
+    /// splits, so that the rows of no split are taken as late for the watermark of another split.
+    async fn recover_watermarks(&mut self) -> StreamExecutorResult<()> {
+        for generator in &mut self.watermark_generators {
+            let mut recovered: Option<i64> = None;
+            for split in &self.stream_source_splits {
+                if let Some(max_seen_us) = self
+                    .split_state_store
+                    .get_watermark_max_seen(&split.id(), generator.col_idx())
+                    .await?
+                {
+                    recovered = Some(recovered.map_or(max_seen_us, |r| r.min(max_seen_us)));
+                }
+            }
+            if let Some(max_seen_us) = recovered {
+                generator.recover(max_seen_us);
+            }
+        }
+        Ok(())
+    }
+
     async fn build_stream_source_reader(
         &mut self,
         source_desc: &SourceDescRef,
@@ -253,7 +329,7 @@ impl<S: StateStore> SourceExecutor<S> {
             .await
             .unwrap();
 
-        let source_desc = self
+        let mut source_desc = self
             .source_desc_builder
             .build()
             .await
@@ -298,14 +374,20 @@ impl<S: StateStore> SourceExecutor<S> {
                 *ele = recover_state;
             }
         }
+        self.recover_watermarks().await?;
 
-        let recover_state: ConnectorState = (!boot_state.is_empty()).then_some(boot_state);
+        let source_chunk_reader =
+            if boot_state.is_empty() && matches!(source_desc.source, SourceImpl::Connector(_)) {
+                self.park();
+                futures::stream::pending().boxed()
+            } else {
+                let recover_state: ConnectorState = (!boot_state.is_empty()).then_some(boot_state);
 
-        // todo: use epoch from msg to restore state from state store
-        let source_chunk_reader = self
-            .build_stream_source_reader(&source_desc, recover_state)
-            .stack_trace("source_build_reader")
-            .await?;
+                // todo: use epoch from msg to restore state from state store
+                self.build_stream_source_reader(&source_desc, recover_state)
+                    .stack_trace("source_build_reader")
+                    .await?
+            };
 
         // Merge the chunks from source and the barriers into a single stream.
         let mut stream = SourceReaderStream::new(barrier_receiver, source_chunk_reader);
@@ -327,6 +409,17 @@ impl<S: StateStore> SourceExecutor<S> {
                                 self.apply_split_change(&source_desc, &mut stream, actor_splits)
                                     .await?
                             }
+                            Mutation::SourceChangeSchema(source_columns) => {
+                                if let Some(columns) = source_columns.get(&self.source_id) {
+                                    source_desc = self
+                                        .apply_schema_change(&mut stream, columns.clone())
+                                        .await?;
+                                }
+                            }
+                            Mutation::SourceResetOffset(actor_splits) => {
+                                self.apply_offset_reset(&source_desc, &mut stream, actor_splits)
+                                    .await?
+                            }
                             Mutation::Pause => stream.pause_source(),
                             Mutation::Resume => stream.resume_source(),
                             Mutation::Update {
@@ -393,11 +486,21 @@ impl<S: StateStore> SourceExecutor<S> {
                         }
                     };
 
+                    for generator in &mut self.watermark_generators {
+                        chunk = generator.apply(chunk);
+                    }
+
                     self.metrics
                         .source_output_row_count
                         .with_label_values(&[self.source_identify.as_str()])
                         .inc_by(chunk.cardinality() as u64);
                     yield Message::Chunk(chunk);
+
+                    for generator in &mut self.watermark_generators {
+                        if let Some(watermark) = generator.advance() {
+                            yield Message::Watermark(watermark);
+                        }
+                    }
                 }
             }
         }
@@ -425,6 +528,77 @@ impl<S: StateStore> SourceExecutor<S> {
         Ok(())
     }
 
+    /// Restarts the reader from the reset offsets in `mapping`. The reset splits replace the ones
+    /// in the state cache, so that the snapshot of this barrier overwrites the persisted offsets,
+    /// and recovery won't go back to them.
+    async fn apply_offset_reset(
+        &mut self,
+        source_desc: &SourceDescRef,
+        stream: &mut SourceReaderStream,
+        mapping: &HashMap<ActorId, Vec<SplitImpl>>,
+    ) -> StreamExecutorResult<()> {
+        if let Some(target_splits) = mapping.get(&self.ctx.id).cloned() {
+            for split in &target_splits {
+                self.state_cache.insert(split.id(), split.clone());
+            }
+            self.replace_stream_reader_with_target_state(source_desc, stream, target_splits)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the source desc with the altered columns and restarts the reader from the current
+    /// offsets, so that the new parser takes effect exactly after this barrier.
+    ///
+    /// The output columns (`column_ids`) of this executor are never changed: downstream
+    /// materialized views keep the projection they were created with, and only views created after
+    /// the `ALTER` will read the new columns. So the columns out of the projection are not passed
+    /// to the parser.
+    async fn apply_schema_change(
+        &mut self,
+        stream: &mut SourceReaderStream,
+        columns: Vec<ProstColumnCatalog>,
+    ) -> StreamExecutorResult<SourceDescRef> {
+        let columns = columns
+            .into_iter()
+            .filter(|column| {
+                let column_id = ColumnId::from(column.column_desc.as_ref().unwrap().column_id);
+                self.column_ids.contains(&column_id)
+            })
+            .collect();
+        self.source_desc_builder.set_columns(columns);
+        let source_desc = self
+            .source_desc_builder
+            .build()
+            .await
+            .map_err(StreamExecutorError::connector_error)?;
+
+        if let SourceImpl::Connector(_) = &source_desc.source {
+            let mut target_state = Vec::with_capacity(self.stream_source_splits.len());
+            for split in &self.stream_source_splits {
+                let state = if let Some(s) = self.state_cache.get(&split.id()) {
+                    s.clone()
+                } else if let Some(recover_state) = self
+                    .split_state_store
+                    .try_recover_from_state_store(split)
+                    .await?
+                {
+                    recover_state
+                } else {
+                    split.clone()
+                };
+                target_state.push(state);
+            }
+            if !target_state.is_empty() {
+                self.replace_stream_reader_with_target_state(&source_desc, stream, target_state)
+                    .await?;
+            }
+        }
+
+        Ok(source_desc)
+    }
+
     async fn replace_stream_reader_with_target_state(
         &mut self,
         source_desc: &SourceDescRef,
@@ -437,16 +611,39 @@ impl<S: StateStore> SourceExecutor<S> {
             target_state
         );
 
-        // Replace the source reader with a new one of the new state.
-        let reader = self
-            .build_stream_source_reader(source_desc, Some(target_state.clone()))
-            .await?;
-        stream.replace_source_stream(reader);
+        if target_state.is_empty() {
+            // Drop the reader along with its connections.
+            stream.replace_source_stream(futures::stream::pending().boxed());
+            self.park();
+        } else {
+            // Replace the source reader with a new one of the new state.
+            let reader = self
+                .build_stream_source_reader(source_desc, Some(target_state.clone()))
+                .await?;
+            stream.replace_source_stream(reader);
+            if self.parked.take().is_some() {
+                tracing::info!("actor {:?} unparked", self.ctx.id);
+            }
+        }
 
         self.stream_source_splits = target_state;
 
         Ok(())
     }
+
+    fn park(&mut self) {
+        if self.parked.is_none() {
+            tracing::info!(
+                "actor {:?} parked for having no split assigned",
+                self.ctx.id
+            );
+            self.parked = Some(ParkedGuard::new(
+                self.metrics
+                    .source_parked_actors
+                    .with_label_values(&[self.source_identify.as_str()]),
+            ));
+        }
+    }
 }
 
 impl<S: StateStore> Executor for SourceExecutor<S> {
@@ -483,7 +680,7 @@ mod tests {
     use std::time::Duration;
 
     use bytes::Bytes;
-    use futures::StreamExt;
+    use futures::{FutureExt, StreamExt};
     use maplit::{convert_args, hashmap};
     use risingwave_common::array::stream_chunk::StreamChunkTestExt;
     use risingwave_common::array::StreamChunk;
@@ -822,6 +1019,7 @@ mod tests {
                     }),
                 ],
             },
+            deferred_backfills: Default::default(),
         });
         barrier_tx.send(init_barrier).unwrap();
 
@@ -903,4 +1101,194 @@ mod tests {
         let barrier = Barrier::new_test_barrier(4).with_mutation(Mutation::Resume);
         barrier_tx.send(barrier).unwrap();
     }
+
+    #[tokio::test]
+    async fn test_reset_offset() {
+        let source_table_id = TableId::default();
+        let source_builder = mock_source_desc_builder(source_table_id);
+        let mem_state_store = MemoryStateStore::new();
+
+        let column_ids = vec![ColumnId::from(0), ColumnId::from(1)];
+        let schema = Schema::new(vec![
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int32),
+        ]);
+        let (barrier_tx, barrier_rx) = unbounded_channel::<Barrier>();
+        let vnodes = Bitmap::from_bytes(Bytes::from_static(&[0b11111111]));
+        let mut source_state_handler = SourceStateTableHandler::from_table_catalog(
+            &default_source_internal_table(0x2333),
+            mem_state_store.clone(),
+        );
+
+        let source_exec = SourceExecutor::new(
+            ActorContext::create(0),
+            source_builder,
+            source_table_id,
+            vnodes,
+            source_state_handler.clone(),
+            column_ids,
+            schema,
+            vec![0],
+            barrier_rx,
+            1,
+            1,
+            "SourceExecutor".to_string(),
+            Arc::new(StreamingMetrics::unused()),
+            u64::MAX,
+        )
+        .unwrap();
+        let mut source = Box::new(source_exec).execute();
+
+        let split = SplitImpl::Datagen(DatagenSplit {
+            split_index: 0,
+            split_num: 1,
+            start_offset: None,
+        });
+        let init_barrier = Barrier::new_test_barrier(1).with_mutation(Mutation::Add {
+            adds: HashMap::new(),
+            splits: hashmap! {
+                ActorId::default() => vec![split.clone()],
+            },
+            deferred_backfills: Default::default(),
+        });
+        barrier_tx.send(init_barrier).unwrap();
+        assert!(source.next().await.unwrap().unwrap().is_barrier());
+        let first_chunk = source.next().await.unwrap().unwrap().into_chunk().unwrap();
+
+        // Persist the offset after the first chunk.
+        barrier_tx.send(Barrier::new_test_barrier(2)).unwrap();
+        while !source.next().await.unwrap().unwrap().is_barrier() {}
+        source_state_handler.init_epoch(EpochPair::new_test_epoch(2));
+        let state = source_state_handler.get(split.id()).await.unwrap().unwrap();
+        assert!(state.into_datagen().unwrap().start_offset.is_some());
+
+        // Reset the split to the earliest offset, which overrides the persisted one.
+        let barrier =
+            Barrier::new_test_barrier(3).with_mutation(Mutation::SourceResetOffset(hashmap! {
+                ActorId::default() => vec![split.clone()],
+            }));
+        barrier_tx.send(barrier).unwrap();
+        while !source.next().await.unwrap().unwrap().is_barrier() {}
+        source_state_handler.init_epoch(EpochPair::new_test_epoch(3));
+        let state = source_state_handler.get(split.id()).await.unwrap().unwrap();
+        assert_eq!(state.into_datagen().unwrap().start_offset, None);
+
+        // The data is generated again from the start.
+        let chunk = source.next().await.unwrap().unwrap().into_chunk().unwrap();
+        assert_eq!(
+            chunk.drop_row_id().rows().next().unwrap().1.to_owned_row(),
+            first_chunk
+                .drop_row_id()
+                .rows()
+                .next()
+                .unwrap()
+                .1
+                .to_owned_row()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parked_source() {
+        let source_table_id = TableId::default();
+        let source_builder = mock_source_desc_builder(source_table_id);
+        let mem_state_store = MemoryStateStore::new();
+
+        let column_ids = vec![ColumnId::from(0), ColumnId::from(1)];
+        let schema = Schema::new(vec![
+            Field::unnamed(DataType::Int64),
+            Field::unnamed(DataType::Int32),
+        ]);
+        let (barrier_tx, barrier_rx) = unbounded_channel::<Barrier>();
+        let vnodes = Bitmap::from_bytes(Bytes::from_static(&[0b11111111]));
+        let source_state_handler = SourceStateTableHandler::from_table_catalog(
+            &default_source_internal_table(0x2333),
+            mem_state_store.clone(),
+        );
+        let metrics = Arc::new(StreamingMetrics::unused());
+        let parked_actors = metrics.source_parked_actors.with_label_values(&["Table_0"]);
+
+        let source_exec = SourceExecutor::new(
+            ActorContext::create(0),
+            source_builder,
+            source_table_id,
+            vnodes,
+            source_state_handler,
+            column_ids,
+            schema,
+            vec![0],
+            barrier_rx,
+            1,
+            1,
+            "SourceExecutor".to_string(),
+            metrics.clone(),
+            u64::MAX,
+        )
+        .unwrap();
+        let mut source = Box::new(source_exec).execute();
+
+        // There are fewer splits than actors, and this actor is assigned no split.
+        let init_barrier = Barrier::new_test_barrier(1).with_mutation(Mutation::Add {
+            adds: HashMap::new(),
+            splits: hashmap! {
+                1 => vec![
+                    SplitImpl::Datagen(DatagenSplit {
+                        split_index: 0,
+                        split_num: 1,
+                        start_offset: None,
+                    }),
+                ],
+            },
+            deferred_backfills: Default::default(),
+        });
+        barrier_tx.send(init_barrier).unwrap();
+        source
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_barrier()
+            .unwrap();
+        assert_eq!(parked_actors.get(), 1);
+
+        // The parked actor doesn't read anything.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(source.next().now_or_never().is_none());
+
+        // Unpark the actor after a rebalance assigns the split to it.
+        let split = SplitImpl::Datagen(DatagenSplit {
+            split_index: 0,
+            split_num: 1,
+            start_offset: None,
+        });
+        let barrier =
+            Barrier::new_test_barrier(2).with_mutation(Mutation::SourceChangeSplit(hashmap! {
+                ActorId::default() => vec![split],
+                1 => vec![],
+            }));
+        barrier_tx.send(barrier).unwrap();
+        source
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_barrier()
+            .unwrap();
+        assert_eq!(parked_actors.get(), 0);
+        source.next().await.unwrap().unwrap().into_chunk().unwrap();
+
+        // Park the actor again after the split is moved away.
+        let barrier =
+            Barrier::new_test_barrier(3).with_mutation(Mutation::SourceChangeSplit(hashmap! {
+                ActorId::default() => vec![],
+            }));
+        barrier_tx.send(barrier).unwrap();
+        while !source.next().await.unwrap().unwrap().is_barrier() {}
+        assert_eq!(parked_actors.get(), 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(source.next().now_or_never().is_none());
+
+        // Dropping the parked executor resets the metric.
+        drop(source);
+        assert_eq!(parked_actors.get(), 0);
+    }
 }
@@ -14,6 +14,7 @@
 
 use std::ops::Deref;
 
+use anyhow::anyhow;
 use bytes::Bytes;
 use risingwave_common::array::Row;
 use risingwave_common::bail;
@@ -96,6 +97,45 @@ impl<S: StateStore> SourceStateTableHandler<S> {
         Ok(())
     }
 
+    /// The watermark states are stored along with the splits, so that they move with the splits
+    /// when the source is rescheduled.
+    fn watermark_key(split_id: &SplitId, col_idx: usize) -> SplitId {
+        format!("{}:watermark:{}", split_id, col_idx).into()
+    }
+
+    /// Persists the max value seen in the watermark column at `col_idx` by the reader of the
+    /// split, in microseconds since the unix epoch.
+    pub async fn set_watermark_max_seen(
+        &mut self,
+        split_id: &SplitId,
+        col_idx: usize,
+        max_seen_us: i64,
+    ) -> StreamExecutorResult<()> {
+        self.set(
+            Self::watermark_key(split_id, col_idx),
+            Bytes::from(max_seen_us.to_string()),
+        )
+        .await
+    }
+
+    pub async fn get_watermark_max_seen(
+        &self,
+        split_id: &SplitId,
+        col_idx: usize,
+    ) -> StreamExecutorResult<Option<i64>> {
+        Ok(
+            match self.get(Self::watermark_key(split_id, col_idx)).await? {
+                None => None,
+                Some(row) => match row.0.get(1).unwrap() {
+                    Some(ScalarImpl::Utf8(s)) => Some(s.parse().map_err(|e| {
+                        anyhow!("invalid watermark state of split {}: {}", split_id, e)
+                    })?),
+                    _ => unreachable!(),
+                },
+            },
+        )
+    }
+
     ///
     pub async fn try_recover_from_state_store(
         &mut self,
@@ -216,4 +256,57 @@ pub(crate) mod tests {
         }
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_watermark_state() -> StreamExecutorResult<()> {
+        let store = MemoryStateStore::new();
+        let mut state_table_handler = SourceStateTableHandler::from_table_catalog(
+            &default_source_internal_table(0x2333),
+            store,
+        );
+        let split_impl = SplitImpl::Kafka(KafkaSplit::new(0, Some(0), None, "test".into()));
+        let split_id = split_impl.id();
+
+        state_table_handler.init_epoch(EpochPair::new_test_epoch(1));
+        state_table_handler
+            .take_snapshot(vec![split_impl.clone()])
+            .await?;
+        state_table_handler
+            .set_watermark_max_seen(&split_id, 1, 10000)
+            .await?;
+        state_table_handler
+            .state_store
+            .commit(EpochPair::new_test_epoch(2))
+            .await?;
+        state_table_handler
+            .set_watermark_max_seen(&split_id, 1, 12000)
+            .await?;
+        state_table_handler
+            .state_store
+            .commit(EpochPair::new_test_epoch(3))
+            .await?;
+
+        assert_eq!(
+            state_table_handler
+                .get_watermark_max_seen(&split_id, 1)
+                .await?,
+            Some(12000)
+        );
+        assert_eq!(
+            state_table_handler
+                .get_watermark_max_seen(&split_id, 2)
+                .await?,
+            None
+        );
+        // The split state is kept apart from the watermark state.
+        assert_eq!(
+            state_table_handler
+                .try_recover_from_state_store(&split_impl)
+                .await?
+                .unwrap()
+                .encode_to_bytes(),
+            split_impl.encode_to_bytes()
+        );
+        Ok(())
+    }
 }
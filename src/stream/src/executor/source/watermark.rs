@@ -0,0 +1,273 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::NaiveDateTime;
+use risingwave_common::array::StreamChunk;
+use risingwave_common::buffer::BitmapBuilder;
+use risingwave_common::types::{
+    DataType, DatumRef, NaiveDateTimeWrapper, NaiveDateWrapper, ScalarImpl, ScalarRefImpl,
+};
+use risingwave_pb::catalog::WatermarkDesc;
+
+use crate::executor::Watermark;
+
+/// Generates a bounded-disorder watermark on a time column of a source, which lags behind the max
+/// value seen in the column by the allowed lateness. The rows older than the watermark are late
+/// and filtered out.
+#[derive(Debug)]
+pub struct BoundedDisorderWatermarkGenerator {
+    col_idx: usize,
+    /// The type of the column, one of `TIMESTAMP`, `DATE` and `TIMESTAMP WITH TIME ZONE`. No
+    /// watermark is generated on the other types.
+    data_type: DataType,
+    allowed_lateness_us: i64,
+    /// The max value seen in the column, in microseconds since the unix epoch.
+    max_seen_us: Option<i64>,
+    /// The max value seen last returned by [`Self::take_updated_max_seen_us`].
+    persisted_max_seen_us: Option<i64>,
+    /// The last watermark returned by [`Self::advance`].
+    emitted: Option<ScalarImpl>,
+}
+
+impl BoundedDisorderWatermarkGenerator {
+    pub fn new(desc: &WatermarkDesc, data_type: DataType) -> Self {
+        Self {
+            col_idx: desc.watermark_idx as usize,
+            data_type,
+            allowed_lateness_us: (desc.allowed_lateness_ms as i64).saturating_mul(1000),
+            max_seen_us: None,
+            persisted_max_seen_us: None,
+            emitted: None,
+        }
+    }
+
+    pub fn col_idx(&self) -> usize {
+        self.col_idx
+    }
+
+    /// Returns the max value seen in the column to persist, if it's updated since the last call.
+    pub fn take_updated_max_seen_us(&mut self) -> Option<i64> {
+        if self.max_seen_us == self.persisted_max_seen_us {
+            return None;
+        }
+        self.persisted_max_seen_us = self.max_seen_us;
+        self.max_seen_us
+    }
+
+    /// Recovers the watermark from the persisted max value seen in the column.
+    pub fn recover(&mut self, max_seen_us: i64) {
+        self.max_seen_us = Some(self.max_seen_us.map_or(max_seen_us, |m| m.max(max_seen_us)));
+        self.persisted_max_seen_us = self.max_seen_us;
+    }
+
+    /// The current watermark in microseconds since the unix epoch, or `None` if no row is seen.
+    pub fn watermark_us(&self) -> Option<i64> {
+        self.max_seen_us
+            .map(|max_seen_us| max_seen_us.saturating_sub(self.allowed_lateness_us))
+    }
+
+    /// Advances the watermark with the rows in `chunk`, and hides the late ones. Rows with a null
+    /// time are always kept.
+    pub fn apply(&mut self, chunk: StreamChunk) -> StreamChunk {
+        let chunk = chunk.compact();
+        let column = chunk.column_at(self.col_idx).array_ref().clone();
+        let mut visibility = BitmapBuilder::with_capacity(chunk.capacity());
+        let mut has_late = false;
+        for i in 0..chunk.capacity() {
+            let Some(time_us) = to_micros(&self.data_type, column.value_at(i)) else {
+                visibility.append(true);
+                continue;
+            };
+            let late = matches!(self.watermark_us(), Some(watermark_us) if time_us < watermark_us);
+            if !late {
+                self.max_seen_us = Some(self.max_seen_us.map_or(time_us, |m| m.max(time_us)));
+            }
+            has_late |= late;
+            visibility.append(!late);
+        }
+
+        if !has_late {
+            return chunk;
+        }
+        let (data_chunk, ops) = chunk.into_parts();
+        let (columns, _) = data_chunk.into_parts();
+        StreamChunk::new(ops, columns, Some(visibility.finish()))
+    }
+
+    /// Returns the watermark to emit if it's advanced since the last call.
+    pub fn advance(&mut self) -> Option<Watermark> {
+        let watermark = from_micros(&self.data_type, self.watermark_us()?)?;
+        if matches!(&self.emitted, Some(emitted) if *emitted >= watermark) {
+            return None;
+        }
+        self.emitted = Some(watermark.clone());
+        Some(Watermark::new(
+            self.col_idx,
+            self.data_type.clone(),
+            Some(watermark),
+        ))
+    }
+}
+
+fn to_micros(data_type: &DataType, datum: DatumRef<'_>) -> Option<i64> {
+    match (data_type, datum?) {
+        (DataType::Timestamp, ScalarRefImpl::NaiveDateTime(v)) => {
+            Some(v.0.timestamp() * 1_000_000 + v.0.timestamp_subsec_micros() as i64)
+        }
+        (DataType::Date, ScalarRefImpl::NaiveDate(v)) => {
+            Some(v.0.and_hms(0, 0, 0).timestamp() * 1_000_000)
+        }
+        // `TIMESTAMP WITH TIME ZONE` is stored as microseconds since the unix epoch.
+        (DataType::Timestampz, ScalarRefImpl::Int64(v)) => Some(v),
+        _ => None,
+    }
+}
+
+/// The value of the column at `time_us`, rounded down for `DATE`.
+fn from_micros(data_type: &DataType, time_us: i64) -> Option<ScalarImpl> {
+    let datetime = || {
+        NaiveDateTime::from_timestamp_opt(
+            time_us.div_euclid(1_000_000),
+            time_us.rem_euclid(1_000_000) as u32 * 1000,
+        )
+    };
+    match data_type {
+        DataType::Timestamp => Some(NaiveDateTimeWrapper::new(datetime()?).into()),
+        DataType::Date => Some(NaiveDateWrapper::new(datetime()?.date()).into()),
+        DataType::Timestampz => Some(ScalarImpl::Int64(time_us)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risingwave_common::array::stream_chunk::StreamChunkTestExt;
+
+    use super::*;
+
+    #[test]
+    fn test_bounded_disorder_watermark() {
+        let mut generator = BoundedDisorderWatermarkGenerator::new(
+            &WatermarkDesc {
+                source_id: 1,
+                watermark_idx: 1,
+                allowed_lateness_ms: 2,
+            },
+            DataType::Timestampz,
+        );
+        assert_eq!(generator.watermark_us(), None);
+
+        let chunk = StreamChunk::from_pretty(
+            " I I
+            + 1 10000
+            + 2 8000
+            + 3 .",
+        );
+        let chunk = generator.apply(chunk);
+        assert_eq!(chunk.cardinality(), 3);
+        assert_eq!(generator.watermark_us(), Some(8000));
+        assert_eq!(
+            generator.advance(),
+            Some(Watermark::new(
+                1,
+                DataType::Timestampz,
+                Some(ScalarImpl::Int64(8000))
+            ))
+        );
+        assert_eq!(generator.advance(), None);
+
+        // Rows earlier than the watermark are late, even if it's advanced in the same chunk.
+        let chunk = StreamChunk::from_pretty(
+            " I I
+            + 4 7999
+            + 5 13000
+            + 6 10000
+            + 7 11000",
+        );
+        let chunk = generator.apply(chunk);
+        assert_eq!(
+            chunk,
+            StreamChunk::from_pretty(
+                " I I
+                + 4 7999 D
+                + 5 13000
+                + 6 10000 D
+                + 7 11000",
+            )
+        );
+        assert_eq!(generator.watermark_us(), Some(11000));
+    }
+
+    #[test]
+    fn test_watermark_on_non_time_column() {
+        let mut generator = BoundedDisorderWatermarkGenerator::new(
+            &WatermarkDesc {
+                source_id: 1,
+                watermark_idx: 1,
+                allowed_lateness_ms: 0,
+            },
+            DataType::Int64,
+        );
+        let chunk = StreamChunk::from_pretty(
+            " I I
+            + 1 10000
+            + 2 8000",
+        );
+        assert_eq!(generator.apply(chunk.clone()), chunk);
+        assert_eq!(generator.watermark_us(), None);
+        assert_eq!(generator.advance(), None);
+    }
+
+    #[test]
+    fn test_recover_watermark() {
+        let desc = WatermarkDesc {
+            source_id: 1,
+            watermark_idx: 0,
+            allowed_lateness_ms: 1000,
+        };
+        let mut generator = BoundedDisorderWatermarkGenerator::new(&desc, DataType::Timestamp);
+        let chunk = StreamChunk::from_pretty(
+            " TS
+            + 2022-11-01T00:00:10",
+        );
+        generator.apply(chunk);
+        let max_seen_us = generator.take_updated_max_seen_us().unwrap();
+        assert_eq!(generator.take_updated_max_seen_us(), None);
+
+        // The rows late to the recovered watermark are still filtered out.
+        let mut generator = BoundedDisorderWatermarkGenerator::new(&desc, DataType::Timestamp);
+        generator.recover(max_seen_us);
+        let chunk = StreamChunk::from_pretty(
+            " TS
+            + 2022-11-01T00:00:08
+            + 2022-11-01T00:00:09",
+        );
+        assert_eq!(
+            generator.apply(chunk),
+            StreamChunk::from_pretty(
+                " TS
+                + 2022-11-01T00:00:08 D
+                + 2022-11-01T00:00:09",
+            )
+        );
+        assert_eq!(
+            generator.advance(),
+            Some(Watermark::new(
+                0,
+                DataType::Timestamp,
+                Some(NaiveDateTimeWrapper::new("2022-11-01T00:00:09".parse().unwrap()).into())
+            ))
+        );
+    }
+}
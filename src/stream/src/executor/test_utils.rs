@@ -18,7 +18,7 @@ use risingwave_common::catalog::Schema;
 use tokio::sync::mpsc;
 
 use super::error::StreamExecutorError;
-use super::{Barrier, Executor, Message, PkIndices, StreamChunk};
+use super::{Barrier, Executor, Message, PkIndices, StreamChunk, Watermark};
 
 pub struct MockSource {
     schema: Schema,
@@ -46,6 +46,11 @@ impl MessageSender {
         }
         self.0.send(Message::Barrier(barrier)).unwrap();
     }
+
+    #[allow(dead_code)]
+    pub fn push_watermark(&mut self, watermark: Watermark) {
+        self.0.send(Message::Watermark(watermark)).unwrap();
+    }
 }
 
 impl std::fmt::Debug for MockSource {
@@ -214,7 +219,7 @@ pub mod agg_executor {
             | AggKind::Sum
             | AggKind::Count
             | AggKind::Avg
-            | AggKind::ApproxCountDistinct => {
+            | AggKind::ApproxCountDistinct(_) => {
                 AggStateStorage::ResultValue
             }
             _ => {
@@ -254,12 +259,15 @@ pub mod agg_executor {
             add_column_desc(agg_call.return_type.clone());
         });
 
-        StateTable::new_without_distribution(
+        // The result table stores the group key in its primary key, and only the agg results in
+        // its value, the same as the one built by the frontend.
+        StateTable::new_without_distribution_partial(
             store,
             table_id,
             column_descs,
             order_types,
             (0..group_key_indices.len()).collect(),
+            (group_key_indices.len()..group_key_indices.len() + agg_calls.len()).collect(),
         )
     }
 
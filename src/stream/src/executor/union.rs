@@ -80,9 +80,7 @@ pub fn merge(inputs: Vec<BoxedMessageStream>) -> BoxedMessageStream {
             #[for_await]
             for item in input {
                 match item? {
-                    Message::Watermark(_) => {
-                        todo!("https://github.com/risingwavelabs/risingwave/issues/6042")
-                    }
+                    Message::Watermark(_) => {}
                     msg @ Message::Chunk(_) => yield msg,
                     msg @ Message::Barrier(_) => {
                         if barrier.wait().await.is_leader() {
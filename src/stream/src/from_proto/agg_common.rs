@@ -31,7 +31,7 @@ pub fn build_agg_call_from_prost(
     append_only: bool,
     agg_call_proto: &risingwave_pb::expr::AggCall,
 ) -> StreamResult<AggCall> {
-    let agg_kind = AggKind::try_from(agg_call_proto.get_type()?)?;
+    let agg_kind = AggKind::from_protobuf(agg_call_proto)?;
     let args = match &agg_call_proto.get_args()[..] {
         [] => AggArgs::None,
         [arg] if agg_kind != AggKind::StringAgg => AggArgs::Unary(
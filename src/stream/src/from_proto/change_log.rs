@@ -0,0 +1,31 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::executor::ChangeLogExecutor;
+
+pub struct ChangeLogExecutorBuilder;
+
+impl ExecutorBuilder for ChangeLogExecutorBuilder {
+    fn new_boxed_executor(
+        params: ExecutorParams,
+        node: &StreamNode,
+        _store: impl StateStore,
+        _stream: &mut LocalStreamManagerCore,
+    ) -> StreamResult<BoxedExecutor> {
+        try_match_expand!(node.get_node_body().unwrap(), NodeBody::ChangeLog)?;
+        let [input]: [_; 1] = params.input.try_into().unwrap();
+        Ok(ChangeLogExecutor::new(input, params.executor_id).boxed())
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+
+use super::*;
+use crate::executor::DedupExecutor;
+
+pub struct DedupExecutorBuilder;
+
+impl ExecutorBuilder for DedupExecutorBuilder {
+    fn new_boxed_executor(
+        params: ExecutorParams,
+        node: &StreamNode,
+        store: impl StateStore,
+        stream: &mut LocalStreamManagerCore,
+    ) -> StreamResult<BoxedExecutor> {
+        let node = try_match_expand!(node.get_node_body().unwrap(), NodeBody::Dedup)?;
+        let [input]: [_; 1] = params.input.try_into().unwrap();
+
+        let vnodes = params.vnode_bitmap.map(Arc::new);
+        let state_table = StateTable::from_table_catalog(node.get_state_table()?, store, vnodes);
+        let dedup_col_indices = node
+            .dedup_column_indices
+            .iter()
+            .map(|idx| *idx as usize)
+            .collect();
+        let retention_col_idx = node
+            .retention
+            .as_ref()
+            .map(|retention| retention.column_index as usize);
+
+        Ok(DedupExecutor::new(
+            params.actor_context,
+            input,
+            state_table,
+            dedup_col_indices,
+            retention_col_idx,
+            params.executor_id,
+            stream.config.developer.unsafe_stream_dedup_cache_size,
+            stream.context.lru_manager.clone(),
+        )
+        .boxed())
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_expr::expr::build_from_prost;
+use risingwave_expr::expr::build_optimized_from_prost;
 
 use super::*;
 use crate::executor::FilterExecutor;
@@ -28,7 +28,7 @@ impl ExecutorBuilder for FilterExecutorBuilder {
     ) -> StreamResult<BoxedExecutor> {
         let node = try_match_expand!(node.get_node_body().unwrap(), NodeBody::Filter)?;
         let [input]: [_; 1] = params.input.try_into().unwrap();
-        let search_condition = build_from_prost(node.get_search_condition()?)?;
+        let search_condition = build_optimized_from_prost(node.get_search_condition()?)?;
 
         Ok(FilterExecutor::new(
             params.actor_context,
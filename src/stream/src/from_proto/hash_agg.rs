@@ -15,6 +15,7 @@
 //! Global Streaming Hash Aggregators
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use risingwave_common::hash::{HashKey, HashKeyDispatcher};
 use risingwave_common::types::DataType;
@@ -23,6 +24,7 @@ use risingwave_storage::table::streaming_table::state_table::StateTable;
 use super::agg_common::{build_agg_call_from_prost, build_agg_state_storages_from_proto};
 use super::*;
 use crate::cache::LruManagerRef;
+use crate::common::StatePrefetchConfig;
 use crate::executor::aggregation::{AggCall, AggStateStorage};
 use crate::executor::monitor::StreamingMetrics;
 use crate::executor::{ActorContextRef, HashAggExecutor, PkIndices};
@@ -42,6 +44,7 @@ pub struct HashAggExecutorDispatcherArgs<S: StateStore> {
     lru_manager: Option<LruManagerRef>,
     metrics: Arc<StreamingMetrics>,
     chunk_size: usize,
+    state_prefetch: StatePrefetchConfig,
 }
 
 impl<S: StateStore> HashKeyDispatcher for HashAggExecutorDispatcherArgs<S> {
@@ -62,6 +65,7 @@ impl<S: StateStore> HashKeyDispatcher for HashAggExecutorDispatcherArgs<S> {
             self.lru_manager,
             self.metrics,
             self.chunk_size,
+            self.state_prefetch,
         )?
         .boxed())
     }
@@ -124,6 +128,12 @@ impl ExecutorBuilder for HashAggExecutorBuilder {
             lru_manager: stream.context.lru_manager.clone(),
             metrics: params.executor_stats,
             chunk_size: params.env.config().developer.stream_chunk_size,
+            state_prefetch: StatePrefetchConfig {
+                max_bytes: stream.config.developer.stream_state_prefetch_bytes,
+                deadline: Duration::from_millis(
+                    stream.config.developer.stream_state_prefetch_deadline_ms,
+                ),
+            },
         };
         args.dispatch()
     }
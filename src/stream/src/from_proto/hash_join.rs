@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use risingwave_common::hash::{HashKey, HashKeyDispatcher};
 use risingwave_common::types::DataType;
@@ -22,6 +23,7 @@ use risingwave_storage::table::streaming_table::state_table::StateTable;
 
 use super::*;
 use crate::cache::LruManagerRef;
+use crate::common::StatePrefetchConfig;
 use crate::executor::hash_join::*;
 use crate::executor::monitor::StreamingMetrics;
 use crate::executor::{ActorContextRef, PkIndices};
@@ -121,6 +123,12 @@ impl ExecutorBuilder for HashJoinExecutorBuilder {
             join_type_proto: node.get_join_type()?,
             join_key_data_types,
             chunk_size: params.env.config().developer.stream_chunk_size,
+            state_prefetch: StatePrefetchConfig {
+                max_bytes: stream.config.developer.stream_state_prefetch_bytes,
+                deadline: Duration::from_millis(
+                    stream.config.developer.stream_state_prefetch_deadline_ms,
+                ),
+            },
         };
 
         args.dispatch()
@@ -150,6 +158,7 @@ struct HashJoinExecutorDispatcherArgs<S: StateStore> {
     join_type_proto: JoinTypeProto,
     join_key_data_types: Vec<DataType>,
     chunk_size: usize,
+    state_prefetch: StatePrefetchConfig,
 }
 
 impl<S: StateStore> HashKeyDispatcher for HashJoinExecutorDispatcherArgs<S> {
@@ -181,6 +190,7 @@ impl<S: StateStore> HashKeyDispatcher for HashJoinExecutorDispatcherArgs<S> {
                         self.is_append_only,
                         self.metrics,
                         self.chunk_size,
+                        self.state_prefetch,
                     ),
                 ))
             };
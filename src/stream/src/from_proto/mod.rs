@@ -17,6 +17,8 @@
 mod agg_common;
 mod batch_query;
 mod chain;
+mod change_log;
+mod dedup;
 mod dynamic_filter;
 mod expand;
 mod filter;
@@ -30,6 +32,8 @@ mod lookup;
 mod lookup_union;
 mod merge;
 mod mview;
+mod now;
+mod over_window;
 mod project;
 mod project_set;
 mod sink;
@@ -47,6 +51,8 @@ use risingwave_storage::StateStore;
 
 use self::batch_query::*;
 use self::chain::*;
+use self::change_log::*;
+use self::dedup::*;
 use self::dynamic_filter::*;
 use self::expand::*;
 use self::filter::*;
@@ -60,6 +66,8 @@ use self::lookup::*;
 use self::lookup_union::*;
 use self::merge::*;
 use self::mview::*;
+use self::now::*;
+use self::over_window::*;
 use self::project::*;
 use self::project_set::*;
 use self::sink::*;
@@ -129,5 +137,9 @@ pub fn create_executor(
         NodeBody::DynamicFilter => DynamicFilterExecutorBuilder,
         NodeBody::ProjectSet => ProjectSetExecutorBuilder,
         NodeBody::GroupTopN => GroupTopNExecutorBuilder,
+        NodeBody::Dedup => DedupExecutorBuilder,
+        NodeBody::ChangeLog => ChangeLogExecutorBuilder,
+        NodeBody::Now => NowExecutorBuilder,
+        NodeBody::OverWindow => OverWindowExecutorBuilder,
     }
 }
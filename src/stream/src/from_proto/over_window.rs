@@ -0,0 +1,66 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use risingwave_expr::vector_op::agg::AggStateFactory;
+use risingwave_pb::stream_plan::over_window_node;
+use risingwave_storage::table::streaming_table::state_table::StateTable;
+
+use super::*;
+use crate::executor::{OverWindowExecutor, WindowFunction};
+
+pub struct OverWindowExecutorBuilder;
+
+impl ExecutorBuilder for OverWindowExecutorBuilder {
+    fn new_boxed_executor(
+        params: ExecutorParams,
+        node: &StreamNode,
+        store: impl StateStore,
+        stream: &mut LocalStreamManagerCore,
+    ) -> StreamResult<BoxedExecutor> {
+        let node = try_match_expand!(node.get_node_body().unwrap(), NodeBody::OverWindow)?;
+        let [input]: [_; 1] = params.input.try_into().unwrap();
+
+        let vnodes = params.vnode_bitmap.map(Arc::new);
+        let state_table = StateTable::from_table_catalog(node.get_state_table()?, store, vnodes);
+        let partition_key_indices = node.partition_by.iter().map(|idx| *idx as usize).collect();
+        let window_function = match node.window_function.as_ref().unwrap() {
+            over_window_node::WindowFunction::Aggregate(aggregate) => {
+                let frame = aggregate.get_frame()?;
+                WindowFunction::Aggregate {
+                    call: AggStateFactory::new(aggregate.get_call()?)?,
+                    start_offset: frame.start_offset,
+                    end_offset: frame.end_offset,
+                }
+            }
+            over_window_node::WindowFunction::ValueAt(value_at) => WindowFunction::ValueAt {
+                arg_idx: value_at.arg_index as usize,
+                offset: value_at.offset,
+            },
+        };
+
+        Ok(OverWindowExecutor::new(
+            params.actor_context,
+            input,
+            state_table,
+            partition_key_indices,
+            window_function,
+            params.executor_id,
+            stream.config.developer.unsafe_stream_over_window_cache_size,
+            stream.context.lru_manager.clone(),
+        )
+        .boxed())
+    }
+}
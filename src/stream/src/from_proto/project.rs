@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use risingwave_expr::expr::build_from_prost;
+use risingwave_expr::expr::build_optimized_from_prost;
+use risingwave_pb::expr::expr_node::RexNode;
 
 use super::*;
 use crate::executor::ProjectExecutor;
@@ -31,8 +32,19 @@ impl ExecutorBuilder for ProjectExecutorBuilder {
         let project_exprs: Vec<_> = node
             .get_select_list()
             .iter()
-            .map(build_from_prost)
+            .map(build_optimized_from_prost)
             .try_collect()?;
+        let watermark_derivations = node
+            .get_select_list()
+            .iter()
+            .enumerate()
+            .filter_map(|(output_idx, expr)| match expr.get_rex_node() {
+                Ok(RexNode::InputRef(input_ref)) => {
+                    Some((input_ref.column_idx as usize, output_idx))
+                }
+                _ => None,
+            })
+            .collect();
 
         Ok(ProjectExecutor::new(
             params.actor_context,
@@ -41,6 +53,7 @@ impl ExecutorBuilder for ProjectExecutorBuilder {
             project_exprs,
             params.executor_id,
         )
+        .with_watermark_derivations(watermark_derivations)
         .boxed())
     }
 }
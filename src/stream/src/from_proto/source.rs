@@ -71,21 +71,24 @@ impl ExecutorBuilder for SourceExecutorBuilder {
         let state_table_handler =
             SourceStateTableHandler::from_table_catalog(node.state_table.as_ref().unwrap(), store);
 
-        Ok(Box::new(SourceExecutor::new(
-            params.actor_context,
-            source_builder,
-            source_id,
-            vnodes,
-            state_table_handler,
-            column_ids,
-            schema,
-            params.pk_indices,
-            barrier_receiver,
-            params.executor_id,
-            params.operator_id,
-            params.op_info,
-            params.executor_stats,
-            stream.config.barrier_interval_ms as u64,
-        )?))
+        Ok(Box::new(
+            SourceExecutor::new(
+                params.actor_context,
+                source_builder,
+                source_id,
+                vnodes,
+                state_table_handler,
+                column_ids,
+                schema,
+                params.pk_indices,
+                barrier_receiver,
+                params.executor_id,
+                params.operator_id,
+                params.op_info,
+                params.executor_stats,
+                stream.config.barrier_interval_ms as u64,
+            )?
+            .with_watermark_descs(&node.watermark_descs),
+        ))
     }
 }
@@ -33,6 +33,7 @@ use risingwave_pb::{stream_plan, stream_service};
 use risingwave_storage::{dispatch_state_store, StateStore, StateStoreImpl};
 use tokio::sync::mpsc::{channel, Receiver};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
 use super::{unique_executor_id, unique_operator_id, CollectResult};
 use crate::error::StreamResult;
@@ -232,6 +233,11 @@ impl LocalStreamManager {
             .expect("no rx for local mode")
             .await
             .context("failed to collect barrier")?;
+        // The state tables flush the mem-tables sealed in this epoch in the background, and the
+        // epoch can only be synced after they are ingested.
+        let flush_tracker =
+            dispatch_state_store!(self.state_store(), store, { store.flush_tracker().clone() });
+        flush_tracker.collect_epoch(epoch).await?;
         complete_receiver
             .barrier_inflight_timer
             .expect("no timer for test")
@@ -429,6 +435,7 @@ impl LocalStreamManagerCore {
         input: BoxedExecutor,
         dispatchers: &[stream_plan::Dispatcher],
         actor_id: ActorId,
+        fragment_id: FragmentId,
     ) -> StreamResult<DispatchExecutor> {
         let dispatcher_impls = dispatchers
             .iter()
@@ -439,6 +446,7 @@ impl LocalStreamManagerCore {
             input,
             dispatcher_impls,
             actor_id,
+            fragment_id,
             self.context.clone(),
             self.streaming_metrics.clone(),
         ))
@@ -470,6 +478,8 @@ impl LocalStreamManagerCore {
                     | NodeBody::Chain(_)
                     | NodeBody::DynamicFilter(_)
                     | NodeBody::GroupTopN(_)
+                    | NodeBody::Dedup(_)
+                    | NodeBody::OverWindow(_)
             )
         }
         let is_stateful = is_stateful_executor(node);
@@ -576,7 +586,8 @@ impl LocalStreamManagerCore {
         for &actor_id in actors {
             let actor = self.actors.remove(&actor_id).unwrap();
             let mview_definition = &actor.mview_definition;
-            let actor_context = ActorContext::create(actor_id);
+            let fragment_id = actor.fragment_id;
+            let actor_context = ActorContext::create_with_fragment(actor_id, actor.fragment_id);
             let vnode_bitmap = actor
                 .vnode_bitmap
                 .as_ref()
@@ -592,7 +603,8 @@ impl LocalStreamManagerCore {
                 vnode_bitmap,
             )?;
 
-            let dispatcher = self.create_dispatcher(executor, &actor.dispatcher, actor_id)?;
+            let dispatcher =
+                self.create_dispatcher(executor, &actor.dispatcher, actor_id, actor.fragment_id)?;
             let actor = Actor::new(
                 dispatcher,
                 subtasks,
@@ -608,12 +620,16 @@ impl LocalStreamManagerCore {
                 .map(|(m, _)| m.register(actor_id));
 
             let handle = {
+                // The events of the actor are tagged with the span, to enable verbose logging for
+                // the actor at runtime.
+                let actor_span = tracing::info_span!("actor", actor_id, fragment_id);
                 let actor = async move {
                     let _ = actor.run().await.inspect_err(|err| {
                         // TODO: check error type and panic if it's unexpected.
                         tracing::error!(actor=%actor_id, error=%err, "actor exit");
                     });
-                };
+                }
+                .instrument(actor_span);
                 #[auto_enums::auto_enum(Future)]
                 let traced = match trace_reporter {
                     Some(trace_reporter) => trace_reporter.trace(
@@ -749,6 +765,8 @@ impl LocalStreamManagerCore {
         }
         self.actor_monitor_tasks.clear();
         self.context.actor_infos.write().clear();
+        // The state tables of the actors abort their flushes in progress when dropped.
+        dispatch_state_store!(&self.state_store, store, { store.flush_tracker().reset() });
     }
 
     fn update_actors(
@@ -12,14 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use clap::Parser;
 use futures::future::BoxFuture;
+use madsim::net::NetSim;
 use madsim::rand::thread_rng;
 use madsim::runtime::{Handle, NodeHandle};
+use madsim::task::JoinHandle;
 use rand::seq::SliceRandom;
 
 use crate::RisingWave;
@@ -34,6 +38,10 @@ pub struct Configuration {
     #[clap(long, default_value = "3")]
     compute_nodes: usize,
 
+    /// The number of compute nodes started with `--role serving`, taken from the last ones.
+    #[clap(long, default_value = "0")]
+    serving_nodes: usize,
+
     /// The number of compactor nodes.
     #[clap(long, default_value = "1")]
     compactor_nodes: usize,
@@ -43,8 +51,19 @@ pub struct Configuration {
     /// This determines worker_node_parallelism.
     #[clap(long, default_value = "2")]
     compute_node_cores: usize,
+
+    /// Start the frontend nodes in read-only standby mode.
+    #[clap(long)]
+    frontend_read_only_standby: bool,
+
+    /// The path of the config file of the compute nodes. Empty to use the default config.
+    #[clap(long, default_value = "")]
+    compute_node_config_path: String,
 }
 
+/// The interval to poll the sink table in [`Cluster::drain_and_verify_sink`].
+const DRAIN_INTERVAL: Duration = Duration::from_secs(1);
+
 impl Default for Configuration {
     fn default() -> Self {
         Self::parse_from::<_, &str>([])
@@ -54,7 +73,16 @@ impl Default for Configuration {
 pub struct Cluster {
     frontends: Vec<IpAddr>,
 
-    _handle: Handle,
+    meta_node: NodeHandle,
+    frontend_nodes: Vec<NodeHandle>,
+    /// The names of the compute nodes by IP address.
+    compute_node_names: HashMap<IpAddr, String>,
+    compactor_nodes: usize,
+    /// The meta backup to restore from when the meta node is restarted next time. See
+    /// [`Cluster::restore_meta_from_backup`].
+    meta_restore_from: Arc<Mutex<Option<u64>>>,
+
+    handle: Handle,
     pub(crate) client: NodeHandle,
     pub(crate) ctl: NodeHandle,
 }
@@ -72,19 +100,30 @@ impl Cluster {
         std::env::set_var("RW_META_ADDR", format!("https://{meta}:5690/"));
 
         // meta node
-        handle
+        let meta_restore_from = Arc::new(Mutex::new(None));
+        let restore_from = meta_restore_from.clone();
+        let meta_node = handle
             .create_node()
             .name("meta")
             .ip(meta)
-            .init(|| async {
-                let opts = risingwave_meta::MetaNodeOpts::parse_from([
-                    "meta-node",
-                    "--listen-addr",
-                    "0.0.0.0:5690",
-                    "--backend",
-                    "mem",
-                ]);
-                risingwave_meta::start(opts).await
+            .init(move || {
+                let restore_from = restore_from.lock().unwrap().map(|id| id.to_string());
+                async move {
+                    let mut args = vec![
+                        "meta-node",
+                        "--listen-addr",
+                        "0.0.0.0:5690",
+                        "--backend",
+                        "mem",
+                        "--backup-storage-url",
+                        "memory-shared",
+                    ];
+                    if let Some(restore_from) = &restore_from {
+                        args.extend(["--restore-from", restore_from]);
+                    }
+                    let opts = risingwave_meta::MetaNodeOpts::parse_from(args);
+                    risingwave_meta::start(opts).await
+                }
             })
             .build();
         // wait for the service to be ready
@@ -92,48 +131,75 @@ impl Cluster {
 
         // frontend node
         let mut frontends = vec![];
+        let mut frontend_nodes = vec![];
+        let read_only_standby = conf.frontend_read_only_standby;
         for i in 1..=conf.frontend_nodes {
             let frontend_ip = format!("192.168.2.{i}").parse().unwrap();
             frontends.push(frontend_ip);
-            handle
+            let node = handle
                 .create_node()
                 .name(format!("frontend-{i}"))
                 .ip([192, 168, 2, i as u8].into())
                 .init(move || async move {
-                    let opts = risingwave_frontend::FrontendOpts::parse_from([
+                    let client_address = format!("{frontend_ip}:4566");
+                    let meta_addr = format!("{meta}:5690");
+                    let mut args = vec![
                         "frontend-node",
                         "--host",
                         "0.0.0.0:4566",
                         "--client-address",
-                        &format!("{frontend_ip}:4566"),
+                        &client_address,
                         "--meta-addr",
-                        &format!("{meta}:5690"),
-                    ]);
+                        &meta_addr,
+                    ];
+                    if read_only_standby {
+                        args.push("--read-only-standby");
+                    }
+                    let opts = risingwave_frontend::FrontendOpts::parse_from(args);
                     risingwave_frontend::start(opts).await
                 })
                 .build();
+            frontend_nodes.push(node);
         }
 
         // compute node
+        let mut compute_node_names = HashMap::new();
         for i in 1..=conf.compute_nodes {
+            let compute_ip = [192, 168, 3, i as u8].into();
+            compute_node_names.insert(compute_ip, format!("compute-{i}"));
+            let role = if i + conf.serving_nodes > conf.compute_nodes {
+                "serving"
+            } else {
+                "both"
+            };
             handle
                 .create_node()
                 .name(format!("compute-{i}"))
-                .ip([192, 168, 3, i as u8].into())
+                .ip(compute_ip)
                 .cores(conf.compute_node_cores)
-                .init(move || async move {
-                    let opts = risingwave_compute::ComputeNodeOpts::parse_from([
-                        "compute-node",
-                        "--host",
-                        "0.0.0.0:5688",
-                        "--client-address",
-                        &format!("192.168.3.{i}:5688"),
-                        "--meta-address",
-                        &format!("{meta}:5690"),
-                        "--state-store",
-                        "hummock+memory-shared",
-                    ]);
-                    risingwave_compute::start(opts).await
+                .init({
+                    let config_path = conf.compute_node_config_path.clone();
+                    move || {
+                        let config_path = config_path.clone();
+                        async move {
+                            let opts = risingwave_compute::ComputeNodeOpts::parse_from([
+                                "compute-node",
+                                "--host",
+                                "0.0.0.0:5688",
+                                "--client-address",
+                                &format!("192.168.3.{i}:5688"),
+                                "--meta-address",
+                                &format!("{meta}:5690"),
+                                "--state-store",
+                                "hummock+memory-shared",
+                                "--role",
+                                role,
+                                "--config-path",
+                                &config_path,
+                            ]);
+                            risingwave_compute::start(opts).await
+                        }
+                    }
                 })
                 .build();
         }
@@ -180,7 +246,12 @@ impl Cluster {
 
         Ok(Self {
             frontends,
-            _handle: handle,
+            meta_node,
+            frontend_nodes,
+            compute_node_names,
+            compactor_nodes: conf.compactor_nodes,
+            meta_restore_from,
+            handle,
             client,
             ctl,
         })
@@ -190,6 +261,59 @@ impl Cluster {
         Box::pin(Self::start_inner(conf))
     }
 
+    /// The name of the compute node with the given IP address.
+    pub fn compute_node_name(&self, ip: &IpAddr) -> Option<&str> {
+        self.compute_node_names.get(ip).map(|name| name.as_str())
+    }
+
+    /// Get the handle of the simulation runtime, which can be used to kill and restart nodes.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Partition the network between the frontend nodes and the meta node. The frontend nodes can
+    /// still reach the compute nodes.
+    pub fn disconnect_frontends_from_meta(&self) {
+        let net = NetSim::current();
+        for frontend in &self.frontend_nodes {
+            net.disconnect2(frontend.id(), self.meta_node.id());
+        }
+    }
+
+    /// Heal the partition made by [`Cluster::disconnect_frontends_from_meta`].
+    pub fn reconnect_frontends_to_meta(&self) {
+        let net = NetSim::current();
+        for frontend in &self.frontend_nodes {
+            net.connect2(frontend.id(), self.meta_node.id());
+        }
+    }
+
+    /// Restart the meta node with an empty meta store restored from the meta backup `backup_id`,
+    /// together with all other nodes, as their states are newer than the backup.
+    pub async fn restore_meta_from_backup(&mut self, backup_id: u64) -> Result<()> {
+        let mut nodes = vec!["meta".to_string()];
+        nodes.extend((1..=self.frontend_nodes.len()).map(|i| format!("frontend-{i}")));
+        nodes.extend(self.compute_node_names.values().cloned());
+        nodes.extend((1..=self.compactor_nodes).map(|i| format!("compactor-{i}")));
+        for node in &nodes {
+            self.handle.kill(node);
+        }
+
+        *self.meta_restore_from.lock().unwrap() = Some(backup_id);
+        self.handle.restart("meta");
+        // wait for the service to be ready
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        // Restarting the meta node again should not restore the backup again.
+        *self.meta_restore_from.lock().unwrap() = None;
+
+        for node in &nodes[1..] {
+            self.handle.restart(node);
+        }
+        // wait for the service to be ready
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok(())
+    }
+
     async fn run_inner(&mut self, sql: String) -> Result<String> {
         let frontend = self
             .frontends
@@ -215,6 +339,56 @@ impl Cluster {
         Box::pin(self.run_inner(sql.to_string()))
     }
 
+    /// Run the statements one by one in a single session in the background, and return the
+    /// output of each one. Unlike [`Cluster::run`], the returned handle doesn't borrow the cluster,
+    /// so the statements can run concurrently with other operations, e.g. rescheduling.
+    pub fn spawn_run_in_session(&self, sqls: Vec<String>) -> JoinHandle<Result<Vec<String>>> {
+        let frontend = self
+            .frontends
+            .choose(&mut thread_rng())
+            .unwrap()
+            .to_string();
+
+        self.client.spawn(async move {
+            let mut session = RisingWave::connect(frontend, "dev".to_string()).await;
+            let mut outputs = Vec::with_capacity(sqls.len());
+            for sql in &sqls {
+                outputs.push(session.run(sql).await?);
+            }
+            session.close().await;
+            Ok::<_, anyhow::Error>(outputs)
+        })
+    }
+
+    async fn run_with_notices_inner(&mut self, sqls: Vec<String>) -> Result<Vec<String>> {
+        let frontend = self
+            .frontends
+            .choose(&mut thread_rng())
+            .unwrap()
+            .to_string();
+
+        let notices = self
+            .client
+            .spawn(async move {
+                let mut session = RisingWave::connect(frontend, "dev".to_string()).await;
+                for sql in &sqls {
+                    session.run(sql).await?;
+                }
+                let notices = session.take_notices();
+                session.close().await;
+                Ok::<_, anyhow::Error>(notices)
+            })
+            .await??;
+
+        Ok(notices)
+    }
+
+    /// Run the statements in a single session, and return the notices received.
+    pub fn run_with_notices(&mut self, sqls: &[&str]) -> BoxFuture<'_, Result<Vec<String>>> {
+        let sqls = sqls.iter().map(|sql| sql.to_string()).collect();
+        Box::pin(self.run_with_notices_inner(sqls))
+    }
+
     async fn wait_until_inner(
         &mut self,
         sql: String,
@@ -267,4 +441,49 @@ impl Cluster {
     ) -> BoxFuture<'_, Result<String>> {
         Box::pin(self.wait_until_non_empty_inner(sql.to_string(), interval, timeout))
     }
+
+    async fn drain_and_verify_sink_inner(
+        &mut self,
+        sink_table: String,
+        unique_key: String,
+        expected_row_count: usize,
+        timeout: Duration,
+    ) -> Result<()> {
+        let result = self
+            .wait_until_inner(
+                format!("SELECT {unique_key} FROM {sink_table};"),
+                move |r| r.lines().count() >= expected_row_count,
+                DRAIN_INTERVAL,
+                timeout,
+            )
+            .await
+            .map_err(|e| {
+                anyhow!("{sink_table} doesn't reach {expected_row_count} rows in time: {e}")
+            })?;
+
+        let mut keys = HashSet::new();
+        if let Some(key) = result.lines().find(|key| !keys.insert(*key)) {
+            bail!("{sink_table} contains duplicated key ({unique_key}): {key}");
+        }
+        Ok(())
+    }
+
+    /// Poll the materialized view `sink_table` until it contains at least `expected_row_count`
+    /// rows within `timeout`, then check that no two rows share the same `unique_key`, i.e., every
+    /// row has been delivered exactly once. `unique_key` is a comma-separated list of columns that
+    /// uniquely identifies a row of the view.
+    pub fn drain_and_verify_sink(
+        &mut self,
+        sink_table: &str,
+        unique_key: &str,
+        expected_row_count: usize,
+        timeout: Duration,
+    ) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.drain_and_verify_sink_inner(
+            sink_table.to_string(),
+            unique_key.to_string(),
+            expected_row_count,
+            timeout,
+        ))
+    }
 }
@@ -16,7 +16,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::sync::Arc;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use clap::Parser;
 use futures::future::BoxFuture;
 use itertools::Itertools;
@@ -24,9 +24,11 @@ use madsim::rand::thread_rng;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::Rng;
 use risingwave_common::types::ParallelUnitId;
+use risingwave_pb::common::{WorkerNode, WorkerRole};
 use risingwave_pb::meta::table_fragments::fragment::FragmentDistributionType;
-use risingwave_pb::meta::table_fragments::Fragment as ProstFragment;
-use risingwave_pb::meta::GetClusterInfoResponse;
+use risingwave_pb::meta::table_fragments::{Fragment as ProstFragment, State};
+use risingwave_pb::meta::{GetClusterInfoResponse, MetaBackupInfo};
+use risingwave_pb::stream_plan::stream_node::NodeBody;
 use risingwave_pb::stream_plan::StreamNode;
 
 use self::predicate::BoxedPredicate;
@@ -116,6 +118,51 @@ impl Fragment {
         self.inner.fragment_id
     }
 
+    /// The ids of the worker nodes that the actors of the fragment are on.
+    pub fn worker_ids(&self) -> HashSet<u32> {
+        let actor_to_worker: HashMap<_, _> = self
+            .r
+            .table_fragments
+            .iter()
+            .flat_map(|tf| {
+                tf.actor_status.iter().map(|(&actor_id, status)| {
+                    (actor_id, status.get_parallel_unit().unwrap().worker_node_id)
+                })
+            })
+            .collect();
+        self.inner
+            .actors
+            .iter()
+            .map(|a| actor_to_worker[&a.actor_id])
+            .collect()
+    }
+
+    /// The number of source splits assigned to the actors of the fragment.
+    pub fn split_count(&self) -> usize {
+        let actor_ids: HashSet<_> = self.inner.actors.iter().map(|a| a.actor_id).collect();
+        self.r
+            .table_fragments
+            .iter()
+            .flat_map(|tf| tf.actor_splits.iter())
+            .filter(|(actor_id, _)| actor_ids.contains(actor_id))
+            .map(|(_, splits)| splits.splits.len())
+            .sum()
+    }
+
+    /// The id of the state table of the source executor in the fragment, which stores the offsets
+    /// of the splits read by the actors, if any.
+    pub fn source_state_table_id(&self) -> Option<u32> {
+        fn find(node: &StreamNode) -> Option<u32> {
+            if let Some(NodeBody::Source(source)) = &node.node_body {
+                if let Some(table) = &source.state_table {
+                    return Some(table.id);
+                }
+            }
+            node.input.iter().find_map(find)
+        }
+        find(self.inner.actors.first()?.nodes.as_ref()?)
+    }
+
     /// Generate a reschedule plan for the fragment.
     pub fn reschedule(
         &self,
@@ -136,10 +183,8 @@ impl Fragment {
         f
     }
 
-    /// Generate a random reschedule plan for the fragment.
-    ///
-    /// Consumes `self` as the actor info will be stale after rescheduling.
-    pub fn random_reschedule(self) -> String {
+    /// All parallel units that actors can be scheduled on, and the ones of the fragment.
+    fn parallel_units(&self) -> (Vec<ParallelUnitId>, HashSet<ParallelUnitId>) {
         let actor_to_parallel_unit: HashMap<_, _> = self
             .r
             .table_fragments
@@ -155,6 +200,7 @@ impl Fragment {
             .r
             .worker_nodes
             .iter()
+            .filter(|n| n.role() != WorkerRole::Serving)
             .flat_map(|n| n.parallel_units.iter())
             .map(|p| p.id)
             .collect_vec();
@@ -164,6 +210,33 @@ impl Fragment {
             .iter()
             .map(|a| actor_to_parallel_unit[&a.actor_id])
             .collect();
+        (all_parallel_units, current_parallel_units)
+    }
+
+    /// Generate a reschedule plan from the current parallel units of the fragment to the target
+    /// ones.
+    fn reschedule_to(
+        &self,
+        current_parallel_units: &HashSet<ParallelUnitId>,
+        target_parallel_units: &HashSet<ParallelUnitId>,
+    ) -> String {
+        let remove = current_parallel_units
+            .difference(target_parallel_units)
+            .copied()
+            .collect_vec();
+        let add = target_parallel_units
+            .difference(current_parallel_units)
+            .copied()
+            .collect_vec();
+
+        self.reschedule(remove, add)
+    }
+
+    /// Generate a random reschedule plan for the fragment.
+    ///
+    /// Consumes `self` as the actor info will be stale after rescheduling.
+    pub fn random_reschedule(self) -> String {
+        let (all_parallel_units, current_parallel_units) = self.parallel_units();
 
         let rng = &mut thread_rng();
         let target_parallel_unit_count = match self.inner.distribution_type() {
@@ -176,16 +249,71 @@ impl Fragment {
             .copied()
             .collect();
 
-        let remove = current_parallel_units
-            .difference(&target_parallel_units)
-            .copied()
-            .collect_vec();
-        let add = target_parallel_units
-            .difference(&current_parallel_units)
-            .copied()
-            .collect_vec();
+        self.reschedule_to(&current_parallel_units, &target_parallel_units)
+    }
 
-        self.reschedule(remove, add)
+    /// Generate a reschedule plan to move the actors of the fragment to the worker nodes they are
+    /// not on, as if they were migrated from a failed worker node on recovery.
+    ///
+    /// Consumes `self` as the actor info will be stale after rescheduling.
+    pub fn migrate_to_other_workers(self) -> Result<String> {
+        let (_, current_parallel_units) = self.parallel_units();
+        let current_workers = self.worker_ids();
+        let target_parallel_units: HashSet<_> = self
+            .r
+            .worker_nodes
+            .iter()
+            .filter(|n| n.role() != WorkerRole::Serving && !current_workers.contains(&n.id))
+            .flat_map(|n| n.parallel_units.iter())
+            .map(|p| p.id)
+            .take(current_parallel_units.len())
+            .collect();
+        if target_parallel_units.len() < current_parallel_units.len() {
+            bail!(
+                "not enough parallel units on other worker nodes to migrate {} actors",
+                current_parallel_units.len()
+            );
+        }
+        Ok(self.reschedule_to(&current_parallel_units, &target_parallel_units))
+    }
+
+    /// Generate a reschedule plan to scale the fragment to `parallelism` parallel units, keeping
+    /// the current ones as far as possible. A singleton fragment is kept as is. Returns `None` if
+    /// the fragment is already at the parallelism.
+    ///
+    /// Consumes `self` as the actor info will be stale after rescheduling.
+    pub fn reschedule_to_parallelism(self, parallelism: usize) -> Result<Option<String>> {
+        let (all_parallel_units, current_parallel_units) = self.parallel_units();
+        if parallelism == 0 || parallelism > all_parallel_units.len() {
+            bail!(
+                "invalid parallelism {parallelism}, there are {} parallel units",
+                all_parallel_units.len()
+            );
+        }
+
+        let target_parallel_units: HashSet<_> = match self.inner.distribution_type() {
+            FragmentDistributionType::Unspecified => unreachable!(),
+            FragmentDistributionType::Single => current_parallel_units.clone(),
+            FragmentDistributionType::Hash => current_parallel_units
+                .iter()
+                .sorted()
+                .chain(
+                    all_parallel_units
+                        .iter()
+                        .filter(|p| !current_parallel_units.contains(p)),
+                )
+                .take(parallelism)
+                .copied()
+                .collect(),
+        };
+
+        if target_parallel_units == current_parallel_units {
+            return Ok(None);
+        }
+        Ok(Some(self.reschedule_to(
+            &current_parallel_units,
+            &target_parallel_units,
+        )))
     }
 }
 
@@ -299,4 +427,89 @@ impl Cluster {
     pub fn reschedule(&mut self, plan: String) -> BoxFuture<'_, Result<()>> {
         Box::pin(self.reschedule_inner(plan))
     }
+
+    /// List the compute nodes registered in the meta.
+    async fn list_worker_nodes_inner(&mut self) -> Result<Vec<WorkerNode>> {
+        let worker_nodes = self
+            .ctl
+            .spawn(async move {
+                let r = risingwave_ctl::cmd_impl::meta::get_cluster_info().await?;
+                Ok::<_, anyhow::Error>(r.worker_nodes)
+            })
+            .await??;
+
+        Ok(worker_nodes)
+    }
+
+    pub fn list_worker_nodes(&mut self) -> BoxFuture<'_, Result<Vec<WorkerNode>>> {
+        Box::pin(self.list_worker_nodes_inner())
+    }
+
+    /// Count the streaming jobs being created. Unlike `show cluster status`, this goes through
+    /// `risectl` rather than the frontends, so it works while they're disconnected from the meta.
+    async fn count_creating_streaming_jobs_inner(&mut self) -> Result<usize> {
+        let count = self
+            .ctl
+            .spawn(async move {
+                let r = risingwave_ctl::cmd_impl::meta::get_cluster_info().await?;
+                let count = r
+                    .table_fragments
+                    .iter()
+                    .filter(|tf| tf.state() == State::Creating)
+                    .count();
+                Ok::<_, anyhow::Error>(count)
+            })
+            .await??;
+
+        Ok(count)
+    }
+
+    pub fn count_creating_streaming_jobs(&mut self) -> BoxFuture<'_, Result<usize>> {
+        Box::pin(self.count_creating_streaming_jobs_inner())
+    }
+
+    /// List the inconsistencies in the fragment metadata with `risectl meta fix-fragments
+    /// --dry-run`, e.g. the splits of the actors not in the fragments.
+    async fn list_fragment_fixes_inner(&mut self) -> Result<Vec<String>> {
+        let fixes = self
+            .ctl
+            .spawn(async move {
+                risingwave_ctl::cmd_impl::meta::fix_fragments(true, false, false, false).await
+            })
+            .await??;
+
+        Ok(fixes)
+    }
+
+    pub fn list_fragment_fixes(&mut self) -> BoxFuture<'_, Result<Vec<String>>> {
+        Box::pin(self.list_fragment_fixes_inner())
+    }
+
+    /// Take a meta backup with `risectl meta backup create`.
+    async fn create_meta_backup_inner(&mut self) -> Result<MetaBackupInfo> {
+        let backup = self
+            .ctl
+            .spawn(async move { risingwave_ctl::cmd_impl::meta::create_backup().await })
+            .await??;
+
+        Ok(backup)
+    }
+
+    pub fn create_meta_backup(&mut self) -> BoxFuture<'_, Result<MetaBackupInfo>> {
+        Box::pin(self.create_meta_backup_inner())
+    }
+
+    /// List the meta backups ordered by id.
+    async fn list_meta_backups_inner(&mut self) -> Result<Vec<MetaBackupInfo>> {
+        let backups = self
+            .ctl
+            .spawn(async move { risingwave_ctl::cmd_impl::meta::get_backups().await })
+            .await??;
+
+        Ok(backups)
+    }
+
+    pub fn list_meta_backups(&mut self) -> BoxFuture<'_, Result<Vec<MetaBackupInfo>>> {
+        Box::pin(self.list_meta_backups_inner())
+    }
 }
@@ -19,6 +19,7 @@
 use std::time::Duration;
 
 use anyhow::Result;
+use futures::StreamExt;
 
 pub mod cluster;
 pub mod ctl_ext;
@@ -28,11 +29,12 @@ pub mod utils;
 struct RisingWave {
     client: tokio_postgres::Client,
     task: tokio::task::JoinHandle<()>,
+    notice_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
 }
 
 impl RisingWave {
     async fn connect(host: String, dbname: String) -> Self {
-        let (client, connection) = tokio_postgres::Config::new()
+        let (client, mut connection) = tokio_postgres::Config::new()
             .host(&host)
             .port(4566)
             .dbname(&dbname)
@@ -41,10 +43,31 @@ impl RisingWave {
             .connect(tokio_postgres::NoTls)
             .await
             .expect("Failed to connect to database");
+        let (notice_tx, notice_rx) = tokio::sync::mpsc::unbounded_channel();
         let task = tokio::spawn(async move {
-            connection.await.expect("Postgres connection error");
+            let mut messages = futures::stream::poll_fn(move |cx| connection.poll_message(cx));
+            while let Some(message) = messages.next().await {
+                if let tokio_postgres::AsyncMessage::Notice(notice) =
+                    message.expect("Postgres connection error")
+                {
+                    let _ = notice_tx.send(notice.message().to_string());
+                }
+            }
         });
-        RisingWave { client, task }
+        RisingWave {
+            client,
+            task,
+            notice_rx,
+        }
+    }
+
+    /// Takes the notices received so far in this session.
+    fn take_notices(&mut self) -> Vec<String> {
+        let mut notices = vec![];
+        while let Ok(notice) = self.notice_rx.try_recv() {
+            notices.push(notice);
+        }
+        notices
     }
 
     async fn run(&mut self, sql: &str) -> Result<String> {
@@ -12,20 +12,45 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
 use std::fmt::Write;
+use std::future::Future;
+use std::net::IpAddr;
 use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use futures::future::{join, join_all};
+use madsim::time::sleep;
+use risingwave_meta::manager::WorkerId;
 
 use crate::cluster::{Cluster, Configuration};
+use crate::ctl_ext::predicate;
 
 /// The target number of events of the three sources per second totally.
 pub const THROUGHPUT: usize = 10_000;
 
+/// How long a killed worker stays down in [`NexmarkCluster::run_with_failure_injection`].
+pub const FAILURE_DOWNTIME: Duration = Duration::from_secs(1);
+
+const REFERENCE_STABLE_INTERVAL: Duration = Duration::from_secs(5);
+const NON_EMPTY_INTERVAL: Duration = Duration::from_secs(1);
+const CONVERGE_TIMEOUT: Duration = Duration::from_secs(300);
+const DRAIN_INTERVAL: Duration = Duration::from_secs(1);
+/// The number of consecutive polls without any change of the source states for the sources to be
+/// drained.
+const DRAIN_STABLE_POLLS: usize = 5;
+
+/// The number of times the view is queried concurrently with the scaling in
+/// [`NexmarkCluster::run_scale_during_query`].
+const QUERIES_DURING_SCALE: usize = 10;
+
 /// Cluster for nexmark tests.
 pub struct NexmarkCluster {
     pub cluster: Cluster,
+
+    /// The total number of events of the sources, if bounded.
+    event_num: Option<usize>,
 }
 
 impl NexmarkCluster {
@@ -40,6 +65,7 @@ impl NexmarkCluster {
     ) -> Result<Self> {
         let mut cluster = Self {
             cluster: Cluster::start(conf).await?,
+            event_num,
         };
         cluster.create_nexmark_source(split_num, event_num).await?;
         Ok(cluster)
@@ -125,6 +151,251 @@ with (
     }
 }
 
+impl NexmarkCluster {
+    /// Wait until the bounded sources have been drained by all the materialized views, and the
+    /// views have processed all the events.
+    ///
+    /// The source executor of each view persists the offsets of its splits in its own state table
+    /// on checkpoints, so the source is drained once its state is non-empty and stays the same for
+    /// [`DRAIN_STABLE_POLLS`] consecutive polls. As checkpoints are consistent snapshots, the
+    /// views have processed all the events up to the committed offsets by then.
+    pub async fn drain_to_completion(&mut self, timeout: Duration) -> Result<()> {
+        if self.event_num.is_none() {
+            bail!("the nexmark sources are unbounded without `event_num`")
+        }
+        let state_table_ids = self
+            .locate_fragments(vec![])
+            .await?
+            .iter()
+            .filter_map(|f| f.source_state_table_id())
+            .collect::<Vec<_>>();
+        if state_table_ids.is_empty() {
+            bail!("no source to drain");
+        }
+
+        madsim::time::timeout(timeout, self.wait_drained(&state_table_ids))
+            .await
+            .map_err(|_| anyhow!("nexmark sources are not drained in {timeout:?}"))?
+    }
+
+    async fn wait_drained(&mut self, state_table_ids: &[u32]) -> Result<()> {
+        let mut interval = madsim::time::interval(DRAIN_INTERVAL);
+        let mut last_states = None;
+        let mut stable_polls = 0;
+        while stable_polls < DRAIN_STABLE_POLLS {
+            interval.tick().await;
+            let mut states = Vec::with_capacity(state_table_ids.len());
+            for table_id in state_table_ids {
+                states.push(
+                    self.run(&format!("SELECT * FROM rw_table({table_id}) ORDER BY 1;"))
+                        .await?,
+                );
+            }
+            let started = states.iter().all(|state| !state.trim().is_empty());
+            if started && last_states.as_ref() == Some(&states) {
+                stable_polls += 1;
+            } else {
+                stable_polls = 0;
+            }
+            last_states = Some(states);
+        }
+
+        Ok(())
+    }
+
+    /// Run `workload` while killing the workers in `failure_schedule` at the given time since the
+    /// start, and restarting them after [`FAILURE_DOWNTIME`]. After the workload completes, check
+    /// that every nexmark materialized view converges to the same result as a reference view,
+    /// which is created from the same definition without any failure.
+    ///
+    /// As the nexmark sources are deterministic, this verifies exactly-once processing under the
+    /// failures. The sources should be bounded with `event_num` for the results to converge.
+    pub async fn run_with_failure_injection<F>(
+        &mut self,
+        workload: F,
+        failure_schedule: Vec<(Duration, WorkerId)>,
+    ) -> Result<()>
+    where
+        F: Future<Output = Result<()>>,
+    {
+        let worker_nodes = self.list_worker_nodes().await?;
+        let mut failures = Vec::with_capacity(failure_schedule.len());
+        for (at, worker_id) in failure_schedule {
+            let worker = worker_nodes
+                .iter()
+                .find(|w| w.id == worker_id)
+                .ok_or_else(|| anyhow!("worker {worker_id} not found"))?;
+            let ip: IpAddr = worker.host.as_ref().unwrap().host.parse()?;
+            let name = self
+                .compute_node_name(&ip)
+                .ok_or_else(|| anyhow!("no compute node with ip {ip}"))?
+                .to_string();
+            let handle = self.handle().clone();
+            failures.push(async move {
+                sleep(at).await;
+                tracing::info!("kill {name} (worker {worker_id})");
+                handle.kill(&name);
+                sleep(FAILURE_DOWNTIME).await;
+                tracing::info!("restart {name} (worker {worker_id})");
+                handle.restart(&name);
+            });
+        }
+
+        let (result, _) = join(workload, join_all(failures)).await;
+        result?;
+
+        self.check_against_reference().await
+    }
+
+    /// Create the materialized view of the nexmark query `query`, i.e. the `CREATE` statement of
+    /// one of [`queries::ALL`], and scale all its reschedulable fragments to `new_parallelism`
+    /// parallel units while the view is being updated by the sources and queried in another
+    /// session. Returns the final result of the view.
+    ///
+    /// After the scaling, the view must converge to the same result as a reference view created
+    /// without scaling, i.e. no rows are lost or duplicated by the reschedule. The sources should
+    /// be bounded with `event_num` for the results to converge.
+    ///
+    /// The concurrent load is plain batch queries on the view, as `EXPLAIN ANALYZE` is not
+    /// supported by the frontend yet.
+    pub async fn run_scale_during_query(
+        &mut self,
+        query: &str,
+        new_parallelism: usize,
+    ) -> Result<String> {
+        let &(name, create, select) = queries::ALL
+            .iter()
+            .find(|(_, create, _)| *create == query)
+            .ok_or_else(|| anyhow!("not a nexmark query: {query}"))?;
+        self.run(create).await?;
+        self.wait_until_non_empty(select, NON_EMPTY_INTERVAL, CONVERGE_TIMEOUT)
+            .await?;
+
+        let plan = self
+            .locate_fragments(vec![predicate::can_reschedule()])
+            .await?
+            .into_iter()
+            .map(|f| f.reschedule_to_parallelism(new_parallelism))
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>>>()?
+            .join(";");
+        if plan.is_empty() {
+            bail!("no fragment of {name} to scale to {new_parallelism} parallel units");
+        }
+
+        let queries = self.spawn_run_in_session(vec![select.to_string(); QUERIES_DURING_SCALE]);
+        tracing::info!("scale {name} to {new_parallelism} parallel units: {plan}");
+        self.reschedule(plan).await?;
+        queries
+            .await?
+            .map_err(|e| anyhow!("failed to query {name} during scaling: {e}"))?;
+
+        self.check_against_reference().await?;
+        self.run(select).await
+    }
+
+    /// Check every nexmark materialized view against a reference view. See
+    /// [`NexmarkCluster::run_with_failure_injection`].
+    async fn check_against_reference(&mut self) -> Result<()> {
+        let mviews: HashSet<String> = self
+            .run("SHOW MATERIALIZED VIEWS;")
+            .await?
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        for &(name, create, select) in queries::ALL {
+            if !mviews.contains(name) {
+                continue;
+            }
+            let reference = format!("{name}_reference");
+            self.run(&create.replace(name, &reference)).await?;
+
+            // The reference result is ready when it doesn't change any more.
+            let mut last = None;
+            let expected = self
+                .wait_until(
+                    &select.replace(name, &reference),
+                    move |r| {
+                        let stable = !r.trim().is_empty() && last.as_deref() == Some(r);
+                        last = Some(r.to_string());
+                        stable
+                    },
+                    REFERENCE_STABLE_INTERVAL,
+                    CONVERGE_TIMEOUT,
+                )
+                .await?;
+
+            self.wait_until(
+                select,
+                move |r| r == expected,
+                REFERENCE_STABLE_INTERVAL,
+                CONVERGE_TIMEOUT,
+            )
+            .await
+            .map_err(|e| anyhow!("{name} doesn't converge to the reference result: {e}"))?;
+
+            self.run(&format!("DROP MATERIALIZED VIEW {reference};"))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl NexmarkCluster {
+    /// Create the materialized views of all nexmark queries concurrently, each in its own session,
+    /// then query all of them concurrently until none of them is empty. This stresses the meta
+    /// service with concurrent DDL.
+    ///
+    /// If any DDL fails, the error tells which views have been created successfully.
+    pub async fn run_all_queries_parallel(&mut self) -> Result<()> {
+        let this = &*self;
+
+        let results = join_all(
+            queries::ALL
+                .iter()
+                .map(|&(_, create, _)| this.spawn_run_in_session(vec![create.to_string()])),
+        )
+        .await;
+        let mut created = vec![];
+        let mut errors = vec![];
+        for (&(name, _, _), result) in queries::ALL.iter().zip(results) {
+            match result.map_err(anyhow::Error::from).and_then(|r| r) {
+                Ok(_) => created.push(name),
+                Err(e) => errors.push(format!("{name}: {e}")),
+            }
+        }
+        if !errors.is_empty() {
+            bail!(
+                "failed to create materialized views concurrently: [{}], created: {:?}",
+                errors.join(", "),
+                created
+            );
+        }
+
+        let results = join_all(queries::ALL.iter().map(|&(name, _, select)| async move {
+            let wait_non_empty = async {
+                let mut interval = madsim::time::interval(NON_EMPTY_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let output = this
+                        .spawn_run_in_session(vec![select.to_string()])
+                        .await??;
+                    if !output[0].trim().is_empty() {
+                        return Ok::<_, anyhow::Error>(());
+                    }
+                }
+            };
+            madsim::time::timeout(CONVERGE_TIMEOUT, wait_non_empty)
+                .await
+                .map_err(|_| anyhow!("{name} is still empty after {CONVERGE_TIMEOUT:?}"))?
+        }))
+        .await;
+        results.into_iter().collect()
+    }
+}
+
 impl Deref for NexmarkCluster {
     type Target = Cluster;
 
@@ -148,6 +419,8 @@ pub mod queries {
 
     pub mod q3 {
         use super::*;
+        pub const NAME: &str = "nexmark_q3";
+        pub const UNIQUE_KEY: &str = "id";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q3
 AS
@@ -170,6 +443,8 @@ DROP MATERIALIZED VIEW nexmark_q3;
 
     pub mod q4 {
         use super::*;
+        pub const NAME: &str = "nexmark_q4";
+        pub const UNIQUE_KEY: &str = "category";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q4
 AS
@@ -203,10 +478,12 @@ DROP MATERIALIZED VIEW nexmark_q4;
 
     pub mod q5 {
         use super::*;
+        pub const NAME: &str = "nexmark_q5";
+        pub const UNIQUE_KEY: &str = "auction, starttime";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q5
 AS
-SELECT AuctionBids.auction, AuctionBids.num FROM (
+SELECT AuctionBids.auction, AuctionBids.num, AuctionBids.starttime FROM (
   SELECT
     bid.auction,
     count(*) AS num,
@@ -236,7 +513,7 @@ JOIN (
 ON AuctionBids.starttime = MaxBids.starttime_c AND AuctionBids.num >= MaxBids.maxn;
 "#;
         pub const SELECT: &str = r#"
-SELECT * FROM nexmark_q5 ORDER BY auction;
+SELECT * FROM nexmark_q5 ORDER BY auction, starttime;
 "#;
         pub const DROP: &str = r#"
 DROP MATERIALIZED VIEW nexmark_q5;
@@ -247,6 +524,8 @@ DROP MATERIALIZED VIEW nexmark_q5;
 
     pub mod q7 {
         use super::*;
+        pub const NAME: &str = "nexmark_q7";
+        pub const UNIQUE_KEY: &str = "auction, bidder, date_time";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q7
 AS
@@ -282,6 +561,8 @@ DROP MATERIALIZED VIEW nexmark_q7;
 
     pub mod q8 {
         use super::*;
+        pub const NAME: &str = "nexmark_q8";
+        pub const UNIQUE_KEY: &str = "id, starttime";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q8
 AS
@@ -330,6 +611,8 @@ DROP MATERIALIZED VIEW nexmark_q8;
 
     pub mod q9 {
         use super::*;
+        pub const NAME: &str = "nexmark_q9";
+        pub const UNIQUE_KEY: &str = "id";
         pub const CREATE: &str = r#"
 CREATE MATERIALIZED VIEW nexmark_q9
 AS
@@ -353,4 +636,14 @@ DROP MATERIALIZED VIEW nexmark_q9;
         pub const INITIAL_INTERVAL: Duration = DEFAULT_INITIAL_INTERVAL;
         pub const INITIAL_TIMEOUT: Duration = DEFAULT_INITIAL_TIMEOUT;
     }
+
+    /// The materialized view name, `CREATE` and `SELECT` statements of all the queries.
+    pub const ALL: &[(&str, &str, &str)] = &[
+        (q3::NAME, q3::CREATE, q3::SELECT),
+        (q4::NAME, q4::CREATE, q4::SELECT),
+        (q5::NAME, q5::CREATE, q5::SELECT),
+        (q7::NAME, q7::CREATE, q7::SELECT),
+        (q8::NAME, q8::CREATE, q8::SELECT),
+        (q9::NAME, q9::CREATE, q9::SELECT),
+    ];
 }
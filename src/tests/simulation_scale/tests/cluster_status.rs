@@ -0,0 +1,79 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+
+const STATUS: &str = "show cluster status;";
+
+/// Returns the value of the row of the given category and name in the output of `STATUS`.
+fn value(status: &str, category: &str, name: &str) -> Option<String> {
+    status.lines().find_map(|line| {
+        let mut columns = line.splitn(3, ' ');
+        (columns.next() == Some(category) && columns.next() == Some(name))
+            .then(|| columns.next().unwrap_or_default().to_string())
+    })
+}
+
+fn committed_epoch(status: &str) -> u64 {
+    value(status, "checkpoint", "committed_epoch")
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[madsim::test]
+async fn test_cluster_status() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run("create table t (v int);").await?;
+    cluster
+        .run("create materialized view mv as select count(*) from t;")
+        .await?;
+
+    let status = cluster.run(STATUS).await?;
+    assert_eq!(
+        value(&status, "streaming_job", "created").as_deref(),
+        Some("2"),
+        "{status}"
+    );
+    assert_eq!(
+        value(&status, "worker", "192.168.3.1:5688").as_deref(),
+        Some("ComputeNode Running"),
+        "{status}"
+    );
+    let epoch = committed_epoch(&status);
+
+    // The checkpoint of a flush advances the committed epoch.
+    cluster.run("insert into t values (1);").await?;
+    cluster.run("flush;").await?;
+    let status = cluster.run(STATUS).await?;
+    assert!(committed_epoch(&status) > epoch, "{status}");
+
+    // The killed compute node is removed from the cluster when its heartbeat expires.
+    cluster.handle().kill("compute-1");
+    cluster
+        .wait_until(
+            STATUS,
+            |status| value(status, "worker", "192.168.3.1:5688").is_none(),
+            Duration::from_secs(10),
+            Duration::from_secs(600),
+        )
+        .await?;
+
+    Ok(())
+}
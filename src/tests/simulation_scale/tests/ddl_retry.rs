@@ -0,0 +1,77 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::utils::AssertResult;
+
+#[madsim::test]
+async fn test_create_mv_retried_across_partition() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run("create table t (v int);").await?;
+    cluster.run("insert into t values (1), (2), (3);").await?;
+    cluster.run("flush;").await?;
+
+    let create = cluster.spawn_run_in_session(vec![
+        "create materialized view mv as select sum(v) as s from t;".to_string(),
+    ]);
+
+    // Partition the frontend from meta once meta is creating the materialized view, which takes
+    // at least a barrier, so that the response is lost and the frontend retries the request.
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while cluster.count_creating_streaming_jobs().await? == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok::<_, anyhow::Error>(())
+    })
+    .await??;
+    cluster.disconnect_frontends_from_meta();
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    cluster.reconnect_frontends_to_meta();
+    create.await??;
+
+    // Exactly one healthy materialized view is created.
+    cluster
+        .run("show materialized views;")
+        .await?
+        .assert_result_eq("mv");
+    let status = cluster.run("show cluster status;").await?;
+    assert!(
+        status.lines().any(|line| line == "streaming_job created 2"),
+        "{status}"
+    );
+    assert!(
+        status
+            .lines()
+            .any(|line| line == "streaming_job creating 0"),
+        "{status}"
+    );
+    cluster
+        .run("select s from mv;")
+        .await?
+        .assert_result_eq("6");
+
+    cluster.run("insert into t values (4);").await?;
+    cluster.run("flush;").await?;
+    cluster
+        .run("select s from mv;")
+        .await?
+        .assert_result_eq("10");
+
+    Ok(())
+}
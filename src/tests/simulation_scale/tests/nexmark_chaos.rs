@@ -25,12 +25,16 @@ use risingwave_simulation_scale::ctl_ext::Fragment;
 use risingwave_simulation_scale::nexmark::{NexmarkCluster, THROUGHPUT};
 use risingwave_simulation_scale::utils::AssertResult;
 
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+
 /// Common code for Nexmark chaos tests.
 ///
 /// - If `MULTIPLE` is false, we'll randomly pick a single fragment and reschedule it twice.
 /// - If `MULTIPLE` is true, we'll randomly pick random number of fragments and reschedule them,
 ///   then pick another set to reschedule again.
 async fn nexmark_chaos_common_inner(
+    name: &'static str,
+    unique_key: &'static str,
     create: &'static str,
     select: &'static str,
     drop: &'static str,
@@ -42,7 +46,7 @@ async fn nexmark_chaos_common_inner(
     let mut cluster =
         NexmarkCluster::new(Configuration::default(), 6, Some(20 * THROUGHPUT)).await?;
     cluster.run(create).await?;
-    sleep(Duration::from_secs(30)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
     let final_result = cluster.run(select).await?;
     cluster.run(drop).await?;
     sleep(Duration::from_secs(5)).await;
@@ -82,14 +86,24 @@ async fn nexmark_chaos_common_inner(
         cluster.reschedule(fragment.random_reschedule()).await?;
     }
 
-    sleep(Duration::from_secs(50)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
 
+    cluster
+        .drain_and_verify_sink(
+            name,
+            unique_key,
+            final_result.lines().count(),
+            Duration::from_secs(10),
+        )
+        .await?;
     cluster.run(select).await?.assert_result_eq(&final_result);
 
     Ok(())
 }
 
 fn nexmark_chaos_common(
+    name: &'static str,
+    unique_key: &'static str,
     create: &'static str,
     select: &'static str,
     drop: &'static str,
@@ -99,6 +113,8 @@ fn nexmark_chaos_common(
     multiple: bool,
 ) -> BoxFuture<'static, Result<()>> {
     Box::pin(nexmark_chaos_common_inner(
+        name,
+        unique_key,
         create,
         select,
         drop,
@@ -119,6 +135,8 @@ macro_rules! test {
             async fn [< nexmark_chaos_ $query _single >]() -> Result<()> {
                 use risingwave_simulation_scale::nexmark::queries::$query::*;
                 nexmark_chaos_common(
+                    NAME,
+                    UNIQUE_KEY,
                     CREATE,
                     SELECT,
                     DROP,
@@ -134,6 +152,8 @@ macro_rules! test {
             async fn [< nexmark_chaos_ $query _multiple >]() -> Result<()> {
                 use risingwave_simulation_scale::nexmark::queries::$query::*;
                 nexmark_chaos_common(
+                    NAME,
+                    UNIQUE_KEY,
                     CREATE,
                     SELECT,
                     DROP,
@@ -0,0 +1,40 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::Configuration;
+use risingwave_simulation_scale::nexmark::{queries, NexmarkCluster, THROUGHPUT};
+
+#[madsim::test]
+async fn nexmark_parallel_ddl() -> Result<()> {
+    let mut cluster =
+        NexmarkCluster::new(Configuration::default(), 6, Some(20 * THROUGHPUT)).await?;
+    cluster.run_all_queries_parallel().await?;
+
+    let mviews = cluster.run("SHOW MATERIALIZED VIEWS;").await?;
+    assert_eq!(mviews.lines().count(), queries::ALL.len());
+
+    // The concurrently created views should be dropped cleanly.
+    for &(name, _, _) in queries::ALL {
+        cluster
+            .run(&format!("DROP MATERIALIZED VIEW {name};"))
+            .await?;
+    }
+    let mviews = cluster.run("SHOW MATERIALIZED VIEWS;").await?;
+    assert!(mviews.trim().is_empty(), "{mviews}");
+
+    Ok(())
+}
@@ -35,6 +35,9 @@ const RESULT: &str = r#"
 14 29586298.617934551636209094773
 "#;
 
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(120);
+
 async fn init() -> Result<NexmarkCluster> {
     let mut cluster =
         NexmarkCluster::new(Configuration::default(), 6, Some(20 * THROUGHPUT)).await?;
@@ -52,7 +55,15 @@ async fn wait_initial_data(cluster: &mut NexmarkCluster) -> Result<String> {
 async fn nexmark_q4_ref() -> Result<()> {
     let mut cluster = init().await?;
 
-    sleep(Duration::from_secs(25)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
+    cluster
+        .drain_and_verify_sink(
+            NAME,
+            UNIQUE_KEY,
+            RESULT.trim().lines().count(),
+            DRAIN_TIMEOUT,
+        )
+        .await?;
     cluster.run(SELECT).await?.assert_result_eq(RESULT);
 
     Ok(())
@@ -78,9 +89,16 @@ async fn nexmark_q4_common_inner(predicates: Vec<BoxedPredicate>) -> Result<()>
     cluster.run(SELECT).await?.assert_result_ne(RESULT);
     cluster.reschedule(format!("{id}-[2,3]+[0,1]")).await?;
 
-    sleep(Duration::from_secs(20)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
 
-    // 25~35s
+    cluster
+        .drain_and_verify_sink(
+            NAME,
+            UNIQUE_KEY,
+            RESULT.trim().lines().count(),
+            DRAIN_TIMEOUT,
+        )
+        .await?;
     cluster.run(SELECT).await?.assert_result_eq(RESULT);
 
     Ok(())
@@ -154,9 +172,16 @@ async fn nexmark_q4_cascade() -> Result<()> {
         .reschedule(format!("{id_1}-[2,4]+[0,1]; {id_2}-[3]+[0,4]"))
         .await?;
 
-    sleep(Duration::from_secs(20)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
 
-    // 25~35s
+    cluster
+        .drain_and_verify_sink(
+            NAME,
+            UNIQUE_KEY,
+            RESULT.trim().lines().count(),
+            DRAIN_TIMEOUT,
+        )
+        .await?;
     cluster.run(SELECT).await?.assert_result_eq(RESULT);
 
     Ok(())
@@ -193,8 +218,16 @@ async fn nexmark_q4_materialize_agg_cache_invalidation() -> Result<()> {
     // Note that there're only 5 groups, so if the parallel unit 0 doesn't invalidate the cache
     // correctly, it will yield the wrong result.
     cluster.reschedule(format!("{id}-[1,2,3,4,5]")).await?;
-    sleep(Duration::from_secs(20)).await;
+    cluster.drain_to_completion(COMPLETION_TIMEOUT).await?;
 
+    cluster
+        .drain_and_verify_sink(
+            NAME,
+            UNIQUE_KEY,
+            RESULT.trim().lines().count(),
+            DRAIN_TIMEOUT,
+        )
+        .await?;
     cluster.run(SELECT).await?.assert_result_eq(RESULT);
 
     Ok(())
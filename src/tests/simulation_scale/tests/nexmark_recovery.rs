@@ -0,0 +1,83 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use madsim::rand::thread_rng;
+use madsim::time::sleep;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use risingwave_simulation_scale::cluster::Configuration;
+use risingwave_simulation_scale::nexmark::{NexmarkCluster, THROUGHPUT};
+
+/// Create the materialized view, then kill and restart random compute nodes at random times while
+/// the sources are producing events.
+async fn nexmark_recovery_common_inner(create: &'static str) -> Result<()> {
+    let mut cluster =
+        NexmarkCluster::new(Configuration::default(), 6, Some(20 * THROUGHPUT)).await?;
+    cluster.run(create).await?;
+
+    let worker_ids = cluster
+        .list_worker_nodes()
+        .await?
+        .into_iter()
+        .map(|w| w.id)
+        .collect::<Vec<_>>();
+    let failure_schedule = {
+        let rng = &mut thread_rng();
+        (0..rng.gen_range(1..=3))
+            .map(|_| {
+                let at = Duration::from_secs(rng.gen_range(1..20));
+                (at, *worker_ids.choose(rng).unwrap())
+            })
+            .collect()
+    };
+
+    cluster
+        .run_with_failure_injection(
+            async {
+                sleep(Duration::from_secs(30)).await;
+                Ok(())
+            },
+            failure_schedule,
+        )
+        .await
+}
+
+fn nexmark_recovery_common(create: &'static str) -> BoxFuture<'static, Result<()>> {
+    Box::pin(nexmark_recovery_common_inner(create))
+}
+
+macro_rules! test {
+    ($query:ident) => {
+        paste::paste! {
+            #[madsim::test]
+            async fn [< nexmark_recovery_ $query >]() -> Result<()> {
+                use risingwave_simulation_scale::nexmark::queries::$query::*;
+                nexmark_recovery_common(CREATE).await
+            }
+        }
+    };
+}
+
+test!(q3);
+test!(q4);
+test!(q5);
+test!(q7);
+test!(q8);
+test!(q9);
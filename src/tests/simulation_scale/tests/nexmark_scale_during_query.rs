@@ -0,0 +1,52 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use risingwave_simulation_scale::cluster::Configuration;
+use risingwave_simulation_scale::nexmark::{NexmarkCluster, THROUGHPUT};
+
+/// Scale the materialized view in from 6 parallel units to 2, while the sources are producing
+/// events and the view is being queried.
+async fn nexmark_scale_during_query_common_inner(create: &'static str) -> Result<()> {
+    let mut cluster =
+        NexmarkCluster::new(Configuration::default(), 6, Some(20 * THROUGHPUT)).await?;
+    cluster.run_scale_during_query(create, 2).await?;
+    Ok(())
+}
+
+fn nexmark_scale_during_query_common(create: &'static str) -> BoxFuture<'static, Result<()>> {
+    Box::pin(nexmark_scale_during_query_common_inner(create))
+}
+
+macro_rules! test {
+    ($query:ident) => {
+        paste::paste! {
+            #[madsim::test]
+            async fn [< nexmark_scale_during_query_ $query >]() -> Result<()> {
+                use risingwave_simulation_scale::nexmark::queries::$query::*;
+                nexmark_scale_during_query_common(CREATE).await
+            }
+        }
+    };
+}
+
+test!(q3);
+test!(q4);
+test!(q5);
+test!(q7);
+test!(q8);
+test!(q9);
@@ -0,0 +1,116 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use anyhow::Result;
+use itertools::Itertools;
+use madsim::time::Instant;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::ctl_ext::predicate::identity_contains;
+
+const ROWS: i64 = 1000;
+const GROUPS: i64 = 100;
+const QUERIES: usize = 100;
+
+/// The point get on the distribution key of `mv`.
+fn point_get(k: i64) -> String {
+    format!("select s from mv where k = {k};")
+}
+
+fn expected(k: i64) -> String {
+    let s: i64 = (0..ROWS).filter(|i| i % GROUPS == k).sum();
+    format!("{s}\n")
+}
+
+async fn init() -> Result<Cluster> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run("create table t (k bigint, v bigint);").await?;
+    cluster
+        .run("create materialized view mv as select k, sum(v) as s from t group by k;")
+        .await?;
+    let values = (0..ROWS)
+        .map(|i| format!("({}, {})", i % GROUPS, i))
+        .join(", ");
+    cluster
+        .run(&format!("insert into t values {values};"))
+        .await?;
+    cluster.run("flush;").await?;
+    Ok(cluster)
+}
+
+/// The statements that run `QUERIES` point gets in distributed query mode.
+fn point_gets(local_point_get: bool) -> Vec<String> {
+    let mut sqls = vec![
+        "set query_mode to distributed;".to_string(),
+        format!("set rw_batch_local_point_get to {local_point_get};"),
+    ];
+    sqls.extend((0..QUERIES).map(|i| point_get(i as i64 % GROUPS)));
+    sqls
+}
+
+fn check_point_gets(outputs: &[String]) {
+    for (i, output) in outputs.iter().skip(2).enumerate() {
+        assert_eq!(output, &expected(i as i64 % GROUPS), "query {i}");
+    }
+}
+
+/// The point gets executed in local mode skip the scheduling of the distributed query, so they
+/// should take less time than those in distributed mode.
+#[madsim::test]
+async fn test_point_get_latency() -> Result<()> {
+    let cluster = init().await?;
+
+    let mut latencies = vec![];
+    for local_point_get in [false, true] {
+        let start = Instant::now();
+        let outputs = cluster
+            .spawn_run_in_session(point_gets(local_point_get))
+            .await??;
+        latencies.push(start.elapsed() / QUERIES as u32);
+        check_point_gets(&outputs);
+    }
+
+    let [distributed, local]: [_; 2] = latencies.try_into().unwrap();
+    assert!(
+        local < distributed,
+        "local point get ({local:?}) is not faster than distributed one ({distributed:?})"
+    );
+
+    Ok(())
+}
+
+/// The point gets should return correct results while the vnodes of `mv` are moved between the
+/// compute nodes, whether they're executed in local mode or fall back to distributed mode.
+#[madsim::test]
+async fn test_point_get_during_reschedule() -> Result<()> {
+    let mut cluster = init().await?;
+
+    let id = cluster
+        .locate_one_fragment(vec![
+            identity_contains("materialize"),
+            identity_contains("hashagg"),
+        ])
+        .await?
+        .id();
+
+    for _ in 0..10 {
+        let queries = cluster.spawn_run_in_session(point_gets(true));
+        let plan = cluster.locate_fragment_by_id(id).await?.random_reschedule();
+        cluster.reschedule(plan).await?;
+        check_point_gets(&queries.await??);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use madsim::time::Instant;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::utils::AssertResult;
+
+const SELECT: &str = "select sum(v) from t;";
+const STATUS: &str =
+    "select connected, read_only_standby from rw_catalog.rw_meta_connection_status;";
+
+#[madsim::test]
+async fn test_read_only_standby_partitioned_from_meta() -> Result<()> {
+    let conf = Configuration::parse_from(["", "--frontend-read-only-standby"]);
+    let mut cluster = Cluster::start(conf).await?;
+
+    cluster.run("create table t (v int);").await?;
+    cluster.run("insert into t values (1), (2), (3);").await?;
+    cluster.run("flush;").await?;
+    cluster.run(SELECT).await?.assert_result_eq("6");
+    cluster.run(STATUS).await?.assert_result_eq("t t");
+
+    cluster.disconnect_frontends_from_meta();
+    let partitioned_at = Instant::now();
+
+    // The frontend detects the partition by the idle notification stream.
+    cluster
+        .wait_until(
+            STATUS,
+            |r| r.trim() == "f t",
+            Duration::from_secs(1),
+            Duration::from_secs(20),
+        )
+        .await?;
+
+    while partitioned_at.elapsed() < Duration::from_secs(30) {
+        cluster.run(SELECT).await?.assert_result_eq("6");
+
+        let start = Instant::now();
+        let err = cluster
+            .run("create table t2 (v int);")
+            .await
+            .expect_err("DDL should be rejected while meta is unavailable");
+        assert!(
+            err.to_string().contains("Meta service unavailable"),
+            "unexpected error: {err}"
+        );
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "DDL should fail fast"
+        );
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    cluster.reconnect_frontends_to_meta();
+    cluster
+        .wait_until(
+            STATUS,
+            |r| r.trim() == "t t",
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        )
+        .await?;
+    cluster.run("create table t2 (v int);").await?;
+    cluster.run(SELECT).await?.assert_result_eq("6");
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::utils::AssertResult;
+
+const ROW_COUNT: usize = 100000;
+
+const CREATE_MV: &str = "create materialized view m as select count(*) as c, sum(t1.v) as s from t1 join t2 on t1.v = t2.v;";
+const SELECT_MV: &str = "select c, s from m;";
+const PROGRESS: &str = "select upstream_table_id, phase from rw_catalog.rw_backfill_progress;";
+
+/// The phases of each upstream table observed while creating the mview.
+#[derive(Default)]
+struct ObservedPhases {
+    /// The max number of upstream tables backfilling at the same time.
+    max_backfilling: usize,
+    /// The upstream tables observed pending.
+    pending: usize,
+    /// The number of polls.
+    polls: usize,
+}
+
+async fn create_upstreams(cluster: &mut Cluster) -> Result<()> {
+    for table in ["t1", "t2"] {
+        cluster
+            .run(&format!("create table {table} (v int);"))
+            .await?;
+        cluster
+            .run(&format!(
+                "insert into {table} select * from generate_series(1, {ROW_COUNT}, 1);"
+            ))
+            .await?;
+    }
+    cluster.run("flush;").await?;
+    Ok(())
+}
+
+/// Create the mview joining the two upstream tables with the given backfill order, and poll the
+/// backfill progress of each upstream table until the creation finishes.
+async fn create_mv(cluster: &mut Cluster, sequential: bool) -> Result<ObservedPhases> {
+    let mut create = cluster.spawn_run_in_session(vec![
+        format!("set rw_streaming_sequential_backfill to {sequential};"),
+        CREATE_MV.to_string(),
+    ]);
+
+    let mut observed = ObservedPhases::default();
+    loop {
+        if let Ok(result) = madsim::time::timeout(Duration::from_millis(100), &mut create).await {
+            result??;
+            return Ok(observed);
+        }
+
+        let progress = cluster.run(PROGRESS).await?;
+        let phases = progress
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .collect::<HashMap<_, _>>();
+        let backfilling = phases.values().filter(|&&p| p == "BACKFILLING").count();
+        observed.max_backfilling = observed.max_backfilling.max(backfilling);
+        observed.pending += phases.values().filter(|&&p| p == "PENDING").count();
+        observed.polls += 1;
+    }
+}
+
+/// The storage read rate of the backfill is proportional to the number of upstream tables
+/// backfilling at the same time, since each chain reads the snapshot of its upstream table as
+/// fast as it can. The storage metrics are not reachable in the simulation, so we check the
+/// number of concurrently backfilling upstream tables instead: the sequential backfill reads from
+/// one of the two upstream tables at a time, i.e. half of the concurrent one.
+#[madsim::test]
+async fn test_sequential_backfill() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    create_upstreams(&mut cluster).await?;
+
+    // Each phase lasts at least a barrier interval, so the polls must have observed them.
+    let concurrent = create_mv(&mut cluster, false).await?;
+    let expected = cluster.run(SELECT_MV).await?;
+    assert!(concurrent.polls > 0, "the backfill should be observed");
+    assert_eq!(
+        concurrent.max_backfilling, 2,
+        "both upstream tables should be backfilling at the same time"
+    );
+    assert_eq!(concurrent.pending, 0, "no upstream table should be pending");
+    cluster.run("drop materialized view m;").await?;
+
+    let sequential = create_mv(&mut cluster, true).await?;
+    assert!(sequential.polls > 0, "the backfill should be observed");
+    assert_eq!(
+        sequential.max_backfilling, 1,
+        "one upstream table should be backfilling at a time"
+    );
+    assert!(
+        sequential.pending > 0,
+        "the second upstream table should wait for the first one"
+    );
+
+    // The results should be the same regardless of the backfill order.
+    cluster.run(SELECT_MV).await?.assert_result_eq(&expected);
+    cluster
+        .run(SELECT_MV)
+        .await?
+        .assert_result_eq(&format!("{ROW_COUNT} {}", ROW_COUNT * (ROW_COUNT + 1) / 2));
+
+    Ok(())
+}
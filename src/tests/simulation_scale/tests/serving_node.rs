@@ -0,0 +1,60 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use anyhow::Result;
+use clap::Parser;
+use itertools::Itertools;
+use risingwave_pb::common::WorkerRole;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+
+#[madsim::test]
+async fn test_no_actor_on_serving_node() -> Result<()> {
+    let conf = Configuration::parse_from(["simulation", "--serving-nodes", "1"]);
+    let mut cluster = Cluster::start(conf).await?;
+    cluster.run("create table t (v int);").await?;
+    cluster
+        .run("create materialized view mv as select v, count(*) from t group by v;")
+        .await?;
+    cluster.run("insert into t values (1), (2), (3);").await?;
+    cluster.run("flush;").await?;
+
+    // The role is visible in the worker nodes.
+    let workers = cluster.list_worker_nodes().await?;
+    let serving_workers = workers
+        .iter()
+        .filter(|worker| worker.role() == WorkerRole::Serving)
+        .map(|worker| worker.id)
+        .collect_vec();
+    assert_eq!(serving_workers.len(), 1, "{workers:#?}");
+    let serving_worker = serving_workers[0];
+
+    // No actor is on the serving node.
+    let fragments = cluster.locate_fragments(vec![]).await?;
+    assert!(!fragments.is_empty());
+    for fragment in fragments {
+        assert!(
+            !fragment.worker_ids().contains(&serving_worker),
+            "fragment {} is on the serving node",
+            fragment.id()
+        );
+    }
+
+    // The batch queries still work.
+    let result = cluster.run("select count(*) from mv;").await?;
+    assert_eq!(result.trim(), "3");
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+
+const ROW_COUNT: usize = 30;
+
+const CREATE_SOURCE: &str = "create source s (v1 int) with (
+    connector = 'datagen',
+    fields.v1.kind = 'sequence',
+    fields.v1.start = '1',
+    fields.v1.end = '30',
+    datagen.rows.per.second = '30',
+    datagen.split.num = '3'
+) row format json;";
+const SELECT_MV: &str = "select c, d from mv;";
+
+async fn wait_until_count(cluster: &mut Cluster, count: usize, distinct: usize) -> Result<()> {
+    let expected = format!("{count} {distinct}");
+    cluster
+        .wait_until(
+            SELECT_MV,
+            move |output| output.trim() == expected,
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        )
+        .await?;
+    Ok(())
+}
+
+#[madsim::test]
+async fn test_reset_datagen_source_to_earliest() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run(CREATE_SOURCE).await?;
+    cluster
+        .run("create materialized view mv as select count(*) as c, count(distinct v1) as d from s;")
+        .await?;
+    wait_until_count(&mut cluster, ROW_COUNT, ROW_COUNT).await?;
+
+    // The reset is rejected without `FORCE`.
+    assert!(cluster
+        .run("alter source s reset offset to earliest;")
+        .await
+        .is_err());
+
+    // All splits are read again from the beginning.
+    cluster
+        .run("alter source s reset offset to earliest force;")
+        .await?;
+    wait_until_count(&mut cluster, ROW_COUNT * 2, ROW_COUNT).await?;
+
+    // The reset is done once the statement returns.
+    let progress = cluster
+        .run("select phase from rw_catalog.rw_backfill_progress;")
+        .await?;
+    assert!(!progress.contains("RESETTING_OFFSET"), "{progress}");
+
+    Ok(())
+}
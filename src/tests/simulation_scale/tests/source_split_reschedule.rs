@@ -0,0 +1,85 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::ctl_ext::predicate::identity_contains;
+
+const SPLIT_COUNT: usize = 3;
+
+const CREATE_SOURCE: &str = "create source s (v1 int) with (
+    connector = 'datagen',
+    fields.v1.kind = 'sequence',
+    fields.v1.start = '1',
+    fields.v1.end = '30',
+    datagen.rows.per.second = '10',
+    datagen.split.num = '3'
+) row format json;";
+const CREATE_MV: &str = "create materialized view mv as select count(distinct v1) as d from s;";
+const RESET_OFFSET: &str = "alter source s reset offset to earliest force;";
+
+async fn wait_until_all_read(cluster: &mut Cluster) -> Result<()> {
+    cluster
+        .wait_until(
+            "select d from mv;",
+            |output| output.trim() == "30",
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Resets the offsets of the source, which assigns the splits to its actors, while the source
+/// fragment is being rescheduled with a plan made before the reset. Either command may be
+/// scheduled first, but neither fails, and the splits are only assigned to the actors after the
+/// reschedule.
+#[madsim::test]
+async fn test_reset_source_offset_during_reschedule() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run(CREATE_SOURCE).await?;
+    cluster.run(CREATE_MV).await?;
+    wait_until_all_read(&mut cluster).await?;
+
+    for parallelism in [2, 6, 3] {
+        let fragment = cluster
+            .locate_one_fragment(vec![identity_contains("StreamSource")])
+            .await?;
+        let plan = fragment
+            .reschedule_to_parallelism(parallelism)?
+            .ok_or_else(|| anyhow!("the source fragment is already at {parallelism}"))?;
+
+        let reset = cluster.spawn_run_in_session(vec![RESET_OFFSET.to_owned()]);
+        cluster.reschedule(plan).await?;
+        reset
+            .await?
+            .map_err(|e| anyhow!("failed to reset the offsets during rescheduling: {e}"))?;
+
+        let fixes = cluster.list_fragment_fixes().await?;
+        assert!(fixes.is_empty(), "{fixes:?}");
+        let fragment = cluster
+            .locate_one_fragment(vec![identity_contains("StreamSource")])
+            .await?;
+        assert_eq!(fragment.inner.actors.len(), parallelism);
+        assert_eq!(fragment.split_count(), SPLIT_COUNT);
+    }
+
+    wait_until_all_read(&mut cluster).await?;
+
+    Ok(())
+}
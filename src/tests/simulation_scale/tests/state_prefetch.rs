@@ -0,0 +1,118 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use madsim::time::Instant;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+use risingwave_simulation_scale::ctl_ext::predicate::identity_contains;
+use risingwave_simulation_scale::utils::AssertResult;
+
+/// The number of groups of the agg, within the agg cache size.
+const GROUP_COUNT: usize = 50000;
+
+/// The latency of each read of the object store. The in-memory object store is read without
+/// delay otherwise, so a cold cache would not slow down the barriers in the simulation.
+const READ_LATENCY_MS: &str = "1000";
+
+/// The compute node config enabling the state prefetch, with a deadline long enough for the
+/// prefetch to read all the groups.
+const PREFETCH_CONFIG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/state_prefetch.toml");
+
+/// The time to wait after the migration before updating the groups, which is long enough for the
+/// prefetch to finish.
+const PREFETCH_WAIT: Duration = Duration::from_secs(60);
+
+/// Migrates the hash agg of `GROUP_COUNT` groups to another compute node, and returns the time to
+/// update every group and flush afterwards.
+async fn update_latency_after_migration(prefetch: bool) -> Result<Duration> {
+    let conf = if prefetch {
+        Configuration::parse_from(["", "--compute-node-config-path", PREFETCH_CONFIG_PATH])
+    } else {
+        Configuration::default()
+    };
+    let mut cluster = Cluster::start(conf).await?;
+    cluster.run("create table t (k int, v int);").await?;
+    cluster
+        .run(
+            "create materialized view m as select k, count(*) as c, sum(v) as s from t group by k;",
+        )
+        .await?;
+    cluster
+        .run(&format!(
+            "insert into t select i, 1 from generate_series(1, {GROUP_COUNT}, 1) as i;"
+        ))
+        .await?;
+    cluster.run("flush;").await?;
+
+    // Put all the groups on one actor, and move it to another compute node.
+    let id = cluster
+        .locate_one_fragment(vec![
+            identity_contains("materialize"),
+            identity_contains("hashagg"),
+        ])
+        .await?
+        .id();
+    let fragment = cluster.locate_fragment_by_id(id).await?;
+    if let Some(plan) = fragment.reschedule_to_parallelism(1)? {
+        cluster.reschedule(plan).await?;
+    }
+    let plan = cluster
+        .locate_fragment_by_id(id)
+        .await?
+        .migrate_to_other_workers()?;
+    cluster.reschedule(plan).await?;
+    madsim::time::sleep(PREFETCH_WAIT).await;
+
+    let start = Instant::now();
+    cluster
+        .run(&format!(
+            "insert into t select i, 1 from generate_series(1, {GROUP_COUNT}, 1) as i;"
+        ))
+        .await?;
+    cluster.run("flush;").await?;
+    let latency = start.elapsed();
+
+    cluster
+        .run("select count(*), sum(c), sum(s) from m;")
+        .await?
+        .assert_result_eq(&format!(
+            "{GROUP_COUNT} {} {}",
+            2 * GROUP_COUNT,
+            2 * GROUP_COUNT
+        ));
+    Ok(latency)
+}
+
+/// After an actor with a large agg state is migrated, the first barrier updating its groups
+/// should be faster with the state prefetched than with a cold cache.
+#[test]
+fn test_state_prefetch_after_migration() {
+    std::env::set_var("RW_SIM_OBJECT_STORE_READ_LATENCY_MS", READ_LATENCY_MS);
+    let run = |prefetch| {
+        madsim::runtime::Runtime::new()
+            .block_on(update_latency_after_migration(prefetch))
+            .unwrap()
+    };
+    let cold = run(false);
+    let prefetched = run(true);
+    assert!(
+        prefetched < cold,
+        "the prefetch should speed up the first barrier after migration: cold {cold:?}, prefetched {prefetched:?}"
+    );
+}
@@ -0,0 +1,63 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(madsim)]
+
+use anyhow::Result;
+use risingwave_simulation_scale::cluster::{Cluster, Configuration};
+
+#[madsim::test]
+async fn test_streaming_graph_notice() -> Result<()> {
+    let mut cluster = Cluster::start(Configuration::default()).await?;
+    cluster.run("create table t1 (v1 int, v2 int);").await?;
+    cluster.run("create table t2 (v1 int, v2 int);").await?;
+
+    // No notice by default.
+    let notices = cluster
+        .run_with_notices(&["create materialized view mv0 as select v1 from t1;"])
+        .await?;
+    assert!(notices.is_empty());
+
+    let notices = cluster
+        .run_with_notices(&[
+            "set rw_streaming_graph_notice = true;",
+            "create materialized view mv1 as select t1.v2 as a, t2.v2 as b from t1 join t2 on t1.v1 = t2.v1;",
+        ])
+        .await?;
+    assert_eq!(notices.len(), 1);
+    let notice = &notices[0];
+
+    // The join fragment, the two chain fragments on the upstream tables, and the materialize
+    // fragment.
+    let fragment_headers = notice
+        .lines()
+        .filter(|line| line.starts_with("Fragment "))
+        .collect::<Vec<_>>();
+    assert!(fragment_headers.len() >= 3, "{}", notice);
+    for header in fragment_headers {
+        assert!(header.contains("parallelism: "), "{}", header);
+        assert!(header.contains("state tables: ["), "{}", header);
+    }
+    assert!(notice.contains("StreamHashJoin"), "{}", notice);
+    assert!(notice.contains("left table: "), "{}", notice);
+    // Both sides of the join are shuffled by the join key.
+    assert!(
+        notice.matches("StreamMerge Hash from ").count() >= 2,
+        "{}",
+        notice
+    );
+    assert!(notice.contains("materialized table: "), "{}", notice);
+
+    Ok(())
+}
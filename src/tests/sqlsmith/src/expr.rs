@@ -280,7 +280,7 @@ impl<'a, R: Rng> SqlGenerator<'a, R> {
             A::Avg => Some(Expr::Function(make_agg_func("avg", exprs, distinct))),
             A::StringAgg => Some(Expr::Function(make_agg_func("string_agg", exprs, distinct))),
             A::FirstValue => None,
-            A::ApproxCountDistinct => {
+            A::ApproxCountDistinct(_) => {
                 if distinct {
                     None
                 } else {
@@ -291,6 +291,8 @@ impl<'a, R: Rng> SqlGenerator<'a, R> {
                     )))
                 }
             }
+            // The fraction of `approx_percentile` must be a constant.
+            A::ApproxPercentile(..) => None,
             // TODO(yuchao): `array_agg` support is still WIP, see #4657.
             A::ArrayAgg => None,
         }
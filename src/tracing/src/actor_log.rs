@@ -0,0 +1,315 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-actor log levels, to debug a single actor or fragment at runtime without raising the log
+//! level of the whole compute node.
+//!
+//! The events are attributed to the actors by the spans with an `actor_id` field they're in, e.g.
+//! the span each actor runs in. [`ActorLogLayer`] records the actor ids of such spans, and
+//! [`ActorLogFilter`] enables the events in them at the levels set in [`ACTOR_LOG_LEVELS`], in
+//! addition to the ones enabled by the filter it wraps.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, RwLock};
+use std::time::SystemTime;
+
+use tracing::field::{Field, Visit};
+use tracing::metadata::LevelFilter;
+use tracing::span::{Attributes, Id};
+use tracing::subscriber::Interest;
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The name of the span field that tags the events in the span with an actor.
+const ACTOR_ID_FIELD: &str = "actor_id";
+
+pub static ACTOR_LOG_LEVELS: LazyLock<ActorLogLevels> = LazyLock::new(ActorLogLevels::default);
+
+/// The log levels overridden for some actors, each until an expiry time.
+#[derive(Default)]
+pub struct ActorLogLevels {
+    levels: RwLock<HashMap<u32, (Level, SystemTime)>>,
+    /// Whether `levels` is non-empty, to skip the lookups for every event in the common case.
+    has_levels: AtomicBool,
+}
+
+impl ActorLogLevels {
+    /// Logs the events of the actor at `level` until `expire_at`.
+    pub fn set(&self, actor_id: u32, level: Level, expire_at: SystemTime) {
+        let mut levels = self.levels.write().unwrap();
+        levels.insert(actor_id, (level, expire_at));
+        self.update_has_levels(!levels.is_empty());
+    }
+
+    /// Reverts the log level of the actor, e.g. when it's dropped.
+    pub fn remove(&self, actor_id: u32) {
+        let mut levels = self.levels.write().unwrap();
+        levels.remove(&actor_id);
+        self.update_has_levels(!levels.is_empty());
+    }
+
+    /// Returns the log level of the actor if it's overridden and not expired. The expired ones are
+    /// removed lazily, all at once.
+    pub fn get(&self, actor_id: u32) -> Option<Level> {
+        if !self.has_levels.load(Ordering::Relaxed) {
+            return None;
+        }
+        let (level, expire_at) = *self.levels.read().unwrap().get(&actor_id)?;
+        let now = SystemTime::now();
+        if now < expire_at {
+            Some(level)
+        } else {
+            let mut levels = self.levels.write().unwrap();
+            levels.retain(|_, (_, expire_at)| now < *expire_at);
+            self.update_has_levels(!levels.is_empty());
+            None
+        }
+    }
+
+    /// Rebuilds the interests of the callsites when the levels become empty or non-empty, since
+    /// [`ActorLogFilter`] only has to check the events disabled by its inner filter while there're
+    /// any levels.
+    fn update_has_levels(&self, has_levels: bool) {
+        if self.has_levels.swap(has_levels, Ordering::Relaxed) != has_levels {
+            tracing::callsite::rebuild_interest_cache();
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.has_levels.load(Ordering::Relaxed)
+    }
+}
+
+/// The actor id recorded in the extensions of a span with an `actor_id` field.
+struct ActorTag(u32);
+
+struct ActorIdVisitor(Option<u32>);
+
+impl Visit for ActorIdVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == ACTOR_ID_FIELD {
+            self.0 = Some(value as u32);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if field.name() == ACTOR_ID_FIELD {
+            self.0 = Some(value as u32);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == ACTOR_ID_FIELD {
+            self.0 = format!("{:?}", value).parse().ok();
+        }
+    }
+}
+
+fn is_actor_span(metadata: &Metadata<'_>) -> bool {
+    metadata.is_span() && metadata.fields().field(ACTOR_ID_FIELD).is_some()
+}
+
+/// A layer that tags the spans with an `actor_id` field, for [`ActorLogFilter`] to find the actor
+/// of an event. See [`actor_log_layer`].
+pub struct ActorLogLayer;
+
+impl<S> Layer<S> for ActorLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = ActorIdVisitor(None);
+        attrs.record(&mut visitor);
+        if let Some(actor_id) = visitor.0 && let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(ActorTag(actor_id));
+        }
+    }
+}
+
+/// Creates an [`ActorLogLayer`] that only sees the spans with an `actor_id` field, so that it
+/// doesn't enable any other spans or events by itself.
+pub fn actor_log_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    ActorLogLayer.with_filter(filter_fn(is_actor_span))
+}
+
+/// A filter that enables the events enabled by `inner`, and the ones of the actors in
+/// [`ACTOR_LOG_LEVELS`] at their levels.
+pub struct ActorLogFilter<F> {
+    inner: F,
+}
+
+impl<F> ActorLogFilter<F> {
+    pub fn new(inner: F) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, F> Filter<S> for ActorLogFilter<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    F: Filter<S>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        // The actor spans are always enabled for the events in them to find the actor.
+        if self.inner.enabled(metadata, cx) || is_actor_span(metadata) {
+            return true;
+        }
+        if !metadata.is_event() || ACTOR_LOG_LEVELS.is_empty() {
+            return false;
+        }
+        let Some(current) = cx.lookup_current() else {
+            return false;
+        };
+        for span in current.scope() {
+            if let Some(ActorTag(actor_id)) = span.extensions().get::<ActorTag>() {
+                return ACTOR_LOG_LEVELS
+                    .get(*actor_id)
+                    .map_or(false, |level| metadata.level() <= &level);
+            }
+        }
+        false
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let interest = self.inner.callsite_enabled(metadata);
+        if interest.is_always() || is_actor_span(metadata) {
+            Interest::always()
+        } else if ACTOR_LOG_LEVELS.is_empty() {
+            // The interests are rebuilt once any levels are set.
+            interest
+        } else {
+            // The events disabled by `inner` may be enabled for some actors.
+            Interest::sometimes()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        if ACTOR_LOG_LEVELS.is_empty() {
+            self.inner.max_level_hint()
+        } else {
+            Some(LevelFilter::TRACE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tracing::Event;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct CollectLayer(Arc<Mutex<Vec<String>>>);
+
+    impl<S: Subscriber> Layer<S> for CollectLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            struct MessageVisitor(String);
+            impl Visit for MessageVisitor {
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    if field.name() == "message" {
+                        self.0 = format!("{:?}", value);
+                    }
+                }
+            }
+            let mut visitor = MessageVisitor(String::new());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+    }
+
+    impl CollectLayer {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    fn log_in_actors(actor_ids: &[u32]) {
+        for &actor_id in actor_ids {
+            tracing::info_span!("actor", actor_id, fragment_id = 1).in_scope(|| {
+                tracing::info!("info {}", actor_id);
+                tracing::debug!("debug {}", actor_id);
+                // The nested spans of the actor are also tagged.
+                tracing::info_span!("executor").in_scope(|| {
+                    tracing::trace!("trace {}", actor_id);
+                });
+            });
+        }
+        tracing::debug!("debug outside");
+    }
+
+    #[test]
+    fn test_actor_log_filter() {
+        let collect = CollectLayer::default();
+        let subscriber = tracing_subscriber::registry().with(actor_log_layer()).with(
+            collect.clone().with_filter(ActorLogFilter::new(
+                Targets::new().with_default(Level::INFO),
+            )),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            log_in_actors(&[1001, 1002]);
+            assert_eq!(collect.take(), vec!["info 1001", "info 1002"]);
+
+            ACTOR_LOG_LEVELS.set(
+                1001,
+                Level::TRACE,
+                SystemTime::now() + Duration::from_secs(3600),
+            );
+            ACTOR_LOG_LEVELS.set(
+                1002,
+                Level::DEBUG,
+                SystemTime::now() + Duration::from_millis(200),
+            );
+            log_in_actors(&[1001, 1002, 1003]);
+            assert_eq!(
+                collect.take(),
+                vec![
+                    "info 1001",
+                    "debug 1001",
+                    "trace 1001",
+                    "info 1002",
+                    "debug 1002",
+                    "info 1003"
+                ]
+            );
+
+            // The level of 1002 reverts after the expiry.
+            std::thread::sleep(Duration::from_millis(300));
+            log_in_actors(&[1001, 1002]);
+            assert_eq!(
+                collect.take(),
+                vec!["info 1001", "debug 1001", "trace 1001", "info 1002"]
+            );
+            assert_eq!(ACTOR_LOG_LEVELS.get(1002), None);
+
+            // The disabled events are filtered by the callsite interests again without any levels.
+            ACTOR_LOG_LEVELS.remove(1001);
+            assert!(ACTOR_LOG_LEVELS.is_empty());
+            log_in_actors(&[1001, 1002]);
+            assert_eq!(collect.take(), vec!["info 1001", "info 1002"]);
+        });
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::Context;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The context of a distributed trace, which is propagated across the nodes in the protobuf
+/// messages as the [W3C trace context](https://www.w3.org/TR/trace-context/) headers.
+///
+/// A context is only valid if the span it's created from is exported by OpenTelemetry, that is,
+/// the span is sampled and the node has an OpenTelemetry layer installed.
+#[derive(Debug, Clone, Default)]
+pub struct TracingContext(Context);
+
+impl TracingContext {
+    /// Create a tracing context that does not belong to any trace.
+    pub fn none() -> Self {
+        Self(Context::new())
+    }
+
+    /// Create a tracing context from the given span.
+    pub fn from_span(span: &tracing::Span) -> Self {
+        Self(span.context())
+    }
+
+    /// Whether this context belongs to a trace.
+    pub fn is_valid(&self) -> bool {
+        self.0.span().span_context().is_valid()
+    }
+
+    /// Create a span with `f` and attach it to this context as the child. Returns a disabled span
+    /// if this context does not belong to any trace, so that untraced requests bring no overhead.
+    pub fn child_span(&self, f: impl FnOnce() -> tracing::Span) -> tracing::Span {
+        if !self.is_valid() {
+            return tracing::Span::none();
+        }
+        let span = f();
+        span.set_parent(self.0.clone());
+        span
+    }
+
+    pub fn to_protobuf(&self) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+        if self.is_valid() {
+            TraceContextPropagator::new().inject_context(&self.0, &mut fields);
+        }
+        fields
+    }
+
+    pub fn from_protobuf(fields: &HashMap<String, String>) -> Self {
+        if fields.is_empty() {
+            return Self::none();
+        }
+        Self(TraceContextPropagator::new().extract(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use opentelemetry::sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+    use opentelemetry::sdk::trace::TracerProvider;
+    use opentelemetry::trace::TracerProvider as _;
+    use parking_lot::Mutex;
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    /// Captures the exported spans in memory.
+    #[derive(Debug, Clone, Default)]
+    struct CapturingExporter(Arc<Mutex<Vec<SpanData>>>);
+
+    #[async_trait::async_trait]
+    impl SpanExporter for CapturingExporter {
+        async fn export(&mut self, batch: Vec<SpanData>) -> ExportResult {
+            self.0.lock().extend(batch);
+            Ok(())
+        }
+    }
+
+    impl CapturingExporter {
+        fn span(&self, name: &str) -> SpanData {
+            self.0
+                .lock()
+                .iter()
+                .find(|span| span.name == name)
+                .unwrap_or_else(|| panic!("span {} not exported", name))
+                .clone()
+        }
+    }
+
+    #[test]
+    fn test_span_parentage() {
+        let exporter = CapturingExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!TracingContext::from_span(&tracing::Span::none()).is_valid());
+            assert!(TracingContext::none().to_protobuf().is_empty());
+            assert!(TracingContext::none()
+                .child_span(|| tracing::info_span!("untraced"))
+                .is_disabled());
+
+            // The meta node injects a barrier.
+            let barrier_span = tracing::info_span!("barrier", epoch = 233);
+            let fields = TracingContext::from_span(&barrier_span).to_protobuf();
+            assert!(fields.contains_key("traceparent"));
+
+            // The compute node handles the barrier in an actor.
+            let actor_span = TracingContext::from_protobuf(&fields)
+                .child_span(|| tracing::info_span!("dispatch_barrier", actor_id = 1));
+            actor_span.in_scope(|| {
+                let _flush = tracing::info_span!("sync_epoch").entered();
+            });
+
+            drop(actor_span);
+            drop(barrier_span);
+        });
+        provider.force_flush();
+
+        let barrier = exporter.span("barrier");
+        let actor = exporter.span("dispatch_barrier");
+        let flush = exporter.span("sync_epoch");
+        let trace_id = barrier.span_context.trace_id();
+        assert_eq!(actor.span_context.trace_id(), trace_id);
+        assert_eq!(flush.span_context.trace_id(), trace_id);
+        assert_eq!(actor.parent_span_id, barrier.span_context.span_id());
+        assert_eq!(flush.parent_span_id, actor.span_context.span_id());
+        assert!(exporter.0.lock().iter().all(|span| span.name != "untraced"));
+    }
+}
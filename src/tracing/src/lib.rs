@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#![feature(let_chains)]
+#![feature(once_cell)]
+
 use std::env;
 use std::thread::JoinHandle;
 
@@ -20,6 +23,11 @@ use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
 use futures::StreamExt;
 use minitrace::prelude::*;
 
+mod actor_log;
+mod context;
+pub use actor_log::*;
+pub use context::TracingContext;
+
 pub struct RwTracingService {
     tx: UnboundedSender<Collector>,
     _join_handle: JoinHandle<()>,
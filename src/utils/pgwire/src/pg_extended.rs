@@ -211,7 +211,17 @@ where
 
         // If the result is consumed completely or is not a query result, clear the cache.
         if query_end || !self.result.as_ref().unwrap().is_query() {
-            self.result.take();
+            let mut result = self.result.take().unwrap();
+            if let Some(callback) = result.take_callback() {
+                msg_stream.flush().await?;
+                if let Err(e) = callback.await {
+                    tracing::warn!(
+                        "failed to run the callback of \"{}\": {}",
+                        self.query_string,
+                        e
+                    );
+                }
+            }
         }
 
         Ok(())
@@ -352,6 +352,13 @@ where
                 }))?;
         }
 
+        if let Some(callback) = res.take_callback() {
+            self.stream.flush().await?;
+            if let Err(e) = callback.await {
+                tracing::warn!("failed to run the callback of \"{}\": {}", sql, e);
+            }
+        }
+
         self.stream.write_no_flush(&BeMessage::ReadyForQuery)?;
         Ok(())
     }
@@ -681,7 +688,7 @@ where
         }
     }
 
-    async fn flush(&mut self) -> io::Result<()> {
+    pub(crate) async fn flush(&mut self) -> io::Result<()> {
         match self {
             Conn::Unencrypted(s) => s.flush().await,
             Conn::Ssl(s) => s.flush().await,
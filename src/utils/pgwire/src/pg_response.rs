@@ -15,6 +15,7 @@
 use std::fmt::Formatter;
 use std::pin::Pin;
 
+use futures::future::BoxFuture;
 use futures::Stream;
 
 use crate::pg_field_descriptor::PgFieldDescriptor;
@@ -23,6 +24,8 @@ use crate::types::Row;
 
 pub type RowSet = Vec<Row>;
 pub type RowSetResult = Result<RowSet, BoxedError>;
+/// Runs after all the rows of a response are flushed to the client.
+pub type Callback = BoxFuture<'static, Result<(), BoxedError>>;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[expect(non_camel_case_types, clippy::upper_case_acronyms)]
@@ -39,10 +42,13 @@ pub enum StatementType {
     CREATE_MATERIALIZED_VIEW,
     CREATE_SOURCE,
     CREATE_SINK,
+    CREATE_SUBSCRIPTION,
     CREATE_DATABASE,
     CREATE_SCHEMA,
     CREATE_USER,
     CREATE_INDEX,
+    ALTER_SOURCE,
+    ANALYZE,
     DESCRIBE_TABLE,
     GRANT_PRIVILEGE,
     DROP_TABLE,
@@ -50,6 +56,7 @@ pub enum StatementType {
     DROP_INDEX,
     DROP_SOURCE,
     DROP_SINK,
+    DROP_SUBSCRIPTION,
     DROP_SCHEMA,
     DROP_DATABASE,
     DROP_USER,
@@ -89,6 +96,7 @@ where
     notice: Option<String>,
     values_stream: Option<VS>,
     row_desc: Vec<PgFieldDescriptor>,
+    callback: Option<Callback>,
 }
 
 impl<VS> std::fmt::Debug for PgResponse<VS>
@@ -101,6 +109,7 @@ where
             .field("row_cnt", &self.row_cnt)
             .field("notice", &self.notice)
             .field("row_desc", &self.row_desc)
+            .field("has_callback", &self.callback.is_some())
             .finish()
     }
 }
@@ -130,6 +139,7 @@ impl StatementType {
         matches!(
             self,
             StatementType::SELECT
+                | StatementType::FETCH
                 | StatementType::EXPLAIN
                 | StatementType::SHOW_COMMAND
                 | StatementType::DESCRIBE_TABLE
@@ -149,6 +159,7 @@ where
             values_stream: None,
             row_desc: vec![],
             notice: None,
+            callback: None,
         }
     }
 
@@ -160,6 +171,7 @@ where
             values_stream: None,
             row_desc: vec![],
             notice: Some(notice),
+            callback: None,
         }
     }
 
@@ -175,9 +187,22 @@ where
             values_stream: Some(values_stream),
             row_desc,
             notice: None,
+            callback: None,
         }
     }
 
+    /// Sets the callback to run once the response is completely sent, e.g. to acknowledge the
+    /// rows only after the client has received them.
+    pub fn with_callback(mut self, callback: Callback) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    /// Takes the callback to run after the response is sent. Returns `None` if it has been taken.
+    pub fn take_callback(&mut self) -> Option<Callback> {
+        self.callback.take()
+    }
+
     pub fn get_stmt_type(&self) -> StatementType {
         self.stmt_type
     }
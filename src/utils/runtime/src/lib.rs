@@ -20,11 +20,12 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use futures::Future;
+use risingwave_tracing::{actor_log_layer, ActorLogFilter};
 use tracing::Level;
-use tracing_subscriber::filter;
 use tracing_subscriber::fmt::time;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter, Layer};
 
 /// Configure log targets for all `RisingWave` crates. When new crates are added and TRACE level
 /// logs are needed, add them here.
@@ -60,6 +61,9 @@ pub struct LoggerSettings {
     enable_tokio_console: bool,
     /// Enable colorful output in console.
     colorful: bool,
+    /// Export traces to this OpenTelemetry collector endpoint with OTLP, along with the service
+    /// name to report.
+    otlp: Option<(&'static str, String)>,
 }
 
 impl LoggerSettings {
@@ -72,8 +76,68 @@ impl LoggerSettings {
             enable_jaeger_tracing,
             enable_tokio_console,
             colorful: console::colors_enabled_stderr(),
+            otlp: None,
         }
     }
+
+    /// Export traces to the OpenTelemetry collector at `endpoint` with OTLP, if specified.
+    #[must_use]
+    pub fn with_otlp_endpoint(
+        mut self,
+        service_name: &'static str,
+        endpoint: Option<String>,
+    ) -> Self {
+        self.otlp = endpoint.map(|endpoint| (service_name, endpoint));
+        self
+    }
+}
+
+/// Create a layer to export the spans of `RisingWave` crates to the OpenTelemetry collector at
+/// `endpoint` with OTLP.
+fn otlp_layer<S>(service_name: &'static str, endpoint: String) -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    use opentelemetry::sdk::{trace, Resource};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+
+    // The exporter runs in a dedicated runtime, since the logger is initialized before the main
+    // runtime is built.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let tracer =
+        {
+            let _guard = runtime.enter();
+            opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", service_name),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)
+                .expect("failed to install OTLP trace pipeline")
+        };
+    std::thread::Builder::new()
+        .name("risingwave-otlp".to_string())
+        .spawn(move || runtime.block_on(futures::future::pending::<()>()))
+        .unwrap();
+
+    let filter = filter::Targets::new()
+        .with_target("risingwave_stream", Level::INFO)
+        .with_target("risingwave_storage", Level::INFO)
+        .with_target("risingwave_compute", Level::INFO)
+        .with_target("risingwave_meta", Level::INFO);
+
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(filter)
 }
 
 /// Set panic hook to abort the process (without losing debug info and stack trace).
@@ -120,7 +184,8 @@ pub fn init_risingwave_logger(settings: LoggerSettings) {
         // TODO: remove this in release mode
         let filter = filter.with_default(Level::DEBUG);
 
-        fmt_layer.with_filter(filter)
+        // The events of the actors may be enabled at a more verbose level at runtime.
+        fmt_layer.with_filter(ActorLogFilter::new(filter))
     };
 
     if settings.enable_jaeger_tracing {
@@ -141,10 +206,16 @@ pub fn init_risingwave_logger(settings: LoggerSettings) {
         None
     };
 
+    let otlp_layer = settings
+        .otlp
+        .map(|(service_name, endpoint)| otlp_layer(service_name, endpoint));
+
     match tokio_console_layer {
         Some((tokio_console_layer, server)) => {
             tracing_subscriber::registry()
+                .with(actor_log_layer())
                 .with(fmt_layer)
+                .with(otlp_layer)
                 .with(tokio_console_layer)
                 .init();
             std::thread::spawn(|| {
@@ -159,7 +230,11 @@ pub fn init_risingwave_logger(settings: LoggerSettings) {
             });
         }
         None => {
-            tracing_subscriber::registry().with(fmt_layer).init();
+            tracing_subscriber::registry()
+                .with(actor_log_layer())
+                .with(fmt_layer)
+                .with(otlp_layer)
+                .init();
         }
     }
 